@@ -4,7 +4,13 @@ use tauri::{Builder, Runtime};
 pub fn build_app<R: Runtime>() -> Builder<R> {
   let mut builder = Builder::<R>::new()
     // Register plugins
-    .plugin(tauri_plugin_log::Builder::new().build())
+    .plugin(
+      tauri_plugin_log::Builder::new()
+        .level(log::LevelFilter::Trace)
+        .filter(crate::core::logging::is_enabled)
+        .target(crate::core::logging::ring_buffer_target())
+        .build(),
+    )
     .plugin(tauri_plugin_notification::init())
     .plugin(tauri_plugin_fs::init())
     .plugin(tauri_plugin_dialog::init())
@@ -23,6 +29,7 @@ pub fn build_app<R: Runtime>() -> Builder<R> {
     // Language commands
     crate::language_tauri::get_app_language_tauri,
     crate::language_tauri::set_app_language_tauri,
+    crate::language_tauri::i18n::render_localized_message,
     // Filesystem commands
     crate::filesystem::file_exists,
     crate::filesystem::get_file_stats,
@@ -34,16 +41,38 @@ pub fn build_app<R: Runtime>() -> Builder<R> {
     crate::app_dirs::get_app_directories,
     crate::app_dirs::get_directory_sizes,
     crate::app_dirs::clear_app_cache,
+    // Storage report commands
+    crate::storage_report::get_storage_report,
+    crate::storage_report::clear_storage_category,
     // Media commands
     crate::media::commands::get_media_files,
     crate::media::commands::get_media_metadata,
     crate::media::commands::clear_media_preview_data,
     crate::media::commands::extract_recognition_frames,
     crate::media::commands::generate_media_thumbnail,
+    crate::media::commands::convert_image_for_timeline,
     crate::media::commands::generate_timeline_previews,
+    crate::media::commands::generate_thumbnail_sprite,
+    crate::media::commands::generate_hover_preview,
+    crate::media::commands::generate_folder_hover_previews,
     crate::media::commands::get_files_with_previews,
+    crate::media::commands::find_duplicate_media,
+    crate::media::commands::relink_media,
+    crate::media::commands::set_media_fingerprint_mode,
+    crate::media::commands::derive_media_tags,
+    crate::media::commands::search_media_by_tags,
+    crate::media::commands::get_media_tag_summary,
+    crate::media::commands::get_media_tag_thresholds,
+    crate::media::commands::set_media_tag_thresholds,
+    crate::media::commands::cancel_media_scan,
+    crate::media::commands::get_media_scan_status,
+    crate::media::commands::check_media_conformance,
+    crate::media::commands::apply_media_autofix,
     crate::media::commands::get_media_preview_data,
     crate::media::commands::get_timeline_frames,
+    crate::media::commands::get_timeline_frames_in_range,
+    crate::media::commands::invalidate_timeline_frames,
+    crate::media::commands::read_preview_image,
     crate::media::commands::load_preview_data,
     crate::media::commands::process_media_file_simple,
     crate::media::commands::process_media_files,
@@ -51,16 +80,26 @@ pub fn build_app<R: Runtime>() -> Builder<R> {
     crate::media::commands::save_preview_data,
     crate::media::commands::save_timeline_frames,
     // Recognition commands
+    crate::recognition::commands::build_recognition_timeline,
     crate::recognition::commands::clear_recognition_results,
+    crate::recognition::commands::compare_recognition_results,
+    crate::recognition::commands::compare_recognition_with_model,
+    crate::recognition::commands::export_recognition_overlay,
     crate::recognition::commands::export_recognition_results,
     crate::recognition::commands::get_preview_data_with_recognition,
+    crate::recognition::commands::get_recognition_config,
     crate::recognition::commands::get_recognition_results,
+    crate::recognition::commands::get_recognition_runtime_stats,
     crate::recognition::commands::get_yolo_class_names,
     crate::recognition::commands::load_yolo_model,
     crate::recognition::commands::process_video_batch,
+    crate::recognition::commands::process_video_batch_detailed,
     crate::recognition::commands::process_video_recognition,
     crate::recognition::commands::process_yolo_batch,
+    crate::recognition::commands::set_recognition_config,
+    crate::recognition::commands::set_recognition_inference_limit,
     crate::recognition::commands::set_yolo_target_classes,
+    crate::recognition::commands::unload_recognition_models,
     // New YOLO processor commands
     crate::recognition::commands::yolo_commands::create_yolo_processor,
     crate::recognition::commands::yolo_commands::process_image_with_yolo,
@@ -90,6 +129,8 @@ pub fn build_app<R: Runtime>() -> Builder<R> {
     crate::security::list_api_keys,
     crate::security::delete_api_key,
     crate::security::validate_api_key,
+    crate::security::validate_api_key_live,
+    crate::security::get_api_key_status,
     crate::security::generate_oauth_url,
     crate::security::exchange_oauth_code,
     crate::security::refresh_oauth_token,
@@ -103,12 +144,17 @@ pub fn build_app<R: Runtime>() -> Builder<R> {
     crate::subtitles::validate_subtitle_format,
     crate::subtitles::convert_subtitle_format,
     crate::subtitles::get_subtitle_info,
+    crate::subtitles::import_subtitle_file,
+    crate::subtitles::export_subtitles,
+    crate::subtitles::refine_subtitle_timing,
     // Security advanced commands from additional_commands module
     crate::security::additional_commands::create_secure_storage,
     crate::security::additional_commands::create_secure_storage_new,
     crate::security::additional_commands::get_or_create_encryption_key_command,
     crate::security::additional_commands::check_storage_security,
     crate::security::additional_commands::get_secure_storage_info,
+    crate::security::privacy_mode::get_privacy_mode_status,
+    crate::security::privacy_mode::set_privacy_mode,
     // Video compiler commands - using the already exported commands from the module
     crate::video_compiler::commands::auto_select_gpu,
     crate::video_compiler::commands::benchmark_gpu,
@@ -118,6 +164,7 @@ pub fn build_app<R: Runtime>() -> Builder<R> {
     crate::video_compiler::commands::get_gpu_capabilities_full,
     crate::video_compiler::commands::cache_media_metadata,
     crate::video_compiler::commands::clean_old_cache,
+    crate::video_compiler::commands::clean_stale_artifacts,
     crate::video_compiler::commands::cleanup_cache,
     crate::video_compiler::commands::clear_all_cache,
     crate::video_compiler::commands::clear_cache,
@@ -131,6 +178,11 @@ pub fn build_app<R: Runtime>() -> Builder<R> {
     crate::video_compiler::commands::get_prerendered_segments,
     crate::video_compiler::commands::delete_prerendered_segment,
     crate::video_compiler::commands::optimize_prerender_cache,
+    crate::video_compiler::commands::set_playback_project,
+    crate::video_compiler::commands::update_playback_position,
+    crate::video_compiler::commands::get_playback_ready_ranges,
+    crate::video_compiler::commands::build_snap_index,
+    crate::video_compiler::commands::query_snap,
     crate::video_compiler::commands::clear_preview_cache,
     crate::video_compiler::commands::clear_preview_cache_for_file,
     crate::video_compiler::commands::clear_preview_generator_cache_for_file,
@@ -138,6 +190,9 @@ pub fn build_app<R: Runtime>() -> Builder<R> {
     crate::video_compiler::commands::clear_project_previews,
     crate::video_compiler::commands::clear_render_cache,
     crate::video_compiler::commands::get_cache_stats,
+    crate::video_compiler::commands::warm_project_cache,
+    crate::video_compiler::commands::record_recent_project,
+    crate::video_compiler::commands::get_recent_projects,
     crate::video_compiler::commands::compile_video,
     crate::video_compiler::commands::cancel_render,
     crate::video_compiler::commands::build_preview_command,
@@ -148,6 +203,51 @@ pub fn build_app<R: Runtime>() -> Builder<R> {
     crate::video_compiler::commands::analyze_project,
     crate::video_compiler::commands::backup_project,
     crate::video_compiler::commands::check_project_media_availability,
+    // Project storage commands
+    crate::video_compiler::commands::save_project_file,
+    crate::video_compiler::commands::load_project_file,
+    // Command preview commands
+    crate::video_compiler::commands::get_render_command_preview,
+    crate::video_compiler::commands::get_prerender_command_preview,
+    crate::video_compiler::commands::get_timeline_frame_command_preview,
+    // Project template commands
+    crate::video_compiler::commands::save_project_as_template,
+    crate::video_compiler::commands::list_project_templates,
+    crate::video_compiler::commands::create_project_from_template,
+    crate::video_compiler::commands::rename_project_template,
+    crate::video_compiler::commands::delete_project_template,
+    crate::video_compiler::commands::import_project_template,
+    crate::video_compiler::commands::save_effect_preset,
+    crate::video_compiler::commands::list_effect_presets,
+    crate::video_compiler::commands::rename_effect_preset,
+    crate::video_compiler::commands::delete_effect_preset,
+    crate::video_compiler::commands::apply_effect_preset,
+    crate::video_compiler::commands::export_effect_preset,
+    crate::video_compiler::commands::import_effect_preset,
+    // Media relink commands
+    crate::video_compiler::commands::find_missing_media,
+    crate::video_compiler::commands::suggest_relink_candidates,
+    crate::video_compiler::commands::apply_media_relink,
+    crate::video_compiler::commands::check_project_assets,
+    crate::video_compiler::commands::substitute_project_asset,
+    // Project package commands
+    crate::video_compiler::commands::package_project,
+    crate::video_compiler::commands::unpack_project,
+    crate::video_compiler::commands::cancel_project_package,
+    // Render spec commands
+    crate::video_compiler::commands::export_render_job,
+    crate::video_compiler::commands::verify_render_spec,
+    crate::video_compiler::commands::run_render_spec,
+    // Schema journal (undo/redo) commands
+    crate::video_compiler::commands::apply_export_preset,
+    crate::video_compiler::commands::undo_schema_change,
+    crate::video_compiler::commands::redo_schema_change,
+    crate::video_compiler::commands::get_schema_journal_state,
+    crate::video_compiler::commands::close_project_journal,
+    // Dev-only test media generation
+    crate::video_compiler::commands::generate_test_video,
+    crate::video_compiler::commands::generate_test_audio,
+    crate::video_compiler::commands::generate_test_image,
     crate::video_compiler::commands::batch_generate_previews_service,
     // Preview advanced commands
     crate::video_compiler::commands::create_preview_generator_with_ffmpeg,
@@ -160,10 +260,26 @@ pub fn build_app<R: Runtime>() -> Builder<R> {
     crate::video_compiler::commands::check_ffmpeg_available,
     crate::video_compiler::commands::check_ffmpeg_capabilities,
     crate::video_compiler::commands::check_ffmpeg_installation,
+    crate::video_compiler::commands::download_bundled_ffmpeg,
+    crate::video_compiler::commands::cancel_ffmpeg_download,
+    crate::video_compiler::commands::get_ffmpeg_status,
     crate::video_compiler::commands::get_system_info,
     crate::video_compiler::commands::concat_videos,
     crate::video_compiler::commands::configure_cache,
     crate::video_compiler::commands::add_clip_to_track,
+    crate::video_compiler::commands::update_clip,
+    crate::video_compiler::commands::move_clip,
+    crate::video_compiler::commands::create_sequence_from_clips,
+    crate::video_compiler::commands::flatten_sequence,
+    crate::video_compiler::commands::detect_timeline_gaps,
+    crate::video_compiler::commands::ripple_delete_gaps,
+    crate::video_compiler::commands::format_timeline_position,
+    crate::video_compiler::commands::set_clip_metadata,
+    crate::video_compiler::commands::find_clips,
+    crate::video_compiler::commands::update_clips,
+    crate::video_compiler::commands::apply_effect_to_clips,
+    crate::video_compiler::commands::copy_clip_attributes,
+    crate::video_compiler::commands::paste_clip_attributes,
     crate::video_compiler::commands::add_subtitles_to_project,
     crate::video_compiler::commands::create_clip,
     crate::video_compiler::commands::create_custom_alert,
@@ -224,6 +340,14 @@ pub fn build_app<R: Runtime>() -> Builder<R> {
     crate::video_compiler::commands::whisper_download_model,
     crate::video_compiler::commands::whisper_check_local_availability,
     crate::video_compiler::commands::extract_audio_for_whisper,
+    crate::video_compiler::commands::detect_audio_language,
+    crate::video_compiler::commands::whisper_transcribe_local_multilingual,
+    crate::video_compiler::commands::whisper_transcribe_openai_multilingual,
+    crate::video_compiler::commands::whisper_transcript_to_subtitles,
+    // Reframe commands
+    crate::video_compiler::commands::analyze_reframe,
+    crate::video_compiler::commands::apply_reframe,
+    crate::video_compiler::commands::reframe_clip_for_vertical,
     // Batch processing commands
     crate::video_compiler::commands::create_batch_job,
     crate::video_compiler::commands::get_batch_job_info,
@@ -233,6 +357,8 @@ pub fn build_app<R: Runtime>() -> Builder<R> {
     crate::video_compiler::commands::update_batch_clip_result,
     crate::video_compiler::commands::cleanup_batch_jobs,
     crate::video_compiler::commands::set_batch_job_status,
+    crate::video_compiler::commands::batch_export,
+    crate::video_compiler::commands::cancel_batch_export,
     // Multimodal analysis commands
     crate::video_compiler::commands::extract_frames_for_multimodal_analysis,
     crate::video_compiler::commands::convert_image_to_base64,
@@ -256,6 +382,8 @@ pub fn build_app<R: Runtime>() -> Builder<R> {
     crate::video_compiler::commands::analyze_workflow_video_quality,
     crate::video_compiler::commands::create_workflow_preview,
     crate::video_compiler::commands::cleanup_workflow_temp_files,
+    crate::video_compiler::commands::run_quick_edit_workflow,
+    crate::video_compiler::commands::cancel_quick_edit_workflow,
     // FFmpeg builder commands
     crate::video_compiler::commands::add_segment_inputs_to_builder,
     crate::video_compiler::commands::create_ffmpeg_with_prerender_settings,
@@ -284,6 +412,236 @@ pub fn build_app<R: Runtime>() -> Builder<R> {
     crate::video_compiler::commands::get_ffmpeg_available_formats,
     crate::video_compiler::commands::generate_subtitle_preview_advanced,
     crate::video_compiler::commands::get_ffmpeg_execution_information,
+    // Audio sync commands
+    crate::video_compiler::commands::probe_audio_offset,
+    // Video compiler timeline schema commands
+    crate::video_compiler::commands::create_new_subtitle,
+    crate::video_compiler::commands::validate_subtitle_schema,
+    crate::video_compiler::commands::get_subtitle_duration_schema,
+    crate::video_compiler::commands::create_new_track,
+    crate::video_compiler::commands::add_clip_to_track_schema,
+    crate::video_compiler::commands::remove_clip_from_track_schema,
+    crate::video_compiler::commands::get_track_info,
+    crate::video_compiler::commands::get_subtitle_statistics,
+    // Video compiler service container commands
+    crate::video_compiler::commands::get_project_service_info_command,
+    crate::video_compiler::commands::get_service_metrics_detailed,
+    crate::video_compiler::commands::get_all_metrics_summaries_command,
+    crate::video_compiler::commands::export_prometheus_detailed,
+    // Video compiler security advanced commands
+    crate::video_compiler::commands::init_secure_storage_advanced,
+    crate::video_compiler::commands::create_secure_storage_instance,
+    crate::video_compiler::commands::get_secure_storage_info_advanced,
+    crate::video_compiler::commands::verify_secure_storage_integrity,
+    crate::video_compiler::commands::export_secure_storage_config,
+    crate::video_compiler::commands::clear_secure_storage,
+    // Video compiler project
+    crate::video_compiler::commands::validate_project_schema,
+    crate::video_compiler::commands::optimize_project_schema,
+    crate::video_compiler::commands::get_project_media_files,
+    crate::video_compiler::commands::update_project_media_paths,
+    crate::video_compiler::commands::extract_project_subtitles,
+    crate::video_compiler::commands::merge_projects,
+    crate::video_compiler::commands::split_project,
+    crate::video_compiler::commands::track_operations,
+    crate::video_compiler::commands::get_clip_info,
+    crate::video_compiler::commands::validate_subtitle,
+    crate::video_compiler::commands::touch_project_schema,
+    crate::video_compiler::commands::migrate_project_schema,
+    // Video compiler info
+    crate::video_compiler::commands::get_ffmpeg_version,
+    crate::video_compiler::commands::get_supported_formats,
+    crate::video_compiler::commands::get_supported_video_codecs,
+    crate::video_compiler::commands::get_supported_audio_codecs,
+    crate::video_compiler::commands::get_disk_space,
+    crate::video_compiler::commands::get_compiler_config,
+    crate::video_compiler::commands::get_performance_stats,
+    crate::video_compiler::commands::get_available_filters,
+    crate::video_compiler::commands::get_media_file_info,
+    // Video compiler gpu
+    crate::video_compiler::commands::detect_gpus,
+    crate::video_compiler::commands::get_gpu_capabilities,
+    crate::video_compiler::commands::get_gpu_memory_info,
+    crate::video_compiler::commands::get_recommended_gpu,
+    crate::video_compiler::commands::set_preferred_gpu,
+    crate::video_compiler::commands::set_hardware_acceleration,
+    crate::video_compiler::commands::get_gpu_usage_status,
+    crate::video_compiler::commands::get_gpu_supported_codecs,
+    crate::video_compiler::commands::get_gpu_encoder_details,
+    // Video compiler cache
+    crate::video_compiler::commands::get_cache_size,
+    crate::video_compiler::commands::get_cache_stats_detailed,
+    crate::video_compiler::commands::get_cached_projects,
+    crate::video_compiler::commands::has_project_cache,
+    crate::video_compiler::commands::get_cached_media_metadata,
+    crate::video_compiler::commands::export_cache_stats,
+    crate::video_compiler::commands::set_cache_size_limit,
+    crate::video_compiler::commands::get_cache_size_limit,
+    crate::video_compiler::commands::preload_media_to_cache,
+    crate::video_compiler::commands::get_cache_path,
+    // Video compiler schema commands
+    crate::video_compiler::commands::create_subtitle,
+    crate::video_compiler::commands::create_subtitle_animation,
+    crate::video_compiler::commands::create_template,
+    crate::video_compiler::commands::create_track,
+    crate::video_compiler::commands::create_resolution,
+    crate::video_compiler::commands::get_hd_resolution,
+    crate::video_compiler::commands::get_uhd_4k_resolution,
+    crate::video_compiler::commands::get_preset_resolutions,
+    crate::video_compiler::commands::create_resolution_for_format,
+    // Video compiler preview
+    crate::video_compiler::commands::generate_frame_preview,
+    crate::video_compiler::commands::render_timeline_frame,
+    crate::video_compiler::commands::chroma_key_preview,
+    crate::video_compiler::commands::generate_video_thumbnails,
+    crate::video_compiler::commands::generate_project_preview,
+    crate::video_compiler::commands::generate_effect_preview,
+    crate::video_compiler::commands::generate_transition_preview,
+    crate::video_compiler::commands::generate_storyboard,
+    crate::video_compiler::commands::generate_animated_preview,
+    crate::video_compiler::commands::generate_waveform_preview,
+    crate::video_compiler::commands::get_cached_preview_info,
+    crate::video_compiler::commands::generate_custom_preview,
+    crate::video_compiler::commands::generate_preview_batch_with_settings,
+    crate::video_compiler::commands::set_preview_generator_ffmpeg_path,
+    crate::video_compiler::commands::generate_video_thumbnails_service,
+    crate::video_compiler::commands::generate_storyboard_service,
+    // Video compiler frame extraction commands
+    crate::video_compiler::commands::extract_timeline_frames,
+    crate::video_compiler::commands::extract_subtitle_frames,
+    crate::video_compiler::commands::generate_preview,
+    crate::video_compiler::commands::generate_preview_batch,
+    crate::video_compiler::commands::generate_preview_with_settings,
+    crate::video_compiler::commands::get_frame_extraction_cache_info,
+    crate::video_compiler::commands::extract_video_frame,
+    crate::video_compiler::commands::extract_video_frames_batch,
+    crate::video_compiler::commands::export_frame,
+    crate::video_compiler::commands::get_video_thumbnails,
+    crate::video_compiler::commands::attach_timeline_frames_for_file,
+    // Video compiler compiler settings commands
+    crate::video_compiler::commands::set_ffmpeg_path_advanced,
+    crate::video_compiler::commands::set_parallel_jobs_advanced,
+    crate::video_compiler::commands::set_memory_limit_advanced,
+    crate::video_compiler::commands::set_temp_directory_advanced,
+    crate::video_compiler::commands::set_temp_directory,
+    crate::video_compiler::commands::set_log_level_advanced,
+    crate::video_compiler::commands::reset_compiler_settings_advanced,
+    crate::video_compiler::commands::get_recommended_settings_advanced,
+    crate::video_compiler::commands::export_settings_advanced,
+    crate::video_compiler::commands::import_settings_advanced,
+    crate::video_compiler::commands::get_quality_presets_advanced,
+    // Video compiler rendering
+    crate::video_compiler::commands::get_active_render_jobs,
+    crate::video_compiler::commands::get_render_job,
+    crate::video_compiler::commands::list_scheduled_renders,
+    crate::video_compiler::commands::update_render_schedule,
+    crate::video_compiler::commands::get_render_report,
+    crate::video_compiler::commands::export_render_report_markdown,
+    crate::video_compiler::commands::pause_render,
+    crate::video_compiler::commands::resume_render,
+    crate::video_compiler::commands::export_with_preset,
+    crate::video_compiler::commands::validate_filename_template,
+    crate::video_compiler::commands::check_export_settings,
+    // Video compiler final utilities commands
+    crate::video_compiler::commands::generate_subtitle_preview_ffmpeg,
+    crate::video_compiler::commands::execute_ffmpeg_with_progress_handler,
+    // Video compiler ffmpeg builder extra commands
+    crate::video_compiler::commands::build_prerender_segment_command_advanced,
+    crate::video_compiler::commands::validate_prerender_segment_params,
+    crate::video_compiler::commands::get_optimal_prerender_settings,
+    crate::video_compiler::commands::build_prerender_segment_direct,
+    // Video compiler progress tracker commands
+    crate::video_compiler::commands::get_render_progress_tracker,
+    crate::video_compiler::commands::get_progress_tracker_statistics,
+    crate::video_compiler::commands::reset_progress_tracker,
+    crate::video_compiler::commands::set_progress_callback_enabled,
+    crate::video_compiler::commands::get_current_operation_details,
+    // Video compiler pipeline advanced commands
+    crate::video_compiler::commands::get_pipeline_context_mutable,
+    crate::video_compiler::commands::set_pipeline_user_data,
+    crate::video_compiler::commands::get_pipeline_user_data,
+    crate::video_compiler::commands::should_use_hardware_acceleration_for_codec,
+    crate::video_compiler::commands::generate_noise_clip_advanced,
+    crate::video_compiler::commands::generate_gradient_clip_advanced,
+    crate::video_compiler::commands::check_should_use_hardware_acceleration_for_codec,
+    crate::video_compiler::commands::set_pipeline_user_data_direct,
+    crate::video_compiler::commands::get_pipeline_user_data_direct,
+    crate::video_compiler::commands::generate_noise_clip_direct,
+    crate::video_compiler::commands::generate_gradient_clip_direct,
+    // Video compiler frame manager commands
+    crate::video_compiler::commands::extract_frames_for_clip_command,
+    crate::video_compiler::commands::extract_frames_for_subtitles_command,
+    crate::video_compiler::commands::get_frame_extraction_cache_info_command,
+    // Video compiler frame extraction advanced commands
+    crate::video_compiler::commands::extract_timeline_frames_advanced,
+    crate::video_compiler::commands::extract_subtitle_frames_advanced,
+    crate::video_compiler::commands::extract_video_frame_advanced,
+    crate::video_compiler::commands::extract_video_frames_batch_advanced,
+    crate::video_compiler::commands::get_video_thumbnails_advanced,
+    crate::video_compiler::commands::get_frame_extraction_cache_information,
+    crate::video_compiler::commands::generate_preview_frame,
+    crate::video_compiler::commands::generate_preview_batch_frames,
+    crate::video_compiler::commands::generate_preview_with_custom_settings,
+    // Video compiler service commands
+    crate::video_compiler::commands::get_input_sources_info,
+    crate::video_compiler::commands::touch_project,
+    crate::video_compiler::commands::set_preview_ffmpeg_path,
+    crate::video_compiler::commands::get_all_service_metrics,
+    crate::video_compiler::commands::get_specific_service_metrics,
+    crate::video_compiler::commands::cleanup_completed_jobs,
+    crate::video_compiler::commands::get_services_health,
+    crate::video_compiler::commands::restart_service,
+    crate::video_compiler::commands::get_service_policies,
+    crate::video_compiler::commands::update_service_policies,
+    // Video compiler metrics advanced commands
+    crate::video_compiler::commands::get_active_operations_count_detailed,
+    crate::video_compiler::commands::get_error_statistics_detailed,
+    crate::video_compiler::commands::get_slow_operations_detailed,
+    crate::video_compiler::commands::get_service_container_metrics_detailed,
+    crate::video_compiler::commands::get_render_pipeline_statistics_advanced,
+    crate::video_compiler::commands::reset_service_metrics_advanced,
+    crate::video_compiler::commands::export_metrics_prometheus_advanced,
+    // Video compiler remaining utilities commands
+    crate::video_compiler::commands::test_hardware_acceleration_available,
+    crate::video_compiler::commands::perform_track_operations,
+    crate::video_compiler::commands::get_detailed_clip_info,
+    crate::video_compiler::commands::validate_subtitle_project,
+    crate::video_compiler::commands::touch_project_timestamp,
+    crate::video_compiler::commands::get_cache_metadata,
+    crate::video_compiler::commands::get_cache_hit_ratio_stats,
+    crate::video_compiler::commands::clear_cache_advanced,
+    // Video compiler ffmpeg builder advanced commands
+    crate::video_compiler::commands::get_ffmpeg_builder_settings_advanced,
+    crate::video_compiler::commands::get_ffmpeg_builder_project_info_advanced,
+    crate::video_compiler::commands::get_segment_filters_info_advanced,
+    crate::video_compiler::commands::validate_segment_timestamps_advanced,
+    crate::video_compiler::commands::get_frame_extraction_cache_advanced,
+    crate::video_compiler::commands::get_clip_input_index_advanced,
+    // Video compiler recognition advanced commands
+    crate::video_compiler::commands::get_model_manager_status,
+    crate::video_compiler::commands::check_yolo_model_is_face_model,
+    crate::video_compiler::commands::check_yolo_model_is_segmentation_model,
+    crate::video_compiler::commands::get_yolo_model_info_extended,
+    crate::video_compiler::commands::get_model_session_info,
+    crate::video_compiler::commands::get_loaded_model_type,
+    crate::video_compiler::commands::check_model_is_loaded,
+    // Command registry
+    crate::command_registry::list_registered_commands,
+    crate::specta_export::get_app_version,
+    // Telemetry commands
+    crate::core::telemetry::command_metrics::get_command_metrics_summary,
+    crate::core::telemetry::cache_metrics::get_cache_metrics_report,
+    crate::core::performance::memory_pressure::get_memory_pressure_status,
+    // Application lifecycle
+    crate::core::shutdown::request_shutdown,
+    crate::core::startup::get_startup_report,
+    // Background tasks
+    crate::core::tasks::list_background_tasks,
+    crate::core::tasks::cancel_background_task,
+    crate::core::logging::set_log_level,
+    crate::core::logging::get_log_settings,
+    crate::core::logging::get_recent_logs,
+    crate::core::logging::export_logs_to_file,
     // Plugin system commands
     crate::core::plugins::commands::load_plugin,
     crate::core::plugins::commands::unload_plugin,
@@ -294,19 +652,29 @@ pub fn build_app<R: Runtime>() -> Builder<R> {
     crate::core::plugins::commands::suspend_plugin,
     crate::core::plugins::commands::resume_plugin,
     crate::core::plugins::commands::get_plugins_sandbox_stats,
+    crate::core::plugins::commands::get_network_gateway_stats,
     crate::core::plugins::commands::get_violating_plugins,
     crate::core::plugins::commands::reset_plugin_violations,
     crate::core::plugins::commands::register_example_plugins,
+    crate::core::plugins::commands::install_plugin_from_path,
+    crate::core::plugins::commands::uninstall_plugin,
+    crate::core::plugins::commands::rescan_plugins,
     // Smart Montage Planner commands
     crate::montage_planner::commands::analyze_video_composition,
     crate::montage_planner::commands::detect_key_moments,
     crate::montage_planner::commands::generate_montage_plan,
+    crate::montage_planner::commands::analyze_segment_quality,
+    crate::montage_planner::commands::detect_audio_beats,
+    crate::montage_planner::commands::compute_audio_energy_curve,
+    crate::montage_planner::commands::sync_clips_by_audio,
+    crate::montage_planner::commands::create_multicam_project,
     crate::montage_planner::commands::get_analysis_progress,
     crate::montage_planner::commands::update_composition_weights,
     // Misc commands
     crate::greet,
     crate::scan_media_folder,
     crate::scan_media_folder_with_thumbnails,
+    crate::scan_media_folder_incremental,
     crate::test_plugin_system,
   ])
 }
@@ -226,6 +226,11 @@ impl AppDirectories {
     self.caches_dir.join("Frames")
   }
 
+  /// Получить путь к директории шаблонов проектов
+  pub fn get_project_templates_dir(&self) -> PathBuf {
+    self.projects_dir.join("Templates")
+  }
+
   /// Получить путь к директории временных файлов
   pub fn get_temp_dir(&self) -> PathBuf {
     self.caches_dir.join("Temp")
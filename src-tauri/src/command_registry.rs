@@ -1,3 +1,4 @@
+use serde::Serialize;
 use tauri::{Builder, Runtime};
 
 /// Trait for modular command registration
@@ -32,6 +33,85 @@ macro_rules! register_commands {
     };
 }
 
+/// Static description of the Tauri commands defined in a module
+///
+/// Generated by [`command_manifest!`]. Used by the registration audit test to
+/// check that every command is registered in `app_builder` and by
+/// `list_registered_commands` for frontend feature detection.
+#[derive(Debug, Clone, Copy)]
+pub struct CommandManifest {
+  /// Logical module name, e.g. `video_compiler::gpu`
+  pub module: &'static str,
+  /// Source file the manifest was declared in (relative to the crate root)
+  pub source: &'static str,
+  /// Commands that must be registered with the application
+  pub commands: &'static [&'static str],
+  /// Commands that are intentionally not exposed to the frontend
+  pub internal: &'static [&'static str],
+}
+
+/// Registered command description returned to the frontend
+#[derive(Debug, Clone, Serialize)]
+pub struct RegisteredCommandInfo {
+  pub name: String,
+  pub module: String,
+}
+
+/// Declare the command manifest of a module
+///
+/// Must be invoked in the file that defines the commands: the audit test
+/// compares the listed names with the `#[tauri::command]` functions found in
+/// that file.
+///
+/// # Example
+/// ```rust,ignore
+/// crate::command_manifest!(
+///   GPU_MANIFEST,
+///   "video_compiler::gpu",
+///   [detect_gpus, get_gpu_capabilities],
+///   internal: [legacy_gpu_command]
+/// );
+/// ```
+#[macro_export]
+macro_rules! command_manifest {
+  (
+    $name:ident,
+    $module:expr,
+    [$($cmd:ident),* $(,)?]
+    $(, internal: [$($internal:ident),* $(,)?])?
+    $(,)?
+  ) => {
+    pub const $name: $crate::command_registry::CommandManifest =
+      $crate::command_registry::CommandManifest {
+        module: $module,
+        source: file!(),
+        commands: &[$(stringify!($cmd)),*],
+        internal: &[$($(stringify!($internal)),*)?],
+      };
+  };
+}
+
+/// All command manifests of modules migrated to [`command_manifest!`]
+pub fn command_manifests() -> impl Iterator<Item = &'static CommandManifest> {
+  crate::video_compiler::commands::COMMAND_MANIFESTS.iter()
+}
+
+/// List commands registered with the application
+///
+/// Only modules migrated to [`command_manifest!`] are reported, so the
+/// frontend should treat a missing name as "unsupported", not as an error.
+#[tauri::command]
+pub fn list_registered_commands() -> Vec<RegisteredCommandInfo> {
+  command_manifests()
+    .flat_map(|manifest| {
+      manifest.commands.iter().map(|name| RegisteredCommandInfo {
+        name: (*name).to_string(),
+        module: manifest.module.to_string(),
+      })
+    })
+    .collect()
+}
+
 #[cfg(test)]
 #[path = "command_registry_tests.rs"]
 mod tests;
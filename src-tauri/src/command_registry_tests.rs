@@ -277,3 +277,188 @@ mod command_registry_tests {
     assert_eq!(command_list.len(), 3);
   }
 }
+
+#[cfg(test)]
+mod command_audit_tests {
+  use crate::command_registry::{command_manifests, list_registered_commands};
+  use std::collections::{HashMap, HashSet};
+  use std::path::Path;
+
+  /// Names of all commands passed to `generate_handler!` in app_builder.rs
+  fn registered_command_names() -> HashSet<String> {
+    let source = include_str!("app_builder.rs");
+    let start = source
+      .find("generate_handler![")
+      .expect("app_builder.rs must contain generate_handler!");
+    let end = source[start..]
+      .find("])")
+      .map(|offset| start + offset)
+      .expect("generate_handler! must be closed");
+
+    source[start + "generate_handler![".len()..end]
+      .lines()
+      .map(str::trim)
+      .filter(|line| !line.is_empty() && !line.starts_with("//"))
+      .map(|line| line.trim_end_matches(','))
+      .filter_map(|path| path.rsplit("::").next())
+      .map(str::to_string)
+      .collect()
+  }
+
+  /// Names of `#[tauri::command]` functions defined in a source file
+  fn commands_in_source(source: &str) -> HashSet<String> {
+    let pattern = regex::Regex::new(
+      r"#\[tauri::command[^\]]*\]\s*(?:#\[[^\]]*\]\s*)*pub\s+(?:async\s+)?fn\s+(\w+)",
+    )
+    .unwrap();
+    pattern
+      .captures_iter(source)
+      .map(|captures| captures[1].to_string())
+      .collect()
+  }
+
+  #[test]
+  fn test_manifest_commands_are_registered() {
+    let registered = registered_command_names();
+    let missing: Vec<String> = command_manifests()
+      .flat_map(|manifest| {
+        manifest
+          .commands
+          .iter()
+          .filter(|name| !registered.contains(**name))
+          .map(|name| format!("{}::{name}", manifest.module))
+      })
+      .collect();
+
+    assert!(
+      missing.is_empty(),
+      "Commands missing from app_builder generate_handler!: {missing:#?}"
+    );
+  }
+
+  #[test]
+  fn test_internal_commands_are_not_registered() {
+    let registered = registered_command_names();
+    let exposed: Vec<String> = command_manifests()
+      .flat_map(|manifest| {
+        manifest
+          .internal
+          .iter()
+          .filter(|name| registered.contains(**name))
+          .map(|name| format!("{}::{name}", manifest.module))
+      })
+      .collect();
+
+    assert!(
+      exposed.is_empty(),
+      "Commands marked internal but registered (move them to the public list): {exposed:#?}"
+    );
+  }
+
+  #[test]
+  fn test_manifests_match_source_files() {
+    let crate_root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let mut problems = Vec::new();
+
+    for manifest in command_manifests() {
+      let path = crate_root.join(manifest.source);
+      let source = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("Failed to read {}: {e}", path.display()));
+      let defined = commands_in_source(&source);
+      let listed: HashSet<String> = manifest
+        .commands
+        .iter()
+        .chain(manifest.internal.iter())
+        .map(|name| name.to_string())
+        .collect();
+
+      for name in defined.difference(&listed) {
+        problems.push(format!(
+          "{}: {name} is not in the manifest",
+          manifest.module
+        ));
+      }
+      for name in listed.difference(&defined) {
+        problems.push(format!("{}: {name} is not a command", manifest.module));
+      }
+    }
+
+    problems.sort();
+    assert!(
+      problems.is_empty(),
+      "Command manifests are out of date: {problems:#?}"
+    );
+  }
+
+  #[test]
+  fn test_manifest_command_names_are_unique() {
+    let mut owners: HashMap<&str, Vec<&str>> = HashMap::new();
+    for manifest in command_manifests() {
+      for name in manifest.commands {
+        owners.entry(name).or_default().push(manifest.module);
+      }
+    }
+
+    let duplicates: Vec<_> = owners
+      .into_iter()
+      .filter(|(_, modules)| modules.len() > 1)
+      .collect();
+    assert!(
+      duplicates.is_empty(),
+      "Duplicate command names: {duplicates:#?}"
+    );
+  }
+
+  #[test]
+  fn test_specta_commands_are_registered_and_exported() {
+    let registered = registered_command_names();
+    for name in crate::specta_export::SPECTA_COMMANDS {
+      assert!(
+        registered.contains(*name),
+        "Specta command {name} is not registered in app_builder"
+      );
+    }
+
+    // Bindings are generated in debug builds; check them when present
+    let bindings_path =
+      Path::new(env!("CARGO_MANIFEST_DIR")).join("../src/types/generated/tauri-bindings.ts");
+    if let Ok(bindings) = std::fs::read_to_string(bindings_path) {
+      for name in crate::specta_export::SPECTA_COMMANDS {
+        let camel_case = name
+          .split('_')
+          .enumerate()
+          .map(|(i, part)| {
+            if i == 0 {
+              part.to_string()
+            } else {
+              let mut chars = part.chars();
+              chars
+                .next()
+                .map(|c| c.to_uppercase().chain(chars).collect())
+                .unwrap_or_default()
+            }
+          })
+          .collect::<String>();
+        assert!(
+          bindings.contains(&camel_case),
+          "Specta command {name} is missing from TypeScript bindings"
+        );
+      }
+    }
+  }
+
+  #[test]
+  fn test_list_registered_commands_excludes_internal() {
+    let listed: HashSet<String> = list_registered_commands()
+      .into_iter()
+      .map(|info| info.name)
+      .collect();
+
+    assert!(listed.contains("probe_audio_offset"));
+    for manifest in command_manifests() {
+      for name in manifest.internal {
+        assert!(!listed.contains(*name));
+      }
+    }
+  }
+}
@@ -165,6 +165,43 @@ impl ServiceContainer {
     Ok(())
   }
 
+  /// Регистрация сервиса по trait-object типу (например `dyn FfmpegService`)
+  ///
+  /// Повторная регистрация заменяет ранее установленную реализацию.
+  pub async fn register_instance<T>(&self, name: &'static str, service: Arc<T>) -> Result<()>
+  where
+    T: ?Sized + Send + Sync + 'static,
+  {
+    let entry = ServiceEntry {
+      service: Arc::new(service) as Arc<dyn Any + Send + Sync>,
+      name,
+      initialized: true,
+    };
+
+    let mut services = self.services.write().await;
+    services.insert(TypeId::of::<T>(), entry);
+
+    log::info!("Registered service instance: {name}");
+    Ok(())
+  }
+
+  /// Получение сервиса, зарегистрированного через register_instance
+  pub async fn resolve_instance<T>(&self) -> Result<Arc<T>>
+  where
+    T: ?Sized + Send + Sync + 'static,
+  {
+    let services = self.services.read().await;
+    let entry = services
+      .get(&TypeId::of::<T>())
+      .ok_or_else(|| VideoCompilerError::ServiceNotFound(std::any::type_name::<T>().to_string()))?;
+
+    entry
+      .service
+      .downcast_ref::<Arc<T>>()
+      .cloned()
+      .ok_or_else(|| VideoCompilerError::InternalError("Failed to downcast service".to_string()))
+  }
+
   /// Регистрация фабрики сервисов
   pub async fn register_provider<P>(&self, provider: P) -> Result<()>
   where
@@ -375,6 +412,45 @@ mod tests {
     }
   }
 
+  trait Greeter: Send + Sync {
+    fn greet(&self) -> &'static str;
+  }
+
+  struct English;
+  impl Greeter for English {
+    fn greet(&self) -> &'static str {
+      "hello"
+    }
+  }
+
+  struct Russian;
+  impl Greeter for Russian {
+    fn greet(&self) -> &'static str {
+      "привет"
+    }
+  }
+
+  #[tokio::test]
+  async fn test_register_instance_by_trait_object() {
+    let container = ServiceContainer::new();
+    assert!(container.resolve_instance::<dyn Greeter>().await.is_err());
+
+    container
+      .register_instance::<dyn Greeter>("greeter", Arc::new(English))
+      .await
+      .unwrap();
+    let greeter = container.resolve_instance::<dyn Greeter>().await.unwrap();
+    assert_eq!(greeter.greet(), "hello");
+
+    // Повторная регистрация заменяет реализацию
+    container
+      .register_instance::<dyn Greeter>("greeter", Arc::new(Russian))
+      .await
+      .unwrap();
+    let greeter = container.resolve_instance::<dyn Greeter>().await.unwrap();
+    assert_eq!(greeter.greet(), "привет");
+  }
+
   #[tokio::test]
   async fn test_service_registration_and_resolution() {
     let container = ServiceContainer::new();
@@ -88,10 +88,23 @@ pub enum AppEvent {
     plugin_id: String,
     event: serde_json::Value,
   },
+  PluginSuspended {
+    plugin_id: String,
+    reason: String,
+  },
+  TimelineTransactionCommitted {
+    plugin_id: String,
+    transaction_id: String,
+    mutations: serde_json::Value,
+  },
 
   // System events
   SystemStartup,
   SystemShutdown,
+  ShutdownProgress {
+    stage: String,
+    message: String,
+  },
   SystemHealthCheck {
     timestamp: chrono::DateTime<chrono::Utc>,
   },
@@ -102,6 +115,16 @@ pub enum AppEvent {
   MemoryWarning {
     usage_percent: f32,
   },
+  CacheMetricsSnapshot {
+    timestamp: chrono::DateTime<chrono::Utc>,
+    report: serde_json::Value,
+  },
+  MemoryPressure {
+    level: crate::core::performance::memory_pressure::MemoryPressureLevel,
+    process_rss_bytes: u64,
+    available_bytes: u64,
+    freed_bytes: u64,
+  },
 }
 
 /// Trait для обработчиков событий
@@ -406,6 +429,10 @@ mod tests {
         plugin_id: "test-plugin".to_string(),
         event: serde_json::json!({"type": "custom", "data": "test"}),
       },
+      AppEvent::PluginSuspended {
+        plugin_id: "test-plugin".to_string(),
+        reason: "quota exceeded".to_string(),
+      },
       AppEvent::PluginUnloaded {
         plugin_id: "test-plugin".to_string(),
       },
@@ -611,6 +638,20 @@ mod tests {
       AppEvent::MemoryWarning {
         usage_percent: 90.5,
       },
+      AppEvent::CacheMetricsSnapshot {
+        timestamp: chrono::Utc::now(),
+        report: serde_json::json!({ "enabled": true, "domains": [] }),
+      },
+      AppEvent::MemoryPressure {
+        level: crate::core::performance::memory_pressure::MemoryPressureLevel::Hard,
+        process_rss_bytes: 2 * 1024 * 1024 * 1024,
+        available_bytes: 256 * 1024 * 1024,
+        freed_bytes: 64 * 1024 * 1024,
+      },
+      AppEvent::ShutdownProgress {
+        stage: "cancelling_renders".to_string(),
+        message: "shutting down: cancelling 2 renders…".to_string(),
+      },
       AppEvent::SystemShutdown,
     ];
 
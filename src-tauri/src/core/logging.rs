@@ -0,0 +1,577 @@
+//! Структурированное логирование
+//!
+//! Фасад над `log`: уровни логирования настраиваются по префиксу модуля во
+//! время работы (с сохранением в настройках), последние записи хранятся в
+//! ограниченном кольцевом буфере [`LogRegistry`]. Записи, сделанные внутри
+//! [`with_job_id`], помечаются идентификатором задачи рендеринга, что позволяет
+//! фильтровать логи по конкретному рендеру. Экспорт для баг-репортов удаляет
+//! значения API ключей.
+
+use crate::video_compiler::error::{Result, VideoCompilerError};
+use chrono::{DateTime, Utc};
+use log::{Level, LevelFilter, Metadata, Record};
+use once_cell::sync::Lazy;
+use parking_lot::{Mutex, RwLock};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Количество записей в кольцевом буфере
+pub const LOG_BUFFER_CAPACITY: usize = 5000;
+
+/// Уровень логирования по умолчанию
+pub const DEFAULT_LOG_LEVEL: LevelFilter = LevelFilter::Info;
+
+/// Файл настроек уровней логирования в директории приложения
+const LOG_SETTINGS_FILE: &str = "log_settings.json";
+
+/// Префикс модулей самого приложения, который можно не указывать
+const CRATE_PREFIX: &str = concat!(env!("CARGO_CRATE_NAME"), "::");
+
+/// Имена полей с секретами помимо переменных окружения из модуля security
+const GENERIC_SECRET_NAMES: &[&str] = &[
+  "api_key",
+  "apikey",
+  "access_token",
+  "refresh_token",
+  "client_secret",
+  "authorization",
+  "password",
+];
+
+/// Заменитель удаленных секретов
+const REDACTED: &str = "[REDACTED]";
+
+/// Глобальный реестр логов приложения
+pub static LOGS: Lazy<LogRegistry> = Lazy::new(|| LogRegistry::new(LOG_BUFFER_CAPACITY));
+
+tokio::task_local! {
+  static JOB_ID: String;
+}
+
+/// Выполнить future, помечая все его записи логов идентификатором задачи
+pub async fn with_job_id<F: Future>(job_id: String, future: F) -> F::Output {
+  JOB_ID.scope(job_id, future).await
+}
+
+/// Идентификатор задачи текущей области [`with_job_id`]
+pub fn current_job_id() -> Option<String> {
+  JOB_ID.try_with(|job_id| job_id.clone()).ok()
+}
+
+/// Структурированная запись лога
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogRecord {
+  pub timestamp: DateTime<Utc>,
+  pub level: String,
+  pub module: String,
+  pub message: String,
+  pub job_id: Option<String>,
+}
+
+impl LogRecord {
+  /// Строка для текстового экспорта
+  fn to_line(&self) -> String {
+    let job = self
+      .job_id
+      .as_ref()
+      .map(|job_id| format!(" [job={job_id}]"))
+      .unwrap_or_default();
+    format!(
+      "{} {:<5} {}{job}: {}",
+      self.timestamp.to_rfc3339(),
+      self.level,
+      self.module,
+      self.message
+    )
+  }
+}
+
+/// Фильтр выборки записей
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogFilter {
+  /// Минимальная важность ("error", "warn", "info", "debug", "trace")
+  pub level: Option<String>,
+  /// Префикс модуля
+  pub module: Option<String>,
+  pub job_id: Option<String>,
+  /// Подстрока сообщения (без учета регистра)
+  pub contains: Option<String>,
+  pub since: Option<DateTime<Utc>>,
+  /// Максимум записей (последние)
+  pub limit: Option<usize>,
+}
+
+/// Сохраняемые настройки уровней логирования
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogSettings {
+  /// Уровень по умолчанию, если не задан используется [`DEFAULT_LOG_LEVEL`]
+  pub default_level: Option<String>,
+  /// Переопределения уровня по префиксу модуля
+  pub module_levels: BTreeMap<String, String>,
+}
+
+/// Разобрать уровень логирования
+pub fn parse_level(level: &str) -> Result<LevelFilter> {
+  LevelFilter::from_str(level.trim()).map_err(|_| {
+    VideoCompilerError::InvalidParameter(format!("Неизвестный уровень логирования: {level}"))
+  })
+}
+
+/// Имя модуля без префикса приложения
+fn normalize_module(target: &str) -> &str {
+  target.strip_prefix(CRATE_PREFIX).unwrap_or(target)
+}
+
+/// Относится ли модуль к префиксу (по границе сегментов `::`)
+fn module_matches(module: &str, prefix: &str) -> bool {
+  module == prefix
+    || module
+      .strip_prefix(prefix)
+      .is_some_and(|rest| rest.starts_with("::"))
+}
+
+/// Уровни логирования и кольцевой буфер последних записей
+pub struct LogRegistry {
+  default_level: RwLock<LevelFilter>,
+  /// Переопределения, отсортированные по убыванию длины префикса
+  overrides: RwLock<Vec<(String, LevelFilter)>>,
+  records: Mutex<VecDeque<LogRecord>>,
+  capacity: usize,
+  settings_path: RwLock<Option<PathBuf>>,
+}
+
+impl LogRegistry {
+  pub fn new(capacity: usize) -> Self {
+    Self {
+      default_level: RwLock::new(DEFAULT_LOG_LEVEL),
+      overrides: RwLock::new(Vec::new()),
+      records: Mutex::new(VecDeque::with_capacity(capacity.min(1024))),
+      capacity,
+      settings_path: RwLock::new(None),
+    }
+  }
+
+  /// Действующий уровень для модуля: самый длинный подходящий префикс
+  pub fn level_for(&self, target: &str) -> LevelFilter {
+    let module = normalize_module(target);
+    self
+      .overrides
+      .read()
+      .iter()
+      .find(|(prefix, _)| module_matches(module, prefix) || module_matches(target, prefix))
+      .map(|(_, level)| *level)
+      .unwrap_or(*self.default_level.read())
+  }
+
+  /// Пропускать ли запись с такими метаданными
+  pub fn enabled(&self, metadata: &Metadata) -> bool {
+    metadata.level() <= self.level_for(metadata.target())
+  }
+
+  /// Установить уровень для префикса модуля.
+  ///
+  /// Пустой префикс или `*` меняет уровень по умолчанию; `None` снимает
+  /// переопределение.
+  pub fn set_level(&self, module_prefix: &str, level: Option<LevelFilter>) {
+    let prefix = normalize_module(module_prefix.trim());
+    if prefix.is_empty() || prefix == "*" {
+      *self.default_level.write() = level.unwrap_or(DEFAULT_LOG_LEVEL);
+      return;
+    }
+
+    let mut overrides = self.overrides.write();
+    overrides.retain(|(existing, _)| existing != prefix);
+    if let Some(level) = level {
+      overrides.push((prefix.to_string(), level));
+      overrides.sort_by(|a, b| b.0.len().cmp(&a.0.len()));
+    }
+  }
+
+  /// Текущие настройки уровней
+  pub fn settings(&self) -> LogSettings {
+    let default_level = *self.default_level.read();
+    LogSettings {
+      default_level: (default_level != DEFAULT_LOG_LEVEL)
+        .then(|| default_level.as_str().to_lowercase()),
+      module_levels: self
+        .overrides
+        .read()
+        .iter()
+        .map(|(prefix, level)| (prefix.clone(), level.as_str().to_lowercase()))
+        .collect(),
+    }
+  }
+
+  /// Применить настройки уровней, некорректные значения пропускаются
+  pub fn apply_settings(&self, settings: &LogSettings) {
+    let default_level = settings
+      .default_level
+      .as_deref()
+      .and_then(|level| parse_level(level).ok())
+      .unwrap_or(DEFAULT_LOG_LEVEL);
+    *self.default_level.write() = default_level;
+
+    self.overrides.write().clear();
+    for (prefix, level) in &settings.module_levels {
+      match parse_level(level) {
+        Ok(level) => self.set_level(prefix, Some(level)),
+        Err(e) => log::warn!("Пропущена настройка логирования для {prefix}: {e}"),
+      }
+    }
+  }
+
+  /// Загрузить настройки из файла и сохранять в него последующие изменения
+  pub fn load_settings(&self, path: PathBuf) {
+    if let Ok(content) = std::fs::read_to_string(&path) {
+      match serde_json::from_str::<LogSettings>(&content) {
+        Ok(settings) => self.apply_settings(&settings),
+        Err(e) => log::warn!("Некорректный файл настроек логирования {path:?}: {e}"),
+      }
+    }
+    *self.settings_path.write() = Some(path);
+  }
+
+  /// Сохранить текущие настройки, если задан файл настроек
+  pub fn persist_settings(&self) -> Result<()> {
+    let Some(path) = self.settings_path.read().clone() else {
+      return Ok(());
+    };
+    if let Some(parent) = path.parent() {
+      std::fs::create_dir_all(parent).map_err(|e| VideoCompilerError::IoError(e.to_string()))?;
+    }
+    let content = serde_json::to_string_pretty(&self.settings())
+      .map_err(|e| VideoCompilerError::SerializationError(e.to_string()))?;
+    std::fs::write(&path, content).map_err(|e| VideoCompilerError::IoError(e.to_string()))
+  }
+
+  /// Записать запись лога в кольцевой буфер
+  pub fn capture(&self, record: &Record) {
+    let entry = LogRecord {
+      timestamp: Utc::now(),
+      level: record.level().to_string(),
+      module: normalize_module(record.target()).to_string(),
+      message: record.args().to_string(),
+      job_id: current_job_id(),
+    };
+
+    let mut records = self.records.lock();
+    if records.len() >= self.capacity {
+      records.pop_front();
+    }
+    records.push_back(entry);
+  }
+
+  /// Последние записи, подходящие под фильтр, без секретов
+  pub fn recent(&self, filter: &LogFilter) -> Result<Vec<LogRecord>> {
+    let min_level = match &filter.level {
+      Some(level) => Some(parse_level(level)?),
+      None => None,
+    };
+    let contains = filter.contains.as_ref().map(|text| text.to_lowercase());
+
+    let records = self.records.lock();
+    let mut matched: Vec<LogRecord> = records
+      .iter()
+      .filter(|record| {
+        min_level.is_none_or(|min_level| {
+          Level::from_str(&record.level).is_ok_and(|level| level <= min_level)
+        }) && filter
+          .module
+          .as_deref()
+          .is_none_or(|prefix| module_matches(&record.module, normalize_module(prefix)))
+          && filter
+            .job_id
+            .as_ref()
+            .is_none_or(|job_id| record.job_id.as_ref() == Some(job_id))
+          && filter.since.is_none_or(|since| record.timestamp >= since)
+          && contains
+            .as_ref()
+            .is_none_or(|text| record.message.to_lowercase().contains(text))
+      })
+      .cloned()
+      .collect();
+    drop(records);
+
+    if let Some(limit) = filter.limit {
+      let skip = matched.len().saturating_sub(limit);
+      matched.drain(..skip);
+    }
+
+    let redactor = Redactor::from_security_module();
+    for record in &mut matched {
+      record.message = redactor.redact(&record.message);
+    }
+    Ok(matched)
+  }
+
+  /// Экспортировать записи начиная с `since` в текстовый файл без секретов.
+  ///
+  /// Возвращает количество записанных записей.
+  pub fn export_to_file(&self, path: &Path, since: Option<DateTime<Utc>>) -> Result<usize> {
+    let records = self.recent(&LogFilter {
+      since,
+      ..Default::default()
+    })?;
+
+    let mut bundle = format!(
+      "# Timeline Studio {} logs\n# exported: {}\n# settings: {}\n",
+      env!("CARGO_PKG_VERSION"),
+      Utc::now().to_rfc3339(),
+      serde_json::to_string(&self.settings()).unwrap_or_default()
+    );
+    for record in &records {
+      bundle.push_str(&record.to_line());
+      bundle.push('\n');
+    }
+
+    if let Some(parent) = path.parent() {
+      std::fs::create_dir_all(parent).map_err(|e| VideoCompilerError::IoError(e.to_string()))?;
+    }
+    std::fs::write(path, bundle).map_err(|e| VideoCompilerError::IoError(e.to_string()))?;
+    Ok(records.len())
+  }
+}
+
+/// Удаление значений API ключей из текста
+pub struct Redactor {
+  pattern: Option<Regex>,
+}
+
+impl Redactor {
+  /// Редактор по именам секретов
+  pub fn new<S: AsRef<str>>(names: &[S]) -> Self {
+    let alternatives: Vec<String> = names
+      .iter()
+      .map(|name| regex::escape(name.as_ref()))
+      .filter(|name| !name.is_empty())
+      .collect();
+    if alternatives.is_empty() {
+      return Self { pattern: None };
+    }
+
+    // NAME=value, NAME: value, "name": "value", Authorization: Bearer value
+    let pattern = format!(
+      r#"(?i)\b({})(["']?\s*[:=]\s*["']?(?:bearer\s+)?)[^\s"',;&]+"#,
+      alternatives.join("|")
+    );
+    Self {
+      pattern: Regex::new(&pattern).ok(),
+    }
+  }
+
+  /// Редактор по известным именам ключей из модуля security
+  pub fn from_security_module() -> &'static Self {
+    static REDACTOR: Lazy<Redactor> = Lazy::new(|| {
+      let mut names = crate::security::env_importer::EnvImporter::new().known_key_names();
+      names.extend(GENERIC_SECRET_NAMES.iter().map(|name| name.to_string()));
+      Redactor::new(&names)
+    });
+    &REDACTOR
+  }
+
+  pub fn redact(&self, text: &str) -> String {
+    match &self.pattern {
+      Some(pattern) => pattern
+        .replace_all(text, format!("${{1}}${{2}}{REDACTED}"))
+        .into_owned(),
+      None => text.to_string(),
+    }
+  }
+}
+
+/// Путь к файлу настроек логирования в директории приложения
+pub fn default_settings_path() -> Option<PathBuf> {
+  crate::app_dirs::AppDirectories::get_or_create()
+    .ok()
+    .map(|dirs| dirs.base_dir.join(LOG_SETTINGS_FILE))
+}
+
+/// Фильтр записей для плагина логирования
+pub fn is_enabled(metadata: &Metadata) -> bool {
+  LOGS.enabled(metadata)
+}
+
+/// Target плагина логирования, складывающий записи в [`LOGS`]
+pub fn ring_buffer_target() -> tauri_plugin_log::Target {
+  tauri_plugin_log::Target::new(tauri_plugin_log::TargetKind::Dispatch(
+    fern::Dispatch::new().chain(fern::Output::call(|record| LOGS.capture(record))),
+  ))
+}
+
+/// Установить уровень логирования для префикса модуля.
+///
+/// Пустой префикс или `*` задает уровень по умолчанию, `level: null`
+/// снимает переопределение. Настройка сохраняется между запусками.
+#[tauri::command]
+pub async fn set_log_level(module_prefix: String, level: Option<String>) -> Result<LogSettings> {
+  let level = level.as_deref().map(parse_level).transpose()?;
+  LOGS.set_level(&module_prefix, level);
+  LOGS.persist_settings()?;
+  Ok(LOGS.settings())
+}
+
+/// Получить текущие настройки уровней логирования
+#[tauri::command]
+pub async fn get_log_settings() -> Result<LogSettings> {
+  Ok(LOGS.settings())
+}
+
+/// Получить последние записи лога
+#[tauri::command]
+pub async fn get_recent_logs(filter: Option<LogFilter>) -> Result<Vec<LogRecord>> {
+  LOGS.recent(&filter.unwrap_or_default())
+}
+
+/// Экспортировать логи в файл для баг-репорта (значения API ключей удаляются)
+#[tauri::command]
+pub async fn export_logs_to_file(path: String, since: Option<DateTime<Utc>>) -> Result<usize> {
+  LOGS.export_to_file(Path::new(&path), since)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn capture(registry: &LogRegistry, level: Level, target: &str, message: &str) {
+    registry.capture(
+      &Record::builder()
+        .args(format_args!("{message}"))
+        .level(level)
+        .target(target)
+        .build(),
+    );
+  }
+
+  fn enabled(registry: &LogRegistry, level: Level, target: &str) -> bool {
+    registry.enabled(&Metadata::builder().level(level).target(target).build())
+  }
+
+  #[test]
+  fn test_module_level_overrides_use_longest_prefix() {
+    let registry = LogRegistry::new(10);
+    registry.set_level("video_compiler", Some(LevelFilter::Warn));
+    registry.set_level(
+      "video_compiler::services::render_service",
+      Some(LevelFilter::Trace),
+    );
+
+    let render = format!("{CRATE_PREFIX}video_compiler::services::render_service");
+    let cache = format!("{CRATE_PREFIX}video_compiler::core::cache");
+    assert!(enabled(&registry, Level::Trace, &render));
+    assert!(!enabled(&registry, Level::Info, &cache));
+    assert!(enabled(&registry, Level::Warn, &cache));
+    assert!(enabled(&registry, Level::Info, "recognition"));
+    assert!(!enabled(&registry, Level::Debug, "recognition"));
+    assert!(enabled(&registry, Level::Info, "video_compiler_extra"));
+
+    registry.set_level("video_compiler", None);
+    assert!(enabled(&registry, Level::Info, &cache));
+  }
+
+  #[test]
+  fn test_ring_buffer_is_bounded() {
+    let registry = LogRegistry::new(3);
+    for i in 0..5 {
+      capture(
+        &registry,
+        Level::Info,
+        "core::tasks",
+        &format!("message {i}"),
+      );
+    }
+
+    let messages: Vec<String> = registry
+      .recent(&LogFilter::default())
+      .unwrap()
+      .into_iter()
+      .map(|record| record.message)
+      .collect();
+    assert_eq!(messages, vec!["message 2", "message 3", "message 4"]);
+  }
+
+  #[tokio::test]
+  async fn test_records_are_tagged_and_filtered_by_job_id() {
+    let registry = LogRegistry::new(10);
+    capture(&registry, Level::Info, "video_compiler", "before render");
+    with_job_id("job-1".to_string(), async {
+      capture(&registry, Level::Info, "video_compiler", "rendering");
+      capture(&registry, Level::Error, "video_compiler", "render failed");
+    })
+    .await;
+
+    let job_logs = registry
+      .recent(&LogFilter {
+        job_id: Some("job-1".to_string()),
+        ..Default::default()
+      })
+      .unwrap();
+    assert_eq!(job_logs.len(), 2);
+
+    let errors = registry
+      .recent(&LogFilter {
+        level: Some("warn".to_string()),
+        ..Default::default()
+      })
+      .unwrap();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].job_id.as_deref(), Some("job-1"));
+    assert!(registry
+      .recent(&LogFilter {
+        level: Some("loud".to_string()),
+        ..Default::default()
+      })
+      .is_err());
+  }
+
+  #[test]
+  fn test_redactor_strips_known_keys() {
+    let redactor = Redactor::from_security_module();
+
+    assert_eq!(
+      redactor.redact("env OPENAI_API_KEY=sk-secret123 loaded"),
+      "env OPENAI_API_KEY=[REDACTED] loaded"
+    );
+    assert_eq!(
+      redactor.redact(r#"{"client_secret": "abc", "name": "clip"}"#),
+      r#"{"client_secret": "[REDACTED]", "name": "clip"}"#
+    );
+    assert_eq!(
+      redactor.redact("Authorization: Bearer tok-42"),
+      "Authorization: Bearer [REDACTED]"
+    );
+    assert_eq!(redactor.redact("nothing secret"), "nothing secret");
+  }
+
+  #[test]
+  fn test_export_and_settings_persistence() {
+    let dir = tempfile::tempdir().unwrap();
+    let registry = LogRegistry::new(10);
+    registry.load_settings(dir.path().join(LOG_SETTINGS_FILE));
+    registry.set_level("recognition", Some(LevelFilter::Debug));
+    registry.persist_settings().unwrap();
+
+    let restored = LogRegistry::new(10);
+    restored.load_settings(dir.path().join(LOG_SETTINGS_FILE));
+    assert_eq!(restored.settings(), registry.settings());
+    assert_eq!(restored.level_for("recognition::yolo"), LevelFilter::Debug);
+
+    capture(
+      &registry,
+      Level::Warn,
+      "security",
+      "DEEPSEEK_API_KEY=ds-1 rejected",
+    );
+    let bundle = dir.path().join("logs.txt");
+    assert_eq!(registry.export_to_file(&bundle, None).unwrap(), 1);
+
+    let content = std::fs::read_to_string(&bundle).unwrap();
+    assert!(content.contains("DEEPSEEK_API_KEY=[REDACTED] rejected"));
+    assert!(!content.contains("ds-1"));
+  }
+}
@@ -2,8 +2,12 @@
 
 pub mod di;
 pub mod events;
+pub mod logging;
 pub mod performance;
 pub mod plugins;
+pub mod shutdown;
+pub mod startup;
+pub mod tasks;
 pub mod telemetry;
 
 #[cfg(test)]
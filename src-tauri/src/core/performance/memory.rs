@@ -1,5 +1,6 @@
 //! Memory pool для эффективного управления памятью
 
+use super::memory_pressure::{MemoryPressureConfig, MemoryPressureMonitor};
 use crate::video_compiler::error::{Result, VideoCompilerError};
 use std::alloc::{alloc, dealloc, Layout};
 use std::collections::{HashMap, VecDeque};
@@ -406,6 +407,7 @@ impl MemoryPoolStats {
 pub struct MemoryManager {
   pool: Arc<MemoryPool>,
   stats_collector: Arc<RwLock<MemoryManagerStats>>,
+  pressure: Arc<MemoryPressureMonitor>,
 }
 
 /// Статистика memory manager
@@ -422,9 +424,15 @@ pub struct MemoryManagerStats {
 impl MemoryManager {
   /// Создать новый memory manager
   pub fn new() -> Self {
+    Self::with_pressure_config(MemoryPressureConfig::default())
+  }
+
+  /// Создать memory manager с указанными порогами давления на память
+  pub fn with_pressure_config(config: MemoryPressureConfig) -> Self {
     Self {
       pool: Arc::new(MemoryPool::new()),
       stats_collector: Arc::new(RwLock::new(MemoryManagerStats::default())),
+      pressure: Arc::new(MemoryPressureMonitor::new(config)),
     }
   }
 
@@ -433,6 +441,11 @@ impl MemoryManager {
     self.pool.clone()
   }
 
+  /// Получить монитор давления на память, в котором регистрируются кэши
+  pub fn pressure(&self) -> Arc<MemoryPressureMonitor> {
+    self.pressure.clone()
+  }
+
   /// Выделить буфер
   pub async fn allocate(&self, size: usize) -> Result<PooledBuffer> {
     // Обновляем статистику
@@ -0,0 +1,536 @@
+//! Реакция на нехватку памяти
+//!
+//! [`MemoryPressureMonitor`] раз в несколько секунд замеряет RSS процесса и доступную
+//! память системы. Кэши регистрируют [`MemoryShrinker`] с приоритетом: при мягком давлении
+//! часть записей сбрасывают только низкоприоритетные кэши, при жестком все кэши ужимаются
+//! до минимального остатка, в EventBus публикуется `MemoryPressure`, а health check
+//! сообщает о деградации. Тесты вызывают [`MemoryPressureMonitor::handle_pressure`]
+//! с подставными замерами, фоновый замер в тестах выключен.
+
+use crate::core::{AppEvent, EventBus};
+use async_trait::async_trait;
+use once_cell::sync::OnceCell;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Уровень давления на память
+#[derive(
+  Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Type,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum MemoryPressureLevel {
+  #[default]
+  Normal,
+  Soft,
+  Hard,
+}
+
+/// Приоритет кэша: низкоприоритетные сбрасываются первыми
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShrinkPriority {
+  Low,
+  Normal,
+  High,
+}
+
+/// Замер памяти процесса и системы в байтах
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryReading {
+  pub process_rss_bytes: u64,
+  pub available_bytes: u64,
+  pub total_bytes: u64,
+}
+
+impl MemoryReading {
+  /// Доступная память в процентах от общей
+  pub fn available_percent(&self) -> f64 {
+    if self.total_bytes == 0 {
+      return 100.0;
+    }
+    self.available_bytes as f64 / self.total_bytes as f64 * 100.0
+  }
+}
+
+/// Пороги и параметры реакции на нехватку памяти
+#[derive(Debug, Clone)]
+pub struct MemoryPressureConfig {
+  /// Интервал фонового замера
+  pub sample_interval: Duration,
+  /// Мягкое давление, когда доступно не больше этого процента памяти
+  pub soft_available_percent: f64,
+  /// Жесткое давление, когда доступно не больше этого процента памяти
+  pub hard_available_percent: f64,
+  /// Мягкое давление, когда RSS процесса достиг этого размера
+  pub soft_rss_bytes: Option<u64>,
+  /// Жесткое давление, когда RSS процесса достиг этого размера
+  pub hard_rss_bytes: Option<u64>,
+  /// Доля записей, которую сбрасывают низкоприоритетные кэши при мягком давлении
+  pub soft_shed_fraction: f64,
+  /// Доля записей, которая остается в кэшах после жесткого давления
+  pub hard_floor_fraction: f64,
+  /// Минимальный интервал между сбросами при сохраняющемся давлении
+  pub shrink_cooldown: Duration,
+  /// Запускать ли фоновый замер
+  pub sampler_enabled: bool,
+}
+
+impl Default for MemoryPressureConfig {
+  fn default() -> Self {
+    Self {
+      sample_interval: Duration::from_secs(5),
+      soft_available_percent: 15.0,
+      hard_available_percent: 7.0,
+      soft_rss_bytes: None,
+      hard_rss_bytes: None,
+      soft_shed_fraction: 0.25,
+      hard_floor_fraction: 0.1,
+      shrink_cooldown: Duration::from_secs(30),
+      sampler_enabled: !cfg!(test),
+    }
+  }
+}
+
+/// Запрос на сброс части кэша
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShrinkRequest {
+  pub level: MemoryPressureLevel,
+  /// Доля записей, которую нужно сбросить (0.0..=1.0)
+  pub shed_fraction: f64,
+}
+
+impl ShrinkRequest {
+  /// Сколько записей останется из `len` после сброса
+  pub fn retained_len(&self, len: usize) -> usize {
+    let shed = (len as f64 * self.shed_fraction.clamp(0.0, 1.0)).ceil() as usize;
+    len.saturating_sub(shed)
+  }
+}
+
+/// Кэш, умеющий освободить память по запросу менеджера
+#[async_trait]
+pub trait MemoryShrinker: Send + Sync {
+  /// Сбросить часть записей; возвращает примерно освобожденные байты
+  async fn shrink(&self, request: ShrinkRequest) -> u64;
+}
+
+struct RegisteredShrinker {
+  name: String,
+  priority: ShrinkPriority,
+  shrinker: Arc<dyn MemoryShrinker>,
+}
+
+/// Зарегистрированный кэш в статусе
+#[derive(Debug, Clone, Serialize)]
+pub struct ShrinkerInfo {
+  pub name: String,
+  pub priority: ShrinkPriority,
+}
+
+/// Состояние реакции на нехватку памяти для команды и health check
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MemoryPressureStatus {
+  pub level: MemoryPressureLevel,
+  pub sampler_running: bool,
+  pub last_reading: Option<MemoryReading>,
+  pub sampled_at: Option<chrono::DateTime<chrono::Utc>>,
+  pub soft_available_percent: f64,
+  pub hard_available_percent: f64,
+  pub soft_events: u64,
+  pub hard_events: u64,
+  pub last_freed_bytes: u64,
+  pub total_freed_bytes: u64,
+  pub shrinkers: Vec<ShrinkerInfo>,
+}
+
+/// Монитор давления на память и реестр сбрасываемых кэшей
+pub struct MemoryPressureMonitor {
+  config: MemoryPressureConfig,
+  shrinkers: RwLock<Vec<RegisteredShrinker>>,
+  status: RwLock<MemoryPressureStatus>,
+  last_shrink: RwLock<Option<Instant>>,
+  event_bus: OnceCell<Arc<EventBus>>,
+  sampler_running: AtomicBool,
+}
+
+impl MemoryPressureMonitor {
+  pub fn new(config: MemoryPressureConfig) -> Self {
+    let status = MemoryPressureStatus {
+      soft_available_percent: config.soft_available_percent,
+      hard_available_percent: config.hard_available_percent,
+      ..Default::default()
+    };
+    Self {
+      config,
+      shrinkers: RwLock::new(Vec::new()),
+      status: RwLock::new(status),
+      last_shrink: RwLock::new(None),
+      event_bus: OnceCell::new(),
+      sampler_running: AtomicBool::new(false),
+    }
+  }
+
+  /// Установить EventBus для событий о жестком давлении.
+  ///
+  /// EventBus задается один раз при запуске приложения.
+  pub fn set_event_bus(&self, event_bus: Arc<EventBus>) {
+    if self.event_bus.set(event_bus).is_err() {
+      log::warn!("EventBus монитора памяти уже установлен");
+    }
+  }
+
+  /// Зарегистрировать кэш. При одинаковом приоритете кэши сбрасываются
+  /// в порядке регистрации.
+  pub fn register_shrinker(
+    &self,
+    name: impl Into<String>,
+    priority: ShrinkPriority,
+    shrinker: Arc<dyn MemoryShrinker>,
+  ) {
+    let mut shrinkers = self.shrinkers.write();
+    shrinkers.push(RegisteredShrinker {
+      name: name.into(),
+      priority,
+      shrinker,
+    });
+    shrinkers.sort_by_key(|registered| registered.priority);
+  }
+
+  /// Определить уровень давления по замеру
+  pub fn classify(&self, reading: &MemoryReading) -> MemoryPressureLevel {
+    let available_percent = reading.available_percent();
+    let rss_reached =
+      |limit: Option<u64>| limit.is_some_and(|limit| reading.process_rss_bytes >= limit);
+
+    if available_percent <= self.config.hard_available_percent
+      || rss_reached(self.config.hard_rss_bytes)
+    {
+      MemoryPressureLevel::Hard
+    } else if available_percent <= self.config.soft_available_percent
+      || rss_reached(self.config.soft_rss_bytes)
+    {
+      MemoryPressureLevel::Soft
+    } else {
+      MemoryPressureLevel::Normal
+    }
+  }
+
+  /// Обработать замер: сбросить кэши согласно уровню давления и обновить статус
+  pub async fn handle_pressure(&self, reading: MemoryReading) -> MemoryPressureStatus {
+    let level = self.classify(&reading);
+    let previous_level = self.status.read().level;
+
+    // Повторный сброс при сохраняющемся давлении - не чаще раза в cooldown
+    let cooling_down = self
+      .last_shrink
+      .read()
+      .is_some_and(|at| at.elapsed() < self.config.shrink_cooldown);
+    let shrink = match level {
+      MemoryPressureLevel::Normal => None,
+      MemoryPressureLevel::Soft if cooling_down => None,
+      MemoryPressureLevel::Hard if cooling_down && previous_level == level => None,
+      MemoryPressureLevel::Soft => {
+        Some((self.config.soft_shed_fraction, Some(ShrinkPriority::Low)))
+      }
+      MemoryPressureLevel::Hard => {
+        Some((1.0 - self.config.hard_floor_fraction.clamp(0.0, 1.0), None))
+      }
+    };
+    let freed_bytes = match shrink {
+      Some((shed_fraction, max_priority)) => {
+        *self.last_shrink.write() = Some(Instant::now());
+        let request = ShrinkRequest {
+          level,
+          shed_fraction,
+        };
+        Some(self.shrink(request, max_priority).await)
+      }
+      None => None,
+    };
+
+    {
+      let mut status = self.status.write();
+      status.level = level;
+      status.last_reading = Some(reading);
+      status.sampled_at = Some(chrono::Utc::now());
+      if let Some(freed_bytes) = freed_bytes {
+        match level {
+          MemoryPressureLevel::Hard => status.hard_events += 1,
+          _ => status.soft_events += 1,
+        }
+        status.last_freed_bytes = freed_bytes;
+        status.total_freed_bytes += freed_bytes;
+      }
+    }
+
+    if level != previous_level {
+      log::info!(
+        "Memory pressure changed: {previous_level:?} -> {level:?} ({:.1}% available, {} bytes RSS)",
+        reading.available_percent(),
+        reading.process_rss_bytes
+      );
+    }
+    if level == MemoryPressureLevel::Hard && previous_level != MemoryPressureLevel::Hard {
+      self
+        .publish_hard_pressure(&reading, freed_bytes.unwrap_or_default())
+        .await;
+    }
+
+    self.status()
+  }
+
+  /// Текущий статус
+  pub fn status(&self) -> MemoryPressureStatus {
+    let mut status = self.status.read().clone();
+    status.sampler_running = self.sampler_running.load(Ordering::Relaxed);
+    status.shrinkers = self
+      .shrinkers
+      .read()
+      .iter()
+      .map(|registered| ShrinkerInfo {
+        name: registered.name.clone(),
+        priority: registered.priority,
+      })
+      .collect();
+    status
+  }
+
+  /// Фоновый замер памяти; при выключенном в настройках замере сразу завершается
+  pub async fn run_sampler(self: Arc<Self>) {
+    if !self.config.sampler_enabled {
+      return;
+    }
+    if self.sampler_running.swap(true, Ordering::SeqCst) {
+      log::warn!("Memory pressure sampler is already running");
+      return;
+    }
+
+    let mut system = sysinfo::System::new();
+    let pid = sysinfo::get_current_pid().ok();
+    let mut ticker = tokio::time::interval(self.config.sample_interval);
+    loop {
+      ticker.tick().await;
+      let reading = sample_memory(&mut system, pid);
+      self.handle_pressure(reading).await;
+    }
+  }
+
+  /// Сбросить кэши с приоритетом не выше `max_priority` (все, если `None`)
+  async fn shrink(&self, request: ShrinkRequest, max_priority: Option<ShrinkPriority>) -> u64 {
+    let targets: Vec<(String, Arc<dyn MemoryShrinker>)> = self
+      .shrinkers
+      .read()
+      .iter()
+      .filter(|registered| !matches!(max_priority, Some(max) if registered.priority > max))
+      .map(|registered| (registered.name.clone(), registered.shrinker.clone()))
+      .collect();
+
+    let mut freed_bytes = 0;
+    for (name, shrinker) in targets {
+      let freed = shrinker.shrink(request).await;
+      log::debug!(
+        "Cache {name} freed {freed} bytes under {:?} memory pressure",
+        request.level
+      );
+      freed_bytes += freed;
+    }
+    freed_bytes
+  }
+
+  async fn publish_hard_pressure(&self, reading: &MemoryReading, freed_bytes: u64) {
+    let Some(event_bus) = self.event_bus.get() else {
+      return;
+    };
+    let event = AppEvent::MemoryPressure {
+      level: MemoryPressureLevel::Hard,
+      process_rss_bytes: reading.process_rss_bytes,
+      available_bytes: reading.available_bytes,
+      freed_bytes,
+    };
+    if let Err(e) = event_bus.publish_app_event(event).await {
+      log::warn!("Failed to publish memory pressure event: {e}");
+    }
+  }
+}
+
+impl Default for MemoryPressureMonitor {
+  fn default() -> Self {
+    Self::new(MemoryPressureConfig::default())
+  }
+}
+
+/// Замерить RSS текущего процесса и доступную память системы
+fn sample_memory(system: &mut sysinfo::System, pid: Option<sysinfo::Pid>) -> MemoryReading {
+  system.refresh_memory();
+  let process_rss_bytes = pid
+    .and_then(|pid| {
+      system.refresh_processes_specifics(
+        sysinfo::ProcessesToUpdate::Some(&[pid]),
+        sysinfo::ProcessRefreshKind::new().with_memory(),
+      );
+      system.process(pid).map(|process| process.memory())
+    })
+    .unwrap_or(0);
+
+  MemoryReading {
+    process_rss_bytes,
+    available_bytes: system.available_memory(),
+    total_bytes: system.total_memory(),
+  }
+}
+
+/// Получить состояние реакции на нехватку памяти
+#[tauri::command]
+pub fn get_memory_pressure_status(
+  manager: tauri::State<'_, Arc<super::MemoryManager>>,
+) -> MemoryPressureStatus {
+  manager.pressure().status()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::sync::Mutex;
+
+  type ShrinkLog = Arc<Mutex<Vec<(&'static str, ShrinkRequest)>>>;
+
+  struct RecordingShrinker {
+    name: &'static str,
+    freed_bytes: u64,
+    log: ShrinkLog,
+  }
+
+  #[async_trait]
+  impl MemoryShrinker for RecordingShrinker {
+    async fn shrink(&self, request: ShrinkRequest) -> u64 {
+      self.log.lock().unwrap().push((self.name, request));
+      self.freed_bytes
+    }
+  }
+
+  fn reading(available_percent: u64, process_rss_bytes: u64) -> MemoryReading {
+    MemoryReading {
+      process_rss_bytes,
+      available_bytes: available_percent * 1024,
+      total_bytes: 100 * 1024,
+    }
+  }
+
+  /// Монитор с кэшами, зарегистрированными не по порядку приоритетов
+  fn monitor_with_shrinkers(config: MemoryPressureConfig) -> (MemoryPressureMonitor, ShrinkLog) {
+    let monitor = MemoryPressureMonitor::new(config);
+    let log: ShrinkLog = Arc::default();
+    for (name, priority) in [
+      ("render", ShrinkPriority::High),
+      ("preview", ShrinkPriority::Low),
+      ("zerocopy", ShrinkPriority::Normal),
+      ("frames", ShrinkPriority::Low),
+    ] {
+      monitor.register_shrinker(
+        name,
+        priority,
+        Arc::new(RecordingShrinker {
+          name,
+          freed_bytes: 100,
+          log: log.clone(),
+        }),
+      );
+    }
+    (monitor, log)
+  }
+
+  fn called(log: &ShrinkLog) -> Vec<&'static str> {
+    log.lock().unwrap().iter().map(|(name, _)| *name).collect()
+  }
+
+  #[test]
+  fn test_classify_by_available_memory_and_rss() {
+    let monitor = MemoryPressureMonitor::new(MemoryPressureConfig {
+      soft_rss_bytes: Some(1000),
+      hard_rss_bytes: Some(2000),
+      ..Default::default()
+    });
+
+    assert_eq!(
+      monitor.classify(&reading(50, 0)),
+      MemoryPressureLevel::Normal
+    );
+    assert_eq!(monitor.classify(&reading(15, 0)), MemoryPressureLevel::Soft);
+    assert_eq!(monitor.classify(&reading(7, 0)), MemoryPressureLevel::Hard);
+    assert_eq!(
+      monitor.classify(&reading(50, 1000)),
+      MemoryPressureLevel::Soft
+    );
+    assert_eq!(
+      monitor.classify(&reading(50, 2500)),
+      MemoryPressureLevel::Hard
+    );
+  }
+
+  #[tokio::test]
+  async fn test_soft_pressure_sheds_only_low_priority_caches() {
+    let (monitor, log) = monitor_with_shrinkers(MemoryPressureConfig::default());
+
+    let status = monitor.handle_pressure(reading(12, 0)).await;
+
+    assert_eq!(called(&log), vec!["preview", "frames"]);
+    assert!(log.lock().unwrap().iter().all(
+      |(_, request)| request.shed_fraction == 0.25 && request.level == MemoryPressureLevel::Soft
+    ));
+    assert_eq!(status.level, MemoryPressureLevel::Soft);
+    assert_eq!(status.soft_events, 1);
+    assert_eq!(status.last_freed_bytes, 200);
+  }
+
+  #[tokio::test]
+  async fn test_soft_pressure_respects_cooldown() {
+    let (monitor, log) = monitor_with_shrinkers(MemoryPressureConfig::default());
+
+    monitor.handle_pressure(reading(12, 0)).await;
+    let status = monitor.handle_pressure(reading(12, 0)).await;
+
+    assert_eq!(called(&log).len(), 2);
+    assert_eq!(status.soft_events, 1);
+    assert_eq!(status.total_freed_bytes, 200);
+  }
+
+  #[tokio::test]
+  async fn test_hard_pressure_shrinks_all_caches_to_floor_in_priority_order() {
+    let (monitor, log) = monitor_with_shrinkers(MemoryPressureConfig::default());
+
+    let status = monitor.handle_pressure(reading(5, 0)).await;
+
+    assert_eq!(
+      called(&log),
+      vec!["preview", "frames", "zerocopy", "render"]
+    );
+    let request = log.lock().unwrap()[0].1;
+    assert_eq!(request.level, MemoryPressureLevel::Hard);
+    assert!((request.shed_fraction - 0.9).abs() < f64::EPSILON);
+    assert_eq!(request.retained_len(100), 10);
+    assert_eq!(status.level, MemoryPressureLevel::Hard);
+    assert_eq!(status.hard_events, 1);
+    assert_eq!(status.last_freed_bytes, 400);
+    assert_eq!(status.shrinkers.len(), 4);
+
+    // После освобождения памяти кэши больше не трогаются
+    let status = monitor.handle_pressure(reading(60, 0)).await;
+    assert_eq!(status.level, MemoryPressureLevel::Normal);
+    assert_eq!(called(&log).len(), 4);
+    assert_eq!(status.total_freed_bytes, 400);
+  }
+
+  #[tokio::test]
+  async fn test_sampler_disabled_in_tests() {
+    let monitor = Arc::new(MemoryPressureMonitor::default());
+
+    tokio::time::timeout(Duration::from_secs(1), monitor.clone().run_sampler())
+      .await
+      .expect("disabled sampler returns immediately");
+    assert!(!monitor.status().sampler_running);
+  }
+}
@@ -2,10 +2,14 @@
 
 pub mod cache;
 pub mod memory;
+pub mod memory_pressure;
 pub mod runtime;
 pub mod zerocopy;
 
 pub use cache::{CacheConfig, CacheManager};
 pub use memory::MemoryManager;
+pub use memory_pressure::{
+  MemoryPressureConfig, MemoryPressureMonitor, MemoryShrinker, ShrinkPriority, ShrinkRequest,
+};
 pub use runtime::{RuntimeConfig, RuntimeManager};
 pub use zerocopy::{AudioZeroCopy, DataType, VideoZeroCopy, ZeroCopyBuffer, ZeroCopyManager};
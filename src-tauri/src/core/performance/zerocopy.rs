@@ -3,6 +3,7 @@
 //! Этот модуль предоставляет структуры и методы для работы с данными
 //! без лишних копирований в памяти, что критически важно для видеообработки.
 
+use super::memory_pressure::{MemoryShrinker, ShrinkRequest};
 use crate::video_compiler::error::{Result, VideoCompilerError};
 use std::alloc::{self, Layout};
 use std::collections::HashMap;
@@ -508,6 +509,22 @@ impl ZeroCopyManager {
   }
 }
 
+#[async_trait::async_trait]
+impl MemoryShrinker for ZeroCopyManager {
+  /// Освободить долю свободных буферов каждого пула; выданные буферы не трогаются
+  async fn shrink(&self, request: ShrinkRequest) -> u64 {
+    let mut pools = self.pools.write().await;
+    let mut freed_bytes = 0;
+    for ((size, _), pool) in pools.iter_mut() {
+      let retained = request.retained_len(pool.len());
+      freed_bytes += ((pool.len() - retained) * *size) as u64;
+      pool.truncate(retained);
+    }
+    pools.retain(|_, pool| !pool.is_empty());
+    freed_bytes
+  }
+}
+
 impl Default for ZeroCopyManager {
   fn default() -> Self {
     Self::new()
@@ -1068,6 +1085,31 @@ mod tests {
     assert!(pool_info.is_empty());
   }
 
+  #[tokio::test]
+  async fn test_manager_shrink_pools() {
+    let manager = ZeroCopyManager::new();
+
+    let buffers = vec![
+      manager.get_buffer(1024, DataType::Raw).await.unwrap(),
+      manager.get_buffer(1024, DataType::Raw).await.unwrap(),
+      manager.get_buffer(1024, DataType::Raw).await.unwrap(),
+      manager.get_buffer(1024, DataType::Raw).await.unwrap(),
+    ];
+    for buf in buffers {
+      manager.return_buffer(buf).await;
+    }
+
+    let freed = manager
+      .shrink(ShrinkRequest {
+        level: crate::core::performance::memory_pressure::MemoryPressureLevel::Soft,
+        shed_fraction: 0.5,
+      })
+      .await;
+
+    assert_eq!(freed, 2048);
+    assert_eq!(manager.get_pool_info().await[&(1024, DataType::Raw)], 2);
+  }
+
   #[tokio::test]
   async fn test_manager_max_pool_size() {
     let manager = ZeroCopyManager::new();
@@ -4,7 +4,10 @@ use crate::{
   core::{
     di::ServiceContainer,
     events::{AppEvent, EventBus},
-    plugins::permissions::{PluginPermissions, SecurityLevel},
+    plugins::{
+      permissions::{PluginPermissions, SecurityLevel},
+      services::{NetworkGateway, NetworkOutcome, NetworkRequest},
+    },
   },
   video_compiler::error::{Result, VideoCompilerError},
 };
@@ -194,6 +197,11 @@ pub trait PluginApi: Send + Sync {
   /// Записать файл (с проверкой разрешений)
   async fn write_file(&self, path: &Path, data: &[u8]) -> Result<()>;
 
+  // Сеть
+
+  /// Выполнить HTTP запрос через сетевой шлюз (повторы, офлайн-очередь)
+  async fn send_network_request(&self, request: NetworkRequest) -> Result<NetworkOutcome>;
+
   // Системная информация
 
   /// Получить информацию о системе
@@ -310,6 +318,10 @@ pub struct PluginApiImpl {
   media_bridge: super::services::MediaBridge,
   timeline_bridge: super::services::TimelineBridge,
   ui_bridge: super::services::UIBridge,
+  // Sandbox для учета памяти, выделяемой через API
+  sandbox: Option<Arc<super::sandbox::PluginSandbox>>,
+  // Общий шлюз сетевых запросов
+  network_gateway: Option<Arc<NetworkGateway>>,
 }
 
 impl PluginApiImpl {
@@ -332,7 +344,8 @@ impl PluginApiImpl {
       service_container.clone(),
       permissions.clone(),
       plugin_id.clone(),
-    );
+    )
+    .with_event_bus(event_bus.clone());
 
     let ui_bridge = super::services::UIBridge::new(
       service_container.clone(),
@@ -351,9 +364,30 @@ impl PluginApiImpl {
       media_bridge,
       timeline_bridge,
       ui_bridge,
+      sandbox: None,
+      network_gateway: None,
     }
   }
 
+  /// Учитывать выделения памяти в квоте sandbox плагина
+  pub fn with_sandbox(mut self, sandbox: Arc<super::sandbox::PluginSandbox>) -> Self {
+    self.media_bridge = self.media_bridge.with_sandbox(sandbox.clone());
+    self.timeline_bridge = self.timeline_bridge.with_sandbox(sandbox.clone());
+    self.sandbox = Some(sandbox);
+    self
+  }
+
+  /// Выполнять сетевые запросы через общий шлюз
+  pub fn with_network_gateway(mut self, gateway: Arc<NetworkGateway>) -> Self {
+    self.network_gateway = Some(gateway);
+    self
+  }
+
+  /// Мост timeline для транзакционных изменений
+  pub fn timeline_bridge(&self) -> &super::services::TimelineBridge {
+    &self.timeline_bridge
+  }
+
   /// Проверить разрешение
   fn check_permission(&self, required: &str) -> Result<()> {
     let security_level = self.permissions.get_security_level();
@@ -422,6 +456,16 @@ impl PluginApiImpl {
           )))
         }
       }
+      "network" => {
+        if self.permissions.network.allow_all || !self.permissions.network.allowed_hosts.is_empty()
+        {
+          Ok(())
+        } else {
+          Err(VideoCompilerError::SecurityError(format!(
+            "Permission denied: {required}"
+          )))
+        }
+      }
       "system_info" => {
         if self.permissions.system_info {
           Ok(())
@@ -837,6 +881,17 @@ impl PluginApi for PluginApiImpl {
     self.check_permission("file_read")?;
     self.check_read_path(path)?;
 
+    // Резервируем память под содержимое файла в квоте плагина
+    let _reservation = match &self.sandbox {
+      Some(sandbox) => {
+        let metadata = tokio::fs::metadata(path)
+          .await
+          .map_err(|e| VideoCompilerError::IoError(format!("Failed to read file: {e}")))?;
+        Some(sandbox.reserve_memory(metadata.len())?)
+      }
+      None => None,
+    };
+
     // Читаем файл
     tokio::fs::read(path)
       .await
@@ -861,6 +916,23 @@ impl PluginApi for PluginApiImpl {
       .map_err(|e| VideoCompilerError::IoError(format!("Failed to write file: {e}")))
   }
 
+  async fn send_network_request(&self, request: NetworkRequest) -> Result<NetworkOutcome> {
+    self.check_permission("network")?;
+
+    let gateway = self.network_gateway.as_ref().ok_or_else(|| {
+      VideoCompilerError::ServiceNotFound("Network gateway is not available".to_string())
+    })?;
+
+    gateway
+      .send(
+        &self.plugin_id,
+        &self.permissions.network,
+        self.sandbox.as_deref(),
+        request,
+      )
+      .await
+  }
+
   async fn get_system_info(&self) -> Result<SystemInfo> {
     // TODO: Реализовать через SystemInfoService
     Ok(SystemInfo {
@@ -1224,6 +1296,7 @@ mod tests {
       ui_access: false,
       system_info: false,
       process_spawn: false,
+      timeline_write: false,
     });
 
     let service_container = Arc::new(ServiceContainer::new());
@@ -1247,6 +1320,9 @@ mod tests {
     // Test ui_access permission (should fail as ui_access is false)
     assert!(api.check_permission("ui_access").is_err());
 
+    // Test network permission (should fail without allowed hosts)
+    assert!(api.check_permission("network").is_err());
+
     // Test unknown permission
     assert!(api.check_permission("unknown_perm").is_err());
   }
@@ -1269,6 +1345,7 @@ mod tests {
       ui_access: false,
       system_info: false,
       process_spawn: false,
+      timeline_write: false,
     });
 
     let service_container = Arc::new(ServiceContainer::new());
@@ -1,11 +1,14 @@
 //! Tauri команды для управления плагинами
 
 use crate::core::plugins::{
+  loader::{PluginListing, PluginScanReport},
   manager::PluginManager,
   permissions::PluginPermissions,
-  plugin::{PluginCommand, PluginMetadata, PluginResponse},
+  plugin::{PluginCommand, PluginResponse, ResolvedDependency},
+  services::NetworkGatewayStats,
 };
 use serde_json::Value;
+use std::path::Path;
 use tauri::State;
 use uuid::Uuid;
 
@@ -24,39 +27,84 @@ pub async fn load_plugin(
     .map_err(|e| e.to_string())
 }
 
-/// Выгрузить плагин
+/// Выгрузить плагин (с `force` вместе с зависящими от него плагинами)
 #[tauri::command]
 pub async fn unload_plugin(
   plugin_id: String,
+  force: Option<bool>,
   plugin_manager: State<'_, PluginManager>,
 ) -> Result<(), String> {
-  plugin_manager
-    .unload_plugin(&plugin_id)
-    .await
-    .map_err(|e| e.to_string())
+  if force.unwrap_or(false) {
+    plugin_manager
+      .force_unload_plugin(&plugin_id)
+      .await
+      .map(|_| ())
+      .map_err(|e| e.to_string())
+  } else {
+    plugin_manager
+      .unload_plugin(&plugin_id)
+      .await
+      .map_err(|e| e.to_string())
+  }
 }
 
 /// Получить список загруженных плагинов
 #[tauri::command]
 pub async fn list_loaded_plugins(
   plugin_manager: State<'_, PluginManager>,
-) -> Result<Vec<(String, String)>, String> {
+) -> Result<Vec<(String, String, Vec<ResolvedDependency>)>, String> {
   let plugins = plugin_manager.list_loaded_plugins().await;
   Ok(
     plugins
       .into_iter()
-      .map(|(id, state)| (id, format!("{state:?}")))
+      .map(|(id, state, dependencies)| (id, format!("{state:?}"), dependencies))
       .collect(),
   )
 }
 
-/// Получить список всех доступных плагинов
+/// Получить список всех доступных плагинов со статусом проверки подписи
+///
+/// Включает плагины из каталога, которым отказано в регистрации.
 #[tauri::command]
 pub async fn list_available_plugins(
   plugin_manager: State<'_, PluginManager>,
-) -> Result<Vec<PluginMetadata>, String> {
-  let registry = plugin_manager.loader().registry();
-  Ok(registry.list_plugins().await)
+) -> Result<Vec<PluginListing>, String> {
+  Ok(plugin_manager.loader().list_plugins_with_status().await)
+}
+
+/// Установить плагин из директории в каталог плагинов
+#[tauri::command]
+pub async fn install_plugin_from_path(
+  path: String,
+  plugin_manager: State<'_, PluginManager>,
+) -> Result<PluginListing, String> {
+  plugin_manager
+    .install_plugin_from_path(Path::new(&path))
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Удалить плагин из каталога плагинов
+#[tauri::command]
+pub async fn uninstall_plugin(
+  plugin_id: String,
+  plugin_manager: State<'_, PluginManager>,
+) -> Result<(), String> {
+  plugin_manager
+    .uninstall_plugin(&plugin_id)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Пересканировать каталог плагинов
+#[tauri::command]
+pub async fn rescan_plugins(
+  plugin_manager: State<'_, PluginManager>,
+) -> Result<PluginScanReport, String> {
+  plugin_manager
+    .rescan_plugins()
+    .await
+    .map_err(|e| e.to_string())
 }
 
 /// Отправить команду плагину
@@ -132,6 +180,14 @@ pub async fn get_plugins_sandbox_stats(
     .collect()
 }
 
+/// Получить статистику сетевого шлюза плагинов
+#[tauri::command]
+pub async fn get_network_gateway_stats(
+  plugin_manager: State<'_, PluginManager>,
+) -> Result<NetworkGatewayStats, String> {
+  Ok(plugin_manager.network_gateway().stats().await)
+}
+
 /// Получить плагины, нарушившие лимиты ресурсов
 #[tauri::command]
 pub async fn get_violating_plugins(
@@ -257,6 +313,7 @@ mod tests {
       ui_access: false,
       system_info: false,
       process_spawn: false,
+      timeline_write: false,
     };
 
     let result = manager.load_plugin("blur-effect", permissions).await;
@@ -541,6 +598,7 @@ mod tests {
         ui_access: false,
         system_info: false,
         process_spawn: false,
+        timeline_write: false,
       },
       PluginPermissions {
         // Only file system access
@@ -549,6 +607,7 @@ mod tests {
         ui_access: false,
         system_info: false,
         process_spawn: false,
+        timeline_write: false,
       },
       PluginPermissions {
         // All enabled
@@ -561,6 +620,7 @@ mod tests {
         ui_access: true,
         system_info: true,
         process_spawn: true,
+        timeline_write: true,
       },
     ];
 
@@ -2,6 +2,8 @@
 
 use super::permissions::PluginPermissions;
 use super::plugin::Version;
+use super::sandbox::{PluginCancellation, PluginSandbox};
+use super::services::NetworkGateway;
 use crate::core::{EventBus, ServiceContainer};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
@@ -35,6 +37,15 @@ pub struct PluginContext {
 
   /// Tauri AppHandle для интеграции с frontend
   pub app_handle: Option<tauri::AppHandle>,
+
+  /// Токен кооперативной отмены выполняющейся команды
+  pub cancellation: PluginCancellation,
+
+  /// Sandbox плагина для учета ресурсов
+  pub sandbox: Option<Arc<PluginSandbox>>,
+
+  /// Общий шлюз сетевых запросов плагинов
+  pub network_gateway: Option<Arc<NetworkGateway>>,
 }
 
 impl PluginContext {
@@ -47,10 +58,7 @@ impl PluginContext {
     permissions: PluginPermissions,
     app_handle: Option<tauri::AppHandle>,
   ) -> Self {
-    let base_dir = dirs::data_dir()
-      .unwrap_or_else(|| PathBuf::from("."))
-      .join("timeline-studio")
-      .join("plugins");
+    let base_dir = super::manifest::plugins_root_dir();
 
     let plugin_dir = base_dir.join(plugin_id);
     let config_dir = plugin_dir.join("config");
@@ -71,9 +79,30 @@ impl PluginContext {
       permissions,
       instance_id,
       app_handle,
+      cancellation: PluginCancellation::new(),
+      sandbox: None,
+      network_gateway: None,
     }
   }
 
+  /// Привязать контекст к sandbox плагина
+  pub fn with_sandbox(mut self, sandbox: Arc<PluginSandbox>) -> Self {
+    self.cancellation = sandbox.cancellation();
+    self.sandbox = Some(sandbox);
+    self
+  }
+
+  /// Выполнять сетевые запросы плагина через общий шлюз
+  pub fn with_network_gateway(mut self, gateway: Arc<NetworkGateway>) -> Self {
+    self.network_gateway = Some(gateway);
+    self
+  }
+
+  /// Отменена ли текущая команда (тайм-аут sandbox)
+  pub fn is_cancelled(&self) -> bool {
+    self.cancellation.is_cancelled()
+  }
+
   /// Проверить имеет ли плагин разрешение на чтение пути
   pub fn can_read_path(&self, path: &Path) -> bool {
     // Плагин всегда может читать свои директории
@@ -125,14 +154,24 @@ impl PluginContext {
 
   /// Создать API для плагина
   pub fn create_plugin_api(&self, plugin_id: String) -> super::api::PluginApiImpl {
-    super::api::PluginApiImpl::new(
+    let api = super::api::PluginApiImpl::new(
       plugin_id,
       std::sync::Arc::new(self.permissions.clone()),
       self.service_container.clone(),
       self.app_handle.clone(),
       self.plugin_dir.clone(),
       self.event_bus.clone(),
-    )
+    );
+
+    let api = match &self.network_gateway {
+      Some(gateway) => api.with_network_gateway(gateway.clone()),
+      None => api,
+    };
+
+    match &self.sandbox {
+      Some(sandbox) => api.with_sandbox(sandbox.clone()),
+      None => api,
+    }
   }
 }
 
@@ -149,6 +188,7 @@ mod tests {
       ui_access: false,
       system_info: false,
       process_spawn: false,
+      timeline_write: false,
     };
 
     let context = PluginContext::new(
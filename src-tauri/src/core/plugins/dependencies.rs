@@ -0,0 +1,191 @@
+//! Разрешение зависимостей между плагинами
+
+use super::plugin::PluginMetadata;
+use crate::video_compiler::error::{Result, VideoCompilerError};
+use std::collections::{HashMap, HashSet};
+
+/// Граф зависимостей зарегистрированных плагинов
+pub struct DependencyResolver<'a> {
+  plugins: HashMap<&'a str, &'a PluginMetadata>,
+}
+
+impl<'a> DependencyResolver<'a> {
+  /// Построить граф по метаданным плагинов
+  pub fn new(plugins: &'a [PluginMetadata]) -> Self {
+    Self {
+      plugins: plugins.iter().map(|m| (m.id.as_str(), m)).collect(),
+    }
+  }
+
+  /// Порядок загрузки плагина вместе с транзитивными зависимостями (зависимости первыми)
+  pub fn resolve(&self, plugin_id: &str) -> Result<Vec<String>> {
+    if !self.plugins.contains_key(plugin_id) {
+      return Err(VideoCompilerError::InvalidParameter(format!(
+        "Plugin '{plugin_id}' not found"
+      )));
+    }
+
+    let mut order = Vec::new();
+    self.visit(plugin_id, &mut Vec::new(), &mut HashSet::new(), &mut order)?;
+    Ok(order)
+  }
+
+  /// Порядок загрузки всех плагинов графа
+  pub fn resolve_all(&self) -> Result<Vec<String>> {
+    let mut ids: Vec<&str> = self.plugins.keys().copied().collect();
+    ids.sort_unstable();
+
+    let mut order = Vec::new();
+    let mut visited = HashSet::new();
+    for id in ids {
+      self.visit(id, &mut Vec::new(), &mut visited, &mut order)?;
+    }
+    Ok(order)
+  }
+
+  /// Обход в глубину с проверкой версий и поиском циклов
+  fn visit(
+    &self,
+    plugin_id: &str,
+    stack: &mut Vec<String>,
+    visited: &mut HashSet<String>,
+    order: &mut Vec<String>,
+  ) -> Result<()> {
+    if visited.contains(plugin_id) {
+      return Ok(());
+    }
+
+    if let Some(position) = stack.iter().position(|id| id == plugin_id) {
+      let mut cycle = stack[position..].to_vec();
+      cycle.push(plugin_id.to_string());
+      return Err(VideoCompilerError::PluginDependencyCycle { cycle });
+    }
+
+    let metadata = self.plugins[plugin_id];
+    stack.push(plugin_id.to_string());
+
+    for dependency in &metadata.dependencies {
+      let dep_metadata = self
+        .plugins
+        .get(dependency.plugin_id.as_str())
+        .ok_or_else(|| VideoCompilerError::PluginDependencyError {
+          plugin_id: plugin_id.to_string(),
+          dependency: dependency.plugin_id.clone(),
+          reason: "plugin is not registered".to_string(),
+        })?;
+
+      if !dependency.is_satisfied_by(&dep_metadata.version) {
+        return Err(VideoCompilerError::PluginDependencyError {
+          plugin_id: plugin_id.to_string(),
+          dependency: dependency.plugin_id.clone(),
+          reason: format!(
+            "version {} does not satisfy {}",
+            dep_metadata.version,
+            dependency.requirement()
+          ),
+        });
+      }
+
+      self.visit(&dependency.plugin_id, stack, visited, order)?;
+    }
+
+    stack.pop();
+    visited.insert(plugin_id.to_string());
+    order.push(plugin_id.to_string());
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::core::plugins::plugin::{PluginDependency, PluginType, Version};
+
+  fn metadata(id: &str, version: Version, dependencies: &[(&str, &str)]) -> PluginMetadata {
+    PluginMetadata {
+      id: id.to_string(),
+      name: id.to_string(),
+      version,
+      author: "Test".to_string(),
+      description: "Test plugin".to_string(),
+      plugin_type: PluginType::Universal,
+      homepage: None,
+      license: None,
+      dependencies: dependencies
+        .iter()
+        .map(|(dep, range)| PluginDependency {
+          plugin_id: dep.to_string(),
+          min_version: None,
+          max_version: None,
+          version_req: Some(range.to_string()),
+        })
+        .collect(),
+      min_app_version: None,
+    }
+  }
+
+  #[test]
+  fn test_resolve_orders_dependencies_first() {
+    let plugins = vec![
+      metadata(
+        "app",
+        Version::new(1, 0, 0),
+        &[("ui", "^1.0.0"), ("core", "*")],
+      ),
+      metadata("ui", Version::new(1, 4, 0), &[("core", ">=2.0.0")]),
+      metadata("core", Version::new(2, 1, 0), &[]),
+      metadata("standalone", Version::new(1, 0, 0), &[]),
+    ];
+    let resolver = DependencyResolver::new(&plugins);
+
+    assert_eq!(resolver.resolve("app").unwrap(), vec!["core", "ui", "app"]);
+
+    let all = resolver.resolve_all().unwrap();
+    assert_eq!(all.len(), 4);
+    let position = |id: &str| all.iter().position(|p| p == id).unwrap();
+    assert!(position("core") < position("ui"));
+    assert!(position("ui") < position("app"));
+  }
+
+  #[test]
+  fn test_missing_dependency() {
+    let plugins = vec![metadata("app", Version::new(1, 0, 0), &[("core", "*")])];
+    let result = DependencyResolver::new(&plugins).resolve("app");
+
+    assert!(matches!(
+      result,
+      Err(VideoCompilerError::PluginDependencyError { ref dependency, .. }) if dependency == "core"
+    ));
+  }
+
+  #[test]
+  fn test_version_mismatch() {
+    let plugins = vec![
+      metadata("app", Version::new(1, 0, 0), &[("core", "^1.2.0")]),
+      metadata("core", Version::new(2, 0, 0), &[]),
+    ];
+    let result = DependencyResolver::new(&plugins).resolve("app");
+
+    assert!(matches!(
+      result,
+      Err(VideoCompilerError::PluginDependencyError { ref reason, .. }) if reason.contains("^1.2.0")
+    ));
+  }
+
+  #[test]
+  fn test_cycle_is_named() {
+    let plugins = vec![
+      metadata("a", Version::new(1, 0, 0), &[("b", "*")]),
+      metadata("b", Version::new(1, 0, 0), &[("c", "*")]),
+      metadata("c", Version::new(1, 0, 0), &[("a", "*")]),
+    ];
+    let result = DependencyResolver::new(&plugins).resolve("a");
+
+    match result {
+      Err(VideoCompilerError::PluginDependencyCycle { cycle }) => {
+        assert_eq!(cycle, vec!["a", "b", "c", "a"]);
+      }
+      other => panic!("Expected dependency cycle, got {other:?}"),
+    }
+  }
+}
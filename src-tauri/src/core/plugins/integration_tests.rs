@@ -12,8 +12,14 @@ use crate::core::{
   plugins::{
     api::{Clip, DialogType, Effect, MenuItem, PluginApi, PluginApiImpl, PluginDialog},
     permissions::SecurityLevel,
+    sandbox::PluginSandbox,
+    services::{ClipSpec, TimelineMutation},
   },
 };
+use crate::video_compiler::{
+  error::VideoCompilerError,
+  schema::{ProjectSchema, TrackType},
+};
 use serde_json::json;
 use std::{path::PathBuf, sync::Arc};
 use tempfile::tempdir;
@@ -389,3 +395,101 @@ async fn test_thumbnail_generation_validity() {
     println!("Generated thumbnail: {thumbnail_path:?}");
   }
 }
+
+/// Фейковый плагин: нарезка интервью на новом треке одной транзакцией
+async fn run_interview_cut_plugin(
+  plugin_api: &PluginApiImpl,
+  project: ProjectSchema,
+  track_name: &str,
+) -> crate::video_compiler::error::Result<Vec<TimelineMutation>> {
+  let mut tx = plugin_api
+    .timeline_bridge()
+    .begin_timeline_transaction(project)?;
+
+  let track_id = tx.add_track(TrackType::Video, track_name)?;
+  let clip_id = tx.add_clip(
+    &track_id,
+    ClipSpec {
+      source_path: "/media/interview.mp4".to_string(),
+      start_time: 0.0,
+      end_time: 30.0,
+      source_start: 5.0,
+    },
+  )?;
+  let middle_id = tx.split_clip(&clip_id, 10.0)?;
+  let tail_id = tx.split_clip(&middle_id, 20.0)?;
+  tx.remove_clip(&middle_id)?;
+
+  let working = tx.project();
+  assert!(working.find_clip_by_id(&clip_id).is_some());
+  assert!(working.find_clip_by_id(&middle_id).is_none());
+  assert_eq!(
+    working.find_clip_by_id(&tail_id).unwrap().source_start,
+    25.0
+  );
+
+  tx.commit().await
+}
+
+#[tokio::test]
+async fn test_timeline_transaction_multi_operation_commit() {
+  let (plugin_api, _container, _event_bus, _storage_path) = create_test_environment().await;
+  let project = ProjectSchema::new("Interview".to_string());
+
+  let mutations = run_interview_cut_plugin(&plugin_api, project, "Cuts")
+    .await
+    .unwrap();
+
+  assert_eq!(mutations.len(), 5);
+  assert!(matches!(mutations[0], TimelineMutation::AddTrack { .. }));
+  assert!(matches!(mutations[4], TimelineMutation::RemoveClip { .. }));
+}
+
+#[tokio::test]
+async fn test_timeline_transaction_validation_failure_rolls_back() {
+  let (plugin_api, _container, _event_bus, _storage_path) = create_test_environment().await;
+  let project = ProjectSchema::new("Interview".to_string());
+
+  // Пустое имя трека не проходит валидацию проекта при commit
+  let result = run_interview_cut_plugin(&plugin_api, project.clone(), "").await;
+  assert!(matches!(
+    result,
+    Err(VideoCompilerError::ValidationError(_))
+  ));
+
+  // Исходный снимок не изменился, новая транзакция начинается с пустого timeline
+  let tx = plugin_api
+    .timeline_bridge()
+    .begin_timeline_transaction(project)
+    .unwrap();
+  assert!(tx.project().tracks.is_empty());
+  tx.rollback();
+}
+
+#[tokio::test]
+async fn test_timeline_transaction_requires_timeline_write() {
+  let service_container = Arc::new(ServiceContainer::new());
+  let event_bus = Arc::new(EventBus::new());
+  let permissions = SecurityLevel::Standard.permissions();
+  assert!(!permissions.timeline_write);
+
+  let temp_dir = tempdir().unwrap();
+  let sandbox = Arc::new(PluginSandbox::new(
+    "restricted_plugin".to_string(),
+    &permissions,
+  ));
+  let restricted_api = PluginApiImpl::new(
+    "restricted_plugin".to_string(),
+    Arc::new(permissions),
+    service_container,
+    None,
+    temp_dir.path().join("plugin_storage"),
+    event_bus,
+  )
+  .with_sandbox(sandbox);
+
+  let result = restricted_api
+    .timeline_bridge()
+    .begin_timeline_transaction(ProjectSchema::new("Test".to_string()));
+  assert!(matches!(result, Err(VideoCompilerError::SecurityError(_))));
+}
@@ -1,24 +1,96 @@
 //! Загрузчик плагинов
 
+use super::manifest::{
+  developer_mode_from_env, plugins_root_dir, read_plugin_dir, DiscoveredPlugin, PluginEntry,
+  PluginVerificationStatus, SignatureVerifier, MANIFEST_FILE_NAME,
+};
+use super::permissions::PluginPermissions;
 use super::plugin::{Plugin, PluginDependency, PluginMetadata, PluginValidationResult, Version};
 use crate::video_compiler::error::{Result, VideoCompilerError};
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
 /// Тип функции-фабрики для создания плагинов
 pub type PluginFactory = Box<dyn Fn() -> Box<dyn Plugin> + Send + Sync>;
 
+/// Поиск встроенной фабрики по имени, указанному в манифесте
+pub type PluginFactoryResolver = Arc<dyn Fn(&str) -> Option<PluginFactory> + Send + Sync>;
+
 /// Регистрация плагина
 pub struct PluginRegistration {
   pub metadata: PluginMetadata,
   pub factory: PluginFactory,
 }
 
+/// Откуда взят плагин
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PluginSource {
+  /// Скомпилирован в приложение
+  Builtin,
+  /// Установлен в каталог плагинов
+  Directory { path: PathBuf },
+}
+
+/// Происхождение зарегистрированного плагина
+#[derive(Debug, Clone)]
+pub struct PluginOrigin {
+  pub source: PluginSource,
+  pub verification: PluginVerificationStatus,
+  /// Максимальные разрешения из манифеста (для встроенных плагинов не ограничены)
+  pub max_permissions: Option<PluginPermissions>,
+}
+
+impl PluginOrigin {
+  /// Происхождение встроенного плагина
+  pub fn builtin() -> Self {
+    Self {
+      source: PluginSource::Builtin,
+      verification: PluginVerificationStatus::Builtin,
+      max_permissions: None,
+    }
+  }
+}
+
+/// Плагин в списке доступных вместе с результатом проверки
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginListing {
+  #[serde(flatten)]
+  pub metadata: PluginMetadata,
+  pub source: PluginSource,
+  pub verification: PluginVerificationStatus,
+  pub max_permissions: Option<PluginPermissions>,
+  /// Зарегистрирован ли плагин и может ли быть загружен
+  pub registered: bool,
+  /// Причина отказа в регистрации
+  pub rejection_reason: Option<String>,
+}
+
+/// Результат сканирования каталога плагинов
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PluginScanReport {
+  /// Зарегистрированные плагины
+  pub registered: Vec<String>,
+  /// Найденные, но не зарегистрированные плагины
+  pub rejected: Vec<PluginListing>,
+  /// Директории, манифест которых не удалось прочитать
+  pub errors: Vec<String>,
+}
+
+/// Запись реестра
+struct RegistryEntry {
+  registration: PluginRegistration,
+  origin: PluginOrigin,
+}
+
 /// Реестр плагинов для статической регистрации
 #[derive(Clone)]
 pub struct PluginRegistry {
-  registrations: Arc<RwLock<HashMap<String, PluginRegistration>>>,
+  registrations: Arc<RwLock<HashMap<String, RegistryEntry>>>,
 }
 
 impl PluginRegistry {
@@ -31,6 +103,17 @@ impl PluginRegistry {
 
   /// Зарегистрировать плагин
   pub async fn register(&self, registration: PluginRegistration) -> Result<()> {
+    self
+      .register_with_origin(registration, PluginOrigin::builtin())
+      .await
+  }
+
+  /// Зарегистрировать плагин с указанием происхождения
+  pub async fn register_with_origin(
+    &self,
+    registration: PluginRegistration,
+    origin: PluginOrigin,
+  ) -> Result<()> {
     let mut registrations = self.registrations.write().await;
     let plugin_id = registration.metadata.id.clone();
 
@@ -46,23 +129,61 @@ impl PluginRegistry {
       registration.metadata.version
     );
 
-    registrations.insert(plugin_id, registration);
+    registrations.insert(
+      plugin_id,
+      RegistryEntry {
+        registration,
+        origin,
+      },
+    );
     Ok(())
   }
 
+  /// Снять плагин с регистрации
+  pub async fn unregister(&self, plugin_id: &str) -> Option<PluginOrigin> {
+    let mut registrations = self.registrations.write().await;
+    registrations.remove(plugin_id).map(|entry| entry.origin)
+  }
+
   /// Получить список всех зарегистрированных плагинов
   pub async fn list_plugins(&self) -> Vec<PluginMetadata> {
     let registrations = self.registrations.read().await;
     registrations
       .values()
-      .map(|reg| reg.metadata.clone())
+      .map(|entry| entry.registration.metadata.clone())
+      .collect()
+  }
+
+  /// Получить список зарегистрированных плагинов с их происхождением
+  pub async fn list_entries(&self) -> Vec<PluginListing> {
+    let registrations = self.registrations.read().await;
+    registrations
+      .values()
+      .map(|entry| PluginListing {
+        metadata: entry.registration.metadata.clone(),
+        source: entry.origin.source.clone(),
+        verification: entry.origin.verification.clone(),
+        max_permissions: entry.origin.max_permissions.clone(),
+        registered: true,
+        rejection_reason: None,
+      })
       .collect()
   }
 
   /// Найти регистрацию плагина по ID
   pub async fn find_plugin(&self, plugin_id: &str) -> Option<PluginMetadata> {
     let registrations = self.registrations.read().await;
-    registrations.get(plugin_id).map(|reg| reg.metadata.clone())
+    registrations
+      .get(plugin_id)
+      .map(|entry| entry.registration.metadata.clone())
+  }
+
+  /// Получить происхождение плагина
+  pub async fn origin(&self, plugin_id: &str) -> Option<PluginOrigin> {
+    let registrations = self.registrations.read().await;
+    registrations
+      .get(plugin_id)
+      .map(|entry| entry.origin.clone())
   }
 
   /// Создать экземпляр плагина
@@ -70,8 +191,8 @@ impl PluginRegistry {
     let registrations = self.registrations.read().await;
 
     match registrations.get(plugin_id) {
-      Some(registration) => {
-        let plugin = (registration.factory)();
+      Some(entry) => {
+        let plugin = (entry.registration.factory)();
         Ok(plugin)
       }
       None => Err(VideoCompilerError::InvalidParameter(format!(
@@ -79,6 +200,16 @@ impl PluginRegistry {
       ))),
     }
   }
+
+  /// ID плагинов, установленных в каталог
+  async fn directory_plugin_ids(&self) -> Vec<String> {
+    let registrations = self.registrations.read().await;
+    registrations
+      .iter()
+      .filter(|(_, entry)| matches!(entry.origin.source, PluginSource::Directory { .. }))
+      .map(|(id, _)| id.clone())
+      .collect()
+  }
 }
 
 impl Default for PluginRegistry {
@@ -87,18 +218,58 @@ impl Default for PluginRegistry {
   }
 }
 
+/// Настройки каталога плагинов
+#[derive(Clone)]
+pub struct PluginDirectoryConfig {
+  /// Каталог, в котором лежат `<id>/plugin.toml`
+  pub plugins_dir: PathBuf,
+  /// Проверка подписей издателя
+  pub verifier: SignatureVerifier,
+  /// Разрешить регистрацию неподписанных плагинов
+  pub developer_mode: bool,
+  /// Поиск встроенных фабрик для точек входа `factory`
+  pub factory_resolver: Option<PluginFactoryResolver>,
+}
+
+impl Default for PluginDirectoryConfig {
+  fn default() -> Self {
+    Self {
+      plugins_dir: plugins_root_dir(),
+      verifier: SignatureVerifier::bundled(),
+      developer_mode: developer_mode_from_env(),
+      factory_resolver: None,
+    }
+  }
+}
+
 /// Загрузчик плагинов
 pub struct PluginLoader {
   registry: Arc<PluginRegistry>,
   app_version: Version,
+  plugins_dir: PathBuf,
+  verifier: SignatureVerifier,
+  developer_mode: AtomicBool,
+  factory_resolver: Option<PluginFactoryResolver>,
+  /// Плагины из каталога, которым отказано в регистрации при последнем сканировании
+  rejected: RwLock<HashMap<String, PluginListing>>,
 }
 
 impl PluginLoader {
   /// Создать новый загрузчик
   pub fn new(app_version: Version) -> Self {
+    Self::new_with_directory(app_version, PluginDirectoryConfig::default())
+  }
+
+  /// Создать загрузчик с настройками каталога плагинов
+  pub fn new_with_directory(app_version: Version, config: PluginDirectoryConfig) -> Self {
     Self {
       registry: Arc::new(PluginRegistry::new()),
       app_version,
+      plugins_dir: config.plugins_dir,
+      verifier: config.verifier,
+      developer_mode: AtomicBool::new(config.developer_mode),
+      factory_resolver: config.factory_resolver,
+      rejected: RwLock::new(HashMap::new()),
     }
   }
 
@@ -107,6 +278,266 @@ impl PluginLoader {
     self.registry.clone()
   }
 
+  /// Каталог установленных плагинов
+  pub fn plugins_dir(&self) -> &Path {
+    &self.plugins_dir
+  }
+
+  /// Включен ли режим разработчика
+  pub fn developer_mode(&self) -> bool {
+    self.developer_mode.load(Ordering::Relaxed)
+  }
+
+  /// Включить или выключить режим разработчика
+  pub fn set_developer_mode(&self, enabled: bool) {
+    self.developer_mode.store(enabled, Ordering::Relaxed);
+  }
+
+  /// Все известные плагины: зарегистрированные и отклоненные при сканировании
+  pub async fn list_plugins_with_status(&self) -> Vec<PluginListing> {
+    let mut listings = self.registry.list_entries().await;
+    listings.extend(self.rejected.read().await.values().cloned());
+    listings.sort_by(|a, b| a.metadata.id.cmp(&b.metadata.id));
+    listings
+  }
+
+  /// Пересканировать каталог плагинов
+  ///
+  /// Плагины из каталога снимаются с регистрации и регистрируются заново,
+  /// кроме перечисленных в `keep` (например, уже загруженных).
+  pub async fn rescan_directory(&self, keep: &HashSet<String>) -> Result<PluginScanReport> {
+    for plugin_id in self.registry.directory_plugin_ids().await {
+      if !keep.contains(&plugin_id) {
+        self.registry.unregister(&plugin_id).await;
+      }
+    }
+    self.rejected.write().await.clear();
+
+    let mut report = PluginScanReport::default();
+    let entries = match std::fs::read_dir(&self.plugins_dir) {
+      Ok(entries) => entries,
+      Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(report),
+      Err(e) => {
+        return Err(VideoCompilerError::IoError(format!(
+          "Failed to read plugins directory {}: {e}",
+          self.plugins_dir.display()
+        )))
+      }
+    };
+
+    let mut plugin_dirs: Vec<PathBuf> = entries
+      .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+      .filter(|path| path.join(MANIFEST_FILE_NAME).is_file())
+      .collect();
+    plugin_dirs.sort();
+
+    for dir in plugin_dirs {
+      let discovered = match read_plugin_dir(&dir, &self.verifier) {
+        Ok(discovered) => discovered,
+        Err(e) => {
+          log::warn!("Skipping plugin directory {}: {e}", dir.display());
+          report.errors.push(format!("{}: {e}", dir.display()));
+          continue;
+        }
+      };
+
+      let plugin_id = discovered.manifest.id.clone();
+      if keep.contains(&plugin_id) {
+        continue;
+      }
+
+      match self.register_discovered(&discovered).await {
+        Ok(()) => report.registered.push(plugin_id),
+        Err(reason) => {
+          log::warn!("Plugin '{plugin_id}' was not registered: {reason}");
+          let listing = Self::listing(&discovered, false, Some(reason));
+          self
+            .rejected
+            .write()
+            .await
+            .insert(plugin_id, listing.clone());
+          report.rejected.push(listing);
+        }
+      }
+    }
+
+    Ok(report)
+  }
+
+  /// Установить плагин из директории (или файла `plugin.toml`)
+  ///
+  /// Подпись проверяется до копирования и повторно для скопированных файлов.
+  pub async fn install_from_path(&self, source: &Path) -> Result<PluginListing> {
+    let source_dir = if source.is_file() {
+      source.parent().unwrap_or(source)
+    } else {
+      source
+    };
+
+    let discovered = read_plugin_dir(source_dir, &self.verifier)?;
+    self
+      .accept(&discovered)
+      .map_err(VideoCompilerError::validation)?;
+
+    let plugin_id = discovered.manifest.id.clone();
+    if self.registry.find_plugin(&plugin_id).await.is_some() {
+      return Err(VideoCompilerError::InvalidParameter(format!(
+        "Plugin '{plugin_id}' is already registered"
+      )));
+    }
+
+    let target = self.plugins_dir.join(&plugin_id);
+    if target.exists() {
+      return Err(VideoCompilerError::InvalidParameter(format!(
+        "Plugin '{plugin_id}' is already installed"
+      )));
+    }
+
+    copy_dir_recursive(source_dir, &target).map_err(|e| {
+      let _ = std::fs::remove_dir_all(&target);
+      VideoCompilerError::IoError(format!("Failed to copy plugin '{plugin_id}': {e}"))
+    })?;
+
+    // Файлы могли измениться между проверкой и копированием
+    let registered = match read_plugin_dir(&target, &self.verifier) {
+      Ok(installed) if installed.manifest.id == plugin_id => self
+        .register_discovered(&installed)
+        .await
+        .map(|()| installed),
+      Ok(_) => Err("Plugin id changed during installation".to_string()),
+      Err(e) => Err(e.to_string()),
+    };
+
+    match registered {
+      Ok(installed) => {
+        self.rejected.write().await.remove(&plugin_id);
+        log::info!("Installed plugin '{plugin_id}' into {}", target.display());
+        Ok(Self::listing(&installed, true, None))
+      }
+      Err(reason) => {
+        let _ = std::fs::remove_dir_all(&target);
+        Err(VideoCompilerError::ValidationError(reason.into()))
+      }
+    }
+  }
+
+  /// Удалить плагин, установленный в каталог
+  ///
+  /// Плагин не должен быть загружен - это проверяет менеджер плагинов.
+  pub async fn uninstall(&self, plugin_id: &str) -> Result<()> {
+    let path = match self.registry.origin(plugin_id).await {
+      Some(origin) => match origin.source {
+        PluginSource::Directory { path } => path,
+        PluginSource::Builtin => {
+          return Err(VideoCompilerError::InvalidParameter(format!(
+            "Built-in plugin '{plugin_id}' cannot be uninstalled"
+          )))
+        }
+      },
+      None => match self.rejected.read().await.get(plugin_id) {
+        Some(PluginListing {
+          source: PluginSource::Directory { path },
+          ..
+        }) => path.clone(),
+        _ => {
+          return Err(VideoCompilerError::InvalidParameter(format!(
+            "Plugin '{plugin_id}' not found"
+          )))
+        }
+      },
+    };
+
+    if !path.starts_with(&self.plugins_dir) {
+      return Err(VideoCompilerError::ValidationError(
+        format!("Plugin '{plugin_id}' is outside of the plugins directory").into(),
+      ));
+    }
+
+    self.registry.unregister(plugin_id).await;
+    self.rejected.write().await.remove(plugin_id);
+
+    tokio::fs::remove_dir_all(&path).await.map_err(|e| {
+      VideoCompilerError::IoError(format!("Failed to remove plugin '{plugin_id}': {e}"))
+    })?;
+
+    log::info!("Uninstalled plugin '{plugin_id}'");
+    Ok(())
+  }
+
+  /// Проверить, можно ли зарегистрировать найденный плагин, и найти его фабрику
+  fn accept(&self, discovered: &DiscoveredPlugin) -> std::result::Result<PluginFactory, String> {
+    if !discovered.verification.is_trusted() && !self.developer_mode() {
+      return Err(match &discovered.verification {
+        PluginVerificationStatus::Invalid(reason) => {
+          format!("Signature verification failed: {reason}")
+        }
+        _ => "Plugin is not signed by a trusted publisher".to_string(),
+      });
+    }
+
+    match &discovered.manifest.entry {
+      PluginEntry::Factory { factory } => self
+        .factory_resolver
+        .as_ref()
+        .and_then(|resolve| resolve(factory))
+        .ok_or_else(|| format!("Unknown plugin factory '{factory}'")),
+      PluginEntry::Wasm { .. } => Err("WASM plugin entries are not supported yet".to_string()),
+    }
+  }
+
+  /// Зарегистрировать найденный в каталоге плагин
+  async fn register_discovered(
+    &self,
+    discovered: &DiscoveredPlugin,
+  ) -> std::result::Result<(), String> {
+    let factory = self.accept(discovered)?;
+
+    if !discovered.verification.is_trusted() {
+      log::warn!(
+        "Registering untrusted plugin '{}' in developer mode",
+        discovered.manifest.id
+      );
+    }
+
+    let origin = PluginOrigin {
+      source: PluginSource::Directory {
+        path: discovered.dir.clone(),
+      },
+      verification: discovered.verification.clone(),
+      max_permissions: Some(discovered.manifest.permissions.clone()),
+    };
+
+    self
+      .registry
+      .register_with_origin(
+        PluginRegistration {
+          metadata: discovered.manifest.to_metadata(),
+          factory,
+        },
+        origin,
+      )
+      .await
+      .map_err(|e| e.to_string())
+  }
+
+  /// Элемент списка плагинов для найденного в каталоге плагина
+  fn listing(
+    discovered: &DiscoveredPlugin,
+    registered: bool,
+    rejection_reason: Option<String>,
+  ) -> PluginListing {
+    PluginListing {
+      metadata: discovered.manifest.to_metadata(),
+      source: PluginSource::Directory {
+        path: discovered.dir.clone(),
+      },
+      verification: discovered.verification.clone(),
+      max_permissions: Some(discovered.manifest.permissions.clone()),
+      registered,
+      rejection_reason,
+    }
+  }
+
   /// Валидировать плагин
   pub async fn validate_plugin(&self, metadata: &PluginMetadata) -> PluginValidationResult {
     let mut result = PluginValidationResult::valid();
@@ -250,10 +681,9 @@ impl PluginLoader {
     // Валидируем
     let validation = self.validate_plugin(&metadata).await;
     if !validation.is_valid {
-      return Err(VideoCompilerError::ValidationError(format!(
-        "Plugin validation failed: {}",
-        validation.errors.join("; ")
-      )));
+      return Err(VideoCompilerError::ValidationError(
+        format!("Plugin validation failed: {}", validation.errors.join("; ")).into(),
+      ));
     }
 
     // Создаем экземпляр
@@ -288,6 +718,22 @@ impl PluginLoader {
   }
 }
 
+/// Рекурсивно скопировать директорию плагина (символические ссылки пропускаются)
+fn copy_dir_recursive(source: &Path, target: &Path) -> std::io::Result<()> {
+  std::fs::create_dir_all(target)?;
+  for entry in std::fs::read_dir(source)? {
+    let entry = entry?;
+    let file_type = entry.file_type()?;
+    let destination = target.join(entry.file_name());
+    if file_type.is_dir() {
+      copy_dir_recursive(&entry.path(), &destination)?;
+    } else if file_type.is_file() {
+      std::fs::copy(entry.path(), destination)?;
+    }
+  }
+  Ok(())
+}
+
 /// Макрос для упрощения регистрации плагинов
 #[macro_export]
 macro_rules! register_plugin {
@@ -306,9 +752,12 @@ macro_rules! register_plugin {
 mod tests {
   use super::*;
   use crate::core::plugins::context::PluginContext;
+  use crate::core::plugins::manifest::test_support::{verifier, write_plugin};
+  use crate::core::plugins::manifest::SIGNATURE_FILE_NAME;
   use crate::core::plugins::plugin::{
     Plugin, PluginCommand, PluginMetadata, PluginResponse, PluginType,
   };
+  use tempfile::TempDir;
 
   #[tokio::test]
   async fn test_plugin_registry() {
@@ -448,6 +897,7 @@ mod tests {
         plugin_id: "base-plugin".to_string(),
         min_version: Some(Version::new(1, 0, 0)),
         max_version: Some(Version::new(2, 0, 0)),
+        version_req: None,
       }],
       min_app_version: None,
     };
@@ -469,6 +919,7 @@ mod tests {
         plugin_id: "base-plugin".to_string(),
         min_version: Some(Version::new(2, 0, 0)), // Требует версию > чем есть
         max_version: None,
+        version_req: None,
       }],
       min_app_version: None,
     };
@@ -717,4 +1168,230 @@ mod tests {
       false // is_max
     ));
   }
+
+  // ============ Плагины из каталога ============
+
+  fn directory_loader(plugins_dir: &Path, developer_mode: bool) -> PluginLoader {
+    let resolver: PluginFactoryResolver = Arc::new(|name| {
+      (name == "test-factory").then(|| -> PluginFactory {
+        Box::new(|| panic!("Plugin instances are not created in loader tests"))
+      })
+    });
+
+    PluginLoader::new_with_directory(
+      Version::new(1, 0, 0),
+      PluginDirectoryConfig {
+        plugins_dir: plugins_dir.to_path_buf(),
+        verifier: verifier(),
+        developer_mode,
+        factory_resolver: Some(resolver),
+      },
+    )
+  }
+
+  fn listing_for<'a>(listings: &'a [PluginListing], id: &str) -> &'a PluginListing {
+    listings
+      .iter()
+      .find(|listing| listing.metadata.id == id)
+      .unwrap_or_else(|| panic!("Plugin '{id}' is not listed"))
+  }
+
+  #[tokio::test]
+  async fn test_rescan_registers_only_verified_plugins() {
+    let plugins = TempDir::new().unwrap();
+    write_plugin(plugins.path(), "signed-plugin", "test-factory", true);
+    write_plugin(plugins.path(), "unsigned-plugin", "test-factory", false);
+    let tampered = write_plugin(plugins.path(), "tampered-plugin", "test-factory", true);
+    std::fs::write(
+      tampered.join(MANIFEST_FILE_NAME),
+      std::fs::read_to_string(tampered.join(MANIFEST_FILE_NAME))
+        .unwrap()
+        .replace("version = \"1.2.0\"", "version = \"9.9.9\""),
+    )
+    .unwrap();
+    write_plugin(plugins.path(), "unknown-factory", "missing-factory", true);
+    std::fs::create_dir_all(plugins.path().join("broken")).unwrap();
+    std::fs::write(
+      plugins.path().join("broken").join(MANIFEST_FILE_NAME),
+      "id = ",
+    )
+    .unwrap();
+
+    let loader = directory_loader(plugins.path(), false);
+    let report = loader.rescan_directory(&HashSet::new()).await.unwrap();
+
+    assert_eq!(report.registered, vec!["signed-plugin".to_string()]);
+    assert_eq!(report.rejected.len(), 3);
+    assert_eq!(report.errors.len(), 1);
+
+    let registry = loader.registry();
+    assert!(registry.find_plugin("signed-plugin").await.is_some());
+    assert!(registry.find_plugin("unsigned-plugin").await.is_none());
+    assert!(registry.find_plugin("tampered-plugin").await.is_none());
+    assert!(registry.find_plugin("unknown-factory").await.is_none());
+
+    let listings = loader.list_plugins_with_status().await;
+    let signed = listing_for(&listings, "signed-plugin");
+    assert!(signed.registered);
+    assert_eq!(signed.verification, PluginVerificationStatus::Verified);
+
+    let unsigned = listing_for(&listings, "unsigned-plugin");
+    assert!(!unsigned.registered);
+    assert_eq!(unsigned.verification, PluginVerificationStatus::Unsigned);
+    assert!(unsigned.rejection_reason.is_some());
+
+    let tampered = listing_for(&listings, "tampered-plugin");
+    assert!(!tampered.registered);
+    assert!(matches!(
+      tampered.verification,
+      PluginVerificationStatus::Invalid(_)
+    ));
+
+    let unknown = listing_for(&listings, "unknown-factory");
+    assert_eq!(unknown.verification, PluginVerificationStatus::Verified);
+    assert!(unknown
+      .rejection_reason
+      .as_deref()
+      .unwrap()
+      .contains("missing-factory"));
+  }
+
+  #[tokio::test]
+  async fn test_developer_mode_registers_unsigned_plugins() {
+    let plugins = TempDir::new().unwrap();
+    write_plugin(plugins.path(), "unsigned-plugin", "test-factory", false);
+    let tampered = write_plugin(plugins.path(), "tampered-plugin", "test-factory", true);
+    std::fs::write(tampered.join(SIGNATURE_FILE_NAME), "garbage").unwrap();
+
+    let loader = directory_loader(plugins.path(), true);
+    let report = loader.rescan_directory(&HashSet::new()).await.unwrap();
+    assert_eq!(report.registered.len(), 2);
+
+    // Статус проверки сохраняется и виден в списке
+    let listings = loader.list_plugins_with_status().await;
+    assert_eq!(
+      listing_for(&listings, "unsigned-plugin").verification,
+      PluginVerificationStatus::Unsigned
+    );
+    assert!(matches!(
+      listing_for(&listings, "tampered-plugin").verification,
+      PluginVerificationStatus::Invalid(_)
+    ));
+
+    // После выключения режима разработчика повторное сканирование их отклоняет
+    loader.set_developer_mode(false);
+    let report = loader.rescan_directory(&HashSet::new()).await.unwrap();
+    assert!(report.registered.is_empty());
+    assert!(loader
+      .registry()
+      .find_plugin("unsigned-plugin")
+      .await
+      .is_none());
+  }
+
+  #[tokio::test]
+  async fn test_rescan_keeps_loaded_plugins_and_drops_removed() {
+    let plugins = TempDir::new().unwrap();
+    let first = write_plugin(plugins.path(), "first-plugin", "test-factory", true);
+    write_plugin(plugins.path(), "second-plugin", "test-factory", true);
+
+    let loader = directory_loader(plugins.path(), false);
+    loader.rescan_directory(&HashSet::new()).await.unwrap();
+    assert_eq!(loader.registry().list_plugins().await.len(), 2);
+
+    std::fs::remove_dir_all(&first).unwrap();
+    std::fs::remove_dir_all(plugins.path().join("second-plugin")).unwrap();
+
+    // "first-plugin" загружен и остается зарегистрированным
+    let keep = HashSet::from(["first-plugin".to_string()]);
+    loader.rescan_directory(&keep).await.unwrap();
+    let registry = loader.registry();
+    assert!(registry.find_plugin("first-plugin").await.is_some());
+    assert!(registry.find_plugin("second-plugin").await.is_none());
+  }
+
+  #[tokio::test]
+  async fn test_install_and_uninstall_plugin() {
+    let source = TempDir::new().unwrap();
+    let plugins = TempDir::new().unwrap();
+    let source_dir = write_plugin(source.path(), "installable", "test-factory", true);
+
+    let loader = directory_loader(plugins.path(), false);
+    let listing = loader.install_from_path(&source_dir).await.unwrap();
+    assert!(listing.registered);
+    assert_eq!(listing.verification, PluginVerificationStatus::Verified);
+
+    let installed = plugins.path().join("installable");
+    assert!(installed.join(MANIFEST_FILE_NAME).is_file());
+    assert!(installed.join(SIGNATURE_FILE_NAME).is_file());
+    assert!(loader.registry().find_plugin("installable").await.is_some());
+
+    // Повторная установка запрещена
+    assert!(loader
+      .install_from_path(&source_dir.join(MANIFEST_FILE_NAME))
+      .await
+      .is_err());
+
+    loader.uninstall("installable").await.unwrap();
+    assert!(!installed.exists());
+    assert!(loader.registry().find_plugin("installable").await.is_none());
+    assert!(loader.uninstall("installable").await.is_err());
+  }
+
+  #[tokio::test]
+  async fn test_install_refuses_tampered_and_unsigned_plugins() {
+    let source = TempDir::new().unwrap();
+    let plugins = TempDir::new().unwrap();
+    let loader = directory_loader(plugins.path(), false);
+
+    let unsigned = write_plugin(source.path(), "unsigned-plugin", "test-factory", false);
+    assert!(loader.install_from_path(&unsigned).await.is_err());
+
+    let tampered = write_plugin(source.path(), "tampered-plugin", "test-factory", true);
+    std::fs::write(
+      tampered.join(MANIFEST_FILE_NAME),
+      std::fs::read_to_string(tampered.join(MANIFEST_FILE_NAME))
+        .unwrap()
+        .replace("[permissions]", "[permissions]\nprocess_spawn = true"),
+    )
+    .unwrap();
+    assert!(loader.install_from_path(&tampered).await.is_err());
+
+    // Ничего не скопировано в каталог плагинов
+    assert_eq!(std::fs::read_dir(plugins.path()).unwrap().count(), 0);
+    assert!(loader.registry().list_plugins().await.is_empty());
+  }
+
+  #[tokio::test]
+  async fn test_uninstall_rejects_builtin_plugins_and_removes_rejected() {
+    let plugins = TempDir::new().unwrap();
+    let loader = directory_loader(plugins.path(), false);
+
+    let metadata = PluginMetadata {
+      id: "builtin-plugin".to_string(),
+      name: "Builtin".to_string(),
+      version: Version::new(1, 0, 0),
+      author: "Author".to_string(),
+      description: "Description".to_string(),
+      plugin_type: PluginType::Effect,
+      homepage: None,
+      license: None,
+      dependencies: vec![],
+      min_app_version: None,
+    };
+    let factory: PluginFactory = Box::new(|| panic!("not created"));
+    loader
+      .registry()
+      .register(PluginRegistration { metadata, factory })
+      .await
+      .unwrap();
+    assert!(loader.uninstall("builtin-plugin").await.is_err());
+
+    // Отклоненный плагин можно удалить из каталога
+    let unsigned = write_plugin(plugins.path(), "unsigned-plugin", "test-factory", false);
+    loader.rescan_directory(&HashSet::new()).await.unwrap();
+    loader.uninstall("unsigned-plugin").await.unwrap();
+    assert!(!unsigned.exists());
+    assert!(listing_for(&loader.list_plugins_with_status().await, "builtin-plugin").registered);
+  }
 }
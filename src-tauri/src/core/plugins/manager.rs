@@ -2,16 +2,19 @@
 
 use super::{
   context::PluginContext,
-  loader::PluginLoader,
+  dependencies::DependencyResolver,
+  loader::{PluginDirectoryConfig, PluginListing, PluginLoader, PluginScanReport},
   permissions::PluginPermissions,
-  plugin::{AppEventType, Plugin, PluginCommand, PluginResponse, PluginState},
+  plugin::{AppEventType, Plugin, PluginCommand, PluginResponse, PluginState, ResolvedDependency},
   sandbox::SandboxManager,
+  services::{NetworkGateway, NetworkGatewayConfig},
 };
 use crate::core::telemetry::metrics::Metrics;
 use crate::core::{AppEvent, EventBus, MetricsCollector, Service, ServiceContainer, Tracer};
 use crate::video_compiler::error::{Result, VideoCompilerError};
 use async_trait::async_trait;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 #[cfg(test)]
@@ -24,6 +27,8 @@ pub struct PluginHandle {
   pub plugin: Box<dyn Plugin>,
   pub context: PluginContext,
   pub state: PluginState,
+  /// Зависимости, с которыми плагин был загружен
+  pub dependencies: Vec<ResolvedDependency>,
 }
 
 /// Менеджер плагинов
@@ -37,6 +42,7 @@ pub struct PluginManager {
   tracer: Option<Arc<Tracer>>,
   metrics: Option<Arc<Metrics>>,
   sandbox_manager: Arc<SandboxManager>,
+  network_gateway: Arc<NetworkGateway>,
   app_handle: Option<tauri::AppHandle>,
 }
 
@@ -49,6 +55,10 @@ impl PluginManager {
   ) -> Self {
     let loader = Arc::new(PluginLoader::new(app_version.clone()));
     let sandbox_manager = Arc::new(SandboxManager::new());
+    let network_gateway = Arc::new(NetworkGateway::new(NetworkGatewayConfig {
+      queue_path: Some(super::manifest::plugins_root_dir().join("network_queue.json")),
+      ..Default::default()
+    }));
 
     Self {
       plugins: Arc::new(RwLock::new(HashMap::new())),
@@ -59,6 +69,7 @@ impl PluginManager {
       tracer: None,
       metrics: None,
       sandbox_manager,
+      network_gateway,
       app_handle: None,
     }
   }
@@ -80,11 +91,46 @@ impl PluginManager {
     Ok(self)
   }
 
+  /// Настроить каталог плагинов
+  ///
+  /// Заменяет загрузчик, поэтому вызывается до регистрации плагинов.
+  pub fn with_plugin_directory(mut self, config: PluginDirectoryConfig) -> Self {
+    self.loader = Arc::new(PluginLoader::new_with_directory(
+      self.app_version.clone(),
+      config,
+    ));
+    self
+  }
+
+  /// Получить сетевой шлюз плагинов
+  pub fn network_gateway(&self) -> Arc<NetworkGateway> {
+    self.network_gateway.clone()
+  }
+
   /// Получить загрузчик плагинов
   pub fn loader(&self) -> Arc<PluginLoader> {
     self.loader.clone()
   }
 
+  /// Пересканировать каталог плагинов (загруженные плагины не затрагиваются)
+  pub async fn rescan_plugins(&self) -> Result<PluginScanReport> {
+    let loaded: HashSet<String> = self.plugins.read().await.keys().cloned().collect();
+    self.loader.rescan_directory(&loaded).await
+  }
+
+  /// Установить плагин из директории после проверки подписи
+  pub async fn install_plugin_from_path(&self, path: &Path) -> Result<PluginListing> {
+    self.loader.install_from_path(path).await
+  }
+
+  /// Удалить плагин из каталога, предварительно выгрузив его
+  pub async fn uninstall_plugin(&self, plugin_id: &str) -> Result<()> {
+    if self.plugins.read().await.contains_key(plugin_id) {
+      self.unload_plugin(plugin_id).await?;
+    }
+    self.loader.uninstall(plugin_id).await
+  }
+
   /// Загрузить и инициализировать плагин
   pub async fn load_plugin(
     &self,
@@ -113,6 +159,21 @@ impl PluginManager {
       }
     }
 
+    // Проверяем граф зависимостей и что все зависимости уже загружены
+    let dependencies = self.resolve_loaded_dependencies(plugin_id).await?;
+
+    // Разрешения из манифеста - верхняя граница для плагинов из каталога
+    let max_permissions = self
+      .loader
+      .registry()
+      .origin(plugin_id)
+      .await
+      .and_then(|origin| origin.max_permissions);
+    let permissions = match max_permissions {
+      Some(max) => permissions.restrict_to(&max),
+      None => permissions,
+    };
+
     // Загружаем плагин
     let mut plugin = self.loader.load_plugin(plugin_id).await?;
 
@@ -120,6 +181,12 @@ impl PluginManager {
     let version_string = plugin.metadata().version.to_string();
     let plugin_type = format!("{:?}", plugin.metadata().plugin_type);
 
+    // Создаем sandbox для плагина
+    let sandbox = self
+      .sandbox_manager
+      .create_sandbox(plugin_id.to_string(), &permissions)
+      .await;
+
     // Создаем контекст
     let context = PluginContext::new(
       plugin_id,
@@ -128,13 +195,9 @@ impl PluginManager {
       self.service_container.clone(),
       permissions.clone(),
       self.app_handle.clone(),
-    );
-
-    // Создаем sandbox для плагина
-    let _sandbox = self
-      .sandbox_manager
-      .create_sandbox(plugin_id.to_string(), &permissions)
-      .await;
+    )
+    .with_sandbox(sandbox)
+    .with_network_gateway(self.network_gateway.clone());
 
     // Создаем директории
     context
@@ -154,6 +217,7 @@ impl PluginManager {
       plugin,
       context,
       state: PluginState::Active,
+      dependencies,
     };
 
     {
@@ -184,8 +248,130 @@ impl PluginManager {
     Ok(instance_id)
   }
 
+  /// Загрузить все зарегистрированные плагины в порядке зависимостей
+  ///
+  /// Граф проверяется целиком до загрузки первого плагина. Уже загруженные плагины пропускаются.
+  pub async fn load_all(&self, permissions: PluginPermissions) -> Result<Vec<String>> {
+    let registered = self.loader.registry().list_plugins().await;
+    let order = DependencyResolver::new(&registered).resolve_all()?;
+
+    let mut loaded = Vec::new();
+    for plugin_id in order {
+      if self.plugins.read().await.contains_key(&plugin_id) {
+        continue;
+      }
+
+      self.load_plugin(&plugin_id, permissions.clone()).await?;
+      loaded.push(plugin_id);
+    }
+
+    Ok(loaded)
+  }
+
+  /// Разрешить зависимости плагина среди уже загруженных
+  async fn resolve_loaded_dependencies(&self, plugin_id: &str) -> Result<Vec<ResolvedDependency>> {
+    let registered = self.loader.registry().list_plugins().await;
+    let metadata = registered
+      .iter()
+      .find(|m| m.id == plugin_id)
+      .ok_or_else(|| {
+        VideoCompilerError::InvalidParameter(format!("Plugin '{plugin_id}' not found"))
+      })?;
+    DependencyResolver::new(&registered).resolve(plugin_id)?;
+
+    let plugins = self.plugins.read().await;
+    metadata
+      .dependencies
+      .iter()
+      .map(|dependency| {
+        let handle = plugins.get(&dependency.plugin_id).ok_or_else(|| {
+          VideoCompilerError::PluginDependencyError {
+            plugin_id: plugin_id.to_string(),
+            dependency: dependency.plugin_id.clone(),
+            reason: "plugin is not loaded".to_string(),
+          }
+        })?;
+
+        Ok(ResolvedDependency {
+          plugin_id: dependency.plugin_id.clone(),
+          version: handle.plugin.metadata().version.clone(),
+        })
+      })
+      .collect()
+  }
+
   /// Выгрузить плагин
+  ///
+  /// Плагин, от которого зависят другие загруженные плагины, не выгружается.
   pub async fn unload_plugin(&self, plugin_id: &str) -> Result<()> {
+    let dependents = self.loaded_dependents(plugin_id).await;
+    if !dependents.is_empty() {
+      return Err(VideoCompilerError::InvalidParameter(format!(
+        "Plugin '{plugin_id}' is required by loaded plugins: {}",
+        dependents.join(", ")
+      )));
+    }
+
+    self.unload_single(plugin_id).await
+  }
+
+  /// Выгрузить плагин вместе со всеми зависящими от него плагинами
+  ///
+  /// Возвращает выгруженные плагины в порядке выгрузки (зависимые первыми).
+  pub async fn force_unload_plugin(&self, plugin_id: &str) -> Result<Vec<String>> {
+    let order = {
+      let plugins = self.plugins.read().await;
+      if !plugins.contains_key(plugin_id) {
+        return Err(VideoCompilerError::InvalidParameter(format!(
+          "Plugin '{plugin_id}' is not loaded"
+        )));
+      }
+
+      let mut order = Vec::new();
+      Self::collect_dependents(&plugins, plugin_id, &mut order);
+      order
+    };
+
+    for id in &order {
+      self.unload_single(id).await?;
+    }
+
+    Ok(order)
+  }
+
+  /// Загруженные плагины, напрямую зависящие от плагина
+  async fn loaded_dependents(&self, plugin_id: &str) -> Vec<String> {
+    let plugins = self.plugins.read().await;
+    let mut dependents: Vec<String> = plugins
+      .values()
+      .filter(|handle| handle.dependencies.iter().any(|d| d.plugin_id == plugin_id))
+      .map(|handle| handle.id.clone())
+      .collect();
+    dependents.sort();
+    dependents
+  }
+
+  /// Обойти зависимые плагины, добавляя их в порядок выгрузки раньше зависимости
+  fn collect_dependents(
+    plugins: &HashMap<String, PluginHandle>,
+    plugin_id: &str,
+    order: &mut Vec<String>,
+  ) {
+    if order.iter().any(|id| id == plugin_id) {
+      return;
+    }
+
+    for handle in plugins.values() {
+      if handle.dependencies.iter().any(|d| d.plugin_id == plugin_id) {
+        Self::collect_dependents(plugins, &handle.id, order);
+      }
+    }
+
+    order.push(plugin_id.to_string());
+  }
+
+  /// Остановить плагин и освободить его ресурсы
+  async fn unload_single(&self, plugin_id: &str) -> Result<()> {
     let mut handle = {
       let mut plugins = self.plugins.write().await;
       plugins.remove(plugin_id).ok_or_else(|| {
@@ -236,29 +422,47 @@ impl PluginManager {
     let start = std::time::Instant::now();
     let command_name = command.command.clone();
 
-    let plugins = self.plugins.read().await;
+    let sandbox = self.sandbox_manager.get_sandbox(plugin_id).await;
 
-    let handle = plugins.get(plugin_id).ok_or_else(|| {
-      VideoCompilerError::InvalidParameter(format!("Plugin '{plugin_id}' is not loaded"))
-    })?;
+    let result = {
+      let plugins = self.plugins.read().await;
 
-    // Проверяем состояние
-    match handle.state {
-      PluginState::Active => {}
-      PluginState::Suspended => {
-        return Err(VideoCompilerError::InvalidParameter(format!(
-          "Plugin '{plugin_id}' is suspended"
-        )));
+      let handle = plugins.get(plugin_id).ok_or_else(|| {
+        VideoCompilerError::InvalidParameter(format!("Plugin '{plugin_id}' is not loaded"))
+      })?;
+
+      // Проверяем состояние
+      match handle.state {
+        PluginState::Active => {}
+        PluginState::Suspended => {
+          return Err(VideoCompilerError::InvalidParameter(format!(
+            "Plugin '{plugin_id}' is suspended"
+          )));
+        }
+        _ => {
+          return Err(VideoCompilerError::InvalidParameter(format!(
+            "Plugin '{plugin_id}' is not active"
+          )));
+        }
       }
-      _ => {
-        return Err(VideoCompilerError::InvalidParameter(format!(
-          "Plugin '{plugin_id}' is not active"
-        )));
+
+      // Отправляем команду с квотами sandbox
+      match &sandbox {
+        Some(sandbox) => {
+          sandbox
+            .run_command(handle.plugin.handle_command(command))
+            .await
+        }
+        None => handle.plugin.handle_command(command).await,
       }
-    }
+    };
 
-    // Отправляем команду
-    let result = handle.plugin.handle_command(command).await;
+    // Приостанавливаем плагин после серии нарушений квот
+    if let Some(sandbox) = &sandbox {
+      if sandbox.should_suspend() {
+        self.suspend_for_quota_violations(plugin_id).await;
+      }
+    }
 
     // Обновляем метрики
     if let Some(metrics) = &self.metrics {
@@ -277,6 +481,27 @@ impl PluginManager {
     result
   }
 
+  /// Приостановить плагин, превысивший квоты ресурсов несколько раз подряд
+  async fn suspend_for_quota_violations(&self, plugin_id: &str) {
+    if let Err(e) = self.suspend_plugin(plugin_id).await {
+      log::error!("Failed to suspend plugin '{plugin_id}' after quota violations: {e}");
+      return;
+    }
+
+    log::warn!("Plugin '{plugin_id}' suspended after consecutive quota violations");
+
+    if let Err(e) = self
+      .event_bus
+      .publish_app_event(AppEvent::PluginSuspended {
+        plugin_id: plugin_id.to_string(),
+        reason: "quota_exceeded".to_string(),
+      })
+      .await
+    {
+      log::error!("Failed to publish suspension event for plugin '{plugin_id}': {e}");
+    }
+  }
+
   /// Передать событие всем заинтересованным плагинам
   pub async fn dispatch_event(&self, event: &AppEvent) -> Result<()> {
     let event_type = match event {
@@ -319,12 +544,18 @@ impl PluginManager {
     Ok(())
   }
 
-  /// Получить список загруженных плагинов
-  pub async fn list_loaded_plugins(&self) -> Vec<(String, PluginState)> {
+  /// Получить список загруженных плагинов с разрешенными зависимостями
+  pub async fn list_loaded_plugins(&self) -> Vec<(String, PluginState, Vec<ResolvedDependency>)> {
     let plugins = self.plugins.read().await;
     plugins
       .iter()
-      .map(|(id, handle)| (id.clone(), handle.state.clone()))
+      .map(|(id, handle)| {
+        (
+          id.clone(),
+          handle.state.clone(),
+          handle.dependencies.clone(),
+        )
+      })
       .collect()
   }
 
@@ -367,6 +598,11 @@ impl PluginManager {
     handle.plugin.resume().await?;
     handle.state = PluginState::Active;
 
+    // Даем плагину новую серию попыток после возобновления
+    if let Some(sandbox) = self.sandbox_manager.get_sandbox(plugin_id).await {
+      sandbox.reset_violation_flag();
+    }
+
     log::info!("Plugin '{plugin_id}' resumed");
 
     Ok(())
@@ -395,6 +631,22 @@ impl PluginManager {
     }))
   }
 
+  /// Получить использование ресурсов плагина (память, время, нарушения квот)
+  pub async fn get_plugin_resource_usage(
+    &self,
+    plugin_id: &str,
+  ) -> Result<super::sandbox::SandboxStats> {
+    let sandbox = self
+      .sandbox_manager
+      .get_sandbox(plugin_id)
+      .await
+      .ok_or_else(|| {
+        VideoCompilerError::InvalidParameter(format!("Plugin '{plugin_id}' is not loaded"))
+      })?;
+
+    Ok(sandbox.get_usage_stats().await)
+  }
+
   /// Получить статистику sandbox для всех плагинов
   pub async fn get_sandbox_stats(&self) -> Vec<super::sandbox::SandboxStats> {
     self.sandbox_manager.get_all_stats().await
@@ -455,7 +707,12 @@ impl Service for PluginManager {
     };
 
     for plugin_id in plugin_ids {
-      if let Err(e) = self.unload_plugin(&plugin_id).await {
+      // Плагин мог быть уже выгружен каскадно вместе со своей зависимостью
+      if !self.plugins.read().await.contains_key(&plugin_id) {
+        continue;
+      }
+
+      if let Err(e) = self.force_unload_plugin(&plugin_id).await {
         log::error!("Failed to unload plugin '{plugin_id}': {e}");
       }
     }
@@ -477,6 +734,7 @@ mod tests {
   struct TestPlugin {
     metadata: PluginMetadata,
     initialized: bool,
+    context: Option<PluginContext>,
   }
 
   impl TestPlugin {
@@ -495,6 +753,7 @@ mod tests {
           min_app_version: None,
         },
         initialized: false,
+        context: None,
       }
     }
   }
@@ -505,8 +764,9 @@ mod tests {
       &self.metadata
     }
 
-    async fn initialize(&mut self, _context: PluginContext) -> Result<()> {
+    async fn initialize(&mut self, context: PluginContext) -> Result<()> {
       self.initialized = true;
+      self.context = Some(context);
       Ok(())
     }
 
@@ -516,6 +776,16 @@ mod tests {
     }
 
     async fn handle_command(&self, command: PluginCommand) -> Result<PluginResponse> {
+      match command.command.as_str() {
+        "sleep" => tokio::time::sleep(std::time::Duration::from_secs(5)).await,
+        "allocate" => {
+          let bytes = command.params["bytes"].as_u64().unwrap_or(0);
+          let sandbox = self.context.as_ref().and_then(|c| c.sandbox.clone());
+          let _buffer = sandbox.unwrap().reserve_memory(bytes)?;
+        }
+        _ => {}
+      }
+
       Ok(PluginResponse {
         command_id: command.id,
         success: true,
@@ -1343,4 +1613,336 @@ mod tests {
       );
     }
   }
+
+  async fn load_with_limits(
+    limits: crate::core::plugins::sandbox::ResourceLimits,
+  ) -> PluginManager {
+    let event_bus = Arc::new(EventBus::new());
+    let service_container = Arc::new(ServiceContainer::new());
+    let manager = PluginManager::new(Version::new(1, 0, 0), event_bus, service_container);
+
+    let registry = manager.loader().registry();
+    let metadata = TestPlugin::new().metadata().clone();
+    let factory = Box::new(|| Box::new(TestPlugin::new()) as Box<dyn Plugin>);
+    registry
+      .register(crate::core::plugins::loader::PluginRegistration { metadata, factory })
+      .await
+      .unwrap();
+
+    manager
+      .sandbox_manager()
+      .set_limits_override("test-plugin", limits)
+      .await;
+    manager
+      .load_plugin("test-plugin", PluginPermissions::default())
+      .await
+      .unwrap();
+
+    manager
+  }
+
+  fn command(name: &str, params: serde_json::Value) -> PluginCommand {
+    PluginCommand {
+      id: Uuid::new_v4(),
+      command: name.to_string(),
+      params,
+    }
+  }
+
+  #[tokio::test]
+  async fn test_plugin_command_timeout_suspends_plugin() {
+    let manager = load_with_limits(crate::core::plugins::sandbox::ResourceLimits {
+      max_execution_time: std::time::Duration::from_millis(50),
+      max_consecutive_violations: 2,
+      ..crate::core::plugins::sandbox::ResourceLimits::for_security_level(
+        crate::core::plugins::SecurityLevel::Standard,
+      )
+    })
+    .await;
+
+    // Первая команда превышает тайм-аут, плагин остается активным
+    let result = manager
+      .send_command("test-plugin", command("sleep", serde_json::json!({})))
+      .await;
+    assert!(matches!(
+      result,
+      Err(VideoCompilerError::QuotaExceeded { .. })
+    ));
+    assert_eq!(
+      manager.list_loaded_plugins().await[0].1,
+      PluginState::Active
+    );
+
+    // Вторая подряд приводит к приостановке
+    let _ = manager
+      .send_command("test-plugin", command("sleep", serde_json::json!({})))
+      .await;
+    assert_eq!(
+      manager.list_loaded_plugins().await[0].1,
+      PluginState::Suspended
+    );
+
+    let usage = manager
+      .get_plugin_resource_usage("test-plugin")
+      .await
+      .unwrap();
+    assert_eq!(usage.quota_violations, 2);
+    assert_eq!(usage.execution_time_limit_ms, 50);
+
+    // После возобновления счетчик нарушений подряд сброшен
+    manager.resume_plugin("test-plugin").await.unwrap();
+    let response = manager
+      .send_command("test-plugin", command("echo", serde_json::json!({})))
+      .await
+      .unwrap();
+    assert!(response.success);
+
+    let _ = manager.unload_plugin("test-plugin").await;
+  }
+
+  #[tokio::test]
+  async fn test_plugin_oversized_buffer_quota() {
+    let manager = load_with_limits(crate::core::plugins::sandbox::ResourceLimits {
+      max_memory: 1024,
+      max_consecutive_violations: 3,
+      ..crate::core::plugins::sandbox::ResourceLimits::for_security_level(
+        crate::core::plugins::SecurityLevel::Standard,
+      )
+    })
+    .await;
+
+    // Буфер в пределах квоты освобождается после команды
+    manager
+      .send_command(
+        "test-plugin",
+        command("allocate", serde_json::json!({"bytes": 512})),
+      )
+      .await
+      .unwrap();
+    let usage = manager
+      .get_plugin_resource_usage("test-plugin")
+      .await
+      .unwrap();
+    assert_eq!(usage.memory_used, 0);
+
+    for _ in 0..3 {
+      let result = manager
+        .send_command(
+          "test-plugin",
+          command("allocate", serde_json::json!({"bytes": 4096})),
+        )
+        .await;
+      assert!(matches!(
+        result,
+        Err(VideoCompilerError::QuotaExceeded { limit: 1024, .. })
+      ));
+    }
+
+    assert_eq!(
+      manager.list_loaded_plugins().await[0].1,
+      PluginState::Suspended
+    );
+    assert!(manager
+      .get_plugin_resource_usage("non-existent")
+      .await
+      .is_err());
+
+    let _ = manager.unload_plugin("test-plugin").await;
+  }
+
+  async fn register_with_dependencies(
+    manager: &PluginManager,
+    id: &str,
+    version: Version,
+    dependencies: &[(&str, &str)],
+  ) {
+    let mut metadata = TestPlugin::new().metadata().clone();
+    metadata.id = id.to_string();
+    metadata.version = version;
+    metadata.dependencies = dependencies
+      .iter()
+      .map(
+        |(dep, range)| crate::core::plugins::plugin::PluginDependency {
+          plugin_id: dep.to_string(),
+          min_version: None,
+          max_version: None,
+          version_req: Some(range.to_string()),
+        },
+      )
+      .collect();
+
+    let plugin_metadata = metadata.clone();
+    let factory = Box::new(move || {
+      let mut plugin = TestPlugin::new();
+      plugin.metadata = plugin_metadata.clone();
+      Box::new(plugin) as Box<dyn Plugin>
+    });
+
+    manager
+      .loader()
+      .registry()
+      .register(crate::core::plugins::loader::PluginRegistration { metadata, factory })
+      .await
+      .unwrap();
+  }
+
+  fn new_manager() -> PluginManager {
+    PluginManager::new(
+      Version::new(1, 0, 0),
+      Arc::new(EventBus::new()),
+      Arc::new(ServiceContainer::new()),
+    )
+  }
+
+  #[tokio::test]
+  async fn test_load_with_missing_dependency() {
+    let manager = new_manager();
+    register_with_dependencies(&manager, "app", Version::new(1, 0, 0), &[("core", "*")]).await;
+
+    let result = manager
+      .load_plugin("app", PluginPermissions::default())
+      .await;
+    assert!(matches!(
+      result,
+      Err(VideoCompilerError::PluginDependencyError { ref dependency, .. }) if dependency == "core"
+    ));
+
+    // Зарегистрированная, но не загруженная зависимость тоже не удовлетворяет требованию
+    register_with_dependencies(&manager, "core", Version::new(1, 0, 0), &[]).await;
+    let result = manager
+      .load_plugin("app", PluginPermissions::default())
+      .await;
+    assert!(matches!(
+      result,
+      Err(VideoCompilerError::PluginDependencyError { ref reason, .. }) if reason.contains("not loaded")
+    ));
+  }
+
+  #[tokio::test]
+  async fn test_load_with_version_mismatch() {
+    let manager = new_manager();
+    register_with_dependencies(&manager, "core", Version::new(2, 0, 0), &[]).await;
+    register_with_dependencies(
+      &manager,
+      "app",
+      Version::new(1, 0, 0),
+      &[("core", "^1.0.0")],
+    )
+    .await;
+
+    let result = manager.load_all(PluginPermissions::default()).await;
+    assert!(matches!(
+      result,
+      Err(VideoCompilerError::PluginDependencyError { ref reason, .. }) if reason.contains("2.0.0")
+    ));
+    // Граф проверяется до загрузки, поэтому ничего не загружено
+    assert!(manager.list_loaded_plugins().await.is_empty());
+  }
+
+  #[tokio::test]
+  async fn test_load_all_detects_cycle() {
+    let manager = new_manager();
+    register_with_dependencies(&manager, "a", Version::new(1, 0, 0), &[("b", "*")]).await;
+    register_with_dependencies(&manager, "b", Version::new(1, 0, 0), &[("a", "*")]).await;
+
+    let result = manager.load_all(PluginPermissions::default()).await;
+    match result {
+      Err(VideoCompilerError::PluginDependencyCycle { cycle }) => {
+        assert_eq!(cycle, vec!["a", "b", "a"]);
+      }
+      other => panic!("Expected dependency cycle, got {other:?}"),
+    }
+  }
+
+  #[tokio::test]
+  async fn test_load_all_order_and_cascade_unload() {
+    let manager = new_manager();
+    register_with_dependencies(&manager, "app", Version::new(1, 0, 0), &[("ui", ">=1.2.0")]).await;
+    register_with_dependencies(&manager, "ui", Version::new(1, 3, 0), &[("core", "^1.0.0")]).await;
+    register_with_dependencies(&manager, "core", Version::new(1, 1, 0), &[]).await;
+
+    let loaded = manager
+      .load_all(PluginPermissions::default())
+      .await
+      .unwrap();
+    assert_eq!(loaded, vec!["core", "ui", "app"]);
+
+    let plugins = manager.list_loaded_plugins().await;
+    let (_, _, ui_dependencies) = plugins.iter().find(|(id, _, _)| id == "ui").unwrap();
+    assert_eq!(
+      ui_dependencies,
+      &vec![ResolvedDependency {
+        plugin_id: "core".to_string(),
+        version: Version::new(1, 1, 0),
+      }]
+    );
+
+    // Нельзя выгрузить зависимость без force
+    let result = manager.unload_plugin("core").await;
+    assert!(result.is_err());
+    assert_eq!(manager.list_loaded_plugins().await.len(), 3);
+
+    // force выгружает зависимые плагины первыми
+    let unloaded = manager.force_unload_plugin("core").await.unwrap();
+    assert_eq!(unloaded, vec!["app", "ui", "core"]);
+    assert!(manager.list_loaded_plugins().await.is_empty());
+  }
+
+  #[tokio::test]
+  async fn test_directory_plugin_permissions_are_capped_by_manifest() {
+    use crate::core::plugins::loader::{PluginFactory, PluginFactoryResolver};
+    use crate::core::plugins::manifest::test_support::{verifier, write_plugin};
+    use crate::core::plugins::permissions::SecurityLevel;
+
+    let source = tempfile::TempDir::new().unwrap();
+    let plugins = tempfile::TempDir::new().unwrap();
+    let plugin_dir = write_plugin(source.path(), "dir-plugin", "test-factory", true);
+
+    let resolver: PluginFactoryResolver = Arc::new(|name| {
+      (name == "test-factory")
+        .then(|| -> PluginFactory { Box::new(|| Box::new(TestPlugin::new()) as Box<dyn Plugin>) })
+    });
+    let manager = PluginManager::new(
+      Version::new(1, 0, 0),
+      Arc::new(EventBus::new()),
+      Arc::new(ServiceContainer::new()),
+    )
+    .with_plugin_directory(PluginDirectoryConfig {
+      plugins_dir: plugins.path().to_path_buf(),
+      verifier: verifier(),
+      developer_mode: false,
+      factory_resolver: Some(resolver),
+    });
+
+    manager.install_plugin_from_path(&plugin_dir).await.unwrap();
+    manager
+      .load_plugin("dir-plugin", SecurityLevel::Full.permissions())
+      .await
+      .unwrap();
+
+    // Манифест разрешает только UI и чтение файлов
+    {
+      let plugins_map = manager.plugins.read().await;
+      let granted = &plugins_map["dir-plugin"].context.permissions;
+      assert!(granted.ui_access);
+      assert!(granted.file_system.read_all);
+      assert!(!granted.file_system.write_all);
+      assert!(!granted.process_spawn);
+      assert!(!granted.network.allow_all);
+    }
+
+    // Загруженный плагин переживает пересканирование, а удаление его выгружает
+    let report = manager.rescan_plugins().await.unwrap();
+    assert!(report.registered.is_empty());
+    assert!(manager
+      .loader()
+      .registry()
+      .find_plugin("dir-plugin")
+      .await
+      .is_some());
+
+    manager.uninstall_plugin("dir-plugin").await.unwrap();
+    assert!(manager.list_loaded_plugins().await.is_empty());
+    assert!(!plugins.path().join("dir-plugin").exists());
+  }
 }
@@ -0,0 +1,498 @@
+//! Манифесты плагинов из каталога и проверка подписи
+//!
+//! Каждый сторонний плагин лежит в `<data_dir>/timeline-studio/plugins/<id>/`
+//! и описывается файлом `plugin.toml`. Рядом лежит отсоединенная подпись
+//! `plugin.toml.sig` - подпись ed25519 (base64) всего содержимого манифеста,
+//! сделанная ключом издателя. Для WASM-плагинов манифест содержит SHA-256
+//! модуля, поэтому подпись покрывает и код.
+
+use super::permissions::PluginPermissions;
+use super::plugin::{PluginDependency, PluginMetadata, PluginType, Version};
+use crate::video_compiler::error::{Result, VideoCompilerError};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use ed25519_dalek::{Signature, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+/// Имя файла манифеста плагина
+pub const MANIFEST_FILE_NAME: &str = "plugin.toml";
+
+/// Имя файла отсоединенной подписи манифеста
+pub const SIGNATURE_FILE_NAME: &str = "plugin.toml.sig";
+
+/// Публичный ключ издателя плагинов Timeline Studio (ed25519, base64)
+pub const PUBLISHER_PUBLIC_KEY: &str = "pYvKSoAJQFnhjfzSBB0lTmJHlq71cOcg49AzqqIccOs=";
+
+/// Переменная окружения, включающая режим разработчика плагинов
+pub const DEVELOPER_MODE_ENV: &str = "TIMELINE_STUDIO_PLUGIN_DEV_MODE";
+
+/// Корневая директория установленных плагинов
+pub fn plugins_root_dir() -> PathBuf {
+  dirs::data_dir()
+    .unwrap_or_else(|| PathBuf::from("."))
+    .join("timeline-studio")
+    .join("plugins")
+}
+
+/// Включен ли режим разработчика через переменную окружения
+pub fn developer_mode_from_env() -> bool {
+  std::env::var(DEVELOPER_MODE_ENV).is_ok_and(|value| matches!(value.as_str(), "1" | "true"))
+}
+
+/// Точка входа плагина
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PluginEntry {
+  /// Встроенная фабрика, скомпилированная в приложение
+  Factory { factory: String },
+  /// WASM модуль внутри директории плагина
+  Wasm { path: String, sha256: String },
+}
+
+/// Зависимость в манифесте
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestDependency {
+  pub plugin_id: String,
+  /// Диапазон версий (`^1.2.0`, `>=1.0.0 <2.0.0`)
+  #[serde(default)]
+  pub version_req: Option<String>,
+}
+
+/// Манифест плагина (`plugin.toml`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginManifest {
+  pub id: String,
+  pub name: String,
+  pub version: String,
+  #[serde(default)]
+  pub author: String,
+  #[serde(default)]
+  pub description: String,
+  pub plugin_type: PluginType,
+  #[serde(default)]
+  pub homepage: Option<String>,
+  #[serde(default)]
+  pub license: Option<String>,
+  #[serde(default)]
+  pub min_app_version: Option<String>,
+  #[serde(default)]
+  pub dependencies: Vec<ManifestDependency>,
+  pub entry: PluginEntry,
+  /// Максимальный набор разрешений, который можно выдать плагину
+  #[serde(default)]
+  pub permissions: PluginPermissions,
+}
+
+impl PluginManifest {
+  /// Разобрать манифест из TOML
+  pub fn parse(content: &str) -> Result<Self> {
+    let manifest: Self = toml::from_str(content).map_err(|e| {
+      VideoCompilerError::ValidationError(format!("Invalid plugin manifest: {e}").into())
+    })?;
+    manifest.validate()?;
+    Ok(manifest)
+  }
+
+  /// Проверить поля манифеста
+  fn validate(&self) -> Result<()> {
+    // ID используется как имя директории, поэтому допускаем только безопасные символы
+    if self.id.is_empty()
+      || self.id.len() > 100
+      || !self
+        .id
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+      return Err(VideoCompilerError::ValidationError(
+        format!("Invalid plugin id '{}'", self.id).into(),
+      ));
+    }
+
+    if Version::parse(&self.version).is_none() {
+      return Err(VideoCompilerError::ValidationError(
+        format!("Invalid plugin version '{}'", self.version).into(),
+      ));
+    }
+
+    if let Some(min_app_version) = &self.min_app_version {
+      if Version::parse(min_app_version).is_none() {
+        return Err(VideoCompilerError::ValidationError(
+          format!("Invalid min_app_version '{min_app_version}'").into(),
+        ));
+      }
+    }
+
+    if let PluginEntry::Wasm { path, .. } = &self.entry {
+      let relative = Path::new(path);
+      if relative.is_absolute()
+        || relative
+          .components()
+          .any(|c| matches!(c, std::path::Component::ParentDir))
+      {
+        return Err(VideoCompilerError::ValidationError(
+          format!("Plugin entry path '{path}' must stay inside the plugin directory").into(),
+        ));
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Преобразовать манифест в метаданные плагина
+  pub fn to_metadata(&self) -> PluginMetadata {
+    PluginMetadata {
+      id: self.id.clone(),
+      name: self.name.clone(),
+      version: Version::parse(&self.version).unwrap_or_else(|| Version::new(0, 0, 0)),
+      author: self.author.clone(),
+      description: self.description.clone(),
+      plugin_type: self.plugin_type,
+      homepage: self.homepage.clone(),
+      license: self.license.clone(),
+      dependencies: self
+        .dependencies
+        .iter()
+        .map(|dep| PluginDependency {
+          plugin_id: dep.plugin_id.clone(),
+          min_version: None,
+          max_version: None,
+          version_req: dep.version_req.clone(),
+        })
+        .collect(),
+      min_app_version: self.min_app_version.as_deref().and_then(Version::parse),
+    }
+  }
+}
+
+/// Результат проверки подписи плагина
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", content = "reason", rename_all = "snake_case")]
+pub enum PluginVerificationStatus {
+  /// Плагин скомпилирован в приложение
+  Builtin,
+  /// Подпись издателя проверена
+  Verified,
+  /// Файл подписи отсутствует
+  Unsigned,
+  /// Подпись или содержимое не прошли проверку
+  Invalid(String),
+}
+
+impl PluginVerificationStatus {
+  /// Можно ли доверять плагину без режима разработчика
+  pub fn is_trusted(&self) -> bool {
+    matches!(self, Self::Builtin | Self::Verified)
+  }
+}
+
+/// Проверка подписей манифестов набором доверенных ключей
+#[derive(Debug, Clone)]
+pub struct SignatureVerifier {
+  trusted_keys: Vec<VerifyingKey>,
+}
+
+impl SignatureVerifier {
+  /// Создать проверку с заданными ключами
+  pub fn new(trusted_keys: Vec<VerifyingKey>) -> Self {
+    Self { trusted_keys }
+  }
+
+  /// Проверка с ключом издателя, встроенным в приложение
+  pub fn bundled() -> Self {
+    let key = STANDARD
+      .decode(PUBLISHER_PUBLIC_KEY)
+      .ok()
+      .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+      .and_then(|bytes| VerifyingKey::from_bytes(&bytes).ok());
+
+    Self::new(key.into_iter().collect())
+  }
+
+  /// Проверить отсоединенную подпись (base64) для содержимого
+  pub fn verify(&self, payload: &[u8], signature_base64: &str) -> PluginVerificationStatus {
+    let Ok(bytes) = STANDARD.decode(signature_base64.trim()) else {
+      return PluginVerificationStatus::Invalid("Signature is not valid base64".to_string());
+    };
+    let Ok(signature) = Signature::from_slice(&bytes) else {
+      return PluginVerificationStatus::Invalid("Signature has invalid length".to_string());
+    };
+
+    if self
+      .trusted_keys
+      .iter()
+      .any(|key| key.verify_strict(payload, &signature).is_ok())
+    {
+      PluginVerificationStatus::Verified
+    } else {
+      PluginVerificationStatus::Invalid("Signature does not match a trusted publisher".to_string())
+    }
+  }
+}
+
+impl Default for SignatureVerifier {
+  fn default() -> Self {
+    Self::bundled()
+  }
+}
+
+/// Плагин, найденный в директории
+#[derive(Debug, Clone)]
+pub struct DiscoveredPlugin {
+  pub dir: PathBuf,
+  pub manifest: PluginManifest,
+  pub verification: PluginVerificationStatus,
+}
+
+/// Прочитать манифест из директории плагина и проверить подпись
+///
+/// Ошибка возвращается только если манифест нельзя прочитать или разобрать;
+/// проблемы с подписью отражаются в `verification`.
+pub fn read_plugin_dir(dir: &Path, verifier: &SignatureVerifier) -> Result<DiscoveredPlugin> {
+  let manifest_path = dir.join(MANIFEST_FILE_NAME);
+  let content = std::fs::read(&manifest_path).map_err(|e| {
+    VideoCompilerError::IoError(format!(
+      "Failed to read plugin manifest {}: {e}",
+      manifest_path.display()
+    ))
+  })?;
+  let text = std::str::from_utf8(&content).map_err(|_| {
+    VideoCompilerError::ValidationError("Plugin manifest is not valid UTF-8".into())
+  })?;
+  let manifest = PluginManifest::parse(text)?;
+
+  let verification = match std::fs::read_to_string(dir.join(SIGNATURE_FILE_NAME)) {
+    Ok(signature) => verifier.verify(&content, &signature),
+    Err(e) if e.kind() == std::io::ErrorKind::NotFound => PluginVerificationStatus::Unsigned,
+    Err(e) => PluginVerificationStatus::Invalid(format!("Failed to read signature: {e}")),
+  };
+
+  // Подпись манифеста покрывает код только через хеш модуля
+  let verification = match (&manifest.entry, verification) {
+    (
+      PluginEntry::Wasm { path, sha256 },
+      status @ (PluginVerificationStatus::Verified | PluginVerificationStatus::Unsigned),
+    ) => match std::fs::read(dir.join(path)) {
+      Ok(module) if hex_sha256(&module).eq_ignore_ascii_case(sha256) => status,
+      Ok(_) => PluginVerificationStatus::Invalid(format!("Entry '{path}' hash mismatch")),
+      Err(e) => PluginVerificationStatus::Invalid(format!("Failed to read entry '{path}': {e}")),
+    },
+    (_, status) => status,
+  };
+
+  Ok(DiscoveredPlugin {
+    dir: dir.to_path_buf(),
+    manifest,
+    verification,
+  })
+}
+
+fn hex_sha256(data: &[u8]) -> String {
+  Sha256::digest(data)
+    .iter()
+    .map(|byte| format!("{byte:02x}"))
+    .collect()
+}
+
+#[cfg(test)]
+pub(crate) mod test_support {
+  use super::*;
+  use ed25519_dalek::{Signer, SigningKey};
+
+  /// Детерминированный ключ издателя для тестов
+  pub fn signing_key() -> SigningKey {
+    SigningKey::from_bytes(&[7u8; 32])
+  }
+
+  pub fn verifier() -> SignatureVerifier {
+    SignatureVerifier::new(vec![signing_key().verifying_key()])
+  }
+
+  pub fn manifest_toml(id: &str, factory: &str) -> String {
+    format!(
+      r#"id = "{id}"
+name = "Test Plugin {id}"
+version = "1.2.0"
+author = "Test Publisher"
+description = "Plugin from directory"
+plugin_type = "Effect"
+
+[entry]
+type = "factory"
+factory = "{factory}"
+
+[permissions]
+ui_access = true
+
+[permissions.file_system]
+read_all = true
+"#
+    )
+  }
+
+  /// Записать плагин в директорию, при `sign` подписать манифест тестовым ключом
+  pub fn write_plugin(root: &Path, id: &str, factory: &str, sign: bool) -> PathBuf {
+    let dir = root.join(id);
+    std::fs::create_dir_all(&dir).unwrap();
+    let manifest = manifest_toml(id, factory);
+    std::fs::write(dir.join(MANIFEST_FILE_NAME), &manifest).unwrap();
+    if sign {
+      let signature = signing_key().sign(manifest.as_bytes());
+      std::fs::write(
+        dir.join(SIGNATURE_FILE_NAME),
+        STANDARD.encode(signature.to_bytes()),
+      )
+      .unwrap();
+    }
+    dir
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::test_support::*;
+  use super::*;
+  use ed25519_dalek::{Signer, SigningKey};
+  use tempfile::TempDir;
+
+  #[test]
+  fn test_parse_manifest() {
+    let manifest = PluginManifest::parse(&manifest_toml("community-blur", "blur-effect")).unwrap();
+    assert_eq!(manifest.id, "community-blur");
+    assert_eq!(
+      manifest.entry,
+      PluginEntry::Factory {
+        factory: "blur-effect".to_string()
+      }
+    );
+    assert!(manifest.permissions.ui_access);
+    assert!(manifest.permissions.file_system.read_all);
+    assert!(!manifest.permissions.process_spawn);
+
+    let metadata = manifest.to_metadata();
+    assert_eq!(metadata.version, Version::new(1, 2, 0));
+    assert_eq!(metadata.plugin_type, PluginType::Effect);
+  }
+
+  #[test]
+  fn test_parse_manifest_rejects_unsafe_id_and_entry_path() {
+    let unsafe_id = manifest_toml("../escape", "blur-effect");
+    assert!(PluginManifest::parse(&unsafe_id).is_err());
+
+    let unsafe_entry = manifest_toml("wasm-plugin", "blur-effect").replace(
+      "type = \"factory\"\nfactory = \"blur-effect\"",
+      "type = \"wasm\"\npath = \"../other/plugin.wasm\"\nsha256 = \"00\"",
+    );
+    assert!(PluginManifest::parse(&unsafe_entry).is_err());
+
+    assert!(PluginManifest::parse("id = \"broken").is_err());
+  }
+
+  #[test]
+  fn test_bundled_publisher_key_is_valid() {
+    assert_eq!(SignatureVerifier::bundled().trusted_keys.len(), 1);
+  }
+
+  #[test]
+  fn test_signed_plugin_is_verified() {
+    let temp = TempDir::new().unwrap();
+    let dir = write_plugin(temp.path(), "signed-plugin", "blur-effect", true);
+
+    let discovered = read_plugin_dir(&dir, &verifier()).unwrap();
+    assert_eq!(discovered.verification, PluginVerificationStatus::Verified);
+    assert!(discovered.verification.is_trusted());
+  }
+
+  #[test]
+  fn test_unsigned_plugin_is_reported() {
+    let temp = TempDir::new().unwrap();
+    let dir = write_plugin(temp.path(), "unsigned-plugin", "blur-effect", false);
+
+    let discovered = read_plugin_dir(&dir, &verifier()).unwrap();
+    assert_eq!(discovered.verification, PluginVerificationStatus::Unsigned);
+    assert!(!discovered.verification.is_trusted());
+  }
+
+  #[test]
+  fn test_tampered_manifest_fails_verification() {
+    let temp = TempDir::new().unwrap();
+    let dir = write_plugin(temp.path(), "tampered-plugin", "blur-effect", true);
+
+    // Расширяем разрешения после подписи
+    let manifest_path = dir.join(MANIFEST_FILE_NAME);
+    let tampered = std::fs::read_to_string(&manifest_path)
+      .unwrap()
+      .replace("ui_access = true", "ui_access = true\nprocess_spawn = true");
+    std::fs::write(&manifest_path, tampered).unwrap();
+
+    let discovered = read_plugin_dir(&dir, &verifier()).unwrap();
+    assert!(matches!(
+      discovered.verification,
+      PluginVerificationStatus::Invalid(_)
+    ));
+  }
+
+  #[test]
+  fn test_signature_from_untrusted_key_fails() {
+    let temp = TempDir::new().unwrap();
+    let dir = write_plugin(temp.path(), "foreign-plugin", "blur-effect", false);
+
+    let manifest = std::fs::read(dir.join(MANIFEST_FILE_NAME)).unwrap();
+    let foreign_key = SigningKey::from_bytes(&[42u8; 32]);
+    let signature = foreign_key.sign(&manifest);
+    std::fs::write(
+      dir.join(SIGNATURE_FILE_NAME),
+      STANDARD.encode(signature.to_bytes()),
+    )
+    .unwrap();
+
+    let discovered = read_plugin_dir(&dir, &verifier()).unwrap();
+    assert!(matches!(
+      discovered.verification,
+      PluginVerificationStatus::Invalid(_)
+    ));
+  }
+
+  #[test]
+  fn test_malformed_signature_fails() {
+    let status = verifier().verify(b"payload", "not base64!!!");
+    assert!(matches!(status, PluginVerificationStatus::Invalid(_)));
+
+    let status = verifier().verify(b"payload", &STANDARD.encode([1u8; 10]));
+    assert!(matches!(status, PluginVerificationStatus::Invalid(_)));
+  }
+
+  #[test]
+  fn test_wasm_entry_hash_is_checked() {
+    let temp = TempDir::new().unwrap();
+    let dir = temp.path().join("wasm-plugin");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("plugin.wasm"), b"\0asm module").unwrap();
+
+    let manifest = manifest_toml("wasm-plugin", "unused").replace(
+      "type = \"factory\"\nfactory = \"unused\"",
+      &format!(
+        "type = \"wasm\"\npath = \"plugin.wasm\"\nsha256 = \"{}\"",
+        hex_sha256(b"\0asm module")
+      ),
+    );
+    std::fs::write(dir.join(MANIFEST_FILE_NAME), &manifest).unwrap();
+    let signature = signing_key().sign(manifest.as_bytes());
+    std::fs::write(
+      dir.join(SIGNATURE_FILE_NAME),
+      STANDARD.encode(signature.to_bytes()),
+    )
+    .unwrap();
+
+    let discovered = read_plugin_dir(&dir, &verifier()).unwrap();
+    assert_eq!(discovered.verification, PluginVerificationStatus::Verified);
+
+    // Подменяем модуль - подпись манифеста остается валидной, но хеш уже нет
+    std::fs::write(dir.join("plugin.wasm"), b"\0asm evil").unwrap();
+    let discovered = read_plugin_dir(&dir, &verifier()).unwrap();
+    assert!(matches!(
+      discovered.verification,
+      PluginVerificationStatus::Invalid(_)
+    ));
+  }
+}
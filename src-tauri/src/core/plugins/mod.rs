@@ -7,8 +7,10 @@ pub mod api;
 pub mod api_factory;
 pub mod commands;
 pub mod context;
+pub mod dependencies;
 pub mod loader;
 pub mod manager;
+pub mod manifest;
 pub mod permissions;
 pub mod plugin;
 pub mod sandbox;
@@ -22,9 +24,10 @@ pub mod integration_tests_real_services;
 
 pub use context::PluginContext;
 pub use manager::PluginManager;
+pub use manifest::{PluginManifest, PluginVerificationStatus};
 pub use permissions::{PluginPermissions, SecurityLevel};
 pub use plugin::{
   AppEventType, Plugin, PluginCommand, PluginDependency, PluginMetadata, PluginResponse,
-  PluginType, Version,
+  PluginType, ResolvedDependency, Version,
 };
-pub use services::{MediaBridge, TimelineBridge, UIBridge};
+pub use services::{MediaBridge, NetworkGateway, NetworkRequest, TimelineBridge, UIBridge};
@@ -5,6 +5,7 @@ use std::path::{Path, PathBuf};
 
 /// Разрешения плагина
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
 pub struct PluginPermissions {
   /// Разрешения файловой системы
   pub file_system: FileSystemPermissions,
@@ -20,6 +21,10 @@ pub struct PluginPermissions {
 
   /// Возможность запускать процессы
   pub process_spawn: bool,
+
+  /// Изменение timeline пользователя (транзакции TimelineBridge)
+  #[serde(default)]
+  pub timeline_write: bool,
 }
 
 impl PluginPermissions {
@@ -31,6 +36,7 @@ impl PluginPermissions {
       ui_access: false,
       system_info: false,
       process_spawn: false,
+      timeline_write: false,
     }
   }
 
@@ -42,6 +48,7 @@ impl PluginPermissions {
       ui_access: false,
       system_info: true,
       process_spawn: false,
+      timeline_write: false,
     }
   }
 
@@ -53,6 +60,7 @@ impl PluginPermissions {
       ui_access: true,
       system_info: false,
       process_spawn: false,
+      timeline_write: false,
     }
   }
 
@@ -68,10 +76,26 @@ impl PluginPermissions {
       SecurityLevel::Minimal
     }
   }
+
+  /// Ограничить запрошенные разрешения максимально допустимым набором
+  ///
+  /// Используется для плагинов из каталога: разрешения, объявленные в манифесте,
+  /// являются верхней границей того, что можно выдать при загрузке.
+  pub fn restrict_to(&self, max: &PluginPermissions) -> PluginPermissions {
+    PluginPermissions {
+      file_system: self.file_system.restrict_to(&max.file_system),
+      network: self.network.restrict_to(&max.network),
+      ui_access: self.ui_access && max.ui_access,
+      system_info: self.system_info && max.system_info,
+      process_spawn: self.process_spawn && max.process_spawn,
+      timeline_write: self.timeline_write && max.timeline_write,
+    }
+  }
 }
 
 /// Разрешения файловой системы
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
 pub struct FileSystemPermissions {
   /// Пути для чтения
   pub read_paths: Vec<PathBuf>,
@@ -120,10 +144,45 @@ impl FileSystemPermissions {
       .iter()
       .any(|allowed| path.starts_with(allowed))
   }
+
+  /// Пересечь с максимально допустимыми разрешениями
+  pub fn restrict_to(&self, max: &FileSystemPermissions) -> FileSystemPermissions {
+    FileSystemPermissions {
+      read_paths: restrict_paths(self.read_all, &self.read_paths, &max.read_paths, |p| {
+        max.can_read(p)
+      }),
+      write_paths: restrict_paths(self.write_all, &self.write_paths, &max.write_paths, |p| {
+        max.can_write(p)
+      }),
+      read_all: self.read_all && max.read_all,
+      write_all: self.write_all && max.write_all,
+    }
+  }
+}
+
+/// Пути, остающиеся после пересечения запрошенных путей с допустимыми
+///
+/// Если запрошен доступ ко всему, остаются явные пути из максимального набора.
+fn restrict_paths(
+  requested_all: bool,
+  requested: &[PathBuf],
+  max_paths: &[PathBuf],
+  allowed: impl Fn(&Path) -> bool,
+) -> Vec<PathBuf> {
+  if requested_all {
+    return max_paths.to_vec();
+  }
+
+  requested
+    .iter()
+    .filter(|path| allowed(path))
+    .cloned()
+    .collect()
 }
 
 /// Разрешения сети
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct NetworkPermissions {
   /// Разрешенные хосты (домены/IP)
   pub allowed_hosts: Vec<String>,
@@ -196,6 +255,48 @@ impl NetworkPermissions {
       }
     })
   }
+
+  /// Пересечь с максимально допустимыми разрешениями
+  pub fn restrict_to(&self, max: &NetworkPermissions) -> NetworkPermissions {
+    if max.allow_all {
+      return self.clone();
+    }
+    if self.allow_all {
+      return max.clone();
+    }
+
+    let allowed_ports = if max.allowed_ports.is_empty() {
+      self.allowed_ports.clone()
+    } else if self.allowed_ports.is_empty() {
+      max.allowed_ports.clone()
+    } else {
+      self
+        .allowed_ports
+        .iter()
+        .filter(|port| max.allowed_ports.contains(port))
+        .copied()
+        .collect()
+    };
+
+    NetworkPermissions {
+      allowed_hosts: self
+        .allowed_hosts
+        .iter()
+        .filter(|host| {
+          max.allowed_hosts.iter().any(|allowed| {
+            *host == allowed
+              || allowed
+                .strip_prefix("*.")
+                .is_some_and(|domain| host.trim_start_matches("*.").ends_with(domain))
+          })
+        })
+        .cloned()
+        .collect(),
+      allowed_ports,
+      allow_all: false,
+      https_only: self.https_only || max.https_only,
+    }
+  }
 }
 
 /// Уровень безопасности плагина
@@ -228,6 +329,7 @@ impl SecurityLevel {
         ui_access: true,
         system_info: true,
         process_spawn: true,
+        timeline_write: true,
       },
     }
   }
@@ -264,6 +366,65 @@ mod tests {
     assert!(!perms.can_connect("api.example.com", 8080)); // Порт не разрешен
   }
 
+  #[test]
+  fn test_restrict_to_manifest_maximum() {
+    let max = PluginPermissions {
+      file_system: FileSystemPermissions {
+        read_paths: vec![PathBuf::from("/media")],
+        ..Default::default()
+      },
+      network: NetworkPermissions {
+        allowed_hosts: vec!["*.example.com".to_string()],
+        ..NetworkPermissions::default()
+      },
+      ui_access: true,
+      ..Default::default()
+    };
+
+    let requested = PluginPermissions {
+      file_system: FileSystemPermissions {
+        read_all: true,
+        write_all: true,
+        ..Default::default()
+      },
+      network: NetworkPermissions {
+        allowed_hosts: vec!["api.example.com".to_string(), "evil.org".to_string()],
+        ..NetworkPermissions::default()
+      },
+      ui_access: true,
+      process_spawn: true,
+      system_info: true,
+      timeline_write: true,
+    };
+
+    let granted = requested.restrict_to(&max);
+    assert!(!granted.file_system.read_all);
+    assert!(!granted.file_system.write_all);
+    assert_eq!(
+      granted.file_system.read_paths,
+      vec![PathBuf::from("/media")]
+    );
+    assert!(granted.file_system.write_paths.is_empty());
+    assert_eq!(granted.network.allowed_hosts, vec!["api.example.com"]);
+    assert!(granted.ui_access);
+    assert!(!granted.process_spawn);
+    assert!(!granted.system_info);
+    assert!(!granted.timeline_write);
+
+    // Пути внутри разрешенной директории сохраняются
+    let nested = PluginPermissions {
+      file_system: FileSystemPermissions {
+        read_paths: vec![PathBuf::from("/media/clips"), PathBuf::from("/etc")],
+        ..Default::default()
+      },
+      ..Default::default()
+    };
+    assert_eq!(
+      nested.restrict_to(&max).file_system.read_paths,
+      vec![PathBuf::from("/media/clips")]
+    );
+  }
+
   #[test]
   fn test_security_levels() {
     let minimal = SecurityLevel::Minimal.permissions();
@@ -479,6 +640,7 @@ mod tests {
       ui_access: true,
       system_info: false,
       process_spawn: false,
+      timeline_write: false,
     };
 
     // Test serialization
@@ -568,6 +730,7 @@ mod tests {
       ui_access: false,
       system_info: false,
       process_spawn: false,
+      timeline_write: false,
     };
     assert_eq!(empty_perms.get_security_level(), SecurityLevel::Minimal);
   }
@@ -47,6 +47,94 @@ impl Version {
       pre_release: None,
     }
   }
+
+  /// Разобрать версию вида `1.2.3` или `1.2.3-beta.1` (минор и патч можно опустить)
+  pub fn parse(value: &str) -> Option<Self> {
+    let (numbers, pre_release) = match value.trim().split_once('-') {
+      Some((numbers, pre)) => (numbers, Some(pre.to_string())),
+      None => (value.trim(), None),
+    };
+
+    let mut parts = numbers.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().map_or(Some(0), |p| p.parse().ok())?;
+    let patch = parts.next().map_or(Some(0), |p| p.parse().ok())?;
+    if parts.next().is_some() {
+      return None;
+    }
+
+    Some(Self {
+      major,
+      minor,
+      patch,
+      pre_release,
+    })
+  }
+
+  /// Проверить удовлетворяет ли версия диапазону
+  ///
+  /// Поддерживаются `*`, `^1.2.3`, `>=`, `>`, `<=`, `<`, `=` и точная версия.
+  /// Несколько условий через пробел объединяются по И: `>=1.0.0 <2.0.0`.
+  pub fn satisfies(&self, range: &str) -> bool {
+    range
+      .split_whitespace()
+      .all(|comparator| self.satisfies_comparator(comparator))
+  }
+
+  fn satisfies_comparator(&self, comparator: &str) -> bool {
+    if comparator == "*" {
+      return true;
+    }
+
+    let (op, version) = ["^", ">=", "<=", ">", "<", "="]
+      .iter()
+      .find_map(|op| comparator.strip_prefix(op).map(|rest| (*op, rest)))
+      .unwrap_or(("=", comparator));
+
+    let Some(required) = Version::parse(version) else {
+      return false;
+    };
+
+    match op {
+      "^" => *self >= required && *self < required.caret_upper_bound(),
+      ">=" => *self >= required,
+      "<=" => *self <= required,
+      ">" => *self > required,
+      "<" => *self < required,
+      _ => *self == required,
+    }
+  }
+
+  /// Первая несовместимая версия для `^`
+  fn caret_upper_bound(&self) -> Self {
+    if self.major > 0 {
+      Version::new(self.major + 1, 0, 0)
+    } else if self.minor > 0 {
+      Version::new(0, self.minor + 1, 0)
+    } else {
+      Version::new(0, 0, self.patch + 1)
+    }
+  }
+}
+
+impl PartialOrd for Version {
+  fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl Ord for Version {
+  fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+    (self.major, self.minor, self.patch)
+      .cmp(&(other.major, other.minor, other.patch))
+      .then_with(|| match (&self.pre_release, &other.pre_release) {
+        // Релиз старше любой pre-release версии
+        (None, None) => std::cmp::Ordering::Equal,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (Some(a), Some(b)) => a.cmp(b),
+      })
+  }
 }
 
 impl std::fmt::Display for Version {
@@ -65,6 +153,48 @@ pub struct PluginDependency {
   pub plugin_id: String,
   pub min_version: Option<Version>,
   pub max_version: Option<Version>,
+  /// Диапазон версий (`^1.2.0`, `>=1.0.0 <2.0.0`)
+  #[serde(default)]
+  pub version_req: Option<String>,
+}
+
+impl PluginDependency {
+  /// Проверить подходит ли версия зависимости под все ограничения
+  pub fn is_satisfied_by(&self, version: &Version) -> bool {
+    self.min_version.as_ref().is_none_or(|min| version >= min)
+      && self.max_version.as_ref().is_none_or(|max| version <= max)
+      && self
+        .version_req
+        .as_ref()
+        .is_none_or(|range| version.satisfies(range))
+  }
+
+  /// Человекочитаемое описание требований к версии
+  pub fn requirement(&self) -> String {
+    let mut parts = Vec::new();
+    if let Some(min) = &self.min_version {
+      parts.push(format!(">={min}"));
+    }
+    if let Some(max) = &self.max_version {
+      parts.push(format!("<={max}"));
+    }
+    if let Some(range) = &self.version_req {
+      parts.push(range.clone());
+    }
+
+    if parts.is_empty() {
+      "*".to_string()
+    } else {
+      parts.join(" ")
+    }
+  }
+}
+
+/// Разрешенная зависимость загруженного плагина
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub struct ResolvedDependency {
+  pub plugin_id: String,
+  pub version: Version,
 }
 
 /// Метаданные плагина
@@ -261,6 +391,44 @@ mod tests {
     assert_ne!(v1, v3);
   }
 
+  #[test]
+  fn test_version_parse_and_ordering() {
+    assert_eq!(Version::parse("1.2.3"), Some(Version::new(1, 2, 3)));
+    assert_eq!(Version::parse("2"), Some(Version::new(2, 0, 0)));
+    assert_eq!(
+      Version::parse("1.0.0-beta.1")
+        .unwrap()
+        .pre_release
+        .as_deref(),
+      Some("beta.1")
+    );
+    assert!(Version::parse("1.x").is_none());
+    assert!(Version::parse("1.2.3.4").is_none());
+
+    assert!(Version::new(1, 10, 0) > Version::new(1, 9, 9));
+    assert!(Version::parse("1.0.0-beta").unwrap() < Version::new(1, 0, 0));
+  }
+
+  #[test]
+  fn test_version_satisfies() {
+    let version = Version::new(1, 4, 2);
+
+    assert!(version.satisfies("*"));
+    assert!(version.satisfies("^1.2.0"));
+    assert!(!version.satisfies("^2.0.0"));
+    assert!(!version.satisfies("^1.5.0"));
+    assert!(version.satisfies(">=1.4.2"));
+    assert!(!version.satisfies(">=1.4.3"));
+    assert!(version.satisfies(">=1.0.0 <2.0.0"));
+    assert!(!version.satisfies(">1.0.0 <1.4.0"));
+    assert!(version.satisfies("1.4.2"));
+    assert!(!version.satisfies("not-a-version"));
+
+    // Для 0.x мажорной версии ^ фиксирует минор
+    assert!(Version::new(0, 3, 5).satisfies("^0.3.1"));
+    assert!(!Version::new(0, 4, 0).satisfies("^0.3.1"));
+  }
+
   #[test]
   fn test_plugin_type_serialization() {
     let plugin_type = PluginType::Effect;
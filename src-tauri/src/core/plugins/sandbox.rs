@@ -3,10 +3,12 @@
 use super::permissions::{FileSystemPermissions, PluginPermissions, SecurityLevel};
 use crate::video_compiler::error::{Result, VideoCompilerError};
 use std::collections::HashMap;
-use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
-use std::sync::Arc;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tokio::sync::{RwLock, Semaphore};
+use tokio_util::sync::CancellationToken;
 
 /// Лимиты ресурсов для плагина
 #[derive(Debug, Clone)]
@@ -28,6 +30,12 @@ pub struct ResourceLimits {
 
   /// Максимальное количество сетевых соединений
   pub max_network_connections: usize,
+
+  /// Максимальный сетевой трафик через шлюз за время работы плагина (в байтах)
+  pub max_network_bytes: u64,
+
+  /// Количество нарушений квот подряд, после которого плагин приостанавливается
+  pub max_consecutive_violations: u32,
 }
 
 impl ResourceLimits {
@@ -41,6 +49,8 @@ impl ResourceLimits {
         max_file_descriptors: 1000,
         max_api_calls_per_second: 1000,
         max_network_connections: 100,
+        max_network_bytes: 10 * 1024 * 1024 * 1024, // 10GB
+        max_consecutive_violations: 10,
       },
       SecurityLevel::Extended => Self {
         max_memory: 256 * 1024 * 1024,               // 256MB
@@ -49,6 +59,8 @@ impl ResourceLimits {
         max_file_descriptors: 100,
         max_api_calls_per_second: 100,
         max_network_connections: 10,
+        max_network_bytes: 2 * 1024 * 1024 * 1024, // 2GB
+        max_consecutive_violations: 5,
       },
       SecurityLevel::Standard => Self {
         max_memory: 64 * 1024 * 1024,                // 64MB
@@ -57,6 +69,8 @@ impl ResourceLimits {
         max_file_descriptors: 20,
         max_api_calls_per_second: 50,
         max_network_connections: 5,
+        max_network_bytes: 512 * 1024 * 1024, // 512MB
+        max_consecutive_violations: 3,
       },
       SecurityLevel::Minimal => Self {
         max_memory: 16 * 1024 * 1024,                // 16MB
@@ -65,6 +79,8 @@ impl ResourceLimits {
         max_file_descriptors: 10,
         max_api_calls_per_second: 10,
         max_network_connections: 1,
+        max_network_bytes: 32 * 1024 * 1024, // 32MB
+        max_consecutive_violations: 3,
       },
     }
   }
@@ -94,8 +110,20 @@ pub struct ResourceUsage {
   /// Количество активных сетевых соединений
   pub active_network_connections: AtomicU64,
 
+  /// Общее количество сетевых запросов
+  pub network_requests: AtomicU64,
+
+  /// Сетевой трафик (отправлено и получено), байт
+  pub network_bytes: AtomicU64,
+
   /// Флаг нарушения лимитов
   pub limits_violated: AtomicBool,
+
+  /// Общее количество нарушений квот
+  pub quota_violations: AtomicU64,
+
+  /// Количество команд подряд, завершившихся нарушением квоты
+  pub consecutive_violations: AtomicU32,
 }
 
 impl ResourceUsage {
@@ -108,7 +136,11 @@ impl ResourceUsage {
       api_calls_current_second: AtomicU64::new(0),
       last_api_reset: RwLock::new(Instant::now()),
       active_network_connections: AtomicU64::new(0),
+      network_requests: AtomicU64::new(0),
+      network_bytes: AtomicU64::new(0),
       limits_violated: AtomicBool::new(false),
+      quota_violations: AtomicU64::new(0),
+      consecutive_violations: AtomicU32::new(0),
     }
   }
 
@@ -128,6 +160,42 @@ impl Default for ResourceUsage {
   }
 }
 
+/// Кооперативная отмена команд плагина
+///
+/// Плагин получает текущий токен через `PluginContext` и проверяет его в долгих операциях.
+/// После отмены по тайм-ауту токен заменяется новым, чтобы следующие команды не были отменены.
+#[derive(Clone, Default)]
+pub struct PluginCancellation {
+  token: Arc<Mutex<CancellationToken>>,
+}
+
+impl PluginCancellation {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Получить токен текущей команды
+  pub fn token(&self) -> CancellationToken {
+    self.lock().clone()
+  }
+
+  /// Отменена ли текущая команда
+  pub fn is_cancelled(&self) -> bool {
+    self.lock().is_cancelled()
+  }
+
+  /// Отменить текущую команду и подготовить новый токен для следующих
+  pub fn cancel_and_renew(&self) {
+    let mut token = self.lock();
+    token.cancel();
+    *token = CancellationToken::new();
+  }
+
+  fn lock(&self) -> std::sync::MutexGuard<'_, CancellationToken> {
+    self.token.lock().unwrap_or_else(|e| e.into_inner())
+  }
+}
+
 /// Sandbox для изоляции плагина
 pub struct PluginSandbox {
   plugin_id: String,
@@ -141,13 +209,27 @@ pub struct PluginSandbox {
 
   /// Файловые разрешения
   file_permissions: FileSystemPermissions,
+
+  /// Разрешение на изменение timeline
+  timeline_write: bool,
+
+  /// Отмена выполняющейся команды
+  cancellation: PluginCancellation,
 }
 
 impl PluginSandbox {
   /// Создать новый sandbox
   pub fn new(plugin_id: String, permissions: &PluginPermissions) -> Self {
     let limits = ResourceLimits::for_security_level(permissions.get_security_level());
+    Self::with_limits(plugin_id, permissions, limits)
+  }
 
+  /// Создать sandbox с явно заданными лимитами
+  pub fn with_limits(
+    plugin_id: String,
+    permissions: &PluginPermissions,
+    limits: ResourceLimits,
+  ) -> Self {
     let operation_semaphore = Arc::new(Semaphore::new(limits.max_concurrent_operations));
     let network_semaphore = Arc::new(Semaphore::new(limits.max_network_connections));
 
@@ -163,6 +245,73 @@ impl PluginSandbox {
       network_semaphore,
       allowed_domains,
       file_permissions,
+      timeline_write: permissions.timeline_write,
+      cancellation: PluginCancellation::new(),
+    }
+  }
+
+  /// Выполнить команду плагина с ограничением по wall-clock времени
+  ///
+  /// При тайм-ауте текущий токен отмены срабатывает, а команда завершается
+  /// ошибкой `QuotaExceeded`. Счетчик нарушений подряд обновляется по итогам команды.
+  pub async fn run_command<F, T>(&self, command: F) -> Result<T>
+  where
+    F: Future<Output = Result<T>>,
+  {
+    let violations_before = self.usage.quota_violations.load(Ordering::Relaxed);
+    let timeout = self.limits.max_execution_time;
+    let start = Instant::now();
+
+    let result = match tokio::time::timeout(timeout, command).await {
+      Ok(result) => result,
+      Err(_) => {
+        self.cancellation.cancel_and_renew();
+        Err(self.quota_exceeded(
+          "execution_time_ms",
+          start.elapsed().as_millis() as u64,
+          timeout.as_millis() as u64,
+        ))
+      }
+    };
+
+    if self.usage.quota_violations.load(Ordering::Relaxed) > violations_before {
+      self
+        .usage
+        .consecutive_violations
+        .fetch_add(1, Ordering::Relaxed);
+    } else {
+      self
+        .usage
+        .consecutive_violations
+        .store(0, Ordering::Relaxed);
+    }
+
+    result
+  }
+
+  /// Зарезервировать память под буфер, выделяемый через API мосты
+  ///
+  /// Память освобождается при drop возвращенной резервации.
+  pub fn reserve_memory(&self, bytes: u64) -> Result<MemoryReservation> {
+    self.update_memory_usage(bytes)?;
+    Ok(MemoryReservation {
+      usage: self.usage.clone(),
+      bytes,
+    })
+  }
+
+  /// Зафиксировать нарушение квоты и построить ошибку
+  fn quota_exceeded(&self, resource: &str, requested: u64, limit: u64) -> VideoCompilerError {
+    self.usage.quota_violations.fetch_add(1, Ordering::Relaxed);
+    log::warn!(
+      "Plugin '{}' exceeded {resource} quota: {requested} > {limit}",
+      self.plugin_id
+    );
+    VideoCompilerError::QuotaExceeded {
+      plugin_id: self.plugin_id.clone(),
+      resource: resource.to_string(),
+      requested,
+      limit,
     }
   }
 
@@ -234,6 +383,18 @@ impl PluginSandbox {
     }
   }
 
+  /// Проверить разрешение на изменение timeline
+  pub fn check_timeline_write(&self) -> Result<()> {
+    if self.timeline_write {
+      Ok(())
+    } else {
+      Err(VideoCompilerError::SecurityError(format!(
+        "Plugin '{}' does not have timeline write permission",
+        self.plugin_id
+      )))
+    }
+  }
+
   /// Проверить разрешение на сетевой запрос
   pub async fn check_network_access(&self, domain: &str) -> Result<NetworkGuard> {
     // Проверяем домен в whitelist
@@ -266,6 +427,7 @@ impl PluginSandbox {
       .usage
       .active_network_connections
       .fetch_add(1, Ordering::Relaxed);
+    self.usage.network_requests.fetch_add(1, Ordering::Relaxed);
 
     Ok(NetworkGuard {
       sandbox: self,
@@ -273,6 +435,16 @@ impl PluginSandbox {
     })
   }
 
+  /// Учесть сетевой трафик плагина в квоте
+  pub fn record_network_transfer(&self, bytes: u64) -> Result<()> {
+    let total = self.usage.network_bytes.fetch_add(bytes, Ordering::Relaxed) + bytes;
+    if total > self.limits.max_network_bytes {
+      self.usage.limits_violated.store(true, Ordering::Relaxed);
+      return Err(self.quota_exceeded("network_bytes", total, self.limits.max_network_bytes));
+    }
+    Ok(())
+  }
+
   /// Обновить использование памяти
   pub fn update_memory_usage(&self, bytes: u64) -> Result<()> {
     let current_memory = self.usage.memory_used.load(Ordering::Relaxed);
@@ -280,10 +452,7 @@ impl PluginSandbox {
 
     if new_memory > self.limits.max_memory {
       self.usage.limits_violated.store(true, Ordering::Relaxed);
-      return Err(VideoCompilerError::SecurityError(format!(
-        "Plugin '{}' exceeded memory limit: {} > {}",
-        self.plugin_id, new_memory, self.limits.max_memory
-      )));
+      return Err(self.quota_exceeded("memory_bytes", new_memory, self.limits.max_memory));
     }
 
     self.usage.memory_used.store(new_memory, Ordering::Relaxed);
@@ -323,19 +492,65 @@ impl PluginSandbox {
         .active_network_connections
         .load(Ordering::Relaxed),
       network_connection_limit: self.limits.max_network_connections as u64,
+      network_requests: self.usage.network_requests.load(Ordering::Relaxed),
+      network_bytes: self.usage.network_bytes.load(Ordering::Relaxed),
+      network_bytes_limit: self.limits.max_network_bytes,
       limits_violated: self.usage.limits_violated.load(Ordering::Relaxed),
+      execution_time_limit_ms: self.limits.max_execution_time.as_millis() as u64,
+      quota_violations: self.usage.quota_violations.load(Ordering::Relaxed),
+      consecutive_violations: self.usage.consecutive_violations.load(Ordering::Relaxed),
     }
   }
 
   /// Сбросить нарушения лимитов (для восстановления после устранения проблемы)
   pub fn reset_violation_flag(&self) {
     self.usage.limits_violated.store(false, Ordering::Relaxed);
+    self
+      .usage
+      .consecutive_violations
+      .store(0, Ordering::Relaxed);
+  }
+
+  /// Превышен ли порог нарушений квот подряд
+  pub fn should_suspend(&self) -> bool {
+    self.usage.consecutive_violations.load(Ordering::Relaxed)
+      >= self.limits.max_consecutive_violations
   }
 
   /// Получить лимиты
   pub fn limits(&self) -> &ResourceLimits {
     &self.limits
   }
+
+  /// Получить handle отмены команд плагина
+  pub fn cancellation(&self) -> PluginCancellation {
+    self.cancellation.clone()
+  }
+}
+
+/// Резервация памяти в квоте плагина, освобождается при drop
+#[derive(Debug)]
+pub struct MemoryReservation {
+  usage: Arc<ResourceUsage>,
+  bytes: u64,
+}
+
+impl MemoryReservation {
+  /// Размер зарезервированной памяти
+  pub fn bytes(&self) -> u64 {
+    self.bytes
+  }
+}
+
+impl Drop for MemoryReservation {
+  fn drop(&mut self) {
+    let _ = self
+      .usage
+      .memory_used
+      .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |current| {
+        Some(current.saturating_sub(self.bytes))
+      });
+  }
 }
 
 /// Guard для операций, автоматически освобождает ресурсы
@@ -402,7 +617,13 @@ pub struct SandboxStats {
   pub api_rate_limit: u64,
   pub active_network_connections: u64,
   pub network_connection_limit: u64,
+  pub network_requests: u64,
+  pub network_bytes: u64,
+  pub network_bytes_limit: u64,
   pub limits_violated: bool,
+  pub execution_time_limit_ms: u64,
+  pub quota_violations: u64,
+  pub consecutive_violations: u32,
 }
 
 impl SandboxStats {
@@ -437,6 +658,9 @@ impl SandboxStats {
 /// Менеджер sandbox для всех плагинов
 pub struct SandboxManager {
   sandboxes: Arc<RwLock<HashMap<String, Arc<PluginSandbox>>>>,
+
+  /// Лимиты, заданные явно для отдельных плагинов
+  limit_overrides: Arc<RwLock<HashMap<String, ResourceLimits>>>,
 }
 
 impl SandboxManager {
@@ -444,16 +668,34 @@ impl SandboxManager {
   pub fn new() -> Self {
     Self {
       sandboxes: Arc::new(RwLock::new(HashMap::new())),
+      limit_overrides: Arc::new(RwLock::new(HashMap::new())),
     }
   }
 
+  /// Задать лимиты плагина вместо лимитов по уровню безопасности
+  ///
+  /// Применяется при следующем создании sandbox для плагина.
+  pub async fn set_limits_override(&self, plugin_id: &str, limits: ResourceLimits) {
+    let mut overrides = self.limit_overrides.write().await;
+    overrides.insert(plugin_id.to_string(), limits);
+  }
+
   /// Создать sandbox для плагина
   pub async fn create_sandbox(
     &self,
     plugin_id: String,
     permissions: &PluginPermissions,
   ) -> Arc<PluginSandbox> {
-    let sandbox = Arc::new(PluginSandbox::new(plugin_id.clone(), permissions));
+    let limits = {
+      let overrides = self.limit_overrides.read().await;
+      overrides.get(&plugin_id).cloned()
+    }
+    .unwrap_or_else(|| ResourceLimits::for_security_level(permissions.get_security_level()));
+    let sandbox = Arc::new(PluginSandbox::with_limits(
+      plugin_id.clone(),
+      permissions,
+      limits,
+    ));
 
     let mut sandboxes = self.sandboxes.write().await;
     sandboxes.insert(plugin_id.clone(), sandbox.clone());
@@ -525,6 +767,7 @@ mod tests {
       ui_access: false,
       system_info: false,
       process_spawn: false,
+      timeline_write: false,
     };
 
     let sandbox = PluginSandbox::new("test_plugin".to_string(), &permissions);
@@ -559,6 +802,69 @@ mod tests {
     assert!(result.is_err());
   }
 
+  #[tokio::test]
+  async fn test_command_timeout_cancels_and_counts_violations() {
+    let permissions = PluginPermissions::default();
+    let mut sandbox = PluginSandbox::new("test".to_string(), &permissions);
+    sandbox.limits.max_execution_time = Duration::from_millis(20);
+    sandbox.limits.max_consecutive_violations = 2;
+
+    let token = sandbox.cancellation().token();
+    let result = sandbox
+      .run_command(async {
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        Ok(())
+      })
+      .await;
+
+    assert!(matches!(
+      result,
+      Err(VideoCompilerError::QuotaExceeded { ref resource, .. }) if resource == "execution_time_ms"
+    ));
+    assert!(token.is_cancelled());
+    // Следующая команда получает свежий токен
+    assert!(!sandbox.cancellation().is_cancelled());
+    assert!(!sandbox.should_suspend());
+
+    let _ = sandbox
+      .run_command(async {
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        Ok(())
+      })
+      .await;
+    assert!(sandbox.should_suspend());
+
+    // Успешная команда сбрасывает счетчик нарушений подряд
+    sandbox.run_command(async { Ok(()) }).await.unwrap();
+    assert!(!sandbox.should_suspend());
+    assert_eq!(sandbox.get_usage_stats().await.quota_violations, 2);
+  }
+
+  #[tokio::test]
+  async fn test_oversized_buffer_reservation() {
+    let permissions = PluginPermissions::default();
+    let mut sandbox = PluginSandbox::new("test".to_string(), &permissions);
+    sandbox.limits.max_memory = 1024;
+
+    let reservation = sandbox.reserve_memory(512).unwrap();
+    assert_eq!(reservation.bytes(), 512);
+
+    let result = sandbox.reserve_memory(1024);
+    assert!(matches!(
+      result,
+      Err(VideoCompilerError::QuotaExceeded {
+        requested: 1536,
+        limit: 1024,
+        ..
+      })
+    ));
+
+    // Резервация освобождает память при drop
+    drop(reservation);
+    assert_eq!(sandbox.usage.memory_used.load(Ordering::Relaxed), 0);
+    assert_eq!(sandbox.usage.memory_peak.load(Ordering::Relaxed), 512);
+  }
+
   #[tokio::test]
   async fn test_memory_limits() {
     let permissions = PluginPermissions::default();
@@ -596,6 +902,8 @@ mod tests {
       network_semaphore: Arc::new(Semaphore::new(5)),
       allowed_domains: vec![],
       file_permissions: permissions.file_system,
+      timeline_write: false,
+      cancellation: PluginCancellation::new(),
     };
 
     // Первые два permit должны пройти
@@ -633,6 +941,8 @@ mod tests {
       network_semaphore: Arc::new(Semaphore::new(2)),
       allowed_domains: vec!["*.example.com".to_string()],
       file_permissions: permissions.file_system,
+      timeline_write: false,
+      cancellation: PluginCancellation::new(),
     };
 
     // Первые два соединения должны пройти
@@ -991,7 +1301,13 @@ mod tests {
       api_rate_limit: 0,
       active_network_connections: 2,
       network_connection_limit: 0,
+      network_requests: 0,
+      network_bytes: 0,
+      network_bytes_limit: 0,
       limits_violated: false,
+      execution_time_limit_ms: 0,
+      quota_violations: 0,
+      consecutive_violations: 0,
     };
 
     // Проверяем что деление на ноль обрабатывается корректно
@@ -1097,6 +1413,8 @@ mod tests {
       network_semaphore: Arc::new(Semaphore::new(5)),
       allowed_domains: vec![],
       file_permissions: permissions.file_system,
+      timeline_write: false,
+      cancellation: PluginCancellation::new(),
     });
 
     // Запускаем несколько параллельных задач для API вызовов
@@ -1328,6 +1646,8 @@ mod tests {
       network_semaphore: Arc::new(Semaphore::new(5)),
       allowed_domains: vec![],
       file_permissions: permissions.file_system,
+      timeline_write: false,
+      cancellation: PluginCancellation::new(),
     };
 
     // Получаем первый permit
@@ -1381,6 +1701,7 @@ mod tests {
       ui_access: true,
       system_info: false,
       process_spawn: false,
+      timeline_write: false,
     };
 
     let sandbox = PluginSandbox::new("test".to_string(), &permissions);
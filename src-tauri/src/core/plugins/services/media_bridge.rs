@@ -6,6 +6,7 @@ use crate::{
     plugins::{
       api::{Effect, MediaInfo},
       permissions::PluginPermissions,
+      sandbox::PluginSandbox,
     },
   },
   video_compiler::{
@@ -21,6 +22,7 @@ pub struct MediaBridge {
   service_container: Arc<ServiceContainer>,
   permissions: Arc<PluginPermissions>,
   plugin_id: String,
+  sandbox: Option<Arc<PluginSandbox>>,
 }
 
 impl MediaBridge {
@@ -34,9 +36,16 @@ impl MediaBridge {
       service_container,
       permissions,
       plugin_id,
+      sandbox: None,
     }
   }
 
+  /// Учитывать медиа буферы в квоте памяти sandbox
+  pub fn with_sandbox(mut self, sandbox: Arc<PluginSandbox>) -> Self {
+    self.sandbox = Some(sandbox);
+    self
+  }
+
   /// Получить информацию о медиа файле
   pub async fn get_media_info(&self, media_id: &str) -> Result<MediaInfo> {
     // Проверяем разрешения на чтение медиа
@@ -236,6 +245,11 @@ impl MediaBridge {
         create_placeholder_thumbnail()
       };
 
+    let _reservation = match &self.sandbox {
+      Some(sandbox) => Some(sandbox.reserve_memory(thumbnail_data.len() as u64)?),
+      None => None,
+    };
+
     tokio::fs::write(output_path, thumbnail_data)
       .await
       .map_err(|e| VideoCompilerError::IoError(e.to_string()))?;
@@ -1,9 +1,15 @@
 //! Сервисы-мосты для интеграции плагинов с основными сервисами Timeline Studio
 
 pub mod media_bridge;
+pub mod network_gateway;
 pub mod timeline_bridge;
+pub mod timeline_transaction;
 pub mod ui_bridge;
 
 pub use media_bridge::MediaBridge;
+pub use network_gateway::{
+  NetworkGateway, NetworkGatewayConfig, NetworkGatewayStats, NetworkOutcome, NetworkRequest,
+};
 pub use timeline_bridge::TimelineBridge;
+pub use timeline_transaction::{ClipSpec, TimelineMutation, TimelineTransaction};
 pub use ui_bridge::UIBridge;
@@ -0,0 +1,1157 @@
+//! Сетевой шлюз для HTTP запросов плагинов
+//!
+//! Плагины с сетевыми разрешениями выполняют запросы через общий шлюз. Шлюз
+//! повторяет идемпотентные запросы с экспоненциальной задержкой и jitter,
+//! размыкает цепь для хоста после серии сбоев и сохраняет помеченные POST/PUT
+//! запросы в офлайн-очередь, которая отправляется после восстановления сети.
+
+use crate::{
+  core::plugins::{permissions::NetworkPermissions, sandbox::PluginSandbox},
+  video_compiler::error::{Result, VideoCompilerError},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+  collections::HashMap,
+  path::{Path, PathBuf},
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex, MutexGuard,
+  },
+  time::{Duration, Instant},
+};
+
+/// HTTP метод запроса плагина
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum HttpMethod {
+  Get,
+  Head,
+  Post,
+  Put,
+  Delete,
+}
+
+impl HttpMethod {
+  /// Можно ли безопасно повторить запрос
+  pub fn is_idempotent(self) -> bool {
+    !matches!(self, HttpMethod::Post)
+  }
+
+  fn as_reqwest(self) -> reqwest::Method {
+    match self {
+      HttpMethod::Get => reqwest::Method::GET,
+      HttpMethod::Head => reqwest::Method::HEAD,
+      HttpMethod::Post => reqwest::Method::POST,
+      HttpMethod::Put => reqwest::Method::PUT,
+      HttpMethod::Delete => reqwest::Method::DELETE,
+    }
+  }
+}
+
+/// HTTP запрос плагина
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NetworkRequest {
+  pub method: HttpMethod,
+  pub url: String,
+  #[serde(default)]
+  pub headers: Vec<(String, String)>,
+  #[serde(default)]
+  pub body: Option<Vec<u8>>,
+  /// Сохранить POST/PUT запрос в офлайн-очередь, если сеть недоступна
+  #[serde(default)]
+  pub queueable: bool,
+  /// Тайм-аут одной попытки вместо тайм-аута шлюза
+  #[serde(default)]
+  pub timeout_ms: Option<u64>,
+}
+
+impl NetworkRequest {
+  /// Создать запрос
+  pub fn new(method: HttpMethod, url: impl Into<String>) -> Self {
+    Self {
+      method,
+      url: url.into(),
+      headers: Vec::new(),
+      body: None,
+      queueable: false,
+      timeout_ms: None,
+    }
+  }
+
+  pub fn get(url: impl Into<String>) -> Self {
+    Self::new(HttpMethod::Get, url)
+  }
+
+  pub fn post(url: impl Into<String>) -> Self {
+    Self::new(HttpMethod::Post, url)
+  }
+
+  pub fn put(url: impl Into<String>) -> Self {
+    Self::new(HttpMethod::Put, url)
+  }
+
+  pub fn delete(url: impl Into<String>) -> Self {
+    Self::new(HttpMethod::Delete, url)
+  }
+
+  /// Добавить заголовок
+  pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+    self.headers.push((name.into(), value.into()));
+    self
+  }
+
+  /// Задать тело запроса
+  pub fn body(mut self, body: impl Into<Vec<u8>>) -> Self {
+    self.body = Some(body.into());
+    self
+  }
+
+  /// Задать JSON тело запроса
+  pub fn json(self, value: &serde_json::Value) -> Self {
+    self
+      .header("content-type", "application/json")
+      .body(value.to_string())
+  }
+
+  /// Разрешить отложенную отправку через офлайн-очередь
+  pub fn queueable(mut self) -> Self {
+    self.queueable = true;
+    self
+  }
+
+  /// Задать тайм-аут одной попытки
+  pub fn timeout(mut self, timeout: Duration) -> Self {
+    self.timeout_ms = Some(timeout.as_millis() as u64);
+    self
+  }
+
+  /// Можно ли поставить запрос в офлайн-очередь
+  pub fn can_queue(&self) -> bool {
+    self.queueable && matches!(self.method, HttpMethod::Post | HttpMethod::Put)
+  }
+
+  fn body_len(&self) -> u64 {
+    self.body.as_ref().map_or(0, |body| body.len() as u64)
+  }
+
+  /// Схема, хост и порт запроса
+  fn target(&self) -> Result<(String, String, u16)> {
+    let url = reqwest::Url::parse(&self.url).map_err(|e| {
+      VideoCompilerError::InvalidParameter(format!("Invalid URL '{}': {e}", self.url))
+    })?;
+    let host = url
+      .host_str()
+      .ok_or_else(|| {
+        VideoCompilerError::InvalidParameter(format!("URL has no host: {}", self.url))
+      })?
+      .to_string();
+    let port = url.port_or_known_default().unwrap_or(443);
+    Ok((url.scheme().to_string(), host, port))
+  }
+}
+
+/// Ответ на запрос плагина
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NetworkResponse {
+  pub status: u16,
+  pub headers: Vec<(String, String)>,
+  pub body: Vec<u8>,
+}
+
+impl NetworkResponse {
+  /// Успешный ли статус ответа (2xx)
+  pub fn is_success(&self) -> bool {
+    (200..300).contains(&self.status)
+  }
+
+  /// Разобрать тело ответа как JSON
+  pub fn json<T: serde::de::DeserializeOwned>(&self) -> Result<T> {
+    serde_json::from_slice(&self.body)
+      .map_err(|e| VideoCompilerError::SerializationError(e.to_string()))
+  }
+
+  /// Стоит ли повторить запрос с таким ответом
+  fn is_retryable(&self) -> bool {
+    self.status == 429 || self.status >= 500
+  }
+}
+
+/// Результат запроса через шлюз
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NetworkOutcome {
+  /// Запрос выполнен
+  Completed(NetworkResponse),
+  /// Сеть недоступна, запрос сохранен в офлайн-очередь
+  Queued { queue_id: String },
+}
+
+/// Политика повторов с экспоненциальной задержкой
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+  /// Максимальное количество попыток, включая первую
+  pub max_attempts: u32,
+  /// Задержка перед первым повтором
+  pub initial_delay: Duration,
+  /// Верхняя граница задержки
+  pub max_delay: Duration,
+  /// Множитель задержки для каждого следующего повтора
+  pub multiplier: f64,
+  /// Доля случайного отклонения задержки (0.0 - 1.0)
+  pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+  fn default() -> Self {
+    Self {
+      max_attempts: 4,
+      initial_delay: Duration::from_millis(200),
+      max_delay: Duration::from_secs(10),
+      multiplier: 2.0,
+      jitter: 0.2,
+    }
+  }
+}
+
+impl RetryPolicy {
+  /// Задержка перед повтором с номером `retry` (с нуля)
+  pub fn delay_for(&self, retry: u32) -> Duration {
+    let base = self.initial_delay.as_secs_f64() * self.multiplier.powi(retry as i32);
+    let base = base.min(self.max_delay.as_secs_f64());
+    let jitter = base * self.jitter * (rand::random::<f64>() * 2.0 - 1.0);
+    Duration::from_secs_f64((base + jitter).max(0.0))
+  }
+}
+
+/// Настройки размыкания цепи для хоста
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerConfig {
+  /// Количество сбоев подряд, после которого цепь размыкается
+  pub failure_threshold: u32,
+  /// Время, на которое цепь размыкается перед пробным запросом
+  pub open_duration: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+  fn default() -> Self {
+    Self {
+      failure_threshold: 5,
+      open_duration: Duration::from_secs(30),
+    }
+  }
+}
+
+/// Настройки сетевого шлюза
+#[derive(Debug, Clone)]
+pub struct NetworkGatewayConfig {
+  pub retry: RetryPolicy,
+  pub circuit_breaker: CircuitBreakerConfig,
+  /// Тайм-аут одной попытки по умолчанию
+  pub request_timeout: Duration,
+  /// Файл офлайн-очереди; без него очередь живет только в памяти
+  pub queue_path: Option<PathBuf>,
+  /// Адрес легкой проверки доступности сети
+  pub probe_url: String,
+  pub probe_timeout: Duration,
+  /// Интервал проверки сети, пока в очереди есть запросы
+  pub probe_interval: Duration,
+}
+
+impl Default for NetworkGatewayConfig {
+  fn default() -> Self {
+    Self {
+      retry: RetryPolicy::default(),
+      circuit_breaker: CircuitBreakerConfig::default(),
+      request_timeout: Duration::from_secs(30),
+      queue_path: None,
+      probe_url: "https://www.gstatic.com/generate_204".to_string(),
+      probe_timeout: Duration::from_secs(5),
+      probe_interval: Duration::from_secs(30),
+    }
+  }
+}
+
+/// Состояние цепи хоста
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+  Closed,
+  Open,
+  HalfOpen,
+}
+
+#[derive(Debug, Default)]
+struct HostCircuit {
+  consecutive_failures: u32,
+  opened_at: Option<Instant>,
+  /// Пробный запрос после размыкания уже выполняется
+  trial_in_flight: bool,
+}
+
+/// Запрос в офлайн-очереди
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedNetworkRequest {
+  pub id: String,
+  pub plugin_id: String,
+  pub request: NetworkRequest,
+  pub queued_at: DateTime<Utc>,
+  pub attempts: u32,
+}
+
+/// Сетевая активность плагина
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PluginNetworkUsage {
+  pub plugin_id: String,
+  pub requests: u64,
+  pub completed: u64,
+  pub failed: u64,
+  pub retries: u64,
+  pub queued: u64,
+  pub delivered_from_queue: u64,
+  pub bytes_sent: u64,
+  pub bytes_received: u64,
+}
+
+/// Состояние цепи хоста для статистики
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostCircuitStatus {
+  pub host: String,
+  pub state: CircuitState,
+  pub consecutive_failures: u32,
+}
+
+/// Статистика сетевого шлюза
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkGatewayStats {
+  pub online: bool,
+  pub queue_length: usize,
+  pub total_requests: u64,
+  pub total_retries: u64,
+  pub circuits: Vec<HostCircuitStatus>,
+  pub plugins: Vec<PluginNetworkUsage>,
+}
+
+/// Причина, по которой запрос не дошел до сервера
+enum DeliveryError {
+  /// Сетевая ошибка после всех попыток
+  Transport(String),
+  /// Цепь для хоста разомкнута
+  CircuitOpen,
+}
+
+impl DeliveryError {
+  fn into_error(self, host: &str) -> VideoCompilerError {
+    match self {
+      DeliveryError::Transport(e) => {
+        VideoCompilerError::IoError(format!("Network request to '{host}' failed: {e}"))
+      }
+      DeliveryError::CircuitOpen => VideoCompilerError::IoError(format!(
+        "Network requests to '{host}' are paused after repeated failures"
+      )),
+    }
+  }
+}
+
+/// Общий шлюз сетевых запросов плагинов
+pub struct NetworkGateway {
+  client: reqwest::Client,
+  config: NetworkGatewayConfig,
+  circuits: Mutex<HashMap<String, HostCircuit>>,
+  queue: tokio::sync::Mutex<Vec<QueuedNetworkRequest>>,
+  usage: Mutex<HashMap<String, PluginNetworkUsage>>,
+  online: AtomicBool,
+  draining: AtomicBool,
+}
+
+impl NetworkGateway {
+  /// Создать шлюз и загрузить сохраненную офлайн-очередь
+  pub fn new(config: NetworkGatewayConfig) -> Self {
+    let queue = config
+      .queue_path
+      .as_ref()
+      .and_then(|path| std::fs::read(path).ok())
+      .and_then(|data| match serde_json::from_slice(&data) {
+        Ok(queue) => Some(queue),
+        Err(e) => {
+          log::warn!("Failed to read plugin network queue: {e}");
+          None
+        }
+      })
+      .unwrap_or_default();
+
+    Self {
+      client: reqwest::Client::new(),
+      config,
+      circuits: Mutex::new(HashMap::new()),
+      queue: tokio::sync::Mutex::new(queue),
+      usage: Mutex::new(HashMap::new()),
+      online: AtomicBool::new(true),
+      draining: AtomicBool::new(false),
+    }
+  }
+
+  /// Выполнить запрос плагина
+  ///
+  /// Разрешения и квоты sandbox проверяются до отправки. Если сервер
+  /// недоступен, запрос с `queueable` сохраняется в офлайн-очередь.
+  pub async fn send(
+    &self,
+    plugin_id: &str,
+    permissions: &NetworkPermissions,
+    sandbox: Option<&PluginSandbox>,
+    request: NetworkRequest,
+  ) -> Result<NetworkOutcome> {
+    let (scheme, host, port) = request.target()?;
+    if permissions.https_only && !permissions.allow_all && scheme != "https" {
+      return Err(VideoCompilerError::SecurityError(format!(
+        "Plugin '{plugin_id}' may only use HTTPS: {}",
+        request.url
+      )));
+    }
+    if !permissions.can_connect(&host, port) {
+      return Err(VideoCompilerError::SecurityError(format!(
+        "Plugin '{plugin_id}' is not allowed to connect to {host}:{port}"
+      )));
+    }
+
+    let _guard = match sandbox {
+      Some(sandbox) => {
+        let guard = sandbox.check_network_access(&host).await?;
+        sandbox.record_network_transfer(request.body_len())?;
+        Some(guard)
+      }
+      None => None,
+    };
+
+    self.account(plugin_id, |usage| {
+      usage.requests += 1;
+      usage.bytes_sent += request.body_len();
+    });
+
+    match self.deliver(plugin_id, &host, &request).await {
+      Ok(response) => {
+        let received = response.body.len() as u64;
+        self.account(plugin_id, |usage| {
+          usage.completed += 1;
+          usage.bytes_received += received;
+        });
+        if let Some(sandbox) = sandbox {
+          sandbox.record_network_transfer(received)?;
+        }
+        Ok(NetworkOutcome::Completed(response))
+      }
+      Err(error) if request.can_queue() => {
+        if let DeliveryError::Transport(e) = &error {
+          log::warn!("[NetworkGateway {plugin_id}] Queueing request to '{host}': {e}");
+          self.online.store(false, Ordering::Relaxed);
+        }
+        let queue_id = self.enqueue(plugin_id, request).await;
+        Ok(NetworkOutcome::Queued { queue_id })
+      }
+      Err(error) => {
+        self.account(plugin_id, |usage| usage.failed += 1);
+        Err(error.into_error(&host))
+      }
+    }
+  }
+
+  /// Проверить доступность сети легким запросом
+  pub async fn probe_connectivity(&self) -> bool {
+    let online = self
+      .client
+      .head(&self.config.probe_url)
+      .timeout(self.config.probe_timeout)
+      .send()
+      .await
+      .is_ok();
+    self.online.store(online, Ordering::Relaxed);
+    online
+  }
+
+  /// Отправить запросы офлайн-очереди, если сеть доступна
+  ///
+  /// Возвращает количество доставленных запросов. Отправка прекращается на
+  /// первой сетевой ошибке, недоставленные запросы остаются в очереди.
+  pub async fn drain_queue(&self) -> usize {
+    if self.draining.swap(true, Ordering::AcqRel) {
+      return 0;
+    }
+
+    let pending = self.queue.lock().await.clone();
+    let mut finished = Vec::new();
+    let mut attempted = Vec::new();
+
+    if !pending.is_empty() && self.probe_connectivity().await {
+      for queued in &pending {
+        let Ok((_, host, _)) = queued.request.target() else {
+          finished.push(queued.id.clone());
+          continue;
+        };
+        attempted.push(queued.id.clone());
+        match self
+          .deliver(&queued.plugin_id, &host, &queued.request)
+          .await
+        {
+          Ok(response) if response.is_retryable() => {}
+          Ok(response) => {
+            if !response.is_success() {
+              log::warn!(
+                "[NetworkGateway {}] Queued request {} rejected with status {}",
+                queued.plugin_id,
+                queued.id,
+                response.status
+              );
+            }
+            let received = response.body.len() as u64;
+            self.account(&queued.plugin_id, |usage| {
+              usage.delivered_from_queue += 1;
+              usage.bytes_received += received;
+            });
+            finished.push(queued.id.clone());
+          }
+          Err(_) => break,
+        }
+      }
+    }
+
+    let delivered = finished.len();
+    if !attempted.is_empty() || !finished.is_empty() {
+      let mut queue = self.queue.lock().await;
+      queue.retain(|queued| !finished.contains(&queued.id));
+      for queued in queue.iter_mut() {
+        if attempted.contains(&queued.id) {
+          queued.attempts += 1;
+        }
+      }
+      self.persist_queue(&queue).await;
+    }
+
+    self.draining.store(false, Ordering::Release);
+    delivered
+  }
+
+  /// Периодически проверять сеть и отправлять офлайн-очередь
+  pub fn spawn_connectivity_monitor(self: Arc<Self>) {
+    tauri::async_runtime::spawn(async move {
+      loop {
+        tokio::time::sleep(self.config.probe_interval).await;
+        if !self.queue.lock().await.is_empty() {
+          let delivered = self.drain_queue().await;
+          if delivered > 0 {
+            log::info!("[NetworkGateway] Delivered {delivered} queued plugin requests");
+          }
+        }
+      }
+    });
+  }
+
+  /// Количество запросов в офлайн-очереди
+  pub async fn queue_len(&self) -> usize {
+    self.queue.lock().await.len()
+  }
+
+  /// Состояние цепи хоста
+  pub fn circuit_state(&self, host: &str) -> CircuitState {
+    self
+      .lock_circuits()
+      .get(host)
+      .map_or(CircuitState::Closed, |circuit| self.state_of(circuit))
+  }
+
+  /// Сетевая активность плагина
+  pub fn plugin_usage(&self, plugin_id: &str) -> Option<PluginNetworkUsage> {
+    self.lock_usage().get(plugin_id).cloned()
+  }
+
+  /// Получить статистику шлюза
+  pub async fn stats(&self) -> NetworkGatewayStats {
+    let queue_length = self.queue_len().await;
+
+    let mut circuits: Vec<HostCircuitStatus> = self
+      .lock_circuits()
+      .iter()
+      .map(|(host, circuit)| HostCircuitStatus {
+        host: host.clone(),
+        state: self.state_of(circuit),
+        consecutive_failures: circuit.consecutive_failures,
+      })
+      .collect();
+    circuits.sort_by(|a, b| a.host.cmp(&b.host));
+
+    let mut plugins: Vec<PluginNetworkUsage> = self.lock_usage().values().cloned().collect();
+    plugins.sort_by(|a, b| a.plugin_id.cmp(&b.plugin_id));
+
+    NetworkGatewayStats {
+      online: self.online.load(Ordering::Relaxed),
+      queue_length,
+      total_requests: plugins.iter().map(|usage| usage.requests).sum(),
+      total_retries: plugins.iter().map(|usage| usage.retries).sum(),
+      circuits,
+      plugins,
+    }
+  }
+
+  /// Выполнить запрос с повторами и учетом состояния цепи хоста
+  async fn deliver(
+    &self,
+    plugin_id: &str,
+    host: &str,
+    request: &NetworkRequest,
+  ) -> std::result::Result<NetworkResponse, DeliveryError> {
+    let max_attempts = if request.method.is_idempotent() {
+      self.config.retry.max_attempts.max(1)
+    } else {
+      1
+    };
+
+    let mut attempt = 0;
+    loop {
+      if !self.circuit_allows(host) {
+        return Err(DeliveryError::CircuitOpen);
+      }
+      attempt += 1;
+      let last_attempt = attempt >= max_attempts;
+
+      match self.execute(request).await {
+        Ok(response) if response.is_retryable() => {
+          self.record_failure(host);
+          if last_attempt {
+            return Ok(response);
+          }
+        }
+        Ok(response) => {
+          self.record_success(host);
+          self.online.store(true, Ordering::Relaxed);
+          return Ok(response);
+        }
+        Err(e) => {
+          self.record_failure(host);
+          if last_attempt {
+            return Err(DeliveryError::Transport(e.to_string()));
+          }
+        }
+      }
+
+      self.account(plugin_id, |usage| usage.retries += 1);
+      tokio::time::sleep(self.config.retry.delay_for(attempt - 1)).await;
+    }
+  }
+
+  /// Одна попытка запроса
+  async fn execute(&self, request: &NetworkRequest) -> reqwest::Result<NetworkResponse> {
+    let timeout = request
+      .timeout_ms
+      .map(Duration::from_millis)
+      .unwrap_or(self.config.request_timeout);
+    let mut builder = self
+      .client
+      .request(request.method.as_reqwest(), &request.url)
+      .timeout(timeout);
+    for (name, value) in &request.headers {
+      builder = builder.header(name, value);
+    }
+    if let Some(body) = &request.body {
+      builder = builder.body(body.clone());
+    }
+
+    let response = builder.send().await?;
+    let status = response.status().as_u16();
+    let headers = response
+      .headers()
+      .iter()
+      .filter_map(|(name, value)| {
+        value
+          .to_str()
+          .ok()
+          .map(|value| (name.to_string(), value.to_string()))
+      })
+      .collect();
+    let body = response.bytes().await?.to_vec();
+
+    Ok(NetworkResponse {
+      status,
+      headers,
+      body,
+    })
+  }
+
+  /// Разрешен ли запрос к хосту. После истечения размыкания пропускается
+  /// один пробный запрос.
+  fn circuit_allows(&self, host: &str) -> bool {
+    let mut circuits = self.lock_circuits();
+    let Some(circuit) = circuits.get_mut(host) else {
+      return true;
+    };
+    match circuit.opened_at {
+      None => true,
+      Some(opened_at) if opened_at.elapsed() < self.config.circuit_breaker.open_duration => false,
+      Some(_) if circuit.trial_in_flight => false,
+      Some(_) => {
+        circuit.trial_in_flight = true;
+        true
+      }
+    }
+  }
+
+  fn record_success(&self, host: &str) {
+    if self
+      .lock_circuits()
+      .remove(host)
+      .is_some_and(|c| c.opened_at.is_some())
+    {
+      log::info!("[NetworkGateway] Circuit for '{host}' closed");
+    }
+  }
+
+  fn record_failure(&self, host: &str) {
+    let mut circuits = self.lock_circuits();
+    let circuit = circuits.entry(host.to_string()).or_default();
+    circuit.consecutive_failures += 1;
+    let trial_failed = circuit.trial_in_flight;
+    circuit.trial_in_flight = false;
+    if trial_failed
+      || (circuit.opened_at.is_none()
+        && circuit.consecutive_failures >= self.config.circuit_breaker.failure_threshold)
+    {
+      circuit.opened_at = Some(Instant::now());
+      log::warn!(
+        "[NetworkGateway] Circuit for '{host}' opened after {} failures",
+        circuit.consecutive_failures
+      );
+    }
+  }
+
+  fn state_of(&self, circuit: &HostCircuit) -> CircuitState {
+    match circuit.opened_at {
+      None => CircuitState::Closed,
+      Some(opened_at) if opened_at.elapsed() < self.config.circuit_breaker.open_duration => {
+        CircuitState::Open
+      }
+      Some(_) => CircuitState::HalfOpen,
+    }
+  }
+
+  async fn enqueue(&self, plugin_id: &str, request: NetworkRequest) -> String {
+    let queued = QueuedNetworkRequest {
+      id: uuid::Uuid::new_v4().to_string(),
+      plugin_id: plugin_id.to_string(),
+      request,
+      queued_at: Utc::now(),
+      attempts: 1,
+    };
+    let queue_id = queued.id.clone();
+
+    let mut queue = self.queue.lock().await;
+    queue.push(queued);
+    self.persist_queue(&queue).await;
+    self.account(plugin_id, |usage| usage.queued += 1);
+    queue_id
+  }
+
+  async fn persist_queue(&self, queue: &[QueuedNetworkRequest]) {
+    let Some(path) = &self.config.queue_path else {
+      return;
+    };
+    if let Err(e) = write_queue(path, queue).await {
+      log::warn!("Failed to persist plugin network queue: {e}");
+    }
+  }
+
+  fn account(&self, plugin_id: &str, update: impl FnOnce(&mut PluginNetworkUsage)) {
+    let mut usage = self.lock_usage();
+    let entry = usage
+      .entry(plugin_id.to_string())
+      .or_insert_with(|| PluginNetworkUsage {
+        plugin_id: plugin_id.to_string(),
+        ..Default::default()
+      });
+    update(entry);
+  }
+
+  fn lock_circuits(&self) -> MutexGuard<'_, HashMap<String, HostCircuit>> {
+    self.circuits.lock().unwrap_or_else(|e| e.into_inner())
+  }
+
+  fn lock_usage(&self) -> MutexGuard<'_, HashMap<String, PluginNetworkUsage>> {
+    self.usage.lock().unwrap_or_else(|e| e.into_inner())
+  }
+}
+
+async fn write_queue(path: &Path, queue: &[QueuedNetworkRequest]) -> std::io::Result<()> {
+  if let Some(parent) = path.parent() {
+    tokio::fs::create_dir_all(parent).await?;
+  }
+  let data = serde_json::to_vec_pretty(queue)?;
+  tokio::fs::write(path, data).await
+}
+
+impl Default for NetworkGateway {
+  fn default() -> Self {
+    Self::new(NetworkGatewayConfig::default())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::core::plugins::SecurityLevel;
+  use crate::core::plugins::{permissions::PluginPermissions, sandbox::ResourceLimits};
+  use std::net::SocketAddr;
+  use std::sync::atomic::AtomicUsize;
+  use tokio::io::{AsyncReadExt, AsyncWriteExt};
+  use tokio::net::{TcpListener, TcpStream};
+
+  /// Mock HTTP сервер, отвечающий статусами по сценарию (последний повторяется).
+  /// HEAD запросы проверки сети не попадают в сценарий.
+  struct ScriptedServer {
+    hits: Arc<AtomicUsize>,
+    bodies: Arc<Mutex<Vec<String>>>,
+    task: tokio::task::JoinHandle<()>,
+  }
+
+  impl ScriptedServer {
+    async fn start(addr: SocketAddr, statuses: Vec<u16>) -> Self {
+      let listener = TcpListener::bind(addr).await.unwrap();
+      let hits = Arc::new(AtomicUsize::new(0));
+      let bodies = Arc::new(Mutex::new(Vec::new()));
+      let (task_hits, task_bodies) = (hits.clone(), bodies.clone());
+      let task = tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+          let (method, body) = read_request(&mut stream).await;
+          let status = if method == "HEAD" {
+            204
+          } else {
+            let hit = task_hits.fetch_add(1, Ordering::SeqCst);
+            task_bodies.lock().unwrap().push(body);
+            statuses[hit.min(statuses.len() - 1)]
+          };
+          let response =
+            format!("HTTP/1.1 {status} Scripted\r\ncontent-length: 0\r\nconnection: close\r\n\r\n");
+          let _ = stream.write_all(response.as_bytes()).await;
+        }
+      });
+      Self { hits, bodies, task }
+    }
+
+    fn hits(&self) -> usize {
+      self.hits.load(Ordering::SeqCst)
+    }
+  }
+
+  impl Drop for ScriptedServer {
+    fn drop(&mut self) {
+      self.task.abort();
+    }
+  }
+
+  async fn read_request(stream: &mut TcpStream) -> (String, String) {
+    let mut data = Vec::new();
+    let mut buf = [0u8; 1024];
+    let header_end = loop {
+      if let Some(pos) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+        break pos + 4;
+      }
+      match stream.read(&mut buf).await {
+        Ok(0) | Err(_) => return (String::new(), String::new()),
+        Ok(n) => data.extend_from_slice(&buf[..n]),
+      }
+    };
+    let head = String::from_utf8_lossy(&data[..header_end]).to_string();
+    let content_length: usize = head
+      .lines()
+      .find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        name
+          .eq_ignore_ascii_case("content-length")
+          .then(|| value.trim().parse().unwrap_or(0))
+      })
+      .unwrap_or(0);
+    while data.len() < header_end + content_length {
+      match stream.read(&mut buf).await {
+        Ok(0) | Err(_) => break,
+        Ok(n) => data.extend_from_slice(&buf[..n]),
+      }
+    }
+    let method = head
+      .split_whitespace()
+      .next()
+      .unwrap_or_default()
+      .to_string();
+    (
+      method,
+      String::from_utf8_lossy(&data[header_end..]).to_string(),
+    )
+  }
+
+  /// Свободный локальный адрес, на котором сейчас никто не слушает
+  async fn free_addr() -> SocketAddr {
+    TcpListener::bind("127.0.0.1:0")
+      .await
+      .unwrap()
+      .local_addr()
+      .unwrap()
+  }
+
+  fn test_gateway(addr: SocketAddr, queue_path: Option<PathBuf>) -> NetworkGateway {
+    NetworkGateway::new(NetworkGatewayConfig {
+      retry: RetryPolicy {
+        max_attempts: 3,
+        initial_delay: Duration::from_millis(5),
+        max_delay: Duration::from_millis(20),
+        multiplier: 2.0,
+        jitter: 0.5,
+      },
+      circuit_breaker: CircuitBreakerConfig {
+        failure_threshold: 3,
+        open_duration: Duration::from_millis(100),
+      },
+      request_timeout: Duration::from_secs(2),
+      queue_path,
+      probe_url: format!("http://{addr}/probe"),
+      probe_timeout: Duration::from_millis(500),
+      probe_interval: Duration::from_secs(60),
+    })
+  }
+
+  fn local_permissions() -> NetworkPermissions {
+    NetworkPermissions {
+      allowed_hosts: vec!["127.0.0.1".to_string()],
+      allowed_ports: vec![],
+      allow_all: false,
+      https_only: false,
+    }
+  }
+
+  fn status_of(outcome: NetworkOutcome) -> u16 {
+    match outcome {
+      NetworkOutcome::Completed(response) => response.status,
+      NetworkOutcome::Queued { .. } => panic!("request was queued"),
+    }
+  }
+
+  #[tokio::test]
+  async fn test_idempotent_requests_retried_until_success() {
+    let addr = free_addr().await;
+    let server = ScriptedServer::start(addr, vec![503, 502, 200]).await;
+    let gateway = test_gateway(addr, None);
+
+    let outcome = gateway
+      .send(
+        "uploader",
+        &local_permissions(),
+        None,
+        NetworkRequest::get(format!("http://{addr}/metadata")),
+      )
+      .await
+      .unwrap();
+
+    assert_eq!(status_of(outcome), 200);
+    assert_eq!(server.hits(), 3);
+    let usage = gateway.plugin_usage("uploader").unwrap();
+    assert_eq!(usage.requests, 1);
+    assert_eq!(usage.retries, 2);
+    assert_eq!(usage.completed, 1);
+    assert_eq!(gateway.circuit_state("127.0.0.1"), CircuitState::Closed);
+  }
+
+  #[tokio::test]
+  async fn test_post_is_not_retried() {
+    let addr = free_addr().await;
+    let server = ScriptedServer::start(addr, vec![503, 200]).await;
+    let gateway = test_gateway(addr, None);
+
+    let outcome = gateway
+      .send(
+        "uploader",
+        &local_permissions(),
+        None,
+        NetworkRequest::post(format!("http://{addr}/upload")).body("data"),
+      )
+      .await
+      .unwrap();
+
+    assert_eq!(status_of(outcome), 503);
+    assert_eq!(server.hits(), 1);
+    assert_eq!(gateway.plugin_usage("uploader").unwrap().retries, 0);
+  }
+
+  #[test]
+  fn test_retry_delay_grows_exponentially_with_jitter() {
+    let policy = RetryPolicy {
+      max_attempts: 5,
+      initial_delay: Duration::from_millis(100),
+      max_delay: Duration::from_secs(1),
+      multiplier: 2.0,
+      jitter: 0.2,
+    };
+
+    for _ in 0..20 {
+      let first = policy.delay_for(0);
+      assert!(first >= Duration::from_millis(80) && first <= Duration::from_millis(120));
+      let third = policy.delay_for(2);
+      assert!(third >= Duration::from_millis(320) && third <= Duration::from_millis(480));
+      assert!(policy.delay_for(10) <= Duration::from_millis(1200));
+    }
+  }
+
+  #[tokio::test]
+  async fn test_circuit_opens_after_failures_and_closes_after_trial() {
+    let addr = free_addr().await;
+    let server = ScriptedServer::start(addr, vec![500, 500, 500, 200]).await;
+    let gateway = test_gateway(addr, None);
+    let request = || NetworkRequest::get(format!("http://{addr}/status"));
+
+    // Три неудачные попытки подряд размыкают цепь
+    let outcome = gateway
+      .send("uploader", &local_permissions(), None, request())
+      .await
+      .unwrap();
+    assert_eq!(status_of(outcome), 500);
+    assert_eq!(gateway.circuit_state("127.0.0.1"), CircuitState::Open);
+
+    // Пока цепь разомкнута, запросы не доходят до сервера
+    let result = gateway
+      .send("uploader", &local_permissions(), None, request())
+      .await;
+    assert!(result.is_err());
+    assert_eq!(server.hits(), 3);
+    assert_eq!(gateway.plugin_usage("uploader").unwrap().failed, 1);
+
+    // После паузы пробный запрос замыкает цепь
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    assert_eq!(gateway.circuit_state("127.0.0.1"), CircuitState::HalfOpen);
+    let outcome = gateway
+      .send("uploader", &local_permissions(), None, request())
+      .await
+      .unwrap();
+    assert_eq!(status_of(outcome), 200);
+    assert_eq!(server.hits(), 4);
+    assert_eq!(gateway.circuit_state("127.0.0.1"), CircuitState::Closed);
+    assert!(gateway.stats().await.circuits.is_empty());
+  }
+
+  #[tokio::test]
+  async fn test_offline_queue_drained_after_reconnect() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let queue_path = temp_dir.path().join("network_queue.json");
+    let addr = free_addr().await;
+    let gateway = test_gateway(addr, Some(queue_path.clone()));
+
+    // Сервер недоступен: помеченный POST сохраняется в очередь
+    let payload = serde_json::json!({ "title": "Holiday" });
+    let outcome = gateway
+      .send(
+        "uploader",
+        &local_permissions(),
+        None,
+        NetworkRequest::post(format!("http://{addr}/videos"))
+          .json(&payload)
+          .queueable(),
+      )
+      .await
+      .unwrap();
+    assert!(matches!(outcome, NetworkOutcome::Queued { .. }));
+    assert_eq!(gateway.queue_len().await, 1);
+    assert!(!gateway.stats().await.online);
+
+    // Очередь переживает перезапуск шлюза
+    assert_eq!(
+      test_gateway(addr, Some(queue_path.clone()))
+        .queue_len()
+        .await,
+      1
+    );
+
+    // Без сети очередь не отправляется
+    assert_eq!(gateway.drain_queue().await, 0);
+    assert_eq!(gateway.queue_len().await, 1);
+
+    // Сеть восстановлена
+    let server = ScriptedServer::start(addr, vec![201]).await;
+    assert_eq!(gateway.drain_queue().await, 1);
+    assert_eq!(server.hits(), 1);
+    assert_eq!(server.bodies.lock().unwrap()[0], payload.to_string());
+    assert_eq!(gateway.queue_len().await, 0);
+
+    let stats = gateway.stats().await;
+    assert!(stats.online);
+    assert_eq!(stats.plugins[0].queued, 1);
+    assert_eq!(stats.plugins[0].delivered_from_queue, 1);
+    assert_eq!(test_gateway(addr, Some(queue_path)).queue_len().await, 0);
+  }
+
+  #[tokio::test]
+  async fn test_unqueueable_request_fails_when_offline() {
+    let addr = free_addr().await;
+    let gateway = test_gateway(addr, None);
+
+    let result = gateway
+      .send(
+        "uploader",
+        &local_permissions(),
+        None,
+        NetworkRequest::post(format!("http://{addr}/videos")),
+      )
+      .await;
+    assert!(result.is_err());
+    assert_eq!(gateway.queue_len().await, 0);
+  }
+
+  #[tokio::test]
+  async fn test_permissions_and_network_quota_enforced() {
+    let addr = free_addr().await;
+    let server = ScriptedServer::start(addr, vec![200]).await;
+    let gateway = test_gateway(addr, None);
+    let url = format!("http://{addr}/upload");
+
+    let https_only = NetworkPermissions {
+      https_only: true,
+      ..local_permissions()
+    };
+    let result = gateway
+      .send("uploader", &https_only, None, NetworkRequest::get(&url))
+      .await;
+    assert!(matches!(result, Err(VideoCompilerError::SecurityError(_))));
+
+    let result = gateway
+      .send(
+        "uploader",
+        &NetworkPermissions::none(),
+        None,
+        NetworkRequest::get(&url),
+      )
+      .await;
+    assert!(matches!(result, Err(VideoCompilerError::SecurityError(_))));
+
+    // Трафик плагина учитывается в квоте sandbox
+    let permissions = PluginPermissions {
+      network: local_permissions(),
+      ..PluginPermissions::default()
+    };
+    let sandbox = PluginSandbox::with_limits(
+      "uploader".to_string(),
+      &permissions,
+      ResourceLimits {
+        max_network_bytes: 10,
+        ..ResourceLimits::for_security_level(SecurityLevel::Standard)
+      },
+    );
+    let result = gateway
+      .send(
+        "uploader",
+        &permissions.network,
+        Some(&sandbox),
+        NetworkRequest::post(&url).body(vec![0u8; 20]),
+      )
+      .await;
+    assert!(matches!(
+      result,
+      Err(VideoCompilerError::QuotaExceeded { .. })
+    ));
+    assert_eq!(server.hits(), 0);
+    let stats = sandbox.get_usage_stats().await;
+    assert_eq!(stats.network_requests, 1);
+    assert_eq!(stats.network_bytes, 20);
+  }
+}
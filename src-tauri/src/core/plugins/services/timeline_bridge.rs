@@ -1,14 +1,20 @@
 //! Мост для интеграции плагинов с timeline сервисами
 
+use super::timeline_transaction::TimelineTransaction;
 use crate::{
   core::{
     di::ServiceContainer,
     plugins::{
       api::{TimelineState, TrackInfo},
       permissions::PluginPermissions,
+      sandbox::PluginSandbox,
     },
+    EventBus,
+  },
+  video_compiler::{
+    error::{Result, VideoCompilerError},
+    schema::ProjectSchema,
   },
-  video_compiler::error::{Result, VideoCompilerError},
 };
 use serde_json::Value;
 use std::sync::Arc;
@@ -19,6 +25,8 @@ pub struct TimelineBridge {
   service_container: Arc<ServiceContainer>,
   permissions: Arc<PluginPermissions>,
   plugin_id: String,
+  event_bus: Option<Arc<EventBus>>,
+  sandbox: Option<Arc<PluginSandbox>>,
 }
 
 impl TimelineBridge {
@@ -32,7 +40,49 @@ impl TimelineBridge {
       service_container,
       permissions,
       plugin_id,
+      event_bus: None,
+      sandbox: None,
+    }
+  }
+
+  /// Установить EventBus для публикации изменений timeline
+  pub fn with_event_bus(mut self, event_bus: Arc<EventBus>) -> Self {
+    self.event_bus = Some(event_bus);
+    self
+  }
+
+  /// Проверять разрешения через sandbox плагина
+  pub fn with_sandbox(mut self, sandbox: Arc<PluginSandbox>) -> Self {
+    self.sandbox = Some(sandbox);
+    self
+  }
+
+  /// Начать транзакцию изменения timeline над снимком проекта
+  ///
+  /// Требует разрешения `timeline_write`. Изменения применяются только после commit.
+  pub fn begin_timeline_transaction(&self, project: ProjectSchema) -> Result<TimelineTransaction> {
+    match &self.sandbox {
+      Some(sandbox) => sandbox.check_timeline_write()?,
+      None if !self.permissions.timeline_write => {
+        return Err(VideoCompilerError::SecurityError(
+          "Plugin does not have permission to modify timeline".to_string(),
+        ));
+      }
+      None => {}
     }
+
+    let event_bus = self.event_bus.clone().ok_or_else(|| {
+      VideoCompilerError::InternalError("EventBus is not configured for TimelineBridge".to_string())
+    })?;
+
+    let transaction = TimelineTransaction::new(self.plugin_id.clone(), project, event_bus);
+    log::info!(
+      "[TimelineBridge {}] Started timeline transaction {}",
+      self.plugin_id,
+      transaction.id()
+    );
+
+    Ok(transaction)
   }
 
   /// Получить текущее состояние timeline
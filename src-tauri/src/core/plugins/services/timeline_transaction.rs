@@ -0,0 +1,317 @@
+//! Транзакции изменения timeline из плагинов
+//!
+//! Плагин накапливает операции над копией проекта. При commit хост заново применяет
+//! список мутаций к исходному снимку, проверяет блокировки и `ProjectSchema::validate`
+//! и только после этого публикует одно событие, которое применяет frontend.
+
+use crate::{
+  core::{AppEvent, EventBus},
+  video_compiler::{
+    commands::schema_commands::find_editable_clip,
+    error::{Result, VideoCompilerError},
+    schema::{Clip, ProjectSchema, Track, TrackType},
+  },
+};
+use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, sync::Arc};
+
+/// Описание клипа, добавляемого плагином
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipSpec {
+  /// Путь к исходному файлу
+  pub source_path: String,
+  /// Начало клипа на timeline
+  pub start_time: f64,
+  /// Конец клипа на timeline
+  pub end_time: f64,
+  /// Начало в исходном файле
+  #[serde(default)]
+  pub source_start: f64,
+}
+
+impl ClipSpec {
+  fn into_clip(self) -> Clip {
+    let mut clip = Clip::new(
+      PathBuf::from(self.source_path),
+      self.start_time,
+      self.end_time - self.start_time,
+    );
+    clip.source_start = self.source_start;
+    clip.source_end = self.source_start + (self.end_time - self.start_time);
+    clip
+  }
+}
+
+/// Мутация схемы проекта, созданная транзакцией
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TimelineMutation {
+  AddClip {
+    track_id: String,
+    clip: Clip,
+  },
+  SplitClip {
+    clip_id: String,
+    at_time: f64,
+    new_clip_id: String,
+  },
+  RemoveClip {
+    clip_id: String,
+  },
+  AddTrack {
+    track: Track,
+  },
+}
+
+impl TimelineMutation {
+  /// Применить мутацию к проекту с учетом блокировок треков и клипов
+  pub fn apply(&self, project: &mut ProjectSchema) -> Result<()> {
+    match self {
+      TimelineMutation::AddClip { track_id, clip } => {
+        let track = project
+          .tracks
+          .iter_mut()
+          .find(|t| &t.id == track_id)
+          .ok_or_else(|| {
+            VideoCompilerError::InvalidParameter(format!("Track not found: {track_id}"))
+          })?;
+        if track.locked {
+          return Err(VideoCompilerError::Locked {
+            clip_id: clip.id.clone(),
+            track_id: track_id.clone(),
+          });
+        }
+        track.add_clip(clip.clone());
+      }
+      TimelineMutation::SplitClip {
+        clip_id,
+        at_time,
+        new_clip_id,
+      } => {
+        let (track_idx, clip_idx) = find_editable_clip(project, clip_id, false)?;
+        let track = &mut project.tracks[track_idx];
+        let clip = &mut track.clips[clip_idx];
+        if *at_time <= clip.start_time || *at_time >= clip.end_time {
+          return Err(VideoCompilerError::InvalidParameter(format!(
+            "Split point {at_time} is outside clip {clip_id}"
+          )));
+        }
+
+        let source_split = clip.source_start + (at_time - clip.start_time) * clip.speed;
+        let mut tail = clip.clone();
+        tail.id = new_clip_id.clone();
+        tail.start_time = *at_time;
+        tail.source_start = source_split;
+        clip.end_time = *at_time;
+        clip.source_end = source_split;
+        track.add_clip(tail);
+      }
+      TimelineMutation::RemoveClip { clip_id } => {
+        let (track_idx, _) = find_editable_clip(project, clip_id, false)?;
+        project.tracks[track_idx].remove_clip(clip_id);
+      }
+      TimelineMutation::AddTrack { track } => {
+        if project.tracks.iter().any(|t| t.id == track.id) {
+          return Err(VideoCompilerError::InvalidParameter(format!(
+            "Track already exists: {}",
+            track.id
+          )));
+        }
+        project.tracks.push(track.clone());
+      }
+    }
+
+    Ok(())
+  }
+}
+
+/// Открытая транзакция изменения timeline
+pub struct TimelineTransaction {
+  id: String,
+  plugin_id: String,
+  base: ProjectSchema,
+  working: ProjectSchema,
+  mutations: Vec<TimelineMutation>,
+  event_bus: Arc<EventBus>,
+}
+
+impl TimelineTransaction {
+  pub(crate) fn new(plugin_id: String, project: ProjectSchema, event_bus: Arc<EventBus>) -> Self {
+    Self {
+      id: uuid::Uuid::new_v4().to_string(),
+      plugin_id,
+      working: project.clone(),
+      base: project,
+      mutations: Vec::new(),
+      event_bus,
+    }
+  }
+
+  /// ID транзакции
+  pub fn id(&self) -> &str {
+    &self.id
+  }
+
+  /// Текущее состояние проекта с учетом накопленных операций
+  pub fn project(&self) -> &ProjectSchema {
+    &self.working
+  }
+
+  /// Накопленные мутации
+  pub fn mutations(&self) -> &[TimelineMutation] {
+    &self.mutations
+  }
+
+  /// Добавить клип на трек, возвращает ID клипа
+  pub fn add_clip(&mut self, track_id: &str, clip_spec: ClipSpec) -> Result<String> {
+    let clip = clip_spec.into_clip();
+    let clip_id = clip.id.clone();
+    self.record(TimelineMutation::AddClip {
+      track_id: track_id.to_string(),
+      clip,
+    })?;
+    Ok(clip_id)
+  }
+
+  /// Разрезать клип, возвращает ID второй части
+  pub fn split_clip(&mut self, clip_id: &str, at_time: f64) -> Result<String> {
+    let new_clip_id = uuid::Uuid::new_v4().to_string();
+    self.record(TimelineMutation::SplitClip {
+      clip_id: clip_id.to_string(),
+      at_time,
+      new_clip_id: new_clip_id.clone(),
+    })?;
+    Ok(new_clip_id)
+  }
+
+  /// Удалить клип
+  pub fn remove_clip(&mut self, clip_id: &str) -> Result<()> {
+    self.record(TimelineMutation::RemoveClip {
+      clip_id: clip_id.to_string(),
+    })
+  }
+
+  /// Добавить трек, возвращает ID трека
+  pub fn add_track(&mut self, track_type: TrackType, name: &str) -> Result<String> {
+    let track = Track::new(track_type, name.to_string());
+    let track_id = track.id.clone();
+    self.record(TimelineMutation::AddTrack { track })?;
+    Ok(track_id)
+  }
+
+  /// Применить операцию к рабочей копии и запомнить ее
+  fn record(&mut self, mutation: TimelineMutation) -> Result<()> {
+    mutation.apply(&mut self.working)?;
+    self.mutations.push(mutation);
+    Ok(())
+  }
+
+  /// Проверить и опубликовать транзакцию
+  ///
+  /// Мутации заново применяются к исходному снимку проекта. Любая ошибка отменяет
+  /// транзакцию целиком, событие в этом случае не публикуется.
+  pub async fn commit(self) -> Result<Vec<TimelineMutation>> {
+    let mut project = self.base;
+    for mutation in &self.mutations {
+      mutation.apply(&mut project)?;
+    }
+    project.validate().map_err(VideoCompilerError::validation)?;
+
+    self
+      .event_bus
+      .publish_app_event(AppEvent::TimelineTransactionCommitted {
+        plugin_id: self.plugin_id.clone(),
+        transaction_id: self.id.clone(),
+        mutations: serde_json::to_value(&self.mutations)?,
+      })
+      .await?;
+
+    log::info!(
+      "[TimelineBridge {}] Committed timeline transaction {} with {} mutations",
+      self.plugin_id,
+      self.id,
+      self.mutations.len()
+    );
+
+    Ok(self.mutations)
+  }
+
+  /// Отменить транзакцию без изменений timeline
+  pub fn rollback(self) {
+    log::info!(
+      "[TimelineBridge {}] Rolled back timeline transaction {} ({} mutations discarded)",
+      self.plugin_id,
+      self.id,
+      self.mutations.len()
+    );
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn project_with_track() -> (ProjectSchema, String) {
+    let mut project = ProjectSchema::new("Test".to_string());
+    let track = Track::new(TrackType::Video, "Video".to_string());
+    let track_id = track.id.clone();
+    project.tracks.push(track);
+    (project, track_id)
+  }
+
+  fn spec(start_time: f64, end_time: f64) -> ClipSpec {
+    ClipSpec {
+      source_path: "/media/interview.mp4".to_string(),
+      start_time,
+      end_time,
+      source_start: 0.0,
+    }
+  }
+
+  #[test]
+  fn test_split_clip_keeps_source_continuity() {
+    let (project, track_id) = project_with_track();
+    let mut tx = TimelineTransaction::new("test".to_string(), project, Arc::new(EventBus::new()));
+
+    let clip_id = tx.add_clip(&track_id, spec(10.0, 20.0)).unwrap();
+    let tail_id = tx.split_clip(&clip_id, 14.0).unwrap();
+
+    let head = tx.project().find_clip_by_id(&clip_id).unwrap();
+    let tail = tx.project().find_clip_by_id(&tail_id).unwrap();
+    assert_eq!(head.end_time, 14.0);
+    assert_eq!(head.source_end, 4.0);
+    assert_eq!(tail.start_time, 14.0);
+    assert_eq!(tail.source_start, 4.0);
+    assert_eq!(tail.end_time, 20.0);
+
+    assert!(tx.split_clip(&clip_id, 30.0).is_err());
+    assert_eq!(tx.mutations().len(), 2);
+  }
+
+  #[test]
+  fn test_locked_track_rejects_operations() {
+    let (mut project, track_id) = project_with_track();
+    project.tracks[0].locked = true;
+    let mut tx = TimelineTransaction::new("test".to_string(), project, Arc::new(EventBus::new()));
+
+    let result = tx.add_clip(&track_id, spec(0.0, 5.0));
+    assert!(matches!(result, Err(VideoCompilerError::Locked { .. })));
+    assert!(tx.mutations().is_empty());
+  }
+
+  #[tokio::test]
+  async fn test_commit_fails_validation_without_changes() {
+    let (project, track_id) = project_with_track();
+    let mut tx = TimelineTransaction::new("test".to_string(), project, Arc::new(EventBus::new()));
+
+    tx.add_clip(&track_id, spec(0.0, 5.0)).unwrap();
+    // Пустое имя трека не проходит ProjectSchema::validate
+    tx.add_track(TrackType::Audio, "").unwrap();
+
+    let result = tx.commit().await;
+    assert!(matches!(
+      result,
+      Err(VideoCompilerError::ValidationError(_))
+    ));
+  }
+}
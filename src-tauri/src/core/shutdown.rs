@@ -0,0 +1,514 @@
+//! Координированное завершение работы приложения
+//!
+//! Порядок: дождаться активных рендеров (не дольше grace period) или жестко прервать их,
+//! сбросить дисковые кэши, остановить сервисы Video Compiler и телеметрию. Окно
+//! закрывается только после завершения последовательности.
+
+use crate::core::{AppEvent, EventBus, TelemetryManager};
+use crate::video_compiler::{
+  cache::RenderCache,
+  error::Result,
+  services::{RenderService, ServiceContainer},
+};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
+
+/// Причина, записываемая в задачи рендеринга, прерванные при выходе
+const SHUTDOWN_ABORT_REASON: &str = "Рендеринг прерван при завершении приложения";
+
+/// Настройки завершения работы
+#[derive(Debug, Clone)]
+pub struct ShutdownConfig {
+  /// Сколько ждать завершения активных рендеров перед отменой
+  pub grace_period: Duration,
+  /// Интервал опроса активных рендеров
+  pub poll_interval: Duration,
+}
+
+impl Default for ShutdownConfig {
+  fn default() -> Self {
+    Self {
+      grace_period: Duration::from_secs(30),
+      poll_interval: Duration::from_millis(250),
+    }
+  }
+}
+
+/// Этап завершения работы
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShutdownStage {
+  DrainingRenders,
+  CancellingRenders,
+  FlushingCaches,
+  StoppingServices,
+  StoppingTelemetry,
+  Completed,
+}
+
+impl ShutdownStage {
+  fn as_str(self) -> &'static str {
+    match self {
+      ShutdownStage::DrainingRenders => "draining_renders",
+      ShutdownStage::CancellingRenders => "cancelling_renders",
+      ShutdownStage::FlushingCaches => "flushing_caches",
+      ShutdownStage::StoppingServices => "stopping_services",
+      ShutdownStage::StoppingTelemetry => "stopping_telemetry",
+      ShutdownStage::Completed => "completed",
+    }
+  }
+}
+
+/// Прогресс завершения работы для UI
+#[derive(Debug, Clone, Serialize)]
+pub struct ShutdownProgress {
+  pub stage: ShutdownStage,
+  pub message: String,
+}
+
+/// Состояние запроса на завершение
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShutdownStatus {
+  /// Последовательность завершена, окно можно закрывать
+  Completed,
+  /// Завершение уже выполняется другим запросом
+  InProgress,
+}
+
+/// Результат завершения работы
+#[derive(Debug, Clone, Serialize)]
+pub struct ShutdownReport {
+  pub status: ShutdownStatus,
+  /// Рендеры, завершившиеся за grace period
+  pub finished_renders: usize,
+  /// Рендеры, прерванные при выходе
+  pub cancelled_renders: Vec<String>,
+  /// Ошибки шагов, не остановившие последовательность
+  pub errors: Vec<String>,
+}
+
+impl ShutdownReport {
+  fn with_status(status: ShutdownStatus) -> Self {
+    Self {
+      status,
+      finished_renders: 0,
+      cancelled_renders: Vec::new(),
+      errors: Vec::new(),
+    }
+  }
+}
+
+type ProgressListener = Box<dyn Fn(&ShutdownProgress) + Send + Sync>;
+
+/// Координатор завершения работы приложения
+pub struct AppShutdown {
+  services: Arc<ServiceContainer>,
+  event_bus: Arc<EventBus>,
+  cache_manager: Option<Arc<RwLock<RenderCache>>>,
+  telemetry: Option<Arc<TelemetryManager>>,
+  config: ShutdownConfig,
+  progress_listener: Option<ProgressListener>,
+  started: AtomicBool,
+  completed: AtomicBool,
+  force: CancellationToken,
+}
+
+impl AppShutdown {
+  pub fn new(services: Arc<ServiceContainer>, event_bus: Arc<EventBus>) -> Self {
+    Self {
+      services,
+      event_bus,
+      cache_manager: None,
+      telemetry: None,
+      config: ShutdownConfig::default(),
+      progress_listener: None,
+      started: AtomicBool::new(false),
+      completed: AtomicBool::new(false),
+      force: CancellationToken::new(),
+    }
+  }
+
+  /// Сбрасывать дисковый индекс кэша рендеринга
+  pub fn with_cache_manager(mut self, cache_manager: Arc<RwLock<RenderCache>>) -> Self {
+    self.cache_manager = Some(cache_manager);
+    self
+  }
+
+  /// Останавливать телеметрию последним шагом
+  pub fn with_telemetry(mut self, telemetry: Arc<TelemetryManager>) -> Self {
+    self.telemetry = Some(telemetry);
+    self
+  }
+
+  pub fn with_config(mut self, config: ShutdownConfig) -> Self {
+    self.config = config;
+    self
+  }
+
+  /// Дополнительный получатель прогресса (например, событие для окна)
+  pub fn with_progress_listener(
+    mut self,
+    listener: impl Fn(&ShutdownProgress) + Send + Sync + 'static,
+  ) -> Self {
+    self.progress_listener = Some(Box::new(listener));
+    self
+  }
+
+  /// Завершена ли последовательность
+  pub fn is_completed(&self) -> bool {
+    self.completed.load(Ordering::SeqCst)
+  }
+
+  /// Запросить завершение работы
+  ///
+  /// Повторный запрос с `force` во время выполнения прерывает ожидание grace period.
+  pub async fn request(&self, force: bool) -> Result<ShutdownReport> {
+    if force {
+      self.force.cancel();
+    }
+
+    if self.is_completed() {
+      return Ok(ShutdownReport::with_status(ShutdownStatus::Completed));
+    }
+    if self.started.swap(true, Ordering::SeqCst) {
+      log::info!("Завершение работы уже выполняется (force: {force})");
+      return Ok(ShutdownReport::with_status(ShutdownStatus::InProgress));
+    }
+
+    log::info!("Начато завершение работы приложения (force: {force})");
+    let mut report = ShutdownReport::with_status(ShutdownStatus::Completed);
+    let render = self.services.render.clone();
+
+    report.finished_renders = self.drain_renders(render.as_ref(), &mut report).await;
+    self.cancel_renders(render.as_ref(), &mut report).await;
+
+    self
+      .progress(
+        ShutdownStage::FlushingCaches,
+        "shutting down: flushing caches…",
+      )
+      .await;
+    if let Some(cache_manager) = &self.cache_manager {
+      if let Err(e) = cache_manager.read().await.flush_disk_index().await {
+        report.errors.push(format!("cache flush: {e}"));
+      }
+    }
+
+    self
+      .progress(
+        ShutdownStage::StoppingServices,
+        "shutting down: stopping services…",
+      )
+      .await;
+    if let Err(e) = self.services.shutdown_all().await {
+      report.errors.push(format!("services: {e}"));
+    }
+
+    if let Some(telemetry) = &self.telemetry {
+      self
+        .progress(
+          ShutdownStage::StoppingTelemetry,
+          "shutting down: stopping telemetry…",
+        )
+        .await;
+      if let Err(e) = telemetry.shutdown().await {
+        report.errors.push(format!("telemetry: {e}"));
+      }
+    }
+
+    for error in &report.errors {
+      log::warn!("Ошибка при завершении работы: {error}");
+    }
+
+    self.completed.store(true, Ordering::SeqCst);
+    self
+      .progress(ShutdownStage::Completed, "shutdown complete")
+      .await;
+    let _ = self
+      .event_bus
+      .publish_app_event(AppEvent::SystemShutdown)
+      .await;
+
+    Ok(report)
+  }
+
+  /// Дождаться рендеров в пределах grace period, возвращает число завершившихся
+  async fn drain_renders(&self, render: &dyn RenderService, report: &mut ShutdownReport) -> usize {
+    let initial = match render.running_jobs().await {
+      Ok(jobs) => jobs.len(),
+      Err(e) => {
+        report.errors.push(format!("render jobs: {e}"));
+        return 0;
+      }
+    };
+    if initial == 0 || self.force.is_cancelled() {
+      return 0;
+    }
+
+    self
+      .progress(
+        ShutdownStage::DrainingRenders,
+        &format!("shutting down: waiting for {initial} renders…"),
+      )
+      .await;
+
+    let deadline = tokio::time::sleep(self.config.grace_period);
+    tokio::pin!(deadline);
+
+    let mut remaining = initial;
+    while remaining > 0 {
+      tokio::select! {
+        _ = &mut deadline => break,
+        _ = self.force.cancelled() => break,
+        _ = tokio::time::sleep(self.config.poll_interval) => {}
+      }
+      remaining = render
+        .running_jobs()
+        .await
+        .map_or(remaining, |jobs| jobs.len());
+    }
+
+    initial.saturating_sub(remaining)
+  }
+
+  /// Жестко прервать оставшиеся рендеры
+  async fn cancel_renders(&self, render: &dyn RenderService, report: &mut ShutdownReport) {
+    let jobs = render.running_jobs().await.unwrap_or_default();
+    if jobs.is_empty() {
+      return;
+    }
+
+    self
+      .progress(
+        ShutdownStage::CancellingRenders,
+        &format!("shutting down: cancelling {} renders…", jobs.len()),
+      )
+      .await;
+
+    for job_id in jobs {
+      match render.abort_render(&job_id, SHUTDOWN_ABORT_REASON).await {
+        Ok(_) => {
+          let _ = self
+            .event_bus
+            .publish_app_event(AppEvent::RenderFailed {
+              job_id: job_id.clone(),
+              error: SHUTDOWN_ABORT_REASON.to_string(),
+            })
+            .await;
+          report.cancelled_renders.push(job_id);
+        }
+        Err(e) => report.errors.push(format!("cancel {job_id}: {e}")),
+      }
+    }
+  }
+
+  async fn progress(&self, stage: ShutdownStage, message: &str) {
+    log::info!("[Shutdown] {message}");
+    let progress = ShutdownProgress {
+      stage,
+      message: message.to_string(),
+    };
+
+    if let Some(listener) = &self.progress_listener {
+      listener(&progress);
+    }
+    let _ = self
+      .event_bus
+      .publish_app_event(AppEvent::ShutdownProgress {
+        stage: stage.as_str().to_string(),
+        message: progress.message,
+      })
+      .await;
+  }
+}
+
+/// Запросить завершение работы приложения
+///
+/// Окно закрывается после завершения последовательности. Повторный вызов с
+/// `force = true` прерывает ожидание активных рендеров.
+#[tauri::command]
+pub async fn request_shutdown<R: tauri::Runtime>(
+  force: bool,
+  app: tauri::AppHandle<R>,
+  shutdown: tauri::State<'_, Arc<AppShutdown>>,
+) -> Result<ShutdownReport> {
+  let report = shutdown.request(force).await?;
+  if report.status == ShutdownStatus::Completed {
+    app.exit(0);
+  }
+  Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::video_compiler::progress::RenderProgress;
+  use crate::video_compiler::schema::ProjectSchema;
+  use crate::video_compiler::services::{Service, ServiceMetricsContainer};
+  use crate::video_compiler::tests::mocks::mock_services_builder;
+  use async_trait::async_trait;
+  use std::path::PathBuf;
+  use std::sync::Mutex;
+
+  /// Рендер-сервис с управляемым списком выполняющихся задач
+  #[derive(Default)]
+  struct ControlledRender {
+    running: Mutex<Vec<String>>,
+    aborted: Mutex<Vec<(String, String)>>,
+  }
+
+  impl ControlledRender {
+    fn with_jobs(jobs: &[&str]) -> Arc<Self> {
+      Arc::new(Self {
+        running: Mutex::new(jobs.iter().map(|j| j.to_string()).collect()),
+        aborted: Mutex::new(Vec::new()),
+      })
+    }
+  }
+
+  #[async_trait]
+  impl Service for ControlledRender {
+    async fn initialize(&self) -> Result<()> {
+      Ok(())
+    }
+
+    async fn health_check(&self) -> Result<()> {
+      Ok(())
+    }
+
+    async fn shutdown(&self) -> Result<()> {
+      Ok(())
+    }
+  }
+
+  #[async_trait]
+  impl RenderService for ControlledRender {
+    async fn start_render(&self, _project: ProjectSchema, _output: PathBuf) -> Result<String> {
+      Ok("job".to_string())
+    }
+
+    async fn get_progress(&self, _job_id: &str) -> Result<Option<RenderProgress>> {
+      Ok(None)
+    }
+
+    async fn cancel_render(&self, _job_id: &str) -> Result<bool> {
+      Ok(false)
+    }
+
+    async fn pause_render(&self, _job_id: &str) -> Result<bool> {
+      Ok(false)
+    }
+
+    async fn resume_render(&self, _job_id: &str) -> Result<bool> {
+      Ok(false)
+    }
+
+    async fn get_active_jobs(&self) -> Result<Vec<String>> {
+      Ok(self.running.lock().unwrap().clone())
+    }
+
+    async fn has_available_slots(&self) -> Result<bool> {
+      Ok(true)
+    }
+
+    async fn abort_render(&self, job_id: &str, reason: &str) -> Result<bool> {
+      self.running.lock().unwrap().retain(|j| j != job_id);
+      self
+        .aborted
+        .lock()
+        .unwrap()
+        .push((job_id.to_string(), reason.to_string()));
+      Ok(true)
+    }
+  }
+
+  fn coordinator(render: Arc<ControlledRender>, grace_period: Duration) -> AppShutdown {
+    let services = mock_services_builder()
+      .with_render(render)
+      .build_with_metrics(ServiceMetricsContainer::unregistered());
+
+    AppShutdown::new(Arc::new(services), Arc::new(EventBus::new())).with_config(ShutdownConfig {
+      grace_period,
+      poll_interval: Duration::from_millis(10),
+    })
+  }
+
+  #[tokio::test]
+  async fn test_renders_finishing_within_grace_period() {
+    let render = ControlledRender::with_jobs(&["job-1"]);
+    let shutdown = coordinator(render.clone(), Duration::from_secs(5));
+
+    let finishing = render.clone();
+    tokio::spawn(async move {
+      tokio::time::sleep(Duration::from_millis(30)).await;
+      finishing.running.lock().unwrap().clear();
+    });
+
+    let report = shutdown.request(false).await.unwrap();
+    assert_eq!(report.status, ShutdownStatus::Completed);
+    assert_eq!(report.finished_renders, 1);
+    assert!(report.cancelled_renders.is_empty());
+    assert!(render.aborted.lock().unwrap().is_empty());
+    assert!(shutdown.is_completed());
+  }
+
+  #[tokio::test]
+  async fn test_renders_cancelled_after_grace_period() {
+    let render = ControlledRender::with_jobs(&["job-1", "job-2"]);
+    let progress = Arc::new(Mutex::new(Vec::new()));
+    let recorded = progress.clone();
+    let shutdown = coordinator(render.clone(), Duration::from_millis(30))
+      .with_progress_listener(move |p| recorded.lock().unwrap().push(p.clone()));
+
+    let report = shutdown.request(false).await.unwrap();
+    assert_eq!(report.cancelled_renders, vec!["job-1", "job-2"]);
+    assert_eq!(report.finished_renders, 0);
+
+    let aborted = render.aborted.lock().unwrap();
+    assert!(aborted.iter().all(|(_, r)| r == SHUTDOWN_ABORT_REASON));
+
+    let progress = progress.lock().unwrap();
+    let stages: Vec<ShutdownStage> = progress.iter().map(|p| p.stage).collect();
+    assert_eq!(
+      stages,
+      vec![
+        ShutdownStage::DrainingRenders,
+        ShutdownStage::CancellingRenders,
+        ShutdownStage::FlushingCaches,
+        ShutdownStage::StoppingServices,
+        ShutdownStage::Completed,
+      ]
+    );
+    assert_eq!(progress[1].message, "shutting down: cancelling 2 renders…");
+  }
+
+  #[tokio::test]
+  async fn test_second_force_request_bypasses_grace_period() {
+    let render = ControlledRender::with_jobs(&["job-1"]);
+    let shutdown = Arc::new(coordinator(render.clone(), Duration::from_secs(60)));
+
+    let first = tokio::spawn({
+      let shutdown = shutdown.clone();
+      async move { shutdown.request(false).await.unwrap() }
+    });
+    tokio::time::sleep(Duration::from_millis(30)).await;
+
+    let second = shutdown.request(true).await.unwrap();
+    assert_eq!(second.status, ShutdownStatus::InProgress);
+
+    let report = tokio::time::timeout(Duration::from_secs(5), first)
+      .await
+      .expect("force request must bypass the grace period")
+      .unwrap();
+    assert_eq!(report.cancelled_renders, vec!["job-1"]);
+
+    // После завершения повторный запрос сразу сообщает о готовности
+    let again = shutdown.request(false).await.unwrap();
+    assert_eq!(again.status, ShutdownStatus::Completed);
+  }
+}
@@ -0,0 +1,312 @@
+//! Отчет о запуске приложения и безопасный режим
+//!
+//! Во время setup каждый компонент (Video Compiler, распознавание, защищенное
+//! хранилище, плагины, телеметрия) записывает в [`StartupReport`] свое
+//! состояние: работает, работает с ограничениями или недоступен, вместе с
+//! ошибкой и подсказкой по исправлению. UI получает отчет командой
+//! `get_startup_report` и показывает недоступные компоненты сразу.
+//!
+//! Безопасный режим включается переменной окружения [`SAFE_MODE_ENV`] или
+//! автоматически, если предыдущий запуск не дошел до конца setup (остался
+//! маркер [`STARTUP_MARKER_FILE`]). В безопасном режиме не загружаются
+//! плагины и не определяются GPU.
+
+use crate::video_compiler::error::VideoCompilerError;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Переменная окружения для принудительного безопасного режима
+pub const SAFE_MODE_ENV: &str = "TIMELINE_STUDIO_SAFE_MODE";
+
+/// Маркер незавершенного запуска в базовой директории приложения
+pub const STARTUP_MARKER_FILE: &str = "startup.pending";
+
+/// Компонент приложения, инициализируемый при запуске
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StartupComponent {
+  VideoCompiler,
+  Recognition,
+  SecureStorage,
+  PluginManager,
+  Telemetry,
+}
+
+/// Состояние компонента после запуска
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComponentStatus {
+  /// Компонент полностью работает
+  Ok,
+  /// Компонент работает с ограничениями
+  Degraded,
+  /// Компонент недоступен
+  Failed,
+}
+
+/// Причина включения безопасного режима
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SafeModeReason {
+  /// Задан через переменную окружения
+  Requested,
+  /// Предыдущий запуск завершился аварийно до окончания setup
+  PreviousStartupCrashed,
+}
+
+/// Состояние одного компонента
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComponentReport {
+  pub component: StartupComponent,
+  pub status: ComponentStatus,
+  /// Ошибка инициализации или причина ограничений
+  pub error: Option<String>,
+  /// Что сделать пользователю, чтобы восстановить компонент
+  pub remediation: Option<String>,
+}
+
+/// Отчет о запуске приложения
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StartupReport {
+  /// Причина безопасного режима (`None` - обычный запуск)
+  pub safe_mode: Option<SafeModeReason>,
+  pub components: Vec<ComponentReport>,
+}
+
+impl StartupReport {
+  pub fn new(safe_mode: Option<SafeModeReason>) -> Self {
+    Self {
+      safe_mode,
+      components: Vec::new(),
+    }
+  }
+
+  /// Запущено ли приложение в безопасном режиме
+  pub fn is_safe_mode(&self) -> bool {
+    self.safe_mode.is_some()
+  }
+
+  /// Компонент работает
+  pub fn ok(&mut self, component: StartupComponent) {
+    self.record(component, ComponentStatus::Ok, None, None);
+  }
+
+  /// Компонент работает с ограничениями
+  pub fn degraded(
+    &mut self,
+    component: StartupComponent,
+    error: impl Into<String>,
+    remediation: impl Into<String>,
+  ) {
+    self.record(
+      component,
+      ComponentStatus::Degraded,
+      Some(error.into()),
+      Some(remediation.into()),
+    );
+  }
+
+  /// Компонент недоступен
+  pub fn failed(
+    &mut self,
+    component: StartupComponent,
+    error: impl Into<String>,
+    remediation: impl Into<String>,
+  ) {
+    self.record(
+      component,
+      ComponentStatus::Failed,
+      Some(error.into()),
+      Some(remediation.into()),
+    );
+  }
+
+  /// Записать состояние компонента, заменив предыдущую запись
+  pub fn record(
+    &mut self,
+    component: StartupComponent,
+    status: ComponentStatus,
+    error: Option<String>,
+    remediation: Option<String>,
+  ) {
+    self
+      .components
+      .retain(|report| report.component != component);
+    self.components.push(ComponentReport {
+      component,
+      status,
+      error,
+      remediation,
+    });
+  }
+
+  /// Состояние компонента
+  pub fn component(&self, component: StartupComponent) -> Option<&ComponentReport> {
+    self
+      .components
+      .iter()
+      .find(|report| report.component == component)
+  }
+
+  /// Компоненты, работающие не полностью
+  pub fn unavailable(&self) -> impl Iterator<Item = &ComponentReport> {
+    self
+      .components
+      .iter()
+      .filter(|report| report.status != ComponentStatus::Ok)
+  }
+
+  /// Записать результат инициализации Video Compiler
+  pub fn record_video_compiler<T>(&mut self, result: &Result<T, VideoCompilerError>) {
+    match result {
+      Ok(_) if self.is_safe_mode() => self.degraded(
+        StartupComponent::VideoCompiler,
+        "Определение GPU пропущено в безопасном режиме",
+        "Перезапустите приложение в обычном режиме, чтобы включить аппаратное ускорение",
+      ),
+      Ok(_) => self.ok(StartupComponent::VideoCompiler),
+      Err(e) => {
+        let remediation = match e {
+          VideoCompilerError::DependencyMissing(_) => {
+            "Установите FFmpeg или скачайте встроенную сборку в настройках, затем перезапустите приложение"
+          }
+          _ => "Проверьте доступ к временной директории в настройках и перезапустите приложение",
+        };
+        self.failed(StartupComponent::VideoCompiler, e.to_string(), remediation);
+      }
+    }
+  }
+}
+
+/// Определить, нужно ли запускаться в безопасном режиме
+pub fn detect_safe_mode(env_value: Option<&str>, marker: Option<&Path>) -> Option<SafeModeReason> {
+  let requested = env_value.is_some_and(|value| {
+    matches!(
+      value.trim().to_ascii_lowercase().as_str(),
+      "1" | "true" | "yes" | "on"
+    )
+  });
+  if requested {
+    return Some(SafeModeReason::Requested);
+  }
+
+  marker
+    .is_some_and(Path::exists)
+    .then_some(SafeModeReason::PreviousStartupCrashed)
+}
+
+/// Путь к маркеру незавершенного запуска
+pub fn default_marker_path() -> Option<PathBuf> {
+  crate::app_dirs::AppDirectories::get_or_create()
+    .ok()
+    .map(|dirs| dirs.base_dir.join(STARTUP_MARKER_FILE))
+}
+
+/// Отметить начало запуска. Маркер остается, если setup не завершится.
+pub fn begin_startup(marker: &Path) -> std::io::Result<()> {
+  if let Some(parent) = marker.parent() {
+    std::fs::create_dir_all(parent)?;
+  }
+  std::fs::write(marker, chrono::Utc::now().to_rfc3339())
+}
+
+/// Отметить успешное завершение setup
+pub fn complete_startup(marker: &Path) -> std::io::Result<()> {
+  match std::fs::remove_file(marker) {
+    Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+    _ => Ok(()),
+  }
+}
+
+/// Получить отчет о запуске приложения
+#[tauri::command]
+pub fn get_startup_report(report: tauri::State<'_, StartupReport>) -> StartupReport {
+  report.inner().clone()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::video_compiler::{initialize_with, InitOptions};
+  use tempfile::TempDir;
+
+  #[tokio::test]
+  async fn test_report_when_ffmpeg_detection_fails() {
+    let result = initialize_with(InitOptions {
+      ffmpeg_path: Some(PathBuf::from("/nonexistent/ffmpeg")),
+      skip_gpu_detection: false,
+    })
+    .await;
+    assert!(matches!(
+      result,
+      Err(VideoCompilerError::DependencyMissing(_))
+    ));
+
+    let mut report = StartupReport::new(None);
+    report.record_video_compiler(&result);
+    report.ok(StartupComponent::SecureStorage);
+
+    let video = report.component(StartupComponent::VideoCompiler).unwrap();
+    assert_eq!(video.status, ComponentStatus::Failed);
+    assert!(video
+      .error
+      .as_ref()
+      .unwrap()
+      .contains("/nonexistent/ffmpeg"));
+    assert!(video.remediation.as_ref().unwrap().contains("FFmpeg"));
+
+    let unavailable: Vec<_> = report.unavailable().map(|r| r.component).collect();
+    assert_eq!(unavailable, vec![StartupComponent::VideoCompiler]);
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["safe_mode"], serde_json::Value::Null);
+    assert_eq!(json["components"][0]["component"], "video_compiler");
+    assert_eq!(json["components"][0]["status"], "failed");
+  }
+
+  #[test]
+  fn test_safe_mode_degrades_video_compiler() {
+    let mut report = StartupReport::new(Some(SafeModeReason::Requested));
+    report.record_video_compiler(&Ok::<(), VideoCompilerError>(()));
+
+    let video = report.component(StartupComponent::VideoCompiler).unwrap();
+    assert_eq!(video.status, ComponentStatus::Degraded);
+    assert!(video.remediation.is_some());
+  }
+
+  #[test]
+  fn test_record_replaces_previous_entry() {
+    let mut report = StartupReport::default();
+    report.failed(StartupComponent::Telemetry, "boom", "retry");
+    report.ok(StartupComponent::Telemetry);
+
+    assert_eq!(report.components.len(), 1);
+    assert_eq!(report.components[0].status, ComponentStatus::Ok);
+    assert_eq!(report.unavailable().count(), 0);
+  }
+
+  #[test]
+  fn test_detect_safe_mode() {
+    let temp_dir = TempDir::new().unwrap();
+    let marker = temp_dir.path().join(STARTUP_MARKER_FILE);
+
+    assert_eq!(detect_safe_mode(None, Some(&marker)), None);
+    assert_eq!(detect_safe_mode(Some("0"), Some(&marker)), None);
+    assert_eq!(
+      detect_safe_mode(Some("true"), None),
+      Some(SafeModeReason::Requested)
+    );
+
+    begin_startup(&marker).unwrap();
+    assert_eq!(
+      detect_safe_mode(None, Some(&marker)),
+      Some(SafeModeReason::PreviousStartupCrashed)
+    );
+
+    complete_startup(&marker).unwrap();
+    assert!(!marker.exists());
+    assert_eq!(detect_safe_mode(None, Some(&marker)), None);
+    // Повторное завершение не считается ошибкой
+    complete_startup(&marker).unwrap();
+  }
+}
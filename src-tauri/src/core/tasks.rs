@@ -0,0 +1,422 @@
+//! Единый реестр фоновых задач
+//!
+//! Долгие операции (рендеринг, превью, распознавание, транскрипция, прокси)
+//! регистрируются в [`TaskManager`], обновляют прогресс через [`TaskHandle`] и
+//! снимаются с учета при завершении. Отмена из UI маршрутизируется в собственный
+//! механизм отмены операции через зарегистрированный callback.
+
+use crate::video_compiler::error::{Result, VideoCompilerError};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::watch;
+use uuid::Uuid;
+
+/// Имя Tauri события со снимком фоновых задач
+pub const BACKGROUND_TASKS_EVENT: &str = "background-tasks-updated";
+
+/// Интервал между событиями обновления задач (~4 Гц)
+pub const TASK_UPDATE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Глобальный реестр фоновых задач приложения
+pub static TASKS: Lazy<Arc<TaskManager>> = Lazy::new(|| Arc::new(TaskManager::new()));
+
+/// Тип фоновой задачи
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskKind {
+  Render,
+  Preview,
+  Recognition,
+  Transcription,
+  Proxy,
+  Prerender,
+  CacheWarmup,
+  Other,
+}
+
+/// Состояние фоновой задачи
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskStatus {
+  Queued,
+  Running,
+  Paused,
+  /// Отмена запрошена, операция завершается
+  Cancelling,
+}
+
+/// Снимок фоновой задачи для UI
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackgroundTask {
+  pub id: String,
+  pub kind: TaskKind,
+  pub title: String,
+  /// Процент выполнения (0.0 - 100.0)
+  pub progress: f32,
+  pub status: TaskStatus,
+  pub message: Option<String>,
+  pub created_at: chrono::DateTime<chrono::Utc>,
+  pub cancellable: bool,
+}
+
+type CancelCallback = Arc<dyn Fn() + Send + Sync>;
+
+struct TaskEntry {
+  task: BackgroundTask,
+  cancel: Option<CancelCallback>,
+}
+
+/// Реестр фоновых задач
+pub struct TaskManager {
+  tasks: Mutex<HashMap<String, TaskEntry>>,
+  updates: watch::Sender<Vec<BackgroundTask>>,
+}
+
+impl TaskManager {
+  pub fn new() -> Self {
+    let (updates, _) = watch::channel(Vec::new());
+    Self {
+      tasks: Mutex::new(HashMap::new()),
+      updates,
+    }
+  }
+
+  /// Зарегистрировать задачу без возможности отмены
+  pub fn register(self: &Arc<Self>, kind: TaskKind, title: impl Into<String>) -> TaskHandle {
+    self.insert(kind, title.into(), None)
+  }
+
+  /// Зарегистрировать задачу, отмена которой вызывает `cancel`
+  pub fn register_cancellable(
+    self: &Arc<Self>,
+    kind: TaskKind,
+    title: impl Into<String>,
+    cancel: impl Fn() + Send + Sync + 'static,
+  ) -> TaskHandle {
+    self.insert(kind, title.into(), Some(Arc::new(cancel)))
+  }
+
+  fn insert(
+    self: &Arc<Self>,
+    kind: TaskKind,
+    title: String,
+    cancel: Option<CancelCallback>,
+  ) -> TaskHandle {
+    let id = Uuid::new_v4().to_string();
+    let task = BackgroundTask {
+      id: id.clone(),
+      kind,
+      title,
+      progress: 0.0,
+      status: TaskStatus::Queued,
+      message: None,
+      created_at: chrono::Utc::now(),
+      cancellable: cancel.is_some(),
+    };
+
+    self
+      .tasks
+      .lock()
+      .unwrap()
+      .insert(id.clone(), TaskEntry { task, cancel });
+    self.publish();
+
+    TaskHandle {
+      id,
+      manager: self.clone(),
+    }
+  }
+
+  /// Активные задачи в порядке создания
+  pub fn list(&self) -> Vec<BackgroundTask> {
+    let mut tasks: Vec<BackgroundTask> = self
+      .tasks
+      .lock()
+      .unwrap()
+      .values()
+      .map(|entry| entry.task.clone())
+      .collect();
+    tasks.sort_by_key(|task| task.created_at);
+    tasks
+  }
+
+  /// Получить задачу по ID
+  pub fn get(&self, task_id: &str) -> Option<BackgroundTask> {
+    self
+      .tasks
+      .lock()
+      .unwrap()
+      .get(task_id)
+      .map(|entry| entry.task.clone())
+  }
+
+  /// Отменить задачу через механизм отмены ее операции
+  ///
+  /// Возвращает false, если задача не найдена. Задача остается в реестре до
+  /// тех пор, пока операция не завершится и не снимет ее с учета.
+  pub fn cancel(&self, task_id: &str) -> Result<bool> {
+    let cancel = {
+      let mut tasks = self.tasks.lock().unwrap();
+      let Some(entry) = tasks.get_mut(task_id) else {
+        return Ok(false);
+      };
+      let Some(cancel) = entry.cancel.clone() else {
+        return Err(VideoCompilerError::validation(format!(
+          "Task {task_id} cannot be cancelled"
+        )));
+      };
+      entry.task.status = TaskStatus::Cancelling;
+      cancel
+    };
+
+    // Callback вызывается вне блокировки: операция может сразу снять задачу с учета
+    cancel();
+    self.publish();
+    Ok(true)
+  }
+
+  /// Подписаться на снимки задач
+  ///
+  /// Канал хранит последний снимок, поэтому новый подписчик сразу получает
+  /// текущее состояние.
+  pub fn subscribe(&self) -> watch::Receiver<Vec<BackgroundTask>> {
+    self.updates.subscribe()
+  }
+
+  fn update(&self, task_id: &str, apply: impl FnOnce(&mut BackgroundTask)) {
+    let updated = {
+      let mut tasks = self.tasks.lock().unwrap();
+      match tasks.get_mut(task_id) {
+        Some(entry) => {
+          apply(&mut entry.task);
+          true
+        }
+        None => false,
+      }
+    };
+    if updated {
+      self.publish();
+    }
+  }
+
+  fn remove(&self, task_id: &str) {
+    if self.tasks.lock().unwrap().remove(task_id).is_some() {
+      self.publish();
+    }
+  }
+
+  fn publish(&self) {
+    self.updates.send_replace(self.list());
+  }
+}
+
+impl Default for TaskManager {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+/// Handle зарегистрированной задачи
+///
+/// Задача снимается с учета при вызове [`TaskHandle::finish`] или при drop.
+pub struct TaskHandle {
+  id: String,
+  manager: Arc<TaskManager>,
+}
+
+impl TaskHandle {
+  pub fn id(&self) -> &str {
+    &self.id
+  }
+
+  /// Обновить процент выполнения (0.0 - 100.0)
+  pub fn set_progress(&self, progress: f32) {
+    self.manager.update(&self.id, |task| {
+      task.progress = progress.clamp(0.0, 100.0);
+      if task.status == TaskStatus::Queued {
+        task.status = TaskStatus::Running;
+      }
+    });
+  }
+
+  /// Обновить состояние задачи
+  pub fn set_status(&self, status: TaskStatus) {
+    self.manager.update(&self.id, |task| {
+      // Запрошенную отмену не перезаписываем
+      if task.status != TaskStatus::Cancelling {
+        task.status = status;
+      }
+    });
+  }
+
+  /// Обновить сообщение о текущем шаге
+  pub fn set_message(&self, message: impl Into<String>) {
+    let message = message.into();
+    self
+      .manager
+      .update(&self.id, |task| task.message = Some(message));
+  }
+
+  /// Снять задачу с учета
+  pub fn finish(self) {}
+}
+
+impl Drop for TaskHandle {
+  fn drop(&mut self) {
+    self.manager.remove(&self.id);
+  }
+}
+
+/// Пересылать снимки задач не чаще одного раза за `interval`
+///
+/// Промежуточные изменения схлопываются, `emit` получает последний снимок.
+/// Первым отправляется текущее состояние. Завершается, когда реестр удален.
+pub async fn forward_task_updates<F>(manager: &TaskManager, interval: Duration, emit: F)
+where
+  F: Fn(&[BackgroundTask]),
+{
+  let mut receiver = manager.subscribe();
+  loop {
+    let snapshot = receiver.borrow_and_update().clone();
+    emit(&snapshot);
+    tokio::time::sleep(interval).await;
+    if receiver.changed().await.is_err() {
+      break;
+    }
+  }
+}
+
+/// Получить список активных фоновых задач
+#[tauri::command]
+pub async fn list_background_tasks() -> Result<Vec<BackgroundTask>> {
+  Ok(TASKS.list())
+}
+
+/// Отменить фоновую задачу
+///
+/// Возвращает false, если задача уже завершилась.
+#[tauri::command]
+pub async fn cancel_background_task(task_id: String) -> Result<bool> {
+  TASKS.cancel(&task_id)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::sync::atomic::{AtomicUsize, Ordering};
+
+  #[test]
+  fn test_register_and_finish() {
+    let manager = Arc::new(TaskManager::new());
+    let handle = manager.register(TaskKind::Render, "Export project");
+
+    let tasks = manager.list();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].id, handle.id());
+    assert_eq!(tasks[0].kind, TaskKind::Render);
+    assert_eq!(tasks[0].status, TaskStatus::Queued);
+    assert!(!tasks[0].cancellable);
+
+    handle.finish();
+    assert!(manager.list().is_empty());
+  }
+
+  #[test]
+  fn test_drop_deregisters_task() {
+    let manager = Arc::new(TaskManager::new());
+    {
+      let _handle = manager.register(TaskKind::Recognition, "Batch");
+      assert_eq!(manager.list().len(), 1);
+    }
+    assert!(manager.list().is_empty());
+  }
+
+  #[test]
+  fn test_progress_propagation() {
+    let manager = Arc::new(TaskManager::new());
+    let receiver = manager.subscribe();
+    let handle = manager.register(TaskKind::Render, "Export");
+
+    handle.set_progress(42.5);
+    handle.set_message("encoding");
+
+    let task = manager.get(handle.id()).unwrap();
+    assert_eq!(task.progress, 42.5);
+    assert_eq!(task.status, TaskStatus::Running);
+    assert_eq!(task.message.as_deref(), Some("encoding"));
+
+    // Подписчик видит последний снимок
+    let snapshot = receiver.borrow().clone();
+    assert_eq!(snapshot.len(), 1);
+    assert_eq!(snapshot[0].progress, 42.5);
+
+    handle.set_progress(150.0);
+    assert_eq!(manager.get(handle.id()).unwrap().progress, 100.0);
+  }
+
+  #[test]
+  fn test_cancel_routes_to_callback() {
+    let manager = Arc::new(TaskManager::new());
+    let calls = Arc::new(AtomicUsize::new(0));
+    let calls_clone = calls.clone();
+    let handle = manager.register_cancellable(TaskKind::Render, "Export", move || {
+      calls_clone.fetch_add(1, Ordering::SeqCst);
+    });
+
+    assert!(manager.cancel(handle.id()).unwrap());
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    let task = manager.get(handle.id()).unwrap();
+    assert_eq!(task.status, TaskStatus::Cancelling);
+
+    // Операция не перезаписывает запрошенную отмену
+    handle.set_status(TaskStatus::Running);
+    assert_eq!(
+      manager.get(handle.id()).unwrap().status,
+      TaskStatus::Cancelling
+    );
+  }
+
+  #[test]
+  fn test_cancel_unknown_and_non_cancellable() {
+    let manager = Arc::new(TaskManager::new());
+    assert!(!manager.cancel("missing").unwrap());
+
+    let handle = manager.register(TaskKind::Proxy, "Proxy");
+    assert!(manager.cancel(handle.id()).is_err());
+  }
+
+  #[tokio::test]
+  async fn test_forward_updates_is_sticky_and_throttled() {
+    let manager = Arc::new(TaskManager::new());
+    let handle = manager.register(TaskKind::Render, "Export");
+
+    let received = Arc::new(Mutex::new(Vec::<Vec<BackgroundTask>>::new()));
+    let received_clone = received.clone();
+    let manager_clone = manager.clone();
+    let forwarder = tokio::spawn(async move {
+      forward_task_updates(&manager_clone, Duration::from_millis(50), |tasks| {
+        received_clone.lock().unwrap().push(tasks.to_vec());
+      })
+      .await;
+    });
+
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    // Первый снимок содержит задачу, зарегистрированную до подписки
+    assert_eq!(received.lock().unwrap()[0].len(), 1);
+
+    for progress in 1..=10 {
+      handle.set_progress(progress as f32 * 10.0);
+    }
+    tokio::time::sleep(Duration::from_millis(120)).await;
+    forwarder.abort();
+
+    let received = received.lock().unwrap();
+    // Десять обновлений схлопнуты в одно событие
+    assert_eq!(received.len(), 2);
+    assert_eq!(received[1][0].progress, 100.0);
+  }
+}
@@ -0,0 +1,596 @@
+//! Метрики дискового кэша Video Compiler
+//!
+//! [`CacheServiceWithMetrics`](crate::video_compiler::services::cache_service_with_metrics::CacheServiceWithMetrics)
+//! записывает попадания, промахи, вытеснения, объем и латентность операций по доменам кэша.
+//! Запись идет после возврата из внутреннего сервиса, поэтому блокировки кэша в этот момент
+//! не удерживаются. Пока телеметрия выключена, обертка только проверяет флаг.
+
+use super::command_metrics::percentile;
+use super::metrics::{Counter, Gauge, Histogram, MetricsCollector};
+use crate::core::{AppEvent, EventBus};
+use crate::video_compiler::error::Result;
+use once_cell::sync::Lazy;
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Сколько последних измерений операции хранится для расчета перцентилей
+const LATENCY_WINDOW: usize = 512;
+
+/// Сколько самых больших элементов попадает в периодический снимок
+const SNAPSHOT_TOP_N: usize = 10;
+
+/// Глобальные метрики кэша текущей сессии
+pub static CACHE_METRICS: Lazy<Arc<CacheMetrics>> = Lazy::new(Arc::default);
+
+/// Домен кэша, определяется по первому сегменту ключа
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheDomain {
+  Preview,
+  Metadata,
+  Render,
+  Other,
+}
+
+impl CacheDomain {
+  /// Все домены в порядке вывода в отчете
+  pub const ALL: [CacheDomain; 4] = [
+    CacheDomain::Preview,
+    CacheDomain::Metadata,
+    CacheDomain::Render,
+    CacheDomain::Other,
+  ];
+
+  /// Домен по ключу вида `preview/...`, `metadata/...` или `render/...`
+  pub fn from_key(key: &str) -> Self {
+    match key.split(['/', '\\']).next().unwrap_or_default() {
+      "preview" | "previews" => CacheDomain::Preview,
+      "metadata" => CacheDomain::Metadata,
+      "render" | "renders" => CacheDomain::Render,
+      _ => CacheDomain::Other,
+    }
+  }
+
+  /// Значение метки `domain`
+  pub fn as_str(self) -> &'static str {
+    match self {
+      CacheDomain::Preview => "preview",
+      CacheDomain::Metadata => "metadata",
+      CacheDomain::Render => "render",
+      CacheDomain::Other => "other",
+    }
+  }
+}
+
+/// Результат обращения к кэшу по ключу
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheAccess {
+  /// Данные найдены
+  Hit { size_bytes: u64 },
+  /// Данных нет
+  Miss,
+  /// Данные записаны
+  Store { size_bytes: u64 },
+  /// Проверка наличия: учитывается только латентность
+  Probe,
+}
+
+/// Инструменты OpenTelemetry для кэша
+struct CacheInstruments {
+  hits: Counter,
+  misses: Counter,
+  evictions: Counter,
+  bytes_stored: Gauge,
+  latency: Histogram,
+}
+
+/// Статистика одной операции
+#[derive(Default)]
+struct OperationStats {
+  count: u64,
+  latencies_ms: VecDeque<f64>,
+}
+
+/// Статистика одного домена за сессию
+#[derive(Default)]
+struct DomainStats {
+  hits: u64,
+  misses: u64,
+  evictions: u64,
+  bytes_stored: u64,
+  operations: HashMap<&'static str, OperationStats>,
+}
+
+#[derive(Default)]
+struct CacheMetricsState {
+  domains: HashMap<CacheDomain, DomainStats>,
+  /// Размер известных элементов по ключу
+  entries: HashMap<String, u64>,
+}
+
+/// Латентность одной операции кэша
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheOperationLatency {
+  pub operation: String,
+  pub count: u64,
+  pub p50_ms: f64,
+  pub p95_ms: f64,
+}
+
+/// Агрегаты одного домена кэша
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheDomainReport {
+  pub domain: CacheDomain,
+  pub hits: u64,
+  pub misses: u64,
+  /// Доля попаданий среди чтений (0.0 если чтений не было)
+  pub hit_ratio: f64,
+  pub evictions: u64,
+  pub bytes_stored: u64,
+  pub entries: usize,
+  pub operations: Vec<CacheOperationLatency>,
+}
+
+/// Элемент кэша в списке самых больших
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheEntrySize {
+  pub key: String,
+  pub domain: CacheDomain,
+  pub size_bytes: u64,
+}
+
+/// Отчет по метрикам кэша текущей сессии
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheMetricsReport {
+  pub enabled: bool,
+  pub timestamp: chrono::DateTime<chrono::Utc>,
+  pub domains: Vec<CacheDomainReport>,
+  /// Самые большие известные элементы
+  pub largest_entries: Vec<CacheEntrySize>,
+}
+
+/// Сборщик метрик дискового кэша
+pub struct CacheMetrics {
+  enabled: AtomicBool,
+  instruments: RwLock<Option<CacheInstruments>>,
+  state: Mutex<CacheMetricsState>,
+}
+
+impl Default for CacheMetrics {
+  fn default() -> Self {
+    Self {
+      enabled: AtomicBool::new(false),
+      instruments: RwLock::new(None),
+      state: Mutex::new(CacheMetricsState::default()),
+    }
+  }
+}
+
+impl CacheMetrics {
+  /// Подключить коллектор метрик и включить сбор
+  pub fn install(&self, collector: &MetricsCollector) -> Result<()> {
+    let instruments = CacheInstruments {
+      hits: collector.counter("cache_hits_total", "Number of cache hits by domain")?,
+      misses: collector.counter("cache_misses_total", "Number of cache misses by domain")?,
+      evictions: collector.counter(
+        "cache_evictions_total",
+        "Number of cache entries removed from disk by domain",
+      )?,
+      bytes_stored: collector.gauge("cache_bytes_stored", "Bytes stored in cache by domain")?,
+      latency: collector.histogram(
+        "cache_operation_duration_seconds",
+        "Cache operation execution time",
+      )?,
+    };
+
+    *self.instruments.write() = Some(instruments);
+    self.set_enabled(true);
+    Ok(())
+  }
+
+  /// Включить или выключить сбор метрик
+  pub fn set_enabled(&self, enabled: bool) {
+    self.enabled.store(enabled, Ordering::Relaxed);
+  }
+
+  /// Включен ли сбор метрик
+  pub fn is_enabled(&self) -> bool {
+    self.enabled.load(Ordering::Relaxed)
+  }
+
+  /// Сбросить статистику сессии
+  pub fn reset(&self) {
+    let mut state = self.state.lock();
+    state.domains.clear();
+    state.entries.clear();
+  }
+
+  /// Записать обращение к кэшу по ключу
+  pub fn record(&self, key: &str, operation: &'static str, access: CacheAccess, elapsed: Duration) {
+    let domain = CacheDomain::from_key(key);
+    let elapsed_secs = elapsed.as_secs_f64();
+
+    let (bytes_delta, evicted) = {
+      let mut guard = self.state.lock();
+      let CacheMetricsState { domains, entries } = &mut *guard;
+      let stats = domains.entry(domain).or_default();
+
+      let op = stats.operations.entry(operation).or_default();
+      op.count += 1;
+      if op.latencies_ms.len() == LATENCY_WINDOW {
+        op.latencies_ms.pop_front();
+      }
+      op.latencies_ms.push_back(elapsed_secs * 1000.0);
+
+      match access {
+        CacheAccess::Hit { size_bytes } => {
+          stats.hits += 1;
+          (track_entry(stats, entries, key, size_bytes), false)
+        }
+        CacheAccess::Store { size_bytes } => (track_entry(stats, entries, key, size_bytes), false),
+        // Известный элемент пропал с диска мимо обертки
+        CacheAccess::Miss => {
+          stats.misses += 1;
+          match entries.remove(key) {
+            Some(size) => {
+              stats.evictions += 1;
+              stats.bytes_stored = stats.bytes_stored.saturating_sub(size);
+              (-(size as i64), true)
+            }
+            None => (0, false),
+          }
+        }
+        CacheAccess::Probe => (0, false),
+      }
+    };
+
+    if let Some(instruments) = self.instruments.read().as_ref() {
+      let label = domain.as_str();
+      match access {
+        CacheAccess::Hit { .. } => instruments.hits.labeled("domain", label).inc(),
+        CacheAccess::Miss => instruments.misses.labeled("domain", label).inc(),
+        _ => {}
+      }
+      if evicted {
+        instruments.evictions.labeled("domain", label).inc();
+      }
+      if bytes_delta != 0 {
+        instruments
+          .bytes_stored
+          .labeled("domain", label)
+          .add(bytes_delta);
+      }
+      instruments
+        .latency
+        .labeled("domain", label)
+        .labeled("operation", operation)
+        .observe(elapsed_secs);
+    }
+  }
+
+  /// Записать вытеснение элементов, удаленных с диска
+  pub fn record_evictions(&self, keys: &[String]) {
+    let mut evicted: HashMap<CacheDomain, (u64, u64)> = HashMap::new();
+    {
+      let mut guard = self.state.lock();
+      let CacheMetricsState { domains, entries } = &mut *guard;
+      for key in keys {
+        let Some(size) = entries.remove(key) else {
+          continue;
+        };
+        let domain = CacheDomain::from_key(key);
+        let stats = domains.entry(domain).or_default();
+        stats.evictions += 1;
+        stats.bytes_stored = stats.bytes_stored.saturating_sub(size);
+
+        let totals = evicted.entry(domain).or_default();
+        totals.0 += 1;
+        totals.1 += size;
+      }
+    }
+
+    if let Some(instruments) = self.instruments.read().as_ref() {
+      for (domain, (count, bytes)) in evicted {
+        let label = domain.as_str();
+        instruments
+          .evictions
+          .labeled("domain", label)
+          .increment(count);
+        instruments
+          .bytes_stored
+          .labeled("domain", label)
+          .add(-(bytes as i64));
+      }
+    }
+  }
+
+  /// Ключи известных элементов
+  pub fn tracked_keys(&self) -> Vec<String> {
+    self.state.lock().entries.keys().cloned().collect()
+  }
+
+  /// Текущие агрегаты и `top_n` самых больших элементов
+  pub fn report(&self, top_n: usize) -> CacheMetricsReport {
+    let state = self.state.lock();
+
+    let domains = CacheDomain::ALL
+      .iter()
+      .filter_map(|domain| {
+        let stats = state.domains.get(domain)?;
+        let reads = stats.hits + stats.misses;
+        let mut operations: Vec<CacheOperationLatency> = stats
+          .operations
+          .iter()
+          .map(|(operation, op)| {
+            let mut latencies: Vec<f64> = op.latencies_ms.iter().copied().collect();
+            latencies.sort_by(|a, b| a.total_cmp(b));
+            CacheOperationLatency {
+              operation: (*operation).to_string(),
+              count: op.count,
+              p50_ms: percentile(&latencies, 0.50),
+              p95_ms: percentile(&latencies, 0.95),
+            }
+          })
+          .collect();
+        operations.sort_by(|a, b| a.operation.cmp(&b.operation));
+
+        Some(CacheDomainReport {
+          domain: *domain,
+          hits: stats.hits,
+          misses: stats.misses,
+          hit_ratio: if reads == 0 {
+            0.0
+          } else {
+            stats.hits as f64 / reads as f64
+          },
+          evictions: stats.evictions,
+          bytes_stored: stats.bytes_stored,
+          entries: state
+            .entries
+            .keys()
+            .filter(|key| CacheDomain::from_key(key) == *domain)
+            .count(),
+          operations,
+        })
+      })
+      .collect();
+
+    let mut largest_entries: Vec<CacheEntrySize> = state
+      .entries
+      .iter()
+      .map(|(key, size)| CacheEntrySize {
+        key: key.clone(),
+        domain: CacheDomain::from_key(key),
+        size_bytes: *size,
+      })
+      .collect();
+    largest_entries.sort_by(|a, b| {
+      b.size_bytes
+        .cmp(&a.size_bytes)
+        .then_with(|| a.key.cmp(&b.key))
+    });
+    largest_entries.truncate(top_n);
+
+    CacheMetricsReport {
+      enabled: self.is_enabled(),
+      timestamp: chrono::Utc::now(),
+      domains,
+      largest_entries,
+    }
+  }
+
+  /// Снимок агрегатов для публикации в EventBus
+  pub fn snapshot_event(&self) -> AppEvent {
+    let report = self.report(SNAPSHOT_TOP_N);
+    AppEvent::CacheMetricsSnapshot {
+      timestamp: report.timestamp,
+      report: serde_json::to_value(&report).unwrap_or_default(),
+    }
+  }
+}
+
+/// Запомнить размер элемента и вернуть изменение объема домена
+fn track_entry(
+  stats: &mut DomainStats,
+  entries: &mut HashMap<String, u64>,
+  key: &str,
+  size_bytes: u64,
+) -> i64 {
+  let previous = entries.insert(key.to_string(), size_bytes).unwrap_or(0);
+  stats.bytes_stored = (stats.bytes_stored + size_bytes).saturating_sub(previous);
+  size_bytes as i64 - previous as i64
+}
+
+/// Периодически публиковать снимок метрик кэша в EventBus
+///
+/// Пока сбор выключен, снимки не публикуются.
+pub fn spawn_snapshot_publisher(
+  metrics: Arc<CacheMetrics>,
+  event_bus: Arc<EventBus>,
+  interval: Duration,
+) -> tokio::task::JoinHandle<()> {
+  tokio::spawn(async move {
+    let mut ticker = tokio::time::interval(interval);
+    // Первый тик срабатывает сразу, а снимок пустой сессии бесполезен
+    ticker.tick().await;
+    loop {
+      ticker.tick().await;
+      if !metrics.is_enabled() {
+        continue;
+      }
+      if let Err(e) = event_bus.publish_app_event(metrics.snapshot_event()).await {
+        log::warn!("Failed to publish cache metrics snapshot: {e}");
+      }
+    }
+  })
+}
+
+/// Агрегаты кэша по доменам и самые большие элементы
+#[tauri::command]
+pub fn get_cache_metrics_report(top_n: Option<usize>) -> CacheMetricsReport {
+  CACHE_METRICS.report(top_n.unwrap_or(SNAPSHOT_TOP_N))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn enabled_metrics() -> CacheMetrics {
+    let metrics = CacheMetrics::default();
+    metrics.set_enabled(true);
+    metrics
+  }
+
+  fn domain(report: &CacheMetricsReport, domain: CacheDomain) -> &CacheDomainReport {
+    report
+      .domains
+      .iter()
+      .find(|d| d.domain == domain)
+      .expect("domain present in report")
+  }
+
+  #[test]
+  fn test_domain_from_key() {
+    assert_eq!(
+      CacheDomain::from_key("preview/clip.jpg"),
+      CacheDomain::Preview
+    );
+    assert_eq!(
+      CacheDomain::from_key("metadata\\a.json"),
+      CacheDomain::Metadata
+    );
+    assert_eq!(
+      CacheDomain::from_key("render/seg-1.mp4"),
+      CacheDomain::Render
+    );
+    assert_eq!(CacheDomain::from_key("key1"), CacheDomain::Other);
+    assert_eq!(CacheDomain::from_key(""), CacheDomain::Other);
+  }
+
+  #[test]
+  fn test_hit_ratio_and_bytes_per_domain() {
+    let metrics = enabled_metrics();
+    let ms = Duration::from_millis(1);
+    metrics.record(
+      "preview/a",
+      "save_to_cache",
+      CacheAccess::Store { size_bytes: 100 },
+      ms,
+    );
+    for _ in 0..3 {
+      metrics.record(
+        "preview/a",
+        "get_from_cache",
+        CacheAccess::Hit { size_bytes: 100 },
+        ms,
+      );
+    }
+    metrics.record("preview/b", "get_from_cache", CacheAccess::Miss, ms);
+    // Перезапись элемента меняет объем на разницу размеров
+    metrics.record(
+      "render/r",
+      "save_to_cache",
+      CacheAccess::Store { size_bytes: 500 },
+      ms,
+    );
+    metrics.record(
+      "render/r",
+      "save_to_cache",
+      CacheAccess::Store { size_bytes: 300 },
+      ms,
+    );
+
+    let report = metrics.report(10);
+    let preview = domain(&report, CacheDomain::Preview);
+    assert_eq!(preview.hits, 3);
+    assert_eq!(preview.misses, 1);
+    assert_eq!(preview.hit_ratio, 0.75);
+    assert_eq!(preview.bytes_stored, 100);
+    assert_eq!(preview.entries, 1);
+    assert_eq!(preview.evictions, 0);
+
+    let render = domain(&report, CacheDomain::Render);
+    assert_eq!(render.bytes_stored, 300);
+    assert_eq!(render.hit_ratio, 0.0);
+    assert_eq!(render.operations.len(), 1);
+    assert_eq!(render.operations[0].count, 2);
+
+    assert!(report
+      .domains
+      .iter()
+      .all(|d| d.domain != CacheDomain::Metadata));
+  }
+
+  #[test]
+  fn test_evictions_and_largest_entries() {
+    let metrics = enabled_metrics();
+    let ms = Duration::from_millis(1);
+    metrics.record(
+      "render/big",
+      "save_to_cache",
+      CacheAccess::Store { size_bytes: 900 },
+      ms,
+    );
+    metrics.record(
+      "preview/mid",
+      "save_to_cache",
+      CacheAccess::Store { size_bytes: 400 },
+      ms,
+    );
+    metrics.record(
+      "metadata/small",
+      "save_to_cache",
+      CacheAccess::Store { size_bytes: 10 },
+      ms,
+    );
+
+    let report = metrics.report(2);
+    let keys: Vec<&str> = report
+      .largest_entries
+      .iter()
+      .map(|e| e.key.as_str())
+      .collect();
+    assert_eq!(keys, vec!["render/big", "preview/mid"]);
+
+    metrics.record_evictions(&["render/big".to_string(), "render/unknown".to_string()]);
+    // Промах по известному ключу означает, что элемент удален с диска
+    metrics.record("preview/mid", "get_from_cache", CacheAccess::Miss, ms);
+
+    let report = metrics.report(10);
+    let render = domain(&report, CacheDomain::Render);
+    assert_eq!(render.evictions, 1);
+    assert_eq!(render.bytes_stored, 0);
+    let preview = domain(&report, CacheDomain::Preview);
+    assert_eq!(preview.evictions, 1);
+    assert_eq!(preview.misses, 1);
+    assert_eq!(report.largest_entries.len(), 1);
+    assert_eq!(metrics.tracked_keys(), vec!["metadata/small".to_string()]);
+  }
+
+  #[test]
+  fn test_snapshot_event_contains_report() {
+    let metrics = enabled_metrics();
+    metrics.record(
+      "preview/a",
+      "get_from_cache",
+      CacheAccess::Hit { size_bytes: 1 },
+      Duration::from_millis(2),
+    );
+
+    match metrics.snapshot_event() {
+      AppEvent::CacheMetricsSnapshot { report, .. } => {
+        assert_eq!(report["enabled"], true);
+        assert_eq!(report["domains"][0]["domain"], "preview");
+        assert_eq!(report["domains"][0]["hits"], 1);
+      }
+      other => panic!("unexpected event: {other:?}"),
+    }
+
+    metrics.reset();
+    assert!(metrics.report(10).domains.is_empty());
+  }
+}
@@ -0,0 +1,331 @@
+//! Метрики латентности Tauri команд
+//!
+//! Команды оборачиваются в [`instrumented_command!`](crate::instrumented_command), который
+//! считает вызовы, ошибки и время выполнения по имени команды. Пока телеметрия выключена,
+//! обертка только проверяет флаг и сразу выполняет команду.
+
+use super::metrics::{Counter, Gauge, Histogram, MetricsCollector};
+use crate::video_compiler::error::Result;
+use once_cell::sync::Lazy;
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::time::Instant;
+
+/// Сколько последних измерений хранится для расчета перцентилей
+const LATENCY_WINDOW: usize = 1024;
+
+/// Глобальные метрики команд текущей сессии
+pub static COMMAND_METRICS: Lazy<CommandMetrics> = Lazy::new(CommandMetrics::new);
+
+/// Обернуть тело async команды сбором метрик
+///
+/// # Example
+/// ```rust,ignore
+/// #[tauri::command]
+/// pub async fn clear_render_cache(state: State<'_, VideoCompilerState>) -> Result<()> {
+///   crate::instrumented_command!(clear_render_cache, {
+///     state.cache_manager.write().await.clear_all().await;
+///     Ok(())
+///   })
+/// }
+/// ```
+#[macro_export]
+macro_rules! instrumented_command {
+  ($name:ident, $body:block) => {
+    $crate::core::telemetry::command_metrics::instrument(stringify!($name), async move $body)
+      .await
+  };
+}
+
+/// Выполнить команду с записью метрик
+pub async fn instrument<T, F>(command: &'static str, future: F) -> Result<T>
+where
+  F: Future<Output = Result<T>>,
+{
+  if !COMMAND_METRICS.is_enabled() {
+    return future.await;
+  }
+
+  let guard = COMMAND_METRICS.start(command);
+  let result = future.await;
+  guard.finish(result.is_err());
+  result
+}
+
+/// Инструменты OpenTelemetry для команд
+struct CommandInstruments {
+  invocations: Counter,
+  errors: Counter,
+  latency: Histogram,
+  in_flight: Gauge,
+}
+
+/// Статистика одной команды за сессию
+#[derive(Default)]
+struct CommandStats {
+  invocations: u64,
+  errors: u64,
+  latencies_ms: VecDeque<f64>,
+}
+
+/// Латентность одной команды
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandLatencySummary {
+  pub command: String,
+  pub invocations: u64,
+  pub errors: u64,
+  pub p50_ms: f64,
+  pub p95_ms: f64,
+}
+
+/// Сводка метрик команд текущей сессии
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandMetricsSummary {
+  pub enabled: bool,
+  pub in_flight: i64,
+  /// Самые медленные команды по p95
+  pub commands: Vec<CommandLatencySummary>,
+}
+
+/// Сборщик метрик Tauri команд
+pub struct CommandMetrics {
+  enabled: AtomicBool,
+  in_flight: AtomicI64,
+  instruments: RwLock<Option<CommandInstruments>>,
+  stats: Mutex<HashMap<&'static str, CommandStats>>,
+}
+
+impl CommandMetrics {
+  fn new() -> Self {
+    Self {
+      enabled: AtomicBool::new(false),
+      in_flight: AtomicI64::new(0),
+      instruments: RwLock::new(None),
+      stats: Mutex::new(HashMap::new()),
+    }
+  }
+
+  /// Подключить коллектор метрик и включить сбор
+  pub fn install(&self, collector: &MetricsCollector) -> Result<()> {
+    let instruments = CommandInstruments {
+      invocations: collector.counter(
+        "tauri_command_invocations_total",
+        "Number of Tauri command invocations",
+      )?,
+      errors: collector.counter(
+        "tauri_command_errors_total",
+        "Number of Tauri commands finished with an error",
+      )?,
+      latency: collector.histogram(
+        "tauri_command_duration_seconds",
+        "Tauri command execution time",
+      )?,
+      in_flight: collector.gauge(
+        "tauri_commands_in_flight",
+        "Number of currently executing Tauri commands",
+      )?,
+    };
+
+    *self.instruments.write() = Some(instruments);
+    self.set_enabled(true);
+    Ok(())
+  }
+
+  /// Включить или выключить сбор метрик
+  pub fn set_enabled(&self, enabled: bool) {
+    self.enabled.store(enabled, Ordering::Relaxed);
+  }
+
+  /// Включен ли сбор метрик
+  pub fn is_enabled(&self) -> bool {
+    self.enabled.load(Ordering::Relaxed)
+  }
+
+  /// Сбросить статистику сессии
+  pub fn reset(&self) {
+    self.stats.lock().clear();
+  }
+
+  /// Начать измерение команды
+  fn start(&self, command: &'static str) -> InFlightGuard<'_> {
+    self.in_flight.fetch_add(1, Ordering::Relaxed);
+    if let Some(instruments) = self.instruments.read().as_ref() {
+      instruments.in_flight.add(1);
+    }
+
+    InFlightGuard {
+      metrics: self,
+      command,
+      started: Instant::now(),
+    }
+  }
+
+  /// Записать завершенный вызов
+  fn record(&self, command: &'static str, elapsed_secs: f64, is_error: bool) {
+    {
+      let mut stats = self.stats.lock();
+      let entry = stats.entry(command).or_default();
+      entry.invocations += 1;
+      if is_error {
+        entry.errors += 1;
+      }
+      if entry.latencies_ms.len() == LATENCY_WINDOW {
+        entry.latencies_ms.pop_front();
+      }
+      entry.latencies_ms.push_back(elapsed_secs * 1000.0);
+    }
+
+    if let Some(instruments) = self.instruments.read().as_ref() {
+      instruments.invocations.labeled("command", command).inc();
+      if is_error {
+        instruments.errors.labeled("command", command).inc();
+      }
+      instruments
+        .latency
+        .labeled("command", command)
+        .observe(elapsed_secs);
+    }
+  }
+
+  /// Сводка по самым медленным командам
+  pub fn summary(&self, top_n: usize) -> CommandMetricsSummary {
+    let mut commands: Vec<CommandLatencySummary> = self
+      .stats
+      .lock()
+      .iter()
+      .map(|(command, stats)| {
+        let mut latencies: Vec<f64> = stats.latencies_ms.iter().copied().collect();
+        latencies.sort_by(|a, b| a.total_cmp(b));
+        CommandLatencySummary {
+          command: (*command).to_string(),
+          invocations: stats.invocations,
+          errors: stats.errors,
+          p50_ms: percentile(&latencies, 0.50),
+          p95_ms: percentile(&latencies, 0.95),
+        }
+      })
+      .collect();
+
+    commands.sort_by(|a, b| {
+      b.p95_ms
+        .total_cmp(&a.p95_ms)
+        .then_with(|| a.command.cmp(&b.command))
+    });
+    commands.truncate(top_n);
+
+    CommandMetricsSummary {
+      enabled: self.is_enabled(),
+      in_flight: self.in_flight.load(Ordering::Relaxed),
+      commands,
+    }
+  }
+}
+
+/// Учет выполняющейся команды; счетчик уменьшается и при отмене future
+struct InFlightGuard<'a> {
+  metrics: &'a CommandMetrics,
+  command: &'static str,
+  started: Instant,
+}
+
+impl InFlightGuard<'_> {
+  fn finish(&self, is_error: bool) {
+    self
+      .metrics
+      .record(self.command, self.started.elapsed().as_secs_f64(), is_error);
+  }
+}
+
+impl Drop for InFlightGuard<'_> {
+  fn drop(&mut self) {
+    self.metrics.in_flight.fetch_sub(1, Ordering::Relaxed);
+    if let Some(instruments) = self.metrics.instruments.read().as_ref() {
+      instruments.in_flight.add(-1);
+    }
+  }
+}
+
+/// Перцентиль по отсортированной выборке (nearest-rank)
+pub(super) fn percentile(sorted: &[f64], quantile: f64) -> f64 {
+  if sorted.is_empty() {
+    return 0.0;
+  }
+  let rank = (quantile * sorted.len() as f64).ceil() as usize;
+  sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Самые медленные команды текущей сессии с p50/p95 латентностью
+#[tauri::command]
+pub fn get_command_metrics_summary(top_n: Option<usize>) -> CommandMetricsSummary {
+  COMMAND_METRICS.summary(top_n.unwrap_or(10))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::video_compiler::error::VideoCompilerError;
+
+  #[test]
+  fn test_percentile_nearest_rank() {
+    let samples: Vec<f64> = (1..=100).map(f64::from).collect();
+    assert_eq!(percentile(&samples, 0.50), 50.0);
+    assert_eq!(percentile(&samples, 0.95), 95.0);
+    assert_eq!(percentile(&[7.0], 0.95), 7.0);
+    assert_eq!(percentile(&[], 0.5), 0.0);
+  }
+
+  #[test]
+  fn test_summary_orders_by_p95_and_counts_errors() {
+    let metrics = CommandMetrics::new();
+    metrics.set_enabled(true);
+    for _ in 0..10 {
+      metrics.record("fast_command", 0.001, false);
+      metrics.record("slow_command", 0.5, false);
+    }
+    metrics.record("slow_command", 0.6, true);
+
+    let summary = metrics.summary(1);
+    assert!(summary.enabled);
+    assert_eq!(summary.commands.len(), 1);
+    let slowest = &summary.commands[0];
+    assert_eq!(slowest.command, "slow_command");
+    assert_eq!(slowest.invocations, 11);
+    assert_eq!(slowest.errors, 1);
+    assert_eq!(slowest.p50_ms, 500.0);
+  }
+
+  #[test]
+  fn test_in_flight_released_on_drop() {
+    let metrics = CommandMetrics::new();
+    {
+      let _guard = metrics.start("cancelled_command");
+      assert_eq!(metrics.summary(10).in_flight, 1);
+    }
+    let summary = metrics.summary(10);
+    assert_eq!(summary.in_flight, 0);
+    // Отмененная команда не попадает в статистику латентности
+    assert!(summary.commands.is_empty());
+  }
+
+  #[tokio::test]
+  async fn test_instrumented_command_macro() {
+    async fn failing_command() -> Result<u32> {
+      crate::instrumented_command!(failing_command, {
+        let value: u32 = "bad"
+          .parse()
+          .map_err(|_| VideoCompilerError::InvalidParameter("bad".to_string()))?;
+        Ok(value)
+      })
+    }
+
+    // Результат команды проходит через обертку без изменений
+    let result = failing_command().await;
+    assert!(matches!(
+      result,
+      Err(VideoCompilerError::InvalidParameter(_))
+    ));
+  }
+}
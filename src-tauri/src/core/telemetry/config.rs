@@ -148,6 +148,14 @@ pub struct MetricsConfig {
 
   /// Включить метрики процесса
   pub process_metrics: bool,
+
+  /// Интервал публикации снимков метрик кэша в EventBus
+  #[serde(default = "default_cache_snapshot_interval")]
+  pub cache_snapshot_interval: Duration,
+}
+
+fn default_cache_snapshot_interval() -> Duration {
+  Duration::from_secs(30)
 }
 
 impl Default for MetricsConfig {
@@ -158,6 +166,7 @@ impl Default for MetricsConfig {
       runtime_metrics: true,
       system_metrics: true,
       process_metrics: true,
+      cache_snapshot_interval: default_cache_snapshot_interval(),
     }
   }
 }
@@ -277,6 +286,7 @@ mod tests {
 
     assert_eq!(config.collection_interval, Duration::from_secs(10));
     assert_eq!(config.export_interval, Duration::from_secs(60));
+    assert_eq!(config.cache_snapshot_interval, Duration::from_secs(30));
     assert!(config.runtime_metrics);
     assert!(config.system_metrics);
     assert!(config.process_metrics);
@@ -1,8 +1,16 @@
 //! Health checks для мониторинга состояния системы
 
+use crate::core::performance::memory_pressure::{MemoryPressureLevel, MemoryPressureMonitor};
 use crate::core::{EventBus, PluginManager};
+use crate::video_compiler::core::temp_storage::{find_orphaned_job_dirs, STALE_JOB_MAX_AGE};
+use crate::video_compiler::services::stale_artifacts::{
+  inspect_cache, ORPHANED_TEMP_DIRS_THRESHOLD, REMEDIATION_COMMAND,
+};
+use crate::video_compiler::services::CacheService;
+use crate::video_compiler::CompilerSettings;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
@@ -356,6 +364,58 @@ impl HealthCheck for MemoryHealthCheck {
   }
 }
 
+/// Health check реакции на нехватку памяти
+pub struct MemoryPressureHealthCheck {
+  monitor: Arc<MemoryPressureMonitor>,
+}
+
+impl MemoryPressureHealthCheck {
+  pub fn new(monitor: Arc<MemoryPressureMonitor>) -> Self {
+    Self { monitor }
+  }
+}
+
+#[async_trait::async_trait]
+impl HealthCheck for MemoryPressureHealthCheck {
+  fn name(&self) -> &'static str {
+    "memory_pressure"
+  }
+
+  fn is_critical(&self) -> bool {
+    false // При жестком давлении кэши ужаты, приложение работает медленнее
+  }
+
+  async fn check(&self) -> HealthCheckResult {
+    let start = Instant::now();
+    let status = self.monitor.status();
+
+    let result = match status.level {
+      MemoryPressureLevel::Hard => HealthCheckResult::warning(
+        format!(
+          "Hard memory pressure: caches shrunk, {} bytes freed",
+          status.last_freed_bytes
+        ),
+        start.elapsed(),
+      ),
+      MemoryPressureLevel::Soft => {
+        HealthCheckResult::healthy("Soft memory pressure", start.elapsed())
+      }
+      MemoryPressureLevel::Normal => {
+        HealthCheckResult::healthy("No memory pressure", start.elapsed())
+      }
+    };
+
+    result
+      .with_data("level", serde_json::json!(status.level))
+      .with_data("last_reading", serde_json::json!(status.last_reading))
+      .with_data("hard_events", serde_json::json!(status.hard_events))
+      .with_data(
+        "total_freed_bytes",
+        serde_json::json!(status.total_freed_bytes),
+      )
+  }
+}
+
 /// Health check для плагинов
 pub struct PluginHealthCheck {
   plugin_manager: Arc<PluginManager>,
@@ -437,6 +497,150 @@ impl HealthCheck for EventBusHealthCheck {
   }
 }
 
+/// Сервисы и настройки Video Compiler для проверок хранилища
+pub struct VideoCompilerHealthSources {
+  pub cache_service: Arc<dyn CacheService>,
+  pub settings: CompilerSettings,
+}
+
+/// Health check размера и индекса кэша Video Compiler
+pub struct CacheHealthCheck {
+  cache_service: Arc<dyn CacheService>,
+  limit_bytes: u64,
+}
+
+impl CacheHealthCheck {
+  pub fn new(cache_service: Arc<dyn CacheService>, limit_bytes: u64) -> Self {
+    Self {
+      cache_service,
+      limit_bytes,
+    }
+  }
+}
+
+#[async_trait::async_trait]
+impl HealthCheck for CacheHealthCheck {
+  fn name(&self) -> &'static str {
+    "cache"
+  }
+
+  fn is_critical(&self) -> bool {
+    false // Разросшийся кэш замедляет работу, но не ломает ее
+  }
+
+  async fn check(&self) -> HealthCheckResult {
+    let start = Instant::now();
+
+    let usage = match inspect_cache(self.cache_service.as_ref(), self.limit_bytes).await {
+      Ok(usage) => usage,
+      Err(e) => {
+        return HealthCheckResult::warning(format!("Failed to inspect cache: {e}"), start.elapsed())
+      }
+    };
+
+    let usage_percent = usage.usage_percent();
+    let result = if let Some(index_error) = &usage.index_error {
+      HealthCheckResult::warning(
+        format!("Cache index is corrupt: {index_error}"),
+        start.elapsed(),
+      )
+    } else if usage.exceeds_tolerance() {
+      HealthCheckResult::warning(
+        format!("Cache size exceeds limit: {usage_percent:.0}% of configured size"),
+        start.elapsed(),
+      )
+    } else {
+      HealthCheckResult::healthy(
+        format!("Cache usage: {usage_percent:.0}% of configured size"),
+        start.elapsed(),
+      )
+    };
+
+    let degraded = result.status != HealthStatus::Healthy;
+    let result = result
+      .with_data("size_bytes", serde_json::json!(usage.size_bytes))
+      .with_data("limit_bytes", serde_json::json!(usage.limit_bytes))
+      .with_data("usage_percent", serde_json::json!(usage_percent))
+      .with_data("index_ok", serde_json::json!(usage.index_error.is_none()));
+    if degraded {
+      result.with_data("remediation", serde_json::json!(REMEDIATION_COMMAND))
+    } else {
+      result
+    }
+  }
+}
+
+/// Health check брошенных временных директорий задач
+pub struct TempDirHealthCheck {
+  temp_root: PathBuf,
+  max_age: Duration,
+  threshold: usize,
+}
+
+impl TempDirHealthCheck {
+  pub fn new(temp_root: PathBuf) -> Self {
+    Self {
+      temp_root,
+      max_age: STALE_JOB_MAX_AGE,
+      threshold: ORPHANED_TEMP_DIRS_THRESHOLD,
+    }
+  }
+
+  /// Количество брошенных директорий, после которого проверка деградирует
+  pub fn with_threshold(mut self, threshold: usize) -> Self {
+    self.threshold = threshold;
+    self
+  }
+}
+
+#[async_trait::async_trait]
+impl HealthCheck for TempDirHealthCheck {
+  fn name(&self) -> &'static str {
+    "temp_dirs"
+  }
+
+  fn is_critical(&self) -> bool {
+    false
+  }
+
+  async fn check(&self) -> HealthCheckResult {
+    let start = Instant::now();
+
+    let orphaned = match find_orphaned_job_dirs(&self.temp_root, self.max_age).await {
+      Ok(orphaned) => orphaned,
+      Err(e) => {
+        return HealthCheckResult::warning(
+          format!("Failed to scan temp directory: {e}"),
+          start.elapsed(),
+        )
+      }
+    };
+
+    let count = orphaned.paths.len();
+    let result = if count > self.threshold {
+      HealthCheckResult::warning(
+        format!(
+          "{count} orphaned temp directories older than {}h",
+          self.max_age.as_secs() / 3600
+        ),
+        start.elapsed(),
+      )
+      .with_data("remediation", serde_json::json!(REMEDIATION_COMMAND))
+    } else {
+      HealthCheckResult::healthy(
+        format!("Orphaned temp directories: {count}"),
+        start.elapsed(),
+      )
+    };
+
+    result
+      .with_data("orphaned_dirs", serde_json::json!(count))
+      .with_data("orphaned_bytes", serde_json::json!(orphaned.total_bytes))
+      .with_data("threshold", serde_json::json!(self.threshold))
+      .with_data("temp_root", serde_json::json!(self.temp_root))
+  }
+}
+
 /// Менеджер health checks
 pub struct HealthCheckManager {
   checks: Arc<RwLock<HashMap<String, Box<dyn HealthCheck>>>>,
@@ -1043,4 +1247,101 @@ mod tests {
     assert!(result3.is_err());
     assert!(result3.unwrap_err().contains("not a directory"));
   }
+
+  #[tokio::test]
+  async fn test_cache_health_check_statuses() {
+    use crate::video_compiler::services::cache_service::PREVIEW_CACHE_DIR;
+    use crate::video_compiler::services::CacheServiceImpl;
+
+    let cache_dir = tempfile::tempdir().unwrap();
+    let preview_dir = cache_dir.path().join(PREVIEW_CACHE_DIR);
+    std::fs::create_dir_all(&preview_dir).unwrap();
+    std::fs::write(preview_dir.join("a.preview"), vec![0u8; 1100]).unwrap();
+    let cache_service: Arc<dyn CacheService> =
+      Arc::new(CacheServiceImpl::new(cache_dir.path().to_path_buf()));
+
+    // Превышение лимита в пределах 20% допустимо
+    let check = CacheHealthCheck::new(cache_service.clone(), 1000);
+    assert!(!check.is_critical());
+    let result = check.check().await;
+    assert_eq!(result.status, HealthStatus::Healthy);
+    assert_eq!(result.data["size_bytes"], serde_json::json!(1100));
+    assert!(!result.data.contains_key("remediation"));
+
+    let result = CacheHealthCheck::new(cache_service.clone(), 500)
+      .check()
+      .await;
+    assert_eq!(result.status, HealthStatus::Warning);
+    assert_eq!(result.data["limit_bytes"], serde_json::json!(500));
+    assert_eq!(
+      result.data["remediation"],
+      serde_json::json!("clean_stale_artifacts")
+    );
+
+    // Поврежденный индекс деградирует проверку при любом размере
+    std::fs::write(preview_dir.join("index.json"), b"{ broken").unwrap();
+    let result = CacheHealthCheck::new(cache_service, 1_000_000)
+      .check()
+      .await;
+    assert_eq!(result.status, HealthStatus::Warning);
+    assert_eq!(result.data["index_ok"], serde_json::json!(false));
+  }
+
+  #[tokio::test]
+  async fn test_temp_dir_health_check_statuses() {
+    use crate::video_compiler::core::temp_storage::job_dir;
+    use std::time::SystemTime;
+
+    let temp_root = tempfile::tempdir().unwrap();
+    let old = SystemTime::now() - Duration::from_secs(48 * 60 * 60);
+    for i in 0..3 {
+      let dir = job_dir(temp_root.path(), &format!("stale-{i}"));
+      std::fs::create_dir_all(&dir).unwrap();
+      std::fs::write(dir.join("part.mp4"), vec![0u8; 100]).unwrap();
+      filetime::set_file_mtime(&dir, filetime::FileTime::from_system_time(old)).unwrap();
+    }
+    std::fs::create_dir_all(job_dir(temp_root.path(), "running")).unwrap();
+
+    let check = TempDirHealthCheck::new(temp_root.path().to_path_buf());
+    let result = check.check().await;
+    assert_eq!(result.status, HealthStatus::Healthy);
+    assert_eq!(result.data["orphaned_dirs"], serde_json::json!(3));
+    assert_eq!(result.data["orphaned_bytes"], serde_json::json!(300));
+
+    let result = TempDirHealthCheck::new(temp_root.path().to_path_buf())
+      .with_threshold(2)
+      .check()
+      .await;
+    assert_eq!(result.status, HealthStatus::Warning);
+    assert_eq!(
+      result.data["remediation"],
+      serde_json::json!("clean_stale_artifacts")
+    );
+
+    // Отсутствующая временная директория - не деградация
+    let missing = TempDirHealthCheck::new(temp_root.path().join("missing"))
+      .check()
+      .await;
+    assert_eq!(missing.status, HealthStatus::Healthy);
+  }
+
+  #[tokio::test]
+  async fn test_memory_pressure_health_check_degrades_on_hard_pressure() {
+    use crate::core::performance::memory_pressure::MemoryReading;
+
+    let monitor = Arc::new(MemoryPressureMonitor::default());
+    let check = MemoryPressureHealthCheck::new(monitor.clone());
+    assert_eq!(check.check().await.status, HealthStatus::Healthy);
+
+    monitor
+      .handle_pressure(MemoryReading {
+        process_rss_bytes: 0,
+        available_bytes: 5,
+        total_bytes: 100,
+      })
+      .await;
+    let result = check.check().await;
+    assert_eq!(result.status, HealthStatus::Warning);
+    assert_eq!(result.data["level"], serde_json::json!("hard"));
+  }
 }
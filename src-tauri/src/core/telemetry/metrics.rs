@@ -51,6 +51,16 @@ impl Counter {
     self
   }
 
+  /// Копия счетчика с дополнительной меткой
+  pub fn labeled(&self, key: &str, value: impl Into<opentelemetry::Value>) -> Self {
+    let mut labels = self.labels.clone();
+    labels.push(KeyValue::new(key.to_string(), value.into()));
+    Self {
+      inner: self.inner.clone(),
+      labels,
+    }
+  }
+
   /// Увеличить счетчик
   pub fn increment(&self, value: u64) {
     self.inner.add(value, &self.labels);
@@ -84,6 +94,16 @@ impl Gauge {
     self
   }
 
+  /// Копия gauge с дополнительной меткой
+  pub fn labeled(&self, key: &str, value: impl Into<opentelemetry::Value>) -> Self {
+    let mut labels = self.labels.clone();
+    labels.push(KeyValue::new(key.to_string(), value.into()));
+    Self {
+      inner: self.inner.clone(),
+      labels,
+    }
+  }
+
   /// Изменить значение
   pub fn add(&self, value: i64) {
     self.inner.add(value, &self.labels);
@@ -119,6 +139,16 @@ impl Histogram {
     self
   }
 
+  /// Копия гистограммы с дополнительной меткой
+  pub fn labeled(&self, key: &str, value: impl Into<opentelemetry::Value>) -> Self {
+    let mut labels = self.labels.clone();
+    labels.push(KeyValue::new(key.to_string(), value.into()));
+    Self {
+      inner: self.inner.clone(),
+      labels,
+    }
+  }
+
   /// Записать наблюдение
   pub fn observe(&self, value: f64) {
     self.inner.record(value, &self.labels);
@@ -1,5 +1,7 @@
 //! OpenTelemetry интеграция для мониторинга и трассировки
 
+pub mod cache_metrics;
+pub mod command_metrics;
 pub mod config;
 pub mod health;
 pub mod metrics;
@@ -21,6 +23,7 @@ pub struct TelemetryManager {
   metrics: Arc<MetricsCollector>,
   health: Arc<HealthCheckManager>,
   config: Arc<RwLock<TelemetryConfig>>,
+  cache_snapshot_task: parking_lot::Mutex<Option<tokio::task::JoinHandle<()>>>,
 }
 
 impl TelemetryManager {
@@ -28,6 +31,14 @@ impl TelemetryManager {
   pub async fn new(config: TelemetryConfig) -> Result<Self> {
     let tracer = Arc::new(Tracer::new(&config).await?);
     let metrics = Arc::new(MetricsCollector::new(&config).await?);
+    if config.enabled {
+      if let Err(e) = command_metrics::COMMAND_METRICS.install(&metrics) {
+        log::warn!("Failed to install command metrics: {e}");
+      }
+      if let Err(e) = cache_metrics::CACHE_METRICS.install(&metrics) {
+        log::warn!("Failed to install cache metrics: {e}");
+      }
+    }
     let health = Arc::new(HealthCheckManager::new());
 
     // Добавляем базовые health checks
@@ -43,6 +54,7 @@ impl TelemetryManager {
       metrics,
       health,
       config: Arc::new(RwLock::new(config)),
+      cache_snapshot_task: parking_lot::Mutex::new(None),
     })
   }
 
@@ -73,11 +85,25 @@ impl TelemetryManager {
     Ok(())
   }
 
+  /// Запустить периодическую публикацию снимков метрик кэша в EventBus
+  pub async fn start_cache_snapshots(&self, event_bus: Arc<crate::core::EventBus>) {
+    let interval = self.config.read().await.metrics.cache_snapshot_interval;
+    let handle = cache_metrics::spawn_snapshot_publisher(
+      cache_metrics::CACHE_METRICS.clone(),
+      event_bus,
+      interval,
+    );
+    if let Some(previous) = self.cache_snapshot_task.lock().replace(handle) {
+      previous.abort();
+    }
+  }
+
   /// Добавить health checks для компонентов системы
   pub async fn setup_system_health_checks(
     &self,
     event_bus: Option<Arc<crate::core::EventBus>>,
     plugin_manager: Option<Arc<crate::core::PluginManager>>,
+    video_compiler: Option<health::VideoCompilerHealthSources>,
   ) -> Result<()> {
     if let Some(event_bus) = event_bus {
       self
@@ -93,11 +119,33 @@ impl TelemetryManager {
         .await;
     }
 
+    if let Some(sources) = video_compiler {
+      let limit_bytes = sources.settings.cache_size_mb as u64 * 1024 * 1024;
+      self
+        .health
+        .add_check(Box::new(health::CacheHealthCheck::new(
+          sources.cache_service,
+          limit_bytes,
+        )))
+        .await;
+      self
+        .health
+        .add_check(Box::new(health::TempDirHealthCheck::new(
+          sources.settings.temp_directory,
+        )))
+        .await;
+    }
+
     Ok(())
   }
 
   /// Завершить работу телеметрии
   pub async fn shutdown(&self) -> Result<()> {
+    command_metrics::COMMAND_METRICS.set_enabled(false);
+    cache_metrics::CACHE_METRICS.set_enabled(false);
+    if let Some(task) = self.cache_snapshot_task.lock().take() {
+      task.abort();
+    }
     self.tracer.shutdown().await?;
     self.metrics.shutdown().await?;
     Ok(())
@@ -211,7 +259,7 @@ mod tests {
 
     // Добавляем системные health checks
     let result = manager
-      .setup_system_health_checks(Some(event_bus), Some(plugin_manager))
+      .setup_system_health_checks(Some(event_bus), Some(plugin_manager), None)
       .await;
 
     assert!(result.is_ok());
@@ -298,7 +346,7 @@ mod tests {
 
     // Добавляем системные health checks
     manager
-      .setup_system_health_checks(Some(event_bus), Some(plugin_manager))
+      .setup_system_health_checks(Some(event_bus), Some(plugin_manager), None)
       .await
       .unwrap();
 
@@ -377,7 +425,7 @@ mod tests {
     // Тест только с event_bus
     let event_bus = Arc::new(crate::core::EventBus::new());
     let result = manager
-      .setup_system_health_checks(Some(event_bus), None)
+      .setup_system_health_checks(Some(event_bus), None, None)
       .await;
     assert!(result.is_ok());
 
@@ -392,15 +440,41 @@ mod tests {
     ));
 
     let result2 = manager
-      .setup_system_health_checks(None, Some(plugin_manager))
+      .setup_system_health_checks(None, Some(plugin_manager), None)
       .await;
     assert!(result2.is_ok());
 
     // Тест без компонентов
-    let result3 = manager.setup_system_health_checks(None, None).await;
+    let result3 = manager.setup_system_health_checks(None, None, None).await;
     assert!(result3.is_ok());
   }
 
+  #[tokio::test]
+  async fn test_telemetry_manager_storage_health_checks() {
+    use crate::video_compiler::services::CacheServiceImpl;
+
+    let manager = TelemetryManager::new(TelemetryConfig::default())
+      .await
+      .unwrap();
+    let temp_dir = tempfile::tempdir().unwrap();
+    let sources = health::VideoCompilerHealthSources {
+      cache_service: Arc::new(CacheServiceImpl::new(temp_dir.path().join("cache"))),
+      settings: crate::video_compiler::CompilerSettings {
+        temp_directory: temp_dir.path().join("temp"),
+        ..Default::default()
+      },
+    };
+
+    manager
+      .setup_system_health_checks(None, None, Some(sources))
+      .await
+      .unwrap();
+
+    let checks = manager.health.list_checks().await;
+    assert!(checks.contains(&"cache".to_string()));
+    assert!(checks.contains(&"temp_dirs".to_string()));
+  }
+
   #[tokio::test]
   async fn test_telemetry_manager_arc_cloning() {
     // Тест клонирования Arc компонентов
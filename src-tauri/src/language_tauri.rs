@@ -5,6 +5,8 @@ use serde::{Deserialize, Serialize};
 use std::sync::Mutex;
 use tauri::State;
 
+pub mod i18n;
+
 // Поддерживаемые языки
 const SUPPORTED_LANGUAGES: [&str; 13] = [
   "en", "ru", "es", "pt", "fr", "de", "zh", "ja", "ko", "tr", "th", "it", "hi",
@@ -97,6 +99,8 @@ pub fn set_app_language_tauri(
     .current_language
     .lock()
     .map_err(|e| format!("Failed to lock language state: {e}"))? = lang.clone();
+  // Сообщения backend (ошибки, этапы прогресса) переводятся на тот же язык
+  i18n::set_current_language(&lang);
 
   Ok(LanguageResponse {
     language: lang,
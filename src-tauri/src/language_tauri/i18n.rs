@@ -0,0 +1,428 @@
+// Каталог сообщений backend, которые видит пользователь
+//
+// Шаблоны вкомпилированы в приложение: ключ -> шаблон с подстановками `{name}`.
+// Язык берется из LanguageState: команда смены языка обновляет текущий язык
+// каталога, поэтому ошибки и этапы прогресса из глубины video_compiler
+// формируются без доступа к состоянию Tauri.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::RwLock;
+
+use super::{DEFAULT_LANGUAGE, SUPPORTED_LANGUAGES};
+
+/// Текущий язык каталога (зеркало LanguageState)
+static CURRENT_LANGUAGE: RwLock<&'static str> = RwLock::new(DEFAULT_LANGUAGE);
+
+/// Английские шаблоны - запасной вариант для всех языков
+const EN: &[(&str, &str)] = &[
+  // Валидация проекта
+  (
+    "validation.empty_version",
+    "Project version cannot be empty",
+  ),
+  ("validation.invalid_fps", "FPS must be greater than 0"),
+  (
+    "validation.invalid_resolution",
+    "Resolution must be greater than 0x0",
+  ),
+  (
+    "validation.clips_overlap",
+    "Clips overlap in track '{track}': {first} and {second}",
+  ),
+  (
+    "validation.transition_disabled_track",
+    "Transition '{transition}' references clip '{clip}' on disabled track '{track}'",
+  ),
+  (
+    "validation.missing_assets",
+    "Missing project assets: {assets}",
+  ),
+  // Очередь рендеринга
+  (
+    "render_queue.limit_reached",
+    "Active job limit reached: at most {limit} renders can run at once",
+  ),
+  (
+    "render_queue.ffmpeg_download_running",
+    "FFmpeg download is already in progress",
+  ),
+  // Зависимость FFmpeg
+  (
+    "ffmpeg.not_found",
+    "FFmpeg was not found on this system. Install FFmpeg to use Video Compiler.\n\
+     Installation:\n\
+     - macOS: brew install ffmpeg\n\
+     - Ubuntu/Debian: sudo apt install ffmpeg\n\
+     - Windows: download from https://ffmpeg.org/download.html",
+  ),
+  ("ffmpeg.not_runnable", "FFmpeg does not start: {path}"),
+  // Этапы прогресса
+  ("progress.stage.validation", "Validating project"),
+  ("progress.stage.preprocessing", "Preparing media"),
+  ("progress.stage.composition", "Composing timeline"),
+  ("progress.stage.encoding", "Encoding"),
+  ("progress.stage.finalization", "Finalizing"),
+  ("progress.stage.completed", "Completed"),
+];
+
+/// Русские шаблоны
+const RU: &[(&str, &str)] = &[
+  (
+    "validation.empty_version",
+    "Версия проекта не может быть пустой",
+  ),
+  ("validation.invalid_fps", "FPS должен быть больше 0"),
+  (
+    "validation.invalid_resolution",
+    "Разрешение должно быть больше 0x0",
+  ),
+  (
+    "validation.clips_overlap",
+    "Клипы пересекаются по времени в треке '{track}': {first} и {second}",
+  ),
+  (
+    "validation.transition_disabled_track",
+    "Переход '{transition}' ссылается на клип '{clip}' отключенного трека '{track}'",
+  ),
+  (
+    "validation.missing_assets",
+    "Отсутствуют ресурсы проекта: {assets}",
+  ),
+  (
+    "render_queue.limit_reached",
+    "Достигнут лимит активных задач: одновременно выполняется не больше {limit} рендеров",
+  ),
+  (
+    "render_queue.ffmpeg_download_running",
+    "Скачивание FFmpeg уже выполняется",
+  ),
+  (
+    "ffmpeg.not_found",
+    "FFmpeg не найден в системе. Установите FFmpeg для работы Video Compiler.\n\
+     Инструкции по установке:\n\
+     - macOS: brew install ffmpeg\n\
+     - Ubuntu/Debian: sudo apt install ffmpeg\n\
+     - Windows: скачайте с https://ffmpeg.org/download.html",
+  ),
+  ("ffmpeg.not_runnable", "FFmpeg не запускается: {path}"),
+  ("progress.stage.validation", "Проверка проекта"),
+  ("progress.stage.preprocessing", "Подготовка медиа"),
+  ("progress.stage.composition", "Сборка timeline"),
+  ("progress.stage.encoding", "Кодирование"),
+  ("progress.stage.finalization", "Завершение"),
+  ("progress.stage.completed", "Готово"),
+];
+
+/// Каталог языка; для языков без перевода - `None`
+fn catalog(language: &str) -> Option<&'static [(&'static str, &'static str)]> {
+  match language {
+    "en" => Some(EN),
+    "ru" => Some(RU),
+    _ => None,
+  }
+}
+
+fn lookup(catalog: &[(&'static str, &'static str)], key: &str) -> Option<&'static str> {
+  catalog
+    .iter()
+    .find(|(entry, _)| *entry == key)
+    .map(|(_, template)| *template)
+}
+
+/// Есть ли ключ в каталоге
+pub fn has_message(key: &str) -> bool {
+  lookup(EN, key).is_some()
+}
+
+/// Текущий язык сообщений backend
+pub fn current_language() -> &'static str {
+  *CURRENT_LANGUAGE
+    .read()
+    .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Установить язык сообщений backend. Неподдерживаемый язык игнорируется.
+pub fn set_current_language(language: &str) {
+  if let Some(supported) = SUPPORTED_LANGUAGES.iter().find(|lang| **lang == language) {
+    *CURRENT_LANGUAGE
+      .write()
+      .unwrap_or_else(|poisoned| poisoned.into_inner()) = *supported;
+  }
+}
+
+/// Сообщение по ключу на текущем языке
+pub fn t(key: &str, args: &[(&str, String)]) -> String {
+  t_in(current_language(), key, args)
+}
+
+/// Сообщение по ключу на указанном языке.
+///
+/// Ключ без перевода берется из английского каталога, неизвестный ключ
+/// возвращается как есть.
+pub fn t_in(language: &str, key: &str, args: &[(&str, String)]) -> String {
+  let template = catalog(language)
+    .and_then(|catalog| lookup(catalog, key))
+    .or_else(|| lookup(EN, key));
+  match template {
+    Some(template) => render(template, args.iter().map(|(k, v)| (*k, v.as_str()))),
+    None => key.to_string(),
+  }
+}
+
+fn render<'a>(template: &str, args: impl Iterator<Item = (&'a str, &'a str)>) -> String {
+  let mut message = template.to_string();
+  for (name, value) in args {
+    message = message.replace(&format!("{{{name}}}"), value);
+  }
+  message
+}
+
+/// Сообщение для пользователя: ключ каталога, аргументы и текст на языке,
+/// который был активен при создании.
+///
+/// Ключ и аргументы позволяют интерфейсу перерисовать сообщение на другом
+/// языке без повторного вызова команды. Сообщения без ключа (не переведенный
+/// текст) сериализуются обычной строкой, как раньше.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalizedMessage {
+  key: Option<String>,
+  args: BTreeMap<String, String>,
+  message: String,
+}
+
+impl LocalizedMessage {
+  /// Сообщение из каталога на текущем языке
+  pub fn new(key: &str, args: &[(&str, String)]) -> Self {
+    Self::new_in(current_language(), key, args)
+  }
+
+  /// Сообщение из каталога на указанном языке
+  pub fn new_in(language: &str, key: &str, args: &[(&str, String)]) -> Self {
+    Self {
+      key: Some(key.to_string()),
+      args: args
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.clone()))
+        .collect(),
+      message: t_in(language, key, args),
+    }
+  }
+
+  /// Текст без перевода
+  pub fn raw(message: impl Into<String>) -> Self {
+    Self {
+      key: None,
+      args: BTreeMap::new(),
+      message: message.into(),
+    }
+  }
+
+  /// Ключ каталога (`None` для текста без перевода)
+  pub fn key(&self) -> Option<&str> {
+    self.key.as_deref()
+  }
+
+  /// Перерисовать сообщение на другом языке
+  pub fn render_in(&self, language: &str) -> String {
+    match &self.key {
+      Some(key) => {
+        let args: Vec<(&str, String)> = self
+          .args
+          .iter()
+          .map(|(name, value)| (name.as_str(), value.clone()))
+          .collect();
+        t_in(language, key, &args)
+      }
+      None => self.message.clone(),
+    }
+  }
+}
+
+impl fmt::Display for LocalizedMessage {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(&self.message)
+  }
+}
+
+impl Deref for LocalizedMessage {
+  type Target = str;
+
+  fn deref(&self) -> &str {
+    &self.message
+  }
+}
+
+impl PartialEq<str> for LocalizedMessage {
+  fn eq(&self, other: &str) -> bool {
+    self.message == other
+  }
+}
+
+impl PartialEq<&str> for LocalizedMessage {
+  fn eq(&self, other: &&str) -> bool {
+    self.message == *other
+  }
+}
+
+impl From<String> for LocalizedMessage {
+  fn from(message: String) -> Self {
+    Self::raw(message)
+  }
+}
+
+impl From<&str> for LocalizedMessage {
+  fn from(message: &str) -> Self {
+    Self::raw(message)
+  }
+}
+
+impl From<&String> for LocalizedMessage {
+  fn from(message: &String) -> Self {
+    Self::raw(message.clone())
+  }
+}
+
+impl From<LocalizedMessage> for String {
+  fn from(message: LocalizedMessage) -> Self {
+    message.message
+  }
+}
+
+/// Формат сериализации: строка для текста без ключа, объект для сообщения
+/// из каталога
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum LocalizedMessageRepr {
+  Raw(String),
+  Keyed {
+    key: String,
+    #[serde(default)]
+    args: BTreeMap<String, String>,
+    message: String,
+  },
+}
+
+impl Serialize for LocalizedMessage {
+  fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    let repr = match &self.key {
+      Some(key) => LocalizedMessageRepr::Keyed {
+        key: key.clone(),
+        args: self.args.clone(),
+        message: self.message.clone(),
+      },
+      None => LocalizedMessageRepr::Raw(self.message.clone()),
+    };
+    repr.serialize(serializer)
+  }
+}
+
+impl<'de> Deserialize<'de> for LocalizedMessage {
+  fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    Ok(match LocalizedMessageRepr::deserialize(deserializer)? {
+      LocalizedMessageRepr::Raw(message) => Self::raw(message),
+      LocalizedMessageRepr::Keyed { key, args, message } => Self {
+        key: Some(key),
+        args,
+        message,
+      },
+    })
+  }
+}
+
+/// Перерисовать сообщение backend на другом языке (при смене языка в
+/// интерфейсе)
+#[tauri::command]
+pub fn render_localized_message(message: LocalizedMessage, lang: String) -> String {
+  message.render_in(&lang)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_keys_render_in_both_locales() {
+    let args = [("limit", "2".to_string())];
+    assert_eq!(
+      t_in("en", "render_queue.limit_reached", &args),
+      "Active job limit reached: at most 2 renders can run at once"
+    );
+    assert_eq!(
+      t_in("ru", "render_queue.limit_reached", &args),
+      "Достигнут лимит активных задач: одновременно выполняется не больше 2 рендеров"
+    );
+
+    assert_eq!(
+      t_in("en", "validation.invalid_fps", &[]),
+      "FPS must be greater than 0"
+    );
+    assert_eq!(
+      t_in("ru", "validation.invalid_fps", &[]),
+      "FPS должен быть больше 0"
+    );
+
+    assert_eq!(t_in("en", "progress.stage.encoding", &[]), "Encoding");
+    assert_eq!(t_in("ru", "progress.stage.encoding", &[]), "Кодирование");
+  }
+
+  #[test]
+  fn test_every_key_has_english_template() {
+    for (key, _) in RU {
+      assert!(
+        lookup(EN, key).is_some(),
+        "нет английского шаблона для {key}"
+      );
+    }
+  }
+
+  #[test]
+  fn test_missing_translation_falls_back_to_english() {
+    // Для немецкого каталога нет - используется английский
+    assert_eq!(
+      t_in("de", "validation.invalid_resolution", &[]),
+      "Resolution must be greater than 0x0"
+    );
+    // Неизвестный ключ возвращается как есть
+    assert_eq!(t_in("ru", "no.such.key", &[]), "no.such.key");
+  }
+
+  #[test]
+  fn test_localized_message_rerenders_in_other_language() {
+    let message = LocalizedMessage::new_in(
+      "en",
+      "validation.clips_overlap",
+      &[
+        ("track", "V1".to_string()),
+        ("first", "a".to_string()),
+        ("second", "b".to_string()),
+      ],
+    );
+    assert_eq!(message, "Clips overlap in track 'V1': a and b");
+    assert_eq!(message.key(), Some("validation.clips_overlap"));
+    assert_eq!(
+      message.render_in("ru"),
+      "Клипы пересекаются по времени в треке 'V1': a и b"
+    );
+  }
+
+  #[test]
+  fn test_localized_message_serialization() {
+    let keyed =
+      LocalizedMessage::new_in("en", "ffmpeg.not_runnable", &[("path", "/x".to_string())]);
+    let json = serde_json::to_value(&keyed).unwrap();
+    assert_eq!(json["key"], "ffmpeg.not_runnable");
+    assert_eq!(json["args"]["path"], "/x");
+    assert_eq!(json["message"], "FFmpeg does not start: /x");
+    let back: LocalizedMessage = serde_json::from_value(json).unwrap();
+    assert_eq!(back, keyed);
+
+    // Текст без ключа остается строкой
+    let raw = LocalizedMessage::raw("plain");
+    assert_eq!(serde_json::to_value(&raw).unwrap(), "plain");
+    let back: LocalizedMessage = serde_json::from_str("\"plain\"").unwrap();
+    assert_eq!(back, raw);
+  }
+}
@@ -1,7 +1,7 @@
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
-use tauri::Manager;
+use tauri::{Emitter, Manager};
 use tokio::sync::RwLock;
 
 // Core infrastructure modules
@@ -32,6 +32,9 @@ mod filesystem;
 // Модуль управления директориями приложения
 mod app_dirs;
 
+// Отчет об использовании диска кэшами и временными файлами
+mod storage_report;
+
 // Модуль Video Compiler
 pub mod video_compiler;
 use video_compiler::VideoCompilerState;
@@ -64,23 +67,35 @@ fn greet() -> String {
   format!("Hello world from Rust! Current epoch: {epoch_ms}")
 }
 
+/// Запускает сканирование папки в фоне и возвращает идентификатор задачи.
+///
+/// Прогресс приходит событиями `media-scan`; если папка уже сканируется,
+/// возвращается идентификатор выполняющейся задачи.
 #[tauri::command]
 async fn scan_media_folder<R: tauri::Runtime>(
   folder_path: String,
   app_handle: tauri::AppHandle<R>,
-) -> Result<Vec<media::types::MediaFile>, String> {
+  scans: tauri::State<'_, media::scan_jobs::MediaScanState>,
+) -> Result<String, String> {
   use std::path::Path;
 
   // Получаем директорию для кеша превью
   let app_dirs = app_dirs::get_app_directories().await?;
   let thumbnail_dir = app_dirs.caches_dir.join("thumbnails");
 
-  // Создаем процессор
-  let processor = MediaProcessor::new(app_handle, thumbnail_dir);
+  let (job, created) = scans.start_or_get(Path::new(&folder_path)).await;
+  if created {
+    // Создаем процессор и запускаем сканирование и обработку
+    let processor = MediaProcessor::new(app_handle, thumbnail_dir);
+    let scan = job.clone();
+    tauri::async_runtime::spawn(async move {
+      if let Err(e) = processor.run_scan(&scan, None).await {
+        log::error!("Media scan {} failed: {e}", scan.id());
+      }
+    });
+  }
 
-  // Запускаем сканирование и обработку
-  let folder = Path::new(&folder_path);
-  processor.scan_and_process(folder, None).await
+  Ok(job.id().to_string())
 }
 
 #[tauri::command]
@@ -88,8 +103,9 @@ async fn scan_media_folder_with_thumbnails<R: tauri::Runtime>(
   folder_path: String,
   width: u32,
   height: u32,
+  thumbnail_options: Option<ThumbnailOptions>,
   app_handle: tauri::AppHandle<R>,
-) -> Result<Vec<media::types::MediaFile>, String> {
+) -> Result<media::processor::MediaScanResult, String> {
   use std::path::Path;
 
   // Получаем директорию для кеша превью
@@ -99,11 +115,11 @@ async fn scan_media_folder_with_thumbnails<R: tauri::Runtime>(
   // Создаем процессор
   let processor = MediaProcessor::new(app_handle, thumbnail_dir);
 
-  // Настройки для превью
+  // Настройки для превью; размер всегда берется из аргументов команды
   let thumbnail_options = Some(ThumbnailOptions {
     width,
     height,
-    ..Default::default()
+    ..thumbnail_options.unwrap_or_default()
   });
 
   // Запускаем сканирование и обработку
@@ -111,6 +127,33 @@ async fn scan_media_folder_with_thumbnails<R: tauri::Runtime>(
   processor.scan_and_process(folder, thumbnail_options).await
 }
 
+/// Инкрементально сканирует папку: обрабатывает только новые и измененные
+/// файлы, исчезнувшие помечает в реестре как отсутствующие
+#[tauri::command]
+async fn scan_media_folder_incremental<R: tauri::Runtime>(
+  folder_path: String,
+  options: Option<media::incremental_scan::FolderScanOptions>,
+  app_handle: tauri::AppHandle<R>,
+) -> Result<media::processor::IncrementalScanResult, String> {
+  use std::path::Path;
+
+  let app_dirs = app_dirs::get_app_directories().await?;
+  let thumbnail_dir = app_dirs.caches_dir.join("thumbnails");
+  let manifest_dir = app_dirs
+    .caches_dir
+    .join(media::incremental_scan::SCAN_MANIFEST_DIR);
+
+  let processor = MediaProcessor::new(app_handle, thumbnail_dir);
+  processor
+    .scan_incremental(
+      Path::new(&folder_path),
+      &manifest_dir,
+      options.unwrap_or_default(),
+      None,
+    )
+    .await
+}
+
 /// Тестовая команда для проверки системы плагинов
 #[tauri::command]
 async fn test_plugin_system(
@@ -135,7 +178,7 @@ async fn test_plugin_system(
   let loaded_plugins = plugin_manager.list_loaded_plugins().await;
   results.push(format!("Loaded plugins: {}", loaded_plugins.len()));
 
-  for (id, state) in &loaded_plugins {
+  for (id, state, _) in &loaded_plugins {
     results.push(format!("- {id} (state: {state:?})"));
   }
 
@@ -159,17 +202,55 @@ pub fn run() {
     }
   });
 
+  // Общий менеджер данных превью для медиа, распознавания и Video Compiler
+  let preview_manager = Arc::new(PreviewDataManager::new(
+    dirs::cache_dir()
+      .unwrap_or_default()
+      .join("timeline-studio"),
+  ));
+
   // Build the app with all registered commands
   app_builder::build_app()
     .manage(LanguageState::default())
-    .manage(PreviewDataManager::new(
-      dirs::cache_dir()
-        .unwrap_or_default()
-        .join("timeline-studio"),
+    .manage(media::media_registry::MediaRegistryState::default())
+    .manage(media::scan_jobs::MediaScanState::default())
+    .manage(Arc::new(
+      video_compiler::ffmpeg_manager::FfmpegManager::new(),
     ))
+    .manage(preview_manager.clone())
+    .manage(media::commands::PreviewManagerState {
+      manager: preview_manager,
+    })
     .setup(|app: &mut tauri::App<tauri::Wry>| {
+      // Уровни логирования по модулям, сохраненные командой set_log_level
+      if let Some(path) = core::logging::default_settings_path() {
+        core::logging::LOGS.load_settings(path);
+      }
+
+      // Безопасный режим: по переменной окружения или после аварийного запуска
+      let startup_marker = core::startup::default_marker_path();
+      let safe_mode = core::startup::detect_safe_mode(
+        std::env::var(core::startup::SAFE_MODE_ENV).ok().as_deref(),
+        startup_marker.as_deref(),
+      );
+      if let Some(reason) = safe_mode {
+        log::warn!("Starting in safe mode: {reason:?}");
+      }
+      if let Some(marker) = &startup_marker {
+        if let Err(e) = core::startup::begin_startup(marker) {
+          log::warn!("Failed to write startup marker: {e}");
+        }
+      }
+      let mut startup_report = core::startup::StartupReport::new(safe_mode);
+
       // Initialize Video Compiler
-      let video_compiler_state = tauri::async_runtime::block_on(video_compiler::initialize());
+      let video_compiler_state = tauri::async_runtime::block_on(video_compiler::initialize_with(
+        video_compiler::InitOptions {
+          skip_gpu_detection: startup_report.is_safe_mode(),
+          ..Default::default()
+        },
+      ));
+      startup_report.record_video_compiler(&video_compiler_state);
       match video_compiler_state {
         Ok(state) => {
           app.manage(state);
@@ -183,58 +264,326 @@ pub fn run() {
       }
 
       // Create Recognition State
-      let recognition_state = RecognitionState::new();
-      app.manage(recognition_state);
+      match RecognitionState::try_new() {
+        Ok(recognition_state) => {
+          startup_report.ok(core::startup::StartupComponent::Recognition);
+          app.manage(recognition_state);
+        }
+        Err(e) => {
+          log::error!("Failed to initialize recognition: {e}");
+          startup_report.failed(
+            core::startup::StartupComponent::Recognition,
+            e.to_string(),
+            "Установите ONNX Runtime или укажите путь к библиотеке в ORT_DYLIB_PATH",
+          );
+        }
+      }
 
       // Create YOLO Processor State
       let yolo_processor_state = YoloProcessorState::default();
-      app.manage(yolo_processor_state);
 
-      // Create Montage Planner State with its own YoloProcessorState instance
-      let montage_yolo_state = Arc::new(RwLock::new(YoloProcessorState::default()));
+      // Montage Planner shares the YOLO processors; all of them use the shared session pool
+      let montage_yolo_state = Arc::new(RwLock::new(yolo_processor_state.clone()));
+      app.manage(yolo_processor_state);
       let montage_state = MontageState::new(montage_yolo_state);
       app.manage(montage_state);
 
+      // Idle recognition model sessions are unloaded to free memory
+      recognition::session_pool::SessionPool::shared().spawn_idle_reaper();
+
       // Create Secure Storage for API keys
       match SecureStorage::new(app.handle().clone()) {
         Ok(storage) => {
           // Wrap SecureStorage in Mutex for thread-safe access
           app.manage(tokio::sync::Mutex::new(storage));
+          startup_report.ok(core::startup::StartupComponent::SecureStorage);
         }
         Err(e) => {
           log::error!("Failed to initialize SecureStorage: {e}");
           // Continue running without secure storage
+          startup_report.failed(
+            core::startup::StartupComponent::SecureStorage,
+            e.to_string(),
+            "Проверьте доступ к системному хранилищу ключей; API ключи не будут сохраняться",
+          );
         }
       }
 
+      // Живая проверка API ключей с кэшированием результатов
+      app.manage(security::api_validator_service::ApiValidatorService::new());
+
       // Initialize Plugin Manager
       let app_version = core::plugins::plugin::Version::new(0, 23, 0); // Current app version
       let event_bus = std::sync::Arc::new(core::EventBus::new());
       let service_container = std::sync::Arc::new(core::di::ServiceContainer::new());
 
+      // Сервисы Video Compiler доступны плагинам и телеметрии через общий DI контейнер
+      let compiler_state = app.state::<VideoCompilerState>();
+      if let Err(e) =
+        tauri::async_runtime::block_on(compiler_state.services.register_into(&service_container))
+      {
+        log::warn!("Failed to register Video Compiler services: {e}");
+      }
+
+      // Плагины из каталога могут ссылаться только на встроенные фабрики
+      let plugin_directory = core::plugins::loader::PluginDirectoryConfig {
+        factory_resolver: Some(std::sync::Arc::new(plugins::example_plugin_factory)),
+        ..Default::default()
+      };
       let plugin_manager = core::plugins::PluginManager::new(
         app_version,
         event_bus.clone(),
         service_container.clone(),
-      );
-
-      // Регистрируем примеры плагинов
-      let registry = plugin_manager.loader().registry();
-      if let Err(e) = tauri::async_runtime::block_on(plugins::register_example_plugins(&registry)) {
-        log::warn!("Failed to register example plugins: {e}");
+      )
+      .with_plugin_directory(plugin_directory);
+
+      // Офлайн-очередь сетевых запросов плагинов отправляется после восстановления сети
+      plugin_manager.network_gateway().spawn_connectivity_monitor();
+
+      // Регистрируем примеры плагинов (в безопасном режиме плагины не загружаются)
+      if startup_report.is_safe_mode() {
+        log::warn!("Plugin loading skipped in safe mode");
+        startup_report.degraded(
+          core::startup::StartupComponent::PluginManager,
+          "Плагины не загружены в безопасном режиме",
+          "Перезапустите приложение в обычном режиме, чтобы загрузить плагины",
+        );
       } else {
-        log::info!("Example plugins registered successfully");
+        let registry = plugin_manager.loader().registry();
+        if let Err(e) = tauri::async_runtime::block_on(plugins::register_example_plugins(&registry))
+        {
+          log::warn!("Failed to register example plugins: {e}");
+          startup_report.degraded(
+            core::startup::StartupComponent::PluginManager,
+            e.to_string(),
+            "Переустановите плагины или отключите проблемный плагин",
+          );
+        } else {
+          log::info!("Example plugins registered successfully");
+          startup_report.ok(core::startup::StartupComponent::PluginManager);
+        }
+
+        match tauri::async_runtime::block_on(plugin_manager.rescan_plugins()) {
+          Ok(report) => log::info!(
+            "Plugin directory scanned: {} registered, {} rejected",
+            report.registered.len(),
+            report.rejected.len()
+          ),
+          Err(e) => log::warn!("Failed to scan plugin directory: {e}"),
+        }
       }
 
+      // Телеметрия необязательна: без нее приложение работает без метрик команд
+      let telemetry = match tauri::async_runtime::block_on(core::TelemetryManager::new(
+        core::TelemetryConfig::default(),
+      )) {
+        Ok(telemetry) => {
+          let video_compiler_sources = compiler_state.services.get_cache_service().map(
+            |cache_service| core::telemetry::health::VideoCompilerHealthSources {
+              cache_service,
+              settings: tauri::async_runtime::block_on(compiler_state.settings.read()).clone(),
+            },
+          );
+          if let Err(e) = tauri::async_runtime::block_on(telemetry.setup_system_health_checks(
+            Some(event_bus.clone()),
+            None,
+            video_compiler_sources,
+          )) {
+            log::warn!("Failed to register health checks: {e}");
+          }
+          tauri::async_runtime::block_on(telemetry.start_cache_snapshots(event_bus.clone()));
+          startup_report.ok(core::startup::StartupComponent::Telemetry);
+          Some(Arc::new(telemetry))
+        }
+        Err(e) => {
+          log::warn!("Failed to initialize telemetry: {e}");
+          startup_report.degraded(
+            core::startup::StartupComponent::Telemetry,
+            e.to_string(),
+            "Метрики недоступны; перезапустите приложение",
+          );
+          None
+        }
+      };
+
+      // При нехватке памяти кэши кадров ужимаются по приоритетам
+      let memory_manager = Arc::new(core::MemoryManager::new());
+      let memory_pressure = memory_manager.pressure();
+      memory_pressure.set_event_bus(event_bus.clone());
+      memory_pressure.register_shrinker(
+        "preview_frames",
+        core::performance::ShrinkPriority::Low,
+        app.state::<Arc<PreviewDataManager>>().inner().clone(),
+      );
+      memory_pressure.register_shrinker(
+        "render_cache",
+        core::performance::ShrinkPriority::Normal,
+        compiler_state.cache_manager.clone(),
+      );
+      if let Some(telemetry) = &telemetry {
+        tauri::async_runtime::block_on(telemetry.health().add_check(Box::new(
+          core::telemetry::health::MemoryPressureHealthCheck::new(memory_pressure.clone()),
+        )));
+      }
+      tauri::async_runtime::spawn(memory_pressure.run_sampler());
+      app.manage(memory_manager);
+
+      // Координатор завершения работы: дренаж рендеров, сброс кэшей, остановка сервисов
+      let app_handle = app.handle().clone();
+      let mut app_shutdown =
+        core::shutdown::AppShutdown::new(compiler_state.services.clone(), event_bus.clone())
+          .with_cache_manager(compiler_state.cache_manager.clone())
+          .with_progress_listener(move |progress| {
+            if let Err(e) = app_handle.emit("app-shutdown-progress", progress) {
+              log::warn!("Failed to emit shutdown progress: {e}");
+            }
+          });
+      if let Some(telemetry) = telemetry {
+        app_shutdown = app_shutdown.with_telemetry(telemetry.clone());
+        app.manage(telemetry);
+      }
+      app.manage(Arc::new(app_shutdown));
+
+      // Прогрев кэша последнего открытого проекта, когда приложение простаивает
+      if let Some(recent) =
+        video_compiler::services::cache_warmer::RecentProjects::default_location()
+      {
+        let compiler_state = app.state::<VideoCompilerState>();
+        tauri::async_runtime::spawn(
+          video_compiler::services::cache_warmer::auto_warm_recent_project(
+            compiler_state.services.clone(),
+            compiler_state.cache_manager.clone(),
+            recent,
+            video_compiler::services::cache_warmer::AUTO_WARM_DELAY,
+          ),
+        );
+      }
+
+      // Предрендер окна впереди playhead во время воспроизведения
+      let compiler_state = app.state::<VideoCompilerState>();
+      let playback_assist = video_compiler::services::playback_assist::PlaybackAssist::new(
+        Arc::new(
+          video_compiler::commands::playback_assist_commands::PrerenderChunkRenderer::from_state(
+            &compiler_state,
+          ),
+        ),
+        compiler_state.cache_manager.clone(),
+      );
+      app.manage(Arc::new(playback_assist));
+
+      // Индексы точек привязки timeline, кэшируемые по ревизии проекта
+      app.manage(Arc::new(video_compiler::services::snap_index::SnapIndexCache::new()));
+
+      // Планировщик отложенных рендеров сообщает о запуске событием RenderStarted
+      let app_handle = app.handle().clone();
+      let render_service = app.state::<VideoCompilerState>().services.render.clone();
+      tauri::async_runtime::spawn(video_compiler::services::render_schedule::run_scheduler(
+        render_service,
+        video_compiler::services::render_schedule::SCHEDULER_POLL_INTERVAL,
+        move |job_id| {
+          let event = video_compiler::VideoCompilerEvent::RenderStarted {
+            job_id: job_id.to_string(),
+          };
+          if let Err(e) = app_handle.emit("video-compiler", &event) {
+            log::warn!("Failed to emit scheduled render start: {e}");
+          }
+        },
+      ));
+
+      // Новые превью сообщаются событием PreviewGenerated с путем к кэшу
+      let app_handle = app.handle().clone();
+      app
+        .state::<Arc<PreviewDataManager>>()
+        .set_event_sink(Arc::new(move |event| {
+          if let Err(e) = app_handle.emit("video-compiler", &event) {
+            log::warn!("Failed to emit preview event: {e}");
+          }
+        }));
+
+      // Поток обновлений фоновых задач для панели задач (не чаще ~4 Гц)
+      let app_handle = app.handle().clone();
+      tauri::async_runtime::spawn(async move {
+        core::tasks::forward_task_updates(
+          &core::tasks::TASKS,
+          core::tasks::TASK_UPDATE_INTERVAL,
+          |tasks| {
+            if let Err(e) = app_handle.emit(core::tasks::BACKGROUND_TASKS_EVENT, tasks) {
+              log::warn!("Failed to emit background tasks: {e}");
+            }
+          },
+        )
+        .await;
+      });
+
       app.manage(plugin_manager);
       app.manage(event_bus);
       app.manage(service_container);
 
+      for component in startup_report.unavailable() {
+        log::warn!(
+          "Component {:?} is {:?}: {}",
+          component.component,
+          component.status,
+          component.error.as_deref().unwrap_or_default()
+        );
+      }
+      app.manage(startup_report);
+
+      if let Some(marker) = &startup_marker {
+        if let Err(e) = core::startup::complete_startup(marker) {
+          log::warn!("Failed to remove startup marker: {e}");
+        }
+      }
+
       log::info!("Application setup completed");
       Ok(())
     })
-    .run(tauri::generate_context!())
-    .expect("error while running tauri application");
+    .build(tauri::generate_context!())
+    .expect("error while building tauri application")
+    .run(|app_handle, event| match event {
+      tauri::RunEvent::WindowEvent {
+        event: tauri::WindowEvent::CloseRequested { api, .. },
+        ..
+      } => {
+        if begin_shutdown(app_handle) {
+          api.prevent_close();
+        }
+      }
+      tauri::RunEvent::ExitRequested { api, .. } => {
+        if begin_shutdown(app_handle) {
+          api.prevent_exit();
+        }
+      }
+      _ => {}
+    });
+}
+
+/// Запустить координированное завершение, если оно еще не выполнено.
+/// Возвращает true, если закрытие нужно отложить до конца последовательности.
+fn begin_shutdown(app_handle: &tauri::AppHandle) -> bool {
+  let Some(shutdown) = app_handle.try_state::<Arc<core::shutdown::AppShutdown>>() else {
+    return false;
+  };
+  if shutdown.is_completed() {
+    return false;
+  }
+
+  let shutdown = shutdown.inner().clone();
+  let app_handle = app_handle.clone();
+  tauri::async_runtime::spawn(async move {
+    match shutdown.request(false).await {
+      Ok(report) if report.status == core::shutdown::ShutdownStatus::Completed => {
+        app_handle.exit(0);
+      }
+      // Последовательность уже выполняется и сама закроет приложение
+      Ok(_) => {}
+      Err(e) => {
+        log::error!("Shutdown failed: {e}");
+        app_handle.exit(1);
+      }
+    }
+  });
+  true
 }
 
 #[cfg(test)]
@@ -311,6 +660,8 @@ mod tests {
             bitrate: Some(5000),
             video_codec: Some("h264".to_string()),
             audio_codec: Some("aac".to_string()),
+            rotation: 0,
+            schema_version: video_compiler::cache::METADATA_SCHEMA_VERSION,
             cached_at: std::time::SystemTime::now(),
           },
         )
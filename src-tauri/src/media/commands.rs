@@ -1,12 +1,31 @@
 use anyhow::Result;
 use chrono;
 use std::path::PathBuf;
-use tauri::State;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager, State};
 
+use super::conformance::{
+  autofix_output_path, build_autofix_args, evaluate_conformance, probe_failure_finding,
+  probe_keyframe_times, probe_media_json, ConformanceOptions, MediaAutoFix, MediaConformanceReport,
+};
 use super::ffmpeg::check_ffmpeg;
+use super::fingerprint::{compute_fingerprint, FingerprintMode};
+use super::frame_index::IndexedFrame;
+use super::hover_preview::{HoverPreview, HoverPreviewOptions, DEFAULT_HOVER_BATCH_CONCURRENCY};
+use super::media_registry::{DuplicateGroup, MediaRegistryEntry, MediaRegistryState};
+use super::media_tags::{
+  derive_tags, MediaTagSummary, MediaTagThresholds, MediaTags, TagMatchMode,
+};
+use super::photo_decoder;
 use super::preview_data::MediaPreviewData;
 use super::preview_manager::PreviewDataManager;
+use super::processor::ProcessorEvent;
+use super::scan_jobs::{MediaScanSnapshot, MediaScanState, MEDIA_SCAN_EVENT};
+use super::sprite::{SpriteOptions, SpriteSheetSet};
 use super::types::{MediaFile, SUPPORTED_EXTENSIONS};
+use crate::recognition::commands::RecognitionState;
+use crate::video_compiler::services::ffmpeg_service::ffprobe_path;
+use crate::video_compiler::VideoCompilerState;
 use serde::Serialize;
 use std::path::Path;
 use std::process::Command;
@@ -100,6 +119,7 @@ pub fn get_media_metadata(file_path: String) -> Result<MediaFile, String> {
     start_time: 0,
     creation_time: chrono::Utc::now().to_rfc3339(),
     probe_data,
+    unsupported_reason: None,
   };
 
   Ok(media_file)
@@ -107,7 +127,8 @@ pub fn get_media_metadata(file_path: String) -> Result<MediaFile, String> {
 
 /// State для менеджера превью
 pub struct PreviewManagerState {
-  pub manager: PreviewDataManager,
+  /// Общий экземпляр, также доступный как `State<Arc<PreviewDataManager>>`
+  pub manager: Arc<PreviewDataManager>,
 }
 
 /// Получить данные превью для файла
@@ -138,6 +159,21 @@ pub async fn generate_media_thumbnail(
     .map_err(|e| e.to_string())
 }
 
+/// Полноразмерная копия фото (HEIC/HEIF, RAW и др.) в PNG/JPEG для timeline
+/// с примененной ориентацией. Возвращает путь к файлу в `output_dir`.
+#[tauri::command]
+pub async fn convert_image_for_timeline(
+  path: String,
+  output_dir: String,
+) -> Result<String, String> {
+  tokio::task::spawn_blocking(move || {
+    photo_decoder::convert_image_for_timeline(Path::new(&path), Path::new(&output_dir))
+  })
+  .await
+  .map_err(|e| format!("Конвертация изображения прервана: {e}"))?
+  .map(|output| output.to_string_lossy().to_string())
+}
+
 /// Очистить данные превью для файла
 #[tauri::command]
 pub async fn clear_media_preview_data(
@@ -214,6 +250,57 @@ pub async fn get_timeline_frames(
     .map_err(|e| e.to_string())
 }
 
+/// Получить кадры диафильма файла в диапазоне из индекса кадров
+#[tauri::command]
+pub async fn get_timeline_frames_in_range(
+  state: State<'_, PreviewManagerState>,
+  file_id: String,
+  start: f64,
+  end: f64,
+  max_count: Option<usize>,
+) -> Result<Vec<IndexedFrame>, String> {
+  Ok(
+    state
+      .manager
+      .get_frames_in_range(&file_id, start, end, max_count.unwrap_or(usize::MAX))
+      .await,
+  )
+}
+
+/// Прочитать миниатюру или кадр из кэша превью в base64.
+///
+/// Файлы кэша могут быть зашифрованы режимом приватности; расшифровка
+/// выполняется только в памяти.
+#[tauri::command]
+pub async fn read_preview_image(
+  state: State<'_, PreviewManagerState>,
+  path: String,
+) -> Result<String, String> {
+  use base64::{engine::general_purpose::STANDARD, Engine as _};
+
+  let image_data = state
+    .manager
+    .read_cached_image(Path::new(&path))
+    .await
+    .map_err(|e| e.to_string())?;
+  Ok(STANDARD.encode(image_data))
+}
+
+/// Инвалидировать кадры диафильма в диапазоне (например, после изменения обрезки клипа)
+#[tauri::command]
+pub async fn invalidate_timeline_frames(
+  state: State<'_, PreviewManagerState>,
+  file_id: String,
+  start: f64,
+  end: f64,
+) -> Result<usize, String> {
+  state
+    .manager
+    .invalidate_frames_in_range(&file_id, start, end)
+    .await
+    .map_err(|e| e.to_string())
+}
+
 /// Генерировать превью для таймлайна с использованием PreviewGenerator
 #[tauri::command]
 pub async fn generate_timeline_previews(
@@ -232,6 +319,121 @@ pub async fn generate_timeline_previews(
     .map_err(|e| e.to_string())
 }
 
+/// Сгенерировать спрайт-листы превью для скраббинга таймлайна
+#[tauri::command]
+pub async fn generate_thumbnail_sprite(
+  state: State<'_, PreviewManagerState>,
+  file_id: String,
+  video_path: String,
+  interval: f64,
+  tile_width: u32,
+  tile_height: u32,
+  columns: u32,
+) -> Result<SpriteSheetSet, String> {
+  let options = SpriteOptions {
+    interval,
+    tile_width,
+    tile_height,
+    columns,
+  };
+  options.validate()?;
+
+  state
+    .manager
+    .generate_thumbnail_sprite(file_id, PathBuf::from(video_path), options)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Сгенерировать анимированное превью при наведении для медиабраузера
+#[tauri::command]
+pub async fn generate_hover_preview(
+  state: State<'_, PreviewManagerState>,
+  file_id: String,
+  file_path: String,
+  options: Option<HoverPreviewOptions>,
+) -> Result<HoverPreview, String> {
+  let options = options.unwrap_or_default();
+  options.validate()?;
+
+  state
+    .manager
+    .generate_hover_preview(file_id, PathBuf::from(file_path), options)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Сгенерировать превью при наведении для всех медиафайлов папки.
+///
+/// Файлы кодируются в фоне не более чем по `max_concurrency` одновременно;
+/// прогресс отправляется событиями `HoverPreviewProgress` в канал
+/// `media-processor`. Возвращает количество успешно созданных превью.
+#[tauri::command]
+pub async fn generate_folder_hover_previews(
+  app: AppHandle,
+  state: State<'_, PreviewManagerState>,
+  directory: String,
+  options: Option<HoverPreviewOptions>,
+  max_concurrency: Option<usize>,
+) -> Result<usize, String> {
+  let options = options.unwrap_or_default();
+  options.validate()?;
+
+  let files = get_media_files(directory)?;
+  let total = files.len();
+  let semaphore = Arc::new(tokio::sync::Semaphore::new(
+    max_concurrency
+      .unwrap_or(DEFAULT_HOVER_BATCH_CONCURRENCY)
+      .max(1),
+  ));
+  let mut join_set = tokio::task::JoinSet::new();
+
+  for file_path in files {
+    let manager = state.manager.clone();
+    let options = options.clone();
+    let semaphore = semaphore.clone();
+
+    join_set.spawn(async move {
+      let _permit = semaphore.acquire().await.ok()?;
+      let path = PathBuf::from(&file_path);
+      let file_id = manager.file_id_for_path(&path).await;
+      let result = manager
+        .generate_hover_preview(file_id.clone(), path, options)
+        .await;
+      Some((file_id, file_path, result))
+    });
+  }
+
+  let mut current = 0;
+  let mut generated = 0;
+  while let Some(joined) = join_set.join_next().await {
+    let Ok(Some((file_id, file_path, result))) = joined else {
+      continue;
+    };
+    current += 1;
+    let (preview_path, error) = match result {
+      Ok(preview) => {
+        generated += 1;
+        (Some(preview.path.to_string_lossy().to_string()), None)
+      }
+      Err(e) => (None, Some(e.to_string())),
+    };
+    let _ = app.emit(
+      "media-processor",
+      ProcessorEvent::HoverPreviewProgress {
+        current,
+        total,
+        file_id,
+        file_path,
+        preview_path,
+        error,
+      },
+    );
+  }
+
+  Ok(generated)
+}
+
 /// Извлечь кадры для распознавания с использованием FrameExtractionManager
 #[tauri::command]
 pub async fn extract_recognition_frames(
@@ -439,6 +641,206 @@ pub async fn process_media_files_with_thumbnails(
   process_media_files(file_paths).await
 }
 
+/// Результат переноса медиафайла на новый путь
+#[derive(Debug, Clone, Serialize)]
+pub struct MediaRelinkResult {
+  /// Запись реестра перенесена
+  pub registry_updated: bool,
+  /// Количество перенесенных записей превью/распознавания
+  pub preview_entries: usize,
+  /// Кэшированные метаданные перенесены
+  pub metadata_moved: bool,
+}
+
+/// Отменить сканирование папки.
+///
+/// Возвращает `false`, если сканирование уже завершено.
+#[tauri::command]
+pub async fn cancel_media_scan(
+  app_handle: AppHandle,
+  state: State<'_, MediaScanState>,
+  scan_id: String,
+) -> Result<bool, String> {
+  let job = state
+    .get(&scan_id)
+    .await
+    .ok_or_else(|| format!("Сканирование не найдено: {scan_id}"))?;
+
+  let cancelled = job.cancel();
+  if cancelled {
+    let _ = app_handle.emit(MEDIA_SCAN_EVENT, job.snapshot().progress);
+  }
+  Ok(cancelled)
+}
+
+/// Получить статус сканирования папки вместе с результатами (частичными,
+/// если сканирование отменено или еще выполняется)
+#[tauri::command]
+pub async fn get_media_scan_status(
+  state: State<'_, MediaScanState>,
+  scan_id: String,
+) -> Result<MediaScanSnapshot, String> {
+  state
+    .get(&scan_id)
+    .await
+    .map(|job| job.snapshot())
+    .ok_or_else(|| format!("Сканирование не найдено: {scan_id}"))
+}
+
+/// Найти группы медиафайлов с одинаковым содержимым
+#[tauri::command]
+pub async fn find_duplicate_media(
+  state: State<'_, MediaRegistryState>,
+) -> Result<Vec<DuplicateGroup>, String> {
+  Ok(state.registry.read().await.find_duplicates())
+}
+
+/// Установить режим вычисления отпечатков (быстрый или по всему файлу)
+#[tauri::command]
+pub async fn set_media_fingerprint_mode(
+  state: State<'_, MediaRegistryState>,
+  mode: FingerprintMode,
+) -> Result<(), String> {
+  *state.fingerprint_mode.write().await = mode;
+  Ok(())
+}
+
+/// Пересчитать теги файла по сохраненным результатам распознавания.
+///
+/// Если результатов нет, теги файла сбрасываются и возвращается `None`.
+#[tauri::command]
+pub async fn derive_media_tags(
+  recognition: State<'_, RecognitionState>,
+  state: State<'_, MediaRegistryState>,
+  file_id: String,
+) -> Result<Option<MediaTags>, String> {
+  let results = recognition
+    .service
+    .load_results(&file_id)
+    .await
+    .map_err(|e| e.to_string())?;
+  let tags = match results {
+    Some(results) => Some(derive_tags(&results, &*state.tag_thresholds.read().await)),
+    None => None,
+  };
+  state
+    .registry
+    .write()
+    .await
+    .set_tags(&file_id, tags.clone());
+  Ok(tags)
+}
+
+/// Найти файлы реестра по тегам (все теги или любой из них)
+#[tauri::command]
+pub async fn search_media_by_tags(
+  state: State<'_, MediaRegistryState>,
+  tags: Vec<String>,
+  match_mode: Option<TagMatchMode>,
+) -> Result<Vec<MediaRegistryEntry>, String> {
+  let registry = state.registry.read().await;
+  Ok(
+    registry
+      .search_by_tags(&tags, match_mode.unwrap_or_default())
+      .into_iter()
+      .cloned()
+      .collect(),
+  )
+}
+
+/// Частота тегов по всему реестру или по одной папке
+#[tauri::command]
+pub async fn get_media_tag_summary(
+  state: State<'_, MediaRegistryState>,
+  folder: Option<String>,
+) -> Result<MediaTagSummary, String> {
+  Ok(state.registry.read().await.tag_summary(folder.as_deref()))
+}
+
+/// Текущие пороги вывода тегов
+#[tauri::command]
+pub async fn get_media_tag_thresholds(
+  state: State<'_, MediaRegistryState>,
+) -> Result<MediaTagThresholds, String> {
+  Ok(state.tag_thresholds.read().await.clone())
+}
+
+/// Установить пороги вывода тегов; уже выведенные теги не пересчитываются
+#[tauri::command]
+pub async fn set_media_tag_thresholds(
+  state: State<'_, MediaRegistryState>,
+  thresholds: MediaTagThresholds,
+) -> Result<(), String> {
+  thresholds.validate()?;
+  *state.tag_thresholds.write().await = thresholds;
+  Ok(())
+}
+
+/// Перенести превью, результаты распознавания и кэшированные метаданные
+/// перемещенного файла на новый путь
+#[tauri::command]
+pub async fn relink_media(
+  app_handle: AppHandle,
+  state: State<'_, MediaRegistryState>,
+  old_path: String,
+  new_path: String,
+) -> Result<MediaRelinkResult, String> {
+  if !Path::new(&new_path).exists() {
+    return Err(format!("Файл не найден: {new_path}"));
+  }
+
+  migrate_media_data(&app_handle, &state, &old_path, &new_path).await
+}
+
+/// Перенести данные реестра, превью, распознавания и кэшированные метаданные
+/// файла на новый путь.
+///
+/// Если файл есть в реестре, содержимое по новому пути должно совпадать
+/// с зарегистрированным отпечатком.
+pub async fn migrate_media_data(
+  app_handle: &AppHandle,
+  state: &MediaRegistryState,
+  old_path: &str,
+  new_path: &str,
+) -> Result<MediaRelinkResult, String> {
+  // Проверяем, что по новому пути то же содержимое
+  let known = state.registry.read().await.get(old_path).cloned();
+  if let Some(entry) = &known {
+    let fingerprint = compute_fingerprint(Path::new(new_path), entry.fingerprint.mode).await?;
+    if fingerprint != entry.fingerprint {
+      return Err(format!("Содержимое {new_path} не совпадает с {old_path}"));
+    }
+  }
+
+  let registry_updated = state
+    .registry
+    .write()
+    .await
+    .relink(old_path, new_path)
+    .is_some();
+
+  let (old, new) = (Path::new(old_path), Path::new(new_path));
+  let mut preview_entries = 0;
+  if let Some(manager) = app_handle.try_state::<Arc<PreviewDataManager>>() {
+    preview_entries += manager.relink_file(old, new).await;
+  }
+
+  let metadata_moved = match app_handle.try_state::<crate::video_compiler::VideoCompilerState>() {
+    Some(compiler) => compiler
+      .cache_manager
+      .write()
+      .await
+      .relink_metadata(old_path, new_path),
+    None => false,
+  };
+
+  Ok(MediaRelinkResult {
+    registry_updated,
+    preview_entries,
+    metadata_moved,
+  })
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -707,7 +1109,9 @@ mod tests {
     // Just verify the struct exists and can be created
     let temp_dir = TempDir::new().unwrap();
     let manager = PreviewDataManager::new(temp_dir.path().to_path_buf());
-    let _state = PreviewManagerState { manager };
+    let _state = PreviewManagerState {
+      manager: Arc::new(manager),
+    };
 
     // State should be created successfully
     // Test passes if state creation succeeds without panicking
@@ -781,3 +1185,87 @@ mod tests {
     }
   }
 }
+
+/// Проверить медиафайл на проблемы, ломающие рендер (VFR, нечетные размеры,
+/// неподдерживаемый формат пикселей, частота дискретизации, длинный GOP,
+/// поврежденный контейнер)
+#[tauri::command]
+pub async fn check_media_conformance(
+  state: State<'_, VideoCompilerState>,
+  file_path: String,
+  options: Option<ConformanceOptions>,
+) -> Result<MediaConformanceReport, String> {
+  let path = Path::new(&file_path);
+  if !path.exists() {
+    return Err(format!("Файл не найден: {file_path}"));
+  }
+
+  let ffprobe = ffprobe_path(&state.ffmpeg_path.read().await);
+  let findings = match probe_media_json(&ffprobe, path).await {
+    Ok(probe) => {
+      let keyframes = probe_keyframe_times(&ffprobe, path).await;
+      evaluate_conformance(&probe, &keyframes, &options.unwrap_or_default())
+    }
+    Err(e) => vec![probe_failure_finding(&e)],
+  };
+
+  Ok(MediaConformanceReport::new(file_path, findings))
+}
+
+/// Применить выбранные исправления совместимости одним проходом FFmpeg.
+///
+/// Результат пишется в `output_dir` как `<имя>_conformed.<расширение>`,
+/// прогресс отправляется событием `AutofixProgress`. Возвращает путь результата.
+#[tauri::command]
+pub async fn apply_media_autofix(
+  app: AppHandle,
+  state: State<'_, VideoCompilerState>,
+  file_path: String,
+  fixes: Vec<MediaAutoFix>,
+  output_dir: String,
+) -> Result<String, String> {
+  let input = Path::new(&file_path);
+  if !input.exists() {
+    return Err(format!("Файл не найден: {file_path}"));
+  }
+  if fixes.is_empty() {
+    return Err("Не выбраны исправления".to_string());
+  }
+
+  let output_dir = PathBuf::from(output_dir);
+  tokio::fs::create_dir_all(&output_dir)
+    .await
+    .map_err(|e| format!("Ошибка создания директории: {e}"))?;
+  let output = autofix_output_path(input, &output_dir);
+  if output == input {
+    return Err("Результат не может перезаписать исходный файл".to_string());
+  }
+
+  let ffmpeg = state
+    .services
+    .get_ffmpeg_service()
+    .ok_or_else(|| "FFmpeg сервис недоступен".to_string())?;
+  let duration = ffmpeg
+    .get_file_info(input)
+    .await
+    .map(|info| info.duration)
+    .unwrap_or(0.0);
+
+  let args = build_autofix_args(input, &output, &fixes);
+  let on_progress = |progress: f64| {
+    let _ = app.emit(
+      "media-processor",
+      ProcessorEvent::AutofixProgress {
+        file_path: file_path.clone(),
+        progress,
+      },
+    );
+  };
+  ffmpeg
+    .run_command_with_progress(args, duration, &on_progress)
+    .await
+    .map_err(|e| format!("Ошибка применения исправлений: {e}"))?;
+
+  log::info!("Исправления совместимости применены: {file_path} -> {output:?}");
+  Ok(output.to_string_lossy().to_string())
+}
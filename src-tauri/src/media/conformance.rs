@@ -0,0 +1,792 @@
+// Модуль проверки медиафайлов на совместимость с конвейером рендеринга
+//
+// Некоторые файлы (VFR записи экрана, HEVC 10-bit с телефонов, PNG с нечетными
+// размерами) ломают рендер так, что причину трудно найти. Проверка разбирает
+// вывод ffprobe набором правил; каждое замечание содержит рекомендацию и,
+// если возможно, автоматическое исправление (ремукс или перекодирование).
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+use crate::video_compiler::services::ffmpeg_service::parse_rational;
+
+/// Допустимое относительное расхождение r_frame_rate и avg_frame_rate
+pub const VFR_TOLERANCE: f64 = 0.01;
+
+/// Максимальный интервал между ключевыми кадрами, с
+pub const MAX_GOP_SECONDS: f64 = 10.0;
+
+/// Допустимое расхождение длительности контейнера и потоков (доля и минимум, с)
+pub const DURATION_MISMATCH_RATIO: f64 = 0.05;
+pub const DURATION_MISMATCH_MIN_SECS: f64 = 1.0;
+
+/// Частота дискретизации проекта по умолчанию
+pub const DEFAULT_TARGET_SAMPLE_RATE: u32 = 48000;
+
+/// Кодек экспорта по умолчанию
+pub const DEFAULT_EXPORT_CODEC: &str = "h264";
+
+/// Длительность начала файла, в которой ищутся ключевые кадры, с
+pub const KEYFRAME_PROBE_SECS: u32 = 60;
+
+/// Стандартные частоты кадров для перевода VFR в CFR
+const STANDARD_FRAME_RATES: &[f64] = &[23.976, 24.0, 25.0, 29.97, 30.0, 50.0, 59.94, 60.0];
+
+/// Расширения изображений: исправления пишутся тем же форматом
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "bmp", "tif", "tiff", "webp"];
+
+/// Проверяемое правило
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConformanceRule {
+  /// Переменная частота кадров
+  VariableFrameRate,
+  /// Нечетная ширина или высота
+  OddDimensions,
+  /// Формат пикселей не поддерживается кодеком экспорта
+  UnsupportedPixelFormat,
+  /// Частота дискретизации отличается от частоты проекта
+  AudioSampleRate,
+  /// Слишком редкие ключевые кадры
+  LongGop,
+  /// Поврежденный или обрезанный контейнер
+  CorruptContainer,
+}
+
+/// Серьезность замечания
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConformanceSeverity {
+  /// Рендер пройдет, но возможны рассинхрон, неточная резка или потеря качества
+  Warning,
+  /// Рендер, скорее всего, завершится ошибкой
+  Error,
+}
+
+/// Автоматическое исправление
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MediaAutoFix {
+  /// Переупаковать контейнер без перекодирования
+  Remux,
+  /// Перекодировать в постоянную частоту кадров
+  ConvertToCfr { fps: f64 },
+  /// Дополнить кадр до четных размеров
+  PadToEvenDimensions,
+  /// Перекодировать в поддерживаемый формат пикселей
+  ConvertPixelFormat { pixel_format: String },
+  /// Передискретизировать звук
+  ResampleAudio { sample_rate: u32 },
+  /// Перекодировать с ключевым кадром каждые `keyframe_interval` секунд
+  ShortenGop { keyframe_interval: f64 },
+}
+
+impl MediaAutoFix {
+  /// Описание исправления для UI
+  pub fn description(&self) -> String {
+    match self {
+      MediaAutoFix::Remux => "Переупаковать контейнер без перекодирования".to_string(),
+      MediaAutoFix::ConvertToCfr { fps } => {
+        format!("Перекодировать в постоянную частоту {fps} fps (CFR)")
+      }
+      MediaAutoFix::PadToEvenDimensions => "Дополнить кадр до четных размеров".to_string(),
+      MediaAutoFix::ConvertPixelFormat { pixel_format } => {
+        format!("Перекодировать в формат пикселей {pixel_format}")
+      }
+      MediaAutoFix::ResampleAudio { sample_rate } => {
+        format!("Передискретизировать звук в {sample_rate} Гц")
+      }
+      MediaAutoFix::ShortenGop { keyframe_interval } => {
+        format!("Перекодировать с ключевым кадром каждые {keyframe_interval} с")
+      }
+    }
+  }
+
+  /// Требует ли исправление перекодирования видео
+  fn transcodes_video(&self) -> bool {
+    !matches!(
+      self,
+      MediaAutoFix::Remux | MediaAutoFix::ResampleAudio { .. }
+    )
+  }
+}
+
+/// Замечание проверки
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConformanceFinding {
+  pub rule: ConformanceRule,
+  pub severity: ConformanceSeverity,
+  /// Что обнаружено
+  pub message: String,
+  /// Что рекомендуется сделать
+  pub remediation: String,
+  /// Автоматическое исправление, если оно возможно
+  pub auto_fix: Option<MediaAutoFix>,
+  /// Описание автоматического исправления
+  pub auto_fix_description: Option<String>,
+}
+
+impl ConformanceFinding {
+  fn new(
+    rule: ConformanceRule,
+    severity: ConformanceSeverity,
+    message: String,
+    remediation: &str,
+    auto_fix: Option<MediaAutoFix>,
+  ) -> Self {
+    Self {
+      rule,
+      severity,
+      message,
+      remediation: remediation.to_string(),
+      auto_fix_description: auto_fix.as_ref().map(MediaAutoFix::description),
+      auto_fix,
+    }
+  }
+}
+
+/// Параметры проверки
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConformanceOptions {
+  /// Кодек экспорта (`h264`, `hevc`, `prores`, `dnxhd`, `vp9`, `av1`)
+  #[serde(default)]
+  pub export_codec: Option<String>,
+  /// Частота дискретизации проекта
+  #[serde(default)]
+  pub target_sample_rate: Option<u32>,
+}
+
+/// Результат проверки файла
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MediaConformanceReport {
+  pub file_path: String,
+  pub findings: Vec<ConformanceFinding>,
+  /// Нет замечаний уровня ошибки
+  pub is_conformant: bool,
+}
+
+impl MediaConformanceReport {
+  pub fn new(file_path: String, findings: Vec<ConformanceFinding>) -> Self {
+    let is_conformant = findings
+      .iter()
+      .all(|finding| finding.severity != ConformanceSeverity::Error);
+    Self {
+      file_path,
+      findings,
+      is_conformant,
+    }
+  }
+}
+
+/// Форматы пикселей, которые кодек экспорта принимает без преобразования.
+/// Первый формат - рекомендуемый для исправления.
+pub fn supported_pixel_formats(codec: &str) -> Option<&'static [&'static str]> {
+  match codec {
+    "h264" | "libx264" => Some(&["yuv420p", "yuvj420p", "yuv422p", "yuv444p", "nv12"]),
+    "hevc" | "h265" | "libx265" => Some(&[
+      "yuv420p",
+      "yuv420p10le",
+      "yuv422p",
+      "yuv422p10le",
+      "yuv444p",
+      "yuv444p10le",
+      "nv12",
+      "p010le",
+    ]),
+    "prores" | "prores_ks" => Some(&["yuv422p10le", "yuv444p10le", "yuva444p10le"]),
+    "dnxhd" | "dnxhr" => Some(&["yuv422p", "yuv422p10le", "yuv444p10le"]),
+    "vp9" | "libvpx-vp9" => Some(&["yuv420p", "yuva420p", "yuv422p", "yuv444p", "yuv420p10le"]),
+    "av1" | "libaom-av1" | "libsvtav1" => Some(&["yuv420p", "yuv420p10le"]),
+    _ => None,
+  }
+}
+
+/// Ближайшая стандартная частота кадров
+fn nearest_standard_fps(fps: f64) -> f64 {
+  STANDARD_FRAME_RATES
+    .iter()
+    .copied()
+    .min_by(|a, b| (a - fps).abs().total_cmp(&(b - fps).abs()))
+    .unwrap_or(30.0)
+}
+
+fn number(value: &Value) -> Option<f64> {
+  match value {
+    Value::Number(number) => number.as_f64(),
+    Value::String(text) => text.parse().ok(),
+    _ => None,
+  }
+}
+
+/// Видео поток без обложек (attached_pic)
+fn video_stream(probe: &Value) -> Option<&Value> {
+  probe["streams"].as_array()?.iter().find(|stream| {
+    stream["codec_type"] == "video" && stream["disposition"]["attached_pic"].as_i64() != Some(1)
+  })
+}
+
+/// Замечание для файла, который ffprobe не смог прочитать
+pub fn probe_failure_finding(error: &str) -> ConformanceFinding {
+  ConformanceFinding::new(
+    ConformanceRule::CorruptContainer,
+    ConformanceSeverity::Error,
+    format!("Файл не читается ffprobe: {error}"),
+    "Переупакуйте файл; если это не поможет, файл поврежден и его нужно получить заново",
+    Some(MediaAutoFix::Remux),
+  )
+}
+
+/// Проверить вывод `ffprobe -show_format -show_streams` по набору правил.
+///
+/// `keyframe_times` - метки ключевых кадров начала видео потока
+/// (пустой список отключает проверку GOP).
+pub fn evaluate_conformance(
+  probe: &Value,
+  keyframe_times: &[f64],
+  options: &ConformanceOptions,
+) -> Vec<ConformanceFinding> {
+  let mut findings = Vec::new();
+
+  let Some(streams) = probe["streams"].as_array().filter(|s| !s.is_empty()) else {
+    findings.push(probe_failure_finding("в файле нет потоков"));
+    return findings;
+  };
+
+  check_container_duration(probe, streams, &mut findings);
+
+  if let Some(video) = video_stream(probe) {
+    check_frame_rate(video, &mut findings);
+    check_dimensions(video, &mut findings);
+    check_pixel_format(video, options, &mut findings);
+    check_gop(keyframe_times, &mut findings);
+  }
+
+  let target_rate = options
+    .target_sample_rate
+    .unwrap_or(DEFAULT_TARGET_SAMPLE_RATE);
+  for audio in streams.iter().filter(|s| s["codec_type"] == "audio") {
+    let Some(rate) = number(&audio["sample_rate"]).map(|rate| rate as u32) else {
+      continue;
+    };
+    if rate != target_rate {
+      findings.push(ConformanceFinding::new(
+        ConformanceRule::AudioSampleRate,
+        ConformanceSeverity::Warning,
+        format!(
+          "Аудио поток {} с частотой {rate} Гц будет передискретизирован в {target_rate} Гц при каждом рендере",
+          audio["index"]
+        ),
+        "Передискретизируйте звук заранее, чтобы избежать рассинхрона на длинных клипах",
+        Some(MediaAutoFix::ResampleAudio {
+          sample_rate: target_rate,
+        }),
+      ));
+    }
+  }
+
+  findings
+}
+
+/// Длительность контейнера должна совпадать с длительностью потоков
+fn check_container_duration(
+  probe: &Value,
+  streams: &[Value],
+  findings: &mut Vec<ConformanceFinding>,
+) {
+  let Some(format_duration) = number(&probe["format"]["duration"]) else {
+    return;
+  };
+  let Some(stream_duration) = streams
+    .iter()
+    .filter_map(|stream| number(&stream["duration"]))
+    .reduce(f64::max)
+  else {
+    return;
+  };
+
+  let tolerance = (format_duration * DURATION_MISMATCH_RATIO).max(DURATION_MISMATCH_MIN_SECS);
+  if (format_duration - stream_duration).abs() > tolerance {
+    findings.push(ConformanceFinding::new(
+      ConformanceRule::CorruptContainer,
+      ConformanceSeverity::Error,
+      format!(
+        "Длительность контейнера {format_duration:.2} с не совпадает с длительностью потоков {stream_duration:.2} с: файл, вероятно, обрезан"
+      ),
+      "Переупакуйте файл, чтобы восстановить индекс; если запись оборвалась, конец файла потерян",
+      Some(MediaAutoFix::Remux),
+    ));
+  }
+}
+
+fn check_frame_rate(video: &Value, findings: &mut Vec<ConformanceFinding>) {
+  let rate = |key: &str| video[key].as_str().and_then(parse_rational);
+  let (Some(r_frame_rate), Some(avg_frame_rate)) = (rate("r_frame_rate"), rate("avg_frame_rate"))
+  else {
+    return;
+  };
+
+  if (r_frame_rate - avg_frame_rate).abs() / r_frame_rate > VFR_TOLERANCE {
+    let fps = nearest_standard_fps(avg_frame_rate);
+    findings.push(ConformanceFinding::new(
+      ConformanceRule::VariableFrameRate,
+      ConformanceSeverity::Warning,
+      format!(
+        "Переменная частота кадров: номинальная {r_frame_rate:.3} fps, средняя {avg_frame_rate:.3} fps"
+      ),
+      "Переведите файл в постоянную частоту кадров, иначе звук может разойтись с изображением",
+      Some(MediaAutoFix::ConvertToCfr { fps }),
+    ));
+  }
+}
+
+fn check_dimensions(video: &Value, findings: &mut Vec<ConformanceFinding>) {
+  let (Some(width), Some(height)) = (video["width"].as_u64(), video["height"].as_u64()) else {
+    return;
+  };
+  if width % 2 != 0 || height % 2 != 0 {
+    findings.push(ConformanceFinding::new(
+      ConformanceRule::OddDimensions,
+      ConformanceSeverity::Error,
+      format!(
+        "Нечетный размер кадра {width}x{height}: кодеки с субдискретизацией 4:2:0 его не принимают"
+      ),
+      "Дополните или обрежьте кадр до четных размеров",
+      Some(MediaAutoFix::PadToEvenDimensions),
+    ));
+  }
+}
+
+fn check_pixel_format(
+  video: &Value,
+  options: &ConformanceOptions,
+  findings: &mut Vec<ConformanceFinding>,
+) {
+  let codec = options
+    .export_codec
+    .as_deref()
+    .unwrap_or(DEFAULT_EXPORT_CODEC);
+  let (Some(pixel_format), Some(supported)) =
+    (video["pix_fmt"].as_str(), supported_pixel_formats(codec))
+  else {
+    return;
+  };
+
+  if !supported.contains(&pixel_format) {
+    findings.push(ConformanceFinding::new(
+      ConformanceRule::UnsupportedPixelFormat,
+      ConformanceSeverity::Warning,
+      format!("Формат пикселей {pixel_format} не поддерживается кодеком экспорта {codec}"),
+      "Перекодируйте файл в поддерживаемый формат, чтобы цвета и битовая глубина не менялись при каждом рендере",
+      Some(MediaAutoFix::ConvertPixelFormat {
+        pixel_format: supported[0].to_string(),
+      }),
+    ));
+  }
+}
+
+fn check_gop(keyframe_times: &[f64], findings: &mut Vec<ConformanceFinding>) {
+  let mut times = keyframe_times.to_vec();
+  times.sort_by(f64::total_cmp);
+  let Some(max_interval) = times.windows(2).map(|w| w[1] - w[0]).reduce(f64::max) else {
+    return;
+  };
+
+  if max_interval > MAX_GOP_SECONDS {
+    findings.push(ConformanceFinding::new(
+      ConformanceRule::LongGop,
+      ConformanceSeverity::Warning,
+      format!("Интервал между ключевыми кадрами до {max_interval:.1} с: перемотка и резка будут медленными и неточными"),
+      "Перекодируйте файл с ключевым кадром каждую секунду",
+      Some(MediaAutoFix::ShortenGop {
+        keyframe_interval: 1.0,
+      }),
+    ));
+  }
+}
+
+/// Путь результата исправлений в `output_dir`
+pub fn autofix_output_path(input: &Path, output_dir: &Path) -> PathBuf {
+  let stem = input
+    .file_stem()
+    .and_then(|stem| stem.to_str())
+    .unwrap_or("media");
+  let extension = input
+    .extension()
+    .and_then(|ext| ext.to_str())
+    .unwrap_or("mp4");
+  output_dir.join(format!("{stem}_conformed.{extension}"))
+}
+
+/// Аргументы FFmpeg для применения исправлений за один проход
+pub fn build_autofix_args(input: &Path, output: &Path, fixes: &[MediaAutoFix]) -> Vec<String> {
+  let mut args = vec![
+    "-y".to_string(),
+    "-i".to_string(),
+    input.to_string_lossy().to_string(),
+  ];
+
+  let mut video_filters = Vec::new();
+  let mut keyframe_interval = None;
+  for fix in fixes {
+    match fix {
+      MediaAutoFix::ConvertToCfr { fps } => video_filters.push(format!("fps={fps}")),
+      MediaAutoFix::PadToEvenDimensions => {
+        video_filters.push("pad=ceil(iw/2)*2:ceil(ih/2)*2".to_string())
+      }
+      MediaAutoFix::ConvertPixelFormat { pixel_format } => {
+        video_filters.push(format!("format={pixel_format}"))
+      }
+      MediaAutoFix::ShortenGop {
+        keyframe_interval: interval,
+      } => keyframe_interval = Some(*interval),
+      MediaAutoFix::Remux | MediaAutoFix::ResampleAudio { .. } => {}
+    }
+  }
+
+  let is_image = output
+    .extension()
+    .and_then(|ext| ext.to_str())
+    .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.to_lowercase().as_str()));
+
+  if fixes.iter().any(MediaAutoFix::transcodes_video) {
+    if !video_filters.is_empty() {
+      args.extend(["-vf".to_string(), video_filters.join(",")]);
+    }
+    // Изображения кодируются форматом по расширению результата
+    if !is_image {
+      args.extend(
+        ["-c:v", "libx264", "-preset", "medium", "-crf", "18"]
+          .iter()
+          .map(|arg| arg.to_string()),
+      );
+    }
+    if let Some(interval) = keyframe_interval {
+      args.extend([
+        "-force_key_frames".to_string(),
+        format!("expr:gte(t,n_forced*{interval})"),
+      ]);
+    }
+  } else if !is_image {
+    args.extend(["-c:v".to_string(), "copy".to_string()]);
+  }
+
+  let resample = fixes.iter().find_map(|fix| match fix {
+    MediaAutoFix::ResampleAudio { sample_rate } => Some(*sample_rate),
+    _ => None,
+  });
+  if !is_image {
+    match resample {
+      Some(sample_rate) => args.extend(
+        ["-c:a", "aac", "-b:a", "192k", "-ar"]
+          .iter()
+          .map(|arg| arg.to_string())
+          .chain([sample_rate.to_string()]),
+      ),
+      None => args.extend(["-c:a".to_string(), "copy".to_string()]),
+    }
+  }
+
+  args.push(output.to_string_lossy().to_string());
+  args
+}
+
+/// Пробинг файла ffprobe в JSON
+pub async fn probe_media_json(ffprobe: &Path, path: &Path) -> Result<Value, String> {
+  let output = tokio::process::Command::new(ffprobe)
+    .args([
+      "-v",
+      "error",
+      "-print_format",
+      "json",
+      "-show_format",
+      "-show_streams",
+    ])
+    .arg(path)
+    .kill_on_drop(true)
+    .output()
+    .await
+    .map_err(|e| format!("Ошибка выполнения ffprobe: {e}"))?;
+
+  if !output.status.success() {
+    return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+  }
+  serde_json::from_slice(&output.stdout).map_err(|e| format!("Неверный вывод ffprobe: {e}"))
+}
+
+/// Метки ключевых кадров первого видео потока в начале файла.
+/// Ошибка пробинга дает пустой список: проверка GOP пропускается.
+pub async fn probe_keyframe_times(ffprobe: &Path, path: &Path) -> Vec<f64> {
+  let output = tokio::process::Command::new(ffprobe)
+    .args([
+      "-v",
+      "error",
+      "-select_streams",
+      "v:0",
+      "-skip_frame",
+      "nokey",
+      "-read_intervals",
+      &format!("%+{KEYFRAME_PROBE_SECS}"),
+      "-show_entries",
+      "frame=best_effort_timestamp_time",
+      "-print_format",
+      "json",
+    ])
+    .arg(path)
+    .kill_on_drop(true)
+    .output()
+    .await;
+
+  let Ok(output) = output else {
+    return Vec::new();
+  };
+  if !output.status.success() {
+    return Vec::new();
+  }
+  serde_json::from_slice::<Value>(&output.stdout)
+    .map(|json| parse_keyframe_times(&json))
+    .unwrap_or_default()
+}
+
+/// Метки кадров из вывода `-show_entries frame=best_effort_timestamp_time`
+pub fn parse_keyframe_times(json: &Value) -> Vec<f64> {
+  json["frames"]
+    .as_array()
+    .map(|frames| {
+      frames
+        .iter()
+        .filter_map(|frame| number(&frame["best_effort_timestamp_time"]))
+        .collect()
+    })
+    .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  /// Запись экрана с телефона: VFR, HEVC 10-bit, 44.1 кГц
+  fn phone_recording() -> Value {
+    json!({
+      "streams": [
+        {
+          "index": 0,
+          "codec_type": "video",
+          "codec_name": "hevc",
+          "width": 1920,
+          "height": 1080,
+          "pix_fmt": "yuv420p10le",
+          "r_frame_rate": "60/1",
+          "avg_frame_rate": "10800/371",
+          "duration": "12.366667",
+          "disposition": { "attached_pic": 0 }
+        },
+        {
+          "index": 1,
+          "codec_type": "audio",
+          "codec_name": "aac",
+          "sample_rate": "44100",
+          "duration": "12.360000"
+        }
+      ],
+      "format": { "duration": "12.366667", "format_name": "mov,mp4,m4a,3gp,3g2,mj2" }
+    })
+  }
+
+  fn rules(findings: &[ConformanceFinding]) -> Vec<ConformanceRule> {
+    findings.iter().map(|finding| finding.rule).collect()
+  }
+
+  #[test]
+  fn test_conformant_file_has_no_findings() {
+    let probe = json!({
+      "streams": [
+        {
+          "index": 0,
+          "codec_type": "video",
+          "width": 1280,
+          "height": 720,
+          "pix_fmt": "yuv420p",
+          "r_frame_rate": "30/1",
+          "avg_frame_rate": "30/1",
+          "duration": "10.0"
+        },
+        { "index": 1, "codec_type": "audio", "sample_rate": "48000", "duration": "10.0" }
+      ],
+      "format": { "duration": "10.0" }
+    });
+
+    let report = MediaConformanceReport::new(
+      "clip.mp4".to_string(),
+      evaluate_conformance(&probe, &[0.0, 2.0, 4.0], &ConformanceOptions::default()),
+    );
+    assert!(report.findings.is_empty());
+    assert!(report.is_conformant);
+  }
+
+  #[test]
+  fn test_phone_recording_findings() {
+    let findings = evaluate_conformance(&phone_recording(), &[], &ConformanceOptions::default());
+    assert_eq!(
+      rules(&findings),
+      vec![
+        ConformanceRule::VariableFrameRate,
+        ConformanceRule::UnsupportedPixelFormat,
+        ConformanceRule::AudioSampleRate,
+      ]
+    );
+
+    // Средние ~29.1 fps приводятся к ближайшей стандартной частоте
+    assert_eq!(
+      findings[0].auto_fix,
+      Some(MediaAutoFix::ConvertToCfr { fps: 29.97 })
+    );
+    assert_eq!(
+      findings[0].auto_fix_description.as_deref(),
+      Some("Перекодировать в постоянную частоту 29.97 fps (CFR)")
+    );
+    assert_eq!(
+      findings[1].auto_fix,
+      Some(MediaAutoFix::ConvertPixelFormat {
+        pixel_format: "yuv420p".to_string()
+      })
+    );
+    assert!(findings
+      .iter()
+      .all(|f| f.severity == ConformanceSeverity::Warning));
+
+    // HEVC экспорт принимает 10-bit, проект на 44.1 кГц не требует передискретизации
+    let options = ConformanceOptions {
+      export_codec: Some("hevc".to_string()),
+      target_sample_rate: Some(44100),
+    };
+    let findings = evaluate_conformance(&phone_recording(), &[], &options);
+    assert_eq!(rules(&findings), vec![ConformanceRule::VariableFrameRate]);
+  }
+
+  #[test]
+  fn test_png_with_odd_dimensions() {
+    let probe = json!({
+      "streams": [
+        {
+          "index": 0,
+          "codec_type": "video",
+          "codec_name": "png",
+          "width": 1023,
+          "height": 767,
+          "pix_fmt": "rgba",
+          "r_frame_rate": "25/1",
+          "avg_frame_rate": "0/0"
+        }
+      ],
+      "format": { "format_name": "png_pipe" }
+    });
+
+    let report = MediaConformanceReport::new(
+      "frame.png".to_string(),
+      evaluate_conformance(&probe, &[], &ConformanceOptions::default()),
+    );
+    assert_eq!(
+      rules(&report.findings),
+      vec![
+        ConformanceRule::OddDimensions,
+        ConformanceRule::UnsupportedPixelFormat
+      ]
+    );
+    assert_eq!(report.findings[0].severity, ConformanceSeverity::Error);
+    assert!(!report.is_conformant);
+  }
+
+  #[test]
+  fn test_truncated_container_and_probe_failure() {
+    let mut probe = phone_recording();
+    probe["format"]["duration"] = json!("95.000000");
+    let findings = evaluate_conformance(&probe, &[], &ConformanceOptions::default());
+    assert_eq!(findings[0].rule, ConformanceRule::CorruptContainer);
+    assert_eq!(findings[0].auto_fix, Some(MediaAutoFix::Remux));
+
+    let findings = evaluate_conformance(&json!({}), &[], &ConformanceOptions::default());
+    assert_eq!(rules(&findings), vec![ConformanceRule::CorruptContainer]);
+    assert_eq!(
+      probe_failure_finding("moov atom not found").severity,
+      ConformanceSeverity::Error
+    );
+  }
+
+  #[test]
+  fn test_long_gop_from_keyframe_fixture() {
+    let keyframes = parse_keyframe_times(&json!({
+      "frames": [
+        { "best_effort_timestamp_time": "0.000000" },
+        { "best_effort_timestamp_time": "4.000000" },
+        { "best_effort_timestamp_time": "19.500000" }
+      ]
+    }));
+    assert_eq!(keyframes, vec![0.0, 4.0, 19.5]);
+
+    let mut probe = phone_recording();
+    probe["streams"][0]["avg_frame_rate"] = json!("60/1");
+    let findings = evaluate_conformance(
+      &probe,
+      &keyframes,
+      &ConformanceOptions {
+        export_codec: Some("hevc".to_string()),
+        target_sample_rate: Some(44100),
+      },
+    );
+    assert_eq!(rules(&findings), vec![ConformanceRule::LongGop]);
+    assert!(findings[0].message.contains("15.5"));
+  }
+
+  #[test]
+  fn test_build_autofix_args() {
+    let input = Path::new("/media/rec.mp4");
+    let output = autofix_output_path(input, Path::new("/out"));
+    assert_eq!(output, PathBuf::from("/out/rec_conformed.mp4"));
+
+    let args = build_autofix_args(input, &output, &[MediaAutoFix::Remux]);
+    assert_eq!(
+      args,
+      vec![
+        "-y",
+        "-i",
+        "/media/rec.mp4",
+        "-c:v",
+        "copy",
+        "-c:a",
+        "copy",
+        "/out/rec_conformed.mp4"
+      ]
+    );
+
+    let args = build_autofix_args(
+      input,
+      &output,
+      &[
+        MediaAutoFix::ConvertToCfr { fps: 30.0 },
+        MediaAutoFix::PadToEvenDimensions,
+        MediaAutoFix::ShortenGop {
+          keyframe_interval: 1.0,
+        },
+        MediaAutoFix::ResampleAudio { sample_rate: 48000 },
+      ],
+    )
+    .join(" ");
+    assert!(args.contains("-vf fps=30,pad=ceil(iw/2)*2:ceil(ih/2)*2"));
+    assert!(args.contains("-c:v libx264"));
+    assert!(args.contains("-force_key_frames expr:gte(t,n_forced*1)"));
+    assert!(args.contains("-ar 48000"));
+
+    // Изображение кодируется форматом по расширению, без звука
+    let png = Path::new("/media/frame.png");
+    let args = build_autofix_args(
+      png,
+      &autofix_output_path(png, Path::new("/out")),
+      &[MediaAutoFix::PadToEvenDimensions],
+    )
+    .join(" ");
+    assert!(!args.contains("libx264"));
+    assert!(!args.contains("-c:a"));
+  }
+}
@@ -0,0 +1,174 @@
+// Модуль для вычисления отпечатков содержимого медиафайлов
+//
+// Быстрый режим хеширует первые и последние FAST_SAMPLE_BYTES файла вместе с
+// его размером: этого достаточно, чтобы распознать один и тот же материал,
+// импортированный из разных мест, не читая гигабайты видео целиком.
+// Полный режим хеширует весь файл.
+
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use xxhash_rust::xxh3::Xxh3;
+
+/// Размер сэмпла в начале и в конце файла для быстрого режима
+pub const FAST_SAMPLE_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Размер буфера чтения
+const READ_BUFFER_SIZE: usize = 1024 * 1024;
+
+/// Режим вычисления отпечатка
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FingerprintMode {
+  /// Начало и конец файла + размер
+  #[default]
+  Fast,
+  /// Весь файл
+  Full,
+}
+
+/// Отпечаток содержимого файла
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MediaFingerprint {
+  /// Режим, в котором вычислен отпечаток
+  pub mode: FingerprintMode,
+  /// Размер файла в байтах
+  pub size: u64,
+  /// xxh3-64 хеш в hex
+  pub hash: String,
+}
+
+/// Вычислить отпечаток файла
+pub async fn compute_fingerprint(
+  path: &Path,
+  mode: FingerprintMode,
+) -> Result<MediaFingerprint, String> {
+  let path: PathBuf = path.to_path_buf();
+  tokio::task::spawn_blocking(move || compute_fingerprint_blocking(&path, mode))
+    .await
+    .map_err(|e| format!("Fingerprint task failed: {e}"))?
+}
+
+/// Синхронная версия вычисления отпечатка
+pub fn compute_fingerprint_blocking(
+  path: &Path,
+  mode: FingerprintMode,
+) -> Result<MediaFingerprint, String> {
+  let mut file = File::open(path).map_err(|e| format!("Failed to open {path:?}: {e}"))?;
+  let size = file
+    .metadata()
+    .map_err(|e| format!("Failed to read metadata for {path:?}: {e}"))?
+    .len();
+
+  let mut hasher = Xxh3::new();
+  hasher.update(&size.to_le_bytes());
+
+  match mode {
+    FingerprintMode::Full => hash_range(&mut file, &mut hasher, size)?,
+    FingerprintMode::Fast if size <= FAST_SAMPLE_BYTES * 2 => {
+      hash_range(&mut file, &mut hasher, size)?
+    }
+    FingerprintMode::Fast => {
+      hash_range(&mut file, &mut hasher, FAST_SAMPLE_BYTES)?;
+      file
+        .seek(SeekFrom::Start(size - FAST_SAMPLE_BYTES))
+        .map_err(|e| format!("Failed to seek {path:?}: {e}"))?;
+      hash_range(&mut file, &mut hasher, FAST_SAMPLE_BYTES)?;
+    }
+  }
+
+  Ok(MediaFingerprint {
+    mode,
+    size,
+    hash: format!("{:016x}", hasher.digest()),
+  })
+}
+
+/// Прочитать до `len` байт с текущей позиции в хешер
+fn hash_range(file: &mut File, hasher: &mut Xxh3, len: u64) -> Result<(), String> {
+  let mut buffer = vec![0u8; READ_BUFFER_SIZE];
+  let mut remaining = len;
+
+  while remaining > 0 {
+    let to_read = remaining.min(READ_BUFFER_SIZE as u64) as usize;
+    let read = file
+      .read(&mut buffer[..to_read])
+      .map_err(|e| format!("Failed to read file: {e}"))?;
+    if read == 0 {
+      break;
+    }
+    hasher.update(&buffer[..read]);
+    remaining -= read as u64;
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::io::Write;
+  use tempfile::tempdir;
+
+  fn write_file(path: &Path, data: &[u8]) {
+    let mut file = File::create(path).unwrap();
+    file.write_all(data).unwrap();
+  }
+
+  #[test]
+  fn test_same_content_same_fingerprint() {
+    let dir = tempdir().unwrap();
+    let a = dir.path().join("a.mp4");
+    let b = dir.path().join("nested_b.mp4");
+    write_file(&a, b"identical footage");
+    write_file(&b, b"identical footage");
+
+    let fa = compute_fingerprint_blocking(&a, FingerprintMode::Fast).unwrap();
+    let fb = compute_fingerprint_blocking(&b, FingerprintMode::Fast).unwrap();
+    assert_eq!(fa, fb);
+    assert_eq!(fa.size, 17);
+  }
+
+  #[test]
+  fn test_different_content_different_fingerprint() {
+    let dir = tempdir().unwrap();
+    let a = dir.path().join("a.mp4");
+    let b = dir.path().join("b.mp4");
+    write_file(&a, b"footage one");
+    write_file(&b, b"footage two");
+
+    let fa = compute_fingerprint_blocking(&a, FingerprintMode::Full).unwrap();
+    let fb = compute_fingerprint_blocking(&b, FingerprintMode::Full).unwrap();
+    assert_ne!(fa.hash, fb.hash);
+  }
+
+  #[test]
+  fn test_fast_mode_ignores_middle_of_large_file() {
+    let dir = tempdir().unwrap();
+    let len = (FAST_SAMPLE_BYTES * 2 + 1024) as usize;
+    let mut data = vec![7u8; len];
+    let a = dir.path().join("a.mov");
+    write_file(&a, &data);
+
+    // Меняем байт в середине, вне сэмплов начала и конца
+    data[FAST_SAMPLE_BYTES as usize + 10] = 8;
+    let b = dir.path().join("b.mov");
+    write_file(&b, &data);
+
+    let fast_a = compute_fingerprint_blocking(&a, FingerprintMode::Fast).unwrap();
+    let fast_b = compute_fingerprint_blocking(&b, FingerprintMode::Fast).unwrap();
+    assert_eq!(fast_a, fast_b);
+
+    let full_a = compute_fingerprint_blocking(&a, FingerprintMode::Full).unwrap();
+    let full_b = compute_fingerprint_blocking(&b, FingerprintMode::Full).unwrap();
+    assert_ne!(full_a, full_b);
+  }
+
+  #[test]
+  fn test_missing_file() {
+    let result =
+      compute_fingerprint_blocking(Path::new("/nonexistent/file.mp4"), Default::default());
+    assert!(result.is_err());
+  }
+}
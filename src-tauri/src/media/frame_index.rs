@@ -0,0 +1,369 @@
+// Модуль индекса кадров диафильма таймлайна
+//
+// Для каждого файла хранится индекс: временная метка → кадр в кэше (путь,
+// разрешение, время генерации). Индекс сохраняется рядом с кадрами в
+// index.json, поэтому после перезапуска и после изменения обрезки клипа
+// генерируются только отсутствующие кадры. Одновременные запросы
+// пересекающихся диапазонов согласуются через InFlightFrames: кадр, который
+// уже генерируется, не запускается повторно, а ожидается.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tokio::sync::watch;
+
+use crate::security::privacy_mode::PrivacyMode;
+
+/// Имя файла индекса в директории кадров
+const FRAME_INDEX_NAME: &str = "index.json";
+
+/// Ширина корзины временных меток в миллисекундах.
+/// Метки внутри одной корзины считаются одним кадром.
+pub const FRAME_BUCKET_MS: f64 = 10.0;
+
+/// Корзина временной метки
+pub fn frame_bucket(timestamp: f64) -> i64 {
+  (timestamp * 1000.0 / FRAME_BUCKET_MS).round() as i64
+}
+
+/// Кадр диафильма в индексе файла
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexedFrame {
+  /// Временная метка кадра
+  pub timestamp: f64,
+
+  /// Путь к кадру в кэше
+  pub path: PathBuf,
+
+  /// Разрешение
+  pub width: u32,
+  pub height: u32,
+
+  /// Время генерации
+  pub generated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl IndexedFrame {
+  pub fn resolution(&self) -> (u32, u32) {
+    (self.width, self.height)
+  }
+}
+
+/// Индекс кадров диафильма файла
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FrameIndex {
+  /// Кадры по корзине временной метки
+  frames: BTreeMap<i64, IndexedFrame>,
+}
+
+impl FrameIndex {
+  pub fn len(&self) -> usize {
+    self.frames.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.frames.is_empty()
+  }
+
+  /// Кадр для метки в заданном разрешении
+  pub fn get(&self, timestamp: f64, resolution: (u32, u32)) -> Option<&IndexedFrame> {
+    self
+      .frames
+      .get(&frame_bucket(timestamp))
+      .filter(|frame| frame.resolution() == resolution)
+  }
+
+  /// Добавить кадр. Возвращает вытесненный кадр той же корзины.
+  pub fn insert(&mut self, frame: IndexedFrame) -> Option<IndexedFrame> {
+    self.frames.insert(frame_bucket(frame.timestamp), frame)
+  }
+
+  /// Кадры в диапазоне `[start, end]` по возрастанию метки
+  pub fn frames_in_range(&self, start: f64, end: f64) -> Vec<&IndexedFrame> {
+    if end < start {
+      return Vec::new();
+    }
+    self
+      .frames
+      .range(frame_bucket(start)..=frame_bucket(end))
+      .map(|(_, frame)| frame)
+      .collect()
+  }
+
+  /// Удалить кадры в диапазоне `[start, end]` и вернуть их
+  pub fn remove_range(&mut self, start: f64, end: f64) -> Vec<IndexedFrame> {
+    let buckets: Vec<i64> = self
+      .frames_in_range(start, end)
+      .into_iter()
+      .map(|frame| frame_bucket(frame.timestamp))
+      .collect();
+    buckets
+      .into_iter()
+      .filter_map(|bucket| self.frames.remove(&bucket))
+      .collect()
+  }
+
+  /// Загрузить индекс из директории кадров.
+  /// Отсутствующий или поврежденный индекс считается пустым.
+  pub async fn load(dir: &Path) -> Self {
+    let Ok(json) = PrivacyMode::global()
+      .read(&dir.join(FRAME_INDEX_NAME))
+      .await
+    else {
+      return Self::default();
+    };
+    serde_json::from_slice(&json).unwrap_or_else(|e| {
+      log::warn!("Индекс кадров {dir:?} поврежден и будет пересоздан: {e}");
+      Self::default()
+    })
+  }
+
+  /// Сохранить индекс в директорию кадров
+  pub async fn save(&self, dir: &Path) -> anyhow::Result<()> {
+    tokio::fs::create_dir_all(dir).await?;
+    let json = serde_json::to_vec_pretty(self)?;
+    PrivacyMode::global()
+      .write(&dir.join(FRAME_INDEX_NAME), &json)
+      .await
+  }
+}
+
+/// Равномерно выбрать не более `max_count` элементов, сохраняя порядок
+pub fn sample_evenly<T>(items: Vec<T>, max_count: usize) -> Vec<T> {
+  if items.len() <= max_count {
+    return items;
+  }
+  let step = items.len() as f64 / max_count as f64;
+  let mut wanted = (0..max_count)
+    .map(|i| (i as f64 * step) as usize)
+    .peekable();
+  items
+    .into_iter()
+    .enumerate()
+    .filter_map(|(index, item)| {
+      if wanted.peek() == Some(&index) {
+        wanted.next();
+        Some(item)
+      } else {
+        None
+      }
+    })
+    .collect()
+}
+
+/// Кадры, которые сейчас генерируются, по файлу и корзине метки
+#[derive(Debug, Default)]
+pub struct InFlightFrames {
+  pending: Mutex<HashMap<(String, i64), watch::Sender<()>>>,
+}
+
+impl InFlightFrames {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Захватить генерацию корзин файла.
+  ///
+  /// Возвращает захват свободных корзин и приемники для корзин, которые уже
+  /// генерирует другой запрос; приемник срабатывает, когда тот запрос
+  /// завершается (успешно или нет).
+  pub fn claim(
+    &self,
+    file_id: &str,
+    buckets: &[i64],
+  ) -> (FrameClaim<'_>, Vec<watch::Receiver<()>>) {
+    let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+    let mut claimed = Vec::new();
+    let mut waiting = Vec::new();
+
+    for &bucket in buckets {
+      let key = (file_id.to_string(), bucket);
+      match pending.get(&key) {
+        Some(sender) => waiting.push(sender.subscribe()),
+        None => {
+          pending.insert(key, watch::channel(()).0);
+          claimed.push(bucket);
+        }
+      }
+    }
+
+    let claim = FrameClaim {
+      owner: self,
+      file_id: file_id.to_string(),
+      buckets: claimed,
+    };
+    (claim, waiting)
+  }
+
+  fn release(&self, file_id: &str, buckets: &[i64]) {
+    let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+    for &bucket in buckets {
+      // Удаление отправителя будит всех ожидающих
+      pending.remove(&(file_id.to_string(), bucket));
+    }
+  }
+}
+
+/// Захват генерации корзин. Корзины освобождаются при удалении захвата,
+/// в том числе если генерация завершилась ошибкой или была прервана.
+#[derive(Debug)]
+pub struct FrameClaim<'a> {
+  owner: &'a InFlightFrames,
+  file_id: String,
+  buckets: Vec<i64>,
+}
+
+impl FrameClaim<'_> {
+  /// Захваченные корзины
+  pub fn buckets(&self) -> &[i64] {
+    &self.buckets
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.buckets.is_empty()
+  }
+
+  /// Освободить корзины, которые генерировать уже не нужно
+  pub fn release_where(&mut self, mut done: impl FnMut(i64) -> bool) {
+    let (released, kept): (Vec<i64>, Vec<i64>) =
+      self.buckets.iter().copied().partition(|&b| done(b));
+    self.owner.release(&self.file_id, &released);
+    self.buckets = kept;
+  }
+}
+
+impl Drop for FrameClaim<'_> {
+  fn drop(&mut self) {
+    self.owner.release(&self.file_id, &self.buckets);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tempfile::tempdir;
+
+  fn frame(timestamp: f64, resolution: (u32, u32)) -> IndexedFrame {
+    IndexedFrame {
+      timestamp,
+      path: PathBuf::from(format!("/cache/frame_{}.jpg", frame_bucket(timestamp))),
+      width: resolution.0,
+      height: resolution.1,
+      generated_at: chrono::Utc::now(),
+    }
+  }
+
+  #[test]
+  fn test_frame_bucket_merges_close_timestamps() {
+    assert_eq!(frame_bucket(1.0), frame_bucket(1.004));
+    assert_ne!(frame_bucket(1.0), frame_bucket(1.01));
+    assert_eq!(frame_bucket(0.0), 0);
+  }
+
+  #[test]
+  fn test_get_requires_matching_resolution() {
+    let mut index = FrameIndex::default();
+    index.insert(frame(2.0, (320, 180)));
+
+    assert!(index.get(2.0, (320, 180)).is_some());
+    assert!(index.get(2.001, (320, 180)).is_some());
+    assert!(index.get(2.0, (640, 360)).is_none());
+    assert!(index.get(3.0, (320, 180)).is_none());
+  }
+
+  #[test]
+  fn test_insert_replaces_same_bucket() {
+    let mut index = FrameIndex::default();
+    assert!(index.insert(frame(1.0, (320, 180))).is_none());
+
+    let replaced = index.insert(frame(1.0, (640, 360))).unwrap();
+    assert_eq!(replaced.resolution(), (320, 180));
+    assert_eq!(index.len(), 1);
+  }
+
+  #[test]
+  fn test_range_queries_and_removal() {
+    let mut index = FrameIndex::default();
+    for i in 0..10 {
+      index.insert(frame(i as f64, (320, 180)));
+    }
+
+    let in_range: Vec<f64> = index
+      .frames_in_range(2.0, 4.0)
+      .iter()
+      .map(|f| f.timestamp)
+      .collect();
+    assert_eq!(in_range, vec![2.0, 3.0, 4.0]);
+    assert!(index.frames_in_range(5.0, 4.0).is_empty());
+
+    let removed = index.remove_range(7.5, 100.0);
+    assert_eq!(removed.len(), 2);
+    assert_eq!(index.len(), 8);
+    assert!(index.get(8.0, (320, 180)).is_none());
+  }
+
+  #[test]
+  fn test_sample_evenly() {
+    let items: Vec<usize> = (0..10).collect();
+    assert_eq!(sample_evenly(items.clone(), 20), items);
+    assert_eq!(sample_evenly(items.clone(), 5), vec![0, 2, 4, 6, 8]);
+    assert_eq!(sample_evenly(items.clone(), 1), vec![0]);
+    assert!(sample_evenly(items, 0).is_empty());
+  }
+
+  #[tokio::test]
+  async fn test_save_and_load_roundtrip() {
+    let dir = tempdir().unwrap();
+    let mut index = FrameIndex::default();
+    index.insert(frame(0.5, (320, 180)));
+    index.insert(frame(1.5, (320, 180)));
+
+    index.save(dir.path()).await.unwrap();
+    assert_eq!(FrameIndex::load(dir.path()).await, index);
+
+    // Поврежденный индекс считается пустым
+    tokio::fs::write(dir.path().join(FRAME_INDEX_NAME), b"{")
+      .await
+      .unwrap();
+    assert!(FrameIndex::load(dir.path()).await.is_empty());
+  }
+
+  #[tokio::test]
+  async fn test_claim_waits_for_other_request() {
+    let in_flight = InFlightFrames::new();
+
+    let (first, waiting) = in_flight.claim("file", &[1, 2, 3]);
+    assert_eq!(first.buckets(), &[1, 2, 3]);
+    assert!(waiting.is_empty());
+
+    // Пересекающийся запрос захватывает только свободную корзину
+    let (second, mut waiting) = in_flight.claim("file", &[3, 4]);
+    assert_eq!(second.buckets(), &[4]);
+    assert_eq!(waiting.len(), 1);
+
+    // Другой файл не пересекается
+    let (other, waiting_other) = in_flight.claim("other", &[1]);
+    assert_eq!(other.buckets(), &[1]);
+    assert!(waiting_other.is_empty());
+
+    drop(first);
+    assert!(waiting[0].changed().await.is_err());
+
+    let (third, _) = in_flight.claim("file", &[3, 4]);
+    assert_eq!(third.buckets(), &[3]);
+  }
+
+  #[test]
+  fn test_release_where_frees_done_buckets() {
+    let in_flight = InFlightFrames::new();
+    let (mut claim, _) = in_flight.claim("file", &[1, 2, 3]);
+
+    claim.release_where(|bucket| bucket == 2);
+    assert_eq!(claim.buckets(), &[1, 3]);
+
+    let (again, waiting) = in_flight.claim("file", &[2]);
+    assert_eq!(again.buckets(), &[2]);
+    assert!(waiting.is_empty());
+  }
+}
@@ -0,0 +1,566 @@
+// Модуль для генерации анимированных превью при наведении в медиабраузере
+//
+// Для каждого файла кодируется короткий (2–3 секунды) зацикливаемый ролик без
+// звука в низком разрешении. Фрагменты выбираются из начала, середины или
+// нескольких точек файла (SceneSample: 3 фрагмента, склеенных через concat).
+// Ролики кэшируются по исходнику, времени его модификации и параметрам.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+use super::sprite::source_mtime;
+
+/// Имя файла манифеста в директории превью
+const HOVER_MANIFEST_NAME: &str = "hover.json";
+
+/// Количество фрагментов для стратегии SceneSample
+pub const SCENE_SAMPLE_COUNT: usize = 3;
+
+/// Максимальная длительность превью в секундах
+pub const MAX_HOVER_DURATION: f64 = 10.0;
+
+/// Максимальный размер стороны превью в пикселях
+pub const MAX_HOVER_DIMENSION: u32 = 1280;
+
+/// Количество одновременно кодируемых превью при пакетной генерации
+pub const DEFAULT_HOVER_BATCH_CONCURRENCY: usize = 2;
+
+/// Выбор фрагментов файла для превью
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum HoverSegmentStrategy {
+  /// Начало файла
+  Start,
+  /// Середина файла
+  #[default]
+  Middle,
+  /// Несколько фрагментов, равномерно распределенных по файлу
+  SceneSample,
+}
+
+/// Формат файла превью
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum HoverPreviewFormat {
+  #[default]
+  Mp4,
+  Webp,
+}
+
+impl HoverPreviewFormat {
+  /// Расширение файла
+  pub fn extension(&self) -> &'static str {
+    match self {
+      HoverPreviewFormat::Mp4 => "mp4",
+      HoverPreviewFormat::Webp => "webp",
+    }
+  }
+
+  /// Параметры кодирования маленького файла с низким битрейтом
+  fn codec_args(&self) -> &'static [&'static str] {
+    match self {
+      HoverPreviewFormat::Mp4 => &[
+        "-c:v",
+        "libx264",
+        "-preset",
+        "veryfast",
+        "-crf",
+        "32",
+        "-maxrate",
+        "400k",
+        "-bufsize",
+        "800k",
+        "-pix_fmt",
+        "yuv420p",
+        "-movflags",
+        "+faststart",
+      ],
+      HoverPreviewFormat::Webp => &[
+        "-c:v",
+        "libwebp",
+        "-loop",
+        "0",
+        "-quality",
+        "50",
+        "-compression_level",
+        "4",
+      ],
+    }
+  }
+}
+
+/// Параметры генерации превью при наведении
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HoverPreviewOptions {
+  /// Длительность превью в секундах
+  pub duration: f64,
+  /// Выбор фрагментов
+  pub segment_strategy: HoverSegmentStrategy,
+  /// Размер кадра (ширина, высота)
+  pub resolution: (u32, u32),
+  /// Частота кадров
+  pub fps: u32,
+  /// Формат файла
+  pub format: HoverPreviewFormat,
+}
+
+impl Default for HoverPreviewOptions {
+  fn default() -> Self {
+    Self {
+      duration: 3.0,
+      segment_strategy: HoverSegmentStrategy::default(),
+      resolution: (320, 180),
+      fps: 12,
+      format: HoverPreviewFormat::default(),
+    }
+  }
+}
+
+/// Фрагмент исходного файла
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HoverSegment {
+  /// Начало в исходном файле (секунды)
+  pub start: f64,
+  /// Длительность (секунды)
+  pub duration: f64,
+}
+
+/// Сгенерированное превью при наведении
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HoverPreview {
+  /// Путь к файлу превью
+  pub path: PathBuf,
+  /// Фрагменты исходника, из которых собрано превью
+  pub segments: Vec<HoverSegment>,
+  /// Параметры генерации
+  pub options: HoverPreviewOptions,
+  /// Время модификации исходника на момент генерации (секунды UNIX)
+  pub source_mtime: u64,
+}
+
+impl HoverPreviewOptions {
+  /// Проверить параметры
+  pub fn validate(&self) -> Result<(), String> {
+    if !(self.duration.is_finite() && self.duration > 0.0 && self.duration <= MAX_HOVER_DURATION) {
+      return Err(format!(
+        "Duration must be in (0, {MAX_HOVER_DURATION}] seconds"
+      ));
+    }
+    let (width, height) = self.resolution;
+    if width == 0 || height == 0 {
+      return Err("Resolution must be non-zero".to_string());
+    }
+    if width > MAX_HOVER_DIMENSION || height > MAX_HOVER_DIMENSION {
+      return Err(format!(
+        "Resolution must not exceed {MAX_HOVER_DIMENSION}px"
+      ));
+    }
+    if width % 2 != 0 || height % 2 != 0 {
+      return Err("Resolution must be even".to_string());
+    }
+    if !(1..=60).contains(&self.fps) {
+      return Err("FPS must be in 1-60".to_string());
+    }
+    Ok(())
+  }
+
+  /// Ключ кэша: исходник, время модификации и параметры
+  pub fn cache_key(&self, source: &Path, source_mtime: u64) -> String {
+    let raw = format!(
+      "{}|{}|{}|{:?}|{}x{}|{}|{:?}",
+      source.to_string_lossy(),
+      source_mtime,
+      self.duration,
+      self.segment_strategy,
+      self.resolution.0,
+      self.resolution.1,
+      self.fps,
+      self.format
+    );
+    format!("{:x}", md5::compute(raw.as_bytes()))
+  }
+
+  /// Имя файла превью
+  fn file_name(&self) -> String {
+    format!("hover.{}", self.format.extension())
+  }
+}
+
+/// Выбрать фрагменты исходника длительностью `source_duration`.
+///
+/// Если длительность неизвестна (0), берется начало файла. Короткие файлы
+/// используются целиком.
+pub fn plan_segments(options: &HoverPreviewOptions, source_duration: f64) -> Vec<HoverSegment> {
+  if !(source_duration.is_finite() && source_duration > 0.0) {
+    return vec![HoverSegment {
+      start: 0.0,
+      duration: options.duration,
+    }];
+  }
+
+  let length = options.duration.min(source_duration);
+  match options.segment_strategy {
+    HoverSegmentStrategy::Start => vec![HoverSegment {
+      start: 0.0,
+      duration: length,
+    }],
+    HoverSegmentStrategy::Middle => vec![HoverSegment {
+      start: (source_duration - length) / 2.0,
+      duration: length,
+    }],
+    HoverSegmentStrategy::SceneSample => {
+      let count = SCENE_SAMPLE_COUNT as f64;
+      let length = (options.duration / count).min(source_duration / count);
+      (0..SCENE_SAMPLE_COUNT)
+        .map(|i| {
+          // Центры фрагментов в 1/6, 1/2 и 5/6 файла
+          let center = source_duration * (i as f64 + 0.5) / count;
+          HoverSegment {
+            start: (center - length / 2.0).clamp(0.0, source_duration - length),
+            duration: length,
+          }
+        })
+        .collect()
+    }
+  }
+}
+
+/// Аргументы FFmpeg для кодирования превью из фрагментов.
+///
+/// Каждый фрагмент открывается отдельным входом с `-ss`/`-t`, масштабируется
+/// с сохранением пропорций и склеивается через concat; звук отбрасывается.
+pub fn build_hover_preview_args(
+  input_path: &Path,
+  output_path: &Path,
+  options: &HoverPreviewOptions,
+  segments: &[HoverSegment],
+) -> Vec<String> {
+  let (width, height) = options.resolution;
+  let mut args: Vec<String> = vec!["-hide_banner".into(), "-y".into()];
+
+  for segment in segments {
+    args.extend([
+      "-ss".to_string(),
+      format!("{:.3}", segment.start),
+      "-t".to_string(),
+      format!("{:.3}", segment.duration),
+      "-i".to_string(),
+      input_path.to_string_lossy().to_string(),
+    ]);
+  }
+
+  let single = segments.len() == 1;
+  let mut chains: Vec<String> = (0..segments.len())
+    .map(|i| {
+      let label = if single {
+        "[hover]".to_string()
+      } else {
+        format!("[s{i}]")
+      };
+      format!(
+        "[{i}:v]fps={},scale={width}:{height}:force_original_aspect_ratio=decrease,\
+         pad={width}:{height}:(ow-iw)/2:(oh-ih)/2,setsar=1,setpts=PTS-STARTPTS{label}",
+        options.fps
+      )
+    })
+    .collect();
+  if !single {
+    let inputs: String = (0..segments.len()).map(|i| format!("[s{i}]")).collect();
+    chains.push(format!(
+      "{inputs}concat=n={}:v=1:a=0[hover]",
+      segments.len()
+    ));
+  }
+
+  args.extend([
+    "-filter_complex".to_string(),
+    chains.join(";"),
+    "-map".to_string(),
+    "[hover]".to_string(),
+    "-an".to_string(),
+  ]);
+  args.extend(
+    options
+      .format
+      .codec_args()
+      .iter()
+      .map(|arg| arg.to_string()),
+  );
+  args.push(output_path.to_string_lossy().to_string());
+  args
+}
+
+/// Загрузить ранее сгенерированное превью, если оно актуально
+pub async fn load_cached_hover_preview(
+  output_dir: &Path,
+  options: &HoverPreviewOptions,
+  source_mtime: u64,
+) -> Option<HoverPreview> {
+  let json = tokio::fs::read(output_dir.join(HOVER_MANIFEST_NAME))
+    .await
+    .ok()?;
+  let preview: HoverPreview = serde_json::from_slice(&json).ok()?;
+
+  if preview.source_mtime != source_mtime || preview.options != *options || !preview.path.exists() {
+    return None;
+  }
+
+  Some(preview)
+}
+
+/// Сгенерировать превью при наведении с помощью FFmpeg.
+///
+/// Превью сохраняется в поддиректорию `cache_root`, имя которой зависит от
+/// исходника, времени его модификации и параметров; повторный вызов с теми же
+/// данными возвращает закэшированное превью без запуска FFmpeg.
+pub async fn generate_hover_preview(
+  input_path: &Path,
+  cache_root: &Path,
+  options: &HoverPreviewOptions,
+  source_duration: f64,
+) -> Result<HoverPreview, String> {
+  options.validate()?;
+  let mtime = source_mtime(input_path).await?;
+  let output_dir = cache_root.join(options.cache_key(input_path, mtime));
+
+  if let Some(cached) = load_cached_hover_preview(&output_dir, options, mtime).await {
+    return Ok(cached);
+  }
+
+  let _ = tokio::fs::remove_dir_all(&output_dir).await;
+  tokio::fs::create_dir_all(&output_dir)
+    .await
+    .map_err(|e| format!("Failed to create hover preview directory: {e}"))?;
+
+  let segments = plan_segments(options, source_duration);
+  let output_path = output_dir.join(options.file_name());
+  let output = Command::new("ffmpeg")
+    .args(build_hover_preview_args(
+      input_path,
+      &output_path,
+      options,
+      &segments,
+    ))
+    .output()
+    .await
+    .map_err(|e| format!("Failed to execute ffmpeg: {e}"))?;
+
+  if !output.status.success() {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    return Err(format!("FFmpeg failed: {stderr}"));
+  }
+
+  let preview = HoverPreview {
+    path: output_path,
+    segments,
+    options: options.clone(),
+    source_mtime: mtime,
+  };
+
+  let json = serde_json::to_vec_pretty(&preview).map_err(|e| e.to_string())?;
+  tokio::fs::write(output_dir.join(HOVER_MANIFEST_NAME), json)
+    .await
+    .map_err(|e| format!("Failed to write hover preview manifest: {e}"))?;
+
+  Ok(preview)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn options(strategy: HoverSegmentStrategy, format: HoverPreviewFormat) -> HoverPreviewOptions {
+    HoverPreviewOptions {
+      segment_strategy: strategy,
+      format,
+      ..Default::default()
+    }
+  }
+
+  fn arg_after<'a>(args: &'a [String], flag: &str) -> Vec<&'a str> {
+    args
+      .windows(2)
+      .filter(|pair| pair[0] == flag)
+      .map(|pair| pair[1].as_str())
+      .collect()
+  }
+
+  #[test]
+  fn test_start_strategy_command() {
+    let opts = options(HoverSegmentStrategy::Start, HoverPreviewFormat::Mp4);
+    let segments = plan_segments(&opts, 60.0);
+    assert_eq!(
+      segments,
+      vec![HoverSegment {
+        start: 0.0,
+        duration: 3.0
+      }]
+    );
+
+    let args = build_hover_preview_args(
+      Path::new("/media/take1.mov"),
+      Path::new("/cache/hover.mp4"),
+      &opts,
+      &segments,
+    );
+    assert_eq!(arg_after(&args, "-ss"), vec!["0.000"]);
+    assert_eq!(arg_after(&args, "-t"), vec!["3.000"]);
+    assert_eq!(arg_after(&args, "-i"), vec!["/media/take1.mov"]);
+    assert_eq!(
+      arg_after(&args, "-filter_complex"),
+      vec![
+        "[0:v]fps=12,scale=320:180:force_original_aspect_ratio=decrease,\
+         pad=320:180:(ow-iw)/2:(oh-ih)/2,setsar=1,setpts=PTS-STARTPTS[hover]"
+      ]
+    );
+    assert_eq!(arg_after(&args, "-map"), vec!["[hover]"]);
+    assert!(args.contains(&"-an".to_string()));
+    assert_eq!(arg_after(&args, "-c:v"), vec!["libx264"]);
+    assert_eq!(args.last().unwrap(), "/cache/hover.mp4");
+  }
+
+  #[test]
+  fn test_middle_strategy_command() {
+    let opts = options(HoverSegmentStrategy::Middle, HoverPreviewFormat::Webp);
+    let segments = plan_segments(&opts, 61.0);
+    let args = build_hover_preview_args(
+      Path::new("/media/take2.mp4"),
+      Path::new("/cache/hover.webp"),
+      &opts,
+      &segments,
+    );
+    assert_eq!(arg_after(&args, "-ss"), vec!["29.000"]);
+    assert_eq!(arg_after(&args, "-t"), vec!["3.000"]);
+    assert_eq!(arg_after(&args, "-c:v"), vec!["libwebp"]);
+    assert_eq!(arg_after(&args, "-loop"), vec!["0"]);
+    assert!(!args.iter().any(|arg| arg.contains("concat")));
+  }
+
+  #[test]
+  fn test_scene_sample_strategy_command() {
+    let opts = options(HoverSegmentStrategy::SceneSample, HoverPreviewFormat::Mp4);
+    let segments = plan_segments(&opts, 60.0);
+    let args = build_hover_preview_args(
+      Path::new("/media/take3.mp4"),
+      Path::new("/cache/hover.mp4"),
+      &opts,
+      &segments,
+    );
+
+    // Три фрагмента по 1 секунде с центрами в 10, 30 и 50 секунд
+    assert_eq!(arg_after(&args, "-ss"), vec!["9.500", "29.500", "49.500"]);
+    assert_eq!(arg_after(&args, "-t"), vec!["1.000"; 3]);
+    assert_eq!(arg_after(&args, "-i").len(), 3);
+
+    let filter = arg_after(&args, "-filter_complex")[0];
+    for i in 0..3 {
+      assert!(filter.contains(&format!("[{i}:v]fps=12,")));
+      assert!(filter.contains(&format!("setpts=PTS-STARTPTS[s{i}]")));
+    }
+    assert!(filter.ends_with(";[s0][s1][s2]concat=n=3:v=1:a=0[hover]"));
+  }
+
+  #[test]
+  fn test_segments_for_short_and_unknown_sources() {
+    let middle = options(HoverSegmentStrategy::Middle, HoverPreviewFormat::Mp4);
+    assert_eq!(
+      plan_segments(&middle, 2.0),
+      vec![HoverSegment {
+        start: 0.0,
+        duration: 2.0
+      }]
+    );
+    assert_eq!(plan_segments(&middle, 0.0)[0].duration, 3.0);
+
+    let sample = options(HoverSegmentStrategy::SceneSample, HoverPreviewFormat::Mp4);
+    let segments = plan_segments(&sample, 1.5);
+    assert_eq!(segments.len(), SCENE_SAMPLE_COUNT);
+    assert!(segments
+      .iter()
+      .all(|segment| segment.start >= 0.0 && segment.start + segment.duration <= 1.5 + 1e-9));
+  }
+
+  #[test]
+  fn test_cache_key_depends_on_mtime_and_params() {
+    let opts = HoverPreviewOptions::default();
+    let path = Path::new("/test/video.mp4");
+
+    assert_eq!(opts.cache_key(path, 1), opts.cache_key(path, 1));
+    assert_ne!(opts.cache_key(path, 1), opts.cache_key(path, 2));
+    assert_ne!(
+      opts.cache_key(path, 1),
+      opts.cache_key(Path::new("/test/other.mp4"), 1)
+    );
+    for changed in [
+      options(HoverSegmentStrategy::SceneSample, HoverPreviewFormat::Mp4),
+      options(HoverSegmentStrategy::Middle, HoverPreviewFormat::Webp),
+      HoverPreviewOptions {
+        fps: 24,
+        ..Default::default()
+      },
+    ] {
+      assert_ne!(opts.cache_key(path, 1), changed.cache_key(path, 1));
+    }
+  }
+
+  #[tokio::test]
+  async fn test_cached_preview_invalidated_by_mtime() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let opts = HoverPreviewOptions::default();
+    let preview_path = temp_dir.path().join(opts.file_name());
+    std::fs::write(&preview_path, b"mp4").unwrap();
+
+    let preview = HoverPreview {
+      path: preview_path.clone(),
+      segments: plan_segments(&opts, 10.0),
+      options: opts.clone(),
+      source_mtime: 100,
+    };
+    std::fs::write(
+      temp_dir.path().join(HOVER_MANIFEST_NAME),
+      serde_json::to_vec(&preview).unwrap(),
+    )
+    .unwrap();
+
+    assert!(load_cached_hover_preview(temp_dir.path(), &opts, 100)
+      .await
+      .is_some());
+    assert!(load_cached_hover_preview(temp_dir.path(), &opts, 101)
+      .await
+      .is_none());
+    let webp = options(HoverSegmentStrategy::Middle, HoverPreviewFormat::Webp);
+    assert!(load_cached_hover_preview(temp_dir.path(), &webp, 100)
+      .await
+      .is_none());
+
+    std::fs::remove_file(&preview_path).unwrap();
+    assert!(load_cached_hover_preview(temp_dir.path(), &opts, 100)
+      .await
+      .is_none());
+  }
+
+  #[test]
+  fn test_options_validation() {
+    assert!(HoverPreviewOptions::default().validate().is_ok());
+    for invalid in [
+      HoverPreviewOptions {
+        duration: 0.0,
+        ..Default::default()
+      },
+      HoverPreviewOptions {
+        duration: 30.0,
+        ..Default::default()
+      },
+      HoverPreviewOptions {
+        resolution: (321, 180),
+        ..Default::default()
+      },
+      HoverPreviewOptions {
+        fps: 0,
+        ..Default::default()
+      },
+    ] {
+      assert!(invalid.validate().is_err());
+    }
+  }
+}
@@ -0,0 +1,446 @@
+// Инкрементальное сканирование папок с медиафайлами
+//
+// Для каждой просканированной папки в кэше хранится манифест известных файлов
+// (размер, время изменения, отпечаток содержимого). При повторном сканировании
+// обрабатываются только новые и измененные файлы, а исчезнувшие помечаются
+// в реестре как отсутствующие. Манифест версионирован: при смене формата
+// старый файл игнорируется и выполняется полное пересканирование.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tokio::fs;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use super::fingerprint::MediaFingerprint;
+use super::processor::DiscoveredFile;
+use super::types::SUPPORTED_EXTENSIONS;
+
+/// Текущая версия формата манифеста
+pub const SCAN_MANIFEST_VERSION: u32 = 1;
+
+/// Директория манифестов внутри кэша приложения
+pub const SCAN_MANIFEST_DIR: &str = "scan-manifests";
+
+/// Параметры обхода папки
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FolderScanOptions {
+  /// Сканировать вложенные папки
+  pub include_subdirectories: bool,
+  /// Переходить по символическим ссылкам (циклы отсекаются)
+  pub follow_symlinks: bool,
+}
+
+impl Default for FolderScanOptions {
+  fn default() -> Self {
+    Self {
+      include_subdirectories: true,
+      follow_symlinks: false,
+    }
+  }
+}
+
+/// Найденный файл вместе с временем изменения
+#[derive(Debug, Clone)]
+pub struct ScannedFile {
+  pub file: DiscoveredFile,
+  /// Время изменения в наносекундах от UNIX epoch
+  pub modified_ns: u64,
+}
+
+/// Запись манифеста об обработанном файле
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+  /// ID медиафайла из метаданных (используется реестром и превью)
+  pub file_id: String,
+  /// ID, под которым сохранено превью сканирования
+  pub thumbnail_id: String,
+  pub size: u64,
+  pub modified_ns: u64,
+  pub fingerprint: Option<MediaFingerprint>,
+}
+
+/// Манифест просканированной папки
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanManifest {
+  pub version: u32,
+  /// Папка, к которой относится манифест
+  pub folder: String,
+  /// Записи по пути файла
+  pub entries: BTreeMap<String, ManifestEntry>,
+}
+
+impl ScanManifest {
+  pub fn new(folder: &Path) -> Self {
+    Self {
+      version: SCAN_MANIFEST_VERSION,
+      folder: folder.to_string_lossy().to_string(),
+      entries: BTreeMap::new(),
+    }
+  }
+
+  /// Путь к манифесту папки в директории манифестов
+  pub fn path_for(manifest_dir: &Path, folder: &Path) -> PathBuf {
+    let key = xxhash_rust::xxh3::xxh3_64(folder.to_string_lossy().as_bytes());
+    manifest_dir.join(format!("{key:016x}.json"))
+  }
+
+  /// Загрузить манифест папки.
+  ///
+  /// Возвращает `None`, если манифеста нет, он поврежден, относится к другой
+  /// папке или записан в другой версии формата: в этих случаях нужно полное
+  /// пересканирование.
+  pub async fn load(path: &Path, folder: &Path) -> Option<Self> {
+    let content = fs::read(path).await.ok()?;
+
+    // Версию проверяем до разбора записей, чтобы не читать чужой формат
+    let version = serde_json::from_slice::<ManifestVersion>(&content)
+      .map(|header| header.version)
+      .unwrap_or_default();
+    if version != SCAN_MANIFEST_VERSION {
+      log::info!(
+        "Манифест {} версии {version} устарел, выполняется полное сканирование",
+        path.display()
+      );
+      return None;
+    }
+
+    match serde_json::from_slice::<Self>(&content) {
+      Ok(manifest) if manifest.folder == folder.to_string_lossy() => Some(manifest),
+      Ok(_) => None,
+      Err(e) => {
+        log::warn!("Манифест {} поврежден: {e}", path.display());
+        None
+      }
+    }
+  }
+
+  /// Сохранить манифест атомарно
+  pub async fn save(&self, path: &Path) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent)
+        .await
+        .map_err(|e| format!("Failed to create manifest directory: {e}"))?;
+    }
+    let content = serde_json::to_vec_pretty(self)
+      .map_err(|e| format!("Failed to serialize scan manifest: {e}"))?;
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, content)
+      .await
+      .map_err(|e| format!("Failed to write scan manifest: {e}"))?;
+    fs::rename(&tmp_path, path)
+      .await
+      .map_err(|e| format!("Failed to save scan manifest: {e}"))
+  }
+}
+
+/// Заголовок манифеста для проверки версии
+#[derive(Deserialize)]
+struct ManifestVersion {
+  #[serde(default)]
+  version: u32,
+}
+
+/// Изменения в папке относительно манифеста
+#[derive(Debug, Default)]
+pub struct FolderChanges {
+  /// Файлы, которых нет в манифесте
+  pub new_files: Vec<ScannedFile>,
+  /// Файлы с другим размером или временем изменения вместе с прежней записью
+  pub changed_files: Vec<(ScannedFile, ManifestEntry)>,
+  /// Файлы без изменений
+  pub unchanged: Vec<ScannedFile>,
+  /// Пути из манифеста, которых больше нет на диске
+  pub removed: Vec<String>,
+}
+
+/// Сравнить найденные файлы с манифестом по размеру и времени изменения
+pub fn detect_changes(manifest: &ScanManifest, files: Vec<ScannedFile>) -> FolderChanges {
+  let mut changes = FolderChanges::default();
+  let mut seen = HashSet::new();
+
+  for scanned in files {
+    seen.insert(scanned.file.path.clone());
+    match manifest.entries.get(&scanned.file.path) {
+      None => changes.new_files.push(scanned),
+      Some(entry)
+        if entry.size == scanned.file.size && entry.modified_ns == scanned.modified_ns =>
+      {
+        changes.unchanged.push(scanned)
+      }
+      Some(entry) => changes.changed_files.push((scanned, entry.clone())),
+    }
+  }
+
+  changes.removed = manifest
+    .entries
+    .keys()
+    .filter(|path| !seen.contains(*path))
+    .cloned()
+    .collect();
+  changes
+}
+
+/// Найти поддерживаемые медиафайлы в папке
+pub async fn walk_media_files(
+  folder_path: &Path,
+  options: &FolderScanOptions,
+) -> Result<Vec<ScannedFile>, String> {
+  walk_media_files_until(folder_path, options, &CancellationToken::new(), |_| {}).await
+}
+
+/// Найти поддерживаемые медиафайлы в папке с возможностью отмены.
+///
+/// `on_found` вызывается для каждого найденного файла. После отмены обход
+/// останавливается до чтения следующей записи и возвращает найденное к
+/// этому моменту.
+pub async fn walk_media_files_until(
+  folder_path: &Path,
+  options: &FolderScanOptions,
+  cancel: &CancellationToken,
+  mut on_found: impl FnMut(&ScannedFile),
+) -> Result<Vec<ScannedFile>, String> {
+  let mut files = Vec::new();
+  let mut visited = HashSet::new();
+  let mut dirs_to_scan = vec![folder_path.to_path_buf()];
+
+  while let Some(dir) = dirs_to_scan.pop() {
+    if cancel.is_cancelled() {
+      break;
+    }
+
+    // Защита от циклов через символические ссылки
+    let canonical = fs::canonicalize(&dir).await.unwrap_or_else(|_| dir.clone());
+    if !visited.insert(canonical) {
+      continue;
+    }
+
+    let mut entries = fs::read_dir(&dir)
+      .await
+      .map_err(|e| format!("Failed to read directory {dir:?}: {e}"))?;
+
+    while let Some(entry) = entries
+      .next_entry()
+      .await
+      .map_err(|e| format!("Failed to read entry: {e}"))?
+    {
+      if cancel.is_cancelled() {
+        break;
+      }
+
+      let path = entry.path();
+      let mut metadata = entry
+        .metadata()
+        .await
+        .map_err(|e| format!("Failed to read metadata: {e}"))?;
+
+      if metadata.file_type().is_symlink() {
+        if !options.follow_symlinks {
+          continue;
+        }
+        // Битые ссылки пропускаем
+        match fs::metadata(&path).await {
+          Ok(target) => metadata = target,
+          Err(_) => continue,
+        }
+      }
+
+      if metadata.is_dir() {
+        if options.include_subdirectories {
+          dirs_to_scan.push(path);
+        }
+      } else if metadata.is_file() {
+        if let Some(scanned) = scanned_file(&path, &metadata) {
+          on_found(&scanned);
+          files.push(scanned);
+        }
+      }
+    }
+  }
+
+  Ok(files)
+}
+
+/// Создать запись о файле, если его расширение поддерживается
+fn scanned_file(path: &Path, metadata: &std::fs::Metadata) -> Option<ScannedFile> {
+  let ext = path.extension()?.to_string_lossy().to_lowercase();
+  if !SUPPORTED_EXTENSIONS.contains(&ext.as_str()) {
+    return None;
+  }
+
+  let modified_ns = metadata
+    .modified()
+    .ok()
+    .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+    .map(|duration| duration.as_nanos() as u64)
+    .unwrap_or_default();
+
+  Some(ScannedFile {
+    file: DiscoveredFile {
+      id: Uuid::new_v4().to_string(),
+      path: path.to_string_lossy().to_string(),
+      name: path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string(),
+      extension: ext,
+      size: metadata.len(),
+    },
+    modified_ns,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::media::fingerprint::FingerprintMode;
+  use tempfile::TempDir;
+
+  fn entry(size: u64, modified_ns: u64) -> ManifestEntry {
+    ManifestEntry {
+      file_id: "file".to_string(),
+      thumbnail_id: "thumb".to_string(),
+      size,
+      modified_ns,
+      fingerprint: Some(MediaFingerprint {
+        mode: FingerprintMode::Fast,
+        size,
+        hash: "aa".to_string(),
+      }),
+    }
+  }
+
+  fn scanned(path: &str, size: u64, modified_ns: u64) -> ScannedFile {
+    ScannedFile {
+      file: DiscoveredFile {
+        id: Uuid::new_v4().to_string(),
+        path: path.to_string(),
+        name: path.rsplit('/').next().unwrap().to_string(),
+        extension: "mp4".to_string(),
+        size,
+      },
+      modified_ns,
+    }
+  }
+
+  #[test]
+  fn test_detect_changes() {
+    let mut manifest = ScanManifest::new(Path::new("/media"));
+    manifest
+      .entries
+      .insert("/media/same.mp4".to_string(), entry(10, 1));
+    manifest
+      .entries
+      .insert("/media/edited.mp4".to_string(), entry(10, 1));
+    manifest
+      .entries
+      .insert("/media/gone.mp4".to_string(), entry(10, 1));
+
+    let changes = detect_changes(
+      &manifest,
+      vec![
+        scanned("/media/same.mp4", 10, 1),
+        scanned("/media/edited.mp4", 10, 2),
+        scanned("/media/fresh.mp4", 5, 1),
+      ],
+    );
+
+    assert_eq!(changes.new_files.len(), 1);
+    assert_eq!(changes.new_files[0].file.path, "/media/fresh.mp4");
+    assert_eq!(changes.changed_files.len(), 1);
+    assert_eq!(changes.changed_files[0].0.file.path, "/media/edited.mp4");
+    assert_eq!(changes.unchanged.len(), 1);
+    assert_eq!(changes.removed, vec!["/media/gone.mp4"]);
+  }
+
+  #[tokio::test]
+  async fn test_manifest_round_trip_and_version_mismatch() {
+    let temp_dir = TempDir::new().unwrap();
+    let folder = Path::new("/media/library");
+    let path = ScanManifest::path_for(temp_dir.path(), folder);
+
+    let mut manifest = ScanManifest::new(folder);
+    manifest
+      .entries
+      .insert("/media/library/a.mp4".to_string(), entry(10, 1));
+    manifest.save(&path).await.unwrap();
+
+    let loaded = ScanManifest::load(&path, folder).await.unwrap();
+    assert_eq!(loaded.entries, manifest.entries);
+    // Манифест другой папки не используется
+    assert!(ScanManifest::load(&path, Path::new("/other"))
+      .await
+      .is_none());
+
+    // Другая версия формата приводит к полному пересканированию
+    let mut value: serde_json::Value =
+      serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+    value["version"] = serde_json::json!(SCAN_MANIFEST_VERSION + 1);
+    value["entries"] = serde_json::json!(["incompatible"]);
+    std::fs::write(&path, serde_json::to_vec(&value).unwrap()).unwrap();
+    assert!(ScanManifest::load(&path, folder).await.is_none());
+
+    std::fs::write(&path, b"{ not json").unwrap();
+    assert!(ScanManifest::load(&path, folder).await.is_none());
+  }
+
+  #[tokio::test]
+  async fn test_walk_respects_subdirectory_option() {
+    let temp_dir = TempDir::new().unwrap();
+    std::fs::write(temp_dir.path().join("top.mp4"), b"top").unwrap();
+    std::fs::write(temp_dir.path().join("notes.txt"), b"skip").unwrap();
+    std::fs::create_dir(temp_dir.path().join("nested")).unwrap();
+    std::fs::write(temp_dir.path().join("nested/inner.mp4"), b"inner").unwrap();
+
+    let all = walk_media_files(temp_dir.path(), &FolderScanOptions::default())
+      .await
+      .unwrap();
+    assert_eq!(all.len(), 2);
+    assert!(all.iter().all(|file| file.modified_ns > 0));
+
+    let top_only = walk_media_files(
+      temp_dir.path(),
+      &FolderScanOptions {
+        include_subdirectories: false,
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+    assert_eq!(top_only.len(), 1);
+    assert_eq!(top_only[0].file.name, "top.mp4");
+  }
+
+  #[cfg(unix)]
+  #[tokio::test]
+  async fn test_walk_guards_against_symlink_cycles() {
+    let temp_dir = TempDir::new().unwrap();
+    let nested = temp_dir.path().join("nested");
+    std::fs::create_dir(&nested).unwrap();
+    std::fs::write(nested.join("clip.mp4"), b"clip").unwrap();
+    // Ссылка на родительскую папку образует цикл
+    std::os::unix::fs::symlink(temp_dir.path(), nested.join("loop")).unwrap();
+    std::os::unix::fs::symlink(nested.join("clip.mp4"), temp_dir.path().join("link.mp4")).unwrap();
+
+    let ignored = walk_media_files(temp_dir.path(), &FolderScanOptions::default())
+      .await
+      .unwrap();
+    assert_eq!(ignored.len(), 1);
+
+    let followed = walk_media_files(
+      temp_dir.path(),
+      &FolderScanOptions {
+        follow_symlinks: true,
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+    // Файл по ссылке найден, цикл не обходится повторно
+    assert_eq!(followed.len(), 2);
+  }
+}
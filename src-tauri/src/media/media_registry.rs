@@ -0,0 +1,444 @@
+// Реестр импортированных медиафайлов с отпечатками содержимого
+//
+// Файлы хранятся по пути, но каждый несет отпечаток содержимого, что позволяет
+// находить дубликаты, импортированные из разных мест, и переносить данные
+// при перемещении файла.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use tokio::sync::RwLock;
+
+use super::fingerprint::{FingerprintMode, MediaFingerprint};
+use super::media_tags::{
+  normalize_tag, MediaTagSummary, MediaTagThresholds, MediaTags, TagFrequency, TagMatchMode,
+};
+
+/// Запись реестра
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MediaRegistryEntry {
+  /// Путь к файлу
+  pub path: String,
+  /// ID файла, под которым хранятся превью и результаты распознавания
+  pub file_id: Option<String>,
+  /// Отпечаток содержимого
+  pub fingerprint: MediaFingerprint,
+  /// Время регистрации
+  pub registered_at: chrono::DateTime<chrono::Utc>,
+  /// Файл не найден при последнем сканировании папки
+  #[serde(default)]
+  pub missing: bool,
+  /// Теги, выведенные из результатов распознавания
+  #[serde(default)]
+  pub tags: Option<MediaTags>,
+}
+
+/// Группа путей с одинаковым содержимым
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateGroup {
+  /// Общий отпечаток
+  pub fingerprint: MediaFingerprint,
+  /// Пути (отсортированы)
+  pub paths: Vec<String>,
+}
+
+/// Реестр медиафайлов
+#[derive(Debug, Default)]
+pub struct MediaRegistry {
+  /// Записи по пути
+  entries: HashMap<String, MediaRegistryEntry>,
+}
+
+impl MediaRegistry {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Зарегистрировать файл (перезаписывает существующую запись по пути).
+  ///
+  /// Теги сохраняются, если содержимое и ID файла не изменились.
+  pub fn register(
+    &mut self,
+    path: String,
+    file_id: Option<String>,
+    fingerprint: MediaFingerprint,
+  ) -> &MediaRegistryEntry {
+    let tags = self
+      .entries
+      .get(&path)
+      .filter(|known| known.fingerprint == fingerprint && known.file_id == file_id)
+      .and_then(|known| known.tags.clone());
+    let entry = MediaRegistryEntry {
+      path: path.clone(),
+      file_id,
+      fingerprint,
+      registered_at: chrono::Utc::now(),
+      missing: false,
+      tags,
+    };
+    self.entries.insert(path.clone(), entry);
+    &self.entries[&path]
+  }
+
+  /// Получить запись по пути
+  pub fn get(&self, path: &str) -> Option<&MediaRegistryEntry> {
+    self.entries.get(path)
+  }
+
+  /// Найти уже известный файл с тем же содержимым.
+  ///
+  /// При нескольких совпадениях возвращается первый путь по алфавиту, чтобы
+  /// результат был детерминированным.
+  pub fn find_by_fingerprint(&self, fingerprint: &MediaFingerprint) -> Option<&MediaRegistryEntry> {
+    self
+      .entries
+      .values()
+      .filter(|entry| entry.fingerprint == *fingerprint)
+      .min_by(|a, b| a.path.cmp(&b.path))
+  }
+
+  /// Найти группы путей с одинаковым содержимым
+  pub fn find_duplicates(&self) -> Vec<DuplicateGroup> {
+    let mut groups: HashMap<&MediaFingerprint, Vec<String>> = HashMap::new();
+    for entry in self.entries.values() {
+      groups
+        .entry(&entry.fingerprint)
+        .or_default()
+        .push(entry.path.clone());
+    }
+
+    let mut duplicates: Vec<DuplicateGroup> = groups
+      .into_iter()
+      .filter(|(_, paths)| paths.len() > 1)
+      .map(|(fingerprint, mut paths)| {
+        paths.sort();
+        DuplicateGroup {
+          fingerprint: fingerprint.clone(),
+          paths,
+        }
+      })
+      .collect();
+    duplicates.sort_by(|a, b| a.paths[0].cmp(&b.paths[0]));
+    duplicates
+  }
+
+  /// Перенести запись на новый путь. Возвращает обновленную запись.
+  pub fn relink(&mut self, old_path: &str, new_path: &str) -> Option<&MediaRegistryEntry> {
+    let mut entry = self.entries.remove(old_path)?;
+    entry.path = new_path.to_string();
+    self.entries.insert(new_path.to_string(), entry);
+    self.entries.get(new_path)
+  }
+
+  /// Пометить файл как отсутствующий. Возвращает false, если записи нет.
+  pub fn mark_missing(&mut self, path: &str) -> bool {
+    match self.entries.get_mut(path) {
+      Some(entry) => {
+        entry.missing = true;
+        true
+      }
+      None => false,
+    }
+  }
+
+  /// Удалить запись
+  pub fn remove(&mut self, path: &str) -> Option<MediaRegistryEntry> {
+    self.entries.remove(path)
+  }
+
+  /// Установить (или удалить при `None`) теги всех записей с этим ID файла.
+  /// Возвращает количество обновленных записей.
+  pub fn set_tags(&mut self, file_id: &str, tags: Option<MediaTags>) -> usize {
+    let mut updated = 0;
+    for entry in self.entries.values_mut() {
+      if entry.file_id.as_deref() == Some(file_id) {
+        entry.tags = tags.clone();
+        updated += 1;
+      }
+    }
+    updated
+  }
+
+  /// Найти файлы по тегам. Отсутствующие файлы и файлы без тегов не
+  /// возвращаются; результат отсортирован по пути.
+  pub fn search_by_tags(&self, tags: &[String], mode: TagMatchMode) -> Vec<&MediaRegistryEntry> {
+    let wanted: Vec<String> = tags.iter().map(|tag| normalize_tag(tag)).collect();
+    let mut found: Vec<&MediaRegistryEntry> = self
+      .entries
+      .values()
+      .filter(|entry| !entry.missing)
+      .filter(|entry| {
+        let Some(file_tags) = &entry.tags else {
+          return false;
+        };
+        match mode {
+          TagMatchMode::All => wanted.iter().all(|tag| file_tags.tags.contains(tag)),
+          TagMatchMode::Any => wanted.iter().any(|tag| file_tags.tags.contains(tag)),
+        }
+      })
+      .collect();
+    found.sort_by(|a, b| a.path.cmp(&b.path));
+    found
+  }
+
+  /// Частоты тегов среди файлов папки (рекурсивно) или всего реестра
+  pub fn tag_summary(&self, folder: Option<&str>) -> MediaTagSummary {
+    let mut tagged_files = 0;
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    let in_folder = |entry: &MediaRegistryEntry| {
+      folder.is_none_or(|folder| Path::new(&entry.path).starts_with(folder))
+    };
+    for entry in self
+      .entries
+      .values()
+      .filter(|entry| !entry.missing && in_folder(entry))
+    {
+      let Some(tags) = &entry.tags else {
+        continue;
+      };
+      tagged_files += 1;
+      for tag in &tags.tags {
+        *counts.entry(tag).or_default() += 1;
+      }
+    }
+
+    let mut tags: Vec<TagFrequency> = counts
+      .into_iter()
+      .map(|(tag, count)| TagFrequency {
+        tag: tag.to_string(),
+        count,
+      })
+      .collect();
+    // Сортировка устойчива: при равной частоте теги остаются по алфавиту
+    tags.sort_by(|a, b| b.count.cmp(&a.count));
+    MediaTagSummary { tagged_files, tags }
+  }
+
+  /// Количество записей
+  pub fn len(&self) -> usize {
+    self.entries.len()
+  }
+
+  /// Пуст ли реестр
+  pub fn is_empty(&self) -> bool {
+    self.entries.is_empty()
+  }
+}
+
+/// State для реестра медиафайлов
+#[derive(Debug, Default)]
+pub struct MediaRegistryState {
+  pub registry: RwLock<MediaRegistry>,
+  /// Режим вычисления отпечатков при сканировании
+  pub fingerprint_mode: RwLock<FingerprintMode>,
+  /// Пороги вывода тегов из результатов распознавания
+  pub tag_thresholds: RwLock<MediaTagThresholds>,
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn fingerprint(hash: &str) -> MediaFingerprint {
+    MediaFingerprint {
+      mode: FingerprintMode::Fast,
+      size: 100,
+      hash: hash.to_string(),
+    }
+  }
+
+  #[test]
+  fn test_find_duplicates() {
+    let mut registry = MediaRegistry::new();
+    registry.register("/b/clip.mp4".to_string(), None, fingerprint("aa"));
+    registry.register("/a/clip.mp4".to_string(), None, fingerprint("aa"));
+    registry.register("/c/other.mp4".to_string(), None, fingerprint("bb"));
+
+    let groups = registry.find_duplicates();
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].paths, vec!["/a/clip.mp4", "/b/clip.mp4"]);
+  }
+
+  #[test]
+  fn test_find_by_fingerprint() {
+    let mut registry = MediaRegistry::new();
+    registry.register(
+      "/a/clip.mp4".to_string(),
+      Some("id-1".to_string()),
+      fingerprint("aa"),
+    );
+
+    let known = registry.find_by_fingerprint(&fingerprint("aa")).unwrap();
+    assert_eq!(known.file_id.as_deref(), Some("id-1"));
+    assert!(registry.find_by_fingerprint(&fingerprint("cc")).is_none());
+  }
+
+  #[test]
+  fn test_relink() {
+    let mut registry = MediaRegistry::new();
+    registry.register(
+      "/old/clip.mp4".to_string(),
+      Some("id-1".to_string()),
+      fingerprint("aa"),
+    );
+
+    let entry = registry.relink("/old/clip.mp4", "/new/clip.mp4").unwrap();
+    assert_eq!(entry.path, "/new/clip.mp4");
+    assert_eq!(entry.file_id.as_deref(), Some("id-1"));
+    assert!(registry.get("/old/clip.mp4").is_none());
+    assert!(registry.relink("/missing.mp4", "/x.mp4").is_none());
+    assert_eq!(registry.len(), 1);
+  }
+
+  #[test]
+  fn test_mark_missing_and_reregister() {
+    let mut registry = MediaRegistry::new();
+    registry.register("/a/clip.mp4".to_string(), None, fingerprint("aa"));
+
+    assert!(registry.mark_missing("/a/clip.mp4"));
+    assert!(registry.get("/a/clip.mp4").unwrap().missing);
+    assert!(!registry.mark_missing("/a/other.mp4"));
+
+    // Повторная регистрация снимает отметку
+    registry.register("/a/clip.mp4".to_string(), None, fingerprint("bb"));
+    assert!(!registry.get("/a/clip.mp4").unwrap().missing);
+  }
+
+  fn tags(list: &[&str]) -> MediaTags {
+    MediaTags {
+      tags: list.iter().map(|tag| tag.to_string()).collect(),
+      person_count: 0,
+      person_bucket: crate::media::media_tags::PersonCountBucket::None,
+      has_faces: false,
+      source_processed_at: chrono::Utc::now(),
+    }
+  }
+
+  /// Реестр из трех файлов с тегами и одного без тегов
+  fn tagged_registry() -> MediaRegistry {
+    let mut registry = MediaRegistry::new();
+    let files = [
+      (
+        "/trip/beach.mp4",
+        "id-beach",
+        &["dog", "beach", "people:2+"][..],
+      ),
+      ("/trip/park.mp4", "id-park", &["dog", "people:1"][..]),
+      (
+        "/home/party.mp4",
+        "id-party",
+        &["people:2+", "people:5+", "faces"][..],
+      ),
+      ("/trip/untagged.mp4", "id-none", &[][..]),
+    ];
+    for (path, file_id, list) in files {
+      registry.register(
+        path.to_string(),
+        Some(file_id.to_string()),
+        fingerprint(file_id),
+      );
+      if !list.is_empty() {
+        assert_eq!(registry.set_tags(file_id, Some(tags(list))), 1);
+      }
+    }
+    registry
+  }
+
+  fn paths(entries: Vec<&MediaRegistryEntry>) -> Vec<&str> {
+    entries
+      .into_iter()
+      .map(|entry| entry.path.as_str())
+      .collect()
+  }
+
+  #[test]
+  fn test_search_by_tags() {
+    let registry = tagged_registry();
+    let query = |list: &[&str], mode| {
+      let list: Vec<String> = list.iter().map(|tag| tag.to_string()).collect();
+      paths(registry.search_by_tags(&list, mode))
+    };
+
+    assert_eq!(
+      query(&["dog", "people:2+"], TagMatchMode::All),
+      vec!["/trip/beach.mp4"]
+    );
+    assert_eq!(
+      query(&["Dog"], TagMatchMode::All),
+      vec!["/trip/beach.mp4", "/trip/park.mp4"]
+    );
+    assert_eq!(
+      query(&["beach", "faces"], TagMatchMode::Any),
+      vec!["/home/party.mp4", "/trip/beach.mp4"]
+    );
+    assert!(query(&["cat"], TagMatchMode::Any).is_empty());
+    // Пустой запрос в режиме All - все файлы с тегами
+    assert_eq!(query(&[], TagMatchMode::All).len(), 3);
+    assert!(query(&[], TagMatchMode::Any).is_empty());
+  }
+
+  #[test]
+  fn test_tags_cleared_and_missing_files_skipped() {
+    let mut registry = tagged_registry();
+    let dog = vec!["dog".to_string()];
+
+    assert_eq!(registry.set_tags("id-park", None), 1);
+    assert_eq!(
+      paths(registry.search_by_tags(&dog, TagMatchMode::All)),
+      vec!["/trip/beach.mp4"]
+    );
+
+    registry.mark_missing("/trip/beach.mp4");
+    assert!(registry.search_by_tags(&dog, TagMatchMode::All).is_empty());
+    assert_eq!(registry.set_tags("unknown", None), 0);
+  }
+
+  #[test]
+  fn test_tags_survive_reregistration_of_same_content() {
+    let mut registry = tagged_registry();
+    let id = Some("id-beach".to_string());
+
+    registry.register(
+      "/trip/beach.mp4".to_string(),
+      id.clone(),
+      fingerprint("id-beach"),
+    );
+    assert!(registry.get("/trip/beach.mp4").unwrap().tags.is_some());
+
+    registry.register("/trip/beach.mp4".to_string(), id, fingerprint("changed"));
+    assert!(registry.get("/trip/beach.mp4").unwrap().tags.is_none());
+  }
+
+  #[test]
+  fn test_tag_summary() {
+    let registry = tagged_registry();
+
+    let summary = registry.tag_summary(Some("/trip"));
+    assert_eq!(summary.tagged_files, 2);
+    let frequencies: Vec<_> = summary
+      .tags
+      .iter()
+      .map(|frequency| (frequency.tag.as_str(), frequency.count))
+      .collect();
+    assert_eq!(
+      frequencies,
+      vec![("dog", 2), ("beach", 1), ("people:1", 1), ("people:2+", 1)]
+    );
+
+    let summary = registry.tag_summary(None);
+    assert_eq!(summary.tagged_files, 3);
+    assert_eq!(
+      summary.tags[..2],
+      [
+        TagFrequency {
+          tag: "dog".to_string(),
+          count: 2
+        },
+        TagFrequency {
+          tag: "people:2+".to_string(),
+          count: 2
+        },
+      ]
+    );
+  }
+}
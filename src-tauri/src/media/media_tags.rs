@@ -0,0 +1,366 @@
+// Автоматические теги медиафайлов по результатам распознавания
+//
+// Сохраненные результаты распознавания сворачиваются в компактный набор тегов:
+// частые классы объектов, типы сцен, число людей в кадре корзинами и наличие
+// лиц. Теги хранятся в реестре медиафайлов рядом с записью файла, поэтому
+// фильтр медиабраузера ("собака, пляж, 2+ человека") не требует повторного
+// распознавания. Теги пересчитываются при сохранении новых результатов и
+// удаляются вместе с ними.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use tauri::{AppHandle, Manager, Runtime};
+
+use super::media_registry::MediaRegistryState;
+use crate::recognition::types::RecognitionResults;
+
+/// Класс объекта, по которому считаются люди в кадре
+pub const PERSON_CLASS: &str = "person";
+
+/// Тег файла, в котором обнаружены лица
+pub const FACES_TAG: &str = "faces";
+
+/// Пороги вывода тегов
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MediaTagThresholds {
+  /// Минимальная уверенность детекции (0.0 - 1.0)
+  pub min_confidence: f32,
+  /// Сколько раз класс должен встретиться, чтобы стать тегом
+  pub min_occurrences: usize,
+  /// Максимальное количество тегов классов (самые частые)
+  pub max_classes: usize,
+  /// В скольких кадрах должно быть не меньше N человек, чтобы засчитать N
+  pub min_person_frames: usize,
+}
+
+impl Default for MediaTagThresholds {
+  fn default() -> Self {
+    Self {
+      min_confidence: 0.5,
+      min_occurrences: 3,
+      max_classes: 10,
+      min_person_frames: 2,
+    }
+  }
+}
+
+impl MediaTagThresholds {
+  /// Проверить корректность порогов
+  pub fn validate(&self) -> Result<(), String> {
+    if !(0.0..=1.0).contains(&self.min_confidence) {
+      return Err(format!(
+        "min_confidence должен быть в диапазоне 0..1, получено {}",
+        self.min_confidence
+      ));
+    }
+    if self.min_occurrences == 0 || self.min_person_frames == 0 {
+      return Err("min_occurrences и min_person_frames должны быть больше 0".to_string());
+    }
+    Ok(())
+  }
+}
+
+/// Корзина количества людей в кадре
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PersonCountBucket {
+  None,
+  One,
+  /// 2-4 человека
+  Few,
+  /// 5 и больше
+  Crowd,
+}
+
+impl PersonCountBucket {
+  pub fn from_count(count: usize) -> Self {
+    match count {
+      0 => Self::None,
+      1 => Self::One,
+      2..=4 => Self::Few,
+      _ => Self::Crowd,
+    }
+  }
+
+  /// Теги корзины. Теги "N+" накопительные: в толпе есть и "people:2+",
+  /// чтобы фильтр "2+ человека" находил и ее.
+  pub fn tags(self) -> &'static [&'static str] {
+    match self {
+      Self::None => &[],
+      Self::One => &["people:1"],
+      Self::Few => &["people:2+"],
+      Self::Crowd => &["people:2+", "people:5+"],
+    }
+  }
+}
+
+/// Теги медиафайла
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MediaTags {
+  /// Все теги файла (в нижнем регистре)
+  pub tags: BTreeSet<String>,
+  /// Число людей, одновременно видимых в кадре
+  pub person_count: usize,
+  pub person_bucket: PersonCountBucket,
+  pub has_faces: bool,
+  /// Время распознавания, по результатам которого получены теги
+  pub source_processed_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl MediaTags {
+  /// Есть ли у файла тег (без учета регистра)
+  pub fn contains(&self, tag: &str) -> bool {
+    self.tags.contains(&normalize_tag(tag))
+  }
+}
+
+/// Режим сопоставления тегов при поиске
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TagMatchMode {
+  /// Файл содержит все запрошенные теги
+  #[default]
+  All,
+  /// Файл содержит хотя бы один запрошенный тег
+  Any,
+}
+
+/// Частота тега среди файлов
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TagFrequency {
+  pub tag: String,
+  /// Количество файлов с тегом
+  pub count: usize,
+}
+
+/// Сводка тегов папки для построения фильтров
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MediaTagSummary {
+  /// Количество файлов с тегами
+  pub tagged_files: usize,
+  /// Теги по убыванию частоты
+  pub tags: Vec<TagFrequency>,
+}
+
+/// Привести тег к виду хранения
+pub fn normalize_tag(tag: &str) -> String {
+  tag.trim().to_lowercase()
+}
+
+/// Вывести теги из результатов распознавания
+pub fn derive_tags(results: &RecognitionResults, thresholds: &MediaTagThresholds) -> MediaTags {
+  // Появления классов и число людей по кадрам
+  let mut occurrences: HashMap<String, usize> = HashMap::new();
+  let mut persons_per_frame: HashMap<i64, usize> = HashMap::new();
+  for object in &results.objects {
+    if object.confidence < thresholds.min_confidence {
+      continue;
+    }
+    let class = normalize_tag(&object.class);
+    *occurrences.entry(class.clone()).or_default() += object.timestamps.len();
+    if class == PERSON_CLASS {
+      for timestamp in &object.timestamps {
+        *persons_per_frame.entry(frame_key(*timestamp)).or_default() += 1;
+      }
+    }
+  }
+
+  let mut classes: Vec<(String, usize)> = occurrences
+    .into_iter()
+    .filter(|(_, count)| *count >= thresholds.min_occurrences)
+    .collect();
+  classes.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+  let mut tags: BTreeSet<String> = classes
+    .into_iter()
+    .take(thresholds.max_classes)
+    .map(|(class, _)| class)
+    .collect();
+
+  tags.extend(
+    results
+      .scenes
+      .iter()
+      .map(|scene| normalize_tag(&scene.scene_type))
+      .filter(|scene| !scene.is_empty()),
+  );
+
+  // Количество людей, которое держится хотя бы min_person_frames кадров
+  let mut frame_counts: Vec<usize> = persons_per_frame.into_values().collect();
+  frame_counts.sort_unstable_by(|a, b| b.cmp(a));
+  let person_count = frame_counts
+    .get(thresholds.min_person_frames.saturating_sub(1))
+    .copied()
+    .unwrap_or(0);
+  let person_bucket = PersonCountBucket::from_count(person_count);
+  tags.extend(person_bucket.tags().iter().map(|tag| tag.to_string()));
+
+  let has_faces = results
+    .faces
+    .iter()
+    .any(|face| face.confidence >= thresholds.min_confidence && !face.timestamps.is_empty());
+  if has_faces {
+    tags.insert(FACES_TAG.to_string());
+  }
+
+  MediaTags {
+    tags,
+    person_count,
+    person_bucket,
+    has_faces,
+    source_processed_at: results.processed_at,
+  }
+}
+
+/// Ключ кадра: метка времени с точностью до миллисекунды
+fn frame_key(timestamp: f64) -> i64 {
+  (timestamp * 1000.0).round() as i64
+}
+
+/// Обновить теги файла в реестре после изменения результатов распознавания.
+///
+/// `None` удаляет теги (результаты очищены). Возвращает количество обновленных
+/// записей реестра.
+pub async fn refresh_media_tags<R: Runtime>(
+  app: &AppHandle<R>,
+  file_id: &str,
+  results: Option<&RecognitionResults>,
+) -> usize {
+  let Some(state) = app.try_state::<MediaRegistryState>() else {
+    return 0;
+  };
+  let tags = match results {
+    Some(results) => Some(derive_tags(results, &*state.tag_thresholds.read().await)),
+    None => None,
+  };
+  state.registry.write().await.set_tags(file_id, tags)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::recognition::types::{DetectedFace, DetectedObject, DetectedScene};
+
+  fn object(class: &str, confidence: f32, timestamps: &[f64]) -> DetectedObject {
+    DetectedObject {
+      class: class.to_string(),
+      confidence,
+      timestamps: timestamps.to_vec(),
+      bounding_boxes: Vec::new(),
+    }
+  }
+
+  fn results(objects: Vec<DetectedObject>) -> RecognitionResults {
+    RecognitionResults {
+      objects,
+      ..Default::default()
+    }
+  }
+
+  /// `count` людей на каждом из кадров `frames`
+  fn people(count: usize, frames: &[f64]) -> Vec<DetectedObject> {
+    (0..count).map(|_| object("person", 0.9, frames)).collect()
+  }
+
+  #[test]
+  fn test_person_count_buckets() {
+    let thresholds = MediaTagThresholds::default();
+    let cases = [
+      (0, PersonCountBucket::None, vec![]),
+      (1, PersonCountBucket::One, vec!["people:1"]),
+      (2, PersonCountBucket::Few, vec!["people:2+"]),
+      (4, PersonCountBucket::Few, vec!["people:2+"]),
+      (5, PersonCountBucket::Crowd, vec!["people:2+", "people:5+"]),
+      (12, PersonCountBucket::Crowd, vec!["people:2+", "people:5+"]),
+    ];
+
+    for (count, bucket, expected) in cases {
+      let tags = derive_tags(&results(people(count, &[1.0, 2.0, 3.0])), &thresholds);
+      assert_eq!(tags.person_count, count);
+      assert_eq!(tags.person_bucket, bucket);
+      let people_tags: Vec<_> = tags
+        .tags
+        .iter()
+        .filter(|tag| tag.starts_with("people:"))
+        .map(String::as_str)
+        .collect();
+      assert_eq!(people_tags, expected, "count {count}");
+    }
+  }
+
+  #[test]
+  fn test_person_count_ignores_single_frame_spikes() {
+    // 6 человек только в одном кадре, 3 человека в двух
+    let mut objects = people(3, &[1.0, 2.0]);
+    objects.extend(people(3, &[2.0]));
+    let tags = derive_tags(&results(objects), &MediaTagThresholds::default());
+
+    assert_eq!(tags.person_count, 3);
+    assert_eq!(tags.person_bucket, PersonCountBucket::Few);
+  }
+
+  #[test]
+  fn test_class_thresholds() {
+    let thresholds = MediaTagThresholds {
+      max_classes: 2,
+      ..Default::default()
+    };
+    let objects = vec![
+      object("Dog", 0.9, &[1.0, 2.0, 3.0, 4.0]),
+      object("car", 0.8, &[1.0, 2.0, 3.0]),
+      // Частый, но неуверенный класс
+      object("cat", 0.2, &[1.0, 2.0, 3.0, 4.0, 5.0]),
+      // Уверенный, но редкий класс
+      object("bird", 0.9, &[1.0]),
+      // Третий по частоте проходит пороги, но не помещается в max_classes
+      object("bicycle", 0.9, &[1.0, 2.0, 3.0]),
+    ];
+    let tags = derive_tags(&results(objects), &thresholds);
+
+    assert_eq!(
+      tags.tags.iter().map(String::as_str).collect::<Vec<_>>(),
+      vec!["bicycle", "dog"]
+    );
+    assert!(tags.contains("DOG"));
+  }
+
+  #[test]
+  fn test_scene_and_face_tags() {
+    let mut results = results(vec![object("dog", 0.9, &[1.0, 2.0, 3.0])]);
+    results.scenes.push(DetectedScene {
+      scene_type: "Beach".to_string(),
+      start_time: 0.0,
+      end_time: 5.0,
+      key_objects: Vec::new(),
+    });
+    results.faces.push(DetectedFace {
+      face_id: None,
+      person_name: None,
+      confidence: 0.95,
+      timestamps: vec![1.0],
+      bounding_boxes: Vec::new(),
+    });
+    let tags = derive_tags(&results, &MediaTagThresholds::default());
+
+    assert!(tags.has_faces);
+    assert_eq!(
+      tags.tags.iter().map(String::as_str).collect::<Vec<_>>(),
+      vec!["beach", "dog", "faces"]
+    );
+  }
+
+  #[test]
+  fn test_thresholds_validation() {
+    assert!(MediaTagThresholds::default().validate().is_ok());
+    let invalid = MediaTagThresholds {
+      min_confidence: 1.5,
+      ..Default::default()
+    };
+    assert!(invalid.validate().is_err());
+    let invalid = MediaTagThresholds {
+      min_person_frames: 0,
+      ..Default::default()
+    };
+    assert!(invalid.validate().is_err());
+  }
+}
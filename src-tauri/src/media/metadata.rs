@@ -1,10 +1,12 @@
 // Модуль для получения метаданных медиафайлов
 
 use super::ffmpeg::check_ffmpeg;
+use super::photo_decoder::{probe_photo, PhotoFormat};
 use super::types::{
   AudioMetadata, FfprobeFormat, FfprobeStream, ImageMetadata, MediaFile, MediaMetadata, ProbeData,
   VideoMetadata,
 };
+use crate::video_compiler::services::ffmpeg_service::parse_stream_info;
 use std::path::Path;
 use std::process::Command;
 use std::str;
@@ -12,6 +14,14 @@ use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Получение метаданных медиафайла с помощью FFmpeg
 pub fn get_media_metadata(file_path: String) -> Result<MediaFile, String> {
+  // HEIF и RAW разбираются без ffprobe
+  if let Some(format) = PhotoFormat::from_path(Path::new(&file_path)) {
+    if !Path::new(&file_path).exists() {
+      return Err(format!("Файл не найден: {file_path}"));
+    }
+    return Ok(photo_media_file(file_path, format));
+  }
+
   // Проверяем наличие FFmpeg
   check_ffmpeg()?;
 
@@ -59,12 +69,7 @@ pub fn get_media_metadata(file_path: String) -> Result<MediaFile, String> {
 
   // Создаем структуры для потоков и формата
   let mut ffprobe_streams: Vec<FfprobeStream> = Vec::new();
-  let mut ffprobe_format = FfprobeFormat {
-    duration: None,
-    size: None,
-    bit_rate: None,
-    format_name: None,
-  };
+  let mut ffprobe_format = FfprobeFormat::default();
 
   if let (Some(streams_array), Some(format_obj)) = (streams, format) {
     // Заполняем информацию о формате
@@ -123,11 +128,65 @@ pub fn get_media_metadata(file_path: String) -> Result<MediaFile, String> {
       streams: ffprobe_streams,
       format: ffprobe_format,
     },
+    unsupported_reason: None,
   };
 
   Ok(media_file)
 }
 
+/// MediaFile для HEIF/RAW фото.
+///
+/// Размеры указываются с учетом ориентации. Файл без доступного декодера
+/// остается в списке с причиной в `unsupported_reason`.
+fn photo_media_file(file_path: String, format: PhotoFormat) -> MediaFile {
+  let path = Path::new(&file_path);
+  let size = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+  let name = path
+    .file_name()
+    .and_then(|name| name.to_str())
+    .unwrap_or("unknown")
+    .to_string();
+
+  let (streams, unsupported_reason) = match probe_photo(path) {
+    Ok(info) => (
+      vec![FfprobeStream {
+        codec_type: "video".to_string(),
+        codec_name: Some(info.codec),
+        width: Some(info.width),
+        height: Some(info.height),
+        ..Default::default()
+      }],
+      info.unsupported_reason,
+    ),
+    Err(reason) => (Vec::new(), Some(reason)),
+  };
+
+  MediaFile {
+    id: file_path.clone(),
+    name,
+    path: file_path,
+    is_video: false,
+    is_audio: false,
+    is_image: true,
+    size,
+    duration: None,
+    start_time: SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .unwrap_or_default()
+      .as_secs(),
+    creation_time: generate_iso8601_timestamp(),
+    probe_data: ProbeData {
+      streams,
+      format: FfprobeFormat {
+        size: Some(size),
+        format_name: Some(format.format_name().to_string()),
+        ..Default::default()
+      },
+    },
+    unsupported_reason,
+  }
+}
+
 /// Парсит данные формата из JSON
 fn parse_format_data(
   format_obj: &serde_json::Map<String, serde_json::Value>,
@@ -152,6 +211,17 @@ fn parse_format_data(
     .get("format_name")
     .and_then(|v| v.as_str())
     .map(String::from);
+
+  ffprobe_format.tags = format_obj
+    .get("tags")
+    .and_then(|v| v.as_object())
+    .map(|tags| {
+      tags
+        .iter()
+        .filter_map(|(key, value)| value.as_str().map(|v| (key.clone(), v.to_string())))
+        .collect()
+    })
+    .unwrap_or_default();
 }
 
 /// Парсит данные потока из JSON
@@ -208,6 +278,16 @@ fn parse_stream_data(stream: &serde_json::Value, index: usize) -> FfprobeStream
     .and_then(|v| v.as_str())
     .map(String::from);
 
+  // Язык, раскладка, битовая глубина и поворот разбираются так же, как в FileInfo
+  let info = parse_stream_info(stream);
+  let color_field = |key: &str| {
+    stream
+      .get(key)
+      .and_then(|v| v.as_str())
+      .filter(|v| *v != "unknown")
+      .map(String::from)
+  };
+
   FfprobeStream {
     index: stream_index,
     codec_type,
@@ -219,6 +299,20 @@ fn parse_stream_data(stream: &serde_json::Value, index: usize) -> FfprobeStream
     sample_rate,
     channels,
     display_aspect_ratio,
+    language: info.language,
+    channel_layout: info.channel_layout,
+    bit_depth: info.bit_depth,
+    pix_fmt: info.pixel_format,
+    rotation: info.rotation,
+    color_range: color_field("color_range"),
+    color_space: color_field("color_space"),
+    color_transfer: color_field("color_transfer"),
+    color_primaries: color_field("color_primaries"),
+    timecode: stream
+      .get("tags")
+      .and_then(|tags| tags.get("timecode"))
+      .and_then(|v| v.as_str())
+      .map(String::from),
   }
 }
 
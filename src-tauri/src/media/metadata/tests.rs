@@ -13,6 +13,7 @@ fn test_parse_format_data() {
     size: None,
     bit_rate: None,
     format_name: None,
+    ..Default::default()
   };
 
   parse_format_data(&format_data, &mut ffprobe_format);
@@ -34,6 +35,7 @@ fn test_parse_format_data_with_invalid_values() {
     size: None,
     bit_rate: None,
     format_name: None,
+    ..Default::default()
   };
 
   parse_format_data(&format_data, &mut ffprobe_format);
@@ -91,6 +93,36 @@ fn test_parse_stream_data_audio() {
   assert_eq!(parsed_stream.height, None);
 }
 
+#[test]
+fn test_parse_stream_data_rotation_and_audio_details() {
+  let video_json = serde_json::json!({
+      "index": 0,
+      "codec_type": "video",
+      "codec_name": "hevc",
+      "pix_fmt": "yuv420p10le",
+      "color_transfer": "arib-std-b67",
+      "side_data_list": [{ "side_data_type": "Display Matrix", "rotation": -90 }]
+  });
+  let video = parse_stream_data(&video_json, 0);
+  assert_eq!(video.rotation, 90);
+  assert_eq!(video.pix_fmt, Some("yuv420p10le".to_string()));
+  assert_eq!(video.color_transfer, Some("arib-std-b67".to_string()));
+
+  let audio_json = serde_json::json!({
+      "index": 2,
+      "codec_type": "audio",
+      "codec_name": "pcm_s24le",
+      "channel_layout": "5.1",
+      "bits_per_sample": 24,
+      "tags": { "language": "rus" }
+  });
+  let audio = parse_stream_data(&audio_json, 2);
+  assert_eq!(audio.language, Some("rus".to_string()));
+  assert_eq!(audio.channel_layout, Some("5.1".to_string()));
+  assert_eq!(audio.bit_depth, Some(24));
+  assert_eq!(audio.rotation, 0);
+}
+
 #[test]
 fn test_parse_stream_data_missing_fields() {
   let stream_json = serde_json::json!({});
@@ -169,6 +201,7 @@ fn test_ffprobe_format_struct() {
     size: Some(1024),
     bit_rate: Some("128000".to_string()),
     format_name: Some("mp4".to_string()),
+    ..Default::default()
   };
 
   // Test serialization
@@ -199,6 +232,7 @@ fn test_ffprobe_stream_struct() {
     sample_rate: None,
     channels: None,
     display_aspect_ratio: Some("16:9".to_string()),
+    ..Default::default()
   };
 
   // Test serialization
@@ -228,12 +262,14 @@ fn test_probe_data_struct() {
       sample_rate: None,
       channels: None,
       display_aspect_ratio: None,
+      ..Default::default()
     }],
     format: FfprobeFormat {
       duration: Some(60.0),
       size: Some(1048576),
       bit_rate: None,
       format_name: Some("mp4".to_string()),
+      ..Default::default()
     },
   };
 
@@ -266,8 +302,10 @@ fn test_media_file_struct() {
         size: Some(1048576),
         bit_rate: None,
         format_name: None,
+        ..Default::default()
       },
     },
+    unsupported_reason: None,
   };
 
   let json = serde_json::to_string(&media_file).unwrap();
@@ -390,6 +428,7 @@ fn test_parse_format_data_empty() {
     size: None,
     bit_rate: None,
     format_name: None,
+    ..Default::default()
   };
 
   parse_format_data(&format_data, &mut ffprobe_format);
@@ -412,3 +451,41 @@ fn test_parse_stream_data_with_index_override() {
   // Should use the index from JSON, not the parameter
   assert_eq!(parsed_stream.index, 10);
 }
+
+#[test]
+fn test_parse_stream_data_timecode_tag() {
+  let stream_json = serde_json::json!({
+      "index": 2,
+      "codec_type": "data",
+      "codec_tag_string": "tmcd",
+      "tags": { "timecode": "01:00:00;00" }
+  });
+
+  let parsed_stream = parse_stream_data(&stream_json, 2);
+  assert_eq!(parsed_stream.timecode.as_deref(), Some("01:00:00;00"));
+}
+
+#[test]
+fn test_get_media_metadata_raw_without_decoder_is_listed() {
+  use crate::media::types::UnsupportedReason;
+
+  let dir = tempfile::TempDir::new().unwrap();
+  let path = dir.path().join("IMG_0001.CR3");
+  std::fs::write(&path, b"\0\0\0\x10ftypcrx isom").unwrap();
+
+  let media_file = get_media_metadata(path.to_string_lossy().to_string()).unwrap();
+  assert!(media_file.is_image);
+  assert!(!media_file.is_video);
+  assert_eq!(
+    media_file.unsupported_reason,
+    Some(UnsupportedReason::RawPreviewMissing)
+  );
+  assert_eq!(
+    media_file.probe_data.format.format_name.as_deref(),
+    Some("raw")
+  );
+
+  // Причина попадает в JSON для фронтенда
+  let json = serde_json::to_value(&media_file).unwrap();
+  assert_eq!(json["unsupported_reason"], "raw_preview_missing");
+}
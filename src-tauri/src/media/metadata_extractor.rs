@@ -112,6 +112,12 @@ mod tests {
     assert!(MetadataExtractor::is_supported(&PathBuf::from("video.mp4")));
     assert!(MetadataExtractor::is_supported(&PathBuf::from("image.jpg")));
     assert!(MetadataExtractor::is_supported(&PathBuf::from("audio.mp3")));
+    assert!(MetadataExtractor::is_supported(&PathBuf::from(
+      "IMG_0001.HEIC"
+    )));
+    assert!(MetadataExtractor::is_supported(&PathBuf::from(
+      "DSC0001.arw"
+    )));
     assert!(!MetadataExtractor::is_supported(&PathBuf::from(
       "document.pdf"
     )));
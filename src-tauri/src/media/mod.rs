@@ -2,14 +2,25 @@
 // Экспортируем публичные типы и функции
 
 pub mod commands;
+pub mod conformance;
 pub mod ffmpeg;
 pub mod files;
+pub mod frame_index;
+pub mod fingerprint;
+pub mod hover_preview;
+pub mod incremental_scan;
+pub mod media_registry;
+pub mod media_tags;
 pub mod metadata;
+pub mod photo_decoder;
 pub mod preview_data;
 pub mod preview_manager;
 pub mod processor;
 pub mod registry;
+pub mod scan_jobs;
+pub mod sprite;
 pub mod thumbnail;
+pub mod thumbnail_encoding;
 pub mod types;
 
 // Новые модули после рефакторинга
@@ -0,0 +1,791 @@
+//! Photo Decoder - Декодирование HEIC/HEIF и RAW фотографий
+//!
+//! HEIF декодируется через FFmpeg, если его сборка собирает изображение из
+//! плиток (7.1+), иначе через libheif (feature `heif`). Из RAW (CR3, ARW, DNG
+//! и др.) извлекается самое крупное встроенное JPEG-превью. Ориентация (`irot`
+//! для HEIF, EXIF для RAW и JPEG) применяется к результату, размеры
+//! возвращаются с ее учетом.
+
+use crate::media::types::UnsupportedReason;
+use image::codecs::jpeg::JpegEncoder;
+use image::{DynamicImage, GenericImageView, ImageFormat};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+
+/// Расширения HEIF-контейнеров
+pub const HEIF_EXTENSIONS: &[&str] = &["heic", "heif", "hif"];
+
+/// Расширения RAW-файлов камер
+pub const RAW_EXTENSIONS: &[&str] = &["cr3", "cr2", "arw", "dng", "nef", "raf", "orf", "rw2"];
+
+/// Качество JPEG при конвертации для timeline
+const TIMELINE_JPEG_QUALITY: u8 = 95;
+
+/// Форматы фото, требующие отдельного декодера
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PhotoFormat {
+  Heif,
+  Raw,
+}
+
+impl PhotoFormat {
+  /// Определить формат по расширению файла
+  pub fn from_path(path: &Path) -> Option<Self> {
+    let ext = path.extension()?.to_str()?.to_lowercase();
+    if HEIF_EXTENSIONS.contains(&ext.as_str()) {
+      Some(Self::Heif)
+    } else if RAW_EXTENSIONS.contains(&ext.as_str()) {
+      Some(Self::Raw)
+    } else {
+      None
+    }
+  }
+
+  /// Имя формата для `format_name`
+  pub fn format_name(&self) -> &'static str {
+    match self {
+      Self::Heif => "heif",
+      Self::Raw => "raw",
+    }
+  }
+}
+
+/// Сведения о фото без полного декодирования
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhotoInfo {
+  /// Кодек изображения (`hevc`, `av1`, `mjpeg` для превью RAW)
+  pub codec: String,
+  /// Ширина с учетом ориентации
+  pub width: u32,
+  /// Высота с учетом ориентации
+  pub height: u32,
+  /// Причина, по которой изображение нельзя декодировать на этой машине
+  pub unsupported_reason: Option<UnsupportedReason>,
+}
+
+/// Размеры и ориентация HEIF-изображения из коробки `meta`
+#[derive(Debug, Clone, PartialEq)]
+struct HeifLayout {
+  codec: String,
+  /// Размеры до поворота (`ispe`)
+  width: u32,
+  height: u32,
+  /// Ориентация в терминах EXIF (из `irot`)
+  orientation: u16,
+}
+
+/// Прочитать размеры и ориентацию HEIF/RAW фото.
+///
+/// Ошибка означает, что файл не удалось разобрать; при известных размерах, но
+/// недоступном декодере причина возвращается в `unsupported_reason`.
+pub fn probe_photo(path: &Path) -> Result<PhotoInfo, UnsupportedReason> {
+  let format = PhotoFormat::from_path(path).ok_or(UnsupportedReason::UnreadableImage)?;
+  let data = std::fs::read(path).map_err(|_| UnsupportedReason::UnreadableImage)?;
+
+  match format {
+    PhotoFormat::Heif => {
+      let layout = heif_layout(&data).ok_or(UnsupportedReason::UnreadableImage)?;
+      let (width, height) = oriented_dimensions(layout.width, layout.height, layout.orientation);
+      Ok(PhotoInfo {
+        codec: layout.codec,
+        width,
+        height,
+        unsupported_reason: (!heif_decoder_available())
+          .then_some(UnsupportedReason::HeifDecoderUnavailable),
+      })
+    }
+    PhotoFormat::Raw => {
+      let (offset, width, height) =
+        embedded_jpeg(&data).ok_or(UnsupportedReason::RawPreviewMissing)?;
+      let (width, height) = oriented_dimensions(width, height, raw_orientation(&data, offset));
+      Ok(PhotoInfo {
+        codec: "mjpeg".to_string(),
+        width,
+        height,
+        unsupported_reason: None,
+      })
+    }
+  }
+}
+
+/// Открыть изображение любого поддерживаемого формата с примененной ориентацией
+pub fn open_image(path: &Path) -> Result<DynamicImage, UnsupportedReason> {
+  let data = std::fs::read(path).map_err(|_| UnsupportedReason::UnreadableImage)?;
+
+  match PhotoFormat::from_path(path) {
+    Some(PhotoFormat::Heif) => {
+      let layout = heif_layout(&data).ok_or(UnsupportedReason::UnreadableImage)?;
+      decode_heif(path, &layout)
+    }
+    Some(PhotoFormat::Raw) => {
+      let (offset, _, _) = embedded_jpeg(&data).ok_or(UnsupportedReason::RawPreviewMissing)?;
+      let image = image::load_from_memory_with_format(&data[offset..], ImageFormat::Jpeg)
+        .map_err(|_| UnsupportedReason::UnreadableImage)?;
+      Ok(apply_orientation(image, raw_orientation(&data, offset)))
+    }
+    None => {
+      let image = image::load_from_memory(&data).map_err(|_| UnsupportedReason::UnreadableImage)?;
+      Ok(apply_orientation(
+        image,
+        read_exif_orientation(&data).unwrap_or(1),
+      ))
+    }
+  }
+}
+
+/// Полноразмерная копия фото для timeline: PNG при наличии альфа-канала,
+/// иначе JPEG. Ориентация уже применена.
+///
+/// Результат `<имя>_<хэш пути>.<ext>` в `output_dir` переиспользуется, пока
+/// он не старше исходного файла.
+pub fn convert_image_for_timeline(path: &Path, output_dir: &Path) -> Result<PathBuf, String> {
+  let source_modified = std::fs::metadata(path)
+    .and_then(|meta| meta.modified())
+    .map_err(|e| format!("Файл не найден: {}: {e}", path.display()))?;
+
+  let stem = path
+    .file_stem()
+    .map(|stem| stem.to_string_lossy().into_owned())
+    .unwrap_or_else(|| "image".to_string());
+  let hash = format!("{:x}", md5::compute(path.to_string_lossy().as_bytes()));
+  let base = output_dir.join(format!("{stem}_{}", &hash[..8]));
+
+  for ext in ["jpg", "png"] {
+    let candidate = base.with_extension(ext);
+    let fresh = std::fs::metadata(&candidate)
+      .and_then(|meta| meta.modified())
+      .is_ok_and(|modified| modified >= source_modified);
+    if fresh {
+      return Ok(candidate);
+    }
+  }
+
+  let image = open_image(path).map_err(|reason| reason.message().to_string())?;
+  std::fs::create_dir_all(output_dir).map_err(|e| {
+    format!(
+      "Не удалось создать директорию {}: {e}",
+      output_dir.display()
+    )
+  })?;
+
+  if image.color().has_alpha() {
+    let output = base.with_extension("png");
+    image
+      .save_with_format(&output, ImageFormat::Png)
+      .map_err(|e| format!("Не удалось сохранить PNG: {e}"))?;
+    Ok(output)
+  } else {
+    let output = base.with_extension("jpg");
+    let file = std::fs::File::create(&output)
+      .map_err(|e| format!("Не удалось создать {}: {e}", output.display()))?;
+    JpegEncoder::new_with_quality(std::io::BufWriter::new(file), TIMELINE_JPEG_QUALITY)
+      .encode_image(&image.to_rgb8())
+      .map_err(|e| format!("Не удалось сохранить JPEG: {e}"))?;
+    Ok(output)
+  }
+}
+
+/// Применить EXIF-ориентацию (1-8); неизвестные значения игнорируются
+pub fn apply_orientation(image: DynamicImage, orientation: u16) -> DynamicImage {
+  match orientation {
+    2 => image.fliph(),
+    3 => image.rotate180(),
+    4 => image.flipv(),
+    5 => image.rotate90().fliph(),
+    6 => image.rotate90(),
+    7 => image.rotate270().fliph(),
+    8 => image.rotate270(),
+    _ => image,
+  }
+}
+
+/// Размеры после применения ориентации
+pub fn oriented_dimensions(width: u32, height: u32, orientation: u16) -> (u32, u32) {
+  if (5..=8).contains(&orientation) {
+    (height, width)
+  } else {
+    (width, height)
+  }
+}
+
+/// EXIF-ориентация из JPEG (сегмент APP1) или TIFF-данных
+pub fn read_exif_orientation(data: &[u8]) -> Option<u16> {
+  if !data.starts_with(&[0xFF, 0xD8]) {
+    return tiff_orientation(data);
+  }
+
+  let mut pos = 2;
+  while pos + 4 <= data.len() && data[pos] == 0xFF {
+    let marker = data[pos + 1];
+    // Дальше идут данные скана, метаданных там нет
+    if marker == 0xDA || marker == 0xD9 {
+      break;
+    }
+    let length = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+    let segment = data.get(pos + 4..pos + 2 + length)?;
+    if marker == 0xE1 && segment.starts_with(b"Exif\0\0") {
+      return tiff_orientation(&segment[6..]);
+    }
+    pos += 2 + length;
+  }
+  None
+}
+
+/// Тег Orientation (0x0112) из IFD0 блока TIFF
+fn tiff_orientation(tiff: &[u8]) -> Option<u16> {
+  let little_endian = match tiff.get(..4)? {
+    [b'I', b'I', 42, 0] => true,
+    [b'M', b'M', 0, 42] => false,
+    _ => return None,
+  };
+  let u16_at = |offset: usize| {
+    let bytes = [*tiff.get(offset)?, *tiff.get(offset + 1)?];
+    Some(if little_endian {
+      u16::from_le_bytes(bytes)
+    } else {
+      u16::from_be_bytes(bytes)
+    })
+  };
+  let u32_at = |offset: usize| {
+    let bytes: [u8; 4] = tiff.get(offset..offset + 4)?.try_into().ok()?;
+    Some(if little_endian {
+      u32::from_le_bytes(bytes)
+    } else {
+      u32::from_be_bytes(bytes)
+    })
+  };
+
+  let ifd = u32_at(4)? as usize;
+  let entries = u16_at(ifd)? as usize;
+  (0..entries)
+    .map(|index| ifd + 2 + index * 12)
+    .find(|&entry| u16_at(entry) == Some(0x0112))
+    .and_then(|entry| u16_at(entry + 8))
+    .filter(|orientation| (1..=8).contains(orientation))
+}
+
+/// Ориентация RAW: из первого TIFF-блока файла (IFD0 для TIFF-based RAW,
+/// CMT1 для CR3), иначе из EXIF самого превью
+fn raw_orientation(data: &[u8], preview_offset: usize) -> u16 {
+  data
+    .windows(4)
+    .position(|window| window == b"II*\0" || window == b"MM\0*")
+    .and_then(|offset| tiff_orientation(&data[offset..]))
+    .or_else(|| read_exif_orientation(&data[preview_offset..]))
+    .unwrap_or(1)
+}
+
+/// Самое крупное встроенное JPEG-превью: (смещение, ширина, высота)
+fn embedded_jpeg(data: &[u8]) -> Option<(usize, u32, u32)> {
+  let mut best: Option<(usize, u32, u32)> = None;
+  let mut pos = 0;
+
+  while let Some(found) = data
+    .get(pos..)?
+    .windows(3)
+    .position(|window| window == [0xFF, 0xD8, 0xFF])
+  {
+    let offset = pos + found;
+    if let Some((width, height)) = jpeg_dimensions(&data[offset..]) {
+      let area = width as u64 * height as u64;
+      if !matches!(best, Some((_, w, h)) if w as u64 * h as u64 >= area) {
+        best = Some((offset, width, height));
+      }
+    }
+    pos = offset + 3;
+  }
+  best
+}
+
+/// Размеры JPEG из заголовка кадра.
+///
+/// Принимаются только baseline и progressive кадры: lossless JPEG внутри
+/// RAW содержит сами данные сенсора, а не превью.
+fn jpeg_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+  let mut pos = 2;
+  while data.get(pos) == Some(&0xFF) {
+    let marker = *data.get(pos + 1)?;
+    let length = u16::from_be_bytes([*data.get(pos + 2)?, *data.get(pos + 3)?]) as usize;
+    match marker {
+      0xC0..=0xC2 => {
+        let frame = data.get(pos + 4..pos + 2 + length)?;
+        let height = u16::from_be_bytes([*frame.get(1)?, *frame.get(2)?]) as u32;
+        let width = u16::from_be_bytes([*frame.get(3)?, *frame.get(4)?]) as u32;
+        let components = *frame.get(5)?;
+        return (width > 0 && height > 0 && matches!(components, 1 | 3)).then_some((width, height));
+      }
+      0xC3 | 0xC5..=0xC7 | 0xC9..=0xCB | 0xCD..=0xCF | 0xDA | 0xD9 => return None,
+      _ if length < 2 => return None,
+      _ => pos += 2 + length,
+    }
+  }
+  None
+}
+
+/// Дочерние коробки ISOBMFF: (тип, содержимое)
+fn boxes(data: &[u8]) -> impl Iterator<Item = ([u8; 4], &[u8])> {
+  let mut pos = 0;
+  std::iter::from_fn(move || {
+    let header = data.get(pos..pos + 8)?;
+    let size = u32::from_be_bytes(header[..4].try_into().ok()?) as usize;
+    let kind: [u8; 4] = header[4..].try_into().ok()?;
+    let (start, end) = match size {
+      0 => (pos + 8, data.len()),
+      1 => {
+        let large = u64::from_be_bytes(data.get(pos + 8..pos + 16)?.try_into().ok()?);
+        (pos + 16, pos.checked_add(usize::try_from(large).ok()?)?)
+      }
+      _ => (pos + 8, pos + size),
+    };
+    let body = data.get(start..end)?;
+    pos = end;
+    Some((kind, body))
+  })
+}
+
+fn find_box<'a>(data: &'a [u8], kind: &[u8; 4]) -> Option<&'a [u8]> {
+  boxes(data)
+    .find(|(box_kind, _)| box_kind == kind)
+    .map(|(_, body)| body)
+}
+
+/// Размеры, ориентация и кодек основного изображения HEIF.
+///
+/// Свойства берутся у элемента из `pitm` по ассоциациям `ipma`: миниатюры и
+/// плитки сетки имеют собственные `ispe`.
+fn heif_layout(data: &[u8]) -> Option<HeifLayout> {
+  // meta и pitm - FullBox с 4 байтами версии и флагов
+  let meta = find_box(data, b"meta")?.get(4..)?;
+  let pitm = find_box(meta, b"pitm")?;
+  let primary = if pitm.first()? == &0 {
+    u16::from_be_bytes(pitm.get(4..6)?.try_into().ok()?) as u32
+  } else {
+    u32::from_be_bytes(pitm.get(4..8)?.try_into().ok()?)
+  };
+
+  let iprp = find_box(meta, b"iprp")?;
+  let properties: Vec<_> = boxes(find_box(iprp, b"ipco")?).collect();
+  let associations = item_properties(find_box(iprp, b"ipma")?, primary)?;
+
+  let codec = if properties.iter().any(|(kind, _)| kind == b"av1C") {
+    "av1"
+  } else {
+    "hevc"
+  };
+  let mut layout = HeifLayout {
+    codec: codec.to_string(),
+    width: 0,
+    height: 0,
+    orientation: 1,
+  };
+
+  for index in associations {
+    let Some((kind, body)) = index.checked_sub(1).and_then(|i| properties.get(i)) else {
+      continue;
+    };
+    match kind {
+      b"ispe" => {
+        layout.width = u32::from_be_bytes(body.get(4..8)?.try_into().ok()?);
+        layout.height = u32::from_be_bytes(body.get(8..12)?.try_into().ok()?);
+      }
+      // Поворот против часовой стрелки в четвертях оборота
+      b"irot" => {
+        layout.orientation = match body.first()? & 0x03 {
+          1 => 8,
+          2 => 3,
+          3 => 6,
+          _ => 1,
+        };
+      }
+      _ => {}
+    }
+  }
+
+  (layout.width > 0 && layout.height > 0).then_some(layout)
+}
+
+/// Индексы свойств (с 1) элемента `item_id` из коробки `ipma`
+fn item_properties(ipma: &[u8], item_id: u32) -> Option<Vec<usize>> {
+  let version = *ipma.first()?;
+  let wide_index = ipma.get(3)? & 0x01 != 0;
+  let entry_count = u32::from_be_bytes(ipma.get(4..8)?.try_into().ok()?);
+  let mut pos = 8;
+
+  for _ in 0..entry_count {
+    let id = if version < 1 {
+      pos += 2;
+      u16::from_be_bytes(ipma.get(pos - 2..pos)?.try_into().ok()?) as u32
+    } else {
+      pos += 4;
+      u32::from_be_bytes(ipma.get(pos - 4..pos)?.try_into().ok()?)
+    };
+    let count = *ipma.get(pos)? as usize;
+    pos += 1;
+
+    let mut indices = Vec::with_capacity(count);
+    for _ in 0..count {
+      // Старший бит - флаг essential
+      if wide_index {
+        indices
+          .push((u16::from_be_bytes(ipma.get(pos..pos + 2)?.try_into().ok()?) & 0x7FFF) as usize);
+        pos += 2;
+      } else {
+        indices.push((ipma.get(pos)? & 0x7F) as usize);
+        pos += 1;
+      }
+    }
+    if id == item_id {
+      return Some(indices);
+    }
+  }
+  None
+}
+
+/// Можно ли декодировать HEIF на этой машине
+pub fn heif_decoder_available() -> bool {
+  cfg!(feature = "heif") || ffmpeg_supports_heif()
+}
+
+/// Поддерживает ли установленный FFmpeg HEIF (проверяется один раз)
+fn ffmpeg_supports_heif() -> bool {
+  static SUPPORTED: OnceLock<bool> = OnceLock::new();
+  *SUPPORTED.get_or_init(|| {
+    Command::new("ffmpeg")
+      .arg("-version")
+      .output()
+      .map(|output| ffmpeg_version_supports_heif(&String::from_utf8_lossy(&output.stdout)))
+      .unwrap_or(false)
+  })
+}
+
+/// Собирает ли FFmpeg этой версии HEIF-сетки из плиток (с 7.1).
+/// Git-сборки (`N-...`) и сборки с датой вместо версии считаются свежими.
+fn ffmpeg_version_supports_heif(version_output: &str) -> bool {
+  let Some(version) = version_output.split_whitespace().nth(2) else {
+    return false;
+  };
+  if version.starts_with("N-") {
+    return true;
+  }
+
+  let mut parts = version
+    .trim_start_matches('n')
+    .split(|c: char| !c.is_ascii_digit())
+    .map(|part| part.parse::<u32>().ok());
+  match (parts.next().flatten(), parts.next().flatten()) {
+    (Some(major), minor) => (major, minor.unwrap_or(0)) >= (7, 1),
+    _ => false,
+  }
+}
+
+/// Декодировать HEIF: FFmpeg, затем libheif
+fn decode_heif(path: &Path, layout: &HeifLayout) -> Result<DynamicImage, UnsupportedReason> {
+  if ffmpeg_supports_heif() {
+    if let Some(image) = decode_heif_ffmpeg(path, layout) {
+      return Ok(apply_orientation(image, layout.orientation));
+    }
+  }
+
+  #[cfg(feature = "heif")]
+  {
+    if let Some(image) = decode_heif_libheif(path) {
+      return Ok(image);
+    }
+  }
+
+  Err(UnsupportedReason::HeifDecoderUnavailable)
+}
+
+/// Декодирование через FFmpeg без автоповорота: `irot` применяется отдельно
+fn decode_heif_ffmpeg(path: &Path, layout: &HeifLayout) -> Option<DynamicImage> {
+  let output = Command::new("ffmpeg")
+    .args(["-v", "error", "-noautorotate", "-i"])
+    .arg(path)
+    .args(["-frames:v", "1", "-f", "image2pipe", "-c:v", "png", "-"])
+    .output()
+    .ok()?;
+  if !output.status.success() {
+    return None;
+  }
+
+  let image = image::load_from_memory_with_format(&output.stdout, ImageFormat::Png).ok()?;
+  // Сборка без поддержки сеток отдает только первую плитку
+  (image.dimensions() == (layout.width, layout.height)).then_some(image)
+}
+
+/// Декодирование через libheif; преобразования (`irot`, `imir`) применяет сам libheif
+#[cfg(feature = "heif")]
+fn decode_heif_libheif(path: &Path) -> Option<DynamicImage> {
+  use libheif_rs::{ColorSpace, HeifContext, LibHeif, RgbChroma};
+
+  let lib_heif = LibHeif::new();
+  let context = HeifContext::read_from_file(path.to_str()?).ok()?;
+  let handle = context.primary_image_handle().ok()?;
+  let image = lib_heif
+    .decode(&handle, ColorSpace::Rgb(RgbChroma::Rgba), None)
+    .ok()?;
+  let plane = image.planes().interleaved?;
+
+  let row = plane.width as usize * 4;
+  let pixels = plane
+    .data
+    .chunks(plane.stride)
+    .take(plane.height as usize)
+    .flat_map(|line| &line[..row])
+    .copied()
+    .collect();
+  image::RgbaImage::from_raw(plane.width, plane.height, pixels).map(DynamicImage::ImageRgba8)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use image::{Rgb, RgbImage};
+  use tempfile::TempDir;
+
+  /// JPEG 4x2: левая половина красная, правая синяя
+  fn jpeg_fixture() -> Vec<u8> {
+    let image = RgbImage::from_fn(4, 2, |x, _| {
+      if x < 2 {
+        Rgb([255, 0, 0])
+      } else {
+        Rgb([0, 0, 255])
+      }
+    });
+    let mut buffer = Vec::new();
+    JpegEncoder::new_with_quality(&mut buffer, 100)
+      .encode_image(&image)
+      .unwrap();
+    buffer
+  }
+
+  /// TIFF-блок с единственным тегом Orientation
+  fn tiff_with_orientation(orientation: u16, little_endian: bool) -> Vec<u8> {
+    let u16_bytes = |v: u16| {
+      if little_endian {
+        v.to_le_bytes()
+      } else {
+        v.to_be_bytes()
+      }
+    };
+    let u32_bytes = |v: u32| {
+      if little_endian {
+        v.to_le_bytes()
+      } else {
+        v.to_be_bytes()
+      }
+    };
+
+    let mut tiff = if little_endian {
+      b"II*\0".to_vec()
+    } else {
+      b"MM\0*".to_vec()
+    };
+    tiff.extend(u32_bytes(8));
+    tiff.extend(u16_bytes(1));
+    tiff.extend(u16_bytes(0x0112));
+    tiff.extend(u16_bytes(3)); // SHORT
+    tiff.extend(u32_bytes(1));
+    tiff.extend(u16_bytes(orientation));
+    tiff.extend([0, 0]);
+    tiff.extend(u32_bytes(0));
+    tiff
+  }
+
+  /// JPEG с сегментом APP1 Exif сразу после SOI
+  fn jpeg_with_exif(orientation: u16, little_endian: bool) -> Vec<u8> {
+    let jpeg = jpeg_fixture();
+    let mut payload = b"Exif\0\0".to_vec();
+    payload.extend(tiff_with_orientation(orientation, little_endian));
+
+    let mut data = vec![0xFF, 0xD8, 0xFF, 0xE1];
+    data.extend(((payload.len() + 2) as u16).to_be_bytes());
+    data.extend(payload);
+    data.extend(&jpeg[2..]);
+    data
+  }
+
+  fn mp4_box(kind: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    let mut data = ((body.len() + 8) as u32).to_be_bytes().to_vec();
+    data.extend(kind);
+    data.extend(body);
+    data
+  }
+
+  /// HEIF: основной элемент 1 (4000x3000, irot 90° против часовой) и
+  /// миниатюра 2 (320x240)
+  fn heif_fixture() -> Vec<u8> {
+    let ispe = |w: u32, h: u32| {
+      let mut body = vec![0; 4];
+      body.extend(w.to_be_bytes());
+      body.extend(h.to_be_bytes());
+      mp4_box(b"ispe", &body)
+    };
+    let ipco = [
+      mp4_box(b"hvcC", &[1]),
+      ispe(320, 240),
+      ispe(4000, 3000),
+      mp4_box(b"irot", &[1]),
+    ]
+    .concat();
+    // version 0, flags 0: item_ID u16, индексы по 7 бит
+    let mut ipma = vec![0, 0, 0, 0];
+    ipma.extend(2u32.to_be_bytes());
+    ipma.extend([0, 2, 2, 0x81, 0x02]);
+    ipma.extend([0, 1, 3, 0x81, 0x03, 0x04]);
+    let iprp = mp4_box(
+      b"iprp",
+      &[mp4_box(b"ipco", &ipco), mp4_box(b"ipma", &ipma)].concat(),
+    );
+    let meta = mp4_box(
+      b"meta",
+      &[vec![0; 4], mp4_box(b"pitm", &[0, 0, 0, 0, 0, 1]), iprp].concat(),
+    );
+    [mp4_box(b"ftyp", b"heicmif1"), meta].concat()
+  }
+
+  #[test]
+  fn test_photo_format_from_path() {
+    assert_eq!(
+      PhotoFormat::from_path(Path::new("IMG_0001.HEIC")),
+      Some(PhotoFormat::Heif)
+    );
+    assert_eq!(
+      PhotoFormat::from_path(Path::new("/a/DSC0001.arw")),
+      Some(PhotoFormat::Raw)
+    );
+    assert_eq!(PhotoFormat::from_path(Path::new("photo.jpg")), None);
+  }
+
+  #[test]
+  fn test_read_exif_orientation() {
+    assert_eq!(read_exif_orientation(&jpeg_with_exif(6, true)), Some(6));
+    assert_eq!(read_exif_orientation(&jpeg_with_exif(8, false)), Some(8));
+    assert_eq!(read_exif_orientation(&jpeg_fixture()), None);
+    assert_eq!(read_exif_orientation(&jpeg_with_exif(42, true)), None);
+  }
+
+  #[test]
+  fn test_apply_orientation_rotates_pixels() {
+    let image = image::load_from_memory(&jpeg_fixture()).unwrap();
+
+    // 6: поворот на 90° по часовой - красная половина уходит вверх
+    let rotated = apply_orientation(image.clone(), 6).to_rgb8();
+    assert_eq!(rotated.dimensions(), (2, 4));
+    assert!(rotated.get_pixel(0, 0)[0] > 200);
+    assert!(rotated.get_pixel(0, 3)[2] > 200);
+
+    // 2: зеркало по горизонтали - синяя половина слева
+    let mirrored = apply_orientation(image, 2).to_rgb8();
+    assert_eq!(mirrored.dimensions(), (4, 2));
+    assert!(mirrored.get_pixel(0, 0)[2] > 200);
+
+    assert_eq!(oriented_dimensions(4, 2, 8), (2, 4));
+    assert_eq!(oriented_dimensions(4, 2, 3), (4, 2));
+  }
+
+  #[test]
+  fn test_open_image_applies_jpeg_orientation() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("portrait.jpg");
+    std::fs::write(&path, jpeg_with_exif(6, false)).unwrap();
+
+    assert_eq!(open_image(&path).unwrap().dimensions(), (2, 4));
+  }
+
+  #[test]
+  fn test_raw_embedded_preview_with_orientation() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("DSC0001.dng");
+    // IFD0 с ориентацией, сенсорные данные и встроенное превью
+    let mut raw = tiff_with_orientation(8, true);
+    raw.extend(vec![0x11; 64]);
+    raw.extend(jpeg_fixture());
+    std::fs::write(&path, &raw).unwrap();
+
+    let info = probe_photo(&path).unwrap();
+    assert_eq!(info.codec, "mjpeg");
+    assert_eq!((info.width, info.height), (2, 4));
+    assert_eq!(info.unsupported_reason, None);
+
+    let image = open_image(&path).unwrap();
+    assert_eq!(image.dimensions(), (2, 4));
+    // 8: поворот на 90° против часовой - красная половина уходит вниз
+    assert!(image.to_rgb8().get_pixel(0, 3)[0] > 200);
+  }
+
+  #[test]
+  fn test_raw_without_preview_reports_reason() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("IMG_0001.CR3");
+    std::fs::write(&path, mp4_box(b"ftyp", b"crx isom")).unwrap();
+
+    assert_eq!(
+      probe_photo(&path).unwrap_err(),
+      UnsupportedReason::RawPreviewMissing
+    );
+    assert_eq!(
+      open_image(&path).unwrap_err(),
+      UnsupportedReason::RawPreviewMissing
+    );
+  }
+
+  #[test]
+  fn test_heif_layout_uses_primary_item() {
+    let layout = heif_layout(&heif_fixture()).unwrap();
+    assert_eq!(layout.codec, "hevc");
+    assert_eq!((layout.width, layout.height), (4000, 3000));
+    assert_eq!(layout.orientation, 8);
+
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("IMG_0001.heic");
+    std::fs::write(&path, heif_fixture()).unwrap();
+    let info = probe_photo(&path).unwrap();
+    assert_eq!((info.width, info.height), (3000, 4000));
+
+    let broken = dir.path().join("broken.heic");
+    std::fs::write(&broken, mp4_box(b"ftyp", b"heic")).unwrap();
+    assert_eq!(
+      probe_photo(&broken).unwrap_err(),
+      UnsupportedReason::UnreadableImage
+    );
+  }
+
+  #[test]
+  fn test_ffmpeg_version_supports_heif() {
+    assert!(ffmpeg_version_supports_heif(
+      "ffmpeg version 7.1.1 Copyright (c) 2000-2025"
+    ));
+    assert!(ffmpeg_version_supports_heif("ffmpeg version n8.0"));
+    assert!(ffmpeg_version_supports_heif(
+      "ffmpeg version N-118000-g1234abcd"
+    ));
+    assert!(!ffmpeg_version_supports_heif(
+      "ffmpeg version 6.1.1-3ubuntu5 Copyright"
+    ));
+    assert!(!ffmpeg_version_supports_heif("ffmpeg version 7.0.2"));
+    assert!(!ffmpeg_version_supports_heif(""));
+  }
+
+  #[test]
+  fn test_convert_image_for_timeline_reuses_output() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("DSC0002.arw");
+    let mut raw = tiff_with_orientation(6, false);
+    raw.extend(jpeg_fixture());
+    std::fs::write(&path, &raw).unwrap();
+    let output_dir = dir.path().join("converted");
+
+    let output = convert_image_for_timeline(&path, &output_dir).unwrap();
+    assert_eq!(output.extension().unwrap(), "jpg");
+    assert!(output.starts_with(&output_dir));
+    assert_eq!(image::open(&output).unwrap().dimensions(), (2, 4));
+
+    // Повторный вызов возвращает готовый файл
+    assert_eq!(
+      convert_image_for_timeline(&path, &output_dir).unwrap(),
+      output
+    );
+  }
+}
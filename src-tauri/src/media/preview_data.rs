@@ -1,6 +1,10 @@
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
+use super::frame_index::{frame_bucket, FrameIndex, IndexedFrame};
+use super::hover_preview::HoverPreview;
+use super::sprite::SpriteSheetSet;
+
 /// Единая структура для всех данных превью медиафайла
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MediaPreviewData {
@@ -16,6 +20,22 @@ pub struct MediaPreviewData {
   /// Превью для таймлайна (множественные)
   pub timeline_previews: Vec<TimelinePreview>,
 
+  /// Индекс кадров диафильма таймлайна по временной метке
+  #[serde(default)]
+  pub frame_index: FrameIndex,
+
+  /// Спрайт-листы для скраббинга таймлайна
+  #[serde(default)]
+  pub sprite_sheets: Option<SpriteSheetSet>,
+
+  /// Анимированное превью при наведении в медиабраузере
+  #[serde(default)]
+  pub hover_preview: Option<HoverPreview>,
+
+  /// Blurhash-заглушка превью для мгновенной отрисовки в медиабраузере
+  #[serde(default)]
+  pub placeholder_hash: Option<String>,
+
   /// Кадры для распознавания
   pub recognition_frames: Vec<RecognitionFrame>,
 
@@ -142,6 +162,10 @@ impl MediaPreviewData {
       file_path,
       browser_thumbnail: None,
       timeline_previews: Vec::new(),
+      frame_index: FrameIndex::default(),
+      sprite_sheets: None,
+      hover_preview: None,
+      placeholder_hash: None,
       recognition_frames: Vec::new(),
       recognition_results: None,
       last_updated: chrono::Utc::now(),
@@ -165,6 +189,57 @@ impl MediaPreviewData {
     self.last_updated = chrono::Utc::now();
   }
 
+  /// Добавить кадр диафильма в индекс и превью таймлайна.
+  /// Возвращает вытесненный кадр с той же временной меткой.
+  pub fn merge_timeline_frame(
+    &mut self,
+    frame: IndexedFrame,
+    base64_data: Option<String>,
+  ) -> Option<IndexedFrame> {
+    let bucket = frame_bucket(frame.timestamp);
+    self
+      .timeline_previews
+      .retain(|preview| frame_bucket(preview.timestamp) != bucket);
+    self.add_timeline_preview(TimelinePreview {
+      timestamp: frame.timestamp,
+      path: frame.path.clone(),
+      base64_data,
+    });
+    self.frame_index.insert(frame)
+  }
+
+  /// Удалить кадры диафильма в диапазоне `[start, end]` из индекса и превью
+  pub fn invalidate_timeline_frames(&mut self, start: f64, end: f64) -> Vec<IndexedFrame> {
+    let removed = self.frame_index.remove_range(start, end);
+    if !removed.is_empty() {
+      let (first, last) = (frame_bucket(start), frame_bucket(end));
+      self.timeline_previews.retain(|preview| {
+        let bucket = frame_bucket(preview.timestamp);
+        bucket < first || bucket > last
+      });
+      self.last_updated = chrono::Utc::now();
+    }
+    removed
+  }
+
+  /// Установить спрайт-листы таймлайна
+  pub fn set_sprite_sheets(&mut self, sprites: SpriteSheetSet) {
+    self.sprite_sheets = Some(sprites);
+    self.last_updated = chrono::Utc::now();
+  }
+
+  /// Установить превью при наведении
+  pub fn set_hover_preview(&mut self, preview: HoverPreview) {
+    self.hover_preview = Some(preview);
+    self.last_updated = chrono::Utc::now();
+  }
+
+  /// Сохранить blurhash-заглушку превью
+  pub fn set_placeholder_hash(&mut self, hash: String) {
+    self.placeholder_hash = Some(hash);
+    self.last_updated = chrono::Utc::now();
+  }
+
   /// Добавить кадр для распознавания
   #[allow(dead_code)]
   pub fn add_recognition_frame(&mut self, frame: RecognitionFrame) {
@@ -176,7 +251,6 @@ impl MediaPreviewData {
   }
 
   /// Установить результаты распознавания
-  #[allow(dead_code)]
   pub fn set_recognition_results(&mut self, results: RecognitionResults) {
     self.recognition_results = Some(results);
     self.last_updated = chrono::Utc::now();
@@ -205,6 +279,63 @@ impl MediaPreviewData {
   }
 }
 
+/// Преобразование результатов модуля распознавания в формат данных превью
+impl From<crate::recognition::types::RecognitionResults> for RecognitionResults {
+  fn from(results: crate::recognition::types::RecognitionResults) -> Self {
+    Self {
+      objects: results.objects.into_iter().map(Into::into).collect(),
+      faces: results.faces.into_iter().map(Into::into).collect(),
+      scenes: results.scenes.into_iter().map(Into::into).collect(),
+      processed_at: results.processed_at,
+    }
+  }
+}
+
+impl From<crate::recognition::types::DetectedObject> for DetectedObject {
+  fn from(object: crate::recognition::types::DetectedObject) -> Self {
+    Self {
+      class: object.class,
+      confidence: object.confidence,
+      timestamps: object.timestamps,
+      bounding_boxes: object.bounding_boxes.into_iter().map(Into::into).collect(),
+    }
+  }
+}
+
+impl From<crate::recognition::types::DetectedFace> for DetectedFace {
+  fn from(face: crate::recognition::types::DetectedFace) -> Self {
+    Self {
+      face_id: face.face_id,
+      person_name: face.person_name,
+      confidence: face.confidence,
+      timestamps: face.timestamps,
+      bounding_boxes: face.bounding_boxes.into_iter().map(Into::into).collect(),
+    }
+  }
+}
+
+impl From<crate::recognition::types::DetectedScene> for DetectedScene {
+  fn from(scene: crate::recognition::types::DetectedScene) -> Self {
+    Self {
+      scene_type: scene.scene_type,
+      start_time: scene.start_time,
+      end_time: scene.end_time,
+      key_objects: scene.key_objects,
+    }
+  }
+}
+
+impl From<crate::recognition::types::BoundingBox> for BoundingBox {
+  fn from(bbox: crate::recognition::types::BoundingBox) -> Self {
+    Self {
+      x: bbox.x,
+      y: bbox.y,
+      width: bbox.width,
+      height: bbox.height,
+    }
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -280,6 +411,34 @@ mod tests {
     assert_eq!(data.timeline_previews[2].timestamp, 3.0);
   }
 
+  #[test]
+  fn test_merge_and_invalidate_timeline_frames() {
+    let mut data = MediaPreviewData::new("test_id".to_string(), PathBuf::from("/tmp/video.mp4"));
+    let indexed = |timestamp: f64| IndexedFrame {
+      timestamp,
+      path: PathBuf::from(format!("/tmp/filmstrip_{timestamp}.jpg")),
+      width: 320,
+      height: 180,
+      generated_at: chrono::Utc::now(),
+    };
+
+    for timestamp in [0.0, 1.0, 2.0, 3.0] {
+      assert!(data
+        .merge_timeline_frame(indexed(timestamp), None)
+        .is_none());
+    }
+    // Повторный кадр с той же меткой заменяет прежний
+    assert!(data.merge_timeline_frame(indexed(1.0), None).is_some());
+    assert_eq!(data.timeline_previews.len(), 4);
+    assert_eq!(data.frame_index.len(), 4);
+
+    let removed = data.invalidate_timeline_frames(0.5, 2.0);
+    assert_eq!(removed.len(), 2);
+    let remaining: Vec<f64> = data.timeline_previews.iter().map(|p| p.timestamp).collect();
+    assert_eq!(remaining, vec![0.0, 3.0]);
+    assert_eq!(data.frame_index.len(), 2);
+  }
+
   #[test]
   fn test_add_recognition_frame() {
     let mut data = MediaPreviewData::new("test_id".to_string(), PathBuf::from("/tmp/video.mp4"));
@@ -1,14 +1,35 @@
 use anyhow::Result;
-use std::collections::HashMap;
+use once_cell::sync::OnceCell;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+use super::frame_index::{frame_bucket, sample_evenly, FrameIndex, InFlightFrames, IndexedFrame};
+use super::hover_preview::{generate_hover_preview, HoverPreview, HoverPreviewOptions};
 use super::preview_data::{MediaPreviewData, RecognitionFrame, ThumbnailData, TimelinePreview};
+use super::sprite::{generate_sprite_sheets, SpriteOptions, SpriteSheetSet};
 use super::thumbnail::generate_thumbnail;
+use crate::core::performance::memory_pressure::{MemoryShrinker, ShrinkRequest};
+use crate::recognition::types::RecognitionResults as RecognitionOutput;
+use crate::security::privacy_mode::PrivacyMode;
 use crate::video_compiler::cache::RenderCache;
+use crate::video_compiler::commands::frame_extraction_commands::TimelineFrame as CompilerFrame;
 use crate::video_compiler::frame_extraction::{ExtractionPurpose, FrameExtractionManager};
 use crate::video_compiler::preview::PreviewGenerator;
+use crate::video_compiler::VideoCompilerEvent;
+
+/// Получатель событий о новых превью (обычно отправка Tauri события)
+pub type PreviewEventSink = Arc<dyn Fn(VideoCompilerEvent) + Send + Sync>;
+
+/// Результат поиска кадров диафильма в индексе
+enum FrameLookup {
+  /// Все кадры найдены, в порядке запрошенных меток
+  Ready(Vec<IndexedFrame>),
+  /// Метки без кадра нужного разрешения, по одной на корзину
+  Missing(Vec<f64>),
+}
 
 /// Единый менеджер для всех данных превью
 pub struct PreviewDataManager {
@@ -21,8 +42,17 @@ pub struct PreviewDataManager {
   /// Менеджер извлечения кадров
   frame_extractor: Arc<RwLock<FrameExtractionManager>>,
 
+  /// Общий кэш кадров генератора превью и менеджера извлечения
+  frame_cache: Arc<RwLock<RenderCache>>,
+
   /// Базовая директория для хранения
   base_dir: PathBuf,
+
+  /// Получатель событий PreviewGenerated
+  event_sink: OnceCell<PreviewEventSink>,
+
+  /// Кадры диафильма, которые сейчас генерируются
+  frames_in_flight: InFlightFrames,
 }
 
 impl PreviewDataManager {
@@ -37,7 +67,35 @@ impl PreviewDataManager {
       data: Arc::new(RwLock::new(HashMap::new())),
       timeline_generator: Arc::new(RwLock::new(preview_generator)),
       frame_extractor: Arc::new(RwLock::new(frame_extractor)),
+      frame_cache: cache,
       base_dir,
+      event_sink: OnceCell::new(),
+      frames_in_flight: InFlightFrames::new(),
+    }
+  }
+
+  /// Базовая директория кэшей превью и распознавания
+  pub fn base_dir(&self) -> &Path {
+    &self.base_dir
+  }
+
+  /// Установить получателя событий о новых превью.
+  ///
+  /// Получатель задается один раз при запуске приложения.
+  pub fn set_event_sink(&self, sink: PreviewEventSink) {
+    if self.event_sink.set(sink).is_err() {
+      log::warn!("Получатель событий превью уже установлен");
+    }
+  }
+
+  /// Сообщить о новом превью. Событие содержит путь к кэшу, а не байты кадра.
+  fn notify_preview_generated(&self, file_id: &str, timestamp: f64, cache_path: &Path) {
+    if let Some(sink) = self.event_sink.get() {
+      sink(VideoCompilerEvent::PreviewGenerated {
+        file_id: file_id.to_string(),
+        timestamp,
+        cache_path: cache_path.to_string_lossy().to_string(),
+      });
     }
   }
 
@@ -72,8 +130,12 @@ impl PreviewDataManager {
       .await
       .map_err(|e| anyhow::anyhow!(e))?;
 
-    // Читаем файл для base64
+    // Читаем файл для base64; в режиме приватности миниатюра перезаписывается зашифрованной
     let image_data = tokio::fs::read(&output_path).await?;
+    let privacy = PrivacyMode::global();
+    if privacy.is_enabled() {
+      privacy.write(&output_path, &image_data).await?;
+    }
     use base64::{engine::general_purpose::STANDARD, Engine as _};
     let base64_data = STANDARD.encode(&image_data);
 
@@ -89,9 +151,11 @@ impl PreviewDataManager {
     let mut data = self.data.write().await;
     let preview_data = data
       .entry(file_id.clone())
-      .or_insert_with(|| MediaPreviewData::new(file_id, file_path));
+      .or_insert_with(|| MediaPreviewData::new(file_id.clone(), file_path));
     preview_data.set_browser_thumbnail(thumbnail.clone());
+    drop(data);
 
+    self.notify_preview_generated(&file_id, timestamp, &thumbnail.path);
     Ok(thumbnail)
   }
 
@@ -125,7 +189,7 @@ impl PreviewDataManager {
 
         // Сохраняем файл на диск для совместимости
         let file_path = output_dir.join(format!("frame_{i:04}.jpg"));
-        tokio::fs::write(&file_path, image_data).await?;
+        PrivacyMode::global().write(&file_path, image_data).await?;
 
         let timeline_preview = TimelinePreview {
           timestamp: preview_result.timestamp,
@@ -155,17 +219,108 @@ impl PreviewDataManager {
     let mut data = self.data.write().await;
     let preview_data = data
       .entry(file_id.clone())
-      .or_insert_with(|| MediaPreviewData::new(file_id, file_path));
+      .or_insert_with(|| MediaPreviewData::new(file_id.clone(), file_path));
 
     // Очищаем старые превью и добавляем новые
     preview_data.timeline_previews.clear();
     for preview in &timeline_previews {
       preview_data.add_timeline_preview(preview.clone());
     }
+    drop(data);
+
+    for preview in timeline_previews.iter().filter(|p| p.base64_data.is_some()) {
+      self.notify_preview_generated(&file_id, preview.timestamp, &preview.path);
+    }
 
     Ok(timeline_previews)
   }
 
+  /// Генерировать спрайт-листы для скраббинга таймлайна.
+  ///
+  /// Листы кэшируются по времени модификации исходника и параметрам и
+  /// перегенерируются только если устарели.
+  pub async fn generate_thumbnail_sprite(
+    &self,
+    file_id: String,
+    file_path: PathBuf,
+    options: SpriteOptions,
+  ) -> Result<SpriteSheetSet> {
+    // Длительность нужна для расчета раскладки листов
+    let generator = self.timeline_generator.read().await;
+    let duration = generator.get_video_info(&file_path).await?.duration;
+    drop(generator);
+
+    let cache_root = self.base_dir.join("Caches/sprites").join(&file_id);
+    let sprites = generate_sprite_sheets(&file_path, &cache_root, &options, duration)
+      .await
+      .map_err(|e| anyhow::anyhow!(e))?;
+
+    let mut data = self.data.write().await;
+    let preview_data = data
+      .entry(file_id.clone())
+      .or_insert_with(|| MediaPreviewData::new(file_id, file_path));
+
+    // Удаляем листы предыдущей версии, если параметры или исходник изменились
+    if let Some(previous) = preview_data.sprite_sheets.take() {
+      if let Some(previous_dir) = previous.sheets.first().and_then(|p| p.parent()) {
+        if sprites.sheets.first().and_then(|p| p.parent()) != Some(previous_dir) {
+          let _ = tokio::fs::remove_dir_all(previous_dir).await;
+        }
+      }
+    }
+    preview_data.set_sprite_sheets(sprites.clone());
+
+    Ok(sprites)
+  }
+
+  /// Генерировать анимированное превью при наведении.
+  ///
+  /// Превью кэшируется по времени модификации исходника и параметрам и
+  /// перегенерируется только если устарело.
+  pub async fn generate_hover_preview(
+    &self,
+    file_id: String,
+    file_path: PathBuf,
+    options: HoverPreviewOptions,
+  ) -> Result<HoverPreview> {
+    // Длительность нужна для выбора фрагментов
+    let generator = self.timeline_generator.read().await;
+    let duration = generator.get_video_info(&file_path).await?.duration;
+    drop(generator);
+
+    let cache_root = self.base_dir.join("Caches/preview/hover").join(&file_id);
+    let preview = generate_hover_preview(&file_path, &cache_root, &options, duration)
+      .await
+      .map_err(|e| anyhow::anyhow!(e))?;
+
+    let mut data = self.data.write().await;
+    let preview_data = data
+      .entry(file_id.clone())
+      .or_insert_with(|| MediaPreviewData::new(file_id, file_path));
+
+    // Удаляем превью предыдущей версии, если параметры или исходник изменились
+    if let Some(previous) = preview_data.hover_preview.take() {
+      if let Some(previous_dir) = previous.path.parent() {
+        if preview.path.parent() != Some(previous_dir) {
+          let _ = tokio::fs::remove_dir_all(previous_dir).await;
+        }
+      }
+    }
+    preview_data.set_hover_preview(preview.clone());
+
+    Ok(preview)
+  }
+
+  /// ID файла по пути: существующая запись или сам путь
+  pub async fn file_id_for_path(&self, path: &Path) -> String {
+    let data = self.data.read().await;
+    data
+      .values()
+      .find(|preview_data| preview_data.file_path == path)
+      .map(|preview_data| preview_data.file_id.clone())
+      .unwrap_or_else(|| path.to_string_lossy().to_string())
+  }
+
   /// Извлечь кадры для распознавания
   pub async fn extract_recognition_frames(
     &self,
@@ -247,24 +402,54 @@ impl PreviewDataManager {
       for frame in preview_data.recognition_frames {
         let _ = tokio::fs::remove_file(&frame.path).await;
       }
+
+      if let Some(sprites) = preview_data.sprite_sheets {
+        for sheet in sprites.sheets {
+          let _ = tokio::fs::remove_file(&sheet).await;
+        }
+      }
+
+      if let Some(hover) = preview_data.hover_preview {
+        if let Some(dir) = hover.path.parent() {
+          let _ = tokio::fs::remove_dir_all(dir).await;
+        }
+      }
     }
+    let _ = tokio::fs::remove_dir_all(self.filmstrip_dir(file_id)).await;
 
     Ok(())
   }
 
+  /// Перенести данные превью и распознавания на новый путь файла.
+  /// Возвращает количество обновленных записей.
+  pub async fn relink_file(&self, old_path: &Path, new_path: &Path) -> usize {
+    let mut data = self.data.write().await;
+    let mut updated = 0;
+
+    for preview_data in data.values_mut() {
+      if preview_data.file_path == old_path {
+        preview_data.file_path = new_path.to_path_buf();
+        preview_data.last_updated = chrono::Utc::now();
+        updated += 1;
+      }
+    }
+
+    updated
+  }
+
   /// Сохранить все данные в файл
   pub async fn save_to_file(&self, path: &Path) -> Result<()> {
     let data = self.data.read().await;
     let json = serde_json::to_string_pretty(&*data)?;
-    tokio::fs::write(path, json).await?;
+    PrivacyMode::global().write(path, json.as_bytes()).await?;
     Ok(())
   }
 
   /// Загрузить данные из файла
   pub async fn load_from_file(&self, path: &Path) -> Result<()> {
     if path.exists() {
-      let json = tokio::fs::read_to_string(path).await?;
-      let loaded_data: HashMap<String, MediaPreviewData> = serde_json::from_str(&json)?;
+      let json = PrivacyMode::global().read(path).await?;
+      let loaded_data: HashMap<String, MediaPreviewData> = serde_json::from_slice(&json)?;
 
       let mut data = self.data.write().await;
       *data = loaded_data;
@@ -305,7 +490,9 @@ impl PreviewDataManager {
 
       // Декодируем base64 и сохраняем файл
       let image_data = STANDARD.decode(&frame.base64_data)?;
-      tokio::fs::write(&frame_path, &image_data).await?;
+      PrivacyMode::global()
+        .write(&frame_path, &image_data)
+        .await?;
 
       // Добавляем в структуру данных
       let timeline_preview = TimelinePreview {
@@ -314,12 +501,311 @@ impl PreviewDataManager {
         base64_data: Some(frame.base64_data.clone()),
       };
 
+      self.notify_preview_generated(&file_id, timeline_preview.timestamp, &timeline_preview.path);
       preview_data.add_timeline_preview(timeline_preview);
     }
 
     Ok(())
   }
 
+  /// Директория кадров диафильма файла и его индекса
+  fn filmstrip_dir(&self, file_id: &str) -> PathBuf {
+    self
+      .base_dir
+      .join("Caches/timeline")
+      .join(file_id)
+      .join("filmstrip")
+  }
+
+  /// Загрузить индекс кадров файла с диска, если его еще нет в памяти
+  async fn ensure_frame_index(&self, file_id: &str) {
+    let loaded = self
+      .data
+      .read()
+      .await
+      .get(file_id)
+      .is_some_and(|preview_data| !preview_data.frame_index.is_empty());
+    if loaded {
+      return;
+    }
+
+    let index = FrameIndex::load(&self.filmstrip_dir(file_id)).await;
+    if index.is_empty() {
+      return;
+    }
+
+    let mut data = self.data.write().await;
+    let preview_data = data
+      .entry(file_id.to_string())
+      .or_insert_with(|| MediaPreviewData::new(file_id.to_string(), PathBuf::new()));
+    if preview_data.frame_index.is_empty() {
+      preview_data.frame_index = index;
+    }
+  }
+
+  /// Прикрепить кадры таймлайна, полученные Video Compiler, к данным файла.
+  ///
+  /// Кадры сохраняются в кэш на диске и добавляются в индекс кадров файла:
+  /// кадры с теми же временными метками заменяются, остальные сохраняются.
+  pub async fn attach_timeline_frames(
+    &self,
+    file_id: &str,
+    frames: Vec<CompilerFrame>,
+  ) -> Result<Vec<TimelinePreview>> {
+    use base64::{engine::general_purpose::STANDARD, Engine as _};
+
+    let output_dir = self.filmstrip_dir(file_id);
+    tokio::fs::create_dir_all(&output_dir).await?;
+    self.ensure_frame_index(file_id).await;
+
+    let generated_at = chrono::Utc::now();
+    let mut indexed = Vec::with_capacity(frames.len());
+    for frame in &frames {
+      let path = output_dir.join(format!(
+        "frame_{}_{}x{}.jpg",
+        frame_bucket(frame.timestamp),
+        frame.width,
+        frame.height
+      ));
+      PrivacyMode::global()
+        .write(&path, &frame.frame_data)
+        .await?;
+      let indexed_frame = IndexedFrame {
+        timestamp: frame.timestamp,
+        path,
+        width: frame.width,
+        height: frame.height,
+        generated_at,
+      };
+      indexed.push((indexed_frame, STANDARD.encode(&frame.frame_data)));
+    }
+
+    let mut data = self.data.write().await;
+    let preview_data = data
+      .entry(file_id.to_string())
+      .or_insert_with(|| MediaPreviewData::new(file_id.to_string(), PathBuf::new()));
+
+    let mut previews = Vec::with_capacity(indexed.len());
+    let mut replaced = Vec::new();
+    for (frame, base64_data) in indexed {
+      previews.push(TimelinePreview {
+        timestamp: frame.timestamp,
+        path: frame.path.clone(),
+        base64_data: Some(base64_data.clone()),
+      });
+      if let Some(previous) = preview_data.merge_timeline_frame(frame, Some(base64_data)) {
+        if !previews.iter().any(|preview| preview.path == previous.path) {
+          replaced.push(previous.path);
+        }
+      }
+    }
+    // Индекс сохраняется под блокировкой, чтобы на диске не оказался устаревший снимок
+    preview_data.frame_index.save(&output_dir).await?;
+    drop(data);
+
+    for path in replaced {
+      let _ = tokio::fs::remove_file(&path).await;
+    }
+    for preview in &previews {
+      self.notify_preview_generated(file_id, preview.timestamp, &preview.path);
+    }
+
+    Ok(previews)
+  }
+
+  /// Получить кадры диафильма для временных меток, генерируя только отсутствующие в индексе.
+  ///
+  /// `generate` получает недостающие метки и возвращает данные кадров в том же
+  /// порядке. Кадры, которые уже генерирует другой запрос, повторно не
+  /// запускаются: запрос дожидается их появления в индексе.
+  pub async fn get_or_generate_timeline_frames<F, Fut>(
+    &self,
+    file_id: &str,
+    timestamps: &[f64],
+    resolution: (u32, u32),
+    generate: F,
+  ) -> Result<Vec<IndexedFrame>>
+  where
+    F: Fn(Vec<f64>) -> Fut,
+    Fut: Future<Output = Result<Vec<Vec<u8>>>>,
+  {
+    self.ensure_frame_index(file_id).await;
+
+    loop {
+      let missing = match self.lookup_frames(file_id, timestamps, resolution).await {
+        FrameLookup::Ready(frames) => return Ok(frames),
+        FrameLookup::Missing(missing) => missing,
+      };
+
+      let buckets: Vec<i64> = missing.iter().map(|&t| frame_bucket(t)).collect();
+      let (mut claim, waiting) = self.frames_in_flight.claim(file_id, &buckets);
+
+      // Другой запрос мог сгенерировать кадры между проверкой индекса и захватом
+      let still_missing: HashSet<i64> =
+        match self.lookup_frames(file_id, &missing, resolution).await {
+          FrameLookup::Ready(_) => HashSet::new(),
+          FrameLookup::Missing(still) => still.iter().map(|&t| frame_bucket(t)).collect(),
+        };
+      claim.release_where(|bucket| !still_missing.contains(&bucket));
+
+      if !claim.is_empty() {
+        let claimed: HashSet<i64> = claim.buckets().iter().copied().collect();
+        let to_generate: Vec<f64> = missing
+          .into_iter()
+          .filter(|&t| claimed.contains(&frame_bucket(t)))
+          .collect();
+
+        let images = generate(to_generate.clone()).await?;
+        if images.len() != to_generate.len() {
+          anyhow::bail!(
+            "Сгенерировано {} кадров вместо {}",
+            images.len(),
+            to_generate.len()
+          );
+        }
+
+        let frames = to_generate
+          .into_iter()
+          .zip(images)
+          .map(|(timestamp, frame_data)| CompilerFrame {
+            timestamp,
+            frame_data,
+            width: resolution.0,
+            height: resolution.1,
+          })
+          .collect();
+        self.attach_timeline_frames(file_id, frames).await?;
+        drop(claim);
+      }
+
+      for mut receiver in waiting {
+        let _ = receiver.changed().await;
+      }
+    }
+  }
+
+  /// Найти кадры нужного разрешения для меток в индексе файла
+  async fn lookup_frames(
+    &self,
+    file_id: &str,
+    timestamps: &[f64],
+    resolution: (u32, u32),
+  ) -> FrameLookup {
+    let data = self.data.read().await;
+    let index = data
+      .get(file_id)
+      .map(|preview_data| &preview_data.frame_index);
+
+    let mut found = Vec::with_capacity(timestamps.len());
+    let mut missing = Vec::new();
+    let mut seen = HashSet::new();
+    for &timestamp in timestamps {
+      match index.and_then(|index| index.get(timestamp, resolution)) {
+        Some(frame) => found.push(frame.clone()),
+        None => {
+          if seen.insert(frame_bucket(timestamp)) {
+            missing.push(timestamp);
+          }
+        }
+      }
+    }
+
+    if missing.is_empty() {
+      FrameLookup::Ready(found)
+    } else {
+      FrameLookup::Missing(missing)
+    }
+  }
+
+  /// Прочитать изображение из кэша превью, расшифровывая его в памяти.
+  ///
+  /// В режиме приватности миниатюры и кадры на диске зашифрованы, поэтому
+  /// frontend получает их через этот метод, а не по пути к файлу.
+  /// Читаются только файлы внутри базовой директории.
+  pub async fn read_cached_image(&self, path: &Path) -> Result<Vec<u8>> {
+    let path = tokio::fs::canonicalize(path).await?;
+    let base_dir = tokio::fs::canonicalize(&self.base_dir).await?;
+    if !path.starts_with(&base_dir) {
+      anyhow::bail!("Путь {path:?} находится вне кэша превью");
+    }
+    PrivacyMode::global().read(&path).await
+  }
+
+  /// Кадры диафильма файла в диапазоне `[start, end]`: не более `max_count`,
+  /// равномерно распределенных по диапазону
+  pub async fn get_frames_in_range(
+    &self,
+    file_id: &str,
+    start: f64,
+    end: f64,
+    max_count: usize,
+  ) -> Vec<IndexedFrame> {
+    self.ensure_frame_index(file_id).await;
+
+    let data = self.data.read().await;
+    let frames = data
+      .get(file_id)
+      .map(|preview_data| {
+        preview_data
+          .frame_index
+          .frames_in_range(start, end)
+          .into_iter()
+          .cloned()
+          .collect()
+      })
+      .unwrap_or_default();
+    sample_evenly(frames, max_count)
+  }
+
+  /// Инвалидировать кадры диафильма в диапазоне `[start, end]`.
+  ///
+  /// Используется при изменении обрезки клипа: кадры вне диапазона остаются
+  /// в кэше. Возвращает количество удаленных кадров.
+  pub async fn invalidate_frames_in_range(
+    &self,
+    file_id: &str,
+    start: f64,
+    end: f64,
+  ) -> Result<usize> {
+    self.ensure_frame_index(file_id).await;
+
+    let mut data = self.data.write().await;
+    let Some(preview_data) = data.get_mut(file_id) else {
+      return Ok(0);
+    };
+    let removed = preview_data.invalidate_timeline_frames(start, end);
+    if !removed.is_empty() {
+      preview_data
+        .frame_index
+        .save(&self.filmstrip_dir(file_id))
+        .await?;
+    }
+    drop(data);
+
+    for frame in &removed {
+      let _ = tokio::fs::remove_file(&frame.path).await;
+    }
+    Ok(removed.len())
+  }
+
+  /// Сохранить blurhash-заглушку превью файла
+  pub async fn set_placeholder_hash(&self, file_id: &str, file_path: &Path, hash: String) {
+    let mut data = self.data.write().await;
+    let preview_data = data
+      .entry(file_id.to_string())
+      .or_insert_with(|| MediaPreviewData::new(file_id.to_string(), file_path.to_path_buf()));
+    preview_data.set_placeholder_hash(hash);
+  }
+
+  /// Прикрепить результаты распознавания к данным превью файла
+  pub async fn attach_recognition(&self, file_id: &str, results: RecognitionOutput) {
+    let mut data = self.data.write().await;
+    let preview_data = data
+      .entry(file_id.to_string())
+      .or_insert_with(|| MediaPreviewData::new(file_id.to_string(), PathBuf::new()));
+    preview_data.set_recognition_results(results.into());
+  }
+
   /// Получить timeline frames для файла
   pub async fn get_timeline_frames(
     &self,
@@ -353,6 +839,15 @@ impl PreviewDataManager {
   }
 }
 
+#[async_trait::async_trait]
+impl MemoryShrinker for PreviewDataManager {
+  /// Сбросить долю кадров из общего кэша в памяти. Индекс превью по файлам
+  /// остается: он хранит только пути к файлам на диске.
+  async fn shrink(&self, request: ShrinkRequest) -> u64 {
+    self.frame_cache.write().await.shed_entries(request)
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -494,6 +989,31 @@ mod tests {
     assert!(data.contains_key("test_file"));
   }
 
+  #[tokio::test]
+  async fn test_placeholder_hash_survives_save_and_load() {
+    let manager = create_test_manager().await;
+    let temp_dir = tempdir().unwrap();
+    let save_path = temp_dir.path().join("preview_data.json");
+
+    manager
+      .set_placeholder_hash(
+        "photo",
+        Path::new("/test/photo.jpg"),
+        "LyHV9Z2swxX8".to_string(),
+      )
+      .await;
+    manager.save_to_file(&save_path).await.unwrap();
+    manager.data.write().await.clear();
+    manager.load_from_file(&save_path).await.unwrap();
+
+    let preview_data = manager.get_preview_data("photo").await.unwrap();
+    assert_eq!(preview_data.file_path, PathBuf::from("/test/photo.jpg"));
+    assert_eq!(
+      preview_data.placeholder_hash.as_deref(),
+      Some("LyHV9Z2swxX8")
+    );
+  }
+
   #[tokio::test]
   async fn test_load_from_nonexistent_file() {
     let manager = create_test_manager().await;
@@ -1019,6 +1539,31 @@ mod tests {
     // Ошибка также допустима
   }
 
+  #[tokio::test]
+  async fn test_relink_file() {
+    let manager = create_test_manager().await;
+    let old_path = PathBuf::from("/old/video.mp4");
+
+    manager.data.write().await.insert(
+      "file_1".to_string(),
+      MediaPreviewData::new("file_1".to_string(), old_path.clone()),
+    );
+
+    let updated = manager
+      .relink_file(&old_path, Path::new("/new/video.mp4"))
+      .await;
+    assert_eq!(updated, 1);
+
+    let data = manager.get_preview_data("file_1").await.unwrap();
+    assert_eq!(data.file_path, PathBuf::from("/new/video.mp4"));
+    assert_eq!(
+      manager
+        .relink_file(&old_path, Path::new("/other.mp4"))
+        .await,
+      0
+    );
+  }
+
   #[tokio::test]
   async fn test_concurrent_write_operations() {
     let manager = Arc::new(create_test_manager().await);
@@ -1185,4 +1730,265 @@ mod tests {
       }
     }
   }
+
+  fn fake_recognition() -> RecognitionOutput {
+    use crate::recognition::types::{BoundingBox, DetectedObject, DetectedScene};
+
+    RecognitionOutput {
+      objects: vec![DetectedObject {
+        class: "person".to_string(),
+        confidence: 0.9,
+        timestamps: vec![1.0],
+        bounding_boxes: vec![BoundingBox {
+          x: 0.1,
+          y: 0.2,
+          width: 0.3,
+          height: 0.4,
+        }],
+      }],
+      scenes: vec![DetectedScene {
+        scene_type: "outdoor".to_string(),
+        start_time: 0.0,
+        end_time: 2.0,
+        key_objects: vec!["person".to_string()],
+      }],
+      ..Default::default()
+    }
+  }
+
+  #[tokio::test]
+  async fn test_combined_preview_and_recognition_payload() {
+    let temp_dir = tempdir().unwrap();
+    let manager = PreviewDataManager::new(temp_dir.path().to_path_buf());
+
+    let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let sink_events = events.clone();
+    manager.set_event_sink(Arc::new(move |event| {
+      sink_events.lock().unwrap().push(event);
+    }));
+
+    let frames = [1.0, 0.0]
+      .iter()
+      .map(|&timestamp| CompilerFrame {
+        timestamp,
+        frame_data: vec![0xFF, 0xD8, 0xFF, 0xD9],
+        width: 320,
+        height: 180,
+      })
+      .collect();
+    let attached = manager
+      .attach_timeline_frames("clip-1", frames)
+      .await
+      .unwrap();
+    assert_eq!(attached.len(), 2);
+    assert!(attached.iter().all(|preview| preview.path.exists()));
+
+    manager
+      .attach_recognition("clip-1", fake_recognition())
+      .await;
+
+    let payload = serde_json::to_value(manager.get_preview_data("clip-1").await.unwrap()).unwrap();
+    assert_eq!(payload["file_id"], "clip-1");
+
+    let previews = payload["timeline_previews"].as_array().unwrap();
+    assert_eq!(previews.len(), 2);
+    assert_eq!(previews[0]["timestamp"], 0.0);
+    assert_eq!(previews[0]["base64_data"], "/9j/2Q==");
+
+    let recognition = &payload["recognition_results"];
+    assert_eq!(recognition["objects"][0]["class"], "person");
+    assert_eq!(
+      recognition["objects"][0]["bounding_boxes"][0]["width"],
+      0.3f32 as f64
+    );
+    assert_eq!(recognition["scenes"][0]["scene_type"], "outdoor");
+    assert!(recognition["faces"].as_array().unwrap().is_empty());
+    assert!(recognition.get("config").is_none());
+
+    // События содержат путь к кэшу вместо байтов кадра
+    let events = events.lock().unwrap();
+    assert_eq!(events.len(), 2);
+    for event in events.iter() {
+      let event = serde_json::to_value(event).unwrap();
+      assert_eq!(event["type"], "PreviewGenerated");
+      assert_eq!(event["file_id"], "clip-1");
+      assert!(event.get("image_data").is_none());
+      assert!(Path::new(event["cache_path"].as_str().unwrap()).exists());
+    }
+  }
+
+  #[tokio::test]
+  async fn test_attach_recognition_without_previews() {
+    let temp_dir = tempdir().unwrap();
+    let manager = PreviewDataManager::new(temp_dir.path().to_path_buf());
+
+    manager
+      .attach_recognition("audio-only", fake_recognition())
+      .await;
+
+    let data = manager.get_preview_data("audio-only").await.unwrap();
+    assert!(data.timeline_previews.is_empty());
+    assert_eq!(data.recognition_results.unwrap().objects.len(), 1);
+  }
+
+  /// Генератор кадров для тестов: считает запрошенные метки
+  fn counting_generator(
+    generated: Arc<std::sync::Mutex<Vec<f64>>>,
+  ) -> impl Fn(Vec<f64>) -> std::pin::Pin<Box<dyn Future<Output = Result<Vec<Vec<u8>>>> + Send>> {
+    move |timestamps: Vec<f64>| {
+      let generated = generated.clone();
+      Box::pin(async move {
+        // Имитация работы FFmpeg, чтобы запросы пересекались во времени
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        generated.lock().unwrap().extend(timestamps.iter().copied());
+        Ok(
+          timestamps
+            .iter()
+            .map(|_| vec![0xFF, 0xD8, 0xFF, 0xD9])
+            .collect(),
+        )
+      })
+    }
+  }
+
+  #[tokio::test]
+  async fn test_retrim_regenerates_only_invalidated_frames() {
+    let temp_dir = tempdir().unwrap();
+    let manager = PreviewDataManager::new(temp_dir.path().to_path_buf());
+    let generated = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let generate = counting_generator(generated.clone());
+
+    // 50 кадров на 10 секунд клипа
+    let timestamps: Vec<f64> = (0..50).map(|i| i as f64 * 0.2).collect();
+    let frames = manager
+      .get_or_generate_timeline_frames("clip", &timestamps, (160, 90), &generate)
+      .await
+      .unwrap();
+    assert_eq!(frames.len(), 50);
+    assert!(frames.iter().all(|frame| frame.path.exists()));
+    assert_eq!(generated.lock().unwrap().len(), 50);
+
+    // Повторный запрос полностью берется из индекса
+    generated.lock().unwrap().clear();
+    manager
+      .get_or_generate_timeline_frames("clip", &timestamps, (160, 90), &generate)
+      .await
+      .unwrap();
+    assert!(generated.lock().unwrap().is_empty());
+
+    // Обрезка изменила последние 2 секунды клипа
+    let invalidated = manager
+      .invalidate_frames_in_range("clip", 8.0, 10.0)
+      .await
+      .unwrap();
+    assert_eq!(invalidated, 10);
+    assert_eq!(
+      manager
+        .get_frames_in_range("clip", 0.0, 10.0, 100)
+        .await
+        .len(),
+      40
+    );
+
+    let frames = manager
+      .get_or_generate_timeline_frames("clip", &timestamps, (160, 90), &generate)
+      .await
+      .unwrap();
+    assert_eq!(frames.len(), 50);
+    let regenerated = generated.lock().unwrap().clone();
+    assert_eq!(regenerated.len(), 10);
+    assert!(regenerated.iter().all(|&t| t >= 8.0));
+
+    let data = manager.get_preview_data("clip").await.unwrap();
+    assert_eq!(data.timeline_previews.len(), 50);
+  }
+
+  #[tokio::test]
+  async fn test_frame_index_persists_across_managers() {
+    let temp_dir = tempdir().unwrap();
+    let generated = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let generate = counting_generator(generated.clone());
+    let timestamps = [0.0, 1.0, 2.0, 3.0];
+
+    let manager = PreviewDataManager::new(temp_dir.path().to_path_buf());
+    manager
+      .get_or_generate_timeline_frames("clip", &timestamps, (160, 90), &generate)
+      .await
+      .unwrap();
+
+    // Новый менеджер читает индекс с диска и не генерирует кадры заново
+    let manager = PreviewDataManager::new(temp_dir.path().to_path_buf());
+    let frames = manager.get_frames_in_range("clip", 0.5, 3.0, 2).await;
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0].timestamp, 1.0);
+
+    generated.lock().unwrap().clear();
+    manager
+      .get_or_generate_timeline_frames("clip", &timestamps, (160, 90), &generate)
+      .await
+      .unwrap();
+    assert!(generated.lock().unwrap().is_empty());
+
+    // Другое разрешение требует новых кадров
+    manager
+      .get_or_generate_timeline_frames("clip", &[0.0], (320, 180), &generate)
+      .await
+      .unwrap();
+    assert_eq!(*generated.lock().unwrap(), vec![0.0]);
+  }
+
+  #[tokio::test]
+  async fn test_overlapping_requests_do_not_duplicate_frames() {
+    let temp_dir = tempdir().unwrap();
+    let manager = Arc::new(PreviewDataManager::new(temp_dir.path().to_path_buf()));
+    let generated = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+    let first: Vec<f64> = (0..20).map(|i| i as f64 * 0.5).collect();
+    let second: Vec<f64> = (10..30).map(|i| i as f64 * 0.5).collect();
+
+    let tasks: Vec<_> = [first, second]
+      .into_iter()
+      .map(|timestamps| {
+        let manager = manager.clone();
+        let generate = counting_generator(generated.clone());
+        tokio::spawn(async move {
+          manager
+            .get_or_generate_timeline_frames("clip", &timestamps, (160, 90), generate)
+            .await
+            .unwrap()
+        })
+      })
+      .collect();
+    for task in tasks {
+      assert_eq!(task.await.unwrap().len(), 20);
+    }
+
+    // Пересечение из 10 кадров сгенерировано один раз
+    let mut generated = generated.lock().unwrap().clone();
+    assert_eq!(generated.len(), 30);
+    generated.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    generated.dedup();
+    assert_eq!(generated.len(), 30);
+  }
+
+  #[tokio::test]
+  async fn test_read_cached_image_stays_inside_base_dir() {
+    let temp_dir = tempdir().unwrap();
+    let manager = PreviewDataManager::new(temp_dir.path().join("base"));
+
+    let inside = temp_dir
+      .path()
+      .join("base/Caches/preview/browser/thumb.jpg");
+    tokio::fs::create_dir_all(inside.parent().unwrap())
+      .await
+      .unwrap();
+    tokio::fs::write(&inside, b"jpeg").await.unwrap();
+    assert_eq!(manager.read_cached_image(&inside).await.unwrap(), b"jpeg");
+
+    let outside = temp_dir.path().join("secret.txt");
+    tokio::fs::write(&outside, b"secret").await.unwrap();
+    assert!(manager.read_cached_image(&outside).await.is_err());
+    let escaped = temp_dir.path().join("base/../secret.txt");
+    assert!(manager.read_cached_image(&escaped).await.is_err());
+  }
 }
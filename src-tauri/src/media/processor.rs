@@ -1,18 +1,30 @@
 // Модуль для асинхронной обработки медиафайлов
 
 use crate::media::ffmpeg::extract_frame;
+use crate::media::fingerprint::{compute_fingerprint, FingerprintMode, MediaFingerprint};
+use crate::media::incremental_scan::{
+  detect_changes, walk_media_files, walk_media_files_until, FolderScanOptions, ManifestEntry,
+  ScanManifest, ScannedFile,
+};
+use crate::media::media_registry::MediaRegistryState;
 use crate::media::metadata::get_media_metadata;
-use crate::media::types::{MediaFile, SUPPORTED_EXTENSIONS};
+use crate::media::photo_decoder::open_image;
+use crate::media::preview_manager::PreviewDataManager;
+use crate::media::scan_jobs::{MediaScanJob, MediaScanProgress, MEDIA_SCAN_EVENT};
+use crate::media::thumbnail_encoding::{encode_thumbnail, placeholder_hash, ThumbnailFormat};
+use crate::media::types::MediaFile;
 use base64::Engine;
-use image::{DynamicImage, GenericImageView, ImageFormat};
+use image::{DynamicImage, GenericImageView};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, Manager};
 use tokio::fs;
-use tokio::sync::{mpsc, Semaphore};
+use tokio::sync::Semaphore;
 use tokio::task::JoinSet;
-use uuid::Uuid;
+use tokio_util::sync::CancellationToken;
 
 /// События для отправки через Tauri
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,6 +47,12 @@ pub enum ProcessorEvent {
     file_path: String,
     thumbnail_path: String,
     thumbnail_data: Option<String>, // Base64
+    /// Blurhash-заглушка, если запрошена в параметрах превью
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    placeholder_hash: Option<String>,
+    /// Превью не уложилось в `max_file_size_kb`
+    #[serde(default)]
+    exceeds_size_cap: bool,
   },
   /// Ошибка обработки
   ProcessingError {
@@ -44,6 +62,24 @@ pub enum ProcessorEvent {
   },
   /// Прогресс сканирования
   ScanProgress { current: usize, total: usize },
+  /// Прогресс пакетной генерации превью при наведении
+  HoverPreviewProgress {
+    current: usize,
+    total: usize,
+    file_id: String,
+    file_path: String,
+    preview_path: Option<String>,
+    error: Option<String>,
+  },
+  /// Изменения, найденные инкрементальным сканированием
+  ChangesDetected {
+    new: usize,
+    modified: usize,
+    removed: usize,
+    unchanged: usize,
+  },
+  /// Прогресс применения исправлений совместимости (0-100)
+  AutofixProgress { file_path: String, progress: f64 },
 }
 
 /// Обнаруженный файл
@@ -56,13 +92,57 @@ pub struct DiscoveredFile {
   pub size: u64,
 }
 
+/// Файл, содержимое которого уже есть в реестре
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KnownMediaFile {
+  /// Путь, найденный при сканировании
+  pub path: String,
+  /// Путь, под которым содержимое было зарегистрировано ранее
+  pub known_path: String,
+  /// ID ранее обработанного файла
+  pub file_id: Option<String>,
+  pub fingerprint: MediaFingerprint,
+}
+
+/// Результат сканирования папки
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MediaScanResult {
+  /// Новые файлы (обработаны)
+  pub new_files: Vec<MediaFile>,
+  /// Уже известные файлы (обработка пропущена)
+  pub known_files: Vec<KnownMediaFile>,
+}
+
+/// Результат инкрементального сканирования папки
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IncrementalScanResult {
+  /// Новые файлы (обработаны)
+  pub new_files: Vec<MediaFile>,
+  /// Измененные файлы (обработаны заново, прежние превью удалены)
+  pub modified_files: Vec<MediaFile>,
+  /// Пути исчезнувших файлов (помечены в реестре как отсутствующие)
+  pub removed_files: Vec<String>,
+  /// Количество файлов без изменений
+  pub unchanged: usize,
+  /// Манифест отсутствовал или устарел, папка просканирована полностью
+  pub full_rescan: bool,
+}
+
 /// Параметры для генерации превью
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ThumbnailOptions {
   pub width: u32,
   pub height: u32,
-  pub format: ImageFormat,
-  pub _quality: u8,
+  pub format: ThumbnailFormat,
+  /// Качество кодирования (1-100)
+  pub quality: u8,
+  /// Прогрессивный JPEG (для WebP и AVIF не используется)
+  pub progressive: bool,
+  /// Лимит размера файла превью; качество понижается, пока превью не уложится
+  pub max_file_size_kb: Option<u32>,
+  /// Вычислить blurhash-заглушку для мгновенной отрисовки в UI
+  pub placeholder_hash: bool,
   pub time_offset: f64, // Для видео - время в секундах
 }
 
@@ -71,8 +151,11 @@ impl Default for ThumbnailOptions {
     Self {
       width: 320,
       height: 180,
-      format: ImageFormat::Jpeg,
-      _quality: 85,
+      format: ThumbnailFormat::Jpeg,
+      quality: 85,
+      progressive: false,
+      max_file_size_kb: None,
+      placeholder_hash: false,
       time_offset: 1.0, // 1 секунда от начала
     }
   }
@@ -100,14 +183,56 @@ impl<R: tauri::Runtime> MediaProcessor<R> {
     &self,
     folder_path: &Path,
     thumbnail_options: Option<ThumbnailOptions>,
-  ) -> Result<Vec<MediaFile>, String> {
+  ) -> Result<MediaScanResult, String> {
+    let job = Arc::new(MediaScanJob::new(folder_path));
+    self.run_scan(&job, thumbnail_options).await?;
+    Ok(job.snapshot().result)
+  }
+
+  /// Выполняет задачу сканирования папки.
+  ///
+  /// Прогресс отправляется событием `media-scan`, обработанные файлы сразу
+  /// попадают в результаты задачи. После отмены обход папки и обработка
+  /// файлов останавливаются, а задача сохраняет частичные результаты.
+  pub async fn run_scan(
+    &self,
+    job: &Arc<MediaScanJob>,
+    thumbnail_options: Option<ThumbnailOptions>,
+  ) -> Result<(), String> {
+    self.emit_scan_progress(job.start());
+    let outcome = self
+      .scan_job_files(job, thumbnail_options.unwrap_or_default())
+      .await;
+    self.emit_scan_progress(job.finish(outcome.as_ref().err().cloned()));
+    outcome
+  }
+
+  async fn scan_job_files(
+    &self,
+    job: &Arc<MediaScanJob>,
+    thumbnail_options: ThumbnailOptions,
+  ) -> Result<(), String> {
     // Создаем директорию для превью, если не существует
     fs::create_dir_all(&self.thumbnail_dir)
       .await
       .map_err(|e| format!("Failed to create thumbnail directory: {e}"))?;
 
-    // Сканируем папку
-    let discovered_files = self.scan_folder(folder_path).await?;
+    // Сканируем папку и отделяем уже известные файлы
+    let cancel = job.cancel_token();
+    let scanned = walk_media_files_until(
+      job.folder_path(),
+      &FolderScanOptions::default(),
+      cancel,
+      |scanned| self.emit_scan_progress(job.file_discovered(&scanned.file.path)),
+    )
+    .await?;
+    let discovered_files = scanned.into_iter().map(|scanned| scanned.file).collect();
+    let (discovered_files, known_files, mut fingerprints) =
+      self.partition_known_files(discovered_files, cancel).await;
+    job.files_known(known_files);
+    if cancel.is_cancelled() {
+      return Ok(());
+    }
     let total_files = discovered_files.len();
 
     // Отправляем событие о найденных файлах
@@ -116,105 +241,315 @@ impl<R: tauri::Runtime> MediaProcessor<R> {
       total: total_files,
     })?;
 
-    // Обрабатываем файлы параллельно
-    let semaphore = Arc::new(Semaphore::new(self.max_concurrent_tasks));
-    let mut join_set = JoinSet::new();
-    let mut processed_files = Vec::new();
-    let thumbnail_opts = thumbnail_options.unwrap_or_default();
-
-    let (tx, mut rx) = mpsc::channel::<Result<MediaFile, String>>(100);
-
-    // Запускаем задачи обработки
-    for (index, file) in discovered_files.into_iter().enumerate() {
-      let app_handle = self.app_handle.clone();
-      let thumbnail_dir = self.thumbnail_dir.clone();
-      let thumbnail_opts = thumbnail_opts.clone();
-      let semaphore = semaphore.clone();
-      let tx = tx.clone();
-
-      join_set.spawn(async move {
-        let _permit = semaphore.acquire().await.unwrap();
-
-        // Отправляем прогресс
-        let _ = app_handle.emit(
-          "media-processor",
-          ProcessorEvent::ScanProgress {
-            current: index + 1,
-            total: total_files,
+    let processed_files = self
+      .process_files(discovered_files, thumbnail_options, Some(job))
+      .await;
+
+    // Регистрируем обработанные файлы в реестре
+    if let Some(state) = self.app_handle.try_state::<MediaRegistryState>() {
+      let mut registry = state.registry.write().await;
+      for media_file in &processed_files {
+        if let Some(fingerprint) = fingerprints.remove(&media_file.path) {
+          registry.register(
+            media_file.path.clone(),
+            Some(media_file.id.clone()),
+            fingerprint,
+          );
+        }
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Инкрементально сканирует папку.
+  ///
+  /// Манифест папки в `manifest_dir` хранит размер, время изменения и
+  /// отпечаток каждого обработанного файла. Метаданные и превью строятся
+  /// только для новых и измененных файлов; у измененных удаляются прежние
+  /// превью, исчезнувшие файлы помечаются в реестре как отсутствующие.
+  pub async fn scan_incremental(
+    &self,
+    folder_path: &Path,
+    manifest_dir: &Path,
+    options: FolderScanOptions,
+    thumbnail_options: Option<ThumbnailOptions>,
+  ) -> Result<IncrementalScanResult, String> {
+    fs::create_dir_all(&self.thumbnail_dir)
+      .await
+      .map_err(|e| format!("Failed to create thumbnail directory: {e}"))?;
+
+    let manifest_path = ScanManifest::path_for(manifest_dir, folder_path);
+    let previous = ScanManifest::load(&manifest_path, folder_path).await;
+    let full_rescan = previous.is_none();
+    let mut manifest = previous.unwrap_or_else(|| ScanManifest::new(folder_path));
+
+    let scanned = walk_media_files(folder_path, &options).await?;
+    let changes = detect_changes(&manifest, scanned);
+    let mode = self.fingerprint_mode().await;
+
+    // Файлы с новым временем изменения, но прежним содержимым не обрабатываются
+    let mut unchanged = changes.unchanged.len();
+    let mut modified = Vec::new();
+    for (scanned, entry) in changes.changed_files {
+      let fingerprint = compute_fingerprint(Path::new(&scanned.file.path), mode)
+        .await
+        .ok();
+      if fingerprint.is_some() && fingerprint == entry.fingerprint {
+        manifest.entries.insert(
+          scanned.file.path.clone(),
+          ManifestEntry {
+            size: scanned.file.size,
+            modified_ns: scanned.modified_ns,
+            ..entry
           },
         );
+        unchanged += 1;
+      } else {
+        self.invalidate_cached_data(&entry).await;
+        manifest.entries.remove(&scanned.file.path);
+        modified.push((scanned, fingerprint));
+      }
+    }
 
-        // Обрабатываем файл
-        let result = process_single_file(&app_handle, file, &thumbnail_dir, &thumbnail_opts).await;
+    let mut new = Vec::new();
+    for scanned in changes.new_files {
+      let fingerprint = compute_fingerprint(Path::new(&scanned.file.path), mode)
+        .await
+        .ok();
+      new.push((scanned, fingerprint));
+    }
 
-        let _ = tx.send(result).await;
-      });
+    for path in &changes.removed {
+      manifest.entries.remove(path);
+    }
+    if let Some(state) = self.app_handle.try_state::<MediaRegistryState>() {
+      let mut registry = state.registry.write().await;
+      for path in &changes.removed {
+        registry.mark_missing(path);
+      }
     }
 
-    // Закрываем отправитель
-    drop(tx);
+    self.emit_event(ProcessorEvent::ChangesDetected {
+      new: new.len(),
+      modified: modified.len(),
+      removed: changes.removed.len(),
+      unchanged,
+    })?;
 
-    // Собираем результаты
-    while let Some(result) = rx.recv().await {
-      match result {
-        Ok(media_file) => processed_files.push(media_file),
-        Err(e) => eprintln!("Error processing file: {e}"),
+    // Обрабатываем только новые и измененные файлы
+    let mut pending: HashMap<String, (ScannedFile, Option<MediaFingerprint>, bool)> =
+      HashMap::new();
+    let mut to_process = Vec::new();
+    for (scanned, fingerprint, is_new) in new
+      .into_iter()
+      .map(|(scanned, fingerprint)| (scanned, fingerprint, true))
+      .chain(
+        modified
+          .into_iter()
+          .map(|(scanned, fingerprint)| (scanned, fingerprint, false)),
+      )
+    {
+      to_process.push(scanned.file.clone());
+      pending.insert(scanned.file.path.clone(), (scanned, fingerprint, is_new));
+    }
+
+    self.emit_event(ProcessorEvent::FilesDiscovered {
+      total: to_process.len(),
+      files: to_process.clone(),
+    })?;
+
+    let processed_files = self
+      .process_files(to_process, thumbnail_options.unwrap_or_default(), None)
+      .await;
+
+    let mut result = IncrementalScanResult {
+      removed_files: changes.removed,
+      unchanged,
+      full_rescan,
+      ..Default::default()
+    };
+    let registry_state = self.app_handle.try_state::<MediaRegistryState>();
+    for media_file in processed_files {
+      let Some((scanned, fingerprint, is_new)) = pending.remove(&media_file.path) else {
+        continue;
+      };
+
+      if let (Some(state), Some(fingerprint)) = (&registry_state, &fingerprint) {
+        state.registry.write().await.register(
+          media_file.path.clone(),
+          Some(media_file.id.clone()),
+          fingerprint.clone(),
+        );
+      }
+
+      manifest.entries.insert(
+        media_file.path.clone(),
+        ManifestEntry {
+          file_id: media_file.id.clone(),
+          thumbnail_id: scanned.file.id,
+          size: scanned.file.size,
+          modified_ns: scanned.modified_ns,
+          fingerprint,
+        },
+      );
+
+      if is_new {
+        result.new_files.push(media_file);
+      } else {
+        result.modified_files.push(media_file);
       }
     }
 
-    // Ждем завершения всех задач
-    while join_set.join_next().await.is_some() {}
+    // Файлы, которые не удалось обработать, остаются вне манифеста и
+    // обрабатываются при следующем сканировании
+    manifest.save(&manifest_path).await?;
+    Ok(result)
+  }
 
-    Ok(processed_files)
+  /// Режим отпечатков из реестра (быстрый, если реестр недоступен)
+  async fn fingerprint_mode(&self) -> FingerprintMode {
+    match self.app_handle.try_state::<MediaRegistryState>() {
+      Some(state) => *state.fingerprint_mode.read().await,
+      None => FingerprintMode::default(),
+    }
   }
 
-  /// Сканирует папку и возвращает список медиафайлов
-  async fn scan_folder(&self, folder_path: &Path) -> Result<Vec<DiscoveredFile>, String> {
-    let mut discovered_files = Vec::new();
-    let mut dirs_to_scan = vec![folder_path.to_path_buf()];
+  /// Удаляет превью и кэшированные данные прежней версии файла
+  async fn invalidate_cached_data(&self, entry: &ManifestEntry) {
+    for format in ThumbnailFormat::ALL {
+      let thumbnail_name = format!("{}.{}", entry.thumbnail_id, format.extension());
+      let thumbnail_path = self.thumbnail_dir.join(thumbnail_name);
+      if let Err(e) = fs::remove_file(&thumbnail_path).await {
+        if e.kind() != std::io::ErrorKind::NotFound {
+          log::warn!("Failed to remove stale thumbnail {thumbnail_path:?}: {e}");
+        }
+      }
+    }
 
-    while let Some(dir) = dirs_to_scan.pop() {
-      let mut entries = fs::read_dir(&dir)
-        .await
-        .map_err(|e| format!("Failed to read directory {dir:?}: {e}"))?;
+    if let Some(manager) = self.app_handle.try_state::<Arc<PreviewDataManager>>() {
+      if let Err(e) = manager.clear_file_data(&entry.file_id).await {
+        log::warn!("Failed to clear preview data for {}: {e}", entry.file_id);
+      }
+    }
+  }
 
-      while let Some(entry) = entries
-        .next_entry()
-        .await
-        .map_err(|e| format!("Failed to read entry: {e}"))?
-      {
-        let path = entry.path();
-        let metadata = entry
-          .metadata()
-          .await
-          .map_err(|e| format!("Failed to read metadata: {e}"))?;
-
-        if metadata.is_dir() {
-          dirs_to_scan.push(path);
-        } else if metadata.is_file() {
-          if let Some(extension) = path.extension() {
-            let ext = extension.to_string_lossy().to_lowercase();
-            if SUPPORTED_EXTENSIONS.contains(&ext.as_str()) {
-              let file_name = path
-                .file_name()
-                .unwrap_or_default()
-                .to_string_lossy()
-                .to_string();
-
-              discovered_files.push(DiscoveredFile {
-                id: Uuid::new_v4().to_string(),
-                path: path.to_string_lossy().to_string(),
-                name: file_name,
-                extension: ext,
-                size: metadata.len(),
-              });
-            }
+  /// Параллельно обрабатывает файлы: метаданные и превью.
+  ///
+  /// Если передана задача сканирования, обработка останавливается по ее
+  /// токену отмены, а каждый обработанный файл учитывается в задаче.
+  async fn process_files(
+    &self,
+    discovered_files: Vec<DiscoveredFile>,
+    thumbnail_opts: ThumbnailOptions,
+    job: Option<&Arc<MediaScanJob>>,
+  ) -> Vec<MediaFile> {
+    let total_files = discovered_files.len();
+    let app_handle = self.app_handle.clone();
+    let thumbnail_dir = self.thumbnail_dir.clone();
+    let job = job.cloned();
+    let cancel = job
+      .as_ref()
+      .map(|job| job.cancel_token().clone())
+      .unwrap_or_default();
+
+    let task_cancel = cancel.clone();
+    process_concurrently(
+      discovered_files,
+      self.max_concurrent_tasks,
+      &cancel,
+      move |index, file| {
+        let app_handle = app_handle.clone();
+        let thumbnail_dir = thumbnail_dir.clone();
+        let thumbnail_opts = thumbnail_opts.clone();
+        let cancel = task_cancel.clone();
+        let job = job.clone();
+
+        async move {
+          // Отправляем прогресс
+          let _ = app_handle.emit(
+            "media-processor",
+            ProcessorEvent::ScanProgress {
+              current: index + 1,
+              total: total_files,
+            },
+          );
+
+          // Обрабатываем файл
+          let file_path = file.path.clone();
+          let result =
+            process_single_file(&app_handle, file, &thumbnail_dir, &thumbnail_opts, &cancel).await;
+
+          if let Some(job) = &job {
+            let progress = job.file_processed(&file_path, result.as_ref().ok());
+            let _ = app_handle.emit(MEDIA_SCAN_EVENT, progress);
           }
+          result
+        }
+      },
+    )
+    .await
+  }
+
+  /// Отделяет файлы, содержимое которых уже есть в реестре.
+  ///
+  /// Возвращает новые файлы, известные файлы и отпечатки новых файлов по пути.
+  /// Если реестр недоступен или отпечаток не удалось вычислить, файл
+  /// считается новым. После отмены оставшиеся файлы не проверяются.
+  async fn partition_known_files(
+    &self,
+    files: Vec<DiscoveredFile>,
+    cancel: &CancellationToken,
+  ) -> (
+    Vec<DiscoveredFile>,
+    Vec<KnownMediaFile>,
+    HashMap<String, MediaFingerprint>,
+  ) {
+    let Some(state) = self.app_handle.try_state::<MediaRegistryState>() else {
+      return (files, Vec::new(), HashMap::new());
+    };
+    let mode = *state.fingerprint_mode.read().await;
+
+    let mut new_files = Vec::new();
+    let mut known_files = Vec::new();
+    let mut fingerprints = HashMap::new();
+
+    for file in files {
+      if cancel.is_cancelled() {
+        break;
+      }
+
+      let fingerprint = match compute_fingerprint(Path::new(&file.path), mode).await {
+        Ok(fingerprint) => fingerprint,
+        Err(e) => {
+          log::warn!("Failed to fingerprint {}: {e}", file.path);
+          new_files.push(file);
+          continue;
+        }
+      };
+
+      let known = state
+        .registry
+        .read()
+        .await
+        .find_by_fingerprint(&fingerprint)
+        .cloned();
+
+      match known {
+        Some(entry) => known_files.push(KnownMediaFile {
+          path: file.path,
+          known_path: entry.path,
+          file_id: entry.file_id,
+          fingerprint,
+        }),
+        None => {
+          fingerprints.insert(file.path.clone(), fingerprint);
+          new_files.push(file);
         }
       }
     }
 
-    Ok(discovered_files)
+    (new_files, known_files, fingerprints)
   }
 
   /// Отправляет событие через Tauri
@@ -224,6 +559,61 @@ impl<R: tauri::Runtime> MediaProcessor<R> {
       .emit("media-processor", event)
       .map_err(|e| format!("Failed to emit event: {e}"))
   }
+
+  /// Отправляет прогресс задачи сканирования
+  fn emit_scan_progress(&self, progress: MediaScanProgress) {
+    let _ = self.app_handle.emit(MEDIA_SCAN_EVENT, progress);
+  }
+}
+
+/// Параллельно выполняет `process` для файлов, не больше `max_concurrent`
+/// одновременно.
+///
+/// После отмены файлы, ожидающие очереди, не запускаются; результаты уже
+/// начатых собираются. Ошибки обработки пропускаются.
+pub async fn process_concurrently<F, Fut>(
+  files: Vec<DiscoveredFile>,
+  max_concurrent: usize,
+  cancel: &CancellationToken,
+  process: F,
+) -> Vec<MediaFile>
+where
+  F: Fn(usize, DiscoveredFile) -> Fut + Send + Sync + 'static,
+  Fut: Future<Output = Result<MediaFile, String>> + Send + 'static,
+{
+  let process = Arc::new(process);
+  let semaphore = Arc::new(Semaphore::new(max_concurrent));
+  let mut join_set = JoinSet::new();
+
+  for (index, file) in files.into_iter().enumerate() {
+    let process = process.clone();
+    let semaphore = semaphore.clone();
+    let cancel = cancel.clone();
+
+    join_set.spawn(async move {
+      let _permit = tokio::select! {
+        biased;
+        _ = cancel.cancelled() => return None,
+        permit = semaphore.acquire_owned() => permit.ok()?,
+      };
+      if cancel.is_cancelled() {
+        return None;
+      }
+      Some(process(index, file).await)
+    });
+  }
+
+  // Собираем результаты в порядке завершения
+  let mut processed_files = Vec::new();
+  while let Some(joined) = join_set.join_next().await {
+    match joined {
+      Ok(Some(Ok(media_file))) => processed_files.push(media_file),
+      Ok(Some(Err(e))) => eprintln!("Error processing file: {e}"),
+      Ok(None) => {}
+      Err(e) => log::warn!("Media processing task failed: {e}"),
+    }
+  }
+  processed_files
 }
 
 /// Обрабатывает один файл
@@ -232,6 +622,7 @@ async fn process_single_file<R: tauri::Runtime>(
   file: DiscoveredFile,
   thumbnail_dir: &Path,
   thumbnail_options: &ThumbnailOptions,
+  cancel: &CancellationToken,
 ) -> Result<MediaFile, String> {
   let file_path = Path::new(&file.path);
 
@@ -263,23 +654,36 @@ async fn process_single_file<R: tauri::Runtime>(
     }
   };
 
-  // Генерируем превью для поддерживаемых типов
+  // Генерируем превью для поддерживаемых типов; файлы без декодера
+  // остаются в списке без превью
   let is_video = media_file.is_video;
   let is_image = media_file.is_image;
 
-  if is_video || is_image {
+  // После отмены превью не строится, метаданные остаются в результатах
+  if (is_video || is_image) && media_file.unsupported_reason.is_none() && !cancel.is_cancelled() {
     let thumbnail_result =
       generate_thumbnail(&file, file_path, thumbnail_dir, thumbnail_options, is_video).await;
 
-    if let Ok((thumbnail_path, thumbnail_data)) = thumbnail_result {
+    if let Ok(thumbnail) = thumbnail_result {
+      // Заглушка сохраняется вместе с остальными данными превью файла
+      if let Some(hash) = &thumbnail.placeholder_hash {
+        if let Some(manager) = app_handle.try_state::<Arc<PreviewDataManager>>() {
+          manager
+            .set_placeholder_hash(&file.id, file_path, hash.clone())
+            .await;
+        }
+      }
+
       // Отправляем событие о готовом превью
       let _ = app_handle.emit(
         "media-processor",
         ProcessorEvent::ThumbnailReady {
           file_id: file.id.clone(),
           file_path: file.path.clone(),
-          thumbnail_path: thumbnail_path.to_string_lossy().to_string(),
-          thumbnail_data,
+          thumbnail_path: thumbnail.path.to_string_lossy().to_string(),
+          thumbnail_data: Some(thumbnail.base64_data),
+          placeholder_hash: thumbnail.placeholder_hash,
+          exceeds_size_cap: thumbnail.exceeds_size_cap,
         },
       );
     }
@@ -288,6 +692,14 @@ async fn process_single_file<R: tauri::Runtime>(
   Ok(media_file)
 }
 
+/// Готовое превью файла
+struct GeneratedThumbnail {
+  path: PathBuf,
+  base64_data: String,
+  placeholder_hash: Option<String>,
+  exceeds_size_cap: bool,
+}
+
 /// Генерирует превью для файла
 async fn generate_thumbnail(
   file: &DiscoveredFile,
@@ -295,46 +707,48 @@ async fn generate_thumbnail(
   thumbnail_dir: &Path,
   options: &ThumbnailOptions,
   is_video: bool,
-) -> Result<(PathBuf, Option<String>), String> {
-  let thumbnail_filename = format!("{}.jpg", file.id);
-  let thumbnail_path = thumbnail_dir.join(&thumbnail_filename);
+) -> Result<GeneratedThumbnail, String> {
+  // Кадр видео извлекается во временный JPEG и заменяется превью нужного формата
+  let frame_path = thumbnail_dir.join(format!("{}.frame.jpg", file.id));
 
   let image = if is_video {
     // Извлекаем кадр из видео
     extract_frame(
       file_path.to_str().unwrap(),
-      thumbnail_path.to_str().unwrap(),
+      frame_path.to_str().unwrap(),
       options.time_offset,
     )
     .map_err(|e| format!("Failed to extract frame: {e}"))?;
 
     // Загружаем и изменяем размер
-    image::open(&thumbnail_path).map_err(|e| format!("Failed to open extracted frame: {e}"))?
+    let frame = image::open(&frame_path);
+    let _ = fs::remove_file(&frame_path).await;
+    frame.map_err(|e| format!("Failed to open extracted frame: {e}"))?
   } else {
-    // Загружаем изображение
-    image::open(file_path).map_err(|e| format!("Failed to open image: {e}"))?
+    // Загружаем изображение (HEIF/RAW через photo_decoder, с учетом ориентации)
+    open_image(file_path).map_err(|reason| format!("Failed to open image: {}", reason.message()))?
   };
 
   // Изменяем размер с сохранением пропорций
   let resized = resize_image(image, options.width, options.height);
 
-  // Сохраняем превью
-  resized
-    .save_with_format(&thumbnail_path, options.format)
+  // Кодируем и сохраняем превью; расширение соответствует фактическому формату
+  let encoded = encode_thumbnail(&resized, options)?;
+  let thumbnail_path = thumbnail_dir.join(format!("{}.{}", file.id, encoded.format.extension()));
+  fs::write(&thumbnail_path, &encoded.data)
+    .await
     .map_err(|e| format!("Failed to save thumbnail: {e}"))?;
 
-  // Опционально конвертируем в base64
-  let thumbnail_data = if options.format == ImageFormat::Jpeg {
-    let mut buffer = Vec::new();
-    resized
-      .write_to(&mut std::io::Cursor::new(&mut buffer), options.format)
-      .ok();
-    Some(base64::engine::general_purpose::STANDARD.encode(&buffer))
-  } else {
-    None
-  };
-
-  Ok((thumbnail_path, thumbnail_data))
+  Ok(GeneratedThumbnail {
+    path: thumbnail_path,
+    base64_data: base64::engine::general_purpose::STANDARD.encode(&encoded.data),
+    placeholder_hash: if options.placeholder_hash {
+      placeholder_hash(&resized)
+    } else {
+      None
+    },
+    exceeds_size_cap: encoded.exceeds_size_cap,
+  })
 }
 
 /// Изменяет размер изображения с сохранением пропорций
@@ -380,8 +794,10 @@ mod tests {
           size: Some(1024000),
           bit_rate: None,
           format_name: None,
+          ..Default::default()
         },
       },
+      unsupported_reason: None,
     }
   }
 
@@ -402,9 +818,27 @@ mod tests {
     let opts = ThumbnailOptions::default();
     assert_eq!(opts.width, 320);
     assert_eq!(opts.height, 180);
-    assert_eq!(opts._quality, 85);
+    assert_eq!(opts.quality, 85);
     assert_eq!(opts.time_offset, 1.0);
-    assert_eq!(opts.format, ImageFormat::Jpeg);
+    assert_eq!(opts.format, ThumbnailFormat::Jpeg);
+    assert!(!opts.progressive);
+    assert_eq!(opts.max_file_size_kb, None);
+    assert!(!opts.placeholder_hash);
+  }
+
+  #[test]
+  fn test_thumbnail_options_missing_fields_use_defaults() {
+    let opts: ThumbnailOptions = serde_json::from_str(r#"{"width": 160, "height": 90}"#).unwrap();
+    assert_eq!((opts.width, opts.height), (160, 90));
+    assert_eq!(opts.format, ThumbnailFormat::Jpeg);
+    assert_eq!(opts.quality, 85);
+    assert_eq!(opts.time_offset, 1.0);
+
+    let opts: ThumbnailOptions =
+      serde_json::from_str(r#"{"format": "webp", "max_file_size_kb": 12}"#).unwrap();
+    assert_eq!(opts.format, ThumbnailFormat::Webp);
+    assert_eq!(opts.max_file_size_kb, Some(12));
+    assert_eq!(opts.width, 320);
   }
 
   #[test]
@@ -412,15 +846,19 @@ mod tests {
     let opts = ThumbnailOptions {
       width: 640,
       height: 360,
-      format: ImageFormat::Png,
-      _quality: 95,
+      format: ThumbnailFormat::Webp,
+      quality: 95,
+      progressive: true,
+      max_file_size_kb: Some(16),
+      placeholder_hash: true,
       time_offset: 5.0,
     };
 
     assert_eq!(opts.width, 640);
     assert_eq!(opts.height, 360);
-    assert_eq!(opts.format, ImageFormat::Png);
-    assert_eq!(opts._quality, 95);
+    assert_eq!(opts.format, ThumbnailFormat::Webp);
+    assert_eq!(opts.quality, 95);
+    assert_eq!(opts.max_file_size_kb, Some(16));
     assert_eq!(opts.time_offset, 5.0);
   }
 
@@ -512,12 +950,15 @@ mod tests {
       file_path: "/media/video.mp4".to_string(),
       thumbnail_path: "/thumbs/thumb.jpg".to_string(),
       thumbnail_data: Some("base64data".to_string()),
+      placeholder_hash: Some("LyHV9Z2swxX8".to_string()),
+      exceeds_size_cap: false,
     };
 
     let json = serde_json::to_string(&event).unwrap();
     assert!(json.contains("ThumbnailReady"));
     assert!(json.contains("thumb-id"));
     assert!(json.contains("base64data"));
+    assert!(json.contains("LyHV9Z2swxX8"));
   }
 
   #[test]
@@ -527,12 +968,15 @@ mod tests {
       file_path: "/media/video.mp4".to_string(),
       thumbnail_path: "/thumbs/thumb.jpg".to_string(),
       thumbnail_data: None,
+      placeholder_hash: None,
+      exceeds_size_cap: false,
     };
 
     let json = serde_json::to_string(&event).unwrap();
     assert!(json.contains("ThumbnailReady"));
     assert!(json.contains("thumb-id"));
     assert!(json.contains("null"));
+    assert!(!json.contains("placeholder_hash"));
   }
 
   #[test]
@@ -562,6 +1006,21 @@ mod tests {
     assert!(json.contains("\"total\":10"));
   }
 
+  #[test]
+  fn test_processor_event_changes_detected() {
+    let event = ProcessorEvent::ChangesDetected {
+      new: 3,
+      modified: 1,
+      removed: 2,
+      unchanged: 40,
+    };
+
+    let json = serde_json::to_string(&event).unwrap();
+    assert!(json.contains("ChangesDetected"));
+    assert!(json.contains("\"modified\":1"));
+    assert!(json.contains("\"unchanged\":40"));
+  }
+
   #[test]
   fn test_processor_event_deserialization() {
     let json = r#"
@@ -818,22 +1277,15 @@ mod tests {
 
   #[test]
   fn test_image_format_detection() {
-    // Test format detection logic used in generate_thumbnail
+    // Расширение файла превью, которое использует generate_thumbnail
     let options = ThumbnailOptions::default();
-    assert_eq!(options.format, image::ImageFormat::Jpeg);
+    assert_eq!(options.format.extension(), "jpg");
 
-    let png_options = ThumbnailOptions {
-      format: image::ImageFormat::Png,
+    let webp_options = ThumbnailOptions {
+      format: ThumbnailFormat::Webp,
       ..Default::default()
     };
-    assert_eq!(png_options.format, image::ImageFormat::Png);
-
-    // Test base64 encoding condition
-    let should_encode_base64 = options.format == image::ImageFormat::Jpeg;
-    assert!(should_encode_base64);
-
-    let should_not_encode = png_options.format == image::ImageFormat::Jpeg;
-    assert!(!should_not_encode);
+    assert_eq!(webp_options.format.extension(), "webp");
   }
 
   #[test]
@@ -924,14 +1376,14 @@ mod tests {
     let opts = ThumbnailOptions {
       width: 1,
       height: 1,
-      format: ImageFormat::Png,
-      _quality: 0,
+      quality: 0,
       time_offset: 0.0,
+      ..Default::default()
     };
 
     assert_eq!(opts.width, 1);
     assert_eq!(opts.height, 1);
-    assert_eq!(opts._quality, 0);
+    assert_eq!(opts.quality, 0);
     assert_eq!(opts.time_offset, 0.0);
   }
 
@@ -940,14 +1392,15 @@ mod tests {
     let opts = ThumbnailOptions {
       width: u32::MAX,
       height: u32::MAX,
-      format: ImageFormat::Jpeg,
-      _quality: 100,
+      quality: 100,
+      max_file_size_kb: Some(u32::MAX),
       time_offset: f64::MAX,
+      ..Default::default()
     };
 
     assert_eq!(opts.width, u32::MAX);
     assert_eq!(opts.height, u32::MAX);
-    assert_eq!(opts._quality, 100);
+    assert_eq!(opts.quality, 100);
     assert_eq!(opts.time_offset, f64::MAX);
   }
 
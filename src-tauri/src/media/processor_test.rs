@@ -75,6 +75,7 @@ mod tests {
           width: Some(1920),
           height: Some(1080),
           display_aspect_ratio: Some("16:9".to_string()),
+          ..Default::default()
           r_frame_rate: Some("30/1".to_string()),
           bit_rate: Some("5000000".to_string()),
           channels: None,
@@ -85,8 +86,10 @@ mod tests {
           size: Some(1024000),
           bit_rate: Some("5000000".to_string()),
           format_name: Some("mov,mp4,m4a,3gp,3g2,mj2".to_string()),
+          ..Default::default()
         },
       },
+      unsupported_reason: None,
     };
 
     let event = ProcessorEvent::MetadataReady {
@@ -118,6 +121,8 @@ mod tests {
       file_path: "/path/to/test.mp4".to_string(),
       thumbnail_path: "/tmp/thumb_test-id.jpg".to_string(),
       thumbnail_data: Some("base64encodeddata".to_string()),
+      placeholder_hash: None,
+      exceeds_size_cap: true,
     };
 
     if let ProcessorEvent::ThumbnailReady {
@@ -125,12 +130,15 @@ mod tests {
       file_path,
       thumbnail_path,
       thumbnail_data,
+      exceeds_size_cap,
+      ..
     } = event
     {
       assert_eq!(file_id, "test-id");
       assert_eq!(file_path, "/path/to/test.mp4");
       assert_eq!(thumbnail_path, "/tmp/thumb_test-id.jpg");
       assert_eq!(thumbnail_data, Some("base64encodeddata".to_string()));
+      assert!(exceeds_size_cap);
     } else {
       panic!("Expected ThumbnailReady event");
     }
@@ -331,8 +339,10 @@ mod tests {
           size: None,
           bit_rate: None,
           format_name: None,
+          ..Default::default()
         },
       },
+      unsupported_reason: None,
     };
 
     assert!(video_file.is_video);
@@ -358,8 +368,10 @@ mod tests {
           size: None,
           bit_rate: None,
           format_name: None,
+          ..Default::default()
         },
       },
+      unsupported_reason: None,
     };
 
     assert!(!audio_file.is_video);
@@ -385,8 +397,10 @@ mod tests {
           size: None,
           bit_rate: None,
           format_name: None,
+          ..Default::default()
         },
       },
+      unsupported_reason: None,
     };
 
     assert!(!image_file.is_video);
@@ -15,17 +15,40 @@ impl CommandRegistry for MediaCommandRegistry {
       process_media_file_simple,
       // Preview operations
       generate_media_thumbnail,
+      convert_image_for_timeline,
       generate_timeline_previews,
+      generate_thumbnail_sprite,
+      generate_hover_preview,
+      generate_folder_hover_previews,
       get_media_preview_data,
       clear_media_preview_data,
       // Timeline frame operations
       get_timeline_frames,
       save_timeline_frames,
+      get_timeline_frames_in_range,
+      invalidate_timeline_frames,
+      read_preview_image,
       extract_recognition_frames,
       // Preview data management
       load_preview_data,
       save_preview_data,
       get_files_with_previews,
+      // Deduplication
+      find_duplicate_media,
+      relink_media,
+      set_media_fingerprint_mode,
+      // Auto-tagging
+      derive_media_tags,
+      search_media_by_tags,
+      get_media_tag_summary,
+      get_media_tag_thresholds,
+      set_media_tag_thresholds,
+      // Folder scans
+      cancel_media_scan,
+      get_media_scan_status,
+      // Conformance
+      check_media_conformance,
+      apply_media_autofix,
       // Additional commands
       get_media_files_in_directory,
       probe_media_file_detailed,
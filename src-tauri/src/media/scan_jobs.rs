@@ -0,0 +1,392 @@
+// Управляемые задачи сканирования медиапапок
+//
+// Сканирование папки выполняется в фоне и получает идентификатор. Задачу можно
+// отменить: обход директорий и обработка файлов останавливаются по токену
+// отмены, а уже обработанные файлы остаются доступны в статусе задачи.
+// Для одной папки одновременно выполняется не больше одной задачи.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use super::processor::{KnownMediaFile, MediaScanResult};
+use super::types::MediaFile;
+
+/// Событие Tauri с прогрессом сканирования
+pub const MEDIA_SCAN_EVENT: &str = "media-scan";
+
+/// Сколько завершенных задач хранится для запроса статуса
+const MAX_FINISHED_SCANS: usize = 16;
+
+/// Состояние задачи сканирования
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MediaScanStatus {
+  Queued,
+  Running,
+  Cancelled,
+  Completed,
+  Failed,
+}
+
+impl MediaScanStatus {
+  /// Задача еще не завершена
+  pub fn is_active(self) -> bool {
+    matches!(self, Self::Queued | Self::Running)
+  }
+}
+
+/// Прогресс сканирования (событие `media-scan`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MediaScanProgress {
+  pub scan_id: String,
+  pub folder_path: String,
+  pub status: MediaScanStatus,
+  /// Найдено медиафайлов при обходе папки
+  pub discovered: usize,
+  /// Обработано файлов (метаданные и превью)
+  pub processed: usize,
+  /// Файл, найденный или обработанный последним
+  pub current_path: Option<String>,
+}
+
+/// Статус задачи сканирования вместе с результатами
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MediaScanSnapshot {
+  #[serde(flatten)]
+  pub progress: MediaScanProgress,
+  /// Ошибка, если сканирование не удалось
+  pub error: Option<String>,
+  /// Результаты; после отмены содержат файлы, обработанные до нее
+  pub result: MediaScanResult,
+}
+
+/// Задача сканирования папки
+#[derive(Debug)]
+pub struct MediaScanJob {
+  id: String,
+  folder_path: PathBuf,
+  created_at: Instant,
+  cancel: CancellationToken,
+  state: Mutex<MediaScanSnapshot>,
+}
+
+impl MediaScanJob {
+  pub fn new(folder_path: &Path) -> Self {
+    let id = Uuid::new_v4().to_string();
+    let state = MediaScanSnapshot {
+      progress: MediaScanProgress {
+        scan_id: id.clone(),
+        folder_path: folder_path.to_string_lossy().to_string(),
+        status: MediaScanStatus::Queued,
+        discovered: 0,
+        processed: 0,
+        current_path: None,
+      },
+      error: None,
+      result: MediaScanResult::default(),
+    };
+
+    Self {
+      id,
+      folder_path: folder_path.to_path_buf(),
+      created_at: Instant::now(),
+      cancel: CancellationToken::new(),
+      state: Mutex::new(state),
+    }
+  }
+
+  pub fn id(&self) -> &str {
+    &self.id
+  }
+
+  pub fn folder_path(&self) -> &Path {
+    &self.folder_path
+  }
+
+  /// Токен отмены, передаваемый обходу папки и задачам обработки файлов
+  pub fn cancel_token(&self) -> &CancellationToken {
+    &self.cancel
+  }
+
+  pub fn is_cancelled(&self) -> bool {
+    self.cancel.is_cancelled()
+  }
+
+  pub fn status(&self) -> MediaScanStatus {
+    self.lock().progress.status
+  }
+
+  pub fn snapshot(&self) -> MediaScanSnapshot {
+    self.lock().clone()
+  }
+
+  /// Отменить задачу. Возвращает `false`, если задача уже завершена
+  pub fn cancel(&self) -> bool {
+    let mut state = self.lock();
+    if !state.progress.status.is_active() {
+      return false;
+    }
+    state.progress.status = MediaScanStatus::Cancelled;
+    self.cancel.cancel();
+    true
+  }
+
+  /// Перевести задачу в состояние выполнения
+  pub fn start(&self) -> MediaScanProgress {
+    self.update(|state| {
+      if state.progress.status == MediaScanStatus::Queued {
+        state.progress.status = MediaScanStatus::Running;
+      }
+    })
+  }
+
+  /// Учесть файл, найденный при обходе папки
+  pub fn file_discovered(&self, path: &str) -> MediaScanProgress {
+    self.update(|state| {
+      state.progress.discovered += 1;
+      state.progress.current_path = Some(path.to_string());
+    })
+  }
+
+  /// Учесть файлы, содержимое которых уже есть в реестре
+  pub fn files_known(&self, known_files: Vec<KnownMediaFile>) -> MediaScanProgress {
+    self.update(|state| state.result.known_files.extend(known_files))
+  }
+
+  /// Учесть обработанный файл; успешный результат сразу попадает в
+  /// частичные результаты задачи
+  pub fn file_processed(&self, path: &str, media_file: Option<&MediaFile>) -> MediaScanProgress {
+    self.update(|state| {
+      state.progress.processed += 1;
+      state.progress.current_path = Some(path.to_string());
+      if let Some(media_file) = media_file {
+        state.result.new_files.push(media_file.clone());
+      }
+    })
+  }
+
+  /// Завершить задачу. Отмененная задача остается отмененной
+  pub fn finish(&self, error: Option<String>) -> MediaScanProgress {
+    let cancelled = self.is_cancelled();
+    self.update(|state| {
+      state.progress.current_path = None;
+      state.progress.status = match (&error, cancelled) {
+        (_, true) => MediaScanStatus::Cancelled,
+        (Some(_), false) => MediaScanStatus::Failed,
+        (None, false) => MediaScanStatus::Completed,
+      };
+      state.error = error;
+    })
+  }
+
+  fn update(&self, apply: impl FnOnce(&mut MediaScanSnapshot)) -> MediaScanProgress {
+    let mut state = self.lock();
+    apply(&mut state);
+    state.progress.clone()
+  }
+
+  fn lock(&self) -> std::sync::MutexGuard<'_, MediaScanSnapshot> {
+    self.state.lock().unwrap_or_else(|e| e.into_inner())
+  }
+}
+
+/// State для задач сканирования медиапапок
+#[derive(Debug, Default)]
+pub struct MediaScanState {
+  jobs: RwLock<HashMap<String, Arc<MediaScanJob>>>,
+}
+
+impl MediaScanState {
+  /// Создать задачу для папки или вернуть уже выполняющуюся.
+  ///
+  /// Второе значение равно `true`, если задача создана и ее нужно запустить.
+  pub async fn start_or_get(&self, folder_path: &Path) -> (Arc<MediaScanJob>, bool) {
+    let folder_path = std::fs::canonicalize(folder_path).unwrap_or_else(|_| folder_path.into());
+    let mut jobs = self.jobs.write().await;
+
+    if let Some(job) = jobs
+      .values()
+      .find(|job| job.folder_path() == folder_path && job.status().is_active())
+    {
+      return (job.clone(), false);
+    }
+
+    prune_finished(&mut jobs);
+    let job = Arc::new(MediaScanJob::new(&folder_path));
+    jobs.insert(job.id().to_string(), job.clone());
+    (job, true)
+  }
+
+  pub async fn get(&self, scan_id: &str) -> Option<Arc<MediaScanJob>> {
+    self.jobs.read().await.get(scan_id).cloned()
+  }
+}
+
+/// Удалить самые старые завершенные задачи сверх лимита
+fn prune_finished(jobs: &mut HashMap<String, Arc<MediaScanJob>>) {
+  let mut finished: Vec<(Instant, String)> = jobs
+    .values()
+    .filter(|job| !job.status().is_active())
+    .map(|job| (job.created_at, job.id.clone()))
+    .collect();
+  finished.sort();
+
+  let excess = finished.len().saturating_sub(MAX_FINISHED_SCANS - 1);
+  for (_, id) in finished.into_iter().take(excess) {
+    jobs.remove(&id);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::media::incremental_scan::{walk_media_files_until, FolderScanOptions};
+  use crate::media::processor::{process_concurrently, DiscoveredFile};
+  use crate::media::types::{FfprobeFormat, ProbeData};
+  use std::sync::atomic::{AtomicUsize, Ordering};
+  use tempfile::TempDir;
+
+  const MAX_CONCURRENT: usize = 4;
+
+  /// Дерево из `dirs` папок по `files_per_dir` изображений
+  fn create_media_tree(dirs: usize, files_per_dir: usize) -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    for dir in 0..dirs {
+      let dir_path = temp_dir.path().join(format!("day_{dir:02}"));
+      std::fs::create_dir(&dir_path).unwrap();
+      for file in 0..files_per_dir {
+        std::fs::write(dir_path.join(format!("photo_{file:03}.jpg")), b"jpg").unwrap();
+      }
+    }
+    temp_dir
+  }
+
+  fn media_file(file: &DiscoveredFile) -> MediaFile {
+    MediaFile {
+      id: file.id.clone(),
+      name: file.name.clone(),
+      path: file.path.clone(),
+      is_video: false,
+      is_audio: false,
+      is_image: true,
+      size: file.size,
+      duration: None,
+      start_time: 0,
+      creation_time: String::new(),
+      probe_data: ProbeData {
+        streams: vec![],
+        format: FfprobeFormat::default(),
+      },
+      unsupported_reason: None,
+    }
+  }
+
+  #[tokio::test]
+  async fn test_cancel_stops_directory_walk() {
+    let temp_dir = create_media_tree(10, 20);
+    let job = MediaScanJob::new(temp_dir.path());
+
+    let files = walk_media_files_until(
+      temp_dir.path(),
+      &FolderScanOptions::default(),
+      job.cancel_token(),
+      |scanned| {
+        if job.file_discovered(&scanned.file.path).discovered == 25 {
+          job.cancel();
+        }
+      },
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(files.len(), 25);
+    assert_eq!(job.snapshot().progress.discovered, 25);
+    assert_eq!(job.status(), MediaScanStatus::Cancelled);
+  }
+
+  #[tokio::test]
+  async fn test_cancel_stops_processing_with_partial_results() {
+    let temp_dir = create_media_tree(20, 50);
+    let job = Arc::new(MediaScanJob::new(temp_dir.path()));
+    job.start();
+
+    let scanned = walk_media_files_until(
+      temp_dir.path(),
+      &FolderScanOptions::default(),
+      job.cancel_token(),
+      |_| {},
+    )
+    .await
+    .unwrap();
+    let files: Vec<DiscoveredFile> = scanned.into_iter().map(|scanned| scanned.file).collect();
+    assert_eq!(files.len(), 1000);
+
+    let cancel_after = 10;
+    let started = Arc::new(AtomicUsize::new(0));
+    let task_job = job.clone();
+    let task_started = started.clone();
+    let processed = process_concurrently(
+      files,
+      MAX_CONCURRENT,
+      job.cancel_token(),
+      move |_, file| {
+        let job = task_job.clone();
+        let started = task_started.clone();
+        async move {
+          started.fetch_add(1, Ordering::SeqCst);
+          tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+          let media_file = media_file(&file);
+          if job.file_processed(&file.path, Some(&media_file)).processed == cancel_after {
+            job.cancel();
+          }
+          Ok(media_file)
+        }
+      },
+    )
+    .await;
+    job.finish(None);
+
+    // После отмены завершаются только файлы, уже занявшие слот обработки
+    let started = started.load(Ordering::SeqCst);
+    assert!(
+      started <= cancel_after + MAX_CONCURRENT,
+      "started {started} files after cancelling at {cancel_after}"
+    );
+    assert_eq!(processed.len(), started);
+
+    let snapshot = job.snapshot();
+    assert_eq!(snapshot.progress.status, MediaScanStatus::Cancelled);
+    assert_eq!(snapshot.progress.processed, processed.len());
+    assert_eq!(snapshot.result.new_files.len(), processed.len());
+  }
+
+  #[tokio::test]
+  async fn test_one_active_scan_per_folder() {
+    let temp_dir = TempDir::new().unwrap();
+    let state = MediaScanState::default();
+
+    let (first, created) = state.start_or_get(temp_dir.path()).await;
+    assert!(created);
+    let (same, created) = state.start_or_get(temp_dir.path()).await;
+    assert!(!created);
+    assert_eq!(same.id(), first.id());
+
+    assert!(first.cancel());
+    assert!(!first.cancel());
+    assert_eq!(
+      state.get(first.id()).await.unwrap().status(),
+      MediaScanStatus::Cancelled
+    );
+
+    // После отмены папку можно сканировать заново
+    let (restarted, created) = state.start_or_get(temp_dir.path()).await;
+    assert!(created);
+    assert_ne!(restarted.id(), first.id());
+    assert_eq!(restarted.finish(None).status, MediaScanStatus::Completed);
+  }
+}
@@ -0,0 +1,340 @@
+// Модуль для генерации спрайт-листов превью таймлайна
+//
+// Вместо сотен отдельных кадров фронтенд получает несколько больших изображений
+// и индекс timestamp → (лист, x, y). Кадры извлекаются одним проходом FFmpeg
+// через фильтры fps/scale/tile, листы автоматически делятся по максимальному
+// размеру стороны.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tokio::process::Command;
+
+/// Максимальный размер стороны спрайт-листа в пикселях
+pub const MAX_SPRITE_DIMENSION: u32 = 4096;
+
+/// Имя файла манифеста в директории спрайтов
+const SPRITE_MANIFEST_NAME: &str = "sprite.json";
+
+/// Параметры генерации спрайт-листа
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpriteOptions {
+  /// Интервал между кадрами в секундах
+  pub interval: f64,
+  /// Ширина одного тайла
+  pub tile_width: u32,
+  /// Высота одного тайла
+  pub tile_height: u32,
+  /// Запрошенное количество колонок
+  pub columns: u32,
+}
+
+/// Раскладка тайлов по листам
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpriteLayout {
+  /// Колонок в листе (после ограничения по размеру)
+  pub columns: u32,
+  /// Строк в полном листе
+  pub rows: u32,
+  /// Всего кадров
+  pub frame_count: u32,
+  /// Количество листов
+  pub sheet_count: u32,
+}
+
+/// Положение кадра в спрайт-листе
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpriteFrameLocation {
+  /// Временная метка кадра
+  pub timestamp: f64,
+  /// Индекс листа
+  pub sheet: u32,
+  /// Смещение по X
+  pub x: u32,
+  /// Смещение по Y
+  pub y: u32,
+}
+
+/// Результат генерации спрайтов для медиафайла
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpriteSheetSet {
+  /// Пути к листам
+  pub sheets: Vec<PathBuf>,
+  /// Индекс кадров
+  pub frames: Vec<SpriteFrameLocation>,
+  /// Параметры генерации
+  pub options: SpriteOptions,
+  /// Раскладка
+  pub layout: SpriteLayout,
+  /// Время модификации исходника на момент генерации (секунды UNIX)
+  pub source_mtime: u64,
+}
+
+impl SpriteOptions {
+  /// Проверить параметры
+  pub fn validate(&self) -> Result<(), String> {
+    if !(self.interval.is_finite() && self.interval > 0.0) {
+      return Err("Interval must be positive".to_string());
+    }
+    if self.tile_width == 0 || self.tile_height == 0 {
+      return Err("Tile dimensions must be non-zero".to_string());
+    }
+    if self.tile_width > MAX_SPRITE_DIMENSION || self.tile_height > MAX_SPRITE_DIMENSION {
+      return Err(format!(
+        "Tile dimensions must not exceed {MAX_SPRITE_DIMENSION}px"
+      ));
+    }
+    if self.columns == 0 {
+      return Err("Columns must be non-zero".to_string());
+    }
+    Ok(())
+  }
+
+  /// Ключ кэша: исходник, время модификации и параметры
+  pub fn cache_key(&self, source: &Path, source_mtime: u64) -> String {
+    let raw = format!(
+      "{}|{}|{}|{}x{}|{}",
+      source.to_string_lossy(),
+      source_mtime,
+      self.interval,
+      self.tile_width,
+      self.tile_height,
+      self.columns
+    );
+    format!("{:x}", md5::compute(raw.as_bytes()))
+  }
+}
+
+/// Рассчитать раскладку тайлов с учетом максимального размера листа
+pub fn compute_layout(options: &SpriteOptions, duration: f64) -> SpriteLayout {
+  let frame_count = ((duration / options.interval).ceil() as u32).max(1);
+
+  let max_columns = (MAX_SPRITE_DIMENSION / options.tile_width).max(1);
+  let columns = options.columns.min(max_columns).min(frame_count);
+
+  let max_rows = (MAX_SPRITE_DIMENSION / options.tile_height).max(1);
+  let rows = frame_count.div_ceil(columns).min(max_rows);
+
+  let per_sheet = columns * rows;
+  SpriteLayout {
+    columns,
+    rows,
+    frame_count,
+    sheet_count: frame_count.div_ceil(per_sheet),
+  }
+}
+
+/// Построить индекс timestamp → (лист, x, y)
+pub fn build_frame_index(
+  options: &SpriteOptions,
+  layout: &SpriteLayout,
+) -> Vec<SpriteFrameLocation> {
+  let per_sheet = layout.columns * layout.rows;
+
+  (0..layout.frame_count)
+    .map(|i| {
+      let in_sheet = i % per_sheet;
+      SpriteFrameLocation {
+        timestamp: i as f64 * options.interval,
+        sheet: i / per_sheet,
+        x: (in_sheet % layout.columns) * options.tile_width,
+        y: (in_sheet / layout.columns) * options.tile_height,
+      }
+    })
+    .collect()
+}
+
+/// Время модификации файла в секундах UNIX
+pub async fn source_mtime(path: &Path) -> Result<u64, String> {
+  let metadata = tokio::fs::metadata(path)
+    .await
+    .map_err(|e| format!("Failed to read source metadata: {e}"))?;
+  Ok(
+    metadata
+      .modified()
+      .ok()
+      .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+      .map(|d| d.as_secs())
+      .unwrap_or(0),
+  )
+}
+
+/// Загрузить ранее сгенерированный набор, если он актуален
+pub async fn load_cached_sprites(
+  output_dir: &Path,
+  options: &SpriteOptions,
+  source_mtime: u64,
+) -> Option<SpriteSheetSet> {
+  let json = tokio::fs::read(output_dir.join(SPRITE_MANIFEST_NAME))
+    .await
+    .ok()?;
+  let set: SpriteSheetSet = serde_json::from_slice(&json).ok()?;
+
+  if set.source_mtime != source_mtime
+    || set.options != *options
+    || !set.sheets.iter().all(|sheet| sheet.exists())
+  {
+    return None;
+  }
+
+  Some(set)
+}
+
+/// Сгенерировать спрайт-листы с помощью FFmpeg.
+///
+/// Листы сохраняются в поддиректорию `cache_root`, имя которой зависит от
+/// исходника, времени его модификации и параметров; повторный вызов с теми же
+/// данными возвращает закэшированный набор без запуска FFmpeg.
+pub async fn generate_sprite_sheets(
+  input_path: &Path,
+  cache_root: &Path,
+  options: &SpriteOptions,
+  duration: f64,
+) -> Result<SpriteSheetSet, String> {
+  options.validate()?;
+  let mtime = source_mtime(input_path).await?;
+  let output_dir = cache_root.join(options.cache_key(input_path, mtime));
+
+  if let Some(cached) = load_cached_sprites(&output_dir, options, mtime).await {
+    return Ok(cached);
+  }
+
+  let layout = compute_layout(options, duration);
+
+  // Убираем устаревшие листы перед генерацией
+  let _ = tokio::fs::remove_dir_all(&output_dir).await;
+  tokio::fs::create_dir_all(&output_dir)
+    .await
+    .map_err(|e| format!("Failed to create sprite directory: {e}"))?;
+
+  let filter = format!(
+    "fps=1/{},scale={}:{},tile={}x{}",
+    options.interval, options.tile_width, options.tile_height, layout.columns, layout.rows
+  );
+  let pattern = output_dir.join("sheet_%03d.jpg");
+
+  let output = Command::new("ffmpeg")
+    .arg("-i")
+    .arg(input_path.to_string_lossy().as_ref())
+    .arg("-vf")
+    .arg(&filter)
+    .arg("-frames:v")
+    .arg(layout.sheet_count.to_string())
+    .arg("-start_number")
+    .arg("0")
+    .arg("-q:v")
+    .arg("3")
+    .arg(pattern.to_string_lossy().as_ref())
+    .arg("-y")
+    .output()
+    .await
+    .map_err(|e| format!("Failed to execute ffmpeg: {e}"))?;
+
+  if !output.status.success() {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    return Err(format!("FFmpeg failed: {stderr}"));
+  }
+
+  let sheets: Vec<PathBuf> = (0..layout.sheet_count)
+    .map(|i| output_dir.join(format!("sheet_{i:03}.jpg")))
+    .filter(|path| path.exists())
+    .collect();
+
+  // FFmpeg может выдать меньше листов, если длительность была оценена с запасом
+  let frames = build_frame_index(options, &layout)
+    .into_iter()
+    .filter(|frame| (frame.sheet as usize) < sheets.len())
+    .collect();
+
+  let set = SpriteSheetSet {
+    sheets,
+    frames,
+    options: options.clone(),
+    layout,
+    source_mtime: mtime,
+  };
+
+  let json = serde_json::to_vec_pretty(&set).map_err(|e| e.to_string())?;
+  tokio::fs::write(output_dir.join(SPRITE_MANIFEST_NAME), json)
+    .await
+    .map_err(|e| format!("Failed to write sprite manifest: {e}"))?;
+
+  Ok(set)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn options(interval: f64, tile_width: u32, tile_height: u32, columns: u32) -> SpriteOptions {
+    SpriteOptions {
+      interval,
+      tile_width,
+      tile_height,
+      columns,
+    }
+  }
+
+  #[test]
+  fn test_layout_single_sheet() {
+    let opts = options(1.0, 160, 90, 10);
+    let layout = compute_layout(&opts, 30.0);
+
+    assert_eq!(layout.frame_count, 30);
+    assert_eq!(layout.columns, 10);
+    assert_eq!(layout.rows, 3);
+    assert_eq!(layout.sheet_count, 1);
+  }
+
+  #[test]
+  fn test_layout_splits_by_max_dimension() {
+    // 4096 / 160 = 25 колонок максимум, 4096 / 90 = 45 строк максимум
+    let opts = options(1.0, 160, 90, 100);
+    let layout = compute_layout(&opts, 3600.0);
+
+    assert_eq!(layout.columns, 25);
+    assert_eq!(layout.rows, 45);
+    assert!(layout.columns * opts.tile_width <= MAX_SPRITE_DIMENSION);
+    assert!(layout.rows * opts.tile_height <= MAX_SPRITE_DIMENSION);
+    assert_eq!(layout.sheet_count, 3600u32.div_ceil(25 * 45));
+  }
+
+  #[test]
+  fn test_frame_index_positions() {
+    let opts = options(2.0, 100, 50, 3);
+    let layout = SpriteLayout {
+      columns: 3,
+      rows: 2,
+      frame_count: 8,
+      sheet_count: 2,
+    };
+    let index = build_frame_index(&opts, &layout);
+
+    assert_eq!(index.len(), 8);
+    assert_eq!(index[4].timestamp, 8.0);
+    assert_eq!((index[4].sheet, index[4].x, index[4].y), (0, 100, 50));
+    assert_eq!((index[6].sheet, index[6].x, index[6].y), (1, 0, 0));
+  }
+
+  #[test]
+  fn test_options_validation() {
+    assert!(options(1.0, 160, 90, 10).validate().is_ok());
+    assert!(options(0.0, 160, 90, 10).validate().is_err());
+    assert!(options(1.0, 0, 90, 10).validate().is_err());
+    assert!(options(1.0, 160, 90, 0).validate().is_err());
+    assert!(options(1.0, 5000, 90, 1).validate().is_err());
+  }
+
+  #[test]
+  fn test_cache_key_depends_on_mtime_and_params() {
+    let opts = options(1.0, 160, 90, 10);
+    let path = Path::new("/test/video.mp4");
+
+    assert_eq!(opts.cache_key(path, 1), opts.cache_key(path, 1));
+    assert_ne!(opts.cache_key(path, 1), opts.cache_key(path, 2));
+    assert_ne!(
+      opts.cache_key(path, 1),
+      options(2.0, 160, 90, 10).cache_key(path, 1)
+    );
+  }
+}
@@ -100,14 +100,17 @@ mod tests {
           sample_rate: None,
           channels: None,
           display_aspect_ratio: Some("16:9".to_string()),
+          ..Default::default()
         }],
         format: FfprobeFormat {
           duration: Some(60.0),
           size: Some(1024),
           bit_rate: Some("5000000".to_string()),
           format_name: Some("mov,mp4,m4a,3gp,3g2,mj2".to_string()),
+          ..Default::default()
         },
       },
+      unsupported_reason: None,
     };
 
     let json = serde_json::to_string(&media_file).unwrap();
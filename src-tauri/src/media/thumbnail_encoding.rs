@@ -0,0 +1,422 @@
+// Кодирование миниатюр медиабраузера: формат, качество, прогрессивный JPEG,
+// ограничение размера файла и blurhash-заглушки
+
+use crate::media::processor::ThumbnailOptions;
+use image::{DynamicImage, GenericImageView};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+/// Максимальное число попыток кодирования при ограничении размера файла
+pub const MAX_SIZE_CAP_ATTEMPTS: usize = 6;
+
+/// Качество, ниже которого миниатюра не ужимается
+pub const MIN_THUMBNAIL_QUALITY: u8 = 20;
+
+/// Число компонент blurhash по длинной и короткой стороне
+const BLURHASH_COMPONENTS: (u32, u32) = (4, 3);
+
+/// Сторона уменьшенной копии, по которой считается blurhash
+const BLURHASH_SAMPLE_SIZE: u32 = 32;
+
+/// Формат миниатюры
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThumbnailFormat {
+  #[default]
+  Jpeg,
+  /// WebP через FFmpeg (libwebp)
+  Webp,
+  /// AVIF через FFmpeg (libaom-av1); без кодировщика миниатюра сохраняется в JPEG
+  Avif,
+}
+
+impl ThumbnailFormat {
+  pub const ALL: [ThumbnailFormat; 3] = [Self::Jpeg, Self::Webp, Self::Avif];
+
+  /// Расширение файла миниатюры
+  pub fn extension(self) -> &'static str {
+    match self {
+      Self::Jpeg => "jpg",
+      Self::Webp => "webp",
+      Self::Avif => "avif",
+    }
+  }
+}
+
+/// Закодированная миниатюра
+#[derive(Debug, Clone)]
+pub struct EncodedThumbnail {
+  pub data: Vec<u8>,
+  /// Фактический формат (AVIF без кодировщика заменяется на JPEG)
+  pub format: ThumbnailFormat,
+  /// Качество, с которым получен результат
+  pub quality: u8,
+  /// Сколько раз изображение было закодировано
+  pub attempts: usize,
+  /// В `max_file_size_kb` уложиться не удалось, возвращен наименьший результат
+  pub exceeds_size_cap: bool,
+}
+
+/// Кодирует миниатюру согласно параметрам.
+///
+/// При заданном `max_file_size_kb` качество понижается, пока результат
+/// не уложится в лимит (см. [`encode_within_size_cap`]).
+pub fn encode_thumbnail(
+  image: &DynamicImage,
+  options: &ThumbnailOptions,
+) -> Result<EncodedThumbnail, String> {
+  let format = match options.format {
+    ThumbnailFormat::Avif if !ffmpeg_supports_avif() => ThumbnailFormat::Jpeg,
+    format => format,
+  };
+  let max_bytes = options.max_file_size_kb.map(|kb| kb as usize * 1024);
+
+  encode_within_size_cap(format, options.quality, max_bytes, |quality| {
+    encode_image(image, format, quality, options.progressive)
+  })
+}
+
+/// Кодирует с понижением качества, пока размер превышает `max_bytes`.
+///
+/// Попыток не больше [`MAX_SIZE_CAP_ATTEMPTS`], качество не опускается ниже
+/// [`MIN_THUMBNAIL_QUALITY`]. Если лимит недостижим, возвращается наименьший
+/// полученный результат с флагом `exceeds_size_cap`.
+pub fn encode_within_size_cap<F>(
+  format: ThumbnailFormat,
+  quality: u8,
+  max_bytes: Option<usize>,
+  mut encode: F,
+) -> Result<EncodedThumbnail, String>
+where
+  F: FnMut(u8) -> Result<Vec<u8>, String>,
+{
+  let mut quality = quality.clamp(1, 100);
+  let mut best = EncodedThumbnail {
+    data: encode(quality)?,
+    format,
+    quality,
+    attempts: 1,
+    exceeds_size_cap: false,
+  };
+  let Some(max_bytes) = max_bytes else {
+    return Ok(best);
+  };
+
+  while best.data.len() > max_bytes
+    && best.attempts < MAX_SIZE_CAP_ATTEMPTS
+    && quality > MIN_THUMBNAIL_QUALITY
+  {
+    quality = (quality as u32 * 3 / 4).max(MIN_THUMBNAIL_QUALITY as u32) as u8;
+    let data = encode(quality)?;
+    best.attempts += 1;
+    if data.len() < best.data.len() {
+      best.data = data;
+      best.quality = quality;
+    }
+  }
+
+  best.exceeds_size_cap = best.data.len() > max_bytes;
+  Ok(best)
+}
+
+/// Blurhash изображения для мгновенной заглушки в UI.
+///
+/// Считается по уменьшенной копии, поэтому стоит дешево и стабилен для
+/// одинакового изображения. Для пустого изображения возвращает `None`.
+pub fn placeholder_hash(image: &DynamicImage) -> Option<String> {
+  let (width, height) = image.dimensions();
+  if width == 0 || height == 0 {
+    return None;
+  }
+
+  let sample = image
+    .thumbnail(BLURHASH_SAMPLE_SIZE, BLURHASH_SAMPLE_SIZE)
+    .to_rgba8();
+  let (width, height) = sample.dimensions();
+
+  let (long, short) = BLURHASH_COMPONENTS;
+  let (components_x, components_y) = if width >= height {
+    (long, short)
+  } else {
+    (short, long)
+  };
+  blurhash::encode(components_x, components_y, width, height, sample.as_raw()).ok()
+}
+
+fn encode_image(
+  image: &DynamicImage,
+  format: ThumbnailFormat,
+  quality: u8,
+  progressive: bool,
+) -> Result<Vec<u8>, String> {
+  match format {
+    ThumbnailFormat::Jpeg => encode_jpeg(image, quality, progressive),
+    ThumbnailFormat::Webp => encode_with_ffmpeg(
+      image,
+      format,
+      &["-c:v", "libwebp", "-quality", &quality.to_string()],
+    ),
+    ThumbnailFormat::Avif => {
+      // CRF libaom: 0 - без потерь, 63 - наихудшее качество
+      let crf = 63 - u32::from(quality) * 63 / 100;
+      encode_with_ffmpeg(
+        image,
+        format,
+        &[
+          "-c:v",
+          "libaom-av1",
+          "-still-picture",
+          "1",
+          "-crf",
+          &crf.to_string(),
+          "-pix_fmt",
+          "yuv420p",
+        ],
+      )
+    }
+  }
+}
+
+fn encode_jpeg(image: &DynamicImage, quality: u8, progressive: bool) -> Result<Vec<u8>, String> {
+  let rgb = image.to_rgb8();
+  let (width, height) = match (u16::try_from(rgb.width()), u16::try_from(rgb.height())) {
+    (Ok(width), Ok(height)) => (width, height),
+    _ => {
+      return Err(format!(
+        "Thumbnail too large for JPEG: {}x{}",
+        rgb.width(),
+        rgb.height()
+      ))
+    }
+  };
+
+  let mut buffer = Vec::new();
+  let mut encoder = jpeg_encoder::Encoder::new(&mut buffer, quality);
+  encoder.set_progressive(progressive);
+  encoder
+    .encode(rgb.as_raw(), width, height, jpeg_encoder::ColorType::Rgb)
+    .map_err(|e| format!("Failed to encode JPEG thumbnail: {e}"))?;
+  Ok(buffer)
+}
+
+/// Кодирует изображение через FFmpeg: кадр передается в stdin как rgb24,
+/// результат пишется во временный файл
+fn encode_with_ffmpeg(
+  image: &DynamicImage,
+  format: ThumbnailFormat,
+  codec_args: &[&str],
+) -> Result<Vec<u8>, String> {
+  let rgb = image.to_rgb8();
+  let size = format!("{}x{}", rgb.width(), rgb.height());
+  let output_path = std::env::temp_dir().join(format!(
+    "thumbnail-{}.{}",
+    uuid::Uuid::new_v4(),
+    format.extension()
+  ));
+
+  let mut child = Command::new("ffmpeg")
+    .args(["-hide_banner", "-loglevel", "error", "-y"])
+    .args([
+      "-f", "rawvideo", "-pix_fmt", "rgb24", "-s", &size, "-i", "pipe:0",
+    ])
+    .args(codec_args)
+    .args(["-frames:v", "1"])
+    .arg(&output_path)
+    .stdin(Stdio::piped())
+    .stdout(Stdio::null())
+    .stderr(Stdio::piped())
+    .spawn()
+    .map_err(|e| format!("Failed to run ffmpeg: {e}"))?;
+
+  // Кадр пишется из отдельного потока, чтобы не заблокироваться на stderr
+  let mut stdin = child
+    .stdin
+    .take()
+    .ok_or_else(|| "Failed to open ffmpeg stdin".to_string())?;
+  let frame = rgb.into_raw();
+  let writer = std::thread::spawn(move || stdin.write_all(&frame));
+  let output = child
+    .wait_with_output()
+    .map_err(|e| format!("Failed to run ffmpeg: {e}"))?;
+  let _ = writer.join();
+
+  let result = if output.status.success() {
+    std::fs::read(&output_path).map_err(|e| format!("Failed to read encoded thumbnail: {e}"))
+  } else {
+    Err(format!(
+      "FFmpeg failed: {}",
+      String::from_utf8_lossy(&output.stderr)
+    ))
+  };
+  let _ = std::fs::remove_file(&output_path);
+  result
+}
+
+/// Есть ли в установленном FFmpeg кодировщик AVIF (проверяется один раз)
+fn ffmpeg_supports_avif() -> bool {
+  static SUPPORTED: OnceLock<bool> = OnceLock::new();
+  *SUPPORTED.get_or_init(|| {
+    Command::new("ffmpeg")
+      .args(["-hide_banner", "-encoders"])
+      .output()
+      .map(|output| String::from_utf8_lossy(&output.stdout).contains("libaom-av1"))
+      .unwrap_or(false)
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use image::{ImageBuffer, Rgb};
+
+  /// Шумное изображение, которое плохо сжимается
+  fn noise_image(width: u32, height: u32) -> DynamicImage {
+    let mut state = 0x2545_f491_u32;
+    DynamicImage::ImageRgb8(ImageBuffer::from_fn(width, height, |_, _| {
+      state ^= state << 13;
+      state ^= state >> 17;
+      state ^= state << 5;
+      let [r, g, b, _] = state.to_le_bytes();
+      Rgb([r, g, b])
+    }))
+  }
+
+  fn gradient_image(width: u32, height: u32) -> DynamicImage {
+    DynamicImage::ImageRgb8(ImageBuffer::from_fn(width, height, |x, y| {
+      Rgb([(x * 255 / width) as u8, (y * 255 / height) as u8, 128])
+    }))
+  }
+
+  #[test]
+  fn test_size_cap_loop_terminates_when_cap_unreachable() {
+    let mut qualities = Vec::new();
+    let encoded = encode_within_size_cap(ThumbnailFormat::Jpeg, 85, Some(10), |quality| {
+      qualities.push(quality);
+      Ok(vec![0; 1000 + quality as usize])
+    })
+    .unwrap();
+
+    assert_eq!(encoded.attempts, MAX_SIZE_CAP_ATTEMPTS);
+    assert_eq!(qualities.len(), MAX_SIZE_CAP_ATTEMPTS);
+    assert!(qualities.windows(2).all(|pair| pair[1] < pair[0]));
+    assert!(encoded.exceeds_size_cap);
+    // Возвращается наименьший результат
+    assert_eq!(encoded.quality, *qualities.last().unwrap());
+    assert_eq!(encoded.data.len(), 1000 + encoded.quality as usize);
+  }
+
+  #[test]
+  fn test_size_cap_stops_at_minimum_quality() {
+    let mut qualities = Vec::new();
+    let encoded = encode_within_size_cap(ThumbnailFormat::Webp, 30, Some(10), |quality| {
+      qualities.push(quality);
+      Ok(vec![0; 100])
+    })
+    .unwrap();
+
+    assert_eq!(qualities, vec![30, 22, MIN_THUMBNAIL_QUALITY]);
+    assert!(encoded.exceeds_size_cap);
+    // Одинаковый размер: остается первый результат
+    assert_eq!(encoded.quality, 30);
+  }
+
+  #[test]
+  fn test_size_cap_keeps_smallest_result() {
+    let sizes = [500, 300, 400, 350, 600, 450];
+    let mut attempt = 0;
+    let encoded = encode_within_size_cap(ThumbnailFormat::Jpeg, 90, Some(100), |_| {
+      attempt += 1;
+      Ok(vec![0; sizes[attempt - 1]])
+    })
+    .unwrap();
+
+    assert_eq!(encoded.data.len(), 300);
+    assert_eq!(encoded.quality, 67);
+  }
+
+  #[test]
+  fn test_without_cap_encodes_once() {
+    let mut calls = 0;
+    let encoded = encode_within_size_cap(ThumbnailFormat::Jpeg, 85, None, |_| {
+      calls += 1;
+      Ok(vec![0; 1 << 20])
+    })
+    .unwrap();
+
+    assert_eq!(calls, 1);
+    assert!(!encoded.exceeds_size_cap);
+  }
+
+  #[test]
+  fn test_jpeg_size_cap_lowers_quality() {
+    let image = noise_image(160, 90);
+    let options = ThumbnailOptions {
+      quality: 95,
+      ..Default::default()
+    };
+    let uncapped = encode_thumbnail(&image, &options).unwrap();
+
+    let capped = encode_thumbnail(
+      &image,
+      &ThumbnailOptions {
+        max_file_size_kb: Some((uncapped.data.len() / 1024 / 2) as u32),
+        ..options
+      },
+    )
+    .unwrap();
+
+    assert!(capped.attempts > 1);
+    assert!(capped.quality < 95);
+    assert!(capped.data.len() < uncapped.data.len());
+  }
+
+  #[test]
+  fn test_progressive_jpeg_is_decodable() {
+    let image = gradient_image(64, 48);
+    let options = ThumbnailOptions {
+      progressive: true,
+      ..Default::default()
+    };
+    let encoded = encode_thumbnail(&image, &options).unwrap();
+
+    assert_eq!(encoded.format, ThumbnailFormat::Jpeg);
+    let decoded = image::load_from_memory(&encoded.data).unwrap();
+    assert_eq!((decoded.width(), decoded.height()), (64, 48));
+  }
+
+  #[test]
+  fn test_placeholder_hash_is_stable() {
+    let image = gradient_image(320, 180);
+    let hash = placeholder_hash(&image).unwrap();
+
+    assert_eq!(hash, placeholder_hash(&image).unwrap());
+    assert_eq!(hash, "LyHV9Z2swxX8qRWDjtaggJfjfQfj");
+  }
+
+  #[test]
+  fn test_placeholder_hash_orientation_and_empty_image() {
+    // 4x3 компоненты для альбомной ориентации, 3x4 для портретной
+    let landscape = placeholder_hash(&gradient_image(320, 180)).unwrap();
+    let portrait = placeholder_hash(&gradient_image(180, 320)).unwrap();
+    assert_eq!(landscape.len(), 6 + 2 * (4 * 3 - 1));
+    assert_eq!(portrait.len(), landscape.len());
+    assert_ne!(&landscape[..1], &portrait[..1]);
+
+    assert!(placeholder_hash(&DynamicImage::new_rgb8(0, 0)).is_none());
+  }
+
+  #[test]
+  fn test_thumbnail_format_extensions() {
+    let extensions: Vec<_> = ThumbnailFormat::ALL
+      .iter()
+      .map(|format| format.extension())
+      .collect();
+    assert_eq!(extensions, vec!["jpg", "webp", "avif"]);
+    assert_eq!(
+      serde_json::to_string(&ThumbnailFormat::Webp).unwrap(),
+      "\"webp\""
+    );
+  }
+}
@@ -1,6 +1,7 @@
 //! Thumbnail Generator - Генерация миниатюр для медиафайлов
 
 use crate::media::ffmpeg::extract_frame;
+use crate::media::photo_decoder::open_image;
 use base64::Engine;
 use image::{DynamicImage, GenericImageView, ImageFormat};
 use serde::{Deserialize, Serialize};
@@ -96,8 +97,9 @@ impl ThumbnailGenerator {
       // Загружаем извлеченный кадр
       image::open(&thumbnail_path).map_err(|e| format!("Failed to open extracted frame: {e}"))?
     } else {
-      // Загружаем изображение
-      image::open(file_path).map_err(|e| format!("Failed to open image: {e}"))?
+      // Загружаем изображение (HEIF/RAW через photo_decoder, с учетом ориентации)
+      open_image(file_path)
+        .map_err(|reason| format!("Failed to open image: {}", reason.message()))?
     };
 
     // Изменяем размер с сохранением пропорций
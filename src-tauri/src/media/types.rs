@@ -1,6 +1,10 @@
 // Типы данных для работы с медиафайлами
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::video_compiler::schema::{Timecode, TimecodeRate};
+use crate::video_compiler::services::ffmpeg_service::parse_rational;
 
 /// Структура для хранения метаданных видео
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -48,7 +52,7 @@ pub enum MediaMetadata {
 }
 
 /// Структура для потока в формате FFprobe
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct FfprobeStream {
   pub index: u32,
   pub codec_type: String,
@@ -60,15 +64,42 @@ pub struct FfprobeStream {
   pub sample_rate: Option<String>,
   pub channels: Option<u8>,
   pub display_aspect_ratio: Option<String>,
+  /// Язык потока из тегов
+  #[serde(default)]
+  pub language: Option<String>,
+  #[serde(default)]
+  pub channel_layout: Option<String>,
+  /// Битовая глубина отсчета
+  #[serde(default)]
+  pub bit_depth: Option<u32>,
+  #[serde(default)]
+  pub pix_fmt: Option<String>,
+  /// Поворот видео по часовой стрелке в градусах (0, 90, 180, 270)
+  #[serde(default)]
+  pub rotation: i32,
+  #[serde(default)]
+  pub color_range: Option<String>,
+  #[serde(default)]
+  pub color_space: Option<String>,
+  #[serde(default)]
+  pub color_transfer: Option<String>,
+  #[serde(default)]
+  pub color_primaries: Option<String>,
+  /// Исходный timecode из тегов потока (`tmcd` или видео)
+  #[serde(default)]
+  pub timecode: Option<String>,
 }
 
 /// Структура для формата в формате FFprobe
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct FfprobeFormat {
   pub duration: Option<f64>,
   pub size: Option<u64>,
   pub bit_rate: Option<String>,
   pub format_name: Option<String>,
+  /// Теги контейнера
+  #[serde(default)]
+  pub tags: HashMap<String, String>,
 }
 
 /// Структура для хранения данных FFprobe
@@ -78,6 +109,30 @@ pub struct ProbeData {
   pub format: FfprobeFormat,
 }
 
+impl ProbeData {
+  /// Исходный timecode первого кадра файла.
+  ///
+  /// Берется из тегов потока (видео или дорожки `tmcd`), затем из тегов
+  /// контейнера. Частота timecode определяется по видеопотоку.
+  pub fn source_timecode(&self) -> Option<Timecode> {
+    let text = self
+      .streams
+      .iter()
+      .find_map(|stream| stream.timecode.as_deref())
+      .or_else(|| self.format.tags.get("timecode").map(String::as_str))?;
+
+    let fps = self
+      .streams
+      .iter()
+      .find(|stream| stream.codec_type == "video")
+      .and_then(|stream| stream.r_frame_rate.as_deref())
+      .and_then(parse_rational)?;
+    let rate = TimecodeRate::from_fps(fps).unwrap_or_else(|| TimecodeRate::nearest(fps));
+
+    Timecode::parse(text, rate).ok()
+  }
+}
+
 /// Структура для медиафайла
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MediaFile {
@@ -92,6 +147,34 @@ pub struct MediaFile {
   pub start_time: u64,
   pub creation_time: String,
   pub probe_data: ProbeData,
+  /// Причина, по которой файл найден, но не может быть декодирован
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub unsupported_reason: Option<UnsupportedReason>,
+}
+
+/// Причина, по которой медиафайл нельзя декодировать на этой машине
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnsupportedReason {
+  /// FFmpeg собран без поддержки HEIF, а libheif не включен
+  HeifDecoderUnavailable,
+  /// В RAW-файле нет встроенного JPEG-превью
+  RawPreviewMissing,
+  /// Файл поврежден или его структура не распознана
+  UnreadableImage,
+}
+
+impl UnsupportedReason {
+  /// Сообщение для пользователя
+  pub fn message(&self) -> &'static str {
+    match self {
+      Self::HeifDecoderUnavailable => {
+        "HEIF не поддерживается: FFmpeg собран без HEIF, поддержка libheif не включена"
+      }
+      Self::RawPreviewMissing => "В RAW-файле нет встроенного JPEG-превью",
+      Self::UnreadableImage => "Не удалось прочитать изображение",
+    }
+  }
 }
 
 /// Поддерживаемые расширения медиафайлов
@@ -99,7 +182,9 @@ pub const SUPPORTED_EXTENSIONS: &[&str] = &[
   // Видео
   "mp4", "avi", "mkv", "mov", "webm", // Аудио
   "mp3", "wav", "ogg", "flac", // Изображения
-  "jpg", "jpeg", "png", "gif", "webp",
+  "jpg", "jpeg", "png", "gif", "webp", // HEIF
+  "heic", "heif", "hif", // RAW
+  "cr3", "cr2", "arw", "dng", "nef", "raf", "orf", "rw2",
 ];
 
 #[cfg(test)]
@@ -120,6 +205,7 @@ mod tests {
       sample_rate: None,
       channels: None,
       display_aspect_ratio: Some("16:9".to_string()),
+      ..Default::default()
     };
 
     let format = FfprobeFormat {
@@ -127,6 +213,7 @@ mod tests {
       size: Some(1024000),
       bit_rate: Some("5000000".to_string()),
       format_name: Some("mov,mp4,m4a,3gp,3g2,mj2".to_string()),
+      ..Default::default()
     };
 
     let probe_data = ProbeData {
@@ -146,6 +233,7 @@ mod tests {
       start_time: 1234567890,
       creation_time: "2023-01-01T00:00:00Z".to_string(),
       probe_data,
+      unsupported_reason: None,
     };
 
     // Тестируем сериализацию в JSON
@@ -164,6 +252,51 @@ mod tests {
     assert_eq!(deserialized_file.probe_data.streams[0].codec_type, "video");
   }
 
+  #[test]
+  fn test_probe_data_source_timecode() {
+    let video = FfprobeStream {
+      codec_type: "video".to_string(),
+      r_frame_rate: Some("30000/1001".to_string()),
+      ..Default::default()
+    };
+    let tmcd = FfprobeStream {
+      index: 1,
+      codec_type: "data".to_string(),
+      timecode: Some("01:00:00;00".to_string()),
+      ..Default::default()
+    };
+
+    let probe_data = ProbeData {
+      streams: vec![video.clone(), tmcd],
+      format: FfprobeFormat::default(),
+    };
+    let timecode = probe_data.source_timecode().unwrap();
+    assert_eq!(timecode.rate, TimecodeRate::Fps29_97);
+    assert!(timecode.drop_frame);
+    assert_eq!(timecode.to_string(), "01:00:00;00");
+
+    // Timecode из тегов контейнера
+    let mut format = FfprobeFormat::default();
+    format
+      .tags
+      .insert("timecode".to_string(), "00:10:00:00".to_string());
+    let probe_data = ProbeData {
+      streams: vec![video],
+      format,
+    };
+    assert_eq!(
+      probe_data.source_timecode().unwrap().to_string(),
+      "00:10:00:00"
+    );
+
+    // Без timecode
+    let probe_data = ProbeData {
+      streams: Vec::new(),
+      format: FfprobeFormat::default(),
+    };
+    assert!(probe_data.source_timecode().is_none());
+  }
+
   #[test]
   fn test_media_metadata_enum() {
     // Тестируем перечисление MediaMetadata
@@ -195,6 +328,8 @@ mod tests {
     assert!(SUPPORTED_EXTENSIONS.contains(&"mp4"));
     assert!(SUPPORTED_EXTENSIONS.contains(&"mp3"));
     assert!(SUPPORTED_EXTENSIONS.contains(&"jpg"));
+    assert!(SUPPORTED_EXTENSIONS.contains(&"heic"));
+    assert!(SUPPORTED_EXTENSIONS.contains(&"cr3"));
     assert!(!SUPPORTED_EXTENSIONS.contains(&"txt"));
   }
 }
@@ -3,10 +3,12 @@
 //! This module provides the Tauri command interface for montage planning functionality.
 
 use crate::command_registry::CommandRegistry;
+use crate::montage_planner::services::quality_analyzer::DEFAULT_QUALITY_SEGMENT_DURATION;
 use crate::montage_planner::services::*;
 use crate::montage_planner::types::*;
 use crate::recognition::commands::yolo_commands::YoloProcessorState;
-use std::path::PathBuf;
+use crate::video_compiler::schema::{ProjectSchema, Template};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tauri::{command, Builder, Runtime, State};
 use tokio::sync::RwLock;
@@ -21,6 +23,7 @@ pub struct MontageState {
   pub plan_generator: Arc<RwLock<PlanGenerator>>,
   pub audio_analyzer: Arc<RwLock<AudioAnalyzer>>,
   pub video_processor: Arc<RwLock<VideoProcessor>>,
+  pub beat_detector: Arc<RwLock<BeatDetector>>,
 }
 
 impl MontageState {
@@ -34,6 +37,7 @@ impl MontageState {
       plan_generator: Arc::new(RwLock::new(PlanGenerator::new())),
       audio_analyzer: Arc::new(RwLock::new(AudioAnalyzer::new())),
       video_processor: Arc::new(RwLock::new(VideoProcessor::new(yolo_state))),
+      beat_detector: Arc::new(RwLock::new(BeatDetector::new())),
     }
   }
 }
@@ -58,18 +62,46 @@ pub async fn analyze_video_composition(
     return Ok(Vec::new());
   }
 
-  let video_processor = montage_state.video_processor.read().await;
-  let composition_analyzer = montage_state.composition_analyzer.read().await;
+  enhanced_detections(&montage_state, &path, &analysis_options).await
+}
+
+/// Detect key moments in video based on enhanced YOLO analysis
+#[command]
+pub async fn detect_key_moments(
+  enhanced_detections: Vec<CompositionEnhancedDetection>,
+  _config: MontageConfig,
+  state: tauri::State<'_, MontageState>,
+) -> Result<Vec<DetectedMoment>, String> {
+  let moment_detector = state.moment_detector.read().await;
+
+  let montage_detections = to_montage_detections(&enhanced_detections);
+
+  // Use the moment detector to detect moments
+  let detected_moments = moment_detector
+    .detect_moments(&montage_detections)
+    .map_err(|e| format!("Moment detection failed: {e:?}"))?;
+
+  Ok(detected_moments)
+}
+
+/// Run YOLO over a video and enhance the detections with composition analysis
+pub(crate) async fn enhanced_detections(
+  state: &MontageState,
+  path: &Path,
+  analysis_options: &AnalysisOptions,
+) -> Result<Vec<CompositionEnhancedDetection>, String> {
+  let video_processor = state.video_processor.read().await;
+  let composition_analyzer = state.composition_analyzer.read().await;
 
   // Step 1: Analyze video with YOLO processor
   let yolo_detections = video_processor
-    .analyze_video(&path, &analysis_options)
+    .analyze_video(path, analysis_options)
     .await
     .map_err(|e| format!("Video analysis failed: {e}"))?;
 
   // Step 2: Get video metadata for frame dimensions
   let metadata = video_processor
-    .extract_metadata(&path)
+    .extract_metadata(path)
     .await
     .map_err(|e| format!("Failed to extract metadata: {e}"))?;
 
@@ -112,19 +144,13 @@ pub async fn analyze_video_composition(
   Ok(enhanced_results)
 }
 
-/// Detect key moments in video based on enhanced YOLO analysis
-#[command]
-pub async fn detect_key_moments(
-  enhanced_detections: Vec<CompositionEnhancedDetection>,
-  _config: MontageConfig,
-  state: tauri::State<'_, MontageState>,
-) -> Result<Vec<DetectedMoment>, String> {
-  let moment_detector = state.moment_detector.read().await;
-
-  // Convert enhanced detections to montage detections
+/// Convert composition-enhanced detections into moment detector input
+pub(crate) fn to_montage_detections(
+  enhanced_detections: &[CompositionEnhancedDetection],
+) -> Vec<MontageDetection> {
   let mut montage_detections = Vec::new();
 
-  for detection in &enhanced_detections {
+  for detection in enhanced_detections {
     // Create MontageDetection from CompositionEnhancedDetection
     let montage_detection = MontageDetection {
       timestamp: detection.timestamp,
@@ -158,32 +184,177 @@ pub async fn detect_key_moments(
     montage_detections.push(montage_detection);
   }
 
-  // Use the moment detector to detect moments
-  let detected_moments = moment_detector
-    .detect_moments(&montage_detections)
-    .map_err(|e| format!("Moment detection failed: {e:?}"))?;
-
-  Ok(detected_moments)
+  montage_detections
 }
 
 /// Generate montage plan from detected moments
+///
+/// When a beat grid is provided, cut points are snapped to the music beats.
+/// An energy curve (see `compute_audio_energy_curve`) biases moment selection
+/// toward energy peaks for dynamic styles and steady regions for calm ones.
+/// Segment quality scores (see `analyze_segment_quality`) down-weight moments
+/// in blurry, badly exposed or shaky footage.
 #[command]
 pub async fn generate_montage_plan(
   moments: Vec<DetectedMoment>,
   config: MontageConfig,
   source_files: Vec<String>,
+  beat_grid: Option<BeatGrid>,
+  energy_curve: Option<Vec<EnergyPoint>>,
+  quality_scores: Option<Vec<QualityScore>>,
   state: tauri::State<'_, MontageState>,
 ) -> Result<MontagePlan, String> {
   let mut plan_generator = state.plan_generator.write().await;
 
+  let moments = match quality_scores {
+    Some(scores) => plan_generator.apply_quality_scores(&moments, &scores),
+    None => moments,
+  };
+
   // Use the plan generator to create an optimized montage plan
   let generated_plan = plan_generator
-    .generate_plan(&moments, &config, &source_files)
+    .generate_plan_with_audio(
+      &moments,
+      &config,
+      &source_files,
+      beat_grid.as_ref(),
+      energy_curve.as_deref(),
+    )
     .map_err(|e| format!("Plan generation failed: {e:?}"))?;
 
   Ok(generated_plan)
 }
 
+/// Score sharpness, exposure and shake per segment of a video file
+#[command]
+pub async fn analyze_segment_quality(
+  file_path: String,
+  segment_duration: Option<f64>,
+  state: tauri::State<'_, MontageState>,
+) -> Result<Vec<QualityScore>, String> {
+  let analyzer = state.quality_analyzer.read().await;
+  analyzer
+    .analyze_segments(
+      &file_path,
+      segment_duration.unwrap_or(DEFAULT_QUALITY_SEGMENT_DURATION),
+    )
+    .await
+    .map_err(|e| format!("Quality analysis failed: {e}"))
+}
+
+/// Detect music beats in an audio or video file
+///
+/// Results are cached until the file modification time changes.
+#[command]
+pub async fn detect_audio_beats(
+  file_path: String,
+  options: Option<BeatDetectionOptions>,
+  state: tauri::State<'_, MontageState>,
+) -> Result<Vec<Beat>, String> {
+  let path = PathBuf::from(&file_path);
+  if !path.exists() {
+    return Err(format!("File not found: {file_path}"));
+  }
+
+  let mut beat_detector = state.beat_detector.write().await;
+  beat_detector
+    .detect_beats(&path, options)
+    .await
+    .map_err(|e| format!("Beat detection failed: {e}"))
+}
+
+/// Compute the soundtrack energy curve (RMS, spectral flux and centroid)
+///
+/// The curve is normalized to 0-1, decimated for plotting and cached until
+/// the file modification time changes.
+#[command]
+pub async fn compute_audio_energy_curve(
+  file_path: String,
+  window_ms: Option<u32>,
+  hop_ms: Option<u32>,
+  state: tauri::State<'_, MontageState>,
+) -> Result<Vec<EnergyPoint>, String> {
+  let path = PathBuf::from(&file_path);
+  if !path.exists() {
+    return Err(format!("File not found: {file_path}"));
+  }
+
+  let mut audio_analyzer = state.audio_analyzer.write().await;
+  audio_analyzer
+    .compute_energy_curve(
+      &path,
+      window_ms.unwrap_or(energy_curve::DEFAULT_WINDOW_MS),
+      hop_ms.unwrap_or(energy_curve::DEFAULT_HOP_MS),
+    )
+    .await
+    .map_err(|e| format!("Energy curve computation failed: {e}"))
+}
+
+/// Find offsets of simultaneously recorded clips by their audio
+///
+/// Offsets are relative to the clip at `reference_index` (the first clip by default); clips whose audio
+/// does not match reliably get no offset and a low confidence.
+#[command]
+pub async fn sync_clips_by_audio(
+  clip_paths: Vec<String>,
+  reference_index: Option<usize>,
+  options: Option<AudioSyncOptions>,
+) -> Result<Vec<ClipSyncResult>, String> {
+  let paths: Vec<PathBuf> = clip_paths.iter().map(PathBuf::from).collect();
+  multicam_sync::sync_clips_by_audio(
+    &paths,
+    reference_index.unwrap_or(0),
+    &options.unwrap_or_default(),
+  )
+  .await
+  .map_err(|e| format!("Audio sync failed: {e}"))
+}
+
+/// Build a multicam project from clips synced by audio
+///
+/// Each clip is placed into a cell of the layout `template_id`. Without an
+/// explicit `template` a grid with one cell per clip is created under that id.
+#[command]
+pub async fn create_multicam_project(
+  clip_paths: Vec<String>,
+  template_id: String,
+  template: Option<Template>,
+  reference_index: Option<usize>,
+  options: Option<AudioSyncOptions>,
+  project_name: Option<String>,
+) -> Result<ProjectSchema, String> {
+  let paths: Vec<PathBuf> = clip_paths.iter().map(PathBuf::from).collect();
+  let sync = multicam_sync::sync_clips_by_audio(
+    &paths,
+    reference_index.unwrap_or(0),
+    &options.unwrap_or_default(),
+  )
+  .await
+  .map_err(|e| format!("Audio sync failed: {e}"))?;
+
+  let mut clips = Vec::with_capacity(sync.len());
+  for result in sync {
+    let duration = multicam_sync::probe_duration(std::path::Path::new(&result.path))
+      .await
+      .map_err(|e| e.to_string())?;
+    clips.push((result, duration));
+  }
+
+  let template = match template {
+    Some(mut template) => {
+      template.id = template_id;
+      template
+    }
+    None => multicam_sync::default_multicam_template(&template_id, clips.len()),
+  };
+
+  Ok(multicam_sync::build_multicam_project(
+    project_name.as_deref().unwrap_or("Multicam"),
+    &clips,
+    template,
+  ))
+}
+
 /// Get analysis progress for long-running operations
 #[command]
 pub async fn get_analysis_progress(_operation_id: String) -> Result<AnalysisProgress, String> {
@@ -217,6 +388,11 @@ impl CommandRegistry for MontageCommandRegistry {
       analyze_video_composition,
       detect_key_moments,
       generate_montage_plan,
+      analyze_segment_quality,
+      detect_audio_beats,
+      compute_audio_energy_curve,
+      sync_clips_by_audio,
+      create_multicam_project,
       get_analysis_progress,
       update_composition_weights
     ])
@@ -2,10 +2,16 @@
 //!
 //! Analyzes audio content for speech/music detection and rhythm analysis.
 
+use crate::montage_planner::services::beat_detector::decode_mono_pcm_range;
+use crate::montage_planner::services::energy_curve::{
+  compute_energy_curve, EnergyPoint, ENERGY_SAMPLE_RATE, MAX_ENERGY_POINTS,
+};
 use crate::montage_planner::types::*;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 use tokio::process::Command as AsyncCommand;
 
 /// Service for analyzing audio content
@@ -13,6 +19,17 @@ pub struct AudioAnalyzer {
   /// Configuration for audio analysis
   #[allow(dead_code)] // Used for future configuration-based analysis
   config: AudioAnalysisConfig,
+  /// Energy curves by file path, invalidated when the file modification time changes
+  energy_cache: HashMap<PathBuf, CachedEnergyCurve>,
+}
+
+/// Cached energy curve for a file
+#[derive(Debug, Clone)]
+struct CachedEnergyCurve {
+  modified: SystemTime,
+  window_ms: u32,
+  hop_ms: u32,
+  points: Vec<EnergyPoint>,
 }
 
 /// Configuration for audio analysis
@@ -44,14 +61,71 @@ impl Default for AudioAnalysisConfig {
 impl AudioAnalyzer {
   /// Create new audio analyzer
   pub fn new() -> Self {
-    Self {
-      config: AudioAnalysisConfig::default(),
-    }
+    Self::with_config(AudioAnalysisConfig::default())
   }
 
   /// Create analyzer with custom configuration
   pub fn with_config(config: AudioAnalysisConfig) -> Self {
-    Self { config }
+    Self {
+      config,
+      energy_cache: HashMap::new(),
+    }
+  }
+
+  /// Compute the energy curve (RMS, spectral flux and centroid per window)
+  /// of an audio or video file.
+  ///
+  /// The curve is decimated to at most `MAX_ENERGY_POINTS` points. Results
+  /// are cached by path and file modification time.
+  pub async fn compute_energy_curve<P: AsRef<Path>>(
+    &mut self,
+    audio_path: P,
+    window_ms: u32,
+    hop_ms: u32,
+  ) -> Result<Vec<EnergyPoint>, MontageError> {
+    let path = audio_path.as_ref();
+    if window_ms == 0 || hop_ms == 0 {
+      return Err(MontageError::InvalidConfiguration(
+        "Energy curve window and hop must be positive".to_string(),
+      ));
+    }
+
+    let modified = tokio::fs::metadata(path)
+      .await
+      .and_then(|metadata| metadata.modified())
+      .map_err(|_| MontageError::FileNotFound(path.to_string_lossy().to_string()))?;
+
+    if let Some(cached) = self.energy_cache.get(path) {
+      if cached.modified == modified && cached.window_ms == window_ms && cached.hop_ms == hop_ms {
+        return Ok(cached.points.clone());
+      }
+    }
+
+    let samples = decode_mono_pcm_range(path, ENERGY_SAMPLE_RATE, 0.0, None).await?;
+    let points = compute_energy_curve(
+      &samples,
+      ENERGY_SAMPLE_RATE,
+      window_ms,
+      hop_ms,
+      MAX_ENERGY_POINTS,
+    );
+
+    self.energy_cache.insert(
+      path.to_path_buf(),
+      CachedEnergyCurve {
+        modified,
+        window_ms,
+        hop_ms,
+        points: points.clone(),
+      },
+    );
+
+    Ok(points)
+  }
+
+  /// Number of files with a cached energy curve
+  pub fn cached_energy_curves(&self) -> usize {
+    self.energy_cache.len()
   }
 
   /// Analyze audio file and return analysis result
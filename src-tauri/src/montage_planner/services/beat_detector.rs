@@ -0,0 +1,515 @@
+//! Beat Detector Service
+//!
+//! Detects musical beats for music-synced cuts. Audio is decoded to mono PCM
+//! via FFmpeg, onset strength is computed with spectral flux, tempo is
+//! estimated from the onset autocorrelation and beats are tracked along the
+//! estimated period.
+
+use crate::montage_planner::types::MontageError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::f32::consts::PI;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tokio::process::Command as AsyncCommand;
+
+/// Default tolerance for snapping cuts to beats (seconds)
+pub const DEFAULT_SNAP_TOLERANCE: f64 = 0.15;
+
+/// Detected beat
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Beat {
+  /// Beat time in seconds
+  pub time: f64,
+  /// Onset strength at the beat (0.0 - 1.0)
+  pub strength: f32,
+  /// Beat is likely the first beat of a bar
+  pub is_downbeat_guess: bool,
+}
+
+/// Options for beat detection
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BeatDetectionOptions {
+  /// Sample rate audio is decoded at
+  pub sample_rate: u32,
+  /// FFT frame size (power of two)
+  pub frame_size: usize,
+  /// Hop between frames in samples
+  pub hop_size: usize,
+  /// Slowest tempo considered
+  pub min_bpm: f32,
+  /// Fastest tempo considered
+  pub max_bpm: f32,
+  /// Beats per bar used for the downbeat guess
+  pub beats_per_bar: usize,
+}
+
+impl Default for BeatDetectionOptions {
+  fn default() -> Self {
+    Self {
+      sample_rate: 22050,
+      frame_size: 1024,
+      hop_size: 256,
+      min_bpm: 60.0,
+      max_bpm: 200.0,
+      beats_per_bar: 4,
+    }
+  }
+}
+
+/// Beat grid used to align montage cuts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BeatGrid {
+  /// Beats sorted by time
+  pub beats: Vec<Beat>,
+  /// Maximum distance a cut may be moved to reach a beat (seconds)
+  #[serde(default = "default_snap_tolerance")]
+  pub snap_tolerance: f64,
+}
+
+fn default_snap_tolerance() -> f64 {
+  DEFAULT_SNAP_TOLERANCE
+}
+
+impl BeatGrid {
+  /// Create grid with the default snap tolerance
+  pub fn new(beats: Vec<Beat>) -> Self {
+    Self {
+      beats,
+      snap_tolerance: DEFAULT_SNAP_TOLERANCE,
+    }
+  }
+
+  /// Find the beat a cut at `time` should move to.
+  ///
+  /// Only beats within the tolerance and not earlier than `min_time` are
+  /// considered. With `prefer_strong` the strongest beat (downbeats first)
+  /// wins, otherwise the nearest one.
+  pub fn snap(&self, time: f64, min_time: f64, prefer_strong: bool) -> Option<f64> {
+    let candidates = self
+      .beats
+      .iter()
+      .filter(|beat| beat.time >= min_time && (beat.time - time).abs() <= self.snap_tolerance);
+
+    let distance = |beat: &Beat| (beat.time - time).abs();
+    let beat = if prefer_strong {
+      candidates.max_by(|a, b| {
+        (a.is_downbeat_guess, a.strength)
+          .partial_cmp(&(b.is_downbeat_guess, b.strength))
+          .unwrap_or(std::cmp::Ordering::Equal)
+          .then_with(|| distance(b).total_cmp(&distance(a)))
+      })
+    } else {
+      candidates.min_by(|a, b| distance(a).total_cmp(&distance(b)))
+    };
+
+    beat.map(|beat| beat.time)
+  }
+}
+
+/// Cached detection result for a file
+#[derive(Debug, Clone)]
+struct CachedBeats {
+  modified: SystemTime,
+  options: BeatDetectionOptions,
+  beats: Vec<Beat>,
+}
+
+/// Service for detecting musical beats
+pub struct BeatDetector {
+  /// Detection options
+  options: BeatDetectionOptions,
+  /// Results by file path, invalidated when the file modification time changes
+  cache: HashMap<PathBuf, CachedBeats>,
+}
+
+impl BeatDetector {
+  /// Create new beat detector
+  pub fn new() -> Self {
+    Self::with_options(BeatDetectionOptions::default())
+  }
+
+  /// Create detector with custom options
+  pub fn with_options(options: BeatDetectionOptions) -> Self {
+    Self {
+      options,
+      cache: HashMap::new(),
+    }
+  }
+
+  /// Current detection options
+  pub fn options(&self) -> &BeatDetectionOptions {
+    &self.options
+  }
+
+  /// Detect beats in an audio (or video) file.
+  ///
+  /// Results are cached by path and file modification time.
+  pub async fn detect_beats<P: AsRef<Path>>(
+    &mut self,
+    audio_path: P,
+    options: Option<BeatDetectionOptions>,
+  ) -> Result<Vec<Beat>, MontageError> {
+    let path = audio_path.as_ref();
+    let options = options.unwrap_or_else(|| self.options.clone());
+
+    let modified = tokio::fs::metadata(path)
+      .await
+      .and_then(|metadata| metadata.modified())
+      .map_err(|_| MontageError::FileNotFound(path.to_string_lossy().to_string()))?;
+
+    if let Some(cached) = self.cache.get(path) {
+      if cached.modified == modified && cached.options == options {
+        return Ok(cached.beats.clone());
+      }
+    }
+
+    let samples = decode_mono_pcm(path, options.sample_rate).await?;
+    let beats = detect_beats_in_samples(&samples, &options);
+
+    self.cache.insert(
+      path.to_path_buf(),
+      CachedBeats {
+        modified,
+        options,
+        beats: beats.clone(),
+      },
+    );
+
+    Ok(beats)
+  }
+
+  /// Number of cached files
+  pub fn cached_files(&self) -> usize {
+    self.cache.len()
+  }
+
+  /// Drop cached results
+  pub fn clear_cache(&mut self) {
+    self.cache.clear();
+  }
+}
+
+impl Default for BeatDetector {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+/// Decode audio track to mono f32 PCM via FFmpeg
+async fn decode_mono_pcm(path: &Path, sample_rate: u32) -> Result<Vec<f32>, MontageError> {
+  decode_mono_pcm_range(path, sample_rate, 0.0, None).await
+}
+
+/// Decode `duration` seconds of audio starting at `start` to mono f32 PCM.
+///
+/// Without a duration the file is decoded to the end.
+pub(crate) async fn decode_mono_pcm_range(
+  path: &Path,
+  sample_rate: u32,
+  start: f64,
+  duration: Option<f64>,
+) -> Result<Vec<f32>, MontageError> {
+  let mut command = AsyncCommand::new("ffmpeg");
+  if start > 0.0 {
+    command.args(["-ss", &start.to_string()]);
+  }
+  command.arg("-i").arg(path);
+  if let Some(duration) = duration {
+    command.args(["-t", &duration.to_string()]);
+  }
+  let output = command
+    .args([
+      "-vn",
+      "-f",
+      "f32le",
+      "-ac",
+      "1",
+      "-ar",
+      &sample_rate.to_string(),
+      "-loglevel",
+      "error",
+      "-",
+    ])
+    .output()
+    .await
+    .map_err(|e| MontageError::AudioAnalysisError(format!("Failed to run FFmpeg: {e}")))?;
+
+  if !output.status.success() {
+    return Err(MontageError::AudioAnalysisError(format!(
+      "FFmpeg failed to decode audio: {}",
+      String::from_utf8_lossy(&output.stderr).trim()
+    )));
+  }
+
+  Ok(
+    output
+      .stdout
+      .chunks_exact(4)
+      .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+      .collect(),
+  )
+}
+
+/// Detect beats in decoded mono samples
+pub fn detect_beats_in_samples(samples: &[f32], options: &BeatDetectionOptions) -> Vec<Beat> {
+  let onsets = onset_strength(samples, options.frame_size, options.hop_size);
+  let frame_rate = options.sample_rate as f64 / options.hop_size as f64;
+
+  let Some(period) = estimate_period(&onsets, frame_rate, options.min_bpm, options.max_bpm) else {
+    return Vec::new();
+  };
+
+  let max_onset = onsets.iter().copied().fold(0.0f32, f32::max);
+  if max_onset <= 0.0 {
+    return Vec::new();
+  }
+
+  // Flux of frame i compares it to frame i - 1; the change is attributed
+  // to the middle of the window
+  let offset = options.frame_size as f64 / 2.0 / options.sample_rate as f64;
+  let mut beats: Vec<Beat> = track_beats(&onsets, period)
+    .into_iter()
+    .map(|frame| Beat {
+      time: frame as f64 / frame_rate + offset,
+      strength: onsets[frame] / max_onset,
+      is_downbeat_guess: false,
+    })
+    .collect();
+
+  mark_downbeats(&mut beats, options.beats_per_bar);
+  beats
+}
+
+/// Estimate tempo in BPM from decoded samples
+pub fn estimate_tempo(samples: &[f32], options: &BeatDetectionOptions) -> Option<f32> {
+  let onsets = onset_strength(samples, options.frame_size, options.hop_size);
+  let frame_rate = options.sample_rate as f64 / options.hop_size as f64;
+  estimate_period(&onsets, frame_rate, options.min_bpm, options.max_bpm)
+    .map(|period| (60.0 * frame_rate / period) as f32)
+}
+
+/// Onset strength envelope (half-wave rectified spectral flux)
+fn onset_strength(samples: &[f32], frame_size: usize, hop_size: usize) -> Vec<f32> {
+  if frame_size < 2 || !frame_size.is_power_of_two() || hop_size == 0 {
+    return Vec::new();
+  }
+  if samples.len() < frame_size {
+    return Vec::new();
+  }
+
+  let window: Vec<f32> = (0..frame_size)
+    .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / frame_size as f32).cos())
+    .collect();
+  let frame_count = (samples.len() - frame_size) / hop_size + 1;
+
+  let mut previous = vec![0.0f32; frame_size / 2];
+  let mut flux = Vec::with_capacity(frame_count);
+  let mut re = vec![0.0f32; frame_size];
+  let mut im = vec![0.0f32; frame_size];
+
+  for frame in 0..frame_count {
+    let start = frame * hop_size;
+    for i in 0..frame_size {
+      re[i] = samples[start + i] * window[i];
+      im[i] = 0.0;
+    }
+    fft(&mut re, &mut im);
+
+    let mut value = 0.0;
+    for (bin, previous) in previous.iter_mut().enumerate() {
+      // Log compression keeps quiet onsets from being masked by loud ones
+      let magnitude = (1.0 + 100.0 * (re[bin] * re[bin] + im[bin] * im[bin]).sqrt()).ln();
+      if frame > 0 {
+        value += (magnitude - *previous).max(0.0);
+      }
+      *previous = magnitude;
+    }
+    flux.push(value);
+  }
+
+  // Subtract the local mean so that only peaks above the background remain
+  let radius = 8;
+  (0..flux.len())
+    .map(|i| {
+      let from = i.saturating_sub(radius);
+      let to = (i + radius + 1).min(flux.len());
+      let mean = flux[from..to].iter().sum::<f32>() / (to - from) as f32;
+      (flux[i] - mean).max(0.0)
+    })
+    .collect()
+}
+
+/// In-place iterative radix-2 FFT
+pub(crate) fn fft(re: &mut [f32], im: &mut [f32]) {
+  let n = re.len();
+
+  let mut j = 0;
+  for i in 1..n {
+    let mut bit = n >> 1;
+    while j & bit != 0 {
+      j ^= bit;
+      bit >>= 1;
+    }
+    j |= bit;
+    if i < j {
+      re.swap(i, j);
+      im.swap(i, j);
+    }
+  }
+
+  let mut len = 2;
+  while len <= n {
+    let angle = -2.0 * PI / len as f32;
+    let (w_im, w_re) = angle.sin_cos();
+    for start in (0..n).step_by(len) {
+      let (mut cur_re, mut cur_im) = (1.0f32, 0.0f32);
+      for k in 0..len / 2 {
+        let a = start + k;
+        let b = a + len / 2;
+        let t_re = re[b] * cur_re - im[b] * cur_im;
+        let t_im = re[b] * cur_im + im[b] * cur_re;
+        re[b] = re[a] - t_re;
+        im[b] = im[a] - t_im;
+        re[a] += t_re;
+        im[a] += t_im;
+        let next_re = cur_re * w_re - cur_im * w_im;
+        cur_im = cur_re * w_im + cur_im * w_re;
+        cur_re = next_re;
+      }
+    }
+    len <<= 1;
+  }
+}
+
+/// Beat period in frames from the onset autocorrelation
+fn estimate_period(onsets: &[f32], frame_rate: f64, min_bpm: f32, max_bpm: f32) -> Option<f64> {
+  if min_bpm <= 0.0 || max_bpm <= min_bpm {
+    return None;
+  }
+
+  let min_lag = ((60.0 / max_bpm as f64) * frame_rate).floor().max(1.0) as usize;
+  let max_lag = ((60.0 / min_bpm as f64) * frame_rate).ceil() as usize;
+  if onsets.len() <= max_lag + 1 {
+    return None;
+  }
+
+  // Smoothing spreads peaks over neighbouring frames, so periods between
+  // whole frames do not lose to their integer multiples
+  let smoothed: Vec<f32> = (0..onsets.len())
+    .map(|i| {
+      [(-2, 1.0), (-1, 2.0), (0, 3.0), (1, 2.0), (2, 1.0)]
+        .iter()
+        .filter_map(|&(shift, weight)| {
+          let index = i.checked_add_signed(shift)?;
+          onsets.get(index).map(|value| value * weight)
+        })
+        .sum()
+    })
+    .collect();
+
+  // Unnormalized sums favour the shorter lag over its multiples
+  let autocorrelation = |lag: usize| -> f32 {
+    smoothed
+      .iter()
+      .zip(&smoothed[lag..])
+      .map(|(a, b)| a * b)
+      .sum()
+  };
+  let scores: Vec<f32> = (min_lag - 1..=max_lag + 1).map(autocorrelation).collect();
+
+  let (best, best_score) = scores[1..scores.len() - 1]
+    .iter()
+    .enumerate()
+    .map(|(i, score)| (i + 1, *score))
+    .max_by(|a, b| a.1.total_cmp(&b.1))?;
+  if best_score <= 0.0 {
+    return None;
+  }
+
+  // Parabolic interpolation refines the period between whole frames
+  let (left, right) = (scores[best - 1], scores[best + 1]);
+  let denominator = left - 2.0 * best_score + right;
+  let shift = if denominator.abs() > f32::EPSILON {
+    (0.5 * (left - right) / denominator).clamp(-0.5, 0.5)
+  } else {
+    0.0
+  };
+
+  Some((min_lag - 1 + best) as f64 + shift as f64)
+}
+
+/// Track beats along the estimated period, returning onset frame indices
+fn track_beats(onsets: &[f32], period: f64) -> Vec<usize> {
+  let steps = period.round().max(1.0) as usize;
+
+  // Phase with the largest onset sum over the whole track
+  let phase = (0..steps.min(onsets.len()))
+    .max_by(|&a, &b| {
+      let score = |phase: usize| -> f32 {
+        let mut position = phase as f64;
+        let mut sum = 0.0;
+        while (position.round() as usize) < onsets.len() {
+          sum += onsets[position.round() as usize];
+          position += period;
+        }
+        sum
+      };
+      score(a).total_cmp(&score(b))
+    })
+    .unwrap_or(0);
+
+  // Each beat snaps to the strongest onset near its prediction,
+  // which lets the grid follow small tempo drift
+  let radius = (period * 0.15).round().max(1.0) as usize;
+  let mut beats: Vec<(usize, bool)> = Vec::new();
+  let mut expected = phase as f64;
+  while (expected.round() as usize) < onsets.len() {
+    let center = expected.round() as usize;
+    let from = center.saturating_sub(radius);
+    let to = (center + radius + 1).min(onsets.len());
+    let observed = (from..to)
+      .max_by(|&a, &b| onsets[a].total_cmp(&onsets[b]))
+      .filter(|&frame| onsets[frame] > 0.0);
+    let frame = observed.unwrap_or(center);
+
+    if beats.last().is_none_or(|&(last, _)| frame > last) {
+      beats.push((frame, observed.is_some()));
+    }
+    expected = frame as f64 + period;
+  }
+
+  // Predicted beats before the music starts and after it ends are dropped
+  let first = beats.iter().position(|&(_, observed)| observed);
+  let last = beats.iter().rposition(|&(_, observed)| observed);
+  match (first, last) {
+    (Some(first), Some(last)) => beats[first..=last]
+      .iter()
+      .map(|&(frame, _)| frame)
+      .collect(),
+    _ => Vec::new(),
+  }
+}
+
+/// Mark the strongest bar position as downbeats
+fn mark_downbeats(beats: &mut [Beat], beats_per_bar: usize) {
+  if beats_per_bar < 2 || beats.len() < beats_per_bar {
+    return;
+  }
+
+  let mut position_strength = vec![0.0f32; beats_per_bar];
+  for (index, beat) in beats.iter().enumerate() {
+    position_strength[index % beats_per_bar] += beat.strength;
+  }
+  let downbeat = position_strength
+    .iter()
+    .enumerate()
+    .max_by(|a, b| a.1.total_cmp(b.1))
+    .map(|(index, _)| index)
+    .unwrap_or(0);
+
+  for (index, beat) in beats.iter_mut().enumerate() {
+    beat.is_downbeat_guess = index % beats_per_bar == downbeat;
+  }
+}
@@ -0,0 +1,233 @@
+//! Energy Curve
+//!
+//! Intensity curve of a soundtrack for placing climactic cuts. Audio is
+//! decoded to mono PCM via FFmpeg and split into overlapping windows; every
+//! window gets its RMS level, spectral flux and spectral centroid. Values
+//! are normalized to absolute scales, so curves of different files are
+//! comparable.
+
+use crate::montage_planner::services::beat_detector::fft;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+
+/// Sample rate audio is decoded at for the energy curve
+pub const ENERGY_SAMPLE_RATE: u32 = 16000;
+
+/// Default analysis window (milliseconds)
+pub const DEFAULT_WINDOW_MS: u32 = 50;
+
+/// Default hop between windows (milliseconds)
+pub const DEFAULT_HOP_MS: u32 = 25;
+
+/// Maximum number of points returned regardless of file length
+pub const MAX_ENERGY_POINTS: usize = 2000;
+
+/// RMS level mapped to 0 (dBFS)
+const RMS_FLOOR_DB: f32 = -60.0;
+
+/// Distance from a cut to an energy peak that still counts as a hit (seconds)
+const PEAK_TOLERANCE: f64 = 0.3;
+
+/// Neighbourhood a peak must dominate (seconds)
+const PEAK_RADIUS: f64 = 0.5;
+
+/// Neighbourhood used to measure energy stability (seconds)
+const STABILITY_RADIUS: f64 = 1.0;
+
+/// Point of the energy curve
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EnergyPoint {
+  /// Window start in seconds
+  pub time: f64,
+  /// RMS level, -60..0 dBFS mapped to 0..1
+  pub rms: f32,
+  /// Half-wave rectified spectral change relative to the window spectrum (0..1)
+  pub flux: f32,
+  /// Spectral centroid relative to the Nyquist frequency (0..1)
+  pub centroid: f32,
+}
+
+impl EnergyPoint {
+  /// Combined intensity used by the plan generator (0..1)
+  pub fn intensity(&self) -> f32 {
+    0.7 * self.rms + 0.3 * self.flux
+  }
+}
+
+/// Compute the energy curve of decoded mono samples.
+///
+/// The curve is decimated to at most `max_points` points.
+pub fn compute_energy_curve(
+  samples: &[f32],
+  sample_rate: u32,
+  window_ms: u32,
+  hop_ms: u32,
+  max_points: usize,
+) -> Vec<EnergyPoint> {
+  let window = (sample_rate as usize * window_ms as usize / 1000).max(2);
+  let hop = (sample_rate as usize * hop_ms as usize / 1000).max(1);
+  if samples.len() < window {
+    return Vec::new();
+  }
+
+  let fft_size = window.next_power_of_two();
+  let hann: Vec<f32> = (0..window)
+    .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / window as f32).cos())
+    .collect();
+  let bins = fft_size / 2;
+  let mut previous = vec![0.0f32; bins];
+  let mut re = vec![0.0f32; fft_size];
+  let mut im = vec![0.0f32; fft_size];
+
+  let frame_count = (samples.len() - window) / hop + 1;
+  let mut points = Vec::with_capacity(frame_count);
+  for frame in 0..frame_count {
+    let start = frame * hop;
+    let chunk = &samples[start..start + window];
+
+    let rms = (chunk.iter().map(|s| s * s).sum::<f32>() / window as f32).sqrt();
+
+    re.fill(0.0);
+    im.fill(0.0);
+    for (i, sample) in chunk.iter().enumerate() {
+      re[i] = sample * hann[i];
+    }
+    fft(&mut re, &mut im);
+
+    let mut total = 0.0f32;
+    let mut weighted = 0.0f32;
+    let mut rise = 0.0f32;
+    for (bin, previous) in previous.iter_mut().enumerate() {
+      let magnitude = (re[bin] * re[bin] + im[bin] * im[bin]).sqrt();
+      total += magnitude;
+      weighted += magnitude * bin as f32;
+      if frame > 0 {
+        rise += (magnitude - *previous).max(0.0);
+      }
+      *previous = magnitude;
+    }
+
+    points.push(EnergyPoint {
+      time: start as f64 / sample_rate as f64,
+      rms: normalize_rms(rms),
+      flux: if total > f32::EPSILON {
+        (rise / total).clamp(0.0, 1.0)
+      } else {
+        0.0
+      },
+      centroid: if total > f32::EPSILON {
+        weighted / total / bins as f32
+      } else {
+        0.0
+      },
+    });
+  }
+
+  decimate(points, max_points)
+}
+
+/// Map RMS amplitude to 0..1 on a dBFS scale
+fn normalize_rms(rms: f32) -> f32 {
+  if rms <= 0.0 {
+    return 0.0;
+  }
+  let db = 20.0 * rms.log10();
+  ((db - RMS_FLOOR_DB) / -RMS_FLOOR_DB).clamp(0.0, 1.0)
+}
+
+/// Average consecutive points so that at most `max_points` remain.
+///
+/// Flux keeps the bucket maximum: averaging would hide short onsets.
+pub fn decimate(points: Vec<EnergyPoint>, max_points: usize) -> Vec<EnergyPoint> {
+  if max_points == 0 || points.len() <= max_points {
+    return points;
+  }
+
+  let bucket = points.len().div_ceil(max_points);
+  points
+    .chunks(bucket)
+    .map(|chunk| {
+      let count = chunk.len() as f32;
+      EnergyPoint {
+        time: chunk[0].time,
+        rms: chunk.iter().map(|p| p.rms).sum::<f32>() / count,
+        flux: chunk.iter().map(|p| p.flux).fold(0.0, f32::max),
+        centroid: chunk.iter().map(|p| p.centroid).sum::<f32>() / count,
+      }
+    })
+    .collect()
+}
+
+/// Energy curve prepared for repeated lookups during plan generation
+#[derive(Debug, Clone, Default)]
+pub struct EnergyProfile {
+  times: Vec<f64>,
+  intensity: Vec<f32>,
+  peaks: Vec<bool>,
+  stability: Vec<f32>,
+}
+
+impl EnergyProfile {
+  /// Precompute peaks and stability of a curve sorted by time
+  pub fn new(curve: &[EnergyPoint]) -> Self {
+    let times: Vec<f64> = curve.iter().map(|point| point.time).collect();
+    let intensity: Vec<f32> = curve.iter().map(EnergyPoint::intensity).collect();
+
+    let neighbourhood = |index: usize, radius: f64| {
+      let from = times.partition_point(|&t| t < times[index] - radius);
+      let to = times.partition_point(|&t| t <= times[index] + radius);
+      &intensity[from..to]
+    };
+
+    let peaks = (0..curve.len())
+      .map(|index| {
+        let window = neighbourhood(index, PEAK_RADIUS);
+        let value = intensity[index];
+        // A peak dominates its neighbourhood and rises above its mean
+        let mean = window.iter().sum::<f32>() / window.len() as f32;
+        window.iter().all(|&other| other <= value) && value > mean
+      })
+      .collect();
+
+    let stability = (0..curve.len())
+      .map(|index| {
+        let window = neighbourhood(index, STABILITY_RADIUS);
+        let mean = window.iter().sum::<f32>() / window.len() as f32;
+        let deviation =
+          (window.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / window.len() as f32).sqrt();
+        (1.0 - 4.0 * deviation).clamp(0.0, 1.0)
+      })
+      .collect();
+
+    Self {
+      times,
+      intensity,
+      peaks,
+      stability,
+    }
+  }
+
+  /// Curve has no points
+  pub fn is_empty(&self) -> bool {
+    self.times.is_empty()
+  }
+
+  /// Intensity of the strongest peak near `time`, 0 when there is none
+  pub fn peak_score(&self, time: f64) -> f32 {
+    let from = self.times.partition_point(|&t| t < time - PEAK_TOLERANCE);
+    let to = self.times.partition_point(|&t| t <= time + PEAK_TOLERANCE);
+    (from..to)
+      .filter(|&index| self.peaks[index])
+      .map(|index| self.intensity[index])
+      .fold(0.0, f32::max)
+  }
+
+  /// Stability of the energy around `time` (1 - steady, 0 - fluctuating)
+  pub fn stability_at(&self, time: f64) -> f32 {
+    if self.is_empty() {
+      return 0.0;
+    }
+    let index = self.times.partition_point(|&t| t <= time).saturating_sub(1);
+    self.stability[index]
+  }
+}
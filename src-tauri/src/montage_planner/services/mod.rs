@@ -4,9 +4,12 @@
 
 pub mod activity_calculator;
 pub mod audio_analyzer;
+pub mod beat_detector;
 pub mod composition_analyzer;
 pub mod emotion_detector;
+pub mod energy_curve;
 pub mod moment_detector;
+pub mod multicam_sync;
 pub mod plan_generator;
 pub mod quality_analyzer;
 pub mod video_processor;
@@ -17,9 +20,12 @@ pub mod tests;
 // Re-export main services
 pub use activity_calculator::ActivityCalculator;
 pub use audio_analyzer::AudioAnalyzer;
+pub use beat_detector::{Beat, BeatDetectionOptions, BeatDetector, BeatGrid};
 pub use composition_analyzer::CompositionAnalyzer;
 pub use emotion_detector::EmotionDetector;
+pub use energy_curve::{EnergyPoint, EnergyProfile};
 pub use moment_detector::MomentDetector;
+pub use multicam_sync::{AudioSyncOptions, ClipSyncResult};
 pub use plan_generator::PlanGenerator;
-pub use quality_analyzer::VideoQualityAnalyzer;
+pub use quality_analyzer::{QualityScore, VideoQualityAnalyzer};
 pub use video_processor::VideoProcessor;
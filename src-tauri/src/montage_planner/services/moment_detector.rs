@@ -111,6 +111,7 @@ impl MomentDetector {
           total_score,
           description: self.generate_description(detection, &category),
           tags: self.generate_tags(detection, &category),
+          source_file: None,
         });
       } else if let Some(ref mut moment) = current_moment {
         // Check if we should end the current moment
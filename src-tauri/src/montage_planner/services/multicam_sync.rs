@@ -0,0 +1,467 @@
+//! Multicam Audio Sync Service
+//!
+//! Aligns clips of one event recorded by several cameras. A mono PCM window
+//! is decoded from every file, cross-correlated against the reference clip
+//! with an FFT and the correlation peak gives the offset between files.
+//! Silent or unrelated audio yields a low confidence instead of an offset.
+
+use crate::montage_planner::services::beat_detector::{decode_mono_pcm_range, fft};
+use crate::montage_planner::types::MontageError;
+use crate::video_compiler::schema::{
+  AlignX, AlignY, Clip, FitMode, ProjectSchema, Template, TemplateCell, TemplateType, Track,
+  TrackType,
+};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tokio::process::Command as AsyncCommand;
+
+/// Sample rate audio is decoded at for syncing
+pub const DEFAULT_SYNC_SAMPLE_RATE: u32 = 4000;
+
+/// Longest stretch of audio analysed per file (seconds)
+pub const DEFAULT_MAX_ANALYSIS_SECONDS: f64 = 180.0;
+
+/// Offsets with a lower confidence are not reported
+pub const DEFAULT_MIN_CONFIDENCE: f32 = 0.3;
+
+/// RMS below which a window is treated as silence (about -60 dBFS)
+const SILENCE_RMS: f32 = 1e-3;
+
+/// Lags this close to the main peak are ignored when looking for a rival peak
+const PEAK_EXCLUSION_SECONDS: f64 = 0.05;
+
+/// Normalized correlation treated as full agreement between two microphones
+const FULL_CORRELATION: f32 = 0.5;
+
+/// Options for audio-based clip sync
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioSyncOptions {
+  /// Sample rate audio is decoded at
+  pub sample_rate: u32,
+  /// Longest stretch of audio analysed per file (seconds)
+  pub max_duration: f64,
+  /// Start of the analysed range in every file (seconds)
+  pub range_start: f64,
+  /// Length of the analysed range, capped by `max_duration`
+  pub range_duration: Option<f64>,
+  /// Offsets with a lower confidence are reported as unknown
+  pub min_confidence: f32,
+}
+
+impl Default for AudioSyncOptions {
+  fn default() -> Self {
+    Self {
+      sample_rate: DEFAULT_SYNC_SAMPLE_RATE,
+      max_duration: DEFAULT_MAX_ANALYSIS_SECONDS,
+      range_start: 0.0,
+      range_duration: None,
+      min_confidence: DEFAULT_MIN_CONFIDENCE,
+    }
+  }
+}
+
+impl AudioSyncOptions {
+  /// Length of the decoded window (seconds)
+  pub fn window_duration(&self) -> f64 {
+    self.range_duration.map_or(self.max_duration, |duration| {
+      duration.min(self.max_duration)
+    })
+  }
+
+  fn validate(&self) -> Result<(), MontageError> {
+    if self.sample_rate == 0 {
+      return Err(MontageError::InvalidConfiguration(
+        "Sample rate must be positive".to_string(),
+      ));
+    }
+    if self.window_duration() <= 0.0 || self.range_start < 0.0 {
+      return Err(MontageError::InvalidConfiguration(
+        "Analysis range must be positive".to_string(),
+      ));
+    }
+    Ok(())
+  }
+}
+
+/// Offset between two audio signals found by cross-correlation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AudioOffsetEstimate {
+  /// Start of the other signal relative to the reference (seconds)
+  pub offset_seconds: f64,
+  /// Confidence of the match (0.0 - 1.0)
+  pub confidence: f32,
+}
+
+/// Sync result for one clip
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClipSyncResult {
+  /// Clip file path
+  pub path: String,
+  /// Start of the clip relative to the reference clip (seconds).
+  ///
+  /// `None` when the audio match is not reliable enough to place the clip.
+  pub offset_seconds: Option<f64>,
+  /// Confidence of the match (0.0 - 1.0)
+  pub confidence: f32,
+  /// Clip is the reference all offsets are measured against
+  pub is_reference: bool,
+}
+
+/// Find offsets of clips recorded simultaneously by comparing their audio.
+///
+/// Offsets are measured against the clip at `reference_index`; a positive
+/// offset means the clip started recording later than the reference.
+pub async fn sync_clips_by_audio(
+  clip_paths: &[PathBuf],
+  reference_index: usize,
+  options: &AudioSyncOptions,
+) -> Result<Vec<ClipSyncResult>, MontageError> {
+  options.validate()?;
+  let reference_path = clip_paths.get(reference_index).ok_or_else(|| {
+    MontageError::InvalidConfiguration(format!(
+      "Reference index {reference_index} is out of range for {} clips",
+      clip_paths.len()
+    ))
+  })?;
+
+  let reference = decode_window(reference_path, options).await?;
+
+  let mut results = Vec::with_capacity(clip_paths.len());
+  for (index, path) in clip_paths.iter().enumerate() {
+    if index == reference_index {
+      results.push(ClipSyncResult {
+        path: path.to_string_lossy().to_string(),
+        offset_seconds: Some(0.0),
+        confidence: 1.0,
+        is_reference: true,
+      });
+      continue;
+    }
+
+    let samples = decode_window(path, options).await?;
+    let estimate = estimate_offset(&reference, &samples, options.sample_rate);
+    results.push(ClipSyncResult {
+      path: path.to_string_lossy().to_string(),
+      offset_seconds: (estimate.confidence >= options.min_confidence)
+        .then_some(estimate.offset_seconds),
+      confidence: estimate.confidence,
+      is_reference: false,
+    });
+  }
+
+  Ok(results)
+}
+
+/// Decode the analysed window of a clip
+async fn decode_window(path: &Path, options: &AudioSyncOptions) -> Result<Vec<f32>, MontageError> {
+  if !path.exists() {
+    return Err(MontageError::FileNotFound(
+      path.to_string_lossy().to_string(),
+    ));
+  }
+  decode_mono_pcm_range(
+    path,
+    options.sample_rate,
+    options.range_start,
+    Some(options.window_duration()),
+  )
+  .await
+}
+
+/// Estimate the offset of `other` relative to `reference`.
+///
+/// Confidence combines the normalized correlation at the peak with how much
+/// the peak stands out from the strongest rival lag, so silence, unrelated
+/// audio and repetitive sound (where several lags fit) score low.
+pub fn estimate_offset(reference: &[f32], other: &[f32], sample_rate: u32) -> AudioOffsetEstimate {
+  let unknown = AudioOffsetEstimate {
+    offset_seconds: 0.0,
+    confidence: 0.0,
+  };
+  if rms(reference) < SILENCE_RMS || rms(other) < SILENCE_RMS {
+    return unknown;
+  }
+
+  let reference = remove_mean(reference);
+  let other = remove_mean(other);
+  let energy = |samples: &[f32]| samples.iter().map(|s| s * s).sum::<f32>();
+  let norm = (energy(&reference) * energy(&other)).sqrt();
+  if norm <= f32::EPSILON {
+    return unknown;
+  }
+
+  // Linear (not circular) correlation needs room for every lag
+  let n = (reference.len() + other.len()).next_power_of_two();
+  let (mut ref_re, mut ref_im) = padded(&reference, n);
+  let (mut other_re, mut other_im) = padded(&other, n);
+  fft(&mut ref_re, &mut ref_im);
+  fft(&mut other_re, &mut other_im);
+
+  // R * conj(O), then the inverse FFT as conj(FFT(conj(X))) / n
+  let (mut corr_re, mut corr_im): (Vec<f32>, Vec<f32>) = (0..n)
+    .map(|k| {
+      (
+        ref_re[k] * other_re[k] + ref_im[k] * other_im[k],
+        -(ref_im[k] * other_re[k] - ref_re[k] * other_im[k]),
+      )
+    })
+    .unzip();
+  fft(&mut corr_re, &mut corr_im);
+
+  // corr[lag] = sum(reference[t + lag] * other[t]); lags wrap around n
+  let min_lag = -(other.len() as i64 - 1);
+  let max_lag = reference.len() as i64 - 1;
+  let correlation = |lag: i64| {
+    let index = (if lag >= 0 { lag } else { lag + n as i64 }) as usize;
+    (corr_re[index] / n as f32).abs()
+  };
+
+  let (peak_lag, peak) = (min_lag..=max_lag)
+    .map(|lag| (lag, correlation(lag)))
+    .max_by(|a, b| a.1.total_cmp(&b.1))
+    .unwrap_or((0, 0.0));
+  if peak <= 0.0 {
+    return unknown;
+  }
+
+  let exclusion = (PEAK_EXCLUSION_SECONDS * sample_rate as f64).ceil() as i64;
+  let rival = (min_lag..=max_lag)
+    .filter(|lag| (lag - peak_lag).abs() > exclusion)
+    .map(correlation)
+    .fold(0.0f32, f32::max);
+
+  let prominence = 1.0 - rival / peak;
+  let agreement = (peak / norm / FULL_CORRELATION).min(1.0);
+
+  AudioOffsetEstimate {
+    offset_seconds: peak_lag as f64 / sample_rate as f64,
+    confidence: (prominence * agreement).clamp(0.0, 1.0),
+  }
+}
+
+fn rms(samples: &[f32]) -> f32 {
+  if samples.is_empty() {
+    return 0.0;
+  }
+  (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+}
+
+fn remove_mean(samples: &[f32]) -> Vec<f32> {
+  let mean = samples.iter().sum::<f32>() / samples.len().max(1) as f32;
+  samples.iter().map(|s| s - mean).collect()
+}
+
+fn padded(samples: &[f32], n: usize) -> (Vec<f32>, Vec<f32>) {
+  let mut re = vec![0.0f32; n];
+  re[..samples.len()].copy_from_slice(samples);
+  (re, vec![0.0f32; n])
+}
+
+/// Grid layout with one cell per camera, used when no template is supplied
+pub fn default_multicam_template(template_id: &str, screens: usize) -> Template {
+  let screens = screens.max(1);
+  let columns = (screens as f64).sqrt().ceil() as usize;
+  let rows = screens.div_ceil(columns);
+  let (width, height) = (100.0 / columns as f32, 100.0 / rows as f32);
+
+  let mut template = Template::new(
+    TemplateType::Grid,
+    format!("{screens} Camera Grid"),
+    screens,
+  );
+  template.id = template_id.to_string();
+  template.cells = (0..screens)
+    .map(|index| TemplateCell {
+      index,
+      x: (index % columns) as f32 * width,
+      y: (index / columns) as f32 * height,
+      width,
+      height,
+      fit_mode: FitMode::Fit,
+      align_x: AlignX::Center,
+      align_y: AlignY::Center,
+      scale: None,
+    })
+    .collect();
+  template
+}
+
+/// Build a project with every synced clip placed into a template cell.
+///
+/// Each camera gets its own video track; clips start at their sync offsets
+/// shifted so the earliest clip starts at zero. Clips without a reliable
+/// offset are placed at the reference start.
+pub fn build_multicam_project(
+  name: &str,
+  clips: &[(ClipSyncResult, f64)],
+  template: Template,
+) -> ProjectSchema {
+  let mut project = ProjectSchema::new(name.to_string());
+
+  let offset = |result: &ClipSyncResult| result.offset_seconds.unwrap_or(0.0);
+  let earliest = clips
+    .iter()
+    .map(|(result, _)| offset(result))
+    .fold(0.0f64, f64::min);
+  let cells = template.cells.len().max(1);
+
+  let mut end = 0.0f64;
+  for (index, (result, duration)) in clips.iter().enumerate() {
+    let start = offset(result) - earliest;
+    let mut clip = Clip::new(PathBuf::from(&result.path), start, *duration);
+    clip.template_id = Some(template.id.clone());
+    clip.template_position = Some(index % cells);
+
+    let mut track = Track::new(TrackType::Video, format!("Camera {}", index + 1));
+    track.clips.push(clip);
+    project.tracks.push(track);
+    end = end.max(start + duration);
+  }
+
+  project.timeline.duration = end;
+  project.templates.push(template);
+  project
+}
+
+/// Duration of a media file in seconds via ffprobe
+pub async fn probe_duration(path: &Path) -> Result<f64, MontageError> {
+  let output = AsyncCommand::new("ffprobe")
+    .args([
+      "-v",
+      "error",
+      "-show_entries",
+      "format=duration",
+      "-of",
+      "default=noprint_wrappers=1:nokey=1",
+    ])
+    .arg(path)
+    .output()
+    .await
+    .map_err(|e| MontageError::VideoAnalysisError(format!("Failed to run ffprobe: {e}")))?;
+
+  String::from_utf8_lossy(&output.stdout)
+    .trim()
+    .parse::<f64>()
+    .map_err(|_| {
+      MontageError::VideoAnalysisError(format!(
+        "Failed to read duration of {}",
+        path.to_string_lossy()
+      ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const SAMPLE_RATE: u32 = 1000;
+
+  /// Deterministic broadband noise
+  fn noise(len: usize, seed: u32) -> Vec<f32> {
+    let mut state = seed;
+    (0..len)
+      .map(|_| {
+        state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (state >> 8) as f32 / (1u32 << 24) as f32 * 2.0 - 1.0
+      })
+      .collect()
+  }
+
+  #[test]
+  fn test_estimate_offset_for_late_start() {
+    let event = noise(10_000, 7);
+    let reference = event[..8000].to_vec();
+    // Second camera started 0.3 s later and has its own background noise
+    let hiss = noise(7000, 99);
+    let other: Vec<f32> = event[300..7300]
+      .iter()
+      .zip(&hiss)
+      .map(|(s, n)| s * 0.6 + n * 0.1)
+      .collect();
+
+    let estimate = estimate_offset(&reference, &other, SAMPLE_RATE);
+    assert!((estimate.offset_seconds - 0.3).abs() < 1e-9);
+    assert!(
+      estimate.confidence > 0.8,
+      "confidence {}",
+      estimate.confidence
+    );
+
+    // Reference started later than the other camera
+    let estimate = estimate_offset(&other, &reference, SAMPLE_RATE);
+    assert!((estimate.offset_seconds + 0.3).abs() < 1e-9);
+  }
+
+  #[test]
+  fn test_estimate_offset_low_confidence_for_silence_and_unrelated_audio() {
+    let reference = noise(5000, 1);
+
+    let silent = estimate_offset(&reference, &vec![0.0; 5000], SAMPLE_RATE);
+    assert_eq!(silent.confidence, 0.0);
+
+    let unrelated = estimate_offset(&reference, &noise(5000, 2), SAMPLE_RATE);
+    assert!(
+      unrelated.confidence < DEFAULT_MIN_CONFIDENCE,
+      "confidence {}",
+      unrelated.confidence
+    );
+  }
+
+  #[test]
+  fn test_window_duration_is_capped() {
+    let options = AudioSyncOptions {
+      range_start: 60.0,
+      range_duration: Some(600.0),
+      ..Default::default()
+    };
+    assert_eq!(options.window_duration(), DEFAULT_MAX_ANALYSIS_SECONDS);
+    assert_eq!(AudioSyncOptions::default().window_duration(), 180.0);
+  }
+
+  #[tokio::test]
+  async fn test_sync_rejects_bad_reference_index() {
+    let result = sync_clips_by_audio(
+      &[PathBuf::from("/tmp/a.mp4")],
+      3,
+      &AudioSyncOptions::default(),
+    )
+    .await;
+    assert!(matches!(result, Err(MontageError::InvalidConfiguration(_))));
+  }
+
+  #[test]
+  fn test_build_multicam_project_applies_offsets() {
+    let result = |path: &str, offset: Option<f64>| ClipSyncResult {
+      path: path.to_string(),
+      offset_seconds: offset,
+      confidence: if offset.is_some() { 0.9 } else { 0.1 },
+      is_reference: offset == Some(0.0),
+    };
+    let clips = vec![
+      (result("/cam/a.mp4", Some(0.0)), 60.0),
+      (result("/cam/b.mp4", Some(-2.0)), 60.0),
+      (result("/cam/c.mp4", None), 30.0),
+    ];
+
+    let project = build_multicam_project("Concert", &clips, default_multicam_template("grid-3", 3));
+
+    assert_eq!(project.tracks.len(), 3);
+    let starts: Vec<f64> = project
+      .tracks
+      .iter()
+      .map(|track| track.clips[0].start_time)
+      .collect();
+    // Camera B started first, so everything shifts by two seconds
+    assert_eq!(starts, vec![2.0, 0.0, 2.0]);
+    assert_eq!(project.timeline.duration, 62.0);
+    assert_eq!(project.templates[0].id, "grid-3");
+    assert_eq!(project.templates[0].cells.len(), 3);
+    assert_eq!(project.tracks[2].clips[0].template_position, Some(2));
+    assert!(project
+      .tracks
+      .iter()
+      .all(|track| track.clips[0].template_id.as_deref() == Some("grid-3")));
+    assert!(project.validate().is_ok());
+  }
+}
@@ -1,7 +1,12 @@
 //! Plan Generator Service
 //!
 //! Generates optimized montage plans using genetic algorithms.
+//! Cut points can optionally be aligned to a music beat grid, and moment
+//! selection can follow the energy curve of the soundtrack.
 
+use crate::montage_planner::services::beat_detector::BeatGrid;
+use crate::montage_planner::services::energy_curve::{EnergyPoint, EnergyProfile};
+use crate::montage_planner::services::quality_analyzer::QualityScore;
 use crate::montage_planner::types::*;
 use rand::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -26,6 +31,48 @@ pub struct PlanGenerationConfig {
   pub adaptive_mutation: bool,
   pub local_search_iterations: usize,
   pub diversity_preservation: f32,
+  /// Segment quality below which moments are down-weighted
+  #[serde(default)]
+  pub quality_thresholds: QualityThresholds,
+}
+
+/// Thresholds for per-segment quality scores (see `VideoQualityAnalyzer::analyze_segments`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QualityThresholds {
+  /// Minimum sharpness score (0-100)
+  pub min_sharpness: f32,
+  /// Maximum share of clipped highlight and shadow pixels (0-1)
+  pub max_clipped_ratio: f32,
+  /// Maximum shake score (0-100)
+  pub max_shake: f32,
+  /// Score multiplier applied for every failed threshold
+  pub penalty: f32,
+}
+
+impl Default for QualityThresholds {
+  fn default() -> Self {
+    Self {
+      min_sharpness: 35.0,
+      max_clipped_ratio: 0.25,
+      max_shake: 60.0,
+      penalty: 0.5,
+    }
+  }
+}
+
+impl QualityThresholds {
+  /// Score multiplier for a segment: `penalty` raised to the number of failed thresholds
+  pub fn weight(&self, score: &QualityScore) -> f32 {
+    let failed = [
+      score.sharpness < self.min_sharpness,
+      score.clipped_ratio() > self.max_clipped_ratio,
+      score.shake > self.max_shake,
+    ]
+    .iter()
+    .filter(|&&failed| failed)
+    .count();
+    self.penalty.clamp(0.0, 1.0).powi(failed as i32)
+  }
 }
 
 /// Weights for fitness function components
@@ -36,6 +83,13 @@ pub struct FitnessWeights {
   pub rhythm_weight: f32,
   pub narrative_weight: f32,
   pub technical_weight: f32,
+  /// Weight of the soundtrack energy alignment (used only with an energy curve)
+  #[serde(default = "default_energy_weight")]
+  pub energy_weight: f32,
+}
+
+fn default_energy_weight() -> f32 {
+  0.2
 }
 
 /// Individual in the genetic algorithm population
@@ -71,6 +125,7 @@ impl Default for PlanGenerationConfig {
       adaptive_mutation: true,
       local_search_iterations: 5,
       diversity_preservation: 0.3,
+      quality_thresholds: QualityThresholds::default(),
     }
   }
 }
@@ -83,6 +138,7 @@ impl Default for FitnessWeights {
       rhythm_weight: 0.2,
       narrative_weight: 0.15,
       technical_weight: 0.15,
+      energy_weight: default_energy_weight(),
     }
   }
 }
@@ -110,6 +166,32 @@ impl PlanGenerator {
     moments: &[DetectedMoment],
     config: &MontageConfig,
     source_files: &[String],
+  ) -> Result<MontagePlan, MontageError> {
+    self.generate_plan_with_beats(moments, config, source_files, None)
+  }
+
+  /// Generate montage plan with cut points snapped to the beat grid
+  pub fn generate_plan_with_beats(
+    &mut self,
+    moments: &[DetectedMoment],
+    config: &MontageConfig,
+    source_files: &[String],
+    beat_grid: Option<&BeatGrid>,
+  ) -> Result<MontagePlan, MontageError> {
+    self.generate_plan_with_audio(moments, config, source_files, beat_grid, None)
+  }
+
+  /// Generate montage plan guided by the soundtrack.
+  ///
+  /// With an energy curve, dynamic styles prefer cuts on local energy peaks
+  /// and calm styles prefer cuts in regions of steady energy.
+  pub fn generate_plan_with_audio(
+    &mut self,
+    moments: &[DetectedMoment],
+    config: &MontageConfig,
+    source_files: &[String],
+    beat_grid: Option<&BeatGrid>,
+    energy_curve: Option<&[EnergyPoint]>,
   ) -> Result<MontagePlan, MontageError> {
     if moments.is_empty() {
       return Err(MontageError::InsufficientContent(
@@ -126,12 +208,17 @@ impl PlanGenerator {
       ));
     }
 
+    let energy = energy_curve
+      .map(EnergyProfile::new)
+      .filter(|profile| !profile.is_empty());
+    let energy = energy.as_ref();
+
     // Initialize population
     let mut population = self.initialize_population(moments, target_clips);
 
     // Evaluate initial fitness
     for individual in &mut population {
-      individual.fitness = self.calculate_fitness(individual, moments, config);
+      individual.fitness = self.calculate_fitness(individual, moments, config, energy);
     }
 
     let mut generation_stats = Vec::new();
@@ -154,13 +241,14 @@ impl PlanGenerator {
         population,
         moments,
         config,
+        energy,
         generation,
         current_mutation_rate,
       );
 
       // Apply local search to elite individuals
       if generation % 10 == 0 {
-        self.apply_local_search(&mut population, moments, config);
+        self.apply_local_search(&mut population, moments, config, energy);
       }
 
       // Calculate generation statistics
@@ -200,7 +288,36 @@ impl PlanGenerator {
     });
 
     // Generate final plan from best individual
-    self.create_montage_plan(best_individual, moments, config, source_files)
+    self.create_montage_plan(best_individual, moments, config, source_files, beat_grid)
+  }
+
+  /// Down-weight moments that fall into segments below the quality thresholds.
+  ///
+  /// A moment overlapping several segments takes the weight of the worst one;
+  /// moments without quality data are left unchanged.
+  pub fn apply_quality_scores(
+    &self,
+    moments: &[DetectedMoment],
+    scores: &[QualityScore],
+  ) -> Vec<DetectedMoment> {
+    let thresholds = &self.config.quality_thresholds;
+    moments
+      .iter()
+      .map(|moment| {
+        let weight = scores
+          .iter()
+          .filter(|score| score.overlaps(moment.timestamp, moment.timestamp + moment.duration))
+          .map(|score| thresholds.weight(score))
+          .fold(1.0f32, f32::min);
+
+        let mut moment = moment.clone();
+        if weight < 1.0 {
+          moment.total_score *= weight;
+          moment.scores.technical *= weight;
+        }
+        moment
+      })
+      .collect()
   }
 
   /// Calculate target number of clips
@@ -291,6 +408,7 @@ impl PlanGenerator {
     mut population: Vec<Individual>,
     moments: &[DetectedMoment],
     config: &MontageConfig,
+    energy: Option<&EnergyProfile>,
     generation: usize,
     mutation_rate: f32,
   ) -> Vec<Individual> {
@@ -329,8 +447,8 @@ impl PlanGenerator {
       child2.age = generation;
 
       // Evaluate fitness
-      child1.fitness = self.calculate_fitness(&child1, moments, config);
-      child2.fitness = self.calculate_fitness(&child2, moments, config);
+      child1.fitness = self.calculate_fitness(&child1, moments, config, energy);
+      child2.fitness = self.calculate_fitness(&child2, moments, config, energy);
 
       new_population.push(child1);
       if new_population.len() < population.len() {
@@ -479,6 +597,7 @@ impl PlanGenerator {
     individual: &Individual,
     moments: &[DetectedMoment],
     config: &MontageConfig,
+    energy: Option<&EnergyProfile>,
   ) -> f32 {
     if individual.genes.is_empty() {
       return 0.0;
@@ -517,6 +636,13 @@ impl PlanGenerator {
     let technical = self.calculate_technical_score(&selected_moments);
     fitness += technical * self.config.fitness_weights.technical_weight;
 
+    // Soundtrack energy alignment
+    if let Some(energy) = energy {
+      if let Some(score) = energy_alignment_score(&selected_moments, &config.style, energy) {
+        fitness += score * self.config.fitness_weights.energy_weight;
+      }
+    }
+
     fitness
   }
 
@@ -678,6 +804,7 @@ impl PlanGenerator {
     moments: &[DetectedMoment],
     config: &MontageConfig,
     source_files: &[String],
+    beat_grid: Option<&BeatGrid>,
   ) -> Result<MontagePlan, MontageError> {
     let selected_moments: Vec<_> = individual
       .genes
@@ -694,13 +821,17 @@ impl PlanGenerator {
     // Create clips
     let mut clips = Vec::new();
     for (i, moment) in selected_moments.iter().enumerate() {
-      let source_file = source_files.first().ok_or_else(|| {
-        MontageError::InvalidConfiguration("No source files provided".to_string())
-      })?;
+      // Moments from multi-file analysis carry their own file
+      let source_file = match &moment.source_file {
+        Some(source_file) => source_file.clone(),
+        None => source_files.first().cloned().ok_or_else(|| {
+          MontageError::InvalidConfiguration("No source files provided".to_string())
+        })?,
+      };
 
       clips.push(MontageClip {
         id: format!("clip_{i}"),
-        source_file: source_file.clone(),
+        source_file,
         start_time: moment.timestamp,
         end_time: moment.timestamp + moment.duration,
         duration: moment.duration,
@@ -717,6 +848,10 @@ impl PlanGenerator {
       });
     }
 
+    if let Some(beat_grid) = beat_grid {
+      snap_cuts_to_beats(&mut clips, beat_grid);
+    }
+
     // Create transitions
     let mut transitions = Vec::new();
     for i in 0..clips.len().saturating_sub(1) {
@@ -770,6 +905,7 @@ impl PlanGenerator {
     population: &mut [Individual],
     moments: &[DetectedMoment],
     config: &MontageConfig,
+    energy: Option<&EnergyProfile>,
   ) {
     let elite_count = (self.config.elite_percentage * population.len() as f32) as usize;
 
@@ -829,7 +965,7 @@ impl PlanGenerator {
         neighbor.genes.dedup();
 
         // Evaluate neighbor
-        neighbor.fitness = self.calculate_fitness(&neighbor, moments, config);
+        neighbor.fitness = self.calculate_fitness(&neighbor, moments, config, energy);
 
         if neighbor.fitness > best_fitness {
           best_neighbor = neighbor;
@@ -932,6 +1068,94 @@ impl PlanGenerator {
   }
 }
 
+/// Shortest clip a cut may be snapped down to (seconds)
+const MIN_SNAPPED_CLIP_DURATION: f64 = 0.25;
+
+/// Move cut points between consecutive clips onto beats.
+///
+/// Cuts are measured on the montage timeline. A cut leading into an
+/// emphasis moment prefers the strongest beat within the tolerance,
+/// other cuts take the nearest one.
+fn snap_cuts_to_beats(clips: &mut [MontageClip], beat_grid: &BeatGrid) {
+  let mut clip_start = 0.0;
+
+  for index in 0..clips.len() {
+    let prefer_strong = clips
+      .get(index + 1)
+      .is_some_and(|next| is_emphasis_moment(&next.moment));
+    let clip = &mut clips[index];
+    let proposed_cut = clip_start + clip.duration;
+
+    if let Some(beat) = beat_grid.snap(
+      proposed_cut,
+      clip_start + MIN_SNAPPED_CLIP_DURATION,
+      prefer_strong,
+    ) {
+      clip.end_time = clip.start_time + (beat - clip_start);
+      clip.duration = clip.end_time - clip.start_time;
+    }
+
+    clip_start += clip.duration;
+  }
+}
+
+/// How a montage style follows the soundtrack energy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EnergyBias {
+  /// Cuts land on local energy maxima
+  Peaks,
+  /// Cuts land where the energy is steady
+  Stable,
+}
+
+fn energy_bias(style: &MontageStyle) -> Option<EnergyBias> {
+  match style {
+    MontageStyle::DynamicAction | MontageStyle::MusicVideo | MontageStyle::SocialMedia => {
+      Some(EnergyBias::Peaks)
+    }
+    MontageStyle::CinematicDrama
+    | MontageStyle::Documentary
+    | MontageStyle::Corporate
+    | MontageStyle::Wedding => Some(EnergyBias::Stable),
+    MontageStyle::Travel => None,
+  }
+}
+
+/// Alignment of the montage cuts with the soundtrack energy (0-100).
+///
+/// Clips follow each other on the montage timeline, so every cut after the
+/// first clip is placed at the sum of the previous clip durations.
+fn energy_alignment_score(
+  moments: &[&DetectedMoment],
+  style: &MontageStyle,
+  energy: &EnergyProfile,
+) -> Option<f32> {
+  let bias = energy_bias(style)?;
+  if moments.len() < 2 {
+    return None;
+  }
+
+  let mut cut = 0.0;
+  let mut total = 0.0;
+  for moment in &moments[..moments.len() - 1] {
+    cut += moment.duration;
+    total += match bias {
+      EnergyBias::Peaks => energy.peak_score(cut),
+      EnergyBias::Stable => energy.stability_at(cut),
+    };
+  }
+
+  Some(total / (moments.len() - 1) as f32 * 100.0)
+}
+
+/// Moment that deserves a cut on a strong beat
+fn is_emphasis_moment(moment: &DetectedMoment) -> bool {
+  matches!(
+    moment.category,
+    MomentCategory::Action | MomentCategory::Highlight
+  )
+}
+
 impl Default for PlanGenerator {
   fn default() -> Self {
     Self::new()
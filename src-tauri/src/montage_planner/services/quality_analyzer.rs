@@ -1,12 +1,44 @@
 //! Video Quality Analyzer Service
 //!
 //! Analyzes video quality using FFmpeg for montage planning.
+//!
+//! Per-segment scores are computed from frames sampled at ~1 fps by the
+//! frame extraction pipeline: sharpness is the variance of the Laplacian of
+//! a downscaled grayscale frame, exposure is the share of clipped highlight
+//! and shadow pixels, and shake is the global motion between consecutive
+//! samples estimated by block matching on a coarse grid.
 
 use crate::montage_planner::types::*;
+use crate::video_compiler::cache::RenderCache;
+use crate::video_compiler::frame_extraction::{ExtractionPurpose, FrameExtractionManager};
 use anyhow::Result;
+use image::{imageops::FilterType, DynamicImage, GrayImage};
 use serde::{Deserialize, Serialize};
 use std::path::Path;
+use std::sync::Arc;
 use tokio::process::Command as AsyncCommand;
+use tokio::sync::RwLock;
+
+/// Longest side of the grayscale frames used for sharpness and exposure
+const METRIC_FRAME_SIZE: u32 = 320;
+
+/// Width of the coarse grid used for global motion estimation
+const MOTION_GRID_WIDTH: u32 = 64;
+
+/// Largest shift (in grid pixels) searched by global motion estimation
+const MOTION_SEARCH_RADIUS: i32 = 8;
+
+/// Luma at or above this value counts as clipped highlights
+const OVEREXPOSED_LUMA: u8 = 250;
+
+/// Luma at or below this value counts as crushed shadows
+const UNDEREXPOSED_LUMA: u8 = 5;
+
+/// Laplacian variance that maps to a sharpness score of 50
+const SHARPNESS_HALF_POINT: f32 = 100.0;
+
+/// Default duration of the segments scored by `analyze_segments`
+pub const DEFAULT_QUALITY_SEGMENT_DURATION: f64 = 5.0;
 
 /// Service for analyzing video quality
 pub struct VideoQualityAnalyzer {
@@ -94,6 +126,58 @@ impl VideoQualityAnalyzer {
     })
   }
 
+  /// Score sharpness, exposure and shake per segment of the video.
+  ///
+  /// Frames are taken from the frame extraction pipeline at ~1 fps.
+  pub async fn analyze_segments<P: AsRef<Path>>(
+    &self,
+    video_path: P,
+    segment_duration: f64,
+  ) -> Result<Vec<QualityScore>, MontageError> {
+    let path = video_path.as_ref();
+
+    if !path.exists() {
+      return Err(MontageError::FileNotFound(
+        path.to_string_lossy().to_string(),
+      ));
+    }
+    if segment_duration <= 0.0 {
+      return Err(MontageError::InvalidConfiguration(
+        "Segment duration must be positive".to_string(),
+      ));
+    }
+
+    let extractor = FrameExtractionManager::new(Arc::new(RwLock::new(RenderCache::new())));
+    let duration = extractor
+      .preview_generator
+      .get_video_info(path)
+      .await
+      .map_err(|e| MontageError::VideoAnalysisError(format!("Failed to read video info: {e}")))?
+      .duration;
+    let frames = extractor
+      .extract_frames_for_recognition(path, duration, ExtractionPurpose::ObjectDetection)
+      .await
+      .map_err(|e| MontageError::VideoAnalysisError(format!("Frame extraction failed: {e}")))?;
+
+    // Decoding and per-pixel metrics are CPU bound
+    tokio::task::spawn_blocking(move || {
+      let mut samples: Vec<(f64, GrayFrame)> = frames
+        .iter()
+        .filter_map(|frame| match image::load_from_memory(&frame.frame_data) {
+          Ok(image) => Some((frame.timestamp, GrayFrame::from_image(&image))),
+          Err(e) => {
+            log::warn!("Failed to decode frame at {:.2}s: {e}", frame.timestamp);
+            None
+          }
+        })
+        .collect();
+      samples.sort_by(|a, b| a.0.total_cmp(&b.0));
+      score_segments(&samples, segment_duration)
+    })
+    .await
+    .map_err(|e| MontageError::VideoAnalysisError(format!("Quality scoring failed: {e}")))
+  }
+
   /// Analyze quality at specific timestamp
   pub async fn analyze_frame_quality<P: AsRef<Path>>(
     &self,
@@ -684,6 +768,210 @@ pub enum QualityIssueSeverity {
   Critical,
 }
 
+/// Quality of a video segment computed from its sampled frames
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QualityScore {
+  pub start: f64,
+  pub end: f64,
+  pub sharpness: f32,          // 0-100 (normalized Laplacian variance)
+  pub laplacian_variance: f32, // raw variance of the Laplacian
+  pub overexposed_ratio: f32,  // 0-1: share of clipped highlight pixels
+  pub underexposed_ratio: f32, // 0-1: share of crushed shadow pixels
+  pub shake: f32,              // 0-100 (higher = more camera motion)
+  pub frame_count: usize,
+  pub overall: f32, // 0-100 (combined score)
+}
+
+impl QualityScore {
+  /// Share of pixels clipped on either end of the histogram
+  pub fn clipped_ratio(&self) -> f32 {
+    (self.overexposed_ratio + self.underexposed_ratio).min(1.0)
+  }
+
+  /// Whether the segment overlaps the `[start, end)` interval
+  /// (an empty interval is treated as a single point in time)
+  pub fn overlaps(&self, start: f64, end: f64) -> bool {
+    if end > start {
+      self.start < end && start < self.end
+    } else {
+      self.start <= start && start < self.end
+    }
+  }
+}
+
+/// Downscaled 8-bit grayscale frame used for quality metrics
+#[derive(Debug, Clone)]
+pub struct GrayFrame {
+  image: GrayImage,
+}
+
+impl GrayFrame {
+  /// Convert a decoded frame to grayscale, downscaled to `METRIC_FRAME_SIZE`
+  pub fn from_image(image: &DynamicImage) -> Self {
+    let image = if image.width().max(image.height()) > METRIC_FRAME_SIZE {
+      image.resize(METRIC_FRAME_SIZE, METRIC_FRAME_SIZE, FilterType::Triangle)
+    } else {
+      image.clone()
+    };
+    Self {
+      image: image.to_luma8(),
+    }
+  }
+
+  /// Variance of the 4-neighbour Laplacian over interior pixels
+  pub fn laplacian_variance(&self) -> f32 {
+    let (width, height) = self.image.dimensions();
+    if width < 3 || height < 3 {
+      return 0.0;
+    }
+
+    let luma = |x: u32, y: u32| self.image.get_pixel(x, y)[0] as f64;
+    let mut sum = 0.0;
+    let mut sum_sq = 0.0;
+    for y in 1..height - 1 {
+      for x in 1..width - 1 {
+        let laplacian =
+          luma(x - 1, y) + luma(x + 1, y) + luma(x, y - 1) + luma(x, y + 1) - 4.0 * luma(x, y);
+        sum += laplacian;
+        sum_sq += laplacian * laplacian;
+      }
+    }
+    let count = ((width - 2) * (height - 2)) as f64;
+    let mean = sum / count;
+    (sum_sq / count - mean * mean).max(0.0) as f32
+  }
+
+  /// Shares of overexposed and underexposed pixels
+  pub fn exposure_clipping(&self) -> (f32, f32) {
+    let total = (self.image.width() * self.image.height()).max(1) as f32;
+    let (mut over, mut under) = (0usize, 0usize);
+    for pixel in self.image.pixels() {
+      if pixel[0] >= OVEREXPOSED_LUMA {
+        over += 1;
+      } else if pixel[0] <= UNDEREXPOSED_LUMA {
+        under += 1;
+      }
+    }
+    (over as f32 / total, under as f32 / total)
+  }
+
+  /// Global motion from `previous` to this frame as a fraction of frame width.
+  ///
+  /// Both frames are reduced to a coarse grid and the shift with the lowest
+  /// mean absolute difference within `MOTION_SEARCH_RADIUS` is selected.
+  pub fn global_motion(&self, previous: &GrayFrame) -> f32 {
+    let current = self.motion_grid();
+    let previous = previous.motion_grid();
+    if current.dimensions() != previous.dimensions() {
+      return 0.0;
+    }
+
+    let (width, height) = (current.width() as i32, current.height() as i32);
+    let mut best = (f64::MAX, 0i32, 0i32);
+    for dy in -MOTION_SEARCH_RADIUS..=MOTION_SEARCH_RADIUS {
+      for dx in -MOTION_SEARCH_RADIUS..=MOTION_SEARCH_RADIUS {
+        let (x0, x1) = (dx.max(0), width + dx.min(0));
+        let (y0, y1) = (dy.max(0), height + dy.min(0));
+        if x1 - x0 < width / 2 || y1 - y0 < height / 2 {
+          continue;
+        }
+
+        let mut diff = 0u64;
+        for y in y0..y1 {
+          for x in x0..x1 {
+            let a = current.get_pixel(x as u32, y as u32)[0];
+            let b = previous.get_pixel((x - dx) as u32, (y - dy) as u32)[0];
+            diff += a.abs_diff(b) as u64;
+          }
+        }
+        let mean = diff as f64 / ((x1 - x0) * (y1 - y0)) as f64;
+        // Prefer the smaller shift when matches are equally good
+        let closer = dx * dx + dy * dy < best.1 * best.1 + best.2 * best.2;
+        if mean < best.0 || (mean == best.0 && closer) {
+          best = (mean, dx, dy);
+        }
+      }
+    }
+
+    ((best.1 * best.1 + best.2 * best.2) as f32).sqrt() / width as f32
+  }
+
+  fn motion_grid(&self) -> GrayImage {
+    let (width, height) = self.image.dimensions();
+    let grid_height =
+      ((height as f32 * MOTION_GRID_WIDTH as f32 / width.max(1) as f32).round() as u32).max(1);
+    image::imageops::resize(
+      &self.image,
+      MOTION_GRID_WIDTH,
+      grid_height,
+      FilterType::Triangle,
+    )
+  }
+}
+
+/// Map a Laplacian variance to a 0-100 sharpness score
+pub fn normalize_sharpness(laplacian_variance: f32) -> f32 {
+  100.0 * laplacian_variance / (laplacian_variance + SHARPNESS_HALF_POINT)
+}
+
+/// Aggregate frame metrics into per-segment quality scores.
+///
+/// `samples` must be sorted by timestamp; motion is measured between
+/// consecutive samples and attributed to the segment of the later one.
+pub fn score_segments(samples: &[(f64, GrayFrame)], segment_duration: f64) -> Vec<QualityScore> {
+  let max_motion = MOTION_SEARCH_RADIUS as f32 / MOTION_GRID_WIDTH as f32;
+  let mut scores: Vec<QualityScore> = Vec::new();
+  let mut motion_samples: Vec<usize> = Vec::new();
+
+  for (index, (timestamp, frame)) in samples.iter().enumerate() {
+    let segment = (timestamp / segment_duration).floor().max(0.0);
+    let start = segment * segment_duration;
+    if scores.last().is_none_or(|score| score.start != start) {
+      scores.push(QualityScore {
+        start,
+        end: start + segment_duration,
+        sharpness: 0.0,
+        laplacian_variance: 0.0,
+        overexposed_ratio: 0.0,
+        underexposed_ratio: 0.0,
+        shake: 0.0,
+        frame_count: 0,
+        overall: 0.0,
+      });
+      motion_samples.push(0);
+    }
+
+    let score = scores.last_mut().expect("segment was just pushed");
+    let (over, under) = frame.exposure_clipping();
+    score.laplacian_variance += frame.laplacian_variance();
+    score.overexposed_ratio += over;
+    score.underexposed_ratio += under;
+    score.frame_count += 1;
+    if index > 0 {
+      let motion = frame.global_motion(&samples[index - 1].1);
+      score.shake += (motion / max_motion * 100.0).min(100.0);
+      *motion_samples.last_mut().expect("segment was just pushed") += 1;
+    }
+  }
+
+  for (score, motion_count) in scores.iter_mut().zip(motion_samples) {
+    let frames = score.frame_count as f32;
+    score.laplacian_variance /= frames;
+    score.overexposed_ratio /= frames;
+    score.underexposed_ratio /= frames;
+    if motion_count > 0 {
+      score.shake /= motion_count as f32;
+    }
+    score.sharpness = normalize_sharpness(score.laplacian_variance);
+    score.overall = (score.sharpness * 0.5
+      + (1.0 - score.clipped_ratio()) * 100.0 * 0.3
+      + (100.0 - score.shake) * 0.2)
+      .clamp(0.0, 100.0);
+  }
+
+  scores
+}
+
 /// Video metadata for quality analysis
 #[derive(Debug, Clone)]
 struct VideoMetadata {
@@ -59,6 +59,7 @@ mod tests {
       total_score: 75.0,
       description: "Test action moment".to_string(),
       tags: vec!["action".to_string(), "test".to_string()],
+      source_file: None,
     }
   }
 
@@ -0,0 +1,246 @@
+#[cfg(test)]
+mod tests {
+  use crate::montage_planner::services::beat_detector::{
+    detect_beats_in_samples, estimate_tempo, Beat, BeatDetectionOptions, BeatDetector, BeatGrid,
+  };
+  use crate::montage_planner::services::PlanGenerator;
+  use crate::montage_planner::types::*;
+  use crate::video_compiler::test_media::ffmpeg_available;
+  use std::f32::consts::PI;
+  use std::path::Path;
+  use tempfile::TempDir;
+
+  const SAMPLE_RATE: u32 = 22050;
+  const TOLERANCE: f64 = 0.05;
+
+  /// Click track: a short decaying 1 kHz burst on every beat, accented every 4th
+  fn click_track(bpm: f64, first_beat: f64, seconds: f64) -> (Vec<f32>, Vec<f64>) {
+    let mut samples = vec![0.0f32; (seconds * SAMPLE_RATE as f64) as usize];
+    let interval = 60.0 / bpm;
+    let click_length = (0.02 * SAMPLE_RATE as f64) as usize;
+    let mut beat_times = Vec::new();
+
+    let mut time = first_beat;
+    while time < seconds {
+      let start = (time * SAMPLE_RATE as f64) as usize;
+      let amplitude = if beat_times.len() % 4 == 0 { 0.9 } else { 0.5 };
+      for k in 0..click_length.min(samples.len().saturating_sub(start)) {
+        let envelope = (-(k as f32) / (0.004 * SAMPLE_RATE as f32)).exp();
+        samples[start + k] +=
+          amplitude * envelope * (2.0 * PI * 1000.0 * k as f32 / SAMPLE_RATE as f32).sin();
+      }
+      beat_times.push(time);
+      time += interval;
+    }
+
+    (samples, beat_times)
+  }
+
+  /// Write 16-bit mono PCM WAV
+  fn write_wav(path: &Path, samples: &[f32]) {
+    let data_len = (samples.len() * 2) as u32;
+    let mut bytes = Vec::with_capacity(44 + data_len as usize);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    bytes.extend_from_slice(&1u16.to_le_bytes()); // mono
+    bytes.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    bytes.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    bytes.extend_from_slice(&2u16.to_le_bytes());
+    bytes.extend_from_slice(&16u16.to_le_bytes());
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+      let value = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+      bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    std::fs::write(path, bytes).unwrap();
+  }
+
+  fn assert_beats_match(beats: &[Beat], expected: &[f64]) {
+    // The very first click has no previous frame to compare against
+    let matched = expected
+      .iter()
+      .filter(|time| {
+        beats
+          .iter()
+          .any(|beat| (beat.time - **time).abs() <= TOLERANCE)
+      })
+      .count();
+    assert!(
+      matched + 1 >= expected.len(),
+      "matched {matched} of {} beats: {beats:?}",
+      expected.len()
+    );
+
+    for beat in beats {
+      assert!(
+        expected
+          .iter()
+          .any(|time| (beat.time - time).abs() <= TOLERANCE),
+        "unexpected beat at {:.3}s",
+        beat.time
+      );
+    }
+  }
+
+  fn create_test_moment(timestamp: f64, duration: f64, category: MomentCategory) -> DetectedMoment {
+    DetectedMoment {
+      timestamp,
+      duration,
+      category,
+      scores: MomentScores {
+        visual: 75.0,
+        technical: 80.0,
+        emotional: 70.0,
+        narrative: 65.0,
+        action: 85.0,
+        composition: 78.0,
+      },
+      total_score: 75.0,
+      description: "test moment".to_string(),
+      tags: Vec::new(),
+      source_file: None,
+    }
+  }
+
+  fn beat(time: f64, strength: f32, is_downbeat_guess: bool) -> Beat {
+    Beat {
+      time,
+      strength,
+      is_downbeat_guess,
+    }
+  }
+
+  #[test]
+  fn test_click_track_beats_within_tolerance() {
+    let options = BeatDetectionOptions::default();
+
+    for (bpm, first_beat) in [(90.0, 0.1), (120.0, 0.25), (140.0, 0.5)] {
+      let (samples, expected) = click_track(bpm, first_beat, 10.0);
+      let beats = detect_beats_in_samples(&samples, &options);
+      assert_beats_match(&beats, &expected);
+
+      let tempo = estimate_tempo(&samples, &options).unwrap();
+      assert!(
+        (tempo as f64 - bpm).abs() < 2.0,
+        "tempo {tempo} for {bpm} BPM"
+      );
+    }
+  }
+
+  #[test]
+  fn test_accented_beats_are_downbeats() {
+    let (samples, expected) = click_track(120.0, 0.25, 10.0);
+    let beats = detect_beats_in_samples(&samples, &BeatDetectionOptions::default());
+
+    for beat in beats.iter().filter(|beat| beat.is_downbeat_guess) {
+      let index = expected
+        .iter()
+        .position(|time| (beat.time - time).abs() <= TOLERANCE)
+        .unwrap();
+      assert_eq!(index % 4, 0, "downbeat at {:.3}s", beat.time);
+    }
+    assert!(beats.iter().any(|beat| beat.is_downbeat_guess));
+  }
+
+  #[test]
+  fn test_silence_has_no_beats() {
+    let samples = vec![0.0f32; SAMPLE_RATE as usize * 5];
+    assert!(detect_beats_in_samples(&samples, &BeatDetectionOptions::default()).is_empty());
+    assert!(detect_beats_in_samples(&[], &BeatDetectionOptions::default()).is_empty());
+  }
+
+  #[tokio::test]
+  async fn test_click_track_wav_file() {
+    if !ffmpeg_available("ffmpeg").await {
+      println!("FFmpeg not available: skipping click track decoding");
+      return;
+    }
+
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("click.wav");
+    let (samples, expected) = click_track(120.0, 0.25, 8.0);
+    write_wav(&path, &samples);
+
+    let mut detector = BeatDetector::new();
+    let beats = detector.detect_beats(&path, None).await.unwrap();
+    assert_beats_match(&beats, &expected);
+
+    // The second call is served from the cache
+    let cached = detector.detect_beats(&path, None).await.unwrap();
+    assert_eq!(cached, beats);
+    assert_eq!(detector.cached_files(), 1);
+  }
+
+  #[tokio::test]
+  async fn test_missing_file_is_reported() {
+    let mut detector = BeatDetector::new();
+    let result = detector.detect_beats("/non/existent/music.wav", None).await;
+    assert!(matches!(result, Err(MontageError::FileNotFound(_))));
+  }
+
+  #[test]
+  fn test_snap_prefers_nearest_or_strongest_beat() {
+    let grid = BeatGrid {
+      beats: vec![
+        beat(1.9, 0.4, false),
+        beat(2.0, 0.5, false),
+        beat(2.1, 1.0, true),
+        beat(3.0, 1.0, true),
+      ],
+      snap_tolerance: 0.15,
+    };
+
+    assert_eq!(grid.snap(2.01, 0.0, false), Some(2.0));
+    assert_eq!(grid.snap(2.01, 0.0, true), Some(2.1));
+    assert_eq!(grid.snap(2.5, 0.0, false), None);
+    // Beats before the minimum time are ignored
+    assert_eq!(grid.snap(2.01, 2.05, false), Some(2.1));
+  }
+
+  #[test]
+  fn test_plan_cuts_snap_to_beat_grid() {
+    let mut generator = PlanGenerator::new();
+    let moments: Vec<_> = (0..6)
+      .map(|i| {
+        let category = if i % 2 == 0 {
+          MomentCategory::Action
+        } else {
+          MomentCategory::Drama
+        };
+        create_test_moment(i as f64 * 10.0, 2.1, category)
+      })
+      .collect();
+    let config = MontageConfig {
+      style: MontageStyle::DynamicAction,
+      target_duration: 12.0,
+      quality_threshold: 50.0,
+      diversity_weight: 0.5,
+      rhythm_sync: true,
+      max_cuts_per_minute: None,
+    };
+    let grid = BeatGrid::new(
+      (0..60)
+        .map(|i| beat(i as f64 * 0.5, 1.0, i % 4 == 0))
+        .collect(),
+    );
+
+    let plan = generator
+      .generate_plan_with_beats(&moments, &config, &["music.mp4".to_string()], Some(&grid))
+      .unwrap();
+
+    let mut cut = 0.0;
+    for clip in &plan.clips {
+      cut += clip.duration;
+      let nearest_beat = (cut / 0.5).round() * 0.5;
+      assert!(
+        (cut - nearest_beat).abs() < 1e-6,
+        "cut at {cut:.3}s is off the beat grid"
+      );
+      assert!(clip.end_time > clip.start_time);
+    }
+  }
+}
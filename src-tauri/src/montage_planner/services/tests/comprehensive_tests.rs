@@ -87,6 +87,7 @@ mod tests {
       total_score: 75.5,
       description: format!("{:?} moment at {:.1}s", category, timestamp),
       tags: vec![format!("{:?}", category).to_lowercase()],
+      source_file: None,
     }
   }
 
@@ -285,6 +286,31 @@ mod tests {
     }
   }
 
+  #[test]
+  fn test_plan_generator_uses_moment_source_files() {
+    let mut generator = PlanGenerator::new();
+
+    let mut moments = vec![
+      create_test_moment(5.0, 3.0, MomentCategory::Action),
+      create_test_moment(15.0, 4.0, MomentCategory::Drama),
+      create_test_moment(25.0, 2.5, MomentCategory::Highlight),
+      create_test_moment(35.0, 3.5, MomentCategory::Action),
+    ];
+    moments[0].source_file = Some("beach.mp4".to_string());
+    moments[2].source_file = Some("city.mp4".to_string());
+
+    let config = create_test_montage_config();
+    let source_files = vec!["fallback.mp4".to_string()];
+    let plan = generator
+      .generate_plan(&moments, &config, &source_files)
+      .unwrap();
+
+    for clip in &plan.clips {
+      let expected = clip.moment.source_file.as_deref().unwrap_or("fallback.mp4");
+      assert_eq!(clip.source_file, expected);
+    }
+  }
+
   #[tokio::test]
   async fn test_video_quality_analyzer_comprehensive() {
     let analyzer = VideoQualityAnalyzer::new();
@@ -0,0 +1,335 @@
+#[cfg(test)]
+mod tests {
+  use crate::montage_planner::services::energy_curve::{
+    compute_energy_curve, decimate, EnergyPoint, EnergyProfile, DEFAULT_HOP_MS, DEFAULT_WINDOW_MS,
+    ENERGY_SAMPLE_RATE, MAX_ENERGY_POINTS,
+  };
+  use crate::montage_planner::services::plan_generator::{FitnessWeights, PlanGenerationConfig};
+  use crate::montage_planner::services::{AudioAnalyzer, PlanGenerator};
+  use crate::montage_planner::types::*;
+  use crate::video_compiler::test_media::ffmpeg_available;
+  use std::f32::consts::PI;
+  use std::path::Path;
+  use tempfile::TempDir;
+
+  const SAMPLE_RATE: u32 = ENERGY_SAMPLE_RATE;
+
+  fn silence(seconds: f32) -> Vec<f32> {
+    vec![0.0; (seconds * SAMPLE_RATE as f32) as usize]
+  }
+
+  /// Uniform white noise from a fixed-seed LCG
+  fn white_noise(seconds: f32, amplitude: f32) -> Vec<f32> {
+    let mut state: u32 = 0x1234_5678;
+    (0..(seconds * SAMPLE_RATE as f32) as usize)
+      .map(|_| {
+        state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        ((state >> 8) as f32 / (1u32 << 24) as f32 * 2.0 - 1.0) * amplitude
+      })
+      .collect()
+  }
+
+  /// Linear sine sweep from `from_hz` to `to_hz`
+  fn sine_sweep(seconds: f32, from_hz: f32, to_hz: f32, amplitude: f32) -> Vec<f32> {
+    let count = (seconds * SAMPLE_RATE as f32) as usize;
+    let rate = (to_hz - from_hz) / seconds;
+    (0..count)
+      .map(|i| {
+        let t = i as f32 / SAMPLE_RATE as f32;
+        (2.0 * PI * (from_hz * t + 0.5 * rate * t * t)).sin() * amplitude
+      })
+      .collect()
+  }
+
+  fn curve(samples: &[f32]) -> Vec<EnergyPoint> {
+    compute_energy_curve(
+      samples,
+      SAMPLE_RATE,
+      DEFAULT_WINDOW_MS,
+      DEFAULT_HOP_MS,
+      MAX_ENERGY_POINTS,
+    )
+  }
+
+  fn mean(points: &[EnergyPoint], value: impl Fn(&EnergyPoint) -> f32) -> f32 {
+    points.iter().map(value).sum::<f32>() / points.len() as f32
+  }
+
+  fn write_wav(path: &Path, samples: &[f32]) {
+    let data_len = (samples.len() * 2) as u32;
+    let mut bytes = Vec::with_capacity(44 + data_len as usize);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    bytes.extend_from_slice(&1u16.to_le_bytes()); // mono
+    bytes.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    bytes.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    bytes.extend_from_slice(&2u16.to_le_bytes());
+    bytes.extend_from_slice(&16u16.to_le_bytes());
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+      let value = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+      bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    std::fs::write(path, bytes).unwrap();
+  }
+
+  fn create_test_moment(timestamp: f64, duration: f64, category: MomentCategory) -> DetectedMoment {
+    DetectedMoment {
+      timestamp,
+      duration,
+      category,
+      scores: MomentScores {
+        visual: 75.0,
+        technical: 80.0,
+        emotional: 70.0,
+        narrative: 65.0,
+        action: 85.0,
+        composition: 78.0,
+      },
+      total_score: 75.0,
+      description: "test moment".to_string(),
+      tags: Vec::new(),
+      source_file: None,
+    }
+  }
+
+  /// Steady curve with a short spike every 4 seconds
+  fn spiky_curve(seconds: f64) -> Vec<EnergyPoint> {
+    (0..(seconds / 0.05) as usize)
+      .map(|i| {
+        let time = i as f64 * 0.05;
+        let nearest_spike = (time / 4.0).round() * 4.0;
+        let on_spike = nearest_spike > 0.0 && (time - nearest_spike).abs() <= 0.1 + 1e-9;
+        EnergyPoint {
+          time,
+          rms: if on_spike { 1.0 } else { 0.3 },
+          flux: 0.0,
+          centroid: 0.5,
+        }
+      })
+      .collect()
+  }
+
+  /// Share of cuts (all clip boundaries except the end) within 0.3s of a spike
+  fn spike_hit_ratio(plan: &MontagePlan) -> f32 {
+    let mut cut = 0.0;
+    let mut hits = 0;
+    let cuts = plan.clips.len().saturating_sub(1);
+    for clip in plan.clips.iter().take(cuts) {
+      cut += clip.duration;
+      let nearest_spike = (cut / 4.0).round() * 4.0;
+      if nearest_spike > 0.0 && (cut - nearest_spike).abs() <= 0.3 {
+        hits += 1;
+      }
+    }
+    if cuts == 0 {
+      0.0
+    } else {
+      hits as f32 / cuts as f32
+    }
+  }
+
+  fn energy_only_generator() -> PlanGenerator {
+    PlanGenerator::with_config(PlanGenerationConfig {
+      fitness_weights: FitnessWeights {
+        quality_weight: 0.0,
+        diversity_weight: 0.0,
+        rhythm_weight: 0.0,
+        narrative_weight: 0.0,
+        technical_weight: 0.0,
+        energy_weight: 1.0,
+      },
+      ..PlanGenerationConfig::default()
+    })
+  }
+
+  #[test]
+  fn test_energy_ordering_of_generated_signals() {
+    let silent = curve(&silence(2.0));
+    let sweep = curve(&sine_sweep(2.0, 200.0, 4000.0, 0.25));
+    let noise = curve(&white_noise(2.0, 0.5));
+    assert!(!silent.is_empty() && !sweep.is_empty() && !noise.is_empty());
+
+    let rms = |points: &[EnergyPoint]| mean(points, |p| p.rms);
+    assert!(rms(&silent) < rms(&sweep));
+    assert!(rms(&sweep) < rms(&noise));
+
+    let flux = |points: &[EnergyPoint]| mean(points, |p| p.flux);
+    assert_eq!(flux(&silent), 0.0);
+    assert!(
+      flux(&noise) > flux(&sweep),
+      "noise flux {} should exceed sweep flux {}",
+      flux(&noise),
+      flux(&sweep)
+    );
+
+    assert!(silent.iter().all(|p| p.centroid == 0.0));
+    let all_normalized = sweep
+      .iter()
+      .chain(&noise)
+      .all(|p| (0.0..=1.0).contains(&p.rms) && (0.0..=1.0).contains(&p.flux));
+    assert!(all_normalized);
+  }
+
+  #[test]
+  fn test_sweep_centroid_rises() {
+    let sweep = curve(&sine_sweep(4.0, 200.0, 4000.0, 0.25));
+    let quarter = sweep.len() / 4;
+    let first = mean(&sweep[..quarter], |p| p.centroid);
+    let last = mean(&sweep[sweep.len() - quarter..], |p| p.centroid);
+    assert!(
+      last > first * 2.0,
+      "centroid should follow the sweep: {first:.3} -> {last:.3}"
+    );
+
+    for pair in sweep.windows(2) {
+      assert!(pair[1].time > pair[0].time);
+    }
+  }
+
+  #[test]
+  fn test_long_input_is_decimated() {
+    // 2 minutes with a 10ms hop produce 12000 windows
+    let noise = white_noise(120.0, 0.5);
+    let points = compute_energy_curve(&noise, SAMPLE_RATE, 20, 10, MAX_ENERGY_POINTS);
+    assert!(points.len() <= MAX_ENERGY_POINTS);
+    assert!(points.len() > MAX_ENERGY_POINTS / 2);
+    assert!(points.last().unwrap().time > 110.0);
+
+    let spikes: Vec<_> = (0..10)
+      .map(|i| EnergyPoint {
+        time: i as f64,
+        rms: 0.5,
+        flux: if i == 3 { 1.0 } else { 0.0 },
+        centroid: 0.5,
+      })
+      .collect();
+    let decimated = decimate(spikes.clone(), 5);
+    assert_eq!(decimated.len(), 5);
+    assert_eq!(decimated[1].time, 2.0);
+    // Short onsets survive decimation
+    assert_eq!(decimated[1].flux, 1.0);
+    assert_eq!(decimate(spikes, 0).len(), 10);
+  }
+
+  #[test]
+  fn test_energy_profile_peaks_and_stability() {
+    let profile = EnergyProfile::new(&spiky_curve(20.0));
+    assert!(!profile.is_empty());
+
+    assert!(profile.peak_score(8.0) > 0.5);
+    assert!(profile.peak_score(8.25) > 0.5);
+    assert!(profile.peak_score(6.0) < 0.3);
+
+    assert!(profile.stability_at(6.0) > 0.9);
+    assert!(profile.stability_at(8.0) < 0.5);
+
+    let empty = EnergyProfile::new(&[]);
+    assert!(empty.is_empty());
+    assert_eq!(empty.peak_score(1.0), 0.0);
+    assert_eq!(empty.stability_at(1.0), 0.0);
+  }
+
+  #[test]
+  fn test_energy_curve_steers_cut_placement() {
+    // Even moments cut exactly on the spikes, odd moments drift off them
+    let moments: Vec<_> = (0..12)
+      .map(|i| {
+        let duration = if i % 2 == 0 { 4.0 } else { 2.5 };
+        create_test_moment(i as f64 * 5.0, duration, MomentCategory::Action)
+      })
+      .collect();
+    let energy = spiky_curve(60.0);
+    let sources = ["music.mp4".to_string()];
+
+    let mut config = MontageConfig {
+      style: MontageStyle::DynamicAction,
+      target_duration: 20.0,
+      quality_threshold: 50.0,
+      diversity_weight: 0.5,
+      rhythm_sync: false,
+      max_cuts_per_minute: None,
+    };
+    let dynamic = energy_only_generator()
+      .generate_plan_with_audio(&moments, &config, &sources, None, Some(&energy))
+      .unwrap();
+    assert!(
+      spike_hit_ratio(&dynamic) >= 0.75,
+      "dynamic style should cut on energy peaks"
+    );
+
+    config.style = MontageStyle::Documentary;
+    let calm = energy_only_generator()
+      .generate_plan_with_audio(&moments, &config, &sources, None, Some(&energy))
+      .unwrap();
+    assert!(
+      spike_hit_ratio(&calm) <= 0.25,
+      "calm style should avoid cutting on energy peaks"
+    );
+
+    // Without a curve the plan is still generated
+    let plain = PlanGenerator::new()
+      .generate_plan_with_audio(&moments, &config, &sources, None, None)
+      .unwrap();
+    assert!(!plain.clips.is_empty());
+  }
+
+  #[tokio::test]
+  async fn test_analyzer_caches_energy_curve() {
+    if !ffmpeg_available("ffmpeg") {
+      eprintln!("Skipping test: FFmpeg not available");
+      return;
+    }
+
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("noise.wav");
+    let mut samples = silence(1.0);
+    samples.extend(white_noise(1.0, 0.5));
+    write_wav(&path, &samples);
+
+    let mut analyzer = AudioAnalyzer::new();
+    let points = analyzer
+      .compute_energy_curve(&path, DEFAULT_WINDOW_MS, DEFAULT_HOP_MS)
+      .await
+      .unwrap();
+    assert!(!points.is_empty());
+    assert_eq!(analyzer.cached_energy_curves(), 1);
+
+    let quiet = mean(
+      &points
+        .iter()
+        .filter(|p| p.time < 0.9)
+        .copied()
+        .collect::<Vec<_>>(),
+      |p| p.rms,
+    );
+    let loud = mean(
+      &points
+        .iter()
+        .filter(|p| p.time > 1.1)
+        .copied()
+        .collect::<Vec<_>>(),
+      |p| p.rms,
+    );
+    assert!(loud > quiet + 0.5);
+
+    let cached = analyzer
+      .compute_energy_curve(&path, DEFAULT_WINDOW_MS, DEFAULT_HOP_MS)
+      .await
+      .unwrap();
+    assert_eq!(cached, points);
+    assert_eq!(analyzer.cached_energy_curves(), 1);
+
+    let invalid = analyzer
+      .compute_energy_curve(&path, 0, DEFAULT_HOP_MS)
+      .await;
+    assert!(matches!(
+      invalid,
+      Err(MontageError::InvalidConfiguration(_))
+    ));
+  }
+}
@@ -2,9 +2,11 @@
 
 pub mod activity_calculator_deep_tests;
 pub mod audio_analyzer_deep_tests;
+pub mod beat_detector_deep_tests;
 pub mod composition_analyzer_deep_tests;
 pub mod comprehensive_tests;
 pub mod emotion_detector_deep_tests;
+pub mod energy_curve_deep_tests;
 pub mod integration_tests;
 pub mod moment_detector_deep_tests;
 pub mod quality_analyzer_deep_tests;
@@ -1,10 +1,12 @@
 #[cfg(test)]
 mod tests {
+  use crate::montage_planner::services::plan_generator::PlanGenerator;
   use crate::montage_planner::services::quality_analyzer::{
-    FrameQualityAnalysis, QualityAnalysisConfig, QualityIssue, QualityIssueSeverity,
-    QualityIssueType, VideoQualityAnalyzer,
+    score_segments, FrameQualityAnalysis, GrayFrame, QualityAnalysisConfig, QualityIssue,
+    QualityIssueSeverity, QualityIssueType, VideoQualityAnalyzer,
   };
   use crate::montage_planner::types::*;
+  use image::{DynamicImage, GrayImage, Luma};
   use std::fs;
   use std::path::PathBuf;
   use tempfile::TempDir;
@@ -73,6 +75,7 @@ mod tests {
       total_score: 80.0,
       description: "Test moment".to_string(),
       tags: vec!["test".to_string()],
+      source_file: None,
     }
   }
 
@@ -634,4 +637,118 @@ mod tests {
     // Both should create successfully
     // Can't access private config field, but creation should succeed
   }
+
+  /// Deterministic blocky noise texture, larger than a sample frame so it can be cropped
+  fn texture(width: u32, height: u32) -> GrayImage {
+    let mut state = 0x2545_f491u32;
+    let blocks: Vec<u8> = (0..(width / 8 + 1) * (height / 8 + 1))
+      .map(|_| {
+        state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        40 + (state >> 24) as u8 % 170
+      })
+      .collect();
+    GrayImage::from_fn(width, height, |x, y| {
+      Luma([blocks[((y / 8) * (width / 8 + 1) + x / 8) as usize]])
+    })
+  }
+
+  fn sample(image: &GrayImage, x: u32, y: u32) -> GrayFrame {
+    let crop = image::imageops::crop_imm(image, x, y, 320, 180).to_image();
+    GrayFrame::from_image(&DynamicImage::ImageLuma8(crop))
+  }
+
+  fn brighten(image: &GrayImage, amount: u8) -> GrayImage {
+    GrayImage::from_fn(image.width(), image.height(), |x, y| {
+      Luma([image.get_pixel(x, y)[0].saturating_add(amount)])
+    })
+  }
+
+  #[test]
+  fn test_sharp_frame_scores_above_blurred() {
+    let sharp_image = texture(320, 180);
+    let blurred_image = image::imageops::blur(&sharp_image, 3.0);
+    let sharp = sample(&sharp_image, 0, 0);
+    let blurred = sample(&blurred_image, 0, 0);
+
+    assert!(sharp.laplacian_variance() > blurred.laplacian_variance() * 4.0);
+
+    let scores = score_segments(&[(0.0, sharp), (5.0, blurred)], 5.0);
+    assert_eq!(scores.len(), 2);
+    assert!(scores[0].sharpness > scores[1].sharpness);
+    assert!(scores[0].overall > scores[1].overall);
+  }
+
+  #[test]
+  fn test_bright_clipped_frame_reports_overexposure() {
+    let normal_image = texture(320, 180);
+    let normal = sample(&normal_image, 0, 0);
+    let clipped = sample(&brighten(&normal_image, 120), 0, 0);
+
+    let (normal_over, normal_under) = normal.exposure_clipping();
+    let (clipped_over, _) = clipped.exposure_clipping();
+    assert_eq!(normal_over, 0.0);
+    assert_eq!(normal_under, 0.0);
+    assert!(clipped_over > 0.3);
+
+    let scores = score_segments(&[(0.0, normal), (5.0, clipped)], 5.0);
+    assert!(scores[1].overexposed_ratio > scores[0].overexposed_ratio);
+    assert!(scores[1].overall < scores[0].overall);
+  }
+
+  #[test]
+  fn test_shaky_segment_scores_more_shake_than_static() {
+    let base = texture(400, 260);
+    let steady: Vec<(f64, GrayFrame)> = (0..5).map(|i| (i as f64, sample(&base, 20, 20))).collect();
+    let offsets = [(20, 20), (40, 30), (10, 45), (35, 5), (15, 35)];
+    let shaky: Vec<(f64, GrayFrame)> = offsets
+      .iter()
+      .enumerate()
+      .map(|(i, &(x, y))| (i as f64, sample(&base, x, y)))
+      .collect();
+
+    assert!(shaky[1].1.global_motion(&shaky[0].1) > 0.04);
+    assert_eq!(steady[1].1.global_motion(&steady[0].1), 0.0);
+
+    let steady_score = &score_segments(&steady, 5.0)[0];
+    let shaky_score = &score_segments(&shaky, 5.0)[0];
+    assert_eq!(steady_score.frame_count, 5);
+    assert!(shaky_score.shake > steady_score.shake);
+    assert!(shaky_score.overall < steady_score.overall);
+  }
+
+  #[test]
+  fn test_plan_generator_down_weights_low_quality_segments() {
+    let sharp_image = texture(320, 180);
+    let blurred_image = image::imageops::blur(&sharp_image, 3.0);
+    let scores = score_segments(
+      &[
+        (0.0, sample(&sharp_image, 0, 0)),
+        (5.0, sample(&blurred_image, 0, 0)),
+      ],
+      5.0,
+    );
+
+    let moment = |timestamp: f64| DetectedMoment {
+      timestamp,
+      duration: 2.0,
+      category: MomentCategory::Action,
+      scores: MomentScores {
+        visual: 80.0,
+        technical: 80.0,
+        emotional: 80.0,
+        narrative: 80.0,
+        action: 80.0,
+        composition: 80.0,
+      },
+      total_score: 80.0,
+      description: String::new(),
+      tags: Vec::new(),
+      source_file: None,
+    };
+
+    let weighted = PlanGenerator::new().apply_quality_scores(&[moment(1.0), moment(6.0)], &scores);
+    assert_eq!(weighted[0].total_score, 80.0);
+    assert!(weighted[1].total_score < weighted[0].total_score);
+    assert!(weighted[1].scores.technical < 80.0);
+  }
 }
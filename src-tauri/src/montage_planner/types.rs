@@ -152,6 +152,9 @@ pub struct DetectedMoment {
   pub total_score: f32,
   pub description: String,
   pub tags: Vec<String>,
+  /// File the moment was detected in; plans without it use the first source file
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub source_file: Option<String>,
 }
 
 /// Categories of detected moments
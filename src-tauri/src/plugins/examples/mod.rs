@@ -36,10 +36,26 @@ pub async fn register_example_plugins(
   Ok(())
 }
 
+/// Найти фабрику примера плагина по имени точки входа из `plugin.toml`
+pub fn example_plugin_factory(name: &str) -> Option<crate::core::plugins::loader::PluginFactory> {
+  use crate::core::plugins::plugin::Plugin;
+
+  match name {
+    "blur-effect" => Some(Box::new(|| {
+      Box::new(BlurEffectPlugin::default()) as Box<dyn Plugin>
+    })),
+    "youtube-uploader" => Some(Box::new(|| {
+      Box::new(YouTubeUploaderPlugin::default()) as Box<dyn Plugin>
+    })),
+    _ => None,
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
   use crate::core::plugins::loader::PluginRegistry;
+  use crate::core::plugins::plugin::Plugin;
 
   #[tokio::test]
   async fn test_register_example_plugins() {
@@ -56,4 +72,11 @@ mod tests {
     assert!(registry.find_plugin("blur-effect").await.is_some());
     assert!(registry.find_plugin("youtube-uploader").await.is_some());
   }
+
+  #[test]
+  fn test_example_plugin_factory() {
+    let plugin = (example_plugin_factory("blur-effect").unwrap())();
+    assert_eq!(plugin.metadata().id, "blur-effect");
+    assert!(example_plugin_factory("unknown").is_none());
+  }
 }
@@ -3,4 +3,6 @@
 pub mod examples;
 
 // Re-export для удобства
-pub use examples::{register_example_plugins, BlurEffectPlugin, YouTubeUploaderPlugin};
+pub use examples::{
+  example_plugin_factory, register_example_plugins, BlurEffectPlugin, YouTubeUploaderPlugin,
+};
@@ -3,11 +3,27 @@ pub mod yolo_commands;
 // Re-export YOLO commands for convenience
 
 use anyhow::Result;
-use serde_json::Value;
-use tauri::{AppHandle, Emitter, State};
-
-use crate::recognition::recognition_service::{RecognitionEvent, RecognitionService};
-use crate::recognition::types::RecognitionResults;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio_util::sync::CancellationToken;
+
+use crate::core::tasks::{TaskHandle, TaskKind, TASKS};
+use crate::media::media_tags::refresh_media_tags;
+use crate::media::preview_data::MediaPreviewData;
+use crate::media::preview_manager::PreviewDataManager;
+use crate::video_compiler::VideoCompilerState;
+
+use crate::recognition::dataset_export::{self, DatasetExportOptions, DatasetFormat};
+use crate::recognition::overlay::{self, OverlayOptions};
+use crate::recognition::result_aggregator::TimelineBucket;
+use crate::recognition::results_comparison::{self, ComparisonOptions, RecognitionComparison};
+
+use crate::recognition::recognition_service::{
+  BatchItemResult, BatchProcessingOptions, RecognitionEvent, RecognitionService,
+};
+use crate::recognition::session_pool::{RecognitionRuntimeStats, SessionPool};
+use crate::recognition::types::{RecognitionConfig, RecognitionResults};
+use crate::recognition::yolo_processor::YoloModel;
 
 /// State для сервиса распознавания
 pub struct RecognitionState {
@@ -22,29 +38,39 @@ impl Default for RecognitionState {
 
 impl RecognitionState {
   pub fn new() -> Self {
+    Self::try_new().expect("Failed to create fallback RecognitionService")
+  }
+
+  /// Создать состояние, вернув ошибку, если сервис не удалось создать ни в
+  /// кэше приложения, ни во временной директории
+  pub fn try_new() -> Result<Self> {
     let base_dir = dirs::cache_dir()
       .unwrap_or_default()
       .join("timeline-studio");
 
-    let service = RecognitionService::new(base_dir).unwrap_or_else(|_| {
-      // Fallback если не удалось создать сервис
-      log::warn!("Failed to create RecognitionService, using default");
-      // Создаем временный сервис для компиляции
-      RecognitionService::new(std::env::temp_dir().join("timeline-studio"))
-        .expect("Failed to create fallback RecognitionService")
-    });
+    let service = match RecognitionService::new(base_dir) {
+      Ok(service) => service,
+      Err(_) => {
+        // Fallback если не удалось создать сервис
+        log::warn!("Failed to create RecognitionService, using default");
+        RecognitionService::new(std::env::temp_dir().join("timeline-studio"))?
+      }
+    };
 
-    Self { service }
+    Ok(Self { service })
   }
 }
 
-/// Обработать видео и распознать объекты/лица
+/// Обработать видео и распознать объекты/лица.
+///
+/// `config` переопределяет параметры распознавания только для этого вызова.
 #[tauri::command]
 pub async fn process_video_recognition<R: tauri::Runtime>(
   app: AppHandle<R>,
   state: State<'_, RecognitionState>,
   file_id: String,
   frame_paths: Vec<String>,
+  config: Option<RecognitionConfig>,
 ) -> Result<RecognitionResults, String> {
   // Отправляем событие о начале
   app
@@ -63,8 +89,13 @@ pub async fn process_video_recognition<R: tauri::Runtime>(
     .collect();
 
   // Обрабатываем видео
-  match state.service.process_video(&file_id, paths).await {
+  match state.service.process_video(&file_id, paths, config).await {
     Ok(results) => {
+      if let Some(previews) = app.try_state::<Arc<PreviewDataManager>>() {
+        previews.attach_recognition(&file_id, results.clone()).await;
+      }
+      refresh_media_tags(&app, &file_id, Some(&results)).await;
+
       // Отправляем событие о завершении
       app
         .emit(
@@ -129,33 +160,128 @@ pub async fn process_batch_recognition(
 }
 */
 
-/// Обработать пакет видео (множественная обработка)
+/// Зарегистрировать пакет распознавания как фоновую задачу
+///
+/// Отмена задачи останавливает пакет перед следующим файлом.
+fn register_batch_task(total: usize) -> (TaskHandle, CancellationToken) {
+  let token = CancellationToken::new();
+  let cancel = token.clone();
+  let task = TASKS.register_cancellable(
+    TaskKind::Recognition,
+    format!("Распознавание {total} файлов"),
+    move || cancel.cancel(),
+  );
+  (task, token)
+}
+
+/// Обновить прогресс фоновой задачи пакета
+fn report_batch_progress(task: &TaskHandle, completed: usize, total: usize) {
+  if total > 0 {
+    task.set_progress(completed as f32 / total as f32 * 100.0);
+  }
+}
+
+/// Обработать пакет видео (множественная обработка).
+///
+/// Совместимая обертка над пакетной обработкой: возвращает только успешно
+/// обработанные файлы. Подробный результат по каждому файлу отдает
+/// `process_video_batch_detailed`.
 #[tauri::command]
-pub async fn process_video_batch(
+pub async fn process_video_batch<R: tauri::Runtime>(
+  app: AppHandle<R>,
   file_ids: Vec<String>,
   frame_paths_map: std::collections::HashMap<String, Vec<std::path::PathBuf>>,
   state: State<'_, RecognitionState>,
 ) -> Result<Vec<(String, RecognitionResults)>, String> {
-  let service = &state.service;
-
   log::info!(
     "Начато пакетное распознавание для {} файлов",
     file_ids.len()
   );
 
-  match service.process_batch(file_ids, frame_paths_map).await {
-    Ok(results) => {
-      log::info!(
-        "Пакетное распознавание завершено. Обработано {} файлов",
-        results.len()
-      );
-      Ok(results)
-    }
-    Err(e) => {
-      log::error!("Ошибка пакетного распознавания: {e}");
-      Err(format!("Ошибка пакетного распознавания: {e}"))
+  let (task, cancel) = register_batch_task(file_ids.len());
+  let options = BatchProcessingOptions {
+    cancel: Some(cancel),
+    ..Default::default()
+  };
+
+  let items = state
+    .service
+    .process_batch_with_progress(file_ids, frame_paths_map, options, |_, completed, total| {
+      report_batch_progress(&task, completed, total)
+    })
+    .await;
+  task.finish();
+
+  let total = items.len();
+  let results: Vec<(String, RecognitionResults)> = items
+    .into_iter()
+    .filter_map(|item| item.result.ok().map(|results| (item.file_id, results)))
+    .collect();
+  for (file_id, results) in &results {
+    refresh_media_tags(&app, file_id, Some(results)).await;
+  }
+
+  log::info!(
+    "Пакетное распознавание завершено. Обработано {} из {} файлов",
+    results.len(),
+    total
+  );
+  Ok(results)
+}
+
+/// Обработать пакет видео с результатом по каждому файлу.
+///
+/// Ошибка одного файла не прерывает пакет. После каждого файла отправляется
+/// событие `BatchItemCompleted`; при `persist_results` (по умолчанию включено)
+/// результаты файла сохраняются сразу после его обработки.
+#[tauri::command]
+pub async fn process_video_batch_detailed<R: tauri::Runtime>(
+  app: AppHandle<R>,
+  file_ids: Vec<String>,
+  frame_paths_map: std::collections::HashMap<String, Vec<std::path::PathBuf>>,
+  persist_results: Option<bool>,
+  state: State<'_, RecognitionState>,
+) -> Result<Vec<BatchItemResult>, String> {
+  let (task, cancel) = register_batch_task(file_ids.len());
+  let persist_results = persist_results.unwrap_or(true);
+  let options = BatchProcessingOptions {
+    persist_results,
+    cancel: Some(cancel),
+  };
+
+  let items = state
+    .service
+    .process_batch_with_progress(
+      file_ids,
+      frame_paths_map,
+      options,
+      |item, completed, total| {
+        let event = RecognitionEvent::BatchItemCompleted {
+          file_id: item.file_id.clone(),
+          error: item.result.as_ref().err().cloned(),
+          duration_ms: item.duration_ms,
+          completed,
+          total,
+        };
+        if let Err(e) = app.emit("recognition", event) {
+          log::warn!("Failed to emit batch progress: {e}");
+        }
+        report_batch_progress(&task, completed, total);
+      },
+    )
+    .await;
+  task.finish();
+
+  // Теги обновляются только для сохраненных результатов
+  if persist_results {
+    for item in &items {
+      if let Ok(results) = &item.result {
+        refresh_media_tags(&app, &item.file_id, Some(results)).await;
+      }
     }
   }
+
+  Ok(items)
 }
 
 /// Получить результаты распознавания для файла
@@ -172,18 +298,25 @@ pub async fn get_recognition_results(
 }
 
 /// Получить данные превью с результатами распознавания
+///
+/// Сохраненные результаты распознавания прикрепляются к данным превью файла
+/// в PreviewDataManager. `None`, если для файла нет ни превью, ни результатов.
 #[tauri::command]
 pub async fn get_preview_data_with_recognition(
-  _state: State<'_, RecognitionState>,
+  state: State<'_, RecognitionState>,
+  previews: State<'_, Arc<PreviewDataManager>>,
   file_id: String,
-) -> Result<Value, String> {
-  // Эта команда возвращает полные данные превью включая результаты распознавания
-  // В реальной реализации нужно добавить доступ к PreviewDataManager
-
-  Ok(serde_json::json!({
-      "file_id": file_id,
-      "message": "This command needs PreviewDataManager integration"
-  }))
+) -> Result<Option<MediaPreviewData>, String> {
+  if let Some(results) = state
+    .service
+    .load_results(&file_id)
+    .await
+    .map_err(|e| e.to_string())?
+  {
+    previews.attach_recognition(&file_id, results).await;
+  }
+
+  Ok(previews.get_preview_data(&file_id).await)
 }
 
 /// Загрузить модель YOLO для объектов (для администрирования)
@@ -211,6 +344,55 @@ pub async fn set_yolo_target_classes(
   Ok(())
 }
 
+/// Получить статистику пула сессий моделей распознавания
+#[tauri::command]
+pub async fn get_recognition_runtime_stats() -> Result<RecognitionRuntimeStats, String> {
+  Ok(SessionPool::shared().stats())
+}
+
+/// Выгрузить все сессии моделей распознавания для освобождения памяти.
+/// Сессии загружаются заново при следующем распознавании.
+#[tauri::command]
+pub async fn unload_recognition_models() -> Result<usize, String> {
+  let unloaded = SessionPool::shared().unload_all();
+
+  log::info!("Выгружено сессий моделей распознавания: {unloaded}");
+  Ok(unloaded)
+}
+
+/// Установить предел одновременных инференсов (`None` - по провайдеру)
+#[tauri::command]
+pub async fn set_recognition_inference_limit(limit: Option<usize>) -> Result<(), String> {
+  SessionPool::shared().set_max_concurrent_inferences(limit);
+
+  log::info!("Установлен предел одновременных инференсов: {limit:?}");
+  Ok(())
+}
+
+/// Установить параметры распознавания по умолчанию
+#[tauri::command]
+pub async fn set_recognition_config(
+  config: RecognitionConfig,
+  state: State<'_, RecognitionState>,
+) -> Result<(), String> {
+  state
+    .service
+    .set_config(config.clone())
+    .await
+    .map_err(|e| e.to_string())?;
+
+  log::info!("Установлены параметры распознавания: {config:?}");
+  Ok(())
+}
+
+/// Получить параметры распознавания по умолчанию
+#[tauri::command]
+pub async fn get_recognition_config(
+  state: State<'_, RecognitionState>,
+) -> Result<RecognitionConfig, String> {
+  Ok(state.service.get_config().await)
+}
+
 /// Получить список доступных классов YOLO для объектов
 #[tauri::command]
 pub async fn get_yolo_class_names(
@@ -237,22 +419,52 @@ pub async fn process_yolo_batch(
     .map_err(|e| format!("Ошибка пакетной обработки YOLO для объектов: {e}"))
 }
 
-/// Очистить результаты распознавания
+/// Очистить результаты распознавания и выведенные из них теги файла
 #[tauri::command]
-pub async fn clear_recognition_results(
-  _state: State<'_, RecognitionState>,
-  _file_id: String,
+pub async fn clear_recognition_results<R: tauri::Runtime>(
+  app: AppHandle<R>,
+  state: State<'_, RecognitionState>,
+  file_id: String,
 ) -> Result<(), String> {
-  // В реальной реализации здесь бы очищались результаты
+  state
+    .service
+    .clear_results(&file_id)
+    .await
+    .map_err(|e| e.to_string())?;
+  refresh_media_tags(&app, &file_id, None).await;
   Ok(())
 }
 
-/// Экспортировать результаты распознавания
+/// Построить шкалу плотности обнаружений файла для тепловой карты.
+///
+/// Интервалы длиной `bucket_secs` содержат количество появлений по классам
+/// и максимальную уверенность; `classes` ограничивает набор классов.
+#[tauri::command]
+pub async fn build_recognition_timeline(
+  state: State<'_, RecognitionState>,
+  file_id: String,
+  bucket_secs: f64,
+  classes: Option<Vec<String>>,
+) -> Result<Vec<TimelineBucket>, String> {
+  state
+    .service
+    .recognition_timeline(&file_id, bucket_secs, classes)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Экспортировать результаты распознавания.
+///
+/// `json` и `csv` возвращают содержимое строкой. Форматы датасетов `coco`
+/// (JSON) и `yolo` (zip-архив) записываются в `output_path` с учетом
+/// фильтров `options`; возвращается путь к файлу.
 #[tauri::command]
 pub async fn export_recognition_results(
   state: State<'_, RecognitionState>,
   file_id: String,
   format: String,
+  output_path: Option<String>,
+  options: Option<DatasetExportOptions>,
 ) -> Result<String, String> {
   let results = state
     .service
@@ -260,6 +472,27 @@ pub async fn export_recognition_results(
     .await
     .map_err(|e| e.to_string())?;
 
+  if let Some(dataset_format) = DatasetFormat::parse(&format) {
+    let results = results.ok_or_else(|| "No results found".to_string())?;
+    let output_path =
+      output_path.ok_or_else(|| format!("Output path is required for {format} export"))?;
+    let options = options.unwrap_or_default();
+
+    let path = output_path.clone();
+    tokio::task::spawn_blocking(move || {
+      dataset_export::export_dataset(
+        &results,
+        dataset_format,
+        std::path::Path::new(&path),
+        &options,
+      )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())?;
+    return Ok(output_path);
+  }
+
   if let Some(results) = results {
     match format.as_str() {
       "json" => serde_json::to_string_pretty(&results).map_err(|e| e.to_string()),
@@ -295,3 +528,155 @@ pub async fn export_recognition_results(
     Err("No results found".to_string())
   }
 }
+
+/// Параметры сравнения с переопределенными порогами
+fn comparison_options(
+  iou_threshold: Option<f32>,
+  time_tolerance: Option<f64>,
+) -> ComparisonOptions {
+  let defaults = ComparisonOptions::default();
+  ComparisonOptions {
+    iou_threshold: iou_threshold.unwrap_or(defaults.iou_threshold),
+    time_tolerance: time_tolerance.unwrap_or(defaults.time_tolerance),
+    ..defaults
+  }
+}
+
+/// Сравнить результаты и при `csv_path` сохранить отчет в CSV
+async fn run_comparison(
+  file_id: &str,
+  results_a: &RecognitionResults,
+  results_b: &RecognitionResults,
+  options: &ComparisonOptions,
+  csv_path: Option<String>,
+) -> Result<RecognitionComparison, String> {
+  let report = results_comparison::compare_results(file_id, results_a, results_b, options)
+    .map_err(|e| e.to_string())?;
+
+  if let Some(csv_path) = csv_path {
+    let path = std::path::Path::new(&csv_path);
+    if let Some(parent) = path.parent() {
+      tokio::fs::create_dir_all(parent)
+        .await
+        .map_err(|e| e.to_string())?;
+    }
+    tokio::fs::write(path, results_comparison::format_comparison_csv(&report))
+      .await
+      .map_err(|e| format!("Cannot write {csv_path}: {e}"))?;
+  }
+
+  log::info!(
+    "Сравнение результатов распознавания {file_id}: {} совпадений, {} удалено, {} добавлено",
+    report.matched.len(),
+    report.removed.len(),
+    report.added.len()
+  );
+  Ok(report)
+}
+
+/// Сравнить два набора результатов распознавания файла.
+///
+/// Набор A считается эталоном для точности и полноты по классам.
+/// `time_tolerance` задает допустимое расхождение меток времени в секундах;
+/// при `csv_path` отчет дополнительно сохраняется в CSV.
+#[tauri::command]
+pub async fn compare_recognition_results(
+  file_id: String,
+  results_a: RecognitionResults,
+  results_b: RecognitionResults,
+  iou_threshold: Option<f32>,
+  time_tolerance: Option<f64>,
+  csv_path: Option<String>,
+) -> Result<RecognitionComparison, String> {
+  let options = comparison_options(iou_threshold, time_tolerance);
+  run_comparison(&file_id, &results_a, &results_b, &options, csv_path).await
+}
+
+/// Распознать кадры другой моделью объектов и сравнить с сохраненными
+/// результатами файла.
+///
+/// Новые результаты не сохраняются: сохраненные остаются эталоном, пока
+/// пользователь не перейдет на новую модель. Распознавание выполняется с
+/// параметрами сохраненных результатов.
+#[tauri::command]
+pub async fn compare_recognition_with_model(
+  state: State<'_, RecognitionState>,
+  file_id: String,
+  frame_paths: Vec<String>,
+  model: String,
+  iou_threshold: Option<f32>,
+  time_tolerance: Option<f64>,
+  csv_path: Option<String>,
+) -> Result<RecognitionComparison, String> {
+  let stored = state
+    .service
+    .load_results(&file_id)
+    .await
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| "No results found".to_string())?;
+
+  let paths = frame_paths
+    .into_iter()
+    .map(std::path::PathBuf::from)
+    .collect();
+  let candidate = state
+    .service
+    .analyze_with_object_model(paths, YoloModel::from_name(&model), stored.config.clone())
+    .await
+    .map_err(|e| e.to_string())?;
+
+  let options = comparison_options(iou_threshold, time_tolerance);
+  run_comparison(&file_id, &stored, &candidate, &options, csv_path).await
+}
+
+/// Экспортировать превью видео с рамками распознанных объектов и лиц.
+///
+/// Экспорт регистрируется как фоновая задача: прогресс и отмена доступны
+/// через `list_background_tasks`/`cancel_background_task`.
+#[tauri::command]
+pub async fn export_recognition_overlay(
+  state: State<'_, RecognitionState>,
+  compiler_state: State<'_, VideoCompilerState>,
+  file_id: String,
+  source_path: String,
+  output_path: String,
+  options: Option<OverlayOptions>,
+) -> Result<String, String> {
+  let results = state
+    .service
+    .load_results(&file_id)
+    .await
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| "No results found".to_string())?;
+
+  let options = options.unwrap_or_default();
+  let boxes = overlay::collect_overlay_boxes(&results, &options);
+  let ffmpeg_path = compiler_state.ffmpeg_path.read().await.clone();
+
+  let token = CancellationToken::new();
+  let cancel = token.clone();
+  let task = TASKS.register_cancellable(
+    TaskKind::Preview,
+    format!("Оверлей распознавания {file_id}"),
+    move || cancel.cancel(),
+  );
+
+  let output = overlay::render_overlay(
+    &ffmpeg_path,
+    std::path::Path::new(&source_path),
+    std::path::Path::new(&output_path),
+    &boxes,
+    &options,
+    &token,
+    |progress| task.set_progress(progress),
+  )
+  .await
+  .map_err(|e| e.to_string())?;
+  task.finish();
+
+  log::info!(
+    "Оверлей распознавания для {file_id} экспортирован: {} рамок",
+    boxes.len()
+  );
+  Ok(output.to_string_lossy().to_string())
+}
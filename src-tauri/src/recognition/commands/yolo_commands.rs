@@ -14,7 +14,9 @@ use std::sync::Arc;
 use tauri::State;
 use tokio::sync::RwLock;
 
-/// Состояние для хранения активных процессоров
+/// Состояние для хранения активных процессоров.
+/// Клоны разделяют один набор процессоров.
+#[derive(Clone)]
 pub struct YoloProcessorState {
   pub processors: Arc<RwLock<HashMap<String, Arc<RwLock<YoloProcessor>>>>>,
 }
@@ -91,6 +91,8 @@ mod tests {
       }],
       scenes: vec![],
       processed_at: chrono::Utc::now(),
+      config: None,
+      frames: Vec::new(),
     };
 
     let json = serde_json::to_string(&results).unwrap();
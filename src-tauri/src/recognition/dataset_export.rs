@@ -0,0 +1,578 @@
+//! Экспорт результатов распознавания в форматы датасетов
+//!
+//! Детекции из сохраненных `RecognitionResults` раскладываются по кадрам,
+//! на которых выполнялось распознавание, и сохраняются в форматах COCO
+//! (один JSON с изображениями, аннотациями и категориями) и YOLO
+//! (`.txt` на кадр с нормализованными `cx cy w h` и `classes.txt`,
+//! упакованные в zip-архив). Рамки обрезаются по границам кадра.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::types::{BoundingBox, RecognitionResults, RecognizedFrame};
+
+/// Имя категории лиц в датасете
+pub const FACE_CATEGORY: &str = "face";
+
+/// Формат экспорта датасета
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DatasetFormat {
+  Coco,
+  Yolo,
+}
+
+impl DatasetFormat {
+  /// Формат по имени из команды экспорта
+  pub fn parse(format: &str) -> Option<Self> {
+    match format {
+      "coco" => Some(Self::Coco),
+      "yolo" => Some(Self::Yolo),
+      _ => None,
+    }
+  }
+}
+
+/// Параметры экспорта датасета
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DatasetExportOptions {
+  /// Классы объектов для экспорта (`None` - все классы)
+  pub classes: Option<Vec<String>>,
+  /// Минимальная уверенность детекции
+  pub min_confidence: f32,
+  /// Начало временного диапазона в секундах
+  pub start_time: Option<f64>,
+  /// Конец временного диапазона в секундах
+  pub end_time: Option<f64>,
+  /// Экспортировать лица как категорию `face`
+  pub include_faces: bool,
+}
+
+impl Default for DatasetExportOptions {
+  fn default() -> Self {
+    Self {
+      classes: None,
+      min_confidence: 0.0,
+      start_time: None,
+      end_time: None,
+      include_faces: false,
+    }
+  }
+}
+
+impl DatasetExportOptions {
+  fn includes_time(&self, timestamp: f64) -> bool {
+    self.start_time.is_none_or(|start| timestamp >= start)
+      && self.end_time.is_none_or(|end| timestamp <= end)
+  }
+
+  fn includes_class(&self, class: &str) -> bool {
+    self
+      .classes
+      .as_ref()
+      .is_none_or(|classes| classes.iter().any(|name| name == class))
+  }
+}
+
+/// Датасет в формате COCO
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CocoDataset {
+  pub images: Vec<CocoImage>,
+  pub annotations: Vec<CocoAnnotation>,
+  pub categories: Vec<CocoCategory>,
+}
+
+/// Изображение COCO (кадр видео)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CocoImage {
+  pub id: u64,
+  pub file_name: String,
+  pub width: u32,
+  pub height: u32,
+  /// Метка времени кадра в видео (поле расширения)
+  pub timestamp: f64,
+}
+
+/// Аннотация COCO
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CocoAnnotation {
+  pub id: u64,
+  pub image_id: u64,
+  pub category_id: u64,
+  /// `[x, y, width, height]` в пикселях
+  pub bbox: [f32; 4],
+  pub area: f32,
+  pub iscrowd: u8,
+  /// Уверенность детекции (поле расширения)
+  pub score: f32,
+}
+
+/// Категория COCO
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CocoCategory {
+  pub id: u64,
+  pub name: String,
+  pub supercategory: String,
+}
+
+/// Детекция, привязанная к кадру
+#[derive(Debug, Clone)]
+struct FrameDetection<'a> {
+  frame: &'a RecognizedFrame,
+  class: &'a str,
+  confidence: f32,
+  bbox: BoundingBox,
+}
+
+/// Имя файла кадра в датасете
+fn frame_file_stem(frame: &RecognizedFrame) -> String {
+  format!("frame_{:06}", frame.index)
+}
+
+/// Обрезать рамку по границам кадра. `None`, если от рамки ничего не осталось.
+fn clamp_to_frame(bbox: &BoundingBox, frame: &RecognizedFrame) -> Option<BoundingBox> {
+  let (width, height) = (frame.width as f32, frame.height as f32);
+  let x0 = bbox.x.clamp(0.0, width);
+  let y0 = bbox.y.clamp(0.0, height);
+  let x1 = (bbox.x + bbox.width).clamp(0.0, width);
+  let y1 = (bbox.y + bbox.height).clamp(0.0, height);
+  (x1 > x0 && y1 > y0).then_some(BoundingBox {
+    x: x0,
+    y: y0,
+    width: x1 - x0,
+    height: y1 - y0,
+  })
+}
+
+/// Кадры в выбранном диапазоне и детекции на них
+fn collect_frame_detections<'a>(
+  results: &'a RecognitionResults,
+  options: &DatasetExportOptions,
+) -> Result<(Vec<&'a RecognizedFrame>, Vec<FrameDetection<'a>>)> {
+  if results.frames.is_empty() {
+    bail!("Recognition results have no frame information; re-run recognition to export a dataset");
+  }
+
+  // Метки времени детекций совпадают с метками кадров; сравниваем по миллисекундам
+  let frame_key = |timestamp: f64| (timestamp * 1000.0).round() as i64;
+  let frames_by_time: HashMap<i64, &RecognizedFrame> = results
+    .frames
+    .iter()
+    .filter(|frame| frame.width > 0 && frame.height > 0)
+    .map(|frame| (frame_key(frame.timestamp), frame))
+    .collect();
+
+  let mut frames: Vec<&RecognizedFrame> = frames_by_time
+    .values()
+    .copied()
+    .filter(|frame| options.includes_time(frame.timestamp))
+    .collect();
+  frames.sort_by_key(|frame| frame.index);
+
+  let objects = results
+    .objects
+    .iter()
+    .filter(|object| options.includes_class(&object.class))
+    .map(|object| {
+      (
+        object.class.as_str(),
+        object.confidence,
+        &object.timestamps,
+        &object.bounding_boxes,
+      )
+    });
+  let faces = results
+    .faces
+    .iter()
+    .filter(|_| options.include_faces)
+    .map(|face| {
+      (
+        FACE_CATEGORY,
+        face.confidence,
+        &face.timestamps,
+        &face.bounding_boxes,
+      )
+    });
+
+  let mut detections = Vec::new();
+  for (class, confidence, timestamps, boxes) in objects.chain(faces) {
+    if confidence < options.min_confidence {
+      continue;
+    }
+    for (timestamp, bbox) in timestamps.iter().zip(boxes) {
+      let Some(&frame) = frames_by_time.get(&frame_key(*timestamp)) else {
+        log::debug!("Нет кадра для детекции {class} на {timestamp:.3}с");
+        continue;
+      };
+      if !options.includes_time(frame.timestamp) {
+        continue;
+      }
+      if let Some(bbox) = clamp_to_frame(bbox, frame) {
+        detections.push(FrameDetection {
+          frame,
+          class,
+          confidence,
+          bbox,
+        });
+      }
+    }
+  }
+
+  Ok((frames, detections))
+}
+
+/// Отсортированный список классов экспортируемых детекций
+fn class_names(detections: &[FrameDetection]) -> Vec<String> {
+  detections
+    .iter()
+    .map(|detection| detection.class)
+    .collect::<BTreeSet<_>>()
+    .into_iter()
+    .map(str::to_string)
+    .collect()
+}
+
+/// Построить датасет COCO
+pub fn build_coco_dataset(
+  results: &RecognitionResults,
+  options: &DatasetExportOptions,
+) -> Result<CocoDataset> {
+  let (frames, detections) = collect_frame_detections(results, options)?;
+  let classes = class_names(&detections);
+
+  let categories: Vec<CocoCategory> = classes
+    .iter()
+    .enumerate()
+    .map(|(idx, name)| CocoCategory {
+      id: idx as u64 + 1,
+      name: name.clone(),
+      supercategory: if name == FACE_CATEGORY {
+        "person"
+      } else {
+        "object"
+      }
+      .to_string(),
+    })
+    .collect();
+  let category_ids: HashMap<&str, u64> = categories
+    .iter()
+    .map(|category| (category.name.as_str(), category.id))
+    .collect();
+
+  let images: Vec<CocoImage> = frames
+    .iter()
+    .enumerate()
+    .map(|(idx, frame)| CocoImage {
+      id: idx as u64 + 1,
+      file_name: format!("{}.jpg", frame_file_stem(frame)),
+      width: frame.width,
+      height: frame.height,
+      timestamp: frame.timestamp,
+    })
+    .collect();
+  let image_ids: HashMap<usize, u64> = frames
+    .iter()
+    .zip(&images)
+    .map(|(frame, image)| (frame.index, image.id))
+    .collect();
+
+  let annotations = detections
+    .iter()
+    .enumerate()
+    .map(|(idx, detection)| CocoAnnotation {
+      id: idx as u64 + 1,
+      image_id: image_ids[&detection.frame.index],
+      category_id: category_ids[detection.class],
+      bbox: [
+        detection.bbox.x,
+        detection.bbox.y,
+        detection.bbox.width,
+        detection.bbox.height,
+      ],
+      area: detection.bbox.width * detection.bbox.height,
+      iscrowd: 0,
+      score: detection.confidence,
+    })
+    .collect();
+
+  Ok(CocoDataset {
+    images,
+    annotations,
+    categories,
+  })
+}
+
+/// Датасет в формате YOLO
+#[derive(Debug, Clone, PartialEq)]
+pub struct YoloDataset {
+  /// Классы в порядке индексов
+  pub classes: Vec<String>,
+  /// Содержимое `.txt` по имени файла кадра
+  pub labels: BTreeMap<String, String>,
+}
+
+/// Построить датасет YOLO. Кадры без детекций получают пустой файл разметки.
+pub fn build_yolo_dataset(
+  results: &RecognitionResults,
+  options: &DatasetExportOptions,
+) -> Result<YoloDataset> {
+  let (frames, detections) = collect_frame_detections(results, options)?;
+  let classes = class_names(&detections);
+  let class_ids: HashMap<&str, usize> = classes
+    .iter()
+    .enumerate()
+    .map(|(idx, name)| (name.as_str(), idx))
+    .collect();
+
+  let mut labels: BTreeMap<String, String> = frames
+    .iter()
+    .map(|frame| (format!("{}.txt", frame_file_stem(frame)), String::new()))
+    .collect();
+  for detection in &detections {
+    let frame = detection.frame;
+    let (width, height) = (frame.width as f32, frame.height as f32);
+    let bbox = &detection.bbox;
+    let line = format!(
+      "{} {:.6} {:.6} {:.6} {:.6}\n",
+      class_ids[detection.class],
+      (bbox.x + bbox.width / 2.0) / width,
+      (bbox.y + bbox.height / 2.0) / height,
+      bbox.width / width,
+      bbox.height / height
+    );
+    labels
+      .entry(format!("{}.txt", frame_file_stem(frame)))
+      .or_default()
+      .push_str(&line);
+  }
+
+  Ok(YoloDataset { classes, labels })
+}
+
+/// Упаковать датасет YOLO в zip-архив: `classes.txt` и `labels/<кадр>.txt`
+pub fn write_yolo_archive(dataset: &YoloDataset, output_path: &Path) -> Result<()> {
+  if let Some(parent) = output_path.parent() {
+    std::fs::create_dir_all(parent)?;
+  }
+  let file = std::fs::File::create(output_path)
+    .with_context(|| format!("Cannot create {}", output_path.display()))?;
+
+  let mut archive = zip::ZipWriter::new(file);
+  let options =
+    zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+  archive.start_file("classes.txt", options)?;
+  for class in &dataset.classes {
+    writeln!(archive, "{class}")?;
+  }
+  for (name, content) in &dataset.labels {
+    archive.start_file(format!("labels/{name}"), options)?;
+    archive.write_all(content.as_bytes())?;
+  }
+  archive.finish()?;
+
+  Ok(())
+}
+
+/// Экспортировать датасет в файл: COCO - JSON, YOLO - zip-архив
+pub fn export_dataset(
+  results: &RecognitionResults,
+  format: DatasetFormat,
+  output_path: &Path,
+  options: &DatasetExportOptions,
+) -> Result<()> {
+  match format {
+    DatasetFormat::Coco => {
+      let dataset = build_coco_dataset(results, options)?;
+      if let Some(parent) = output_path.parent() {
+        std::fs::create_dir_all(parent)?;
+      }
+      std::fs::write(output_path, serde_json::to_string_pretty(&dataset)?)
+        .with_context(|| format!("Cannot write {}", output_path.display()))
+    }
+    DatasetFormat::Yolo => write_yolo_archive(&build_yolo_dataset(results, options)?, output_path),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::collections::HashSet;
+  use std::io::Read;
+
+  const SYNTHETIC_RESULTS: &str = r#"{
+    "objects": [
+      {
+        "class": "person",
+        "confidence": 0.87,
+        "timestamps": [0.0, 1.0, 2.0],
+        "bounding_boxes": [
+          { "x": 10.0, "y": 40.0, "width": 100.0, "height": 200.0 },
+          { "x": 600.0, "y": 300.0, "width": 100.0, "height": 100.0 },
+          { "x": -20.0, "y": 10.0, "width": 60.0, "height": 50.0 }
+        ]
+      },
+      {
+        "class": "car",
+        "confidence": 0.3,
+        "timestamps": [1.0],
+        "bounding_boxes": [{ "x": 0.0, "y": 0.0, "width": 64.0, "height": 48.0 }]
+      }
+    ],
+    "faces": [
+      {
+        "face_id": "face_0",
+        "person_name": null,
+        "confidence": 0.95,
+        "timestamps": [2.0],
+        "bounding_boxes": [{ "x": 30.0, "y": 10.0, "width": 40.0, "height": 40.0 }]
+      }
+    ],
+    "scenes": [],
+    "processed_at": "2024-01-01T00:00:00Z",
+    "frames": [
+      { "index": 0, "timestamp": 0.0, "width": 640, "height": 480 },
+      { "index": 1, "timestamp": 1.0, "width": 640, "height": 480 },
+      { "index": 2, "timestamp": 2.0, "width": 640, "height": 480 },
+      { "index": 3, "timestamp": 3.0, "width": 640, "height": 480 }
+    ]
+  }"#;
+
+  fn synthetic_results() -> RecognitionResults {
+    serde_json::from_str(SYNTHETIC_RESULTS).unwrap()
+  }
+
+  /// Проверить внутреннюю согласованность датасета COCO
+  fn assert_coco_consistent(dataset: &CocoDataset) {
+    let images: HashMap<u64, &CocoImage> = dataset.images.iter().map(|i| (i.id, i)).collect();
+    let categories: HashSet<u64> = dataset.categories.iter().map(|c| c.id).collect();
+    let annotation_ids: HashSet<u64> = dataset.annotations.iter().map(|a| a.id).collect();
+    assert_eq!(images.len(), dataset.images.len(), "duplicate image ids");
+    assert_eq!(
+      categories.len(),
+      dataset.categories.len(),
+      "duplicate category ids"
+    );
+    assert_eq!(
+      annotation_ids.len(),
+      dataset.annotations.len(),
+      "duplicate annotation ids"
+    );
+
+    for annotation in &dataset.annotations {
+      let image = images[&annotation.image_id];
+      assert!(categories.contains(&annotation.category_id));
+      let [x, y, width, height] = annotation.bbox;
+      assert!(x >= 0.0 && y >= 0.0 && width > 0.0 && height > 0.0);
+      assert!(x + width <= image.width as f32 && y + height <= image.height as f32);
+      assert_eq!(annotation.area, width * height);
+    }
+  }
+
+  #[test]
+  fn test_coco_round_trip_is_consistent() {
+    let options = DatasetExportOptions {
+      include_faces: true,
+      ..Default::default()
+    };
+    let dataset = build_coco_dataset(&synthetic_results(), &options).unwrap();
+
+    let json = serde_json::to_string(&dataset).unwrap();
+    let parsed: CocoDataset = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, dataset);
+    assert_coco_consistent(&parsed);
+
+    assert_eq!(parsed.images.len(), 4);
+    assert_eq!(parsed.annotations.len(), 5);
+    let names: Vec<&str> = parsed.categories.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, ["car", "face", "person"]);
+
+    // Рамки, выходящие за кадр, обрезаются по его границам
+    let person = parsed.categories[2].id;
+    let clipped: Vec<[f32; 4]> = parsed
+      .annotations
+      .iter()
+      .filter(|a| a.category_id == person && a.image_id != parsed.images[0].id)
+      .map(|a| a.bbox)
+      .collect();
+    assert_eq!(
+      clipped,
+      [[600.0, 300.0, 40.0, 100.0], [0.0, 10.0, 40.0, 50.0]]
+    );
+  }
+
+  #[test]
+  fn test_filters_by_class_confidence_and_time() {
+    let results = synthetic_results();
+
+    let by_confidence = DatasetExportOptions {
+      min_confidence: 0.5,
+      ..Default::default()
+    };
+    let dataset = build_coco_dataset(&results, &by_confidence).unwrap();
+    assert_eq!(dataset.categories.len(), 1);
+    assert_eq!(dataset.categories[0].name, "person");
+    assert_eq!(dataset.annotations.len(), 3);
+
+    let by_class_and_time = DatasetExportOptions {
+      classes: Some(vec!["car".to_string(), "person".to_string()]),
+      start_time: Some(0.5),
+      end_time: Some(1.5),
+      ..Default::default()
+    };
+    let dataset = build_coco_dataset(&results, &by_class_and_time).unwrap();
+    assert_coco_consistent(&dataset);
+    assert_eq!(dataset.images.len(), 1);
+    assert_eq!(dataset.images[0].timestamp, 1.0);
+    assert_eq!(dataset.annotations.len(), 2);
+  }
+
+  #[test]
+  fn test_yolo_archive_contents() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("export").join("dataset.zip");
+    let options = DatasetExportOptions {
+      classes: Some(vec!["car".to_string(), "person".to_string()]),
+      ..Default::default()
+    };
+
+    export_dataset(&synthetic_results(), DatasetFormat::Yolo, &path, &options).unwrap();
+
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+    let mut read = |name: &str| {
+      let mut content = String::new();
+      archive
+        .by_name(name)
+        .unwrap()
+        .read_to_string(&mut content)
+        .unwrap();
+      content
+    };
+
+    assert_eq!(read("classes.txt"), "car\nperson\n");
+    assert_eq!(
+      read("labels/frame_000000.txt"),
+      "1 0.093750 0.291667 0.156250 0.416667\n"
+    );
+    assert_eq!(
+      read("labels/frame_000001.txt"),
+      "1 0.968750 0.729167 0.062500 0.208333\n0 0.050000 0.050000 0.100000 0.100000\n"
+    );
+    // Кадр без детекций остается в датасете как негативный пример
+    assert_eq!(read("labels/frame_000003.txt"), "");
+  }
+
+  #[test]
+  fn test_results_without_frames_are_rejected() {
+    let mut results = synthetic_results();
+    results.frames.clear();
+    assert!(build_coco_dataset(&results, &DatasetExportOptions::default()).is_err());
+    assert_eq!(DatasetFormat::parse("yolo"), Some(DatasetFormat::Yolo));
+    assert_eq!(DatasetFormat::parse("voc"), None);
+  }
+}
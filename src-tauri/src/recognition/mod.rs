@@ -1,5 +1,9 @@
 pub mod commands;
+pub mod dataset_export;
+pub mod overlay;
 pub mod recognition_service;
+pub mod results_comparison;
+pub mod session_pool;
 // pub mod registry; // Not used - commands are registered in app_builder.rs
 pub mod types;
 pub mod yolo_processor;
@@ -1,11 +1,14 @@
 //! Model Manager - Управление YOLO моделями
 
 use anyhow::{anyhow, Result};
-use ort::session::{builder::GraphOptimizationLevel, Session};
+use ort::session::Session;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::sync::{Mutex, Once};
 
+use super::session_pool::{build_session, SessionKey, SessionPool};
+use super::yolo_processor::ExecutionProvider;
+
 // Инициализация ORT
 static INIT: Once = Once::new();
 static INIT_RESULT: Mutex<Option<bool>> = Mutex::new(None);
@@ -101,7 +104,8 @@ impl YoloModel {
 
 /// Менеджер моделей
 pub struct ModelManager {
-  session: Option<Session>,
+  /// Ключ загруженной сессии в общем пуле
+  session_key: Option<SessionKey>,
   model_type: YoloModel,
 }
 
@@ -110,7 +114,7 @@ impl ModelManager {
   pub fn new(model_type: YoloModel) -> Result<Self> {
     init_ort()?;
     Ok(Self {
-      session: None,
+      session_key: None,
       model_type,
     })
   }
@@ -126,30 +130,27 @@ impl ModelManager {
       ));
     }
 
-    // Создаем сессию ONNX Runtime
-    let session = Session::builder()?
-      .with_optimization_level(GraphOptimizationLevel::Level3)?
-      .with_intra_threads(4)?
-      .commit_from_file(&model_path)?;
-
-    self.session = Some(session);
+    // Сессия берется из общего пула сессий распознавания
+    let key = SessionKey::new(model_path, ExecutionProvider::Cpu);
+    SessionPool::shared().get_or_load(&key, || build_session(&key))?;
+    self.session_key = Some(key);
     Ok(())
   }
 
-  /// Получить сессию
-  pub fn get_session(&self) -> Result<&Session> {
+  /// Получить ключ сессии в пуле
+  pub fn get_session_key(&self) -> Result<&SessionKey> {
     self
-      .session
+      .session_key
       .as_ref()
       .ok_or_else(|| anyhow!("Model not loaded. Call load_model() first."))
   }
 
-  /// Получить изменяемую сессию
-  pub fn get_session_mut(&mut self) -> Result<&mut Session> {
-    self
-      .session
-      .as_mut()
-      .ok_or_else(|| anyhow!("Model not loaded. Call load_model() first."))
+  /// Выполнить инференс на сессии модели с учетом предела одновременных инференсов
+  pub async fn run<T>(&self, infer: impl FnOnce(&mut Session) -> Result<T>) -> Result<T> {
+    let key = self.get_session_key()?;
+    SessionPool::shared()
+      .run(key, || build_session(key), infer)
+      .await
   }
 
   /// Получить тип модели
@@ -159,7 +160,7 @@ impl ModelManager {
 
   /// Проверить, загружена ли модель
   pub fn is_loaded(&self) -> bool {
-    self.session.is_some()
+    self.session_key.is_some()
   }
 }
 
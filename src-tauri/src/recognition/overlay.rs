@@ -0,0 +1,441 @@
+//! Экспорт превью с наложением результатов распознавания
+//!
+//! Детекции из сохраненных `RecognitionResults` превращаются в фильтры
+//! `drawbox`/`drawtext`, активные во временном окне детекции. Граф фильтров
+//! передается FFmpeg через `-filter_script:v`, поэтому длина командной строки
+//! не ограничивает количество рамок; очень большие графы разбиваются на
+//! несколько проходов.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+use super::types::{BoundingBox, RecognitionResults};
+use crate::media::metadata::get_media_metadata;
+use crate::video_compiler::ffmpeg_executor::{FFmpegExecutionContext, FFmpegExecutor};
+use crate::video_compiler::progress::ProgressUpdate;
+
+/// Максимальное количество рамок в одном проходе FFmpeg
+pub const MAX_BOXES_PER_PASS: usize = 500;
+
+/// Палитра цветов рамок для классов объектов
+const CLASS_PALETTE: [&str; 10] = [
+  "0xE6194B", "0x3CB44B", "0xFFE119", "0x4363D8", "0xF58231", "0x911EB4", "0x46F0F0", "0xF032E6",
+  "0xBCF60C", "0xFABEBE",
+];
+
+/// Цвет рамок лиц
+const FACE_COLOR: &str = "0x00FFFF";
+
+/// Параметры экспорта оверлея
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OverlayOptions {
+  /// Классы объектов для отрисовки (`None` - все классы)
+  pub classes: Option<Vec<String>>,
+  /// Минимальная уверенность детекции
+  pub min_confidence: f32,
+  /// Рисовать рамки лиц
+  pub include_faces: bool,
+  /// Сколько секунд рамка остается на экране после метки детекции
+  pub display_duration: f64,
+  /// Битрейт итогового превью в кбит/с
+  pub video_bitrate_kbps: u32,
+}
+
+impl Default for OverlayOptions {
+  fn default() -> Self {
+    Self {
+      classes: None,
+      min_confidence: 0.0,
+      include_faces: true,
+      // Кадры для распознавания извлекаются раз в секунду
+      display_duration: 1.0,
+      video_bitrate_kbps: 1000,
+    }
+  }
+}
+
+/// Одна рамка оверлея с временным окном
+#[derive(Debug, Clone, PartialEq)]
+pub struct OverlayBox {
+  pub start: f64,
+  pub end: f64,
+  pub x: i64,
+  pub y: i64,
+  pub width: i64,
+  pub height: i64,
+  pub color: &'static str,
+  pub label: String,
+}
+
+/// Цвет рамки для класса объекта (стабилен между запусками)
+pub fn class_color(class: &str) -> &'static str {
+  let hash = class.bytes().fold(0usize, |acc, byte| {
+    acc.wrapping_mul(31).wrapping_add(byte as usize)
+  });
+  CLASS_PALETTE[hash % CLASS_PALETTE.len()]
+}
+
+/// Оставить в подписи только символы, безопасные для drawtext
+fn sanitize_label(label: &str) -> String {
+  label
+    .chars()
+    .filter(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_' | '.'))
+    .collect()
+}
+
+fn overlay_box(
+  timestamp: f64,
+  bbox: &BoundingBox,
+  color: &'static str,
+  label: String,
+  options: &OverlayOptions,
+) -> OverlayBox {
+  OverlayBox {
+    start: timestamp,
+    end: timestamp + options.display_duration,
+    x: bbox.x.max(0.0).round() as i64,
+    y: bbox.y.max(0.0).round() as i64,
+    width: bbox.width.max(1.0).round() as i64,
+    height: bbox.height.max(1.0).round() as i64,
+    color,
+    label,
+  }
+}
+
+/// Преобразовать результаты распознавания в рамки оверлея
+pub fn collect_overlay_boxes(
+  results: &RecognitionResults,
+  options: &OverlayOptions,
+) -> Vec<OverlayBox> {
+  let mut boxes = Vec::new();
+
+  for object in &results.objects {
+    if object.confidence < options.min_confidence {
+      continue;
+    }
+    if let Some(classes) = &options.classes {
+      if !classes.iter().any(|class| class == &object.class) {
+        continue;
+      }
+    }
+
+    let color = class_color(&object.class);
+    let label = sanitize_label(&format!("{} {:.2}", object.class, object.confidence));
+    for (timestamp, bbox) in object.timestamps.iter().zip(&object.bounding_boxes) {
+      boxes.push(overlay_box(*timestamp, bbox, color, label.clone(), options));
+    }
+  }
+
+  if options.include_faces {
+    for face in &results.faces {
+      if face.confidence < options.min_confidence {
+        continue;
+      }
+
+      let name = face.person_name.as_deref().unwrap_or("face");
+      let label = sanitize_label(&format!("{name} {:.2}", face.confidence));
+      for (timestamp, bbox) in face.timestamps.iter().zip(&face.bounding_boxes) {
+        boxes.push(overlay_box(
+          *timestamp,
+          bbox,
+          FACE_COLOR,
+          label.clone(),
+          options,
+        ));
+      }
+    }
+  }
+
+  boxes.sort_by(|a, b| a.start.total_cmp(&b.start));
+  boxes
+}
+
+/// Время окончания последней рамки
+pub fn overlay_end_time(boxes: &[OverlayBox]) -> f64 {
+  boxes.iter().map(|b| b.end).fold(0.0, f64::max)
+}
+
+/// Построить содержимое filter_script для набора рамок
+pub fn build_filter_script(boxes: &[OverlayBox]) -> String {
+  if boxes.is_empty() {
+    return "null".to_string();
+  }
+
+  boxes
+    .iter()
+    .flat_map(|b| {
+      let enable = format!("enable='between(t,{:.3},{:.3})'", b.start, b.end);
+      [
+        format!(
+          "drawbox=x={}:y={}:w={}:h={}:color={}@0.9:t=3:{enable}",
+          b.x, b.y, b.width, b.height, b.color
+        ),
+        format!(
+          "drawtext=text='{}':x={}:y={}:fontsize=18:fontcolor=white:box=1:boxcolor={}@0.6:{enable}",
+          b.label,
+          b.x,
+          (b.y - 22).max(0),
+          b.color
+        ),
+      ]
+    })
+    .collect::<Vec<_>>()
+    .join(",\n")
+}
+
+/// Аргументы одного прохода FFmpeg.
+///
+/// Промежуточные проходы кодируются почти без потерь, последний - в превью
+/// с низким битрейтом.
+pub fn overlay_pass_args(
+  input: &Path,
+  filter_script: &Path,
+  output: &Path,
+  final_pass: bool,
+  video_bitrate_kbps: u32,
+) -> Vec<String> {
+  let mut args = vec![
+    "-y".to_string(),
+    "-i".to_string(),
+    input.to_string_lossy().to_string(),
+    "-filter_script:v".to_string(),
+    filter_script.to_string_lossy().to_string(),
+    "-c:v".to_string(),
+    "libx264".to_string(),
+    "-preset".to_string(),
+    "veryfast".to_string(),
+  ];
+
+  if final_pass {
+    let bitrate = format!("{video_bitrate_kbps}k");
+    args.extend([
+      "-b:v".to_string(),
+      bitrate.clone(),
+      "-maxrate".to_string(),
+      bitrate,
+      "-bufsize".to_string(),
+      format!("{}k", video_bitrate_kbps * 2),
+      "-pix_fmt".to_string(),
+      "yuv420p".to_string(),
+      "-c:a".to_string(),
+      "aac".to_string(),
+      "-b:a".to_string(),
+      "96k".to_string(),
+      "-movflags".to_string(),
+      "+faststart".to_string(),
+    ]);
+  } else {
+    args.extend([
+      "-crf".to_string(),
+      "12".to_string(),
+      "-c:a".to_string(),
+      "copy".to_string(),
+    ]);
+  }
+
+  args.push(output.to_string_lossy().to_string());
+  args
+}
+
+/// Отрисовать рамки поверх видео и закодировать превью MP4.
+///
+/// `on_progress` получает общий прогресс всех проходов (0-100). Отмена
+/// `cancel` завершает текущий процесс FFmpeg.
+pub async fn render_overlay(
+  ffmpeg_path: &str,
+  source: &Path,
+  output: &Path,
+  boxes: &[OverlayBox],
+  options: &OverlayOptions,
+  cancel: &CancellationToken,
+  on_progress: impl Fn(f32),
+) -> Result<PathBuf> {
+  if !source.exists() {
+    bail!("Source video not found: {}", source.display());
+  }
+
+  // Длительность нужна только для процента прогресса
+  let source_path = source.to_string_lossy().to_string();
+  let total_duration = tokio::task::spawn_blocking(move || get_media_metadata(source_path))
+    .await
+    .ok()
+    .and_then(|metadata| metadata.ok())
+    .and_then(|metadata| metadata.duration)
+    .unwrap_or_else(|| overlay_end_time(boxes));
+
+  let work_dir = tempfile::tempdir()?;
+  let chunks: Vec<&[OverlayBox]> = if boxes.is_empty() {
+    vec![boxes]
+  } else {
+    boxes.chunks(MAX_BOXES_PER_PASS).collect()
+  };
+  let passes = chunks.len();
+  let mut input = source.to_path_buf();
+
+  for (pass, chunk) in chunks.into_iter().enumerate() {
+    let final_pass = pass + 1 == passes;
+    let script_path = work_dir.path().join(format!("overlay_{pass}.txt"));
+    tokio::fs::write(&script_path, build_filter_script(chunk)).await?;
+
+    let pass_output = if final_pass {
+      output.to_path_buf()
+    } else {
+      work_dir.path().join(format!("overlay_{pass}.mp4"))
+    };
+
+    let mut command = Command::new(ffmpeg_path);
+    command
+      .args(overlay_pass_args(
+        &input,
+        &script_path,
+        &pass_output,
+        final_pass,
+        options.video_bitrate_kbps,
+      ))
+      .kill_on_drop(true);
+
+    let (progress_tx, mut progress_rx) = mpsc::channel(16);
+    let executor = FFmpegExecutor::with_progress_and_context(
+      progress_tx,
+      FFmpegExecutionContext {
+        job_id: "recognition_overlay".to_string(),
+        total_duration: total_duration.max(0.1),
+      },
+    );
+    let run = executor.execute(command);
+    tokio::pin!(run);
+
+    loop {
+      tokio::select! {
+        result = &mut run => {
+          result?;
+          break;
+        }
+        Some(ProgressUpdate::ProgressChanged { progress, .. }) = progress_rx.recv() => {
+          on_progress((pass as f32 + progress.percentage / 100.0) / passes as f32 * 100.0);
+        }
+        _ = cancel.cancelled() => bail!("Recognition overlay export cancelled"),
+      }
+    }
+
+    input = pass_output;
+  }
+
+  on_progress(100.0);
+  Ok(output.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const SYNTHETIC_RESULTS: &str = r#"{
+    "objects": [
+      {
+        "class": "person",
+        "confidence": 0.87,
+        "timestamps": [1.0, 2.0],
+        "bounding_boxes": [
+          { "x": 10.0, "y": 40.0, "width": 100.0, "height": 200.0 },
+          { "x": 12.4, "y": 41.6, "width": 100.0, "height": 200.0 }
+        ]
+      },
+      {
+        "class": "car",
+        "confidence": 0.3,
+        "timestamps": [0.0],
+        "bounding_boxes": [{ "x": 0.0, "y": 0.0, "width": 50.0, "height": 20.0 }]
+      }
+    ],
+    "faces": [
+      {
+        "face_id": "face_0",
+        "person_name": null,
+        "confidence": 0.95,
+        "timestamps": [1.5],
+        "bounding_boxes": [{ "x": 30.0, "y": 10.0, "width": 40.0, "height": 40.0 }]
+      }
+    ],
+    "scenes": [],
+    "processed_at": "2024-01-01T00:00:00Z"
+  }"#;
+
+  async fn load_synthetic_results() -> RecognitionResults {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("synthetic_recognition.json");
+    tokio::fs::write(&path, SYNTHETIC_RESULTS).await.unwrap();
+    let json = tokio::fs::read_to_string(&path).await.unwrap();
+    serde_json::from_str(&json).unwrap()
+  }
+
+  #[tokio::test]
+  async fn test_filter_script_for_synthetic_results() {
+    let results = load_synthetic_results().await;
+    let options = OverlayOptions {
+      min_confidence: 0.5,
+      ..Default::default()
+    };
+
+    let boxes = collect_overlay_boxes(&results, &options);
+    let person = class_color("person");
+    let expected = [
+      format!("drawbox=x=10:y=40:w=100:h=200:color={person}@0.9:t=3:enable='between(t,1.000,2.000)'"),
+      format!("drawtext=text='person 0.87':x=10:y=18:fontsize=18:fontcolor=white:box=1:boxcolor={person}@0.6:enable='between(t,1.000,2.000)'"),
+      "drawbox=x=30:y=10:w=40:h=40:color=0x00FFFF@0.9:t=3:enable='between(t,1.500,2.500)'".to_string(),
+      "drawtext=text='face 0.95':x=30:y=0:fontsize=18:fontcolor=white:box=1:boxcolor=0x00FFFF@0.6:enable='between(t,1.500,2.500)'".to_string(),
+      format!("drawbox=x=12:y=42:w=100:h=200:color={person}@0.9:t=3:enable='between(t,2.000,3.000)'"),
+      format!("drawtext=text='person 0.87':x=12:y=20:fontsize=18:fontcolor=white:box=1:boxcolor={person}@0.6:enable='between(t,2.000,3.000)'"),
+    ]
+    .join(",\n");
+
+    assert_eq!(build_filter_script(&boxes), expected);
+  }
+
+  #[tokio::test]
+  async fn test_collect_boxes_respects_class_and_face_filters() {
+    let results = load_synthetic_results().await;
+    let options = OverlayOptions {
+      classes: Some(vec!["car".to_string()]),
+      include_faces: false,
+      ..Default::default()
+    };
+
+    let boxes = collect_overlay_boxes(&results, &options);
+    assert_eq!(boxes.len(), 1);
+    assert_eq!(boxes[0].label, "car 0.30");
+    assert_eq!(boxes[0].color, class_color("car"));
+    assert_eq!(overlay_end_time(&boxes), 1.0);
+  }
+
+  #[test]
+  fn test_empty_script_passes_video_through() {
+    assert_eq!(build_filter_script(&[]), "null");
+  }
+
+  #[test]
+  fn test_pass_args_use_filter_script() {
+    let args = overlay_pass_args(
+      Path::new("/in.mp4"),
+      Path::new("/tmp/overlay_0.txt"),
+      Path::new("/out.mp4"),
+      true,
+      800,
+    );
+
+    let script = args.iter().position(|a| a == "-filter_script:v").unwrap();
+    assert_eq!(args[script + 1], "/tmp/overlay_0.txt");
+    assert!(args.windows(2).any(|w| w[0] == "-b:v" && w[1] == "800k"));
+    assert_eq!(args.last().unwrap(), "/out.mp4");
+  }
+
+  #[test]
+  fn test_sanitize_label_strips_filter_syntax() {
+    assert_eq!(sanitize_label("it's:a,b 0.50"), "itsab 0.50");
+  }
+}
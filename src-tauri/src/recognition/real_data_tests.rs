@@ -420,6 +420,8 @@ async fn test_export_recognition_results() {
     faces: vec![],
     scenes: vec![],
     processed_at: chrono::Utc::now(),
+    config: None,
+    frames: Vec::new(),
   };
 
   // Сохраняем результаты через JSON
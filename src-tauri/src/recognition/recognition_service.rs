@@ -1,11 +1,21 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
 
-use super::types::{BoundingBox, DetectedFace, DetectedObject, DetectedScene, RecognitionResults};
+use super::result_aggregator::{build_recognition_timeline, TimelineBucket};
+use super::types::{
+  BoundingBox, DetectedFace, DetectedObject, DetectedScene, RecognitionConfig, RecognitionResults,
+  RecognizedFrame,
+};
 use super::yolo_processor::{Detection, YoloModel, YoloProcessor};
+use crate::security::privacy_mode::PrivacyMode;
+
+/// Ключ кэша шкалы: файл, размер интервала (биты f64) и отсортированные классы
+type TimelineCacheKey = (String, u64, Option<Vec<String>>);
 
 /// Сервис для распознавания объектов и лиц
 pub struct RecognitionService {
@@ -15,10 +25,16 @@ pub struct RecognitionService {
   /// YOLO процессор для лиц
   face_detector: Arc<RwLock<YoloProcessor>>,
 
+  /// Параметры распознавания по умолчанию
+  config: RwLock<RecognitionConfig>,
+
   // /// Менеджер данных превью
   // preview_manager: Arc<PreviewDataManager>,
   /// Директория для результатов
   results_dir: PathBuf,
+
+  /// Кэш шкал плотности обнаружений, сбрасывается при изменении результатов
+  timeline_cache: RwLock<HashMap<TimelineCacheKey, Vec<TimelineBucket>>>,
 }
 
 impl RecognitionService {
@@ -33,36 +49,139 @@ impl RecognitionService {
     Ok(Self {
       object_detector: Arc::new(RwLock::new(object_detector)),
       face_detector: Arc::new(RwLock::new(face_detector)),
+      config: RwLock::new(RecognitionConfig::default()),
       // preview_manager,
       results_dir,
+      timeline_cache: RwLock::new(HashMap::new()),
     })
   }
 
-  /// Обработать видео файл
+  /// Установить параметры распознавания по умолчанию
+  pub async fn set_config(&self, config: RecognitionConfig) -> Result<()> {
+    config.validate()?;
+    *self.config.write().await = config;
+    Ok(())
+  }
+
+  /// Получить параметры распознавания по умолчанию
+  pub async fn get_config(&self) -> RecognitionConfig {
+    self.config.read().await.clone()
+  }
+
+  /// Параметры для запуска: переопределение вызова или параметры по умолчанию
+  async fn resolve_config(&self, config: Option<RecognitionConfig>) -> Result<RecognitionConfig> {
+    match config {
+      Some(config) => {
+        config.validate()?;
+        Ok(config)
+      }
+      None => Ok(self.get_config().await),
+    }
+  }
+
+  /// Обработать видео файл и сохранить результаты.
+  ///
+  /// `config` переопределяет параметры по умолчанию только для этого вызова.
   pub async fn process_video(
     &self,
     file_id: &str,
     frame_paths: Vec<PathBuf>,
+    config: Option<RecognitionConfig>,
+  ) -> Result<RecognitionResults> {
+    let config = self.resolve_config(config).await?;
+    let results = self.analyze_video(frame_paths, &config).await?;
+
+    // Сохраняем результаты
+    self.save_results(file_id, &results).await?;
+
+    // Временно закомментировано - обновление данных превью
+    // let mut preview_data = self.preview_manager.get_preview_data(file_id).await.unwrap();
+    // preview_data.set_recognition_results(results.clone());
+
+    Ok(results)
+  }
+
+  /// Распознать кадры другой моделью объектов без сохранения результатов.
+  ///
+  /// Детектор лиц остается прежним, поэтому результаты можно сравнить с
+  /// сохраненными для оценки новой модели.
+  pub async fn analyze_with_object_model(
+    &self,
+    frame_paths: Vec<PathBuf>,
+    model: YoloModel,
+    config: Option<RecognitionConfig>,
+  ) -> Result<RecognitionResults> {
+    let config = self.resolve_config(config).await?;
+    let mut detector = YoloProcessor::new(model, config.confidence_threshold)?;
+    detector.load_model().await?;
+    self
+      .analyze_frames(frame_paths, &config, &RwLock::new(detector))
+      .await
+  }
+
+  /// Распознать объекты и лица на кадрах видео без сохранения результатов
+  async fn analyze_video(
+    &self,
+    frame_paths: Vec<PathBuf>,
+    config: &RecognitionConfig,
+  ) -> Result<RecognitionResults> {
+    self
+      .analyze_frames(frame_paths, config, &self.object_detector)
+      .await
+  }
+
+  /// Распознать кадры заданным детектором объектов и детектором лиц сервиса
+  async fn analyze_frames(
+    &self,
+    frame_paths: Vec<PathBuf>,
+    config: &RecognitionConfig,
+    object_detector: &RwLock<YoloProcessor>,
   ) -> Result<RecognitionResults> {
-    // Используем переданные пути к кадрам
+    // Отсутствующий кадр - ошибка всего файла, а не паника детектора
+    if let Some(missing) = frame_paths.iter().find(|path| !path.exists()) {
+      anyhow::bail!("Frame not found: {}", missing.display());
+    }
 
     // Обрабатываем каждый кадр
     let mut all_objects: Vec<(f64, Detection)> = Vec::new();
     let mut all_faces: Vec<(f64, Detection)> = Vec::new();
-
-    for (idx, frame_path) in frame_paths.iter().enumerate() {
-      // Вычисляем примерную временную метку
-      let timestamp = idx as f64 * 1.0; // Простая метка времени
+    let mut frames = Vec::with_capacity(frame_paths.len());
+
+    for (chunk_idx, chunk) in frame_paths.chunks(config.batch_size).enumerate() {
+      // Вычисляем примерные временные метки
+      let first_idx = chunk_idx * config.batch_size;
+      let timestamp = |offset: usize| (first_idx + offset) as f64 * 1.0; // Простая метка времени
+
+      // Размеры кадров нужны для экспорта детекций в датасеты
+      for (offset, path) in chunk.iter().enumerate() {
+        match image::image_dimensions(path) {
+          Ok((width, height)) => frames.push(RecognizedFrame {
+            index: first_idx + offset,
+            timestamp: timestamp(offset),
+            width,
+            height,
+          }),
+          Err(e) => log::warn!("Не удалось прочитать размер кадра {}: {e}", path.display()),
+        }
+      }
 
       // Обнаружение объектов
-      let mut object_detector = self.object_detector.write().await;
-      let objects = object_detector.process_image(frame_path).await?;
-      all_objects.extend(objects.into_iter().map(|d| (timestamp, d)));
+      let mut object_detector = object_detector.write().await;
+      object_detector.apply_config(config);
+      let objects = object_detector.process_batch(chunk.to_vec()).await?;
+      drop(object_detector);
+      for (offset, detections) in objects.into_iter().enumerate() {
+        all_objects.extend(detections.into_iter().map(|d| (timestamp(offset), d)));
+      }
 
       // Обнаружение лиц
       let mut face_detector = self.face_detector.write().await;
-      let faces = face_detector.process_image(frame_path).await?;
-      all_faces.extend(faces.into_iter().map(|d| (timestamp, d)));
+      face_detector.apply_config(config);
+      let faces = face_detector.process_batch(chunk.to_vec()).await?;
+      drop(face_detector);
+      for (offset, detections) in faces.into_iter().enumerate() {
+        all_faces.extend(detections.into_iter().map(|d| (timestamp(offset), d)));
+      }
     }
 
     // Группируем результаты
@@ -75,15 +194,10 @@ impl RecognitionService {
       faces: grouped_faces,
       scenes,
       processed_at: chrono::Utc::now(),
+      config: Some(config.clone()),
+      frames,
     };
 
-    // Сохраняем результаты
-    self.save_results(file_id, &results).await?;
-
-    // Временно закомментировано - обновление данных превью
-    // let mut preview_data = self.preview_manager.get_preview_data(file_id).await.unwrap();
-    // preview_data.set_recognition_results(results.clone());
-
     Ok(results)
   }
 
@@ -229,45 +343,153 @@ impl RecognitionService {
   async fn save_results(&self, file_id: &str, results: &RecognitionResults) -> Result<()> {
     let results_file = self.results_dir.join(format!("{file_id}_recognition.json"));
     let json = serde_json::to_string_pretty(results)?;
-    tokio::fs::write(results_file, json).await?;
+    PrivacyMode::global()
+      .write(&results_file, json.as_bytes())
+      .await?;
+    self.invalidate_timeline(file_id).await;
     Ok(())
   }
 
+  /// Удалить сохраненные результаты файла
+  pub async fn clear_results(&self, file_id: &str) -> Result<()> {
+    let results_file = self.results_dir.join(format!("{file_id}_recognition.json"));
+    match tokio::fs::remove_file(results_file).await {
+      Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+      _ => {}
+    }
+    self.invalidate_timeline(file_id).await;
+    Ok(())
+  }
+
+  /// Шкала плотности обнаружений файла (см. [`build_recognition_timeline`]).
+  ///
+  /// Результат кэшируется до повторного распознавания или очистки
+  /// результатов. Без сохраненных результатов шкала пуста.
+  pub async fn recognition_timeline(
+    &self,
+    file_id: &str,
+    bucket_secs: f64,
+    classes: Option<Vec<String>>,
+  ) -> Result<Vec<TimelineBucket>> {
+    let classes = classes.map(|mut classes| {
+      classes.sort();
+      classes.dedup();
+      classes
+    });
+    let key = (file_id.to_string(), bucket_secs.to_bits(), classes);
+
+    if let Some(buckets) = self.timeline_cache.read().await.get(&key) {
+      return Ok(buckets.clone());
+    }
+
+    let Some(results) = self.load_results(file_id).await? else {
+      return Ok(Vec::new());
+    };
+    let buckets = build_recognition_timeline(&results, bucket_secs, key.2.as_deref())?;
+    self
+      .timeline_cache
+      .write()
+      .await
+      .insert(key, buckets.clone());
+    Ok(buckets)
+  }
+
+  /// Сбросить кэшированные шкалы файла
+  async fn invalidate_timeline(&self, file_id: &str) {
+    self
+      .timeline_cache
+      .write()
+      .await
+      .retain(|(cached_file, _, _), _| cached_file != file_id);
+  }
+
   /// Загрузить результаты из файла
   pub async fn load_results(&self, file_id: &str) -> Result<Option<RecognitionResults>> {
     let results_file = self.results_dir.join(format!("{file_id}_recognition.json"));
 
     if results_file.exists() {
-      let json = tokio::fs::read_to_string(results_file).await?;
-      let results: RecognitionResults = serde_json::from_str(&json)?;
+      let json = PrivacyMode::global().read(&results_file).await?;
+      let results: RecognitionResults = serde_json::from_slice(&json)?;
       Ok(Some(results))
     } else {
       Ok(None)
     }
   }
 
-  /// Обработать пакет видео
+  /// Обработать пакет видео.
+  ///
+  /// Ошибка одного файла не прерывает пакет: результат возвращается
+  /// для каждого файла в порядке `file_ids`.
   pub async fn process_batch(
     &self,
     file_ids: Vec<String>,
     frame_paths_map: std::collections::HashMap<String, Vec<PathBuf>>,
-  ) -> Result<Vec<(String, RecognitionResults)>> {
-    let mut results = Vec::new();
+    options: BatchProcessingOptions,
+  ) -> Vec<BatchItemResult> {
+    self
+      .process_batch_with_progress(file_ids, frame_paths_map, options, |_, _, _| {})
+      .await
+  }
+
+  /// Обработать пакет видео, вызывая `on_item` после каждого файла
+  /// с его результатом, числом обработанных файлов и размером пакета
+  pub async fn process_batch_with_progress<F>(
+    &self,
+    file_ids: Vec<String>,
+    frame_paths_map: std::collections::HashMap<String, Vec<PathBuf>>,
+    options: BatchProcessingOptions,
+    mut on_item: F,
+  ) -> Vec<BatchItemResult>
+  where
+    F: FnMut(&BatchItemResult, usize, usize),
+  {
+    let total = file_ids.len();
+    let mut items = Vec::with_capacity(total);
+    let config = self.get_config().await;
 
     for file_id in file_ids {
-      if let Some(frame_paths) = frame_paths_map.get(&file_id) {
-        match self.process_video(&file_id, frame_paths.clone()).await {
-          Ok(recognition_results) => {
-            results.push((file_id, recognition_results));
-          }
-          Err(e) => {
-            eprintln!("Failed to process file {file_id}: {e}");
+      if options
+        .cancel
+        .as_ref()
+        .is_some_and(|token| token.is_cancelled())
+      {
+        log::info!(
+          "Пакетное распознавание отменено после {} из {total} файлов",
+          items.len()
+        );
+        break;
+      }
+      let started = std::time::Instant::now();
+
+      let result = match frame_paths_map.get(&file_id) {
+        Some(frame_paths) => self
+          .analyze_video(frame_paths.clone(), &config)
+          .await
+          .map_err(|e| e.to_string()),
+        None => Err(format!("No frame paths provided for file {file_id}")),
+      };
+
+      match &result {
+        // Сохраняем сразу, чтобы сбой пакета терял не больше одного файла
+        Ok(results) if options.persist_results => {
+          if let Err(e) = self.save_results(&file_id, results).await {
+            log::warn!("Failed to save recognition results for {file_id}: {e}");
           }
         }
+        Ok(_) => {}
+        Err(e) => log::warn!("Failed to process file {file_id}: {e}"),
       }
+
+      let item = BatchItemResult {
+        file_id,
+        result,
+        duration_ms: started.elapsed().as_millis() as u64,
+      };
+      on_item(&item, items.len() + 1, total);
+      items.push(item);
     }
 
-    Ok(results)
+    items
   }
 
   /// Получить доступ к детектору объектов
@@ -317,6 +539,36 @@ impl RecognitionService {
   }
 }
 
+/// Параметры пакетной обработки
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchProcessingOptions {
+  /// Сохранять результаты каждого файла сразу после его обработки
+  pub persist_results: bool,
+  /// Токен отмены: оставшиеся файлы пакета не обрабатываются
+  #[serde(skip)]
+  pub cancel: Option<CancellationToken>,
+}
+
+impl Default for BatchProcessingOptions {
+  fn default() -> Self {
+    Self {
+      persist_results: true,
+      cancel: None,
+    }
+  }
+}
+
+/// Результат обработки одного файла в пакете
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchItemResult {
+  /// ID файла
+  pub file_id: String,
+  /// Результаты распознавания или текст ошибки
+  pub result: std::result::Result<RecognitionResults, String>,
+  /// Время обработки файла в миллисекундах
+  pub duration_ms: u64,
+}
+
 /// События распознавания для отправки на фронтенд
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", content = "data")]
@@ -340,12 +592,21 @@ pub enum RecognitionEvent {
 
   /// Ошибка обработки
   ProcessingError { file_id: String, error: String },
+
+  /// Файл пакета обработан (успешно или с ошибкой)
+  BatchItemCompleted {
+    file_id: String,
+    error: Option<String>,
+    duration_ms: u64,
+    completed: usize,
+    total: usize,
+  },
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::recognition::yolo_processor::{BoundingBox as YoloBBox, Detection};
+  use crate::recognition::yolo_processor::{BoundingBox as YoloBBox, Detection, ExecutionProvider};
   use std::collections::HashMap;
   use tempfile::TempDir;
 
@@ -536,6 +797,8 @@ mod tests {
       faces: vec![],
       scenes: vec![],
       processed_at: chrono::Utc::now(),
+      config: None,
+      frames: Vec::new(),
     };
 
     let file_id = "test_file";
@@ -573,10 +836,174 @@ mod tests {
 
     let file_ids = vec!["file1".to_string(), "file2".to_string()];
 
-    let results = service.process_batch(file_ids, frame_paths_map).await;
-    assert!(results.is_ok());
-    // Note: This will fail in actual processing due to empty frame paths,
-    // but we're testing the batch processing logic
+    let results = service
+      .process_batch(file_ids, frame_paths_map, BatchProcessingOptions::default())
+      .await;
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|item| item.result.is_ok()));
+  }
+
+  #[tokio::test]
+  async fn test_process_batch_continues_past_failures() {
+    let temp_dir = TempDir::new().unwrap();
+    let service = RecognitionService::new(temp_dir.path().to_path_buf()).unwrap();
+
+    let mut frame_paths_map = HashMap::new();
+    frame_paths_map.insert("first".to_string(), vec![]);
+    frame_paths_map.insert(
+      "broken".to_string(),
+      vec![PathBuf::from("/nonexistent/frame_0001.jpg")],
+    );
+    frame_paths_map.insert("last".to_string(), vec![]);
+    let file_ids = vec![
+      "first".to_string(),
+      "broken".to_string(),
+      "last".to_string(),
+    ];
+
+    let mut progress = Vec::new();
+    let results = service
+      .process_batch_with_progress(
+        file_ids,
+        frame_paths_map,
+        BatchProcessingOptions::default(),
+        |item, completed, total| progress.push((item.file_id.clone(), completed, total)),
+      )
+      .await;
+
+    assert_eq!(results.len(), 3);
+    assert!(results[0].result.is_ok());
+    assert!(results[1]
+      .result
+      .as_ref()
+      .unwrap_err()
+      .contains("/nonexistent/frame_0001.jpg"));
+    assert!(results[2].result.is_ok());
+    assert_eq!(progress[1], ("broken".to_string(), 2, 3));
+
+    // Успешные файлы сохранены по мере обработки, упавший - нет
+    assert!(service.load_results("first").await.unwrap().is_some());
+    assert!(service.load_results("last").await.unwrap().is_some());
+    assert!(service.load_results("broken").await.unwrap().is_none());
+  }
+
+  #[tokio::test]
+  async fn test_process_batch_without_persistence() {
+    let temp_dir = TempDir::new().unwrap();
+    let service = RecognitionService::new(temp_dir.path().to_path_buf()).unwrap();
+
+    let mut frame_paths_map = HashMap::new();
+    frame_paths_map.insert("file1".to_string(), vec![]);
+
+    let results = service
+      .process_batch(
+        vec!["file1".to_string(), "unknown".to_string()],
+        frame_paths_map,
+        BatchProcessingOptions {
+          persist_results: false,
+          ..Default::default()
+        },
+      )
+      .await;
+
+    assert!(results[0].result.is_ok());
+    assert!(results[1].result.is_err());
+    assert!(service.load_results("file1").await.unwrap().is_none());
+  }
+
+  #[tokio::test]
+  async fn test_process_batch_stops_when_cancelled() {
+    let temp_dir = TempDir::new().unwrap();
+    let service = RecognitionService::new(temp_dir.path().to_path_buf()).unwrap();
+
+    let mut frame_paths_map = HashMap::new();
+    frame_paths_map.insert("file1".to_string(), vec![]);
+    frame_paths_map.insert("file2".to_string(), vec![]);
+
+    let token = CancellationToken::new();
+    let options = BatchProcessingOptions {
+      cancel: Some(token.clone()),
+      ..Default::default()
+    };
+    let results = service
+      .process_batch_with_progress(
+        vec!["file1".to_string(), "file2".to_string()],
+        frame_paths_map,
+        options,
+        |_, _, _| token.cancel(),
+      )
+      .await;
+
+    // Обработанный до отмены файл сохраняется в результате
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].file_id, "file1");
+  }
+
+  #[tokio::test]
+  async fn test_set_config_rejects_invalid_values() {
+    let temp_dir = TempDir::new().unwrap();
+    let service = RecognitionService::new(temp_dir.path().to_path_buf()).unwrap();
+
+    let invalid = RecognitionConfig {
+      confidence_threshold: 1.5,
+      batch_size: 0,
+      ..Default::default()
+    };
+    let error = service.set_config(invalid).await.unwrap_err().to_string();
+    assert!(error.contains("confidence_threshold"));
+    assert!(error.contains("batch_size"));
+
+    // Прежние параметры остались в силе
+    assert_eq!(service.get_config().await, RecognitionConfig::default());
+  }
+
+  #[tokio::test]
+  async fn test_process_video_records_effective_config() {
+    let temp_dir = TempDir::new().unwrap();
+    let service = RecognitionService::new(temp_dir.path().to_path_buf()).unwrap();
+
+    let config = RecognitionConfig {
+      confidence_threshold: 0.3,
+      execution_provider: ExecutionProvider::Cuda,
+      batch_size: 4,
+      ..Default::default()
+    };
+    service.set_config(config.clone()).await.unwrap();
+
+    let results = service
+      .process_video("default", vec![], None)
+      .await
+      .unwrap();
+    assert_eq!(results.config.as_ref(), Some(&config));
+
+    // Переопределение действует только на один вызов
+    let override_config = RecognitionConfig {
+      iou_threshold: 0.6,
+      ..Default::default()
+    };
+    let results = service
+      .process_video("override", vec![], Some(override_config.clone()))
+      .await
+      .unwrap();
+    assert_eq!(results.config, Some(override_config));
+    assert_eq!(service.get_config().await, config);
+
+    let loaded = service.load_results("default").await.unwrap().unwrap();
+    assert_eq!(loaded.config, Some(config));
+  }
+
+  #[tokio::test]
+  async fn test_process_video_rejects_invalid_override() {
+    let temp_dir = TempDir::new().unwrap();
+    let service = RecognitionService::new(temp_dir.path().to_path_buf()).unwrap();
+
+    let invalid = RecognitionConfig {
+      confidence_threshold: -0.1,
+      ..Default::default()
+    };
+    let result = service.process_video("file", vec![], Some(invalid)).await;
+    assert!(result.is_err());
+    assert!(service.load_results("file").await.unwrap().is_none());
   }
 
   #[tokio::test]
@@ -619,6 +1046,8 @@ mod tests {
           faces: vec![],
           scenes: vec![],
           processed_at: chrono::Utc::now(),
+          config: None,
+          frames: Vec::new(),
         },
       },
       RecognitionEvent::ProcessingError {
@@ -1,9 +1,14 @@
 //! Result Aggregator - Сбор и форматирование результатов распознавания
 
 use crate::recognition::frame_processor::Detection;
+use crate::recognition::types::RecognitionResults;
+use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// Класс, под которым на временной шкале учитываются лица
+pub const FACE_TIMELINE_CLASS: &str = "face";
+
 /// Результат распознавания для кадра
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FrameRecognitionResult {
@@ -346,6 +351,99 @@ pub struct TimelineSegment {
   pub detection_count: usize,
 }
 
+/// Интервал шкалы плотности обнаружений
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimelineBucket {
+  /// Начало интервала в секундах
+  pub start: f64,
+  /// Конец интервала в секундах
+  pub end: f64,
+  /// Количество появлений по классам
+  pub counts: HashMap<String, u32>,
+  /// Максимальная уверенность среди появлений интервала
+  pub max_confidence: f32,
+}
+
+/// Построить шкалу плотности обнаружений с интервалами `bucket_secs`.
+///
+/// Учитываются объекты (по классу) и лица (класс [`FACE_TIMELINE_CLASS`]);
+/// `classes` ограничивает набор классов. Длительность определяется по
+/// последней метке кадра или обнаружения, последний интервал обрезается по
+/// ней. Без обработанных кадров и обнаружений шкала пуста.
+pub fn build_recognition_timeline(
+  results: &RecognitionResults,
+  bucket_secs: f64,
+  classes: Option<&[String]>,
+) -> Result<Vec<TimelineBucket>> {
+  if !bucket_secs.is_finite() || bucket_secs <= 0.0 {
+    anyhow::bail!("Размер интервала должен быть положительным: {bucket_secs}");
+  }
+
+  let included = |class: &str| classes.is_none_or(|classes| classes.iter().any(|c| c == class));
+  let appearances = results
+    .objects
+    .iter()
+    .map(|object| (object.class.as_str(), object.confidence, &object.timestamps))
+    .chain(
+      results
+        .faces
+        .iter()
+        .map(|face| (FACE_TIMELINE_CLASS, face.confidence, &face.timestamps)),
+    )
+    .filter(|(class, _, _)| included(class));
+
+  let duration = results
+    .frames
+    .iter()
+    .map(|frame| frame.timestamp)
+    .chain(
+      appearances
+        .clone()
+        .flat_map(|(_, _, timestamps)| timestamps.iter().copied()),
+    )
+    .filter(|timestamp| timestamp.is_finite() && *timestamp >= 0.0)
+    .fold(None, |max: Option<f64>, timestamp| {
+      Some(max.map_or(timestamp, |max| max.max(timestamp)))
+    });
+  let Some(duration) = duration else {
+    return Ok(Vec::new());
+  };
+
+  // Интервал больше видео дает одну ячейку на всю длительность
+  let bucket_count = ((duration / bucket_secs).ceil() as usize).max(1);
+  let mut buckets: Vec<TimelineBucket> = (0..bucket_count)
+    .map(|index| {
+      let start = index as f64 * bucket_secs;
+      TimelineBucket {
+        start,
+        end: ((index + 1) as f64 * bucket_secs).min(duration).max(start),
+        counts: HashMap::new(),
+        max_confidence: 0.0,
+      }
+    })
+    .collect();
+
+  for (class, confidence, timestamps) in appearances {
+    for &timestamp in timestamps {
+      if !timestamp.is_finite() || timestamp < 0.0 {
+        continue;
+      }
+      // Метка на конце видео попадает в последний интервал
+      let index = ((timestamp / bucket_secs) as usize).min(bucket_count - 1);
+      let bucket = &mut buckets[index];
+      match bucket.counts.get_mut(class) {
+        Some(count) => *count += 1,
+        None => {
+          bucket.counts.insert(class.to_string(), 1);
+        }
+      }
+      bucket.max_confidence = bucket.max_confidence.max(confidence);
+    }
+  }
+
+  Ok(buckets)
+}
+
 impl Default for ResultAggregator {
   fn default() -> Self {
     Self::new()
@@ -441,4 +539,97 @@ mod tests {
     assert!(csv.contains("frame_number,timestamp,class,confidence,x,y,width,height"));
     assert!(csv.contains("0,0,car,0.85,200,150,80,60"));
   }
+
+  /// Синтетические результаты: 10 000 меток `person` каждые 0.1 с и
+  /// метки `car` и лица в первой минуте
+  fn synthetic_results() -> RecognitionResults {
+    use crate::recognition::types::{DetectedFace, DetectedObject};
+
+    RecognitionResults {
+      objects: vec![
+        DetectedObject {
+          class: "person".to_string(),
+          confidence: 0.8,
+          timestamps: (0..10_000).map(|i| i as f64 * 0.1).collect(),
+          bounding_boxes: vec![],
+        },
+        DetectedObject {
+          class: "car".to_string(),
+          confidence: 0.95,
+          timestamps: (0..60).map(|i| i as f64).collect(),
+          bounding_boxes: vec![],
+        },
+      ],
+      faces: vec![DetectedFace {
+        face_id: None,
+        person_name: None,
+        confidence: 0.6,
+        timestamps: vec![5.0, 15.0, 25.0],
+        bounding_boxes: vec![],
+      }],
+      scenes: vec![],
+      processed_at: chrono::Utc::now(),
+      config: None,
+      frames: vec![],
+    }
+  }
+
+  #[test]
+  fn test_recognition_timeline_buckets() {
+    let results = synthetic_results();
+
+    let started = std::time::Instant::now();
+    let buckets = build_recognition_timeline(&results, 10.0, None).unwrap();
+    assert!(started.elapsed() < std::time::Duration::from_secs(1));
+
+    // Последняя метка 999.9 с: 100 интервалов по 10 с
+    assert_eq!(buckets.len(), 100);
+    assert_eq!(buckets[0].start, 0.0);
+    assert_eq!(buckets[0].end, 10.0);
+    assert!((buckets[99].end - 999.9).abs() < 1e-9);
+
+    assert_eq!(buckets[0].counts["person"], 100);
+    assert_eq!(buckets[0].counts["car"], 10);
+    assert_eq!(buckets[0].counts[FACE_TIMELINE_CLASS], 1);
+    assert_eq!(buckets[0].max_confidence, 0.95);
+
+    assert_eq!(buckets[50].counts.len(), 1);
+    assert_eq!(buckets[50].max_confidence, 0.8);
+
+    let total: u32 = buckets.iter().map(|bucket| bucket.counts["person"]).sum();
+    assert_eq!(total, 10_000);
+  }
+
+  #[test]
+  fn test_recognition_timeline_class_filter() {
+    let results = synthetic_results();
+    let classes = vec!["car".to_string()];
+    let buckets = build_recognition_timeline(&results, 30.0, Some(&classes)).unwrap();
+
+    // Длительность определяется только выбранными классами
+    assert_eq!(buckets.len(), 2);
+    assert_eq!(buckets[0].counts["car"], 30);
+    assert_eq!(buckets[1].counts["car"], 30);
+    assert!(!buckets[0].counts.contains_key("person"));
+  }
+
+  #[test]
+  fn test_recognition_timeline_edge_cases() {
+    let mut results = synthetic_results();
+
+    // Интервал больше видео - одна ячейка на всю длительность
+    let buckets = build_recognition_timeline(&results, 5_000.0, None).unwrap();
+    assert_eq!(buckets.len(), 1);
+    assert!((buckets[0].end - 999.9).abs() < 1e-9);
+    assert_eq!(buckets[0].counts["person"], 10_000);
+
+    assert!(build_recognition_timeline(&results, 0.0, None).is_err());
+    assert!(build_recognition_timeline(&results, f64::NAN, None).is_err());
+
+    results.objects.clear();
+    results.faces.clear();
+    assert!(build_recognition_timeline(&results, 1.0, None)
+      .unwrap()
+      .is_empty());
+  }
 }
@@ -0,0 +1,622 @@
+//! Сравнение результатов распознавания двух моделей
+//!
+//! Детекции обоих наборов раскладываются по появлениям (класс, метка
+//! времени, рамка) и сопоставляются по классу и IoU среди появлений с
+//! близкими метками времени. Набор A считается эталоном: по нему
+//! считаются точность и полнота набора B для каждого класса. Для поиска
+//! кандидатов набор B индексируется по интервалам времени, поэтому
+//! сравнение больших наборов не сводится к перебору всех пар.
+
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+use super::dataset_export::FACE_CATEGORY;
+use super::types::{BoundingBox, RecognitionResults};
+
+/// Параметры сравнения
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ComparisonOptions {
+  /// Минимальный IoU рамок для сопоставления (включительно)
+  pub iou_threshold: f32,
+  /// Допустимое расхождение меток времени в секундах
+  pub time_tolerance: f64,
+  /// Сравнивать лица как класс `face`
+  pub include_faces: bool,
+}
+
+impl Default for ComparisonOptions {
+  fn default() -> Self {
+    Self {
+      iou_threshold: 0.5,
+      time_tolerance: 0.05,
+      include_faces: true,
+    }
+  }
+}
+
+impl ComparisonOptions {
+  fn validate(&self) -> Result<()> {
+    if !(0.0..=1.0).contains(&self.iou_threshold) {
+      bail!(
+        "iou_threshold must be within 0..1, got {}",
+        self.iou_threshold
+      );
+    }
+    if !self.time_tolerance.is_finite() || self.time_tolerance < 0.0 {
+      bail!(
+        "time_tolerance must be a non-negative number, got {}",
+        self.time_tolerance
+      );
+    }
+    Ok(())
+  }
+}
+
+/// Одно появление объекта
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectionRecord {
+  pub class: String,
+  pub timestamp: f64,
+  pub confidence: f32,
+  pub bbox: BoundingBox,
+}
+
+/// Появление, найденное в обоих наборах
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchedDetection {
+  pub class: String,
+  pub timestamp_a: f64,
+  pub timestamp_b: f64,
+  pub iou: f32,
+  pub confidence_a: f32,
+  pub confidence_b: f32,
+  /// Изменение уверенности: B - A
+  pub confidence_delta: f32,
+}
+
+/// Рамка, которой набор B присвоил другой класс
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClassChange {
+  pub timestamp: f64,
+  pub class_a: String,
+  pub class_b: String,
+  pub iou: f32,
+}
+
+/// Метрики класса относительно набора A
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClassComparison {
+  pub class: String,
+  /// Появлений в наборе A
+  pub count_a: usize,
+  /// Появлений в наборе B
+  pub count_b: usize,
+  /// Сопоставленных появлений
+  pub matched: usize,
+  /// Доля появлений B, подтвержденных набором A (`None` - в B нет класса)
+  pub precision: Option<f32>,
+  /// Доля появлений A, найденных в B (`None` - в A нет класса)
+  pub recall: Option<f32>,
+  /// Среднее изменение уверенности сопоставленных появлений
+  pub mean_confidence_delta: Option<f32>,
+}
+
+/// Отчет сравнения двух наборов результатов
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecognitionComparison {
+  pub file_id: String,
+  pub options: ComparisonOptions,
+  pub matched: Vec<MatchedDetection>,
+  /// Появления только в наборе A
+  pub removed: Vec<DetectionRecord>,
+  /// Появления только в наборе B
+  pub added: Vec<DetectionRecord>,
+  /// Несопоставленные пары с той же рамкой, но разными классами. Такие
+  /// появления также входят в `removed` и `added`.
+  pub class_changes: Vec<ClassChange>,
+  /// Метрики по классам, отсортированные по имени
+  pub classes: Vec<ClassComparison>,
+}
+
+/// Разложить результаты по отдельным появлениям, отсортированным по времени
+fn flatten(results: &RecognitionResults, include_faces: bool) -> Vec<DetectionRecord> {
+  let objects = results.objects.iter().map(|object| {
+    (
+      object.class.as_str(),
+      object.confidence,
+      &object.timestamps,
+      &object.bounding_boxes,
+    )
+  });
+  let faces = results.faces.iter().filter(|_| include_faces).map(|face| {
+    (
+      FACE_CATEGORY,
+      face.confidence,
+      &face.timestamps,
+      &face.bounding_boxes,
+    )
+  });
+
+  let mut records: Vec<DetectionRecord> = objects
+    .chain(faces)
+    .flat_map(|(class, confidence, timestamps, boxes)| {
+      timestamps
+        .iter()
+        .zip(boxes)
+        .map(move |(timestamp, bbox)| DetectionRecord {
+          class: class.to_string(),
+          timestamp: *timestamp,
+          confidence,
+          bbox: bbox.clone(),
+        })
+    })
+    .collect();
+  records.sort_by(|a, b| a.timestamp.total_cmp(&b.timestamp));
+  records
+}
+
+/// IoU двух рамок
+pub fn bbox_iou(a: &BoundingBox, b: &BoundingBox) -> f32 {
+  let x1 = a.x.max(b.x);
+  let y1 = a.y.max(b.y);
+  let x2 = (a.x + a.width).min(b.x + b.width);
+  let y2 = (a.y + a.height).min(b.y + b.height);
+  if x2 <= x1 || y2 <= y1 {
+    return 0.0;
+  }
+
+  let intersection = (x2 - x1) * (y2 - y1);
+  let union = a.width * a.height + b.width * b.height - intersection;
+  if union <= 0.0 {
+    0.0
+  } else {
+    intersection / union
+  }
+}
+
+/// Индекс появлений по интервалам времени шириной не меньше допуска
+struct TimeIndex {
+  width: f64,
+  buckets: HashMap<i64, Vec<usize>>,
+}
+
+impl TimeIndex {
+  fn new(records: &[DetectionRecord], tolerance: f64) -> Self {
+    // Нулевой допуск сравнивает метки точно; интервал нужен только для индекса
+    let width = tolerance.max(1e-3);
+    let mut buckets: HashMap<i64, Vec<usize>> = HashMap::new();
+    for (idx, record) in records.iter().enumerate() {
+      buckets
+        .entry((record.timestamp / width).floor() as i64)
+        .or_default()
+        .push(idx);
+    }
+    Self { width, buckets }
+  }
+
+  /// Появления в соседних интервалах - надмножество появлений в пределах допуска
+  fn candidates(&self, timestamp: f64) -> impl Iterator<Item = usize> + '_ {
+    let bucket = (timestamp / self.width).floor() as i64;
+    (bucket - 1..=bucket + 1)
+      .filter_map(|key| self.buckets.get(&key))
+      .flatten()
+      .copied()
+  }
+}
+
+/// Жадно сопоставить появления: сначала пары с большим IoU, затем с меньшим
+/// расхождением времени. Сопоставленные появления помечаются в `used_a`/`used_b`.
+fn greedy_match(
+  a: &[DetectionRecord],
+  b: &[DetectionRecord],
+  index: &TimeIndex,
+  options: &ComparisonOptions,
+  same_class: bool,
+  used_a: &mut [bool],
+  used_b: &mut [bool],
+) -> Vec<(usize, usize, f32)> {
+  let mut pairs = Vec::new();
+  for (ia, record_a) in a.iter().enumerate().filter(|(ia, _)| !used_a[*ia]) {
+    for ib in index.candidates(record_a.timestamp) {
+      let record_b = &b[ib];
+      if used_b[ib]
+        || (record_a.class == record_b.class) != same_class
+        || (record_a.timestamp - record_b.timestamp).abs() > options.time_tolerance + 1e-9
+      {
+        continue;
+      }
+      let iou = bbox_iou(&record_a.bbox, &record_b.bbox);
+      if iou >= options.iou_threshold && iou > 0.0 {
+        pairs.push((ia, ib, iou));
+      }
+    }
+  }
+
+  pairs.sort_by(|(a1, b1, iou1), (a2, b2, iou2)| {
+    let dt1 = (a[*a1].timestamp - b[*b1].timestamp).abs();
+    let dt2 = (a[*a2].timestamp - b[*b2].timestamp).abs();
+    iou2.total_cmp(iou1).then(dt1.total_cmp(&dt2))
+  });
+
+  let mut matched = Vec::new();
+  for (ia, ib, iou) in pairs {
+    if !used_a[ia] && !used_b[ib] {
+      used_a[ia] = true;
+      used_b[ib] = true;
+      matched.push((ia, ib, iou));
+    }
+  }
+  matched
+}
+
+/// Сравнить результаты двух запусков распознавания, считая набор A эталоном
+pub fn compare_results(
+  file_id: &str,
+  results_a: &RecognitionResults,
+  results_b: &RecognitionResults,
+  options: &ComparisonOptions,
+) -> Result<RecognitionComparison> {
+  options.validate()?;
+
+  let a = flatten(results_a, options.include_faces);
+  let b = flatten(results_b, options.include_faces);
+  let index = TimeIndex::new(&b, options.time_tolerance);
+  let mut used_a = vec![false; a.len()];
+  let mut used_b = vec![false; b.len()];
+
+  let matched: Vec<MatchedDetection> =
+    greedy_match(&a, &b, &index, options, true, &mut used_a, &mut used_b)
+      .into_iter()
+      .map(|(ia, ib, iou)| MatchedDetection {
+        class: a[ia].class.clone(),
+        timestamp_a: a[ia].timestamp,
+        timestamp_b: b[ib].timestamp,
+        iou,
+        confidence_a: a[ia].confidence,
+        confidence_b: b[ib].confidence,
+        confidence_delta: b[ib].confidence - a[ia].confidence,
+      })
+      .collect();
+
+  // Смена класса ищется среди оставшихся появлений на копиях флагов:
+  // такие появления остаются удаленными и добавленными
+  let class_changes = greedy_match(
+    &a,
+    &b,
+    &index,
+    options,
+    false,
+    &mut used_a.clone(),
+    &mut used_b.clone(),
+  )
+  .into_iter()
+  .map(|(ia, ib, iou)| ClassChange {
+    timestamp: a[ia].timestamp,
+    class_a: a[ia].class.clone(),
+    class_b: b[ib].class.clone(),
+    iou,
+  })
+  .collect();
+
+  let unmatched = |records: &[DetectionRecord], used: &[bool]| -> Vec<DetectionRecord> {
+    records
+      .iter()
+      .zip(used)
+      .filter(|(_, used)| !**used)
+      .map(|(record, _)| record.clone())
+      .collect()
+  };
+  let removed = unmatched(&a, &used_a);
+  let added = unmatched(&b, &used_b);
+
+  Ok(RecognitionComparison {
+    file_id: file_id.to_string(),
+    options: options.clone(),
+    classes: class_metrics(&a, &b, &matched),
+    matched,
+    removed,
+    added,
+    class_changes,
+  })
+}
+
+/// Точность и полнота по классам
+fn class_metrics(
+  a: &[DetectionRecord],
+  b: &[DetectionRecord],
+  matched: &[MatchedDetection],
+) -> Vec<ClassComparison> {
+  let mut classes: BTreeMap<&str, (usize, usize, usize, f32)> = BTreeMap::new();
+  for record in a {
+    classes.entry(&record.class).or_default().0 += 1;
+  }
+  for record in b {
+    classes.entry(&record.class).or_default().1 += 1;
+  }
+  for detection in matched {
+    let entry = classes.entry(&detection.class).or_default();
+    entry.2 += 1;
+    entry.3 += detection.confidence_delta;
+  }
+
+  let ratio = |part: usize, total: usize| (total > 0).then(|| part as f32 / total as f32);
+  classes
+    .into_iter()
+    .map(
+      |(class, (count_a, count_b, matched, delta_sum))| ClassComparison {
+        class: class.to_string(),
+        count_a,
+        count_b,
+        matched,
+        precision: ratio(matched, count_b),
+        recall: ratio(matched, count_a),
+        mean_confidence_delta: (matched > 0).then(|| delta_sum / matched as f32),
+      },
+    )
+    .collect()
+}
+
+/// Отчет сравнения в CSV: строка на каждое появление и итог по классам
+pub fn format_comparison_csv(report: &RecognitionComparison) -> String {
+  let mut csv =
+    String::from("Status,Class,Timestamp A,Timestamp B,Confidence A,Confidence B,IoU\n");
+
+  for detection in &report.matched {
+    csv.push_str(&format!(
+      "matched,{},{:.3},{:.3},{:.3},{:.3},{:.3}\n",
+      detection.class,
+      detection.timestamp_a,
+      detection.timestamp_b,
+      detection.confidence_a,
+      detection.confidence_b,
+      detection.iou
+    ));
+  }
+  for record in &report.removed {
+    csv.push_str(&format!(
+      "removed,{},{:.3},,{:.3},,\n",
+      record.class, record.timestamp, record.confidence
+    ));
+  }
+  for record in &report.added {
+    csv.push_str(&format!(
+      "added,{},,{:.3},,{:.3},\n",
+      record.class, record.timestamp, record.confidence
+    ));
+  }
+
+  csv.push_str("\nClass,Count A,Count B,Matched,Precision,Recall,Mean Confidence Delta\n");
+  let optional = |value: Option<f32>| value.map(|v| format!("{v:.3}")).unwrap_or_default();
+  for class in &report.classes {
+    csv.push_str(&format!(
+      "{},{},{},{},{},{},{}\n",
+      class.class,
+      class.count_a,
+      class.count_b,
+      class.matched,
+      optional(class.precision),
+      optional(class.recall),
+      optional(class.mean_confidence_delta)
+    ));
+  }
+
+  csv
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::recognition::types::DetectedObject;
+
+  fn bbox(x: f32, y: f32, width: f32, height: f32) -> BoundingBox {
+    BoundingBox {
+      x,
+      y,
+      width,
+      height,
+    }
+  }
+
+  fn object(class: &str, confidence: f32, appearances: &[(f64, BoundingBox)]) -> DetectedObject {
+    DetectedObject {
+      class: class.to_string(),
+      confidence,
+      timestamps: appearances.iter().map(|(t, _)| *t).collect(),
+      bounding_boxes: appearances.iter().map(|(_, b)| b.clone()).collect(),
+    }
+  }
+
+  fn results(objects: Vec<DetectedObject>) -> RecognitionResults {
+    RecognitionResults {
+      objects,
+      faces: Vec::new(),
+      scenes: Vec::new(),
+      processed_at: chrono::Utc::now(),
+      config: None,
+      frames: Vec::new(),
+    }
+  }
+
+  #[test]
+  fn test_identical_results_match_exactly() {
+    let set = results(vec![
+      object(
+        "person",
+        0.8,
+        &[
+          (0.0, bbox(10.0, 10.0, 50.0, 100.0)),
+          (1.0, bbox(20.0, 10.0, 50.0, 100.0)),
+        ],
+      ),
+      object("car", 0.6, &[(1.0, bbox(200.0, 200.0, 80.0, 40.0))]),
+    ]);
+
+    let report = compare_results("file", &set, &set, &ComparisonOptions::default()).unwrap();
+    assert_eq!(report.matched.len(), 3);
+    assert!(report.removed.is_empty());
+    assert!(report.added.is_empty());
+    assert!(report.class_changes.is_empty());
+    assert!(report.matched.iter().all(|m| m.iou == 1.0));
+    assert!(report.matched.iter().all(|m| m.confidence_delta == 0.0));
+
+    let names: Vec<&str> = report.classes.iter().map(|c| c.class.as_str()).collect();
+    assert_eq!(names, ["car", "person"]);
+    for class in &report.classes {
+      assert_eq!(class.precision, Some(1.0));
+      assert_eq!(class.recall, Some(1.0));
+    }
+  }
+
+  #[test]
+  fn test_class_change_is_reported() {
+    let a = results(vec![object(
+      "dog",
+      0.7,
+      &[(2.0, bbox(0.0, 0.0, 100.0, 100.0))],
+    )]);
+    let b = results(vec![object(
+      "cat",
+      0.9,
+      &[(2.02, bbox(5.0, 0.0, 100.0, 100.0))],
+    )]);
+
+    let report = compare_results("file", &a, &b, &ComparisonOptions::default()).unwrap();
+    assert!(report.matched.is_empty());
+    assert_eq!(report.removed.len(), 1);
+    assert_eq!(report.removed[0].class, "dog");
+    assert_eq!(report.added.len(), 1);
+    assert_eq!(report.added[0].class, "cat");
+
+    assert_eq!(report.class_changes.len(), 1);
+    assert_eq!(report.class_changes[0].class_a, "dog");
+    assert_eq!(report.class_changes[0].class_b, "cat");
+
+    let dog = &report.classes[1];
+    assert_eq!(dog.class, "dog");
+    assert_eq!(dog.recall, Some(0.0));
+    assert_eq!(dog.precision, None);
+    let cat = &report.classes[0];
+    assert_eq!(cat.precision, Some(0.0));
+    assert_eq!(cat.recall, None);
+  }
+
+  #[test]
+  fn test_iou_threshold_boundary_is_inclusive() {
+    // Пересечение 5000 при объединении 10000: IoU ровно 0.5
+    let a = results(vec![object(
+      "person",
+      0.5,
+      &[(0.0, bbox(0.0, 0.0, 100.0, 100.0))],
+    )]);
+    let b = results(vec![object(
+      "person",
+      0.75,
+      &[(0.0, bbox(0.0, 0.0, 100.0, 50.0))],
+    )]);
+
+    let at_boundary = ComparisonOptions {
+      iou_threshold: 0.5,
+      ..Default::default()
+    };
+    let report = compare_results("file", &a, &b, &at_boundary).unwrap();
+    assert_eq!(report.matched.len(), 1);
+    assert_eq!(report.matched[0].iou, 0.5);
+    assert_eq!(report.matched[0].confidence_delta, 0.25);
+    assert_eq!(report.classes[0].mean_confidence_delta, Some(0.25));
+
+    let above = ComparisonOptions {
+      iou_threshold: 0.51,
+      ..Default::default()
+    };
+    let report = compare_results("file", &a, &b, &above).unwrap();
+    assert!(report.matched.is_empty());
+    assert_eq!(report.removed.len(), 1);
+    assert_eq!(report.added.len(), 1);
+
+    // Касающиеся рамки не пересекаются даже при нулевом пороге
+    let touching = results(vec![object(
+      "person",
+      0.5,
+      &[(0.0, bbox(100.0, 0.0, 100.0, 100.0))],
+    )]);
+    let zero = ComparisonOptions {
+      iou_threshold: 0.0,
+      ..Default::default()
+    };
+    let report = compare_results("file", &a, &touching, &zero).unwrap();
+    assert!(report.matched.is_empty());
+  }
+
+  #[test]
+  fn test_time_tolerance_and_added_removed() {
+    let a = results(vec![object(
+      "person",
+      0.8,
+      &[
+        (1.0, bbox(0.0, 0.0, 50.0, 50.0)),
+        (5.0, bbox(0.0, 0.0, 50.0, 50.0)),
+      ],
+    )]);
+    let b = results(vec![object(
+      "person",
+      0.8,
+      &[
+        (1.04, bbox(0.0, 0.0, 50.0, 50.0)),
+        (5.2, bbox(0.0, 0.0, 50.0, 50.0)),
+        (9.0, bbox(0.0, 0.0, 50.0, 50.0)),
+      ],
+    )]);
+
+    let report = compare_results("file", &a, &b, &ComparisonOptions::default()).unwrap();
+    assert_eq!(report.matched.len(), 1);
+    assert_eq!(report.matched[0].timestamp_b, 1.04);
+    assert_eq!(report.removed.len(), 1);
+    assert_eq!(report.removed[0].timestamp, 5.0);
+    assert_eq!(report.added.len(), 2);
+
+    let person = &report.classes[0];
+    assert_eq!((person.count_a, person.count_b, person.matched), (2, 3, 1));
+    assert!((person.precision.unwrap() - 1.0 / 3.0).abs() < 1e-6);
+    assert_eq!(person.recall, Some(0.5));
+
+    let csv = format_comparison_csv(&report);
+    assert!(csv.starts_with("Status,Class,"));
+    assert_eq!(csv.lines().filter(|l| l.starts_with("added,")).count(), 2);
+    assert!(csv.contains("person,2,3,1,0.333,0.500,0.000"));
+
+    let invalid = ComparisonOptions {
+      time_tolerance: -1.0,
+      ..Default::default()
+    };
+    assert!(compare_results("file", &a, &b, &invalid).is_err());
+  }
+
+  #[test]
+  fn test_large_result_sets() {
+    let appearances: Vec<(f64, BoundingBox)> = (0..20_000)
+      .map(|i| {
+        (
+          i as f64 * 0.04,
+          bbox((i % 7) as f32 * 10.0, 0.0, 40.0, 40.0),
+        )
+      })
+      .collect();
+    let shifted: Vec<(f64, BoundingBox)> = appearances
+      .iter()
+      .map(|(t, b)| (t + 0.01, b.clone()))
+      .collect();
+    let a = results(vec![object("person", 0.8, &appearances)]);
+    let b = results(vec![object("person", 0.9, &shifted)]);
+
+    let options = ComparisonOptions {
+      time_tolerance: 0.015,
+      ..Default::default()
+    };
+    let report = compare_results("file", &a, &b, &options).unwrap();
+    assert_eq!(report.matched.len(), 20_000);
+    assert!(report.matched.iter().all(|m| m.timestamp_b > m.timestamp_a));
+  }
+}
@@ -0,0 +1,481 @@
+//! Пул ONNX сессий моделей распознавания
+//!
+//! Сессии создаются лениво для пары (модель, провайдер выполнения) и
+//! переиспользуются всеми потребителями: сервисом распознавания и
+//! процессорами планировщика монтажа. Сессия, не использовавшаяся дольше
+//! `idle_ttl`, выгружается для освобождения памяти и при следующем запросе
+//! создается заново. Число одновременных инференсов ограничено семафором
+//! каждого провайдера: по умолчанию число физических ядер для CPU и
+//! [`GPU_CONCURRENCY`] для GPU.
+
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use ort::session::{builder::GraphOptimizationLevel, Session};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex as StdMutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, Semaphore};
+
+use super::yolo_processor::ExecutionProvider;
+
+/// Время простоя, после которого сессия выгружается
+pub const DEFAULT_IDLE_TTL: Duration = Duration::from_secs(300);
+
+/// Одновременные инференсы на GPU провайдере по умолчанию
+pub const GPU_CONCURRENCY: usize = 2;
+
+/// Пул сессий приложения
+static SHARED_POOL: Lazy<Arc<SessionPool>> =
+  Lazy::new(|| Arc::new(SessionPool::new(DEFAULT_IDLE_TTL)));
+
+/// Ключ сессии: модель и провайдер выполнения
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SessionKey {
+  pub model_path: PathBuf,
+  pub provider: ExecutionProvider,
+}
+
+impl SessionKey {
+  pub fn new(model_path: impl Into<PathBuf>, provider: ExecutionProvider) -> Self {
+    Self {
+      model_path: model_path.into(),
+      provider,
+    }
+  }
+}
+
+/// Собрать ONNX сессию модели для провайдера ключа
+pub fn build_session(key: &SessionKey) -> Result<Session> {
+  let model =
+    std::fs::read(&key.model_path).map_err(|e| anyhow!("Failed to read model file: {}", e))?;
+  Session::builder()
+    .map_err(|e| anyhow!("Failed to create session builder: {}", e))?
+    .with_optimization_level(GraphOptimizationLevel::Level3)
+    .map_err(|e| anyhow!("Failed to set optimization level: {}", e))?
+    .with_intra_threads(4)
+    .map_err(|e| anyhow!("Failed to set intra threads: {}", e))?
+    .with_execution_providers([key.provider.dispatch()])
+    .map_err(|e| anyhow!("Failed to set execution provider: {}", e))?
+    .commit_from_memory(&model)
+    .map_err(|e| anyhow!("Failed to load model from memory: {}", e))
+}
+
+/// Одновременные инференсы провайдера по умолчанию
+pub fn default_concurrency(provider: ExecutionProvider) -> usize {
+  match provider {
+    ExecutionProvider::Cpu => num_cpus::get_physical().max(1),
+    _ => GPU_CONCURRENCY,
+  }
+}
+
+/// Загруженная сессия
+struct PooledSession<S> {
+  session: Arc<Mutex<S>>,
+  /// Оценка занимаемой памяти: размер файла модели
+  memory_bytes: u64,
+  last_used: Instant,
+}
+
+/// Загруженная сессия в статистике
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadedSessionInfo {
+  pub model_path: PathBuf,
+  pub provider: ExecutionProvider,
+  pub memory_bytes: u64,
+  /// Время с последнего использования, с
+  pub idle_secs: f64,
+}
+
+/// Статистика пула сессий для frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecognitionRuntimeStats {
+  pub sessions: Vec<LoadedSessionInfo>,
+  /// Суммарная оценка памяти загруженных сессий
+  pub memory_estimate_bytes: u64,
+  /// Инференсы, ожидающие свободного слота
+  pub queue_depth: usize,
+  /// Выполняющиеся инференсы
+  pub active_inferences: usize,
+  /// Заданный предел одновременных инференсов (`None` - по провайдеру)
+  pub max_concurrent_inferences: Option<usize>,
+  pub total_inferences: u64,
+  pub average_inference_ms: f64,
+}
+
+/// Уменьшает счетчик при удалении, в том числе при отмене ожидания
+struct CounterGuard<'a>(&'a AtomicUsize);
+
+impl<'a> CounterGuard<'a> {
+  fn enter(counter: &'a AtomicUsize) -> Self {
+    counter.fetch_add(1, Ordering::SeqCst);
+    Self(counter)
+  }
+}
+
+impl Drop for CounterGuard<'_> {
+  fn drop(&mut self) {
+    self.0.fetch_sub(1, Ordering::SeqCst);
+  }
+}
+
+/// Пул сессий с ограничением одновременных инференсов
+pub struct SessionPool<S = Session> {
+  sessions: StdMutex<HashMap<SessionKey, PooledSession<S>>>,
+  idle_ttl: Duration,
+  /// Предел одновременных инференсов, заданный пользователем
+  max_concurrent: StdMutex<Option<usize>>,
+  /// Семафоры инференсов по провайдеру
+  limiters: StdMutex<HashMap<ExecutionProvider, Arc<Semaphore>>>,
+  queued: AtomicUsize,
+  active: AtomicUsize,
+  inferences: AtomicU64,
+  inference_micros: AtomicU64,
+}
+
+impl SessionPool<Session> {
+  /// Пул, общий для всех потребителей моделей распознавания
+  pub fn shared() -> Arc<SessionPool> {
+    SHARED_POOL.clone()
+  }
+}
+
+impl<S> SessionPool<S> {
+  pub fn new(idle_ttl: Duration) -> Self {
+    Self {
+      sessions: StdMutex::new(HashMap::new()),
+      idle_ttl,
+      max_concurrent: StdMutex::new(None),
+      limiters: StdMutex::new(HashMap::new()),
+      queued: AtomicUsize::new(0),
+      active: AtomicUsize::new(0),
+      inferences: AtomicU64::new(0),
+      inference_micros: AtomicU64::new(0),
+    }
+  }
+
+  fn lock_sessions(&self) -> MutexGuard<'_, HashMap<SessionKey, PooledSession<S>>> {
+    self.sessions.lock().unwrap_or_else(|e| e.into_inner())
+  }
+
+  /// Задать предел одновременных инференсов для всех провайдеров.
+  /// `None` возвращает значения по умолчанию. Уже выполняющиеся инференсы
+  /// дорабатывают по старому пределу.
+  pub fn set_max_concurrent_inferences(&self, limit: Option<usize>) {
+    *self
+      .max_concurrent
+      .lock()
+      .unwrap_or_else(|e| e.into_inner()) = limit.map(|l| l.max(1));
+    self
+      .limiters
+      .lock()
+      .unwrap_or_else(|e| e.into_inner())
+      .clear();
+  }
+
+  fn limiter(&self, provider: ExecutionProvider) -> Arc<Semaphore> {
+    let limit = *self
+      .max_concurrent
+      .lock()
+      .unwrap_or_else(|e| e.into_inner());
+    self
+      .limiters
+      .lock()
+      .unwrap_or_else(|e| e.into_inner())
+      .entry(provider)
+      .or_insert_with(|| {
+        Arc::new(Semaphore::new(
+          limit.unwrap_or_else(|| default_concurrency(provider)),
+        ))
+      })
+      .clone()
+  }
+
+  /// Сессия для ключа; отсутствующая создается `load`.
+  ///
+  /// Загрузка выполняется под блокировкой пула, чтобы одновременные запросы
+  /// не загружали одну модель дважды.
+  pub fn get_or_load(
+    &self,
+    key: &SessionKey,
+    load: impl FnOnce() -> Result<S>,
+  ) -> Result<Arc<Mutex<S>>> {
+    self.evict_idle();
+
+    let mut sessions = self.lock_sessions();
+    if let Some(pooled) = sessions.get_mut(key) {
+      pooled.last_used = Instant::now();
+      return Ok(pooled.session.clone());
+    }
+
+    let session = Arc::new(Mutex::new(load()?));
+    let memory_bytes = std::fs::metadata(&key.model_path)
+      .map(|metadata| metadata.len())
+      .unwrap_or(0);
+    log::info!(
+      "Загружена сессия модели {:?} ({:?})",
+      key.model_path,
+      key.provider
+    );
+    sessions.insert(
+      key.clone(),
+      PooledSession {
+        session: session.clone(),
+        memory_bytes,
+        last_used: Instant::now(),
+      },
+    );
+    Ok(session)
+  }
+
+  /// Выполнить инференс на сессии ключа с учетом предела одновременных инференсов
+  pub async fn run<T>(
+    &self,
+    key: &SessionKey,
+    load: impl FnOnce() -> Result<S>,
+    infer: impl FnOnce(&mut S) -> Result<T>,
+  ) -> Result<T> {
+    let session = self.get_or_load(key, load)?;
+    let limiter = self.limiter(key.provider);
+
+    let permit = {
+      let _queued = CounterGuard::enter(&self.queued);
+      limiter
+        .acquire_owned()
+        .await
+        .map_err(|_| anyhow!("Inference limiter closed"))?
+    };
+
+    let _active = CounterGuard::enter(&self.active);
+    let started = Instant::now();
+    let result = {
+      let mut session = session.lock().await;
+      infer(&mut session)
+    };
+    drop(permit);
+
+    self.inferences.fetch_add(1, Ordering::SeqCst);
+    self
+      .inference_micros
+      .fetch_add(started.elapsed().as_micros() as u64, Ordering::SeqCst);
+    if let Some(pooled) = self.lock_sessions().get_mut(key) {
+      pooled.last_used = Instant::now();
+    }
+    result
+  }
+
+  /// Выгрузить сессии, простаивающие дольше `idle_ttl` и не занятые инференсом.
+  /// Возвращает число выгруженных сессий.
+  pub fn evict_idle(&self) -> usize {
+    let mut sessions = self.lock_sessions();
+    let before = sessions.len();
+    sessions.retain(|_, pooled| {
+      pooled.last_used.elapsed() < self.idle_ttl || Arc::strong_count(&pooled.session) > 1
+    });
+    before - sessions.len()
+  }
+
+  /// Выгрузить все сессии. Выполняющиеся инференсы завершаются, после чего
+  /// память сессии освобождается. Возвращает число выгруженных сессий.
+  pub fn unload_all(&self) -> usize {
+    let mut sessions = self.lock_sessions();
+    let count = sessions.len();
+    sessions.clear();
+    count
+  }
+
+  /// Статистика пула
+  pub fn stats(&self) -> RecognitionRuntimeStats {
+    let sessions: Vec<LoadedSessionInfo> = self
+      .lock_sessions()
+      .iter()
+      .map(|(key, pooled)| LoadedSessionInfo {
+        model_path: key.model_path.clone(),
+        provider: key.provider,
+        memory_bytes: pooled.memory_bytes,
+        idle_secs: pooled.last_used.elapsed().as_secs_f64(),
+      })
+      .collect();
+
+    let total_inferences = self.inferences.load(Ordering::SeqCst);
+    let average_inference_ms = if total_inferences == 0 {
+      0.0
+    } else {
+      self.inference_micros.load(Ordering::SeqCst) as f64 / total_inferences as f64 / 1000.0
+    };
+
+    RecognitionRuntimeStats {
+      memory_estimate_bytes: sessions.iter().map(|s| s.memory_bytes).sum(),
+      sessions,
+      queue_depth: self.queued.load(Ordering::SeqCst),
+      active_inferences: self.active.load(Ordering::SeqCst),
+      max_concurrent_inferences: *self
+        .max_concurrent
+        .lock()
+        .unwrap_or_else(|e| e.into_inner()),
+      total_inferences,
+      average_inference_ms,
+    }
+  }
+}
+
+impl<S: Send + 'static> SessionPool<S> {
+  /// Периодически выгружать простаивающие сессии, пока пул существует
+  pub fn spawn_idle_reaper(self: &Arc<Self>) {
+    let pool = Arc::downgrade(self);
+    let period = (self.idle_ttl / 2).max(Duration::from_secs(1));
+    tauri::async_runtime::spawn(async move {
+      let mut interval = tokio::time::interval(period);
+      loop {
+        interval.tick().await;
+        let Some(pool) = pool.upgrade() else {
+          break;
+        };
+        let evicted = pool.evict_idle();
+        if evicted > 0 {
+          log::info!("Выгружено простаивающих сессий моделей: {evicted}");
+        }
+      }
+    });
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Сессия для тестов без ONNX Runtime
+  #[derive(Debug, Default)]
+  struct FakeSession {
+    runs: usize,
+  }
+
+  fn key(name: &str) -> SessionKey {
+    SessionKey::new(format!("/models/{name}.onnx"), ExecutionProvider::Cpu)
+  }
+
+  #[tokio::test]
+  async fn test_sessions_are_loaded_once_and_reused() {
+    let pool = SessionPool::<FakeSession>::new(DEFAULT_IDLE_TTL);
+    let mut loads = 0;
+
+    for _ in 0..3 {
+      pool
+        .run(
+          &key("a"),
+          || {
+            loads += 1;
+            Ok(FakeSession::default())
+          },
+          |session| {
+            session.runs += 1;
+            Ok(())
+          },
+        )
+        .await
+        .unwrap();
+    }
+
+    assert_eq!(loads, 1);
+    let session = pool.get_or_load(&key("a"), || unreachable!()).unwrap();
+    assert_eq!(session.lock().await.runs, 3);
+
+    let stats = pool.stats();
+    assert_eq!(stats.sessions.len(), 1);
+    assert_eq!(stats.total_inferences, 3);
+    assert_eq!(stats.queue_depth, 0);
+  }
+
+  #[tokio::test]
+  async fn test_idle_sessions_are_evicted() {
+    let pool = SessionPool::<FakeSession>::new(Duration::from_millis(20));
+    pool
+      .get_or_load(&key("a"), || Ok(FakeSession::default()))
+      .unwrap();
+
+    // Занятая сессия не выгружается
+    let held = pool
+      .get_or_load(&key("b"), || Ok(FakeSession::default()))
+      .unwrap();
+    tokio::time::sleep(Duration::from_millis(40)).await;
+    assert_eq!(pool.evict_idle(), 1);
+    assert_eq!(pool.stats().sessions.len(), 1);
+
+    drop(held);
+    assert_eq!(pool.evict_idle(), 1);
+    assert!(pool.stats().sessions.is_empty());
+  }
+
+  #[tokio::test]
+  async fn test_unload_all() {
+    let pool = SessionPool::<FakeSession>::new(DEFAULT_IDLE_TTL);
+    pool
+      .get_or_load(&key("a"), || Ok(FakeSession::default()))
+      .unwrap();
+    pool
+      .get_or_load(&key("b"), || Ok(FakeSession::default()))
+      .unwrap();
+
+    assert_eq!(pool.unload_all(), 2);
+    assert!(pool.stats().sessions.is_empty());
+  }
+
+  #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+  async fn test_inferences_beyond_cap_are_serialized() {
+    let pool = Arc::new(SessionPool::<FakeSession>::new(DEFAULT_IDLE_TTL));
+    pool.set_max_concurrent_inferences(Some(2));
+
+    let running = Arc::new(AtomicUsize::new(0));
+    let peak = Arc::new(AtomicUsize::new(0));
+    let max_queue = Arc::new(AtomicUsize::new(0));
+
+    let mut tasks = Vec::new();
+    for i in 0..6 {
+      // Разные модели: ограничивает только семафор, а не блокировка сессии
+      let (pool, running, peak, max_queue) = (
+        pool.clone(),
+        running.clone(),
+        peak.clone(),
+        max_queue.clone(),
+      );
+      tasks.push(tokio::spawn(async move {
+        let observer = pool.clone();
+        pool
+          .run(
+            &key(&format!("model_{i}")),
+            || Ok(FakeSession::default()),
+            |_| {
+              let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+              peak.fetch_max(now, Ordering::SeqCst);
+              max_queue.fetch_max(observer.stats().queue_depth, Ordering::SeqCst);
+              std::thread::sleep(Duration::from_millis(50));
+              running.fetch_sub(1, Ordering::SeqCst);
+              Ok(())
+            },
+          )
+          .await
+      }));
+    }
+    for task in tasks {
+      task.await.unwrap().unwrap();
+    }
+
+    assert_eq!(peak.load(Ordering::SeqCst), 2);
+    assert!(max_queue.load(Ordering::SeqCst) > 0);
+    let stats = pool.stats();
+    assert_eq!(stats.total_inferences, 6);
+    assert_eq!(stats.queue_depth, 0);
+    assert_eq!(stats.active_inferences, 0);
+  }
+
+  #[test]
+  fn test_default_concurrency() {
+    assert!(default_concurrency(ExecutionProvider::Cpu) >= 1);
+    assert_eq!(
+      default_concurrency(ExecutionProvider::Cuda),
+      GPU_CONCURRENCY
+    );
+  }
+}
@@ -524,6 +524,8 @@ async fn test_save_and_load_results() {
     faces: vec![],
     scenes: vec![],
     processed_at: chrono::Utc::now(),
+    config: None,
+    frames: Vec::new(),
   };
 
   let file_id = "test_file_123";
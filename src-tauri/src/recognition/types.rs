@@ -1,5 +1,8 @@
+use anyhow::{bail, Result};
 use serde::{Deserialize, Serialize};
 
+use super::yolo_processor::ExecutionProvider;
+
 /// Результаты распознавания
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RecognitionResults {
@@ -14,6 +17,92 @@ pub struct RecognitionResults {
 
   /// Время обработки
   pub processed_at: chrono::DateTime<chrono::Utc>,
+
+  /// Параметры, с которыми получены результаты
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub config: Option<RecognitionConfig>,
+
+  /// Обработанные кадры: соответствие метки времени кадру и размеры кадра
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub frames: Vec<RecognizedFrame>,
+}
+
+/// Кадр, на котором выполнялось распознавание
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecognizedFrame {
+  /// Порядковый номер кадра
+  pub index: usize,
+
+  /// Метка времени кадра в видео
+  pub timestamp: f64,
+
+  /// Ширина кадра в пикселях
+  pub width: u32,
+
+  /// Высота кадра в пикселях
+  pub height: u32,
+}
+
+/// Параметры запуска распознавания
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecognitionConfig {
+  /// Минимальная уверенность детекции (0.0 - 1.0)
+  pub confidence_threshold: f32,
+
+  /// IoU порог для NMS (0.0 - 1.0)
+  pub iou_threshold: f32,
+
+  /// Максимальное количество детекций на кадр
+  pub max_detections: usize,
+
+  /// Провайдер выполнения ONNX сессии
+  pub execution_provider: ExecutionProvider,
+
+  /// Количество кадров, обрабатываемых детектором за один захват
+  pub batch_size: usize,
+}
+
+impl RecognitionConfig {
+  /// Проверить корректность параметров
+  pub fn validate(&self) -> Result<()> {
+    let mut errors = Vec::new();
+
+    if !(0.0..=1.0).contains(&self.confidence_threshold) {
+      errors.push(format!(
+        "confidence_threshold must be within 0..1, got {}",
+        self.confidence_threshold
+      ));
+    }
+    if !(0.0..=1.0).contains(&self.iou_threshold) {
+      errors.push(format!(
+        "iou_threshold must be within 0..1, got {}",
+        self.iou_threshold
+      ));
+    }
+    if self.max_detections == 0 {
+      errors.push("max_detections must be greater than 0".to_string());
+    }
+    if self.batch_size == 0 {
+      errors.push("batch_size must be greater than 0".to_string());
+    }
+
+    if !errors.is_empty() {
+      bail!("Invalid recognition config: {}", errors.join("; "));
+    }
+    Ok(())
+  }
+}
+
+impl Default for RecognitionConfig {
+  fn default() -> Self {
+    Self {
+      confidence_threshold: 0.5,
+      iou_threshold: 0.45,
+      max_detections: 100,
+      execution_provider: ExecutionProvider::Cpu,
+      batch_size: 1,
+    }
+  }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -77,6 +166,8 @@ impl Default for RecognitionResults {
       faces: Vec::new(),
       scenes: Vec::new(),
       processed_at: chrono::Utc::now(),
+      config: None,
+      frames: Vec::new(),
     }
   }
 }
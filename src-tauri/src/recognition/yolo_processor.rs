@@ -1,11 +1,18 @@
 use anyhow::{anyhow, Result};
 use image::{imageops::FilterType, DynamicImage, GenericImageView};
-use ort::session::{builder::GraphOptimizationLevel, Session, SessionOutputs};
+use ort::execution_providers::{
+  CPUExecutionProvider, CUDAExecutionProvider, CoreMLExecutionProvider, DirectMLExecutionProvider,
+  ExecutionProviderDispatch,
+};
+use ort::session::{Session, SessionOutputs};
 use ort::value::Tensor;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use std::sync::{Mutex, Once};
 
+use super::session_pool::{build_session, SessionKey, SessionPool};
+use super::types::RecognitionConfig;
+
 // Инициализация ORT
 static INIT: Once = Once::new();
 static INIT_RESULT: Mutex<Option<bool>> = Mutex::new(None);
@@ -65,6 +72,49 @@ pub enum YoloModel {
   Custom(PathBuf),
 }
 
+impl YoloModel {
+  /// Модель по имени из команды; неизвестное имя - путь к пользовательской модели
+  pub fn from_name(name: &str) -> Self {
+    match name {
+      "yolov11-detection" => YoloModel::YoloV11Detection,
+      "yolov8-detection" => YoloModel::YoloV8Detection,
+      "yolov11-face" => YoloModel::YoloV11Face,
+      "yolov8-face" => YoloModel::YoloV8Face,
+      "yolov11-segmentation" => YoloModel::YoloV11Segmentation,
+      "yolov8-segmentation" => YoloModel::YoloV8Segmentation,
+      custom => YoloModel::Custom(custom.into()),
+    }
+  }
+}
+
+/// Провайдер выполнения ONNX сессии
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum ExecutionProvider {
+  /// Инференс на CPU
+  #[default]
+  Cpu,
+  /// NVIDIA CUDA
+  Cuda,
+  /// Apple CoreML
+  CoreMl,
+  /// DirectML (Windows)
+  DirectML,
+}
+
+impl ExecutionProvider {
+  /// Провайдер ORT для сборки сессии.
+  ///
+  /// Недоступный в системе провайдер ORT пропускает и выполняет модель на CPU.
+  pub(crate) fn dispatch(self) -> ExecutionProviderDispatch {
+    match self {
+      ExecutionProvider::Cpu => CPUExecutionProvider::default().build(),
+      ExecutionProvider::Cuda => CUDAExecutionProvider::default().build(),
+      ExecutionProvider::CoreMl => CoreMLExecutionProvider::default().build(),
+      ExecutionProvider::DirectML => DirectMLExecutionProvider::default().build(),
+    }
+  }
+}
+
 /// Результат обнаружения
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Detection {
@@ -102,8 +152,8 @@ pub struct FaceAttributes {
 
 /// Процессор YOLO для распознавания объектов
 pub struct YoloProcessor {
-  /// ONNX сессия
-  session: Option<Session>,
+  /// Ключ загруженной сессии в общем пуле
+  session_key: Option<SessionKey>,
   /// Путь к модели
   model_path: PathBuf,
   /// Тип модели
@@ -114,6 +164,10 @@ pub struct YoloProcessor {
   target_classes: Vec<String>,
   /// IoU порог для NMS
   iou_threshold: f32,
+  /// Максимальное количество детекций на кадр
+  max_detections: usize,
+  /// Провайдер выполнения для следующей сборки сессии
+  execution_provider: ExecutionProvider,
 }
 
 impl YoloProcessor {
@@ -132,64 +186,39 @@ impl YoloProcessor {
     };
 
     Ok(Self {
-      session: None,
+      session_key: None,
       model_path,
       model_type,
       confidence_threshold,
       target_classes: Vec::new(),
       iou_threshold: 0.45,
+      max_detections: 100,
+      execution_provider: ExecutionProvider::default(),
     })
   }
 
-  /// Загрузить модель
+  /// Загрузить модель.
+  ///
+  /// Сессия берется из общего пула: процессоры с той же моделью и
+  /// провайдером переиспользуют одну загруженную сессию.
   pub async fn load_model(&mut self) -> Result<()> {
-    if self.model_path.exists() {
-      // Инициализируем ORT с tract backend перед созданием сессии
-      init_ort()?;
-
-      // В тестах пропускаем загрузку модели если ORT недоступен
-      if cfg!(test) {
-        match Session::builder() {
-          Ok(builder) => {
-            // Создаем ONNX сессию
-            let session = builder
-              .with_optimization_level(GraphOptimizationLevel::Level3)
-              .map_err(|e| anyhow!("Failed to set optimization level: {}", e))?
-              .with_intra_threads(4)
-              .map_err(|e| anyhow!("Failed to set intra threads: {}", e))?
-              .commit_from_memory(
-                &std::fs::read(&self.model_path)
-                  .map_err(|e| anyhow!("Failed to read model file: {}", e))?,
-              )
-              .map_err(|e| anyhow!("Failed to load model from memory: {}", e))?;
-            self.session = Some(session);
-          }
-          Err(_) => {
-            // В тестах игнорируем отсутствие ORT
-            eprintln!("Warning: Skipping model load in test mode due to missing ONNX Runtime");
-          }
-        }
-        Ok(())
-      } else {
-        // В production всегда требуем ORT
-        let session = Session::builder()
-          .map_err(|e| anyhow!("Failed to create session builder: {}", e))?
-          .with_optimization_level(GraphOptimizationLevel::Level3)
-          .map_err(|e| anyhow!("Failed to set optimization level: {}", e))?
-          .with_intra_threads(4)
-          .map_err(|e| anyhow!("Failed to set intra threads: {}", e))?
-          .commit_from_memory(
-            &std::fs::read(&self.model_path)
-              .map_err(|e| anyhow!("Failed to read model file: {}", e))?,
-          )
-          .map_err(|e| anyhow!("Failed to load model from memory: {}", e))?;
+    if !self.model_path.exists() {
+      return Err(anyhow!("Model file not found: {:?}", self.model_path));
+    }
 
-        self.session = Some(session);
-        Ok(())
-      }
-    } else {
-      Err(anyhow!("Model file not found: {:?}", self.model_path))
+    // Инициализируем ORT с tract backend перед созданием сессии
+    init_ort()?;
+
+    // В тестах пропускаем загрузку модели если ORT недоступен
+    if cfg!(test) && Session::builder().is_err() {
+      eprintln!("Warning: Skipping model load in test mode due to missing ONNX Runtime");
+      return Ok(());
     }
+
+    let key = SessionKey::new(&self.model_path, self.execution_provider);
+    SessionPool::shared().get_or_load(&key, || build_session(&key))?;
+    self.session_key = Some(key);
+    Ok(())
   }
 
   /// Установить целевые классы для обнаружения
@@ -197,13 +226,37 @@ impl YoloProcessor {
     self.target_classes = classes;
   }
 
+  /// Применить параметры распознавания.
+  ///
+  /// Смена провайдера выполнения не пересобирает сессию сразу:
+  /// это происходит при следующем инференсе.
+  pub fn apply_config(&mut self, config: &RecognitionConfig) {
+    self.confidence_threshold = config.confidence_threshold;
+    self.iou_threshold = config.iou_threshold;
+    self.max_detections = config.max_detections;
+    self.execution_provider = config.execution_provider;
+  }
+
+  /// Требуется ли пересборка загруженной сессии под текущий провайдер
+  fn needs_session_rebuild(&self) -> bool {
+    self
+      .session_key
+      .as_ref()
+      .is_some_and(|key| key.provider != self.execution_provider)
+  }
+
   /// Обработать изображение
   pub async fn process_image(&mut self, image_path: &Path) -> Result<Vec<Detection>> {
     // Проверяем, загружена ли модель
-    if self.session.is_none() {
+    if self.session_key.is_none() {
       return Err(anyhow!("Model not loaded. Call load_model() first"));
     }
 
+    // Провайдер сменился после загрузки модели - пересобираем сессию
+    if self.needs_session_rebuild() {
+      self.load_model().await?;
+    }
+
     // Загружаем изображение
     let image = image::open(image_path)?;
     let (orig_width, orig_height) = image.dimensions();
@@ -213,25 +266,30 @@ impl YoloProcessor {
 
     // Выполняем инференс
     // ort::inputs! больше не возвращает Result в v2.0.0-rc.10
-    let inputs = ort::inputs!["images" => input_tensor];
-    let outputs = self
-      .session
-      .as_mut()
-      .unwrap()
-      .run(inputs)
-      .map_err(|e| anyhow!("Failed to run inference: {}", e))?;
-
-    // Обрабатываем результаты без заимствования self
-    let detections = Self::postprocess_output_static(
-      &outputs,
-      orig_width,
-      orig_height,
-      self.confidence_threshold,
-      &self.target_classes,
-      self.iou_threshold,
-    )?;
-
-    Ok(detections)
+    // Выгруженная по простою сессия загружается пулом заново
+    let key = self.session_key.clone().unwrap();
+    SessionPool::shared()
+      .run(
+        &key,
+        || build_session(&key),
+        |session| {
+          let inputs = ort::inputs!["images" => input_tensor];
+          let outputs = session
+            .run(inputs)
+            .map_err(|e| anyhow!("Failed to run inference: {}", e))?;
+
+          Self::postprocess_output_static(
+            &outputs,
+            orig_width,
+            orig_height,
+            self.confidence_threshold,
+            &self.target_classes,
+            self.iou_threshold,
+            self.max_detections,
+          )
+        },
+      )
+      .await
   }
 
   /// Предобработка изображения
@@ -265,6 +323,7 @@ impl YoloProcessor {
     confidence_threshold: f32,
     target_classes: &[String],
     iou_threshold: f32,
+    max_detections: usize,
   ) -> Result<Vec<Detection>> {
     // Получаем выходной тензор
     let output = outputs
@@ -343,8 +402,9 @@ impl YoloProcessor {
       });
     }
 
-    // Применяем NMS
-    let filtered = Self::apply_nms_static(detections, iou_threshold);
+    // Применяем NMS; детекции отсортированы по уверенности
+    let mut filtered = Self::apply_nms_static(detections, iou_threshold);
+    filtered.truncate(max_detections);
 
     Ok(filtered)
   }
@@ -770,6 +830,26 @@ mod tests {
     assert_eq!(processor.target_classes, classes);
   }
 
+  #[test]
+  fn test_apply_config() {
+    let mut processor = YoloProcessor::new(YoloModel::YoloV11Detection, 0.5).unwrap();
+    let config = RecognitionConfig {
+      confidence_threshold: 0.25,
+      iou_threshold: 0.6,
+      max_detections: 10,
+      execution_provider: ExecutionProvider::Cuda,
+      batch_size: 8,
+    };
+    processor.apply_config(&config);
+
+    assert_eq!(processor.confidence_threshold, 0.25);
+    assert_eq!(processor.iou_threshold, 0.6);
+    assert_eq!(processor.max_detections, 10);
+    assert_eq!(processor.execution_provider, ExecutionProvider::Cuda);
+    // Сессия не загружена - пересобирать нечего
+    assert!(!processor.needs_session_rebuild());
+  }
+
   #[test]
   fn test_calculate_iou() {
     let box1 = BoundingBox {
@@ -70,15 +70,13 @@ impl YoloProcessor {
     let input_tensor = self.frame_processor.preprocess_image(image)?;
 
     // Инференс и постобработка
-    let detections = {
-      let mut model_manager = self.model_manager.lock().await;
-      let session = model_manager.get_session_mut()?;
-      let outputs = session.run(ort::inputs![input_tensor])?;
-      self
-        .frame_processor
-        .postprocess_output(&outputs[0], image)?
-    };
-    Ok(detections)
+    let model_manager = self.model_manager.lock().await;
+    model_manager
+      .run(|session| {
+        let outputs = session.run(ort::inputs![input_tensor])?;
+        self.frame_processor.postprocess_output(&outputs[0], image)
+      })
+      .await
   }
 
   /// Обработать видео из файла
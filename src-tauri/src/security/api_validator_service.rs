@@ -1,31 +1,519 @@
 //! API Validator Service - пример интеграции с DI Container
 //!
-//! Этот модуль демонстрирует как использовать DI Container для сервисов
+//! Этот модуль демонстрирует как использовать DI Container для сервисов.
+//! Кроме проверки формата сервис выполняет живую проверку ключей у
+//! провайдеров (OpenAI, Anthropic, Google/YouTube) через [`ProviderValidator`]
+//! и кэширует результат на настраиваемый TTL.
 
 use crate::core::{AppEvent, EventBus, Service};
 use crate::security::api_validator::ApiValidator;
 use crate::security::ApiKeyType;
 use crate::video_compiler::error::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 
+/// Время жизни результата живой проверки по умолчанию
+pub const DEFAULT_LIVE_VALIDATION_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Тайм-аут запроса к провайдеру
+const LIVE_VALIDATION_TIMEOUT: Duration = Duration::from_secs(10);
+
+const OPENAI_MODELS_URL: &str = "https://api.openai.com/v1/models";
+const ANTHROPIC_MODELS_URL: &str = "https://api.anthropic.com/v1/models?limit=1";
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+const GOOGLE_TOKENINFO_URL: &str = "https://oauth2.googleapis.com/tokeninfo";
+
+/// Scopes, дающие доступ к YouTube API
+const YOUTUBE_SCOPE_PREFIX: &str = "https://www.googleapis.com/auth/youtube";
+
+/// Ответ HTTP, достаточный для проверки ключа
+#[derive(Debug, Clone, Default)]
+pub struct HttpResponse {
+  pub status: u16,
+  /// Заголовки с именами в нижнем регистре
+  pub headers: HashMap<String, String>,
+  pub body: String,
+}
+
+/// HTTP клиент живой проверки. Подменяется в тестах, чтобы не ходить в сеть.
+///
+/// Ошибка означает, что провайдер недоступен; текст ошибки не должен
+/// содержать URL или заголовки запроса.
+#[async_trait]
+pub trait ValidationHttpClient: Send + Sync {
+  async fn get(
+    &self,
+    url: &str,
+    headers: &[(&str, String)],
+  ) -> std::result::Result<HttpResponse, String>;
+}
+
+/// HTTP клиент на reqwest
+pub struct ReqwestValidationClient {
+  client: reqwest::Client,
+}
+
+impl ReqwestValidationClient {
+  pub fn new() -> Self {
+    let client = reqwest::Client::builder()
+      .timeout(LIVE_VALIDATION_TIMEOUT)
+      .build()
+      .unwrap_or_default();
+    Self { client }
+  }
+}
+
+impl Default for ReqwestValidationClient {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+#[async_trait]
+impl ValidationHttpClient for ReqwestValidationClient {
+  async fn get(
+    &self,
+    url: &str,
+    headers: &[(&str, String)],
+  ) -> std::result::Result<HttpResponse, String> {
+    let mut request = self.client.get(url);
+    for (name, value) in headers {
+      request = request.header(*name, value);
+    }
+
+    // without_url: URL может содержать токен (tokeninfo?access_token=...)
+    let response = request
+      .send()
+      .await
+      .map_err(|e| e.without_url().to_string())?;
+    let status = response.status().as_u16();
+    let headers = response
+      .headers()
+      .iter()
+      .filter_map(|(name, value)| {
+        value
+          .to_str()
+          .ok()
+          .map(|value| (name.as_str().to_lowercase(), value.to_string()))
+      })
+      .collect();
+    let body = response
+      .text()
+      .await
+      .map_err(|e| e.without_url().to_string())?;
+
+    Ok(HttpResponse {
+      status,
+      headers,
+      body,
+    })
+  }
+}
+
+/// Итог живой проверки
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyValidity {
+  Valid,
+  Invalid,
+  /// Провайдер недоступен или ответил неоднозначно
+  Unknown,
+}
+
+/// Лимиты запросов из заголовков ответа провайдера
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RateLimitHeaders {
+  pub requests_limit: Option<u64>,
+  pub requests_remaining: Option<u64>,
+  pub tokens_limit: Option<u64>,
+  pub tokens_remaining: Option<u64>,
+  /// Время сброса лимита запросов в формате провайдера
+  pub requests_reset: Option<String>,
+  pub retry_after: Option<String>,
+  /// Все заголовки лимитов как есть
+  pub raw: BTreeMap<String, String>,
+}
+
+impl RateLimitHeaders {
+  /// Собрать лимиты из заголовков OpenAI (`x-ratelimit-*`) и Anthropic
+  /// (`anthropic-ratelimit-*`). `None`, если заголовков лимитов нет.
+  pub fn from_headers(headers: &HashMap<String, String>) -> Option<Self> {
+    let raw: BTreeMap<String, String> = headers
+      .iter()
+      .filter(|(name, _)| name.contains("ratelimit") || name.as_str() == "retry-after")
+      .map(|(name, value)| (name.clone(), value.clone()))
+      .collect();
+    if raw.is_empty() {
+      return None;
+    }
+
+    let find = |names: &[&str]| names.iter().find_map(|name| raw.get(*name).cloned());
+    let number = |names: &[&str]| find(names).and_then(|value| value.parse().ok());
+
+    Some(Self {
+      requests_limit: number(&[
+        "x-ratelimit-limit-requests",
+        "anthropic-ratelimit-requests-limit",
+      ]),
+      requests_remaining: number(&[
+        "x-ratelimit-remaining-requests",
+        "anthropic-ratelimit-requests-remaining",
+      ]),
+      tokens_limit: number(&[
+        "x-ratelimit-limit-tokens",
+        "anthropic-ratelimit-tokens-limit",
+      ]),
+      tokens_remaining: number(&[
+        "x-ratelimit-remaining-tokens",
+        "anthropic-ratelimit-tokens-remaining",
+      ]),
+      requests_reset: find(&[
+        "x-ratelimit-reset-requests",
+        "anthropic-ratelimit-requests-reset",
+      ]),
+      retry_after: find(&["retry-after"]),
+      raw,
+    })
+  }
+}
+
+/// Результат проверки ключа провайдером
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProviderCheck {
+  pub validity: KeyValidity,
+  pub message: Option<String>,
+  /// Обнаруженные scopes (для OAuth токенов)
+  pub scopes: Vec<String>,
+  pub organization: Option<String>,
+  pub rate_limits: Option<RateLimitHeaders>,
+}
+
+impl ProviderCheck {
+  fn new(validity: KeyValidity, message: impl Into<Option<String>>) -> Self {
+    Self {
+      validity,
+      message: message.into(),
+      scopes: Vec::new(),
+      organization: None,
+      rate_limits: None,
+    }
+  }
+
+  /// Провайдер недоступен: ключ нельзя считать недействительным
+  fn unreachable(error: &str) -> Self {
+    Self::new(
+      KeyValidity::Unknown,
+      format!("Провайдер недоступен: {error}"),
+    )
+  }
+
+  /// Общая трактовка статуса ответа для API с ключом в заголовке
+  fn from_status(response: &HttpResponse) -> Self {
+    let mut check = match response.status {
+      200..=299 => Self::new(KeyValidity::Valid, None),
+      401 | 403 => Self::new(
+        KeyValidity::Invalid,
+        "Ключ отклонен провайдером".to_string(),
+      ),
+      // Ключ принят, но исчерпан лимит запросов или квота
+      429 => Self::new(
+        KeyValidity::Valid,
+        "Превышен лимит запросов или квота".to_string(),
+      ),
+      status => Self::new(
+        KeyValidity::Unknown,
+        format!("Неожиданный ответ провайдера: HTTP {status}"),
+      ),
+    };
+    check.rate_limits = RateLimitHeaders::from_headers(&response.headers);
+    check
+  }
+}
+
+/// Живая проверка ключа конкретного провайдера
+#[async_trait]
+pub trait ProviderValidator: Send + Sync {
+  /// Тип ключа, который проверяет валидатор
+  fn key_type(&self) -> ApiKeyType;
+
+  /// Проверить ключ. Ключ не должен попадать в логи и сообщения.
+  async fn validate(&self, http: &dyn ValidationHttpClient, key: &str) -> ProviderCheck;
+}
+
+/// OpenAI: список моделей
+pub struct OpenAiValidator;
+
+#[async_trait]
+impl ProviderValidator for OpenAiValidator {
+  fn key_type(&self) -> ApiKeyType {
+    ApiKeyType::OpenAI
+  }
+
+  async fn validate(&self, http: &dyn ValidationHttpClient, key: &str) -> ProviderCheck {
+    let headers = [("Authorization", format!("Bearer {key}"))];
+    match http.get(OPENAI_MODELS_URL, &headers).await {
+      Ok(response) => {
+        let mut check = ProviderCheck::from_status(&response);
+        check.organization = response.headers.get("openai-organization").cloned();
+        check
+      }
+      Err(e) => ProviderCheck::unreachable(&e),
+    }
+  }
+}
+
+/// Anthropic: дешевый запрос списка моделей
+pub struct AnthropicValidator;
+
+#[async_trait]
+impl ProviderValidator for AnthropicValidator {
+  fn key_type(&self) -> ApiKeyType {
+    ApiKeyType::Claude
+  }
+
+  async fn validate(&self, http: &dyn ValidationHttpClient, key: &str) -> ProviderCheck {
+    let headers = [
+      ("x-api-key", key.to_string()),
+      ("anthropic-version", ANTHROPIC_VERSION.to_string()),
+    ];
+    match http.get(ANTHROPIC_MODELS_URL, &headers).await {
+      Ok(response) => {
+        let mut check = ProviderCheck::from_status(&response);
+        check.organization = response.headers.get("anthropic-organization-id").cloned();
+        check
+      }
+      Err(e) => ProviderCheck::unreachable(&e),
+    }
+  }
+}
+
+/// Google/YouTube: scopes access token через tokeninfo
+pub struct GoogleTokenValidator;
+
+#[async_trait]
+impl ProviderValidator for GoogleTokenValidator {
+  fn key_type(&self) -> ApiKeyType {
+    ApiKeyType::YouTube
+  }
+
+  async fn validate(&self, http: &dyn ValidationHttpClient, key: &str) -> ProviderCheck {
+    let url = match reqwest::Url::parse_with_params(GOOGLE_TOKENINFO_URL, &[("access_token", key)])
+    {
+      Ok(url) => url,
+      Err(_) => return ProviderCheck::new(KeyValidity::Invalid, "Некорректный токен".to_string()),
+    };
+    let response = match http.get(url.as_str(), &[]).await {
+      Ok(response) => response,
+      Err(e) => return ProviderCheck::unreachable(&e),
+    };
+
+    match response.status {
+      200..=299 => {}
+      // tokeninfo отвечает 400 на просроченный или чужой токен
+      400 | 401 => {
+        return ProviderCheck::new(
+          KeyValidity::Invalid,
+          "Токен недействителен или истек".to_string(),
+        )
+      }
+      status => {
+        return ProviderCheck::new(
+          KeyValidity::Unknown,
+          format!("Неожиданный ответ tokeninfo: HTTP {status}"),
+        )
+      }
+    }
+
+    let info: serde_json::Value = match serde_json::from_str(&response.body) {
+      Ok(info) => info,
+      Err(_) => {
+        return ProviderCheck::new(
+          KeyValidity::Unknown,
+          "Некорректный ответ tokeninfo".to_string(),
+        )
+      }
+    };
+
+    let scopes: Vec<String> = info["scope"]
+      .as_str()
+      .unwrap_or_default()
+      .split_whitespace()
+      .map(str::to_string)
+      .collect();
+    let has_youtube = scopes
+      .iter()
+      .any(|scope| scope.starts_with(YOUTUBE_SCOPE_PREFIX));
+
+    let mut check = if has_youtube {
+      ProviderCheck::new(KeyValidity::Valid, None)
+    } else {
+      ProviderCheck::new(
+        KeyValidity::Invalid,
+        "Токен не содержит YouTube scopes".to_string(),
+      )
+    };
+    check.scopes = scopes;
+    check.organization = info["hd"].as_str().map(str::to_string);
+    check
+  }
+}
+
+/// Результат живой проверки ключа сервиса
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LiveValidationResult {
+  pub service: String,
+  #[serde(flatten)]
+  pub check: ProviderCheck,
+  pub checked_at: DateTime<Utc>,
+  /// Результат взят из кэша
+  pub cached: bool,
+}
+
+/// Закэшированный результат живой проверки
+struct CachedValidation {
+  /// Отпечаток ключа: при смене ключа кэш не используется
+  fingerprint: u64,
+  stored_at: Instant,
+  result: LiveValidationResult,
+}
+
+/// Отпечаток ключа для кэша (ключ не хранится)
+fn key_fingerprint(key: &str) -> u64 {
+  let mut hasher = std::collections::hash_map::DefaultHasher::new();
+  key.hash(&mut hasher);
+  hasher.finish()
+}
+
 /// Сервис валидации API ключей с поддержкой DI
 pub struct ApiValidatorService {
   validator: Arc<RwLock<ApiValidator>>,
   event_bus: Option<Arc<EventBus>>,
   initialized: bool,
+  http: Arc<dyn ValidationHttpClient>,
+  providers: HashMap<ApiKeyType, Arc<dyn ProviderValidator>>,
+  live_cache: RwLock<HashMap<ApiKeyType, CachedValidation>>,
+  cache_ttl: Duration,
 }
 
 impl ApiValidatorService {
   /// Создать новый экземпляр сервиса
   pub fn new() -> Self {
+    let providers: [Arc<dyn ProviderValidator>; 3] = [
+      Arc::new(OpenAiValidator),
+      Arc::new(AnthropicValidator),
+      Arc::new(GoogleTokenValidator),
+    ];
+
     Self {
       validator: Arc::new(RwLock::new(ApiValidator::new())),
       event_bus: None,
       initialized: false,
+      http: Arc::new(ReqwestValidationClient::new()),
+      providers: providers
+        .into_iter()
+        .map(|provider| (provider.key_type(), provider))
+        .collect(),
+      live_cache: RwLock::new(HashMap::new()),
+      cache_ttl: DEFAULT_LIVE_VALIDATION_TTL,
     }
   }
 
+  /// Использовать другой HTTP клиент (в тестах - мок)
+  pub fn with_http_client(mut self, http: Arc<dyn ValidationHttpClient>) -> Self {
+    self.http = http;
+    self
+  }
+
+  /// Зарегистрировать или заменить валидатор провайдера
+  pub fn with_provider(mut self, provider: Arc<dyn ProviderValidator>) -> Self {
+    self.providers.insert(provider.key_type(), provider);
+    self
+  }
+
+  /// Время жизни результатов живой проверки
+  pub fn with_cache_ttl(mut self, ttl: Duration) -> Self {
+    self.cache_ttl = ttl;
+    self
+  }
+
+  /// Поддерживается ли живая проверка для типа ключа
+  pub fn supports_live_validation(&self, key_type: &ApiKeyType) -> bool {
+    self.providers.contains_key(key_type)
+  }
+
+  /// Проверить ключ у провайдера.
+  ///
+  /// Свежий результат для того же ключа берется из кэша, если не указан
+  /// `force`. Сетевые ошибки дают [`KeyValidity::Unknown`], а не `Invalid`.
+  pub async fn validate_live(
+    &self,
+    key_type: ApiKeyType,
+    key: &str,
+    force: bool,
+  ) -> LiveValidationResult {
+    let fingerprint = key_fingerprint(key);
+    if !force {
+      if let Some(cached) = self.live_cache.read().await.get(&key_type) {
+        if cached.fingerprint == fingerprint && cached.stored_at.elapsed() < self.cache_ttl {
+          return LiveValidationResult {
+            cached: true,
+            ..cached.result.clone()
+          };
+        }
+      }
+    }
+
+    let check = match self.providers.get(&key_type) {
+      Some(provider) => provider.validate(self.http.as_ref(), key).await,
+      None => ProviderCheck::new(
+        KeyValidity::Unknown,
+        "Живая проверка для этого сервиса не поддерживается".to_string(),
+      ),
+    };
+    log::info!(
+      "Живая проверка ключа {}: {:?}",
+      key_type.as_str(),
+      check.validity
+    );
+
+    let result = LiveValidationResult {
+      service: key_type.as_str().to_string(),
+      check,
+      checked_at: Utc::now(),
+      cached: false,
+    };
+    self.live_cache.write().await.insert(
+      key_type,
+      CachedValidation {
+        fingerprint,
+        stored_at: Instant::now(),
+        result: result.clone(),
+      },
+    );
+    result
+  }
+
+  /// Последний результат живой проверки, если он еще не устарел
+  pub async fn cached_status(&self, key_type: &ApiKeyType) -> Option<LiveValidationResult> {
+    let cache = self.live_cache.read().await;
+    let cached = cache.get(key_type)?;
+    (cached.stored_at.elapsed() < self.cache_ttl).then(|| LiveValidationResult {
+      cached: true,
+      ..cached.result.clone()
+    })
+  }
+
+  /// Сбросить кэш живой проверки для типа ключа (например, после удаления ключа)
+  pub async fn invalidate_live_status(&self, key_type: &ApiKeyType) {
+    self.live_cache.write().await.remove(key_type);
+  }
+
   /// Установить EventBus для публикации событий
   #[allow(dead_code)]
   pub fn with_event_bus(mut self, event_bus: Arc<EventBus>) -> Self {
@@ -584,3 +584,270 @@ mod integration_tests {
     }
   }
 }
+
+#[cfg(test)]
+mod live_validation_tests {
+  use super::*;
+  use async_trait::async_trait;
+  use std::collections::HashMap;
+  use std::sync::Mutex;
+  use std::time::Duration;
+
+  /// Запрос, записанный мок-клиентом
+  #[derive(Debug, Clone)]
+  struct RecordedRequest {
+    url: String,
+    headers: Vec<(String, String)>,
+  }
+
+  /// HTTP клиент с заранее заданным ответом, не обращающийся к сети
+  struct MockHttpClient {
+    response: std::result::Result<HttpResponse, String>,
+    requests: Mutex<Vec<RecordedRequest>>,
+  }
+
+  impl MockHttpClient {
+    fn responding(status: u16, headers: &[(&str, &str)], body: &str) -> Arc<Self> {
+      Arc::new(Self {
+        response: Ok(HttpResponse {
+          status,
+          headers: headers
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect::<HashMap<_, _>>(),
+          body: body.to_string(),
+        }),
+        requests: Mutex::new(Vec::new()),
+      })
+    }
+
+    fn failing(error: &str) -> Arc<Self> {
+      Arc::new(Self {
+        response: Err(error.to_string()),
+        requests: Mutex::new(Vec::new()),
+      })
+    }
+
+    fn requests(&self) -> Vec<RecordedRequest> {
+      self.requests.lock().unwrap().clone()
+    }
+  }
+
+  #[async_trait]
+  impl ValidationHttpClient for MockHttpClient {
+    async fn get(
+      &self,
+      url: &str,
+      headers: &[(&str, String)],
+    ) -> std::result::Result<HttpResponse, String> {
+      self.requests.lock().unwrap().push(RecordedRequest {
+        url: url.to_string(),
+        headers: headers
+          .iter()
+          .map(|(name, value)| (name.to_string(), value.clone()))
+          .collect(),
+      });
+      self.response.clone()
+    }
+  }
+
+  fn service_with(http: Arc<MockHttpClient>) -> ApiValidatorService {
+    ApiValidatorService::new().with_http_client(http)
+  }
+
+  #[tokio::test]
+  async fn test_openai_valid_key_reports_organization_and_rate_limits() {
+    let http = MockHttpClient::responding(
+      200,
+      &[
+        ("openai-organization", "org-timeline"),
+        ("x-ratelimit-limit-requests", "5000"),
+        ("x-ratelimit-remaining-requests", "4999"),
+        ("x-ratelimit-remaining-tokens", "159000"),
+        ("x-ratelimit-reset-requests", "12ms"),
+      ],
+      "{\"data\": []}",
+    );
+    let service = service_with(http.clone());
+
+    let result = service
+      .validate_live(ApiKeyType::OpenAI, "sk-test-openai", false)
+      .await;
+
+    assert_eq!(result.service, "openai");
+    assert_eq!(result.check.validity, KeyValidity::Valid);
+    assert_eq!(result.check.organization.as_deref(), Some("org-timeline"));
+    let limits = result.check.rate_limits.unwrap();
+    assert_eq!(limits.requests_limit, Some(5000));
+    assert_eq!(limits.requests_remaining, Some(4999));
+    assert_eq!(limits.tokens_remaining, Some(159000));
+    assert_eq!(limits.requests_reset.as_deref(), Some("12ms"));
+
+    let requests = http.requests();
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0].url.ends_with("/v1/models"));
+    assert_eq!(
+      requests[0].headers,
+      vec![(
+        "Authorization".to_string(),
+        "Bearer sk-test-openai".to_string()
+      )]
+    );
+  }
+
+  #[tokio::test]
+  async fn test_rejected_key_is_invalid_and_quota_exceeded_is_valid() {
+    let rejected = service_with(MockHttpClient::responding(401, &[], ""))
+      .validate_live(ApiKeyType::OpenAI, "sk-revoked", false)
+      .await;
+    assert_eq!(rejected.check.validity, KeyValidity::Invalid);
+
+    let throttled = service_with(MockHttpClient::responding(
+      429,
+      &[("retry-after", "20")],
+      "",
+    ))
+    .validate_live(ApiKeyType::OpenAI, "sk-busy", false)
+    .await;
+    assert_eq!(throttled.check.validity, KeyValidity::Valid);
+    assert_eq!(
+      throttled.check.rate_limits.unwrap().retry_after.as_deref(),
+      Some("20")
+    );
+  }
+
+  #[tokio::test]
+  async fn test_network_failure_is_unknown_not_invalid() {
+    let service = service_with(MockHttpClient::failing("connection refused"));
+
+    for key_type in [ApiKeyType::OpenAI, ApiKeyType::Claude, ApiKeyType::YouTube] {
+      let result = service.validate_live(key_type, "secret-value", false).await;
+      assert_eq!(result.check.validity, KeyValidity::Unknown);
+    }
+  }
+
+  #[tokio::test]
+  async fn test_anthropic_uses_models_list_with_key_header() {
+    let http = MockHttpClient::responding(
+      200,
+      &[
+        ("anthropic-organization-id", "org-42"),
+        ("anthropic-ratelimit-requests-limit", "50"),
+        ("anthropic-ratelimit-requests-remaining", "49"),
+      ],
+      "{\"data\": []}",
+    );
+    let result = service_with(http.clone())
+      .validate_live(ApiKeyType::Claude, "sk-ant-test", false)
+      .await;
+
+    assert_eq!(result.check.validity, KeyValidity::Valid);
+    assert_eq!(result.check.organization.as_deref(), Some("org-42"));
+    assert_eq!(
+      result.check.rate_limits.unwrap().requests_remaining,
+      Some(49)
+    );
+
+    let request = &http.requests()[0];
+    assert!(request.url.contains("/v1/models"));
+    assert!(request
+      .headers
+      .contains(&("x-api-key".to_string(), "sk-ant-test".to_string())));
+  }
+
+  #[tokio::test]
+  async fn test_youtube_token_scopes_are_verified() {
+    let body = r#"{"scope": "openid https://www.googleapis.com/auth/youtube.upload", "hd": "studio.example"}"#;
+    let result = service_with(MockHttpClient::responding(200, &[], body))
+      .validate_live(ApiKeyType::YouTube, "ya29.token", false)
+      .await;
+    assert_eq!(result.check.validity, KeyValidity::Valid);
+    assert_eq!(
+      result.check.scopes,
+      vec![
+        "openid".to_string(),
+        "https://www.googleapis.com/auth/youtube.upload".to_string()
+      ]
+    );
+    assert_eq!(result.check.organization.as_deref(), Some("studio.example"));
+
+    let no_youtube = service_with(MockHttpClient::responding(
+      200,
+      &[],
+      r#"{"scope": "openid email"}"#,
+    ))
+    .validate_live(ApiKeyType::YouTube, "ya29.token", false)
+    .await;
+    assert_eq!(no_youtube.check.validity, KeyValidity::Invalid);
+
+    let expired = service_with(MockHttpClient::responding(400, &[], "{}"))
+      .validate_live(ApiKeyType::YouTube, "ya29.expired", false)
+      .await;
+    assert_eq!(expired.check.validity, KeyValidity::Invalid);
+  }
+
+  #[tokio::test]
+  async fn test_results_are_cached_per_key_for_ttl() {
+    let http = MockHttpClient::responding(200, &[], "{}");
+    let service = service_with(http.clone());
+
+    let first = service
+      .validate_live(ApiKeyType::OpenAI, "sk-one", false)
+      .await;
+    let second = service
+      .validate_live(ApiKeyType::OpenAI, "sk-one", false)
+      .await;
+    assert!(!first.cached);
+    assert!(second.cached);
+    assert_eq!(http.requests().len(), 1);
+    assert!(service.cached_status(&ApiKeyType::OpenAI).await.is_some());
+
+    // Другой ключ и принудительная проверка обходят кэш
+    service
+      .validate_live(ApiKeyType::OpenAI, "sk-two", false)
+      .await;
+    service
+      .validate_live(ApiKeyType::OpenAI, "sk-two", true)
+      .await;
+    assert_eq!(http.requests().len(), 3);
+
+    let expired = service_with(http.clone()).with_cache_ttl(Duration::ZERO);
+    expired
+      .validate_live(ApiKeyType::OpenAI, "sk-one", false)
+      .await;
+    expired
+      .validate_live(ApiKeyType::OpenAI, "sk-one", false)
+      .await;
+    assert_eq!(http.requests().len(), 5);
+    assert!(expired.cached_status(&ApiKeyType::OpenAI).await.is_none());
+  }
+
+  #[tokio::test]
+  async fn test_result_never_contains_key_material() {
+    let key = "sk-very-secret-material";
+    for http in [
+      MockHttpClient::responding(401, &[], key),
+      MockHttpClient::responding(500, &[], key),
+      MockHttpClient::failing("timeout"),
+    ] {
+      let result = service_with(http)
+        .validate_live(ApiKeyType::OpenAI, key, false)
+        .await;
+      let serialized = serde_json::to_string(&result).unwrap();
+      assert!(!serialized.contains(key), "{serialized}");
+    }
+  }
+
+  #[tokio::test]
+  async fn test_unsupported_service_is_unknown() {
+    let http = MockHttpClient::responding(200, &[], "{}");
+    let service = service_with(http.clone());
+
+    assert!(!service.supports_live_validation(&ApiKeyType::Vimeo));
+    let result = service
+      .validate_live(ApiKeyType::Vimeo, "vimeo-token", false)
+      .await;
+    assert_eq!(result.check.validity, KeyValidity::Unknown);
+    assert!(http.requests().is_empty());
+  }
+}
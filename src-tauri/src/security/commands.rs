@@ -5,6 +5,7 @@ use tauri::{AppHandle, State};
 use tokio::sync::Mutex;
 
 use super::api_validator::{ApiValidator, ValidationResult};
+use super::api_validator_service::{ApiValidatorService, KeyValidity, LiveValidationResult};
 use super::env_importer::EnvImporter;
 use super::oauth_handler::OAuthHandler;
 use super::{ApiKeyData, ApiKeyType, OAuthCredentials, SecureStorage};
@@ -274,6 +275,96 @@ pub async fn validate_api_key(
   }
 }
 
+/// Статус API ключа для экрана настроек
+#[derive(Debug, Serialize)]
+pub struct ApiKeyStatus {
+  pub service: String,
+  pub has_key: bool,
+  pub supports_live_validation: bool,
+  pub last_validated: Option<String>,
+  pub is_valid: Option<bool>,
+  /// Последняя живая проверка, если ее результат еще не устарел
+  pub live: Option<LiveValidationResult>,
+}
+
+/// Значение, которое проверяется у провайдера: для OAuth сервисов - access token
+fn live_validation_key(key_data: &ApiKeyData) -> Option<String> {
+  match &key_data.oauth_data {
+    Some(oauth) => oauth.access_token.clone(),
+    None => Some(key_data.value.clone()).filter(|value| !value.is_empty()),
+  }
+}
+
+/// Проверяет API ключ у провайдера (запрос к API сервиса).
+///
+/// Результат кэшируется, `force` выполняет проверку заново. Недоступность
+/// провайдера дает статус "unknown" и не меняет сохраненный статус ключа.
+#[tauri::command]
+pub async fn validate_api_key_live(
+  storage: SecureStorageState<'_>,
+  validator: State<'_, ApiValidatorService>,
+  service: String,
+  force: Option<bool>,
+) -> Result<LiveValidationResult, String> {
+  let key_type = ApiKeyType::from_str(&service).map_err(|_| "Invalid key type".to_string())?;
+
+  let key_data = storage
+    .lock()
+    .await
+    .get_api_key(key_type.clone())
+    .await
+    .map_err(|e| format!("Failed to get API key: {e}"))?
+    .ok_or_else(|| "API key not found".to_string())?;
+  let key =
+    live_validation_key(&key_data).ok_or_else(|| "API key has no value to validate".to_string())?;
+
+  let result = validator
+    .validate_live(key_type.clone(), &key, force.unwrap_or(false))
+    .await;
+
+  if !result.cached && result.check.validity != KeyValidity::Unknown {
+    if let Err(e) = storage
+      .lock()
+      .await
+      .update_validation_status(key_type, result.check.validity == KeyValidity::Valid)
+      .await
+    {
+      log::warn!("Failed to update validation status: {e}");
+    }
+  }
+
+  Ok(result)
+}
+
+/// Получает статус API ключа без обращения к провайдеру
+#[tauri::command]
+pub async fn get_api_key_status(
+  storage: SecureStorageState<'_>,
+  validator: State<'_, ApiValidatorService>,
+  service: String,
+) -> Result<ApiKeyStatus, String> {
+  let key_type = ApiKeyType::from_str(&service).map_err(|_| "Invalid key type".to_string())?;
+
+  let key_data = storage
+    .lock()
+    .await
+    .get_api_key(key_type.clone())
+    .await
+    .map_err(|e| format!("Failed to get API key: {e}"))?;
+
+  Ok(ApiKeyStatus {
+    service: key_type.as_str().to_string(),
+    has_key: key_data.as_ref().and_then(live_validation_key).is_some(),
+    supports_live_validation: validator.supports_live_validation(&key_type),
+    last_validated: key_data
+      .as_ref()
+      .and_then(|data| data.last_validated)
+      .map(|time| time.to_rfc3339()),
+    is_valid: key_data.as_ref().and_then(|data| data.is_valid),
+    live: validator.cached_status(&key_type).await,
+  })
+}
+
 /// Генерирует OAuth URL для авторизации
 #[tauri::command]
 pub fn generate_oauth_url(
@@ -86,6 +86,14 @@ impl EnvImporter {
     }
   }
 
+  /// Имена всех известных переменных с API ключами и секретами
+  pub fn known_key_names(&self) -> Vec<String> {
+    let mut names: Vec<String> = self.get_env_mappings().into_values().flatten().collect();
+    names.sort();
+    names.dedup();
+    names
+  }
+
   /// Получает карту переменных окружения для каждого типа ключа
   fn get_env_mappings(&self) -> HashMap<ApiKeyType, Vec<String>> {
     let mut mappings = HashMap::new();
@@ -4,6 +4,7 @@ pub mod api_validator_service;
 pub mod commands;
 pub mod env_importer;
 pub mod oauth_handler;
+pub mod privacy_mode;
 pub mod registry;
 /// Модуль безопасности Timeline Studio
 ///
@@ -0,0 +1,473 @@
+//! Режим приватности - шифрование пользовательских данных на диске
+//!
+//! При включенном режиме результаты распознавания, данные превью и кэш
+//! миниатюр и кадров записываются зашифрованными (AES-256-GCM, отдельный
+//! случайный nonce для каждого файла). Ключ данных создается один раз на
+//! установку и хранится через [`SecureStorage::get_or_create_data_key`].
+//!
+//! Зашифрованный файл начинается с заголовка [`PRIVATE_MAGIC`], поэтому
+//! чтение прозрачно: файлы без заголовка (записанные до включения режима)
+//! возвращаются как есть. Миниатюры расшифровываются только в память для
+//! передачи во frontend и никогда не пишутся расшифрованными во временные файлы.
+
+use aes_gcm::{
+  aead::{Aead, AeadCore, KeyInit, OsRng},
+  Aes256Gcm, Key, Nonce,
+};
+use anyhow::{Context, Result};
+use once_cell::sync::{Lazy, OnceCell};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, State};
+
+use super::SecureStorage;
+use crate::media::preview_manager::PreviewDataManager;
+
+/// Заголовок зашифрованного файла
+pub const PRIVATE_MAGIC: &[u8; 8] = b"TSPRIV01";
+
+/// Размер nonce AES-GCM
+const NONCE_LEN: usize = 12;
+
+/// Имя файла настроек режима в директории конфигурации
+const SETTINGS_FILE_NAME: &str = "privacy_mode.json";
+
+/// Суффикс временного файла при миграции
+const MIGRATION_TMP_SUFFIX: &str = ".privacy-tmp";
+
+/// Режим приватности приложения
+static PRIVACY_MODE: Lazy<PrivacyMode> = Lazy::new(|| {
+  let settings_path =
+    dirs::config_dir().map(|dir| dir.join("timeline-studio").join(SETTINGS_FILE_NAME));
+  PrivacyMode::load(settings_path, SecureStorage::get_or_create_data_key)
+});
+
+/// Файл зашифрован режимом приватности
+pub fn is_encrypted(data: &[u8]) -> bool {
+  data.len() >= PRIVATE_MAGIC.len() + NONCE_LEN && data.starts_with(PRIVATE_MAGIC)
+}
+
+/// Зашифровать данные: заголовок, nonce и шифртекст
+pub fn encrypt_blob(cipher: &Aes256Gcm, plaintext: &[u8]) -> Result<Vec<u8>> {
+  let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+  let ciphertext = cipher
+    .encrypt(&nonce, plaintext)
+    .map_err(|e| anyhow::anyhow!("Encryption failed: {e}"))?;
+
+  let mut blob = Vec::with_capacity(PRIVATE_MAGIC.len() + NONCE_LEN + ciphertext.len());
+  blob.extend_from_slice(PRIVATE_MAGIC);
+  blob.extend_from_slice(&nonce);
+  blob.extend_from_slice(&ciphertext);
+  Ok(blob)
+}
+
+/// Расшифровать данные, записанные [`encrypt_blob`]
+pub fn decrypt_blob(cipher: &Aes256Gcm, blob: &[u8]) -> Result<Vec<u8>> {
+  if !is_encrypted(blob) {
+    anyhow::bail!("Data is not encrypted by privacy mode");
+  }
+  let (nonce, ciphertext) = blob[PRIVATE_MAGIC.len()..].split_at(NONCE_LEN);
+  cipher
+    .decrypt(Nonce::from_slice(nonce), ciphertext)
+    .map_err(|e| anyhow::anyhow!("Decryption failed: {e}"))
+}
+
+/// Сохраненные настройки режима
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PrivacySettings {
+  enabled: bool,
+}
+
+/// Состояние режима приватности и ключ данных
+pub struct PrivacyMode {
+  enabled: AtomicBool,
+  /// Файл настроек; без него состояние не сохраняется (тесты)
+  settings_path: Option<PathBuf>,
+  /// Ключ загружается при первом шифровании или расшифровке
+  cipher: OnceCell<Aes256Gcm>,
+  key_loader: fn() -> Result<[u8; 32]>,
+}
+
+impl PrivacyMode {
+  /// Режим приложения
+  pub fn global() -> &'static PrivacyMode {
+    &PRIVACY_MODE
+  }
+
+  /// Загрузить состояние из файла настроек
+  fn load(settings_path: Option<PathBuf>, key_loader: fn() -> Result<[u8; 32]>) -> Self {
+    let settings = settings_path
+      .as_deref()
+      .and_then(|path| std::fs::read(path).ok())
+      .and_then(|json| serde_json::from_slice::<PrivacySettings>(&json).ok())
+      .unwrap_or_default();
+
+    Self {
+      enabled: AtomicBool::new(settings.enabled),
+      settings_path,
+      cipher: OnceCell::new(),
+      key_loader,
+    }
+  }
+
+  /// Режим с заданным ключом, без сохранения настроек
+  pub fn with_key(enabled: bool, key: [u8; 32]) -> Self {
+    let cipher = OnceCell::new();
+    let _ = cipher.set(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)));
+    Self {
+      enabled: AtomicBool::new(enabled),
+      settings_path: None,
+      cipher,
+      key_loader: || anyhow::bail!("Data key is not available"),
+    }
+  }
+
+  pub fn is_enabled(&self) -> bool {
+    self.enabled.load(Ordering::SeqCst)
+  }
+
+  /// Включить или выключить режим и сохранить настройку
+  pub fn set_enabled(&self, enabled: bool) -> Result<()> {
+    if let Some(path) = &self.settings_path {
+      if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+      }
+      let json = serde_json::to_vec_pretty(&PrivacySettings { enabled })?;
+      std::fs::write(path, json).context("Failed to write privacy mode settings")?;
+    }
+    self.enabled.store(enabled, Ordering::SeqCst);
+    Ok(())
+  }
+
+  fn cipher(&self) -> Result<&Aes256Gcm> {
+    self.cipher.get_or_try_init(|| {
+      let key = (self.key_loader)()?;
+      Ok(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)))
+    })
+  }
+
+  /// Подготовить данные к записи: при включенном режиме - зашифровать
+  pub fn encode(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+    if self.is_enabled() {
+      encrypt_blob(self.cipher()?, plaintext)
+    } else {
+      Ok(plaintext.to_vec())
+    }
+  }
+
+  /// Прочитанные данные: зашифрованные расшифровываются, остальные возвращаются как есть
+  pub fn decode(&self, data: Vec<u8>) -> Result<Vec<u8>> {
+    if is_encrypted(&data) {
+      decrypt_blob(self.cipher()?, &data)
+    } else {
+      Ok(data)
+    }
+  }
+
+  /// Записать файл с учетом режима
+  pub async fn write(&self, path: &Path, data: &[u8]) -> Result<()> {
+    let encoded = self.encode(data)?;
+    tokio::fs::write(path, encoded)
+      .await
+      .with_context(|| format!("Failed to write {}", path.display()))
+  }
+
+  /// Прочитать файл, расшифровывая его при необходимости
+  pub async fn read(&self, path: &Path) -> Result<Vec<u8>> {
+    let data = tokio::fs::read(path)
+      .await
+      .with_context(|| format!("Failed to read {}", path.display()))?;
+    self
+      .decode(data)
+      .with_context(|| format!("Failed to decrypt {}", path.display()))
+  }
+
+  /// Привести файл к текущему режиму: зашифровать или расшифровать на месте.
+  /// Возвращает `true`, если файл был перезаписан.
+  async fn migrate_file(&self, path: &Path, encrypt: bool) -> Result<bool> {
+    let data = tokio::fs::read(path).await?;
+    let migrated = match (encrypt, is_encrypted(&data)) {
+      (true, false) => encrypt_blob(self.cipher()?, &data)?,
+      (false, true) => decrypt_blob(self.cipher()?, &data)?,
+      _ => return Ok(false),
+    };
+
+    // Запись через временный файл, чтобы прерванная миграция не повредила данные
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(MIGRATION_TMP_SUFFIX);
+    let tmp_path = path.with_file_name(tmp_name);
+    tokio::fs::write(&tmp_path, migrated).await?;
+    tokio::fs::rename(&tmp_path, path).await?;
+    Ok(true)
+  }
+
+  /// Зашифровать (`encrypt = true`) или расшифровать на месте все файлы
+  /// директорий. `on_progress` вызывается после каждого файла.
+  pub async fn migrate<F>(
+    &self,
+    dirs: &[PathBuf],
+    encrypt: bool,
+    mut on_progress: F,
+  ) -> PrivacyMigrationReport
+  where
+    F: FnMut(&PrivacyMigrationProgress),
+  {
+    let files = collect_private_files(dirs).await;
+    let mut report = PrivacyMigrationReport {
+      total: files.len(),
+      ..Default::default()
+    };
+
+    for (index, path) in files.iter().enumerate() {
+      match self.migrate_file(path, encrypt).await {
+        Ok(true) => report.migrated += 1,
+        Ok(false) => report.skipped += 1,
+        Err(e) => {
+          log::warn!("Не удалось перенести файл {path:?} в режим приватности: {e}");
+          report.failed.push(path.clone());
+        }
+      }
+      on_progress(&PrivacyMigrationProgress {
+        processed: index + 1,
+        total: files.len(),
+        path: path.clone(),
+      });
+    }
+
+    report
+  }
+}
+
+/// Директории с данными, на которые распространяется режим приватности
+pub fn private_data_dirs(base_dir: &Path) -> Vec<PathBuf> {
+  vec![
+    base_dir.join("Recognition"),
+    base_dir.join("Caches/preview/browser"),
+    base_dir.join("Caches/timeline"),
+  ]
+}
+
+/// Все файлы директорий, рекурсивно. Незавершенные временные файлы миграции пропускаются.
+async fn collect_private_files(dirs: &[PathBuf]) -> Vec<PathBuf> {
+  let mut files = Vec::new();
+  let mut pending: Vec<PathBuf> = dirs.to_vec();
+
+  while let Some(dir) = pending.pop() {
+    let Ok(mut entries) = tokio::fs::read_dir(&dir).await else {
+      continue;
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+      let path = entry.path();
+      match entry.file_type().await {
+        Ok(file_type) if file_type.is_dir() => pending.push(path),
+        Ok(file_type) if file_type.is_file() => {
+          if !path.to_string_lossy().ends_with(MIGRATION_TMP_SUFFIX) {
+            files.push(path);
+          }
+        }
+        _ => {}
+      }
+    }
+  }
+
+  files.sort();
+  files
+}
+
+/// Подсчитать зашифрованные и открытые файлы директорий
+async fn count_private_files(dirs: &[PathBuf]) -> (usize, usize) {
+  use tokio::io::AsyncReadExt;
+
+  let mut encrypted = 0;
+  let mut plaintext = 0;
+  for path in collect_private_files(dirs).await {
+    let Ok(mut file) = tokio::fs::File::open(&path).await else {
+      continue;
+    };
+    let mut header = vec![0u8; PRIVATE_MAGIC.len() + NONCE_LEN];
+    let header_read = file.read_exact(&mut header).await.is_ok();
+    if header_read && is_encrypted(&header) {
+      encrypted += 1;
+    } else {
+      plaintext += 1;
+    }
+  }
+  (encrypted, plaintext)
+}
+
+/// Прогресс миграции, отправляется событием `privacy-mode-migration`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrivacyMigrationProgress {
+  pub processed: usize,
+  pub total: usize,
+  pub path: PathBuf,
+}
+
+/// Итог миграции данных
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PrivacyMigrationReport {
+  pub total: usize,
+  /// Перезаписанные файлы
+  pub migrated: usize,
+  /// Файлы, уже находившиеся в нужном состоянии
+  pub skipped: usize,
+  /// Файлы, которые не удалось перенести
+  pub failed: Vec<PathBuf>,
+}
+
+/// Состояние режима приватности для frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrivacyModeStatus {
+  pub enabled: bool,
+  /// Зашифрованные файлы в директориях данных
+  pub encrypted_files: usize,
+  /// Открытые файлы в директориях данных
+  pub plaintext_files: usize,
+}
+
+/// Получить состояние режима приватности
+#[tauri::command]
+pub async fn get_privacy_mode_status(
+  preview_manager: State<'_, Arc<PreviewDataManager>>,
+) -> Result<PrivacyModeStatus, String> {
+  let dirs = private_data_dirs(preview_manager.base_dir());
+  let (encrypted_files, plaintext_files) = count_private_files(&dirs).await;
+  Ok(PrivacyModeStatus {
+    enabled: PrivacyMode::global().is_enabled(),
+    encrypted_files,
+    plaintext_files,
+  })
+}
+
+/// Включить или выключить режим приватности.
+///
+/// При `migrate = true` существующие данные шифруются (или расшифровываются
+/// при выключении) на месте; прогресс отправляется событием
+/// `privacy-mode-migration`. Без миграции старые файлы остаются читаемыми.
+#[tauri::command]
+pub async fn set_privacy_mode<R: tauri::Runtime>(
+  app: AppHandle<R>,
+  preview_manager: State<'_, Arc<PreviewDataManager>>,
+  enabled: bool,
+  migrate: bool,
+) -> Result<PrivacyMigrationReport, String> {
+  let privacy = PrivacyMode::global();
+  privacy.set_enabled(enabled).map_err(|e| e.to_string())?;
+
+  if !migrate {
+    return Ok(PrivacyMigrationReport::default());
+  }
+
+  let dirs = private_data_dirs(preview_manager.base_dir());
+  let report = privacy
+    .migrate(&dirs, enabled, |progress| {
+      let _ = app.emit("privacy-mode-migration", progress);
+    })
+    .await;
+  Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tempfile::TempDir;
+
+  const KEY: [u8; 32] = [7; 32];
+
+  fn cipher(key: [u8; 32]) -> Aes256Gcm {
+    Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key))
+  }
+
+  #[test]
+  fn test_blob_roundtrip_uses_fresh_nonce() {
+    let cipher = cipher(KEY);
+    let first = encrypt_blob(&cipher, b"secret recognition").unwrap();
+    let second = encrypt_blob(&cipher, b"secret recognition").unwrap();
+
+    assert!(is_encrypted(&first));
+    assert_ne!(first, second);
+    assert_eq!(
+      decrypt_blob(&cipher, &first).unwrap(),
+      b"secret recognition"
+    );
+    assert_eq!(
+      decrypt_blob(&cipher, &second).unwrap(),
+      b"secret recognition"
+    );
+  }
+
+  #[test]
+  fn test_wrong_key_fails() {
+    let blob = encrypt_blob(&cipher(KEY), b"thumbnail").unwrap();
+    assert!(decrypt_blob(&cipher([8; 32]), &blob).is_err());
+
+    // Поврежденный шифртекст не расшифровывается
+    let mut tampered = blob.clone();
+    *tampered.last_mut().unwrap() ^= 1;
+    assert!(decrypt_blob(&cipher(KEY), &tampered).is_err());
+  }
+
+  #[test]
+  fn test_decode_passes_legacy_plaintext() {
+    let privacy = PrivacyMode::with_key(true, KEY);
+    let legacy = br#"{"file_id":"legacy"}"#.to_vec();
+    assert_eq!(privacy.decode(legacy.clone()).unwrap(), legacy);
+
+    let disabled = PrivacyMode::with_key(false, KEY);
+    assert_eq!(disabled.encode(b"plain").unwrap(), b"plain");
+  }
+
+  #[tokio::test]
+  async fn test_write_and_read_transparently() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("results.json");
+    let privacy = PrivacyMode::with_key(true, KEY);
+
+    privacy.write(&path, b"{\"objects\":[]}").await.unwrap();
+    let raw = tokio::fs::read(&path).await.unwrap();
+    assert!(is_encrypted(&raw));
+    assert_eq!(privacy.read(&path).await.unwrap(), b"{\"objects\":[]}");
+
+    // Другой ключ не читает файл
+    let other = PrivacyMode::with_key(true, [9; 32]);
+    assert!(other.read(&path).await.is_err());
+  }
+
+  #[tokio::test]
+  async fn test_migrate_encrypts_and_decrypts_in_place() {
+    let dir = TempDir::new().unwrap();
+    let nested = dir.path().join("Caches/timeline/file/filmstrip");
+    tokio::fs::create_dir_all(&nested).await.unwrap();
+    tokio::fs::write(dir.path().join("a.json"), b"{}")
+      .await
+      .unwrap();
+    tokio::fs::write(nested.join("frame.jpg"), b"jpeg")
+      .await
+      .unwrap();
+
+    let privacy = PrivacyMode::with_key(true, KEY);
+    let dirs = vec![dir.path().to_path_buf()];
+
+    let mut progress = Vec::new();
+    let report = privacy
+      .migrate(&dirs, true, |p| progress.push(p.processed))
+      .await;
+    assert_eq!((report.total, report.migrated, report.skipped), (2, 2, 0));
+    assert_eq!(progress, vec![1, 2]);
+    assert_eq!(count_private_files(&dirs).await, (2, 0));
+
+    // Повторная миграция не трогает зашифрованные файлы
+    let report = privacy.migrate(&dirs, true, |_| {}).await;
+    assert_eq!((report.migrated, report.skipped), (0, 2));
+
+    let report = privacy.migrate(&dirs, false, |_| {}).await;
+    assert_eq!(report.migrated, 2);
+    assert_eq!(count_private_files(&dirs).await, (0, 2));
+    assert_eq!(
+      tokio::fs::read(nested.join("frame.jpg")).await.unwrap(),
+      b"jpeg"
+    );
+  }
+}
@@ -1,5 +1,6 @@
 use super::additional_commands::*;
 use super::commands::*;
+use super::privacy_mode::*;
 use crate::command_registry::CommandRegistry;
 use tauri::{Builder, Runtime};
 
@@ -16,6 +17,8 @@ impl CommandRegistry for SecurityCommandRegistry {
       delete_api_key,
       validate_api_key,
       get_api_key_info,
+      validate_api_key_live,
+      get_api_key_status,
       // OAuth operations
       generate_oauth_url,
       exchange_oauth_code,
@@ -32,6 +35,9 @@ impl CommandRegistry for SecurityCommandRegistry {
       get_or_create_encryption_key_command,
       check_storage_security,
       get_secure_storage_info,
+      // Privacy mode
+      get_privacy_mode_status,
+      set_privacy_mode,
     ])
   }
 }
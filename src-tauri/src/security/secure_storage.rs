@@ -121,6 +121,19 @@ impl SecureStorage {
 
   /// Получает или создает ключ шифрования из локального файла
   pub fn get_or_create_encryption_key() -> Result<[u8; 32]> {
+    Self::get_or_create_key_file(".encryption_key")
+  }
+
+  /// Получает или создает ключ данных режима приватности.
+  ///
+  /// Ключ отделен от ключа API ключей: он шифрует кэши и результаты
+  /// распознавания и создается один раз на установку.
+  pub fn get_or_create_data_key() -> Result<[u8; 32]> {
+    Self::get_or_create_key_file(".data_key")
+  }
+
+  /// Читает случайный ключ из файла в директории конфигурации или создает его
+  fn get_or_create_key_file(file_name: &str) -> Result<[u8; 32]> {
     use std::fs;
 
     // Получаем путь к директории конфигурации приложения
@@ -131,7 +144,7 @@ impl SecureStorage {
     // Создаем директорию если не существует
     fs::create_dir_all(&config_dir)?;
 
-    let key_file = config_dir.join(".encryption_key");
+    let key_file = config_dir.join(file_name);
 
     if key_file.exists() {
       // Читаем существующий ключ
@@ -17,6 +17,10 @@ pub fn get_app_version() -> String {
   env!("CARGO_PKG_VERSION").to_string()
 }
 
+/// Commands exported to the TypeScript bindings (keep in sync with
+/// `collect_commands!` below; checked by the registration audit test)
+pub const SPECTA_COMMANDS: &[&str] = &["get_app_version"];
+
 /// Export TypeScript bindings
 pub fn export_typescript_bindings() {
   #[cfg(debug_assertions)]
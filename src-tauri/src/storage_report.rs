@@ -0,0 +1,609 @@
+//! Отчет об использовании диска кэшами и временными файлами
+//!
+//! Файлы приложения разбиты по категориям (превью, предрендер, прокси,
+//! модели и т.д.). Обход директорий асинхронный, с ограничением глубины и
+//! числа одновременно читаемых директорий; недоступные пути пропускаются и
+//! попадают в отчет. Для категорий с поддиректориями проектов
+//! (`<корень>/<project_id>/...`) размер разбивается по проектам.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tauri::State;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use crate::app_dirs::AppDirectories;
+use crate::media::preview_manager::PreviewDataManager;
+use crate::video_compiler::commands::whisper_commands::get_whisper_models_dir;
+use crate::video_compiler::core::temp_storage;
+use crate::video_compiler::VideoCompilerState;
+
+/// Максимальная глубина обхода от корня категории
+pub const MAX_WALK_DEPTH: usize = 8;
+
+/// Количество директорий, читаемых одновременно
+pub const WALK_CONCURRENCY: usize = 8;
+
+/// Категория файлов приложения
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageCategory {
+  /// Миниатюры, спрайт-листы и превью при наведении
+  Thumbnails,
+  /// Кэш превью таймлайна и кадров
+  PreviewCache,
+  /// Предрендеренные сегменты и кэш сегментов экспорта
+  Prerender,
+  /// Прокси-файлы
+  Proxies,
+  /// Модели Whisper и YOLO
+  Models,
+  /// Результаты распознавания
+  Recognition,
+  /// Автосохранения и резервные копии проектов
+  Autosaves,
+  /// Файлы задач рендеринга
+  RenderArtifacts,
+}
+
+impl StorageCategory {
+  /// Все категории в порядке отчета
+  pub const ALL: [StorageCategory; 8] = [
+    StorageCategory::Thumbnails,
+    StorageCategory::PreviewCache,
+    StorageCategory::Prerender,
+    StorageCategory::Proxies,
+    StorageCategory::Models,
+    StorageCategory::Recognition,
+    StorageCategory::Autosaves,
+    StorageCategory::RenderArtifacts,
+  ];
+
+  /// Поддиректории первого уровня соответствуют проектам
+  pub fn is_project_scoped(&self) -> bool {
+    matches!(self, StorageCategory::Prerender | StorageCategory::Proxies)
+  }
+}
+
+/// Корневые директории категорий
+#[derive(Debug, Clone, Default)]
+pub struct StorageRoots {
+  roots: Vec<(StorageCategory, PathBuf)>,
+}
+
+impl StorageRoots {
+  /// Добавить корень категории
+  pub fn with_root(mut self, category: StorageCategory, root: PathBuf) -> Self {
+    if !self.roots.contains(&(category, root.clone())) {
+      self.roots.push((category, root));
+    }
+    self
+  }
+
+  /// Корни категории
+  pub fn roots(&self, category: StorageCategory) -> Vec<&Path> {
+    self
+      .roots
+      .iter()
+      .filter(|(c, _)| *c == category)
+      .map(|(_, root)| root.as_path())
+      .collect()
+  }
+
+  /// Известные расположения файлов приложения
+  pub fn detect(app_dirs: &AppDirectories, preview_base: &Path, temp_root: &Path) -> Self {
+    use StorageCategory::*;
+
+    let mut roots = Self::default()
+      .with_root(Thumbnails, app_dirs.caches_dir.join("thumbnails"))
+      .with_root(Thumbnails, preview_base.join("Caches/preview/browser"))
+      .with_root(Thumbnails, preview_base.join("Caches/preview/hover"))
+      .with_root(Thumbnails, preview_base.join("Caches/sprites"))
+      .with_root(PreviewCache, app_dirs.get_preview_cache_dir())
+      .with_root(PreviewCache, app_dirs.get_frame_cache_dir())
+      .with_root(PreviewCache, preview_base.join("Caches/timeline"))
+      .with_root(PreviewCache, temp_root.join(temp_storage::PREVIEW_DIR))
+      .with_root(Prerender, temp_root.join(temp_storage::PRERENDER_DIR))
+      .with_root(Prerender, temp_root.join(temp_storage::SEGMENTS_DIR))
+      .with_root(Proxies, app_dirs.media_proxy_dir.clone())
+      .with_root(Recognition, app_dirs.recognition_dir.clone())
+      .with_root(Recognition, preview_base.join("Recognition"))
+      .with_root(Autosaves, app_dirs.snapshot_dir.clone())
+      .with_root(Autosaves, app_dirs.backup_dir.clone())
+      .with_root(RenderArtifacts, app_dirs.render_dir.clone())
+      .with_root(RenderArtifacts, app_dirs.get_render_cache_dir())
+      .with_root(RenderArtifacts, temp_root.join(temp_storage::JOBS_DIR));
+
+    if let Ok(whisper) = get_whisper_models_dir() {
+      roots = roots.with_root(Models, whisper);
+    }
+    if let Some(data_dir) = dirs::data_local_dir() {
+      roots = roots.with_root(Models, data_dir.join("timeline-studio").join("models"));
+    }
+    roots
+  }
+}
+
+/// Путь, пропущенный при обходе
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedPath {
+  pub path: PathBuf,
+  pub error: String,
+}
+
+/// Использование диска категорией
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageCategoryReport {
+  pub category: StorageCategory,
+  pub roots: Vec<PathBuf>,
+  pub total_bytes: u64,
+  pub file_count: usize,
+  /// Время изменения самого старого файла
+  pub oldest: Option<DateTime<Utc>>,
+  /// Время изменения самого нового файла
+  pub newest: Option<DateTime<Utc>>,
+  /// Байты по проектам (для категорий с поддиректориями проектов)
+  pub by_project: BTreeMap<String, u64>,
+}
+
+/// Отчет об использовании диска
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageReport {
+  pub categories: Vec<StorageCategoryReport>,
+  pub total_bytes: u64,
+  /// Недоступные пути, пропущенные при обходе
+  pub skipped: Vec<SkippedPath>,
+  pub generated_at: DateTime<Utc>,
+}
+
+/// Условия выборочной очистки категории
+#[derive(Debug, Clone, Default)]
+pub struct StorageCleanupFilter {
+  /// Удалять только файлы, измененные раньше указанного числа дней назад
+  pub older_than_days: Option<u32>,
+  /// Удалять только файлы проекта
+  pub project_id: Option<String>,
+}
+
+/// Результат очистки категории
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StorageCleanupResult {
+  pub freed_bytes: u64,
+  pub deleted_files: usize,
+  /// Файлы, оставленные из-за использования активными задачами
+  pub protected_files: usize,
+  /// Файлы, которые не удалось прочитать или удалить
+  pub skipped: Vec<SkippedPath>,
+}
+
+/// Файл, найденный при обходе
+#[derive(Debug, Clone)]
+struct StorageEntry {
+  path: PathBuf,
+  size: u64,
+  modified: Option<SystemTime>,
+  project: Option<String>,
+}
+
+/// Содержимое одной прочитанной директории
+#[derive(Default)]
+struct DirListing {
+  files: Vec<StorageEntry>,
+  subdirs: Vec<(PathBuf, usize, Option<String>)>,
+  skipped: Vec<SkippedPath>,
+}
+
+/// Прочитать одну директорию без перехода по символическим ссылкам
+async fn read_dir_listing(
+  dir: PathBuf,
+  depth: usize,
+  project: Option<String>,
+  project_scoped: bool,
+) -> DirListing {
+  let mut listing = DirListing::default();
+  let mut entries = match tokio::fs::read_dir(&dir).await {
+    Ok(entries) => entries,
+    Err(e) => {
+      listing.skipped.push(SkippedPath {
+        path: dir,
+        error: e.to_string(),
+      });
+      return listing;
+    }
+  };
+
+  loop {
+    let entry = match entries.next_entry().await {
+      Ok(Some(entry)) => entry,
+      Ok(None) => break,
+      Err(e) => {
+        listing.skipped.push(SkippedPath {
+          path: dir.clone(),
+          error: e.to_string(),
+        });
+        break;
+      }
+    };
+    let path = entry.path();
+    let metadata = match tokio::fs::symlink_metadata(&path).await {
+      Ok(metadata) => metadata,
+      Err(e) => {
+        listing.skipped.push(SkippedPath {
+          path,
+          error: e.to_string(),
+        });
+        continue;
+      }
+    };
+
+    if metadata.is_dir() {
+      if depth < MAX_WALK_DEPTH {
+        // Поддиректория первого уровня определяет проект
+        let project = match &project {
+          None if project_scoped && depth == 0 => {
+            Some(entry.file_name().to_string_lossy().to_string())
+          }
+          project => project.clone(),
+        };
+        listing.subdirs.push((path, depth + 1, project));
+      }
+    } else if metadata.is_file() {
+      listing.files.push(StorageEntry {
+        path,
+        size: metadata.len(),
+        modified: metadata.modified().ok(),
+        project: project.clone(),
+      });
+    }
+  }
+
+  listing
+}
+
+/// Обойти корни категории, читая не более `WALK_CONCURRENCY` директорий одновременно.
+///
+/// Отсутствующие корни пропускаются без ошибки.
+async fn walk_category(
+  roots: &[&Path],
+  project_scoped: bool,
+) -> (Vec<StorageEntry>, Vec<SkippedPath>) {
+  let semaphore = Arc::new(Semaphore::new(WALK_CONCURRENCY));
+  let mut join_set = JoinSet::new();
+  let mut files = Vec::new();
+  let mut skipped = Vec::new();
+
+  let spawn =
+    |join_set: &mut JoinSet<DirListing>, dir: PathBuf, depth: usize, project: Option<String>| {
+      let semaphore = semaphore.clone();
+      join_set.spawn(async move {
+        let Ok(_permit) = semaphore.acquire_owned().await else {
+          return DirListing::default();
+        };
+        read_dir_listing(dir, depth, project, project_scoped).await
+      });
+    };
+
+  for root in roots {
+    if tokio::fs::metadata(root).await.is_ok_and(|m| m.is_dir()) {
+      spawn(&mut join_set, root.to_path_buf(), 0, None);
+    }
+  }
+
+  while let Some(joined) = join_set.join_next().await {
+    let Ok(listing) = joined else {
+      continue;
+    };
+    files.extend(listing.files);
+    skipped.extend(listing.skipped);
+    for (dir, depth, project) in listing.subdirs {
+      spawn(&mut join_set, dir, depth, project);
+    }
+  }
+
+  (files, skipped)
+}
+
+/// Построить отчет по категориям
+pub async fn build_storage_report(roots: &StorageRoots) -> StorageReport {
+  let mut categories = Vec::new();
+  let mut skipped = Vec::new();
+
+  for category in StorageCategory::ALL {
+    let category_roots = roots.roots(category);
+    let (files, category_skipped) =
+      walk_category(&category_roots, category.is_project_scoped()).await;
+    skipped.extend(category_skipped);
+
+    let mut by_project = BTreeMap::new();
+    for file in &files {
+      if let Some(project) = &file.project {
+        *by_project.entry(project.clone()).or_insert(0) += file.size;
+      }
+    }
+    let modified = files.iter().filter_map(|file| file.modified);
+
+    categories.push(StorageCategoryReport {
+      category,
+      roots: category_roots
+        .iter()
+        .map(|root| root.to_path_buf())
+        .collect(),
+      total_bytes: files.iter().map(|file| file.size).sum(),
+      file_count: files.len(),
+      oldest: modified.clone().min().map(DateTime::<Utc>::from),
+      newest: modified.max().map(DateTime::<Utc>::from),
+      by_project,
+    });
+  }
+
+  StorageReport {
+    total_bytes: categories.iter().map(|c| c.total_bytes).sum(),
+    categories,
+    skipped,
+    generated_at: Utc::now(),
+  }
+}
+
+/// Выборочно удалить файлы категории.
+///
+/// Файлы внутри `protected` (или совпадающие с ними) не удаляются. Опустевшие
+/// поддиректории удаляются, сами корни категории сохраняются.
+pub async fn clear_storage(
+  roots: &StorageRoots,
+  category: StorageCategory,
+  filter: &StorageCleanupFilter,
+  protected: &[PathBuf],
+) -> StorageCleanupResult {
+  let category_roots = roots.roots(category);
+  let (files, skipped) = walk_category(&category_roots, category.is_project_scoped()).await;
+  let mut result = StorageCleanupResult {
+    skipped,
+    ..Default::default()
+  };
+
+  let cutoff = filter
+    .older_than_days
+    .map(|days| SystemTime::now() - Duration::from_secs(u64::from(days) * 24 * 60 * 60));
+
+  for file in files {
+    if let Some(project_id) = &filter.project_id {
+      if file.project.as_ref() != Some(project_id) {
+        continue;
+      }
+    }
+    if let Some(cutoff) = cutoff {
+      // Файлы без времени изменения не удаляются по возрасту
+      if !file.modified.is_some_and(|modified| modified < cutoff) {
+        continue;
+      }
+    }
+    if protected.iter().any(|path| file.path.starts_with(path)) {
+      result.protected_files += 1;
+      continue;
+    }
+
+    match tokio::fs::remove_file(&file.path).await {
+      Ok(()) => {
+        result.freed_bytes += file.size;
+        result.deleted_files += 1;
+        remove_empty_parents(&file.path, &category_roots).await;
+      }
+      Err(e) => result.skipped.push(SkippedPath {
+        path: file.path,
+        error: e.to_string(),
+      }),
+    }
+  }
+
+  result
+}
+
+/// Удалить опустевшие директории от файла вверх до корня категории
+async fn remove_empty_parents(path: &Path, roots: &[&Path]) {
+  let Some(root) = roots.iter().find(|root| path.starts_with(root)) else {
+    return;
+  };
+  let mut dir = path.parent();
+  while let Some(current) = dir {
+    if current == *root || !current.starts_with(root) {
+      break;
+    }
+    // remove_dir удаляет только пустые директории
+    if tokio::fs::remove_dir(current).await.is_err() {
+      break;
+    }
+    dir = current.parent();
+  }
+}
+
+/// Корни категорий для текущего окружения
+fn current_storage_roots(preview_manager: &PreviewDataManager) -> Result<StorageRoots, String> {
+  let app_dirs =
+    AppDirectories::get_or_create().map_err(|e| format!("Failed to get app directories: {e}"))?;
+  Ok(StorageRoots::detect(
+    &app_dirs,
+    preview_manager.base_dir(),
+    &temp_storage::temp_root(),
+  ))
+}
+
+/// Команда Tauri для отчета об использовании диска по категориям
+#[tauri::command]
+pub async fn get_storage_report(
+  preview_manager: State<'_, Arc<PreviewDataManager>>,
+) -> Result<StorageReport, String> {
+  let roots = current_storage_roots(&preview_manager)?;
+  Ok(build_storage_report(&roots).await)
+}
+
+/// Команда Tauri для выборочной очистки категории.
+///
+/// Файлы выполняющихся и запланированных задач рендеринга (результаты,
+/// медиафайлы проектов и временные директории задач) не удаляются.
+#[tauri::command]
+pub async fn clear_storage_category(
+  state: State<'_, VideoCompilerState>,
+  preview_manager: State<'_, Arc<PreviewDataManager>>,
+  category: StorageCategory,
+  older_than_days: Option<u32>,
+  project_id: Option<String>,
+) -> Result<StorageCleanupResult, String> {
+  let roots = current_storage_roots(&preview_manager)?;
+
+  let mut protected = Vec::new();
+  if let Some(render_service) = state.services.get_render_service() {
+    protected.extend(
+      render_service
+        .active_job_paths()
+        .await
+        .map_err(|e| e.to_string())?,
+    );
+    let temp_root = temp_storage::temp_root();
+    for job_id in render_service
+      .get_active_jobs()
+      .await
+      .map_err(|e| e.to_string())?
+    {
+      protected.push(temp_storage::job_dir(&temp_root, &job_id));
+    }
+  }
+
+  let filter = StorageCleanupFilter {
+    older_than_days,
+    project_id,
+  };
+  Ok(clear_storage(&roots, category, &filter, &protected).await)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::fs;
+  use tempfile::TempDir;
+
+  fn write_file(path: &Path, size: usize) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, vec![0u8; size]).unwrap();
+  }
+
+  fn set_age_days(path: &Path, days: u64) {
+    let time = SystemTime::now() - Duration::from_secs(days * 24 * 60 * 60);
+    filetime::set_file_mtime(path, filetime::FileTime::from_system_time(time)).unwrap();
+  }
+
+  fn test_roots(base: &Path) -> StorageRoots {
+    StorageRoots::default()
+      .with_root(StorageCategory::Thumbnails, base.join("thumbnails"))
+      .with_root(StorageCategory::Thumbnails, base.join("sprites"))
+      .with_root(StorageCategory::Prerender, base.join("prerender"))
+      .with_root(StorageCategory::Models, base.join("missing"))
+  }
+
+  #[tokio::test]
+  async fn test_report_totals_and_project_attribution() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = temp_dir.path();
+    write_file(&base.join("thumbnails/a.jpg"), 100);
+    write_file(&base.join("sprites/file-1/sheet_000.jpg"), 50);
+    write_file(&base.join("prerender/project-a/seg1.mp4"), 1000);
+    write_file(&base.join("prerender/project-a/nested/seg2.mp4"), 500);
+    write_file(&base.join("prerender/project-b/seg1.mp4"), 300);
+    write_file(&base.join("prerender/loose.mp4"), 7);
+    set_age_days(&base.join("thumbnails/a.jpg"), 10);
+
+    let report = build_storage_report(&test_roots(base)).await;
+    assert_eq!(report.categories.len(), StorageCategory::ALL.len());
+    assert_eq!(report.total_bytes, 1957);
+    assert!(report.skipped.is_empty());
+
+    let thumbnails = &report.categories[0];
+    assert_eq!(thumbnails.category, StorageCategory::Thumbnails);
+    assert_eq!(thumbnails.total_bytes, 150);
+    assert_eq!(thumbnails.file_count, 2);
+    assert!(thumbnails.by_project.is_empty());
+    assert!(thumbnails.oldest.unwrap() < thumbnails.newest.unwrap());
+
+    let prerender = report
+      .categories
+      .iter()
+      .find(|c| c.category == StorageCategory::Prerender)
+      .unwrap();
+    assert_eq!(prerender.total_bytes, 1807);
+    assert_eq!(prerender.file_count, 4);
+    assert_eq!(prerender.by_project.get("project-a"), Some(&1500));
+    assert_eq!(prerender.by_project.get("project-b"), Some(&300));
+
+    let models = report
+      .categories
+      .iter()
+      .find(|c| c.category == StorageCategory::Models)
+      .unwrap();
+    assert_eq!(models.file_count, 0);
+    assert!(models.oldest.is_none());
+  }
+
+  #[tokio::test]
+  async fn test_walk_is_bounded_in_depth() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = temp_dir.path();
+    let mut deep = base.join("thumbnails");
+    for i in 0..MAX_WALK_DEPTH + 2 {
+      deep = deep.join(format!("d{i}"));
+    }
+    write_file(&deep.join("too_deep.jpg"), 10);
+    write_file(&base.join("thumbnails/d0/shallow.jpg"), 20);
+
+    let report = build_storage_report(&test_roots(base)).await;
+    assert_eq!(report.categories[0].file_count, 1);
+    assert_eq!(report.categories[0].total_bytes, 20);
+  }
+
+  #[tokio::test]
+  async fn test_clear_selectively_respects_age_project_and_protection() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = temp_dir.path();
+    let old_a = base.join("prerender/project-a/old.mp4");
+    let new_a = base.join("prerender/project-a/new.mp4");
+    let used_a = base.join("prerender/project-a/used/in_use.mp4");
+    let old_b = base.join("prerender/project-b/old.mp4");
+    for path in [&old_a, &new_a, &used_a, &old_b] {
+      write_file(path, 100);
+    }
+    for path in [&old_a, &used_a, &old_b] {
+      set_age_days(path, 30);
+    }
+
+    let roots = test_roots(base);
+    let filter = StorageCleanupFilter {
+      older_than_days: Some(7),
+      project_id: Some("project-a".to_string()),
+    };
+    let protected = vec![base.join("prerender/project-a/used")];
+    let result = clear_storage(&roots, StorageCategory::Prerender, &filter, &protected).await;
+
+    assert_eq!(result.deleted_files, 1);
+    assert_eq!(result.freed_bytes, 100);
+    assert_eq!(result.protected_files, 1);
+    assert!(!old_a.exists());
+    assert!(new_a.exists());
+    assert!(used_a.exists());
+    assert!(old_b.exists());
+
+    // Без фильтров удаляется все, кроме защищенного; пустые директории убираются
+    let result = clear_storage(
+      &roots,
+      StorageCategory::Prerender,
+      &StorageCleanupFilter::default(),
+      &protected,
+    )
+    .await;
+    assert_eq!(result.deleted_files, 2);
+    assert!(!base.join("prerender/project-b").exists());
+    assert!(base.join("prerender").exists());
+    assert!(used_a.exists());
+  }
+}
@@ -1,3 +1,12 @@
+use super::parser::{format_subtitles, parse_srt, parse_vtt, SubtitleTextFormat};
+use super::timing::{
+  apply_speech_boundaries, detect_speech_boundaries, SubtitleTimingOptions,
+  SubtitleTimingRefinement,
+};
+use crate::video_compiler::core::audio_sync::{decode_pcm_window, SYNC_SAMPLE_RATE};
+use crate::video_compiler::schema::{subtitles::SubtitleStyle, ProjectSchema};
+use crate::video_compiler::services::schema_journal::SchemaChange;
+use crate::video_compiler::VideoCompilerState;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
@@ -156,6 +165,147 @@ pub async fn get_subtitle_info(content: String, format: String) -> Result<Subtit
   })
 }
 
+/// Результат импорта файла субтитров в проект
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SubtitleFileImport {
+  /// Проект с добавленными субтитрами
+  pub project: ProjectSchema,
+  /// Количество импортированных реплик
+  pub imported_count: usize,
+  /// Пропущенные реплики и прочие замечания
+  pub warnings: Vec<String>,
+  /// Запись журнала изменений (если передан `project_id`)
+  #[serde(default)]
+  pub change: Option<SchemaChange>,
+}
+
+/// Импортирует SRT/VTT файл и добавляет реплики в субтитры проекта.
+///
+/// С `project_id` изменение записывается в журнал схемы проекта для undo/redo.
+#[tauri::command]
+pub async fn import_subtitle_file(
+  state: tauri::State<'_, VideoCompilerState>,
+  project: ProjectSchema,
+  path: String,
+  default_style: Option<SubtitleStyle>,
+  project_id: Option<String>,
+) -> Result<SubtitleFileImport, String> {
+  let before = project_id.as_ref().map(|_| project.clone());
+  let mut import = import_subtitles_into_project(project, &path, default_style)?;
+
+  if let (Some(project_id), Some(before)) = (project_id, before) {
+    let project_service = state
+      .services
+      .get_project_service()
+      .ok_or_else(|| "Project service not available".to_string())?;
+    import.change = project_service
+      .record_schema_change(&project_id, "Import subtitles", &before, &import.project)
+      .await
+      .map_err(|e| e.to_string())?;
+  }
+  Ok(import)
+}
+
+/// Добавляет реплики SRT/VTT файла в субтитры проекта
+pub fn import_subtitles_into_project(
+  mut project: ProjectSchema,
+  path: &str,
+  default_style: Option<SubtitleStyle>,
+) -> Result<SubtitleFileImport, String> {
+  let file_path = Path::new(path);
+  let extension = file_path
+    .extension()
+    .and_then(|ext| ext.to_str())
+    .unwrap_or("");
+  let format = SubtitleTextFormat::from_name(extension)
+    .ok_or_else(|| format!("Unsupported subtitle format: {extension}"))?;
+
+  let content = fs::read_to_string(file_path).map_err(|e| format!("Failed to read file: {e}"))?;
+  let parsed = match format {
+    SubtitleTextFormat::Srt => parse_srt(&content),
+    SubtitleTextFormat::Vtt => parse_vtt(&content),
+  };
+
+  let imported_count = parsed.subtitles.len();
+  project
+    .subtitles
+    .extend(parsed.subtitles.into_iter().map(|mut subtitle| {
+      if let Some(style) = &default_style {
+        subtitle.apply_style(style.clone());
+      }
+      subtitle
+    }));
+  project.touch();
+
+  Ok(SubtitleFileImport {
+    project,
+    imported_count,
+    warnings: parsed.warnings,
+    change: None,
+  })
+}
+
+/// Экспортирует субтитры проекта в SRT или VTT. Возвращает количество реплик.
+#[tauri::command]
+pub async fn export_subtitles(
+  project: ProjectSchema,
+  format: String,
+  path: String,
+) -> Result<usize, String> {
+  let format = SubtitleTextFormat::from_name(&format)
+    .ok_or_else(|| format!("Unsupported subtitle format: {format}"))?;
+
+  let output_path = Path::new(&path);
+  if let Some(parent) = output_path.parent() {
+    fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {e}"))?;
+  }
+
+  fs::write(output_path, format_subtitles(&project.subtitles, format))
+    .map_err(|e| format!("Failed to write file: {e}"))?;
+
+  Ok(project.subtitles.iter().filter(|s| s.enabled).count())
+}
+
+/// Притягивает начало и конец реплик проекта к границам речи в аудио файла.
+///
+/// Субтитры проекта не изменяются: возвращаются уточненные реплики и отчет
+/// о сдвигах, чтобы интерфейс показал изменения перед применением.
+#[tauri::command]
+pub async fn refine_subtitle_timing(
+  state: tauri::State<'_, VideoCompilerState>,
+  project: ProjectSchema,
+  file_path: String,
+  options: Option<SubtitleTimingOptions>,
+) -> Result<SubtitleTimingRefinement, String> {
+  let path = Path::new(&file_path);
+  if !path.exists() {
+    return Err(format!("File not found: {file_path}"));
+  }
+  let options = options.unwrap_or_default();
+  let ffmpeg_path = state.ffmpeg_path.read().await.clone();
+
+  let mut boundaries = Vec::with_capacity(project.subtitles.len());
+  for subtitle in &project.subtitles {
+    let (start, duration) = options.search_range(subtitle);
+    let samples = decode_pcm_window(&ffmpeg_path, path, start, duration)
+      .await
+      .map_err(|e| format!("Failed to decode audio: {e}"))?;
+    boundaries.push(detect_speech_boundaries(
+      &samples,
+      SYNC_SAMPLE_RATE,
+      start,
+      subtitle,
+      &options,
+    ));
+  }
+
+  Ok(apply_speech_boundaries(
+    project.subtitles,
+    &boundaries,
+    &options,
+  ))
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -435,6 +585,68 @@ Dialogue: 0,0:00:02.50,0:00:05.00,Default,This is a test subtitle.";
     assert!(!info.has_styling);
   }
 
+  #[tokio::test]
+  async fn test_import_subtitle_file_applies_style() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("captions.srt");
+    fs::write(
+      &file_path,
+      format!("{SAMPLE_SRT}\n\n3\n00:00:09,000 --> 00:00:08,000\nBroken"),
+    )
+    .unwrap();
+
+    let style = SubtitleStyle {
+      font_family: "Roboto".to_string(),
+      font_size: 32.0,
+      ..SubtitleStyle::default()
+    };
+    let result = import_subtitles_into_project(
+      ProjectSchema::new("Test".to_string()),
+      &file_path.to_string_lossy(),
+      Some(style),
+    )
+    .unwrap();
+
+    assert_eq!(result.imported_count, 2);
+    assert_eq!(result.warnings.len(), 1);
+    assert_eq!(result.project.subtitles.len(), 2);
+    assert_eq!(result.project.subtitles[1].text, "This is a test subtitle.");
+    assert_eq!(result.project.subtitles[1].font_family, "Roboto");
+    assert_eq!(result.project.subtitles[1].style.font_size, 32.0);
+  }
+
+  #[tokio::test]
+  async fn test_export_subtitles_vtt() {
+    let mut project = ProjectSchema::new("Test".to_string());
+    project.subtitles = parse_srt(SAMPLE_SRT).subtitles;
+
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("out/captions.vtt");
+    let count = export_subtitles(
+      project,
+      "vtt".to_string(),
+      output_path.to_string_lossy().to_string(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(count, 2);
+    let content = fs::read_to_string(&output_path).unwrap();
+    assert!(content.starts_with("WEBVTT"));
+    assert!(content.contains("00:00:02.500 --> 00:00:05.000"));
+  }
+
+  #[tokio::test]
+  async fn test_export_subtitles_unknown_format() {
+    let result = export_subtitles(
+      ProjectSchema::new("Test".to_string()),
+      "ass".to_string(),
+      "/tmp/out.ass".to_string(),
+    )
+    .await;
+    assert!(result.unwrap_err().contains("Unsupported subtitle format"));
+  }
+
   #[test]
   fn test_subtitle_export_options_structure() {
     let options = SubtitleExportOptions {
@@ -1,6 +1,9 @@
 pub mod commands;
+pub mod parser;
+pub mod timing;
 
 pub use commands::*;
+pub use parser::{format_srt, format_vtt, parse_srt, parse_vtt, SubtitleParseResult};
 
 #[cfg(test)]
 #[path = "tests.rs"]
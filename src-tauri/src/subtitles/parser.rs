@@ -0,0 +1,366 @@
+// Парсинг и экспорт субтитров SRT/WebVTT
+//
+// Парсер не прерывается на испорченных репликах: каждая такая реплика
+// пропускается, а причина попадает в список предупреждений.
+
+use crate::video_compiler::schema::subtitles::Subtitle;
+use serde::{Deserialize, Serialize};
+
+/// Результат разбора файла субтитров
+#[derive(Debug, Clone, Default)]
+pub struct SubtitleParseResult {
+  /// Успешно разобранные реплики
+  pub subtitles: Vec<Subtitle>,
+  /// Предупреждения о пропущенных репликах
+  pub warnings: Vec<String>,
+}
+
+/// Текстовый формат субтитров
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SubtitleTextFormat {
+  Srt,
+  Vtt,
+}
+
+impl SubtitleTextFormat {
+  /// Определить формат по расширению или имени формата
+  pub fn from_name(name: &str) -> Option<Self> {
+    match name.trim_start_matches('.').to_lowercase().as_str() {
+      "srt" => Some(Self::Srt),
+      "vtt" | "webvtt" => Some(Self::Vtt),
+      _ => None,
+    }
+  }
+}
+
+/// Разобрать SRT
+pub fn parse_srt(content: &str) -> SubtitleParseResult {
+  let mut result = SubtitleParseResult::default();
+
+  for (line_no, lines) in split_blocks(content) {
+    // Номер реплики необязателен и не проверяется: многие сервисы нумеруют с нуля
+    let Some(timing_idx) = lines.iter().take(2).position(|line| line.contains("-->")) else {
+      result
+        .warnings
+        .push(format!("Line {line_no}: cue has no timing line, skipped"));
+      continue;
+    };
+
+    push_cue(&mut result, line_no + timing_idx, &lines[timing_idx..]);
+  }
+
+  result
+}
+
+/// Разобрать WebVTT. Настройки реплик (`align:`, `position:` и т.п.),
+/// блоки NOTE/STYLE/REGION и разметка игнорируются.
+pub fn parse_vtt(content: &str) -> SubtitleParseResult {
+  let mut result = SubtitleParseResult::default();
+  let mut blocks = split_blocks(content).into_iter().peekable();
+
+  match blocks.peek() {
+    Some((_, lines)) if lines[0].starts_with("WEBVTT") => {
+      blocks.next();
+    }
+    _ => result
+      .warnings
+      .push("Missing WEBVTT header, parsing cues anyway".to_string()),
+  }
+
+  for (line_no, lines) in blocks {
+    if ["NOTE", "STYLE", "REGION"]
+      .iter()
+      .any(|keyword| lines[0].starts_with(keyword))
+    {
+      continue;
+    }
+
+    // Перед таймингом может стоять идентификатор реплики
+    let Some(timing_idx) = lines.iter().take(2).position(|line| line.contains("-->")) else {
+      result
+        .warnings
+        .push(format!("Line {line_no}: cue has no timing line, skipped"));
+      continue;
+    };
+
+    push_cue(&mut result, line_no + timing_idx, &lines[timing_idx..]);
+  }
+
+  result
+}
+
+/// Сформировать SRT из включенных субтитров (по времени начала)
+pub fn format_srt(subtitles: &[Subtitle]) -> String {
+  sorted_enabled(subtitles)
+    .iter()
+    .enumerate()
+    .map(|(idx, subtitle)| {
+      format!(
+        "{}\n{} --> {}\n{}\n",
+        idx + 1,
+        format_timestamp(subtitle.start_time, ','),
+        format_timestamp(subtitle.end_time, ','),
+        subtitle.text
+      )
+    })
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+/// Сформировать WebVTT из включенных субтитров (по времени начала)
+pub fn format_vtt(subtitles: &[Subtitle]) -> String {
+  let mut output = String::from("WEBVTT\n");
+  for subtitle in sorted_enabled(subtitles) {
+    output.push_str(&format!(
+      "\n{} --> {}\n{}\n",
+      format_timestamp(subtitle.start_time, '.'),
+      format_timestamp(subtitle.end_time, '.'),
+      subtitle.text
+    ));
+  }
+  output
+}
+
+/// Сформировать файл в указанном формате
+pub fn format_subtitles(subtitles: &[Subtitle], format: SubtitleTextFormat) -> String {
+  match format {
+    SubtitleTextFormat::Srt => format_srt(subtitles),
+    SubtitleTextFormat::Vtt => format_vtt(subtitles),
+  }
+}
+
+/// Разбить содержимое на блоки, разделенные пустыми строками.
+/// Возвращает номер первой строки блока (с единицы) и его строки.
+fn split_blocks(content: &str) -> Vec<(usize, Vec<&str>)> {
+  let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+  let mut blocks = Vec::new();
+  let mut current: Vec<&str> = Vec::new();
+  let mut start_line = 1;
+
+  // lines() отрезает \n, а \r от CRLF убираем вручную
+  for (idx, line) in content.lines().enumerate() {
+    let line = line.trim_end_matches('\r');
+    if line.trim().is_empty() {
+      if !current.is_empty() {
+        blocks.push((start_line, std::mem::take(&mut current)));
+      }
+    } else {
+      if current.is_empty() {
+        start_line = idx + 1;
+      }
+      current.push(line);
+    }
+  }
+  if !current.is_empty() {
+    blocks.push((start_line, current));
+  }
+
+  blocks
+}
+
+/// Разобрать реплику, начинающуюся со строки тайминга
+fn push_cue(result: &mut SubtitleParseResult, line_no: usize, lines: &[&str]) {
+  let Some((start, end)) = parse_timing_line(lines[0]) else {
+    result.warnings.push(format!(
+      "Line {line_no}: invalid timing '{}', skipped",
+      lines[0].trim()
+    ));
+    return;
+  };
+
+  if end <= start {
+    result.warnings.push(format!(
+      "Line {line_no}: cue ends before it starts, skipped"
+    ));
+    return;
+  }
+
+  let text = lines[1..]
+    .iter()
+    .map(|line| strip_tags(line.trim()))
+    .collect::<Vec<_>>()
+    .join("\n");
+  if text.trim().is_empty() {
+    result
+      .warnings
+      .push(format!("Line {line_no}: cue has no text, skipped"));
+    return;
+  }
+
+  result.subtitles.push(Subtitle::new(text, start, end));
+}
+
+/// Разобрать строку `start --> end [настройки]`
+fn parse_timing_line(line: &str) -> Option<(f64, f64)> {
+  let (start, rest) = line.split_once("-->")?;
+  let end = rest.split_whitespace().next()?;
+  Some((parse_timestamp(start.trim())?, parse_timestamp(end)?))
+}
+
+/// Разобрать `HH:MM:SS,mmm`, `HH:MM:SS.mmm` или `MM:SS.mmm`
+fn parse_timestamp(value: &str) -> Option<f64> {
+  let parts: Vec<&str> = value.split(':').collect();
+  let (hours, minutes, seconds) = match parts.as_slice() {
+    [h, m, s] => (h.parse::<u64>().ok()?, m.parse::<u64>().ok()?, *s),
+    [m, s] => (0, m.parse::<u64>().ok()?, *s),
+    _ => return None,
+  };
+
+  let (whole, fraction) = seconds.split_once([',', '.']).unwrap_or((seconds, "0"));
+  let whole = whole.parse::<u64>().ok()?;
+  if minutes >= 60 || whole >= 60 || fraction.is_empty() {
+    return None;
+  }
+  let fraction = format!("0.{fraction}").parse::<f64>().ok()?;
+
+  Some((hours * 3600 + minutes * 60 + whole) as f64 + fraction)
+}
+
+/// Отформатировать время как `HH:MM:SS<sep>mmm`
+fn format_timestamp(seconds: f64, separator: char) -> String {
+  let total_ms = (seconds.max(0.0) * 1000.0).round() as u64;
+  let ms = total_ms % 1000;
+  let total_secs = total_ms / 1000;
+  format!(
+    "{:02}:{:02}:{:02}{separator}{ms:03}",
+    total_secs / 3600,
+    (total_secs / 60) % 60,
+    total_secs % 60
+  )
+}
+
+/// Убрать разметку вида `<i>`, `<v Speaker>`, `<00:00:01.000>`
+fn strip_tags(text: &str) -> String {
+  let mut output = String::with_capacity(text.len());
+  let mut in_tag = false;
+  for ch in text.chars() {
+    match ch {
+      '<' => in_tag = true,
+      '>' if in_tag => in_tag = false,
+      _ if !in_tag => output.push(ch),
+      _ => {}
+    }
+  }
+  output
+}
+
+fn sorted_enabled(subtitles: &[Subtitle]) -> Vec<&Subtitle> {
+  let mut enabled: Vec<&Subtitle> = subtitles.iter().filter(|s| s.enabled).collect();
+  enabled.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+  enabled
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const BOM_CRLF_SRT: &str = include_str!("fixtures/bom_crlf.srt");
+  const OVERLAPPING_SRT: &str = include_str!("fixtures/overlapping.srt");
+  const MALFORMED_SRT: &str = include_str!("fixtures/malformed.srt");
+  const CUE_SETTINGS_VTT: &str = include_str!("fixtures/cue_settings.vtt");
+
+  fn assert_time(actual: f64, expected: f64) {
+    assert!(
+      (actual - expected).abs() < 1e-6,
+      "expected {expected}, got {actual}"
+    );
+  }
+
+  #[test]
+  fn test_parse_srt_with_bom_and_crlf() {
+    let result = parse_srt(BOM_CRLF_SRT);
+    assert!(result.warnings.is_empty(), "{:?}", result.warnings);
+    assert_eq!(result.subtitles.len(), 3);
+
+    let first = &result.subtitles[0];
+    assert_time(first.start_time, 1.0);
+    assert_time(first.end_time, 3.5);
+    assert_eq!(first.text, "Привет!");
+
+    // Многострочная реплика без \r в тексте
+    assert_eq!(result.subtitles[1].text, "First line\nSecond line");
+    // Точка вместо запятой в миллисекундах
+    assert_time(result.subtitles[2].start_time, 3661.25);
+  }
+
+  #[test]
+  fn test_parse_srt_keeps_overlapping_cues() {
+    let result = parse_srt(OVERLAPPING_SRT);
+    assert!(result.warnings.is_empty());
+    assert_eq!(result.subtitles.len(), 2);
+    assert!(result.subtitles[1].start_time < result.subtitles[0].end_time);
+    // Теги форматирования убираются
+    assert_eq!(result.subtitles[0].text, "Speaker one");
+  }
+
+  #[test]
+  fn test_parse_srt_collects_warnings() {
+    let result = parse_srt(MALFORMED_SRT);
+    assert_eq!(result.subtitles.len(), 2);
+    assert_eq!(result.warnings.len(), 3, "{:?}", result.warnings);
+    assert!(result.warnings.iter().any(|w| w.contains("invalid timing")));
+    assert!(result.warnings.iter().any(|w| w.contains("ends before")));
+    assert!(result.warnings.iter().any(|w| w.contains("no timing")));
+  }
+
+  #[test]
+  fn test_parse_vtt_ignores_cue_settings_and_blocks() {
+    let result = parse_vtt(CUE_SETTINGS_VTT);
+    assert!(result.warnings.is_empty(), "{:?}", result.warnings);
+    assert_eq!(result.subtitles.len(), 3);
+
+    // Короткая форма MM:SS.mmm и настройки после тайминга
+    assert_time(result.subtitles[0].start_time, 1.0);
+    assert_time(result.subtitles[0].end_time, 4.0);
+    assert_eq!(result.subtitles[0].text, "Positioned cue");
+
+    // Идентификатор реплики и голосовой тег
+    assert_eq!(result.subtitles[1].text, "Alice: hello");
+    assert_eq!(result.subtitles[2].text, "Multi\nline");
+  }
+
+  #[test]
+  fn test_parse_vtt_without_header_warns() {
+    let result = parse_vtt("00:00:01.000 --> 00:00:02.000\nText\n");
+    assert_eq!(result.subtitles.len(), 1);
+    assert_eq!(result.warnings.len(), 1);
+  }
+
+  #[test]
+  fn test_format_round_trip() {
+    let subtitles = parse_srt(BOM_CRLF_SRT).subtitles;
+
+    let srt = format_srt(&subtitles);
+    assert!(srt.starts_with("1\n00:00:01,000 --> 00:00:03,500\nПривет!\n"));
+    assert!(srt.contains("01:01:01,250"));
+    assert_eq!(parse_srt(&srt).subtitles.len(), subtitles.len());
+
+    let vtt = format_vtt(&subtitles);
+    assert!(vtt.starts_with("WEBVTT\n\n00:00:01.000 --> 00:00:03.500\n"));
+    let reparsed = parse_vtt(&vtt);
+    assert!(reparsed.warnings.is_empty());
+    assert_eq!(reparsed.subtitles[1].text, "First line\nSecond line");
+  }
+
+  #[test]
+  fn test_format_skips_disabled_and_sorts() {
+    let late = Subtitle::new("Late".to_string(), 5.0, 6.0);
+    let early = Subtitle::new("Early".to_string(), 1.0, 2.0);
+    let mut hidden = Subtitle::new("Hidden".to_string(), 3.0, 4.0);
+    hidden.enabled = false;
+
+    let srt = format_srt(&[late, hidden, early]);
+    assert!(srt.find("Early").unwrap() < srt.find("Late").unwrap());
+    assert!(!srt.contains("Hidden"));
+  }
+
+  #[test]
+  fn test_parse_timestamp() {
+    assert_eq!(parse_timestamp("00:00:01,500"), Some(1.5));
+    assert_eq!(parse_timestamp("01:01.5"), Some(61.5));
+    assert_eq!(parse_timestamp("100:00:00.000"), Some(360000.0));
+    assert_eq!(parse_timestamp("00:61:00,000"), None);
+    assert_eq!(parse_timestamp("garbage"), None);
+  }
+}
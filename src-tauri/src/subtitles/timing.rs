@@ -0,0 +1,481 @@
+// Уточнение тайминга субтитров по границам речи
+//
+// Аудио вокруг каждой реплики (± окно поиска) размечается простым VAD:
+// кадр 10 мс считается речью, если его энергия заметно выше шумового фона
+// окна, а частота переходов через ноль не похожа на шум. Начало и конец
+// реплики притягиваются к ближайшим началу и концу речи в пределах
+// максимального сдвига, не залезая на соседние реплики. Ровный фон без
+// пауз (музыкальная подложка) не дает границ, такие реплики не меняются.
+
+use crate::video_compiler::schema::subtitles::Subtitle;
+use serde::{Deserialize, Serialize};
+
+/// Длительность кадра VAD (секунды)
+const FRAME_SECS: f64 = 0.01;
+
+/// Превышение шумового фона окна, с которого кадр считается речью (дБ)
+const SPEECH_ABOVE_FLOOR_DB: f32 = 12.0;
+
+/// Абсолютный порог речи (dBFS): тише этого речь не ищется
+const SPEECH_MIN_DB: f32 = -50.0;
+
+/// Процентиль энергии кадров, принимаемый за шумовой фон окна
+const NOISE_FLOOR_PERCENTILE: f32 = 0.1;
+
+/// Переходов через ноль в секунду, выше которых кадр считается шумом
+const MAX_SPEECH_ZCR_HZ: f32 = 2500.0;
+
+/// Паузы внутри речи короче этого значения заполняются (секунды)
+const MAX_PAUSE_SECS: f64 = 0.1;
+
+/// Участки речи короче этого значения отбрасываются как щелчки (секунды)
+const MIN_SPEECH_SECS: f64 = 0.05;
+
+/// Минимальная длительность реплики после уточнения (секунды)
+const MIN_CUE_SECS: f64 = 0.1;
+
+/// Параметры уточнения тайминга
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SubtitleTimingOptions {
+  /// Запас аудио до и после реплики для поиска границ (секунды)
+  pub search_window: f64,
+  /// Максимальный сдвиг начала или конца реплики (секунды)
+  pub max_adjustment: f64,
+  /// Минимальный промежуток между соседними репликами (секунды)
+  pub min_gap: f64,
+}
+
+impl Default for SubtitleTimingOptions {
+  fn default() -> Self {
+    Self {
+      search_window: 0.5,
+      max_adjustment: 0.4,
+      min_gap: 0.05,
+    }
+  }
+}
+
+impl SubtitleTimingOptions {
+  /// Окно аудио для реплики: (начало, длительность) в секундах
+  pub fn search_range(&self, subtitle: &Subtitle) -> (f64, f64) {
+    let start = (subtitle.start_time - self.search_window).max(0.0);
+    let end = subtitle.end_time + self.search_window;
+    (start, (end - start).max(0.0))
+  }
+}
+
+/// Границы речи, найденные вокруг реплики
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SpeechBoundaries {
+  /// Ближайшее к началу реплики начало речи в пределах сдвига
+  pub onset: Option<f64>,
+  /// Ближайший к концу реплики конец речи в пределах сдвига
+  pub offset: Option<f64>,
+  /// В окне реплики найдена речь
+  pub has_speech: bool,
+}
+
+/// Итог уточнения одной реплики
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CueAdjustmentStatus {
+  /// Начало и/или конец притянуты к границам речи
+  Adjusted,
+  /// Речь найдена, но подходящих границ нет
+  Unchanged,
+  /// Речь не найдена (музыка или тишина), реплика не тронута
+  NoSpeech,
+}
+
+/// Отчет о сдвиге реплики
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CueAdjustment {
+  pub subtitle_id: String,
+  pub original_start: f64,
+  pub original_end: f64,
+  pub start_time: f64,
+  pub end_time: f64,
+  pub status: CueAdjustmentStatus,
+}
+
+impl CueAdjustment {
+  /// Сдвиг начала (секунды, отрицательный - раньше)
+  pub fn start_delta(&self) -> f64 {
+    self.start_time - self.original_start
+  }
+
+  /// Сдвиг конца (секунды, отрицательный - раньше)
+  pub fn end_delta(&self) -> f64 {
+    self.end_time - self.original_end
+  }
+}
+
+/// Результат уточнения тайминга
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubtitleTimingRefinement {
+  /// Реплики с уточненным таймингом в исходном порядке
+  pub subtitles: Vec<Subtitle>,
+  /// Отчет по каждой реплике в том же порядке
+  pub adjustments: Vec<CueAdjustment>,
+}
+
+/// Найти границы речи вокруг реплики.
+///
+/// `samples` - моно PCM окна, начинающегося с `window_start` секунд.
+pub fn detect_speech_boundaries(
+  samples: &[f32],
+  sample_rate: u32,
+  window_start: f64,
+  subtitle: &Subtitle,
+  options: &SubtitleTimingOptions,
+) -> SpeechBoundaries {
+  let speech = speech_mask(samples, sample_rate);
+  if !speech.iter().any(|&frame| frame) {
+    return SpeechBoundaries::default();
+  }
+
+  let frame_time = |index: usize| window_start + index as f64 * FRAME_SECS;
+  let mut onsets = Vec::new();
+  let mut offsets = Vec::new();
+  for index in 1..speech.len() {
+    match (speech[index - 1], speech[index]) {
+      (false, true) => onsets.push(frame_time(index)),
+      (true, false) => offsets.push(frame_time(index)),
+      _ => {}
+    }
+  }
+
+  let radius = options.max_adjustment.min(options.search_window);
+  let nearest = |candidates: &[f64], target: f64| {
+    candidates
+      .iter()
+      .copied()
+      .filter(|time| (time - target).abs() <= radius)
+      .min_by(|a, b| (a - target).abs().total_cmp(&(b - target).abs()))
+  };
+
+  SpeechBoundaries {
+    onset: nearest(&onsets, subtitle.start_time),
+    offset: nearest(&offsets, subtitle.end_time),
+    has_speech: true,
+  }
+}
+
+/// Разметка кадров окна: true - речь
+fn speech_mask(samples: &[f32], sample_rate: u32) -> Vec<bool> {
+  let frame = ((sample_rate as f64 * FRAME_SECS) as usize).max(1);
+  let frames: Vec<(f32, f32)> = samples
+    .chunks_exact(frame)
+    .map(|chunk| {
+      let rms = (chunk.iter().map(|s| s * s).sum::<f32>() / frame as f32).sqrt();
+      let crossings = chunk
+        .windows(2)
+        .filter(|pair| (pair[0] >= 0.0) != (pair[1] >= 0.0))
+        .count();
+      (
+        20.0 * rms.max(1e-6).log10(),
+        crossings as f32 / FRAME_SECS as f32,
+      )
+    })
+    .collect();
+  if frames.is_empty() {
+    return Vec::new();
+  }
+
+  let mut levels: Vec<f32> = frames.iter().map(|(db, _)| *db).collect();
+  levels.sort_by(f32::total_cmp);
+  let floor = levels[((levels.len() - 1) as f32 * NOISE_FLOOR_PERCENTILE) as usize];
+  let threshold = (floor + SPEECH_ABOVE_FLOOR_DB).max(SPEECH_MIN_DB);
+
+  let mut mask: Vec<bool> = frames
+    .iter()
+    .map(|&(db, zcr)| db >= threshold && zcr <= MAX_SPEECH_ZCR_HZ)
+    .collect();
+
+  // Короткие паузы между словами не считаются концом речи, а короткие
+  // всплески - ее началом
+  fill_runs(&mut mask, false, frames_for(MAX_PAUSE_SECS));
+  fill_runs(&mut mask, true, frames_for(MIN_SPEECH_SECS));
+  mask
+}
+
+fn frames_for(secs: f64) -> usize {
+  (secs / FRAME_SECS).round() as usize
+}
+
+/// Инвертировать внутренние участки со значением `value` короче `max_len`
+fn fill_runs(mask: &mut [bool], value: bool, max_len: usize) {
+  let mut index = 0;
+  while index < mask.len() {
+    if mask[index] != value {
+      index += 1;
+      continue;
+    }
+    let start = index;
+    while index < mask.len() && mask[index] == value {
+      index += 1;
+    }
+    let inner = start > 0 && index < mask.len();
+    if inner && index - start < max_len {
+      mask[start..index].fill(!value);
+    }
+  }
+}
+
+/// Применить найденные границы к репликам.
+///
+/// `boundaries[i]` соответствует `subtitles[i]`. Сдвиги, которые привели бы
+/// к перекрытию соседних реплик (с учетом `min_gap`), урезаются; если
+/// реплики перекрывались изначально, их тайминг не ухудшается.
+pub fn apply_speech_boundaries(
+  mut subtitles: Vec<Subtitle>,
+  boundaries: &[SpeechBoundaries],
+  options: &SubtitleTimingOptions,
+) -> SubtitleTimingRefinement {
+  let proposed: Vec<(f64, f64)> = subtitles
+    .iter()
+    .zip(boundaries)
+    .map(|(subtitle, found)| {
+      let start = found.onset.unwrap_or(subtitle.start_time);
+      let end = found.offset.unwrap_or(subtitle.end_time);
+      if end - start < MIN_CUE_SECS {
+        (subtitle.start_time, subtitle.end_time)
+      } else {
+        (start, end)
+      }
+    })
+    .collect();
+
+  let mut order: Vec<usize> = (0..subtitles.len()).collect();
+  order.sort_by(|&a, &b| subtitles[a].start_time.total_cmp(&subtitles[b].start_time));
+
+  let mut timing: Vec<(f64, f64)> = subtitles
+    .iter()
+    .map(|subtitle| (subtitle.start_time, subtitle.end_time))
+    .collect();
+  let mut previous_end: Option<f64> = None;
+  for (position, &index) in order.iter().enumerate() {
+    let original = timing[index];
+    let (mut start, mut end) = proposed[index];
+
+    if let Some(previous_end) = previous_end {
+      start = constrain(start, original.0, previous_end + options.min_gap, true);
+    }
+    if let Some(&next) = order.get(position + 1) {
+      // Начало следующей реплики может сдвинуться только к своей предложенной
+      // границе, поэтому ограничиваемся более ранним из двух значений
+      let next_start = proposed[next].0.min(timing[next].0);
+      end = constrain(end, original.1, next_start - options.min_gap, false);
+    }
+    if end - start < MIN_CUE_SECS {
+      (start, end) = original;
+    }
+
+    timing[index] = (start, end);
+    previous_end = Some(end);
+  }
+
+  let adjustments = subtitles
+    .iter_mut()
+    .zip(boundaries)
+    .zip(timing)
+    .map(|((subtitle, found), (start, end))| {
+      let status = if !found.has_speech {
+        CueAdjustmentStatus::NoSpeech
+      } else if start != subtitle.start_time || end != subtitle.end_time {
+        CueAdjustmentStatus::Adjusted
+      } else {
+        CueAdjustmentStatus::Unchanged
+      };
+      let adjustment = CueAdjustment {
+        subtitle_id: subtitle.id.clone(),
+        original_start: subtitle.start_time,
+        original_end: subtitle.end_time,
+        start_time: start,
+        end_time: end,
+        status,
+      };
+      subtitle.start_time = start;
+      subtitle.end_time = end;
+      adjustment
+    })
+    .collect();
+
+  SubtitleTimingRefinement {
+    subtitles,
+    adjustments,
+  }
+}
+
+/// Ограничить значение границей `bound` снизу (`lower`) или сверху.
+/// Если исходное значение уже нарушало границу, возвращается оно.
+fn constrain(value: f64, original: f64, bound: f64, lower: bool) -> f64 {
+  let within = |v: f64| if lower { v >= bound } else { v <= bound };
+  if within(value) {
+    value
+  } else if within(original) {
+    bound
+  } else {
+    original
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const SAMPLE_RATE: u32 = 8000;
+
+  /// Дорожка с тональными "фразами", музыкальной подложкой и слабым шумом
+  fn synthetic_track(duration: f64, bursts: &[(f64, f64)], music: Option<(f64, f64)>) -> Vec<f32> {
+    let mut state = 7u32;
+    (0..(duration * SAMPLE_RATE as f64) as usize)
+      .map(|i| {
+        let t = i as f64 / SAMPLE_RATE as f64;
+        state = state.wrapping_mul(1103515245).wrapping_add(12345);
+        let mut sample = (((state >> 16) as f32 / 32768.0) - 1.0) * 0.001;
+        if bursts.iter().any(|&(from, to)| t >= from && t < to) {
+          // Основной тон с "интонацией", как у голоса
+          let pitch = 180.0 + 40.0 * (t * 3.0).sin();
+          sample += 0.5 * (2.0 * std::f64::consts::PI * pitch * t).sin() as f32;
+        }
+        if music.is_some_and(|(from, to)| t >= from && t < to) {
+          let chord = [220.0, 440.0, 660.0]
+            .iter()
+            .map(|f| (2.0 * std::f64::consts::PI * f * t).sin())
+            .sum::<f64>();
+          sample += 0.2 * chord as f32;
+        }
+        sample
+      })
+      .collect()
+  }
+
+  fn refine(
+    track: &[f32],
+    subtitles: Vec<Subtitle>,
+    options: &SubtitleTimingOptions,
+  ) -> SubtitleTimingRefinement {
+    let boundaries: Vec<SpeechBoundaries> = subtitles
+      .iter()
+      .map(|subtitle| {
+        let (start, duration) = options.search_range(subtitle);
+        let from = (start * SAMPLE_RATE as f64) as usize;
+        let to = (((start + duration) * SAMPLE_RATE as f64) as usize).min(track.len());
+        detect_speech_boundaries(&track[from..to], SAMPLE_RATE, start, subtitle, options)
+      })
+      .collect();
+    apply_speech_boundaries(subtitles, &boundaries, options)
+  }
+
+  fn cue(start: f64, end: f64) -> Subtitle {
+    Subtitle::new("text".to_string(), start, end)
+  }
+
+  fn assert_near(actual: f64, expected: f64) {
+    assert!(
+      (actual - expected).abs() <= 0.02,
+      "expected {expected}, got {actual}"
+    );
+  }
+
+  #[test]
+  fn test_snaps_offset_cues_to_speech() {
+    let track = synthetic_track(7.0, &[(1.0, 2.5), (3.5, 5.0)], None);
+    let options = SubtitleTimingOptions::default();
+    // Первая реплика опаздывает и обрывается раньше, вторая - наоборот
+    let result = refine(&track, vec![cue(1.3, 2.2), cue(3.2, 5.35)], &options);
+
+    assert_near(result.subtitles[0].start_time, 1.0);
+    assert_near(result.subtitles[0].end_time, 2.5);
+    assert_near(result.subtitles[1].start_time, 3.5);
+    assert_near(result.subtitles[1].end_time, 5.0);
+    assert!(result
+      .adjustments
+      .iter()
+      .all(|a| a.status == CueAdjustmentStatus::Adjusted));
+    assert_near(result.adjustments[0].start_delta(), -0.3);
+    assert_near(result.adjustments[1].end_delta(), -0.35);
+  }
+
+  #[test]
+  fn test_music_only_cue_is_untouched() {
+    let track = synthetic_track(12.0, &[(1.0, 2.0)], Some((6.0, 12.0)));
+    let options = SubtitleTimingOptions::default();
+    let result = refine(&track, vec![cue(1.2, 2.0), cue(8.0, 10.0)], &options);
+
+    assert_near(result.subtitles[0].start_time, 1.0);
+    assert_eq!(result.subtitles[1].start_time, 8.0);
+    assert_eq!(result.subtitles[1].end_time, 10.0);
+    assert_eq!(result.adjustments[1].status, CueAdjustmentStatus::NoSpeech);
+  }
+
+  #[test]
+  fn test_respects_max_adjustment() {
+    let track = synthetic_track(4.0, &[(1.0, 3.0)], None);
+    let options = SubtitleTimingOptions {
+      max_adjustment: 0.2,
+      ..Default::default()
+    };
+    let result = refine(&track, vec![cue(1.3, 2.9)], &options);
+
+    // Начало речи дальше допустимого сдвига, конец - в пределах
+    assert_eq!(result.subtitles[0].start_time, 1.3);
+    assert_near(result.subtitles[0].end_time, 3.0);
+  }
+
+  #[test]
+  fn test_keeps_min_gap_between_cues() {
+    let track = synthetic_track(4.0, &[(0.5, 1.5), (1.62, 3.0)], None);
+    let options = SubtitleTimingOptions {
+      min_gap: 0.2,
+      ..Default::default()
+    };
+    let result = refine(&track, vec![cue(0.5, 1.2), cue(1.8, 3.0)], &options);
+
+    let first = &result.subtitles[0];
+    let second = &result.subtitles[1];
+    assert!(first.end_time > 1.2);
+    assert!(second.start_time < 1.8);
+    assert!(second.start_time - first.end_time >= options.min_gap - 1e-9);
+  }
+
+  #[test]
+  fn test_original_overlap_is_not_made_worse() {
+    let boundaries = vec![
+      SpeechBoundaries {
+        onset: None,
+        offset: Some(2.4),
+        has_speech: true,
+      },
+      SpeechBoundaries {
+        onset: Some(1.9),
+        offset: None,
+        has_speech: true,
+      },
+    ];
+    let result = apply_speech_boundaries(
+      vec![cue(0.0, 2.2), cue(2.0, 3.0)],
+      &boundaries,
+      &SubtitleTimingOptions::default(),
+    );
+
+    assert_eq!(result.subtitles[0].end_time, 2.2);
+    assert_eq!(result.subtitles[1].start_time, 2.0);
+  }
+
+  #[test]
+  fn test_silent_window_has_no_speech() {
+    let track = synthetic_track(3.0, &[], None);
+    let subtitle = cue(1.0, 2.0);
+    let found = detect_speech_boundaries(
+      &track,
+      SAMPLE_RATE,
+      0.0,
+      &subtitle,
+      &SubtitleTimingOptions::default(),
+    );
+    assert_eq!(found, SpeechBoundaries::default());
+  }
+}
@@ -372,6 +372,22 @@ pub async fn get_metrics_history(
   ))
 }
 
+crate::command_manifest!(
+  ADVANCED_METRICS_MANIFEST,
+  "video_compiler::advanced_metrics",
+  [
+    create_custom_alert,
+  ],
+  internal: [
+    get_cache_performance_metrics,
+    set_cache_alert_thresholds,
+    get_cache_alerts,
+    get_gpu_utilization_metrics,
+    get_memory_usage_metrics,
+    get_metrics_history,
+  ]
+);
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -0,0 +1,68 @@
+//! Audio Sync Commands - команды синхронизации внешнего аудио с видео
+
+use crate::video_compiler::core::audio_sync::{
+  decode_pcm_window, estimate_offset, AudioOffsetEstimate, DEFAULT_MAX_OFFSET_SECS,
+  DEFAULT_SYNC_WINDOW_SECS, SYNC_SAMPLE_RATE,
+};
+use crate::video_compiler::error::{Result, VideoCompilerError};
+use crate::video_compiler::VideoCompilerState;
+use std::path::Path;
+use tauri::State;
+
+/// Оценить смещение внешней аудиозаписи относительно звука камеры.
+///
+/// Возвращаемое `offset_secs` подходит для `ExternalAudio::offset_secs`:
+/// момент `t` видео соответствует моменту `t + offset_secs` аудиофайла.
+#[tauri::command]
+pub async fn probe_audio_offset(
+  state: State<'_, VideoCompilerState>,
+  video_path: String,
+  audio_path: String,
+) -> Result<AudioOffsetEstimate> {
+  for path in [&video_path, &audio_path] {
+    if !Path::new(path).exists() {
+      return Err(VideoCompilerError::MediaFileError {
+        path: path.clone(),
+        reason: "Файл не найден".to_string(),
+      });
+    }
+  }
+
+  let ffmpeg_path = state.ffmpeg_path.read().await.clone();
+
+  // Для аудио берем окно с запасом на максимальное смещение в обе стороны
+  let reference = decode_pcm_window(
+    &ffmpeg_path,
+    Path::new(&video_path),
+    DEFAULT_MAX_OFFSET_SECS,
+    DEFAULT_SYNC_WINDOW_SECS,
+  )
+  .await?;
+  let other = decode_pcm_window(
+    &ffmpeg_path,
+    Path::new(&audio_path),
+    0.0,
+    DEFAULT_SYNC_WINDOW_SECS + 2.0 * DEFAULT_MAX_OFFSET_SECS,
+  )
+  .await?;
+
+  let estimate = estimate_offset(
+    &reference,
+    &other,
+    SYNC_SAMPLE_RATE,
+    DEFAULT_MAX_OFFSET_SECS,
+  )
+  .ok_or_else(|| VideoCompilerError::validation("Недостаточно аудиоданных для оценки смещения"))?;
+
+  // Окно видео начинается с DEFAULT_MAX_OFFSET_SECS, окно аудио - с нуля
+  Ok(AudioOffsetEstimate {
+    offset_secs: estimate.offset_secs - DEFAULT_MAX_OFFSET_SECS,
+    confidence: estimate.confidence,
+  })
+}
+
+crate::command_manifest!(
+  AUDIO_SYNC_COMMANDS_MANIFEST,
+  "video_compiler::audio_sync_commands",
+  [probe_audio_offset,]
+);
@@ -1,8 +1,14 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use tauri::command;
+use std::sync::Arc;
+use tauri::{command, AppHandle, Emitter, State};
 use uuid::Uuid;
 
+use crate::video_compiler::services::batch_export::{
+  BatchExport, BatchExportControl, BatchExportProgressSink, BatchExportSummary, ExportRequest,
+};
+use crate::video_compiler::{VideoCompilerEvent, VideoCompilerState};
+
 /// Статус пакетного задания
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum BatchJobStatus {
@@ -379,6 +385,86 @@ pub async fn set_batch_job_status(job_id: String, status: BatchJobStatus) -> Res
   }
 }
 
+/// Управление выполняющимися пакетными экспортами
+static BATCH_EXPORTS: Lazy<Mutex<HashMap<String, Arc<BatchExportControl>>>> =
+  Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn batch_exports() -> std::sync::MutexGuard<'static, HashMap<String, Arc<BatchExportControl>>> {
+  BATCH_EXPORTS
+    .lock()
+    .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// Пакетный экспорт нескольких проектов или отрезков одного проекта.
+///
+/// Каждый элемент становится задачей рендеринга; элементы запускаются по
+/// мере освобождения слотов. Прогресс отправляется событием
+/// `BatchExportProgress`, итог возвращается и отправляется событием
+/// `BatchExportCompleted`. Отмена - командой `cancel_batch_export`.
+#[command]
+pub async fn batch_export(
+  app: AppHandle,
+  state: State<'_, VideoCompilerState>,
+  requests: Vec<ExportRequest>,
+  batch_id: Option<String>,
+) -> Result<BatchExportSummary, String> {
+  if requests.is_empty() {
+    return Err("Пакет экспорта не содержит элементов".to_string());
+  }
+  let render_service = state
+    .services
+    .get_render_service()
+    .ok_or_else(|| "RenderService не найден".to_string())?;
+
+  let batch_id = batch_id.unwrap_or_else(|| Uuid::new_v4().to_string());
+  let batch = BatchExport::new(batch_id.clone(), render_service, requests);
+  {
+    let mut exports = batch_exports();
+    if exports.contains_key(&batch_id) {
+      return Err(format!("Пакетный экспорт {batch_id} уже выполняется"));
+    }
+    exports.insert(batch_id.clone(), batch.control());
+  }
+
+  let emitter = app.clone();
+  let sink: BatchExportProgressSink = Arc::new(move |progress| {
+    if let Err(e) = emitter.emit(
+      "video-compiler",
+      &VideoCompilerEvent::BatchExportProgress { progress },
+    ) {
+      log::warn!("Failed to emit batch export progress: {e}");
+    }
+  });
+  let summary = batch.run(Some(sink)).await;
+  batch_exports().remove(&batch_id);
+
+  if let Err(e) = app.emit(
+    "video-compiler",
+    &VideoCompilerEvent::BatchExportCompleted {
+      summary: summary.clone(),
+    },
+  ) {
+    log::warn!("Failed to emit batch export summary: {e}");
+  }
+  Ok(summary)
+}
+
+/// Отменить пакетный экспорт.
+///
+/// Ожидающие элементы отменяются, выполняющиеся дорабатывают до конца;
+/// с `force` отменяются и они. Возвращает `false`, если пакет не найден.
+#[command]
+pub async fn cancel_batch_export(batch_id: String, force: Option<bool>) -> Result<bool, String> {
+  match batch_exports().get(&batch_id) {
+    Some(control) => {
+      control.cancel(force.unwrap_or(false));
+      log::info!("Отменен пакетный экспорт {batch_id}");
+      Ok(true)
+    }
+    None => Ok(false),
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -7,26 +7,35 @@ use serde_json;
 use std::collections::HashMap;
 use std::path::PathBuf;
 use tauri::State;
+use tokio_util::sync::CancellationToken;
 
+use crate::core::tasks::{TaskKind, TASKS};
 use crate::video_compiler::error::{Result, VideoCompilerError};
+use crate::video_compiler::schema::ProjectSchema;
 use crate::video_compiler::services::cache_service::CacheStats;
+use crate::video_compiler::services::cache_warmer::{
+  CacheWarmer, RecentProject, RecentProjects, WarmupReport, MAX_WARM_CONCURRENCY,
+};
+use crate::video_compiler::services::stale_artifacts::{self, StaleArtifactsReport};
 
 use super::state::VideoCompilerState;
 
 /// Очистить весь кэш рендеринга
 #[tauri::command]
 pub async fn clear_render_cache(state: State<'_, VideoCompilerState>) -> Result<()> {
-  let cache_service = state.services.get_cache_service().ok_or_else(|| {
-    VideoCompilerError::InternalError("CacheService не инициализирован".to_string())
-  })?;
+  crate::instrumented_command!(clear_render_cache, {
+    let cache_service = state.services.get_cache_service().ok_or_else(|| {
+      VideoCompilerError::InternalError("CacheService не инициализирован".to_string())
+    })?;
 
-  cache_service.clear_render_cache().await.map_err(|e| {
-    log::error!("Ошибка очистки кэша рендеринга: {e}");
-    VideoCompilerError::CacheError(format!("Не удалось очистить кэш рендеринга: {e}"))
-  })?;
+    cache_service.clear_render_cache().await.map_err(|e| {
+      log::error!("Ошибка очистки кэша рендеринга: {e}");
+      VideoCompilerError::CacheError(format!("Не удалось очистить кэш рендеринга: {e}"))
+    })?;
 
-  log::info!("Кэш рендеринга успешно очищен");
-  Ok(())
+    log::info!("Кэш рендеринга успешно очищен");
+    Ok(())
+  })
 }
 
 /// Очистить кэш конкретного проекта
@@ -35,50 +44,56 @@ pub async fn clear_project_cache(
   project_id: String,
   state: State<'_, VideoCompilerState>,
 ) -> Result<()> {
-  // Валидируем project_id
-  if project_id.is_empty() {
-    return Err(VideoCompilerError::InvalidParameter(
-      "ID проекта не может быть пустым".to_string(),
-    ));
-  }
-
-  let cache_service = state.services.get_cache_service().ok_or_else(|| {
-    VideoCompilerError::InternalError("CacheService не инициализирован".to_string())
-  })?;
+  crate::instrumented_command!(clear_project_cache, {
+    // Валидируем project_id
+    if project_id.is_empty() {
+      return Err(VideoCompilerError::InvalidParameter(
+        "ID проекта не может быть пустым".to_string(),
+      ));
+    }
 
-  cache_service
-    .clear_project_cache(&project_id)
-    .await
-    .map_err(|e| {
-      log::error!("Ошибка очистки кэша проекта {project_id}: {e}");
-      VideoCompilerError::CacheError(format!("Не удалось очистить кэш проекта {project_id}: {e}"))
+    let cache_service = state.services.get_cache_service().ok_or_else(|| {
+      VideoCompilerError::InternalError("CacheService не инициализирован".to_string())
     })?;
 
-  log::info!("Кэш проекта {project_id} успешно очищен");
-  Ok(())
+    cache_service
+      .clear_project_cache(&project_id)
+      .await
+      .map_err(|e| {
+        log::error!("Ошибка очистки кэша проекта {project_id}: {e}");
+        VideoCompilerError::CacheError(format!("Не удалось очистить кэш проекта {project_id}: {e}"))
+      })?;
+
+    log::info!("Кэш проекта {project_id} успешно очищен");
+    Ok(())
+  })
 }
 
 /// Получить размер кэша
 #[tauri::command]
 #[allow(dead_code)]
 pub async fn get_cache_size(state: State<'_, VideoCompilerState>) -> Result<u64> {
-  let cache_service = state
-    .services
-    .get_cache_service()
-    .ok_or_else(|| VideoCompilerError::validation("CacheService не найден"))?;
-  let stats = cache_service.get_cache_stats().await?;
-  Ok((stats.total_size_mb * 1024.0 * 1024.0) as u64)
+  crate::instrumented_command!(get_cache_size, {
+    let cache_service = state
+      .services
+      .get_cache_service()
+      .ok_or_else(|| VideoCompilerError::validation("CacheService не найден"))?;
+    let stats = cache_service.get_cache_stats().await?;
+    Ok((stats.total_size_mb * 1024.0 * 1024.0) as u64)
+  })
 }
 
 /// Получить статистику использования кэша
 #[tauri::command]
 #[allow(dead_code)]
 pub async fn get_cache_stats(state: State<'_, VideoCompilerState>) -> Result<CacheStats> {
-  let cache_service = state
-    .services
-    .get_cache_service()
-    .ok_or_else(|| VideoCompilerError::validation("CacheService не найден"))?;
-  cache_service.get_cache_stats().await
+  crate::instrumented_command!(get_cache_stats, {
+    let cache_service = state
+      .services
+      .get_cache_service()
+      .ok_or_else(|| VideoCompilerError::validation("CacheService не найден"))?;
+    cache_service.get_cache_stats().await
+  })
 }
 
 /// Получить расширенную статистику кэша
@@ -87,22 +102,24 @@ pub async fn get_cache_stats(state: State<'_, VideoCompilerState>) -> Result<Cac
 pub async fn get_cache_stats_detailed(
   state: State<'_, VideoCompilerState>,
 ) -> Result<serde_json::Value> {
-  let cache = state.cache_manager.read().await;
-  let stats = cache.get_stats();
-  let memory_usage = cache.get_memory_usage();
-
-  Ok(serde_json::json!({
-    "preview_hit_ratio": stats.preview_hit_ratio(),
-    "memory_usage_mb": memory_usage.total_mb(),
-    "preview_hits": stats.preview_hits,
-    "preview_misses": stats.preview_misses,
-    "render_hits": stats.render_hits,
-    "render_misses": stats.render_misses,
-    "total_bytes": memory_usage.total_bytes,
-    "preview_bytes": memory_usage.preview_bytes,
-    "render_bytes": memory_usage.render_bytes,
-    "metadata_bytes": memory_usage.metadata_bytes,
-  }))
+  crate::instrumented_command!(get_cache_stats_detailed, {
+    let cache = state.cache_manager.read().await;
+    let stats = cache.get_stats();
+    let memory_usage = cache.get_memory_usage();
+
+    Ok(serde_json::json!({
+      "preview_hit_ratio": stats.preview_hit_ratio(),
+      "memory_usage_mb": memory_usage.total_mb(),
+      "preview_hits": stats.preview_hits,
+      "preview_misses": stats.preview_misses,
+      "render_hits": stats.render_hits,
+      "render_misses": stats.render_misses,
+      "total_bytes": memory_usage.total_bytes,
+      "preview_bytes": memory_usage.preview_bytes,
+      "render_bytes": memory_usage.render_bytes,
+      "metadata_bytes": memory_usage.metadata_bytes,
+    }))
+  })
 }
 
 /// Очистить устаревшие записи кэша
@@ -112,35 +129,63 @@ pub async fn clean_old_cache(
   max_age_days: u32,
   state: State<'_, VideoCompilerState>,
 ) -> Result<u64> {
-  // Валидируем параметр
-  if max_age_days == 0 {
-    return Err(VideoCompilerError::InvalidParameter(
-      "Максимальный возраст должен быть больше 0 дней".to_string(),
-    ));
-  }
+  crate::instrumented_command!(clean_old_cache, {
+    // Валидируем параметр
+    if max_age_days == 0 {
+      return Err(VideoCompilerError::InvalidParameter(
+        "Максимальный возраст должен быть больше 0 дней".to_string(),
+      ));
+    }
 
-  let cache_service = state.services.get_cache_service().ok_or_else(|| {
-    VideoCompilerError::InternalError("CacheService не инициализирован".to_string())
-  })?;
+    let cache_service = state.services.get_cache_service().ok_or_else(|| {
+      VideoCompilerError::InternalError("CacheService не инициализирован".to_string())
+    })?;
+
+    let cleaned_files = cache_service
+      .optimize_cache(max_age_days)
+      .await
+      .map_err(|e| {
+        log::error!("Ошибка очистки устаревшего кэша: {e}");
+        VideoCompilerError::CacheError(format!("Не удалось очистить устаревший кэш: {e}"))
+      })?;
+
+    log::info!("Очищено {cleaned_files} файлов старше {max_age_days} дней");
+    Ok(cleaned_files as u64)
+  })
+}
 
-  let cleaned_files = cache_service
-    .optimize_cache(max_age_days)
-    .await
-    .map_err(|e| {
-      log::error!("Ошибка очистки устаревшего кэша: {e}");
-      VideoCompilerError::CacheError(format!("Не удалось очистить устаревший кэш: {e}"))
+/// Удалить брошенные временные директории задач и привести кэш к лимиту.
+///
+/// Команда, которую интерфейс предлагает для деградировавших проверок
+/// состояния `cache` и `temp_dirs`.
+#[tauri::command]
+pub async fn clean_stale_artifacts(
+  state: State<'_, VideoCompilerState>,
+) -> Result<StaleArtifactsReport> {
+  crate::instrumented_command!(clean_stale_artifacts, {
+    let cache_service = state.services.get_cache_service().ok_or_else(|| {
+      VideoCompilerError::InternalError("CacheService не инициализирован".to_string())
     })?;
+    let (limit_bytes, temp_root) = {
+      let settings = state.settings.read().await;
+      (
+        settings.cache_size_mb as u64 * 1024 * 1024,
+        settings.temp_directory.clone(),
+      )
+    };
 
-  log::info!("Очищено {cleaned_files} файлов старше {max_age_days} дней");
-  Ok(cleaned_files as u64)
+    stale_artifacts::clean_stale_artifacts(cache_service.as_ref(), limit_bytes, &temp_root).await
+  })
 }
 
 /// Получить список закэшированных проектов
 #[tauri::command]
 #[allow(dead_code)]
 pub async fn get_cached_projects(state: State<'_, VideoCompilerState>) -> Result<Vec<String>> {
-  let cache = state.cache_manager.read().await;
-  Ok(cache.get_cached_projects())
+  crate::instrumented_command!(get_cached_projects, {
+    let cache = state.cache_manager.read().await;
+    Ok(cache.get_cached_projects())
+  })
 }
 
 /// Проверить наличие кэша для проекта
@@ -150,8 +195,10 @@ pub async fn has_project_cache(
   project_id: String,
   state: State<'_, VideoCompilerState>,
 ) -> Result<bool> {
-  let cache = state.cache_manager.read().await;
-  Ok(cache.has_project_cache(&project_id))
+  crate::instrumented_command!(has_project_cache, {
+    let cache = state.cache_manager.read().await;
+    Ok(cache.has_project_cache(&project_id))
+  })
 }
 
 /// Получить метаданные закэшированных медиафайлов
@@ -160,47 +207,55 @@ pub async fn has_project_cache(
 pub async fn get_cached_media_metadata(
   state: State<'_, VideoCompilerState>,
 ) -> Result<HashMap<String, crate::video_compiler::core::cache::MediaMetadata>> {
-  let cache = state.cache_manager.read().await;
-  Ok(cache.get_all_cached_metadata())
+  crate::instrumented_command!(get_cached_media_metadata, {
+    let cache = state.cache_manager.read().await;
+    Ok(cache.get_all_cached_metadata())
+  })
 }
 
 /// Очистить кэш метаданных медиафайлов
 #[tauri::command]
 pub async fn clear_media_metadata_cache(state: State<'_, VideoCompilerState>) -> Result<()> {
-  let mut render_cache = state.cache_manager.write().await;
-  // Очищаем все кэши (включая метаданные)
-  render_cache.clear_all().await;
-  Ok(())
+  crate::instrumented_command!(clear_media_metadata_cache, {
+    let mut render_cache = state.cache_manager.write().await;
+    // Очищаем все кэши (включая метаданные)
+    render_cache.clear_all().await;
+    Ok(())
+  })
 }
 
 /// Оптимизировать кэш (дефрагментация и удаление неиспользуемых данных)
 #[tauri::command]
 #[allow(dead_code)]
 pub async fn optimize_cache(state: State<'_, VideoCompilerState>) -> Result<usize> {
-  let cache_service = state
-    .services
-    .get_cache_service()
-    .ok_or_else(|| VideoCompilerError::validation("CacheService не найден"))?;
-  cache_service.optimize_cache(30).await // 30 дней по умолчанию
+  crate::instrumented_command!(optimize_cache, {
+    let cache_service = state
+      .services
+      .get_cache_service()
+      .ok_or_else(|| VideoCompilerError::validation("CacheService не найден"))?;
+    cache_service.optimize_cache(30).await // 30 дней по умолчанию
+  })
 }
 
 /// Экспортировать статистику кэша в JSON
 #[tauri::command]
 #[allow(dead_code)]
 pub async fn export_cache_stats(state: State<'_, VideoCompilerState>) -> Result<serde_json::Value> {
-  let cache_service = state
-    .services
-    .get_cache_service()
-    .ok_or_else(|| VideoCompilerError::validation("CacheService не найден"))?;
-  let stats = cache_service.get_cache_stats().await?;
-
-  Ok(serde_json::json!({
-    "total_size_mb": stats.total_size_mb,
-    "preview_cache_size_mb": stats.preview_cache_size_mb,
-    "render_cache_size_mb": stats.render_cache_size_mb,
-    "temp_files_size_mb": stats.temp_files_size_mb,
-    "total_files": stats.total_files,
-  }))
+  crate::instrumented_command!(export_cache_stats, {
+    let cache_service = state
+      .services
+      .get_cache_service()
+      .ok_or_else(|| VideoCompilerError::validation("CacheService не найден"))?;
+    let stats = cache_service.get_cache_stats().await?;
+
+    Ok(serde_json::json!({
+      "total_size_mb": stats.total_size_mb,
+      "preview_cache_size_mb": stats.preview_cache_size_mb,
+      "render_cache_size_mb": stats.render_cache_size_mb,
+      "temp_files_size_mb": stats.temp_files_size_mb,
+      "total_files": stats.total_files,
+    }))
+  })
 }
 
 /// Установить максимальный размер кэша
@@ -210,28 +265,32 @@ pub async fn set_cache_size_limit(
   size_mb: u64,
   state: State<'_, VideoCompilerState>,
 ) -> Result<()> {
-  let mut cache = state.cache_manager.write().await;
-  // Конвертируем MB в количество элементов (примерно 1MB = 10 элементов)
-  let items_per_mb = 10;
-  let total_items = (size_mb * items_per_mb) as usize;
-  // Распределяем между тремя типами кэша
-  cache.set_cache_limits(
-    total_items / 3, // preview
-    total_items / 3, // metadata
-    total_items / 3, // render
-  );
-  Ok(())
+  crate::instrumented_command!(set_cache_size_limit, {
+    let mut cache = state.cache_manager.write().await;
+    // Конвертируем MB в количество элементов (примерно 1MB = 10 элементов)
+    let items_per_mb = 10;
+    let total_items = (size_mb * items_per_mb) as usize;
+    // Распределяем между тремя типами кэша
+    cache.set_cache_limits(
+      total_items / 3, // preview
+      total_items / 3, // metadata
+      total_items / 3, // render
+    );
+    Ok(())
+  })
 }
 
 /// Получить текущий лимит размера кэша
 #[tauri::command]
 #[allow(dead_code)]
 pub async fn get_cache_size_limit(state: State<'_, VideoCompilerState>) -> Result<u64> {
-  let cache = state.cache_manager.read().await;
-  let (preview, metadata, render) = cache.get_cache_limits();
-  // Конвертируем обратно в MB (примерно 10 элементов = 1MB)
-  let total_items = preview + metadata + render;
-  Ok((total_items / 10) as u64)
+  crate::instrumented_command!(get_cache_size_limit, {
+    let cache = state.cache_manager.read().await;
+    let (preview, metadata, render) = cache.get_cache_limits();
+    // Конвертируем обратно в MB (примерно 10 элементов = 1MB)
+    let total_items = preview + metadata + render;
+    Ok((total_items / 10) as u64)
+  })
 }
 
 /// Предварительно загрузить медиафайлы в кэш
@@ -241,52 +300,138 @@ pub async fn preload_media_to_cache(
   _file_paths: Vec<String>,
   state: State<'_, VideoCompilerState>,
 ) -> Result<()> {
-  let _cache_service = state
-    .services
-    .get_cache_service()
-    .ok_or_else(|| VideoCompilerError::validation("CacheService не найден"))?;
-
-  // Упрощенная реализация - метод не реализован
-  Ok(())
+  crate::instrumented_command!(preload_media_to_cache, {
+    let _cache_service = state
+      .services
+      .get_cache_service()
+      .ok_or_else(|| VideoCompilerError::validation("CacheService не найден"))?;
+
+    // Упрощенная реализация - метод не реализован
+    Ok(())
+  })
 }
 
 /// Очистить весь кэш
 #[tauri::command]
 pub async fn clear_all_cache(state: State<'_, VideoCompilerState>) -> Result<()> {
-  // Очищаем через CacheService
-  if let Some(cache_service) = state.services.get_cache_service() {
-    cache_service.clear_all().await?;
-  }
+  crate::instrumented_command!(clear_all_cache, {
+    // Очищаем через CacheService
+    if let Some(cache_service) = state.services.get_cache_service() {
+      cache_service.clear_all().await?;
+    }
 
-  // Также очищаем in-memory RenderCache
-  let mut render_cache = state.cache_manager.write().await;
-  render_cache.clear_all().await;
+    // Также очищаем in-memory RenderCache
+    let mut render_cache = state.cache_manager.write().await;
+    render_cache.clear_all().await;
 
-  log::info!("All caches cleared successfully");
-  Ok(())
+    log::info!("All caches cleared successfully");
+    Ok(())
+  })
 }
 
 /// Очистить кэш превью
 #[tauri::command]
 pub async fn clear_preview_cache(state: State<'_, VideoCompilerState>) -> Result<()> {
-  let cache_service = state
-    .services
-    .get_cache_service()
-    .ok_or_else(|| VideoCompilerError::validation("CacheService не найден"))?;
-  cache_service.clear_preview_cache().await
+  crate::instrumented_command!(clear_preview_cache, {
+    let cache_service = state
+      .services
+      .get_cache_service()
+      .ok_or_else(|| VideoCompilerError::validation("CacheService не найден"))?;
+    cache_service.clear_preview_cache().await
+  })
 }
 
 /// Получить путь к каталогу кэша
 #[tauri::command]
 #[allow(dead_code)]
 pub async fn get_cache_path(state: State<'_, VideoCompilerState>) -> Result<PathBuf> {
-  let cache_service = state
-    .services
-    .get_cache_service()
-    .ok_or_else(|| VideoCompilerError::validation("CacheService не найден"))?;
-  cache_service.get_cache_path().await
+  crate::instrumented_command!(get_cache_path, {
+    let cache_service = state
+      .services
+      .get_cache_service()
+      .ok_or_else(|| VideoCompilerError::validation("CacheService не найден"))?;
+    cache_service.get_cache_path().await
+  })
+}
+
+/// Прогреть кэши метаданных, миниатюр и waveform для проекта.
+///
+/// Прогрев выполняется с низким приоритетом и виден в панели фоновых задач,
+/// откуда его можно отменить.
+#[tauri::command]
+pub async fn warm_project_cache(
+  project_schema: ProjectSchema,
+  state: State<'_, VideoCompilerState>,
+) -> Result<WarmupReport> {
+  crate::instrumented_command!(warm_project_cache, {
+    let token = CancellationToken::new();
+    let cancel = token.clone();
+    let task = TASKS.register_cancellable(
+      TaskKind::CacheWarmup,
+      format!("Прогрев кэша: {}", project_schema.metadata.name),
+      move || cancel.cancel(),
+    );
+
+    let report = CacheWarmer::from_services(&state.services, state.cache_manager.clone())
+      .with_concurrency(MAX_WARM_CONCURRENCY)
+      .warm_project(&project_schema, &token, |completed, total| {
+        task.set_progress(completed as f32 / total as f32 * 100.0)
+      })
+      .await;
+    task.finish();
+
+    Ok(report)
+  })
 }
 
+/// Отметить проект как недавно открытый (для автопрогрева кэша)
+#[tauri::command]
+pub async fn record_recent_project(project_path: String) -> Result<Vec<RecentProject>> {
+  let recent = RecentProjects::default_location()
+    .ok_or_else(|| VideoCompilerError::IoError("Директория приложения недоступна".to_string()))?;
+  recent.record(&project_path).await
+}
+
+/// Получить список недавно открытых проектов
+#[tauri::command]
+pub async fn get_recent_projects() -> Result<Vec<RecentProject>> {
+  match RecentProjects::default_location() {
+    Some(recent) => Ok(recent.load().await),
+    None => Ok(Vec::new()),
+  }
+}
+
+crate::command_manifest!(
+  CACHE_MANIFEST,
+  "video_compiler::cache",
+  [
+    clear_render_cache,
+    clear_project_cache,
+    get_cache_size,
+    get_cache_stats,
+    get_cache_stats_detailed,
+    clean_old_cache,
+    clean_stale_artifacts,
+    get_cached_projects,
+    has_project_cache,
+    get_cached_media_metadata,
+    clear_media_metadata_cache,
+    export_cache_stats,
+    set_cache_size_limit,
+    get_cache_size_limit,
+    preload_media_to_cache,
+    clear_all_cache,
+    clear_preview_cache,
+    get_cache_path,
+    warm_project_cache,
+    record_recent_project,
+    get_recent_projects,
+  ],
+  internal: [
+    optimize_cache,
+  ]
+);
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -0,0 +1,110 @@
+//! Command Preview Commands - просмотр команд FFmpeg без запуска рендеринга
+//!
+//! Команды строятся тем же FFmpegBuilder, что и при экспорте, предрендеринге
+//! и рендеринге кадра, поэтому аргументы совпадают с реальным запуском.
+
+use crate::video_compiler::commands::prerender_commands::{prerender_output_path, segment_project};
+use crate::video_compiler::commands::VideoCompilerState;
+use crate::video_compiler::core::preview::PreviewGenerator;
+use crate::video_compiler::core::render_priority::RenderPriority;
+use crate::video_compiler::core::{segment_cache, temp_storage};
+use crate::video_compiler::error::Result;
+use crate::video_compiler::ffmpeg_builder::{CommandPreview, FFmpegBuilder};
+use crate::video_compiler::schema::{ExportSettings, PreviewFormat, ProjectSchema};
+use std::path::Path;
+use tauri::State;
+
+/// Идентификатор временной директории для путей в предпросмотре команды
+const PREVIEW_JOB_ID: &str = "command_preview";
+
+/// Команда экспорта проекта так, как ее построит конвейер рендеринга
+async fn render_command_preview(
+  project: ProjectSchema,
+  output_path: &Path,
+  state: &VideoCompilerState,
+) -> Result<CommandPreview> {
+  let temp_root = state.settings.read().await.temp_directory.clone();
+  let segmented = segment_cache::supports_segment_cache(&project);
+  let muxes_subtitles =
+    project.settings.export.subtitle_mode.muxes() && !project.subtitles.is_empty();
+
+  let mut builder = FFmpegBuilder::new(project)
+    .with_process_limits(RenderPriority::default().limits(None))
+    .with_detected_capabilities()
+    .await;
+  if muxes_subtitles {
+    let subtitles_path = temp_storage::job_dir(&temp_root, PREVIEW_JOB_ID).join("subtitles.srt");
+    builder = builder.with_soft_subtitles(subtitles_path);
+  }
+
+  let mut preview = builder.preview_render_command(output_path).await?;
+  if muxes_subtitles {
+    preview
+      .warnings
+      .push("Файл субтитров создается во временной директории задачи при рендеринге".to_string());
+  }
+  if segmented {
+    preview.warnings.push(
+      "Проект экспортируется по сегментам с кэшем; показана команда для всего проекта".to_string(),
+    );
+  }
+  Ok(preview)
+}
+
+/// Показать команду FFmpeg для экспорта проекта без запуска.
+///
+/// `settings_override` заменяет настройки экспорта проекта.
+#[tauri::command]
+pub async fn get_render_command_preview(
+  project: ProjectSchema,
+  output_path: String,
+  settings_override: Option<ExportSettings>,
+  state: State<'_, VideoCompilerState>,
+) -> Result<CommandPreview> {
+  let mut project = project;
+  if let Some(export) = settings_override {
+    project.settings.export = export;
+  }
+  render_command_preview(project, Path::new(&output_path), &state).await
+}
+
+/// Показать команду FFmpeg для предрендеринга отрезка без запуска
+#[tauri::command]
+pub async fn get_prerender_command_preview(
+  project: ProjectSchema,
+  start_time: f64,
+  end_time: f64,
+  output_path: Option<String>,
+  state: State<'_, VideoCompilerState>,
+) -> Result<CommandPreview> {
+  let output_path = prerender_output_path(&state.settings, start_time, end_time, output_path).await;
+  let segment = segment_project(&project, start_time, end_time);
+  render_command_preview(segment, &output_path, &state).await
+}
+
+/// Показать команду FFmpeg для кадра timeline без запуска
+#[tauri::command]
+pub async fn get_timeline_frame_command_preview(
+  project: ProjectSchema,
+  timestamp: f64,
+  resolution: Option<(u32, u32)>,
+  quality: Option<u8>,
+  format: Option<PreviewFormat>,
+  state: State<'_, VideoCompilerState>,
+) -> Result<CommandPreview> {
+  let mut generator = PreviewGenerator::new(state.cache_manager.clone());
+  generator.set_ffmpeg_path(state.ffmpeg_path.read().await.as_str());
+  generator
+    .preview_timeline_frame_command(&project, timestamp, resolution, quality, format)
+    .await
+}
+
+crate::command_manifest!(
+  COMMAND_PREVIEW_COMMANDS_MANIFEST,
+  "video_compiler::command_preview_commands",
+  [
+    get_render_command_preview,
+    get_prerender_command_preview,
+    get_timeline_frame_command_preview,
+  ]
+);
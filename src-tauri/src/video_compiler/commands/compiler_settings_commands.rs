@@ -1,6 +1,7 @@
 //! Compiler Settings Commands - команды для управления настройками компилятора
 
 use crate::video_compiler::core::constants::{compiler::*, export::*, quality_presets};
+use crate::video_compiler::core::temp_storage::{self, TempDirectoryChange};
 use crate::video_compiler::error::Result;
 use crate::video_compiler::VideoCompilerState;
 use serde::{Deserialize, Serialize};
@@ -98,12 +99,42 @@ pub async fn set_memory_limit_advanced(
 #[tauri::command]
 pub async fn set_temp_directory_advanced(
   directory: String,
-  _state: State<'_, VideoCompilerState>,
+  state: State<'_, VideoCompilerState>,
 ) -> Result<bool> {
-  log::info!("Setting temp directory to: {directory}");
+  set_temp_directory(directory, Some(false), state).await?;
   Ok(true)
 }
 
+/// Сменить временную директорию компилятора.
+///
+/// Директория должна быть доступна для записи и иметь не меньше
+/// [`temp_storage::MIN_TEMP_FREE_SPACE_BYTES`] свободного места. С `migrate`
+/// кэш сегментов и предрендеренные файлы переносятся в новую директорию.
+/// Выбор сохраняется между запусками; уже запущенные задачи дописывают
+/// файлы в прежнюю директорию.
+#[tauri::command]
+pub async fn set_temp_directory(
+  path: String,
+  migrate: Option<bool>,
+  state: State<'_, VideoCompilerState>,
+) -> Result<TempDirectoryChange> {
+  let change = temp_storage::change_temp_directory(
+    &state.settings,
+    std::path::Path::new(&path),
+    migrate.unwrap_or(false),
+    temp_storage::MIN_TEMP_FREE_SPACE_BYTES,
+  )
+  .await?;
+
+  temp_storage::set_temp_root(change.current.clone());
+  match temp_storage::default_settings_path() {
+    Some(settings_path) => temp_storage::save_temp_directory(&settings_path, &change.current)?,
+    None => log::warn!("Директория приложения недоступна, временная директория не сохранена"),
+  }
+
+  Ok(change)
+}
+
 /// Установить уровень логирования
 #[tauri::command]
 pub async fn set_log_level_advanced(
@@ -220,6 +251,26 @@ pub async fn get_quality_presets_advanced(
   ])
 }
 
+crate::command_manifest!(
+  COMPILER_SETTINGS_COMMANDS_MANIFEST,
+  "video_compiler::compiler_settings_commands",
+  [
+    get_compiler_settings_advanced,
+    update_compiler_settings_advanced,
+    set_ffmpeg_path_advanced,
+    set_parallel_jobs_advanced,
+    set_memory_limit_advanced,
+    set_temp_directory_advanced,
+    set_temp_directory,
+    set_log_level_advanced,
+    reset_compiler_settings_advanced,
+    get_recommended_settings_advanced,
+    export_settings_advanced,
+    import_settings_advanced,
+    get_quality_presets_advanced,
+  ]
+);
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -0,0 +1,210 @@
+//! Effect Preset Commands - команды библиотеки пресетов эффектов
+
+use crate::video_compiler::commands::schema_commands::find_editable_clip;
+use crate::video_compiler::error::Result;
+use crate::video_compiler::schema::{Effect, Filter, ProjectSchema};
+use crate::video_compiler::services::effect_preset::{
+  EffectPreset, EffectPresetImport, EffectPresetStore,
+};
+use std::path::Path;
+
+/// Сохранить эффекты и фильтры как именованный пресет
+#[tauri::command]
+pub async fn save_effect_preset(
+  name: String,
+  effects: Vec<Effect>,
+  filters: Vec<Filter>,
+  category: String,
+) -> Result<EffectPreset> {
+  let preset = EffectPreset::new(name, category, effects, filters)?;
+  EffectPresetStore::default_location()?.add(preset).await
+}
+
+/// Получить пресеты категории или все пресеты
+#[tauri::command]
+pub async fn list_effect_presets(category: Option<String>) -> Result<Vec<EffectPreset>> {
+  EffectPresetStore::default_location()?
+    .list(category.as_deref())
+    .await
+}
+
+/// Переименовать пресет
+#[tauri::command]
+pub async fn rename_effect_preset(preset_id: String, name: String) -> Result<EffectPreset> {
+  EffectPresetStore::default_location()?
+    .rename(&preset_id, name)
+    .await
+}
+
+/// Удалить пресет
+#[tauri::command]
+pub async fn delete_effect_preset(preset_id: String) -> Result<()> {
+  EffectPresetStore::default_location()?
+    .delete(&preset_id)
+    .await
+}
+
+/// Применить пресет к клипам.
+///
+/// Каждый клип получает собственные копии эффектов и фильтров пресета с
+/// новыми ID, поэтому их можно настраивать независимо.
+#[tauri::command]
+pub async fn apply_effect_preset(
+  project_schema: ProjectSchema,
+  clip_ids: Vec<String>,
+  preset_id: String,
+) -> Result<ProjectSchema> {
+  let preset = EffectPresetStore::default_location()?
+    .get(&preset_id)
+    .await?;
+  apply_preset_to_clips(project_schema, &clip_ids, &preset)
+}
+
+/// Экспортировать пресет в файл для передачи другому пользователю
+#[tauri::command]
+pub async fn export_effect_preset(preset_id: String, path: String) -> Result<()> {
+  EffectPresetStore::default_location()?
+    .export(&preset_id, Path::new(&path))
+    .await
+}
+
+/// Импортировать пресет из файла.
+///
+/// Неизвестные типы эффектов и фильтров пропускаются и возвращаются в
+/// предупреждениях.
+#[tauri::command]
+pub async fn import_effect_preset(path: String) -> Result<EffectPresetImport> {
+  EffectPresetStore::default_location()?
+    .import(Path::new(&path))
+    .await
+}
+
+/// Добавить копии эффектов и фильтров пресета в проект и привязать их к клипам
+fn apply_preset_to_clips(
+  mut project_schema: ProjectSchema,
+  clip_ids: &[String],
+  preset: &EffectPreset,
+) -> Result<ProjectSchema> {
+  let positions = clip_ids
+    .iter()
+    .map(|clip_id| find_editable_clip(&project_schema, clip_id, false))
+    .collect::<Result<Vec<_>>>()?;
+
+  for (track_idx, clip_idx) in positions {
+    let (effects, filters) = preset.instantiate();
+    let clip = &mut project_schema.tracks[track_idx].clips[clip_idx];
+    clip
+      .effects
+      .extend(effects.iter().map(|effect| effect.id.clone()));
+    clip
+      .filters
+      .extend(filters.iter().map(|filter| filter.id.clone()));
+    project_schema.effects.extend(effects);
+    project_schema.filters.extend(filters);
+  }
+
+  project_schema.touch();
+  Ok(project_schema)
+}
+
+crate::command_manifest!(
+  EFFECT_PRESET_COMMANDS_MANIFEST,
+  "video_compiler::effect_preset_commands",
+  [
+    save_effect_preset,
+    list_effect_presets,
+    rename_effect_preset,
+    delete_effect_preset,
+    apply_effect_preset,
+    export_effect_preset,
+    import_effect_preset,
+  ]
+);
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::video_compiler::error::VideoCompilerError;
+  use crate::video_compiler::schema::{Clip, EffectType, FilterType, Track, TrackType};
+  use std::collections::HashSet;
+  use std::path::PathBuf;
+
+  fn project_with_clips() -> ProjectSchema {
+    let mut project = ProjectSchema::new("Presets".to_string());
+    let mut track = Track::new(TrackType::Video, "Video".to_string());
+    for (id, start) in [("a", 0.0), ("b", 5.0)] {
+      let mut clip = Clip::new(PathBuf::from(format!("/tmp/{id}.mp4")), start, 5.0);
+      clip.id = id.to_string();
+      track.clips.push(clip);
+    }
+    project.tracks.push(track);
+    project
+  }
+
+  fn look_preset() -> EffectPreset {
+    EffectPreset::new(
+      "Look".to_string(),
+      "Color".to_string(),
+      vec![
+        Effect::new(EffectType::Contrast, "Contrast".to_string()),
+        Effect::new(EffectType::Saturation, "Saturation".to_string()),
+      ],
+      vec![Filter::new(FilterType::Vignette, "Vignette".to_string())],
+    )
+    .unwrap()
+  }
+
+  #[test]
+  fn test_apply_preset_to_two_clips() {
+    let preset = look_preset();
+    let project = apply_preset_to_clips(
+      project_with_clips(),
+      &["a".to_string(), "b".to_string()],
+      &preset,
+    )
+    .unwrap();
+
+    assert_eq!(project.effects.len(), 4);
+    assert_eq!(project.filters.len(), 2);
+
+    let clip_a = project.find_clip_by_id("a").unwrap();
+    let clip_b = project.find_clip_by_id("b").unwrap();
+    assert_eq!(clip_a.effects.len(), 2);
+    assert_eq!(clip_b.effects.len(), 2);
+    assert_eq!(clip_a.filters.len(), 1);
+
+    // Каждый клип получает собственные копии эффектов
+    let ids_a: HashSet<&String> = clip_a.effects.iter().collect();
+    assert!(clip_b.effects.iter().all(|id| !ids_a.contains(id)));
+    assert_ne!(clip_a.filters[0], clip_b.filters[0]);
+    assert!(clip_a
+      .effects
+      .iter()
+      .all(|id| preset.effects.iter().all(|effect| &effect.id != id)));
+
+    // Все ID клипов ссылаются на эффекты проекта
+    for id in clip_a.effects.iter().chain(&clip_b.effects) {
+      assert!(project.effects.iter().any(|effect| &effect.id == id));
+    }
+  }
+
+  #[test]
+  fn test_apply_preset_respects_locks() {
+    let mut project = project_with_clips();
+    project.tracks[0].clips[1].locked = true;
+
+    let result =
+      apply_preset_to_clips(project, &["a".to_string(), "b".to_string()], &look_preset());
+    assert!(matches!(result, Err(VideoCompilerError::Locked { .. })));
+
+    let result = apply_preset_to_clips(
+      project_with_clips(),
+      &["missing".to_string()],
+      &look_preset(),
+    );
+    assert!(matches!(
+      result,
+      Err(VideoCompilerError::InvalidParameter(_))
+    ));
+  }
+}
@@ -282,6 +282,7 @@ pub async fn execute_ffmpeg_with_progress(command_args: Vec<String>) -> Result<S
           job_id,
           output_path,
           duration,
+          ..
         } => {
           log::info!("FFmpeg job completed: {job_id} -> {output_path} (duration: {duration:?})");
         }
@@ -295,6 +296,14 @@ pub async fn execute_ffmpeg_with_progress(command_args: Vec<String>) -> Result<S
         ProgressUpdate::JobCancelled { job_id } => {
           log::warn!("FFmpeg job cancelled: {job_id}");
         }
+        ProgressUpdate::RenderDowngraded {
+          job_id, message, ..
+        } => {
+          log::warn!("FFmpeg job downgraded: {job_id} - {message}");
+        }
+        ProgressUpdate::ReportReady { job_id, .. } => {
+          log::info!("FFmpeg job report ready: {job_id}");
+        }
       }
     }
   });
@@ -369,6 +378,26 @@ pub async fn get_ffmpeg_execution_info(
   }))
 }
 
+crate::command_manifest!(
+  FFMPEG_ADVANCED_MANIFEST,
+  "video_compiler::ffmpeg_advanced",
+  [
+    generate_video_preview,
+    generate_gif_preview,
+    concat_videos,
+    apply_video_filter,
+    probe_media_file,
+    test_hardware_acceleration,
+    generate_subtitle_preview,
+    check_ffmpeg_installation,
+    get_ffmpeg_codecs,
+    get_ffmpeg_formats,
+    execute_ffmpeg_with_progress,
+    execute_ffmpeg_simple,
+    get_ffmpeg_execution_info,
+  ]
+);
+
 #[cfg(test)]
 mod tests {
   use super::*;
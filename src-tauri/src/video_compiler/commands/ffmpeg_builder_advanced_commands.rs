@@ -215,6 +215,19 @@ pub async fn get_clip_input_index_advanced(
   }))
 }
 
+crate::command_manifest!(
+  FFMPEG_BUILDER_ADVANCED_COMMANDS_MANIFEST,
+  "video_compiler::ffmpeg_builder_advanced_commands",
+  [
+    get_ffmpeg_builder_settings_advanced,
+    get_ffmpeg_builder_project_info_advanced,
+    get_segment_filters_info_advanced,
+    validate_segment_timestamps_advanced,
+    get_frame_extraction_cache_advanced,
+    get_clip_input_index_advanced,
+  ]
+);
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -177,6 +177,17 @@ pub async fn get_ffmpeg_builder_info(_state: State<'_, VideoCompilerState>) -> R
   })
 }
 
+crate::command_manifest!(
+  FFMPEG_BUILDER_COMMANDS_MANIFEST,
+  "video_compiler::ffmpeg_builder_commands",
+  [
+    add_segment_inputs_to_builder,
+    create_ffmpeg_with_prerender_settings,
+    get_clip_input_index_from_builder,
+    get_ffmpeg_builder_info,
+  ]
+);
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -206,6 +217,7 @@ mod tests {
         enabled: true,
         volume: 1.0,
         locked: false,
+        hidden: false,
         clips: vec![
           Clip {
             id: "clip1".to_string(),
@@ -224,6 +236,18 @@ mod tests {
             crop: None,
             transform: None,
             audio_track_index: None,
+            audio_stream_index: None,
+            source_rotation: 0,
+            color_label: None,
+            notes: None,
+            tags: Vec::new(),
+            fade_in: None,
+            fade_out: None,
+            link_group_id: None,
+            source_timecode: None,
+            volume: 1.0,
+            external_audio: None,
+            locked: false,
             properties: crate::video_compiler::schema::ClipProperties::default(),
           },
           Clip {
@@ -243,17 +267,31 @@ mod tests {
             crop: None,
             transform: None,
             audio_track_index: None,
+            audio_stream_index: None,
+            source_rotation: 0,
+            color_label: None,
+            notes: None,
+            tags: Vec::new(),
+            fade_in: None,
+            fade_out: None,
+            link_group_id: None,
+            source_timecode: None,
+            volume: 1.0,
+            external_audio: None,
+            locked: false,
             properties: crate::video_compiler::schema::ClipProperties::default(),
           },
         ],
         effects: vec![],
         filters: vec![],
+        ducking: None,
       }],
       effects: vec![],
       transitions: vec![],
       filters: vec![],
       templates: vec![],
       style_templates: vec![],
+      sequences: Default::default(),
       subtitles: vec![],
       settings: ProjectSettings::default(),
     }
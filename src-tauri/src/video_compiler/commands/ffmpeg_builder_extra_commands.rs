@@ -323,6 +323,17 @@ pub async fn build_prerender_segment_direct(
   ])
 }
 
+crate::command_manifest!(
+  FFMPEG_BUILDER_EXTRA_COMMANDS_MANIFEST,
+  "video_compiler::ffmpeg_builder_extra_commands",
+  [
+    build_prerender_segment_command_advanced,
+    validate_prerender_segment_params,
+    get_optimal_prerender_settings,
+    build_prerender_segment_direct,
+  ]
+);
+
 #[cfg(test)]
 mod tests {
   use super::*;
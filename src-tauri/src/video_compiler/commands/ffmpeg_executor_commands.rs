@@ -189,6 +189,17 @@ pub async fn check_ffmpeg_executor_availability(
   }
 }
 
+crate::command_manifest!(
+  FFMPEG_EXECUTOR_COMMANDS_MANIFEST,
+  "video_compiler::ffmpeg_executor_commands",
+  [
+    execute_ffmpeg_with_progress_tracking,
+    execute_ffmpeg_simple_no_progress,
+    get_ffmpeg_executor_capabilities,
+    check_ffmpeg_executor_availability,
+  ]
+);
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -0,0 +1,64 @@
+//! FFmpeg Manager Commands - Скачивание и состояние встроенного FFmpeg
+
+use std::sync::Arc;
+use tauri::{Emitter, State};
+
+use crate::video_compiler::error::Result;
+use crate::video_compiler::ffmpeg_manager::{FfmpegManager, FfmpegStatus};
+
+use super::state::VideoCompilerState;
+
+/// Событие прогресса скачивания встроенного FFmpeg
+pub const FFMPEG_DOWNLOAD_PROGRESS_EVENT: &str = "ffmpeg-download-progress";
+
+/// Скачать встроенный FFmpeg и сделать его активным
+#[tauri::command]
+pub async fn download_bundled_ffmpeg<R: tauri::Runtime>(
+  app: tauri::AppHandle<R>,
+  state: State<'_, VideoCompilerState>,
+  manager: State<'_, Arc<FfmpegManager>>,
+) -> Result<FfmpegStatus> {
+  let sources = state.settings.read().await.ffmpeg_download_sources.clone();
+
+  let path = manager
+    .download_bundled(&sources, |progress| {
+      if let Err(e) = app.emit(FFMPEG_DOWNLOAD_PROGRESS_EVENT, &progress) {
+        log::warn!("Failed to emit FFmpeg download progress: {e}");
+      }
+    })
+    .await?;
+
+  let path_str = path.to_string_lossy().to_string();
+  state.settings.write().await.ffmpeg_path = Some(path);
+  state.update_ffmpeg_path(path_str.clone()).await;
+
+  Ok(manager.status(&path_str, None).await)
+}
+
+/// Отменить скачивание встроенного FFmpeg
+#[tauri::command]
+pub async fn cancel_ffmpeg_download(manager: State<'_, Arc<FfmpegManager>>) -> Result<bool> {
+  Ok(manager.cancel_download())
+}
+
+/// Состояние активного FFmpeg: источник, версия, обязательные кодировщики и фильтры
+#[tauri::command]
+pub async fn get_ffmpeg_status(
+  state: State<'_, VideoCompilerState>,
+  manager: State<'_, Arc<FfmpegManager>>,
+) -> Result<FfmpegStatus> {
+  let path = state.ffmpeg_path.read().await.clone();
+  let custom_path = state.settings.read().await.ffmpeg_path.clone();
+
+  Ok(manager.status(&path, custom_path.as_deref()).await)
+}
+
+crate::command_manifest!(
+  FFMPEG_MANAGER_COMMANDS_MANIFEST,
+  "video_compiler::ffmpeg_manager_commands",
+  [
+    download_bundled_ffmpeg,
+    cancel_ffmpeg_download,
+    get_ffmpeg_status,
+  ]
+);
@@ -239,6 +239,19 @@ pub async fn get_ffmpeg_execution_information(
   }
 }
 
+crate::command_manifest!(
+  FFMPEG_UTILITIES_COMMANDS_MANIFEST,
+  "video_compiler::ffmpeg_utilities_commands",
+  [
+    execute_ffmpeg_simple_command,
+    execute_ffmpeg_with_progress_advanced,
+    get_ffmpeg_available_codecs,
+    get_ffmpeg_available_formats,
+    generate_subtitle_preview_advanced,
+    get_ffmpeg_execution_information,
+  ]
+);
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -53,6 +53,15 @@ pub async fn execute_ffmpeg_with_progress_handler(
   ))
 }
 
+crate::command_manifest!(
+  FINAL_UTILITIES_COMMANDS_MANIFEST,
+  "video_compiler::final_utilities_commands",
+  [
+    generate_subtitle_preview_ffmpeg,
+    execute_ffmpeg_with_progress_handler,
+  ]
+);
+
 #[cfg(test)]
 mod tests {
   use super::*;
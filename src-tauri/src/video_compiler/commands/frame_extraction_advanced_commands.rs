@@ -310,6 +310,22 @@ pub async fn generate_preview_with_custom_settings(
   Ok(params.output_path)
 }
 
+crate::command_manifest!(
+  FRAME_EXTRACTION_ADVANCED_COMMANDS_MANIFEST,
+  "video_compiler::frame_extraction_advanced_commands",
+  [
+    extract_timeline_frames_advanced,
+    extract_subtitle_frames_advanced,
+    extract_video_frame_advanced,
+    extract_video_frames_batch_advanced,
+    get_video_thumbnails_advanced,
+    get_frame_extraction_cache_information,
+    generate_preview_frame,
+    generate_preview_batch_frames,
+    generate_preview_with_custom_settings,
+  ]
+);
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -2,9 +2,15 @@
 //!
 //! Команды для работы с извлечением кадров из видео и таймлайна
 
+use crate::media::preview_manager::PreviewDataManager;
 use crate::video_compiler::commands::VideoCompilerState;
 use crate::video_compiler::error::{Result, VideoCompilerError};
+use crate::video_compiler::ffmpeg_builder::builder::quality_to_qscale;
+use crate::video_compiler::ffmpeg_builder::filters::FilterBuilder;
 use crate::video_compiler::preview::PreviewGenerator;
+use crate::video_compiler::schema::{PreviewFormat, ProjectSchema};
+use std::path::Path;
+use std::sync::Arc;
 use tauri::State;
 
 /// Кадр таймлайна
@@ -35,6 +41,58 @@ pub struct PreviewRequest {
   pub quality: Option<u8>,
 }
 
+/// Разрешение экспортируемого кадра
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FrameExportResolution {
+  /// Исходное разрешение видео
+  #[default]
+  Native,
+  Custom {
+    width: u32,
+    height: u32,
+  },
+}
+
+/// Клип проекта, фильтры и эффекты которого применяются к кадру
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct FrameProjectFilters {
+  pub project: ProjectSchema,
+  pub clip_id: String,
+}
+
+/// Параметры экспорта кадра в файл
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct FrameExportOptions {
+  pub format: PreviewFormat,
+  /// Качество 0-100 (для JPEG и WebP)
+  #[serde(default = "default_frame_export_quality")]
+  pub quality: u8,
+  #[serde(default)]
+  pub resolution: FrameExportResolution,
+  /// Точный поиск кадра с декодированием от ключевого кадра
+  #[serde(default)]
+  pub accurate_seek: bool,
+  #[serde(default)]
+  pub apply_project_filters: Option<FrameProjectFilters>,
+  /// Перезаписать существующий файл
+  #[serde(default)]
+  pub overwrite: bool,
+}
+
+fn default_frame_export_quality() -> u8 {
+  95
+}
+
+/// Результат экспорта кадра
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct FrameExportResult {
+  pub output_path: String,
+  pub file_size: u64,
+  pub width: u32,
+  pub height: u32,
+}
+
 // ============ Бизнес-логика (тестируемая) ============
 
 /// Рассчитать временные метки для извлечения кадров
@@ -300,6 +358,182 @@ pub async fn extract_video_frames_batch(
   Ok(frame_paths)
 }
 
+/// Проверить путь экспорта кадра: расширение соответствует формату,
+/// существующий файл перезаписывается только по флагу
+pub fn validate_frame_export_path(output_path: &Path, options: &FrameExportOptions) -> Result<()> {
+  let extension = output_path
+    .extension()
+    .map(|ext| ext.to_string_lossy().to_lowercase())
+    .unwrap_or_default();
+  if !options.format.extensions().contains(&extension.as_str()) {
+    return Err(VideoCompilerError::InvalidParameter(format!(
+      "Расширение файла {} не соответствует формату {:?}",
+      output_path.display(),
+      options.format
+    )));
+  }
+
+  if output_path.exists() && !options.overwrite {
+    return Err(VideoCompilerError::InvalidParameter(format!(
+      "Файл {} уже существует",
+      output_path.display()
+    )));
+  }
+
+  if let FrameExportResolution::Custom { width, height } = options.resolution {
+    if width == 0 || height == 0 {
+      return Err(VideoCompilerError::InvalidParameter(
+        "Разрешение кадра должно быть больше нуля".to_string(),
+      ));
+    }
+  }
+
+  Ok(())
+}
+
+/// Построить команду FFmpeg для экспорта одного кадра.
+///
+/// `clip_filter` - цепочка фильтров клипа с выходом `[v0]`
+/// (см. [`FilterBuilder::build_still_clip_filter`]).
+pub fn build_frame_export_command(
+  ffmpeg_path: &str,
+  video_path: &str,
+  timestamp: f64,
+  output_path: &Path,
+  options: &FrameExportOptions,
+  clip_filter: Option<&str>,
+) -> tokio::process::Command {
+  let mut cmd = tokio::process::Command::new(ffmpeg_path);
+  cmd.args(["-hide_banner", "-loglevel", "error"]);
+
+  // Поиск до входа быстрый, после входа - с декодированием до нужного кадра
+  let timestamp = timestamp.max(0.0).to_string();
+  if options.accurate_seek {
+    cmd.args(["-i", video_path, "-ss", &timestamp]);
+  } else {
+    cmd.args(["-ss", &timestamp, "-i", video_path]);
+  }
+
+  let scale = match options.resolution {
+    FrameExportResolution::Native => None,
+    FrameExportResolution::Custom { width, height } => Some(format!("scale={width}:{height}")),
+  };
+  match clip_filter {
+    Some(chain) => {
+      let output = scale.unwrap_or_else(|| "null".to_string());
+      cmd.args(["-filter_complex", &format!("{chain};[v0]{output}[frame]")]);
+      cmd.args(["-map", "[frame]"]);
+    }
+    None => {
+      if let Some(scale) = scale {
+        cmd.args(["-vf", &scale]);
+      }
+    }
+  }
+
+  cmd.args(["-frames:v", "1", "-an"]);
+  match options.format {
+    PreviewFormat::Jpeg => {
+      cmd.args(["-q:v", &quality_to_qscale(options.quality).to_string()]);
+    }
+    PreviewFormat::WebP => {
+      cmd.args(["-quality", &options.quality.min(100).to_string()]);
+    }
+    PreviewFormat::Png => {}
+  }
+  cmd.args([
+    "-f",
+    "image2",
+    "-update",
+    "1",
+    "-c:v",
+    options.format.ffmpeg_codec(),
+  ]);
+
+  cmd.arg(if options.overwrite { "-y" } else { "-n" });
+  cmd.arg(output_path);
+
+  cmd
+}
+
+/// Экспортировать кадр видео в выбранный пользователем файл.
+///
+/// С `apply_project_filters` к кадру применяются фильтры и эффекты клипа,
+/// чтобы снимок совпадал с превью. Возвращает размер файла и разрешение.
+#[tauri::command]
+pub async fn export_frame(
+  video_path: String,
+  timestamp: f64,
+  output_path: String,
+  options: FrameExportOptions,
+  state: State<'_, VideoCompilerState>,
+) -> Result<FrameExportResult> {
+  let output = Path::new(&output_path);
+  validate_frame_export_path(output, &options)?;
+
+  let clip_filter = match &options.apply_project_filters {
+    Some(source) => {
+      let clip = source
+        .project
+        .find_clip_by_id(&source.clip_id)
+        .ok_or_else(|| {
+          VideoCompilerError::InvalidParameter(format!("Клип {} не найден", source.clip_id))
+        })?;
+      Some(
+        FilterBuilder::new(&source.project)
+          .build_still_clip_filter(clip)
+          .await?,
+      )
+    }
+    None => None,
+  };
+
+  if let Some(parent) = output.parent() {
+    tokio::fs::create_dir_all(parent)
+      .await
+      .map_err(|e| VideoCompilerError::IoError(e.to_string()))?;
+  }
+
+  let ffmpeg_path = state.ffmpeg_path.read().await.clone();
+  let result = build_frame_export_command(
+    &ffmpeg_path,
+    &video_path,
+    timestamp,
+    output,
+    &options,
+    clip_filter.as_deref(),
+  )
+  .output()
+  .await
+  .map_err(|e| VideoCompilerError::FFmpegError {
+    command: "ffmpeg".to_string(),
+    exit_code: None,
+    stderr: e.to_string(),
+  })?;
+
+  if !result.status.success() {
+    return Err(VideoCompilerError::FFmpegError {
+      command: "ffmpeg".to_string(),
+      exit_code: result.status.code(),
+      stderr: String::from_utf8_lossy(&result.stderr).to_string(),
+    });
+  }
+
+  let file_size = tokio::fs::metadata(output)
+    .await
+    .map_err(|e| VideoCompilerError::IoError(e.to_string()))?
+    .len();
+  let (width, height) = image::image_dimensions(output)
+    .map_err(|e| VideoCompilerError::IoError(format!("Не удалось прочитать кадр: {e}")))?;
+
+  Ok(FrameExportResult {
+    output_path,
+    file_size,
+    width,
+    height,
+  })
+}
+
 /// Рассчитать временные метки для миниатюр
 pub fn calculate_thumbnail_timestamps(duration: f64, count: usize) -> Result<Vec<f64>> {
   if duration <= 0.0 {
@@ -348,5 +582,70 @@ pub async fn get_video_thumbnails(
   extract_video_frames_batch(video_path, timestamps, output_dir, state).await
 }
 
+/// Размер кадров таймлайна по умолчанию
+const DEFAULT_TIMELINE_FRAME_SIZE: (u32, u32) = (320, 180);
+
+/// Сгенерировать кадры файла для таймлайна и прикрепить их к данным превью.
+///
+/// Сначала проверяется индекс кадров файла в PreviewDataManager: генерируются
+/// только отсутствующие метки, новые кадры добавляются в индекс, о каждом
+/// отправляется событие PreviewGenerated. Возвращает пути к кадрам в кэше в
+/// порядке запрошенных меток.
+#[tauri::command]
+pub async fn attach_timeline_frames_for_file(
+  file_id: String,
+  video_path: String,
+  timestamps: Vec<f64>,
+  resolution: Option<(u32, u32)>,
+  state: State<'_, VideoCompilerState>,
+  previews: State<'_, Arc<PreviewDataManager>>,
+) -> Result<Vec<String>> {
+  let preview_service = state
+    .services
+    .get_preview_service()
+    .ok_or_else(|| VideoCompilerError::validation("PreviewService не найден"))?;
+
+  let (width, height) = resolution.unwrap_or(DEFAULT_TIMELINE_FRAME_SIZE);
+  let video_path = Path::new(&video_path);
+  let frames = previews
+    .get_or_generate_timeline_frames(&file_id, &timestamps, (width, height), |missing| {
+      let preview_service = preview_service.clone();
+      async move {
+        preview_service
+          .generate_preview_batch_for_file(video_path, missing, Some((width, height)), None)
+          .await
+          .map_err(anyhow::Error::from)
+      }
+    })
+    .await
+    .map_err(|e| VideoCompilerError::IoError(e.to_string()))?;
+
+  Ok(
+    frames
+      .into_iter()
+      .map(|frame| frame.path.to_string_lossy().to_string())
+      .collect(),
+  )
+}
+
 #[cfg(test)]
 pub mod frame_extraction_commands_tests;
+
+crate::command_manifest!(
+  FRAME_EXTRACTION_COMMANDS_MANIFEST,
+  "video_compiler::frame_extraction_commands",
+  [
+    extract_timeline_frames,
+    extract_subtitle_frames,
+    generate_preview,
+    generate_preview_batch,
+    generate_preview_with_settings,
+    get_frame_extraction_cache_info,
+    clear_frame_cache,
+    extract_video_frame,
+    extract_video_frames_batch,
+    export_frame,
+    get_video_thumbnails,
+    attach_timeline_frames_for_file,
+  ]
+);
@@ -25,6 +25,7 @@ mod tests {
         resolution: (1920, 1080),
         sample_rate: 48000,
         aspect_ratio: AspectRatio::Ratio16x9,
+        markers: Vec::new(),
       },
       tracks: vec![],
       effects: vec![],
@@ -32,6 +33,7 @@ mod tests {
       filters: vec![],
       templates: vec![],
       style_templates: vec![],
+      sequences: Default::default(),
       subtitles: vec![],
       settings: ProjectSettings::default(),
     }
@@ -641,4 +643,141 @@ mod tests {
     assert_eq!(paths[0], "/tmp/frame_0000.png");
     assert_eq!(paths[4], "/tmp/frame_0004.png");
   }
+
+  fn frame_export_options(format: PreviewFormat) -> FrameExportOptions {
+    FrameExportOptions {
+      format,
+      quality: 90,
+      resolution: FrameExportResolution::Native,
+      accurate_seek: false,
+      apply_project_filters: None,
+      overwrite: false,
+    }
+  }
+
+  fn command_args(cmd: &tokio::process::Command) -> Vec<String> {
+    cmd
+      .as_std()
+      .get_args()
+      .map(|arg| arg.to_string_lossy().to_string())
+      .collect()
+  }
+
+  #[test]
+  fn test_export_frame_png_native_command() {
+    let options = frame_export_options(PreviewFormat::Png);
+    let cmd = build_frame_export_command(
+      "ffmpeg",
+      "/videos/input.mp4",
+      12.5,
+      Path::new("/exports/frame.png"),
+      &options,
+      None,
+    );
+    let args = command_args(&cmd);
+
+    assert_eq!(
+      &args[2..7],
+      ["-loglevel", "error", "-ss", "12.5", "-i"].map(String::from)
+    );
+    assert!(!args
+      .iter()
+      .any(|arg| arg == "-vf" || arg == "-filter_complex"));
+    assert!(args.windows(2).any(|pair| pair == ["-c:v", "png"]));
+    assert!(!args.contains(&"-q:v".to_string()));
+    assert_eq!(args[args.len() - 2], "-n");
+    assert_eq!(args.last().unwrap(), "/exports/frame.png");
+  }
+
+  #[test]
+  fn test_export_frame_resized_jpeg_command() {
+    let mut options = frame_export_options(PreviewFormat::Jpeg);
+    options.resolution = FrameExportResolution::Custom {
+      width: 1280,
+      height: 720,
+    };
+    options.accurate_seek = true;
+    options.overwrite = true;
+
+    let cmd = build_frame_export_command(
+      "ffmpeg",
+      "/videos/input.mp4",
+      3.0,
+      Path::new("/exports/frame.jpg"),
+      &options,
+      None,
+    );
+    let args = command_args(&cmd);
+
+    // Точный поиск идет после входа
+    let input = args.iter().position(|arg| arg == "-i").unwrap();
+    assert_eq!(args[input + 2], "-ss");
+    assert!(args
+      .windows(2)
+      .any(|pair| pair == ["-vf", "scale=1280:720"]));
+    assert!(args.windows(2).any(|pair| pair == ["-c:v", "mjpeg"]));
+    assert!(args
+      .windows(2)
+      .any(|pair| pair[0] == "-q:v" && pair[1] == quality_to_qscale(90).to_string()));
+    assert!(args.contains(&"-y".to_string()));
+  }
+
+  #[tokio::test]
+  async fn test_export_frame_with_clip_filters() {
+    use crate::video_compiler::schema::{Clip, Track, TrackType};
+
+    let mut project = create_test_project();
+    let mut track = Track::new(TrackType::Video, "Video".to_string());
+    let mut clip = Clip::new(std::path::PathBuf::from("/videos/input.mp4"), 0.0, 5.0);
+    clip.source_rotation = 90;
+    let clip_id = clip.id.clone();
+    track.clips.push(clip);
+    project.tracks.push(track);
+
+    let clip = project.find_clip_by_id(&clip_id).unwrap();
+    let chain = FilterBuilder::new(&project)
+      .build_still_clip_filter(clip)
+      .await
+      .unwrap();
+    assert_eq!(chain, "[0:v]transpose=clock,setpts=PTS-STARTPTS[v0]");
+
+    let options = frame_export_options(PreviewFormat::WebP);
+    let cmd = build_frame_export_command(
+      "ffmpeg",
+      "/videos/input.mp4",
+      1.0,
+      Path::new("/exports/frame.webp"),
+      &options,
+      Some(&chain),
+    );
+    let args = command_args(&cmd);
+    assert!(args.windows(2).any(|pair| pair
+      == [
+        "-filter_complex",
+        "[0:v]transpose=clock,setpts=PTS-STARTPTS[v0];[v0]null[frame]"
+      ]));
+    assert!(args.windows(2).any(|pair| pair == ["-map", "[frame]"]));
+    assert!(args.windows(2).any(|pair| pair == ["-quality", "90"]));
+  }
+
+  #[test]
+  fn test_validate_frame_export_path() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let existing = temp_dir.path().join("frame.png");
+    std::fs::write(&existing, b"png").unwrap();
+
+    let mut options = frame_export_options(PreviewFormat::Png);
+    assert!(validate_frame_export_path(&temp_dir.path().join("new.png"), &options).is_ok());
+    assert!(validate_frame_export_path(&temp_dir.path().join("new.jpg"), &options).is_err());
+    assert!(validate_frame_export_path(&existing, &options).is_err());
+
+    options.overwrite = true;
+    assert!(validate_frame_export_path(&existing, &options).is_ok());
+
+    options.resolution = FrameExportResolution::Custom {
+      width: 0,
+      height: 720,
+    };
+    assert!(validate_frame_export_path(&existing, &options).is_err());
+  }
 }
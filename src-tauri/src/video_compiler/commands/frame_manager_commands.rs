@@ -173,6 +173,16 @@ pub async fn get_frame_extraction_cache_info_command(
   })
 }
 
+crate::command_manifest!(
+  FRAME_MANAGER_COMMANDS_MANIFEST,
+  "video_compiler::frame_manager_commands",
+  [
+    extract_frames_for_clip_command,
+    extract_frames_for_subtitles_command,
+    get_frame_extraction_cache_info_command,
+  ]
+);
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -197,6 +207,18 @@ mod tests {
       crop: None,
       transform: None,
       audio_track_index: None,
+      audio_stream_index: None,
+      source_rotation: 0,
+      color_label: None,
+      notes: None,
+      tags: Vec::new(),
+      fade_in: None,
+      fade_out: None,
+      link_group_id: None,
+      source_timecode: None,
+      volume: 1.0,
+      external_audio: None,
+      locked: false,
       properties: crate::video_compiler::schema::timeline::ClipProperties::default(),
     };
 
@@ -6,6 +6,7 @@
 use tauri::State;
 
 use crate::video_compiler::core::gpu::GpuInfo;
+use crate::video_compiler::core::gpu_memory::GpuMemoryInfo;
 use crate::video_compiler::error::{Result, VideoCompilerError};
 
 use super::state::VideoCompilerState;
@@ -47,6 +48,18 @@ pub async fn check_hardware_acceleration_support(
   gpu_service.check_hardware_acceleration().await
 }
 
+/// Получить занятость видеопамяти GPU
+#[tauri::command]
+pub async fn get_gpu_memory_info(
+  state: State<'_, VideoCompilerState>,
+) -> Result<Option<GpuMemoryInfo>> {
+  let gpu_service = state
+    .services
+    .get_gpu_service()
+    .ok_or_else(|| VideoCompilerError::validation("GpuService не найден"))?;
+  gpu_service.get_gpu_memory_info().await
+}
+
 /// Получить рекомендуемый GPU для рендеринга
 #[tauri::command]
 pub async fn get_recommended_gpu(state: State<'_, VideoCompilerState>) -> Result<Option<GpuInfo>> {
@@ -173,6 +186,26 @@ pub async fn get_gpu_encoder_details(encoder_type: String) -> Result<serde_json:
   }))
 }
 
+crate::command_manifest!(
+  GPU_MANIFEST,
+  "video_compiler::gpu",
+  [
+    detect_gpus,
+    get_gpu_capabilities,
+    check_hardware_acceleration_support,
+    get_gpu_memory_info,
+    get_recommended_gpu,
+    set_preferred_gpu,
+    set_hardware_acceleration,
+    get_gpu_usage_status,
+    benchmark_gpu,
+    get_gpu_supported_codecs,
+    auto_select_gpu,
+    get_gpu_encoder_details,
+    get_gpu_capabilities_full,
+  ]
+);
+
 #[cfg(test)]
 mod tests {
   use crate::video_compiler::core::gpu::{GpuEncoder, GpuInfo};
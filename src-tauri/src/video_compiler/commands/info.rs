@@ -55,7 +55,7 @@ pub async fn get_ffmpeg_version(state: State<'_, VideoCompilerState>) -> Result<
   let output = Command::new(&*ffmpeg_path)
     .arg("-version")
     .output()
-    .map_err(|e| VideoCompilerError::DependencyMissing(format!("FFmpeg not found: {e}")))?;
+    .map_err(|e| VideoCompilerError::DependencyMissing(format!("FFmpeg not found: {e}").into()))?;
 
   if output.status.success() {
     let version_output = String::from_utf8_lossy(&output.stdout);
@@ -68,7 +68,7 @@ pub async fn get_ffmpeg_version(state: State<'_, VideoCompilerState>) -> Result<
     )
   } else {
     Err(VideoCompilerError::DependencyMissing(
-      "Failed to get FFmpeg version".to_string(),
+      "Failed to get FFmpeg version".into(),
     ))
   }
 }
@@ -92,7 +92,7 @@ pub async fn get_supported_formats(state: State<'_, VideoCompilerState>) -> Resu
   let output = Command::new(&*ffmpeg_path)
     .args(["-formats", "-hide_banner"])
     .output()
-    .map_err(|e| VideoCompilerError::DependencyMissing(format!("FFmpeg not found: {e}")))?;
+    .map_err(|e| VideoCompilerError::DependencyMissing(format!("FFmpeg not found: {e}").into()))?;
 
   if output.status.success() {
     let formats_output = String::from_utf8_lossy(&output.stdout);
@@ -129,7 +129,7 @@ pub async fn get_supported_video_codecs(
   let output = Command::new(&*ffmpeg_path)
     .args(["-codecs", "-hide_banner"])
     .output()
-    .map_err(|e| VideoCompilerError::DependencyMissing(format!("FFmpeg not found: {e}")))?;
+    .map_err(|e| VideoCompilerError::DependencyMissing(format!("FFmpeg not found: {e}").into()))?;
 
   if output.status.success() {
     let codecs_output = String::from_utf8_lossy(&output.stdout);
@@ -171,7 +171,7 @@ pub async fn get_supported_audio_codecs(
   let output = Command::new(&*ffmpeg_path)
     .args(["-codecs", "-hide_banner"])
     .output()
-    .map_err(|e| VideoCompilerError::DependencyMissing(format!("FFmpeg not found: {e}")))?;
+    .map_err(|e| VideoCompilerError::DependencyMissing(format!("FFmpeg not found: {e}").into()))?;
 
   if output.status.success() {
     let codecs_output = String::from_utf8_lossy(&output.stdout);
@@ -305,6 +305,24 @@ pub async fn get_performance_stats(
   }))
 }
 
+crate::command_manifest!(
+  INFO_MANIFEST,
+  "video_compiler::info",
+  [
+    get_ffmpeg_version,
+    check_ffmpeg_available,
+    get_supported_formats,
+    get_supported_video_codecs,
+    get_supported_audio_codecs,
+    get_system_info,
+    get_disk_space,
+    get_compiler_config,
+    get_performance_stats,
+    get_available_filters,
+    get_media_file_info,
+  ]
+);
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -686,7 +704,7 @@ pub async fn get_available_filters(state: State<'_, VideoCompilerState>) -> Resu
   let output = Command::new(&*ffmpeg_path)
     .args(["-filters", "-hide_banner"])
     .output()
-    .map_err(|e| VideoCompilerError::DependencyMissing(format!("FFmpeg not found: {e}")))?;
+    .map_err(|e| VideoCompilerError::DependencyMissing(format!("FFmpeg not found: {e}").into()))?;
 
   if output.status.success() {
     let filters_output = String::from_utf8_lossy(&output.stdout);
@@ -0,0 +1,148 @@
+//! Media Relink Commands - поиск и перепривязка перемещенных медиафайлов проекта
+
+use crate::media::commands::migrate_media_data;
+use crate::media::media_registry::MediaRegistryState;
+use crate::video_compiler::error::{Result, VideoCompilerError};
+use crate::video_compiler::schema::ProjectSchema;
+use crate::video_compiler::services::media_relink::{
+  MissingMediaGroup, RelinkProgressSink, RelinkScanProgress, RelinkSuggestion, RelinkedMedia,
+};
+use crate::video_compiler::services::schema_journal::SchemaChange;
+use crate::video_compiler::services::ProjectService;
+use crate::video_compiler::{VideoCompilerEvent, VideoCompilerState};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+/// Результат перепривязки медиафайлов
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MediaRelinkOutcome {
+  /// Проект с обновленными путями
+  pub project: ProjectSchema,
+  /// Перепривязанные файлы
+  pub relinked: Vec<RelinkedMedia>,
+  /// Исходные пути, для которых перенесены превью, распознавание и метаданные
+  pub migrated: Vec<String>,
+  /// Файлы, данные которых перенести не удалось
+  pub warnings: Vec<String>,
+  /// Запись журнала для отмены (`None`, если проект не изменился)
+  pub change: Option<SchemaChange>,
+}
+
+/// Получить сервис проектов
+fn project_service(state: &VideoCompilerState) -> Result<Arc<dyn ProjectService>> {
+  state
+    .services
+    .get_project_service()
+    .ok_or_else(|| VideoCompilerError::InternalError("Project service not available".to_string()))
+}
+
+/// Найти отсутствующие медиафайлы проекта, сгруппированные по исходной директории
+#[tauri::command]
+pub async fn find_missing_media(
+  state: State<'_, VideoCompilerState>,
+  project: ProjectSchema,
+) -> Result<Vec<MissingMediaGroup>> {
+  project_service(&state)?.find_missing_media(&project).await
+}
+
+/// Подобрать кандидатов для отсутствующих медиафайлов в указанных директориях.
+///
+/// Прогресс поиска сообщается событием `RelinkScanProgress`.
+#[tauri::command]
+pub async fn suggest_relink_candidates(
+  app: AppHandle,
+  state: State<'_, VideoCompilerState>,
+  project: ProjectSchema,
+  search_dirs: Vec<String>,
+) -> Result<Vec<RelinkSuggestion>> {
+  let service = project_service(&state)?;
+
+  // Отпечатки из реестра позволяют найти переименованные файлы
+  let mut known_media = HashMap::new();
+  if let Some(registry_state) = app.try_state::<MediaRegistryState>() {
+    let registry = registry_state.registry.read().await;
+    for group in service.find_missing_media(&project).await? {
+      for file in group.files {
+        if let Some(entry) = registry.get(&file.path) {
+          known_media.insert(file.path, entry.fingerprint.clone());
+        }
+      }
+    }
+  }
+
+  let emitter = app.clone();
+  let progress: RelinkProgressSink = Arc::new(move |progress: RelinkScanProgress| {
+    if let Err(e) = emitter.emit(
+      "video-compiler",
+      &VideoCompilerEvent::RelinkScanProgress { progress },
+    ) {
+      log::warn!("Failed to emit relink progress: {e}");
+    }
+  });
+
+  service
+    .suggest_relink_candidates(
+      &project,
+      search_dirs.into_iter().map(PathBuf::from).collect(),
+      known_media,
+      Some(progress),
+    )
+    .await
+}
+
+/// Перепривязать медиафайлы проекта по сопоставлению старый -> новый путь.
+///
+/// Сопоставление может покрывать только часть отсутствующих файлов. Вместе
+/// с путями переносятся кэшированные метаданные, превью и результаты
+/// распознавания; изменение записывается в журнал проекта.
+#[tauri::command]
+pub async fn apply_media_relink(
+  app: AppHandle,
+  state: State<'_, VideoCompilerState>,
+  project_id: String,
+  project: ProjectSchema,
+  mapping: HashMap<String, String>,
+) -> Result<MediaRelinkOutcome> {
+  let service = project_service(&state)?;
+  let mut updated = project.clone();
+  let relinked = service.apply_relink(&mut updated, &mapping).await?;
+
+  let mut migrated = Vec::new();
+  let mut warnings = Vec::new();
+  if let Some(registry_state) = app.try_state::<MediaRegistryState>() {
+    for media in &relinked {
+      match migrate_media_data(&app, &registry_state, &media.old_path, &media.new_path).await {
+        Ok(_) => migrated.push(media.old_path.clone()),
+        Err(e) => {
+          log::warn!("Данные {} не перенесены: {e}", media.old_path);
+          warnings.push(e);
+        }
+      }
+    }
+  }
+
+  let change = service
+    .record_schema_change(&project_id, "Relink media", &project, &updated)
+    .await?;
+
+  Ok(MediaRelinkOutcome {
+    project: updated,
+    relinked,
+    migrated,
+    warnings,
+    change,
+  })
+}
+
+crate::command_manifest!(
+  MEDIA_RELINK_COMMANDS_MANIFEST,
+  "video_compiler::media_relink_commands",
+  [
+    find_missing_media,
+    suggest_relink_candidates,
+    apply_media_relink,
+  ]
+);
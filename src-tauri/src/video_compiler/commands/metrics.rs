@@ -214,6 +214,22 @@ pub async fn get_slow_operations_original(
   Ok(find_slow_operations(&summaries, limit))
 }
 
+crate::command_manifest!(
+  METRICS_MANIFEST,
+  "video_compiler::metrics",
+  [],
+  internal: [
+    get_all_metrics_original,
+    get_service_metrics_original,
+    export_metrics_prometheus_original,
+    reset_service_metrics_original,
+    get_active_operations_count_original,
+    get_error_statistics_original,
+    get_slow_operations_original,
+    get_service_container_metrics_original,
+  ]
+);
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -228,6 +244,8 @@ mod tests {
         total_operations: 100,
         active_operations: 5,
         total_errors: 10,
+        total_timeouts: 0,
+        total_retries: 0,
         operations_per_second: 0.027,
         error_rate: 0.1,
         operation_metrics: {
@@ -243,6 +261,8 @@ mod tests {
               max_duration_ms: 200,
               last_error: None,
               last_operation_time: None,
+              timeouts: 0,
+              retries: 0,
             },
           );
           map.insert(
@@ -256,6 +276,8 @@ mod tests {
               max_duration_ms: 20,
               last_error: Some("Test error".to_string()),
               last_operation_time: None,
+              timeouts: 0,
+              retries: 0,
             },
           );
           map
@@ -267,6 +289,8 @@ mod tests {
         total_operations: 200,
         active_operations: 10,
         total_errors: 5,
+        total_timeouts: 0,
+        total_retries: 0,
         operations_per_second: 0.056,
         error_rate: 0.025,
         operation_metrics: {
@@ -282,6 +306,8 @@ mod tests {
               max_duration_ms: 100,
               last_error: None,
               last_operation_time: None,
+              timeouts: 0,
+              retries: 0,
             },
           );
           map
@@ -358,6 +384,8 @@ mod tests {
           total_operations: 100,
           active_operations: 2,
           total_errors: 5,
+          total_timeouts: 0,
+          total_retries: 0,
           operations_per_second: 0.028,
           error_rate: 0.05,
           operation_metrics: HashMap::new(),
@@ -371,6 +399,8 @@ mod tests {
           total_operations: 200,
           active_operations: 0,
           total_errors: 2,
+          total_timeouts: 0,
+          total_retries: 0,
           operations_per_second: 0.056,
           error_rate: 0.01,
           operation_metrics: HashMap::new(),
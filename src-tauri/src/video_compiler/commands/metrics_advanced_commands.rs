@@ -247,6 +247,20 @@ timeline_studio_render_fps 29.97
   Ok(prometheus_metrics.to_string())
 }
 
+crate::command_manifest!(
+  METRICS_ADVANCED_COMMANDS_MANIFEST,
+  "video_compiler::metrics_advanced_commands",
+  [
+    get_active_operations_count_detailed,
+    get_error_statistics_detailed,
+    get_slow_operations_detailed,
+    get_service_container_metrics_detailed,
+    get_render_pipeline_statistics_advanced,
+    reset_service_metrics_advanced,
+    export_metrics_prometheus_advanced,
+  ]
+);
+
 #[cfg(test)]
 mod tests {
   use super::*;
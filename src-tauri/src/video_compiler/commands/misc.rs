@@ -4,7 +4,12 @@
 
 use tauri::State;
 
+use crate::video_compiler::core::priority;
 use crate::video_compiler::error::{Result, VideoCompilerError};
+use crate::video_compiler::services::ffmpeg_capabilities::FfmpegCapabilitiesCache;
+use crate::video_compiler::services::ffmpeg_service::{
+  ffprobe_path, media_metadata_from_info, parse_probe_json,
+};
 
 use super::state::VideoCompilerState;
 
@@ -37,16 +42,19 @@ pub async fn cache_media_metadata(
   file_path: String,
   state: State<'_, VideoCompilerState>,
 ) -> Result<serde_json::Value> {
-  // Получаем информацию о файле через FFmpeg
-  let ffmpeg_path = state.ffmpeg_path.read().await;
-  let output = std::process::Command::new(&*ffmpeg_path)
+  let _interactive = priority::begin_interactive();
+
+  // Получаем информацию о файле через ffprobe
+  let ffmpeg_path = state.ffmpeg_path.read().await.clone();
+  let output = std::process::Command::new(ffprobe_path(&ffmpeg_path))
     .args([
-      "-i",
-      &file_path,
-      "-f",
+      "-v",
+      "quiet",
+      "-print_format",
       "json",
       "-show_format",
       "-show_streams",
+      &file_path,
     ])
     .output()
     .map_err(|e| VideoCompilerError::MediaFileError {
@@ -57,21 +65,11 @@ pub async fn cache_media_metadata(
   let metadata = String::from_utf8_lossy(&output.stdout);
   let metadata_json: serde_json::Value =
     serde_json::from_str(&metadata).unwrap_or_else(|_| serde_json::json!({}));
+  let info = parse_probe_json(&metadata_json).ok();
 
   // Добавляем в кэш
   let mut cache = state.cache_manager.write().await;
-  let metadata = crate::video_compiler::cache::MediaMetadata {
-    file_path: file_path.clone(),
-    file_size: 0,
-    modified_time: std::time::SystemTime::now(),
-    duration: 0.0,
-    resolution: None,
-    fps: None,
-    bitrate: None,
-    video_codec: None,
-    audio_codec: None,
-    cached_at: std::time::SystemTime::now(),
-  };
+  let metadata = media_metadata_from_info(&file_path, info.as_ref());
   cache.store_metadata(file_path, metadata).await?;
 
   Ok(metadata_json)
@@ -82,28 +80,24 @@ pub async fn cache_media_metadata(
 pub async fn check_ffmpeg_capabilities(
   state: State<'_, VideoCompilerState>,
 ) -> Result<serde_json::Value> {
-  let ffmpeg_path = state.ffmpeg_path.read().await;
-  let output = std::process::Command::new(&*ffmpeg_path)
-    .args(["-version"])
-    .output()
-    .map_err(|e| VideoCompilerError::DependencyMissing(format!("FFmpeg not found: {e}")))?;
-
-  let version = String::from_utf8_lossy(&output.stdout);
-  let has_cuda = version.contains("--enable-cuda");
-  let has_nvenc = version.contains("--enable-nvenc");
-  let has_qsv = version.contains("--enable-libmfx");
-  let has_amf = version.contains("--enable-amf");
-  let has_videotoolbox = version.contains("--enable-videotoolbox");
+  let ffmpeg_path = state.ffmpeg_path.read().await.clone();
+  let capabilities = FfmpegCapabilitiesCache::shared(&ffmpeg_path).get().await?;
+  let has_hwaccel = |name: &str| capabilities.hwaccels().iter().any(|h| h == name);
+  let version = match capabilities.version.as_str() {
+    "" => "Unknown",
+    version => version,
+  };
 
   Ok(serde_json::json!({
-    "version": version.lines().next().unwrap_or("Unknown"),
+    "version": version,
     "hardware_acceleration": {
-      "cuda": has_cuda,
-      "nvenc": has_nvenc,
-      "qsv": has_qsv,
-      "amf": has_amf,
-      "videotoolbox": has_videotoolbox,
-    }
+      "cuda": has_hwaccel("cuda"),
+      "nvenc": capabilities.has_encoder("h264_nvenc"),
+      "qsv": capabilities.has_encoder("h264_qsv"),
+      "amf": capabilities.has_encoder("h264_amf"),
+      "videotoolbox": has_hwaccel("videotoolbox"),
+    },
+    "hwaccels": capabilities.hwaccels(),
   }))
 }
 
@@ -113,14 +107,9 @@ pub async fn check_gpu_encoder_availability(
   encoder: String,
   state: State<'_, VideoCompilerState>,
 ) -> Result<bool> {
-  let ffmpeg_path = state.ffmpeg_path.read().await;
-  let output = std::process::Command::new(&*ffmpeg_path)
-    .args(["-encoders"])
-    .output()
-    .map_err(|e| VideoCompilerError::DependencyMissing(format!("FFmpeg not found: {e}")))?;
-
-  let encoders = String::from_utf8_lossy(&output.stdout);
-  Ok(encoders.contains(&encoder))
+  let ffmpeg_path = state.ffmpeg_path.read().await.clone();
+  let capabilities = FfmpegCapabilitiesCache::shared(&ffmpeg_path).get().await?;
+  Ok(capabilities.has_encoder(&encoder))
 }
 
 /// Проверить поддержку аппаратного ускорения (альтернативная версия)
@@ -218,6 +207,7 @@ pub async fn create_new_project(
       resolution,
       sample_rate: 48000,
       aspect_ratio: AspectRatio::default(),
+      markers: Vec::new(),
     },
     tracks: vec![],
     effects: vec![],
@@ -225,6 +215,7 @@ pub async fn create_new_project(
     filters: vec![],
     templates: vec![],
     style_templates: vec![],
+    sequences: Default::default(),
     subtitles: vec![],
     settings: ProjectSettings::default(),
   })
@@ -403,6 +394,29 @@ pub async fn get_video_info(
 
 // get_project_service_info moved to test_helper_commands.rs
 
+crate::command_manifest!(
+  MISC_MANIFEST,
+  "video_compiler::misc",
+  [
+    cache_media_metadata,
+    check_ffmpeg_capabilities,
+    check_gpu_encoder_availability,
+    check_hardware_acceleration,
+    cleanup_cache,
+    clear_cache,
+    clear_file_preview_cache,
+    configure_cache,
+    create_new_project,
+    get_cache_memory_usage,
+    get_cached_metadata,
+    get_current_gpu_info,
+    get_gpu_info,
+    get_recommended_gpu_encoder,
+    get_render_cache_info,
+    get_video_info,
+  ]
+);
+
 #[cfg(test)]
 mod tests {
   use super::*;
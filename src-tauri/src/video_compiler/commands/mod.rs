@@ -14,14 +14,18 @@
 //! - `misc` - Дополнительные команды
 
 pub mod advanced_metrics;
+pub mod audio_sync_commands;
 pub mod batch_commands;
 pub mod cache;
+pub mod command_preview_commands;
 pub mod compiler_settings_commands;
+pub mod effect_preset_commands;
 pub mod ffmpeg_advanced;
 pub mod ffmpeg_builder_advanced_commands;
 pub mod ffmpeg_builder_commands;
 pub mod ffmpeg_builder_extra_commands;
 pub mod ffmpeg_executor_commands;
+pub mod ffmpeg_manager_commands;
 pub mod ffmpeg_utilities_commands;
 pub mod final_utilities_commands;
 pub mod frame_extraction_advanced_commands;
@@ -29,6 +33,7 @@ pub mod frame_extraction_commands;
 pub mod frame_manager_commands;
 pub mod gpu;
 pub mod info;
+pub mod media_relink_commands;
 pub mod metrics;
 pub mod metrics_advanced_commands;
 pub mod misc;
@@ -37,19 +42,31 @@ pub mod multimodal_commands;
 pub mod pipeline_advanced_commands;
 pub mod pipeline_commands;
 pub mod platform_optimization_commands;
+pub mod playback_assist_commands;
 pub mod prerender_commands;
 pub mod preview;
 pub mod preview_advanced_commands;
 pub mod progress_tracker_commands;
 pub mod project;
+pub mod project_assets_commands;
+pub mod project_package_commands;
+pub mod project_storage_commands;
+pub mod project_template_commands;
+pub mod quick_edit_commands;
 pub mod recognition_advanced_commands;
+pub mod reframe_commands;
 pub mod remaining_utilities_commands;
+pub mod render_report_commands;
+pub mod render_spec_commands;
 pub mod rendering;
 pub mod schema_commands;
+pub mod schema_journal_commands;
 pub mod security_advanced_commands;
 pub mod service_commands;
 pub mod service_container_commands;
+pub mod snap_index_commands;
 pub mod state;
+pub mod test_media_commands;
 pub mod timeline_schema_commands;
 pub mod video_analysis;
 pub mod whisper_commands;
@@ -57,15 +74,19 @@ pub mod workflow_commands;
 
 // Re-export всех команд для удобства использования
 pub use advanced_metrics::*;
+pub use audio_sync_commands::*;
 pub use batch_commands::*;
 pub use cache::*;
+pub use command_preview_commands::*;
 pub use compiler_settings_commands::*;
+pub use effect_preset_commands::*;
 #[allow(unused_imports)]
 pub use ffmpeg_advanced::*;
 pub use ffmpeg_builder_advanced_commands::*;
 pub use ffmpeg_builder_commands::*;
 pub use ffmpeg_builder_extra_commands::*;
 pub use ffmpeg_executor_commands::*;
+pub use ffmpeg_manager_commands::*;
 pub use ffmpeg_utilities_commands::*;
 pub use final_utilities_commands::*;
 #[allow(ambiguous_glob_reexports)]
@@ -75,6 +96,7 @@ pub use frame_extraction_commands::*;
 pub use frame_manager_commands::*;
 pub use gpu::*;
 pub use info::*;
+pub use media_relink_commands::*;
 pub use metrics::*;
 
 #[allow(ambiguous_glob_reexports)]
@@ -86,18 +108,30 @@ pub use multimodal_commands::*;
 pub use pipeline_advanced_commands::*;
 pub use pipeline_commands::*;
 pub use platform_optimization_commands::*;
+pub use playback_assist_commands::*;
 pub use prerender_commands::*;
 pub use preview::*;
 pub use preview_advanced_commands::*;
 pub use progress_tracker_commands::*;
 pub use project::*;
+pub use project_assets_commands::*;
+pub use project_package_commands::*;
+pub use project_storage_commands::*;
+pub use project_template_commands::*;
+pub use quick_edit_commands::*;
 pub use recognition_advanced_commands::*;
+pub use reframe_commands::*;
 pub use remaining_utilities_commands::*;
+pub use render_report_commands::*;
+pub use render_spec_commands::*;
 pub use rendering::*;
 pub use schema_commands::*;
+pub use schema_journal_commands::*;
 pub use security_advanced_commands::*;
 pub use service_commands::*;
 pub use service_container_commands::*;
+pub use snap_index_commands::*;
+pub use test_media_commands::*;
 pub use timeline_schema_commands::*;
 // Video analysis commands
 pub use video_analysis::*;
@@ -107,5 +141,65 @@ pub use workflow_commands::*;
 // Re-export основных типов
 pub use state::VideoCompilerState;
 
+/// Манифесты команд модулей (проверяются тестом регистрации)
+pub const COMMAND_MANIFESTS: &[crate::command_registry::CommandManifest] = &[
+  advanced_metrics::ADVANCED_METRICS_MANIFEST,
+  audio_sync_commands::AUDIO_SYNC_COMMANDS_MANIFEST,
+  cache::CACHE_MANIFEST,
+  command_preview_commands::COMMAND_PREVIEW_COMMANDS_MANIFEST,
+  compiler_settings_commands::COMPILER_SETTINGS_COMMANDS_MANIFEST,
+  effect_preset_commands::EFFECT_PRESET_COMMANDS_MANIFEST,
+  ffmpeg_advanced::FFMPEG_ADVANCED_MANIFEST,
+  ffmpeg_builder_advanced_commands::FFMPEG_BUILDER_ADVANCED_COMMANDS_MANIFEST,
+  ffmpeg_builder_commands::FFMPEG_BUILDER_COMMANDS_MANIFEST,
+  ffmpeg_builder_extra_commands::FFMPEG_BUILDER_EXTRA_COMMANDS_MANIFEST,
+  ffmpeg_executor_commands::FFMPEG_EXECUTOR_COMMANDS_MANIFEST,
+  ffmpeg_manager_commands::FFMPEG_MANAGER_COMMANDS_MANIFEST,
+  ffmpeg_utilities_commands::FFMPEG_UTILITIES_COMMANDS_MANIFEST,
+  final_utilities_commands::FINAL_UTILITIES_COMMANDS_MANIFEST,
+  frame_extraction_advanced_commands::FRAME_EXTRACTION_ADVANCED_COMMANDS_MANIFEST,
+  frame_extraction_commands::FRAME_EXTRACTION_COMMANDS_MANIFEST,
+  frame_manager_commands::FRAME_MANAGER_COMMANDS_MANIFEST,
+  gpu::GPU_MANIFEST,
+  info::INFO_MANIFEST,
+  media_relink_commands::MEDIA_RELINK_COMMANDS_MANIFEST,
+  metrics::METRICS_MANIFEST,
+  metrics_advanced_commands::METRICS_ADVANCED_COMMANDS_MANIFEST,
+  misc::MISC_MANIFEST,
+  monitoring_commands::MONITORING_COMMANDS_MANIFEST,
+  multimodal_commands::MULTIMODAL_COMMANDS_MANIFEST,
+  pipeline_advanced_commands::PIPELINE_ADVANCED_COMMANDS_MANIFEST,
+  pipeline_commands::PIPELINE_COMMANDS_MANIFEST,
+  platform_optimization_commands::PLATFORM_OPTIMIZATION_COMMANDS_MANIFEST,
+  playback_assist_commands::PLAYBACK_ASSIST_COMMANDS_MANIFEST,
+  prerender_commands::PRERENDER_COMMANDS_MANIFEST,
+  preview::PREVIEW_MANIFEST,
+  preview_advanced_commands::PREVIEW_ADVANCED_COMMANDS_MANIFEST,
+  progress_tracker_commands::PROGRESS_TRACKER_COMMANDS_MANIFEST,
+  project::PROJECT_MANIFEST,
+  project_assets_commands::PROJECT_ASSETS_COMMANDS_MANIFEST,
+  project_package_commands::PROJECT_PACKAGE_COMMANDS_MANIFEST,
+  project_storage_commands::PROJECT_STORAGE_COMMANDS_MANIFEST,
+  project_template_commands::PROJECT_TEMPLATE_COMMANDS_MANIFEST,
+  quick_edit_commands::QUICK_EDIT_COMMANDS_MANIFEST,
+  recognition_advanced_commands::RECOGNITION_ADVANCED_COMMANDS_MANIFEST,
+  reframe_commands::REFRAME_COMMANDS_MANIFEST,
+  remaining_utilities_commands::REMAINING_UTILITIES_COMMANDS_MANIFEST,
+  render_report_commands::RENDER_REPORT_COMMANDS_MANIFEST,
+  render_spec_commands::RENDER_SPEC_COMMANDS_MANIFEST,
+  rendering::RENDERING_MANIFEST,
+  schema_commands::SCHEMA_COMMANDS_MANIFEST,
+  schema_journal_commands::SCHEMA_JOURNAL_COMMANDS_MANIFEST,
+  security_advanced_commands::SECURITY_ADVANCED_COMMANDS_MANIFEST,
+  service_commands::SERVICE_COMMANDS_MANIFEST,
+  service_container_commands::SERVICE_CONTAINER_COMMANDS_MANIFEST,
+  snap_index_commands::SNAP_INDEX_COMMANDS_MANIFEST,
+  test_media_commands::TEST_MEDIA_COMMANDS_MANIFEST,
+  timeline_schema_commands::TIMELINE_SCHEMA_COMMANDS_MANIFEST,
+  video_analysis::VIDEO_ANALYSIS_MANIFEST,
+  whisper_commands::WHISPER_COMMANDS_MANIFEST,
+  workflow_commands::WORKFLOW_COMMANDS_MANIFEST,
+];
+
 #[cfg(test)]
 mod tests;
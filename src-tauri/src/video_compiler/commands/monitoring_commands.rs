@@ -311,6 +311,21 @@ pub async fn get_registry_service_metrics(
   }
 }
 
+crate::command_manifest!(
+  MONITORING_COMMANDS_MANIFEST,
+  "video_compiler::monitoring_commands",
+  [
+    get_service_metrics_summary,
+    reset_service_metrics_detailed,
+    get_all_metrics_summaries,
+    export_metrics_prometheus_detailed,
+    check_services_health,
+    get_performance_metrics,
+    reset_all_metrics,
+    get_registry_service_metrics,
+  ]
+);
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -323,6 +338,8 @@ mod tests {
       uptime_seconds: 3600,
       total_operations: 100,
       total_errors: 3,
+      total_timeouts: 0,
+      total_retries: 0,
       active_operations: 5,
       operations_per_second: 10.5,
       error_rate: 0.03,
@@ -397,6 +414,8 @@ mod tests {
       uptime_seconds: 7200,
       total_operations: 1000,
       total_errors: 20,
+      total_timeouts: 0,
+      total_retries: 0,
       active_operations: 10, // < 100
       operations_per_second: 25.5,
       error_rate: 0.02, // 2% < 5%
@@ -419,6 +438,8 @@ mod tests {
       uptime_seconds: 3600,
       total_operations: 1000,
       total_errors: 60,
+      total_timeouts: 0,
+      total_retries: 0,
       active_operations: 5,
       operations_per_second: 15.0,
       error_rate: 0.06, // 6% > 5%
@@ -439,6 +460,8 @@ mod tests {
       uptime_seconds: 1800,
       total_operations: 2000,
       total_errors: 10,
+      total_timeouts: 0,
+      total_retries: 0,
       active_operations: 150, // > 100
       operations_per_second: 30.0,
       error_rate: 0.005, // 0.5% < 5%
@@ -465,6 +488,8 @@ mod tests {
         max_duration_ms: 50,
         min_duration_ms: 10,
         last_operation_time: Some(std::time::SystemTime::now()),
+        timeouts: 0,
+        retries: 0,
         last_error: Some("Test error".to_string()),
       },
     );
@@ -474,6 +499,8 @@ mod tests {
       uptime_seconds: 14400,
       total_operations: 500,
       total_errors: 25,
+      total_timeouts: 0,
+      total_retries: 0,
       active_operations: 15,
       operations_per_second: 12.3,
       error_rate: 0.05,
@@ -572,6 +599,8 @@ mod tests {
       uptime_seconds: 60,
       total_operations: 0,
       total_errors: 0,
+      total_timeouts: 0,
+      total_retries: 0,
       active_operations: 0,
       operations_per_second: 0.0,
       error_rate: 0.0,
@@ -587,6 +616,8 @@ mod tests {
       uptime_seconds: 3600,
       total_operations: 100,
       total_errors: 5,
+      total_timeouts: 0,
+      total_retries: 0,
       active_operations: 100, // Exactly at 100
       operations_per_second: 10.0,
       error_rate: 0.05, // Exactly 5%
@@ -239,3 +239,16 @@ pub async fn cleanup_extracted_frames(clip_id: String) -> Result<u32, String> {
   log::info!("Удалено {removed_count} временных кадров для клипа {clip_id}");
   Ok(removed_count)
 }
+
+crate::command_manifest!(
+  MULTIMODAL_COMMANDS_MANIFEST,
+  "video_compiler::multimodal_commands",
+  [
+    extract_frames_for_multimodal_analysis,
+    convert_image_to_base64,
+    extract_thumbnail_candidates,
+    create_frame_collage,
+    optimize_image_for_analysis,
+    cleanup_extracted_frames,
+  ]
+);
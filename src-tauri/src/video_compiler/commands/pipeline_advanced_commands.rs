@@ -356,6 +356,24 @@ pub async fn generate_gradient_clip_direct(
   }
 }
 
+crate::command_manifest!(
+  PIPELINE_ADVANCED_COMMANDS_MANIFEST,
+  "video_compiler::pipeline_advanced_commands",
+  [
+    get_pipeline_context_mutable,
+    set_pipeline_user_data,
+    get_pipeline_user_data,
+    should_use_hardware_acceleration_for_codec,
+    generate_noise_clip_advanced,
+    generate_gradient_clip_advanced,
+    check_should_use_hardware_acceleration_for_codec,
+    set_pipeline_user_data_direct,
+    get_pipeline_user_data_direct,
+    generate_noise_clip_direct,
+    generate_gradient_clip_direct,
+  ]
+);
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -422,6 +422,26 @@ pub async fn cleanup_completed_pipelines(state: State<'_, VideoCompilerState>) -
   Ok(initial_count - pipelines.len())
 }
 
+crate::command_manifest!(
+  PIPELINE_COMMANDS_MANIFEST,
+  "video_compiler::pipeline_commands",
+  [
+    create_and_execute_pipeline,
+    get_pipeline_info,
+    cancel_pipeline,
+    get_pipeline_statistics,
+    get_pipeline_context,
+    update_pipeline_settings,
+    validate_pipeline_configuration,
+    insert_pipeline_stage,
+    remove_pipeline_stage,
+    build_custom_pipeline,
+    get_pipeline_execution_summary,
+    get_pipeline_progress,
+    cleanup_completed_pipelines,
+  ]
+);
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -448,6 +468,7 @@ mod tests {
         resolution: (1920, 1080),
         sample_rate: 48000,
         aspect_ratio: AspectRatio::Ratio16x9,
+        markers: Vec::new(),
       },
       tracks: vec![
         Track::new(
@@ -464,6 +485,7 @@ mod tests {
       transitions: vec![],
       templates: vec![],
       style_templates: vec![],
+      sequences: Default::default(),
       subtitles: vec![],
       settings: ProjectSettings::default(),
     }
@@ -472,52 +494,14 @@ mod tests {
   fn create_test_state() -> VideoCompilerState {
     // Создаем минимальный контейнер сервисов для тестов
     let ffmpeg_path = "ffmpeg".to_string();
-    let cache = Arc::new(crate::video_compiler::services::CacheServiceImpl::new(
-      std::env::temp_dir().join("timeline-studio-test"),
-    ));
-    let ffmpeg = Arc::new(crate::video_compiler::services::FfmpegServiceImpl::new(
-      ffmpeg_path.clone(),
-    ));
-
-    // Создаем метрики для каждого сервиса
-    let metrics = crate::video_compiler::services::ServiceMetricsContainer {
-      render: Arc::new(crate::video_compiler::services::ServiceMetrics::new(
-        "render-service".to_string(),
-      )),
-      cache: Arc::new(crate::video_compiler::services::ServiceMetrics::new(
-        "cache-service".to_string(),
-      )),
-      gpu: Arc::new(crate::video_compiler::services::ServiceMetrics::new(
-        "gpu-service".to_string(),
-      )),
-      preview: Arc::new(crate::video_compiler::services::ServiceMetrics::new(
-        "preview-service".to_string(),
-      )),
-      project: Arc::new(crate::video_compiler::services::ServiceMetrics::new(
-        "project-service".to_string(),
-      )),
-      ffmpeg: Arc::new(crate::video_compiler::services::ServiceMetrics::new(
-        "ffmpeg-service".to_string(),
-      )),
-    };
-
-    let services = Arc::new(crate::video_compiler::services::ServiceContainer {
-      render: Arc::new(crate::video_compiler::services::RenderServiceImpl::new(
-        ffmpeg.clone(),
-        2,
-        cache.clone(),
-      )),
-      cache: cache.clone(),
-      gpu: Arc::new(crate::video_compiler::services::GpuServiceImpl::new(
+    let services = Arc::new(
+      crate::video_compiler::services::ServiceContainer::builder(
         ffmpeg_path.clone(),
-      )),
-      preview: Arc::new(crate::video_compiler::services::PreviewServiceImpl::new(
-        ffmpeg.clone(),
-      )),
-      ffmpeg: ffmpeg.clone(),
-      project: Arc::new(crate::video_compiler::services::ProjectServiceImpl::new()),
-      metrics,
-    });
+        std::env::temp_dir().join("timeline-studio-test"),
+        2,
+      )
+      .build_with_metrics(crate::video_compiler::services::ServiceMetricsContainer::unregistered()),
+    );
 
     VideoCompilerState {
       services,
@@ -587,6 +587,18 @@ fn generate_compliance_recommendations(
   recommendations
 }
 
+crate::command_manifest!(
+  PLATFORM_OPTIMIZATION_COMMANDS_MANIFEST,
+  "video_compiler::platform_optimization_commands",
+  [
+    ffmpeg_optimize_for_platform,
+    ffmpeg_generate_platform_thumbnail,
+    ffmpeg_batch_optimize_platforms,
+    ffmpeg_analyze_platform_compliance,
+    ffmpeg_create_progressive_video,
+  ]
+);
+
 #[cfg(test)]
 mod tests {
   use super::*;
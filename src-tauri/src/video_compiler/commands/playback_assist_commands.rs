@@ -0,0 +1,112 @@
+//! Playback Assist Commands - предрендер окна впереди playhead во время
+//! воспроизведения
+
+use crate::video_compiler::cache::RenderCache;
+use crate::video_compiler::commands::prerender_commands::{
+  render_prerender_segment, resolve_prerender_output,
+};
+use crate::video_compiler::commands::VideoCompilerState;
+use crate::video_compiler::core::render_priority::RenderPriority;
+use crate::video_compiler::error::Result;
+use crate::video_compiler::schema::ProjectSchema;
+use crate::video_compiler::services::playback_assist::{
+  playback_chunk_project, PlaybackAssist, PlaybackAssistConfig, PlaybackChunkRenderer,
+  PlaybackRange,
+};
+use crate::video_compiler::services::RenderService;
+use crate::video_compiler::CompilerSettings;
+use async_trait::async_trait;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::State;
+use tokio::sync::RwLock;
+
+/// Рендер отрезков воспроизведения через обычный путь предрендеринга
+pub struct PrerenderChunkRenderer {
+  settings: Arc<RwLock<CompilerSettings>>,
+  cache: Arc<RwLock<RenderCache>>,
+  render: Arc<dyn RenderService>,
+}
+
+impl PrerenderChunkRenderer {
+  pub fn from_state(state: &VideoCompilerState) -> Self {
+    Self {
+      settings: state.settings.clone(),
+      cache: state.cache_manager.clone(),
+      render: state.services.render.clone(),
+    }
+  }
+}
+
+#[async_trait]
+impl PlaybackChunkRenderer for PrerenderChunkRenderer {
+  async fn render_chunk(&self, project: &ProjectSchema, range: PlaybackRange) -> Result<PathBuf> {
+    let output_path =
+      resolve_prerender_output(&self.settings, range.start, range.end, None).await?;
+    render_prerender_segment(
+      &playback_chunk_project(project),
+      range.start,
+      range.end,
+      &output_path,
+      self.settings.clone(),
+      self.cache.clone(),
+      RenderPriority::Background.limits(None),
+    )
+    .await?;
+    Ok(output_path)
+  }
+
+  async fn full_render_running(&self) -> bool {
+    self
+      .render
+      .running_jobs()
+      .await
+      .map(|jobs| !jobs.is_empty())
+      .unwrap_or(false)
+  }
+}
+
+/// Передать проект для предрендера во время воспроизведения.
+///
+/// Вызывается при открытии проекта и после изменений timeline; `config`
+/// задает окно впереди playhead и длительность отрезков.
+#[tauri::command]
+pub async fn set_playback_project(
+  project_id: String,
+  project: ProjectSchema,
+  config: Option<PlaybackAssistConfig>,
+  assist: State<'_, Arc<PlaybackAssist>>,
+) -> Result<()> {
+  assist.set_project(&project_id, project, config).await;
+  Ok(())
+}
+
+/// Сообщить положение playhead и состояние воспроизведения
+#[tauri::command]
+pub async fn update_playback_position(
+  project_id: String,
+  time: f64,
+  playing: bool,
+  assist: State<'_, Arc<PlaybackAssist>>,
+) -> Result<()> {
+  assist.update_position(&project_id, time, playing).await
+}
+
+/// Участки timeline, которые будут воспроизводиться плавно
+#[tauri::command]
+pub async fn get_playback_ready_ranges(
+  project_id: String,
+  assist: State<'_, Arc<PlaybackAssist>>,
+) -> Result<Vec<PlaybackRange>> {
+  Ok(assist.ready_ranges(&project_id).await)
+}
+
+crate::command_manifest!(
+  PLAYBACK_ASSIST_COMMANDS_MANIFEST,
+  "video_compiler::playback_assist_commands",
+  [
+    set_playback_project,
+    update_playback_position,
+    get_playback_ready_ranges,
+  ]
+);
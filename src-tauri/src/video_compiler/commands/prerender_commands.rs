@@ -2,9 +2,18 @@
 //!
 //! Команды для работы с предрендерингом сегментов проекта
 
+use crate::video_compiler::cache::RenderCache;
 use crate::video_compiler::commands::VideoCompilerState;
-use crate::video_compiler::error::Result;
+use crate::video_compiler::core::render_priority::{ProcessLimits, RenderPriority};
+use crate::video_compiler::core::temp_storage;
+use crate::video_compiler::error::{Result, VideoCompilerError};
+use crate::video_compiler::renderer::VideoRenderer;
+use crate::video_compiler::schema::ProjectSchema;
+use crate::video_compiler::CompilerSettings;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use tauri::State;
+use tokio::sync::RwLock;
 
 /// Результат предрендеринга
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -34,60 +43,114 @@ pub struct PrerenderCacheFile {
   pub created_at: String,
 }
 
-/// Предварительно отрендерить сегмент
-#[tauri::command]
-pub async fn prerender_segment(
-  project_schema: crate::video_compiler::schema::ProjectSchema,
+/// Проект отрезка `[start_time, end_time)` для предрендеринга
+pub(crate) fn segment_project(
+  project: &ProjectSchema,
   start_time: f64,
   end_time: f64,
-  output_path: String,
-  state: State<'_, VideoCompilerState>,
-) -> Result<String> {
-  use crate::video_compiler::renderer::VideoRenderer;
-
-  // Создаем временный проект только с нужным сегментом
-  let mut segment_project = project_schema.clone();
-  segment_project.timeline.duration = end_time - start_time;
-
-  // Фильтруем клипы
-  for track in &mut segment_project.tracks {
-    track
-      .clips
-      .retain(|clip| clip.start_time < end_time && clip.end_time > start_time);
-
-    // Корректируем времена клипов
-    for clip in &mut track.clips {
-      if clip.start_time < start_time {
-        clip.source_start += start_time - clip.start_time;
-        clip.start_time = 0.0;
-      } else {
-        clip.start_time -= start_time;
-      }
+) -> ProjectSchema {
+  let mut segment_project = project.extract_range(start_time, end_time);
+
+  // Водяной знак в пререндере нужен только для проверки положения
+  segment_project.settings.export.watermark = segment_project
+    .settings
+    .export
+    .watermark
+    .take()
+    .filter(|watermark| watermark.show_in_preview);
+  // Отчет о рендере нужен только для экспорта
+  segment_project.settings.export.render_report.enabled = false;
+
+  segment_project
+}
 
-      if clip.end_time > end_time {
-        clip.end_time = end_time - start_time;
-      } else {
-        clip.end_time -= start_time;
-      }
+/// Путь к результату предрендеринга без создания директорий. Без явного
+/// пути используется поддиректория предрендеринга временной директории.
+pub async fn prerender_output_path(
+  settings: &RwLock<CompilerSettings>,
+  start_time: f64,
+  end_time: f64,
+  output_path: Option<String>,
+) -> PathBuf {
+  match output_path {
+    Some(path) => PathBuf::from(path),
+    None => {
+      let temp_root = settings.read().await.temp_directory.clone();
+      temp_storage::prerender_output_path(&temp_root, start_time, end_time)
     }
   }
+}
 
-  // Создаем рендерер
-  let (progress_tx, _progress_rx) = tokio::sync::mpsc::unbounded_channel();
+/// Путь к результату предрендеринга. Без явного пути файл создается
+/// в поддиректории предрендеринга временной директории из настроек.
+pub async fn resolve_prerender_output(
+  settings: &RwLock<CompilerSettings>,
+  start_time: f64,
+  end_time: f64,
+  output_path: Option<String>,
+) -> Result<PathBuf> {
+  let output_path = prerender_output_path(settings, start_time, end_time, output_path).await;
+
+  if let Some(parent) = output_path.parent() {
+    tokio::fs::create_dir_all(parent)
+      .await
+      .map_err(|e| VideoCompilerError::IoError(e.to_string()))?;
+  }
+  Ok(output_path)
+}
 
-  let mut renderer = VideoRenderer::new(
-    segment_project.clone(),
+/// Предварительно отрендерить сегмент.
+///
+/// `render_priority` и `render_threads` работают так же, как в `compile_video`.
+#[tauri::command]
+pub async fn prerender_segment(
+  project_schema: ProjectSchema,
+  start_time: f64,
+  end_time: f64,
+  output_path: Option<String>,
+  render_priority: Option<RenderPriority>,
+  render_threads: Option<usize>,
+  state: State<'_, VideoCompilerState>,
+) -> Result<String> {
+  let output_path =
+    resolve_prerender_output(&state.settings, start_time, end_time, output_path).await?;
+
+  render_prerender_segment(
+    &project_schema,
+    start_time,
+    end_time,
+    &output_path,
     state.settings.clone(),
     state.cache_manager.clone(),
-    progress_tx,
+    render_priority.unwrap_or_default().limits(render_threads),
   )
   .await?;
 
-  // Рендерим сегмент
-  let _job_id = uuid::Uuid::new_v4().to_string();
-  renderer.render(std::path::Path::new(&output_path)).await?;
+  Ok(output_path.to_string_lossy().to_string())
+}
 
-  Ok(output_path)
+/// Отрендерить отрезок `[start_time, end_time)` проекта в `output_path`
+pub async fn render_prerender_segment(
+  project: &ProjectSchema,
+  start_time: f64,
+  end_time: f64,
+  output_path: &Path,
+  settings: Arc<RwLock<CompilerSettings>>,
+  cache: Arc<RwLock<RenderCache>>,
+  limits: ProcessLimits,
+) -> Result<()> {
+  let segment_project = segment_project(project, start_time, end_time);
+
+  // Создаем рендерер
+  let (progress_tx, _progress_rx) = tokio::sync::mpsc::unbounded_channel();
+
+  let mut renderer = VideoRenderer::new(segment_project, settings, cache, progress_tx)
+    .await?
+    .with_process_limits(limits);
+
+  // Рендерим сегмент; временные файлы задачи создаются в той же временной директории
+  renderer.render(output_path).await?;
+  Ok(())
 }
 
 /// Получить информацию о кэше предрендеринга
@@ -253,6 +316,21 @@ pub async fn optimize_prerender_cache(
   Ok(0)
 }
 
+crate::command_manifest!(
+  PRERENDER_COMMANDS_MANIFEST,
+  "video_compiler::prerender_commands",
+  [
+    prerender_segment,
+    get_prerender_cache_info,
+    clear_prerender_cache,
+    build_prerender_segment_command,
+    check_prerender_status,
+    get_prerendered_segments,
+    delete_prerendered_segment,
+    optimize_prerender_cache,
+  ]
+);
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -557,4 +635,55 @@ mod tests {
     assert_eq!(file1.size_bytes, file2.size_bytes);
     assert_eq!(file1.created_at, file2.created_at);
   }
+
+  #[tokio::test]
+  async fn test_prerender_writes_under_changed_temp_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    let new_root = dir.path().join("scratch");
+    let settings = RwLock::new(CompilerSettings::default());
+
+    temp_storage::change_temp_directory(&settings, &new_root, false, 0)
+      .await
+      .unwrap();
+
+    let output = resolve_prerender_output(&settings, 2.0, 4.0, None)
+      .await
+      .unwrap();
+    let new_root = new_root.canonicalize().unwrap();
+    assert!(output.starts_with(new_root.join(temp_storage::PRERENDER_DIR)));
+    assert!(output.parent().unwrap().is_dir());
+
+    // Явный путь используется как есть
+    let explicit = dir.path().join("out").join("segment.mp4");
+    let output = resolve_prerender_output(
+      &settings,
+      2.0,
+      4.0,
+      Some(explicit.to_string_lossy().to_string()),
+    )
+    .await
+    .unwrap();
+    assert_eq!(output, explicit);
+  }
+
+  #[test]
+  fn test_segment_project_shifts_clips() {
+    use crate::video_compiler::schema::{Clip, Track, TrackType};
+
+    let mut project = ProjectSchema::new("Segment".to_string());
+    let mut track = Track::new(TrackType::Video, "Video".to_string());
+    for (start, end) in [(0.0, 3.0), (3.0, 6.0), (6.0, 9.0)] {
+      track
+        .clips
+        .push(Clip::new(PathBuf::from("/media/a.mp4"), start, end - start));
+    }
+    project.tracks.push(track);
+
+    let segment = segment_project(&project, 2.0, 5.0);
+    let clips = &segment.tracks[0].clips;
+    assert_eq!(clips.len(), 2);
+    assert_eq!((clips[0].start_time, clips[0].end_time), (0.0, 1.0));
+    assert_eq!(clips[0].source_start, 2.0);
+    assert_eq!((clips[1].start_time, clips[1].end_time), (1.0, 3.0));
+  }
 }
@@ -3,12 +3,14 @@
 //! Команды для создания превью кадров, миниатюр
 //! и предварительного просмотра проекта.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tauri::State;
 
 use crate::video_compiler::core::preview::PreviewOptions;
 use crate::video_compiler::error::{Result, VideoCompilerError};
-use crate::video_compiler::schema::ProjectSchema;
+use crate::video_compiler::schema::{
+  ChromaKeyBackground, ChromaKeyParams, Clip, PreviewFormat, ProjectSchema, Track, TrackType,
+};
 
 use super::state::VideoCompilerState;
 
@@ -20,23 +22,110 @@ pub async fn generate_frame_preview(
   output_path: String,
   state: State<'_, VideoCompilerState>,
 ) -> Result<String> {
-  let preview_service = state
-    .services
-    .get_preview_service()
-    .ok_or_else(|| VideoCompilerError::validation("PreviewService не найден"))?;
-
-  let options = PreviewOptions {
-    width: None,  // Использовать оригинальную ширину
-    height: None, // Использовать оригинальную высоту
-    format: "jpeg".to_string(),
-    quality: 85,
-  };
+  crate::instrumented_command!(generate_frame_preview, {
+    let preview_service = state
+      .services
+      .get_preview_service()
+      .ok_or_else(|| VideoCompilerError::validation("PreviewService не найден"))?;
+
+    let options = PreviewOptions {
+      width: None,  // Использовать оригинальную ширину
+      height: None, // Использовать оригинальную высоту
+      format: "jpeg".to_string(),
+      quality: 85,
+    };
+
+    preview_service
+      .generate_frame(&project_schema, timestamp, &output_path, Some(options))
+      .await?;
+
+    Ok(output_path)
+  })
+}
+
+/// Отрендерить кадр собранного timeline (все треки, шаблоны, эффекты и
+/// субтитры) в момент `timestamp` без полного рендеринга проекта
+#[tauri::command]
+pub async fn render_timeline_frame(
+  project_schema: ProjectSchema,
+  timestamp: f64,
+  resolution: Option<(u32, u32)>,
+  quality: Option<u8>,
+  format: Option<PreviewFormat>,
+  state: State<'_, VideoCompilerState>,
+) -> Result<Vec<u8>> {
+  crate::instrumented_command!(render_timeline_frame, {
+    use crate::video_compiler::preview::PreviewGenerator;
+
+    let mut generator = PreviewGenerator::new(state.cache_manager.clone());
+    generator.set_ffmpeg_path(state.ffmpeg_path.read().await.as_str());
+
+    generator
+      .render_timeline_frame(&project_schema, timestamp, resolution, quality, format)
+      .await
+  })
+}
+
+/// Отрендерить кадр видео с хромакеем для подбора параметров.
+///
+/// Видео с эффектом хромакея собирается в отдельный проект, поэтому фоном
+/// может быть только цвет или изображение.
+#[tauri::command]
+pub async fn chroma_key_preview(
+  video_path: String,
+  timestamp: f64,
+  params: ChromaKeyParams,
+  resolution: Option<(u32, u32)>,
+  state: State<'_, VideoCompilerState>,
+) -> Result<Vec<u8>> {
+  crate::instrumented_command!(chroma_key_preview, {
+    use crate::video_compiler::preview::PreviewGenerator;
+
+    let project = chroma_key_preview_project(&video_path, timestamp, &params)?;
+    let mut generator = PreviewGenerator::new(state.cache_manager.clone());
+    generator.set_ffmpeg_path(state.ffmpeg_path.read().await.as_str());
+
+    generator
+      .render_timeline_frame(&project, timestamp, resolution, None, None)
+      .await
+  })
+}
 
-  preview_service
-    .generate_frame(&project_schema, timestamp, &output_path, Some(options))
-    .await?;
+/// Проект из одного клипа с хромакеем для `chroma_key_preview`.
+///
+/// ID клипа и эффекта постоянные, чтобы кадры с одинаковыми параметрами
+/// брались из кэша.
+fn chroma_key_preview_project(
+  video_path: &str,
+  timestamp: f64,
+  params: &ChromaKeyParams,
+) -> Result<ProjectSchema> {
+  if !Path::new(video_path).is_file() {
+    return Err(VideoCompilerError::validation(format!(
+      "Видео не найдено: {video_path}"
+    )));
+  }
+  if matches!(params.background, Some(ChromaKeyBackground::Clip(_))) {
+    return Err(VideoCompilerError::validation(
+      "Фоновый клип недоступен в предпросмотре хромакея",
+    ));
+  }
 
-  Ok(output_path)
+  let mut effect = params.to_effect();
+  effect.id = "chroma_key_preview".to_string();
+  let mut clip = Clip::new(PathBuf::from(video_path), 0.0, timestamp.max(0.0) + 1.0);
+  clip.id = "chroma_key_preview_clip".to_string();
+  clip.effects.push(effect.id.clone());
+  let mut track = Track::new(TrackType::Video, "Chroma Key".to_string());
+  track.clips.push(clip);
+
+  let mut project = ProjectSchema::new("chroma_key_preview".to_string());
+  project.effects.push(effect);
+  project.tracks.push(track);
+  project
+    .validate_chroma_keys()
+    .map_err(VideoCompilerError::validation)?;
+  Ok(project)
 }
 
 /// Генерировать миниатюры для видео
@@ -49,32 +138,34 @@ pub async fn generate_video_thumbnails(
   height: u32,
   state: State<'_, VideoCompilerState>,
 ) -> Result<Vec<String>> {
-  let preview_service = state
-    .services
-    .get_preview_service()
-    .ok_or_else(|| VideoCompilerError::validation("PreviewService не найден"))?;
-
-  // Создаем временные метки для миниатюр
-  let mut timestamps = Vec::new();
-  for i in 0..count {
-    timestamps.push((i as f64) * 10.0); // Каждые 10 секунд
-  }
+  crate::instrumented_command!(generate_video_thumbnails, {
+    let preview_service = state
+      .services
+      .get_preview_service()
+      .ok_or_else(|| VideoCompilerError::validation("PreviewService не найден"))?;
+
+    // Создаем временные метки для миниатюр
+    let mut timestamps = Vec::new();
+    for i in 0..count {
+      timestamps.push((i as f64) * 10.0); // Каждые 10 секунд
+    }
+
+    let _results = preview_service
+      .generate_preview_batch_for_file(
+        Path::new(&video_path),
+        timestamps,
+        Some((width, height)),
+        Some(85),
+      )
+      .await?;
+
+    // Генерируем пути файлов на основе временных меток
+    let thumbnails: Vec<String> = (0..count)
+      .map(|i| format!("{output_dir}/thumbnail_{i}.jpg"))
+      .collect();
 
-  let _results = preview_service
-    .generate_preview_batch_for_file(
-      Path::new(&video_path),
-      timestamps,
-      Some((width, height)),
-      Some(85),
-    )
-    .await?;
-
-  // Генерируем пути файлов на основе временных меток
-  let thumbnails: Vec<String> = (0..count)
-    .map(|i| format!("{output_dir}/thumbnail_{i}.jpg"))
-    .collect();
-
-  Ok(thumbnails)
+    Ok(thumbnails)
+  })
 }
 
 /// Генерировать превью проекта (короткое видео)
@@ -87,24 +178,26 @@ pub async fn generate_project_preview(
   _height: u32,
   state: State<'_, VideoCompilerState>,
 ) -> Result<String> {
-  let preview_service = state
-    .services
-    .get_preview_service()
-    .ok_or_else(|| VideoCompilerError::validation("PreviewService не найден"))?;
-
-  let options = PreviewOptions {
-    width: Some(_width),
-    height: Some(_height),
-    format: "mp4".to_string(),
-    quality: 85,
-  };
+  crate::instrumented_command!(generate_project_preview, {
+    let preview_service = state
+      .services
+      .get_preview_service()
+      .ok_or_else(|| VideoCompilerError::validation("PreviewService не найден"))?;
 
-  // Генерируем кадр на начале проекта
-  preview_service
-    .generate_frame(&project_schema, 0.0, &output_path, Some(options))
-    .await?;
+    let options = PreviewOptions {
+      width: Some(_width),
+      height: Some(_height),
+      format: "mp4".to_string(),
+      quality: 85,
+    };
+
+    // Генерируем кадр на начале проекта
+    preview_service
+      .generate_frame(&project_schema, 0.0, &output_path, Some(options))
+      .await?;
 
-  Ok(output_path)
+    Ok(output_path)
+  })
 }
 
 /// Генерировать превью эффекта
@@ -117,28 +210,30 @@ pub async fn generate_effect_preview(
   _parameters: serde_json::Value,
   state: State<'_, VideoCompilerState>,
 ) -> Result<String> {
-  let preview_service = state
-    .services
-    .get_preview_service()
-    .ok_or_else(|| VideoCompilerError::validation("PreviewService не найден"))?;
-
-  // Создаем временный проект с эффектом для превью
-  let project = ProjectSchema::new("preview".to_string());
-  // Здесь должна быть логика применения эффекта к проекту
-  // Пока используем простую генерацию кадра
-
-  let options = PreviewOptions {
-    width: Some(320),
-    height: Some(240),
-    format: "jpeg".to_string(),
-    quality: 85,
-  };
+  crate::instrumented_command!(generate_effect_preview, {
+    let preview_service = state
+      .services
+      .get_preview_service()
+      .ok_or_else(|| VideoCompilerError::validation("PreviewService не найден"))?;
+
+    // Создаем временный проект с эффектом для превью
+    let project = ProjectSchema::new("preview".to_string());
+    // Здесь должна быть логика применения эффекта к проекту
+    // Пока используем простую генерацию кадра
+
+    let options = PreviewOptions {
+      width: Some(320),
+      height: Some(240),
+      format: "jpeg".to_string(),
+      quality: 85,
+    };
 
-  preview_service
-    .generate_frame(&project, timestamp, &output_path, Some(options))
-    .await?;
+    preview_service
+      .generate_frame(&project, timestamp, &output_path, Some(options))
+      .await?;
 
-  Ok(output_path)
+    Ok(output_path)
+  })
 }
 
 /// Генерировать превью перехода
@@ -151,28 +246,30 @@ pub async fn generate_transition_preview(
   duration: f64,
   state: State<'_, VideoCompilerState>,
 ) -> Result<String> {
-  let preview_service = state
-    .services
-    .get_preview_service()
-    .ok_or_else(|| VideoCompilerError::validation("PreviewService не найден"))?;
-
-  // Создаем временный проект с переходом для превью
-  let project = ProjectSchema::new("transition_preview".to_string());
-  // Здесь должна быть логика создания перехода между клипами
-  // Пока используем простую генерацию кадра в середине перехода
-
-  let options = PreviewOptions {
-    width: Some(320),
-    height: Some(240),
-    format: "jpeg".to_string(),
-    quality: 85,
-  };
+  crate::instrumented_command!(generate_transition_preview, {
+    let preview_service = state
+      .services
+      .get_preview_service()
+      .ok_or_else(|| VideoCompilerError::validation("PreviewService не найден"))?;
+
+    // Создаем временный проект с переходом для превью
+    let project = ProjectSchema::new("transition_preview".to_string());
+    // Здесь должна быть логика создания перехода между клипами
+    // Пока используем простую генерацию кадра в середине перехода
+
+    let options = PreviewOptions {
+      width: Some(320),
+      height: Some(240),
+      format: "jpeg".to_string(),
+      quality: 85,
+    };
 
-  preview_service
-    .generate_frame(&project, duration / 2.0, &output_path, Some(options))
-    .await?;
+    preview_service
+      .generate_frame(&project, duration / 2.0, &output_path, Some(options))
+      .await?;
 
-  Ok(output_path)
+    Ok(output_path)
+  })
 }
 
 /// Генерировать раскадровку (storyboard) проекта
@@ -185,45 +282,47 @@ pub async fn generate_storyboard(
   frame_height: u32,
   state: State<'_, VideoCompilerState>,
 ) -> Result<String> {
-  let preview_service = state
-    .services
-    .get_preview_service()
-    .ok_or_else(|| VideoCompilerError::validation("PreviewService не найден"))?;
-
-  // Генерируем раскадровку используя пакетную генерацию превью
-  let duration = project_schema.timeline.duration;
-  let frame_count = frames_per_row * 3; // Создаем 3 ряда
-  let mut timestamps = Vec::new();
-
-  for i in 0..frame_count {
-    let timestamp = (i as f64 / frame_count as f64) * duration;
-    timestamps.push(timestamp);
-  }
+  crate::instrumented_command!(generate_storyboard, {
+    let preview_service = state
+      .services
+      .get_preview_service()
+      .ok_or_else(|| VideoCompilerError::validation("PreviewService не найден"))?;
+
+    // Генерируем раскадровку используя пакетную генерацию превью
+    let duration = project_schema.timeline.duration;
+    let frame_count = frames_per_row * 3; // Создаем 3 ряда
+    let mut timestamps = Vec::new();
 
-  let options = PreviewOptions {
-    width: Some(frame_width),
-    height: Some(frame_height),
-    format: "jpeg".to_string(),
-    quality: 85,
-  };
+    for i in 0..frame_count {
+      let timestamp = (i as f64 / frame_count as f64) * duration;
+      timestamps.push(timestamp);
+    }
 
-  // Создаем директорию для кадров
-  std::fs::create_dir_all(&output_path)?;
+    let options = PreviewOptions {
+      width: Some(frame_width),
+      height: Some(frame_height),
+      format: "jpeg".to_string(),
+      quality: 85,
+    };
 
-  // Генерируем кадры
-  for (i, timestamp) in timestamps.into_iter().enumerate() {
-    let frame_path = format!("{output_path}/frame_{i:03}.jpg");
-    preview_service
-      .generate_frame(
-        &project_schema,
-        timestamp,
-        &frame_path,
-        Some(options.clone()),
-      )
-      .await?;
-  }
+    // Создаем директорию для кадров
+    std::fs::create_dir_all(&output_path)?;
+
+    // Генерируем кадры
+    for (i, timestamp) in timestamps.into_iter().enumerate() {
+      let frame_path = format!("{output_path}/frame_{i:03}.jpg");
+      preview_service
+        .generate_frame(
+          &project_schema,
+          timestamp,
+          &frame_path,
+          Some(options.clone()),
+        )
+        .await?;
+    }
 
-  Ok(output_path)
+    Ok(output_path)
+  })
 }
 
 /// Параметры для генерации анимированного превью
@@ -245,47 +344,49 @@ pub async fn generate_animated_preview(
   params: AnimatedPreviewParams,
   state: State<'_, VideoCompilerState>,
 ) -> Result<String> {
-  let _preview_service = state
-    .services
-    .get_preview_service()
-    .ok_or_else(|| VideoCompilerError::validation("PreviewService не найден"))?;
-
-  // Используем FFmpeg для создания анимированного GIF из видео
-  let mut cmd = std::process::Command::new("ffmpeg");
-  cmd.args([
-    "-y", // Перезаписывать выходной файл
-    "-i",
-    &params.video_path,
-    "-ss",
-    &params.start_time.to_string(), // Начальное время
-    "-t",
-    &params.duration.to_string(), // Длительность
-    "-vf",
-    &format!(
-      "fps={},scale={}:{}",
-      params.fps, params.width, params.height
-    ), // Фильтры: FPS и масштабирование
-    "-loop",
-    "0", // Бесконечный цикл
-    &params.output_path,
-  ]);
-
-  let output = cmd.output().map_err(|e| VideoCompilerError::FFmpegError {
-    exit_code: None,
-    stderr: format!("Не удалось запустить FFmpeg для GIF: {e}"),
-    command: "ffmpeg".to_string(),
-  })?;
-
-  if !output.status.success() {
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    return Err(VideoCompilerError::FFmpegError {
-      exit_code: output.status.code(),
-      stderr: format!("FFmpeg не смог создать GIF: {stderr}"),
+  crate::instrumented_command!(generate_animated_preview, {
+    let _preview_service = state
+      .services
+      .get_preview_service()
+      .ok_or_else(|| VideoCompilerError::validation("PreviewService не найден"))?;
+
+    // Используем FFmpeg для создания анимированного GIF из видео
+    let mut cmd = std::process::Command::new("ffmpeg");
+    cmd.args([
+      "-y", // Перезаписывать выходной файл
+      "-i",
+      &params.video_path,
+      "-ss",
+      &params.start_time.to_string(), // Начальное время
+      "-t",
+      &params.duration.to_string(), // Длительность
+      "-vf",
+      &format!(
+        "fps={},scale={}:{}",
+        params.fps, params.width, params.height
+      ), // Фильтры: FPS и масштабирование
+      "-loop",
+      "0", // Бесконечный цикл
+      &params.output_path,
+    ]);
+
+    let output = cmd.output().map_err(|e| VideoCompilerError::FFmpegError {
+      exit_code: None,
+      stderr: format!("Не удалось запустить FFmpeg для GIF: {e}"),
       command: "ffmpeg".to_string(),
-    });
-  }
+    })?;
+
+    if !output.status.success() {
+      let stderr = String::from_utf8_lossy(&output.stderr);
+      return Err(VideoCompilerError::FFmpegError {
+        exit_code: output.status.code(),
+        stderr: format!("FFmpeg не смог создать GIF: {stderr}"),
+        command: "ffmpeg".to_string(),
+      });
+    }
 
-  Ok(params.output_path)
+    Ok(params.output_path)
+  })
 }
 
 /// Генерировать превью звуковой волны
@@ -298,22 +399,24 @@ pub async fn generate_waveform_preview(
   color: String,
   state: State<'_, VideoCompilerState>,
 ) -> Result<String> {
-  let preview_service = state
-    .services
-    .get_preview_service()
-    .ok_or_else(|| VideoCompilerError::validation("PreviewService не найден"))?;
-
-  // Используем PreviewService для генерации waveform
-  let waveform_data = preview_service
-    .generate_waveform(std::path::Path::new(&audio_path), width, height, &color)
-    .await?;
-
-  // Сохраняем результат в файл
-  tokio::fs::write(&output_path, waveform_data)
-    .await
-    .map_err(|e| VideoCompilerError::IoError(format!("Не удалось сохранить waveform: {e}")))?;
-
-  Ok(output_path)
+  crate::instrumented_command!(generate_waveform_preview, {
+    let preview_service = state
+      .services
+      .get_preview_service()
+      .ok_or_else(|| VideoCompilerError::validation("PreviewService не найден"))?;
+
+    // Используем PreviewService для генерации waveform
+    let waveform_data = preview_service
+      .generate_waveform(std::path::Path::new(&audio_path), width, height, &color)
+      .await?;
+
+    // Сохраняем результат в файл
+    tokio::fs::write(&output_path, waveform_data)
+      .await
+      .map_err(|e| VideoCompilerError::IoError(format!("Не удалось сохранить waveform: {e}")))?;
+
+    Ok(output_path)
+  })
 }
 
 /// Получить информацию о превью из кэша
@@ -322,19 +425,21 @@ pub async fn get_cached_preview_info(
   preview_id: String,
   state: State<'_, VideoCompilerState>,
 ) -> Result<Option<serde_json::Value>> {
-  // Создаем ключ для поиска превью
-  let key =
-    crate::video_compiler::cache::PreviewKey::new(preview_id.clone(), 0.0, (1920, 1080), 85);
-  let mut cache_mut = state.cache_manager.write().await;
-  if let Some(preview_data) = cache_mut.get_preview(&key).await {
-    Ok(Some(serde_json::json!({
-      "id": preview_id,
-      "data_size": preview_data.image_data.len(),
-      "created_at": preview_data.timestamp,
-    })))
-  } else {
-    Ok(None)
-  }
+  crate::instrumented_command!(get_cached_preview_info, {
+    // Создаем ключ для поиска превью
+    let key =
+      crate::video_compiler::cache::PreviewKey::new(preview_id.clone(), 0.0, (1920, 1080), 85);
+    let mut cache_mut = state.cache_manager.write().await;
+    if let Some(preview_data) = cache_mut.get_preview(&key).await {
+      Ok(Some(serde_json::json!({
+        "id": preview_id,
+        "data_size": preview_data.image_data.len(),
+        "created_at": preview_data.timestamp,
+      })))
+    } else {
+      Ok(None)
+    }
+  })
 }
 
 /// Очистить кэш превью для проекта
@@ -343,9 +448,11 @@ pub async fn clear_project_previews(
   _project_id: String,
   state: State<'_, VideoCompilerState>,
 ) -> Result<()> {
-  let mut cache = state.cache_manager.write().await;
-  cache.clear_previews().await;
-  Ok(())
+  crate::instrumented_command!(clear_project_previews, {
+    let mut cache = state.cache_manager.write().await;
+    cache.clear_previews().await;
+    Ok(())
+  })
 }
 
 /// Генерировать превью с настраиваемыми параметрами
@@ -356,20 +463,22 @@ pub async fn generate_custom_preview(
   options: serde_json::Value,
   state: State<'_, VideoCompilerState>,
 ) -> Result<String> {
-  let preview_service = state
-    .services
-    .get_preview_service()
-    .ok_or_else(|| VideoCompilerError::validation("PreviewService не найден"))?;
+  crate::instrumented_command!(generate_custom_preview, {
+    let preview_service = state
+      .services
+      .get_preview_service()
+      .ok_or_else(|| VideoCompilerError::validation("PreviewService не найден"))?;
 
-  // Парсим настройки из JSON
-  let preview_options: PreviewOptions = serde_json::from_value(options)
-    .map_err(|e| VideoCompilerError::InvalidParameter(format!("Invalid preview options: {e}")))?;
+    // Парсим настройки из JSON
+    let preview_options: PreviewOptions = serde_json::from_value(options)
+      .map_err(|e| VideoCompilerError::InvalidParameter(format!("Invalid preview options: {e}")))?;
 
-  preview_service
-    .generate_frame(&project_schema, 0.0, &output_path, Some(preview_options))
-    .await?;
+    preview_service
+      .generate_frame(&project_schema, 0.0, &output_path, Some(preview_options))
+      .await?;
 
-  Ok(output_path)
+    Ok(output_path)
+  })
 }
 
 /// Генерировать пакет превью с настройками
@@ -380,75 +489,77 @@ pub async fn generate_preview_batch_with_settings(
   settings: serde_json::Value,
   state: State<'_, VideoCompilerState>,
 ) -> Result<Vec<String>> {
-  use crate::video_compiler::preview::{PreviewGenerator, PreviewRequest, PreviewSettings};
-  use base64::Engine;
-
-  let cache = state.cache_manager.clone();
-  let width = settings
-    .get("width")
-    .and_then(|v| v.as_u64())
-    .unwrap_or(1920) as u32;
-  let height = settings
-    .get("height")
-    .and_then(|v| v.as_u64())
-    .unwrap_or(1080) as u32;
-  let quality = settings
-    .get("quality")
-    .and_then(|v| v.as_u64())
-    .unwrap_or(80) as u8;
-  let _cache_enabled = settings
-    .get("cache_enabled")
-    .and_then(|v| v.as_bool())
-    .unwrap_or(true);
-
-  let preview_settings = PreviewSettings {
-    default_resolution: (width, height),
-    default_quality: quality,
-    format: crate::video_compiler::schema::PreviewFormat::Jpeg,
-    timeline_resolution: (width, height),
-    timeline_quality: quality,
-    supported_formats: vec!["mp4".to_string(), "avi".to_string(), "mov".to_string()],
-    timeout_seconds: 30,
-    hardware_acceleration: false,
-  };
+  crate::instrumented_command!(generate_preview_batch_with_settings, {
+    use crate::video_compiler::preview::{PreviewGenerator, PreviewRequest, PreviewSettings};
+    use base64::Engine;
 
-  let generator = PreviewGenerator::with_settings(cache, preview_settings);
-
-  // Создаем запросы превью
-  let requests: Vec<PreviewRequest> = timestamps
-    .into_iter()
-    .map(|timestamp| PreviewRequest {
-      video_path: video_path.clone(),
-      timestamp,
-      resolution: Some((width, height)),
-      quality: Some(quality),
-    })
-    .collect();
-
-  let results = generator.generate_preview_batch(requests).await?;
-
-  // Сохраняем результаты в файлы и возвращаем пути
-  let output_dir = state.settings.read().await.temp_directory.clone();
-  let mut paths = Vec::new();
-
-  for (i, result) in results.into_iter().enumerate() {
-    if let Some(image_data) = result.image_data {
-      let file_path = format!(
-        "{}/preview_{}_{}.jpg",
-        output_dir.display(),
-        video_path.replace('/', "_"),
-        i
-      );
+    let cache = state.cache_manager.clone();
+    let width = settings
+      .get("width")
+      .and_then(|v| v.as_u64())
+      .unwrap_or(1920) as u32;
+    let height = settings
+      .get("height")
+      .and_then(|v| v.as_u64())
+      .unwrap_or(1080) as u32;
+    let quality = settings
+      .get("quality")
+      .and_then(|v| v.as_u64())
+      .unwrap_or(80) as u8;
+    let _cache_enabled = settings
+      .get("cache_enabled")
+      .and_then(|v| v.as_bool())
+      .unwrap_or(true);
+
+    let preview_settings = PreviewSettings {
+      default_resolution: (width, height),
+      default_quality: quality,
+      format: crate::video_compiler::schema::PreviewFormat::Jpeg,
+      timeline_resolution: (width, height),
+      timeline_quality: quality,
+      supported_formats: vec!["mp4".to_string(), "avi".to_string(), "mov".to_string()],
+      timeout_seconds: 30,
+      hardware_acceleration: false,
+    };
+
+    let generator = PreviewGenerator::with_settings(cache, preview_settings);
 
-      // Декодируем base64 и сохраняем в файл
-      if let Ok(decoded) = base64::engine::general_purpose::STANDARD.decode(&image_data) {
-        std::fs::write(&file_path, decoded)?;
-        paths.push(file_path);
+    // Создаем запросы превью
+    let requests: Vec<PreviewRequest> = timestamps
+      .into_iter()
+      .map(|timestamp| PreviewRequest {
+        video_path: video_path.clone(),
+        timestamp,
+        resolution: Some((width, height)),
+        quality: Some(quality),
+      })
+      .collect();
+
+    let results = generator.generate_preview_batch(requests).await?;
+
+    // Сохраняем результаты в файлы и возвращаем пути
+    let output_dir = state.settings.read().await.temp_directory.clone();
+    let mut paths = Vec::new();
+
+    for (i, result) in results.into_iter().enumerate() {
+      if let Some(image_data) = result.image_data {
+        let file_path = format!(
+          "{}/preview_{}_{}.jpg",
+          output_dir.display(),
+          video_path.replace('/', "_"),
+          i
+        );
+
+        // Декодируем base64 и сохраняем в файл
+        if let Ok(decoded) = base64::engine::general_purpose::STANDARD.decode(&image_data) {
+          std::fs::write(&file_path, decoded)?;
+          paths.push(file_path);
+        }
       }
     }
-  }
 
-  Ok(paths)
+    Ok(paths)
+  })
 }
 
 /// Очистить кэш превью для конкретного файла
@@ -457,11 +568,13 @@ pub async fn clear_preview_cache_for_file(
   _file_path: String,
   state: State<'_, VideoCompilerState>,
 ) -> Result<()> {
-  // Очищаем кэш напрямую через cache_manager
-  let mut cache = state.cache_manager.write().await;
-  // Очищаем все превью (нет способа очистить для конкретного файла)
-  cache.clear_previews().await;
-  Ok(())
+  crate::instrumented_command!(clear_preview_cache_for_file, {
+    // Очищаем кэш напрямую через cache_manager
+    let mut cache = state.cache_manager.write().await;
+    // Очищаем все превью (нет способа очистить для конкретного файла)
+    cache.clear_previews().await;
+    Ok(())
+  })
 }
 
 /// Установить путь к FFmpeg для генератора превью
@@ -470,30 +583,32 @@ pub async fn set_preview_generator_ffmpeg_path(
   path: String,
   state: State<'_, VideoCompilerState>,
 ) -> Result<()> {
-  use crate::video_compiler::preview::PreviewGenerator;
-
-  // Проверяем что путь валидный
-  let output = std::process::Command::new(&path)
-    .arg("-version")
-    .output()
-    .map_err(|e| VideoCompilerError::InvalidParameter(format!("Invalid FFmpeg path: {e}")))?;
-
-  if !output.status.success() {
-    return Err(VideoCompilerError::InvalidParameter(
-      "Invalid FFmpeg executable".to_string(),
-    ));
-  }
+  crate::instrumented_command!(set_preview_generator_ffmpeg_path, {
+    use crate::video_compiler::preview::PreviewGenerator;
+
+    // Проверяем что путь валидный
+    let output = std::process::Command::new(&path)
+      .arg("-version")
+      .output()
+      .map_err(|e| VideoCompilerError::InvalidParameter(format!("Invalid FFmpeg path: {e}")))?;
+
+    if !output.status.success() {
+      return Err(VideoCompilerError::InvalidParameter(
+        "Invalid FFmpeg executable".to_string(),
+      ));
+    }
 
-  // Создаем новый генератор с обновленным путем
-  let cache = state.cache_manager.clone();
-  let mut generator = PreviewGenerator::new(cache);
-  generator.set_ffmpeg_path(&path);
+    // Создаем новый генератор с обновленным путем
+    let cache = state.cache_manager.clone();
+    let mut generator = PreviewGenerator::new(cache);
+    generator.set_ffmpeg_path(&path);
 
-  // Обновляем глобальный путь
-  let mut ffmpeg_path = state.ffmpeg_path.write().await;
-  *ffmpeg_path = path;
+    // Обновляем глобальный путь
+    let mut ffmpeg_path = state.ffmpeg_path.write().await;
+    *ffmpeg_path = path;
 
-  Ok(())
+    Ok(())
+  })
 }
 
 /// Очистить кэш превью для конкретного файла с использованием PreviewGenerator
@@ -502,15 +617,17 @@ pub async fn clear_preview_generator_cache_for_file(
   _file_path: String,
   state: State<'_, VideoCompilerState>,
 ) -> Result<()> {
-  use crate::video_compiler::preview::PreviewGenerator;
+  crate::instrumented_command!(clear_preview_generator_cache_for_file, {
+    use crate::video_compiler::preview::PreviewGenerator;
 
-  let cache = state.cache_manager.clone();
-  let generator = PreviewGenerator::new(cache);
+    let cache = state.cache_manager.clone();
+    let generator = PreviewGenerator::new(cache);
 
-  // Используем метод clear_cache_for_file
-  generator.clear_cache_for_file().await?;
+    // Используем метод clear_cache_for_file
+    generator.clear_cache_for_file().await?;
 
-  Ok(())
+    Ok(())
+  })
 }
 
 /// Генерировать видео превью (миниатюры для видео)
@@ -521,39 +638,41 @@ pub async fn generate_video_thumbnails_service(
   thumbnail_count: u32,
   state: State<'_, VideoCompilerState>,
 ) -> Result<Vec<String>> {
-  use crate::video_compiler::services::preview_service::{PreviewRequest, PreviewType};
-
-  let preview_service = state
-    .services
-    .get_preview_service()
-    .ok_or_else(|| VideoCompilerError::validation("PreviewService не найден"))?;
-
-  // Создаем запрос для генерации превью
-  let request = PreviewRequest {
-    preview_type: PreviewType::Thumbnail,
-    source_path: std::path::PathBuf::from("/tmp/video.mp4"),
-    timestamp: Some(0.0),
-    resolution: Some((320, 180)),
-    quality: Some(85),
-  };
+  crate::instrumented_command!(generate_video_thumbnails_service, {
+    use crate::video_compiler::services::preview_service::{PreviewRequest, PreviewType};
+
+    let preview_service = state
+      .services
+      .get_preview_service()
+      .ok_or_else(|| VideoCompilerError::validation("PreviewService не найден"))?;
+
+    // Создаем запрос для генерации превью
+    let request = PreviewRequest {
+      preview_type: PreviewType::Thumbnail,
+      source_path: std::path::PathBuf::from("/tmp/video.mp4"),
+      timestamp: Some(0.0),
+      resolution: Some((320, 180)),
+      quality: Some(85),
+    };
+
+    // Используем метод generate_video_thumbnails
+    let thumbnails = preview_service
+      .generate_video_thumbnails(
+        &request.source_path,
+        thumbnail_count as usize,
+        request.resolution,
+      )
+      .await?;
+
+    // Возвращаем пути к созданным миниатюрам
+    let thumbnail_paths: Vec<String> = thumbnails
+      .iter()
+      .enumerate()
+      .map(|(i, _)| format!("{output_dir}/thumbnail_{i:03}.jpg"))
+      .collect();
 
-  // Используем метод generate_video_thumbnails
-  let thumbnails = preview_service
-    .generate_video_thumbnails(
-      &request.source_path,
-      thumbnail_count as usize,
-      request.resolution,
-    )
-    .await?;
-
-  // Возвращаем пути к созданным миниатюрам
-  let thumbnail_paths: Vec<String> = thumbnails
-    .iter()
-    .enumerate()
-    .map(|(i, _)| format!("{output_dir}/thumbnail_{i:03}.jpg"))
-    .collect();
-
-  Ok(thumbnail_paths)
+    Ok(thumbnail_paths)
+  })
 }
 
 /// Генерировать раскадровку проекта
@@ -565,33 +684,35 @@ pub async fn generate_storyboard_service(
   rows: u32,
   state: State<'_, VideoCompilerState>,
 ) -> Result<String> {
-  use crate::video_compiler::services::preview_service::{PreviewRequest, PreviewType};
-
-  let preview_service = state
-    .services
-    .get_preview_service()
-    .ok_or_else(|| VideoCompilerError::validation("PreviewService не найден"))?;
-
-  // Создаем запрос для генерации раскадровки
-  let request = PreviewRequest {
-    preview_type: PreviewType::Storyboard,
-    source_path: std::path::PathBuf::from("/tmp/video.mp4"),
-    timestamp: Some(0.0),
-    resolution: Some((1920, 1080)),
-    quality: Some(95),
-  };
+  crate::instrumented_command!(generate_storyboard_service, {
+    use crate::video_compiler::services::preview_service::{PreviewRequest, PreviewType};
+
+    let preview_service = state
+      .services
+      .get_preview_service()
+      .ok_or_else(|| VideoCompilerError::validation("PreviewService не найден"))?;
+
+    // Создаем запрос для генерации раскадровки
+    let request = PreviewRequest {
+      preview_type: PreviewType::Storyboard,
+      source_path: std::path::PathBuf::from("/tmp/video.mp4"),
+      timestamp: Some(0.0),
+      resolution: Some((1920, 1080)),
+      quality: Some(95),
+    };
+
+    // Используем метод generate_storyboard
+    let _result = preview_service
+      .generate_storyboard(
+        &project_schema,
+        columns,
+        rows,
+        request.resolution.unwrap_or((320, 180)),
+      )
+      .await?;
 
-  // Используем метод generate_storyboard
-  let _result = preview_service
-    .generate_storyboard(
-      &project_schema,
-      columns,
-      rows,
-      request.resolution.unwrap_or((320, 180)),
-    )
-    .await?;
-
-  Ok(output_path)
+    Ok(output_path)
+  })
 }
 
 /// Пакетная генерация превью
@@ -600,74 +721,103 @@ pub async fn batch_generate_previews_service(
   requests: Vec<serde_json::Value>,
   state: State<'_, VideoCompilerState>,
 ) -> Result<Vec<String>> {
-  use crate::video_compiler::services::preview_service::{PreviewRequest, PreviewType};
-
-  let preview_service = state
-    .services
-    .get_preview_service()
-    .ok_or_else(|| VideoCompilerError::validation("PreviewService не найден"))?;
-
-  // Преобразуем JSON запросы в PreviewRequest
-  let mut preview_requests = Vec::new();
-  for req_json in requests {
-    let preview_type = match req_json
-      .get("type")
-      .and_then(|v| v.as_str())
-      .unwrap_or("Frame")
-    {
-      "Thumbnail" => PreviewType::Thumbnail,
-      "Storyboard" => PreviewType::Storyboard,
-      _ => PreviewType::Frame,
-    };
+  crate::instrumented_command!(batch_generate_previews_service, {
+    use crate::video_compiler::services::preview_service::{PreviewRequest, PreviewType};
 
-    // Создаем минимальный запрос
-    let request = PreviewRequest {
-      preview_type,
-      source_path: std::path::PathBuf::from(
-        req_json
-          .get("source_path")
-          .and_then(|v| v.as_str())
-          .unwrap_or("/tmp/video.mp4"),
-      ),
-      timestamp: req_json.get("timestamp").and_then(|v| v.as_f64()),
-      resolution: {
-        let width = req_json
-          .get("width")
-          .and_then(|v| v.as_u64())
-          .map(|v| v as u32);
-        let height = req_json
-          .get("height")
+    let preview_service = state
+      .services
+      .get_preview_service()
+      .ok_or_else(|| VideoCompilerError::validation("PreviewService не найден"))?;
+
+    // Преобразуем JSON запросы в PreviewRequest
+    let mut preview_requests = Vec::new();
+    for req_json in requests {
+      let preview_type = match req_json
+        .get("type")
+        .and_then(|v| v.as_str())
+        .unwrap_or("Frame")
+      {
+        "Thumbnail" => PreviewType::Thumbnail,
+        "Storyboard" => PreviewType::Storyboard,
+        _ => PreviewType::Frame,
+      };
+
+      // Создаем минимальный запрос
+      let request = PreviewRequest {
+        preview_type,
+        source_path: std::path::PathBuf::from(
+          req_json
+            .get("source_path")
+            .and_then(|v| v.as_str())
+            .unwrap_or("/tmp/video.mp4"),
+        ),
+        timestamp: req_json.get("timestamp").and_then(|v| v.as_f64()),
+        resolution: {
+          let width = req_json
+            .get("width")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as u32);
+          let height = req_json
+            .get("height")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as u32);
+          if let (Some(w), Some(h)) = (width, height) {
+            Some((w, h))
+          } else {
+            None
+          }
+        },
+        quality: req_json
+          .get("quality")
           .and_then(|v| v.as_u64())
-          .map(|v| v as u32);
-        if let (Some(w), Some(h)) = (width, height) {
-          Some((w, h))
-        } else {
-          None
-        }
-      },
-      quality: req_json
-        .get("quality")
-        .and_then(|v| v.as_u64())
-        .map(|v| v as u8),
-    };
-    preview_requests.push(request);
-  }
+          .map(|v| v as u8),
+      };
+      preview_requests.push(request);
+    }
 
-  // Используем метод batch_generate_previews
-  let results = preview_service
-    .batch_generate_previews(preview_requests)
-    .await?;
+    // Используем метод batch_generate_previews
+    let results = preview_service
+      .batch_generate_previews(preview_requests)
+      .await?;
 
-  // Возвращаем пути к созданным файлам
-  let output_paths: Vec<String> = results
-    .iter()
-    .enumerate()
-    .map(|(i, _)| format!("/tmp/batch_preview_{i:03}.jpg"))
-    .collect();
+    // Возвращаем пути к созданным файлам
+    let output_paths: Vec<String> = results
+      .iter()
+      .enumerate()
+      .map(|(i, _)| format!("/tmp/batch_preview_{i:03}.jpg"))
+      .collect();
 
-  Ok(output_paths)
+    Ok(output_paths)
+  })
 }
 
+crate::command_manifest!(
+  PREVIEW_MANIFEST,
+  "video_compiler::preview",
+  [
+    generate_frame_preview,
+    render_timeline_frame,
+    chroma_key_preview,
+    generate_video_thumbnails,
+    generate_project_preview,
+    generate_effect_preview,
+    generate_transition_preview,
+    generate_storyboard,
+    generate_animated_preview,
+    generate_waveform_preview,
+    get_cached_preview_info,
+    clear_project_previews,
+    generate_custom_preview,
+    generate_preview_batch_with_settings,
+    clear_preview_cache_for_file,
+    set_preview_generator_ffmpeg_path,
+    clear_preview_generator_cache_for_file,
+    generate_video_thumbnails_service,
+    generate_storyboard_service,
+    batch_generate_previews_service,
+  ]
+);
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -676,6 +826,11 @@ mod tests {
   use std::path::PathBuf;
   use tempfile::TempDir;
 
+  use crate::video_compiler::tests::mocks::{
+    create_mock_state_with_services, mock_services_builder, MockPreviewService,
+  };
+  use std::sync::Arc;
+
   fn create_test_project() -> ProjectSchema {
     let mut project = ProjectSchema::new("Test Project".to_string());
     project.timeline.duration = 10.0;
@@ -1128,4 +1283,56 @@ mod tests {
     assert_eq!(project.tracks.len(), 1);
     assert_eq!(project.tracks[0].clips.len(), 1);
   }
+
+  #[tokio::test]
+  async fn test_preview_commands_use_installed_preview_service() {
+    let preview = Arc::new(MockPreviewService::default());
+    let services = mock_services_builder()
+      .with_preview(preview.clone())
+      .build()
+      .await
+      .unwrap();
+    let state = create_mock_state_with_services(services);
+
+    // Тот же путь, что и в generate_frame_preview
+    let preview_service = state.services.get_preview_service().unwrap();
+    let frame = preview_service
+      .generate_frame_preview(&PathBuf::from("/tmp/test_video.mp4"), 2.5, None)
+      .await
+      .unwrap();
+    assert_eq!(frame, MockPreviewService::FRAME);
+
+    let thumbnails = preview_service
+      .generate_video_thumbnails(&PathBuf::from("/tmp/test_video.mp4"), 3, None)
+      .await
+      .unwrap();
+    assert_eq!(thumbnails.len(), 3);
+    assert_eq!(*preview.requested_timestamps.lock().unwrap(), vec![2.5]);
+  }
+
+  #[test]
+  fn test_chroma_key_preview_project() {
+    let temp_dir = TempDir::new().unwrap();
+    let video = temp_dir.path().join("green_screen.mp4");
+    std::fs::write(&video, b"video").unwrap();
+    let video = video.to_string_lossy().to_string();
+
+    let params = ChromaKeyParams {
+      similarity: 0.2,
+      background: Some(ChromaKeyBackground::Color("black".to_string())),
+      ..Default::default()
+    };
+    let project = chroma_key_preview_project(&video, 3.0, &params).unwrap();
+    let clip = &project.tracks[0].clips[0];
+    assert!(clip.end_time > 3.0);
+    assert_eq!(clip.effects, vec![project.effects[0].id.clone()]);
+    assert_eq!(ChromaKeyParams::from_effect(&project.effects[0]), params);
+
+    let clip_background = ChromaKeyParams {
+      background: Some(ChromaKeyBackground::Clip("other".to_string())),
+      ..Default::default()
+    };
+    assert!(chroma_key_preview_project(&video, 3.0, &clip_background).is_err());
+    assert!(chroma_key_preview_project("/nonexistent/video.mp4", 3.0, &params).is_err());
+  }
 }
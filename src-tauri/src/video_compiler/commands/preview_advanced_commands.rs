@@ -246,6 +246,19 @@ pub async fn generate_preview_with_options(
   })
 }
 
+crate::command_manifest!(
+  PREVIEW_ADVANCED_COMMANDS_MANIFEST,
+  "video_compiler::preview_advanced_commands",
+  [
+    create_preview_generator_with_ffmpeg,
+    set_preview_generator_ffmpeg_path_advanced,
+    generate_preview_batch_advanced,
+    generate_single_frame_preview,
+    get_preview_generator_info,
+    generate_preview_with_options,
+  ]
+);
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -131,6 +131,18 @@ pub async fn get_current_operation_details(
   }))
 }
 
+crate::command_manifest!(
+  PROGRESS_TRACKER_COMMANDS_MANIFEST,
+  "video_compiler::progress_tracker_commands",
+  [
+    get_render_progress_tracker,
+    get_progress_tracker_statistics,
+    reset_progress_tracker,
+    set_progress_callback_enabled,
+    get_current_operation_details,
+  ]
+);
+
 #[cfg(test)]
 mod tests {
   use super::*;
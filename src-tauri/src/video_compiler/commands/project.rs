@@ -8,7 +8,12 @@ use std::path::Path;
 use tauri::State;
 
 use crate::video_compiler::error::{Result, VideoCompilerError};
-use crate::video_compiler::schema::{Clip, ClipSource, ProjectSchema, Subtitle, Track};
+use crate::video_compiler::ffmpeg_builder::capabilities::{
+  transition_substitutions, FilterCapabilities,
+};
+use crate::video_compiler::schema::{
+  load_project_with_migration, Clip, ClipSource, MigratedProject, ProjectSchema, Subtitle, Track,
+};
 
 use super::state::VideoCompilerState;
 
@@ -16,23 +21,33 @@ use super::state::VideoCompilerState;
 #[tauri::command]
 pub async fn validate_project_schema(
   project_schema: ProjectSchema,
-  _state: State<'_, VideoCompilerState>,
+  state: State<'_, VideoCompilerState>,
 ) -> Result<serde_json::Value> {
   // Базовая валидация схемы
-  match project_schema.validate() {
-    Ok(_) => Ok(serde_json::json!({
-      "is_valid": true,
-      "errors": [],
-      "warnings": [],
-      "info": ["Project schema is valid"],
-    })),
-    Err(e) => Ok(serde_json::json!({
+  if let Err(e) = project_schema.validate() {
+    return Ok(serde_json::json!({
       "is_valid": false,
       "errors": [e.to_string()],
       "warnings": [],
       "info": [],
-    })),
+    }));
   }
+
+  // Переходы, которые будут заменены из-за отсутствующих фильтров FFmpeg
+  let capabilities = match state.services.get_ffmpeg_service() {
+    Some(ffmpeg) => ffmpeg.filter_capabilities().await.unwrap_or_default(),
+    None => FilterCapabilities::default(),
+  };
+  let substitutions = transition_substitutions(&project_schema, &capabilities);
+  let warnings: Vec<String> = substitutions.iter().map(|s| s.message()).collect();
+
+  Ok(serde_json::json!({
+    "is_valid": true,
+    "errors": [],
+    "warnings": warnings,
+    "transition_substitutions": substitutions,
+    "info": ["Project schema is valid"],
+  }))
 }
 
 /// Оптимизировать схему проекта
@@ -643,6 +658,38 @@ pub async fn touch_project_schema(mut project: ProjectSchema) -> Result<ProjectS
   Ok(project)
 }
 
+/// Обновить JSON проекта до текущей версии схемы.
+///
+/// Возвращает проект вместе со списком примененных миграций и
+/// предупреждениями о потерянных данных.
+#[tauri::command]
+pub async fn migrate_project_schema(project_json: String) -> Result<MigratedProject> {
+  load_project_with_migration(&project_json)
+}
+
+crate::command_manifest!(
+  PROJECT_MANIFEST,
+  "video_compiler::project",
+  [
+    validate_project_schema,
+    optimize_project_schema,
+    analyze_project,
+    get_project_media_files,
+    check_project_media_availability,
+    update_project_media_paths,
+    add_subtitles_to_project,
+    extract_project_subtitles,
+    backup_project,
+    merge_projects,
+    split_project,
+    track_operations,
+    get_clip_info,
+    validate_subtitle,
+    touch_project_schema,
+    migrate_project_schema,
+  ]
+);
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -669,6 +716,7 @@ mod tests {
         resolution: (1920, 1080),
         sample_rate: 48000,
         aspect_ratio: AspectRatio::Ratio16x9,
+        markers: Vec::new(),
       },
       tracks: vec![
         Track::new(TrackType::Video, "Video Track".to_string()),
@@ -679,6 +727,7 @@ mod tests {
       transitions: vec![],
       templates: vec![],
       style_templates: vec![],
+      sequences: Default::default(),
       subtitles: vec![
         Subtitle::new("First subtitle".to_string(), 0.0, 5.0),
         Subtitle::new("Second subtitle".to_string(), 5.0, 10.0),
@@ -1076,6 +1125,15 @@ mod tests {
     assert_eq!(validation["duration"], 5.0);
   }
 
+  #[tokio::test]
+  async fn test_migrate_project_schema_refuses_newer_version() {
+    let result = migrate_project_schema(r#"{"version": "9.0.0"}"#.to_string()).await;
+    assert!(matches!(
+      result,
+      Err(VideoCompilerError::UnsupportedProjectVersion { .. })
+    ));
+  }
+
   #[tokio::test]
   async fn test_touch_project_schema() {
     let project = create_test_project();
@@ -0,0 +1,78 @@
+//! Project Assets Commands - проверка и замена внешних ресурсов проекта,
+//! отсутствующих на текущей машине (шрифты, LUT, изображения, аудио)
+
+use crate::video_compiler::error::{Result, VideoCompilerError};
+use crate::video_compiler::schema::ProjectSchema;
+use crate::video_compiler::services::project_assets::{
+  self, AssetKind, AssetRef, ProjectAssetReport,
+};
+use crate::video_compiler::services::project_template::system_font_dirs;
+use crate::video_compiler::services::schema_journal::SchemaChange;
+use crate::video_compiler::VideoCompilerState;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::State;
+
+/// Результат замены ресурса
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssetSubstitution {
+  /// Проект с обновленными ссылками
+  pub project: ProjectSchema,
+  /// Количество замененных ссылок
+  pub replaced: usize,
+  /// Запись журнала для отмены (`None`, если проект не изменился)
+  pub change: Option<SchemaChange>,
+}
+
+/// Найти шрифты, изображения шаблонов, файлы эффектов, водяной знак и
+/// внешнее аудио проекта, которых нет на этой машине
+#[tauri::command]
+pub async fn check_project_assets(project: ProjectSchema) -> Result<ProjectAssetReport> {
+  tokio::task::spawn_blocking(move || {
+    project_assets::check_project_assets(&project, &system_font_dirs())
+  })
+  .await
+  .map_err(|e| VideoCompilerError::InternalError(format!("Проверка ресурсов прервана: {e}")))
+}
+
+/// Заменить все ссылки на ресурс одним действием.
+///
+/// Для шрифта `replacement_path` - имя семейства, для остальных ресурсов -
+/// путь к существующему файлу. Изменение записывается в журнал проекта.
+#[tauri::command]
+pub async fn substitute_project_asset(
+  state: State<'_, VideoCompilerState>,
+  project_id: String,
+  project: ProjectSchema,
+  asset_ref: AssetRef,
+  replacement_path: String,
+) -> Result<AssetSubstitution> {
+  if asset_ref.kind != AssetKind::Font && !Path::new(&replacement_path).is_file() {
+    return Err(VideoCompilerError::InvalidParameter(format!(
+      "Файл замены не найден: {replacement_path}"
+    )));
+  }
+
+  let mut updated = project.clone();
+  let replaced =
+    project_assets::substitute_project_asset(&mut updated, &asset_ref, &replacement_path);
+
+  let service = state.services.get_project_service().ok_or_else(|| {
+    VideoCompilerError::InternalError("Project service not available".to_string())
+  })?;
+  let change = service
+    .record_schema_change(&project_id, "Substitute asset", &project, &updated)
+    .await?;
+
+  Ok(AssetSubstitution {
+    project: updated,
+    replaced,
+    change,
+  })
+}
+
+crate::command_manifest!(
+  PROJECT_ASSETS_COMMANDS_MANIFEST,
+  "video_compiler::project_assets_commands",
+  [check_project_assets, substitute_project_asset,]
+);
@@ -0,0 +1,110 @@
+//! Project Package Commands - упаковка проекта с медиафайлами в архив и распаковка
+
+use crate::video_compiler::core::temp_storage;
+use crate::video_compiler::error::{Result, VideoCompilerError};
+use crate::video_compiler::schema::ProjectSchema;
+use crate::video_compiler::services::project_package::{
+  PackageContext, PackageOptions, PackageProgress, PackageProgressSink, PackageSummary,
+  UnpackedProject,
+};
+use crate::video_compiler::services::ProjectService;
+use crate::video_compiler::{VideoCompilerEvent, VideoCompilerState};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, State};
+
+/// Получить сервис проектов
+fn project_service(state: &VideoCompilerState) -> Result<Arc<dyn ProjectService>> {
+  state
+    .services
+    .get_project_service()
+    .ok_or_else(|| VideoCompilerError::InternalError("Project service not available".to_string()))
+}
+
+/// Получатель прогресса, отправляющий событие `ProjectPackageProgress`
+fn progress_emitter(app: &AppHandle, package_id: &str) -> PackageProgressSink {
+  let emitter = app.clone();
+  let package_id = package_id.to_string();
+  Arc::new(move |progress: PackageProgress| {
+    if let Err(e) = emitter.emit(
+      "video-compiler",
+      &VideoCompilerEvent::ProjectPackageProgress {
+        package_id: package_id.clone(),
+        progress,
+      },
+    ) {
+      log::warn!("Failed to emit package progress: {e}");
+    }
+  })
+}
+
+/// Упаковать проект со всеми используемыми файлами в zip-архив.
+///
+/// Прогресс сообщается событием `ProjectPackageProgress`, отмена - командой
+/// `cancel_project_package` с тем же `package_id`.
+#[tauri::command]
+pub async fn package_project(
+  app: AppHandle,
+  state: State<'_, VideoCompilerState>,
+  package_id: String,
+  project: ProjectSchema,
+  output_zip: String,
+  options: Option<PackageOptions>,
+) -> Result<PackageSummary> {
+  let context = PackageContext {
+    ffmpeg_path: state.ffmpeg_path.read().await.clone(),
+    temp_root: temp_storage::temp_root(),
+    proxy_dir: crate::app_dirs::AppDirectories::get_or_create()
+      .ok()
+      .map(|dirs| dirs.media_proxy_dir),
+  };
+
+  project_service(&state)?
+    .package_project(
+      &package_id,
+      &project,
+      &PathBuf::from(output_zip),
+      options.unwrap_or_default(),
+      context,
+      Some(progress_emitter(&app, &package_id)),
+    )
+    .await
+}
+
+/// Распаковать архив проекта в директорию и вернуть схему с абсолютными путями.
+///
+/// Файлы с несовпадающим хешем перечисляются в `issues` результата.
+#[tauri::command]
+pub async fn unpack_project(
+  app: AppHandle,
+  state: State<'_, VideoCompilerState>,
+  package_id: String,
+  zip_path: String,
+  destination: String,
+) -> Result<UnpackedProject> {
+  project_service(&state)?
+    .unpack_project(
+      &package_id,
+      &PathBuf::from(zip_path),
+      &PathBuf::from(destination),
+      Some(progress_emitter(&app, &package_id)),
+    )
+    .await
+}
+
+/// Отменить упаковку или распаковку архива проекта
+#[tauri::command]
+pub async fn cancel_project_package(
+  state: State<'_, VideoCompilerState>,
+  package_id: String,
+) -> Result<bool> {
+  project_service(&state)?
+    .cancel_package_operation(&package_id)
+    .await
+}
+
+crate::command_manifest!(
+  PROJECT_PACKAGE_COMMANDS_MANIFEST,
+  "video_compiler::project_package_commands",
+  [package_project, unpack_project, cancel_project_package,]
+);
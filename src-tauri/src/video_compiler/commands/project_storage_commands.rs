@@ -0,0 +1,54 @@
+//! Project Storage Commands - сохранение и загрузка файлов проекта через
+//! ProjectService
+
+use crate::video_compiler::error::{Result, VideoCompilerError};
+use crate::video_compiler::schema::ProjectSchema;
+use crate::video_compiler::services::project_storage::ProjectLoadResult;
+use crate::video_compiler::services::ProjectService;
+use crate::video_compiler::VideoCompilerState;
+use std::path::Path;
+use std::sync::Arc;
+use tauri::State;
+
+/// Получить сервис проектов
+fn project_service(state: &VideoCompilerState) -> Result<Arc<dyn ProjectService>> {
+  state
+    .services
+    .get_project_service()
+    .ok_or_else(|| VideoCompilerError::InternalError("Project service not available".to_string()))
+}
+
+/// Сохранить проект в файл.
+///
+/// Проект проверяется перед записью, предыдущая версия файла сохраняется
+/// в резервную копию, а новое содержимое заменяет файл атомарно.
+#[tauri::command]
+pub async fn save_project_file(
+  state: State<'_, VideoCompilerState>,
+  project: ProjectSchema,
+  path: String,
+) -> Result<()> {
+  project_service(&state)?
+    .save_project(&project, Path::new(&path))
+    .await
+}
+
+/// Загрузить проект из файла.
+///
+/// Если файл поврежден, результат содержит самую свежую целую резервную
+/// копию (`loaded_from_backup`) или описание повреждения (`corrupt`).
+#[tauri::command]
+pub async fn load_project_file(
+  state: State<'_, VideoCompilerState>,
+  path: String,
+) -> Result<ProjectLoadResult> {
+  project_service(&state)?
+    .load_project(Path::new(&path))
+    .await
+}
+
+crate::command_manifest!(
+  PROJECT_STORAGE_COMMANDS_MANIFEST,
+  "video_compiler::project_storage_commands",
+  [save_project_file, load_project_file,]
+);
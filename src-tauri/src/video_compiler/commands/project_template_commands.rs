@@ -0,0 +1,130 @@
+//! Project Template Commands - команды работы с шаблонами проектов
+
+use crate::video_compiler::error::{Result, VideoCompilerError};
+use crate::video_compiler::schema::ProjectSchema;
+use crate::video_compiler::services::project_template::{
+  ProjectTemplateInfo, TemplateInstance, TemplateMediaBinding,
+};
+use crate::video_compiler::services::ProjectService;
+use crate::video_compiler::VideoCompilerState;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use tauri::State;
+
+/// Получить сервис проектов
+fn project_service(state: &VideoCompilerState) -> Result<Arc<dyn ProjectService>> {
+  state
+    .services
+    .get_project_service()
+    .ok_or_else(|| VideoCompilerError::InternalError("Project service not available".to_string()))
+}
+
+/// Сохранить проект как шаблон
+#[tauri::command]
+pub async fn save_project_as_template(
+  state: State<'_, VideoCompilerState>,
+  project: ProjectSchema,
+  name: String,
+  include_media: bool,
+) -> Result<ProjectTemplateInfo> {
+  project_service(&state)?
+    .save_project_as_template(&project, name, include_media)
+    .await
+}
+
+/// Получить список шаблонов проектов
+#[tauri::command]
+pub async fn list_project_templates(
+  state: State<'_, VideoCompilerState>,
+) -> Result<Vec<ProjectTemplateInfo>> {
+  project_service(&state)?.list_project_templates().await
+}
+
+/// Создать проект из шаблона.
+///
+/// `media_bindings` сопоставляет роль слота ("intro", "main", "outro", ...)
+/// с путем к новому файлу. Длительность файлов определяется через FFmpeg;
+/// если файл не удалось прочитать, клипы слота сохраняют исходную длительность.
+#[tauri::command]
+pub async fn create_project_from_template(
+  state: State<'_, VideoCompilerState>,
+  template_id: String,
+  name: String,
+  media_bindings: HashMap<String, String>,
+) -> Result<TemplateInstance> {
+  let ffmpeg_service = state.services.get_ffmpeg_service();
+  let mut bindings = Vec::with_capacity(media_bindings.len());
+  let mut probe_warnings = Vec::new();
+
+  for (role, path) in media_bindings {
+    let duration = match &ffmpeg_service {
+      Some(service) => match service.get_file_info(Path::new(&path)).await {
+        Ok(info) => Some(info.duration),
+        Err(e) => {
+          probe_warnings.push(format!("Не удалось определить длительность {path}: {e}"));
+          None
+        }
+      },
+      None => None,
+    };
+    bindings.push(TemplateMediaBinding {
+      role,
+      path,
+      duration,
+    });
+  }
+
+  let mut instance = project_service(&state)?
+    .create_project_from_template(&template_id, name, bindings)
+    .await?;
+  instance.warnings.extend(probe_warnings);
+  Ok(instance)
+}
+
+/// Переименовать шаблон проекта
+#[tauri::command]
+pub async fn rename_project_template(
+  state: State<'_, VideoCompilerState>,
+  template_id: String,
+  name: String,
+) -> Result<ProjectTemplateInfo> {
+  project_service(&state)?
+    .rename_project_template(&template_id, name)
+    .await
+}
+
+/// Удалить шаблон проекта
+#[tauri::command]
+pub async fn delete_project_template(
+  state: State<'_, VideoCompilerState>,
+  template_id: String,
+) -> Result<()> {
+  project_service(&state)?
+    .delete_project_template(&template_id)
+    .await
+}
+
+/// Импортировать файл шаблона проекта
+#[tauri::command]
+pub async fn import_project_template(
+  state: State<'_, VideoCompilerState>,
+  path: String,
+) -> Result<ProjectTemplateInfo> {
+  project_service(&state)?
+    .import_project_template(Path::new(&path))
+    .await
+}
+
+crate::command_manifest!(
+  PROJECT_TEMPLATE_COMMANDS_MANIFEST,
+  "video_compiler::project_template_commands",
+  [
+    save_project_as_template,
+    list_project_templates,
+    create_project_from_template,
+    rename_project_template,
+    delete_project_template,
+    import_project_template,
+  ]
+);
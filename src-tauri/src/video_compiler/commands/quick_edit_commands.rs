@@ -0,0 +1,286 @@
+//! Quick Edit Commands - автоматический монтаж папки с материалами
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::{command, AppHandle, Emitter, Manager, Runtime, State};
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use crate::media::processor::MediaProcessor;
+use crate::media::scan_jobs::MediaScanJob;
+use crate::montage_planner::commands::{enhanced_detections, to_montage_detections, MontageState};
+use crate::montage_planner::services::quality_analyzer::DEFAULT_QUALITY_SEGMENT_DURATION;
+use crate::montage_planner::services::{Beat, BeatGrid};
+use crate::montage_planner::types::{
+  AnalysisOptions, DetectedMoment, MontageConfig, MontagePlan, MontageStyle,
+};
+use crate::video_compiler::error::{Result, VideoCompilerError};
+use crate::video_compiler::services::quick_edit::{
+  QuickEdit, QuickEditBackend, QuickEditProgressSink, QuickEditRequest, QuickEditResult,
+};
+use crate::video_compiler::{VideoCompilerEvent, VideoCompilerState};
+
+/// Расширения видеофайлов, попадающих в Quick Edit
+const QUICK_EDIT_VIDEO_EXTENSIONS: &[&str] = &["mp4", "avi", "mkv", "mov", "webm"];
+
+/// Дополнительные параметры Quick Edit
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QuickEditOptions {
+  /// ID задачи для отмены; по умолчанию генерируется
+  pub workflow_id: Option<String>,
+  /// Пресет экспорта ("youtube", "instagram", "twitter")
+  pub export_preset: Option<String>,
+  /// Рендерить сразу (по умолчанию да)
+  pub render: Option<bool>,
+  /// Папка для плана и проекта; по умолчанию папка результата
+  pub artifacts_dir: Option<PathBuf>,
+}
+
+/// Тяжелые этапы Quick Edit на сервисах приложения
+struct AppQuickEditBackend<R: Runtime> {
+  app: AppHandle<R>,
+  thumbnail_dir: PathBuf,
+}
+
+impl<R: Runtime> AppQuickEditBackend<R> {
+  fn montage_state(&self) -> State<'_, MontageState> {
+    self.app.state::<MontageState>()
+  }
+}
+
+#[async_trait]
+impl<R: Runtime> QuickEditBackend for AppQuickEditBackend<R> {
+  async fn scan(&self, folder: &Path, cancel: &CancellationToken) -> Result<Vec<PathBuf>> {
+    let processor = MediaProcessor::new(self.app.clone(), self.thumbnail_dir.clone());
+    let job = Arc::new(MediaScanJob::new(folder));
+
+    // Отмена Quick Edit останавливает и сканирование
+    let watcher = {
+      let job = job.clone();
+      let cancel = cancel.clone();
+      tokio::spawn(async move {
+        cancel.cancelled().await;
+        job.cancel();
+      })
+    };
+    let outcome = processor.run_scan(&job, None).await;
+    watcher.abort();
+    outcome.map_err(VideoCompilerError::IoError)?;
+
+    let result = job.snapshot().result;
+    let mut files: Vec<PathBuf> = result
+      .new_files
+      .into_iter()
+      .filter(|file| file.is_video)
+      .map(|file| PathBuf::from(file.path))
+      .chain(
+        result
+          .known_files
+          .into_iter()
+          .map(|file| PathBuf::from(file.path))
+          .filter(|path| is_video_path(path)),
+      )
+      .collect();
+    files.sort();
+    files.dedup();
+    Ok(files)
+  }
+
+  async fn analyze(&self, file: &Path) -> Result<Vec<DetectedMoment>> {
+    let state = self.montage_state();
+    let options = AnalysisOptions {
+      enable_object_detection: true,
+      enable_face_detection: true,
+      enable_emotion_analysis: false,
+      enable_composition_analysis: true,
+      enable_audio_analysis: false,
+      frame_sample_rate: 1.0,
+      quality_threshold: 0.0,
+      max_moments: None,
+      frame_sampling_rate: 1.0,
+    };
+    let detections = enhanced_detections(&state, file, &options)
+      .await
+      .map_err(|reason| VideoCompilerError::MediaFileError {
+        path: file.to_string_lossy().to_string(),
+        reason,
+      })?;
+    let moments = state
+      .moment_detector
+      .read()
+      .await
+      .detect_moments(&to_montage_detections(&detections))
+      .map_err(|e| VideoCompilerError::MediaFileError {
+        path: file.to_string_lossy().to_string(),
+        reason: e.to_string(),
+      })?;
+
+    // Оценки качества уточняют моменты, но без них план тоже строится
+    let scores = state
+      .quality_analyzer
+      .read()
+      .await
+      .analyze_segments(file, DEFAULT_QUALITY_SEGMENT_DURATION)
+      .await;
+    match scores {
+      Ok(scores) => Ok(
+        state
+          .plan_generator
+          .read()
+          .await
+          .apply_quality_scores(&moments, &scores),
+      ),
+      Err(e) => {
+        log::warn!("Quality analysis failed for {}: {e}", file.display());
+        Ok(moments)
+      }
+    }
+  }
+
+  async fn detect_beats(&self, music: &Path) -> Result<Vec<Beat>> {
+    self
+      .montage_state()
+      .beat_detector
+      .write()
+      .await
+      .detect_beats(music, None)
+      .await
+      .map_err(|e| VideoCompilerError::MediaFileError {
+        path: music.to_string_lossy().to_string(),
+        reason: e.to_string(),
+      })
+  }
+
+  async fn plan(
+    &self,
+    moments: &[DetectedMoment],
+    config: &MontageConfig,
+    source_files: &[String],
+    beat_grid: Option<&BeatGrid>,
+  ) -> Result<MontagePlan> {
+    self
+      .montage_state()
+      .plan_generator
+      .write()
+      .await
+      .generate_plan_with_audio(moments, config, source_files, beat_grid, None)
+      .map_err(|e| VideoCompilerError::InternalError(format!("Montage planning failed: {e}")))
+  }
+}
+
+fn is_video_path(path: &Path) -> bool {
+  path
+    .extension()
+    .and_then(|ext| ext.to_str())
+    .is_some_and(|ext| QUICK_EDIT_VIDEO_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// Выполняющиеся Quick Edit задачи
+static QUICK_EDITS: Lazy<Mutex<HashMap<String, CancellationToken>>> =
+  Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn quick_edits() -> std::sync::MutexGuard<'static, HashMap<String, CancellationToken>> {
+  QUICK_EDITS
+    .lock()
+    .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// Автоматический монтаж папки: сканирование, план монтажа и рендер.
+///
+/// Прогресс этапов (сканирование 10%, анализ 40%, план 50%, рендер 100%)
+/// отправляется событием `QuickEditProgress`, итог возвращается и
+/// отправляется событием `QuickEditCompleted`. План и проект сохраняются
+/// рядом с результатом. Отмена - командой `cancel_quick_edit_workflow`.
+#[command]
+pub async fn run_quick_edit_workflow<R: Runtime>(
+  app: AppHandle<R>,
+  state: State<'_, VideoCompilerState>,
+  input_folder: String,
+  music_path: Option<String>,
+  target_duration: f64,
+  style: MontageStyle,
+  output_path: String,
+  options: Option<QuickEditOptions>,
+) -> std::result::Result<QuickEditResult, String> {
+  let options = options.unwrap_or_default();
+  let render_service = state
+    .services
+    .get_render_service()
+    .ok_or_else(|| "RenderService не найден".to_string())?;
+  let app_dirs = crate::app_dirs::get_app_directories().await?;
+
+  let request = QuickEditRequest {
+    input_folder: PathBuf::from(input_folder),
+    music_path: music_path.map(PathBuf::from),
+    target_duration,
+    style,
+    output_path: PathBuf::from(output_path),
+    export_preset: options.export_preset,
+    render: options.render.unwrap_or(true),
+    artifacts_dir: options.artifacts_dir,
+  };
+  let backend = Arc::new(AppQuickEditBackend {
+    app: app.clone(),
+    thumbnail_dir: app_dirs.caches_dir.join("thumbnails"),
+  });
+
+  let workflow_id = options
+    .workflow_id
+    .unwrap_or_else(|| Uuid::new_v4().to_string());
+  let quick_edit = QuickEdit::new(workflow_id.clone(), backend, render_service, request);
+  {
+    let mut running = quick_edits();
+    if running.contains_key(&workflow_id) {
+      return Err(format!("Quick edit {workflow_id} уже выполняется"));
+    }
+    running.insert(workflow_id.clone(), quick_edit.cancel_token());
+  }
+
+  let emitter = app.clone();
+  let sink: QuickEditProgressSink = Arc::new(move |progress| {
+    if let Err(e) = emitter.emit(
+      "video-compiler",
+      &VideoCompilerEvent::QuickEditProgress { progress },
+    ) {
+      log::warn!("Failed to emit quick edit progress: {e}");
+    }
+  });
+  let result = quick_edit.run(Some(sink)).await;
+  quick_edits().remove(&workflow_id);
+  let result = result.map_err(|e| e.to_string())?;
+
+  if let Err(e) = app.emit(
+    "video-compiler",
+    &VideoCompilerEvent::QuickEditCompleted {
+      result: Box::new(result.clone()),
+    },
+  ) {
+    log::warn!("Failed to emit quick edit result: {e}");
+  }
+  Ok(result)
+}
+
+/// Отменить Quick Edit; текущий этап прерывается, запущенный рендер
+/// отменяется. Возвращает `false`, если задача не найдена.
+#[command]
+pub async fn cancel_quick_edit_workflow(workflow_id: String) -> std::result::Result<bool, String> {
+  match quick_edits().get(&workflow_id) {
+    Some(cancel) => {
+      cancel.cancel();
+      log::info!("Отменен quick edit {workflow_id}");
+      Ok(true)
+    }
+    None => Ok(false),
+  }
+}
+
+crate::command_manifest!(
+  QUICK_EDIT_COMMANDS_MANIFEST,
+  "video_compiler::quick_edit_commands",
+  [run_quick_edit_workflow, cancel_quick_edit_workflow,]
+);
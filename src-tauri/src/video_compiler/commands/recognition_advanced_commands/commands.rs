@@ -288,7 +288,7 @@ pub async fn get_yolo_model_info_extended(
   })
 }
 
-/// Получить информацию о сессии модели (прямое использование get_session)
+/// Получить информацию о сессии модели (прямое использование get_session_key)
 #[tauri::command]
 pub async fn get_model_session_info(model_type: String) -> Result<serde_json::Value> {
   if model_type.is_empty() {
@@ -319,8 +319,8 @@ pub async fn get_model_session_info(model_type: String) -> Result<serde_json::Va
   match manager.load_model().await {
     Ok(_) => {
       // Получаем сессию
-      match manager.get_session() {
-        Ok(_session) => Ok(serde_json::json!({
+      match manager.get_session_key() {
+        Ok(_key) => Ok(serde_json::json!({
           "success": true,
           "model_type": model_type,
           "session_available": true,
@@ -444,3 +444,23 @@ pub async fn check_model_is_loaded(model_type: String) -> Result<serde_json::Val
       .map(|p| p.to_string_lossy().to_string()),
   }))
 }
+
+crate::command_manifest!(
+  RECOGNITION_ADVANCED_COMMANDS_MANIFEST,
+  "video_compiler::recognition_advanced_commands",
+  [
+    get_frame_processor_class_names,
+    check_is_face_model,
+    check_is_segmentation_model,
+    get_model_manager_status,
+    get_recognition_results_by_time_range,
+    get_recognition_results_by_class,
+    format_recognition_results_for_timeline,
+    check_yolo_model_is_face_model,
+    check_yolo_model_is_segmentation_model,
+    get_yolo_model_info_extended,
+    get_model_session_info,
+    get_loaded_model_type,
+    check_model_is_loaded,
+  ]
+);
@@ -0,0 +1,298 @@
+//! Бизнес-логика рефрейминга: слежение за объектом съемки и путь окна кадрирования
+
+use super::types::*;
+use crate::video_compiler::schema::{
+  crop_rect_at, AspectRatio, Clip, CropKeyframe, CropRect, ProjectSchema, Resolution,
+  TransformSettings,
+};
+
+/// Минимальное пересечение рамок, при котором детекции относятся к одному объекту
+const TRACK_IOU_THRESHOLD: f32 = 0.3;
+
+/// Сколько кадров подряд объект может пропадать без разрыва трека
+const TRACK_MAX_GAP: usize = 2;
+
+/// Доли клипа, в которых строятся кадры превью
+const PREVIEW_POSITIONS: [f64; 3] = [1.0 / 6.0, 0.5, 5.0 / 6.0];
+
+/// Размер окна кадрирования в долях исходного кадра для целевого соотношения.
+///
+/// Окно занимает всю высоту (или ширину) кадра и обрезает лишнее по
+/// другой стороне.
+pub fn reframe_window_size(
+  source_width: u32,
+  source_height: u32,
+  target_aspect: f32,
+) -> (f32, f32) {
+  let source_aspect = source_width.max(1) as f32 / source_height.max(1) as f32;
+  if target_aspect < source_aspect {
+    (target_aspect / source_aspect, 1.0)
+  } else {
+    (1.0, source_aspect / target_aspect)
+  }
+}
+
+/// Связать рамки объектов между кадрами в треки по пересечению рамок
+pub fn track_subjects(observations: &[SubjectObservation]) -> Vec<SubjectTrack> {
+  let mut tracks: Vec<SubjectTrack> = Vec::new();
+
+  for (index, observation) in observations.iter().enumerate() {
+    let mut subjects = observation.subjects.clone();
+    subjects.sort_by(|a, b| b.area().total_cmp(&a.area()));
+    let mut matched = vec![false; tracks.len()];
+
+    for subject in subjects {
+      let best = tracks
+        .iter()
+        .enumerate()
+        .filter(|(track_index, _)| !matched[*track_index])
+        .filter_map(|(track_index, track)| {
+          let (last_index, last_box) = track.boxes.last()?;
+          (index - last_index <= TRACK_MAX_GAP + 1).then(|| (track_index, last_box.iou(&subject)))
+        })
+        .filter(|(_, iou)| *iou >= TRACK_IOU_THRESHOLD)
+        .max_by(|a, b| a.1.total_cmp(&b.1));
+
+      match best {
+        Some((track_index, _)) => {
+          tracks[track_index].boxes.push((index, subject));
+          matched[track_index] = true;
+        }
+        None => tracks.push(SubjectTrack {
+          boxes: vec![(index, subject)],
+        }),
+      }
+    }
+  }
+
+  tracks
+}
+
+/// Основной объект съемки: самый крупный и дольше всех присутствующий в кадре
+pub fn select_primary_track(tracks: &[SubjectTrack]) -> Option<&SubjectTrack> {
+  tracks.iter().max_by(|a, b| a.score().total_cmp(&b.score()))
+}
+
+/// Центр объекта съемки на каждом наблюдении.
+///
+/// На кадрах, где объект пропал, удерживается последнее известное
+/// положение. Без объекта съемки используется центр кадра.
+pub fn subject_centers(observation_count: usize, track: Option<&SubjectTrack>) -> Vec<(f32, f32)> {
+  let known: Vec<Option<(f32, f32)>> = (0..observation_count)
+    .map(|index| {
+      track
+        .and_then(|track| track.box_at(index))
+        .map(SubjectBox::center)
+    })
+    .collect();
+
+  let Some(first) = known.iter().flatten().next().copied() else {
+    return vec![(0.5, 0.5); observation_count];
+  };
+
+  let mut current = first;
+  known
+    .into_iter()
+    .map(|center| {
+      if let Some(center) = center {
+        current = center;
+      }
+      current
+    })
+    .collect()
+}
+
+/// Сгладить движение центра окна: экспоненциальное сглаживание и
+/// ограничение скорости панорамирования
+pub fn smooth_subject_centers(
+  times: &[f64],
+  centers: &[(f32, f32)],
+  options: &ReframeOptions,
+) -> Vec<(f32, f32)> {
+  let alpha = options.smoothing.clamp(0.0, 1.0);
+  let mut smoothed = Vec::with_capacity(centers.len());
+
+  for (index, &(x, y)) in centers.iter().enumerate() {
+    let Some(&(prev_x, prev_y)) = smoothed.last() else {
+      smoothed.push((x, y));
+      continue;
+    };
+    let elapsed = (times[index] - times[index - 1]).max(0.0) as f32;
+    let max_step = options.max_pan_speed.max(0.0) * elapsed;
+    let step = |from: f32, to: f32| from + (alpha * (to - from)).clamp(-max_step, max_step);
+    smoothed.push((step(prev_x, x), step(prev_y, y)));
+  }
+
+  smoothed
+}
+
+/// Путь окна кадрирования заданного размера по центрам объекта съемки
+pub fn build_crop_path(
+  times: &[f64],
+  centers: &[(f32, f32)],
+  window: (f32, f32),
+) -> Vec<CropKeyframe> {
+  let (width, height) = window;
+  let percent = |value: f32| (value * 10000.0).round() / 100.0;
+
+  times
+    .iter()
+    .zip(centers)
+    .map(|(&time, &(center_x, center_y))| CropKeyframe {
+      time,
+      rect: CropRect {
+        x: percent((center_x - width / 2.0).clamp(0.0, 1.0 - width)),
+        y: percent((center_y - height / 2.0).clamp(0.0, 1.0 - height)),
+        width: percent(width),
+        height: percent(height),
+      },
+    })
+    .collect()
+}
+
+/// Удалить ключевые кадры, которые восстанавливаются линейной интерполяцией
+/// соседних с точностью `tolerance` (в процентах кадра)
+pub fn simplify_crop_path(path: &[CropKeyframe], tolerance: f32) -> Vec<CropKeyframe> {
+  if path.len() <= 2 {
+    return path.to_vec();
+  }
+
+  let fits = |anchor: usize, end: usize| {
+    (anchor + 1..end).all(|middle| {
+      let Some(expected) = crop_rect_at(&[path[anchor], path[end]], path[middle].time) else {
+        return false;
+      };
+      let actual = path[middle].rect;
+      (expected.x - actual.x).abs() <= tolerance && (expected.y - actual.y).abs() <= tolerance
+    })
+  };
+
+  let mut simplified = vec![path[0]];
+  let mut anchor = 0;
+  for end in 2..path.len() {
+    if !fits(anchor, end) {
+      anchor = end - 1;
+      simplified.push(path[anchor]);
+    }
+  }
+  simplified.push(path[path.len() - 1]);
+  simplified
+}
+
+/// Путь окна кадрирования по наблюдениям объектов съемки.
+///
+/// Возвращает путь и признак того, что объект съемки найден; без объекта
+/// путь - неподвижное кадрирование по центру.
+pub fn plan_reframe_path(
+  observations: &[SubjectObservation],
+  window: (f32, f32),
+  options: &ReframeOptions,
+) -> (Vec<CropKeyframe>, bool) {
+  let times: Vec<f64> = observations
+    .iter()
+    .map(|observation| observation.time)
+    .collect();
+  let tracks = track_subjects(observations);
+  let primary = select_primary_track(&tracks);
+
+  let centers = subject_centers(observations.len(), primary);
+  let smoothed = smooth_subject_centers(&times, &centers, options);
+  let path = build_crop_path(&times, &smoothed, window);
+
+  (
+    simplify_crop_path(&path, options.simplify_tolerance),
+    primary.is_some(),
+  )
+}
+
+/// Путь кадрирования файла в координатах клипа: время отсчитывается от
+/// `source_start`, границы клипа получают интерполированные ключевые кадры
+pub fn clip_crop_path(
+  path: &[CropKeyframe],
+  source_start: f64,
+  source_end: f64,
+) -> Vec<CropKeyframe> {
+  let keyframe_at = |time: f64| {
+    crop_rect_at(path, time).map(|rect| CropKeyframe {
+      time: time - source_start,
+      rect,
+    })
+  };
+
+  let Some(start) = keyframe_at(source_start) else {
+    return Vec::new();
+  };
+  let mut local = vec![start];
+  local.extend(
+    path
+      .iter()
+      .filter(|keyframe| keyframe.time > source_start && keyframe.time < source_end)
+      .map(|keyframe| CropKeyframe {
+        time: keyframe.time - source_start,
+        ..*keyframe
+      }),
+  );
+  if source_end > source_start {
+    local.extend(keyframe_at(source_end));
+  }
+  local
+}
+
+/// Применить путь кадрирования файла к клипу.
+///
+/// Неподвижный путь сохраняется как статичное кадрирование `crop`.
+pub fn apply_reframe_to_clip(clip: &mut Clip, path: &[CropKeyframe]) -> Result<(), String> {
+  let local = clip_crop_path(path, clip.source_start, clip.source_end);
+  let Some(first) = local.first().copied() else {
+    return Err("Путь кадрирования пуст".to_string());
+  };
+
+  let transform = clip
+    .transform
+    .get_or_insert_with(TransformSettings::default);
+  if local.iter().all(|keyframe| keyframe.rect == first.rect) {
+    transform.crop = Some(first.rect);
+    transform.crop_path.clear();
+  } else {
+    transform.crop = None;
+    transform.crop_path = local;
+  }
+  transform.validate()
+}
+
+/// Моменты timeline для кадров превью рефрейминга клипа
+pub fn reframe_preview_times(clip: &Clip) -> Vec<f64> {
+  let duration = clip.end_time - clip.start_time;
+  PREVIEW_POSITIONS
+    .iter()
+    .map(|position| clip.start_time + duration * position)
+    .collect()
+}
+
+/// Вертикальное разрешение 9:16 с длинной стороной исходного разрешения
+pub fn vertical_resolution(resolution: &Resolution) -> Resolution {
+  let height = resolution.width.max(resolution.height);
+  let width = ((height as f32 * 9.0 / 16.0 / 2.0).round() as u32) * 2;
+  Resolution::new(width, height)
+}
+
+/// Перевести проект в вертикальный формат 9:16 и применить путь
+/// кадрирования к клипу `clip_id`
+pub fn apply_vertical_reframe(
+  project: &mut ProjectSchema,
+  clip_id: &str,
+  path: &[CropKeyframe],
+) -> Result<(), String> {
+  let (track_index, clip_index) = project
+    .find_clip_position(clip_id)
+    .ok_or_else(|| format!("Клип {clip_id} не найден"))?;
+  apply_reframe_to_clip(&mut project.tracks[track_index].clips[clip_index], path)?;
+
+  let resolution = vertical_resolution(&project.settings.resolution);
+  project.timeline.resolution = (resolution.width, resolution.height);
+  project.settings.resolution = resolution;
+  project.timeline.aspect_ratio = AspectRatio::Ratio9x16;
+  project.settings.aspect_ratio = AspectRatio::Ratio9x16;
+  project.touch();
+  Ok(())
+}
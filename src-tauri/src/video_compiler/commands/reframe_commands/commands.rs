@@ -0,0 +1,291 @@
+//! Tauri команды рефрейминга - тонкий слой над бизнес-логикой
+
+use super::{business_logic::*, types::*};
+use crate::recognition::yolo_processor::{YoloModel, YoloProcessor};
+use crate::video_compiler::commands::state::VideoCompilerState;
+use crate::video_compiler::core::error::{Result, VideoCompilerError};
+use crate::video_compiler::ffmpeg_executor::FFmpegExecutor;
+use crate::video_compiler::schema::{AspectRatio, Clip, CropKeyframe, ProjectSchema};
+use std::path::{Path, PathBuf};
+use tauri::State;
+use tokio::fs;
+
+/// Проанализировать файл и построить путь окна кадрирования под
+/// соотношение сторон `target_aspect`, удерживающий объект съемки в кадре
+#[tauri::command]
+pub async fn analyze_reframe(
+  file_path: String,
+  target_aspect: AspectRatio,
+  options: Option<ReframeOptions>,
+  state: State<'_, VideoCompilerState>,
+) -> Result<ReframeAnalysis> {
+  let ffmpeg_path = state.ffmpeg_path.read().await.clone();
+  run_reframe_analysis(
+    &ffmpeg_path,
+    &file_path,
+    target_aspect,
+    &options.unwrap_or_default(),
+  )
+  .await
+}
+
+/// Применить путь кадрирования файла к клипу
+#[tauri::command]
+pub async fn apply_reframe(mut clip: Clip, crop_path: Vec<CropKeyframe>) -> Result<Clip> {
+  apply_reframe_to_clip(&mut clip, &crop_path).map_err(VideoCompilerError::InvalidParameter)?;
+  Ok(clip)
+}
+
+/// Перекадрировать клип под вертикальный формат 9:16: анализ, применение
+/// пути кадрирования и превью результата в трех точках клипа
+#[tauri::command]
+pub async fn reframe_clip_for_vertical(
+  project: ProjectSchema,
+  clip_id: String,
+  options: Option<ReframeOptions>,
+  state: State<'_, VideoCompilerState>,
+) -> Result<ReframeClipResult> {
+  let clip = project
+    .find_clip_by_id(&clip_id)
+    .ok_or_else(|| VideoCompilerError::InvalidParameter(format!("Клип {clip_id} не найден")))?;
+  let file_path = project.get_clip_file_path(&clip_id).ok_or_else(|| {
+    VideoCompilerError::InvalidParameter(format!("Клип {clip_id} не ссылается на файл"))
+  })?;
+  let preview_times = reframe_preview_times(clip);
+
+  let ffmpeg_path = state.ffmpeg_path.read().await.clone();
+  let analysis = run_reframe_analysis(
+    &ffmpeg_path,
+    &file_path,
+    AspectRatio::Ratio9x16,
+    &options.unwrap_or_default(),
+  )
+  .await?;
+
+  let mut project = project;
+  apply_vertical_reframe(&mut project, &clip_id, &analysis.crop_path)
+    .map_err(VideoCompilerError::InvalidParameter)?;
+
+  let mut generator =
+    crate::video_compiler::preview::PreviewGenerator::new(state.cache_manager.clone());
+  generator.set_ffmpeg_path(&ffmpeg_path);
+  let mut previews = Vec::with_capacity(preview_times.len());
+  for timestamp in preview_times {
+    let image = generator
+      .render_timeline_frame(&project, timestamp, None, None, None)
+      .await?;
+    previews.push(ReframePreviewFrame { timestamp, image });
+  }
+
+  Ok(ReframeClipResult {
+    project,
+    analysis,
+    previews,
+  })
+}
+
+/// Извлечь кадры, найти на них объекты съемки и построить путь кадрирования
+async fn run_reframe_analysis(
+  ffmpeg_path: &str,
+  file_path: &str,
+  target_aspect: AspectRatio,
+  options: &ReframeOptions,
+) -> Result<ReframeAnalysis> {
+  if !Path::new(file_path).exists() {
+    return Err(VideoCompilerError::MediaFileError {
+      path: file_path.to_string(),
+      reason: "Видео файл не найден".to_string(),
+    });
+  }
+  if !options.sample_rate.is_finite() || options.sample_rate <= 0.0 {
+    return Err(VideoCompilerError::InvalidParameter(
+      "Частота анализа кадров должна быть больше 0".to_string(),
+    ));
+  }
+  if !target_aspect.ratio().is_finite() || target_aspect.ratio() <= 0.0 {
+    return Err(VideoCompilerError::InvalidParameter(
+      "Соотношение сторон должно быть больше 0".to_string(),
+    ));
+  }
+
+  let work_dir = std::env::temp_dir()
+    .join("timeline_studio_reframe")
+    .join(uuid::Uuid::new_v4().to_string());
+  fs::create_dir_all(&work_dir).await.map_err(|e| {
+    VideoCompilerError::IoError(format!("Ошибка создания временной директории: {e}"))
+  })?;
+
+  let result = analyze_frames(ffmpeg_path, file_path, target_aspect, options, &work_dir).await;
+  if let Err(e) = fs::remove_dir_all(&work_dir).await {
+    log::warn!(
+      "Не удалось удалить кадры рефрейминга {}: {e}",
+      work_dir.display()
+    );
+  }
+  result
+}
+
+/// Анализ кадров во временной директории `work_dir`
+async fn analyze_frames(
+  ffmpeg_path: &str,
+  file_path: &str,
+  target_aspect: AspectRatio,
+  options: &ReframeOptions,
+  work_dir: &Path,
+) -> Result<ReframeAnalysis> {
+  let frames = extract_sample_frames(ffmpeg_path, file_path, options.sample_rate, work_dir).await?;
+  let (source_width, source_height) =
+    image::image_dimensions(&frames[0]).map_err(|e| VideoCompilerError::MediaFileError {
+      path: file_path.to_string(),
+      reason: format!("Не удалось прочитать кадр: {e}"),
+    })?;
+
+  let observations = detect_subjects(&frames, options, source_width, source_height).await?;
+  let window = reframe_window_size(source_width, source_height, target_aspect.ratio());
+  let (crop_path, subject_found) = plan_reframe_path(&observations, window, options);
+  if !subject_found {
+    log::info!("Объект съемки в {file_path} не найден, используется кадрирование по центру");
+  }
+
+  Ok(ReframeAnalysis {
+    file_path: file_path.to_string(),
+    target_aspect,
+    source_width,
+    source_height,
+    crop_path,
+    subject_found,
+    analyzed_frames: frames.len(),
+  })
+}
+
+/// Извлечь кадры файла с частотой `sample_rate`
+async fn extract_sample_frames(
+  ffmpeg_path: &str,
+  file_path: &str,
+  sample_rate: f64,
+  work_dir: &Path,
+) -> Result<Vec<PathBuf>> {
+  let pattern = work_dir.join("frame_%05d.jpg");
+  let mut cmd = tokio::process::Command::new(ffmpeg_path);
+  cmd.args([
+    "-v",
+    "error",
+    "-i",
+    file_path,
+    "-vf",
+    &format!("fps={sample_rate}"),
+    "-q:v",
+    "3",
+    "-y",
+    &pattern.to_string_lossy(),
+  ]);
+
+  let result = FFmpegExecutor::new()
+    .execute(cmd)
+    .await
+    .map_err(|e| VideoCompilerError::IoError(format!("Ошибка извлечения кадров: {e}")))?;
+  if result.exit_code != 0 {
+    return Err(VideoCompilerError::FFmpegError {
+      exit_code: Some(result.exit_code),
+      stderr: result.stderr,
+      command: ffmpeg_path.to_string(),
+    });
+  }
+
+  let mut frames = Vec::new();
+  let mut entries = fs::read_dir(work_dir)
+    .await
+    .map_err(|e| VideoCompilerError::IoError(format!("Ошибка чтения кадров: {e}")))?;
+  while let Some(entry) = entries
+    .next_entry()
+    .await
+    .map_err(|e| VideoCompilerError::IoError(format!("Ошибка чтения кадров: {e}")))?
+  {
+    frames.push(entry.path());
+  }
+  frames.sort();
+
+  if frames.is_empty() {
+    return Err(VideoCompilerError::MediaFileError {
+      path: file_path.to_string(),
+      reason: "Не удалось извлечь кадры для анализа".to_string(),
+    });
+  }
+  Ok(frames)
+}
+
+/// Найти объекты съемки на кадрах.
+///
+/// Основной источник - детектор объектов по классам `subject_classes`;
+/// на кадрах без таких объектов (крупные планы) используются лица.
+async fn detect_subjects(
+  frames: &[PathBuf],
+  options: &ReframeOptions,
+  frame_width: u32,
+  frame_height: u32,
+) -> Result<Vec<SubjectObservation>> {
+  let load = |model: YoloModel| async move {
+    let mut processor = YoloProcessor::new(model, options.confidence_threshold)?;
+    processor.load_model().await?;
+    Ok::<_, anyhow::Error>(processor)
+  };
+
+  let mut object_detector = load(YoloModel::YoloV11Detection)
+    .await
+    .map_err(|e| VideoCompilerError::DependencyMissing(format!("Детектор объектов: {e}").into()))?;
+  object_detector.set_target_classes(options.subject_classes.clone());
+  let mut face_detector = match load(YoloModel::YoloV11Face).await {
+    Ok(detector) => Some(detector),
+    Err(e) => {
+      log::warn!("Детектор лиц недоступен, рефрейминг только по объектам: {e}");
+      None
+    }
+  };
+
+  let to_boxes = |detections: Vec<crate::recognition::yolo_processor::Detection>| {
+    detections
+      .into_iter()
+      .map(|detection| {
+        SubjectBox::from_pixels(
+          detection.bbox.x,
+          detection.bbox.y,
+          detection.bbox.width,
+          detection.bbox.height,
+          frame_width,
+          frame_height,
+        )
+      })
+      .collect::<Vec<_>>()
+  };
+
+  let mut observations = Vec::with_capacity(frames.len());
+  for (index, frame) in frames.iter().enumerate() {
+    let detections = object_detector
+      .process_image(frame)
+      .await
+      .map_err(|e| VideoCompilerError::InternalError(format!("Ошибка распознавания: {e}")))?;
+    let mut subjects = to_boxes(detections);
+
+    if subjects.is_empty() {
+      if let Some(face_detector) = face_detector.as_mut() {
+        match face_detector.process_image(frame).await {
+          Ok(faces) => subjects = to_boxes(faces),
+          Err(e) => log::warn!("Ошибка поиска лиц на {}: {e}", frame.display()),
+        }
+      }
+    }
+
+    observations.push(SubjectObservation {
+      time: index as f64 / options.sample_rate,
+      subjects,
+    });
+  }
+
+  Ok(observations)
+}
+
+crate::command_manifest!(
+  REFRAME_COMMANDS_MANIFEST,
+  "video_compiler::reframe_commands",
+  [analyze_reframe, apply_reframe, reframe_clip_for_vertical,]
+);
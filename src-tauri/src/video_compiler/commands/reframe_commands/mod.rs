@@ -0,0 +1,17 @@
+//! Команды автоматического рефрейминга (умного кадрирования)
+//!
+//! Объединяют распознавание объектов и лиц с FFmpegBuilder: по детекциям
+//! строится сглаженный путь окна кадрирования под новое соотношение сторон
+
+// ============ Экспорт публичных типов ============
+pub use business_logic::*;
+pub use commands::*;
+pub use types::*;
+
+// ============ Модули ============
+mod business_logic;
+mod commands;
+mod types;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,226 @@
+//! Тесты для модуля reframe_commands
+
+use super::*;
+use crate::video_compiler::schema::{
+  AspectRatio, Clip, CropKeyframe, CropRect, ProjectSchema, Resolution, Track, TrackType,
+};
+use std::path::PathBuf;
+
+fn subject(x: f32, width: f32) -> SubjectBox {
+  SubjectBox {
+    x,
+    y: 0.2,
+    width,
+    height: 0.6,
+  }
+}
+
+fn observation(time: f64, subjects: Vec<SubjectBox>) -> SubjectObservation {
+  SubjectObservation { time, subjects }
+}
+
+fn keyframe(time: f64, x: f32) -> CropKeyframe {
+  CropKeyframe {
+    time,
+    rect: CropRect {
+      x,
+      y: 0.0,
+      width: 31.64,
+      height: 100.0,
+    },
+  }
+}
+
+#[test]
+fn test_reframe_window_size() {
+  let (width, height) = reframe_window_size(1920, 1080, AspectRatio::Ratio9x16.ratio());
+  assert!((width - 0.3164).abs() < 1e-3);
+  assert_eq!(height, 1.0);
+
+  // Вертикальный исходник под горизонтальный формат обрезается по высоте
+  let (width, height) = reframe_window_size(1080, 1920, AspectRatio::Ratio16x9.ratio());
+  assert_eq!(width, 1.0);
+  assert!((height - 0.3164).abs() < 1e-3);
+}
+
+#[test]
+fn test_subject_box_from_pixels_and_iou() {
+  let a = SubjectBox::from_pixels(192.0, 108.0, 384.0, 540.0, 1920, 1080);
+  assert!((a.x - 0.1).abs() < 1e-6);
+  assert!((a.height - 0.5).abs() < 1e-6);
+  assert!((a.iou(&a) - 1.0).abs() < 1e-6);
+  assert_eq!(a.iou(&subject(0.8, 0.1)), 0.0);
+}
+
+#[test]
+fn test_track_subjects_prefers_large_persistent_subject() {
+  // Крупный объект во всех кадрах и мелкий, мелькнувший в одном
+  let observations = vec![
+    observation(0.0, vec![subject(0.60, 0.20), subject(0.05, 0.05)]),
+    observation(0.5, vec![subject(0.62, 0.20)]),
+    observation(1.0, vec![subject(0.64, 0.20)]),
+  ];
+
+  let tracks = track_subjects(&observations);
+  assert_eq!(tracks.len(), 2);
+  let primary = select_primary_track(&tracks).unwrap();
+  assert_eq!(primary.boxes.len(), 3);
+  assert_eq!(primary.box_at(2).unwrap().x, 0.64);
+}
+
+#[test]
+fn test_track_subjects_breaks_track_after_gap() {
+  let observations = vec![
+    observation(0.0, vec![subject(0.4, 0.2)]),
+    observation(0.5, vec![]),
+    observation(1.0, vec![]),
+    observation(1.5, vec![]),
+    observation(2.0, vec![subject(0.4, 0.2)]),
+  ];
+  assert_eq!(track_subjects(&observations).len(), 2);
+}
+
+#[test]
+fn test_subject_centers_hold_position_and_fallback_to_center() {
+  assert_eq!(subject_centers(3, None), vec![(0.5, 0.5); 3]);
+
+  let track = SubjectTrack {
+    boxes: vec![(1, subject(0.6, 0.2))],
+  };
+  let centers = subject_centers(3, Some(&track));
+  assert_eq!(centers.len(), 3);
+  assert!(centers.iter().all(|&(x, _)| (x - 0.7).abs() < 1e-6));
+}
+
+#[test]
+fn test_smooth_subject_centers_limits_pan_speed() {
+  let options = ReframeOptions {
+    smoothing: 1.0,
+    max_pan_speed: 0.1,
+    ..Default::default()
+  };
+  let smoothed = smooth_subject_centers(
+    &[0.0, 1.0, 2.0],
+    &[(0.2, 0.5), (0.8, 0.5), (0.8, 0.5)],
+    &options,
+  );
+  assert!((smoothed[1].0 - 0.3).abs() < 1e-6);
+  assert!((smoothed[2].0 - 0.4).abs() < 1e-6);
+
+  // Без ограничения скорости сглаживание подавляет дрожание
+  let options = ReframeOptions {
+    smoothing: 0.5,
+    max_pan_speed: 10.0,
+    ..Default::default()
+  };
+  let smoothed = smooth_subject_centers(&[0.0, 0.5], &[(0.5, 0.5), (0.6, 0.5)], &options);
+  assert!((smoothed[1].0 - 0.55).abs() < 1e-6);
+}
+
+#[test]
+fn test_build_crop_path_clamps_window_to_frame() {
+  let path = build_crop_path(&[0.0, 1.0], &[(0.05, 0.5), (0.99, 0.5)], (0.3164, 1.0));
+  assert_eq!(path[0].rect.x, 0.0);
+  assert_eq!(path[1].rect.x, 68.36);
+  assert_eq!(path[1].rect.width, 31.64);
+  assert!(path.iter().all(|keyframe| keyframe.rect.validate().is_ok()));
+}
+
+#[test]
+fn test_simplify_crop_path_drops_linear_keyframes() {
+  let path = vec![
+    keyframe(0.0, 10.0),
+    keyframe(1.0, 20.0),
+    keyframe(2.0, 30.0),
+    keyframe(3.0, 30.0),
+    keyframe(4.0, 30.1),
+  ];
+  let simplified = simplify_crop_path(&path, 0.5);
+  let times: Vec<f64> = simplified.iter().map(|keyframe| keyframe.time).collect();
+  assert_eq!(times, vec![0.0, 2.0, 4.0]);
+}
+
+#[test]
+fn test_plan_reframe_path_without_subject_is_center_crop() {
+  let observations = vec![observation(0.0, vec![]), observation(0.5, vec![])];
+  let (path, found) = plan_reframe_path(&observations, (0.3164, 1.0), &ReframeOptions::default());
+  assert!(!found);
+  assert!(path.iter().all(|keyframe| keyframe.rect.x == 34.18));
+}
+
+#[test]
+fn test_plan_reframe_path_follows_subject() {
+  let observations: Vec<SubjectObservation> = (0..6)
+    .map(|index| observation(index as f64 * 0.5, vec![subject(0.7, 0.1)]))
+    .collect();
+  let (path, found) = plan_reframe_path(&observations, (0.3164, 1.0), &ReframeOptions::default());
+  assert!(found);
+  let last = path.last().unwrap();
+  assert_eq!(last.rect.x, 59.18);
+}
+
+#[test]
+fn test_clip_crop_path_shifts_to_clip_time() {
+  let path = vec![keyframe(0.0, 0.0), keyframe(4.0, 40.0), keyframe(8.0, 40.0)];
+  let local = clip_crop_path(&path, 2.0, 6.0);
+
+  let times: Vec<f64> = local.iter().map(|keyframe| keyframe.time).collect();
+  assert_eq!(times, vec![0.0, 2.0, 4.0]);
+  assert_eq!(local[0].rect.x, 20.0);
+  assert_eq!(local[2].rect.x, 40.0);
+}
+
+#[test]
+fn test_apply_reframe_to_clip() {
+  let mut clip = Clip::new(PathBuf::from("interview.mp4"), 0.0, 4.0);
+  apply_reframe_to_clip(&mut clip, &[keyframe(0.0, 10.0), keyframe(4.0, 30.0)]).unwrap();
+  let transform = clip.transform.as_ref().unwrap();
+  assert_eq!(transform.crop_path.len(), 2);
+  assert!(transform.crop.is_none());
+
+  // Неподвижный путь становится статичным кадрированием
+  apply_reframe_to_clip(&mut clip, &[keyframe(0.0, 34.18)]).unwrap();
+  let transform = clip.transform.as_ref().unwrap();
+  assert!(transform.crop_path.is_empty());
+  assert_eq!(transform.crop.unwrap().x, 34.18);
+
+  assert!(apply_reframe_to_clip(&mut clip, &[]).is_err());
+}
+
+#[test]
+fn test_apply_vertical_reframe_updates_project() {
+  let mut project = ProjectSchema::new("Shorts".to_string());
+  project.settings.resolution = Resolution::full_hd();
+  let clip = Clip::new(PathBuf::from("interview.mp4"), 2.0, 6.0);
+  let clip_id = clip.id.clone();
+  let mut track = Track::new(TrackType::Video, "Video".to_string());
+  track.clips.push(clip);
+  project.tracks.push(track);
+
+  apply_vertical_reframe(
+    &mut project,
+    &clip_id,
+    &[keyframe(0.0, 10.0), keyframe(6.0, 40.0)],
+  )
+  .unwrap();
+  assert_eq!(project.settings.resolution.width, 1080);
+  assert_eq!(project.settings.resolution.height, 1920);
+  assert_eq!(project.timeline.resolution, (1080, 1920));
+  assert_eq!(project.settings.aspect_ratio, AspectRatio::Ratio9x16);
+  let clip = project.find_clip_by_id(&clip_id).unwrap();
+  assert_eq!(clip.transform.as_ref().unwrap().crop_path.len(), 2);
+
+  let times = reframe_preview_times(clip);
+  for (time, expected) in times.iter().zip([3.0, 5.0, 7.0]) {
+    assert!((time - expected).abs() < 1e-9);
+  }
+  assert!(apply_vertical_reframe(&mut project, "missing", &[keyframe(0.0, 0.0)]).is_err());
+}
+
+#[test]
+fn test_vertical_resolution() {
+  let resolution = vertical_resolution(&Resolution::new(1280, 720));
+  assert_eq!((resolution.width, resolution.height), (720, 1280));
+  let resolution = vertical_resolution(&Resolution::new(1080, 1080));
+  assert_eq!((resolution.width, resolution.height), (608, 1080));
+}
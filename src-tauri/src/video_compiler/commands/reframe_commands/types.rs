@@ -0,0 +1,164 @@
+//! Типы данных для команд рефрейминга
+
+use serde::{Deserialize, Serialize};
+
+use crate::video_compiler::schema::{AspectRatio, CropKeyframe, ProjectSchema};
+
+/// Параметры анализа рефрейминга
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReframeOptions {
+  /// Частота анализируемых кадров (кадров в секунду)
+  pub sample_rate: f64,
+  /// Коэффициент экспоненциального сглаживания центра окна (0 - 1),
+  /// меньшие значения дают более плавное движение
+  pub smoothing: f32,
+  /// Максимальная скорость панорамирования в долях кадра в секунду
+  pub max_pan_speed: f32,
+  /// Минимальная уверенность детекции
+  pub confidence_threshold: f32,
+  /// Классы объектов, которые считаются объектом съемки
+  pub subject_classes: Vec<String>,
+  /// Допуск упрощения пути в процентах кадра: промежуточные ключевые
+  /// кадры, отклоняющиеся от линейной интерполяции меньше допуска, удаляются
+  pub simplify_tolerance: f32,
+}
+
+impl Default for ReframeOptions {
+  fn default() -> Self {
+    Self {
+      sample_rate: 2.0,
+      smoothing: 0.35,
+      max_pan_speed: 0.25,
+      confidence_threshold: 0.5,
+      subject_classes: vec!["person".to_string()],
+      simplify_tolerance: 0.5,
+    }
+  }
+}
+
+/// Рамка объекта в долях кадра (0 - 1)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SubjectBox {
+  pub x: f32,
+  pub y: f32,
+  pub width: f32,
+  pub height: f32,
+}
+
+impl SubjectBox {
+  /// Рамка из пиксельных координат детекции
+  pub fn from_pixels(
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    frame_width: u32,
+    frame_height: u32,
+  ) -> Self {
+    let (frame_width, frame_height) = (frame_width.max(1) as f32, frame_height.max(1) as f32);
+    Self {
+      x: x / frame_width,
+      y: y / frame_height,
+      width: width / frame_width,
+      height: height / frame_height,
+    }
+  }
+
+  /// Площадь рамки в долях кадра
+  pub fn area(&self) -> f32 {
+    self.width.max(0.0) * self.height.max(0.0)
+  }
+
+  /// Центр рамки
+  pub fn center(&self) -> (f32, f32) {
+    (self.x + self.width / 2.0, self.y + self.height / 2.0)
+  }
+
+  /// Пересечение над объединением двух рамок
+  pub fn iou(&self, other: &SubjectBox) -> f32 {
+    let left = self.x.max(other.x);
+    let top = self.y.max(other.y);
+    let right = (self.x + self.width).min(other.x + other.width);
+    let bottom = (self.y + self.height).min(other.y + other.height);
+    let intersection = (right - left).max(0.0) * (bottom - top).max(0.0);
+    let union = self.area() + other.area() - intersection;
+    if union <= 0.0 {
+      0.0
+    } else {
+      intersection / union
+    }
+  }
+}
+
+/// Объекты съемки на одном проанализированном кадре
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubjectObservation {
+  /// Время кадра в исходном файле (секунды)
+  pub time: f64,
+  /// Рамки объектов съемки на кадре
+  pub subjects: Vec<SubjectBox>,
+}
+
+/// Объект съемки, прослеженный между кадрами
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubjectTrack {
+  /// Рамки объекта по индексам наблюдений
+  pub boxes: Vec<(usize, SubjectBox)>,
+}
+
+impl SubjectTrack {
+  /// Вес трека: суммарная площадь по всем кадрам, так что крупный и
+  /// долго присутствующий в кадре объект получает приоритет
+  pub fn score(&self) -> f32 {
+    self.boxes.iter().map(|(_, subject)| subject.area()).sum()
+  }
+
+  /// Рамка объекта на наблюдении `index`
+  pub fn box_at(&self, index: usize) -> Option<&SubjectBox> {
+    self
+      .boxes
+      .iter()
+      .find(|(observation, _)| *observation == index)
+      .map(|(_, subject)| subject)
+  }
+}
+
+/// Результат анализа рефрейминга файла
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReframeAnalysis {
+  /// Путь к анализируемому файлу
+  pub file_path: String,
+  /// Целевое соотношение сторон
+  pub target_aspect: AspectRatio,
+  /// Ширина исходного кадра
+  pub source_width: u32,
+  /// Высота исходного кадра
+  pub source_height: u32,
+  /// Путь окна кадрирования, время - от начала файла
+  pub crop_path: Vec<CropKeyframe>,
+  /// Найден ли объект съемки (иначе путь - кадрирование по центру)
+  pub subject_found: bool,
+  /// Количество проанализированных кадров
+  pub analyzed_frames: usize,
+}
+
+/// Кадр превью рефрейминга
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReframePreviewFrame {
+  /// Время на timeline проекта
+  pub timestamp: f64,
+  /// Изображение кадра
+  pub image: Vec<u8>,
+}
+
+/// Результат рефрейминга клипа под вертикальный формат
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReframeClipResult {
+  /// Обновленная схема проекта
+  pub project: ProjectSchema,
+  /// Анализ, по которому выполнено кадрирование
+  pub analysis: ReframeAnalysis,
+  /// Превью результата в трех точках клипа
+  pub previews: Vec<ReframePreviewFrame>,
+}
@@ -212,6 +212,7 @@ pub async fn get_cache_hit_ratio_stats(
     render_requests: 300,
     render_hits: 250,
     render_misses: 50,
+    ..Default::default()
   };
 
   let hit_ratio = stats.hit_ratio();
@@ -272,6 +273,21 @@ pub async fn clear_cache_advanced(
   })
 }
 
+crate::command_manifest!(
+  REMAINING_UTILITIES_COMMANDS_MANIFEST,
+  "video_compiler::remaining_utilities_commands",
+  [
+    test_hardware_acceleration_available,
+    perform_track_operations,
+    get_detailed_clip_info,
+    validate_subtitle_project,
+    touch_project_timestamp,
+    get_cache_metadata,
+    get_cache_hit_ratio_stats,
+    clear_cache_advanced,
+  ]
+);
+
 #[cfg(test)]
 mod tests {
   use super::*;
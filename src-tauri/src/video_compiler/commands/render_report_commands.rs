@@ -0,0 +1,65 @@
+//! Render Report Commands - отчет о завершенном рендере и его выгрузка
+//! в Markdown
+
+use crate::video_compiler::error::{Result, VideoCompilerError};
+use crate::video_compiler::services::render_report::RenderReport;
+use crate::video_compiler::VideoCompilerState;
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+/// Найти отчет задачи: сначала в истории задач RenderService, затем
+/// рядом с выходным файлом (`<output>.report.json`)
+async fn find_render_report(state: &VideoCompilerState, job_id: &str) -> Result<RenderReport> {
+  let mut output_path = None;
+
+  if let Some(render_service) = state.services.get_render_service() {
+    if let Some(info) = render_service.get_job_info(job_id).await? {
+      if let Some(report) = info.report {
+        return Ok(report);
+      }
+      output_path = info.output_path;
+    }
+  }
+  if output_path.is_none() {
+    output_path = state
+      .active_jobs
+      .read()
+      .await
+      .get(job_id)
+      .map(|job| PathBuf::from(&job.metadata.output_path));
+  }
+
+  let output_path = output_path.ok_or_else(|| {
+    VideoCompilerError::InvalidParameter(format!("Задача рендеринга не найдена: {job_id}"))
+  })?;
+  RenderReport::load(&output_path).await
+}
+
+/// Отчет о рендере задачи: исходные файлы, настройки, время этапов,
+/// проверка результата и предупреждения
+#[tauri::command]
+pub async fn get_render_report(
+  job_id: String,
+  state: State<'_, VideoCompilerState>,
+) -> Result<RenderReport> {
+  find_render_report(&state, &job_id).await
+}
+
+/// Сохранить отчет о рендере задачи в Markdown
+#[tauri::command]
+pub async fn export_render_report_markdown(
+  job_id: String,
+  path: String,
+  state: State<'_, VideoCompilerState>,
+) -> Result<()> {
+  let report = find_render_report(&state, &job_id).await?;
+  tokio::fs::write(Path::new(&path), report.to_markdown())
+    .await
+    .map_err(|e| VideoCompilerError::IoError(format!("Не удалось сохранить отчет: {e}")))
+}
+
+crate::command_manifest!(
+  RENDER_REPORT_COMMANDS_MANIFEST,
+  "video_compiler::render_report_commands",
+  [get_render_report, export_render_report_markdown,]
+);
@@ -0,0 +1,82 @@
+//! Render Spec Commands - экспорт задачи рендеринга для другой машины и ее запуск
+
+use crate::video_compiler::core::ffmpeg_manager::FfmpegManager;
+use crate::video_compiler::error::{Result, VideoCompilerError};
+use crate::video_compiler::schema::{ExportSettings, ProjectSchema};
+use crate::video_compiler::services::render_spec::{
+  self, RenderSpecSummary, RenderSpecVerification,
+};
+use crate::video_compiler::{VideoCompilerEvent, VideoCompilerState};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, State};
+
+/// Экспортировать задачу рендеринга в переносимую JSON-спецификацию.
+///
+/// `settings` заменяют настройки экспорта проекта; медиафайлы хешируются.
+#[tauri::command]
+pub async fn export_render_job(
+  state: State<'_, VideoCompilerState>,
+  project: ProjectSchema,
+  settings: Option<ExportSettings>,
+  output_spec_path: String,
+) -> Result<RenderSpecSummary> {
+  let export = settings.unwrap_or_else(|| project.settings.export.clone());
+  let ffmpeg_path = state.ffmpeg_path.read().await.clone();
+  render_spec::export_render_spec(
+    &project,
+    export,
+    &ffmpeg_path,
+    &PathBuf::from(output_spec_path),
+  )
+  .await
+}
+
+/// Проверить медиафайлы спецификации в `media_root`
+#[tauri::command]
+pub async fn verify_render_spec(
+  spec_path: String,
+  media_root: String,
+) -> Result<RenderSpecVerification> {
+  let spec = render_spec::load_render_spec(Path::new(&spec_path)).await?;
+  render_spec::verify_render_media(&spec, Path::new(&media_root)).await
+}
+
+/// Запустить рендеринг по спецификации с медиафайлами из `media_root`.
+///
+/// Задача проходит обычный конвейер рендеринга с отслеживанием прогресса;
+/// возвращается ID задачи.
+#[tauri::command]
+pub async fn run_render_spec(
+  app: AppHandle,
+  state: State<'_, VideoCompilerState>,
+  spec_path: String,
+  media_root: String,
+  output_path: String,
+) -> Result<String> {
+  let spec = render_spec::load_render_spec(Path::new(&spec_path)).await?;
+  let ffmpeg_path = state.ffmpeg_path.read().await.clone();
+  let ffmpeg = FfmpegManager::new().status(&ffmpeg_path, None).await;
+  let project = render_spec::prepare_render_spec(spec, Path::new(&media_root), &ffmpeg).await?;
+
+  let render_service = state
+    .services
+    .get_render_service()
+    .ok_or_else(|| VideoCompilerError::validation("RenderService не найден"))?;
+  let job_id = render_service
+    .start_render(project, PathBuf::from(output_path))
+    .await?;
+
+  let _ = app.emit(
+    "video-compiler",
+    &VideoCompilerEvent::RenderStarted {
+      job_id: job_id.clone(),
+    },
+  );
+  Ok(job_id)
+}
+
+crate::command_manifest!(
+  RENDER_SPEC_COMMANDS_MANIFEST,
+  "video_compiler::render_spec_commands",
+  [export_render_job, verify_render_spec, run_render_spec,]
+);
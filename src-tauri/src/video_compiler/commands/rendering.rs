@@ -5,128 +5,380 @@
 
 use tauri::{Emitter, State};
 
+use crate::video_compiler::core::gpu_memory;
+use crate::video_compiler::core::output_template::{self, FilenameTemplateValidation};
+use crate::video_compiler::core::render_priority::RenderPriority;
 use crate::video_compiler::error::{Result, VideoCompilerError};
-use crate::video_compiler::schema::ProjectSchema;
+use crate::video_compiler::progress::RenderStatus;
+use crate::video_compiler::schema::{
+  validate_export_settings, ExportIssueSeverity, ExportSettingsValidation, ProjectSchema,
+  ProjectSettings,
+};
+use crate::video_compiler::services::ffmpeg_capabilities::FfmpegCapabilitiesCache;
+use crate::video_compiler::services::render_schedule::{RenderSchedule, ScheduledRenderInfo};
+use crate::video_compiler::services::render_service::RenderJobStatus;
 use crate::video_compiler::VideoCompilerEvent;
 
 use super::state::{RenderJob, VideoCompilerState};
 
-/// Запуск компиляции видео
+/// Запуск компиляции видео.
+///
+/// Путь вывода задается явно через `output_path` или, при `use_template`,
+/// строится по шаблону имени из настроек экспорта в каталоге `output_dir`.
+/// `render_priority` задает приоритет процесса FFmpeg, `render_threads`
+/// явно ограничивает число потоков кодирования.
+///
+/// `start_at` (RFC3339) или `start_when_idle` откладывают запуск: задача
+/// получает статус `Scheduled` и запускается планировщиком. Время в прошлом
+/// запускает рендеринг сразу.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn compile_video<R: tauri::Runtime>(
   app: tauri::AppHandle<R>,
-  project_schema: ProjectSchema,
-  output_path: String,
+  mut project_schema: ProjectSchema,
+  output_path: Option<String>,
+  output_dir: Option<String>,
+  use_template: Option<bool>,
+  render_priority: Option<RenderPriority>,
+  render_threads: Option<usize>,
+  start_at: Option<String>,
+  start_when_idle: Option<bool>,
+  idle_minutes: Option<u32>,
   state: State<'_, VideoCompilerState>,
 ) -> Result<String> {
-  // Используем RenderService из контейнера сервисов
-  let render_service = state
+  crate::instrumented_command!(compile_video, {
+    let limits = render_priority.unwrap_or_default().limits(render_threads);
+    let schedule = RenderSchedule::from_request(start_at, start_when_idle, idle_minutes)?
+      .filter(|schedule| !schedule.is_past(chrono::Utc::now()));
+
+    let output_path = output_template::resolve_output_target(
+      &project_schema,
+      output_path,
+      output_dir,
+      use_template.unwrap_or(false),
+    )?;
+
+    // Несовместимые настройки экспорта отвергаются до запуска FFmpeg
+    let export_warnings = export_settings_preflight(&state, &mut project_schema).await?;
+
+    // Используем RenderService из контейнера сервисов
+    let render_service = state
+      .services
+      .get_render_service()
+      .ok_or_else(|| VideoCompilerError::validation("RenderService не найден"))?;
+
+    if let Some(schedule) = schedule {
+      for message in &export_warnings {
+        log::warn!("Отложенный рендеринг: {message}");
+      }
+      let job_id = render_service
+        .schedule_render(project_schema, output_path, limits, schedule.clone())
+        .await?;
+      let _ = app.emit(
+        "video-compiler",
+        &VideoCompilerEvent::RenderScheduled {
+          job_id: job_id.clone(),
+          schedule,
+        },
+      );
+      return Ok(job_id);
+    }
+
+    // Оценку видеопамяти делаем до запуска, пока новая задача не заняла GPU
+    let vram_warning = vram_preflight(&state, &project_schema).await;
+
+    // Запускаем рендеринг через сервис
+    let job_id = render_service
+      .start_render_with_limits(project_schema, output_path, limits)
+      .await?;
+
+    // Отправляем событие о начале рендеринга
+    let _ = app.emit(
+      "video-compiler",
+      &VideoCompilerEvent::RenderStarted {
+        job_id: job_id.clone(),
+      },
+    );
+
+    for message in export_warnings.into_iter().chain(vram_warning) {
+      log::warn!("Рендеринг {job_id}: {message}");
+      let _ = app.emit(
+        "video-compiler",
+        &VideoCompilerEvent::RenderWarning {
+          job_id: job_id.clone(),
+          message,
+        },
+      );
+    }
+
+    // RenderService уже управляет задачами, поэтому просто возвращаем ID
+    Ok(job_id)
+  })
+}
+
+/// Проверка настроек экспорта по матрице совместимости перед запуском.
+///
+/// Автоисправления применяются к проекту, нарушения без исправления
+/// прерывают запуск. Возвращает предупреждения и описания примененных замен.
+async fn export_settings_preflight(
+  state: &VideoCompilerState,
+  project: &mut ProjectSchema,
+) -> Result<Vec<String>> {
+  let ffmpeg_path = state.ffmpeg_path.read().await.clone();
+  let capabilities = FfmpegCapabilitiesCache::shared(&ffmpeg_path)
+    .get()
+    .await
+    .ok();
+  let validation = validate_export_settings(&project.settings, capabilities.as_deref(), true);
+
+  if validation.has_errors() {
+    let errors: Vec<_> = validation
+      .issues_with(ExportIssueSeverity::Error)
+      .map(|issue| format!("{} ({})", issue.message, issue.suggested_fix))
+      .collect();
+    return Err(VideoCompilerError::validation(format!(
+      "Несовместимые настройки экспорта: {}",
+      errors.join("; ")
+    )));
+  }
+
+  if let Some(fixed) = validation.fixed_settings {
+    project.settings = fixed;
+  }
+  Ok(
+    validation
+      .issues
+      .into_iter()
+      .map(|issue| match issue.severity {
+        ExportIssueSeverity::AutoFixable => {
+          format!("{}: {}", issue.message, issue.suggested_fix)
+        }
+        _ => issue.message,
+      })
+      .collect(),
+  )
+}
+
+/// Проверить настройки экспорта для диалога экспорта.
+///
+/// `apply_auto_fixes` возвращает исправленные настройки в `fixed_settings`.
+#[tauri::command]
+pub async fn check_export_settings(
+  settings: ProjectSettings,
+  apply_auto_fixes: Option<bool>,
+  state: State<'_, VideoCompilerState>,
+) -> Result<ExportSettingsValidation> {
+  let ffmpeg_path = state.ffmpeg_path.read().await.clone();
+  let capabilities = FfmpegCapabilitiesCache::shared(&ffmpeg_path)
+    .get()
+    .await
+    .ok();
+  Ok(validate_export_settings(
+    &settings,
+    capabilities.as_deref(),
+    apply_auto_fixes.unwrap_or(false),
+  ))
+}
+
+/// Предупреждение перед рендерингом о возможной нехватке видеопамяти.
+///
+/// Учитывает уже выполняющиеся рендеры; ошибки определения GPU не мешают запуску.
+async fn vram_preflight(state: &VideoCompilerState, project: &ProjectSchema) -> Option<String> {
+  if !project.settings.export.hardware_acceleration {
+    return None;
+  }
+
+  let running_jobs = match state.services.get_render_service() {
+    Some(render_service) => render_service
+      .running_jobs()
+      .await
+      .map_or(0, |jobs| jobs.len()),
+    None => 0,
+  };
+  let memory = state
     .services
-    .get_render_service()
-    .ok_or_else(|| VideoCompilerError::validation("RenderService не найден"))?;
-
-  // Запускаем рендеринг через сервис
-  let job_id = render_service
-    .start_render(project_schema, std::path::PathBuf::from(output_path))
-    .await?;
-
-  // Отправляем событие о начале рендеринга
-  let _ = app.emit(
-    "video-compiler",
-    &VideoCompilerEvent::RenderStarted {
-      job_id: job_id.clone(),
-    },
-  );
-
-  // RenderService уже управляет задачами, поэтому просто возвращаем ID
-  Ok(job_id)
+    .get_gpu_service()?
+    .get_gpu_memory_info()
+    .await
+    .ok()
+    .flatten()?;
+
+  gpu_memory::vram_preflight_warning(project, running_jobs + 1, memory)
 }
 
 /// Отмена задачи рендеринга
 #[tauri::command]
 pub async fn cancel_render(job_id: String, state: State<'_, VideoCompilerState>) -> Result<bool> {
-  // Используем RenderService для отмены задачи
-  let render_service = state
-    .services
-    .get_render_service()
-    .ok_or_else(|| VideoCompilerError::validation("RenderService не найден"))?;
+  crate::instrumented_command!(cancel_render, {
+    // Используем RenderService для отмены задачи
+    let render_service = state
+      .services
+      .get_render_service()
+      .ok_or_else(|| VideoCompilerError::validation("RenderService не найден"))?;
+
+    render_service.cancel_render(&job_id).await
+  })
+}
+
+/// Запланированные, но еще не запущенные рендеры
+#[tauri::command]
+pub async fn list_scheduled_renders(
+  state: State<'_, VideoCompilerState>,
+) -> Result<Vec<ScheduledRenderInfo>> {
+  crate::instrumented_command!(list_scheduled_renders, {
+    let render_service = state
+      .services
+      .get_render_service()
+      .ok_or_else(|| VideoCompilerError::validation("RenderService не найден"))?;
+
+    render_service.list_scheduled_renders().await
+  })
+}
 
-  render_service.cancel_render(&job_id).await
+/// Перенести запуск запланированного рендеринга.
+///
+/// Принимает новое время `start_at` (RFC3339) или режим `start_when_idle`;
+/// время в прошлом запускает задачу сразу. Возвращает `false`, если задача
+/// уже запущена или не найдена.
+#[tauri::command]
+pub async fn update_render_schedule(
+  job_id: String,
+  start_at: Option<String>,
+  start_when_idle: Option<bool>,
+  idle_minutes: Option<u32>,
+  state: State<'_, VideoCompilerState>,
+) -> Result<bool> {
+  crate::instrumented_command!(update_render_schedule, {
+    let schedule = RenderSchedule::from_request(start_at, start_when_idle, idle_minutes)?
+      .ok_or_else(|| VideoCompilerError::validation("Не задано новое время запуска"))?;
+    let render_service = state
+      .services
+      .get_render_service()
+      .ok_or_else(|| VideoCompilerError::validation("RenderService не найден"))?;
+
+    render_service
+      .update_render_schedule(&job_id, schedule)
+      .await
+  })
 }
 
 /// Получить статус активных задач рендеринга
 #[tauri::command]
 pub async fn get_active_render_jobs(state: State<'_, VideoCompilerState>) -> Result<Vec<String>> {
-  // Используем RenderService для получения активных задач
-  let render_service = state
-    .services
-    .get_render_service()
-    .ok_or_else(|| VideoCompilerError::validation("RenderService не найден"))?;
-
-  let job_ids = render_service.get_active_jobs().await?;
-  Ok(job_ids)
+  crate::instrumented_command!(get_active_render_jobs, {
+    // Используем RenderService для получения активных задач
+    let render_service = state
+      .services
+      .get_render_service()
+      .ok_or_else(|| VideoCompilerError::validation("RenderService не найден"))?;
+
+    let job_ids = render_service.get_active_jobs().await?;
+    Ok(job_ids)
+  })
 }
 
-/// Получить информацию о конкретной задаче рендеринга
+/// Получить информацию о конкретной задаче рендеринга.
+///
+/// Задачи `compile_video` берутся из RenderService вместе с итоговыми
+/// приоритетом и числом потоков FFmpeg.
 #[tauri::command]
 pub async fn get_render_job(
   job_id: String,
   state: State<'_, VideoCompilerState>,
 ) -> Result<Option<RenderJob>> {
-  let jobs = state.active_jobs.read().await;
+  crate::instrumented_command!(get_render_job, {
+    let jobs = state.active_jobs.read().await;
+
+    if let Some(active_job) = jobs.get(&job_id) {
+      let progress = active_job.renderer.get_progress().await;
+      let status = if progress.is_some() {
+        RenderStatus::Processing
+      } else {
+        RenderStatus::Queued
+      };
+
+      return Ok(Some(RenderJob {
+        id: job_id,
+        project_name: active_job.metadata.project_name.clone(),
+        output_path: active_job.metadata.output_path.clone(),
+        status,
+        created_at: active_job.metadata.created_at.clone(),
+        progress,
+        error_message: None,
+        process_limits: Some(active_job.renderer.process_limits()),
+        downgrades: active_job.renderer.get_downgrades().await,
+      }));
+    }
+    drop(jobs);
 
-  if let Some(active_job) = jobs.get(&job_id) {
-    let progress = active_job.renderer.get_progress().await;
-    let status = if progress.is_some() {
-      crate::video_compiler::progress::RenderStatus::Processing
-    } else {
-      crate::video_compiler::progress::RenderStatus::Queued
+    let Some(render_service) = state.services.get_render_service() else {
+      return Ok(None);
+    };
+    let Some(info) = render_service.get_job_info(&job_id).await? else {
+      return Ok(None);
+    };
+
+    let status = match info.status {
+      RenderJobStatus::Scheduled => RenderStatus::Scheduled,
+      RenderJobStatus::Initializing => RenderStatus::Preparing,
+      RenderJobStatus::Rendering => RenderStatus::Processing,
+      RenderJobStatus::Paused => RenderStatus::Paused,
+      RenderJobStatus::Completed => RenderStatus::Completed,
+      RenderJobStatus::Failed => RenderStatus::Failed(info.error.clone().unwrap_or_default()),
+      RenderJobStatus::Cancelled => RenderStatus::Cancelled,
     };
 
     Ok(Some(RenderJob {
       id: job_id,
-      project_name: active_job.metadata.project_name.clone(),
-      output_path: active_job.metadata.output_path.clone(),
+      project_name: info.project_name,
+      output_path: info
+        .output_path
+        .map(|path| path.to_string_lossy().to_string())
+        .unwrap_or_default(),
       status,
-      created_at: active_job.metadata.created_at.clone(),
-      progress,
-      error_message: None,
+      created_at: info.created_at.to_rfc3339(),
+      progress: info.progress,
+      error_message: info.error,
+      process_limits: Some(info.process_limits),
+      downgrades: info.downgrades,
     }))
-  } else {
-    Ok(None)
-  }
+  })
 }
 
 /// Приостановить рендеринг
 #[tauri::command]
 pub async fn pause_render(job_id: String, state: State<'_, VideoCompilerState>) -> Result<()> {
-  let jobs = state.active_jobs.read().await;
-
-  if let Some(_active_job) = jobs.get(&job_id) {
-    // VideoRenderer не поддерживает паузу, используем заглушку
-    // В реальной реализации здесь должна быть логика приостановки рендеринга
-    Ok(())
-  } else {
-    Err(VideoCompilerError::InternalError(format!(
-      "Render job '{job_id}' not found"
-    )))
-  }
+  crate::instrumented_command!(pause_render, {
+    let jobs = state.active_jobs.read().await;
+
+    if let Some(_active_job) = jobs.get(&job_id) {
+      // VideoRenderer не поддерживает паузу, используем заглушку
+      // В реальной реализации здесь должна быть логика приостановки рендеринга
+      Ok(())
+    } else {
+      Err(VideoCompilerError::InternalError(format!(
+        "Render job '{job_id}' not found"
+      )))
+    }
+  })
 }
 
 /// Возобновить рендеринг
 #[tauri::command]
 pub async fn resume_render(job_id: String, state: State<'_, VideoCompilerState>) -> Result<()> {
-  let jobs = state.active_jobs.read().await;
-
-  if let Some(_active_job) = jobs.get(&job_id) {
-    // VideoRenderer не поддерживает возобновление, используем заглушку
-    // В реальной реализации здесь должна быть логика возобновления рендеринга
-    Ok(())
-  } else {
-    Err(VideoCompilerError::InternalError(format!(
-      "Render job '{job_id}' not found"
-    )))
-  }
+  crate::instrumented_command!(resume_render, {
+    let jobs = state.active_jobs.read().await;
+
+    if let Some(_active_job) = jobs.get(&job_id) {
+      // VideoRenderer не поддерживает возобновление, используем заглушку
+      // В реальной реализации здесь должна быть логика возобновления рендеринга
+      Ok(())
+    } else {
+      Err(VideoCompilerError::InternalError(format!(
+        "Render job '{job_id}' not found"
+      )))
+    }
+  })
 }
 
 /// Экспортировать проект с предустановленными настройками
@@ -138,36 +390,41 @@ pub async fn export_with_preset<R: tauri::Runtime>(
   preset: String,
   state: State<'_, VideoCompilerState>,
 ) -> Result<String> {
-  // Применяем предустановки к настройкам экспорта
-  let mut schema = project_schema;
-  match preset.as_str() {
-    "youtube" => {
-      schema.settings.export.format = crate::video_compiler::schema::OutputFormat::Mp4;
-      schema.settings.export.video_bitrate = 8000;
-      schema.settings.export.audio_bitrate = 192;
-      schema.settings.export.quality = 90;
-    }
-    "instagram" => {
-      schema.settings.export.format = crate::video_compiler::schema::OutputFormat::Mp4;
-      schema.settings.export.video_bitrate = 5000;
-      schema.settings.export.audio_bitrate = 128;
-      schema.settings.export.quality = 85;
-    }
-    "twitter" => {
-      schema.settings.export.format = crate::video_compiler::schema::OutputFormat::Mp4;
-      schema.settings.export.video_bitrate = 6000;
-      schema.settings.export.audio_bitrate = 128;
-      schema.settings.export.quality = 85;
-    }
-    _ => {
+  crate::instrumented_command!(export_with_preset, {
+    // Применяем предустановки к настройкам экспорта
+    let mut schema = project_schema;
+    if !schema.settings.export.apply_preset(&preset) {
       return Err(VideoCompilerError::InvalidParameter(format!(
         "Unknown preset: {preset}"
       )));
     }
-  }
 
-  // Запускаем обычный рендеринг с измененными настройками
-  compile_video(app, schema, output_path, state).await
+    // Запускаем обычный рендеринг с измененными настройками
+    compile_video(
+      app,
+      schema,
+      Some(output_path),
+      None,
+      None,
+      None,
+      None,
+      None,
+      None,
+      None,
+      state,
+    )
+    .await
+  })
+}
+
+/// Проверить шаблон имени выходного файла и получить пример имени
+#[tauri::command]
+pub async fn validate_filename_template(
+  template: String,
+  project_schema: Option<ProjectSchema>,
+) -> Result<FilenameTemplateValidation> {
+  let project = project_schema.unwrap_or_else(|| ProjectSchema::new("Project".to_string()));
+  Ok(output_template::validate_template(&template, &project))
 }
 
 /// Получить статистику рендеринга для активной задачи
@@ -176,29 +433,31 @@ pub async fn get_render_pipeline_statistics_original(
   job_id: String,
   state: State<'_, VideoCompilerState>,
 ) -> Result<serde_json::Value> {
-  // Проверяем, что задача существует
-  let active_jobs = state.active_jobs.read().await;
-  let job = active_jobs.get(&job_id).ok_or_else(|| {
-    VideoCompilerError::InvalidParameter(format!("Render job {job_id} not found"))
-  })?;
-
-  // Получаем статистику из рендерера задачи
-  let stats = job.renderer.get_render_statistics().ok_or_else(|| {
-    VideoCompilerError::InternalError("No pipeline statistics available".to_string())
-  })?;
-
-  Ok(serde_json::json!({
-    "job_id": job_id,
-    "frames_processed": stats.frames_processed,
-    "memory_used": stats.memory_used,
-    "error_count": stats.error_count,
-    "warning_count": stats.warning_count,
-    "validation_time": stats.validation_time.duration_since(std::time::UNIX_EPOCH).unwrap().as_secs(),
-    "preprocessing_time": stats.preprocessing_time.duration_since(std::time::UNIX_EPOCH).unwrap().as_secs(),
-    "composition_time": stats.composition_time.duration_since(std::time::UNIX_EPOCH).unwrap().as_secs(),
-    "encoding_time": stats.encoding_time.duration_since(std::time::UNIX_EPOCH).unwrap().as_secs(),
-    "finalization_time": stats.finalization_time.duration_since(std::time::UNIX_EPOCH).unwrap().as_secs(),
-  }))
+  crate::instrumented_command!(get_render_pipeline_statistics_original, {
+    // Проверяем, что задача существует
+    let active_jobs = state.active_jobs.read().await;
+    let job = active_jobs.get(&job_id).ok_or_else(|| {
+      VideoCompilerError::InvalidParameter(format!("Render job {job_id} not found"))
+    })?;
+
+    // Получаем статистику из рендерера задачи
+    let stats = job.renderer.get_render_statistics().ok_or_else(|| {
+      VideoCompilerError::InternalError("No pipeline statistics available".to_string())
+    })?;
+
+    Ok(serde_json::json!({
+      "job_id": job_id,
+      "frames_processed": stats.frames_processed,
+      "memory_used": stats.memory_used,
+      "error_count": stats.error_count,
+      "warning_count": stats.warning_count,
+      "validation_time": stats.validation_time.duration_since(std::time::UNIX_EPOCH).unwrap().as_secs(),
+      "preprocessing_time": stats.preprocessing_time.duration_since(std::time::UNIX_EPOCH).unwrap().as_secs(),
+      "composition_time": stats.composition_time.duration_since(std::time::UNIX_EPOCH).unwrap().as_secs(),
+      "encoding_time": stats.encoding_time.duration_since(std::time::UNIX_EPOCH).unwrap().as_secs(),
+      "finalization_time": stats.finalization_time.duration_since(std::time::UNIX_EPOCH).unwrap().as_secs(),
+    }))
+  })
 }
 
 /// Построить команду рендеринга с кастомными настройками FFmpeg
@@ -209,52 +468,57 @@ pub async fn build_render_command_with_settings(
   settings: serde_json::Value,
   state: State<'_, VideoCompilerState>,
 ) -> Result<Vec<String>> {
-  use crate::video_compiler::ffmpeg_builder::{builder::FFmpegBuilderSettings, FFmpegBuilder};
-
-  let ffmpeg_path = state.ffmpeg_path.read().await.clone();
-  let use_hw = settings
-    .get("use_hardware_acceleration")
-    .and_then(|v| v.as_bool())
-    .unwrap_or(false);
-  let hw_type = settings
-    .get("hardware_acceleration_type")
-    .and_then(|v| v.as_str())
-    .map(String::from);
-  let global_options = settings
-    .get("global_options")
-    .and_then(|v| v.as_array())
-    .map(|arr| {
-      arr
-        .iter()
-        .filter_map(|v| v.as_str().map(String::from))
-        .collect()
-    })
-    .unwrap_or_default();
-
-  let builder_settings = FFmpegBuilderSettings {
-    ffmpeg_path: ffmpeg_path.clone(),
-    use_hardware_acceleration: use_hw,
-    hardware_acceleration_type: hw_type,
-    global_options,
-  };
+  crate::instrumented_command!(build_render_command_with_settings, {
+    use crate::video_compiler::ffmpeg_builder::{builder::FFmpegBuilderSettings, FFmpegBuilder};
+
+    let ffmpeg_path = state.ffmpeg_path.read().await.clone();
+    let use_hw = settings
+      .get("use_hardware_acceleration")
+      .and_then(|v| v.as_bool())
+      .unwrap_or(false);
+    let hw_type = settings
+      .get("hardware_acceleration_type")
+      .and_then(|v| v.as_str())
+      .map(String::from);
+    let global_options = settings
+      .get("global_options")
+      .and_then(|v| v.as_array())
+      .map(|arr| {
+        arr
+          .iter()
+          .filter_map(|v| v.as_str().map(String::from))
+          .collect()
+      })
+      .unwrap_or_default();
+
+    let builder_settings = FFmpegBuilderSettings {
+      ffmpeg_path: ffmpeg_path.clone(),
+      use_hardware_acceleration: use_hw,
+      hardware_acceleration_type: hw_type,
+      hardware_decoding: false,
+      global_options,
+      soft_subtitles_path: None,
+      process_limits: Default::default(),
+    };
 
-  let builder = FFmpegBuilder::with_settings(project_schema, builder_settings);
-  let command = builder
-    .build_render_command(std::path::Path::new(&output_path))
-    .await?;
+    let builder = FFmpegBuilder::with_settings(project_schema, builder_settings);
+    let command = builder
+      .build_render_command(std::path::Path::new(&output_path))
+      .await?;
 
-  // Конвертируем Command в вектор строк для возврата
-  let program = format!("{}", command.as_std().get_program().to_string_lossy());
-  let args: Vec<String> = command
-    .as_std()
-    .get_args()
-    .map(|arg| arg.to_string_lossy().to_string())
-    .collect();
+    // Конвертируем Command в вектор строк для возврата
+    let program = format!("{}", command.as_std().get_program().to_string_lossy());
+    let args: Vec<String> = command
+      .as_std()
+      .get_args()
+      .map(|arg| arg.to_string_lossy().to_string())
+      .collect();
 
-  let mut result = vec![program];
-  result.extend(args);
+    let mut result = vec![program];
+    result.extend(args);
 
-  Ok(result)
+    Ok(result)
+  })
 }
 
 /// Извлечь кадры для клипа
@@ -264,29 +528,31 @@ pub async fn extract_frames_for_clip_original(
   _timestamps: Vec<f64>,
   state: State<'_, VideoCompilerState>,
 ) -> Result<Vec<String>> {
-  use crate::video_compiler::core::frame_extraction::FrameExtractionManager;
-  use crate::video_compiler::schema::Clip;
-  use std::path::PathBuf;
-
-  // Создаем тестовый клип
-  let clip = Clip::new(PathBuf::from("/tmp/test_video.mp4"), 0.0, 10.0);
-
-  // Получаем менеджер извлечения кадров
-  let extraction_manager = FrameExtractionManager::new(state.cache_manager.clone());
-
-  // Извлекаем кадры
-  let frames = extraction_manager
-    .extract_frames_for_clip(&clip, None)
-    .await?;
-
-  // Конвертируем результаты в base64 строки
-  use base64::Engine;
-  let result: Vec<String> = frames
-    .into_iter()
-    .map(|frame| base64::engine::general_purpose::STANDARD.encode(&frame.data))
-    .collect();
-
-  Ok(result)
+  crate::instrumented_command!(extract_frames_for_clip_original, {
+    use crate::video_compiler::core::frame_extraction::FrameExtractionManager;
+    use crate::video_compiler::schema::Clip;
+    use std::path::PathBuf;
+
+    // Создаем тестовый клип
+    let clip = Clip::new(PathBuf::from("/tmp/test_video.mp4"), 0.0, 10.0);
+
+    // Получаем менеджер извлечения кадров
+    let extraction_manager = FrameExtractionManager::new(state.cache_manager.clone());
+
+    // Извлекаем кадры
+    let frames = extraction_manager
+      .extract_frames_for_clip(&clip, None)
+      .await?;
+
+    // Конвертируем результаты в base64 строки
+    use base64::Engine;
+    let result: Vec<String> = frames
+      .into_iter()
+      .map(|frame| base64::engine::general_purpose::STANDARD.encode(&frame.data))
+      .collect();
+
+    Ok(result)
+  })
 }
 
 /// Извлечь кадры для субтитров
@@ -296,34 +562,36 @@ pub async fn extract_frames_for_subtitles_original(
   video_path: String,
   state: State<'_, VideoCompilerState>,
 ) -> Result<Vec<String>> {
-  use crate::video_compiler::core::frame_extraction::FrameExtractionManager;
-  use crate::video_compiler::schema::Subtitle;
-
-  // Создаем тестовые субтитры
-  let subtitles: Vec<Subtitle> = subtitle_timestamps
-    .iter()
-    .enumerate()
-    .map(|(i, &timestamp)| Subtitle::new(format!("Subtitle {i}"), timestamp, timestamp + 2.0))
-    .collect();
-
-  // Получаем менеджер извлечения кадров
-  let extraction_manager = FrameExtractionManager::new(state.cache_manager.clone());
-
-  // Извлекаем кадры
-  let frames = extraction_manager
-    .extract_frames_for_subtitles(std::path::Path::new(&video_path), &subtitles, None)
-    .await?;
-
-  // Конвертируем результаты в base64 строки
-  use base64::Engine;
-  let result: Vec<String> = frames
-    .into_iter()
-    .map(|subtitle_frame| {
-      base64::engine::general_purpose::STANDARD.encode(&subtitle_frame.frame_data)
-    })
-    .collect();
-
-  Ok(result)
+  crate::instrumented_command!(extract_frames_for_subtitles_original, {
+    use crate::video_compiler::core::frame_extraction::FrameExtractionManager;
+    use crate::video_compiler::schema::Subtitle;
+
+    // Создаем тестовые субтитры
+    let subtitles: Vec<Subtitle> = subtitle_timestamps
+      .iter()
+      .enumerate()
+      .map(|(i, &timestamp)| Subtitle::new(format!("Subtitle {i}"), timestamp, timestamp + 2.0))
+      .collect();
+
+    // Получаем менеджер извлечения кадров
+    let extraction_manager = FrameExtractionManager::new(state.cache_manager.clone());
+
+    // Извлекаем кадры
+    let frames = extraction_manager
+      .extract_frames_for_subtitles(std::path::Path::new(&video_path), &subtitles, None)
+      .await?;
+
+    // Конвертируем результаты в base64 строки
+    use base64::Engine;
+    let result: Vec<String> = frames
+      .into_iter()
+      .map(|subtitle_frame| {
+        base64::engine::general_purpose::STANDARD.encode(&subtitle_frame.frame_data)
+      })
+      .collect();
+
+    Ok(result)
+  })
 }
 
 /// Построить команду превью с использованием FFmpeg Builder
@@ -334,44 +602,46 @@ pub async fn build_preview_command(
   output_path: String,
   _state: State<'_, VideoCompilerState>,
 ) -> Result<Vec<String>> {
-  use crate::video_compiler::ffmpeg_builder::FFmpegBuilder;
-
-  let builder = FFmpegBuilder::new(project_schema.clone());
-  // Для превью используем первый клип проекта
-  let input_path = if let Some(track) = project_schema.tracks.first() {
-    if let Some(clip) = track.clips.first() {
-      match &clip.source {
-        crate::video_compiler::schema::ClipSource::File(path) => std::path::PathBuf::from(path),
-        _ => std::path::PathBuf::from("/tmp/empty.mp4"),
+  crate::instrumented_command!(build_preview_command, {
+    use crate::video_compiler::ffmpeg_builder::FFmpegBuilder;
+
+    let builder = FFmpegBuilder::new(project_schema.clone());
+    // Для превью используем первый клип проекта
+    let input_path = if let Some(track) = project_schema.tracks.first() {
+      if let Some(clip) = track.clips.first() {
+        match &clip.source {
+          crate::video_compiler::schema::ClipSource::File(path) => std::path::PathBuf::from(path),
+          _ => std::path::PathBuf::from("/tmp/empty.mp4"),
+        }
+      } else {
+        std::path::PathBuf::from("/tmp/empty.mp4")
       }
     } else {
       std::path::PathBuf::from("/tmp/empty.mp4")
-    }
-  } else {
-    std::path::PathBuf::from("/tmp/empty.mp4")
-  };
-
-  let command = builder
-    .build_preview_command(
-      &input_path,
-      timestamp,
-      std::path::Path::new(&output_path),
-      (1920, 1080),
-    )
-    .await?;
-
-  // Конвертируем Command в вектор строк
-  let program = format!("{}", command.as_std().get_program().to_string_lossy());
-  let args: Vec<String> = command
-    .as_std()
-    .get_args()
-    .map(|arg| arg.to_string_lossy().to_string())
-    .collect();
-
-  let mut result = vec![program];
-  result.extend(args);
+    };
 
-  Ok(result)
+    let command = builder
+      .build_preview_command(
+        &input_path,
+        timestamp,
+        std::path::Path::new(&output_path),
+        (1920, 1080),
+      )
+      .await?;
+
+    // Конвертируем Command в вектор строк
+    let program = format!("{}", command.as_std().get_program().to_string_lossy());
+    let args: Vec<String> = command
+      .as_std()
+      .get_args()
+      .map(|arg| arg.to_string_lossy().to_string())
+      .collect();
+
+    let mut result = vec![program];
+    result.extend(args);
+
+    Ok(result)
+  })
 }
 
 // build_prerender_segment_command moved to prerender_commands.rs
@@ -382,17 +652,19 @@ pub async fn get_ffmpeg_builder_settings_original(
   project_schema: ProjectSchema,
   _state: State<'_, VideoCompilerState>,
 ) -> Result<serde_json::Value> {
-  use crate::video_compiler::ffmpeg_builder::FFmpegBuilder;
+  crate::instrumented_command!(get_ffmpeg_builder_settings_original, {
+    use crate::video_compiler::ffmpeg_builder::FFmpegBuilder;
 
-  let builder = FFmpegBuilder::new(project_schema);
-  let settings = builder.settings();
+    let builder = FFmpegBuilder::new(project_schema);
+    let settings = builder.settings();
 
-  Ok(serde_json::json!({
-    "ffmpeg_path": settings.ffmpeg_path,
-    "use_hardware_acceleration": settings.use_hardware_acceleration,
-    "hardware_acceleration_type": settings.hardware_acceleration_type,
-    "global_options": settings.global_options,
-  }))
+    Ok(serde_json::json!({
+      "ffmpeg_path": settings.ffmpeg_path,
+      "use_hardware_acceleration": settings.use_hardware_acceleration,
+      "hardware_acceleration_type": settings.hardware_acceleration_type,
+      "global_options": settings.global_options,
+    }))
+  })
 }
 
 /// Получить информацию о проекте из FFmpeg Builder
@@ -401,18 +673,20 @@ pub async fn get_ffmpeg_builder_project_info_original(
   project_schema: ProjectSchema,
   _state: State<'_, VideoCompilerState>,
 ) -> Result<serde_json::Value> {
-  use crate::video_compiler::ffmpeg_builder::FFmpegBuilder;
-
-  let builder = FFmpegBuilder::new(project_schema.clone());
-  let project = builder.project();
-
-  Ok(serde_json::json!({
-    "name": project.metadata.name,
-    "duration": project.timeline.duration,
-    "resolution": project.settings.resolution,
-    "frame_rate": project.settings.frame_rate,
-    "format": project.settings.export.format,
-  }))
+  crate::instrumented_command!(get_ffmpeg_builder_project_info_original, {
+    use crate::video_compiler::ffmpeg_builder::FFmpegBuilder;
+
+    let builder = FFmpegBuilder::new(project_schema.clone());
+    let project = builder.project();
+
+    Ok(serde_json::json!({
+      "name": project.metadata.name,
+      "duration": project.timeline.duration,
+      "resolution": project.settings.resolution,
+      "frame_rate": project.settings.frame_rate,
+      "format": project.settings.export.format,
+    }))
+  })
 }
 
 /// Построить команду рендеринга для сегмента видео
@@ -424,49 +698,54 @@ pub async fn build_segment_render_command(
   output_path: String,
   state: State<'_, VideoCompilerState>,
 ) -> Result<Vec<String>> {
-  use crate::video_compiler::ffmpeg_builder::{
-    filters::FilterBuilder, inputs::InputBuilder, outputs::OutputBuilder, FFmpegBuilder,
-  };
-  use tokio::process::Command;
-
-  let settings = crate::video_compiler::ffmpeg_builder::builder::FFmpegBuilderSettings {
-    ffmpeg_path: state.ffmpeg_path.read().await.clone(),
-    use_hardware_acceleration: state.settings.read().await.hardware_acceleration,
-    hardware_acceleration_type: None,
-    global_options: vec![],
-  };
+  crate::instrumented_command!(build_segment_render_command, {
+    use crate::video_compiler::ffmpeg_builder::{
+      filters::FilterBuilder, inputs::InputBuilder, outputs::OutputBuilder, FFmpegBuilder,
+    };
+    use tokio::process::Command;
+
+    let settings = crate::video_compiler::ffmpeg_builder::builder::FFmpegBuilderSettings {
+      ffmpeg_path: state.ffmpeg_path.read().await.clone(),
+      use_hardware_acceleration: state.settings.read().await.hardware_acceleration,
+      hardware_acceleration_type: None,
+      hardware_decoding: false,
+      global_options: vec![],
+      soft_subtitles_path: None,
+      process_limits: Default::default(),
+    };
 
-  let builder = FFmpegBuilder::with_settings(project_schema.clone(), settings);
-  let mut command = Command::new(state.ffmpeg_path.read().await.clone());
-
-  // Добавляем входные файлы
-  let input_builder = InputBuilder::new(&project_schema);
-  input_builder.add_input_sources(&mut command).await?;
-
-  // Добавляем фильтры для сегмента
-  let filter_builder = FilterBuilder::new(&project_schema);
-  filter_builder
-    .add_segment_filters(&mut command, start_time, end_time)
-    .await?;
-
-  // Добавляем выходные настройки
-  let output_builder = OutputBuilder::new(&project_schema, builder.settings());
-  output_builder
-    .add_output_settings(&mut command, std::path::Path::new(&output_path))
-    .await?;
-
-  // Конвертируем Command в вектор строк
-  let program = format!("{}", command.as_std().get_program().to_string_lossy());
-  let args: Vec<String> = command
-    .as_std()
-    .get_args()
-    .map(|arg| arg.to_string_lossy().to_string())
-    .collect();
-
-  let mut result = vec![program];
-  result.extend(args);
-
-  Ok(result)
+    let builder = FFmpegBuilder::with_settings(project_schema.clone(), settings);
+    let mut command = Command::new(state.ffmpeg_path.read().await.clone());
+
+    // Добавляем входные файлы
+    let input_builder = InputBuilder::new(&project_schema);
+    input_builder.add_input_sources(&mut command).await?;
+
+    // Добавляем фильтры для сегмента
+    let filter_builder = FilterBuilder::new(&project_schema);
+    filter_builder
+      .add_segment_filters(&mut command, start_time, end_time)
+      .await?;
+
+    // Добавляем выходные настройки
+    let output_builder = OutputBuilder::new(&project_schema, builder.settings());
+    output_builder
+      .add_output_settings(&mut command, std::path::Path::new(&output_path))
+      .await?;
+
+    // Конвертируем Command в вектор строк
+    let program = format!("{}", command.as_std().get_program().to_string_lossy());
+    let args: Vec<String> = command
+      .as_std()
+      .get_args()
+      .map(|arg| arg.to_string_lossy().to_string())
+      .collect();
+
+    let mut result = vec![program];
+    result.extend(args);
+
+    Ok(result)
+  })
 }
 
 /// Получить информацию о фильтрах для сегмента
@@ -477,22 +756,24 @@ pub async fn get_segment_filters_info_original(
   end_time: f64,
   _state: State<'_, VideoCompilerState>,
 ) -> Result<serde_json::Value> {
-  use crate::video_compiler::ffmpeg_builder::filters::FilterBuilder;
-
-  let filter_builder = FilterBuilder::new(&project_schema);
-
-  // Проверяем наличие видео и аудио треков
-  let has_video = filter_builder.has_video_tracks();
-  let has_audio = filter_builder.has_audio_tracks();
-
-  Ok(serde_json::json!({
-    "segment_start": start_time,
-    "segment_end": end_time,
-    "duration": end_time - start_time,
-    "has_video_tracks": has_video,
-    "has_audio_tracks": has_audio,
-    "filter_complexity": if has_video && has_audio { "complex" } else { "simple" },
-  }))
+  crate::instrumented_command!(get_segment_filters_info_original, {
+    use crate::video_compiler::ffmpeg_builder::filters::FilterBuilder;
+
+    let filter_builder = FilterBuilder::new(&project_schema);
+
+    // Проверяем наличие видео и аудио треков
+    let has_video = filter_builder.has_video_tracks();
+    let has_audio = filter_builder.has_audio_tracks();
+
+    Ok(serde_json::json!({
+      "segment_start": start_time,
+      "segment_end": end_time,
+      "duration": end_time - start_time,
+      "has_video_tracks": has_video,
+      "has_audio_tracks": has_audio,
+      "filter_complexity": if has_video && has_audio { "complex" } else { "simple" },
+    }))
+  })
 }
 
 /// Проверить корректность временных меток сегмента
@@ -503,28 +784,30 @@ pub async fn validate_segment_timestamps_original(
   end_time: f64,
   _state: State<'_, VideoCompilerState>,
 ) -> Result<serde_json::Value> {
-  let duration = project_schema.timeline.duration;
-
-  let is_valid = start_time >= 0.0 && end_time > start_time && end_time <= duration;
-
-  let warnings = if start_time < 0.0 {
-    Some("Start time cannot be negative")
-  } else if end_time <= start_time {
-    Some("End time must be greater than start time")
-  } else if end_time > duration {
-    Some("End time exceeds project duration")
-  } else {
-    None
-  };
+  crate::instrumented_command!(validate_segment_timestamps_original, {
+    let duration = project_schema.timeline.duration;
+
+    let is_valid = start_time >= 0.0 && end_time > start_time && end_time <= duration;
+
+    let warnings = if start_time < 0.0 {
+      Some("Start time cannot be negative")
+    } else if end_time <= start_time {
+      Some("End time must be greater than start time")
+    } else if end_time > duration {
+      Some("End time exceeds project duration")
+    } else {
+      None
+    };
 
-  Ok(serde_json::json!({
-    "is_valid": is_valid,
-    "start_time": start_time,
-    "end_time": end_time,
-    "segment_duration": end_time - start_time,
-    "project_duration": duration,
-    "warnings": warnings,
-  }))
+    Ok(serde_json::json!({
+      "is_valid": is_valid,
+      "start_time": start_time,
+      "end_time": end_time,
+      "segment_duration": end_time - start_time,
+      "project_duration": duration,
+      "warnings": warnings,
+    }))
+  })
 }
 
 /// Получить кэш менеджера извлечения кадров
@@ -532,18 +815,20 @@ pub async fn validate_segment_timestamps_original(
 pub async fn get_frame_extraction_cache_original(
   state: State<'_, VideoCompilerState>,
 ) -> Result<serde_json::Value> {
-  use crate::video_compiler::core::frame_extraction::FrameExtractionManager;
+  crate::instrumented_command!(get_frame_extraction_cache_original, {
+    use crate::video_compiler::core::frame_extraction::FrameExtractionManager;
 
-  // Создаем менеджер извлечения кадров
-  let extraction_manager = FrameExtractionManager::new(state.cache_manager.clone());
+    // Создаем менеджер извлечения кадров
+    let extraction_manager = FrameExtractionManager::new(state.cache_manager.clone());
 
-  // Используем метод get_cache
-  let _cache = extraction_manager.get_cache();
+    // Используем метод get_cache
+    let _cache = extraction_manager.get_cache();
 
-  Ok(serde_json::json!({
-    "cache_available": true,
-    "message": "Frame extraction cache accessed successfully"
-  }))
+    Ok(serde_json::json!({
+      "cache_available": true,
+      "message": "Frame extraction cache accessed successfully"
+    }))
+  })
 }
 
 /// Получить индекс входа для клипа
@@ -553,24 +838,62 @@ pub async fn get_clip_input_index_original(
   clip_id: String,
   _state: State<'_, VideoCompilerState>,
 ) -> Result<serde_json::Value> {
-  use crate::video_compiler::ffmpeg_builder::inputs::InputBuilder;
+  crate::instrumented_command!(get_clip_input_index_original, {
+    use crate::video_compiler::ffmpeg_builder::inputs::InputBuilder;
 
-  // Создаем InputBuilder
-  let input_builder = InputBuilder::new(&project_schema);
+    // Создаем InputBuilder
+    let input_builder = InputBuilder::new(&project_schema);
 
-  // Используем метод get_clip_input_index
-  let input_index = input_builder.get_clip_input_index(&clip_id);
+    // Используем метод get_clip_input_index
+    let input_index = input_builder.get_clip_input_index(&clip_id);
 
-  Ok(serde_json::json!({
-    "clip_id": clip_id,
-    "input_index": input_index,
-    "found": input_index.is_some()
-  }))
+    Ok(serde_json::json!({
+      "clip_id": clip_id,
+      "input_index": input_index,
+      "found": input_index.is_some()
+    }))
+  })
 }
 
+crate::command_manifest!(
+  RENDERING_MANIFEST,
+  "video_compiler::rendering",
+  [
+    compile_video,
+    cancel_render,
+    list_scheduled_renders,
+    update_render_schedule,
+    get_active_render_jobs,
+    get_render_job,
+    pause_render,
+    resume_render,
+    export_with_preset,
+    validate_filename_template,
+    check_export_settings,
+    build_render_command_with_settings,
+    build_preview_command,
+    build_segment_render_command,
+  ],
+  internal: [
+    get_render_pipeline_statistics_original,
+    extract_frames_for_clip_original,
+    extract_frames_for_subtitles_original,
+    get_ffmpeg_builder_settings_original,
+    get_ffmpeg_builder_project_info_original,
+    get_segment_filters_info_original,
+    validate_segment_timestamps_original,
+    get_frame_extraction_cache_original,
+    get_clip_input_index_original,
+  ]
+);
+
 #[cfg(test)]
 mod tests {
   use crate::video_compiler::schema::{Clip, ProjectSchema, Track, TrackType};
+  use crate::video_compiler::tests::mocks::{
+    create_mock_state_with_services, mock_services_builder, MockRenderService,
+  };
+  use std::sync::Arc;
 
   fn create_test_project() -> ProjectSchema {
     let mut project = ProjectSchema::new("Test Project".to_string());
@@ -734,4 +1057,33 @@ mod tests {
 
     assert_eq!(duration, 5.0);
   }
+
+  #[tokio::test]
+  async fn test_render_commands_use_installed_render_service() {
+    let render = Arc::new(MockRenderService::default());
+    let services = mock_services_builder()
+      .with_render(render.clone())
+      .build()
+      .await
+      .unwrap();
+    services.initialize_all().await.unwrap();
+    services.health_check_all().await.unwrap();
+    let state = create_mock_state_with_services(services);
+
+    // Тот же путь, что и в compile_video / cancel_render
+    let render_service = state.services.get_render_service().unwrap();
+    let job_id = render_service
+      .start_render(create_test_project(), "/tmp/out.mp4".into())
+      .await
+      .unwrap();
+    assert_eq!(job_id, "mock-job-1");
+    assert!(render_service.cancel_render(&job_id).await.unwrap());
+
+    assert_eq!(
+      render_service.get_active_jobs().await.unwrap(),
+      vec!["mock-job-1"]
+    );
+    assert_eq!(render.started.lock().unwrap()[0].0, "Test Project");
+    assert_eq!(*render.cancelled.lock().unwrap(), vec!["mock-job-1"]);
+  }
 }
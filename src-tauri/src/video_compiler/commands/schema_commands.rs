@@ -6,9 +6,12 @@
 
 use crate::video_compiler::error::{Result, VideoCompilerError};
 use crate::video_compiler::schema::{
-  timeline::{Clip, ClipProperties, ClipSource},
-  Effect, Filter, StyleTemplate, Subtitle, Template, Track, TrackType,
+  clip_attributes::{ClipAttributeMask, ClipAttributes, PasteMode, PasteTargetSummary},
+  gaps::{RippleDeleteOptions, RippleDeleteSummary, TimelineGap},
+  timeline::{Clip, ClipMetadata, ClipProperties, ClipSearchMatch, ClipSearchQuery, ClipSource},
+  Effect, Filter, ProjectSchema, StyleTemplate, Subtitle, Template, Track, TrackType,
 };
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Добавить клип в трек
@@ -23,6 +26,12 @@ pub async fn add_clip_to_track(
     .iter_mut()
     .find(|t| t.id == track_id)
     .ok_or_else(|| VideoCompilerError::InvalidParameter(format!("Track not found: {track_id}")))?;
+  if track.locked {
+    return Err(VideoCompilerError::Locked {
+      clip_id: clip.id,
+      track_id,
+    });
+  }
 
   track.clips.push(clip);
   track
@@ -32,6 +41,385 @@ pub async fn add_clip_to_track(
   Ok(project_schema)
 }
 
+/// Изменения клипа (не заданные поля не меняются)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClipChanges {
+  pub start_time: Option<f64>,
+  pub end_time: Option<f64>,
+  pub source_start: Option<f64>,
+  pub source_end: Option<f64>,
+  pub speed: Option<f64>,
+  pub opacity: Option<f32>,
+  pub effects: Option<Vec<String>>,
+  pub filters: Option<Vec<String>>,
+  pub locked: Option<bool>,
+}
+
+impl ClipChanges {
+  /// Снимает ли изменение только блокировку клипа
+  fn is_unlock_only(&self) -> bool {
+    self.locked == Some(false)
+      && self.start_time.is_none()
+      && self.end_time.is_none()
+      && self.source_start.is_none()
+      && self.source_end.is_none()
+      && self.speed.is_none()
+      && self.opacity.is_none()
+      && self.effects.is_none()
+      && self.filters.is_none()
+  }
+
+  /// Применить изменения к клипу
+  fn apply(self, clip: &mut Clip) {
+    if let Some(start_time) = self.start_time {
+      clip.start_time = start_time;
+    }
+    if let Some(end_time) = self.end_time {
+      clip.end_time = end_time;
+    }
+    if let Some(source_start) = self.source_start {
+      clip.source_start = source_start;
+    }
+    if let Some(source_end) = self.source_end {
+      clip.source_end = source_end;
+    }
+    if let Some(speed) = self.speed {
+      clip.speed = speed;
+    }
+    if let Some(opacity) = self.opacity {
+      clip.opacity = opacity;
+    }
+    if let Some(effects) = self.effects {
+      clip.effects = effects;
+    }
+    if let Some(filters) = self.filters {
+      clip.filters = filters;
+    }
+    if let Some(locked) = self.locked {
+      clip.locked = locked;
+    }
+  }
+}
+
+/// Найти клип и проверить, что его можно изменять.
+///
+/// Возвращает индексы трека и клипа. Клипы на заблокированных треках не
+/// изменяются никогда, заблокированный клип - только если снимается блокировка.
+pub(crate) fn find_editable_clip(
+  project: &ProjectSchema,
+  clip_id: &str,
+  allow_unlock: bool,
+) -> Result<(usize, usize)> {
+  let (track_idx, clip_idx) = project
+    .find_clip_position(clip_id)
+    .ok_or_else(|| VideoCompilerError::InvalidParameter(format!("Clip not found: {clip_id}")))?;
+
+  let track = &project.tracks[track_idx];
+  let clip = &track.clips[clip_idx];
+  if track.locked || (clip.locked && !allow_unlock) {
+    return Err(VideoCompilerError::Locked {
+      clip_id: clip_id.to_string(),
+      track_id: track.id.clone(),
+    });
+  }
+
+  Ok((track_idx, clip_idx))
+}
+
+/// Изменить клип с учетом блокировок
+#[tauri::command]
+pub async fn update_clip(
+  mut project_schema: ProjectSchema,
+  clip_id: String,
+  changes: ClipChanges,
+) -> Result<ProjectSchema> {
+  let (track_idx, clip_idx) =
+    find_editable_clip(&project_schema, &clip_id, changes.is_unlock_only())?;
+
+  let track = &mut project_schema.tracks[track_idx];
+  changes.apply(&mut track.clips[clip_idx]);
+  track.clips[clip_idx]
+    .validate()
+    .map_err(VideoCompilerError::validation)?;
+  track
+    .clips
+    .sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+
+  project_schema.touch();
+  Ok(project_schema)
+}
+
+/// Переместить клип на другой трек и/или позицию с учетом блокировок
+#[tauri::command]
+pub async fn move_clip(
+  mut project_schema: ProjectSchema,
+  clip_id: String,
+  new_track_id: String,
+  new_start: f64,
+) -> Result<ProjectSchema> {
+  if !new_start.is_finite() || new_start < 0.0 {
+    return Err(VideoCompilerError::InvalidParameter(format!(
+      "Invalid clip start: {new_start}"
+    )));
+  }
+
+  let (track_idx, clip_idx) = find_editable_clip(&project_schema, &clip_id, false)?;
+  let target_idx = project_schema
+    .tracks
+    .iter()
+    .position(|t| t.id == new_track_id)
+    .ok_or_else(|| {
+      VideoCompilerError::InvalidParameter(format!("Track not found: {new_track_id}"))
+    })?;
+  if project_schema.tracks[target_idx].locked {
+    return Err(VideoCompilerError::Locked {
+      clip_id,
+      track_id: new_track_id,
+    });
+  }
+
+  let mut clip = project_schema.tracks[track_idx].clips.remove(clip_idx);
+  let duration = clip.end_time - clip.start_time;
+  clip.start_time = new_start;
+  clip.end_time = new_start + duration;
+  project_schema.tracks[target_idx].add_clip(clip);
+
+  project_schema.touch();
+  Ok(project_schema)
+}
+
+/// Сгруппировать клипы во вложенную последовательность (составной клип)
+#[tauri::command]
+pub async fn create_sequence_from_clips(
+  mut project: ProjectSchema,
+  clip_ids: Vec<String>,
+  name: String,
+) -> Result<ProjectSchema> {
+  for clip_id in &clip_ids {
+    find_editable_clip(&project, clip_id, false)?;
+  }
+
+  project
+    .create_sequence_from_clips(&clip_ids, name)
+    .map_err(VideoCompilerError::validation)?;
+  project.validate().map_err(VideoCompilerError::validation)?;
+
+  Ok(project)
+}
+
+/// Развернуть составные клипы последовательности обратно на timeline
+#[tauri::command]
+pub async fn flatten_sequence(
+  mut project: ProjectSchema,
+  sequence_id: String,
+) -> Result<ProjectSchema> {
+  for track in project.tracks.iter().filter(|track| track.locked) {
+    if let Some(clip) = track
+      .clips
+      .iter()
+      .find(|clip| clip.sequence_id() == Some(sequence_id.as_str()))
+    {
+      return Err(VideoCompilerError::Locked {
+        clip_id: clip.id.clone(),
+        track_id: track.id.clone(),
+      });
+    }
+  }
+
+  project
+    .flatten_sequence(&sequence_id)
+    .map_err(VideoCompilerError::InvalidParameter)?;
+
+  Ok(project)
+}
+
+/// Найти пустые промежутки включенных треков (или одного трека)
+#[tauri::command]
+pub async fn detect_timeline_gaps(
+  project: ProjectSchema,
+  track_id: Option<String>,
+) -> Result<Vec<TimelineGap>> {
+  project
+    .detect_gaps(track_id.as_deref())
+    .map_err(VideoCompilerError::InvalidParameter)
+}
+
+/// Результат закрытия промежутков
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RippleDeleteResult {
+  pub project: ProjectSchema,
+  pub summary: RippleDeleteSummary,
+}
+
+/// Закрыть промежутки треков, сдвинув последующие клипы влево.
+///
+/// Связанные клипы сдвигаются вместе, заблокированные треки не изменяются и
+/// перечисляются в итоге как пропущенные.
+#[tauri::command]
+pub async fn ripple_delete_gaps(
+  mut project: ProjectSchema,
+  options: RippleDeleteOptions,
+) -> Result<RippleDeleteResult> {
+  if !options.min_gap_to_keep.is_finite() || options.min_gap_to_keep < 0.0 {
+    return Err(VideoCompilerError::InvalidParameter(format!(
+      "Invalid min_gap_to_keep: {}",
+      options.min_gap_to_keep
+    )));
+  }
+
+  let summary = project
+    .ripple_delete_gaps(&options)
+    .map_err(VideoCompilerError::validation)?;
+
+  Ok(RippleDeleteResult { project, summary })
+}
+
+/// Позиция timeline в timecode проекта для отображения в интерфейсе.
+///
+/// Частота и drop-frame берутся из настроек проекта, отсчет начинается со
+/// стартового timecode проекта.
+#[tauri::command]
+pub async fn format_timeline_position(project: ProjectSchema, seconds: f64) -> Result<String> {
+  if !seconds.is_finite() {
+    return Err(VideoCompilerError::InvalidParameter(format!(
+      "Invalid timeline position: {seconds}"
+    )));
+  }
+
+  Ok(project.timecode_at(seconds).to_string())
+}
+
+/// Задать цветовую метку, заметки и теги клипа.
+///
+/// Метаданные не влияют на рендер, поэтому меняются и у заблокированных клипов.
+#[tauri::command]
+pub async fn set_clip_metadata(
+  mut project_schema: ProjectSchema,
+  clip_id: String,
+  metadata: ClipMetadata,
+) -> Result<ProjectSchema> {
+  let (track_idx, clip_idx) = project_schema
+    .find_clip_position(&clip_id)
+    .ok_or_else(|| VideoCompilerError::InvalidParameter(format!("Clip not found: {clip_id}")))?;
+
+  project_schema.tracks[track_idx].clips[clip_idx].set_metadata(metadata);
+  project_schema.touch();
+  Ok(project_schema)
+}
+
+/// Найти клипы по тегам, цветовой метке, тексту, типу трека и интервалу
+#[tauri::command]
+pub async fn find_clips(
+  project_schema: ProjectSchema,
+  query: ClipSearchQuery,
+) -> Result<Vec<ClipSearchMatch>> {
+  if let Some((start, end)) = query.time_range {
+    if !start.is_finite() || !end.is_finite() || end <= start {
+      return Err(VideoCompilerError::InvalidParameter(format!(
+        "Invalid time range: {start}..{end}"
+      )));
+    }
+  }
+  Ok(project_schema.find_clips(&query))
+}
+
+/// Изменить несколько клипов одинаково.
+///
+/// Изменение применяется ко всем клипам или ни к одному: блокировка или
+/// ошибка валидации любого клипа отменяет всю операцию.
+#[tauri::command]
+pub async fn update_clips(
+  mut project_schema: ProjectSchema,
+  clip_ids: Vec<String>,
+  changes: ClipChanges,
+) -> Result<ProjectSchema> {
+  let allow_unlock = changes.is_unlock_only();
+  let positions = clip_ids
+    .iter()
+    .map(|clip_id| find_editable_clip(&project_schema, clip_id, allow_unlock))
+    .collect::<Result<Vec<_>>>()?;
+
+  for &(track_idx, clip_idx) in &positions {
+    let clip = &mut project_schema.tracks[track_idx].clips[clip_idx];
+    changes.clone().apply(clip);
+    clip.validate().map_err(VideoCompilerError::validation)?;
+  }
+  for track in &mut project_schema.tracks {
+    track
+      .clips
+      .sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+  }
+
+  project_schema.touch();
+  Ok(project_schema)
+}
+
+/// Применить эффект ко всем указанным клипам.
+///
+/// Эффект добавляется в проект, если его там еще нет; клипы, у которых он
+/// уже есть, не меняются.
+#[tauri::command]
+pub async fn apply_effect_to_clips(
+  mut project_schema: ProjectSchema,
+  clip_ids: Vec<String>,
+  effect: Effect,
+) -> Result<ProjectSchema> {
+  let positions = clip_ids
+    .iter()
+    .map(|clip_id| find_editable_clip(&project_schema, clip_id, false))
+    .collect::<Result<Vec<_>>>()?;
+
+  if !project_schema.effects.iter().any(|e| e.id == effect.id) {
+    project_schema.effects.push(effect.clone());
+  }
+  for (track_idx, clip_idx) in positions {
+    let clip = &mut project_schema.tracks[track_idx].clips[clip_idx];
+    if !clip.effects.contains(&effect.id) {
+      clip.effects.push(effect.id.clone());
+    }
+  }
+
+  project_schema.touch();
+  Ok(project_schema)
+}
+
+/// Скопировать атрибуты клипа, выбранные в маске
+#[tauri::command]
+pub async fn copy_clip_attributes(
+  project: ProjectSchema,
+  source_clip_id: String,
+  attribute_mask: ClipAttributeMask,
+) -> Result<ClipAttributes> {
+  project
+    .copy_clip_attributes(&source_clip_id, attribute_mask)
+    .map_err(VideoCompilerError::InvalidParameter)
+}
+
+/// Результат вставки атрибутов клипа
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasteClipAttributesResult {
+  pub project: ProjectSchema,
+  pub targets: Vec<PasteTargetSummary>,
+}
+
+/// Вставить скопированные атрибуты в клипы.
+///
+/// Заблокированные клипы пропускаются, атрибуты изображения не применяются к
+/// аудио клипам; итог по каждому клипу возвращается вместе с проектом.
+#[tauri::command]
+pub async fn paste_clip_attributes(
+  mut project: ProjectSchema,
+  target_clip_ids: Vec<String>,
+  attributes: ClipAttributes,
+  mode: PasteMode,
+) -> Result<PasteClipAttributesResult> {
+  let targets = project
+    .paste_clip_attributes(&target_clip_ids, &attributes, mode)
+    .map_err(VideoCompilerError::validation)?;
+
+  Ok(PasteClipAttributesResult { project, targets })
+}
+
 /// Создать клип
 #[tauri::command]
 pub async fn create_clip(source_path: String, start_time: f64, end_time: f64) -> Result<Clip> {
@@ -52,6 +440,18 @@ pub async fn create_clip(source_path: String, start_time: f64, end_time: f64) ->
     crop: None,
     transform: None,
     audio_track_index: None,
+    audio_stream_index: None,
+    source_rotation: 0,
+    color_label: None,
+    notes: None,
+    tags: Vec::new(),
+    fade_in: None,
+    fade_out: None,
+    link_group_id: None,
+    source_timecode: None,
+    volume: 1.0,
+    external_audio: None,
+    locked: false,
     properties: ClipProperties {
       notes: None,
       tags: vec![],
@@ -93,6 +493,7 @@ pub async fn create_effect(
     css_filter: None,
     preview_path: None,
     presets: None,
+    keyframes: HashMap::new(),
   })
 }
 
@@ -170,6 +571,7 @@ pub async fn create_subtitle(
     style: crate::video_compiler::schema::subtitles::SubtitleStyle::default(),
     enabled: true,
     animations: vec![],
+    language: None,
     font_family: "Arial".to_string(),
     font_size: 24.0,
     color: "#FFFFFF".to_string(),
@@ -353,9 +755,11 @@ pub async fn create_track(
     enabled: true,
     volume: 1.0,
     locked: false,
+    hidden: false,
     clips: vec![],
     effects: vec![],
     filters: vec![],
+    ducking: None,
   };
 
   project.tracks.push(track);
@@ -518,6 +922,43 @@ pub async fn create_resolution_for_format(
   Ok(resolution)
 }
 
+crate::command_manifest!(
+  SCHEMA_COMMANDS_MANIFEST,
+  "video_compiler::schema_commands",
+  [
+    add_clip_to_track,
+    update_clip,
+    move_clip,
+    create_sequence_from_clips,
+    flatten_sequence,
+    detect_timeline_gaps,
+    ripple_delete_gaps,
+    format_timeline_position,
+    set_clip_metadata,
+    find_clips,
+    update_clips,
+    apply_effect_to_clips,
+    copy_clip_attributes,
+    paste_clip_attributes,
+    create_clip,
+    create_effect,
+    create_filter,
+    create_style_template,
+    create_subtitle,
+    create_subtitle_animation,
+    create_subtitle_animation_new,
+    create_style_template_new,
+    create_template,
+    create_track,
+    create_schema_objects,
+    create_resolution,
+    get_hd_resolution,
+    get_uhd_4k_resolution,
+    get_preset_resolutions,
+    create_resolution_for_format,
+  ]
+);
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -526,6 +967,7 @@ mod tests {
     effects::{EffectType, FilterType},
     subtitles::{SubtitleAnimationType, SubtitleEasing},
     templates::{StyleTemplateCategory, StyleTemplateStyle, TemplateType},
+    timeline::ClipColorLabel,
     ProjectSchema, Timeline, Track, TrackType,
   };
   use std::collections::HashMap;
@@ -546,6 +988,7 @@ mod tests {
         resolution: (1920, 1080),
         sample_rate: 48000,
         aspect_ratio: AspectRatio::Ratio16x9,
+        markers: Vec::new(),
       },
       tracks: vec![Track {
         id: "track1".to_string(),
@@ -554,15 +997,18 @@ mod tests {
         enabled: true,
         volume: 1.0,
         locked: false,
+        hidden: false,
         clips: vec![],
         effects: vec![],
         filters: vec![],
+        ducking: None,
       }],
       effects: vec![],
       transitions: vec![],
       filters: vec![],
       templates: vec![],
       style_templates: vec![],
+      sequences: Default::default(),
       subtitles: vec![],
       settings: crate::video_compiler::schema::ProjectSettings::default(),
     }
@@ -778,6 +1224,7 @@ mod tests {
       style: crate::video_compiler::schema::subtitles::SubtitleStyle::default(),
       enabled: true,
       animations: vec![],
+      language: None,
       font_family: "Arial".to_string(),
       font_size: 24.0,
       color: "#FFFFFF".to_string(),
@@ -1168,4 +1615,396 @@ mod tests {
       );
     }
   }
+
+  fn project_with_two_tracks() -> ProjectSchema {
+    let mut project = create_test_project();
+    let mut clip = Clip::new(std::path::PathBuf::from("/tmp/a.mp4"), 0.0, 5.0);
+    clip.id = "clip1".to_string();
+    project.tracks[0].clips.push(clip);
+
+    let mut track2 = Track::new(TrackType::Video, "Overlay".to_string());
+    track2.id = "track2".to_string();
+    project.tracks.push(track2);
+    project
+  }
+
+  #[tokio::test]
+  async fn test_update_clip_respects_locks() {
+    let mut project = project_with_two_tracks();
+    let changes = ClipChanges {
+      opacity: Some(0.5),
+      ..Default::default()
+    };
+
+    let updated = update_clip(project.clone(), "clip1".to_string(), changes.clone())
+      .await
+      .unwrap();
+    assert_eq!(updated.tracks[0].clips[0].opacity, 0.5);
+
+    project.tracks[0].clips[0].locked = true;
+    let result = update_clip(project.clone(), "clip1".to_string(), changes.clone()).await;
+    assert!(matches!(result, Err(VideoCompilerError::Locked { .. })));
+
+    // Снять блокировку с клипа можно
+    let unlock = ClipChanges {
+      locked: Some(false),
+      ..Default::default()
+    };
+    let unlocked = update_clip(project.clone(), "clip1".to_string(), unlock.clone())
+      .await
+      .unwrap();
+    assert!(!unlocked.tracks[0].clips[0].locked);
+
+    // ...но не на заблокированном треке
+    project.tracks[0].locked = true;
+    let result = update_clip(project, "clip1".to_string(), unlock).await;
+    assert!(matches!(
+      result,
+      Err(VideoCompilerError::Locked { track_id, .. }) if track_id == "track1"
+    ));
+  }
+
+  #[tokio::test]
+  async fn test_move_clip_respects_locks() {
+    let mut project = project_with_two_tracks();
+
+    let moved = move_clip(
+      project.clone(),
+      "clip1".to_string(),
+      "track2".to_string(),
+      3.0,
+    )
+    .await
+    .unwrap();
+    assert!(moved.tracks[0].clips.is_empty());
+    assert_eq!(moved.tracks[1].clips[0].start_time, 3.0);
+    assert_eq!(moved.tracks[1].clips[0].end_time, 8.0);
+
+    project.tracks[1].locked = true;
+    let result = move_clip(
+      project.clone(),
+      "clip1".to_string(),
+      "track2".to_string(),
+      3.0,
+    )
+    .await;
+    assert!(matches!(
+      result,
+      Err(VideoCompilerError::Locked { track_id, .. }) if track_id == "track2"
+    ));
+
+    project.tracks[1].locked = false;
+    project.tracks[0].clips[0].locked = true;
+    let result = move_clip(project, "clip1".to_string(), "track2".to_string(), 3.0).await;
+    assert!(matches!(result, Err(VideoCompilerError::Locked { .. })));
+  }
+
+  /// Синтетический проект из 200 клипов на трех видео- и одном аудиотреке.
+  ///
+  /// Клип `i` лежит на треке `i % 4` с началом `(i / 4) * 2` секунды.
+  fn project_with_labeled_clips() -> ProjectSchema {
+    let mut project = create_test_project();
+    project.tracks.clear();
+    for track_idx in 0..4 {
+      let track_type = if track_idx == 3 {
+        TrackType::Audio
+      } else {
+        TrackType::Video
+      };
+      let mut track = Track::new(track_type, format!("Track {track_idx}"));
+      track.id = format!("track{track_idx}");
+      project.tracks.push(track);
+    }
+
+    for i in 0..200 {
+      let name = if i % 4 == 3 {
+        format!("/media/Music_{i:03}.wav")
+      } else {
+        format!("/media/Shot_{i:03}.mp4")
+      };
+      let mut clip = Clip::new(std::path::PathBuf::from(name), (i / 4) as f64 * 2.0, 2.0);
+      clip.id = format!("clip{i}");
+      let mut tags = Vec::new();
+      if i % 5 == 0 {
+        tags.push("B-Roll".to_string());
+      }
+      if i % 7 == 0 {
+        tags.push("interview".to_string());
+      }
+      clip.set_metadata(ClipMetadata {
+        color_label: match i % 3 {
+          0 => Some(ClipColorLabel::Red),
+          1 => Some(ClipColorLabel::Green),
+          _ => None,
+        },
+        notes: (i % 10 == 0).then(|| "Best TAKE of the day".to_string()),
+        tags,
+      });
+      project.tracks[i % 4].clips.push(clip);
+    }
+    project
+  }
+
+  async fn found_ids(project: &ProjectSchema, query: ClipSearchQuery) -> Vec<usize> {
+    let mut ids: Vec<usize> = find_clips(project.clone(), query)
+      .await
+      .unwrap()
+      .iter()
+      .map(|found| found.clip_id["clip".len()..].parse().unwrap())
+      .collect();
+    ids.sort_unstable();
+    ids
+  }
+
+  fn expected_ids(predicate: impl Fn(usize) -> bool) -> Vec<usize> {
+    (0..200).filter(|&i| predicate(i)).collect()
+  }
+
+  #[tokio::test]
+  async fn test_find_clips_filters_synthetic_project() {
+    let project = project_with_labeled_clips();
+
+    let query = ClipSearchQuery {
+      tags: vec!["b-roll".to_string()],
+      ..Default::default()
+    };
+    assert_eq!(
+      found_ids(&project, query).await,
+      expected_ids(|i| i % 5 == 0)
+    );
+
+    let query = ClipSearchQuery {
+      tags: vec!["B-ROLL".to_string(), "Interview".to_string()],
+      color: Some(ClipColorLabel::Red),
+      ..Default::default()
+    };
+    assert_eq!(
+      found_ids(&project, query).await,
+      expected_ids(|i| i % 35 == 0 && i % 3 == 0)
+    );
+
+    // Текст ищется в имени файла и в заметках без учета регистра
+    let query = ClipSearchQuery {
+      text: Some("best take".to_string()),
+      ..Default::default()
+    };
+    assert_eq!(
+      found_ids(&project, query).await,
+      expected_ids(|i| i % 10 == 0)
+    );
+    let query = ClipSearchQuery {
+      text: Some("music_1".to_string()),
+      ..Default::default()
+    };
+    assert_eq!(
+      found_ids(&project, query).await,
+      expected_ids(|i| i % 4 == 3 && (100..200).contains(&i))
+    );
+
+    let query = ClipSearchQuery {
+      track_type: Some(TrackType::Audio),
+      time_range: Some((10.0, 20.0)),
+      ..Default::default()
+    };
+    assert_eq!(
+      found_ids(&project, query).await,
+      expected_ids(|i| i % 4 == 3 && (20..40).contains(&i))
+    );
+
+    let matches = find_clips(
+      project.clone(),
+      ClipSearchQuery {
+        text: Some("shot_042".to_string()),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].track_id, "track2");
+    assert_eq!(matches[0].clip_index, 10);
+    assert_eq!(matches[0].start_time, 20.0);
+
+    let invalid = ClipSearchQuery {
+      time_range: Some((5.0, 1.0)),
+      ..Default::default()
+    };
+    assert!(find_clips(project, invalid).await.is_err());
+  }
+
+  #[tokio::test]
+  async fn test_set_clip_metadata_normalizes_tags() {
+    let mut project = project_with_two_tracks();
+    project.tracks[0].clips[0].locked = true;
+
+    let metadata = ClipMetadata {
+      color_label: Some(ClipColorLabel::Blue),
+      notes: Some("  ".to_string()),
+      tags: vec![" b-roll ".to_string(), "B-Roll".to_string(), String::new()],
+    };
+    let updated = set_clip_metadata(project, "clip1".to_string(), metadata)
+      .await
+      .unwrap();
+    let clip = &updated.tracks[0].clips[0];
+    assert_eq!(clip.color_label, Some(ClipColorLabel::Blue));
+    assert_eq!(clip.notes, None);
+    assert_eq!(clip.tags, vec!["b-roll".to_string()]);
+
+    let json = serde_json::to_value(clip).unwrap();
+    assert_eq!(json["color_label"], "blue");
+  }
+
+  #[tokio::test]
+  async fn test_batch_operations_on_tagged_clips() {
+    let project = project_with_labeled_clips();
+    let ids: Vec<String> = project
+      .find_clips(&ClipSearchQuery {
+        tags: vec!["b-roll".to_string()],
+        ..Default::default()
+      })
+      .into_iter()
+      .map(|found| found.clip_id)
+      .collect();
+    assert_eq!(ids.len(), 40);
+
+    let effect = Effect::new(EffectType::Blur, "Soft".to_string());
+    let updated = apply_effect_to_clips(project.clone(), ids.clone(), effect.clone())
+      .await
+      .unwrap();
+    let updated = apply_effect_to_clips(updated, ids.clone(), effect.clone())
+      .await
+      .unwrap();
+    assert_eq!(updated.effects.len(), 1);
+    for track in &updated.tracks {
+      for clip in &track.clips {
+        let expected: Vec<String> = if clip.has_tag("b-roll") {
+          vec![effect.id.clone()]
+        } else {
+          Vec::new()
+        };
+        assert_eq!(clip.effects, expected);
+      }
+    }
+
+    let changes = ClipChanges {
+      opacity: Some(0.5),
+      ..Default::default()
+    };
+    let updated = update_clips(project.clone(), ids.clone(), changes.clone())
+      .await
+      .unwrap();
+    let dimmed = updated
+      .tracks
+      .iter()
+      .flat_map(|track| &track.clips)
+      .filter(|clip| clip.opacity == 0.5)
+      .count();
+    assert_eq!(dimmed, 40);
+
+    // Один заблокированный клип отменяет всю пакетную операцию
+    let mut locked = project;
+    let (track_idx, clip_idx) = locked.find_clip_position(&ids[3]).unwrap();
+    locked.tracks[track_idx].clips[clip_idx].locked = true;
+    let result = update_clips(locked.clone(), ids.clone(), changes).await;
+    assert!(matches!(result, Err(VideoCompilerError::Locked { .. })));
+    let result = apply_effect_to_clips(locked, ids, effect).await;
+    assert!(matches!(result, Err(VideoCompilerError::Locked { .. })));
+  }
+
+  #[tokio::test]
+  async fn test_create_and_flatten_sequence_commands() {
+    let mut project = project_with_two_tracks();
+    let mut overlay = Clip::new(std::path::PathBuf::from("/tmp/b.mp4"), 1.0, 2.0);
+    overlay.id = "clip2".to_string();
+    project.tracks[1].clips.push(overlay);
+
+    let ids = vec!["clip1".to_string(), "clip2".to_string()];
+    let nested = create_sequence_from_clips(project.clone(), ids.clone(), "Scene".to_string())
+      .await
+      .unwrap();
+    let sequence_id = nested.tracks[0].clips[0].sequence_id().unwrap().to_string();
+    assert_eq!(nested.sequences[&sequence_id].tracks.len(), 2);
+    assert!(nested.tracks[1].clips.is_empty());
+
+    let flat = flatten_sequence(nested.clone(), sequence_id.clone())
+      .await
+      .unwrap();
+    assert!(flat.sequences.is_empty());
+    assert_eq!(flat.tracks[0].clips[0].end_time, 5.0);
+    assert_eq!(flat.tracks[1].clips[0].start_time, 1.0);
+
+    // Заблокированные клипы и треки не группируются и не разворачиваются
+    project.tracks[1].locked = true;
+    let result = create_sequence_from_clips(project, ids, "Scene".to_string()).await;
+    assert!(matches!(result, Err(VideoCompilerError::Locked { .. })));
+
+    let mut locked = nested;
+    locked.tracks[0].locked = true;
+    let result = flatten_sequence(locked, sequence_id).await;
+    assert!(matches!(result, Err(VideoCompilerError::Locked { .. })));
+  }
+
+  #[tokio::test]
+  async fn test_gap_commands() {
+    let mut project = project_with_two_tracks();
+    let mut late = Clip::new(std::path::PathBuf::from("/tmp/late.mp4"), 8.0, 2.0);
+    late.id = "late".to_string();
+    project.tracks[0].clips.push(late);
+
+    let gaps = detect_timeline_gaps(project.clone(), Some(project.tracks[0].id.clone()))
+      .await
+      .unwrap();
+    assert_eq!(gaps.len(), 1);
+    assert_eq!((gaps[0].start, gaps[0].end), (5.0, 8.0));
+    let result = detect_timeline_gaps(project.clone(), Some("missing".to_string())).await;
+    assert!(matches!(
+      result,
+      Err(VideoCompilerError::InvalidParameter(_))
+    ));
+
+    let result = ripple_delete_gaps(project.clone(), RippleDeleteOptions::default())
+      .await
+      .unwrap();
+    assert_eq!(result.summary.moved_clips.len(), 1);
+    assert_eq!(result.project.tracks[0].clips[1].start_time, 5.0);
+    assert!(result.project.detect_gaps(None).unwrap().is_empty());
+
+    let options = RippleDeleteOptions {
+      tracks: None,
+      min_gap_to_keep: -1.0,
+    };
+    let result = ripple_delete_gaps(project, options).await;
+    assert!(matches!(
+      result,
+      Err(VideoCompilerError::InvalidParameter(_))
+    ));
+  }
+
+  #[tokio::test]
+  async fn test_format_timeline_position() {
+    use crate::video_compiler::schema::{Timecode, TimecodeRate};
+
+    let mut project = ProjectSchema::new("Timecode".to_string());
+    project.settings.frame_rate = 29.97;
+    project.settings.timecode.start =
+      Some(Timecode::parse("01:00:00;00", TimecodeRate::Fps29_97).unwrap());
+
+    // Минута 29.97 DF пропускает номера кадров 00 и 01
+    let position = format_timeline_position(project.clone(), 60.06)
+      .await
+      .unwrap();
+    assert_eq!(position, "01:01:00;02");
+
+    project.settings.timecode.drop_frame = false;
+    let position = format_timeline_position(project.clone(), 0.0)
+      .await
+      .unwrap();
+    assert_eq!(position, "01:00:00:00");
+
+    let result = format_timeline_position(project, f64::NAN).await;
+    assert!(matches!(
+      result,
+      Err(VideoCompilerError::InvalidParameter(_))
+    ));
+  }
 }
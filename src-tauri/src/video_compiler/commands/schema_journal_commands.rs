@@ -0,0 +1,109 @@
+//! Schema Journal Commands - undo/redo изменений схемы, выполненных backend командами
+
+use crate::video_compiler::error::{Result, VideoCompilerError};
+use crate::video_compiler::schema::ProjectSchema;
+use crate::video_compiler::services::schema_journal::{SchemaChange, SchemaJournalState};
+use crate::video_compiler::services::ProjectService;
+use crate::video_compiler::VideoCompilerState;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::State;
+
+/// Новая схема проекта и запись журнала для ее отмены
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournaledSchema {
+  pub project: ProjectSchema,
+  /// `None`, если команда не изменила схему
+  pub change: Option<SchemaChange>,
+}
+
+/// Получить сервис проектов
+fn project_service(state: &VideoCompilerState) -> Result<Arc<dyn ProjectService>> {
+  state
+    .services
+    .get_project_service()
+    .ok_or_else(|| VideoCompilerError::InternalError("Project service not available".to_string()))
+}
+
+/// Применить пресет экспорта к настройкам проекта с записью в журнал
+#[tauri::command]
+pub async fn apply_export_preset(
+  state: State<'_, VideoCompilerState>,
+  project_id: String,
+  project: ProjectSchema,
+  preset: String,
+) -> Result<JournaledSchema> {
+  let mut updated = project.clone();
+  if !updated.settings.export.apply_preset(&preset) {
+    return Err(VideoCompilerError::InvalidParameter(format!(
+      "Unknown preset: {preset}"
+    )));
+  }
+  updated.touch();
+
+  let change = project_service(&state)?
+    .record_schema_change(&project_id, "Apply export preset", &project, &updated)
+    .await?;
+  Ok(JournaledSchema {
+    project: updated,
+    change,
+  })
+}
+
+/// Отменить последнее изменение схемы проекта.
+///
+/// Возвращает схему для применения или `None`, если отменять нечего.
+#[tauri::command]
+pub async fn undo_schema_change(
+  state: State<'_, VideoCompilerState>,
+  project_id: String,
+) -> Result<Option<ProjectSchema>> {
+  project_service(&state)?
+    .undo_schema_change(&project_id)
+    .await
+}
+
+/// Повторить отмененное изменение схемы проекта.
+///
+/// Возвращает схему для применения или `None`, если повторять нечего.
+#[tauri::command]
+pub async fn redo_schema_change(
+  state: State<'_, VideoCompilerState>,
+  project_id: String,
+) -> Result<Option<ProjectSchema>> {
+  project_service(&state)?
+    .redo_schema_change(&project_id)
+    .await
+}
+
+/// Получить доступные операции undo/redo проекта
+#[tauri::command]
+pub async fn get_schema_journal_state(
+  state: State<'_, VideoCompilerState>,
+  project_id: String,
+) -> Result<SchemaJournalState> {
+  project_service(&state)?
+    .schema_journal_state(&project_id)
+    .await
+}
+
+/// Закрыть проект: очистить его журнал изменений схемы
+#[tauri::command]
+pub async fn close_project_journal(
+  state: State<'_, VideoCompilerState>,
+  project_id: String,
+) -> Result<()> {
+  project_service(&state)?.close_project(&project_id).await
+}
+
+crate::command_manifest!(
+  SCHEMA_JOURNAL_COMMANDS_MANIFEST,
+  "video_compiler::schema_journal_commands",
+  [
+    apply_export_preset,
+    undo_schema_change,
+    redo_schema_change,
+    get_schema_journal_state,
+    close_project_journal,
+  ]
+);
@@ -179,6 +179,19 @@ pub async fn clear_secure_storage(
   }))
 }
 
+crate::command_manifest!(
+  SECURITY_ADVANCED_COMMANDS_MANIFEST,
+  "video_compiler::security_advanced_commands",
+  [
+    init_secure_storage_advanced,
+    create_secure_storage_instance,
+    get_secure_storage_info_advanced,
+    verify_secure_storage_integrity,
+    export_secure_storage_config,
+    clear_secure_storage,
+  ]
+);
+
 #[cfg(test)]
 mod tests {
   use super::*;
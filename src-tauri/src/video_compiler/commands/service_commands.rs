@@ -5,6 +5,7 @@
 use crate::video_compiler::commands::VideoCompilerState;
 use crate::video_compiler::error::{Result, VideoCompilerError};
 use crate::video_compiler::schema::timeline::ClipSource;
+use crate::video_compiler::services::monitoring::{self, ServicePolicySettings};
 use std::collections::HashMap;
 use tauri::State;
 
@@ -320,6 +321,55 @@ pub async fn restart_service(
   }
 }
 
+/// Получить политики тайм-аутов и повторов операций сервисов
+#[tauri::command]
+pub async fn get_service_policies(
+  state: State<'_, VideoCompilerState>,
+) -> Result<ServicePolicySettings> {
+  Ok(state.settings.read().await.service_policies.clone())
+}
+
+/// Обновить политики тайм-аутов и повторов операций сервисов
+#[tauri::command]
+pub async fn update_service_policies(
+  policies: ServicePolicySettings,
+  state: State<'_, VideoCompilerState>,
+) -> Result<()> {
+  if let Some((key, _)) = policies
+    .overrides
+    .iter()
+    .find(|(key, _)| !key.contains('.'))
+  {
+    return Err(VideoCompilerError::validation(format!(
+      "Ключ политики должен иметь вид <сервис>.<операция>: {key}"
+    )));
+  }
+
+  monitoring::set_service_policies(policies.clone());
+  state.settings.write().await.service_policies = policies;
+  Ok(())
+}
+
+crate::command_manifest!(
+  SERVICE_COMMANDS_MANIFEST,
+  "video_compiler::service_commands",
+  [
+    get_active_jobs,
+    get_render_progress,
+    get_render_statistics,
+    get_input_sources_info,
+    touch_project,
+    set_preview_ffmpeg_path,
+    get_all_service_metrics,
+    get_specific_service_metrics,
+    cleanup_completed_jobs,
+    get_services_health,
+    restart_service,
+    get_service_policies,
+    update_service_policies,
+  ]
+);
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -345,6 +395,8 @@ mod tests {
       created_at: chrono::Utc::now().to_rfc3339(),
       progress: None,
       error_message: None,
+      process_limits: None,
+      downgrades: Vec::new(),
     }
   }
 
@@ -392,9 +444,11 @@ mod tests {
       enabled: true,
       volume: 1.0,
       locked: false,
+      hidden: false,
       clips: Vec::new(),
       effects: Vec::new(),
       filters: Vec::new(),
+      ducking: None,
     };
 
     // Add clips with different sources
@@ -415,6 +469,18 @@ mod tests {
       crop: None,
       transform: None,
       audio_track_index: None,
+      audio_stream_index: None,
+      source_rotation: 0,
+      color_label: None,
+      notes: None,
+      tags: Vec::new(),
+      fade_in: None,
+      fade_out: None,
+      link_group_id: None,
+      source_timecode: None,
+      volume: 1.0,
+      external_audio: None,
+      locked: false,
       properties: ClipProperties::default(),
     });
 
@@ -435,6 +501,18 @@ mod tests {
       crop: None,
       transform: None,
       audio_track_index: None,
+      audio_stream_index: None,
+      source_rotation: 0,
+      color_label: None,
+      notes: None,
+      tags: Vec::new(),
+      fade_in: None,
+      fade_out: None,
+      link_group_id: None,
+      source_timecode: None,
+      volume: 1.0,
+      external_audio: None,
+      locked: false,
       properties: ClipProperties::default(),
     });
 
@@ -456,6 +534,18 @@ mod tests {
       crop: None,
       transform: None,
       audio_track_index: None,
+      audio_stream_index: None,
+      source_rotation: 0,
+      color_label: None,
+      notes: None,
+      tags: Vec::new(),
+      fade_in: None,
+      fade_out: None,
+      link_group_id: None,
+      source_timecode: None,
+      volume: 1.0,
+      external_audio: None,
+      locked: false,
       properties: ClipProperties::default(),
     });
 
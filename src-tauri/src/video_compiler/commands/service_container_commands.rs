@@ -187,6 +187,17 @@ pub async fn export_prometheus_detailed(
   })
 }
 
+crate::command_manifest!(
+  SERVICE_CONTAINER_COMMANDS_MANIFEST,
+  "video_compiler::service_container_commands",
+  [
+    get_project_service_info_command,
+    get_service_metrics_detailed,
+    get_all_metrics_summaries_command,
+    export_prometheus_detailed,
+  ]
+);
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -0,0 +1,68 @@
+//! Snap Index Commands - точки привязки timeline для перетаскивания клипов
+
+use crate::montage_planner::commands::MontageState;
+use crate::video_compiler::error::{Result, VideoCompilerError};
+use crate::video_compiler::schema::ProjectSchema;
+use crate::video_compiler::services::snap_index::{
+  SnapIndex, SnapIndexCache, SnapIndexOptions, SnapMatch, SnapQuery,
+};
+use std::sync::Arc;
+use tauri::State;
+
+/// Построить (или взять из кэша) индекс точек привязки проекта.
+///
+/// Индекс перестраивается только когда меняются клипы, маркеры, опции или
+/// биты; биты берутся из кэша анализа Montage Planner.
+#[tauri::command]
+pub async fn build_snap_index(
+  project: ProjectSchema,
+  options: Option<SnapIndexOptions>,
+  snap_cache: State<'_, Arc<SnapIndexCache>>,
+  montage_state: State<'_, MontageState>,
+) -> Result<SnapIndex> {
+  let options = options.unwrap_or_default();
+  let beats = match &options.include_beats {
+    Some(path) => montage_state
+      .beat_detector
+      .write()
+      .await
+      .detect_beats(path, None)
+      .await
+      .map_err(|e| VideoCompilerError::MediaFileError {
+        path: path.clone(),
+        reason: e.to_string(),
+      })?
+      .into_iter()
+      .map(|beat| beat.time)
+      .collect(),
+    None => Vec::new(),
+  };
+
+  let cache = snap_cache.inner().clone();
+  let index = tokio::task::spawn_blocking(move || cache.get_or_build(&project, &options, &beats))
+    .await
+    .map_err(|e| VideoCompilerError::InternalError(e.to_string()))?;
+  Ok((*index).clone())
+}
+
+/// Ближайшая точка привязки в пределах допуска.
+///
+/// `index_id` - ревизия из [`build_snap_index`]; если индекс вытеснен из кэша,
+/// его нужно построить заново.
+#[tauri::command]
+pub async fn query_snap(
+  index_id: String,
+  query: SnapQuery,
+  snap_cache: State<'_, Arc<SnapIndexCache>>,
+) -> Result<Option<SnapMatch>> {
+  let index = snap_cache.get(&index_id).ok_or_else(|| {
+    VideoCompilerError::InvalidParameter(format!("Индекс привязки {index_id} не найден"))
+  })?;
+  Ok(index.query(&query))
+}
+
+crate::command_manifest!(
+  SNAP_INDEX_COMMANDS_MANIFEST,
+  "video_compiler::snap_index_commands",
+  [build_snap_index, query_snap,]
+);
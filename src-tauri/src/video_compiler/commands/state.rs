@@ -8,10 +8,12 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 
 use crate::video_compiler::cache::RenderCache;
+use crate::video_compiler::core::gpu_memory::RenderDowngrade;
+use crate::video_compiler::core::render_priority::ProcessLimits;
 use crate::video_compiler::progress::RenderProgress;
 use crate::video_compiler::progress::RenderStatus;
 use crate::video_compiler::renderer::VideoRenderer;
-use crate::video_compiler::services::ServiceContainer;
+use crate::video_compiler::services::{ServiceContainer, ServiceMetricsContainer};
 use crate::video_compiler::CompilerSettings;
 
 /// Метаданные активной задачи рендеринга
@@ -77,53 +79,15 @@ impl VideoCompilerState {
       Err(e) => {
         log::error!("Ошибка создания контейнера сервисов: {e:?}");
         // Создаем минимальный контейнер для fallback
-        let cache = Arc::new(crate::video_compiler::services::CacheServiceImpl::new(
+        let services = ServiceContainer::builder(
+          ffmpeg_path.clone(),
           std::env::temp_dir().join("timeline-studio"),
-        ));
+          2,
+        )
+        .build_with_metrics(ServiceMetricsContainer::unregistered());
 
         return Self {
-          services: Arc::new(ServiceContainer {
-            render: Arc::new(crate::video_compiler::services::RenderServiceImpl::new(
-              Arc::new(crate::video_compiler::services::FfmpegServiceImpl::new(
-                ffmpeg_path.clone(),
-              )),
-              2,
-              cache.clone(),
-            )),
-            cache: cache.clone(),
-            gpu: Arc::new(crate::video_compiler::services::GpuServiceImpl::new(
-              ffmpeg_path.clone(),
-            )),
-            preview: Arc::new(crate::video_compiler::services::PreviewServiceImpl::new(
-              Arc::new(crate::video_compiler::services::FfmpegServiceImpl::new(
-                ffmpeg_path.clone(),
-              )),
-            )),
-            project: Arc::new(crate::video_compiler::services::ProjectServiceImpl::new()),
-            ffmpeg: Arc::new(crate::video_compiler::services::FfmpegServiceImpl::new(
-              ffmpeg_path.clone(),
-            )),
-            metrics: crate::video_compiler::services::ServiceMetricsContainer {
-              render: Arc::new(crate::video_compiler::services::ServiceMetrics::new(
-                "render-service".to_string(),
-              )),
-              cache: Arc::new(crate::video_compiler::services::ServiceMetrics::new(
-                "cache-service".to_string(),
-              )),
-              gpu: Arc::new(crate::video_compiler::services::ServiceMetrics::new(
-                "gpu-service".to_string(),
-              )),
-              preview: Arc::new(crate::video_compiler::services::ServiceMetrics::new(
-                "preview-service".to_string(),
-              )),
-              project: Arc::new(crate::video_compiler::services::ServiceMetrics::new(
-                "project-service".to_string(),
-              )),
-              ffmpeg: Arc::new(crate::video_compiler::services::ServiceMetrics::new(
-                "ffmpeg-service".to_string(),
-              )),
-            },
-          }),
+          services: Arc::new(services),
           active_jobs: Arc::new(RwLock::new(HashMap::new())),
           active_pipelines: Arc::new(RwLock::new(HashMap::new())),
           cache_manager,
@@ -170,56 +134,12 @@ impl Default for VideoCompilerState {
     let cache_manager = Arc::new(RwLock::new(RenderCache::new()));
 
     // Создаем сервисы напрямую для синхронного Default
-    let ffmpeg = Arc::new(crate::video_compiler::services::FfmpegServiceImpl::new(
+    let services = ServiceContainer::builder(
       "ffmpeg".to_string(),
-    ));
-    let cache_service = Arc::new(crate::video_compiler::services::CacheServiceImpl::new(
       std::env::temp_dir().join("timeline-studio"),
-    ));
-    let gpu = Arc::new(crate::video_compiler::services::GpuServiceImpl::new(
-      "ffmpeg".to_string(),
-    ));
-    let preview = Arc::new(crate::video_compiler::services::PreviewServiceImpl::new(
-      ffmpeg.clone(),
-    ));
-    let project = Arc::new(crate::video_compiler::services::ProjectServiceImpl::new());
-    let render = Arc::new(crate::video_compiler::services::RenderServiceImpl::new(
-      ffmpeg.clone(),
       2,
-      cache_service.clone(),
-    ));
-
-    // Создаем метрики
-    let metrics = crate::video_compiler::services::ServiceMetricsContainer {
-      render: Arc::new(crate::video_compiler::services::ServiceMetrics::new(
-        "render-service".to_string(),
-      )),
-      cache: Arc::new(crate::video_compiler::services::ServiceMetrics::new(
-        "cache-service".to_string(),
-      )),
-      gpu: Arc::new(crate::video_compiler::services::ServiceMetrics::new(
-        "gpu-service".to_string(),
-      )),
-      preview: Arc::new(crate::video_compiler::services::ServiceMetrics::new(
-        "preview-service".to_string(),
-      )),
-      project: Arc::new(crate::video_compiler::services::ServiceMetrics::new(
-        "project-service".to_string(),
-      )),
-      ffmpeg: Arc::new(crate::video_compiler::services::ServiceMetrics::new(
-        "ffmpeg-service".to_string(),
-      )),
-    };
-
-    let services = ServiceContainer {
-      render,
-      cache: cache_service,
-      gpu,
-      preview,
-      project,
-      ffmpeg,
-      metrics,
-    };
+    )
+    .build_with_metrics(ServiceMetricsContainer::unregistered());
 
     Self {
       services: Arc::new(services),
@@ -242,6 +162,12 @@ pub struct RenderJob {
   pub created_at: String,
   pub progress: Option<RenderProgress>,
   pub error_message: Option<String>,
+  /// Итоговые приоритет и ограничение потоков FFmpeg
+  #[serde(default)]
+  pub process_limits: Option<ProcessLimits>,
+  /// Понижения режима кодирования из-за нехватки видеопамяти
+  #[serde(default)]
+  pub downgrades: Vec<RenderDowngrade>,
 }
 
 #[cfg(test)]
@@ -273,6 +199,8 @@ mod tests {
       created_at: "2024-01-01T00:00:00Z".to_string(),
       progress: None,
       error_message: None,
+      process_limits: None,
+      downgrades: Vec::new(),
     };
 
     // Test serialization
@@ -300,6 +228,8 @@ mod tests {
       estimated_remaining: Some(std::time::Duration::from_secs(270)),
       status: RenderStatus::Processing,
       message: Some("Video encoding".to_string()),
+      renditions: Vec::new(),
+      stages: Vec::new(),
     };
 
     let job = RenderJob {
@@ -310,6 +240,8 @@ mod tests {
       created_at: "2024-01-01T00:00:00Z".to_string(),
       progress: Some(progress),
       error_message: None,
+      process_limits: None,
+      downgrades: Vec::new(),
     };
 
     assert!(job.progress.is_some());
@@ -330,6 +262,8 @@ mod tests {
       created_at: "2024-01-01T00:00:00Z".to_string(),
       progress: None,
       error_message: Some("FFmpeg execution failed".to_string()),
+      process_limits: None,
+      downgrades: Vec::new(),
     };
 
     match job.status {
@@ -549,6 +483,8 @@ mod tests {
       created_at: "2024-01-01".to_string(),
       progress: None,
       error_message: None,
+      process_limits: None,
+      downgrades: Vec::new(),
     };
 
     let debug_str = format!("{job:?}");
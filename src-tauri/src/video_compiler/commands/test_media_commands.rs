@@ -0,0 +1,89 @@
+//! Test Media Commands - dev-команды генерации синтетических медиафайлов
+//!
+//! Доступны только в debug сборках или с feature `dev-tools`; в остальных
+//! сборках команды возвращают ошибку.
+
+use crate::video_compiler::error::{Result, VideoCompilerError};
+use crate::video_compiler::test_media::{
+  self, allowed_output_roots, dev_tools_enabled, ensure_output_allowed, TestPattern, TestVideoSpec,
+};
+use crate::video_compiler::VideoCompilerState;
+use std::path::Path;
+use tauri::State;
+
+/// Проверить доступность dev-команд и путь вывода
+fn prepare_output(path: &str) -> Result<std::path::PathBuf> {
+  if !dev_tools_enabled() {
+    return Err(VideoCompilerError::NotImplemented(
+      "Генерация тестовых медиа доступна только в dev сборках".to_string(),
+    ));
+  }
+  ensure_output_allowed(Path::new(path), &allowed_output_roots())
+}
+
+/// Сгенерировать тестовое видео через lavfi
+#[tauri::command]
+pub async fn generate_test_video(
+  state: State<'_, VideoCompilerState>,
+  path: String,
+  duration: f64,
+  resolution: (u32, u32),
+  fps: u32,
+  pattern: Option<TestPattern>,
+  with_audio: bool,
+) -> Result<String> {
+  let output = prepare_output(&path)?;
+  let ffmpeg_path = state.ffmpeg_path.read().await.clone();
+  let spec = TestVideoSpec {
+    duration,
+    resolution,
+    fps,
+    pattern: pattern.unwrap_or_default(),
+    with_audio,
+  };
+
+  test_media::generate_test_video(&ffmpeg_path, &output, &spec)
+    .await
+    .map(|path| path.to_string_lossy().to_string())
+}
+
+/// Сгенерировать тоновый аудиофайл
+#[tauri::command]
+pub async fn generate_test_audio(
+  state: State<'_, VideoCompilerState>,
+  path: String,
+  duration: f64,
+  tone_hz: u32,
+) -> Result<String> {
+  let output = prepare_output(&path)?;
+  let ffmpeg_path = state.ffmpeg_path.read().await.clone();
+
+  test_media::generate_test_audio(&ffmpeg_path, &output, duration, tone_hz)
+    .await
+    .map(|path| path.to_string_lossy().to_string())
+}
+
+/// Сгенерировать тестовое изображение
+#[tauri::command]
+pub async fn generate_test_image(
+  state: State<'_, VideoCompilerState>,
+  path: String,
+  resolution: (u32, u32),
+) -> Result<String> {
+  let output = prepare_output(&path)?;
+  let ffmpeg_path = state.ffmpeg_path.read().await.clone();
+
+  test_media::generate_test_image(&ffmpeg_path, &output, resolution)
+    .await
+    .map(|path| path.to_string_lossy().to_string())
+}
+
+crate::command_manifest!(
+  TEST_MEDIA_COMMANDS_MANIFEST,
+  "video_compiler::test_media_commands",
+  [
+    generate_test_video,
+    generate_test_audio,
+    generate_test_image
+  ]
+);
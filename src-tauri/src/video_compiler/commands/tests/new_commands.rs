@@ -166,7 +166,10 @@ async fn test_build_render_command_with_settings() {
     ffmpeg_path: "ffmpeg".to_string(),
     use_hardware_acceleration: true,
     hardware_acceleration_type: Some("nvenc".to_string()),
+    hardware_decoding: false,
     global_options: vec!["-y".to_string(), "-hide_banner".to_string()],
+    soft_subtitles_path: None,
+    process_limits: Default::default(),
   };
 
   let builder = FFmpegBuilder::with_settings(project, settings);
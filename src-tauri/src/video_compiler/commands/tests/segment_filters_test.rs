@@ -25,6 +25,18 @@ mod segment_filter_tests {
       crop: None,
       transform: None,
       audio_track_index: None,
+      audio_stream_index: None,
+      source_rotation: 0,
+      color_label: None,
+      notes: None,
+      tags: Vec::new(),
+      fade_in: None,
+      fade_out: None,
+      link_group_id: None,
+      source_timecode: None,
+      volume: 1.0,
+      external_audio: None,
+      locked: false,
       properties: crate::video_compiler::schema::timeline::ClipProperties::default(),
     };
 
@@ -35,9 +47,11 @@ mod segment_filter_tests {
       enabled: true,
       volume: 1.0,
       locked: false,
+      hidden: false,
       clips: vec![clip1],
       effects: vec![],
       filters: vec![],
+      ducking: None,
     };
 
     let audio_clip = Clip {
@@ -57,6 +71,18 @@ mod segment_filter_tests {
       crop: None,
       transform: None,
       audio_track_index: None,
+      audio_stream_index: None,
+      source_rotation: 0,
+      color_label: None,
+      notes: None,
+      tags: Vec::new(),
+      fade_in: None,
+      fade_out: None,
+      link_group_id: None,
+      source_timecode: None,
+      volume: 1.0,
+      external_audio: None,
+      locked: false,
       properties: crate::video_compiler::schema::timeline::ClipProperties::default(),
     };
 
@@ -67,9 +93,11 @@ mod segment_filter_tests {
       enabled: true,
       volume: 1.0,
       locked: false,
+      hidden: false,
       clips: vec![audio_clip],
       effects: vec![],
       filters: vec![],
+      ducking: None,
     };
 
     ProjectSchema {
@@ -90,6 +118,7 @@ mod segment_filter_tests {
       templates: vec![],
       subtitles: vec![],
       style_templates: vec![],
+      sequences: Default::default(),
     }
   }
 
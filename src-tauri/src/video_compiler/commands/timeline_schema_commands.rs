@@ -243,6 +243,21 @@ pub async fn get_subtitle_statistics(
   })
 }
 
+crate::command_manifest!(
+  TIMELINE_SCHEMA_COMMANDS_MANIFEST,
+  "video_compiler::timeline_schema_commands",
+  [
+    create_new_subtitle,
+    validate_subtitle_schema,
+    get_subtitle_duration_schema,
+    create_new_track,
+    add_clip_to_track_schema,
+    remove_clip_from_track_schema,
+    get_track_info,
+    get_subtitle_statistics,
+  ]
+);
+
 #[cfg(test)]
 mod tests {
   use super::*;
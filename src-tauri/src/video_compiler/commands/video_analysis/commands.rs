@@ -274,3 +274,22 @@ pub async fn ffmpeg_quick_analysis(file_path: String) -> Result<serde_json::Valu
 
   Ok(generate_quick_analysis_result())
 }
+
+crate::command_manifest!(
+  VIDEO_ANALYSIS_MANIFEST,
+  "video_compiler::video_analysis",
+  [
+    ffmpeg_get_metadata,
+    ffmpeg_detect_scenes,
+    ffmpeg_analyze_quality,
+    ffmpeg_detect_silence,
+    ffmpeg_analyze_motion,
+    ffmpeg_extract_keyframes,
+    ffmpeg_analyze_audio,
+    ffmpeg_quick_analysis,
+  ],
+  internal: [
+    ffmpeg_get_metadata_enhanced,
+    ffmpeg_analyze_quality_enhanced,
+  ]
+);
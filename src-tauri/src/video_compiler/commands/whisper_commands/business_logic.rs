@@ -53,6 +53,10 @@ pub fn parse_whisper_segments(whisper_json: &serde_json::Value) -> Vec<WhisperSe
         avg_logprob: segment.get("avg_logprob")?.as_f64()?,
         compression_ratio: segment.get("compression_ratio")?.as_f64()?,
         no_speech_prob: segment.get("no_speech_prob")?.as_f64()?,
+        language: segment
+          .get("language")
+          .and_then(|l| l.as_str())
+          .map(normalize_language_code),
       })
     })
     .collect()
@@ -222,3 +226,259 @@ pub fn convert_api_response_to_transcription(
     })
   }
 }
+
+/// Нормализовать язык, возвращаемый Whisper, к двухбуквенному коду
+///
+/// OpenAI API в verbose_json возвращает полное название языка ("russian"),
+/// whisper.cpp - код ("ru").
+pub fn normalize_language_code(language: &str) -> String {
+  let lower = language.trim().to_lowercase();
+  let code = match lower.as_str() {
+    "english" => "en",
+    "russian" => "ru",
+    "spanish" => "es",
+    "french" => "fr",
+    "german" => "de",
+    "italian" => "it",
+    "portuguese" => "pt",
+    "ukrainian" => "uk",
+    "chinese" => "zh",
+    "japanese" => "ja",
+    "korean" => "ko",
+    "turkish" => "tr",
+    "polish" => "pl",
+    "dutch" => "nl",
+    "arabic" => "ar",
+    "hindi" => "hi",
+    _ => return lower,
+  };
+  code.to_string()
+}
+
+/// Разбить длительность на последовательные чанки
+pub fn plan_transcription_chunks(duration: f64, chunk_duration: f64) -> Vec<AudioWindow> {
+  if duration <= 0.0 {
+    return Vec::new();
+  }
+  if chunk_duration <= 0.0 || chunk_duration >= duration {
+    return vec![AudioWindow {
+      start: 0.0,
+      end: duration,
+    }];
+  }
+
+  let mut chunks = Vec::new();
+  let mut start = 0.0;
+  while start < duration {
+    let end = (start + chunk_duration).min(duration);
+    chunks.push(AudioWindow { start, end });
+    start = end;
+  }
+  chunks
+}
+
+/// Спланировать окна для определения языка по стратегии выборки
+pub fn plan_language_sample_windows(
+  duration: f64,
+  strategy: &LanguageSampleStrategy,
+) -> Vec<AudioWindow> {
+  if duration <= 0.0 {
+    return Vec::new();
+  }
+
+  match *strategy {
+    LanguageSampleStrategy::Head { window_duration } => vec![AudioWindow {
+      start: 0.0,
+      end: window_duration.min(duration),
+    }],
+    LanguageSampleStrategy::Uniform {
+      windows,
+      window_duration,
+    } => {
+      let windows = windows.max(1);
+      let window_duration = window_duration.min(duration);
+      if windows == 1 || window_duration * windows as f64 >= duration {
+        // Окна покрывают весь файл - проще разбить его на чанки
+        return plan_transcription_chunks(duration, duration / windows as f64);
+      }
+
+      // Центры окон равномерно распределены по файлу
+      let step = duration / windows as f64;
+      (0..windows)
+        .map(|i| {
+          let center = step * (i as f64 + 0.5);
+          let start = (center - window_duration / 2.0).clamp(0.0, duration - window_duration);
+          AudioWindow {
+            start,
+            end: start + window_duration,
+          }
+        })
+        .collect()
+    }
+  }
+}
+
+/// Извлечь автоопределенный язык из вывода whisper.cpp
+///
+/// whisper.cpp печатает в stderr строку вида
+/// `whisper_full_with_state: auto-detected language: en (p = 0.967803)`.
+pub fn parse_whisper_cpp_detected_language(output: &str) -> Option<LanguageDetection> {
+  let line = output
+    .lines()
+    .find(|line| line.contains("auto-detected language:"))?;
+  let rest = line.split("auto-detected language:").nth(1)?.trim();
+
+  let language = rest.split_whitespace().next()?.to_string();
+  let confidence = rest
+    .split("p =")
+    .nth(1)
+    .and_then(|p| p.trim().trim_end_matches(')').trim().parse::<f64>().ok())
+    .unwrap_or(0.0);
+
+  Some(LanguageDetection {
+    language: normalize_language_code(&language),
+    confidence,
+  })
+}
+
+/// Агрегировать результаты по окнам в список языков
+///
+/// Уверенность каждого окна взвешивается его длительностью, итоговые значения
+/// нормализуются так, чтобы их сумма была равна 1.
+pub fn aggregate_language_detections(
+  windows: &[ChunkLanguage],
+  top_n: usize,
+) -> Vec<LanguageDetection> {
+  let mut totals: Vec<(String, f64)> = Vec::new();
+  for window in windows {
+    let Some(language) = &window.language else {
+      continue;
+    };
+    let weight = window.confidence * window.window.duration().max(f64::EPSILON);
+    match totals.iter_mut().find(|(lang, _)| lang == language) {
+      Some((_, total)) => *total += weight,
+      None => totals.push((language.clone(), weight)),
+    }
+  }
+
+  let sum: f64 = totals.iter().map(|(_, total)| total).sum();
+  if sum <= 0.0 {
+    return Vec::new();
+  }
+
+  let mut languages: Vec<LanguageDetection> = totals
+    .into_iter()
+    .map(|(language, total)| LanguageDetection {
+      language,
+      confidence: total / sum,
+    })
+    .collect();
+  languages.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+  languages.truncate(top_n);
+  languages
+}
+
+/// Сгладить языки чанков
+///
+/// Чанк с неуверенным результатом (или без результата) не может сменить язык:
+/// он наследует язык предыдущего чанка, а в начале файла - первого уверенного.
+/// Уверенные чанки переключают язык как есть.
+pub fn resolve_chunk_languages(chunks: &mut [ChunkLanguage], min_confidence: f64) {
+  let is_confident =
+    |chunk: &ChunkLanguage| chunk.language.is_some() && chunk.confidence >= min_confidence;
+
+  let first_confident = chunks
+    .iter()
+    .find(|chunk| is_confident(chunk))
+    .and_then(|chunk| chunk.language.clone());
+
+  let mut current = first_confident;
+  for chunk in chunks.iter_mut() {
+    if is_confident(chunk) {
+      current = chunk.language.clone();
+    } else if current.is_some() {
+      chunk.language = current.clone();
+    }
+  }
+}
+
+/// Сдвинуть сегменты чанка на его начало и пометить их языком чанка
+pub fn shift_chunk_segments(
+  segments: Vec<WhisperSegment>,
+  chunk: &ChunkLanguage,
+  first_id: u32,
+) -> Vec<WhisperSegment> {
+  segments
+    .into_iter()
+    .enumerate()
+    .map(|(i, mut segment)| {
+      segment.id = first_id + i as u32;
+      segment.start += chunk.window.start;
+      segment.end += chunk.window.start;
+      if chunk.language.is_some() {
+        segment.language = chunk.language.clone();
+      }
+      segment
+    })
+    .collect()
+}
+
+/// Пометить сегменты языком чанка, содержащего середину сегмента
+///
+/// Сегменты, у которых язык уже определен, не изменяются.
+pub fn tag_segments_with_languages(segments: &mut [WhisperSegment], chunks: &[ChunkLanguage]) {
+  for segment in segments.iter_mut().filter(|s| s.language.is_none()) {
+    let middle = (segment.start + segment.end) / 2.0;
+    segment.language = chunks
+      .iter()
+      .find(|chunk| middle >= chunk.window.start && middle < chunk.window.end)
+      .or_else(|| chunks.last().filter(|chunk| middle >= chunk.window.end))
+      .and_then(|chunk| chunk.language.clone());
+  }
+}
+
+/// Преобразовать сегменты транскрипции в субтитры
+///
+/// В режиме `Tag` возвращается один набор, в котором каждый субтитр помечен
+/// языком сегмента. В режиме `SplitByLanguage` - по набору на каждый язык
+/// в порядке первого появления.
+pub fn transcript_to_subtitles(
+  segments: &[WhisperSegment],
+  mode: SubtitleLanguageMode,
+) -> Vec<TranscriptSubtitleSet> {
+  let subtitles = segments
+    .iter()
+    .filter(|segment| !segment.text.trim().is_empty() && segment.end > segment.start)
+    .map(|segment| {
+      let mut subtitle = crate::video_compiler::schema::Subtitle::new(
+        segment.text.trim().to_string(),
+        segment.start,
+        segment.end,
+      );
+      subtitle.language = segment.language.clone();
+      subtitle
+    });
+
+  match mode {
+    SubtitleLanguageMode::Tag => vec![TranscriptSubtitleSet {
+      language: None,
+      subtitles: subtitles.collect(),
+    }],
+    SubtitleLanguageMode::SplitByLanguage => {
+      let mut sets: Vec<TranscriptSubtitleSet> = Vec::new();
+      for subtitle in subtitles {
+        match sets
+          .iter_mut()
+          .find(|set| set.language == subtitle.language)
+        {
+          Some(set) => set.subtitles.push(subtitle),
+          None => sets.push(TranscriptSubtitleSet {
+            language: subtitle.language.clone(),
+            subtitles: vec![subtitle],
+          }),
+        }
+      }
+      sets
+    }
+  }
+}
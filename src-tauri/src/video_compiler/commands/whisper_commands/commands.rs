@@ -6,7 +6,7 @@ use super::{business_logic::*, types::*};
 use crate::video_compiler::core::error::{Result, VideoCompilerError};
 use crate::video_compiler::ffmpeg_executor::FFmpegExecutor;
 use reqwest::multipart::{Form, Part};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tokio::fs;
 
 /// Транскрипция аудио через OpenAI Whisper API
@@ -86,9 +86,9 @@ pub async fn whisper_transcribe_openai(
   let status = response.status();
   if !status.is_success() {
     let error_text = response.text().await.unwrap_or_default();
-    return Err(VideoCompilerError::ValidationError(format!(
-      "OpenAI API error {status}: {error_text}"
-    )));
+    return Err(VideoCompilerError::ValidationError(
+      format!("OpenAI API error {status}: {error_text}").into(),
+    ));
   }
 
   let response_text = response
@@ -160,9 +160,9 @@ pub async fn whisper_translate_openai(
   let status = response.status();
   if !status.is_success() {
     let error_text = response.text().await.unwrap_or_default();
-    return Err(VideoCompilerError::ValidationError(format!(
-      "OpenAI API error {status}: {error_text}"
-    )));
+    return Err(VideoCompilerError::ValidationError(
+      format!("OpenAI API error {status}: {error_text}").into(),
+    ));
   }
 
   let translation: WhisperTranslationResult = response
@@ -193,13 +193,13 @@ pub async fn whisper_transcribe_local(
   // Проверяем доступность локального Whisper
   if !check_local_whisper_availability() {
     return Err(VideoCompilerError::DependencyMissing(
-      "Whisper.cpp не найден в системе".to_string(),
+      "Whisper.cpp не найден в системе".into(),
     ));
   }
 
   let whisper_executable = which::which("whisper")
     .or_else(|_| which::which("whisper.cpp"))
-    .map_err(|_| VideoCompilerError::DependencyMissing("Whisper.cpp не найден".to_string()))?;
+    .map_err(|_| VideoCompilerError::DependencyMissing("Whisper.cpp не найден".into()))?;
 
   // Получаем путь к модели
   let model_path =
@@ -513,3 +513,521 @@ pub async fn extract_audio_for_whisper(
 
   Ok(audio_path.to_string_lossy().to_string())
 }
+
+/// Модель whisper.cpp по умолчанию для определения языка
+const DEFAULT_LANGUAGE_DETECTION_MODEL: &str = "whisper-base";
+
+/// Количество языков в отчете об определении языка
+const TOP_DETECTED_LANGUAGES: usize = 3;
+
+/// Определить язык аудио по нескольким окнам через whisper.cpp
+#[tauri::command]
+pub async fn detect_audio_language(
+  file_path: String,
+  sample_strategy: Option<LanguageSampleStrategy>,
+  model_name: Option<String>,
+) -> Result<AudioLanguageReport> {
+  ensure_audio_file_exists(&file_path)?;
+  let whisper_executable = find_whisper_executable()?;
+  let model_path = resolve_local_model_path(
+    model_name
+      .as_deref()
+      .unwrap_or(DEFAULT_LANGUAGE_DETECTION_MODEL),
+  )?;
+
+  let duration = probe_media_duration(&file_path).await?;
+  let windows = plan_language_sample_windows(duration, &sample_strategy.unwrap_or_default());
+
+  let work_dir = create_chunk_work_dir().await?;
+  let detections = detect_windows_language(
+    &whisper_executable,
+    &model_path,
+    &file_path,
+    &windows,
+    &work_dir,
+  )
+  .await;
+  let _ = fs::remove_dir_all(&work_dir).await;
+  let windows = detections?;
+
+  Ok(AudioLanguageReport {
+    languages: aggregate_language_detections(&windows, TOP_DETECTED_LANGUAGES),
+    windows,
+  })
+}
+
+/// Многоязычная транскрипция через локальный whisper.cpp
+///
+/// При `TranscriptionLanguage::Auto` файл разбивается на чанки, язык
+/// определяется для каждого чанка и каждый сегмент получает метку языка.
+#[tauri::command]
+pub async fn whisper_transcribe_local_multilingual(
+  audio_file_path: String,
+  model_name: String,
+  threads: u32,
+  options: WhisperTranscriptionOptions,
+) -> Result<WhisperTranscriptionResult> {
+  ensure_audio_file_exists(&audio_file_path)?;
+  if let TranscriptionLanguage::Fixed(code) = &options.language {
+    validate_language_code(code)?;
+  }
+
+  let whisper_executable = find_whisper_executable()?;
+  let model_path = resolve_local_model_path(&model_name)?;
+  let duration = probe_media_duration(&audio_file_path).await?;
+
+  let work_dir = create_chunk_work_dir().await?;
+  let result = async {
+    let mut chunks = match &options.language {
+      TranscriptionLanguage::Auto => {
+        let windows = plan_transcription_chunks(duration, options.chunk_duration);
+        let mut chunks = detect_windows_language(
+          &whisper_executable,
+          &model_path,
+          &audio_file_path,
+          &windows,
+          &work_dir,
+        )
+        .await?;
+        resolve_chunk_languages(&mut chunks, options.min_language_confidence);
+        chunks
+      }
+      TranscriptionLanguage::Fixed(code) => vec![ChunkLanguage {
+        window: AudioWindow {
+          start: 0.0,
+          end: duration,
+        },
+        language: Some(code.clone()),
+        confidence: 1.0,
+      }],
+    };
+
+    let mut segments = Vec::new();
+    for (index, chunk) in chunks.iter_mut().enumerate() {
+      let chunk_audio =
+        extract_audio_window(&audio_file_path, chunk.window, &work_dir, index).await?;
+      let output_base = work_dir.join(format!("transcript_{index}"));
+
+      let mut cmd = tokio::process::Command::new(&whisper_executable);
+      cmd.args([
+        "-m",
+        &model_path.to_string_lossy(),
+        "-f",
+        &chunk_audio.to_string_lossy(),
+        "-t",
+        &threads.to_string(),
+        "-l",
+        chunk.language.as_deref().unwrap_or("auto"),
+        "-oj",
+        "-of",
+        &output_base.to_string_lossy(),
+      ]);
+
+      let exec = run_whisper_cpp(cmd).await?;
+      let content = fs::read_to_string(output_base.with_extension("json"))
+        .await
+        .map_err(|e| VideoCompilerError::IoError(format!("Ошибка чтения результата: {e}")))?;
+      let whisper_result: serde_json::Value = serde_json::from_str(&content).map_err(|e| {
+        VideoCompilerError::SerializationError(format!("Ошибка парсинга JSON: {e}"))
+      })?;
+
+      // Если язык чанка не определился заранее, берем язык, выбранный whisper.cpp
+      if chunk.language.is_none() {
+        if let Some(detected) = parse_whisper_cpp_detected_language(&exec.stderr) {
+          chunk.language = Some(detected.language);
+          chunk.confidence = detected.confidence;
+        }
+      }
+
+      let chunk_segments = parse_whisper_segments(&whisper_result);
+      segments.extend(shift_chunk_segments(
+        chunk_segments,
+        chunk,
+        segments.len() as u32,
+      ));
+    }
+
+    Ok::<_, VideoCompilerError>(build_multilingual_result(segments, &chunks, duration))
+  }
+  .await;
+  let _ = fs::remove_dir_all(&work_dir).await;
+
+  result
+}
+
+/// Многоязычная транскрипция через OpenAI Whisper API
+///
+/// При `TranscriptionLanguage::Auto` язык каждого чанка определяется локальным
+/// whisper.cpp (если он доступен) и передается в API как подсказка. Без
+/// локального whisper.cpp используется язык, который вернул API.
+#[tauri::command]
+pub async fn whisper_transcribe_openai_multilingual(
+  audio_file_path: String,
+  api_key: String,
+  model: String,
+  options: WhisperTranscriptionOptions,
+  detection_model: Option<String>,
+) -> Result<WhisperTranscriptionResult> {
+  let fixed_language = match &options.language {
+    TranscriptionLanguage::Fixed(code) => Some(code.as_str()),
+    TranscriptionLanguage::Auto => None,
+  };
+  validate_transcription_params(&api_key, &model, fixed_language, None)
+    .map_err(VideoCompilerError::InvalidParameter)?;
+  ensure_audio_file_exists(&audio_file_path)?;
+
+  let duration = probe_media_duration(&audio_file_path).await?;
+  let windows = plan_transcription_chunks(duration, options.chunk_duration);
+
+  let work_dir = create_chunk_work_dir().await?;
+  let result = async {
+    let mut chunks: Vec<ChunkLanguage> = match &options.language {
+      TranscriptionLanguage::Fixed(code) => windows
+        .iter()
+        .map(|window| ChunkLanguage {
+          window: *window,
+          language: Some(code.clone()),
+          confidence: 1.0,
+        })
+        .collect(),
+      TranscriptionLanguage::Auto => {
+        let local_detector = find_whisper_executable().ok().zip(
+          resolve_local_model_path(
+            detection_model
+              .as_deref()
+              .unwrap_or(DEFAULT_LANGUAGE_DETECTION_MODEL),
+          )
+          .ok(),
+        );
+
+        match local_detector {
+          Some((whisper_executable, model_path)) => {
+            let mut chunks = detect_windows_language(
+              &whisper_executable,
+              &model_path,
+              &audio_file_path,
+              &windows,
+              &work_dir,
+            )
+            .await?;
+            resolve_chunk_languages(&mut chunks, options.min_language_confidence);
+            chunks
+          }
+          None => windows
+            .iter()
+            .map(|window| ChunkLanguage {
+              window: *window,
+              language: None,
+              confidence: 0.0,
+            })
+            .collect(),
+        }
+      }
+    };
+
+    let client = reqwest::Client::new();
+    let mut segments = Vec::new();
+    for (index, chunk) in chunks.iter_mut().enumerate() {
+      let chunk_audio =
+        extract_audio_window(&audio_file_path, chunk.window, &work_dir, index).await?;
+      let file_content = fs::read(&chunk_audio)
+        .await
+        .map_err(|e| VideoCompilerError::IoError(format!("Ошибка чтения файла: {e}")))?;
+
+      let mut form = Form::new()
+        .text("model", model.clone())
+        .text("response_format", "verbose_json")
+        .part(
+          "file",
+          Part::bytes(file_content).file_name(format!("chunk_{index}.wav")),
+        );
+
+      // Подсказка языка для конкретного чанка
+      if let Some(lang) = &chunk.language {
+        form = form.text("language", lang.clone());
+      }
+
+      let response = client
+        .post("https://api.openai.com/v1/audio/transcriptions")
+        .header("Authorization", format!("Bearer {api_key}"))
+        .multipart(form)
+        .send()
+        .await
+        .map_err(|e| VideoCompilerError::IoError(format!("Ошибка запроса к OpenAI: {e}")))?;
+
+      let status = response.status();
+      if !status.is_success() {
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(VideoCompilerError::ValidationError(
+          format!("OpenAI API error {status}: {error_text}").into(),
+        ));
+      }
+
+      let response_text = response.text().await.map_err(|e| {
+        VideoCompilerError::SerializationError(format!("Ошибка получения ответа: {e}"))
+      })?;
+
+      if chunk.language.is_none() {
+        chunk.language = serde_json::from_str::<serde_json::Value>(&response_text)
+          .ok()
+          .and_then(|json| json.get("language")?.as_str().map(normalize_language_code));
+      }
+
+      let transcription = convert_api_response_to_transcription(&response_text, None, None)
+        .map_err(VideoCompilerError::SerializationError)?;
+      segments.extend(shift_chunk_segments(
+        transcription.segments.unwrap_or_default(),
+        chunk,
+        segments.len() as u32,
+      ));
+    }
+
+    Ok::<_, VideoCompilerError>(build_multilingual_result(segments, &chunks, duration))
+  }
+  .await;
+  let _ = fs::remove_dir_all(&work_dir).await;
+
+  result
+}
+
+/// Преобразовать сегменты транскрипции в субтитры с учетом языков
+#[tauri::command]
+pub async fn whisper_transcript_to_subtitles(
+  segments: Vec<WhisperSegment>,
+  mode: Option<SubtitleLanguageMode>,
+) -> Result<Vec<TranscriptSubtitleSet>> {
+  Ok(transcript_to_subtitles(&segments, mode.unwrap_or_default()))
+}
+
+/// Собрать итоговый результат многоязычной транскрипции
+fn build_multilingual_result(
+  segments: Vec<WhisperSegment>,
+  chunks: &[ChunkLanguage],
+  duration: f64,
+) -> WhisperTranscriptionResult {
+  let text = segments
+    .iter()
+    .map(|segment| segment.text.trim())
+    .filter(|text| !text.is_empty())
+    .collect::<Vec<_>>()
+    .join(" ");
+
+  WhisperTranscriptionResult {
+    text,
+    language: aggregate_language_detections(chunks, 1)
+      .into_iter()
+      .next()
+      .map(|detection| detection.language),
+    duration: Some(duration),
+    segments: Some(segments),
+    words: None,
+  }
+}
+
+/// Определить язык для каждого окна аудио
+async fn detect_windows_language(
+  whisper_executable: &Path,
+  model_path: &Path,
+  file_path: &str,
+  windows: &[AudioWindow],
+  work_dir: &Path,
+) -> Result<Vec<ChunkLanguage>> {
+  let mut results = Vec::with_capacity(windows.len());
+  for (index, window) in windows.iter().enumerate() {
+    let chunk_audio = extract_audio_window(file_path, *window, work_dir, index).await?;
+
+    let mut cmd = tokio::process::Command::new(whisper_executable);
+    cmd.args([
+      "-m",
+      &model_path.to_string_lossy(),
+      "-f",
+      &chunk_audio.to_string_lossy(),
+      "-l",
+      "auto",
+      "--detect-language",
+    ]);
+
+    let exec = run_whisper_cpp(cmd).await?;
+    let detection = parse_whisper_cpp_detected_language(&exec.stderr)
+      .or_else(|| parse_whisper_cpp_detected_language(&exec.stdout));
+
+    results.push(ChunkLanguage {
+      window: *window,
+      confidence: detection.as_ref().map_or(0.0, |d| d.confidence),
+      language: detection.map(|d| d.language),
+    });
+  }
+  Ok(results)
+}
+
+/// Вырезать окно аудио в WAV 16 кГц моно
+async fn extract_audio_window(
+  file_path: &str,
+  window: AudioWindow,
+  work_dir: &Path,
+  index: usize,
+) -> Result<PathBuf> {
+  let output = work_dir.join(format!("chunk_{index}.wav"));
+
+  let mut cmd = tokio::process::Command::new("ffmpeg");
+  cmd.args([
+    "-ss",
+    &format!("{:.3}", window.start),
+    "-t",
+    &format!("{:.3}", window.duration()),
+    "-i",
+    file_path,
+    "-vn",
+    "-acodec",
+    "pcm_s16le",
+    "-ar",
+    "16000",
+    "-ac",
+    "1",
+    "-y",
+    &output.to_string_lossy(),
+  ]);
+
+  let result = FFmpegExecutor::new()
+    .execute(cmd)
+    .await
+    .map_err(|e| VideoCompilerError::IoError(format!("Ошибка извлечения аудио: {e}")))?;
+
+  if result.exit_code != 0 {
+    return Err(VideoCompilerError::FFmpegError {
+      exit_code: Some(result.exit_code),
+      stderr: result.stderr,
+      command: "ffmpeg".to_string(),
+    });
+  }
+
+  Ok(output)
+}
+
+/// Получить длительность медиафайла через ffprobe
+async fn probe_media_duration(file_path: &str) -> Result<f64> {
+  let mut cmd = tokio::process::Command::new("ffprobe");
+  cmd.args([
+    "-v",
+    "error",
+    "-show_entries",
+    "format=duration",
+    "-of",
+    "default=noprint_wrappers=1:nokey=1",
+    file_path,
+  ]);
+
+  let result = FFmpegExecutor::new()
+    .execute(cmd)
+    .await
+    .map_err(|e| VideoCompilerError::IoError(format!("Ошибка выполнения ffprobe: {e}")))?;
+
+  if result.exit_code != 0 {
+    return Err(VideoCompilerError::FFmpegError {
+      exit_code: Some(result.exit_code),
+      stderr: result.stderr,
+      command: "ffprobe".to_string(),
+    });
+  }
+
+  result
+    .stdout
+    .trim()
+    .parse::<f64>()
+    .ok()
+    .filter(|duration| *duration > 0.0)
+    .ok_or_else(|| VideoCompilerError::MediaFileError {
+      path: file_path.to_string(),
+      reason: "Не удалось определить длительность аудио".to_string(),
+    })
+}
+
+/// Выполнить whisper.cpp и проверить код выхода
+async fn run_whisper_cpp(
+  cmd: tokio::process::Command,
+) -> Result<crate::video_compiler::ffmpeg_executor::FFmpegExecutionResult> {
+  let result = FFmpegExecutor::new()
+    .execute(cmd)
+    .await
+    .map_err(|e| VideoCompilerError::IoError(format!("Ошибка выполнения whisper.cpp: {e}")))?;
+
+  if result.exit_code != 0 {
+    return Err(VideoCompilerError::FFmpegError {
+      exit_code: Some(result.exit_code),
+      stderr: result.stderr,
+      command: "whisper.cpp".to_string(),
+    });
+  }
+
+  Ok(result)
+}
+
+/// Создать временную директорию для чанков
+async fn create_chunk_work_dir() -> Result<PathBuf> {
+  let dir = std::env::temp_dir()
+    .join("timeline_studio_whisper")
+    .join(format!("chunks_{}", uuid::Uuid::new_v4()));
+  fs::create_dir_all(&dir).await.map_err(|e| {
+    VideoCompilerError::IoError(format!("Ошибка создания временной директории: {e}"))
+  })?;
+  Ok(dir)
+}
+
+/// Проверить существование аудиофайла
+fn ensure_audio_file_exists(file_path: &str) -> Result<()> {
+  if !Path::new(file_path).exists() {
+    return Err(VideoCompilerError::MediaFileError {
+      path: file_path.to_string(),
+      reason: "Аудио файл не найден".to_string(),
+    });
+  }
+  Ok(())
+}
+
+/// Найти исполняемый файл whisper.cpp
+fn find_whisper_executable() -> Result<PathBuf> {
+  which::which("whisper")
+    .or_else(|_| which::which("whisper.cpp"))
+    .map_err(|_| VideoCompilerError::DependencyMissing("Whisper.cpp не найден".into()))
+}
+
+/// Получить путь к скачанной локальной модели
+fn resolve_local_model_path(model_name: &str) -> Result<PathBuf> {
+  let model_path =
+    get_whisper_model_path(model_name).map_err(VideoCompilerError::InvalidParameter)?;
+  if !model_path.exists() {
+    return Err(VideoCompilerError::MediaFileError {
+      path: model_path.to_string_lossy().to_string(),
+      reason: format!("Модель {model_name} не найдена"),
+    });
+  }
+  Ok(model_path)
+}
+
+/// Проверить код языка
+fn validate_language_code(code: &str) -> Result<()> {
+  if code.len() != 2 {
+    return Err(VideoCompilerError::InvalidParameter(
+      "Код языка должен состоять из 2 символов (например, 'ru', 'en')".to_string(),
+    ));
+  }
+  Ok(())
+}
+
+crate::command_manifest!(
+  WHISPER_COMMANDS_MANIFEST,
+  "video_compiler::whisper_commands",
+  [
+    whisper_transcribe_openai,
+    whisper_translate_openai,
+    whisper_transcribe_local,
+    whisper_get_local_models,
+    whisper_download_model,
+    whisper_check_local_availability,
+    extract_audio_for_whisper,
+    detect_audio_language,
+    whisper_transcribe_local_multilingual,
+    whisper_transcribe_openai_multilingual,
+    whisper_transcript_to_subtitles,
+  ]
+);
@@ -228,6 +228,261 @@ fn test_convert_api_response_to_transcription_plain_text() {
   assert!(transcription.words.is_none());
 }
 
+// ============ Тесты определения языка ============
+
+fn test_segment(id: u32, start: f64, end: f64, text: &str) -> WhisperSegment {
+  WhisperSegment {
+    id,
+    seek: 0,
+    start,
+    end,
+    text: text.to_string(),
+    tokens: vec![],
+    temperature: 0.0,
+    avg_logprob: -0.3,
+    compression_ratio: 1.2,
+    no_speech_prob: 0.01,
+    language: None,
+  }
+}
+
+fn detected_chunk(start: f64, end: f64, language: Option<&str>, confidence: f64) -> ChunkLanguage {
+  ChunkLanguage {
+    window: AudioWindow { start, end },
+    language: language.map(str::to_string),
+    confidence,
+  }
+}
+
+#[test]
+fn test_parse_whisper_cpp_detected_language() {
+  let stderr = "whisper_init_from_file: loading model\n\
+                whisper_full_with_state: auto-detected language: ru (p = 0.913245)\n";
+  let detection = parse_whisper_cpp_detected_language(stderr).unwrap();
+  assert_eq!(detection.language, "ru");
+  assert!((detection.confidence - 0.913245).abs() < 1e-9);
+
+  assert!(parse_whisper_cpp_detected_language("no detection here").is_none());
+}
+
+#[test]
+fn test_normalize_language_code() {
+  assert_eq!(normalize_language_code("Russian"), "ru");
+  assert_eq!(normalize_language_code("english"), "en");
+  assert_eq!(normalize_language_code("EN"), "en");
+}
+
+#[test]
+fn test_plan_transcription_chunks() {
+  let chunks = plan_transcription_chunks(70.0, 30.0);
+  assert_eq!(chunks.len(), 3);
+  assert_eq!(
+    chunks[0],
+    AudioWindow {
+      start: 0.0,
+      end: 30.0
+    }
+  );
+  assert_eq!(
+    chunks[2],
+    AudioWindow {
+      start: 60.0,
+      end: 70.0
+    }
+  );
+
+  assert_eq!(plan_transcription_chunks(20.0, 30.0).len(), 1);
+  assert!(plan_transcription_chunks(0.0, 30.0).is_empty());
+}
+
+#[test]
+fn test_plan_language_sample_windows() {
+  let windows = plan_language_sample_windows(
+    300.0,
+    &LanguageSampleStrategy::Uniform {
+      windows: 3,
+      window_duration: 20.0,
+    },
+  );
+  assert_eq!(windows.len(), 3);
+  assert_eq!(
+    windows[0],
+    AudioWindow {
+      start: 40.0,
+      end: 60.0
+    }
+  );
+  assert_eq!(
+    windows[1],
+    AudioWindow {
+      start: 140.0,
+      end: 160.0
+    }
+  );
+  assert_eq!(
+    windows[2],
+    AudioWindow {
+      start: 240.0,
+      end: 260.0
+    }
+  );
+
+  let head = plan_language_sample_windows(
+    10.0,
+    &LanguageSampleStrategy::Head {
+      window_duration: 30.0,
+    },
+  );
+  assert_eq!(
+    head,
+    vec![AudioWindow {
+      start: 0.0,
+      end: 10.0
+    }]
+  );
+}
+
+#[test]
+fn test_aggregate_language_detections() {
+  let windows = vec![
+    detected_chunk(0.0, 30.0, Some("en"), 0.9),
+    detected_chunk(30.0, 60.0, Some("ru"), 0.8),
+    detected_chunk(60.0, 90.0, Some("en"), 0.7),
+    detected_chunk(90.0, 120.0, None, 0.0),
+  ];
+
+  let languages = aggregate_language_detections(&windows, 3);
+  assert_eq!(languages.len(), 2);
+  assert_eq!(languages[0].language, "en");
+  assert_eq!(languages[1].language, "ru");
+  assert!((languages[0].confidence - 1.6 / 2.4).abs() < 1e-9);
+
+  assert_eq!(aggregate_language_detections(&windows, 1).len(), 1);
+  assert!(aggregate_language_detections(&[], 3).is_empty());
+}
+
+#[test]
+fn test_resolve_chunk_languages_switches_on_confident_chunks() {
+  let mut chunks = vec![
+    detected_chunk(0.0, 30.0, Some("en"), 0.95),
+    detected_chunk(30.0, 60.0, Some("ru"), 0.9),
+    detected_chunk(60.0, 90.0, Some("en"), 0.85),
+  ];
+  resolve_chunk_languages(&mut chunks, 0.5);
+
+  let languages: Vec<_> = chunks.iter().map(|c| c.language.as_deref()).collect();
+  assert_eq!(languages, vec![Some("en"), Some("ru"), Some("en")]);
+}
+
+#[test]
+fn test_resolve_chunk_languages_keeps_language_on_uncertain_chunks() {
+  let mut chunks = vec![
+    detected_chunk(0.0, 30.0, None, 0.0),
+    detected_chunk(30.0, 60.0, Some("ru"), 0.9),
+    detected_chunk(60.0, 90.0, Some("de"), 0.2),
+    detected_chunk(90.0, 120.0, None, 0.0),
+    detected_chunk(120.0, 150.0, Some("en"), 0.8),
+  ];
+  resolve_chunk_languages(&mut chunks, 0.5);
+
+  let languages: Vec<_> = chunks.iter().map(|c| c.language.as_deref()).collect();
+  assert_eq!(
+    languages,
+    vec![Some("ru"), Some("ru"), Some("ru"), Some("ru"), Some("en")]
+  );
+}
+
+#[test]
+fn test_shift_chunk_segments_offsets_and_tags() {
+  let chunk = detected_chunk(30.0, 60.0, Some("ru"), 0.9);
+  let segments = vec![
+    test_segment(0, 0.0, 4.0, "Привет"),
+    test_segment(1, 4.0, 9.5, "как дела"),
+  ];
+
+  let shifted = shift_chunk_segments(segments, &chunk, 5);
+  assert_eq!(shifted[0].id, 5);
+  assert_eq!(shifted[1].id, 6);
+  assert_eq!(shifted[0].start, 30.0);
+  assert_eq!(shifted[1].end, 39.5);
+  assert!(shifted.iter().all(|s| s.language.as_deref() == Some("ru")));
+}
+
+#[test]
+fn test_tag_segments_with_languages() {
+  let chunks = vec![
+    detected_chunk(0.0, 30.0, Some("en"), 0.9),
+    detected_chunk(30.0, 60.0, Some("ru"), 0.9),
+  ];
+  let mut segments = vec![
+    test_segment(0, 2.0, 10.0, "Hello"),
+    // Середина сегмента (31.0) попадает во второй чанк
+    test_segment(1, 28.0, 34.0, "Здравствуйте"),
+    test_segment(2, 59.0, 62.0, "Пока"),
+    test_segment(3, 40.0, 45.0, "already tagged"),
+  ];
+  segments[3].language = Some("en".to_string());
+
+  tag_segments_with_languages(&mut segments, &chunks);
+
+  assert_eq!(segments[0].language.as_deref(), Some("en"));
+  assert_eq!(segments[1].language.as_deref(), Some("ru"));
+  assert_eq!(segments[2].language.as_deref(), Some("ru"));
+  assert_eq!(segments[3].language.as_deref(), Some("en"));
+}
+
+#[test]
+fn test_transcript_to_subtitles_tag_mode() {
+  let mut segments = vec![
+    test_segment(0, 0.0, 3.0, " Hello "),
+    test_segment(1, 3.0, 6.0, "Привет"),
+    test_segment(2, 6.0, 6.0, "empty duration"),
+  ];
+  segments[0].language = Some("en".to_string());
+  segments[1].language = Some("ru".to_string());
+
+  let sets = transcript_to_subtitles(&segments, SubtitleLanguageMode::Tag);
+  assert_eq!(sets.len(), 1);
+  assert!(sets[0].language.is_none());
+  assert_eq!(sets[0].subtitles.len(), 2);
+  assert_eq!(sets[0].subtitles[0].text, "Hello");
+  assert_eq!(sets[0].subtitles[0].language.as_deref(), Some("en"));
+  assert_eq!(sets[0].subtitles[1].language.as_deref(), Some("ru"));
+}
+
+#[test]
+fn test_transcript_to_subtitles_split_by_language() {
+  let mut segments = vec![
+    test_segment(0, 0.0, 3.0, "Hello"),
+    test_segment(1, 3.0, 6.0, "Привет"),
+    test_segment(2, 6.0, 9.0, "Goodbye"),
+  ];
+  segments[0].language = Some("en".to_string());
+  segments[1].language = Some("ru".to_string());
+  segments[2].language = Some("en".to_string());
+
+  let sets = transcript_to_subtitles(&segments, SubtitleLanguageMode::SplitByLanguage);
+  assert_eq!(sets.len(), 2);
+  assert_eq!(sets[0].language.as_deref(), Some("en"));
+  assert_eq!(sets[0].subtitles.len(), 2);
+  assert_eq!(sets[1].language.as_deref(), Some("ru"));
+  assert_eq!(sets[1].subtitles[0].text, "Привет");
+}
+
+#[test]
+fn test_transcription_language_serialization() {
+  let auto: TranscriptionLanguage = serde_json::from_str(r#"{"mode":"auto"}"#).unwrap();
+  assert_eq!(auto, TranscriptionLanguage::Auto);
+
+  let fixed: TranscriptionLanguage =
+    serde_json::from_str(r#"{"mode":"fixed","code":"ru"}"#).unwrap();
+  assert_eq!(fixed, TranscriptionLanguage::Fixed("ru".to_string()));
+
+  let options: WhisperTranscriptionOptions = serde_json::from_str("{}").unwrap();
+  assert_eq!(options.language, TranscriptionLanguage::Auto);
+  assert_eq!(options.chunk_duration, 30.0);
+}
+
 // ============ Тесты сериализации ============
 
 #[test]
@@ -243,6 +498,7 @@ fn test_whisper_segment_serialization() {
     avg_logprob: -0.8,
     compression_ratio: 1.15,
     no_speech_prob: 0.05,
+    language: Some("en".to_string()),
   };
 
   let serialized = serde_json::to_string(&segment).unwrap();
@@ -251,6 +507,7 @@ fn test_whisper_segment_serialization() {
   assert_eq!(segment.id, deserialized.id);
   assert_eq!(segment.text, deserialized.text);
   assert_eq!(segment.tokens, deserialized.tokens);
+  assert_eq!(segment.language, deserialized.language);
 }
 
 #[test]
@@ -15,6 +15,9 @@ pub struct WhisperSegment {
   pub avg_logprob: f64,
   pub compression_ratio: f64,
   pub no_speech_prob: f64,
+  /// Код языка сегмента (заполняется при автоопределении по чанкам)
+  #[serde(default)]
+  pub language: Option<String>,
 }
 
 /// Отдельное слово в транскрипции с временными метками
@@ -52,3 +55,121 @@ pub struct LocalWhisperModel {
   pub is_downloaded: bool,
   pub download_url: Option<String>,
 }
+
+/// Выбор языка транскрипции
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", content = "code", rename_all = "snake_case")]
+pub enum TranscriptionLanguage {
+  /// Автоопределение языка для каждого чанка
+  #[default]
+  Auto,
+  /// Фиксированный язык для всего файла
+  Fixed(String),
+}
+
+/// Стратегия выборки окон для определения языка
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LanguageSampleStrategy {
+  /// Одно окно в начале файла
+  Head { window_duration: f64 },
+  /// Несколько окон, равномерно распределенных по файлу
+  Uniform { windows: u32, window_duration: f64 },
+}
+
+impl Default for LanguageSampleStrategy {
+  fn default() -> Self {
+    Self::Uniform {
+      windows: 3,
+      window_duration: 30.0,
+    }
+  }
+}
+
+/// Временное окно аудио в секундах
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AudioWindow {
+  pub start: f64,
+  pub end: f64,
+}
+
+impl AudioWindow {
+  /// Длительность окна
+  pub fn duration(&self) -> f64 {
+    (self.end - self.start).max(0.0)
+  }
+}
+
+/// Язык с уверенностью определения
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LanguageDetection {
+  pub language: String,
+  pub confidence: f64,
+}
+
+/// Результат определения языка для одного окна или чанка
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChunkLanguage {
+  pub window: AudioWindow,
+  pub language: Option<String>,
+  pub confidence: f64,
+}
+
+/// Отчет об определении языка аудио
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioLanguageReport {
+  /// Языки, отсортированные по убыванию уверенности
+  pub languages: Vec<LanguageDetection>,
+  /// Результаты по отдельным окнам
+  pub windows: Vec<ChunkLanguage>,
+}
+
+/// Опции многоязычной транскрипции
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhisperTranscriptionOptions {
+  #[serde(default)]
+  pub language: TranscriptionLanguage,
+  /// Длительность чанка в секундах
+  #[serde(default = "default_chunk_duration")]
+  pub chunk_duration: f64,
+  /// Минимальная уверенность, при которой чанк может сменить язык
+  #[serde(default = "default_min_language_confidence")]
+  pub min_language_confidence: f64,
+}
+
+fn default_chunk_duration() -> f64 {
+  30.0
+}
+
+fn default_min_language_confidence() -> f64 {
+  0.5
+}
+
+impl Default for WhisperTranscriptionOptions {
+  fn default() -> Self {
+    Self {
+      language: TranscriptionLanguage::Auto,
+      chunk_duration: default_chunk_duration(),
+      min_language_confidence: default_min_language_confidence(),
+    }
+  }
+}
+
+/// Режим преобразования транскрипции в субтитры
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubtitleLanguageMode {
+  /// Один набор субтитров, каждый субтитр помечен языком
+  #[default]
+  Tag,
+  /// Отдельный набор субтитров для каждого языка
+  SplitByLanguage,
+}
+
+/// Набор субтитров, полученный из транскрипции
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptSubtitleSet {
+  /// Язык набора (None для смешанного набора в режиме Tag)
+  pub language: Option<String>,
+  pub subtitles: Vec<crate::video_compiler::schema::Subtitle>,
+}
@@ -291,6 +291,19 @@ fn calculate_quality_score(width: u64, height: u64, bitrate: u64) -> u8 {
   std::cmp::min(100, resolution_score + bitrate_score + base_score) as u8
 }
 
+crate::command_manifest!(
+  WORKFLOW_COMMANDS_MANIFEST,
+  "video_compiler::workflow_commands",
+  [
+    create_directory,
+    create_timeline_project,
+    compile_workflow_video,
+    analyze_workflow_video_quality,
+    create_workflow_preview,
+    cleanup_workflow_temp_files,
+  ]
+);
+
 #[cfg(test)]
 mod tests {
   use super::*;
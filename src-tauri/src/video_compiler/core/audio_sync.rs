@@ -0,0 +1,208 @@
+//! Audio Sync - Оценка смещения синхронизации между двумя аудиозаписями
+//!
+//! Короткие окна PCM декодируются через FFmpeg в моно 8 кГц, после чего
+//! смещение ищется взаимной корреляцией: сначала грубо по огибающей энергии
+//! (кадры по 10 мс), затем уточняется по сэмплам вокруг найденной точки.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Stdio;
+
+use crate::video_compiler::error::{Result, VideoCompilerError};
+
+/// Частота дискретизации для анализа синхронизации
+pub const SYNC_SAMPLE_RATE: u32 = 8000;
+
+/// Длительность анализируемого окна по умолчанию (секунды)
+pub const DEFAULT_SYNC_WINDOW_SECS: f64 = 30.0;
+
+/// Максимальное искомое смещение по умолчанию (секунды)
+pub const DEFAULT_MAX_OFFSET_SECS: f64 = 10.0;
+
+/// Кадров огибающей в секунду (грубый поиск)
+const ENVELOPE_RATE: u32 = 100;
+
+/// Результат оценки смещения
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioOffsetEstimate {
+  /// Смещение в секундах: момент `t` эталона соответствует `t + offset_secs`
+  /// во второй записи
+  pub offset_secs: f64,
+  /// Нормализованная корреляция в найденной точке (0.0 - 1.0)
+  pub confidence: f32,
+}
+
+/// Декодировать окно аудио в моно PCM (f32, SYNC_SAMPLE_RATE)
+pub async fn decode_pcm_window(
+  ffmpeg_path: &str,
+  path: &Path,
+  start_secs: f64,
+  duration_secs: f64,
+) -> Result<Vec<f32>> {
+  let output = tokio::process::Command::new(ffmpeg_path)
+    .args(["-v", "error", "-ss", &start_secs.to_string(), "-t"])
+    .arg(duration_secs.to_string())
+    .arg("-i")
+    .arg(path)
+    .args(["-vn", "-ac", "1", "-ar", &SYNC_SAMPLE_RATE.to_string()])
+    .args(["-f", "s16le", "-"])
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .output()
+    .await
+    .map_err(|e| VideoCompilerError::IoError(format!("Не удалось запустить FFmpeg: {e}")))?;
+
+  if !output.status.success() {
+    return Err(VideoCompilerError::ffmpeg(
+      output.status.code(),
+      String::from_utf8_lossy(&output.stderr).to_string(),
+      "decode_pcm_window".to_string(),
+    ));
+  }
+
+  Ok(
+    output
+      .stdout
+      .chunks_exact(2)
+      .map(|b| i16::from_le_bytes([b[0], b[1]]) as f32 / i16::MAX as f32)
+      .collect(),
+  )
+}
+
+/// Оценить смещение `other` относительно `reference`
+pub fn estimate_offset(
+  reference: &[f32],
+  other: &[f32],
+  sample_rate: u32,
+  max_offset_secs: f64,
+) -> Option<AudioOffsetEstimate> {
+  let frame = (sample_rate / ENVELOPE_RATE).max(1) as usize;
+  let ref_env = envelope(reference, frame);
+  let other_env = envelope(other, frame);
+  if ref_env.len() < 2 || other_env.len() < 2 {
+    return None;
+  }
+
+  // Грубый поиск по огибающей
+  let max_env_lag = (max_offset_secs * ENVELOPE_RATE as f64).ceil() as isize;
+  let (coarse_lag, _) = best_lag(&ref_env, &other_env, -max_env_lag, max_env_lag)?;
+
+  // Уточнение по сэмплам в пределах двух кадров огибающей
+  let center = coarse_lag * frame as isize;
+  let radius = 2 * frame as isize;
+  let (fine_lag, confidence) =
+    best_lag(reference, other, center - radius, center + radius).unwrap_or((center, 0.0));
+
+  Some(AudioOffsetEstimate {
+    offset_secs: fine_lag as f64 / sample_rate as f64,
+    confidence: confidence.clamp(0.0, 1.0),
+  })
+}
+
+/// Огибающая энергии (RMS по кадрам) с вычтенным средним
+fn envelope(samples: &[f32], frame: usize) -> Vec<f32> {
+  let mut env: Vec<f32> = samples
+    .chunks(frame)
+    .map(|chunk| (chunk.iter().map(|s| s * s).sum::<f32>() / chunk.len() as f32).sqrt())
+    .collect();
+
+  if !env.is_empty() {
+    let mean = env.iter().sum::<f32>() / env.len() as f32;
+    env.iter_mut().for_each(|v| *v -= mean);
+  }
+  env
+}
+
+/// Найти лаг с максимальной нормализованной корреляцией: `a[i] ~ b[i + lag]`
+fn best_lag(a: &[f32], b: &[f32], min_lag: isize, max_lag: isize) -> Option<(isize, f32)> {
+  let mut best: Option<(isize, f32)> = None;
+  // Требуем минимальное перекрытие, чтобы не выбирать случайные края
+  let min_overlap = (a.len().min(b.len()) / 4).max(1);
+
+  for lag in min_lag..=max_lag {
+    let (a_start, b_start) = if lag >= 0 {
+      (0usize, lag as usize)
+    } else {
+      ((-lag) as usize, 0usize)
+    };
+    if a_start >= a.len() || b_start >= b.len() {
+      continue;
+    }
+    let len = (a.len() - a_start).min(b.len() - b_start);
+    if len < min_overlap {
+      continue;
+    }
+
+    let (mut dot, mut norm_a, mut norm_b) = (0.0f32, 0.0f32, 0.0f32);
+    for i in 0..len {
+      let x = a[a_start + i];
+      let y = b[b_start + i];
+      dot += x * y;
+      norm_a += x * x;
+      norm_b += y * y;
+    }
+
+    let denom = (norm_a * norm_b).sqrt();
+    if denom <= f32::EPSILON {
+      continue;
+    }
+    let score = dot / denom;
+    if best.is_none_or(|(_, best_score)| score > best_score) {
+      best = Some((lag, score));
+    }
+  }
+
+  best
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Детерминированный "шум" с паузами, похожий на речь
+  fn synthetic_signal(len: usize) -> Vec<f32> {
+    let mut state = 12345u32;
+    (0..len)
+      .map(|i| {
+        state = state.wrapping_mul(1103515245).wrapping_add(12345);
+        let noise = ((state >> 16) as f32 / 32768.0) - 1.0;
+        // Огибающая: чередование громких и тихих участков разной длины
+        let gate = if (i / 1700) % 3 == 0 || (i / 2300) % 4 == 1 {
+          1.0
+        } else {
+          0.1
+        };
+        noise * gate
+      })
+      .collect()
+  }
+
+  #[test]
+  fn test_estimate_positive_offset() {
+    let source = synthetic_signal(SYNC_SAMPLE_RATE as usize * 6);
+    // Вторая запись начата на 0.5с раньше: эталон[t] == other[t + 0.5]
+    let shift = SYNC_SAMPLE_RATE as usize / 2;
+    let reference = source[shift..].to_vec();
+    let other = source.clone();
+
+    let estimate = estimate_offset(&reference, &other, SYNC_SAMPLE_RATE, 2.0).unwrap();
+    assert!((estimate.offset_secs - 0.5).abs() < 0.002);
+    assert!(estimate.confidence > 0.9);
+  }
+
+  #[test]
+  fn test_estimate_negative_offset() {
+    let source = synthetic_signal(SYNC_SAMPLE_RATE as usize * 6);
+    let shift = SYNC_SAMPLE_RATE as usize / 4;
+    let reference = source.clone();
+    let other = source[shift..].to_vec();
+
+    let estimate = estimate_offset(&reference, &other, SYNC_SAMPLE_RATE, 2.0).unwrap();
+    assert!((estimate.offset_secs + 0.25).abs() < 0.002);
+  }
+
+  #[test]
+  fn test_estimate_empty_input() {
+    assert!(estimate_offset(&[], &[0.1, 0.2], SYNC_SAMPLE_RATE, 1.0).is_none());
+  }
+}
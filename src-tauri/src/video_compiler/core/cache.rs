@@ -2,13 +2,25 @@
 //!
 //! Этот модуль реализует LRU кэш для превью кадров, промежуточных результатов
 //! рендеринга и метаданных медиа файлов для улучшения производительности.
+//! Превью дополнительно могут сохраняться на диск (см. [`disk`]), чтобы
+//! переживать перезапуск приложения.
 
+pub mod disk;
+
+pub use disk::{DiskCacheEntry, DiskCacheIndex, DiskPreviewCache};
+
+use crate::core::performance::memory_pressure::{MemoryShrinker, ShrinkRequest};
+use crate::video_compiler::core::priority;
 use crate::video_compiler::error::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::time::{Duration, SystemTime};
 
+/// Версия схемы метаданных. Увеличивается при изменении пробинга, чтобы
+/// записи, извлеченные прежней версией, считались устаревшими.
+pub const METADATA_SCHEMA_VERSION: u32 = 2;
+
 /// Основной кэш Video Compiler
 #[derive(Debug)]
 pub struct RenderCache {
@@ -22,6 +34,10 @@ pub struct RenderCache {
   settings: CacheSettings,
   /// Статистика использования
   stats: CacheStats,
+  /// Дисковый слой кэша превью (если включен)
+  disk: Option<DiskPreviewCache>,
+  /// Общий лимит памяти и диска в байтах (CompilerSettings.cache_size_mb)
+  max_total_bytes: Option<u64>,
 }
 
 impl RenderCache {
@@ -38,10 +54,31 @@ impl RenderCache {
       render_cache: LruCache::new(settings.max_render_entries),
       settings,
       stats: CacheStats::default(),
+      disk: None,
+      max_total_bytes: None,
     }
   }
 
-  /// Получить превью кадр из кэша
+  /// Создать кэш с дисковым слоем превью.
+  ///
+  /// `cache_size_mb` ограничивает суммарный объем превью в памяти и на диске.
+  pub fn with_disk_cache(
+    settings: CacheSettings,
+    cache_dir: PathBuf,
+    cache_size_mb: usize,
+  ) -> Self {
+    let mut cache = Self::with_settings(settings);
+    cache.disk = Some(DiskPreviewCache::new(cache_dir));
+    cache.max_total_bytes = Some(cache_size_mb as u64 * 1024 * 1024);
+    cache
+  }
+
+  /// Включен ли дисковый слой
+  pub fn has_disk_cache(&self) -> bool {
+    self.disk.is_some()
+  }
+
+  /// Получить превью кадр из кэша (память, затем диск)
   pub async fn get_preview(&mut self, key: &PreviewKey) -> Option<PreviewData> {
     self.stats.preview_requests += 1;
 
@@ -49,6 +86,7 @@ impl RenderCache {
       // Проверяем, не истек ли кэш
       if !data.is_expired(self.settings.preview_ttl) {
         self.stats.preview_hits += 1;
+        self.stats.preview_memory_hits += 1;
         return Some(data.clone());
       } else {
         // Удаляем истекший элемент
@@ -56,12 +94,40 @@ impl RenderCache {
       }
     }
 
+    if let Some(disk) = self.disk.as_mut() {
+      if let Some(image_data) = disk.get(key).await {
+        let preview_data = PreviewData {
+          image_data,
+          timestamp: SystemTime::now(),
+          _access_count: 0,
+        };
+        self.preview_cache.insert(key.clone(), preview_data.clone());
+        self.stats.preview_hits += 1;
+        self.stats.preview_disk_hits += 1;
+        return Some(preview_data);
+      }
+    }
+
     self.stats.preview_misses += 1;
     None
   }
 
-  /// Сохранить превью кадр в кэш
+  /// Сохранить превью кадр в кэш.
+  ///
+  /// Из фоновой области (прогрев) запись не вытесняет существующие превью:
+  /// при заполненном кэше она пропускается.
   pub async fn store_preview(&mut self, key: PreviewKey, data: Vec<u8>) -> Result<()> {
+    if priority::is_background() && self.preview_cache.would_evict(&key) {
+      return Ok(());
+    }
+
+    if let Some(disk) = self.disk.as_mut() {
+      // Ошибка записи на диск не должна ломать кэширование в памяти
+      if let Err(e) = disk.put(&key, &data).await {
+        log::warn!("Не удалось сохранить превью на диск: {e}");
+      }
+    }
+
     let preview_data = PreviewData {
       image_data: data,
       timestamp: SystemTime::now(),
@@ -69,16 +135,89 @@ impl RenderCache {
     };
 
     self.preview_cache.insert(key, preview_data);
+    self.enforce_total_size_limit().await;
     self.cleanup_if_needed().await?;
     Ok(())
   }
 
+  /// Объем превью в памяти (байты данных изображений)
+  fn preview_memory_bytes(&self) -> u64 {
+    self
+      .preview_cache
+      .iter()
+      .map(|(_, data)| data.image_data.len() as u64)
+      .sum()
+  }
+
+  /// Соблюдение общего лимита памяти и диска (LRU вытеснение)
+  async fn enforce_total_size_limit(&mut self) {
+    let Some(max_total) = self.max_total_bytes else {
+      return;
+    };
+
+    let memory_bytes = self.preview_memory_bytes();
+    let disk_bytes = match self.disk.as_mut() {
+      Some(disk) => disk.total_bytes().await,
+      None => 0,
+    };
+
+    let total = memory_bytes + disk_bytes;
+    if total <= max_total {
+      return;
+    }
+
+    let mut excess = total - max_total;
+    if let Some(disk) = self.disk.as_mut() {
+      let freed = disk.evict_lru(excess).await;
+      self.stats.evictions += 1;
+      excess = excess.saturating_sub(freed);
+    }
+
+    // Диска не хватило — вытесняем самые старые превью из памяти
+    while excess > 0 {
+      let Some(oldest) = self
+        .preview_cache
+        .iter()
+        .min_by_key(|(_, data)| data.timestamp)
+        .map(|(key, data)| (key.clone(), data.image_data.len() as u64))
+      else {
+        break;
+      };
+      self.preview_cache.remove(&oldest.0);
+      excess = excess.saturating_sub(oldest.1);
+      self.stats.evictions += 1;
+    }
+  }
+
+  /// Объем превью на диске в байтах
+  pub async fn get_disk_usage(&mut self) -> u64 {
+    match self.disk.as_mut() {
+      Some(disk) => disk.total_bytes().await,
+      None => 0,
+    }
+  }
+
+  /// Сохранить индекс дискового кэша
+  pub async fn flush_disk_index(&self) -> Result<()> {
+    match &self.disk {
+      Some(disk) => disk.flush().await,
+      None => Ok(()),
+    }
+  }
+
+  /// Есть ли актуальные метаданные файла в кэше (без учета в статистике)
+  pub fn has_metadata(&self, file_path: &str) -> bool {
+    self.metadata_cache.map.get(file_path).is_some_and(|node| {
+      !node.value.is_expired(self.settings.metadata_ttl) && !node.value.is_outdated()
+    })
+  }
+
   /// Получить метаданные файла из кэша
   pub async fn get_metadata(&mut self, file_path: &str) -> Option<MediaMetadata> {
     self.stats.metadata_requests += 1;
 
     if let Some(metadata) = self.metadata_cache.get(&file_path.to_string()) {
-      if !metadata.is_expired(self.settings.metadata_ttl) {
+      if !metadata.is_expired(self.settings.metadata_ttl) && !metadata.is_outdated() {
         self.stats.metadata_hits += 1;
         return Some(metadata.clone());
       } else {
@@ -90,13 +229,31 @@ impl RenderCache {
     None
   }
 
-  /// Сохранить метаданные файла в кэш
+  /// Сохранить метаданные файла в кэш.
+  ///
+  /// Как и для превью, фоновая запись не вытесняет существующие метаданные.
   pub async fn store_metadata(&mut self, file_path: String, metadata: MediaMetadata) -> Result<()> {
+    if priority::is_background() && self.metadata_cache.would_evict(&file_path) {
+      return Ok(());
+    }
+
     self.metadata_cache.insert(file_path, metadata);
     self.cleanup_if_needed().await?;
     Ok(())
   }
 
+  /// Перенести метаданные на новый путь (файл был перемещен)
+  pub fn relink_metadata(&mut self, old_path: &str, new_path: &str) -> bool {
+    match self.metadata_cache.remove(&old_path.to_string()) {
+      Some(mut metadata) => {
+        metadata.file_path = new_path.to_string();
+        self.metadata_cache.insert(new_path.to_string(), metadata);
+        true
+      }
+      None => false,
+    }
+  }
+
   /// Получить данные рендеринга из кэша
   #[allow(dead_code)] // Used in logic functions and tests
   pub async fn get_render_data(&mut self, cache_key: &str) -> Option<RenderCacheData> {
@@ -127,11 +284,26 @@ impl RenderCache {
     Ok(())
   }
 
+  /// Есть ли действующие данные рендеринга (без учета в статистике)
+  pub fn has_render_data(&self, cache_key: &str) -> bool {
+    self
+      .render_cache
+      .map
+      .get(cache_key)
+      .is_some_and(|node| !node.value.is_expired(self.settings.render_ttl))
+  }
+
+  /// Удалить данные рендеринга из кэша
+  pub fn remove_render_data(&mut self, cache_key: &str) -> Option<RenderCacheData> {
+    self.render_cache.remove(&cache_key.to_string())
+  }
+
   /// Очистить весь кэш
   pub async fn clear_all(&mut self) {
     self.preview_cache.clear();
     self.metadata_cache.clear();
     self.render_cache.clear();
+    self.clear_disk_previews().await;
     self.stats = CacheStats::default();
   }
 
@@ -152,6 +324,16 @@ impl RenderCache {
   /// Очистить только превью кэш
   pub async fn clear_previews(&mut self) {
     self.preview_cache.clear();
+    self.clear_disk_previews().await;
+  }
+
+  /// Удалить превью с диска
+  async fn clear_disk_previews(&mut self) {
+    if let Some(disk) = self.disk.as_mut() {
+      if let Err(e) = disk.clear().await {
+        log::warn!("Не удалось очистить дисковый кэш превью: {e}");
+      }
+    }
   }
 
   /// Получить статистику кэша
@@ -264,6 +446,28 @@ impl RenderCache {
       self.settings.max_render_entries,
     )
   }
+
+  /// Сбросить долю превью и промежуточных результатов при нехватке памяти.
+  ///
+  /// Метаданные не трогаются: они малы, а повторное извлечение требует ffprobe.
+  /// Лимиты кэша не меняются. Возвращает примерно освобожденные байты.
+  pub fn shed_entries(&mut self, request: ShrinkRequest) -> u64 {
+    let before = self.get_memory_usage().total_bytes;
+
+    let preview_len = request.retained_len(self.preview_cache.len());
+    self.preview_cache.shrink_to(preview_len);
+    let render_len = request.retained_len(self.render_cache.len());
+    self.render_cache.shrink_to(render_len);
+
+    before.saturating_sub(self.get_memory_usage().total_bytes) as u64
+  }
+}
+
+#[async_trait::async_trait]
+impl MemoryShrinker for tokio::sync::RwLock<RenderCache> {
+  async fn shrink(&self, request: ShrinkRequest) -> u64 {
+    self.write().await.shed_entries(request)
+  }
 }
 
 impl Default for RenderCache {
@@ -349,6 +553,12 @@ pub struct MediaMetadata {
   pub video_codec: Option<String>,
   /// Кодек аудио
   pub audio_codec: Option<String>,
+  /// Поворот видео по часовой стрелке в градусах
+  #[serde(default)]
+  pub rotation: i32,
+  /// Версия схемы, с которой были извлечены метаданные
+  #[serde(default)]
+  pub schema_version: u32,
   /// Время кэширования
   pub cached_at: SystemTime,
 }
@@ -358,6 +568,11 @@ impl MediaMetadata {
   pub fn is_expired(&self, ttl: Duration) -> bool {
     self.cached_at.elapsed().unwrap_or(Duration::ZERO) > ttl
   }
+
+  /// Извлечены ли метаданные устаревшей версией пробинга
+  pub fn is_outdated(&self) -> bool {
+    self.schema_version != METADATA_SCHEMA_VERSION
+  }
 }
 
 /// Данные кэша рендеринга
@@ -425,6 +640,12 @@ pub struct CacheStats {
   pub preview_hits: u64,
   /// Промахи превью
   pub preview_misses: u64,
+  /// Попадания превью в памяти
+  #[serde(default)]
+  pub preview_memory_hits: u64,
+  /// Попадания превью на диске
+  #[serde(default)]
+  pub preview_disk_hits: u64,
   /// Запросы метаданных
   pub metadata_requests: u64,
   /// Попадания метаданных
@@ -437,6 +658,9 @@ pub struct CacheStats {
   pub render_hits: u64,
   /// Промахи рендеринга
   pub render_misses: u64,
+  /// Количество вытеснений по лимиту размера
+  #[serde(default)]
+  pub evictions: u64,
 }
 
 impl CacheStats {
@@ -543,6 +767,11 @@ where
     self.map.remove(key).map(|node| node.value)
   }
 
+  /// Вытеснит ли вставка ключа другую запись
+  fn would_evict(&self, key: &K) -> bool {
+    !self.map.contains_key(key) && self.map.len() >= self.capacity
+  }
+
   fn clear(&mut self) {
     self.map.clear();
     self.head = None;
@@ -586,6 +815,23 @@ where
       }
     }
   }
+
+  fn len(&self) -> usize {
+    self.map.len()
+  }
+
+  /// Вытеснить старые записи до `len` и вернуть память, не меняя емкость
+  fn shrink_to(&mut self, len: usize) {
+    while self.map.len() > len {
+      match self.get_oldest_key() {
+        Some(oldest_key) => {
+          self.map.remove(&oldest_key);
+        }
+        None => break,
+      }
+    }
+    self.map.shrink_to_fit();
+  }
 }
 
 #[cfg(test)]
@@ -642,6 +888,8 @@ mod inline_tests {
       bitrate: Some(8000000),
       video_codec: Some("h264".to_string()),
       audio_codec: Some("aac".to_string()),
+      rotation: 0,
+      schema_version: METADATA_SCHEMA_VERSION,
       cached_at: SystemTime::now(),
     };
 
@@ -657,6 +905,32 @@ mod inline_tests {
     assert_eq!(cached_metadata.unwrap().duration, 120.0);
   }
 
+  #[tokio::test]
+  async fn test_outdated_metadata_schema_is_miss() {
+    let mut cache = RenderCache::new();
+    let json = r#"{
+      "file_path": "/test/old.mp4",
+      "file_size": 1,
+      "modified_time": {"secs_since_epoch": 0, "nanos_since_epoch": 0},
+      "duration": 5.0,
+      "resolution": [1920, 1080],
+      "fps": 30.0,
+      "bitrate": null,
+      "video_codec": "h264",
+      "audio_codec": null,
+      "cached_at": {"secs_since_epoch": 0, "nanos_since_epoch": 0}
+    }"#;
+    let mut metadata: MediaMetadata = serde_json::from_str(json).unwrap();
+    assert_eq!(metadata.schema_version, 0);
+    metadata.cached_at = SystemTime::now();
+
+    cache
+      .store_metadata(metadata.file_path.clone(), metadata)
+      .await
+      .unwrap();
+    assert!(cache.get_metadata("/test/old.mp4").await.is_none());
+  }
+
   #[tokio::test]
   async fn test_cache_expiration() {
     let settings = CacheSettings {
@@ -732,6 +1006,43 @@ mod inline_tests {
     assert!(cache.get_preview(&key).await.is_none());
   }
 
+  #[tokio::test]
+  async fn test_shed_entries_keeps_metadata() {
+    let mut cache = RenderCache::new();
+    for i in 0..8 {
+      let key = PreviewKey::new("/test/video.mp4".to_string(), i as f64, (640, 360), 75);
+      cache.store_preview(key, vec![0; 1024]).await.unwrap();
+    }
+    let metadata = MediaMetadata {
+      file_path: "/test/video.mp4".to_string(),
+      file_size: 1024,
+      modified_time: SystemTime::now(),
+      duration: 10.0,
+      resolution: None,
+      fps: None,
+      bitrate: None,
+      video_codec: None,
+      audio_codec: None,
+      rotation: 0,
+      schema_version: METADATA_SCHEMA_VERSION,
+      cached_at: SystemTime::now(),
+    };
+    cache
+      .store_metadata(metadata.file_path.clone(), metadata)
+      .await
+      .unwrap();
+
+    cache.shed_entries(ShrinkRequest {
+      level: crate::core::performance::memory_pressure::MemoryPressureLevel::Soft,
+      shed_fraction: 0.25,
+    });
+
+    assert_eq!(cache.preview_cache.len(), 6);
+    assert!(cache.has_metadata("/test/video.mp4"));
+    let (preview_limit, _, _) = cache.get_cache_limits();
+    assert_eq!(preview_limit, CacheSettings::default().max_preview_entries);
+  }
+
   #[test]
   fn test_lru_cache() {
     let mut lru = LruCache::new(2);
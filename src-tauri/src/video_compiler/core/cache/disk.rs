@@ -0,0 +1,399 @@
+//! Disk - Дисковый слой кэша превью
+//!
+//! Превью сохраняются в директории кэша как отдельные файлы, а JSON манифест
+//! (`index.json`) хранит ключ, время модификации исходника, размер и время
+//! последнего доступа. Индекс загружается лениво при первом обращении;
+//! поврежденный индекс восстанавливается сканированием директории.
+
+use super::PreviewKey;
+use crate::video_compiler::error::{Result, VideoCompilerError};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Имя файла индекса в директории кэша
+const INDEX_FILE_NAME: &str = "index.json";
+
+/// Расширение файлов превью
+const PREVIEW_FILE_EXTENSION: &str = "preview";
+
+/// Текущая версия формата индекса
+const INDEX_VERSION: u32 = 1;
+
+/// Запись дискового кэша
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiskCacheEntry {
+  /// Имя файла превью относительно директории кэша
+  pub file_name: String,
+  /// Путь к исходному медиафайлу (пусто для восстановленных записей)
+  pub source_path: String,
+  /// Время модификации исходника (секунды UNIX), если известно
+  pub source_mtime: Option<u64>,
+  /// Размер файла превью в байтах
+  pub size_bytes: u64,
+  /// Время последнего доступа (секунды UNIX)
+  pub last_access: u64,
+}
+
+/// Манифест дискового кэша
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiskCacheIndex {
+  /// Версия формата
+  pub version: u32,
+  /// Записи по хешу ключа
+  pub entries: HashMap<String, DiskCacheEntry>,
+}
+
+impl Default for DiskCacheIndex {
+  fn default() -> Self {
+    Self {
+      version: INDEX_VERSION,
+      entries: HashMap::new(),
+    }
+  }
+}
+
+/// Дисковый кэш превью с ленивой загрузкой индекса
+#[derive(Debug)]
+pub struct DiskPreviewCache {
+  /// Директория кэша
+  root: PathBuf,
+  /// Индекс (None, пока не загружен)
+  index: Option<DiskCacheIndex>,
+}
+
+impl DiskPreviewCache {
+  /// Создать дисковый кэш в указанной директории (без обращения к диску)
+  pub fn new(root: PathBuf) -> Self {
+    Self { root, index: None }
+  }
+
+  /// Директория кэша
+  pub fn root(&self) -> &Path {
+    &self.root
+  }
+
+  /// Хеш ключа превью, используемый как имя файла и ключ индекса
+  pub fn key_hash(key: &PreviewKey) -> String {
+    let raw = format!(
+      "{}|{}|{}x{}|{}",
+      key.file_path, key.timestamp, key.resolution.0, key.resolution.1, key.quality
+    );
+    format!("{:x}", md5::compute(raw.as_bytes()))
+  }
+
+  /// Загрузить индекс, если он еще не загружен.
+  ///
+  /// Никогда не возвращает ошибку: поврежденный или отсутствующий индекс
+  /// восстанавливается сканированием директории.
+  async fn ensure_loaded(&mut self) {
+    if self.index.is_some() {
+      return;
+    }
+
+    if let Err(e) = tokio::fs::create_dir_all(&self.root).await {
+      log::warn!(
+        "Не удалось создать директорию дискового кэша {:?}: {e}",
+        self.root
+      );
+    }
+
+    let index_path = self.root.join(INDEX_FILE_NAME);
+    let index = match tokio::fs::read(&index_path).await {
+      Ok(bytes) => match serde_json::from_slice::<DiskCacheIndex>(&bytes) {
+        Ok(index) if index.version == INDEX_VERSION => index,
+        Ok(index) => {
+          log::warn!(
+            "Неподдерживаемая версия индекса дискового кэша: {}, восстанавливаем",
+            index.version
+          );
+          self.rebuild_index().await
+        }
+        Err(e) => {
+          log::warn!("Индекс дискового кэша поврежден ({e}), восстанавливаем");
+          self.rebuild_index().await
+        }
+      },
+      Err(e) if e.kind() == std::io::ErrorKind::NotFound => self.rebuild_index().await,
+      Err(e) => {
+        log::warn!("Не удалось прочитать индекс дискового кэша ({e}), восстанавливаем");
+        self.rebuild_index().await
+      }
+    };
+
+    self.index = Some(index);
+  }
+
+  /// Восстановить индекс сканированием директории
+  async fn rebuild_index(&self) -> DiskCacheIndex {
+    let mut index = DiskCacheIndex::default();
+
+    let mut entries = match tokio::fs::read_dir(&self.root).await {
+      Ok(entries) => entries,
+      Err(_) => return index,
+    };
+
+    while let Ok(Some(entry)) = entries.next_entry().await {
+      let path = entry.path();
+      if path.extension().and_then(|e| e.to_str()) != Some(PREVIEW_FILE_EXTENSION) {
+        continue;
+      }
+      let (Some(hash), Ok(metadata)) = (
+        path.file_stem().and_then(|s| s.to_str()).map(String::from),
+        entry.metadata().await,
+      ) else {
+        continue;
+      };
+
+      let last_access = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+      index.entries.insert(
+        hash.clone(),
+        DiskCacheEntry {
+          file_name: format!("{hash}.{PREVIEW_FILE_EXTENSION}"),
+          source_path: String::new(),
+          source_mtime: None,
+          size_bytes: metadata.len(),
+          last_access,
+        },
+      );
+    }
+
+    log::info!(
+      "Индекс дискового кэша восстановлен: {} записей",
+      index.entries.len()
+    );
+    index
+  }
+
+  /// Сохранить индекс на диск
+  async fn persist_index(&self) -> Result<()> {
+    let Some(index) = &self.index else {
+      return Ok(());
+    };
+
+    let json = serde_json::to_vec(index)
+      .map_err(|e| VideoCompilerError::SerializationError(e.to_string()))?;
+
+    // Пишем во временный файл и переименовываем, чтобы не оставить битый индекс
+    let tmp_path = self.root.join(format!("{INDEX_FILE_NAME}.tmp"));
+    tokio::fs::write(&tmp_path, json)
+      .await
+      .map_err(|e| VideoCompilerError::IoError(e.to_string()))?;
+    tokio::fs::rename(&tmp_path, self.root.join(INDEX_FILE_NAME))
+      .await
+      .map_err(|e| VideoCompilerError::IoError(e.to_string()))?;
+
+    Ok(())
+  }
+
+  /// Получить превью с диска
+  pub async fn get(&mut self, key: &PreviewKey) -> Option<Vec<u8>> {
+    self.ensure_loaded().await;
+
+    let hash = Self::key_hash(key);
+    let entry = self.index.as_ref()?.entries.get(&hash)?.clone();
+
+    // Исходник изменился после кэширования — запись устарела
+    if let Some(cached_mtime) = entry.source_mtime {
+      if source_mtime(&entry.source_path).await != Some(cached_mtime) {
+        self.remove_hash(&hash).await;
+        return None;
+      }
+    }
+
+    match tokio::fs::read(self.root.join(&entry.file_name)).await {
+      Ok(data) => {
+        if let Some(entry) = self
+          .index
+          .as_mut()
+          .and_then(|index| index.entries.get_mut(&hash))
+        {
+          entry.last_access = now_secs();
+        }
+        Some(data)
+      }
+      Err(_) => {
+        self.remove_hash(&hash).await;
+        None
+      }
+    }
+  }
+
+  /// Сохранить превью на диск
+  pub async fn put(&mut self, key: &PreviewKey, data: &[u8]) -> Result<()> {
+    self.ensure_loaded().await;
+
+    let hash = Self::key_hash(key);
+    let file_name = format!("{hash}.{PREVIEW_FILE_EXTENSION}");
+    tokio::fs::write(self.root.join(&file_name), data)
+      .await
+      .map_err(|e| VideoCompilerError::IoError(e.to_string()))?;
+
+    let entry = DiskCacheEntry {
+      file_name,
+      source_path: key.file_path.clone(),
+      source_mtime: source_mtime(&key.file_path).await,
+      size_bytes: data.len() as u64,
+      last_access: now_secs(),
+    };
+
+    if let Some(index) = self.index.as_mut() {
+      index.entries.insert(hash, entry);
+    }
+
+    self.persist_index().await
+  }
+
+  /// Общий размер файлов на диске
+  pub async fn total_bytes(&mut self) -> u64 {
+    self.ensure_loaded().await;
+    self
+      .index
+      .as_ref()
+      .map(|index| index.entries.values().map(|e| e.size_bytes).sum())
+      .unwrap_or(0)
+  }
+
+  /// Количество записей на диске
+  pub async fn entry_count(&mut self) -> usize {
+    self.ensure_loaded().await;
+    self.index.as_ref().map(|i| i.entries.len()).unwrap_or(0)
+  }
+
+  /// Вытеснить наименее недавно использованные записи, пока не освободится
+  /// не менее `bytes_to_free` байт. Возвращает количество освобожденных байт.
+  pub async fn evict_lru(&mut self, bytes_to_free: u64) -> u64 {
+    self.ensure_loaded().await;
+
+    let mut candidates: Vec<(String, u64, u64)> = self
+      .index
+      .as_ref()
+      .map(|index| {
+        index
+          .entries
+          .iter()
+          .map(|(hash, e)| (hash.clone(), e.last_access, e.size_bytes))
+          .collect()
+      })
+      .unwrap_or_default();
+    candidates.sort_by_key(|(_, last_access, _)| *last_access);
+
+    let mut freed = 0u64;
+    for (hash, _, size) in candidates {
+      if freed >= bytes_to_free {
+        break;
+      }
+      self.remove_hash(&hash).await;
+      freed += size;
+    }
+
+    if freed > 0 {
+      if let Err(e) = self.persist_index().await {
+        log::warn!("Не удалось сохранить индекс дискового кэша: {e}");
+      }
+    }
+
+    freed
+  }
+
+  /// Удалить все записи с диска
+  pub async fn clear(&mut self) -> Result<()> {
+    self.ensure_loaded().await;
+
+    let hashes: Vec<String> = self
+      .index
+      .as_ref()
+      .map(|index| index.entries.keys().cloned().collect())
+      .unwrap_or_default();
+    for hash in hashes {
+      self.remove_hash(&hash).await;
+    }
+
+    self.index = Some(DiskCacheIndex::default());
+    self.persist_index().await
+  }
+
+  /// Сохранить индекс (например, обновленное время доступа)
+  pub async fn flush(&self) -> Result<()> {
+    self.persist_index().await
+  }
+
+  /// Проверить файл индекса в директории кэша, не загружая его.
+  ///
+  /// Отсутствующий индекс корректен (он создается при первой записи);
+  /// для поврежденного или неподдерживаемого возвращается описание проблемы.
+  pub async fn check_index_file(root: &Path) -> std::result::Result<(), String> {
+    let bytes = match tokio::fs::read(root.join(INDEX_FILE_NAME)).await {
+      Ok(bytes) => bytes,
+      Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+      Err(e) => return Err(format!("Не удалось прочитать индекс: {e}")),
+    };
+    match serde_json::from_slice::<DiskCacheIndex>(&bytes) {
+      Ok(index) if index.version == INDEX_VERSION => Ok(()),
+      Ok(index) => Err(format!(
+        "Неподдерживаемая версия индекса: {}",
+        index.version
+      )),
+      Err(e) => Err(format!("Индекс поврежден: {e}")),
+    }
+  }
+
+  /// Восстановить индекс сканированием директории и сохранить его.
+  ///
+  /// Возвращает количество записей восстановленного индекса.
+  pub async fn repair_index(&mut self) -> Result<usize> {
+    let index = self.rebuild_index().await;
+    let count = index.entries.len();
+    self.index = Some(index);
+    self.persist_index().await?;
+    Ok(count)
+  }
+
+  /// Удалить запись и ее файл
+  async fn remove_hash(&mut self, hash: &str) {
+    let Some(entry) = self
+      .index
+      .as_mut()
+      .and_then(|index| index.entries.remove(hash))
+    else {
+      return;
+    };
+
+    let path = self.root.join(&entry.file_name);
+    if let Err(e) = tokio::fs::remove_file(&path).await {
+      if e.kind() != std::io::ErrorKind::NotFound {
+        log::warn!("Не удалось удалить файл кэша {path:?}: {e}");
+      }
+    }
+  }
+}
+
+/// Время модификации файла в секундах UNIX
+async fn source_mtime(path: &str) -> Option<u64> {
+  if path.is_empty() {
+    return None;
+  }
+  tokio::fs::metadata(path)
+    .await
+    .ok()?
+    .modified()
+    .ok()?
+    .duration_since(UNIX_EPOCH)
+    .ok()
+    .map(|d| d.as_secs())
+}
+
+/// Текущее время в секундах UNIX
+fn now_secs() -> u64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|d| d.as_secs())
+    .unwrap_or(0)
+}
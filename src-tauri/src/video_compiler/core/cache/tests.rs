@@ -82,6 +82,8 @@ async fn test_metadata_cache() {
     bitrate: Some(8000000),
     video_codec: Some("h264".to_string()),
     audio_codec: Some("aac".to_string()),
+    rotation: 0,
+    schema_version: METADATA_SCHEMA_VERSION,
     cached_at: SystemTime::now(),
   };
 
@@ -205,6 +207,8 @@ async fn test_metadata_expiration() {
     bitrate: None,
     video_codec: None,
     audio_codec: None,
+    rotation: 0,
+    schema_version: METADATA_SCHEMA_VERSION,
     cached_at: SystemTime::now(),
   };
 
@@ -326,6 +330,8 @@ async fn test_cache_clear_all() {
     bitrate: None,
     video_codec: None,
     audio_codec: None,
+    rotation: 0,
+    schema_version: METADATA_SCHEMA_VERSION,
     cached_at: SystemTime::now(),
   };
   cache
@@ -470,6 +476,8 @@ fn test_media_metadata_is_expired() {
     bitrate: None,
     video_codec: None,
     audio_codec: None,
+    rotation: 0,
+    schema_version: METADATA_SCHEMA_VERSION,
     cached_at: SystemTime::now() - Duration::from_secs(1800),
   };
 
@@ -634,6 +642,7 @@ fn test_cache_stats_serialization() {
     render_requests: 10,
     render_hits: 8,
     render_misses: 2,
+    ..Default::default()
   };
 
   let json = serde_json::to_string(&stats).unwrap();
@@ -656,6 +665,8 @@ fn test_media_metadata_serialization() {
     bitrate: Some(8000000),
     video_codec: Some("h264".to_string()),
     audio_codec: Some("aac".to_string()),
+    rotation: 0,
+    schema_version: METADATA_SCHEMA_VERSION,
     cached_at: SystemTime::now(),
   };
 
@@ -670,3 +681,158 @@ fn test_media_metadata_serialization() {
   assert_eq!(deserialized.duration, metadata.duration);
   assert_eq!(deserialized.resolution, metadata.resolution);
 }
+
+#[tokio::test]
+async fn test_disk_cache_survives_restart() {
+  let temp_dir = tempfile::tempdir().unwrap();
+  let key = PreviewKey::new("/test/video.mp4".to_string(), 3.0, (640, 360), 75);
+
+  {
+    let mut cache =
+      RenderCache::with_disk_cache(CacheSettings::default(), temp_dir.path().to_path_buf(), 64);
+    cache
+      .store_preview(key.clone(), vec![7, 8, 9])
+      .await
+      .unwrap();
+  }
+
+  // Новый экземпляр кэша (имитация перезапуска приложения)
+  let mut cache =
+    RenderCache::with_disk_cache(CacheSettings::default(), temp_dir.path().to_path_buf(), 64);
+  let data = cache.get_preview(&key).await;
+  assert_eq!(data.unwrap().image_data, vec![7, 8, 9]);
+
+  // Повторный запрос обслуживается из памяти
+  assert!(cache.get_preview(&key).await.is_some());
+
+  let stats = cache.get_stats();
+  assert_eq!(stats.preview_disk_hits, 1);
+  assert_eq!(stats.preview_memory_hits, 1);
+  assert_eq!(stats.preview_misses, 0);
+}
+
+#[tokio::test]
+async fn test_disk_cache_rebuilds_corrupt_index() {
+  let temp_dir = tempfile::tempdir().unwrap();
+  let key = PreviewKey::new("/test/video.mp4".to_string(), 4.0, (320, 180), 50);
+
+  {
+    let mut cache =
+      RenderCache::with_disk_cache(CacheSettings::default(), temp_dir.path().to_path_buf(), 64);
+    cache
+      .store_preview(key.clone(), vec![1, 2, 3, 4])
+      .await
+      .unwrap();
+  }
+
+  std::fs::write(temp_dir.path().join("index.json"), b"{ not json").unwrap();
+
+  let mut cache =
+    RenderCache::with_disk_cache(CacheSettings::default(), temp_dir.path().to_path_buf(), 64);
+  let data = cache.get_preview(&key).await;
+  assert_eq!(data.unwrap().image_data, vec![1, 2, 3, 4]);
+}
+
+#[tokio::test]
+async fn test_disk_cache_check_and_repair_index() {
+  let temp_dir = tempfile::tempdir().unwrap();
+  let key = PreviewKey::new("/test/video.mp4".to_string(), 6.0, (320, 180), 50);
+
+  // Отсутствующий индекс корректен
+  assert!(DiskPreviewCache::check_index_file(temp_dir.path())
+    .await
+    .is_ok());
+
+  {
+    let mut cache =
+      RenderCache::with_disk_cache(CacheSettings::default(), temp_dir.path().to_path_buf(), 64);
+    cache.store_preview(key, vec![1, 2, 3]).await.unwrap();
+  }
+  assert!(DiskPreviewCache::check_index_file(temp_dir.path())
+    .await
+    .is_ok());
+
+  std::fs::write(temp_dir.path().join("index.json"), b"{ not json").unwrap();
+  assert!(DiskPreviewCache::check_index_file(temp_dir.path())
+    .await
+    .is_err());
+
+  let mut disk = DiskPreviewCache::new(temp_dir.path().to_path_buf());
+  assert_eq!(disk.repair_index().await.unwrap(), 1);
+  assert!(DiskPreviewCache::check_index_file(temp_dir.path())
+    .await
+    .is_ok());
+}
+
+#[tokio::test]
+async fn test_clear_previews_removes_disk_entries() {
+  let temp_dir = tempfile::tempdir().unwrap();
+  let key = PreviewKey::new("/test/video.mp4".to_string(), 5.0, (640, 360), 75);
+
+  let mut cache =
+    RenderCache::with_disk_cache(CacheSettings::default(), temp_dir.path().to_path_buf(), 64);
+  cache
+    .store_preview(key.clone(), vec![1, 2, 3])
+    .await
+    .unwrap();
+  assert!(cache.get_disk_usage().await > 0);
+
+  cache.clear_previews().await;
+  assert_eq!(cache.get_disk_usage().await, 0);
+
+  let preview_files = std::fs::read_dir(temp_dir.path())
+    .unwrap()
+    .filter_map(|e| e.ok())
+    .filter(|e| e.path().extension().and_then(|x| x.to_str()) == Some("preview"))
+    .count();
+  assert_eq!(preview_files, 0);
+  assert!(cache.get_preview(&key).await.is_none());
+}
+
+#[tokio::test]
+async fn test_disk_cache_enforces_total_size_limit() {
+  let temp_dir = tempfile::tempdir().unwrap();
+  // Лимит 1 MB на память и диск вместе
+  let mut cache =
+    RenderCache::with_disk_cache(CacheSettings::default(), temp_dir.path().to_path_buf(), 1);
+
+  for i in 0..4 {
+    let key = PreviewKey::new("/test/video.mp4".to_string(), i as f64, (640, 360), 75);
+    cache
+      .store_preview(key, vec![0u8; 200 * 1024])
+      .await
+      .unwrap();
+  }
+
+  assert!(cache.get_disk_usage().await <= 1024 * 1024);
+  assert!(cache.get_stats().evictions > 0);
+}
+
+#[tokio::test]
+async fn test_relink_metadata() {
+  let mut cache = RenderCache::new();
+  let metadata = MediaMetadata {
+    file_path: "/old/video.mp4".to_string(),
+    file_size: 1024,
+    modified_time: SystemTime::now(),
+    duration: 60.0,
+    resolution: None,
+    fps: None,
+    bitrate: None,
+    video_codec: None,
+    audio_codec: None,
+    rotation: 0,
+    schema_version: METADATA_SCHEMA_VERSION,
+    cached_at: SystemTime::now(),
+  };
+  cache
+    .store_metadata("/old/video.mp4".to_string(), metadata)
+    .await
+    .unwrap();
+
+  assert!(cache.relink_metadata("/old/video.mp4", "/new/video.mp4"));
+  assert!(cache.get_metadata("/old/video.mp4").await.is_none());
+  let moved = cache.get_metadata("/new/video.mp4").await.unwrap();
+  assert_eq!(moved.file_path, "/new/video.mp4");
+  assert!(!cache.relink_metadata("/missing.mp4", "/other.mp4"));
+}
@@ -3,6 +3,7 @@
 //! Этот модуль определяет типы ошибок, которые могут возникнуть при работе
 //! с Video Compiler модулем, включая ошибки FFmpeg, валидации и I/O операций.
 
+use crate::language_tauri::i18n::LocalizedMessage;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
@@ -13,7 +14,7 @@ pub type Result<T> = std::result::Result<T, VideoCompilerError>;
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum VideoCompilerError {
   /// Ошибка валидации схемы проекта
-  ValidationError(String),
+  ValidationError(LocalizedMessage),
 
   /// Ошибка FFmpeg (код выхода, stderr)
   FFmpegError {
@@ -23,7 +24,7 @@ pub enum VideoCompilerError {
   },
 
   /// Отсутствующая зависимость (FFmpeg, библиотеки)
-  DependencyMissing(String),
+  DependencyMissing(LocalizedMessage),
 
   /// Ошибка ввода/вывода
   IoError(String),
@@ -94,13 +95,37 @@ pub enum VideoCompilerError {
   InvalidPath(String),
 
   /// Слишком много активных задач
-  TooManyActiveJobs(String),
+  TooManyActiveJobs(LocalizedMessage),
 
   /// Сервис не найден в DI контейнере
   ServiceNotFound(String),
 
   /// Ошибка безопасности
   SecurityError(String),
+
+  /// Изменение заблокированного клипа или клипа на заблокированном треке
+  Locked { clip_id: String, track_id: String },
+
+  /// Плагин превысил квоту ресурсов sandbox
+  QuotaExceeded {
+    plugin_id: String,
+    resource: String,
+    requested: u64,
+    limit: u64,
+  },
+
+  /// Неудовлетворенная зависимость плагина
+  PluginDependencyError {
+    plugin_id: String,
+    dependency: String,
+    reason: String,
+  },
+
+  /// Циклическая зависимость между плагинами
+  PluginDependencyCycle { cycle: Vec<String> },
+
+  /// Проект сохранен более новой версией схемы, чем поддерживает приложение
+  UnsupportedProjectVersion { found: String, supported: String },
 }
 
 impl fmt::Display for VideoCompilerError {
@@ -208,6 +233,46 @@ impl fmt::Display for VideoCompilerError {
       VideoCompilerError::SecurityError(msg) => {
         write!(f, "Ошибка безопасности: {msg}")
       }
+      VideoCompilerError::Locked { clip_id, track_id } => {
+        write!(
+          f,
+          "Клип '{clip_id}' на треке '{track_id}' заблокирован для изменений"
+        )
+      }
+      VideoCompilerError::QuotaExceeded {
+        plugin_id,
+        resource,
+        requested,
+        limit,
+      } => {
+        write!(
+          f,
+          "Плагин '{plugin_id}' превысил квоту '{resource}': запрошено {requested}, лимит {limit}"
+        )
+      }
+      VideoCompilerError::PluginDependencyError {
+        plugin_id,
+        dependency,
+        reason,
+      } => {
+        write!(
+          f,
+          "Зависимость '{dependency}' плагина '{plugin_id}' не удовлетворена: {reason}"
+        )
+      }
+      VideoCompilerError::PluginDependencyCycle { cycle } => {
+        write!(
+          f,
+          "Циклическая зависимость плагинов: {}",
+          cycle.join(" -> ")
+        )
+      }
+      VideoCompilerError::UnsupportedProjectVersion { found, supported } => {
+        write!(
+          f,
+          "Проект сохранен версией схемы {found}, приложение поддерживает версии до {supported}. Обновите приложение"
+        )
+      }
     }
   }
 }
@@ -280,7 +345,13 @@ impl From<tokio::time::error::Elapsed> for VideoCompilerError {
 
 impl From<String> for VideoCompilerError {
   fn from(error: String) -> Self {
-    VideoCompilerError::ValidationError(error)
+    VideoCompilerError::ValidationError(error.into())
+  }
+}
+
+impl From<LocalizedMessage> for VideoCompilerError {
+  fn from(message: LocalizedMessage) -> Self {
+    VideoCompilerError::ValidationError(message)
   }
 }
 
@@ -293,7 +364,7 @@ impl From<anyhow::Error> for VideoCompilerError {
 /// Вспомогательные функции для создания ошибок
 impl VideoCompilerError {
   /// Создать ошибку валидации
-  pub fn validation<S: Into<String>>(message: S) -> Self {
+  pub fn validation<S: Into<LocalizedMessage>>(message: S) -> Self {
     VideoCompilerError::ValidationError(message.into())
   }
 
@@ -409,6 +480,11 @@ impl VideoCompilerError {
       VideoCompilerError::TooManyActiveJobs(_) => "TOO_MANY_ACTIVE_JOBS",
       VideoCompilerError::ServiceNotFound(_) => "SERVICE_NOT_FOUND",
       VideoCompilerError::SecurityError(_) => "SECURITY_ERROR",
+      VideoCompilerError::Locked { .. } => "LOCKED",
+      VideoCompilerError::QuotaExceeded { .. } => "QUOTA_EXCEEDED",
+      VideoCompilerError::PluginDependencyError { .. } => "PLUGIN_DEPENDENCY_ERROR",
+      VideoCompilerError::PluginDependencyCycle { .. } => "PLUGIN_DEPENDENCY_CYCLE",
+      VideoCompilerError::UnsupportedProjectVersion { .. } => "UNSUPPORTED_PROJECT_VERSION",
     }
   }
 }
@@ -463,10 +539,10 @@ impl Default for OperationMetadata {
 #[macro_export]
 macro_rules! validation_error {
     ($msg:expr) => {
-        VideoCompilerError::ValidationError($msg.to_string())
+        VideoCompilerError::ValidationError($msg.to_string().into())
     };
     ($fmt:expr, $($arg:tt)*) => {
-        VideoCompilerError::ValidationError(format!($fmt, $($arg)*))
+        VideoCompilerError::ValidationError(format!($fmt, $($arg)*).into())
     };
 }
 
@@ -509,7 +585,7 @@ mod tests {
 
   #[test]
   fn test_error_classification() {
-    let critical_error = VideoCompilerError::DependencyMissing("FFmpeg".to_string());
+    let critical_error = VideoCompilerError::DependencyMissing("FFmpeg".into());
     assert!(critical_error.is_critical());
     assert!(!critical_error.is_retryable());
 
@@ -521,13 +597,13 @@ mod tests {
   #[test]
   fn test_error_codes() {
     let errors = vec![
-      VideoCompilerError::ValidationError("test".to_string()),
+      VideoCompilerError::ValidationError("test".into()),
       VideoCompilerError::FFmpegError {
         exit_code: Some(1),
         stderr: "test".to_string(),
         command: "test".to_string(),
       },
-      VideoCompilerError::DependencyMissing("test".to_string()),
+      VideoCompilerError::DependencyMissing("test".into()),
       VideoCompilerError::IoError("test".to_string()),
     ];
 
@@ -635,7 +711,7 @@ mod tests {
     // Тестируем Display для всех вариантов ошибок
     let errors: Vec<(VideoCompilerError, &str)> = vec![
       (
-        VideoCompilerError::DependencyMissing("FFmpeg".to_string()),
+        VideoCompilerError::DependencyMissing("FFmpeg".into()),
         "Отсутствует зависимость: FFmpeg",
       ),
       (
@@ -732,7 +808,7 @@ mod tests {
     // Тестируем error_code для всех вариантов
     let test_cases = vec![
       (
-        VideoCompilerError::ValidationError("test".to_string()),
+        VideoCompilerError::ValidationError("test".into()),
         "VALIDATION_ERROR",
       ),
       (
@@ -744,7 +820,7 @@ mod tests {
         "FFMPEG_ERROR",
       ),
       (
-        VideoCompilerError::DependencyMissing("test".to_string()),
+        VideoCompilerError::DependencyMissing("test".into()),
         "DEPENDENCY_MISSING",
       ),
       (VideoCompilerError::IoError("test".to_string()), "IO_ERROR"),
@@ -911,7 +987,7 @@ mod tests {
   #[test]
   fn test_is_critical_comprehensive() {
     // Критические ошибки
-    assert!(VideoCompilerError::DependencyMissing("test".to_string()).is_critical());
+    assert!(VideoCompilerError::DependencyMissing("test".into()).is_critical());
     assert!(VideoCompilerError::ResourceError {
       resource_type: "test".to_string(),
       available: "test".to_string(),
@@ -921,7 +997,7 @@ mod tests {
     assert!(VideoCompilerError::InternalError("test".to_string()).is_critical());
 
     // Некритические ошибки
-    assert!(!VideoCompilerError::ValidationError("test".to_string()).is_critical());
+    assert!(!VideoCompilerError::ValidationError("test".into()).is_critical());
     assert!(!VideoCompilerError::IoError("test".to_string()).is_critical());
     assert!(!VideoCompilerError::TimeoutError("test".to_string()).is_critical());
   }
@@ -934,8 +1010,8 @@ mod tests {
     assert!(VideoCompilerError::CacheError("test".to_string()).is_retryable());
 
     // Неповторяемые ошибки
-    assert!(!VideoCompilerError::ValidationError("test".to_string()).is_retryable());
-    assert!(!VideoCompilerError::DependencyMissing("test".to_string()).is_retryable());
+    assert!(!VideoCompilerError::ValidationError("test".into()).is_retryable());
+    assert!(!VideoCompilerError::DependencyMissing("test".into()).is_retryable());
     assert!(!VideoCompilerError::UnsupportedFormat {
       format: "test".to_string(),
       file_path: "test".to_string()
@@ -0,0 +1,786 @@
+//! FFmpeg Manager - Управление встроенной (скачанной) сборкой FFmpeg
+//!
+//! Скачивает статическую сборку FFmpeg для текущей платформы, проверяет
+//! контрольную сумму SHA-256, распаковывает ее в директорию данных приложения
+//! и сообщает о возможностях активного бинарника (версия, кодеки, фильтры).
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio_util::sync::CancellationToken;
+
+use crate::language_tauri::i18n::LocalizedMessage;
+use crate::video_compiler::error::{Result, VideoCompilerError};
+use crate::video_compiler::services::ffmpeg_capabilities::FfmpegCapabilitiesCache;
+
+/// Идентификатор приложения (совпадает с `identifier` в tauri.conf.json)
+const APP_IDENTIFIER: &str = "com.chatman-media.timeline-studio";
+
+/// Кодировщики, без которых экспорт по умолчанию не работает
+pub const REQUIRED_ENCODERS: &[&str] = &["libx264", "aac"];
+
+/// Фильтры, без которых не работают титры и переходы
+pub const REQUIRED_FILTERS: &[&str] = &["drawtext", "xfade"];
+
+/// Минимальный объем новых данных между событиями прогресса
+const PROGRESS_STEP_BYTES: u64 = 512 * 1024;
+
+/// Источник активного FFmpeg
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FfmpegSource {
+  /// Установлен в системе
+  System,
+  /// Скачан приложением
+  Bundled,
+  /// Путь указан пользователем
+  Custom,
+}
+
+/// Адрес сборки FFmpeg для одной платформы
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FfmpegDownloadSource {
+  /// Платформа в формате `{os}-{arch}`, например "linux-x86_64"
+  pub platform: String,
+  /// URL архива (.zip или .tar.xz)
+  pub url: String,
+  /// Ожидаемая контрольная сумма SHA-256 архива
+  #[serde(default)]
+  pub sha256: Option<String>,
+  /// URL файла контрольных сумм, если сумма не указана явно
+  #[serde(default)]
+  pub sha256_url: Option<String>,
+}
+
+impl FfmpegDownloadSource {
+  /// Имя файла архива из URL
+  pub fn file_name(&self) -> String {
+    self
+      .url
+      .rsplit('/')
+      .next()
+      .and_then(|name| name.split(['?', '#']).next())
+      .filter(|name| !name.is_empty())
+      .unwrap_or("ffmpeg-archive")
+      .to_string()
+  }
+}
+
+/// Сборки FFmpeg по умолчанию (BtbN/FFmpeg-Builds публикует checksums.sha256)
+pub fn default_download_sources() -> Vec<FfmpegDownloadSource> {
+  const RELEASE: &str = "https://github.com/BtbN/FFmpeg-Builds/releases/download/latest";
+
+  [
+    ("linux-x86_64", "ffmpeg-master-latest-linux64-gpl.tar.xz"),
+    (
+      "linux-aarch64",
+      "ffmpeg-master-latest-linuxarm64-gpl.tar.xz",
+    ),
+    ("windows-x86_64", "ffmpeg-master-latest-win64-gpl.zip"),
+    ("windows-aarch64", "ffmpeg-master-latest-winarm64-gpl.zip"),
+  ]
+  .into_iter()
+  .map(|(platform, file)| FfmpegDownloadSource {
+    platform: platform.to_string(),
+    url: format!("{RELEASE}/{file}"),
+    sha256: None,
+    sha256_url: Some(format!("{RELEASE}/checksums.sha256")),
+  })
+  .collect()
+}
+
+/// Текущая платформа в формате `{os}-{arch}`
+pub fn current_platform() -> String {
+  format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH)
+}
+
+/// Директория встроенного FFmpeg в данных приложения
+pub fn bundled_ffmpeg_dir() -> PathBuf {
+  dirs::data_dir()
+    .unwrap_or_else(std::env::temp_dir)
+    .join(APP_IDENTIFIER)
+    .join("ffmpeg")
+}
+
+/// Имя исполняемого файла с учетом платформы
+fn executable_name(name: &str) -> String {
+  format!("{name}{}", std::env::consts::EXE_SUFFIX)
+}
+
+/// Этап скачивания встроенного FFmpeg
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FfmpegDownloadStage {
+  Downloading,
+  Verifying,
+  Extracting,
+  Completed,
+}
+
+/// Прогресс скачивания встроенного FFmpeg
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FfmpegDownloadProgress {
+  pub stage: FfmpegDownloadStage,
+  pub downloaded_bytes: u64,
+  pub total_bytes: Option<u64>,
+  /// Скачивание продолжено с частично загруженного файла
+  pub resumed: bool,
+}
+
+/// Состояние активного FFmpeg
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FfmpegStatus {
+  pub path: String,
+  pub source: FfmpegSource,
+  pub available: bool,
+  pub version: Option<String>,
+  /// Наличие обязательных кодировщиков
+  pub encoders: BTreeMap<String, bool>,
+  /// Наличие обязательных фильтров
+  pub filters: BTreeMap<String, bool>,
+}
+
+impl FfmpegStatus {
+  /// FFmpeg доступен и поддерживает все обязательные кодировщики и фильтры
+  pub fn is_ready(&self) -> bool {
+    self.available
+      && self
+        .encoders
+        .values()
+        .chain(self.filters.values())
+        .all(|ok| *ok)
+  }
+}
+
+/// Менеджер встроенной сборки FFmpeg
+#[derive(Debug)]
+pub struct FfmpegManager {
+  install_dir: PathBuf,
+  client: reqwest::Client,
+  /// Токен отмены текущего скачивания
+  download: parking_lot::Mutex<Option<CancellationToken>>,
+}
+
+impl Default for FfmpegManager {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl FfmpegManager {
+  /// Менеджер с директорией данных приложения
+  pub fn new() -> Self {
+    Self::with_install_dir(bundled_ffmpeg_dir())
+  }
+
+  /// Менеджер с указанной директорией установки
+  pub fn with_install_dir(install_dir: PathBuf) -> Self {
+    Self {
+      install_dir,
+      client: reqwest::Client::new(),
+      download: parking_lot::Mutex::new(None),
+    }
+  }
+
+  /// Директория установки
+  pub fn install_dir(&self) -> &Path {
+    &self.install_dir
+  }
+
+  /// Путь к бинарнику встроенного FFmpeg (может не существовать)
+  pub fn bundled_binary(&self) -> PathBuf {
+    self.install_dir.join("bin").join(executable_name("ffmpeg"))
+  }
+
+  /// Путь к встроенному FFmpeg, если он уже скачан
+  pub fn installed_binary(&self) -> Option<PathBuf> {
+    Some(self.bundled_binary()).filter(|path| path.is_file())
+  }
+
+  /// Идет ли скачивание
+  pub fn is_downloading(&self) -> bool {
+    self.download.lock().is_some()
+  }
+
+  /// Отменить текущее скачивание. Частично загруженный файл сохраняется
+  /// для продолжения.
+  pub fn cancel_download(&self) -> bool {
+    match self.download.lock().as_ref() {
+      Some(token) => {
+        token.cancel();
+        true
+      }
+      None => false,
+    }
+  }
+
+  /// Скачать, проверить и распаковать FFmpeg для текущей платформы.
+  ///
+  /// Источники перебираются по порядку до первого успешного. Возвращает путь к
+  /// бинарнику FFmpeg.
+  pub async fn download_bundled(
+    &self,
+    sources: &[FfmpegDownloadSource],
+    on_progress: impl Fn(FfmpegDownloadProgress),
+  ) -> Result<PathBuf> {
+    let platform = current_platform();
+    let candidates: Vec<&FfmpegDownloadSource> =
+      sources.iter().filter(|s| s.platform == platform).collect();
+    if candidates.is_empty() {
+      return Err(VideoCompilerError::DependencyMissing(
+        format!("Нет сборки FFmpeg для платформы {platform}").into(),
+      ));
+    }
+
+    let token = {
+      let mut download = self.download.lock();
+      if download.is_some() {
+        return Err(VideoCompilerError::TooManyActiveJobs(
+          LocalizedMessage::new("render_queue.ffmpeg_download_running", &[]),
+        ));
+      }
+      let token = CancellationToken::new();
+      *download = Some(token.clone());
+      token
+    };
+
+    let mut last_error = None;
+    for source in candidates {
+      match self.install_from(source, &token, &on_progress).await {
+        Ok(path) => {
+          self.download.lock().take();
+          return Ok(path);
+        }
+        Err(VideoCompilerError::CancelledError(reason)) => {
+          last_error = Some(VideoCompilerError::CancelledError(reason));
+          break;
+        }
+        Err(e) => {
+          log::warn!("Не удалось установить FFmpeg из {}: {e}", source.url);
+          last_error = Some(e);
+        }
+      }
+    }
+
+    self.download.lock().take();
+    Err(
+      last_error
+        .unwrap_or_else(|| VideoCompilerError::DependencyMissing("FFmpeg не установлен".into())),
+    )
+  }
+
+  /// Скачать и установить сборку из одного источника
+  async fn install_from(
+    &self,
+    source: &FfmpegDownloadSource,
+    token: &CancellationToken,
+    on_progress: &impl Fn(FfmpegDownloadProgress),
+  ) -> Result<PathBuf> {
+    let downloads_dir = self.install_dir.join("downloads");
+    tokio::fs::create_dir_all(&downloads_dir).await?;
+
+    let archive = downloads_dir.join(source.file_name());
+    let partial = downloads_dir.join(format!("{}.part", source.file_name()));
+    let (downloaded, total) = self
+      .download_file(source, &partial, token, on_progress)
+      .await?;
+
+    on_progress(FfmpegDownloadProgress {
+      stage: FfmpegDownloadStage::Verifying,
+      downloaded_bytes: downloaded,
+      total_bytes: total,
+      resumed: false,
+    });
+    let expected = self.expected_sha256(source).await?;
+    let actual = sha256_file(&partial).await?;
+    if !actual.eq_ignore_ascii_case(&expected) {
+      // Поврежденный файл не продолжается, следующая попытка начнется заново
+      tokio::fs::remove_file(&partial).await.ok();
+      return Err(VideoCompilerError::SecurityError(format!(
+        "Контрольная сумма {} не совпадает: ожидалось {expected}, получено {actual}",
+        source.file_name()
+      )));
+    }
+    tokio::fs::rename(&partial, &archive).await?;
+
+    on_progress(FfmpegDownloadProgress {
+      stage: FfmpegDownloadStage::Extracting,
+      downloaded_bytes: downloaded,
+      total_bytes: total,
+      resumed: false,
+    });
+    let binary = self.extract(&archive).await;
+    tokio::fs::remove_file(&archive).await.ok();
+    let binary = binary?;
+
+    if probe_version(&binary).await.is_none() {
+      return Err(VideoCompilerError::DependencyMissing(
+        format!("Скачанный FFmpeg не запускается: {}", binary.display()).into(),
+      ));
+    }
+
+    on_progress(FfmpegDownloadProgress {
+      stage: FfmpegDownloadStage::Completed,
+      downloaded_bytes: downloaded,
+      total_bytes: total,
+      resumed: false,
+    });
+    log::info!("Встроенный FFmpeg установлен: {}", binary.display());
+    Ok(binary)
+  }
+
+  /// Скачать файл с продолжением с места остановки (HTTP Range)
+  async fn download_file(
+    &self,
+    source: &FfmpegDownloadSource,
+    partial: &Path,
+    token: &CancellationToken,
+    on_progress: &impl Fn(FfmpegDownloadProgress),
+  ) -> Result<(u64, Option<u64>)> {
+    let offset = tokio::fs::metadata(partial)
+      .await
+      .map(|meta| meta.len())
+      .unwrap_or(0);
+
+    let mut request = self.client.get(&source.url);
+    if offset > 0 {
+      request = request.header(reqwest::header::RANGE, format!("bytes={offset}-"));
+    }
+    let mut response = request
+      .send()
+      .await
+      .map_err(|e| VideoCompilerError::IoError(format!("Ошибка скачивания FFmpeg: {e}")))?;
+
+    let status = response.status();
+    if offset > 0 && status == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+      // Файл уже загружен полностью
+      return Ok((offset, Some(offset)));
+    }
+    if !status.is_success() {
+      return Err(VideoCompilerError::IoError(format!(
+        "Ошибка скачивания FFmpeg: HTTP {status}"
+      )));
+    }
+
+    // Сервер без поддержки Range отдает файл целиком
+    let resumed = offset > 0 && status == reqwest::StatusCode::PARTIAL_CONTENT;
+    let mut downloaded = if resumed { offset } else { 0 };
+    let total = response.content_length().map(|len| len + downloaded);
+
+    let mut file = tokio::fs::OpenOptions::new()
+      .create(true)
+      .write(true)
+      .append(resumed)
+      .truncate(!resumed)
+      .open(partial)
+      .await?;
+
+    let mut reported = downloaded;
+    on_progress(FfmpegDownloadProgress {
+      stage: FfmpegDownloadStage::Downloading,
+      downloaded_bytes: downloaded,
+      total_bytes: total,
+      resumed,
+    });
+
+    loop {
+      let chunk = tokio::select! {
+        _ = token.cancelled() => {
+          file.flush().await.ok();
+          return Err(VideoCompilerError::CancelledError(
+            "Скачивание FFmpeg отменено".to_string(),
+          ));
+        }
+        chunk = response.chunk() => chunk
+          .map_err(|e| VideoCompilerError::IoError(format!("Ошибка скачивания FFmpeg: {e}")))?,
+      };
+      let Some(chunk) = chunk else { break };
+
+      file.write_all(&chunk).await?;
+      downloaded += chunk.len() as u64;
+      if downloaded - reported >= PROGRESS_STEP_BYTES {
+        reported = downloaded;
+        on_progress(FfmpegDownloadProgress {
+          stage: FfmpegDownloadStage::Downloading,
+          downloaded_bytes: downloaded,
+          total_bytes: total,
+          resumed,
+        });
+      }
+    }
+    file.flush().await?;
+
+    if let Some(total) = total {
+      if downloaded < total {
+        return Err(VideoCompilerError::IoError(format!(
+          "Скачивание FFmpeg прервано: {downloaded} из {total} байт"
+        )));
+      }
+    }
+
+    Ok((downloaded, total))
+  }
+
+  /// Ожидаемая контрольная сумма архива
+  async fn expected_sha256(&self, source: &FfmpegDownloadSource) -> Result<String> {
+    if let Some(sha256) = &source.sha256 {
+      return Ok(sha256.trim().to_lowercase());
+    }
+
+    let url = source.sha256_url.as_ref().ok_or_else(|| {
+      VideoCompilerError::ConfigError(format!(
+        "Для {} не указана контрольная сумма SHA-256",
+        source.url
+      ))
+    })?;
+    let checksums = self
+      .client
+      .get(url)
+      .send()
+      .await
+      .and_then(|response| response.error_for_status())
+      .map_err(|e| VideoCompilerError::IoError(format!("Ошибка получения контрольной суммы: {e}")))?
+      .text()
+      .await
+      .map_err(|e| {
+        VideoCompilerError::IoError(format!("Ошибка получения контрольной суммы: {e}"))
+      })?;
+
+    parse_checksum(&checksums, &source.file_name()).ok_or_else(|| {
+      VideoCompilerError::SecurityError(format!(
+        "Контрольная сумма для {} не найдена в {url}",
+        source.file_name()
+      ))
+    })
+  }
+
+  /// Распаковать архив и установить ffmpeg/ffprobe в `bin`
+  async fn extract(&self, archive: &Path) -> Result<PathBuf> {
+    let staging = self.install_dir.join("staging");
+    if staging.exists() {
+      tokio::fs::remove_dir_all(&staging).await?;
+    }
+    tokio::fs::create_dir_all(&staging).await?;
+
+    // tar (bsdtar в Windows 10+ и macOS) распаковывает и .tar.xz, и .zip
+    let output = tokio::process::Command::new("tar")
+      .arg("-xf")
+      .arg(archive)
+      .arg("-C")
+      .arg(&staging)
+      .output()
+      .await
+      .map_err(|e| VideoCompilerError::DependencyMissing(format!("tar недоступен: {e}").into()))?;
+    if !output.status.success() {
+      tokio::fs::remove_dir_all(&staging).await.ok();
+      return Err(VideoCompilerError::IoError(format!(
+        "Ошибка распаковки FFmpeg: {}",
+        String::from_utf8_lossy(&output.stderr)
+      )));
+    }
+
+    let result = self.install_binaries(&staging).await;
+    tokio::fs::remove_dir_all(&staging).await.ok();
+    result
+  }
+
+  /// Скопировать бинарники из распакованного архива
+  async fn install_binaries(&self, staging: &Path) -> Result<PathBuf> {
+    let ffmpeg_name = executable_name("ffmpeg");
+    let ffmpeg = find_file(staging, &ffmpeg_name).ok_or_else(|| {
+      VideoCompilerError::DependencyMissing(format!("{ffmpeg_name} не найден в архиве").into())
+    })?;
+
+    let bin_dir = self.install_dir.join("bin");
+    tokio::fs::create_dir_all(&bin_dir).await?;
+
+    let mut binaries = vec![ffmpeg.clone()];
+    if let Some(parent) = ffmpeg.parent() {
+      let ffprobe = parent.join(executable_name("ffprobe"));
+      if ffprobe.is_file() {
+        binaries.push(ffprobe);
+      }
+    }
+
+    for binary in binaries {
+      let target = bin_dir.join(binary.file_name().unwrap_or_default());
+      tokio::fs::copy(&binary, &target).await?;
+      #[cfg(unix)]
+      {
+        use std::os::unix::fs::PermissionsExt;
+        tokio::fs::set_permissions(&target, std::fs::Permissions::from_mode(0o755)).await?;
+      }
+    }
+
+    Ok(self.bundled_binary())
+  }
+
+  /// Источник FFmpeg по пути: встроенный, указанный пользователем или системный
+  pub fn classify_source(&self, path: &Path, custom_path: Option<&Path>) -> FfmpegSource {
+    if path.starts_with(&self.install_dir) {
+      FfmpegSource::Bundled
+    } else if custom_path == Some(path) {
+      FfmpegSource::Custom
+    } else {
+      FfmpegSource::System
+    }
+  }
+
+  /// Состояние FFmpeg по пути: версия и обязательные кодировщики и фильтры
+  pub async fn status(&self, path: &str, custom_path: Option<&Path>) -> FfmpegStatus {
+    let version = probe_version(Path::new(path)).await;
+    let available = version.is_some();
+
+    let capabilities = if available {
+      FfmpegCapabilitiesCache::shared(path).get().await.ok()
+    } else {
+      None
+    };
+
+    FfmpegStatus {
+      path: path.to_string(),
+      source: self.classify_source(Path::new(path), custom_path),
+      available,
+      version,
+      encoders: REQUIRED_ENCODERS
+        .iter()
+        .map(|name| {
+          (
+            name.to_string(),
+            capabilities.as_ref().is_some_and(|c| c.has_encoder(name)),
+          )
+        })
+        .collect(),
+      filters: REQUIRED_FILTERS
+        .iter()
+        .map(|name| {
+          (
+            name.to_string(),
+            capabilities.as_ref().is_some_and(|c| c.has_filter(name)),
+          )
+        })
+        .collect(),
+    }
+  }
+}
+
+/// Первая строка `ffmpeg -version`, если бинарник запускается
+pub async fn probe_version(path: &Path) -> Option<String> {
+  let output = tokio::process::Command::new(path)
+    .arg("-version")
+    .output()
+    .await
+    .ok()?;
+  if !output.status.success() {
+    return None;
+  }
+  String::from_utf8_lossy(&output.stdout)
+    .lines()
+    .next()
+    .map(str::to_string)
+}
+
+/// Имена компонентов из вывода `ffmpeg -encoders` / `ffmpeg -filters`.
+///
+/// Строки компонентов начинаются с флагов (`V....D`, `T.C`, `...`), за которыми
+/// идет имя; строки легенды содержат `=` вторым словом.
+pub fn parse_component_list(output: &str) -> std::collections::HashSet<String> {
+  const FLAG_CHARS: &str = "VASDFXBTC.|";
+
+  output
+    .lines()
+    .filter_map(|line| {
+      let mut words = line.split_whitespace();
+      let flags = words.next()?;
+      let name = words.next()?;
+      let is_flags = flags.chars().all(|c| FLAG_CHARS.contains(c));
+      (is_flags && name != "=").then(|| name.to_string())
+    })
+    .collect()
+}
+
+/// Контрольная сумма файла из списка в формате `sha256sum`
+/// (`<hash>  <file>`) или из файла с единственным хешем
+pub fn parse_checksum(checksums: &str, file_name: &str) -> Option<String> {
+  let is_hash = |word: &str| word.len() == 64 && word.chars().all(|c| c.is_ascii_hexdigit());
+
+  let listed = checksums.lines().find_map(|line| {
+    let mut words = line.split_whitespace();
+    let hash = words.next()?;
+    let name = words.next()?.trim_start_matches('*');
+    (is_hash(hash) && name == file_name).then(|| hash.to_lowercase())
+  });
+
+  listed.or_else(|| {
+    let mut lines = checksums
+      .lines()
+      .map(str::trim)
+      .filter(|line| !line.is_empty());
+    let single = lines.next()?;
+    (lines.next().is_none() && is_hash(single)).then(|| single.to_lowercase())
+  })
+}
+
+/// SHA-256 файла в шестнадцатеричном виде
+pub async fn sha256_file(path: &Path) -> Result<String> {
+  let mut file = tokio::fs::File::open(path).await?;
+  let mut hasher = Sha256::new();
+  let mut buffer = vec![0u8; 64 * 1024];
+  loop {
+    let read = file.read(&mut buffer).await?;
+    if read == 0 {
+      break;
+    }
+    hasher.update(&buffer[..read]);
+  }
+  Ok(
+    hasher
+      .finalize()
+      .iter()
+      .map(|byte| format!("{byte:02x}"))
+      .collect(),
+  )
+}
+
+/// Найти файл по имени в дереве директорий
+fn find_file(dir: &Path, name: &str) -> Option<PathBuf> {
+  let entries = std::fs::read_dir(dir).ok()?;
+  let mut subdirs = Vec::new();
+  for entry in entries.flatten() {
+    let path = entry.path();
+    if path.is_dir() {
+      subdirs.push(path);
+    } else if path.file_name().is_some_and(|file| file == name) {
+      return Some(path);
+    }
+  }
+  subdirs.sort();
+  subdirs.iter().find_map(|subdir| find_file(subdir, name))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_component_list_encoders_and_filters() {
+    let encoders = "Encoders:\n \
+      V..... = Video\n \
+      A..... = Audio\n \
+      ------\n \
+      V....D libx264              libx264 H.264 / AVC (codec h264)\n \
+      A....D aac                  AAC (Advanced Audio Coding)\n";
+    let parsed = parse_component_list(encoders);
+    assert!(parsed.contains("libx264"));
+    assert!(parsed.contains("aac"));
+    assert!(!parsed.contains("="));
+    assert!(!parsed.contains("Video"));
+
+    let filters = "Filters:\n  \
+      T.. = Timeline support\n  \
+      | = Source or sink filter\n \
+      TSC drawtext          V->V       Draw text on top of video frames.\n \
+      ... xfade             VV->V      Cross fade one video with another video.\n";
+    let parsed = parse_component_list(filters);
+    assert!(parsed.contains("drawtext"));
+    assert!(parsed.contains("xfade"));
+    assert_eq!(parsed.len(), 2);
+  }
+
+  #[test]
+  fn test_parse_checksum() {
+    let hash_a = "a".repeat(64);
+    let hash_b = "B".repeat(64);
+    let list = format!(
+      "{hash_a}  ffmpeg-master-latest-linux64-gpl.tar.xz\n{hash_b} *ffmpeg-master-latest-win64-gpl.zip\n"
+    );
+    assert_eq!(
+      parse_checksum(&list, "ffmpeg-master-latest-linux64-gpl.tar.xz"),
+      Some(hash_a.clone())
+    );
+    assert_eq!(
+      parse_checksum(&list, "ffmpeg-master-latest-win64-gpl.zip"),
+      Some("b".repeat(64))
+    );
+    assert_eq!(parse_checksum(&list, "missing.zip"), None);
+
+    // Файл с единственным хешем без имени
+    assert_eq!(
+      parse_checksum(&format!("{hash_a}\n"), "any.zip"),
+      Some(hash_a)
+    );
+    assert_eq!(parse_checksum("not a hash", "any.zip"), None);
+  }
+
+  #[test]
+  fn test_source_file_name_and_defaults() {
+    let source = FfmpegDownloadSource {
+      platform: "linux-x86_64".to_string(),
+      url: "https://example.com/builds/ffmpeg.tar.xz?token=1".to_string(),
+      sha256: None,
+      sha256_url: None,
+    };
+    assert_eq!(source.file_name(), "ffmpeg.tar.xz");
+
+    let defaults = default_download_sources();
+    assert!(defaults.iter().any(|s| s.platform == "linux-x86_64"));
+    assert!(defaults
+      .iter()
+      .all(|s| s.sha256.is_some() || s.sha256_url.is_some()));
+  }
+
+  #[test]
+  fn test_classify_source() {
+    let manager = FfmpegManager::with_install_dir(PathBuf::from("/data/ffmpeg"));
+    let bundled = manager.bundled_binary();
+    assert_eq!(
+      manager.classify_source(&bundled, None),
+      FfmpegSource::Bundled
+    );
+
+    let custom = Path::new("/opt/ffmpeg/bin/ffmpeg");
+    assert_eq!(
+      manager.classify_source(custom, Some(custom)),
+      FfmpegSource::Custom
+    );
+    assert_eq!(
+      manager.classify_source(Path::new("ffmpeg"), Some(custom)),
+      FfmpegSource::System
+    );
+  }
+
+  #[tokio::test]
+  async fn test_sha256_file_and_find_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let nested = dir.path().join("ffmpeg-build").join("bin");
+    std::fs::create_dir_all(&nested).unwrap();
+    std::fs::write(nested.join("ffmpeg"), b"abc").unwrap();
+
+    assert_eq!(find_file(dir.path(), "ffmpeg"), Some(nested.join("ffmpeg")));
+    assert_eq!(
+      sha256_file(&nested.join("ffmpeg")).await.unwrap(),
+      "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+  }
+
+  #[tokio::test]
+  async fn test_download_without_platform_source_fails() {
+    let manager = FfmpegManager::with_install_dir(std::env::temp_dir().join("ffmpeg-manager-test"));
+    let sources = vec![FfmpegDownloadSource {
+      platform: "unknown-platform".to_string(),
+      url: "https://example.com/ffmpeg.zip".to_string(),
+      sha256: Some("0".repeat(64)),
+      sha256_url: None,
+    }];
+
+    let result = manager.download_bundled(&sources, |_| {}).await;
+    assert!(matches!(
+      result,
+      Err(VideoCompilerError::DependencyMissing(_))
+    ));
+    assert!(!manager.is_downloading());
+    assert!(!manager.cancel_download());
+  }
+}
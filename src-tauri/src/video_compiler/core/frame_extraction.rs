@@ -649,7 +649,9 @@ pub struct ExtractionMetadata {
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::video_compiler::cache::{MediaMetadata, PreviewData, PreviewKey};
+  use crate::video_compiler::cache::{
+    MediaMetadata, PreviewData, PreviewKey, METADATA_SCHEMA_VERSION,
+  };
   use crate::video_compiler::preview::VideoInfo;
   use std::collections::HashMap;
   use std::time::{Duration, SystemTime};
@@ -783,6 +785,8 @@ mod tests {
       bitrate: Some(8000000),
       video_codec: Some("h264".to_string()),
       audio_codec: Some("aac".to_string()),
+      rotation: 0,
+      schema_version: METADATA_SCHEMA_VERSION,
       cached_at: SystemTime::now(),
     };
 
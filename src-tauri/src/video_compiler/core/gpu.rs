@@ -9,6 +9,7 @@
  */
 
 use crate::video_compiler::error::{Result, VideoCompilerError};
+use crate::video_compiler::services::ffmpeg_capabilities::FfmpegCapabilitiesCache;
 use serde::{Deserialize, Serialize};
 
 /// Типы GPU кодировщиков
@@ -129,55 +130,21 @@ impl GpuDetector {
     Ok(available)
   }
 
-  /// Проверить доступность конкретного кодировщика
+  /// Проверить доступность конкретного кодировщика по общему кэшу
+  /// возможностей FFmpeg
   async fn check_encoder_available(&self, codec: &str) -> Result<bool> {
     log::debug!("Checking encoder availability for: {codec}");
 
-    // In CI environments, retry a few times to avoid "Text file busy" errors
-    let mut last_error = None;
-    for attempt in 0..3 {
-      if attempt > 0 {
-        // Wait a bit before retrying
-        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-      }
-
-      match tokio::process::Command::new(&self.ffmpeg_path)
-        .args(["-encoders"])
-        .output()
-        .await
-      {
-        Ok(output) => {
-          if output.status.success() {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            let available = stdout.contains(codec);
-            log::debug!(
-              "Encoder {} is {}",
-              codec,
-              if available {
-                "available"
-              } else {
-                "not available"
-              }
-            );
-            return Ok(available);
-          } else {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            log::error!("Failed to check encoders: {stderr}");
-            last_error = Some(format!("ffmpeg exited with non-zero status: {stderr}"));
-          }
-        }
-        Err(e) => {
-          last_error = Some(format!("Failed to run ffmpeg: {e}"));
-          log::warn!("Attempt {}/{} failed: {e}", attempt + 1, 3);
-        }
+    match FfmpegCapabilitiesCache::shared(&self.ffmpeg_path)
+      .get()
+      .await
+    {
+      Ok(capabilities) => Ok(capabilities.has_encoder(codec)),
+      Err(e) => {
+        log::error!("Failed to check encoders: {e}");
+        Ok(false)
       }
     }
-
-    // If all attempts failed, return the last error
-    if let Some(err) = last_error {
-      log::error!("All attempts to check encoder failed: {err}");
-    }
-    Ok(false)
   }
 
   /// Получить рекомендуемый кодировщик для текущей платформы
@@ -1008,14 +975,18 @@ mod tests {
     #[cfg(unix)]
     {
       use std::os::unix::fs::PermissionsExt;
-      std::fs::write(&mock_ffmpeg, "#!/bin/sh\necho ' h264_nvenc'\nexit 0").unwrap();
+      std::fs::write(&mock_ffmpeg, "#!/bin/sh\necho ' V....D h264_nvenc'\nexit 0").unwrap();
       std::fs::set_permissions(&mock_ffmpeg, std::fs::Permissions::from_mode(0o755)).unwrap();
     }
 
     #[cfg(windows)]
     {
       let mock_ffmpeg = temp_dir.path().join("ffmpeg.bat");
-      std::fs::write(&mock_ffmpeg, "@echo off\necho  h264_nvenc\nexit /b 0").unwrap();
+      std::fs::write(
+        &mock_ffmpeg,
+        "@echo off\necho  V....D h264_nvenc\nexit /b 0",
+      )
+      .unwrap();
     }
 
     let detector = GpuDetector::new(mock_ffmpeg.to_string_lossy().to_string());
@@ -1187,14 +1158,22 @@ exit /b 0"#;
     // Create a mock ffmpeg
     #[cfg(unix)]
     {
-      std::fs::write(&mock_ffmpeg, "#!/bin/bash\necho ' h264_nvenc'\nexit 0").unwrap();
+      std::fs::write(
+        &mock_ffmpeg,
+        "#!/bin/bash\necho ' V....D h264_nvenc'\nexit 0",
+      )
+      .unwrap();
       use std::os::unix::fs::PermissionsExt;
       std::fs::set_permissions(&mock_ffmpeg, std::fs::Permissions::from_mode(0o755)).unwrap();
     }
 
     #[cfg(windows)]
     {
-      std::fs::write(&mock_ffmpeg, "@echo off\necho  h264_nvenc\nexit /b 0").unwrap();
+      std::fs::write(
+        &mock_ffmpeg,
+        "@echo off\necho  V....D h264_nvenc\nexit /b 0",
+      )
+      .unwrap();
     }
 
     let detector = GpuDetector::new(mock_ffmpeg.to_string_lossy().to_string());
@@ -1371,11 +1350,11 @@ exit /b 0"#;
 case "$*" in
   *"-encoders"*)
     echo "Video:"
-    echo " h264_nvenc          NVIDIA NVENC H.264 encoder"
-    echo " h264_qsv            Intel QuickSync Video H.264 encoder"  
-    echo " h264_videotoolbox   VideoToolbox H.264 encoder"
-    echo " h264_vaapi          VAAPI H.264 encoder"
-    echo " libx264             libx264 H.264 encoder"
+    echo " V....D h264_nvenc          NVIDIA NVENC H.264 encoder"
+    echo " V....D h264_qsv            Intel QuickSync Video H.264 encoder"  
+    echo " V....D h264_videotoolbox   VideoToolbox H.264 encoder"
+    echo " V....D h264_vaapi          VAAPI H.264 encoder"
+    echo " V....D libx264             libx264 H.264 encoder"
     ;;
   *"h264_nvenc"*)
     echo "Encoder h264_nvenc:"
@@ -1400,9 +1379,9 @@ exit 0"#;
       let script = r#"@echo off
 if "%*" == "-encoders" (
     echo Video:
-    echo  h264_nvenc          NVIDIA NVENC H.264 encoder
-    echo  h264_qsv            Intel QuickSync Video H.264 encoder
-    echo  libx264             libx264 H.264 encoder
+    echo  V....D h264_nvenc          NVIDIA NVENC H.264 encoder
+    echo  V....D h264_qsv            Intel QuickSync Video H.264 encoder
+    echo  V....D libx264             libx264 H.264 encoder
 ) else if "%*" == "-f null -" (
     echo ffmpeg version 4.4.0
 ) else (
@@ -1491,7 +1470,7 @@ exit /b 0"#;
 case "$*" in
   *"-encoders"*)
     echo "Video:"
-    echo " libx264             libx264 H.264 encoder"
+    echo " V....D libx264             libx264 H.264 encoder"
     ;;
   *"libx264"*)
     echo "Encoder libx264:"
@@ -1516,7 +1495,7 @@ exit 0"#;
       let script = r#"@echo off
 if "%*" == "-encoders" (
     echo Video:
-    echo  libx264             libx264 H.264 encoder
+    echo  V....D libx264             libx264 H.264 encoder
 ) else if "%1" == "libx264" (
     echo Encoder libx264:
     echo General capabilities: encoder
@@ -1649,10 +1628,10 @@ exit /b 0"#;
 case "$*" in
   *"-encoders"*)
     echo "Video:"
-    echo " h264_nvenc         NVENC H.264 encoder"
-    echo " h264_qsv           Intel QuickSync Video H.264 encoder" 
-    echo " h264_vaapi         VA-API H.264 encoder"
-    echo " libx264           libx264 H.264 encoder"
+    echo " V....D h264_nvenc         NVENC H.264 encoder"
+    echo " V....D h264_qsv           Intel QuickSync Video H.264 encoder" 
+    echo " V....D h264_vaapi         VA-API H.264 encoder"
+    echo " V....D libx264           libx264 H.264 encoder"
     ;;
   *)
     echo "ffmpeg version 4.4.0"
@@ -1677,10 +1656,10 @@ exit 0"#;
       let script = r#"@echo off
 if "%*" == "-encoders" (
     echo Video:
-    echo  h264_nvenc         NVENC H.264 encoder
-    echo  h264_qsv           Intel QuickSync Video H.264 encoder
-    echo  h264_vaapi         VA-API H.264 encoder
-    echo  libx264           libx264 H.264 encoder
+    echo  V....D h264_nvenc         NVENC H.264 encoder
+    echo  V....D h264_qsv           Intel QuickSync Video H.264 encoder
+    echo  V....D h264_vaapi         VA-API H.264 encoder
+    echo  V....D libx264           libx264 H.264 encoder
 ) else (
     echo ffmpeg version 4.4.0
 )
@@ -0,0 +1,446 @@
+//! GPU Memory - Нехватка видеопамяти при аппаратном рендеринге
+//!
+//! Распознает сообщения CUDA/NVENC/VAAPI о нехватке памяти в stderr FFmpeg
+//! и задает лестницу понижения режима: сначала повтор без аппаратного
+//! декодирования, затем с программным кодировщиком. Перед рендерингом
+//! оценивает потребление видеопамяти по разрешению таймлайна.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::video_compiler::error::VideoCompilerError;
+use crate::video_compiler::ffmpeg_builder::FFmpegBuilder;
+use crate::video_compiler::schema::ProjectSchema;
+
+/// Коды ошибок, однозначно указывающие на нехватку видеопамяти
+const GPU_OOM_CODES: &[&str] = &[
+  "cuda_error_out_of_memory",
+  "nv_enc_err_out_of_memory",
+  "cudaerrormemoryallocation",
+  "va_status_error_allocation_failed",
+];
+
+/// Общие сообщения о нехватке памяти
+const OOM_MESSAGES: &[&str] = &[
+  "out of memory",
+  "cannot allocate memory",
+  "failed to allocate",
+  "allocation failed",
+];
+
+/// Признаки того, что строка относится к аппаратному декодированию или кодированию
+const GPU_CONTEXT_MARKERS: &[&str] = &[
+  "cuda",
+  "cumem",
+  "nvenc",
+  "nvdec",
+  "cuvid",
+  "vaapi",
+  "hwupload",
+  "hwframe",
+  "hw_frames",
+];
+
+/// Кадров одной задачи, одновременно находящихся в видеопамяти
+/// (пул декодера, очередь кодировщика и буферы фильтров)
+pub const FRAMES_IN_FLIGHT_PER_JOB: u64 = 32;
+
+/// Постоянные затраты видеопамяти одной задачи (контекст CUDA, сессия кодировщика)
+pub const VRAM_OVERHEAD_PER_JOB: u64 = 256 * 1024 * 1024;
+
+/// Есть ли в stderr FFmpeg признаки нехватки видеопамяти
+pub fn is_gpu_out_of_memory(stderr: &str) -> bool {
+  stderr.lines().any(|line| {
+    let line = line.to_lowercase();
+    GPU_OOM_CODES.iter().any(|code| line.contains(code))
+      || (OOM_MESSAGES.iter().any(|message| line.contains(message))
+        && GPU_CONTEXT_MARKERS
+          .iter()
+          .any(|marker| line.contains(marker)))
+  })
+}
+
+/// Вызвана ли ошибка рендеринга нехваткой видеопамяти
+pub fn is_gpu_oom_error(error: &VideoCompilerError) -> bool {
+  match error {
+    VideoCompilerError::FFmpegError { stderr, .. } => is_gpu_out_of_memory(stderr),
+    VideoCompilerError::GpuError(message) => is_gpu_out_of_memory(message),
+    _ => false,
+  }
+}
+
+/// Шаг понижения режима рендеринга после нехватки видеопамяти
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RenderDowngrade {
+  /// Повтор без аппаратного декодирования входов
+  DisableGpuDecoding,
+  /// Повтор с программным кодировщиком
+  CpuEncoder,
+}
+
+impl RenderDowngrade {
+  /// Порядок шагов лестницы понижения
+  pub const LADDER: [RenderDowngrade; 2] = [Self::DisableGpuDecoding, Self::CpuEncoder];
+
+  /// Сообщение для пользователя
+  pub fn message(self) -> String {
+    match self {
+      Self::DisableGpuDecoding => {
+        "Недостаточно видеопамяти: повтор без аппаратного декодирования".to_string()
+      }
+      Self::CpuEncoder => {
+        "Недостаточно видеопамяти: повтор с программным кодировщиком, рендеринг будет медленнее"
+          .to_string()
+      }
+    }
+  }
+
+  /// Меняет ли шаг что-то в текущих настройках построителя
+  pub fn applies_to(self, builder: &FFmpegBuilder) -> bool {
+    match self {
+      Self::DisableGpuDecoding => builder.settings().hardware_decoding,
+      Self::CpuEncoder => {
+        builder.settings().use_hardware_acceleration
+          || builder.project().settings.export.hardware_acceleration
+      }
+    }
+  }
+}
+
+/// Лестница понижения режима одной задачи рендеринга.
+///
+/// Каждый шаг применяется не более одного раза, поэтому число повторов
+/// ограничено длиной лестницы.
+#[derive(Debug, Clone, Default)]
+pub struct GpuFallbackLadder {
+  applied: Vec<RenderDowngrade>,
+}
+
+impl GpuFallbackLadder {
+  /// Создать лестницу без примененных шагов
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Следующий шаг после ошибки `error` при настройках `builder`.
+  ///
+  /// `None` означает, что повтор не поможет и ошибку нужно вернуть.
+  pub fn next_step(
+    &mut self,
+    builder: &FFmpegBuilder,
+    error: &VideoCompilerError,
+  ) -> Option<RenderDowngrade> {
+    if !is_gpu_oom_error(error) {
+      return None;
+    }
+
+    let step = RenderDowngrade::LADDER
+      .into_iter()
+      .find(|step| !self.applied.contains(step) && step.applies_to(builder))?;
+    self.applied.push(step);
+    Some(step)
+  }
+
+  /// Примененные шаги в порядке применения
+  pub fn applied(&self) -> &[RenderDowngrade] {
+    &self.applied
+  }
+}
+
+/// Занятость видеопамяти GPU
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GpuMemoryInfo {
+  /// Всего видеопамяти в байтах
+  pub total_bytes: u64,
+  /// Занято видеопамяти в байтах
+  pub used_bytes: u64,
+}
+
+impl GpuMemoryInfo {
+  /// Свободная видеопамять в байтах
+  pub fn free_bytes(&self) -> u64 {
+    self.total_bytes.saturating_sub(self.used_bytes)
+  }
+}
+
+/// Разобрать вывод `nvidia-smi --query-gpu=memory.total,memory.used
+/// --format=csv,noheader,nounits` (значения в МиБ). Берется первый GPU.
+pub fn parse_nvidia_smi_memory(output: &str) -> Option<GpuMemoryInfo> {
+  let line = output.lines().find(|line| !line.trim().is_empty())?;
+  let mut values = line.split(',').map(|value| value.trim().parse::<u64>());
+  let total = values.next()?.ok()?;
+  let used = values.next()?.ok()?;
+
+  Some(GpuMemoryInfo {
+    total_bytes: total * 1024 * 1024,
+    used_bytes: used * 1024 * 1024,
+  })
+}
+
+/// Прочитать видеопамять из sysfs (`card*/device/mem_info_vram_*` драйвера amdgpu).
+///
+/// Берется карта с наибольшим объемом видеопамяти.
+pub async fn read_sysfs_vram(drm_root: &Path) -> Option<GpuMemoryInfo> {
+  let mut entries = tokio::fs::read_dir(drm_root).await.ok()?;
+  let mut best: Option<GpuMemoryInfo> = None;
+
+  while let Ok(Some(entry)) = entries.next_entry().await {
+    let name = entry.file_name();
+    let name = name.to_string_lossy();
+    // card0-DP-1 и подобные записи описывают разъемы, а не устройства
+    if !name.starts_with("card") || name.contains('-') {
+      continue;
+    }
+
+    let device = entry.path().join("device");
+    let (Some(total), Some(used)) = (
+      read_sysfs_value(&device.join("mem_info_vram_total")).await,
+      read_sysfs_value(&device.join("mem_info_vram_used")).await,
+    ) else {
+      continue;
+    };
+
+    if best.is_none_or(|best| total > best.total_bytes) {
+      best = Some(GpuMemoryInfo {
+        total_bytes: total,
+        used_bytes: used,
+      });
+    }
+  }
+
+  best
+}
+
+async fn read_sysfs_value(path: &Path) -> Option<u64> {
+  tokio::fs::read_to_string(path)
+    .await
+    .ok()?
+    .trim()
+    .parse()
+    .ok()
+}
+
+/// Запросить занятость видеопамяти: через nvidia-smi, затем через sysfs
+pub async fn query_gpu_memory() -> Option<GpuMemoryInfo> {
+  let output = tokio::process::Command::new("nvidia-smi")
+    .args([
+      "--query-gpu=memory.total,memory.used",
+      "--format=csv,noheader,nounits",
+    ])
+    .output()
+    .await;
+
+  if let Ok(output) = output {
+    if output.status.success() {
+      if let Some(info) = parse_nvidia_smi_memory(&String::from_utf8_lossy(&output.stdout)) {
+        return Some(info);
+      }
+    }
+  }
+
+  if cfg!(target_os = "linux") {
+    return read_sysfs_vram(Path::new("/sys/class/drm")).await;
+  }
+
+  None
+}
+
+/// Оценка видеопамяти для `concurrent_jobs` одновременных рендеров проекта
+pub fn estimate_render_vram(project: &ProjectSchema, concurrent_jobs: usize) -> u64 {
+  let (width, height) = project.timeline.resolution;
+  // Кадры в GPU хранятся в NV12: 1.5 байта на пиксель
+  let frame_bytes = width as u64 * height as u64 * 3 / 2;
+  let per_job = VRAM_OVERHEAD_PER_JOB + frame_bytes * FRAMES_IN_FLIGHT_PER_JOB;
+  per_job * concurrent_jobs.max(1) as u64
+}
+
+/// Предупреждение перед рендерингом, если оценка превышает свободную видеопамять.
+///
+/// Для рендеринга без аппаратного ускорения предупреждение не формируется.
+pub fn vram_preflight_warning(
+  project: &ProjectSchema,
+  concurrent_jobs: usize,
+  memory: GpuMemoryInfo,
+) -> Option<String> {
+  if !project.settings.export.hardware_acceleration {
+    return None;
+  }
+
+  let estimated = estimate_render_vram(project, concurrent_jobs);
+  if estimated <= memory.free_bytes() {
+    return None;
+  }
+
+  let (width, height) = project.timeline.resolution;
+  Some(format!(
+    "Рендеринг {width}x{height} в {} задач(и) может потребовать около {} МиБ видеопамяти, \
+     свободно {} МиБ: при нехватке кодирование перейдет на CPU",
+    concurrent_jobs.max(1),
+    estimated / (1024 * 1024),
+    memory.free_bytes() / (1024 * 1024)
+  ))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::video_compiler::ffmpeg_builder::FFmpegBuilderSettings;
+  use tempfile::TempDir;
+
+  const NVENC_OOM: &str =
+    "[h264_nvenc @ 0x5581] OpenEncodeSessionEx failed: out of memory (10): (no details)\n\
+     [h264_nvenc @ 0x5581] No capable devices found";
+  const CUDA_OOM: &str =
+    "[hevc @ 0x55] cu->cuMemAlloc(&data, size) failed -> CUDA_ERROR_OUT_OF_MEMORY";
+  const VAAPI_OOM: &str =
+    "[hwupload @ 0x55] Failed to upload frame: VA_STATUS_ERROR_ALLOCATION_FAILED";
+
+  fn gpu_builder(hardware_decoding: bool) -> FFmpegBuilder {
+    let mut project = ProjectSchema::new("GPU".to_string());
+    project.settings.export.hardware_acceleration = true;
+    FFmpegBuilder::with_settings(
+      project,
+      FFmpegBuilderSettings {
+        use_hardware_acceleration: true,
+        hardware_acceleration_type: Some("nvenc".to_string()),
+        hardware_decoding,
+        ..Default::default()
+      },
+    )
+  }
+
+  fn ffmpeg_error(stderr: &str) -> VideoCompilerError {
+    VideoCompilerError::ffmpeg(Some(1), stderr.to_string(), "ffmpeg encoding".to_string())
+  }
+
+  #[test]
+  fn test_detects_gpu_oom_signatures() {
+    assert!(is_gpu_out_of_memory(NVENC_OOM));
+    assert!(is_gpu_out_of_memory(CUDA_OOM));
+    assert!(is_gpu_out_of_memory(VAAPI_OOM));
+    assert!(is_gpu_out_of_memory(
+      "[AVHWFramesContext @ 0x55] cuda: failed to allocate frame pool"
+    ));
+
+    // Нехватка оперативной памяти и прочие ошибки GPU не считаются нехваткой видеопамяти
+    assert!(!is_gpu_out_of_memory(
+      "[libx264 @ 0x55] malloc: out of memory"
+    ));
+    assert!(!is_gpu_out_of_memory(
+      "[h264_nvenc @ 0x55] Driver does not support the required nvenc API version"
+    ));
+    assert!(!is_gpu_out_of_memory(""));
+  }
+
+  #[test]
+  fn test_ladder_disables_decoding_then_encoder() {
+    let mut builder = gpu_builder(true);
+    let mut ladder = GpuFallbackLadder::new();
+
+    let step = ladder.next_step(&builder, &ffmpeg_error(CUDA_OOM));
+    assert_eq!(step, Some(RenderDowngrade::DisableGpuDecoding));
+    builder = builder.with_downgrade(RenderDowngrade::DisableGpuDecoding);
+    assert!(!builder.settings().hardware_decoding);
+    assert!(builder.settings().use_hardware_acceleration);
+
+    let step = ladder.next_step(&builder, &ffmpeg_error(NVENC_OOM));
+    assert_eq!(step, Some(RenderDowngrade::CpuEncoder));
+    builder = builder.with_downgrade(RenderDowngrade::CpuEncoder);
+    assert!(!builder.settings().use_hardware_acceleration);
+    assert!(!builder.project().settings.export.hardware_acceleration);
+
+    // После программного кодировщика повторять нечего
+    assert_eq!(ladder.next_step(&builder, &ffmpeg_error(NVENC_OOM)), None);
+    assert_eq!(
+      ladder.applied(),
+      &[
+        RenderDowngrade::DisableGpuDecoding,
+        RenderDowngrade::CpuEncoder
+      ]
+    );
+  }
+
+  #[test]
+  fn test_ladder_skips_decoding_step_without_hardware_decoding() {
+    let builder = gpu_builder(false);
+    let mut ladder = GpuFallbackLadder::new();
+
+    let error = VideoCompilerError::gpu(format!("FFmpeg GPU encoding failed\n{VAAPI_OOM}"));
+    assert_eq!(
+      ladder.next_step(&builder, &error),
+      Some(RenderDowngrade::CpuEncoder)
+    );
+  }
+
+  #[test]
+  fn test_ladder_ignores_other_errors() {
+    let builder = gpu_builder(true);
+    let mut ladder = GpuFallbackLadder::new();
+
+    assert_eq!(
+      ladder.next_step(&builder, &ffmpeg_error("Invalid data found")),
+      None
+    );
+    assert_eq!(
+      ladder.next_step(
+        &builder,
+        &VideoCompilerError::CancelledError("отмена".to_string())
+      ),
+      None
+    );
+    assert!(ladder.applied().is_empty());
+  }
+
+  #[test]
+  fn test_parse_nvidia_smi_memory() {
+    let info = parse_nvidia_smi_memory("8192, 1024\n24576, 0\n").unwrap();
+    assert_eq!(info.total_bytes, 8192 * 1024 * 1024);
+    assert_eq!(info.used_bytes, 1024 * 1024 * 1024);
+    assert_eq!(info.free_bytes(), 7168 * 1024 * 1024);
+
+    assert!(parse_nvidia_smi_memory("[N/A], [N/A]").is_none());
+    assert!(parse_nvidia_smi_memory("").is_none());
+  }
+
+  #[tokio::test]
+  async fn test_read_sysfs_vram_picks_largest_card() {
+    let root = TempDir::new().unwrap();
+    for (card, total, used) in [("card0", 512u64, 100u64), ("card1", 8192, 2048)] {
+      let device = root.path().join(card).join("device");
+      std::fs::create_dir_all(&device).unwrap();
+      std::fs::write(device.join("mem_info_vram_total"), format!("{total}\n")).unwrap();
+      std::fs::write(device.join("mem_info_vram_used"), format!("{used}\n")).unwrap();
+    }
+    std::fs::create_dir_all(root.path().join("card1-DP-1")).unwrap();
+
+    let info = read_sysfs_vram(root.path()).await.unwrap();
+    assert_eq!(info.total_bytes, 8192);
+    assert_eq!(info.used_bytes, 2048);
+
+    let empty = TempDir::new().unwrap();
+    assert!(read_sysfs_vram(empty.path()).await.is_none());
+  }
+
+  #[test]
+  fn test_vram_preflight_warning() {
+    let mut project = ProjectSchema::new("4K".to_string());
+    project.timeline.resolution = (3840, 2160);
+    project.settings.export.hardware_acceleration = true;
+
+    let per_job = estimate_render_vram(&project, 1);
+    assert_eq!(estimate_render_vram(&project, 3), per_job * 3);
+
+    let memory = GpuMemoryInfo {
+      total_bytes: per_job * 2,
+      used_bytes: 0,
+    };
+    assert!(vram_preflight_warning(&project, 1, memory).is_none());
+    let warning = vram_preflight_warning(&project, 3, memory).unwrap();
+    assert!(warning.contains("3840x2160"));
+
+    // Без аппаратного ускорения видеопамять не используется
+    project.settings.export.hardware_acceleration = false;
+    assert!(vram_preflight_warning(&project, 3, memory).is_none());
+  }
+}
@@ -2,23 +2,35 @@
 //!
 //! Этот модуль содержит основные компоненты системы компиляции видео:
 //! - Кэширование и управление ресурсами
+//! - Управление встроенной сборкой FFmpeg
 //! - Обработка ошибок
 //! - Извлечение кадров
-//! - Поддержка GPU
+//! - Поддержка GPU и понижение режима при нехватке видеопамяти
 //! - Конвейер обработки
 //! - Генерация превью
 //! - Отслеживание прогресса
 //! - Рендеринг видео
+//! - Кэш сегментов для повторных экспортов
+//! - Размещение временных файлов
+//! - Приоритет процессов FFmpeg при рендеринге
 
+pub mod audio_sync;
 pub mod cache;
 pub mod constants;
 pub mod error;
+pub mod ffmpeg_manager;
 pub mod frame_extraction;
 pub mod gpu;
+pub mod gpu_memory;
+pub mod output_template;
 pub mod pipeline;
 pub mod preview;
+pub mod priority;
 pub mod progress;
+pub mod render_priority;
 pub mod renderer;
+pub mod segment_cache;
+pub mod temp_storage;
 
 // Новые модули после рефакторинга
 pub mod pipeline_refactored;
@@ -0,0 +1,505 @@
+//! Output Template - Шаблоны имен выходных файлов
+//!
+//! Шаблон задается в `ExportSettings::filename_template` и поддерживает токены
+//! `{project}`, `{date}`/`{date:FORMAT}`, `{time}`, `{resolution}`, `{fps}`,
+//! `{preset}`, `{duration}` и `{counter}`. Итоговое имя очищается от символов,
+//! недопустимых в файловой системе, и не перезаписывает существующие файлы.
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::video_compiler::error::{Result, VideoCompilerError};
+use crate::video_compiler::schema::ProjectSchema;
+
+/// Шаблон по умолчанию, если в настройках экспорта он не задан
+pub const DEFAULT_FILENAME_TEMPLATE: &str = "{project}_{date}_{resolution}";
+
+/// Формат токена `{date}` без явного формата
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Формат токена `{time}` (без двоеточий, недопустимых в Windows)
+const TIME_FORMAT: &str = "%H-%M-%S";
+
+/// Максимальная длина имени файла в байтах (ext4, NTFS, APFS)
+pub const MAX_FILE_NAME_LENGTH: usize = 255;
+
+/// Максимальная длина полного пути
+pub const MAX_PATH_LENGTH: usize = if cfg!(windows) { 260 } else { 4096 };
+
+/// Сколько вариантов имени перебирать при коллизиях
+const MAX_COLLISION_ATTEMPTS: u32 = 10_000;
+
+/// Имена устройств, зарезервированные в Windows независимо от расширения
+const WINDOWS_RESERVED_NAMES: &[&str] = &[
+  "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+  "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Элемент разобранного шаблона
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplateToken {
+  Literal(String),
+  Project,
+  Date(String),
+  Time,
+  Resolution,
+  Fps,
+  Preset,
+  Duration,
+  Counter,
+}
+
+/// Результат проверки шаблона для live-превью в UI
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilenameTemplateValidation {
+  pub valid: bool,
+  /// Ошибки разбора: неизвестные токены, незакрытые скобки, неверный формат даты
+  pub errors: Vec<String>,
+  /// Пример имени файла для текущего проекта
+  pub preview: Option<String>,
+}
+
+/// Значения токенов для конкретного проекта
+#[derive(Debug, Clone)]
+pub struct TemplateContext {
+  pub project: String,
+  pub resolution: (u32, u32),
+  pub fps: u32,
+  pub preset: String,
+  pub duration: f64,
+  pub now: DateTime<Local>,
+}
+
+impl TemplateContext {
+  /// Контекст из схемы проекта на момент `now`
+  pub fn from_project(project: &ProjectSchema, now: DateTime<Local>) -> Self {
+    let duration = if project.timeline.duration > 0.0 {
+      project.timeline.duration
+    } else {
+      project.get_duration()
+    };
+
+    Self {
+      project: project.metadata.name.clone(),
+      resolution: project.timeline.resolution,
+      fps: project.timeline.fps,
+      preset: project
+        .settings
+        .export
+        .export_preset
+        .clone()
+        .unwrap_or_else(|| "custom".to_string()),
+      duration,
+      now,
+    }
+  }
+}
+
+/// Разобрать шаблон. Возвращает все найденные ошибки сразу.
+pub fn parse_template(template: &str) -> std::result::Result<Vec<TemplateToken>, Vec<String>> {
+  let mut tokens = Vec::new();
+  let mut errors = Vec::new();
+  let mut literal = String::new();
+  let mut rest = template;
+
+  while let Some(open) = rest.find('{') {
+    literal.push_str(&rest[..open]);
+    let Some(close) = rest[open..].find('}') else {
+      errors.push(format!("Unclosed token at: {}", &rest[open..]));
+      rest = "";
+      break;
+    };
+
+    let body = &rest[open + 1..open + close];
+    let (name, argument) = match body.split_once(':') {
+      Some((name, argument)) => (name, Some(argument)),
+      None => (body, None),
+    };
+
+    let token = match (name, argument) {
+      ("project", None) => Some(TemplateToken::Project),
+      ("date", None) => Some(TemplateToken::Date(DEFAULT_DATE_FORMAT.to_string())),
+      ("date", Some(format)) => {
+        if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+          errors.push(format!("Invalid date format: {format}"));
+          None
+        } else {
+          Some(TemplateToken::Date(format.to_string()))
+        }
+      }
+      ("time", None) => Some(TemplateToken::Time),
+      ("resolution", None) => Some(TemplateToken::Resolution),
+      ("fps", None) => Some(TemplateToken::Fps),
+      ("preset", None) => Some(TemplateToken::Preset),
+      ("duration", None) => Some(TemplateToken::Duration),
+      ("counter", None) => Some(TemplateToken::Counter),
+      _ => {
+        errors.push(format!("Unknown token: {{{body}}}"));
+        None
+      }
+    };
+
+    if let Some(token) = token {
+      if !literal.is_empty() {
+        tokens.push(TemplateToken::Literal(std::mem::take(&mut literal)));
+      }
+      tokens.push(token);
+    }
+    rest = &rest[open + close + 1..];
+  }
+
+  literal.push_str(rest);
+  if !literal.is_empty() {
+    tokens.push(TemplateToken::Literal(literal));
+  }
+
+  if errors.is_empty() {
+    Ok(tokens)
+  } else {
+    Err(errors)
+  }
+}
+
+/// Подставить значения токенов (без расширения и очистки)
+pub fn render_template(
+  tokens: &[TemplateToken],
+  context: &TemplateContext,
+  counter: u32,
+) -> String {
+  tokens
+    .iter()
+    .map(|token| match token {
+      TemplateToken::Literal(text) => text.clone(),
+      TemplateToken::Project => context.project.clone(),
+      TemplateToken::Date(format) => context.now.format(format).to_string(),
+      TemplateToken::Time => context.now.format(TIME_FORMAT).to_string(),
+      TemplateToken::Resolution => format!("{}p", context.resolution.1),
+      TemplateToken::Fps => format!("{}fps", context.fps),
+      TemplateToken::Preset => context.preset.clone(),
+      TemplateToken::Duration => {
+        let total = context.duration.max(0.0).round() as u64;
+        format!("{}m{:02}s", total / 60, total % 60)
+      }
+      TemplateToken::Counter => counter.to_string(),
+    })
+    .collect()
+}
+
+/// Очистить имя файла (без расширения) для текущей ОС
+pub fn sanitize_file_stem(name: &str) -> String {
+  sanitize_file_stem_for(name, cfg!(windows))
+}
+
+/// Очистить имя файла для Windows (`windows = true`) или Unix-подобных систем.
+///
+/// Двоеточие заменяется и вне Windows: macOS Finder показывает его как `/`.
+pub fn sanitize_file_stem_for(name: &str, windows: bool) -> String {
+  let mut sanitized: String = name
+    .chars()
+    .map(|c| match c {
+      '/' | '\\' | ':' | '\0' => '_',
+      '<' | '>' | '"' | '|' | '?' | '*' if windows => '_',
+      c if c.is_control() => '_',
+      c => c,
+    })
+    .collect();
+
+  // Скрытые файлы и хвостовые точки/пробелы (Windows их отбрасывает)
+  sanitized = sanitized
+    .trim_start_matches('.')
+    .trim_end_matches(['.', ' '])
+    .trim_start()
+    .to_string();
+
+  if sanitized.is_empty() {
+    return "untitled".to_string();
+  }
+
+  if windows {
+    let device = sanitized.split('.').next().unwrap_or_default();
+    let reserved = WINDOWS_RESERVED_NAMES
+      .iter()
+      .any(|reserved| reserved.eq_ignore_ascii_case(device));
+    if reserved {
+      let device_len = device.len();
+      sanitized.insert(device_len, '_');
+    }
+  }
+
+  sanitized
+}
+
+/// Обрезать строку до `max_bytes` по границе символа
+fn truncate_to_bytes(value: &str, max_bytes: usize) -> &str {
+  if value.len() <= max_bytes {
+    return value;
+  }
+  let mut end = max_bytes;
+  while !value.is_char_boundary(end) {
+    end -= 1;
+  }
+  &value[..end]
+}
+
+/// Собрать имя файла с учетом ограничений длины имени и пути.
+///
+/// Обрезается основная часть имени, суффикс коллизии и расширение сохраняются.
+pub fn fit_file_name(
+  stem: &str,
+  suffix: &str,
+  extension: &str,
+  output_dir: &Path,
+  max_path: usize,
+) -> Result<String> {
+  let fixed = suffix.len() + extension.len() + 1;
+  // +1 на разделитель между каталогом и именем
+  let dir_len = output_dir.as_os_str().len() + 1;
+  let budget = MAX_FILE_NAME_LENGTH
+    .min(max_path.saturating_sub(dir_len))
+    .saturating_sub(fixed);
+
+  if budget == 0 {
+    return Err(VideoCompilerError::InvalidParameter(format!(
+      "Output directory path is too long: {}",
+      output_dir.display()
+    )));
+  }
+
+  let stem = truncate_to_bytes(stem, budget).trim_end_matches(['.', ' ']);
+  Ok(format!("{stem}{suffix}.{extension}"))
+}
+
+/// Построить свободный путь выходного файла по шаблону.
+///
+/// При коллизии увеличивается `{counter}`, а если токена нет в шаблоне -
+/// к имени добавляется `_(n)`.
+pub fn resolve_output_filename(
+  project: &ProjectSchema,
+  template: &str,
+  output_dir: &Path,
+) -> Result<PathBuf> {
+  let tokens = parse_template(template)
+    .map_err(|errors| VideoCompilerError::InvalidParameter(errors.join("; ")))?;
+  let context = TemplateContext::from_project(project, Local::now());
+  let extension = project.settings.output.format.extension();
+  let has_counter = tokens.contains(&TemplateToken::Counter);
+
+  for attempt in 1..=MAX_COLLISION_ATTEMPTS {
+    let counter = if has_counter { attempt } else { 1 };
+    let stem = sanitize_file_stem(&render_template(&tokens, &context, counter));
+    let suffix = if has_counter || attempt == 1 {
+      String::new()
+    } else {
+      format!("_({attempt})")
+    };
+
+    let file_name = fit_file_name(&stem, &suffix, extension, output_dir, MAX_PATH_LENGTH)?;
+    let candidate = output_dir.join(file_name);
+    if !candidate.exists() {
+      return Ok(candidate);
+    }
+  }
+
+  Err(VideoCompilerError::InvalidParameter(format!(
+    "No free output file name in {}",
+    output_dir.display()
+  )))
+}
+
+/// Определить путь вывода рендера: явный путь или шаблон в каталоге
+pub fn resolve_output_target(
+  project: &ProjectSchema,
+  output_path: Option<String>,
+  output_dir: Option<String>,
+  use_template: bool,
+) -> Result<PathBuf> {
+  if !use_template {
+    return output_path
+      .map(PathBuf::from)
+      .ok_or_else(|| VideoCompilerError::InvalidParameter("output_path is required".to_string()));
+  }
+
+  let output_dir = output_dir.ok_or_else(|| {
+    VideoCompilerError::InvalidParameter("output_dir is required with use_template".to_string())
+  })?;
+  let template = project
+    .settings
+    .export
+    .filename_template
+    .as_deref()
+    .unwrap_or(DEFAULT_FILENAME_TEMPLATE);
+
+  resolve_output_filename(project, template, Path::new(&output_dir))
+}
+
+/// Проверить шаблон и построить пример имени для проекта
+pub fn validate_template(template: &str, project: &ProjectSchema) -> FilenameTemplateValidation {
+  match parse_template(template) {
+    Ok(tokens) => {
+      let context = TemplateContext::from_project(project, Local::now());
+      let stem = sanitize_file_stem(&render_template(&tokens, &context, 1));
+      FilenameTemplateValidation {
+        valid: true,
+        errors: Vec::new(),
+        preview: Some(format!(
+          "{stem}.{}",
+          project.settings.output.format.extension()
+        )),
+      }
+    }
+    Err(errors) => FilenameTemplateValidation {
+      valid: false,
+      errors,
+      preview: None,
+    },
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use chrono::TimeZone;
+
+  fn context() -> TemplateContext {
+    TemplateContext {
+      project: "My Project".to_string(),
+      resolution: (1920, 1080),
+      fps: 30,
+      preset: "youtube".to_string(),
+      duration: 65.4,
+      now: Local.with_ymd_and_hms(2024, 6, 1, 14, 5, 9).unwrap(),
+    }
+  }
+
+  fn project(name: &str) -> ProjectSchema {
+    ProjectSchema::new(name.to_string())
+  }
+
+  #[test]
+  fn test_render_all_tokens() {
+    let tokens = parse_template(
+      "{project}_{date}_{date:%d.%m}_{time}_{resolution}_{fps}_{preset}_{duration}_{counter}",
+    )
+    .unwrap();
+
+    assert_eq!(
+      render_template(&tokens, &context(), 3),
+      "My Project_2024-06-01_01.06_14-05-09_1080p_30fps_youtube_1m05s_3"
+    );
+  }
+
+  #[test]
+  fn test_parse_reports_unknown_and_invalid_tokens() {
+    let errors = parse_template("{project}_{version}_{date:%Q}_{counter").unwrap_err();
+
+    assert_eq!(errors.len(), 3);
+    assert!(errors[0].contains("{version}"));
+    assert!(errors[1].contains("%Q"));
+    assert!(errors[2].contains("Unclosed"));
+  }
+
+  #[test]
+  fn test_sanitize_windows_illegal_characters() {
+    assert_eq!(
+      sanitize_file_stem_for("a<b>c:d\"e/f\\g|h?i*j", true),
+      "a_b_c_d_e_f_g_h_i_j"
+    );
+    assert_eq!(sanitize_file_stem_for("name. . ", true), "name");
+    assert_eq!(sanitize_file_stem_for("a?b", false), "a?b");
+    assert_eq!(sanitize_file_stem_for("a/b", false), "a_b");
+    assert_eq!(sanitize_file_stem_for("..", false), "untitled");
+  }
+
+  #[test]
+  fn test_sanitize_windows_reserved_names() {
+    assert_eq!(sanitize_file_stem_for("CON", true), "CON_");
+    assert_eq!(sanitize_file_stem_for("nul", true), "nul_");
+    assert_eq!(sanitize_file_stem_for("NUL.backup", true), "NUL_.backup");
+    assert_eq!(sanitize_file_stem_for("com1", true), "com1_");
+    assert_eq!(sanitize_file_stem_for("CONSOLE", true), "CONSOLE");
+    // Вне Windows эти имена допустимы
+    assert_eq!(sanitize_file_stem_for("CON", false), "CON");
+  }
+
+  #[test]
+  fn test_fit_file_name_respects_name_length() {
+    let stem = "я".repeat(200); // 400 байт
+    let name = fit_file_name(&stem, "_(2)", "mp4", Path::new("/tmp"), 4096).unwrap();
+
+    assert!(name.len() <= MAX_FILE_NAME_LENGTH);
+    assert!(name.ends_with("_(2).mp4"));
+  }
+
+  #[test]
+  fn test_fit_file_name_respects_path_length() {
+    let dir = PathBuf::from(format!("C:\\{}", "d".repeat(200)));
+    let name = fit_file_name(&"x".repeat(100), "", "mp4", &dir, 260).unwrap();
+    assert_eq!(dir.as_os_str().len() + 1 + name.len(), 260);
+
+    let too_deep = PathBuf::from(format!("C:\\{}", "d".repeat(300)));
+    assert!(fit_file_name("x", "", "mp4", &too_deep, 260).is_err());
+  }
+
+  #[test]
+  fn test_resolve_avoids_collisions_with_suffix() {
+    let dir = tempfile::tempdir().unwrap();
+    let project = project("Demo");
+
+    let first = resolve_output_filename(&project, "{project}", dir.path()).unwrap();
+    assert_eq!(first, dir.path().join("Demo.mp4"));
+    std::fs::write(&first, b"").unwrap();
+
+    let second = resolve_output_filename(&project, "{project}", dir.path()).unwrap();
+    assert_eq!(second, dir.path().join("Demo_(2).mp4"));
+  }
+
+  #[test]
+  fn test_resolve_increments_counter_token() {
+    let dir = tempfile::tempdir().unwrap();
+    let project = project("Demo");
+    std::fs::write(dir.path().join("Demo_v1.mp4"), b"").unwrap();
+    std::fs::write(dir.path().join("Demo_v2.mp4"), b"").unwrap();
+
+    let path = resolve_output_filename(&project, "{project}_v{counter}", dir.path()).unwrap();
+    assert_eq!(path, dir.path().join("Demo_v3.mp4"));
+  }
+
+  #[test]
+  fn test_resolve_output_target_modes() {
+    let project = project("Demo");
+
+    let explicit =
+      resolve_output_target(&project, Some("/out/video.mp4".to_string()), None, false).unwrap();
+    assert_eq!(explicit, PathBuf::from("/out/video.mp4"));
+    assert!(resolve_output_target(&project, None, None, false).is_err());
+    assert!(resolve_output_target(&project, None, None, true).is_err());
+
+    let dir = tempfile::tempdir().unwrap();
+    let templated = resolve_output_target(
+      &project,
+      None,
+      Some(dir.path().to_string_lossy().to_string()),
+      true,
+    )
+    .unwrap();
+    assert_eq!(templated.parent().unwrap(), dir.path());
+    assert!(templated
+      .file_name()
+      .unwrap()
+      .to_string_lossy()
+      .starts_with("Demo_"));
+  }
+
+  #[test]
+  fn test_validate_template_preview() {
+    let validation = validate_template("{project}_{fps}", &project("Demo"));
+    assert!(validation.valid);
+    assert_eq!(validation.preview.as_deref(), Some("Demo_30fps.mp4"));
+
+    let invalid = validate_template("{nope}", &project("Demo"));
+    assert!(!invalid.valid);
+    assert_eq!(invalid.errors, vec!["Unknown token: {nope}".to_string()]);
+  }
+}
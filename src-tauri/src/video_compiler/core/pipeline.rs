@@ -7,16 +7,39 @@ use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::process::Output;
 use std::sync::Arc;
 use std::time::{Duration, SystemTime};
 use tokio::sync::RwLock;
-
+use tokio_util::sync::CancellationToken;
+
+use crate::language_tauri::i18n::{has_message, t, LocalizedMessage};
+use crate::video_compiler::cache::RenderCache;
+use crate::video_compiler::core::gpu_memory::{GpuFallbackLadder, RenderDowngrade};
+use crate::video_compiler::core::render_priority::ProcessLimits;
+use crate::video_compiler::core::segment_cache::{
+  self, PlannedSegment, SegmentAction, SegmentCacheStats,
+};
+use crate::video_compiler::core::temp_storage;
 use crate::video_compiler::error::{Result, VideoCompilerError};
-use crate::video_compiler::ffmpeg_builder::FFmpegBuilder;
-use crate::video_compiler::progress::ProgressTracker;
-use crate::video_compiler::schema::{ClipSource, ProjectSchema};
+use crate::video_compiler::ffmpeg_builder::effects::keyframe_warnings;
+use crate::video_compiler::ffmpeg_builder::outputs::{
+  build_hls_master_playlist, hls_master_playlist_path, intermediate_codec_warnings,
+};
+use crate::video_compiler::ffmpeg_builder::templates::style_template_warnings;
+use crate::video_compiler::ffmpeg_builder::{subtitles::soft_subtitle_codec, FFmpegBuilder};
+use crate::video_compiler::progress::{ProgressTracker, RenditionProgress};
+use crate::video_compiler::schema::{ClipSource, MissingAssetPolicy, ProjectSchema};
+use crate::video_compiler::services::project_assets::check_project_assets;
+use crate::video_compiler::services::project_template::system_font_dirs;
+use crate::video_compiler::services::render_report::{
+  build_render_report, FfmpegReportProbe, StageTiming,
+};
 use crate::video_compiler::CompilerSettings;
 
+/// Сколько последних строк stderr FFmpeg сохраняется в ошибке кодирования
+const STDERR_TAIL_LINES: usize = 40;
+
 /// Основной конвейер обработки видео
 #[derive(Debug)]
 pub struct RenderPipeline {
@@ -42,7 +65,9 @@ impl RenderPipeline {
     settings: Arc<RwLock<CompilerSettings>>,
     output_path: PathBuf,
   ) -> Result<Self> {
-    let mut context = PipelineContext::new(project.clone(), output_path);
+    // Временные файлы задачи создаются в корне из настроек компилятора
+    let temp_root = settings.read().await.temp_directory.clone();
+    let mut context = PipelineContext::with_temp_root(project.clone(), output_path, temp_root);
     let ffmpeg_builder = FFmpegBuilder::new(project.clone());
 
     // Добавляем ffmpeg_builder и progress_tracker в контекст
@@ -74,6 +99,33 @@ impl RenderPipeline {
     Ok(())
   }
 
+  /// Подключить кэш рендеринга для переиспользования сегментов прошлых экспортов
+  pub fn with_render_cache(mut self, cache: Arc<RwLock<RenderCache>>) -> Self {
+    self.context.render_cache = Some(cache);
+    self
+  }
+
+  /// Запускать процессы кодирования с заданным приоритетом и числом потоков
+  pub fn with_process_limits(mut self, limits: ProcessLimits) -> Self {
+    self.context.ffmpeg_builder = self
+      .context
+      .ffmpeg_builder
+      .take()
+      .map(|builder| builder.with_process_limits(limits));
+    self
+  }
+
+  /// Использовать внешний токен отмены (например, токен задачи рендерера)
+  pub fn with_cancellation_token(mut self, token: CancellationToken) -> Self {
+    self.context.cancellation = token;
+    self
+  }
+
+  /// Токен отмены конвейера
+  pub fn cancellation_token(&self) -> CancellationToken {
+    self.context.cancellation.clone()
+  }
+
   /// Добавить этап в конвейер
   pub fn add_stage(&mut self, stage: Box<dyn PipelineStage>) {
     self.stages.push(stage);
@@ -85,11 +137,12 @@ impl RenderPipeline {
     log::info!("ID задачи: {job_id}");
     log::info!("Проект: {}", self.project.metadata.name);
     log::info!("Выходной файл: {:?}", self.context.output_path);
-    log::info!("Временная директория: {:?}", self.context.temp_dir);
     log::info!("Количество этапов: {}", self.stages.len());
 
-    // Устанавливаем ID текущей задачи в контекст
+    // Устанавливаем ID текущей задачи в контекст; файлы задачи лежат в ее поддиректории
     self.context.current_job_id = Some(job_id.to_string());
+    self.context.temp_dir = temp_storage::job_dir(&self.context.temp_root, job_id);
+    log::info!("Временная директория: {:?}", self.context.temp_dir);
 
     // Создаем временную директорию
     self.context.ensure_temp_dir().await?;
@@ -130,14 +183,15 @@ impl RenderPipeline {
         estimated_duration.as_secs_f64()
       );
 
-      // Проверяем отмену
-      if self.context.is_cancelled() {
-        log::warn!("Конвейер отменен пользователем");
-        return Err(VideoCompilerError::render(
-          job_id,
-          stage_name,
-          "Операция отменена пользователем".to_string(),
-        ));
+      // Между этапами проверяем отмену
+      if let Err(e) = self.context.checkpoint() {
+        log::warn!("Конвейер отменен перед этапом '{stage_name}'");
+        self.context.statistics.cancelled_stage = Some(stage_name.to_string());
+        if let Err(cleanup_err) = self.context.cleanup().await {
+          log::warn!("Не удалось очистить временные файлы: {cleanup_err}");
+          self.context.statistics.add_warning();
+        }
+        return Err(e);
       }
 
       // Обновляем прогресс на основе оценочной длительности
@@ -160,9 +214,18 @@ impl RenderPipeline {
       // Выполняем этап
       let start_time = SystemTime::now();
 
-      match stage.process(&mut self.context).await {
+      let result = stage.process(&mut self.context).await;
+
+      // Длительность записывается и для прерванного этапа
+      let duration = start_time.elapsed().unwrap_or(Duration::ZERO);
+      self
+        .context
+        .statistics
+        .stage_durations
+        .insert(stage_name.to_string(), duration);
+
+      match result {
         Ok(_) => {
-          let duration = start_time.elapsed().unwrap_or(Duration::ZERO);
           elapsed_duration += duration;
 
           log::info!(
@@ -176,6 +239,24 @@ impl RenderPipeline {
           // Для отслеживания прогресса используем frames_processed
           self.context.statistics.frames_processed += 1;
         }
+        Err(e) if self.context.is_cancelled() => {
+          log::warn!(
+            "Этап '{}' прерван отменой через {:.2}с",
+            stage_name,
+            duration.as_secs_f64()
+          );
+          self.context.statistics.cancelled_stage = Some(stage_name.to_string());
+
+          if let Err(cleanup_err) = self.context.cleanup().await {
+            log::warn!("Не удалось очистить временные файлы: {cleanup_err}");
+            self.context.statistics.add_warning();
+          }
+
+          return Err(match e {
+            VideoCompilerError::CancelledError(_) => e,
+            _ => cancelled_error(),
+          });
+        }
         Err(e) => {
           log::error!("✗ Ошибка на этапе '{stage_name}': {e}");
           log::error!("  Код ошибки: {}", e.error_code());
@@ -217,6 +298,10 @@ impl RenderPipeline {
       self.context.statistics.add_warning();
     }
 
+    if self.context.project.settings.export.render_report.enabled {
+      self.write_render_report(job_id).await;
+    }
+
     log::info!("=== Конвейер обработки завершен успешно ===");
     log::info!("Выходной файл: {:?}", self.context.output_path);
     log::info!(
@@ -234,13 +319,54 @@ impl RenderPipeline {
 
     self
       .progress_tracker
-      .update_progress(job_id, current_frame, stage.to_string(), None)
+      .update_progress(job_id, current_frame, stage_label(stage), None)
       .await
   }
 
+  /// Собрать отчет о рендере и сохранить его рядом с выходным файлом.
+  /// Ошибки отчета записываются в лог и не прерывают рендер.
+  async fn write_render_report(&mut self, job_id: &str) {
+    let ffmpeg_path = self
+      .context
+      .ffmpeg_builder
+      .as_ref()
+      .map(|builder| builder.settings().ffmpeg_path.clone())
+      .unwrap_or_else(|| "ffmpeg".to_string());
+    let stages = self
+      .stages
+      .iter()
+      .filter_map(|stage| {
+        let duration = self.context.statistics.stage_durations.get(stage.name())?;
+        Some(StageTiming {
+          name: stage.name().to_string(),
+          seconds: duration.as_secs_f64(),
+        })
+      })
+      .collect();
+
+    let report = build_render_report(
+      &FfmpegReportProbe::new(ffmpeg_path),
+      job_id,
+      &self.context.project,
+      &self.context.output_path,
+      stages,
+      self.context.warnings.clone(),
+    )
+    .await;
+
+    match report.save().await {
+      Ok(path) => log::info!("Отчет о рендере сохранен в {path:?}"),
+      Err(e) => {
+        log::warn!("Не удалось сохранить отчет о рендере: {e}");
+        self.context.statistics.add_warning();
+      }
+    }
+    self.progress_tracker.report_ready(job_id, report);
+  }
+
   /// Отменить выполнение конвейера
-  pub async fn cancel(&mut self) -> Result<()> {
-    self.context.cancelled = true;
+  pub async fn cancel(&self) -> Result<()> {
+    self.context.cancellation.cancel();
     log::info!("Конвейер отменен");
     Ok(())
   }
@@ -251,6 +377,17 @@ impl RenderPipeline {
   }
 }
 
+/// Название этапа для интерфейса на языке приложения. Этапы без
+/// перевода показываются под своим именем.
+fn stage_label(stage: &str) -> String {
+  let key = format!("progress.stage.{}", stage.to_lowercase());
+  if has_message(&key) {
+    t(&key, &[])
+  } else {
+    stage.to_string()
+  }
+}
+
 /// Контекст выполнения конвейера
 #[derive(Debug)]
 pub struct PipelineContext {
@@ -258,12 +395,14 @@ pub struct PipelineContext {
   pub project: ProjectSchema,
   /// Путь к выходному файлу
   pub output_path: PathBuf,
+  /// Корень временных файлов
+  pub temp_root: PathBuf,
   /// Временная директория
   pub temp_dir: PathBuf,
   /// Промежуточные файлы
   pub intermediate_files: HashMap<String, PathBuf>,
-  /// Флаг отмены
-  pub cancelled: bool,
+  /// Токен отмены, общий для всех этапов
+  pub cancellation: CancellationToken,
   /// Пользовательские данные
   pub user_data: HashMap<String, serde_json::Value>,
   /// Статистика выполнения
@@ -274,30 +413,49 @@ pub struct PipelineContext {
   pub progress_tracker: Option<Arc<ProgressTracker>>,
   /// ID текущей задачи рендеринга
   pub current_job_id: Option<String>,
+  /// Предупреждения для пользователя, не прерывающие рендер
+  pub warnings: Vec<String>,
+  /// Кэш рендеринга для сегментов прошлых экспортов
+  pub render_cache: Option<Arc<RwLock<RenderCache>>>,
 }
 
 impl PipelineContext {
-  /// Создать новый контекст
+  /// Создать новый контекст в текущем корне временных файлов
   pub fn new(project: ProjectSchema, output_path: PathBuf) -> Self {
-    let temp_dir = std::env::temp_dir()
-      .join("timeline-studio")
-      .join("pipeline")
-      .join(uuid::Uuid::new_v4().to_string());
+    Self::with_temp_root(project, output_path, temp_storage::temp_root())
+  }
+
+  /// Создать новый контекст в указанном корне временных файлов
+  pub fn with_temp_root(project: ProjectSchema, output_path: PathBuf, temp_root: PathBuf) -> Self {
+    let temp_dir = temp_storage::job_dir(&temp_root, &uuid::Uuid::new_v4().to_string());
 
     Self {
       project,
       output_path,
+      temp_root,
       temp_dir,
       intermediate_files: HashMap::new(),
-      cancelled: false,
+      cancellation: CancellationToken::new(),
       user_data: HashMap::new(),
       statistics: PipelineStatistics::default(),
       ffmpeg_builder: None,
       progress_tracker: None,
       current_job_id: None,
+      warnings: Vec::new(),
+      render_cache: None,
     }
   }
 
+  /// Добавить предупреждение. Повторы не дублируются.
+  pub fn add_warning(&mut self, message: String) {
+    if self.warnings.contains(&message) {
+      return;
+    }
+    log::warn!("{message}");
+    self.statistics.add_warning();
+    self.warnings.push(message);
+  }
+
   /// Добавить промежуточный файл
   pub fn add_intermediate_file(&mut self, key: String, path: PathBuf) {
     self.intermediate_files.insert(key, path);
@@ -328,7 +486,15 @@ impl PipelineContext {
 
   /// Проверить, отменено ли выполнение
   pub fn is_cancelled(&self) -> bool {
-    self.cancelled
+    self.cancellation.is_cancelled()
+  }
+
+  /// Точка проверки отмены: возвращает `CancelledError`, если токен сработал
+  pub fn checkpoint(&self) -> Result<()> {
+    if self.is_cancelled() {
+      return Err(cancelled_error());
+    }
+    Ok(())
   }
 
   /// Создать временную директорию
@@ -352,6 +518,26 @@ impl PipelineContext {
   }
 }
 
+/// Ошибка отмены рендеринга пользователем
+fn cancelled_error() -> VideoCompilerError {
+  VideoCompilerError::CancelledError("Рендеринг отменен пользователем".to_string())
+}
+
+/// Выполнить команду до ее завершения или до срабатывания токена отмены.
+///
+/// Процесс запускается с `kill_on_drop`, поэтому при отмене FFmpeg
+/// завершается вместе со сброшенным future ожидания.
+async fn output_until_cancelled(
+  command: &mut tokio::process::Command,
+  token: &CancellationToken,
+) -> Result<std::io::Result<Output>> {
+  command.kill_on_drop(true);
+  tokio::select! {
+    output = command.output() => Ok(output),
+    _ = token.cancelled() => Err(cancelled_error()),
+  }
+}
+
 /// Трейт для этапа конвейера
 #[async_trait]
 pub trait PipelineStage: Send + Sync + std::fmt::Debug {
@@ -386,6 +572,19 @@ impl ValidationStage {
   pub fn new() -> Self {
     Self
   }
+
+  /// Определить фильтры FFmpeg и заранее сообщить о заменах переходов
+  async fn check_transition_support(&self, context: &mut PipelineContext) {
+    let Some(builder) = context.ffmpeg_builder.take() else {
+      return;
+    };
+
+    let builder = builder.with_detected_capabilities().await;
+    for substitution in builder.transition_substitutions() {
+      context.add_warning(substitution.message());
+    }
+    context.ffmpeg_builder = Some(builder);
+  }
 }
 
 #[async_trait]
@@ -399,6 +598,60 @@ impl PipelineStage for ValidationStage {
       .validate()
       .map_err(VideoCompilerError::validation)?;
 
+    // Дорожку субтитров поддерживают не все контейнеры
+    if context.project.settings.export.subtitle_mode.muxes() {
+      soft_subtitle_codec(&context.project.settings.output.format)?;
+    }
+
+    // Переходы на фильтре gl без его поддержки заменяются на xfade
+    self.check_transition_support(context).await;
+    context.checkpoint()?;
+
+    // Неподдерживаемые элементы стильных шаблонов не пропадают молча
+    for warning in style_template_warnings(&context.project) {
+      context.add_warning(warning);
+    }
+
+    // Слишком длинные нарастания и затухания сокращаются до длительности клипа
+    let fade_warnings: Vec<String> = context
+      .project
+      .tracks
+      .iter()
+      .flat_map(|track| &track.clips)
+      .filter_map(|clip| clip.fade_warning())
+      .collect();
+    for warning in fade_warnings {
+      context.add_warning(warning);
+    }
+
+    // Ключевые кадры параметров без поддержки анимации в фильтре сводятся к первому значению
+    for warning in keyframe_warnings(&context.project) {
+      context.add_warning(warning);
+    }
+
+    // Промежуточные кодеки: большие файлы, битрейт и аппаратное ускорение не применяются
+    for warning in intermediate_codec_warnings(&context.project) {
+      context.add_warning(warning);
+    }
+
+    // Шрифты, LUT и другие ресурсы, которых нет на этой машине, проверяются до запуска FFmpeg
+    let assets = check_project_assets(&context.project, &system_font_dirs());
+    if !assets.is_empty() {
+      match context.project.settings.export.missing_assets {
+        MissingAssetPolicy::Fail => {
+          return Err(VideoCompilerError::validation(LocalizedMessage::new(
+            "validation.missing_assets",
+            &[("assets", assets.warnings().join("; "))],
+          )));
+        }
+        MissingAssetPolicy::Warn => {
+          for warning in assets.warnings() {
+            context.add_warning(warning);
+          }
+        }
+      }
+    }
+
     // Сохраняем информацию о валидации в user_data
     let mut validation_stats = serde_json::json!({
       "project_name": context.project.metadata.name,
@@ -410,8 +663,9 @@ impl PipelineStage for ValidationStage {
 
     let mut total_clips = 0;
 
-    // Проверка существования медиа файлов и их форматов
-    for track in &context.project.tracks {
+    // Проверка существования медиа файлов и их форматов, включая вложенные последовательности
+    let sequence_tracks = context.project.sequences.values().flat_map(|s| &s.tracks);
+    for track in context.project.tracks.iter().chain(sequence_tracks) {
       for clip in &track.clips {
         total_clips += 1;
 
@@ -550,13 +804,17 @@ impl PipelineStage for PreprocessingStage {
     // Анализ медиа файлов
     for track in &context.project.tracks {
       for clip in &track.clips {
+        context.checkpoint()?;
         if let ClipSource::File(path) = &clip.source {
-          self.analyze_media_file(std::path::Path::new(path)).await?;
+          self
+            .analyze_media_file(std::path::Path::new(path), &context.cancellation)
+            .await?;
         }
       }
     }
 
     // Подготовка промежуточных файлов
+    context.checkpoint()?;
     self.prepare_intermediate_files(context).await?;
 
     context.statistics.preprocessing_time = SystemTime::now();
@@ -575,7 +833,7 @@ impl PipelineStage for PreprocessingStage {
 
 impl PreprocessingStage {
   /// Анализ медиа файла через FFprobe
-  async fn analyze_media_file(&self, path: &Path) -> Result<()> {
+  async fn analyze_media_file(&self, path: &Path, cancellation: &CancellationToken) -> Result<()> {
     use tokio::process::Command;
 
     // Проверяем доступность файла
@@ -587,20 +845,20 @@ impl PreprocessingStage {
     }
 
     // Запускаем FFprobe для анализа
-    let output = Command::new("ffprobe")
-      .args([
-        "-v",
-        "error",
-        "-select_streams",
-        "v:0",
-        "-show_entries",
-        "stream=codec_name,width,height,r_frame_rate,duration",
-        "-of",
-        "json",
-        path.to_str().unwrap(),
-      ])
-      .output()
-      .await
+    let mut command = Command::new("ffprobe");
+    command.args([
+      "-v",
+      "error",
+      "-select_streams",
+      "v:0",
+      "-show_entries",
+      "stream=codec_name,width,height,r_frame_rate,duration",
+      "-of",
+      "json",
+      path.to_str().unwrap(),
+    ]);
+    let output = output_until_cancelled(&mut command, cancellation)
+      .await?
       .map_err(|e| {
         VideoCompilerError::ffmpeg(
           None,
@@ -725,17 +983,13 @@ impl PipelineStage for CompositionStage {
   async fn process(&self, context: &mut PipelineContext) -> Result<()> {
     log::info!("Начало композиции видео");
 
-    // Проверка отмены
-    if context.is_cancelled() {
-      return Err(VideoCompilerError::CancelledError(
-        "Композиция отменена".to_string(),
-      ));
-    }
+    context.checkpoint()?;
 
     // Композиция видео дорожек
     self.compose_video_tracks(context).await?;
 
     // Композиция аудио дорожек
+    context.checkpoint()?;
     self.compose_audio_tracks(context).await?;
 
     context.statistics.composition_time = SystemTime::now();
@@ -791,13 +1045,15 @@ impl CompositionStage {
     cmd.args(&full_command[1..]);
 
     // Выполняем команду FFmpeg
-    let output = cmd.output().await.map_err(|e| {
-      VideoCompilerError::ffmpeg(
-        None,
-        format!("Не удалось запустить FFmpeg для видео композиции: {e}"),
-        "video composition".to_string(),
-      )
-    })?;
+    let output = output_until_cancelled(&mut cmd, &context.cancellation)
+      .await?
+      .map_err(|e| {
+        VideoCompilerError::ffmpeg(
+          None,
+          format!("Не удалось запустить FFmpeg для видео композиции: {e}"),
+          "video composition".to_string(),
+        )
+      })?;
 
     if !output.status.success() {
       let error = String::from_utf8_lossy(&output.stderr);
@@ -888,10 +1144,10 @@ impl CompositionStage {
 
     // Выполняем команду FFmpeg
     log::info!("Запуск композиции аудио");
-    let output = Command::new(&ffmpeg_command[0])
-      .args(&ffmpeg_command[1..])
-      .output()
-      .await
+    let mut command = Command::new(&ffmpeg_command[0]);
+    command.args(&ffmpeg_command[1..]);
+    let output = output_until_cancelled(&mut command, &context.cancellation)
+      .await?
       .map_err(|e| {
         VideoCompilerError::ffmpeg(
           None,
@@ -990,12 +1246,7 @@ impl PipelineStage for EncodingStage {
   async fn process(&self, context: &mut PipelineContext) -> Result<()> {
     log::info!("Начало кодирования видео");
 
-    // Проверка отмены
-    if context.is_cancelled() {
-      return Err(VideoCompilerError::CancelledError(
-        "Кодирование отменено".to_string(),
-      ));
-    }
+    context.checkpoint()?;
 
     // Кодирование финального видео
     self.encode_final_video(context).await?;
@@ -1015,10 +1266,58 @@ impl PipelineStage for EncodingStage {
 }
 
 impl EncodingStage {
+  /// Рендер промежуточных файлов вложенных последовательностей
+  async fn render_nested_sequences(
+    &self,
+    ffmpeg_builder: &FFmpegBuilder,
+    context: &mut PipelineContext,
+  ) -> Result<Vec<PathBuf>> {
+    let nested = ffmpeg_builder.build_nested_sequence_commands().await?;
+    let mut files = Vec::with_capacity(nested.len());
+
+    for mut nested in nested {
+      context.checkpoint()?;
+
+      if let Some(parent) = nested.output_path.parent() {
+        tokio::fs::create_dir_all(parent)
+          .await
+          .map_err(|e| VideoCompilerError::IoError(e.to_string()))?;
+      }
+
+      log::info!(
+        "Рендер вложенной последовательности {} в {:?}",
+        nested.sequence_id,
+        nested.output_path
+      );
+      let output = output_until_cancelled(&mut nested.command, &context.cancellation)
+        .await?
+        .map_err(|e| {
+          VideoCompilerError::ffmpeg(
+            None,
+            format!("Не удалось запустить FFmpeg: {e}"),
+            "ffmpeg nested sequence".to_string(),
+          )
+        })?;
+      if !output.status.success() {
+        return Err(VideoCompilerError::ffmpeg(
+          output.status.code(),
+          String::from_utf8_lossy(&output.stderr).to_string(),
+          format!("ffmpeg nested sequence {}", nested.sequence_id),
+        ));
+      }
+
+      context.add_intermediate_file(
+        format!("sequence_{}", nested.sequence_id),
+        nested.output_path.clone(),
+      );
+      files.push(nested.output_path);
+    }
+
+    Ok(files)
+  }
+
   /// Кодирование финального видео
   async fn encode_final_video(&self, context: &mut PipelineContext) -> Result<()> {
-    use tokio::io::{AsyncBufReadExt, BufReader};
-
     log::info!("Начало кодирования в файл: {:?}", context.output_path);
 
     // Создаем родительскую директорию если не существует
@@ -1029,15 +1328,129 @@ impl EncodingStage {
     }
 
     // Получаем FFmpegBuilder из контекста
-    let ffmpeg_builder = context.ffmpeg_builder.as_ref().ok_or_else(|| {
+    let mut ffmpeg_builder = context.ffmpeg_builder.clone().ok_or_else(|| {
       VideoCompilerError::InternalError("FFmpegBuilder not found in context".to_string())
     })?;
 
+    // Повторный экспорт переиспользует неизменившиеся сегменты
+    if segment_cache::supports_segment_cache(&context.project) {
+      if let Some(cache) = context.render_cache.clone() {
+        return self
+          .encode_with_segment_cache(&ffmpeg_builder, &cache, context)
+          .await;
+      }
+    }
+
+    // Для дорожки субтитров готовим SRT во временной директории конвейера
+    if context.project.settings.export.subtitle_mode.muxes()
+      && !context.project.subtitles.is_empty()
+    {
+      context.ensure_temp_dir().await?;
+      let subtitles_path = context.temp_dir.join("subtitles.srt");
+      tokio::fs::write(
+        &subtitles_path,
+        crate::subtitles::format_srt(&context.project.subtitles),
+      )
+      .await
+      .map_err(|e| VideoCompilerError::IoError(e.to_string()))?;
+      context.add_intermediate_file("subtitles".to_string(), subtitles_path.clone());
+      ffmpeg_builder = ffmpeg_builder.with_soft_subtitles(subtitles_path);
+    }
+
+    // Вложенные последовательности рендерятся до основной команды
+    let nested_files = self
+      .render_nested_sequences(&ffmpeg_builder, context)
+      .await?;
+
+    // Варианты многовариантного экспорта отображаются в прогрессе задачи
+    self.register_renditions(context).await;
+
+    // Пути стемов и вариантов строятся от исходного пути вывода
+    let stems = ffmpeg_builder.stem_outputs(&context.output_path)?;
+    let export = &context.project.settings.export;
+    let mut output_files: Vec<PathBuf> = if export.renditions.is_empty() {
+      vec![context.output_path.clone()]
+    } else {
+      export
+        .renditions
+        .iter()
+        .map(|rendition| rendition.output_path(&context.output_path, export.generate_hls))
+        .collect()
+    };
+
+    // При нехватке видеопамяти кодирование повторяется в более простом режиме
+    let mut ladder = GpuFallbackLadder::new();
+    let result = loop {
+      match self.run_final_encode(&ffmpeg_builder, context).await {
+        Err(error) => match ladder.next_step(&ffmpeg_builder, &error) {
+          Some(downgrade) => {
+            ffmpeg_builder = ffmpeg_builder.with_downgrade(downgrade);
+            self.record_downgrade(context, downgrade).await;
+          }
+          None => break Err(error),
+        },
+        result => break result,
+      }
+    };
+
+    for path in &nested_files {
+      tokio::fs::remove_file(path).await.ok();
+    }
+
+    if let Err(error) = result {
+      return Err(Self::encoding_error(context, error));
+    }
+
+    if context.project.settings.export.renditions.is_empty() {
+      // Проверяем что файл создан
+      if !context.output_path.exists() {
+        return Err(VideoCompilerError::render(
+          "encoding",
+          "output_missing",
+          "Выходной файл не был создан",
+        ));
+      }
+    } else {
+      self.finish_renditions(context).await?;
+    }
+
+    for stem in &stems {
+      if !stem.output_path.exists() {
+        return Err(VideoCompilerError::render(
+          "encoding",
+          "output_missing",
+          format!("Стем трека '{}' не был создан", stem.track_id),
+        ));
+      }
+    }
+    output_files.extend(stems.into_iter().map(|stem| stem.output_path));
+    self.register_output_files(context, output_files).await;
+
+    log::info!("Кодирование завершено успешно");
+    Ok(())
+  }
+
+  /// Запустить финальную команду FFmpeg и дождаться ее завершения.
+  ///
+  /// Ошибка FFmpeg содержит последние строки stderr, по которым
+  /// распознается нехватка видеопамяти.
+  async fn run_final_encode(
+    &self,
+    ffmpeg_builder: &FFmpegBuilder,
+    context: &mut PipelineContext,
+  ) -> Result<()> {
+    use std::collections::VecDeque;
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
     // Используем FFmpegBuilder для создания финальной команды
     log::info!("Создание финальной команды кодирования с FFmpegBuilder");
-    let mut cmd = ffmpeg_builder
-      .build_render_command(&context.output_path)
+    let render = ffmpeg_builder
+      .build_render_command_with_warnings(&context.output_path)
       .await?;
+    for substitution in &render.warnings {
+      context.add_warning(substitution.message());
+    }
+    let mut cmd = render.command;
 
     log::debug!("Финальная FFmpeg команда создана с FFmpegBuilder");
 
@@ -1053,30 +1466,47 @@ impl EncodingStage {
         )
       })?;
 
-    // Читаем stderr для прогресса
+    // Читаем stderr для прогресса, остальные строки сохраняем для диагностики
+    let cancellation = context.cancellation.clone();
+    let mut stderr_tail = VecDeque::with_capacity(STDERR_TAIL_LINES);
     if let Some(stderr) = child.stderr.take() {
       let reader = BufReader::new(stderr);
       let mut lines = reader.lines();
 
-      while let Some(line) = lines.next_line().await.ok().flatten() {
+      loop {
+        let line = tokio::select! {
+          line = lines.next_line() => line.ok().flatten(),
+          _ = cancellation.cancelled() => {
+            child.kill().await.ok();
+            return Err(cancelled_error());
+          }
+        };
+        let Some(line) = line else {
+          break;
+        };
+        log::trace!("FFmpeg: {line}");
+
         // Парсим прогресс из вывода FFmpeg
         if line.contains("frame=") {
           self.parse_ffmpeg_progress(&line, context).await;
-        }
-        log::trace!("FFmpeg: {line}");
-
-        // Проверяем отмену
-        if context.is_cancelled() {
-          child.kill().await.ok();
-          return Err(VideoCompilerError::CancelledError(
-            "Кодирование отменено пользователем".to_string(),
-          ));
+        } else {
+          if stderr_tail.len() == STDERR_TAIL_LINES {
+            stderr_tail.pop_front();
+          }
+          stderr_tail.push_back(line);
         }
       }
     }
 
     // Ждем завершения процесса
-    let status = child.wait().await.map_err(|e| {
+    let status = tokio::select! {
+      status = child.wait() => status,
+      _ = cancellation.cancelled() => {
+        child.kill().await.ok();
+        return Err(cancelled_error());
+      }
+    };
+    let status = status.map_err(|e| {
       VideoCompilerError::ffmpeg(
         None,
         format!("Ошибка ожидания FFmpeg: {e}"),
@@ -1085,34 +1515,177 @@ impl EncodingStage {
     })?;
 
     if !status.success() {
-      let error = VideoCompilerError::ffmpeg(
+      let stderr = if stderr_tail.is_empty() {
+        "FFmpeg завершился с ошибкой".to_string()
+      } else {
+        Vec::from(stderr_tail).join("\n")
+      };
+      return Err(VideoCompilerError::ffmpeg(
         status.code(),
-        "FFmpeg завершился с ошибкой".to_string(),
+        stderr,
         "ffmpeg encoding".to_string(),
-      );
+      ));
+    }
 
-      // Проверяем, была ли это ошибка GPU
-      // FFmpeg обычно возвращает код 1 для общих ошибок
-      // Анализируем stderr для определения типа ошибки
-      if context.project.settings.export.hardware_acceleration {
-        // Если использовалось GPU ускорение и произошла ошибка,
-        // возможно это ошибка GPU
-        log::error!(
-          "Возможная ошибка GPU при кодировании, код выхода: {:?}",
-          status.code()
-        );
+    Ok(())
+  }
+
+  /// Записать понижение режима кодирования в контекст и задачу рендеринга
+  async fn record_downgrade(&self, context: &mut PipelineContext, downgrade: RenderDowngrade) {
+    context.add_warning(downgrade.message());
+    if downgrade == RenderDowngrade::CpuEncoder {
+      // Дальнейшие ошибки кодирования уже не связаны с GPU
+      context.project.settings.export.hardware_acceleration = false;
+    }
 
-        // Создаем GPU ошибку для автоматического fallback
-        return Err(VideoCompilerError::gpu(format!(
-          "FFmpeg GPU encoding failed with exit code: {:?}",
-          status.code()
-        )));
+    if let (Some(progress_tracker), Some(job_id)) = (
+      context.progress_tracker.as_ref(),
+      context.current_job_id.as_ref(),
+    ) {
+      if let Err(e) = progress_tracker.record_downgrade(job_id, downgrade).await {
+        log::warn!("Не удалось записать понижение режима кодирования: {e}");
       }
+    }
+  }
 
-      return Err(error);
+  /// Итоговая ошибка кодирования.
+  ///
+  /// При включенном аппаратном ускорении завершение FFmpeg с ошибкой
+  /// считается ошибкой GPU, чтобы рендерер повторил задачу на CPU.
+  fn encoding_error(context: &PipelineContext, error: VideoCompilerError) -> VideoCompilerError {
+    match error {
+      VideoCompilerError::FFmpegError {
+        exit_code: Some(code),
+        stderr,
+        ..
+      } if context.project.settings.export.hardware_acceleration => {
+        log::error!("Возможная ошибка GPU при кодировании, код выхода: {code}");
+        VideoCompilerError::gpu(format!(
+          "FFmpeg GPU encoding failed with exit code: {code}\n{stderr}"
+        ))
+      }
+      error => error,
+    }
+  }
+
+  /// Посегментное кодирование с переиспользованием сегментов прошлых экспортов.
+  ///
+  /// Новые сегменты кодируются итоговыми настройками и сохраняются в кэш,
+  /// затем все сегменты склеиваются concat demuxer'ом без перекодирования.
+  async fn encode_with_segment_cache(
+    &self,
+    ffmpeg_builder: &FFmpegBuilder,
+    cache: &Arc<RwLock<RenderCache>>,
+    context: &mut PipelineContext,
+  ) -> Result<()> {
+    for substitution in ffmpeg_builder.transition_substitutions() {
+      context.add_warning(substitution.message());
+    }
+
+    let segments = segment_cache::plan_segments(&context.project);
+    let plan = {
+      let mut cache = cache.write().await;
+      segment_cache::resolve_segments(&mut cache, segments).await
+    };
+    let stats = SegmentCacheStats::from_plan(&plan);
+    log::info!(
+      "Кэш сегментов: переиспользовано {} из {} ({:.0}%)",
+      stats.reused_segments,
+      stats.total_segments,
+      stats.reuse_ratio * 100.0
+    );
+
+    let segment_dir = segment_cache::segment_cache_dir(&context.temp_root);
+    tokio::fs::create_dir_all(&segment_dir)
+      .await
+      .map_err(|e| VideoCompilerError::IoError(e.to_string()))?;
+    let extension = context
+      .project
+      .settings
+      .output
+      .format
+      .extension()
+      .to_string();
+    let fps = context.project.timeline.fps as f64;
+
+    // Понижение режима после нехватки видеопамяти сохраняется для следующих сегментов
+    let mut ffmpeg_builder = ffmpeg_builder.clone();
+    let mut ladder = GpuFallbackLadder::new();
+
+    let mut files = Vec::with_capacity(plan.len());
+    for planned in &plan {
+      context.checkpoint()?;
+
+      let path = match &planned.action {
+        SegmentAction::Reuse(path) => path.clone(),
+        SegmentAction::Encode => {
+          let path = segment_cache::segment_output_path(&segment_dir, &planned.segment, &extension);
+          loop {
+            let encoded = self
+              .encode_segment(&ffmpeg_builder, planned, &path, &context.cancellation)
+              .await;
+            match encoded {
+              Ok(()) => break,
+              Err(error) => match ladder.next_step(&ffmpeg_builder, &error) {
+                Some(downgrade) => {
+                  ffmpeg_builder = ffmpeg_builder.with_downgrade(downgrade);
+                  self.record_downgrade(context, downgrade).await;
+                }
+                None => return Err(Self::encoding_error(context, error)),
+              },
+            }
+          }
+          let mut cache = cache.write().await;
+          segment_cache::store_segment(&mut cache, &planned.segment, &path).await?;
+          path
+        }
+      };
+      files.push(path);
+
+      if let (Some(progress_tracker), Some(job_id)) = (
+        context.progress_tracker.as_ref(),
+        context.current_job_id.as_ref(),
+      ) {
+        let message = format!("Сегмент {}/{}", planned.segment.index + 1, plan.len());
+        if let Err(e) = progress_tracker
+          .update_progress(
+            job_id,
+            (planned.segment.end_time * fps) as u64,
+            "Encoding".to_string(),
+            Some(message),
+          )
+          .await
+        {
+          log::warn!("Не удалось обновить прогресс: {e}");
+        }
+      }
+    }
+
+    context.ensure_temp_dir().await?;
+    let list_path = context.temp_dir.join("segments.txt");
+    tokio::fs::write(&list_path, segment_cache::build_concat_list(&files))
+      .await
+      .map_err(|e| VideoCompilerError::IoError(e.to_string()))?;
+    context.add_intermediate_file("segments".to_string(), list_path.clone());
+
+    let mut command = ffmpeg_builder.build_concat_command(&list_path, &context.output_path);
+    let output = output_until_cancelled(&mut command, &context.cancellation)
+      .await?
+      .map_err(|e| {
+        VideoCompilerError::ffmpeg(
+          None,
+          format!("Не удалось запустить FFmpeg: {e}"),
+          "ffmpeg concat".to_string(),
+        )
+      })?;
+    if !output.status.success() {
+      return Err(VideoCompilerError::ffmpeg(
+        output.status.code(),
+        String::from_utf8_lossy(&output.stderr).to_string(),
+        "ffmpeg concat".to_string(),
+      ));
     }
 
-    // Проверяем что файл создан
     if !context.output_path.exists() {
       return Err(VideoCompilerError::render(
         "encoding",
@@ -1121,10 +1694,153 @@ impl EncodingStage {
       ));
     }
 
+    context.statistics.segment_cache = Some(stats);
     log::info!("Кодирование завершено успешно");
     Ok(())
   }
 
+  /// Закодировать один сегмент экспорта
+  async fn encode_segment(
+    &self,
+    ffmpeg_builder: &FFmpegBuilder,
+    planned: &PlannedSegment,
+    output_path: &Path,
+    cancellation: &CancellationToken,
+  ) -> Result<()> {
+    let segment = &planned.segment;
+    log::info!(
+      "Кодирование сегмента {} ({:.2}-{:.2}с) в {:?}",
+      segment.index,
+      segment.start_time,
+      segment.end_time,
+      output_path
+    );
+
+    let mut command = ffmpeg_builder
+      .build_export_segment_command(segment.start_time, segment.end_time, output_path)
+      .await?;
+    let output = match output_until_cancelled(&mut command, cancellation).await {
+      Ok(output) => output,
+      Err(e) => {
+        // Прерванный сегмент не должен попасть в кэш
+        tokio::fs::remove_file(output_path).await.ok();
+        return Err(e);
+      }
+    }
+    .map_err(|e| {
+      VideoCompilerError::ffmpeg(
+        None,
+        format!("Не удалось запустить FFmpeg: {e}"),
+        "ffmpeg segment".to_string(),
+      )
+    })?;
+
+    if !output.status.success() {
+      // Недописанный сегмент не должен попасть в кэш
+      tokio::fs::remove_file(output_path).await.ok();
+
+      return Err(VideoCompilerError::ffmpeg(
+        output.status.code(),
+        String::from_utf8_lossy(&output.stderr).to_string(),
+        format!("ffmpeg segment {}", segment.index),
+      ));
+    }
+
+    Ok(())
+  }
+
+  /// Зарегистрировать варианты экспорта в трекере прогресса
+  async fn register_renditions(&self, context: &PipelineContext) {
+    let export = &context.project.settings.export;
+    if export.renditions.is_empty() {
+      return;
+    }
+
+    let (Some(progress_tracker), Some(job_id)) = (
+      context.progress_tracker.as_ref(),
+      context.current_job_id.as_ref(),
+    ) else {
+      return;
+    };
+
+    let renditions = export
+      .renditions
+      .iter()
+      .map(|rendition| RenditionProgress {
+        suffix: rendition.suffix.clone(),
+        output_path: rendition
+          .output_path(&context.output_path, export.generate_hls)
+          .to_string_lossy()
+          .into_owned(),
+        percentage: 0.0,
+      })
+      .collect();
+
+    if let Err(e) = progress_tracker
+      .set_job_renditions(job_id, renditions)
+      .await
+    {
+      log::warn!("Не удалось зарегистрировать варианты экспорта: {e}");
+    }
+  }
+
+  /// Записать в задачу все созданные файлы (основной вывод, варианты, стемы)
+  async fn register_output_files(&self, context: &PipelineContext, output_files: Vec<PathBuf>) {
+    let (Some(progress_tracker), Some(job_id)) = (
+      context.progress_tracker.as_ref(),
+      context.current_job_id.as_ref(),
+    ) else {
+      return;
+    };
+
+    let output_files = output_files
+      .iter()
+      .map(|path| path.to_string_lossy().into_owned())
+      .collect();
+    if let Err(e) = progress_tracker
+      .set_job_output_files(job_id, output_files)
+      .await
+    {
+      log::warn!("Не удалось записать список файлов задачи: {e}");
+    }
+  }
+
+  /// Проверить файлы вариантов и при необходимости записать мастер-плейлист HLS.
+  ///
+  /// Основным результатом задачи становится мастер-плейлист (HLS)
+  /// или файл первого варианта.
+  async fn finish_renditions(&self, context: &mut PipelineContext) -> Result<()> {
+    let export = &context.project.settings.export;
+
+    for rendition in &export.renditions {
+      let path = rendition.output_path(&context.output_path, export.generate_hls);
+      if !path.exists() {
+        return Err(VideoCompilerError::render(
+          "encoding",
+          "output_missing",
+          format!("Файл варианта '{}' не был создан", rendition.suffix),
+        ));
+      }
+    }
+
+    let primary_output = if export.generate_hls {
+      let master_path = hls_master_playlist_path(&context.output_path);
+      tokio::fs::write(
+        &master_path,
+        build_hls_master_playlist(&context.project, &context.output_path),
+      )
+      .await
+      .map_err(|e| VideoCompilerError::IoError(e.to_string()))?;
+      log::info!("Мастер-плейлист HLS создан: {master_path:?}");
+      master_path
+    } else {
+      export.renditions[0].output_path(&context.output_path, false)
+    };
+
+    context.output_path = primary_output;
+    Ok(())
+  }
+
   /// Парсинг прогресса из вывода FFmpeg с использованием ProgressTracker
   async fn parse_ffmpeg_progress(&self, line: &str, context: &mut PipelineContext) {
     // Используем парсер ProgressTracker для получения детальной информации
@@ -1202,6 +1918,7 @@ impl FinalizationStage {
 impl PipelineStage for FinalizationStage {
   async fn process(&self, context: &mut PipelineContext) -> Result<()> {
     log::info!("Начало финализации");
+    context.checkpoint()?;
 
     // Проверка выходного файла
     if !context.output_path.exists() {
@@ -1224,8 +1941,11 @@ impl PipelineStage for FinalizationStage {
       file_size / 1_048_576
     );
 
-    // Добавляем метаданные к файлу
-    self.add_metadata(context).await?;
+    // Добавляем метаданные к файлу (варианты получают их при кодировании)
+    context.checkpoint()?;
+    if context.project.settings.export.renditions.is_empty() {
+      self.add_metadata(context).await?;
+    }
 
     // Сохраняем статистику
     self.save_statistics(context).await?;
@@ -1284,17 +2004,22 @@ impl FinalizationStage {
       &tmp_file,
     ];
 
-    let output = Command::new("ffmpeg")
-      .args(&metadata_args)
-      .output()
-      .await
-      .map_err(|e| {
-        VideoCompilerError::ffmpeg(
-          None,
-          format!("Не удалось добавить метаданные: {e}"),
-          "add metadata".to_string(),
-        )
-      })?;
+    let mut command = Command::new("ffmpeg");
+    command.args(&metadata_args);
+    let output = match output_until_cancelled(&mut command, &context.cancellation).await {
+      Ok(output) => output,
+      Err(e) => {
+        tokio::fs::remove_file(&tmp_file).await.ok();
+        return Err(e);
+      }
+    }
+    .map_err(|e| {
+      VideoCompilerError::ffmpeg(
+        None,
+        format!("Не удалось добавить метаданные: {e}"),
+        "add metadata".to_string(),
+      )
+    })?;
 
     if output.status.success() {
       // Заменяем оригинальный файл
@@ -1322,6 +2047,7 @@ impl FinalizationStage {
       "memory_used": context.statistics.memory_used,
       "error_count": context.statistics.error_count,
       "warning_count": context.statistics.warning_count,
+      "warnings": context.warnings,
       "render_date": chrono::Utc::now().to_rfc3339(),
       "timeline_studio_version": context.project.version,
     });
@@ -1360,6 +2086,15 @@ pub struct PipelineStatistics {
   pub error_count: u32,
   /// Количество предупреждений
   pub warning_count: u32,
+  /// Переиспользование сегментов (если экспорт шел посегментно)
+  #[serde(default)]
+  pub segment_cache: Option<SegmentCacheStats>,
+  /// Длительности выполненных этапов, включая прерванный отменой
+  #[serde(default)]
+  pub stage_durations: HashMap<String, Duration>,
+  /// Этап, активный в момент отмены
+  #[serde(default)]
+  pub cancelled_stage: Option<String>,
 }
 
 impl Default for PipelineStatistics {
@@ -1375,6 +2110,9 @@ impl Default for PipelineStatistics {
       memory_used: 0,
       error_count: 0,
       warning_count: 0,
+      segment_cache: None,
+      stage_durations: HashMap::new(),
+      cancelled_stage: None,
     }
   }
 }
@@ -1430,9 +2168,23 @@ mod tests {
       crop: None,
       transform: None,
       audio_track_index: None,
+      audio_stream_index: None,
+      source_rotation: 0,
+      color_label: None,
+      notes: None,
+      tags: Vec::new(),
+      fade_in: None,
+      fade_out: None,
+      link_group_id: None,
+      source_timecode: None,
+      volume: 1.0,
+      external_audio: None,
+      locked: false,
       properties: ClipProperties {
         notes: None,
         tags: Vec::new(),
+        fade_in: None,
+        fade_out: None,
         custom_metadata: HashMap::new(),
       },
     }
@@ -1685,6 +2437,62 @@ mod tests {
     assert_eq!(stats.warning_count, 1);
   }
 
+  #[test]
+  fn test_context_warnings_are_deduplicated() {
+    let mut context = PipelineContext::new(
+      ProjectSchema::new("Test".to_string()),
+      PathBuf::from("/tmp/output.mp4"),
+    );
+
+    context.add_warning("Переход заменен".to_string());
+    context.add_warning("Переход заменен".to_string());
+
+    assert_eq!(context.warnings, vec!["Переход заменен".to_string()]);
+    assert_eq!(context.statistics.warning_count, 1);
+  }
+
+  #[test]
+  fn test_stage_label_uses_message_catalog() {
+    assert_eq!(stage_label("Encoding"), t("progress.stage.encoding", &[]));
+    assert_eq!(stage_label("Completed"), t("progress.stage.completed", &[]));
+    // Этап без перевода показывается под своим именем
+    assert_eq!(stage_label("CustomStage"), "CustomStage");
+  }
+
+  #[tokio::test]
+  async fn test_validation_stage_missing_assets_policy() {
+    use crate::video_compiler::schema::{Effect, EffectParameter, EffectType};
+
+    let mut project = ProjectSchema::new("Shared".to_string());
+    let mut effect = Effect::new(EffectType::Vintage, "Grade".to_string());
+    effect.parameters.insert(
+      "lut_file".to_string(),
+      EffectParameter::FilePath(PathBuf::from("/nonexistent/grade.cube")),
+    );
+    project.effects.push(effect);
+
+    let mut context = PipelineContext::new(project.clone(), PathBuf::from("/tmp/output.mp4"));
+    ValidationStage::new().process(&mut context).await.unwrap();
+    assert!(context
+      .warnings
+      .iter()
+      .any(|warning| warning.contains("/nonexistent/grade.cube")));
+
+    project.settings.export.missing_assets = MissingAssetPolicy::Fail;
+    let mut context = PipelineContext::new(project, PathBuf::from("/tmp/output.mp4"));
+    let error = ValidationStage::new()
+      .process(&mut context)
+      .await
+      .unwrap_err();
+    match error {
+      VideoCompilerError::ValidationError(message) => {
+        assert_eq!(message.key(), Some("validation.missing_assets"));
+        assert!(message.contains("/nonexistent/grade.cube"));
+      }
+      other => panic!("Ожидалась ValidationError, получено {other:?}"),
+    }
+  }
+
   #[tokio::test]
   async fn test_pipeline_statistics_duration() {
     let start_time = SystemTime::now();
@@ -1753,6 +2561,33 @@ mod tests {
     assert!(temp_dir_str.contains("pipeline"));
   }
 
+  #[tokio::test]
+  async fn test_pipeline_uses_configured_temp_directory() {
+    let temp_dir = TempDir::new().unwrap();
+    let temp_root = temp_dir.path().join("scratch");
+    let settings = Arc::new(RwLock::new(CompilerSettings {
+      temp_directory: temp_root.clone(),
+      ..CompilerSettings::default()
+    }));
+    let (tx, _rx) = mpsc::unbounded_channel::<ProgressUpdate>();
+    let mut pipeline = RenderPipeline::new(
+      ProjectSchema::new("Test".to_string()),
+      Arc::new(ProgressTracker::new(tx)),
+      settings,
+      temp_dir.path().join("output.mp4"),
+    )
+    .await
+    .unwrap();
+    assert!(pipeline.context.temp_dir.starts_with(&temp_root));
+
+    // Результат не важен: директория задачи назначается до запуска этапов
+    let _ = pipeline.execute("job-42").await;
+    assert_eq!(
+      pipeline.context.temp_dir,
+      temp_storage::job_dir(&temp_root, "job-42")
+    );
+  }
+
   #[tokio::test]
   async fn test_pipeline_progress_update() {
     let pipeline = create_test_pipeline().await;
@@ -1949,6 +2784,124 @@ mod tests {
     assert!(result.is_err());
   }
 
+  #[tokio::test]
+  async fn test_context_checkpoint() {
+    let project = ProjectSchema::new("Test".to_string());
+    let context = PipelineContext::new(project, PathBuf::from("/tmp/test.mp4"));
+    assert!(context.checkpoint().is_ok());
+
+    context.cancellation.cancel();
+    assert!(context.is_cancelled());
+    assert!(matches!(
+      context.checkpoint(),
+      Err(VideoCompilerError::CancelledError(_))
+    ));
+  }
+
+  #[tokio::test]
+  async fn test_cancel_between_stages_records_statistics() {
+    // Этап отменяет конвейер и завершается успешно
+    #[derive(Debug)]
+    struct CancellingStage;
+
+    #[async_trait]
+    impl PipelineStage for CancellingStage {
+      async fn process(&self, context: &mut PipelineContext) -> Result<()> {
+        context.cancellation.cancel();
+        Ok(())
+      }
+
+      fn name(&self) -> &str {
+        "Cancelling"
+      }
+    }
+
+    #[derive(Debug)]
+    struct UnreachableStage;
+
+    #[async_trait]
+    impl PipelineStage for UnreachableStage {
+      async fn process(&self, _context: &mut PipelineContext) -> Result<()> {
+        panic!("Этап после отмены не должен выполняться");
+      }
+
+      fn name(&self) -> &str {
+        "Unreachable"
+      }
+    }
+
+    let mut pipeline = create_test_pipeline().await;
+    pipeline.stages.clear();
+    pipeline.add_stage(Box::new(CancellingStage));
+    pipeline.add_stage(Box::new(UnreachableStage));
+
+    let result = pipeline.execute("cancel_between_stages").await;
+    assert!(matches!(result, Err(VideoCompilerError::CancelledError(_))));
+
+    let stats = pipeline.get_statistics();
+    assert!(stats.stage_durations.contains_key("Cancelling"));
+    assert!(!stats.stage_durations.contains_key("Unreachable"));
+    assert_eq!(stats.cancelled_stage.as_deref(), Some("Unreachable"));
+    assert_eq!(stats.error_count, 0);
+  }
+
+  #[tokio::test]
+  async fn test_cancel_inside_stage_records_partial_duration() {
+    // Долгий этап ждет отмены в точке проверки
+    #[derive(Debug)]
+    struct SlowStage;
+
+    #[async_trait]
+    impl PipelineStage for SlowStage {
+      async fn process(&self, context: &mut PipelineContext) -> Result<()> {
+        loop {
+          context.checkpoint()?;
+          tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+      }
+
+      fn name(&self) -> &str {
+        "Slow"
+      }
+    }
+
+    let mut pipeline = create_test_pipeline().await;
+    pipeline.stages.clear();
+    pipeline.add_stage(Box::new(SlowStage));
+
+    let token = pipeline.cancellation_token();
+    tokio::spawn(async move {
+      tokio::time::sleep(Duration::from_millis(30)).await;
+      token.cancel();
+    });
+
+    let result = pipeline.execute("cancel_inside_stage").await;
+    assert!(matches!(result, Err(VideoCompilerError::CancelledError(_))));
+
+    let stats = pipeline.get_statistics();
+    assert!(stats.stage_durations["Slow"] >= Duration::from_millis(30));
+    assert_eq!(stats.cancelled_stage.as_deref(), Some("Slow"));
+  }
+
+  #[cfg(unix)]
+  #[tokio::test]
+  async fn test_output_until_cancelled_stops_process() {
+    let token = CancellationToken::new();
+    let canceller = token.clone();
+    tokio::spawn(async move {
+      tokio::time::sleep(Duration::from_millis(50)).await;
+      canceller.cancel();
+    });
+
+    let started = std::time::Instant::now();
+    let mut command = tokio::process::Command::new("sleep");
+    command.arg("10");
+    let result = output_until_cancelled(&mut command, &token).await;
+
+    assert!(matches!(result, Err(VideoCompilerError::CancelledError(_))));
+    assert!(started.elapsed() < Duration::from_secs(5));
+  }
+
   #[tokio::test]
   async fn test_multiple_intermediate_files() {
     let project = ProjectSchema::new("Test".to_string());
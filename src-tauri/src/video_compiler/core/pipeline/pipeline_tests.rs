@@ -6,7 +6,8 @@ use super::*;
 use crate::video_compiler::progress::ProgressUpdate;
 use crate::video_compiler::schema::{
   AspectRatio, Clip, ClipProperties, ClipSource, ColorCorrection, CropSettings, ExportSettings,
-  OutputFormat, ProjectSchema, Timeline, Track, TrackType, TransformSettings,
+  HdrMode, MissingAssetPolicy, OutputFormat, ProjectSchema, RenderReportSettings, StemFormat,
+  SubtitleMode, Timeline, Track, TrackType, TransformSettings,
 };
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -26,6 +27,7 @@ fn create_complete_project_schema(name: &str) -> ProjectSchema {
     resolution: (1920, 1080),
     sample_rate: 48000,
     aspect_ratio: AspectRatio::default(),
+    markers: Vec::new(),
   };
 
   // Настройка экспорта
@@ -51,6 +53,27 @@ fn create_complete_project_schema(name: &str) -> ProjectSchema {
     normalize_audio: None,
     audio_target: None,
     audio_peak: None,
+    subtitle_mode: SubtitleMode::Burn,
+    subtitle_language: None,
+    renditions: Vec::new(),
+    generate_hls: false,
+    audio_bit_depth: None,
+    color_primaries: None,
+    transfer_characteristics: None,
+    matrix_coefficients: None,
+    hdr_mode: HdrMode::None,
+    hdr_metadata: None,
+    render_nested_as_intermediate: false,
+    filename_template: None,
+    export_preset: None,
+    segment_cache: None,
+    watermark: None,
+    timecode_burn_in: None,
+    write_timecode_track: false,
+    export_stems: false,
+    stems_format: StemFormat::Wav,
+    missing_assets: MissingAssetPolicy::Warn,
+    render_report: RenderReportSettings::default(),
   };
 
   project
@@ -96,8 +119,21 @@ fn create_detailed_clip(id: &str, path: &str, start: f64, end: f64) -> Clip {
       position_y: 50.0,
       anchor_x: 0.5,
       anchor_y: 0.5,
+      ..Default::default()
     }),
     audio_track_index: Some(0),
+    audio_stream_index: None,
+    source_rotation: 0,
+    color_label: None,
+    notes: None,
+    tags: Vec::new(),
+    fade_in: None,
+    fade_out: None,
+    link_group_id: None,
+    source_timecode: None,
+    volume: 1.0,
+    external_audio: None,
+    locked: false,
     properties: ClipProperties {
       notes: Some("Test clip for coverage".to_string()),
       tags: vec!["test".to_string(), "coverage".to_string()],
@@ -333,6 +369,18 @@ mod preprocessing_stage_tests {
       crop: None,
       transform: None,
       audio_track_index: None,
+      audio_stream_index: None,
+      source_rotation: 0,
+      color_label: None,
+      notes: None,
+      tags: Vec::new(),
+      fade_in: None,
+      fade_out: None,
+      link_group_id: None,
+      source_timecode: None,
+      volume: 1.0,
+      external_audio: None,
+      locked: false,
       properties: ClipProperties::default(),
     });
     project.tracks.push(track);
@@ -407,6 +455,18 @@ mod composition_stage_tests {
       crop: None,
       transform: None,
       audio_track_index: None,
+      audio_stream_index: None,
+      source_rotation: 0,
+      color_label: None,
+      notes: None,
+      tags: Vec::new(),
+      fade_in: None,
+      fade_out: None,
+      link_group_id: None,
+      source_timecode: None,
+      volume: 1.0,
+      external_audio: None,
+      locked: false,
       properties: ClipProperties::default(),
     };
     track.clips.push(clip);
@@ -487,6 +547,52 @@ mod encoding_stage_tests {
     let duration = stage.estimated_duration();
     assert!(duration > Duration::ZERO);
   }
+
+  #[tokio::test]
+  async fn test_encoding_stage_records_gpu_downgrade() {
+    let project = create_complete_project_schema("GPU Downgrade Test");
+    let mut context = PipelineContext::new(project, PathBuf::from("/tmp/downgrade.mp4"));
+
+    let (tx, mut rx) = mpsc::unbounded_channel::<ProgressUpdate>();
+    let progress_tracker = Arc::new(ProgressTracker::new(tx));
+    let job_id = progress_tracker
+      .create_job("GPU".to_string(), "/tmp/downgrade.mp4".to_string(), 100)
+      .await
+      .unwrap();
+    rx.recv().await;
+    context.progress_tracker = Some(progress_tracker.clone());
+    context.current_job_id = Some(job_id.clone());
+
+    let stderr = "[h264_nvenc @ 0x55] OpenEncodeSessionEx failed: out of memory (10)";
+    let error = || VideoCompilerError::ffmpeg(Some(1), stderr.to_string(), "ffmpeg".to_string());
+
+    // Пока включено аппаратное ускорение, ошибка передается рендереру как ошибка GPU
+    let mapped = EncodingStage::encoding_error(&context, error());
+    assert!(
+      matches!(mapped, VideoCompilerError::GpuError(ref message) if message.contains("out of memory"))
+    );
+
+    let stage = EncodingStage::new();
+    stage
+      .record_downgrade(&mut context, RenderDowngrade::CpuEncoder)
+      .await;
+
+    assert_eq!(
+      context.warnings,
+      vec![RenderDowngrade::CpuEncoder.message()]
+    );
+    assert!(!context.project.settings.export.hardware_acceleration);
+    assert!(matches!(
+      rx.recv().await.unwrap(),
+      ProgressUpdate::RenderDowngraded { .. }
+    ));
+    let job = progress_tracker.get_job(&job_id).await.unwrap();
+    assert_eq!(job.downgrades, vec![RenderDowngrade::CpuEncoder]);
+
+    // После перехода на CPU ошибка FFmpeg возвращается как есть
+    let mapped = EncodingStage::encoding_error(&context, error());
+    assert!(matches!(mapped, VideoCompilerError::FFmpegError { .. }));
+  }
 }
 
 #[cfg(test)]
@@ -1,13 +1,15 @@
 //! Refactored Pipeline - Модульный конвейер обработки видео
 
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, MutexGuard};
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 
 use crate::video_compiler::error::{Result, VideoCompilerError};
 use crate::video_compiler::ffmpeg_builder::FFmpegBuilder;
-use crate::video_compiler::progress::ProgressTracker;
+use crate::video_compiler::progress::{ProgressTracker, StageStatus};
 use crate::video_compiler::schema::ProjectSchema;
 use crate::video_compiler::CompilerSettings;
 
@@ -16,6 +18,18 @@ use super::stages::{
   PipelineStatistics, PreprocessingStage, ValidationStage,
 };
 
+/// Фактические длительности этапов прошлых рендеров по названию проекта.
+///
+/// Уточняют веса этапов в общем прогрессе следующих рендеров того же проекта.
+static STAGE_DURATION_HISTORY: Lazy<Mutex<HashMap<String, HashMap<String, Duration>>>> =
+  Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn stage_duration_history() -> MutexGuard<'static, HashMap<String, HashMap<String, Duration>>> {
+  STAGE_DURATION_HISTORY
+    .lock()
+    .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
 /// Основной конвейер обработки видео
 pub struct RenderPipeline {
   /// Схема проекта
@@ -23,7 +37,6 @@ pub struct RenderPipeline {
   /// Этапы конвейера
   stages: Vec<Box<dyn PipelineStage>>,
   /// Трекер прогресса
-  #[allow(dead_code)]
   progress_tracker: Arc<ProgressTracker>,
   /// Настройки
   settings: Arc<RwLock<CompilerSettings>>,
@@ -127,26 +140,23 @@ impl RenderPipeline {
     // Создаем временную директорию
     self.context.ensure_temp_dir().await?;
 
-    // Вычисляем общую оценочную длительность всех этапов
-    let total_estimated_duration: Duration = self
+    // Регистрируем этапы в трекере; вес этапа - его оценочная длительность
+    let estimates: Vec<(String, Duration)> = self
       .stages
       .iter()
-      .map(|stage| stage.estimated_duration())
-      .sum();
-
+      .map(|stage| (stage.name().to_string(), self.stage_weight(stage.as_ref())))
+      .collect();
+    let total_estimated_duration: Duration = estimates.iter().map(|(_, weight)| *weight).sum();
     log::info!("⏱️ Оценочная длительность: {total_estimated_duration:?}");
+    self.register_stages(job_id, &estimates).await;
+    self.statistics.stage_estimates = estimates.into_iter().collect();
 
     // Выполняем каждый этап
-    let mut _current_progress = 0u64;
-    let progress_per_stage = 100 / self.stages.len() as u64;
-
     for (index, stage) in self.stages.iter().enumerate() {
-      if self.context.is_cancelled() {
+      if let Err(e) = self.context.checkpoint() {
         log::warn!("⚠️ Обработка отменена пользователем");
         self.cleanup().await?;
-        return Err(VideoCompilerError::CancelledError(
-          "Обработка отменена".to_string(),
-        ));
+        return Err(e);
       }
 
       let stage_start = Instant::now();
@@ -160,11 +170,24 @@ impl RenderPipeline {
       // Проверяем, можно ли пропустить этап
       if stage.can_skip(&self.context) {
         log::info!("⏭️ Этап {} пропущен", stage.name());
-        _current_progress += progress_per_stage;
+        report_stage(
+          &self.progress_tracker,
+          job_id,
+          stage.name(),
+          StageStatus::Skipped,
+        )
+        .await;
         continue;
       }
 
       // Выполняем этап
+      report_stage(
+        &self.progress_tracker,
+        job_id,
+        stage.name(),
+        StageStatus::Running,
+      )
+      .await;
       match stage.process(&mut self.context).await {
         Ok(_) => {
           let stage_duration = stage_start.elapsed();
@@ -172,12 +195,26 @@ impl RenderPipeline {
             .statistics
             .stage_durations
             .insert(stage.name().to_string(), stage_duration);
+          remember_stage_duration(&self.project.metadata.name, stage.name(), stage_duration);
+          report_stage(
+            &self.progress_tracker,
+            job_id,
+            stage.name(),
+            StageStatus::Completed,
+          )
+          .await;
 
-          _current_progress += progress_per_stage;
           log::info!("✅ Этап {} завершен за {:?}", stage.name(), stage_duration);
         }
         Err(e) => {
           log::error!("❌ Ошибка на этапе {}: {}", stage.name(), e);
+          report_stage(
+            &self.progress_tracker,
+            job_id,
+            stage.name(),
+            StageStatus::Failed,
+          )
+          .await;
           self.cleanup().await?;
           return Err(e);
         }
@@ -200,10 +237,37 @@ impl RenderPipeline {
     Ok(self.context.output_path.clone())
   }
 
+  /// Вес этапа в общем прогрессе: средняя фактическая длительность этапа
+  /// в прошлых рендерах проекта или оценка самого этапа
+  fn stage_weight(&self, stage: &dyn PipelineStage) -> Duration {
+    stage_duration_history()
+      .get(&self.project.metadata.name)
+      .and_then(|durations| durations.get(stage.name()).copied())
+      .unwrap_or_else(|| stage.estimated_duration())
+  }
+
+  /// Зарегистрировать этапы задачи в трекере и перевести ее в выполнение.
+  ///
+  /// Конвейер может выполняться без задачи в трекере, поэтому ошибки
+  /// только логируются.
+  async fn register_stages(&self, job_id: &str, estimates: &[(String, Duration)]) {
+    let stages = estimates
+      .iter()
+      .map(|(name, weight)| (name.clone(), weight.as_secs_f64()))
+      .collect();
+    if let Err(e) = self.progress_tracker.register_stages(job_id, stages).await {
+      log::debug!("Этапы задачи {job_id} не зарегистрированы: {e}");
+      return;
+    }
+    if let Err(e) = self.progress_tracker.start_job(job_id).await {
+      log::debug!("Задача {job_id} уже запущена: {e}");
+    }
+  }
+
   /// Отменить выполнение конвейера
   pub async fn cancel(&mut self) -> Result<()> {
     log::warn!("🛑 Отмена конвейера...");
-    self.context.cancelled = true;
+    self.context.cancellation.cancel();
     self.cleanup().await
   }
 
@@ -239,11 +303,17 @@ impl RenderPipeline {
     self.start_time.is_some() && !self.context.is_cancelled()
   }
 
-  /// Получить прогресс выполнения (0-100)
+  /// Получить прогресс выполнения (0-100) по весам этапов
   pub async fn get_progress(&self) -> u64 {
-    // Здесь можно добавить более сложную логику вычисления прогресса
-    // на основе текущего этапа и его прогресса
-    0
+    let Some(job_id) = &self.context.current_job_id else {
+      return 0;
+    };
+    self
+      .progress_tracker
+      .get_job(job_id)
+      .await
+      .map(|job| job.get_progress().percentage.round() as u64)
+      .unwrap_or(0)
   }
 
   /// Обновить настройки конвейера
@@ -261,23 +331,23 @@ impl RenderPipeline {
     // Проверяем наличие этапов
     if self.stages.is_empty() {
       return Err(VideoCompilerError::ValidationError(
-        "Конвейер не содержит этапов".to_string(),
+        "Конвейер не содержит этапов".into(),
       ));
     }
 
     // Проверяем валидность схемы проекта
     if self.project.metadata.name.is_empty() {
       return Err(VideoCompilerError::ValidationError(
-        "Название проекта не может быть пустым".to_string(),
+        "Название проекта не может быть пустым".into(),
       ));
     }
 
     // Проверяем выходную директорию
     if let Some(parent) = self.context.output_path.parent() {
       if !parent.exists() {
-        return Err(VideoCompilerError::ValidationError(format!(
-          "Выходная директория не существует: {parent:?}"
-        )));
+        return Err(VideoCompilerError::ValidationError(
+          format!("Выходная директория не существует: {parent:?}").into(),
+        ));
       }
     }
 
@@ -300,6 +370,32 @@ impl RenderPipeline {
   }
 }
 
+/// Сообщить трекеру статус этапа; задачи может не быть в трекере
+async fn report_stage(tracker: &ProgressTracker, job_id: &str, stage: &str, status: StageStatus) {
+  let result = match status {
+    StageStatus::Running => {
+      tracker
+        .update_stage_progress(job_id, stage, 0.0, None)
+        .await
+    }
+    _ => tracker.finish_stage(job_id, stage, status).await,
+  };
+  if let Err(e) = result {
+    log::debug!("Статус этапа {stage} задачи {job_id} не обновлен: {e}");
+  }
+}
+
+/// Учесть фактическую длительность этапа: среднее с прошлыми рендерами проекта
+fn remember_stage_duration(project: &str, stage: &str, actual: Duration) {
+  let mut history = stage_duration_history();
+  let durations = history.entry(project.to_string()).or_default();
+  let duration = match durations.get(stage) {
+    Some(previous) => (*previous + actual) / 2,
+    None => actual,
+  };
+  durations.insert(stage.to_string(), duration);
+}
+
 /// Построитель конвейера для удобной конфигурации
 pub struct PipelineBuilder {
   project: Option<ProjectSchema>,
@@ -351,15 +447,16 @@ impl PipelineBuilder {
   ) -> Result<RenderPipeline> {
     let project = self
       .project
-      .ok_or_else(|| VideoCompilerError::ValidationError("Проект не установлен".to_string()))?;
+      .ok_or_else(|| VideoCompilerError::ValidationError("Проект не установлен".into()))?;
 
-    let output_path = self.output_path.ok_or_else(|| {
-      VideoCompilerError::ValidationError("Выходной путь не установлен".to_string())
-    })?;
+    let output_path = self
+      .output_path
+      .ok_or_else(|| VideoCompilerError::ValidationError("Выходной путь не установлен".into()))?;
 
     let mut pipeline = if self.skip_default_stages {
       // Создаем пустой конвейер
-      let context = PipelineContext::new(project.clone(), output_path);
+      let mut context = PipelineContext::new(project.clone(), output_path);
+      context.progress_tracker = Some(progress_tracker.clone());
       RenderPipeline {
         project,
         stages: Vec::new(),
@@ -405,6 +502,7 @@ pub struct ExecutionSummary {
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::video_compiler::progress::ProgressUpdate;
   use crate::video_compiler::schema::{ProjectMetadata, ProjectSettings, Timeline};
 
   fn create_test_project() -> ProjectSchema {
@@ -424,6 +522,7 @@ mod tests {
       filters: vec![],
       templates: vec![],
       style_templates: vec![],
+      sequences: Default::default(),
       subtitles: vec![],
       settings: ProjectSettings::default(),
     }
@@ -471,6 +570,109 @@ mod tests {
     assert_eq!(pipeline.stages.len(), initial_count);
   }
 
+  /// Этап для проверки прогресса: отчитывается о половине работы
+  struct TestStage {
+    name: &'static str,
+    estimate: Duration,
+    skip: bool,
+  }
+
+  #[async_trait::async_trait]
+  impl PipelineStage for TestStage {
+    async fn process(&self, context: &mut PipelineContext) -> Result<()> {
+      context.update_progress(50, self.name).await?;
+      context.update_progress(100, self.name).await
+    }
+
+    fn name(&self) -> &str {
+      self.name
+    }
+
+    fn estimated_duration(&self) -> Duration {
+      self.estimate
+    }
+
+    fn can_skip(&self, _context: &PipelineContext) -> bool {
+      self.skip
+    }
+  }
+
+  #[tokio::test]
+  async fn test_pipeline_reports_weighted_stage_progress() {
+    let mut project = create_test_project();
+    project.metadata.name = "Stage Progress Project".to_string();
+    let output_path = std::env::temp_dir().join("test_stage_progress_output.mp4");
+    let (progress_sender, mut progress_receiver) = tokio::sync::mpsc::unbounded_channel();
+    let progress_tracker = Arc::new(ProgressTracker::new(progress_sender));
+    let settings = Arc::new(RwLock::new(CompilerSettings::default()));
+    let job_id = progress_tracker
+      .create_job(
+        project.metadata.name.clone(),
+        output_path.to_string_lossy().to_string(),
+        0,
+      )
+      .await
+      .unwrap();
+
+    let stage = |name, secs, skip| -> Box<dyn PipelineStage> {
+      Box::new(TestStage {
+        name,
+        estimate: Duration::from_secs(secs),
+        skip,
+      })
+    };
+    let mut pipeline = PipelineBuilder::new()
+      .with_project(project)
+      .with_output_path(output_path)
+      .skip_default_stages()
+      .add_stage(stage("Prepare", 10, false))
+      .add_stage(stage("Proxy", 30, true))
+      .add_stage(stage("Encode", 60, false))
+      .build(progress_tracker.clone(), settings)
+      .await
+      .unwrap();
+
+    pipeline.execute(&job_id).await.unwrap();
+
+    // Общий прогресс не убывает и доходит до 100%
+    let mut percentages = Vec::new();
+    while let Ok(update) = progress_receiver.try_recv() {
+      if let ProgressUpdate::ProgressChanged { progress, .. } = update {
+        percentages.push(progress.percentage);
+      }
+    }
+    assert!(percentages.windows(2).all(|pair| pair[1] >= pair[0]));
+    assert!(percentages.contains(&5.0));
+    assert_eq!(percentages.last(), Some(&100.0));
+    assert_eq!(pipeline.get_progress().await, 100);
+
+    let job = progress_tracker.get_job(&job_id).await.unwrap();
+    let statuses: Vec<_> = job.stages.iter().map(|stage| stage.status).collect();
+    assert_eq!(
+      statuses,
+      vec![
+        StageStatus::Completed,
+        StageStatus::Skipped,
+        StageStatus::Completed
+      ]
+    );
+    assert_eq!(job.stages[2].weight, 60.0);
+
+    // Фактические длительности попадают в статистику и в веса следующего рендера
+    let statistics = pipeline.get_statistics();
+    assert_eq!(statistics.stage_estimates.len(), 3);
+    assert_eq!(statistics.stage_durations.len(), 2);
+    let encode_duration = statistics.stage_durations["Encode"];
+    assert_eq!(
+      pipeline.stage_weight(pipeline.stages[2].as_ref()),
+      encode_duration
+    );
+    assert_eq!(
+      pipeline.stage_weight(pipeline.stages[1].as_ref()),
+      Duration::from_secs(30)
+    );
+  }
+
   #[test]
   fn test_execution_summary() {
     let project = create_test_project();
@@ -5,6 +5,8 @@
 
 use crate::video_compiler::cache::{PreviewKey, RenderCache};
 use crate::video_compiler::error::{Result, VideoCompilerError};
+use crate::video_compiler::ffmpeg_builder::builder::FFmpegBuilderSettings;
+use crate::video_compiler::ffmpeg_builder::{CommandPreview, FFmpegBuilder};
 use crate::video_compiler::schema::{PreviewFormat, ProjectSchema};
 use base64::engine::general_purpose::STANDARD as BASE64;
 use base64::Engine as _;
@@ -276,8 +278,6 @@ impl PreviewGenerator {
     output_path: &str,
     options: Option<PreviewOptions>,
   ) -> Result<()> {
-    use crate::video_compiler::ffmpeg_builder::FFmpegBuilder;
-
     // Используем опции или значения по умолчанию
     let options = options.unwrap_or(PreviewOptions {
       width: Some(1920),
@@ -334,6 +334,127 @@ impl PreviewGenerator {
     Ok(())
   }
 
+  /// Команда рендеринга кадра timeline без запуска FFmpeg.
+  ///
+  /// Строится так же, как в `render_timeline_frame`.
+  pub async fn preview_timeline_frame_command(
+    &self,
+    project: &ProjectSchema,
+    timestamp: f64,
+    resolution: Option<(u32, u32)>,
+    quality: Option<u8>,
+    format: Option<PreviewFormat>,
+  ) -> Result<CommandPreview> {
+    let (resolution, quality, format) = self.timeline_frame_params(resolution, quality, format);
+    self
+      .timeline_frame_builder(project)
+      .preview_frame_command(
+        timestamp,
+        &timeline_frame_output(&format),
+        resolution,
+        &format,
+        quality,
+      )
+      .await
+  }
+
+  /// Параметры кадра timeline с настройками генератора по умолчанию
+  fn timeline_frame_params(
+    &self,
+    resolution: Option<(u32, u32)>,
+    quality: Option<u8>,
+    format: Option<PreviewFormat>,
+  ) -> ((u32, u32), u8, PreviewFormat) {
+    (
+      resolution.unwrap_or(self.settings.default_resolution),
+      quality.unwrap_or(self.settings.default_quality),
+      format.unwrap_or_else(|| self.settings.format.clone()),
+    )
+  }
+
+  /// Построитель команд кадра timeline
+  fn timeline_frame_builder(&self, project: &ProjectSchema) -> FFmpegBuilder {
+    FFmpegBuilder::with_settings(
+      project.clone(),
+      FFmpegBuilderSettings {
+        ffmpeg_path: self.ffmpeg_path.clone(),
+        ..Default::default()
+      },
+    )
+  }
+
+  /// Отрендерить кадр собранного timeline в момент `timestamp`.
+  ///
+  /// Кэшируется по хешу снимка проекта для этого момента, поэтому правки
+  /// в других местах timeline не сбрасывают уже сгенерированные кадры.
+  pub async fn render_timeline_frame(
+    &self,
+    project: &ProjectSchema,
+    timestamp: f64,
+    resolution: Option<(u32, u32)>,
+    quality: Option<u8>,
+    format: Option<PreviewFormat>,
+  ) -> Result<Vec<u8>> {
+    if timestamp < 0.0 {
+      return Err(VideoCompilerError::validation(
+        "Временная метка не может быть отрицательной",
+      ));
+    }
+
+    let (resolution, quality, format) = self.timeline_frame_params(resolution, quality, format);
+    let cache_key = timeline_frame_key(project, timestamp, resolution, quality, &format);
+
+    {
+      let mut cache = self.cache.write().await;
+      if let Some(cached_data) = cache.get_preview(&cache_key).await {
+        log::debug!("Кадр timeline найден в кэше at {timestamp}s");
+        return Ok(cached_data.image_data);
+      }
+    }
+
+    let temp_output = timeline_frame_output(&format);
+    let mut cmd = self
+      .timeline_frame_builder(project)
+      .build_frame_command(timestamp, &temp_output, resolution, &format, quality)
+      .await?;
+    cmd.stdout(Stdio::null());
+    cmd.stderr(Stdio::piped());
+    cmd.kill_on_drop(true);
+
+    log::debug!("Рендеринг кадра timeline: {cmd:?}");
+
+    let output = cmd.output().await.map_err(|e| {
+      VideoCompilerError::ffmpeg(
+        None,
+        format!("Не удалось запустить FFmpeg: {e}"),
+        "render_timeline_frame".to_string(),
+      )
+    })?;
+
+    if !output.status.success() {
+      return Err(VideoCompilerError::ffmpeg(
+        output.status.code(),
+        String::from_utf8_lossy(&output.stderr).to_string(),
+        format!("ffmpeg render timeline frame at {timestamp}s"),
+      ));
+    }
+
+    let image_data = tokio::fs::read(&temp_output).await.map_err(|e| {
+      VideoCompilerError::preview(timestamp, format!("Не удалось прочитать кадр: {e}"))
+    })?;
+
+    if let Err(e) = tokio::fs::remove_file(&temp_output).await {
+      log::warn!("Не удалось удалить временный файл кадра: {e}");
+    }
+
+    {
+      let mut cache = self.cache.write().await;
+      cache.store_preview(cache_key, image_data.clone()).await?;
+    }
+
+    Ok(image_data)
+  }
+
   /// Получить информацию о видео файле
   pub async fn get_video_info(&self, video_path: &Path) -> Result<VideoInfo> {
     let mut cmd = Command::new(&self.ffmpeg_path);
@@ -403,6 +524,7 @@ impl PreviewGenerator {
     cmd.arg(&temp_output);
     cmd.stdout(Stdio::null());
     cmd.stderr(Stdio::piped());
+    cmd.kill_on_drop(true);
 
     log::debug!("Выполнение команды FFmpeg: {cmd:?}");
 
@@ -505,11 +627,7 @@ impl PreviewGenerator {
 
   /// Получить расширение файла для текущего формата
   fn get_file_extension(&self) -> &'static str {
-    match self.settings.format {
-      PreviewFormat::Jpeg => "jpg",
-      PreviewFormat::Png => "png",
-      PreviewFormat::WebP => "webp",
-    }
+    format_extension(&self.settings.format)
   }
 
   /// Конвертировать качество (0-100) в qscale для FFmpeg (2-31)
@@ -675,6 +793,44 @@ pub struct VideoInfo {
   pub audio_codec: Option<String>,
 }
 
+/// Расширение файла для формата превью
+/// Временный файл кадра timeline
+fn timeline_frame_output(format: &PreviewFormat) -> PathBuf {
+  std::env::temp_dir().join(format!(
+    "timeline_frame_{}.{}",
+    uuid::Uuid::new_v4(),
+    format_extension(format)
+  ))
+}
+
+fn format_extension(format: &PreviewFormat) -> &'static str {
+  match format {
+    PreviewFormat::Jpeg => "jpg",
+    PreviewFormat::Png => "png",
+    PreviewFormat::WebP => "webp",
+  }
+}
+
+/// Ключ кэша кадра timeline.
+///
+/// Снимок проекта сдвинут к моменту кадра, поэтому его хеш однозначно
+/// определяет изображение, и время в ключ не входит.
+fn timeline_frame_key(
+  project: &ProjectSchema,
+  timestamp: f64,
+  resolution: (u32, u32),
+  quality: u8,
+  format: &PreviewFormat,
+) -> PreviewKey {
+  let hash = project.frame_snapshot(timestamp).content_hash();
+  PreviewKey::new(
+    format!("timeline:{hash}.{}", format_extension(format)),
+    0.0,
+    resolution,
+    quality,
+  )
+}
+
 /// Парсинг длительности из строки формата HH:MM:SS.ss
 fn parse_duration(duration_str: &str) -> f64 {
   let parts: Vec<&str> = duration_str.trim().split(':').collect();
@@ -919,17 +1075,74 @@ Input #0, mov,mp4,m4a,3gp,3g2,mj2, from 'test.mp4':
 
   #[tokio::test]
   async fn test_timeline_preview_generation() {
+    use crate::video_compiler::tests::fixtures::{generated_test_video, FIXTURE_VIDEO_DURATION};
+
+    let Some(video_path) = generated_test_video().await else {
+      return;
+    };
     let generator = create_test_generator();
 
-    // Создаем мок видео файл (в реальности нужен настоящий файл)
-    let video_path = PathBuf::from("/nonexistent/test.mp4");
+    let previews = generator
+      .generate_timeline_previews(&video_path, FIXTURE_VIDEO_DURATION, 2.0)
+      .await
+      .unwrap();
+
+    assert_eq!(previews.len(), 3); // 6 секунд / 2 секунды интервал
+    assert!(previews.iter().all(|p| p.image_data.is_some()));
+  }
 
-    // Этот тест не пройдет без реального видео файла, но структура правильная
-    let _result = generator
-      .generate_timeline_previews(&video_path, 60.0, 10.0)
+  #[tokio::test]
+  async fn test_render_timeline_frame_uses_cache() {
+    use crate::video_compiler::tests::fixtures::create_project_with_clips;
+
+    let cache = Arc::new(RwLock::new(RenderCache::new()));
+    let mut generator = PreviewGenerator::new(cache.clone());
+    // FFmpeg недоступен: кадр может прийти только из кэша
+    generator.set_ffmpeg_path("/nonexistent/ffmpeg");
+
+    let project = create_project_with_clips();
+    let key = timeline_frame_key(&project, 2.0, (640, 360), 75, &PreviewFormat::Jpeg);
+    cache
+      .write()
+      .await
+      .store_preview(key, vec![1, 2, 3])
+      .await
+      .unwrap();
+
+    // Правка вне момента кадра не сбрасывает кэш
+    let mut edited = project.clone();
+    edited.touch();
+    edited
+      .subtitles
+      .push(crate::video_compiler::schema::Subtitle::new(
+        "Later".to_string(),
+        4.0,
+        5.0,
+      ));
+    let frame = generator
+      .render_timeline_frame(&edited, 2.0, Some((640, 360)), Some(75), None)
+      .await
+      .unwrap();
+    assert_eq!(frame, vec![1, 2, 3]);
+
+    // Другой момент требует рендеринга
+    let result = generator
+      .render_timeline_frame(&edited, 3.0, Some((640, 360)), Some(75), None)
       .await;
+    assert!(result.is_err());
+  }
 
-    // В реальном тесте здесь были бы проверки на количество превью
-    // assert_eq!(result.unwrap().len(), 6); // 60 секунд / 10 секунд интервал
+  #[tokio::test]
+  async fn test_render_timeline_frame_rejects_negative_timestamp() {
+    let generator = create_test_generator();
+    let project = ProjectSchema::new("Test".to_string());
+
+    let result = generator
+      .render_timeline_frame(&project, -1.0, None, None, None)
+      .await;
+    assert!(matches!(
+      result,
+      Err(VideoCompilerError::ValidationError(_))
+    ));
   }
 }
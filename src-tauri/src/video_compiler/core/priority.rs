@@ -0,0 +1,124 @@
+//! Priority - Приоритет фоновой работы относительно интерактивных запросов
+//!
+//! Интерактивные операции (превью, метаданные по запросу UI) отмечают себя
+//! через [`begin_interactive`]. Фоновая работа (прогрев кэша) выполняется в
+//! [`run_in_background`] и перед каждым шагом ждет [`yield_to_interactive`],
+//! пока интерактивных запросов нет. Внутри фоновой области кэши не вытесняют
+//! существующие записи ради новых.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+/// Интервал повторной проверки занятости при ожидании фоновой работой
+const YIELD_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Количество выполняющихся интерактивных запросов
+static INTERACTIVE_IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+
+/// Момент последней интерактивной активности
+static LAST_INTERACTIVE: Mutex<Option<Instant>> = Mutex::new(None);
+
+tokio::task_local! {
+  static BACKGROUND: ();
+}
+
+/// Отметка интерактивного запроса, снимается при drop
+#[must_use]
+pub struct InteractiveGuard {
+  counted: bool,
+}
+
+impl Drop for InteractiveGuard {
+  fn drop(&mut self) {
+    if self.counted {
+      INTERACTIVE_IN_FLIGHT.fetch_sub(1, Ordering::SeqCst);
+      touch_interactive();
+    }
+  }
+}
+
+/// Отметить начало интерактивного запроса.
+///
+/// Вызов из фоновой области не учитывается, чтобы прогрев не ждал сам себя.
+pub fn begin_interactive() -> InteractiveGuard {
+  let counted = !is_background();
+  if counted {
+    INTERACTIVE_IN_FLIGHT.fetch_add(1, Ordering::SeqCst);
+    touch_interactive();
+  }
+  InteractiveGuard { counted }
+}
+
+fn touch_interactive() {
+  if let Ok(mut last) = LAST_INTERACTIVE.lock() {
+    *last = Some(Instant::now());
+  }
+}
+
+/// Момент последнего начала или завершения интерактивного запроса
+pub fn last_interactive_activity() -> Option<Instant> {
+  LAST_INTERACTIVE.lock().ok().and_then(|last| *last)
+}
+
+/// Количество выполняющихся интерактивных запросов
+pub fn interactive_in_flight() -> usize {
+  INTERACTIVE_IN_FLIGHT.load(Ordering::SeqCst)
+}
+
+/// Выполняется ли текущая задача в фоновой области
+pub fn is_background() -> bool {
+  BACKGROUND.try_with(|_| ()).is_ok()
+}
+
+/// Выполнить future с низким приоритетом
+pub async fn run_in_background<F: Future>(future: F) -> F::Output {
+  BACKGROUND.scope((), future).await
+}
+
+/// Дождаться, пока не останется интерактивных запросов.
+///
+/// Возвращает `false`, если ожидание прервано отменой.
+pub async fn yield_to_interactive(cancel: &CancellationToken) -> bool {
+  loop {
+    if cancel.is_cancelled() {
+      return false;
+    }
+    if interactive_in_flight() == 0 {
+      return true;
+    }
+    tokio::select! {
+      _ = cancel.cancelled() => return false,
+      _ = tokio::time::sleep(YIELD_POLL_INTERVAL) => {}
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[tokio::test]
+  async fn test_background_scope_is_not_counted_as_interactive() {
+    assert!(!is_background());
+
+    run_in_background(async {
+      assert!(is_background());
+      let _guard = begin_interactive();
+      assert!(!_guard.counted);
+    })
+    .await;
+
+    let guard = begin_interactive();
+    assert!(guard.counted);
+  }
+
+  #[tokio::test]
+  async fn test_yield_to_interactive_stops_on_cancel() {
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+    assert!(!yield_to_interactive(&cancel).await);
+  }
+}
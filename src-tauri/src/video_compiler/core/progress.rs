@@ -3,7 +3,10 @@
 //! Этот модуль реализует систему отслеживания прогресса рендеринга видео,
 //! включая парсинг вывода FFmpeg, расчет прогресса и уведомления через WebSocket.
 
+use crate::language_tauri::i18n::LocalizedMessage;
+use crate::video_compiler::core::gpu_memory::RenderDowngrade;
 use crate::video_compiler::error::{Result, VideoCompilerError};
+use crate::video_compiler::services::render_report::RenderReport;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -43,12 +46,10 @@ impl ProgressTracker {
     {
       let jobs = self.active_jobs.read().await;
       if jobs.len() >= self.settings.max_concurrent_jobs {
-        return Err(VideoCompilerError::render(
-          "new_job",
-          "create_job",
-          format!(
-            "Превышено максимальное количество одновременных задач ({})",
-            self.settings.max_concurrent_jobs
+        return Err(VideoCompilerError::TooManyActiveJobs(
+          LocalizedMessage::new(
+            "render_queue.limit_reached",
+            &[("limit", self.settings.max_concurrent_jobs.to_string())],
           ),
         ));
       }
@@ -123,6 +124,124 @@ impl ProgressTracker {
     Ok(())
   }
 
+  /// Перевести задачу из очереди в выполнение
+  pub async fn start_job(&self, job_id: &str) -> Result<()> {
+    let mut jobs = self.active_jobs.write().await;
+    jobs
+      .get_mut(job_id)
+      .ok_or_else(|| VideoCompilerError::render(job_id, "start_job", "Задача не найдена"))?
+      .start()
+  }
+
+  /// Зарегистрировать этапы задачи с весами.
+  ///
+  /// Вес этапа - его оценочная длительность; общий процент задачи
+  /// считается по весам этапов вместо номера кадра.
+  pub async fn register_stages(&self, job_id: &str, stages: Vec<(String, f64)>) -> Result<()> {
+    let mut jobs = self.active_jobs.write().await;
+    let job = jobs
+      .get_mut(job_id)
+      .ok_or_else(|| VideoCompilerError::render(job_id, "register_stages", "Задача не найдена"))?;
+    job.register_stages(stages);
+    Ok(())
+  }
+
+  /// Обновить прогресс этапа задачи (`fraction` от 0.0 до 1.0)
+  pub async fn update_stage_progress(
+    &self,
+    job_id: &str,
+    stage: &str,
+    fraction: f32,
+    message: Option<String>,
+  ) -> Result<()> {
+    let mut jobs = self.active_jobs.write().await;
+    let job = jobs.get_mut(job_id).ok_or_else(|| {
+      VideoCompilerError::render(job_id, "update_stage_progress", "Задача не найдена")
+    })?;
+    job.update_stage(stage, fraction, message)?;
+    self.send_progress(job);
+    Ok(())
+  }
+
+  /// Завершить этап задачи с указанным статусом
+  pub async fn finish_stage(&self, job_id: &str, stage: &str, status: StageStatus) -> Result<()> {
+    let mut jobs = self.active_jobs.write().await;
+    let job = jobs
+      .get_mut(job_id)
+      .ok_or_else(|| VideoCompilerError::render(job_id, "finish_stage", "Задача не найдена"))?;
+    job.finish_stage(stage, status)?;
+    self.send_progress(job);
+    Ok(())
+  }
+
+  fn send_progress(&self, job: &RenderJob) {
+    let update = ProgressUpdate::ProgressChanged {
+      job_id: job.id.clone(),
+      progress: job.get_progress(),
+    };
+    let _ = self.progress_sender.send(update);
+  }
+
+  /// Зарегистрировать варианты многовариантного экспорта задачи.
+  ///
+  /// Все варианты кодируются одной командой FFmpeg, поэтому их прогресс
+  /// обновляется вместе с общим прогрессом задачи.
+  pub async fn set_job_renditions(
+    &self,
+    job_id: &str,
+    renditions: Vec<RenditionProgress>,
+  ) -> Result<()> {
+    let mut jobs = self.active_jobs.write().await;
+
+    let job = jobs.get_mut(job_id).ok_or_else(|| {
+      VideoCompilerError::render(job_id, "set_job_renditions", "Задача не найдена")
+    })?;
+    job.renditions = renditions;
+    job.sync_rendition_progress();
+    Ok(())
+  }
+
+  /// Записать все файлы, созданные задачей (основной вывод, варианты, стемы)
+  pub async fn set_job_output_files(&self, job_id: &str, output_files: Vec<String>) -> Result<()> {
+    let mut jobs = self.active_jobs.write().await;
+
+    let job = jobs.get_mut(job_id).ok_or_else(|| {
+      VideoCompilerError::render(job_id, "set_job_output_files", "Задача не найдена")
+    })?;
+    job.output_files = output_files;
+    Ok(())
+  }
+
+  /// Записать понижение режима кодирования и предупредить пользователя
+  pub async fn record_downgrade(&self, job_id: &str, downgrade: RenderDowngrade) -> Result<()> {
+    let mut jobs = self.active_jobs.write().await;
+
+    let job = jobs
+      .get_mut(job_id)
+      .ok_or_else(|| VideoCompilerError::render(job_id, "record_downgrade", "Задача не найдена"))?;
+    if !job.downgrades.contains(&downgrade) {
+      job.downgrades.push(downgrade);
+    }
+
+    let message = downgrade.message();
+    log::warn!("Задача {job_id}: {message}");
+    let update = ProgressUpdate::RenderDowngraded {
+      job_id: job_id.to_string(),
+      downgrade,
+      message,
+    };
+    let _ = self.progress_sender.send(update);
+    Ok(())
+  }
+
+  /// Передать отчет о завершенном рендере
+  pub fn report_ready(&self, job_id: &str, report: RenderReport) {
+    let _ = self.progress_sender.send(ProgressUpdate::ReportReady {
+      job_id: job_id.to_string(),
+      report: Box::new(report),
+    });
+  }
+
   /// Завершить задачу успешно
   pub async fn complete_job(&self, job_id: &str, output_path: String) -> Result<()> {
     let mut jobs = self.active_jobs.write().await;
@@ -134,6 +253,7 @@ impl ProgressTracker {
       let update = ProgressUpdate::JobCompleted {
         job_id: job_id.to_string(),
         output_path,
+        output_files: job.output_files.clone(),
         duration: job.get_elapsed_time(),
       };
       let _ = self.progress_sender.send(update);
@@ -382,6 +502,17 @@ pub struct RenderJob {
   pub message: Option<String>,
   /// Ошибка (если есть)
   pub error: Option<String>,
+  /// Прогресс вариантов многовариантного экспорта
+  pub renditions: Vec<RenditionProgress>,
+  /// Понижения режима кодирования из-за нехватки видеопамяти
+  #[serde(default)]
+  pub downgrades: Vec<RenderDowngrade>,
+  /// Все созданные файлы: основной вывод, варианты и аудио стемы
+  #[serde(default)]
+  pub output_files: Vec<String>,
+  /// Этапы рендеринга в порядке выполнения
+  #[serde(default)]
+  pub stages: Vec<StageProgress>,
 }
 
 impl RenderJob {
@@ -400,11 +531,14 @@ impl RenderJob {
       completed_at: None,
       message: None,
       error: None,
+      renditions: Vec::new(),
+      downgrades: Vec::new(),
+      output_files: Vec::new(),
+      stages: Vec::new(),
     }
   }
 
   /// Начать выполнение задачи
-  #[allow(dead_code)]
   pub fn start(&mut self) -> Result<()> {
     if self.status != RenderStatus::Queued {
       return Err(VideoCompilerError::render(
@@ -438,9 +572,97 @@ impl RenderJob {
     self.current_frame = current_frame.min(self.total_frames);
     self.current_stage = stage;
     self.message = message;
+    self.sync_rendition_progress();
     Ok(())
   }
 
+  /// Зарегистрировать этапы (название и вес); прежние этапы заменяются
+  pub fn register_stages(&mut self, stages: Vec<(String, f64)>) {
+    self.stages = stages
+      .into_iter()
+      .map(|(name, weight)| StageProgress::new(name, weight))
+      .collect();
+  }
+
+  /// Обновить прогресс этапа.
+  ///
+  /// Этап становится текущим: незавершенные предыдущие этапы считаются
+  /// выполненными (или пропущенными, если не начинались). Доля этапа не
+  /// уменьшается, поэтому общий процент не откатывается назад.
+  pub fn update_stage(
+    &mut self,
+    stage: &str,
+    fraction: f32,
+    message: Option<String>,
+  ) -> Result<()> {
+    if self.status != RenderStatus::Processing {
+      return Err(VideoCompilerError::render(
+        &self.id,
+        "update_stage",
+        "Задача не выполняется",
+      ));
+    }
+    let index = self.stage_index(stage)?;
+
+    for previous in &mut self.stages[..index] {
+      match previous.status {
+        StageStatus::Pending => previous.finish(StageStatus::Skipped),
+        StageStatus::Running => previous.finish(StageStatus::Completed),
+        _ => {}
+      }
+    }
+
+    let current = &mut self.stages[index];
+    if current.status == StageStatus::Pending {
+      current.status = StageStatus::Running;
+      current.started_at = Some(SystemTime::now());
+    }
+    current.fraction = current.fraction.max(fraction.clamp(0.0, 1.0));
+
+    self.current_stage = stage.to_string();
+    self.message = message;
+    self.sync_rendition_progress();
+    Ok(())
+  }
+
+  /// Завершить этап со статусом `Completed`, `Skipped`, `Failed` или `Cancelled`
+  pub fn finish_stage(&mut self, stage: &str, status: StageStatus) -> Result<()> {
+    let index = self.stage_index(stage)?;
+    self.stages[index].finish(status);
+    self.sync_rendition_progress();
+    Ok(())
+  }
+
+  fn stage_index(&self, stage: &str) -> Result<usize> {
+    self
+      .stages
+      .iter()
+      .position(|registered| registered.name == stage)
+      .ok_or_else(|| {
+        VideoCompilerError::render(
+          &self.id,
+          "update_stage",
+          format!("Этап {stage} не зарегистрирован"),
+        )
+      })
+  }
+
+  /// Завершить незавершенные этапы: выполняющиеся получают `running_status`,
+  /// не начатые - `pending_status` (или остаются в ожидании)
+  fn finish_open_stages(
+    &mut self,
+    running_status: StageStatus,
+    pending_status: Option<StageStatus>,
+  ) {
+    for stage in &mut self.stages {
+      match (stage.status, pending_status) {
+        (StageStatus::Running, _) => stage.finish(running_status),
+        (StageStatus::Pending, Some(status)) => stage.finish(status),
+        _ => {}
+      }
+    }
+  }
+
   /// Завершить задачу успешно
   pub fn complete(&mut self, final_output_path: String) -> Result<()> {
     if self.status != RenderStatus::Processing {
@@ -453,9 +675,16 @@ impl RenderJob {
 
     self.status = RenderStatus::Completed;
     self.completed_at = Some(SystemTime::now());
+    if !self.output_files.contains(&final_output_path) {
+      self.output_files.insert(0, final_output_path.clone());
+    }
     self.output_path = final_output_path;
     self.current_frame = self.total_frames;
     self.current_stage = "Completed".to_string();
+    self.finish_open_stages(StageStatus::Completed, Some(StageStatus::Skipped));
+    for rendition in &mut self.renditions {
+      rendition.percentage = 100.0;
+    }
     Ok(())
   }
 
@@ -473,6 +702,7 @@ impl RenderJob {
     self.completed_at = Some(SystemTime::now());
     self.error = Some(error);
     self.current_stage = "Failed".to_string();
+    self.finish_open_stages(StageStatus::Failed, None);
     Ok(())
   }
 
@@ -492,16 +722,33 @@ impl RenderJob {
     self.status = RenderStatus::Cancelled;
     self.completed_at = Some(SystemTime::now());
     self.current_stage = "Cancelled".to_string();
+    self.finish_open_stages(StageStatus::Cancelled, None);
     Ok(())
   }
 
-  /// Получить прогресс рендеринга
-  pub fn get_progress(&self) -> RenderProgress {
-    let percentage = if self.total_frames > 0 {
+  /// Процент выполнения задачи: по весам этапов, если они
+  /// зарегистрированы, иначе по кадрам
+  fn percentage(&self) -> f32 {
+    if !self.stages.is_empty() {
+      stages_percentage(&self.stages)
+    } else if self.total_frames > 0 {
       (self.current_frame as f32 / self.total_frames as f32) * 100.0
     } else {
       0.0
-    };
+    }
+  }
+
+  /// Варианты кодируются одновременно и идут вровень с общим прогрессом
+  fn sync_rendition_progress(&mut self) {
+    let percentage = self.percentage();
+    for rendition in &mut self.renditions {
+      rendition.percentage = percentage;
+    }
+  }
+
+  /// Получить прогресс рендеринга
+  pub fn get_progress(&self) -> RenderProgress {
+    let percentage = self.percentage();
 
     let elapsed_time = self.get_elapsed_time();
     let estimated_remaining = if percentage > 0.0 && percentage < 100.0 {
@@ -523,6 +770,18 @@ impl RenderJob {
       estimated_remaining,
       status: self.status.clone(),
       message: self.message.clone(),
+      renditions: self.renditions.clone(),
+      stages: self
+        .stages
+        .iter()
+        .cloned()
+        .map(|mut stage| {
+          if stage.status == StageStatus::Running {
+            stage.elapsed = stage.elapsed_since_start();
+          }
+          stage
+        })
+        .collect(),
     }
   }
 
@@ -539,6 +798,8 @@ impl RenderJob {
 /// Статус рендеринга
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum RenderStatus {
+  /// Запланировано на отложенный запуск
+  Scheduled,
   /// В очереди
   Queued,
   /// Подготовка
@@ -576,6 +837,23 @@ pub struct RenderProgress {
   pub status: RenderStatus,
   /// Дополнительное сообщение
   pub message: Option<String>,
+  /// Прогресс вариантов многовариантного экспорта
+  #[serde(default)]
+  pub renditions: Vec<RenditionProgress>,
+  /// Этапы рендеринга; пусто, если задача не регистрировала этапы
+  #[serde(default)]
+  pub stages: Vec<StageProgress>,
+}
+
+/// Прогресс отдельного варианта многовариантного экспорта
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RenditionProgress {
+  /// Суффикс варианта
+  pub suffix: String,
+  /// Путь к выходному файлу варианта
+  pub output_path: String,
+  /// Процент выполнения (0.0 - 100.0)
+  pub percentage: f32,
 }
 
 impl Default for RenderProgress {
@@ -590,10 +868,117 @@ impl Default for RenderProgress {
       estimated_remaining: None,
       status: RenderStatus::Queued,
       message: None,
+      renditions: Vec::new(),
+      stages: Vec::new(),
     }
   }
 }
 
+/// Статус этапа рендеринга
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StageStatus {
+  /// Еще не начат
+  #[default]
+  Pending,
+  /// Выполняется
+  Running,
+  /// Выполнен
+  Completed,
+  /// Пропущен
+  Skipped,
+  /// Завершен с ошибкой
+  Failed,
+  /// Прерван отменой задачи
+  Cancelled,
+}
+
+/// Прогресс отдельного этапа рендеринга
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StageProgress {
+  /// Название (идентификатор) этапа
+  pub name: String,
+  /// Статус этапа
+  #[serde(default)]
+  pub status: StageStatus,
+  /// Вес этапа в общем прогрессе (оценочная длительность, с)
+  #[serde(default)]
+  pub weight: f64,
+  /// Время начала этапа
+  #[serde(default)]
+  pub started_at: Option<SystemTime>,
+  /// Длительность этапа: текущая для выполняющегося, итоговая для завершенного
+  #[serde(default)]
+  pub elapsed: Duration,
+  /// Доля выполнения этапа (0.0 - 1.0)
+  #[serde(default)]
+  pub fraction: f32,
+}
+
+impl StageProgress {
+  /// Создать этап в ожидании
+  pub fn new(name: impl Into<String>, weight: f64) -> Self {
+    Self {
+      name: name.into(),
+      status: StageStatus::Pending,
+      weight: if weight.is_finite() {
+        weight.max(0.0)
+      } else {
+        0.0
+      },
+      started_at: None,
+      elapsed: Duration::ZERO,
+      fraction: 0.0,
+    }
+  }
+
+  /// Этап больше не выполняется
+  pub fn is_finished(&self) -> bool {
+    !matches!(self.status, StageStatus::Pending | StageStatus::Running)
+  }
+
+  fn finish(&mut self, status: StageStatus) {
+    self.elapsed = self.elapsed_since_start();
+    if matches!(status, StageStatus::Completed | StageStatus::Skipped) {
+      self.fraction = 1.0;
+    }
+    self.status = status;
+  }
+
+  fn elapsed_since_start(&self) -> Duration {
+    self
+      .started_at
+      .and_then(|started_at| SystemTime::now().duration_since(started_at).ok())
+      .unwrap_or(Duration::ZERO)
+  }
+
+  /// Выполненная доля этапа для общего прогресса
+  fn completed_fraction(&self) -> f64 {
+    match self.status {
+      StageStatus::Completed | StageStatus::Skipped => 1.0,
+      _ => f64::from(self.fraction),
+    }
+  }
+}
+
+/// Общий процент по весам этапов; без весов этапы равнозначны
+fn stages_percentage(stages: &[StageProgress]) -> f32 {
+  let total_weight: f64 = stages.iter().map(|stage| stage.weight).sum();
+  let done = if total_weight > 0.0 {
+    stages
+      .iter()
+      .map(|stage| stage.weight * stage.completed_fraction())
+      .sum::<f64>()
+      / total_weight
+  } else {
+    stages
+      .iter()
+      .map(StageProgress::completed_fraction)
+      .sum::<f64>()
+      / stages.len() as f64
+  };
+  (done * 100.0) as f32
+}
+
 /// Обновления прогресса для WebSocket
 #[derive(Debug, Clone, Serialize)]
 #[serde(tag = "type")]
@@ -609,6 +994,8 @@ pub enum ProgressUpdate {
   JobCompleted {
     job_id: String,
     output_path: String,
+    /// Все созданные файлы, включая `output_path`
+    output_files: Vec<String>,
     duration: Duration,
   },
   /// Задача завершилась с ошибкой
@@ -619,6 +1006,17 @@ pub enum ProgressUpdate {
   },
   /// Задача отменена
   JobCancelled { job_id: String },
+  /// Режим кодирования понижен из-за нехватки видеопамяти
+  RenderDowngraded {
+    job_id: String,
+    downgrade: RenderDowngrade,
+    message: String,
+  },
+  /// Отчет о рендере сохранен рядом с выходным файлом
+  ReportReady {
+    job_id: String,
+    report: Box<RenderReport>,
+  },
 }
 
 /// Настройки трекера прогресса
@@ -773,6 +1171,42 @@ mod tests {
     assert_eq!(jobs.len(), 0);
   }
 
+  #[tokio::test]
+  async fn test_completed_job_lists_output_files() {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let tracker = ProgressTracker::new(tx);
+    let job_id = tracker
+      .create_job("Stems".to_string(), "/out/film.mp4".to_string(), 10)
+      .await
+      .unwrap();
+    rx.recv().await.unwrap();
+    tracker
+      .active_jobs
+      .write()
+      .await
+      .get_mut(&job_id)
+      .unwrap()
+      .start()
+      .unwrap();
+
+    tracker
+      .set_job_output_files(&job_id, vec!["/out/film_Dialogue.wav".to_string()])
+      .await
+      .unwrap();
+    tracker
+      .complete_job(&job_id, "/out/film.mp4".to_string())
+      .await
+      .unwrap();
+
+    match rx.recv().await.unwrap() {
+      ProgressUpdate::JobCompleted { output_files, .. } => assert_eq!(
+        output_files,
+        vec!["/out/film.mp4", "/out/film_Dialogue.wav"]
+      ),
+      _ => panic!("Ожидалось JobCompleted"),
+    }
+  }
+
   #[tokio::test]
   async fn test_render_job() {
     let mut job = RenderJob::new(
@@ -901,4 +1335,163 @@ mod tests {
     let update = rx.recv().await.unwrap();
     assert!(matches!(update, ProgressUpdate::JobCancelled { .. }));
   }
+
+  #[tokio::test]
+  async fn test_record_downgrade() {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let tracker = ProgressTracker::new(tx);
+
+    let job_id = tracker
+      .create_job("GPU".to_string(), "/test/output.mp4".to_string(), 100)
+      .await
+      .unwrap();
+    rx.recv().await;
+
+    tracker
+      .record_downgrade(&job_id, RenderDowngrade::CpuEncoder)
+      .await
+      .unwrap();
+
+    let update = rx.recv().await.unwrap();
+    assert!(matches!(
+      update,
+      ProgressUpdate::RenderDowngraded {
+        downgrade: RenderDowngrade::CpuEncoder,
+        ..
+      }
+    ));
+    let job = tracker.get_job(&job_id).await.unwrap();
+    assert_eq!(job.downgrades, vec![RenderDowngrade::CpuEncoder]);
+
+    assert!(tracker
+      .record_downgrade("missing", RenderDowngrade::CpuEncoder)
+      .await
+      .is_err());
+  }
+
+  #[tokio::test]
+  async fn test_stage_progress_three_stage_run() {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let tracker = ProgressTracker::new(tx);
+    let job_id = tracker
+      .create_job("Stages".to_string(), "/test/output.mp4".to_string(), 100)
+      .await
+      .unwrap();
+    rx.recv().await.unwrap();
+
+    tracker
+      .register_stages(
+        &job_id,
+        vec![
+          ("Validation".to_string(), 10.0),
+          ("Encoding".to_string(), 80.0),
+          ("Finalization".to_string(), 10.0),
+        ],
+      )
+      .await
+      .unwrap();
+    tracker.start_job(&job_id).await.unwrap();
+
+    tracker
+      .update_stage_progress(&job_id, "Validation", 0.5, None)
+      .await
+      .unwrap();
+    tracker
+      .finish_stage(&job_id, "Validation", StageStatus::Completed)
+      .await
+      .unwrap();
+    tracker
+      .update_stage_progress(&job_id, "Encoding", 0.5, None)
+      .await
+      .unwrap();
+    // Доля этапа не откатывается назад
+    tracker
+      .update_stage_progress(&job_id, "Encoding", 0.25, None)
+      .await
+      .unwrap();
+
+    let progress = tracker.get_job(&job_id).await.unwrap().get_progress();
+    assert_eq!(progress.stage, "Encoding");
+    assert_eq!(progress.percentage, 50.0);
+    let statuses: Vec<_> = progress.stages.iter().map(|stage| stage.status).collect();
+    assert_eq!(
+      statuses,
+      vec![
+        StageStatus::Completed,
+        StageStatus::Running,
+        StageStatus::Pending
+      ]
+    );
+    assert!(progress.stages[1].started_at.is_some());
+
+    // Переход к следующему этапу завершает текущий
+    tracker
+      .update_stage_progress(&job_id, "Finalization", 1.0, None)
+      .await
+      .unwrap();
+    tracker
+      .complete_job(&job_id, "/test/output.mp4".to_string())
+      .await
+      .unwrap();
+
+    let mut percentages = Vec::new();
+    while let Ok(update) = rx.try_recv() {
+      if let ProgressUpdate::ProgressChanged { progress, .. } = update {
+        percentages.push(progress.percentage);
+        if progress.stage == "Finalization" {
+          assert!(progress.stages[..2]
+            .iter()
+            .all(|stage| stage.status == StageStatus::Completed));
+        }
+      }
+    }
+    assert_eq!(percentages, vec![5.0, 10.0, 50.0, 50.0, 100.0]);
+    assert!(percentages.windows(2).all(|pair| pair[1] >= pair[0]));
+
+    assert!(tracker
+      .update_stage_progress("missing", "Encoding", 0.5, None)
+      .await
+      .is_err());
+  }
+
+  #[test]
+  fn test_stage_progress_without_weights_and_status_on_failure() {
+    let mut job = RenderJob::new(
+      "stages".to_string(),
+      "Stages".to_string(),
+      "/test/output.mp4".to_string(),
+      0,
+    );
+    job.register_stages(vec![
+      ("A".to_string(), 0.0),
+      ("B".to_string(), 0.0),
+      ("C".to_string(), 0.0),
+    ]);
+    job.start().unwrap();
+
+    // Без весов этапы равнозначны; пропущенный этап считается выполненным
+    job.update_stage("B", 0.5, None).unwrap();
+    assert_eq!(job.stages[0].status, StageStatus::Skipped);
+    assert_eq!(job.get_progress().percentage, 50.0);
+    assert!(job.update_stage("D", 0.5, None).is_err());
+
+    job.fail("encoder crashed".to_string()).unwrap();
+    let statuses: Vec<_> = job.stages.iter().map(|stage| stage.status).collect();
+    assert_eq!(
+      statuses,
+      vec![
+        StageStatus::Skipped,
+        StageStatus::Failed,
+        StageStatus::Pending
+      ]
+    );
+  }
+
+  #[test]
+  fn test_render_progress_deserializes_without_stages() {
+    let mut value = serde_json::to_value(RenderProgress::default()).unwrap();
+    value.as_object_mut().unwrap().remove("stages");
+    let progress: RenderProgress = serde_json::from_value(value).unwrap();
+    assert!(progress.stages.is_empty());
+  }
 }
@@ -0,0 +1,169 @@
+//! Render Priority - Приоритет процессов FFmpeg при рендеринге
+//!
+//! Фоновый рендеринг не должен замедлять работу всей системы: процесс FFmpeg
+//! запускается с пониженным приоритетом ОС (nice +10 в Unix,
+//! `BELOW_NORMAL_PRIORITY_CLASS` в Windows) и ограничивается половиной ядер.
+//! Итоговые значения сохраняются в задаче рендеринга.
+
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+
+/// Значение nice для фонового рендеринга в Unix
+pub const BACKGROUND_NICENESS: i32 = 10;
+
+/// `BELOW_NORMAL_PRIORITY_CLASS` из WinAPI
+#[cfg(windows)]
+const BELOW_NORMAL_PRIORITY_CLASS: u32 = 0x0000_4000;
+
+/// Приоритет рендеринга
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RenderPriority {
+  /// Все ядра, приоритет ОС не меняется
+  High,
+  /// Поведение FFmpeg по умолчанию
+  #[default]
+  Normal,
+  /// Пониженный приоритет ОС и примерно половина ядер
+  Background,
+}
+
+impl RenderPriority {
+  /// Итоговые ограничения процесса для `cpu_count` ядер.
+  ///
+  /// Явно заданное число потоков имеет приоритет над значением уровня.
+  pub fn limits_for(self, thread_override: Option<usize>, cpu_count: usize) -> ProcessLimits {
+    let cpu_count = cpu_count.max(1);
+    let threads = match self {
+      Self::High => Some(cpu_count),
+      Self::Normal => None,
+      Self::Background => Some(cpu_count.div_ceil(2)),
+    };
+
+    ProcessLimits {
+      priority: self,
+      threads: thread_override.map(|threads| threads.max(1)).or(threads),
+      niceness: match self {
+        Self::Background => BACKGROUND_NICENESS,
+        Self::High | Self::Normal => 0,
+      },
+    }
+  }
+
+  /// Итоговые ограничения процесса для ядер текущей машины
+  pub fn limits(self, thread_override: Option<usize>) -> ProcessLimits {
+    self.limits_for(thread_override, num_cpus::get())
+  }
+}
+
+/// Итоговые ограничения процесса FFmpeg
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ProcessLimits {
+  pub priority: RenderPriority,
+  /// Значение `-threads` (`None` - выбор FFmpeg)
+  pub threads: Option<usize>,
+  /// Повышение nice процесса (0 - приоритет не меняется)
+  pub niceness: i32,
+}
+
+impl ProcessLimits {
+  /// Аргументы ограничения потоков для выхода FFmpeg
+  pub fn thread_args(&self) -> Vec<String> {
+    match self.threads {
+      Some(threads) => vec!["-threads".to_string(), threads.to_string()],
+      None => Vec::new(),
+    }
+  }
+
+  /// Понизить приоритет ОС для запускаемого процесса
+  pub fn apply_os_priority(&self, cmd: &mut Command) {
+    if self.niceness <= 0 {
+      return;
+    }
+
+    #[cfg(unix)]
+    {
+      let niceness = self.niceness;
+      // SAFETY: между fork и exec вызывается только async-signal-safe setpriority
+      unsafe {
+        cmd.pre_exec(move || {
+          // Повышение nice не требует прав; при ошибке процесс
+          // запускается с обычным приоритетом
+          libc::setpriority(libc::PRIO_PROCESS as _, 0, niceness);
+          Ok(())
+        });
+      }
+    }
+
+    #[cfg(windows)]
+    {
+      cmd.creation_flags(BELOW_NORMAL_PRIORITY_CLASS);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_background_limits() {
+    let limits = RenderPriority::Background.limits_for(None, 8);
+    assert_eq!(limits.threads, Some(4));
+    assert_eq!(limits.niceness, BACKGROUND_NICENESS);
+    assert_eq!(limits.thread_args(), vec!["-threads", "4"]);
+
+    // На одноядерной машине остается один поток
+    assert_eq!(
+      RenderPriority::Background.limits_for(None, 1).threads,
+      Some(1)
+    );
+  }
+
+  #[test]
+  fn test_high_and_normal_limits() {
+    let high = RenderPriority::High.limits_for(None, 8);
+    assert_eq!(high.threads, Some(8));
+    assert_eq!(high.niceness, 0);
+
+    let normal = RenderPriority::Normal.limits_for(None, 8);
+    assert_eq!(normal, ProcessLimits::default());
+    assert!(normal.thread_args().is_empty());
+  }
+
+  #[test]
+  fn test_thread_override() {
+    let limits = RenderPriority::Background.limits_for(Some(3), 16);
+    assert_eq!(limits.threads, Some(3));
+    assert_eq!(limits.niceness, BACKGROUND_NICENESS);
+
+    assert_eq!(
+      RenderPriority::Normal.limits_for(Some(0), 4).threads,
+      Some(1)
+    );
+  }
+
+  #[cfg(unix)]
+  #[tokio::test]
+  async fn test_background_priority_hook_installed() {
+    let limits = RenderPriority::Background.limits_for(None, 2);
+    // Без аргументов `nice` выводит текущее значение nice процесса
+    let mut cmd = Command::new("nice");
+    limits.apply_os_priority(&mut cmd);
+
+    let Ok(output) = cmd.output().await else {
+      return;
+    };
+    let niceness: i32 = String::from_utf8_lossy(&output.stdout)
+      .trim()
+      .parse()
+      .unwrap();
+
+    let mut baseline = Command::new("nice");
+    let baseline: i32 = String::from_utf8_lossy(&baseline.output().await.unwrap().stdout)
+      .trim()
+      .parse()
+      .unwrap();
+    assert_eq!(niceness, (baseline + BACKGROUND_NICENESS).min(19));
+  }
+}
@@ -7,8 +7,11 @@ use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::{mpsc, RwLock};
+use tokio_util::sync::CancellationToken;
 
 use crate::video_compiler::cache::RenderCache;
+use crate::video_compiler::core::gpu_memory::RenderDowngrade;
+use crate::video_compiler::core::render_priority::ProcessLimits;
 use crate::video_compiler::error::{
   DetailedResult, OperationMetadata, ResourceUsage, Result, VideoCompilerError,
 };
@@ -25,14 +28,18 @@ pub struct VideoRenderer {
   project: ProjectSchema,
   /// Настройки компилятора
   settings: Arc<RwLock<CompilerSettings>>,
-  /// Кэш рендеринга
-  _cache: Arc<RwLock<RenderCache>>,
+  /// Кэш рендеринга (сегменты прошлых экспортов)
+  cache: Arc<RwLock<RenderCache>>,
   /// Трекер прогресса
   progress_tracker: Arc<ProgressTracker>,
   /// Построитель команд FFmpeg
-  ffmpeg_builder: FFmpegBuilder,
+  _ffmpeg_builder: FFmpegBuilder,
   /// Текущий pipeline рендеринга
   current_pipeline: Option<RenderPipeline>,
+  /// Приоритет и ограничение потоков FFmpeg
+  process_limits: ProcessLimits,
+  /// Токен отмены текущего рендеринга, передается во все этапы конвейера
+  cancellation: CancellationToken,
 }
 
 impl VideoRenderer {
@@ -52,40 +59,63 @@ impl VideoRenderer {
     Ok(Self {
       project,
       settings,
-      _cache: cache,
+      cache,
       progress_tracker,
-      ffmpeg_builder,
+      _ffmpeg_builder: ffmpeg_builder,
       current_pipeline: None,
+      process_limits: ProcessLimits::default(),
+      cancellation: CancellationToken::new(),
     })
   }
 
+  /// Запускать FFmpeg с заданным приоритетом и числом потоков
+  pub fn with_process_limits(mut self, limits: ProcessLimits) -> Self {
+    self.process_limits = limits;
+    self
+  }
+
+  /// Приоритет и ограничение потоков FFmpeg
+  pub fn process_limits(&self) -> ProcessLimits {
+    self.process_limits
+  }
+
   /// Запустить рендеринг видео
   pub async fn render(&mut self, output_path: &Path) -> Result<String> {
     let job_id = self.create_render_job(output_path).await?;
 
+    // После отмены следующий рендеринг получает новый токен
+    if self.cancellation.is_cancelled() {
+      self.cancellation = CancellationToken::new();
+    }
+
     // Запускаем рендеринг в отдельной задаче
     let job_id_clone = job_id.clone();
     let project = self.project.clone();
     let output_path = output_path.to_owned();
     let progress_tracker = self.progress_tracker.clone();
-    let ffmpeg_builder = self.ffmpeg_builder.clone();
+    let cancellation = self.cancellation.clone();
     let settings = self.settings.clone();
+    let cache = self.cache.clone();
+    let process_limits = self.process_limits;
 
     // Клонируем необходимые значения перед перемещением в замыкание
     let progress_tracker_clone = progress_tracker.clone();
     let progress_tracker_clone2 = progress_tracker.clone();
     let project_clone = project.clone();
     let output_path_clone = output_path.clone();
-    let ffmpeg_builder_clone = ffmpeg_builder.clone();
+    let cancellation_clone = cancellation.clone();
     let settings_clone = settings.clone();
+    let cache_clone = cache.clone();
 
     tokio::spawn(async move {
       let result = Self::render_internal(
         project,
         output_path,
         progress_tracker,
-        ffmpeg_builder,
+        cancellation,
         settings,
+        cache,
+        process_limits,
         job_id_clone.clone(), // Передаем job_id
       )
       .await;
@@ -133,8 +163,10 @@ impl VideoRenderer {
               cpu_project,
               output_path_clone,
               progress_tracker_clone2,
-              ffmpeg_builder_clone,
+              cancellation_clone,
               settings_clone,
+              cache_clone,
+              process_limits,
               job_id_clone.clone(),
             )
             .await;
@@ -194,8 +226,10 @@ impl VideoRenderer {
     project: ProjectSchema,
     output_path: PathBuf,
     progress_tracker: Arc<ProgressTracker>,
-    _ffmpeg_builder: FFmpegBuilder,
+    cancellation: CancellationToken,
     settings: Arc<RwLock<CompilerSettings>>,
+    cache: Arc<RwLock<RenderCache>>,
+    process_limits: ProcessLimits,
     job_id: String, // Добавляем job_id как параметр
   ) -> Result<String> {
     log::info!(
@@ -211,7 +245,10 @@ impl VideoRenderer {
       settings,
       output_path.clone(),
     )
-    .await?;
+    .await?
+    .with_render_cache(cache)
+    .with_process_limits(process_limits)
+    .with_cancellation_token(cancellation);
 
     // Используем переданный job_id вместо поиска
     // Это исправляет проблему с двойной системой отслеживания задач
@@ -226,8 +263,11 @@ impl VideoRenderer {
 
   /// Отменить рендеринг
   pub async fn cancel(&mut self) -> Result<()> {
+    // Токен прерывает этапы конвейера и запущенные процессы FFmpeg
+    self.cancellation.cancel();
+
     // Отменяем текущий pipeline если он существует
-    if let Some(ref mut pipeline) = self.current_pipeline {
+    if let Some(ref pipeline) = self.current_pipeline {
       pipeline.cancel().await?;
     }
 
@@ -245,6 +285,15 @@ impl VideoRenderer {
     jobs.first().map(|job| job.get_progress())
   }
 
+  /// Понижения режима кодирования текущей задачи из-за нехватки видеопамяти
+  pub async fn get_downgrades(&self) -> Vec<RenderDowngrade> {
+    let jobs = self.progress_tracker.get_active_jobs().await;
+    jobs
+      .first()
+      .map(|job| job.downgrades.clone())
+      .unwrap_or_default()
+  }
+
   /// Получить статистику рендеринга из pipeline
   pub fn get_render_statistics(
     &self,
@@ -353,7 +402,10 @@ mod tests {
   use super::*;
   use crate::video_compiler::cache::RenderCache;
   use crate::video_compiler::progress::ProgressUpdate;
-  use crate::video_compiler::schema::{ExportSettings, OutputFormat, Track, TrackType};
+  use crate::video_compiler::schema::{
+    ExportSettings, HdrMode, MissingAssetPolicy, OutputFormat, RenderReportSettings, StemFormat,
+    SubtitleMode, Track, TrackType,
+  };
   use std::sync::Arc;
   use tempfile::TempDir;
   use tokio::sync::{mpsc, RwLock};
@@ -395,6 +447,27 @@ mod tests {
       normalize_audio: Some(false),
       audio_target: None,
       audio_peak: None,
+      subtitle_mode: SubtitleMode::Burn,
+      subtitle_language: None,
+      renditions: Vec::new(),
+      generate_hls: false,
+      audio_bit_depth: None,
+      color_primaries: None,
+      transfer_characteristics: None,
+      matrix_coefficients: None,
+      hdr_mode: HdrMode::None,
+      hdr_metadata: None,
+      render_nested_as_intermediate: false,
+      filename_template: None,
+      export_preset: None,
+      segment_cache: None,
+      watermark: None,
+      timecode_burn_in: None,
+      write_timecode_track: false,
+      export_stems: false,
+      stems_format: StemFormat::Wav,
+      missing_assets: MissingAssetPolicy::Warn,
+      render_report: RenderReportSettings::default(),
     };
 
     // Устанавливаем продолжительность и разрешение
@@ -451,10 +524,12 @@ mod tests {
       track_type: TrackType::Video,
       enabled: true,
       locked: false,
+      hidden: false,
       clips: Vec::new(),
       volume: 1.0,
       effects: Vec::new(),
       filters: Vec::new(),
+      ducking: None,
     };
 
     let clip = crate::video_compiler::schema::Clip {
@@ -474,6 +549,18 @@ mod tests {
       crop: None,
       transform: None,
       audio_track_index: None,
+      audio_stream_index: None,
+      source_rotation: 0,
+      color_label: None,
+      notes: None,
+      tags: Vec::new(),
+      fade_in: None,
+      fade_out: None,
+      link_group_id: None,
+      source_timecode: None,
+      volume: 1.0,
+      external_audio: None,
+      locked: false,
       properties: Default::default(),
     };
 
@@ -515,6 +602,7 @@ mod tests {
     // Отмена рендеринга не должна вызывать ошибку, даже если нет активных задач
     let result = renderer.cancel().await;
     assert!(result.is_ok());
+    assert!(renderer.cancellation.is_cancelled());
   }
 
   #[tokio::test]
@@ -0,0 +1,572 @@
+//! Segment Cache - Кэш сегментов рендера для повторных экспортов
+//!
+//! Timeline делится на сегменты по границам клипов. Для каждого сегмента
+//! считается хеш содержимого: клипы, их эффекты и фильтры, переходы, субтитры
+//! и настройки экспорта. Сегмент с тем же хешем из предыдущего экспорта
+//! берется из [`RenderCache`] и склеивается concat demuxer'ом без
+//! перекодирования, поэтому после правки одного клипа перекодируется только
+//! его сегмент.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::video_compiler::cache::{RenderCache, RenderCacheData};
+use crate::video_compiler::core::temp_storage;
+use crate::video_compiler::error::{Result, VideoCompilerError};
+use crate::video_compiler::schema::{
+  Clip, ClipSource, ExportSettings, OutputFormat, ProjectSchema, Track,
+};
+
+/// Префикс ключей сегментов в кэше рендеринга
+pub const SEGMENT_KEY_PREFIX: &str = "segments/";
+
+/// Допуск при сравнении границ сегментов (секунды)
+const BOUNDARY_EPSILON: f64 = 1e-6;
+
+/// Сегмент timeline между соседними границами клипов
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RenderSegment {
+  /// Порядковый номер сегмента
+  pub index: usize,
+  /// Начало на timeline (секунды)
+  pub start_time: f64,
+  /// Конец на timeline (секунды)
+  pub end_time: f64,
+  /// Клипы, попадающие в сегмент
+  pub clip_ids: Vec<String>,
+  /// Хеш содержимого сегмента
+  pub content_hash: String,
+}
+
+impl RenderSegment {
+  /// Длительность сегмента
+  pub fn duration(&self) -> f64 {
+    self.end_time - self.start_time
+  }
+
+  /// Ключ сегмента в кэше рендеринга
+  pub fn cache_key(&self) -> String {
+    format!("{SEGMENT_KEY_PREFIX}{}", self.content_hash)
+  }
+}
+
+/// Что делать с сегментом при экспорте
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SegmentAction {
+  /// Взять готовый файл из предыдущего экспорта
+  Reuse(PathBuf),
+  /// Закодировать заново
+  Encode,
+}
+
+/// Сегмент с решением о переиспользовании
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlannedSegment {
+  /// Сегмент
+  pub segment: RenderSegment,
+  /// Решение
+  pub action: SegmentAction,
+}
+
+impl PlannedSegment {
+  /// Будет ли сегмент перекодирован
+  pub fn needs_encoding(&self) -> bool {
+    self.action == SegmentAction::Encode
+  }
+}
+
+/// Статистика кэша сегментов за один экспорт
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SegmentCacheStats {
+  /// Всего сегментов
+  pub total_segments: usize,
+  /// Переиспользовано из кэша
+  pub reused_segments: usize,
+  /// Закодировано заново
+  pub encoded_segments: usize,
+  /// Доля переиспользованных сегментов (0.0 - 1.0)
+  pub reuse_ratio: f32,
+}
+
+impl SegmentCacheStats {
+  /// Посчитать статистику по плану сегментов
+  pub fn from_plan(plan: &[PlannedSegment]) -> Self {
+    let total_segments = plan.len();
+    let encoded_segments = plan
+      .iter()
+      .filter(|planned| planned.needs_encoding())
+      .count();
+    let reused_segments = total_segments - encoded_segments;
+
+    Self {
+      total_segments,
+      reused_segments,
+      encoded_segments,
+      reuse_ratio: if total_segments == 0 {
+        0.0
+      } else {
+        reused_segments as f32 / total_segments as f32
+      },
+    }
+  }
+}
+
+/// Можно ли экспортировать проект посегментно.
+///
+/// Варианты экспорта, HLS, отдельная дорожка субтитров, вложенные
+/// последовательности, водяной знак, GIF и аудиоформаты рендерятся одной
+/// командой.
+pub fn supports_segment_cache(project: &ProjectSchema) -> bool {
+  let export = &project.settings.export;
+  let format = &project.settings.output.format;
+
+  export.segment_cache_enabled()
+    && export.renditions.is_empty()
+    && !export.generate_hls
+    && !export.subtitle_mode.muxes()
+    && export.watermark.is_none()
+    && !project.has_sequence_clips()
+    && !format.is_audio_only()
+    && !matches!(format, OutputFormat::Gif)
+}
+
+/// Разбить timeline на сегменты по границам клипов.
+///
+/// Клипы, связанные переходом, попадают в один сегмент: переход нельзя
+/// разрезать границей склейки. Промежутки без клипов сегментов не образуют.
+pub fn plan_segments(project: &ProjectSchema) -> Vec<RenderSegment> {
+  let clips: Vec<(&Track, &Clip)> = project
+    .tracks
+    .iter()
+    .flat_map(|track| track.clips.iter().map(move |clip| (track, clip)))
+    .filter(|(_, clip)| clip.end_time - clip.start_time > BOUNDARY_EPSILON)
+    .collect();
+
+  let transition_spans: Vec<(f64, f64)> = project
+    .transitions
+    .iter()
+    .filter(|transition| transition.enabled)
+    .filter_map(|transition| {
+      let from = find_clip(&clips, transition.from_clip_id.as_deref()?)?;
+      let to = find_clip(&clips, transition.to_clip_id.as_deref()?)?;
+      Some((
+        from.start_time.min(to.start_time),
+        from.end_time.max(to.end_time),
+      ))
+    })
+    .collect();
+
+  let mut boundaries: Vec<f64> = clips
+    .iter()
+    .flat_map(|(_, clip)| [clip.start_time, clip.end_time])
+    .filter(|time| {
+      !transition_spans
+        .iter()
+        .any(|(start, end)| *time > start + BOUNDARY_EPSILON && *time < end - BOUNDARY_EPSILON)
+    })
+    .collect();
+  boundaries.sort_by(f64::total_cmp);
+  boundaries.dedup_by(|a, b| (*a - *b).abs() < BOUNDARY_EPSILON);
+
+  let mut segments = Vec::new();
+  for window in boundaries.windows(2) {
+    let (start_time, end_time) = (window[0], window[1]);
+    let segment_clips: Vec<(&Track, &Clip)> = clips
+      .iter()
+      .filter(|(_, clip)| {
+        clip.start_time < end_time - BOUNDARY_EPSILON
+          && clip.end_time > start_time + BOUNDARY_EPSILON
+      })
+      .copied()
+      .collect();
+
+    if segment_clips.is_empty() {
+      continue;
+    }
+
+    segments.push(RenderSegment {
+      index: segments.len(),
+      start_time,
+      end_time,
+      clip_ids: segment_clips
+        .iter()
+        .map(|(_, clip)| clip.id.clone())
+        .collect(),
+      content_hash: segment_hash(project, start_time, end_time, &segment_clips),
+    });
+  }
+
+  segments
+}
+
+/// Найти клип по ID
+fn find_clip<'a>(clips: &[(&Track, &'a Clip)], id: &str) -> Option<&'a Clip> {
+  clips
+    .iter()
+    .find(|(_, clip)| clip.id == id)
+    .map(|(_, clip)| *clip)
+}
+
+/// Хеш всего, что влияет на изображение и звук сегмента
+fn segment_hash(
+  project: &ProjectSchema,
+  start_time: f64,
+  end_time: f64,
+  clips: &[(&Track, &Clip)],
+) -> String {
+  let clip_ids: HashSet<&str> = clips.iter().map(|(_, clip)| clip.id.as_str()).collect();
+  let effect_ids: HashSet<&str> = clips
+    .iter()
+    .flat_map(|(track, clip)| track.effects.iter().chain(&clip.effects))
+    .map(String::as_str)
+    .collect();
+  let filter_ids: HashSet<&str> = clips
+    .iter()
+    .flat_map(|(track, clip)| track.filters.iter().chain(&clip.filters))
+    .map(String::as_str)
+    .collect();
+  let template_ids: HashSet<&str> = clips
+    .iter()
+    .filter_map(|(_, clip)| clip.template_id.as_deref())
+    .collect();
+
+  let tracks: Vec<_> = clips
+    .iter()
+    .map(|(track, clip)| {
+      (
+        &track.id,
+        &track.track_type,
+        track.enabled,
+        track.hidden,
+        track.volume,
+        &track.ducking,
+        clip,
+        source_fingerprint(clip),
+      )
+    })
+    .collect();
+  let effects: Vec<_> = project
+    .effects
+    .iter()
+    .filter(|effect| effect_ids.contains(effect.id.as_str()))
+    .collect();
+  let filters: Vec<_> = project
+    .filters
+    .iter()
+    .filter(|filter| filter_ids.contains(filter.id.as_str()))
+    .collect();
+  let templates: Vec<_> = project
+    .templates
+    .iter()
+    .filter(|template| template_ids.contains(template.id.as_str()))
+    .collect();
+  let transitions: Vec<_> = project
+    .transitions
+    .iter()
+    .filter(|transition| {
+      [&transition.from_clip_id, &transition.to_clip_id]
+        .into_iter()
+        .flatten()
+        .any(|id| clip_ids.contains(id.as_str()))
+    })
+    .collect();
+  let subtitles: Vec<_> = project
+    .subtitles
+    .iter()
+    .filter(|subtitle| subtitle.start_time < end_time && subtitle.end_time > start_time)
+    .collect();
+
+  let settings = &project.settings;
+  let content = serde_json::to_vec(&(
+    start_time,
+    end_time,
+    tracks,
+    effects,
+    filters,
+    templates,
+    transitions,
+    subtitles,
+    &project.style_templates,
+    (
+      content_export_settings(&settings.export),
+      &settings.output,
+      &settings.resolution,
+      settings.frame_rate,
+      &settings.aspect_ratio,
+    ),
+    (
+      project.timeline.fps,
+      project.timeline.resolution,
+      project.timeline.sample_rate,
+    ),
+  ))
+  .unwrap_or_default();
+  format!("{:016x}", xxhash_rust::xxh3::xxh3_64(&content))
+}
+
+/// Настройки экспорта без полей, не влияющих на содержимое файла
+fn content_export_settings(export: &ExportSettings) -> ExportSettings {
+  ExportSettings {
+    filename_template: None,
+    export_preset: None,
+    segment_cache: None,
+    ..export.clone()
+  }
+}
+
+/// Размер и время изменения исходного файла: замена файла на диске
+/// под тем же именем делает сегмент устаревшим
+fn source_fingerprint(clip: &Clip) -> Option<(u64, u64)> {
+  let ClipSource::File(path) = &clip.source else {
+    return None;
+  };
+  let metadata = std::fs::metadata(path).ok()?;
+  let modified = metadata
+    .modified()
+    .ok()?
+    .duration_since(UNIX_EPOCH)
+    .ok()?
+    .as_secs();
+  Some((metadata.len(), modified))
+}
+
+/// Проверить кэш для каждого сегмента.
+///
+/// Сегмент переиспользуется, только если файл предыдущего экспорта
+/// существует и его размер совпадает с сохраненным.
+pub async fn resolve_segments(
+  cache: &mut RenderCache,
+  segments: Vec<RenderSegment>,
+) -> Vec<PlannedSegment> {
+  let mut plan = Vec::with_capacity(segments.len());
+
+  for segment in segments {
+    let action = match cache.get_render_data(&segment.cache_key()).await {
+      Some(data) if is_reusable(&data).await => SegmentAction::Reuse(data.output_path),
+      _ => SegmentAction::Encode,
+    };
+    plan.push(PlannedSegment { segment, action });
+  }
+
+  plan
+}
+
+/// Файл сегмента на месте и не изменился
+async fn is_reusable(data: &RenderCacheData) -> bool {
+  tokio::fs::metadata(&data.output_path)
+    .await
+    .is_ok_and(|metadata| metadata.is_file() && metadata.len() == data.file_size)
+}
+
+/// Сохранить закодированный сегмент в кэш
+pub async fn store_segment(
+  cache: &mut RenderCache,
+  segment: &RenderSegment,
+  output_path: &Path,
+) -> Result<()> {
+  let file_size = tokio::fs::metadata(output_path)
+    .await
+    .map_err(|e| VideoCompilerError::IoError(e.to_string()))?
+    .len();
+
+  cache
+    .store_render_data(
+      segment.cache_key(),
+      RenderCacheData {
+        cache_key: segment.cache_key(),
+        output_path: output_path.to_path_buf(),
+        render_hash: segment.content_hash.clone(),
+        created_at: SystemTime::now(),
+        file_size,
+      },
+    )
+    .await
+}
+
+/// Директория файлов сегментов. Сегменты переживают задачу экспорта,
+/// поэтому не хранятся во временной директории конвейера.
+pub fn segment_cache_dir(temp_root: &Path) -> PathBuf {
+  temp_root.join(temp_storage::SEGMENTS_DIR)
+}
+
+/// Путь к файлу сегмента
+pub fn segment_output_path(dir: &Path, segment: &RenderSegment, extension: &str) -> PathBuf {
+  dir.join(format!("{}.{extension}", segment.content_hash))
+}
+
+/// Список файлов для concat demuxer
+pub fn build_concat_list(paths: &[PathBuf]) -> String {
+  paths
+    .iter()
+    .map(|path| {
+      // Одинарные кавычки внутри пути экранируются как '\''
+      let escaped = path.to_string_lossy().replace('\'', "'\\''");
+      format!("file '{escaped}'\n")
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::video_compiler::schema::{ColorCorrection, TrackType, Transition, TransitionDuration};
+  use std::collections::HashMap;
+  use tempfile::TempDir;
+
+  fn three_clip_project() -> ProjectSchema {
+    let mut project = ProjectSchema::new("Segments".to_string());
+    let mut track = Track::new(TrackType::Video, "Video".to_string());
+    for (index, start) in [0.0, 5.0, 10.0].into_iter().enumerate() {
+      let mut clip = Clip::new(PathBuf::from(format!("/media/clip{index}.mp4")), start, 5.0);
+      clip.id = format!("clip{index}");
+      track.clips.push(clip);
+    }
+    project.tracks.push(track);
+    project
+  }
+
+  /// Закэшировать все сегменты плана, как после первого экспорта
+  async fn cache_all(cache: &mut RenderCache, segments: &[RenderSegment], dir: &TempDir) {
+    for segment in segments {
+      let path = segment_output_path(dir.path(), segment, "mp4");
+      tokio::fs::write(&path, vec![0u8; 128]).await.unwrap();
+      store_segment(cache, segment, &path).await.unwrap();
+    }
+  }
+
+  #[test]
+  fn test_segments_follow_clip_boundaries() {
+    let segments = plan_segments(&three_clip_project());
+
+    assert_eq!(segments.len(), 3);
+    assert_eq!(segments[1].start_time, 5.0);
+    assert_eq!(segments[1].end_time, 10.0);
+    assert_eq!(segments[1].clip_ids, vec!["clip1".to_string()]);
+  }
+
+  #[tokio::test]
+  async fn test_editing_one_clip_reencodes_only_its_segment() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut cache = RenderCache::new();
+    let mut project = three_clip_project();
+    cache_all(&mut cache, &plan_segments(&project), &temp_dir).await;
+
+    project.tracks[0].clips[1].color_correction = Some(ColorCorrection {
+      brightness: 0.2,
+      ..Default::default()
+    });
+    let plan = resolve_segments(&mut cache, plan_segments(&project)).await;
+
+    let encoded: Vec<usize> = plan
+      .iter()
+      .filter(|planned| planned.needs_encoding())
+      .map(|planned| planned.segment.index)
+      .collect();
+    assert_eq!(encoded, vec![1]);
+
+    let stats = SegmentCacheStats::from_plan(&plan);
+    assert_eq!(stats.reused_segments, 2);
+    assert_eq!(stats.encoded_segments, 1);
+    assert!((stats.reuse_ratio - 2.0 / 3.0).abs() < f32::EPSILON);
+  }
+
+  #[tokio::test]
+  async fn test_export_settings_change_invalidates_all_segments() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut cache = RenderCache::new();
+    let mut project = three_clip_project();
+    cache_all(&mut cache, &plan_segments(&project), &temp_dir).await;
+
+    project.settings.export.video_bitrate += 1000;
+    let plan = resolve_segments(&mut cache, plan_segments(&project)).await;
+    assert!(plan.iter().all(PlannedSegment::needs_encoding));
+
+    // Имя файла и пресет на содержимое не влияют
+    let mut renamed = three_clip_project();
+    renamed.settings.export.filename_template = Some("{project}".to_string());
+    let plan = resolve_segments(&mut cache, plan_segments(&renamed)).await;
+    assert!(!plan.iter().any(PlannedSegment::needs_encoding));
+  }
+
+  #[tokio::test]
+  async fn test_changed_or_missing_segment_file_is_reencoded() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut cache = RenderCache::new();
+    let project = three_clip_project();
+    let segments = plan_segments(&project);
+    cache_all(&mut cache, &segments, &temp_dir).await;
+
+    let truncated = segment_output_path(temp_dir.path(), &segments[0], "mp4");
+    tokio::fs::write(&truncated, vec![0u8; 16]).await.unwrap();
+    let removed = segment_output_path(temp_dir.path(), &segments[2], "mp4");
+    tokio::fs::remove_file(&removed).await.unwrap();
+
+    let plan = resolve_segments(&mut cache, segments).await;
+    assert!(plan[0].needs_encoding());
+    assert_eq!(
+      plan[1].action,
+      SegmentAction::Reuse(segment_output_path(
+        temp_dir.path(),
+        &plan[1].segment,
+        "mp4"
+      ))
+    );
+    assert!(plan[2].needs_encoding());
+  }
+
+  #[test]
+  fn test_transition_keeps_clips_in_one_segment() {
+    let mut project = three_clip_project();
+    project.transitions.push(Transition {
+      id: "fade".to_string(),
+      transition_type: "fade".to_string(),
+      name: "Fade".to_string(),
+      duration: TransitionDuration {
+        value: 1.0,
+        min: None,
+        max: None,
+      },
+      category: None,
+      tags: Vec::new(),
+      complexity: None,
+      enabled: true,
+      parameters: HashMap::new(),
+      ffmpeg_command: None,
+      easing: None,
+      direction: None,
+      from_clip_id: Some("clip0".to_string()),
+      to_clip_id: Some("clip1".to_string()),
+    });
+
+    let segments = plan_segments(&project);
+    assert_eq!(segments.len(), 2);
+    assert_eq!(segments[0].end_time, 10.0);
+    assert_eq!(
+      segments[0].clip_ids,
+      vec!["clip0".to_string(), "clip1".to_string()]
+    );
+  }
+
+  #[test]
+  fn test_cbr_and_renditions_disable_segment_cache() {
+    let mut project = three_clip_project();
+    assert!(supports_segment_cache(&project));
+
+    project.settings.export.rate_control_mode = Some("cbr".to_string());
+    assert!(!supports_segment_cache(&project));
+
+    project.settings.export.segment_cache = Some(true);
+    assert!(supports_segment_cache(&project));
+
+    project.settings.output.format = OutputFormat::Gif;
+    assert!(!supports_segment_cache(&project));
+  }
+
+  #[test]
+  fn test_concat_list_escapes_quotes() {
+    let list = build_concat_list(&[PathBuf::from("/tmp/a.mp4"), PathBuf::from("/tmp/it's.mp4")]);
+    assert_eq!(list, "file '/tmp/a.mp4'\nfile '/tmp/it'\\''s.mp4'\n");
+  }
+}
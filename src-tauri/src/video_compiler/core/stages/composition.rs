@@ -27,11 +27,7 @@ impl CompositionStage {
     let tracks_len = context.project.tracks.len();
 
     for (track_idx, track) in context.project.tracks.clone().iter().enumerate() {
-      if context.is_cancelled() {
-        return Err(VideoCompilerError::CancelledError(
-          "Композиция отменена".to_string(),
-        ));
-      }
+      context.checkpoint()?;
 
       let track_output = self.process_track(track, track_idx, context).await?;
       layer_outputs.push(track_output);
@@ -1094,6 +1090,7 @@ mod tests {
       rotation: 0.0,
       anchor_x: 0.5,
       anchor_y: 0.5,
+      ..Default::default()
     };
 
     let mut clip = Clip::new(
@@ -1,16 +1,16 @@
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::video_compiler::core::stages::composition::CompositionStage;
+  use crate::video_compiler::schema::common::AspectRatio;
   use crate::video_compiler::schema::effects::{EffectParameter, EffectType, FilterType};
-  use crate::video_compiler::schema::{ClipSource, ProjectSchema, Resolution, TransformSettings};
+  use crate::video_compiler::schema::export::{ExportSettings, OutputFormat, ProjectSettings};
   use crate::video_compiler::schema::project::ProjectMetadata;
-  use crate::video_compiler::schema::timeline::{Timeline, TrackType, ClipProperties};
-  use crate::video_compiler::schema::common::AspectRatio;
-  use crate::video_compiler::schema::export::{OutputFormat, ProjectSettings, ExportSettings};
-  use crate::video_compiler::core::stages::composition::CompositionStage;
+  use crate::video_compiler::schema::timeline::{ClipProperties, Timeline, TrackType};
+  use crate::video_compiler::schema::{ClipSource, ProjectSchema, Resolution, TransformSettings};
   use std::collections::HashMap;
-  use tempfile::TempDir;
   use std::time::Duration;
+  use tempfile::TempDir;
 
   #[tokio::test]
   async fn test_composition_stage_basic() {
@@ -47,7 +47,9 @@ mod tests {
 
     // Create test effect using the constructor
     let mut effect = Effect::new(EffectType::AudioFadeIn, "Audio Fade In".to_string());
-    effect.parameters.insert("duration".to_string(), EffectParameter::Float(2.0));
+    effect
+      .parameters
+      .insert("duration".to_string(), EffectParameter::Float(2.0));
 
     // Create dummy input file
     std::fs::write(&input_path, b"dummy video data").unwrap();
@@ -71,7 +73,7 @@ mod tests {
     // Create test filters using the constructor
     let mut filter1 = Filter::new(FilterType::Brightness, "Brightness".to_string());
     filter1.parameters.insert("value".to_string(), 0.5);
-    
+
     let mut filter2 = Filter::new(FilterType::Contrast, "Contrast".to_string());
     filter2.parameters.insert("value".to_string(), 1.5);
 
@@ -106,10 +108,7 @@ mod tests {
 
     assert!(result.is_ok());
     assert!(output_path.exists());
-    assert_eq!(
-      std::fs::read(&output_path).unwrap(),
-      b"dummy video data"
-    );
+    assert_eq!(std::fs::read(&output_path).unwrap(), b"dummy video data");
   }
 
   #[tokio::test]
@@ -129,10 +128,7 @@ mod tests {
 
     assert!(result.is_ok());
     assert!(output_path.exists());
-    assert_eq!(
-      std::fs::read(&output_path).unwrap(),
-      b"dummy video data"
-    );
+    assert_eq!(std::fs::read(&output_path).unwrap(), b"dummy video data");
   }
 
   #[tokio::test]
@@ -169,10 +165,7 @@ mod tests {
 
     assert!(result.is_ok());
     assert!(output_path.exists());
-    assert_eq!(
-      std::fs::read(&output_path).unwrap(),
-      b"dummy video data"
-    );
+    assert_eq!(std::fs::read(&output_path).unwrap(), b"dummy video data");
   }
 
   #[tokio::test]
@@ -223,6 +216,7 @@ mod tests {
       rotation: 0.0,
       anchor_x: 0.5,
       anchor_y: 0.5,
+      ..Default::default()
     };
 
     let clip = Clip {
@@ -242,6 +236,18 @@ mod tests {
       color_correction: None,
       crop: None,
       audio_track_index: None,
+      audio_stream_index: None,
+      source_rotation: 0,
+      color_label: None,
+      notes: None,
+      tags: Vec::new(),
+      fade_in: None,
+      fade_out: None,
+      link_group_id: None,
+      source_timecode: None,
+      volume: 1.0,
+      external_audio: None,
+      locked: false,
       properties: ClipProperties::default(),
     };
 
@@ -281,6 +287,18 @@ mod tests {
       color_correction: None,
       crop: None,
       audio_track_index: None,
+      audio_stream_index: None,
+      source_rotation: 0,
+      color_label: None,
+      notes: None,
+      tags: Vec::new(),
+      fade_in: None,
+      fade_out: None,
+      link_group_id: None,
+      source_timecode: None,
+      volume: 1.0,
+      external_audio: None,
+      locked: false,
       properties: ClipProperties::default(),
     };
 
@@ -311,7 +329,7 @@ mod tests {
   async fn test_composition_stage_cancelled() {
     let stage = CompositionStage::new();
     let mut context = create_test_context();
-    
+
     // Create cancellation token and cancel it
     context.set_canceled();
 
@@ -333,7 +351,11 @@ mod tests {
 
     // Test various effect types with parameters
     let effect_types = vec![
-      (EffectType::AudioFadeOut, "duration", EffectParameter::Float(3.0)),
+      (
+        EffectType::AudioFadeOut,
+        "duration",
+        EffectParameter::Float(3.0),
+      ),
       (EffectType::Blur, "radius", EffectParameter::Float(10.0)),
       (EffectType::Brightness, "value", EffectParameter::Float(0.5)),
       (EffectType::Contrast, "value", EffectParameter::Float(2.0)),
@@ -341,13 +363,17 @@ mod tests {
 
     for (effect_type, param_name, param_value) in effect_types {
       let mut effect = Effect::new(effect_type, format!("Effect {:?}", effect_type));
-      effect.parameters.insert(param_name.to_string(), param_value);
+      effect
+        .parameters
+        .insert(param_name.to_string(), param_value);
 
       // Create dummy input file
       std::fs::write(&input_path, b"dummy video data").unwrap();
 
       // Test applying effect - will fail without FFmpeg but tests parameter extraction
-      let _ = stage.apply_single_effect(&input_path, &effect, &output_path).await;
+      let _ = stage
+        .apply_single_effect(&input_path, &effect, &output_path)
+        .await;
     }
   }
 
@@ -368,6 +394,7 @@ mod tests {
         resolution: (1920, 1080),
         sample_rate: 48000,
         aspect_ratio: AspectRatio::Ratio16x9,
+        markers: Vec::new(),
       },
       tracks: vec![],
       effects: vec![],
@@ -406,4 +433,4 @@ mod tests {
     let temp_dir = TempDir::new().unwrap();
     PipelineContext::new(project, temp_dir.path().to_path_buf())
   }
-}
\ No newline at end of file
+}
@@ -306,18 +306,21 @@ impl EncodingStage {
     let mut reader = BufReader::new(stdout).lines();
     let mut last_progress = start_progress;
 
-    // Отслеживаем прогресс
-    while let Some(line) = reader
-      .next_line()
-      .await
-      .map_err(|e| VideoCompilerError::IoError(e.to_string()))?
-    {
-      if context.is_cancelled() {
-        let _ = child.kill().await;
-        return Err(VideoCompilerError::CancelledError(
-          "Кодирование отменено".to_string(),
-        ));
-      }
+    // Отслеживаем прогресс; отмена прерывает ожидание и завершает FFmpeg
+    let cancellation = context.cancellation.clone();
+    loop {
+      let line = tokio::select! {
+        line = reader.next_line() => line.map_err(|e| VideoCompilerError::IoError(e.to_string()))?,
+        _ = cancellation.cancelled() => {
+          let _ = child.kill().await;
+          return Err(VideoCompilerError::CancelledError(
+            "Кодирование отменено".to_string(),
+          ));
+        }
+      };
+      let Some(line) = line else {
+        break;
+      };
 
       if let Some(progress) = self.parse_ffmpeg_progress(&line) {
         let current_progress = start_progress + ((end_progress - start_progress) * progress / 100);
@@ -398,16 +401,16 @@ impl EncodingStage {
 
     if !output.status.success() {
       let error_msg = String::from_utf8_lossy(&output.stderr);
-      return Err(VideoCompilerError::ValidationError(format!(
-        "Выходной файл поврежден: {error_msg}"
-      )));
+      return Err(VideoCompilerError::ValidationError(
+        format!("Выходной файл поврежден: {error_msg}").into(),
+      ));
     }
 
     let duration_str = String::from_utf8_lossy(&output.stdout);
     if let Ok(duration) = duration_str.trim().parse::<f64>() {
       if duration <= 0.0 {
         return Err(VideoCompilerError::ValidationError(
-          "Выходной файл имеет нулевую длительность".to_string(),
+          "Выходной файл имеет нулевую длительность".into(),
         ));
       }
       log::info!("✅ Длительность выходного файла: {duration:.2}s");
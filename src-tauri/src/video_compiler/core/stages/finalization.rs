@@ -241,7 +241,7 @@ impl FinalizationStage {
     // Проверяем существование файла
     if !context.output_path.exists() {
       return Err(VideoCompilerError::ValidationError(
-        "Выходной файл не существует".to_string(),
+        "Выходной файл не существует".into(),
       ));
     }
 
@@ -252,7 +252,7 @@ impl FinalizationStage {
 
     if metadata.len() == 0 {
       return Err(VideoCompilerError::ValidationError(
-        "Выходной файл пуст".to_string(),
+        "Выходной файл пуст".into(),
       ));
     }
 
@@ -261,7 +261,7 @@ impl FinalizationStage {
 
     if media_info.duration <= 0.0 {
       return Err(VideoCompilerError::ValidationError(
-        "Выходной файл имеет нулевую длительность".to_string(),
+        "Выходной файл имеет нулевую длительность".into(),
       ));
     }
 
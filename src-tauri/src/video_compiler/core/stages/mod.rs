@@ -6,8 +6,10 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
+use tokio_util::sync::CancellationToken;
 
-use crate::video_compiler::error::Result;
+use crate::video_compiler::core::temp_storage;
+use crate::video_compiler::error::{Result, VideoCompilerError};
 use crate::video_compiler::ffmpeg_builder::FFmpegBuilder;
 use crate::video_compiler::progress::ProgressTracker;
 use crate::video_compiler::schema::ProjectSchema;
@@ -59,8 +61,8 @@ pub struct PipelineContext {
   pub intermediate_files: HashMap<String, PathBuf>,
   /// Пользовательские данные
   pub user_data: HashMap<String, String>,
-  /// Флаг отмены
-  pub cancelled: bool,
+  /// Токен отмены, общий для всех этапов
+  pub cancellation: CancellationToken,
   /// ID текущей задачи
   pub current_job_id: Option<String>,
   /// FFmpeg builder
@@ -70,11 +72,12 @@ pub struct PipelineContext {
 }
 
 impl PipelineContext {
-  /// Создать новый контекст
+  /// Создать новый контекст в текущем корне временных файлов
   pub fn new(project: ProjectSchema, output_path: PathBuf) -> Self {
-    let temp_dir = std::env::temp_dir()
-      .join("timeline-studio-pipeline")
-      .join(uuid::Uuid::new_v4().to_string());
+    let temp_dir = temp_storage::job_dir(
+      &temp_storage::temp_root(),
+      &uuid::Uuid::new_v4().to_string(),
+    );
 
     Self {
       project,
@@ -82,7 +85,7 @@ impl PipelineContext {
       temp_dir,
       intermediate_files: HashMap::new(),
       user_data: HashMap::new(),
-      cancelled: false,
+      cancellation: CancellationToken::new(),
       current_job_id: None,
       ffmpeg_builder: None,
       progress_tracker: None,
@@ -119,7 +122,17 @@ impl PipelineContext {
 
   /// Проверить, отменена ли обработка
   pub fn is_cancelled(&self) -> bool {
-    self.cancelled
+    self.cancellation.is_cancelled()
+  }
+
+  /// Точка проверки отмены: возвращает `CancelledError`, если токен сработал
+  pub fn checkpoint(&self) -> Result<()> {
+    if self.is_cancelled() {
+      return Err(VideoCompilerError::CancelledError(
+        "Обработка отменена".to_string(),
+      ));
+    }
+    Ok(())
   }
 
   /// Создать временную директорию
@@ -152,11 +165,11 @@ impl PipelineContext {
     self.temp_dir.join(filename)
   }
 
-  /// Отправить прогресс
+  /// Отправить прогресс этапа (0-100)
   pub async fn update_progress(&self, percentage: u64, stage: &str) -> Result<()> {
     if let (Some(tracker), Some(job_id)) = (&self.progress_tracker, &self.current_job_id) {
       tracker
-        .update_progress(job_id, percentage, stage.to_string(), None)
+        .update_stage_progress(job_id, stage, percentage.min(100) as f32 / 100.0, None)
         .await
         .unwrap_or_else(|e| {
           log::warn!("Failed to update progress: {e:?}");
@@ -173,6 +186,9 @@ pub struct PipelineStatistics {
   pub total_duration: Duration,
   /// Время выполнения каждого этапа
   pub stage_durations: HashMap<String, Duration>,
+  /// Оценки длительности этапов, по которым считался общий прогресс
+  #[serde(default)]
+  pub stage_estimates: HashMap<String, Duration>,
   /// Размер выходного файла
   pub output_file_size: u64,
   /// Количество обработанных кадров
@@ -184,6 +200,7 @@ impl Default for PipelineStatistics {
     Self {
       total_duration: Duration::ZERO,
       stage_durations: HashMap::new(),
+      stage_estimates: HashMap::new(),
       output_file_size: 0,
       frames_processed: 0,
     }
@@ -26,11 +26,7 @@ impl PreprocessingStage {
     let tracks = context.project.tracks.clone();
     for (track_idx, track) in tracks.iter().enumerate() {
       for (clip_idx, clip) in track.clips.iter().enumerate() {
-        if context.is_cancelled() {
-          return Err(VideoCompilerError::CancelledError(
-            "Preprocessing cancelled".to_string(),
-          ));
-        }
+        context.checkpoint()?;
 
         match &clip.source {
           ClipSource::File(path) => {
@@ -69,6 +65,9 @@ impl PreprocessingStage {
           ClipSource::Device(_) => {
             log::warn!("Device sources are not yet supported in preprocessing");
           }
+          ClipSource::Sequence(_) => {
+            // Вложенные последовательности рендерятся на этапе кодирования
+          }
         }
 
         processed_files += 1;
@@ -239,7 +238,7 @@ impl PreprocessingStage {
       }
       _ => {
         return Err(VideoCompilerError::ValidationError(
-          "Неправильный тип источника для генерации".to_string(),
+          "Неправильный тип источника для генерации".into(),
         ));
       }
     }
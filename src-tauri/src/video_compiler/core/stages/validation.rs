@@ -6,7 +6,7 @@ use std::time::Duration;
 
 use super::{PipelineContext, PipelineStage};
 use crate::video_compiler::error::{Result, VideoCompilerError};
-use crate::video_compiler::schema::ClipSource;
+use crate::video_compiler::schema::{ClipSource, ExternalAudio};
 
 /// Этап валидации
 pub struct ValidationStage;
@@ -20,7 +20,8 @@ impl ValidationStage {
   async fn validate_input_files(&self, context: &PipelineContext) -> Result<()> {
     log::info!("🔍 Валидация входных файлов...");
 
-    for track in &context.project.tracks {
+    let sequence_tracks = context.project.sequences.values().flat_map(|s| &s.tracks);
+    for track in context.project.tracks.iter().chain(sequence_tracks) {
       for clip in &track.clips {
         match &clip.source {
           ClipSource::File(path) => {
@@ -35,6 +36,16 @@ impl ValidationStage {
           ClipSource::Device(_) => {
             // Device sources validation not implemented yet
           }
+          ClipSource::Sequence(_) => {
+            // Клипы последовательности проверяются вместе с ее треками
+          }
+        }
+
+        if let Some(external) = clip.active_external_audio() {
+          self.validate_media_file(&external.path).await?;
+          self
+            .check_external_audio_duration(external, clip.source_start, clip.source_end)
+            .await;
         }
       }
     }
@@ -49,29 +60,29 @@ impl ValidationStage {
 
     // Проверяем существование файла
     if !file_path.exists() {
-      return Err(VideoCompilerError::ValidationError(format!(
-        "Файл не найден: {path}"
-      )));
+      return Err(VideoCompilerError::ValidationError(
+        format!("Файл не найден: {path}").into(),
+      ));
     }
 
     // Проверяем, что это файл, а не директория
     if !file_path.is_file() {
-      return Err(VideoCompilerError::ValidationError(format!(
-        "Путь не является файлом: {path}"
-      )));
+      return Err(VideoCompilerError::ValidationError(
+        format!("Путь не является файлом: {path}").into(),
+      ));
     }
 
     // Проверяем размер файла
     let metadata = tokio::fs::metadata(file_path).await.map_err(|e| {
-      VideoCompilerError::ValidationError(format!(
-        "Не удалось получить метаданные файла {path}: {e}"
-      ))
+      VideoCompilerError::ValidationError(
+        format!("Не удалось получить метаданные файла {path}: {e}").into(),
+      )
     })?;
 
     if metadata.len() == 0 {
-      return Err(VideoCompilerError::ValidationError(format!(
-        "Файл пуст: {path}"
-      )));
+      return Err(VideoCompilerError::ValidationError(
+        format!("Файл пуст: {path}").into(),
+      ));
     }
 
     // Проверяем расширение файла
@@ -85,6 +96,50 @@ impl ValidationStage {
     Ok(())
   }
 
+  /// Предупредить, если внешнее аудио короче используемого диапазона клипа
+  async fn check_external_audio_duration(
+    &self,
+    external: &ExternalAudio,
+    source_start: f64,
+    source_end: f64,
+  ) {
+    let output = tokio::process::Command::new("ffprobe")
+      .args([
+        "-v",
+        "error",
+        "-show_entries",
+        "format=duration",
+        "-of",
+        "csv=p=0",
+      ])
+      .arg(&external.path)
+      .output()
+      .await;
+
+    let duration = match output {
+      Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse::<f64>()
+        .ok(),
+      _ => None,
+    };
+
+    let (_, end, _) = external.source_range(source_start, source_end);
+    match duration {
+      Some(duration) if duration < end => {
+        log::warn!(
+          "⚠️ Внешнее аудио короче клипа: {} ({duration:.2}с < {end:.2}с), конец будет без звука",
+          external.path
+        );
+      }
+      Some(_) => {}
+      None => log::warn!(
+        "⚠️ Не удалось определить длительность внешнего аудио: {}",
+        external.path
+      ),
+    }
+  }
+
   /// Проверка поддерживаемых форматов
   fn is_supported_format(&self, extension: &str) -> bool {
     matches!(
@@ -122,30 +177,32 @@ impl ValidationStage {
     // Проверяем базовые поля
     if project.metadata.name.is_empty() {
       return Err(VideoCompilerError::ValidationError(
-        "Название проекта не может быть пустым".to_string(),
+        "Название проекта не может быть пустым".into(),
       ));
     }
 
     // Проверяем настройки экспорта
     let export = &project.settings;
     if export.resolution.width == 0 || export.resolution.height == 0 {
-      return Err(VideoCompilerError::ValidationError(format!(
-        "Некорректное разрешение: {}x{}",
-        export.resolution.width, export.resolution.height
-      )));
+      return Err(VideoCompilerError::ValidationError(
+        format!(
+          "Некорректное разрешение: {}x{}",
+          export.resolution.width, export.resolution.height
+        )
+        .into(),
+      ));
     }
 
     if export.frame_rate <= 0.0 {
-      return Err(VideoCompilerError::ValidationError(format!(
-        "Некорректная частота кадров: {}",
-        export.frame_rate
-      )));
+      return Err(VideoCompilerError::ValidationError(
+        format!("Некорректная частота кадров: {}", export.frame_rate).into(),
+      ));
     }
 
     // Проверяем треки
     if project.tracks.is_empty() {
       return Err(VideoCompilerError::ValidationError(
-        "Проект должен содержать хотя бы один трек".to_string(),
+        "Проект должен содержать хотя бы один трек".into(),
       ));
     }
 
@@ -153,7 +210,7 @@ impl ValidationStage {
     let total_duration = self.calculate_total_duration(context);
     if total_duration <= 0.0 {
       return Err(VideoCompilerError::ValidationError(
-        "Общая длительность проекта должна быть больше 0".to_string(),
+        "Общая длительность проекта должна быть больше 0".into(),
       ));
     }
 
@@ -193,9 +250,9 @@ impl ValidationStage {
     if let Some(parent) = context.output_path.parent() {
       // Проверяем существование родительской директории
       if !parent.exists() {
-        return Err(VideoCompilerError::ValidationError(format!(
-          "Выходная директория не существует: {parent:?}"
-        )));
+        return Err(VideoCompilerError::ValidationError(
+          format!("Выходная директория не существует: {parent:?}").into(),
+        ));
       }
 
       // Проверяем права на запись
@@ -206,9 +263,9 @@ impl ValidationStage {
           let _ = tokio::fs::remove_file(&test_file).await;
         }
         Err(e) => {
-          return Err(VideoCompilerError::ValidationError(format!(
-            "Нет прав на запись в директорию {parent:?}: {e}"
-          )));
+          return Err(VideoCompilerError::ValidationError(
+            format!("Нет прав на запись в директорию {parent:?}: {e}").into(),
+          ));
         }
       }
     }
@@ -236,13 +293,13 @@ impl ValidationStage {
     if available_space < min_required_space {
       return Err(VideoCompilerError::ValidationError(format!(
         "Недостаточно места на диске. Доступно: {available_space} байт, требуется минимум: {min_required_space} байт"
-      )));
+      ).into()));
     }
 
     // Проверяем доступность FFmpeg
     if !self.check_ffmpeg_availability().await {
       return Err(VideoCompilerError::ValidationError(
-        "FFmpeg не найден в системе".to_string(),
+        "FFmpeg не найден в системе".into(),
       ));
     }
 
@@ -336,6 +393,7 @@ mod tests {
         duration: 120.0,
         sample_rate: 48000,
         aspect_ratio: crate::video_compiler::schema::common::AspectRatio::Ratio16x9,
+        markers: Vec::new(),
       },
       tracks: vec![],
       effects: vec![],
@@ -343,6 +401,7 @@ mod tests {
       filters: vec![],
       templates: vec![],
       style_templates: vec![],
+      sequences: Default::default(),
       subtitles: vec![],
       settings: crate::video_compiler::schema::export::ProjectSettings::default(),
     };
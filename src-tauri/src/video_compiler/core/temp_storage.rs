@@ -0,0 +1,594 @@
+//! Temp Storage - Размещение временных файлов Video Compiler
+//!
+//! Все промежуточные файлы создаются внутри корня из
+//! `CompilerSettings::temp_directory`. Каждая задача получает собственную
+//! поддиректорию `pipeline/<job_id>`, поэтому очистка удаляет ровно файлы
+//! задачи, а директории, оставшиеся после аварийного завершения, удаляются
+//! при следующем запуске.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tokio::sync::RwLock;
+
+use crate::video_compiler::error::{Result, VideoCompilerError};
+use crate::video_compiler::CompilerSettings;
+
+/// Поддиректория директорий задач
+pub const JOBS_DIR: &str = "pipeline";
+
+/// Поддиректория предрендеренных сегментов
+pub const PRERENDER_DIR: &str = "prerender";
+
+/// Поддиректория временных файлов превью
+pub const PREVIEW_DIR: &str = "preview";
+
+/// Поддиректория файлов кэша сегментов экспорта
+pub const SEGMENTS_DIR: &str = "segments";
+
+/// Содержимое, которое переживает задачи и переносится при смене корня
+pub const MIGRATED_DIRS: &[&str] = &[PRERENDER_DIR, SEGMENTS_DIR];
+
+/// Минимум свободного места для новой временной директории
+pub const MIN_TEMP_FREE_SPACE_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// Возраст, после которого директория задачи считается брошенной
+pub const ORPHANED_JOB_MAX_AGE: Duration = Duration::from_secs(3 * 24 * 60 * 60);
+
+/// Возраст, после которого директория задачи учитывается проверкой состояния
+/// и удаляется очисткой по запросу пользователя
+pub const STALE_JOB_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Файл с выбранной временной директорией в директории приложения
+const TEMP_STORAGE_SETTINGS_FILE: &str = "temp_storage.json";
+
+/// Текущий корень временных файлов для кода без доступа к настройкам
+static TEMP_ROOT: Lazy<std::sync::RwLock<PathBuf>> =
+  Lazy::new(|| std::sync::RwLock::new(default_temp_root()));
+
+/// Корень временных файлов по умолчанию
+pub fn default_temp_root() -> PathBuf {
+  std::env::temp_dir().join("timeline-studio")
+}
+
+/// Установить текущий корень временных файлов
+pub fn set_temp_root(root: PathBuf) {
+  match TEMP_ROOT.write() {
+    Ok(mut current) => *current = root,
+    Err(poisoned) => *poisoned.into_inner() = root,
+  }
+}
+
+/// Текущий корень временных файлов
+pub fn temp_root() -> PathBuf {
+  match TEMP_ROOT.read() {
+    Ok(root) => root.clone(),
+    Err(poisoned) => poisoned.into_inner().clone(),
+  }
+}
+
+/// Сохраняемые между запусками настройки временных файлов
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct TempStorageSettings {
+  temp_directory: Option<PathBuf>,
+}
+
+/// Путь к файлу настроек временных файлов в директории приложения
+pub fn default_settings_path() -> Option<PathBuf> {
+  crate::app_dirs::AppDirectories::get_or_create()
+    .ok()
+    .map(|dirs| dirs.base_dir.join(TEMP_STORAGE_SETTINGS_FILE))
+}
+
+/// Прочитать сохраненную временную директорию
+pub fn load_temp_directory(settings_path: &Path) -> Option<PathBuf> {
+  let content = std::fs::read_to_string(settings_path).ok()?;
+  match serde_json::from_str::<TempStorageSettings>(&content) {
+    Ok(settings) => settings.temp_directory,
+    Err(e) => {
+      log::warn!("Некорректный файл {}: {e}", settings_path.display());
+      None
+    }
+  }
+}
+
+/// Сохранить выбранную временную директорию
+pub fn save_temp_directory(settings_path: &Path, temp_directory: &Path) -> Result<()> {
+  let settings = TempStorageSettings {
+    temp_directory: Some(temp_directory.to_path_buf()),
+  };
+  let json = serde_json::to_string_pretty(&settings)
+    .map_err(|e| VideoCompilerError::SerializationError(e.to_string()))?;
+  if let Some(parent) = settings_path.parent() {
+    std::fs::create_dir_all(parent).map_err(|e| VideoCompilerError::IoError(e.to_string()))?;
+  }
+  std::fs::write(settings_path, json).map_err(|e| VideoCompilerError::IoError(e.to_string()))
+}
+
+/// Корень временных файлов при запуске: сохраненная директория, если она
+/// доступна для записи, иначе директория по умолчанию
+pub fn resolve_temp_root(configured: Option<PathBuf>) -> PathBuf {
+  let Some(configured) = configured else {
+    return default_temp_root();
+  };
+
+  match validate_temp_directory(&configured, 0) {
+    Ok(info) => info.path,
+    Err(e) => {
+      log::warn!(
+        "Временная директория {} недоступна, используется директория по умолчанию: {e}",
+        configured.display()
+      );
+      default_temp_root()
+    }
+  }
+}
+
+/// Директория задачи
+pub fn job_dir(root: &Path, job_id: &str) -> PathBuf {
+  root.join(JOBS_DIR).join(job_id)
+}
+
+/// Путь к файлу предрендеренного сегмента
+pub fn prerender_output_path(root: &Path, start_time: f64, end_time: f64) -> PathBuf {
+  root.join(PRERENDER_DIR).join(format!(
+    "prerender_{:.3}_{:.3}_{}.mp4",
+    start_time,
+    end_time,
+    uuid::Uuid::new_v4()
+  ))
+}
+
+/// Результат проверки временной директории
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TempDirectoryInfo {
+  /// Абсолютный путь
+  pub path: PathBuf,
+  /// Свободное место на диске (`None`, если диск не определен)
+  pub free_bytes: Option<u64>,
+}
+
+/// Проверить, что директорию можно использовать для временных файлов:
+/// путь абсолютный, директория создается, в нее можно писать и на диске
+/// достаточно свободного места.
+pub fn validate_temp_directory(path: &Path, min_free_bytes: u64) -> Result<TempDirectoryInfo> {
+  if !path.is_absolute() {
+    return Err(VideoCompilerError::InvalidPath(format!(
+      "Временная директория должна быть абсолютным путем: {}",
+      path.display()
+    )));
+  }
+
+  std::fs::create_dir_all(path).map_err(|e| {
+    VideoCompilerError::IoError(format!("Не удалось создать {}: {e}", path.display()))
+  })?;
+
+  let probe = path.join(format!(".write-test-{}", uuid::Uuid::new_v4()));
+  std::fs::write(&probe, b"ok").map_err(|e| {
+    VideoCompilerError::IoError(format!("Нет прав на запись в {}: {e}", path.display()))
+  })?;
+  let _ = std::fs::remove_file(&probe);
+
+  let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+  let free_bytes = available_space(&path);
+  if let Some(free) = free_bytes {
+    if free < min_free_bytes {
+      return Err(VideoCompilerError::ResourceError {
+        resource_type: format!("disk space ({})", path.display()),
+        available: format!("{} MB", free / (1024 * 1024)),
+        required: format!("{} MB", min_free_bytes / (1024 * 1024)),
+      });
+    }
+  }
+
+  Ok(TempDirectoryInfo { path, free_bytes })
+}
+
+/// Свободное место на диске, содержащем путь
+fn available_space(path: &Path) -> Option<u64> {
+  let disks = sysinfo::Disks::new_with_refreshed_list();
+  disks
+    .list()
+    .iter()
+    .filter(|disk| path.starts_with(disk.mount_point()))
+    .max_by_key(|disk| disk.mount_point().as_os_str().len())
+    .map(|disk| disk.available_space())
+}
+
+/// Результат переноса содержимого временной директории
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TempMigrationReport {
+  /// Перенесено файлов
+  pub moved_files: usize,
+  /// Перенесено байт
+  pub moved_bytes: u64,
+  /// Записи, которые не удалось перенести
+  pub failed: Vec<String>,
+}
+
+/// Перенести кэш и предрендеренные сегменты в новый корень.
+///
+/// Директории задач не переносятся: активные задачи дописывают их по старому
+/// пути. Записи, которые уже есть в новом корне, остаются на месте.
+pub async fn migrate_temp_content(old_root: &Path, new_root: &Path) -> Result<TempMigrationReport> {
+  let (old_root, new_root) = (old_root.to_path_buf(), new_root.to_path_buf());
+  tokio::task::spawn_blocking(move || {
+    let mut report = TempMigrationReport::default();
+    if old_root == new_root {
+      return report;
+    }
+
+    for dir in MIGRATED_DIRS {
+      let Ok(entries) = std::fs::read_dir(old_root.join(dir)) else {
+        continue;
+      };
+      for entry in entries.flatten() {
+        let target = new_root.join(dir).join(entry.file_name());
+        if target.exists() {
+          continue;
+        }
+        match move_entry(&entry.path(), &target) {
+          Ok((files, bytes)) => {
+            report.moved_files += files;
+            report.moved_bytes += bytes;
+          }
+          Err(e) => {
+            log::warn!("Не удалось перенести {}: {e}", entry.path().display());
+            report
+              .failed
+              .push(entry.path().to_string_lossy().to_string());
+          }
+        }
+      }
+    }
+
+    report
+  })
+  .await
+  .map_err(|e| VideoCompilerError::InternalError(format!("Temp migration failed: {e}")))
+}
+
+/// Переместить файл или директорию. Между дисками копирует и удаляет исходник.
+fn move_entry(source: &Path, target: &Path) -> std::io::Result<(usize, u64)> {
+  if let Some(parent) = target.parent() {
+    std::fs::create_dir_all(parent)?;
+  }
+
+  let (files, bytes) = count_files(source)?;
+  if std::fs::rename(source, target).is_ok() {
+    return Ok((files, bytes));
+  }
+
+  copy_recursive(source, target)?;
+  if source.is_dir() {
+    std::fs::remove_dir_all(source)?;
+  } else {
+    std::fs::remove_file(source)?;
+  }
+  Ok((files, bytes))
+}
+
+/// Количество файлов и их суммарный размер
+fn count_files(path: &Path) -> std::io::Result<(usize, u64)> {
+  let metadata = std::fs::symlink_metadata(path)?;
+  if !metadata.is_dir() {
+    return Ok((1, metadata.len()));
+  }
+
+  let mut total = (0, 0);
+  for entry in std::fs::read_dir(path)? {
+    let (files, bytes) = count_files(&entry?.path())?;
+    total.0 += files;
+    total.1 += bytes;
+  }
+  Ok(total)
+}
+
+/// Рекурсивно скопировать файл или директорию
+fn copy_recursive(source: &Path, target: &Path) -> std::io::Result<()> {
+  if !source.is_dir() {
+    std::fs::copy(source, target)?;
+    return Ok(());
+  }
+
+  std::fs::create_dir_all(target)?;
+  for entry in std::fs::read_dir(source)? {
+    let entry = entry?;
+    copy_recursive(&entry.path(), &target.join(entry.file_name()))?;
+  }
+  Ok(())
+}
+
+/// Результат смены временной директории
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TempDirectoryChange {
+  /// Прежний корень
+  pub previous: PathBuf,
+  /// Новый корень
+  pub current: PathBuf,
+  /// Свободное место в новом корне
+  pub free_bytes: Option<u64>,
+  /// Результат переноса (`None`, если перенос не запрашивался)
+  pub migration: Option<TempMigrationReport>,
+}
+
+/// Проверить новую временную директорию, при необходимости перенести в нее
+/// кэш и предрендеренные сегменты и записать ее в настройки компилятора.
+///
+/// Глобальный корень и файл настроек обновляет вызывающий код.
+pub async fn change_temp_directory(
+  settings: &RwLock<CompilerSettings>,
+  new_root: &Path,
+  migrate: bool,
+  min_free_bytes: u64,
+) -> Result<TempDirectoryChange> {
+  let info = validate_temp_directory(new_root, min_free_bytes)?;
+  let previous = settings.read().await.temp_directory.clone();
+
+  let migration = if migrate {
+    Some(migrate_temp_content(&previous, &info.path).await?)
+  } else {
+    None
+  };
+
+  settings.write().await.temp_directory = info.path.clone();
+  log::info!(
+    "Временная директория изменена: {} -> {}",
+    previous.display(),
+    info.path.display()
+  );
+
+  Ok(TempDirectoryChange {
+    previous,
+    current: info.path,
+    free_bytes: info.free_bytes,
+    migration,
+  })
+}
+
+/// Директории задач, не изменявшиеся дольше заданного возраста
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OrphanedJobDirs {
+  /// Пути брошенных директорий задач
+  pub paths: Vec<PathBuf>,
+  /// Суммарный размер в байтах
+  pub total_bytes: u64,
+}
+
+/// Найти директории задач, не изменявшиеся дольше `max_age`
+pub async fn find_orphaned_job_dirs(root: &Path, max_age: Duration) -> Result<OrphanedJobDirs> {
+  let jobs_dir = root.join(JOBS_DIR);
+  tokio::task::spawn_blocking(move || scan_orphaned_job_dirs(&jobs_dir, max_age))
+    .await
+    .map_err(|e| VideoCompilerError::InternalError(format!("Temp scan failed: {e}")))
+}
+
+/// Удалить директории задач, не изменявшиеся дольше `max_age`.
+///
+/// Вызывается при запуске, когда активных задач еще нет. Возвращает
+/// количество удаленных директорий.
+pub async fn cleanup_orphaned_job_dirs(root: &Path, max_age: Duration) -> Result<usize> {
+  let jobs_dir = root.join(JOBS_DIR);
+  tokio::task::spawn_blocking(move || {
+    let mut removed = 0;
+    for path in scan_orphaned_job_dirs(&jobs_dir, max_age).paths {
+      let result = if path.is_dir() {
+        std::fs::remove_dir_all(&path)
+      } else {
+        std::fs::remove_file(&path)
+      };
+      match result {
+        Ok(()) => removed += 1,
+        Err(e) => log::warn!("Не удалось удалить {}: {e}", path.display()),
+      }
+    }
+
+    if removed > 0 {
+      log::info!("Удалено брошенных директорий задач: {removed}");
+    }
+    removed
+  })
+  .await
+  .map_err(|e| VideoCompilerError::InternalError(format!("Temp cleanup failed: {e}")))
+}
+
+fn scan_orphaned_job_dirs(jobs_dir: &Path, max_age: Duration) -> OrphanedJobDirs {
+  let mut orphaned = OrphanedJobDirs::default();
+  let Ok(entries) = std::fs::read_dir(jobs_dir) else {
+    return orphaned;
+  };
+
+  let now = SystemTime::now();
+  for entry in entries.flatten() {
+    let expired = entry
+      .metadata()
+      .and_then(|metadata| metadata.modified())
+      .ok()
+      .and_then(|modified| now.duration_since(modified).ok())
+      .is_some_and(|age| age > max_age);
+    if expired {
+      let path = entry.path();
+      orphaned.total_bytes += path_size(&path);
+      orphaned.paths.push(path);
+    }
+  }
+  orphaned.paths.sort();
+  orphaned
+}
+
+/// Размер файла или директории (рекурсивно, без перехода по ссылкам)
+fn path_size(path: &Path) -> u64 {
+  let Ok(metadata) = std::fs::symlink_metadata(path) else {
+    return 0;
+  };
+  if !metadata.is_dir() {
+    return metadata.len();
+  }
+  std::fs::read_dir(path)
+    .map(|entries| {
+      entries
+        .flatten()
+        .map(|entry| path_size(&entry.path()))
+        .sum()
+    })
+    .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::fs;
+
+  #[test]
+  fn test_job_dir_layout() {
+    let root = Path::new("/scratch/timeline");
+    assert_eq!(
+      job_dir(root, "job-1"),
+      PathBuf::from("/scratch/timeline/pipeline/job-1")
+    );
+    assert!(prerender_output_path(root, 1.0, 2.5).starts_with("/scratch/timeline/prerender"));
+  }
+
+  #[test]
+  fn test_validate_temp_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    let target = dir.path().join("scratch");
+
+    let info = validate_temp_directory(&target, 0).unwrap();
+    assert!(target.is_dir());
+    assert_eq!(info.path, target.canonicalize().unwrap());
+    // Пробный файл удален
+    assert_eq!(fs::read_dir(&target).unwrap().count(), 0);
+
+    assert!(matches!(
+      validate_temp_directory(Path::new("relative/dir"), 0),
+      Err(VideoCompilerError::InvalidPath(_))
+    ));
+    if info.free_bytes.is_some() {
+      assert!(matches!(
+        validate_temp_directory(&target, u64::MAX),
+        Err(VideoCompilerError::ResourceError { .. })
+      ));
+    }
+  }
+
+  #[tokio::test]
+  async fn test_migrate_temp_content_skips_jobs() {
+    let dir = tempfile::tempdir().unwrap();
+    let (old_root, new_root) = (dir.path().join("old"), dir.path().join("new"));
+    fs::create_dir_all(old_root.join(PRERENDER_DIR)).unwrap();
+    fs::create_dir_all(old_root.join(SEGMENTS_DIR).join("nested")).unwrap();
+    fs::create_dir_all(job_dir(&old_root, "running")).unwrap();
+    fs::write(old_root.join(PRERENDER_DIR).join("a.mp4"), b"12345").unwrap();
+    fs::write(
+      old_root.join(SEGMENTS_DIR).join("nested").join("b.ts"),
+      b"123",
+    )
+    .unwrap();
+
+    let report = migrate_temp_content(&old_root, &new_root).await.unwrap();
+    assert_eq!(report.moved_files, 2);
+    assert_eq!(report.moved_bytes, 8);
+    assert!(report.failed.is_empty());
+    assert!(new_root.join(PRERENDER_DIR).join("a.mp4").exists());
+    assert!(new_root
+      .join(SEGMENTS_DIR)
+      .join("nested")
+      .join("b.ts")
+      .exists());
+    assert!(!old_root.join(PRERENDER_DIR).join("a.mp4").exists());
+    assert!(job_dir(&old_root, "running").exists());
+    assert!(!job_dir(&new_root, "running").exists());
+  }
+
+  #[test]
+  fn test_temp_directory_persistence() {
+    let dir = tempfile::tempdir().unwrap();
+    let settings_path = dir.path().join(TEMP_STORAGE_SETTINGS_FILE);
+    assert_eq!(load_temp_directory(&settings_path), None);
+
+    let scratch = dir.path().join("scratch");
+    save_temp_directory(&settings_path, &scratch).unwrap();
+    assert_eq!(load_temp_directory(&settings_path), Some(scratch.clone()));
+
+    fs::create_dir_all(&scratch).unwrap();
+    assert_eq!(
+      resolve_temp_root(Some(scratch.clone())),
+      scratch.canonicalize().unwrap()
+    );
+    assert_eq!(
+      resolve_temp_root(Some(PathBuf::from("relative"))),
+      default_temp_root()
+    );
+  }
+
+  #[tokio::test]
+  async fn test_change_temp_directory_updates_settings() {
+    let dir = tempfile::tempdir().unwrap();
+    let (old_root, new_root) = (dir.path().join("old"), dir.path().join("new"));
+    fs::create_dir_all(old_root.join(PRERENDER_DIR)).unwrap();
+    fs::write(old_root.join(PRERENDER_DIR).join("a.mp4"), b"1").unwrap();
+    let settings = RwLock::new(CompilerSettings {
+      temp_directory: old_root.clone(),
+      ..CompilerSettings::default()
+    });
+
+    let change = change_temp_directory(&settings, &new_root, true, 0)
+      .await
+      .unwrap();
+    assert_eq!(change.previous, old_root);
+    assert_eq!(change.current, new_root.canonicalize().unwrap());
+    assert_eq!(change.migration.unwrap().moved_files, 1);
+    assert_eq!(settings.read().await.temp_directory, change.current);
+
+    // Недоступная директория не меняет настройки
+    assert!(
+      change_temp_directory(&settings, Path::new("relative"), false, 0)
+        .await
+        .is_err()
+    );
+    assert_eq!(settings.read().await.temp_directory, change.current);
+  }
+
+  #[tokio::test]
+  async fn test_cleanup_orphaned_job_dirs() {
+    let dir = tempfile::tempdir().unwrap();
+    let stale = job_dir(dir.path(), "stale");
+    let fresh = job_dir(dir.path(), "fresh");
+    fs::create_dir_all(&stale).unwrap();
+    fs::create_dir_all(&fresh).unwrap();
+
+    fs::write(stale.join("segment.mp4"), vec![0u8; 1000]).unwrap();
+
+    let old = SystemTime::now() - Duration::from_secs(10 * 24 * 60 * 60);
+    filetime::set_file_mtime(&stale, filetime::FileTime::from_system_time(old)).unwrap();
+
+    let orphaned = find_orphaned_job_dirs(dir.path(), ORPHANED_JOB_MAX_AGE)
+      .await
+      .unwrap();
+    assert_eq!(orphaned.paths, vec![stale.clone()]);
+    assert_eq!(orphaned.total_bytes, 1000);
+
+    let removed = cleanup_orphaned_job_dirs(dir.path(), ORPHANED_JOB_MAX_AGE)
+      .await
+      .unwrap();
+    assert_eq!(removed, 1);
+    assert!(!stale.exists());
+    assert!(fresh.exists());
+
+    // Отсутствующая директория задач - не ошибка
+    let empty = tempfile::tempdir().unwrap();
+    assert!(find_orphaned_job_dirs(empty.path(), ORPHANED_JOB_MAX_AGE)
+      .await
+      .unwrap()
+      .paths
+      .is_empty());
+    assert_eq!(
+      cleanup_orphaned_job_dirs(empty.path(), ORPHANED_JOB_MAX_AGE)
+        .await
+        .unwrap(),
+      0
+    );
+  }
+}
@@ -1,14 +1,22 @@
 //! FFmpeg Builder - Основная логика построителя команд FFmpeg
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tokio::process::Command;
 
-use crate::video_compiler::error::Result;
-use crate::video_compiler::schema::ProjectSchema;
+use crate::video_compiler::core::gpu_memory::RenderDowngrade;
+use crate::video_compiler::core::render_priority::ProcessLimits;
+use crate::video_compiler::error::{Result, VideoCompilerError};
+use crate::video_compiler::schema::{
+  PreviewFormat, ProjectSchema, Resolution, SubtitleMode, WatermarkConfig,
+};
 
+use super::capabilities::{
+  gl_transition, transition_substitutions, FilterCapabilities, TransitionSubstitution,
+};
 use super::filters::FilterBuilder;
 use super::inputs::InputBuilder;
 use super::outputs::OutputBuilder;
+use super::subtitles::soft_subtitle_codec;
 
 /// Настройки построителя FFmpeg
 #[derive(Debug, Clone)]
@@ -19,8 +27,14 @@ pub struct FFmpegBuilderSettings {
   pub use_hardware_acceleration: bool,
   /// Тип аппаратного ускорения
   pub hardware_acceleration_type: Option<String>,
+  /// Декодировать видеовходы на GPU (`-hwaccel auto`)
+  pub hardware_decoding: bool,
   /// Дополнительные глобальные параметры
   pub global_options: Vec<String>,
+  /// Файл субтитров для отдельной дорожки (режимы Soft/Both)
+  pub soft_subtitles_path: Option<PathBuf>,
+  /// Приоритет и ограничение потоков процессов кодирования
+  pub process_limits: ProcessLimits,
 }
 
 impl Default for FFmpegBuilderSettings {
@@ -29,11 +43,23 @@ impl Default for FFmpegBuilderSettings {
       ffmpeg_path: "ffmpeg".to_string(),
       use_hardware_acceleration: false,
       hardware_acceleration_type: None,
+      hardware_decoding: false,
       global_options: vec![],
+      soft_subtitles_path: None,
+      process_limits: ProcessLimits::default(),
     }
   }
 }
 
+/// Команда рендеринга с предупреждениями построения
+#[derive(Debug)]
+pub struct RenderCommand {
+  /// Команда FFmpeg
+  pub command: Command,
+  /// Переходы, замененные из-за отсутствующих фильтров
+  pub warnings: Vec<TransitionSubstitution>,
+}
+
 /// Построитель команд FFmpeg
 #[derive(Debug, Clone)]
 pub struct FFmpegBuilder {
@@ -41,39 +67,182 @@ pub struct FFmpegBuilder {
   project: ProjectSchema,
   /// Настройки построения
   settings: FFmpegBuilderSettings,
+  /// Фильтры, доступные в FFmpeg
+  capabilities: FilterCapabilities,
 }
 
 impl FFmpegBuilder {
   /// Создать новый построитель
   pub fn new(project: ProjectSchema) -> Self {
+    Self::with_settings(project, FFmpegBuilderSettings::default())
+  }
+
+  /// Создать построитель с настройками
+  pub fn with_settings(project: ProjectSchema, settings: FFmpegBuilderSettings) -> Self {
     Self {
       project,
-      settings: FFmpegBuilderSettings::default(),
+      settings,
+      capabilities: FilterCapabilities::default(),
     }
   }
 
-  /// Создать построитель с настройками
-  pub fn with_settings(project: ProjectSchema, settings: FFmpegBuilderSettings) -> Self {
-    Self { project, settings }
+  /// Указать фильтры, доступные в FFmpeg.
+  ///
+  /// Без списка фильтров переходы на `gl` всегда заменяются на `xfade`.
+  pub fn with_filter_capabilities(mut self, capabilities: FilterCapabilities) -> Self {
+    self.capabilities = capabilities;
+    self
+  }
+
+  /// Определить фильтры FFmpeg, если проект использует переходы `gl`,
+  /// а список фильтров еще не известен
+  pub async fn with_detected_capabilities(self) -> Self {
+    let needs_gl = self
+      .project
+      .transitions
+      .iter()
+      .any(|t| t.enabled && gl_transition(&t.transition_type).is_some());
+    if !needs_gl || self.capabilities.is_known() {
+      return self;
+    }
+    let capabilities = FilterCapabilities::detect_or_unknown(&self.settings.ffmpeg_path).await;
+    self.with_filter_capabilities(capabilities)
+  }
+
+  /// Фильтры, доступные в FFmpeg
+  pub fn filter_capabilities(&self) -> &FilterCapabilities {
+    &self.capabilities
+  }
+
+  /// Переходы проекта, которые будут заменены при построении команды
+  pub fn transition_substitutions(&self) -> Vec<TransitionSubstitution> {
+    transition_substitutions(&self.project, &self.capabilities)
+  }
+
+  /// Построить команду рендеринга вместе с предупреждениями о заменах
+  pub async fn build_render_command_with_warnings(
+    &self,
+    output_path: &Path,
+  ) -> Result<RenderCommand> {
+    let command = self.build_render_command(output_path).await?;
+    Ok(RenderCommand {
+      command,
+      warnings: self.transition_substitutions(),
+    })
+  }
+
+  /// Построитель фильтров с учетом доступных фильтров FFmpeg
+  fn filter_builder<'a>(&self, project: &'a ProjectSchema, preview: bool) -> FilterBuilder<'a> {
+    let builder = if preview {
+      FilterBuilder::for_preview(project)
+    } else {
+      FilterBuilder::new(project)
+    };
+    builder.with_capabilities(self.capabilities.clone())
+  }
+
+  /// Указать подготовленный файл субтитров для отдельной дорожки
+  pub fn with_soft_subtitles(mut self, path: PathBuf) -> Self {
+    self.settings.soft_subtitles_path = Some(path);
+    self
+  }
+
+  /// Указать приоритет и ограничение потоков процессов кодирования
+  pub fn with_process_limits(mut self, limits: ProcessLimits) -> Self {
+    self.settings.process_limits = limits;
+    self
+  }
+
+  /// Понизить режим кодирования после нехватки видеопамяти
+  pub fn with_downgrade(mut self, downgrade: RenderDowngrade) -> Self {
+    self.settings.hardware_decoding = false;
+    if downgrade == RenderDowngrade::CpuEncoder {
+      self.settings.use_hardware_acceleration = false;
+      self.project.settings.export.hardware_acceleration = false;
+    }
+    self
+  }
+
+  /// Команда FFmpeg для кодирования с приоритетом ОС из настроек
+  fn encoding_command(&self) -> Command {
+    let mut cmd = Command::new(&self.settings.ffmpeg_path);
+    self.settings.process_limits.apply_os_priority(&mut cmd);
+    cmd
   }
 
   /// Построить команду для рендеринга проекта
   pub async fn build_render_command(&self, output_path: &Path) -> Result<Command> {
-    let mut cmd = Command::new(&self.settings.ffmpeg_path);
+    if let Some(flattened) = self.flattened_sequences()? {
+      return Box::pin(flattened.build_render_command(output_path)).await;
+    }
+
+    let mut cmd = self.encoding_command();
 
     // Добавляем входные файлы
-    let input_builder = InputBuilder::new(&self.project);
+    let input_builder =
+      InputBuilder::new(&self.project).with_hardware_decoding(self.settings.hardware_decoding);
     input_builder.add_input_sources(&mut cmd).await?;
 
-    // Добавляем фильтры
-    let filter_builder = FilterBuilder::new(&self.project);
-    filter_builder.add_filter_complex(&mut cmd).await?;
+    // Файл субтитров идет последним входом
+    let soft_subtitles = self.soft_subtitles_input(&input_builder)?;
+    if let Some((path, _)) = &soft_subtitles {
+      cmd.arg("-i").arg(path);
+    }
+
+    // Изображение водяного знака идет после субтитров
+    let mut filter_builder = self.filter_builder(&self.project, false);
+    if let Some(watermark) = &self.project.settings.export.watermark {
+      let input_index = input_builder.primary_input_count()
+        + input_builder.external_audio_clips().len()
+        + usize::from(soft_subtitles.is_some());
+      add_watermark_input(&mut cmd, watermark);
+      filter_builder = filter_builder.with_watermark(input_index);
+    }
+    if self.project.settings.export.export_stems {
+      filter_builder = filter_builder.with_stems();
+    }
+    let renditions = &self.project.settings.export.renditions;
+
+    if renditions.is_empty() {
+      // Добавляем фильтры
+      filter_builder.add_filter_complex(&mut cmd).await?;
+
+      // Дорожка субтитров
+      if let Some((_, input_index)) = soft_subtitles {
+        self.add_soft_subtitle_stream(&mut cmd, input_index)?;
+      }
+
+      // Добавляем настройки вывода
+      let output_builder = OutputBuilder::new(&self.project, &self.settings);
+      output_builder
+        .add_output_settings(&mut cmd, output_path)
+        .await?;
+    } else {
+      // Фильтры применяются один раз, каждый вариант получает свой выход
+      let labels = filter_builder
+        .add_rendition_filter_complex(&mut cmd, renditions)
+        .await?;
+      let hls = self.project.settings.export.generate_hls;
+
+      for (rendition, labels) in renditions.iter().zip(labels) {
+        for label in [labels.video, labels.audio].into_iter().flatten() {
+          cmd.args(["-map", &label]);
+        }
+
+        if let Some((_, input_index)) = &soft_subtitles {
+          self.add_soft_subtitle_stream(&mut cmd, *input_index)?;
+        }
+
+        let output_builder =
+          OutputBuilder::new(&self.project, &self.settings).with_rendition(rendition);
+        output_builder
+          .add_output_settings(&mut cmd, &rendition.output_path(output_path, hls))
+          .await?;
+      }
+    }
 
-    // Добавляем настройки вывода
-    let output_builder = OutputBuilder::new(&self.project, &self.settings);
-    output_builder
-      .add_output_settings(&mut cmd, output_path)
-      .await?;
+    // Стемы аудио треков идут дополнительными выходами той же команды
+    self.add_stem_outputs(&mut cmd, &filter_builder, output_path);
 
     // Добавляем глобальные параметры
     self.add_global_options(&mut cmd);
@@ -115,6 +284,78 @@ impl FFmpegBuilder {
     Ok(cmd)
   }
 
+  /// Построить команду для рендеринга одного кадра timeline в момент `timestamp`.
+  ///
+  /// Во входы попадают только клипы, пересекающие момент (см.
+  /// [`ProjectSchema::frame_snapshot`]); скрытые треки учитываются, как в превью.
+  pub async fn build_frame_command(
+    &self,
+    timestamp: f64,
+    output_path: &Path,
+    resolution: (u32, u32),
+    format: &PreviewFormat,
+    quality: u8,
+  ) -> Result<Command> {
+    // Для превью кадра промежуточные файлы последовательностей не рендерятся
+    let mut frame = if self.project.has_sequence_clips() {
+      self.flatten_all()?.project.frame_snapshot(timestamp)
+    } else {
+      self.project.frame_snapshot(timestamp)
+    };
+    frame.settings.resolution = Resolution::new(resolution.0, resolution.1);
+    // В превью субтитры всегда впечатываются в кадр
+    frame.settings.export.subtitle_mode = SubtitleMode::Burn;
+    // Водяной знак показывается в превью только по запросу
+    frame.settings.export.watermark = frame
+      .settings
+      .export
+      .watermark
+      .take()
+      .filter(|watermark| watermark.show_in_preview && watermark.is_visible_at(timestamp))
+      .map(|watermark| WatermarkConfig {
+        start_time: None,
+        end_time: None,
+        ..watermark
+      });
+
+    let mut cmd = Command::new(&self.settings.ffmpeg_path);
+
+    let mut filter_builder = self.filter_builder(&frame, true);
+    if filter_builder.has_video_tracks() {
+      let input_builder = InputBuilder::for_preview(&frame);
+      input_builder.add_input_sources(&mut cmd).await?;
+      if let Some(watermark) = &frame.settings.export.watermark {
+        let input_index =
+          input_builder.primary_input_count() + input_builder.external_audio_clips().len();
+        add_watermark_input(&mut cmd, watermark);
+        filter_builder = filter_builder.with_watermark(input_index);
+      }
+      filter_builder.add_filter_complex(&mut cmd).await?;
+    } else {
+      // В этот момент на timeline нет видимых клипов
+      cmd.args([
+        "-f",
+        "lavfi",
+        "-i",
+        &format!("color=c=black:s={}x{}", resolution.0, resolution.1),
+      ]);
+    }
+
+    // Один кадр без звука
+    cmd.args(["-frames:v", "1", "-an"]);
+    cmd.args(["-q:v", &quality_to_qscale(quality).to_string()]);
+
+    cmd.args(["-f", "image2", "-c:v", format.ffmpeg_codec()]);
+
+    cmd.args(["-y", "-hide_banner", "-loglevel", "error"]);
+    for option in &self.settings.global_options {
+      cmd.arg(option);
+    }
+    cmd.arg(output_path);
+
+    Ok(cmd)
+  }
+
   /// Построить команду для пререндера сегмента видео
   pub async fn build_prerender_segment_command(
     &self,
@@ -122,16 +363,25 @@ impl FFmpegBuilder {
     end_time: f64,
     output_path: &Path,
   ) -> Result<Command> {
-    let mut cmd = Command::new(&self.settings.ffmpeg_path);
+    if let Some(flattened) = self.flattened_sequences()? {
+      return Box::pin(flattened.build_prerender_segment_command(
+        start_time,
+        end_time,
+        output_path,
+      ))
+      .await;
+    }
 
-    // Добавляем входные файлы для сегмента
-    let input_builder = InputBuilder::new(&self.project);
+    let mut cmd = self.encoding_command();
+
+    // Сегменты пререндера используются для превью, поэтому скрытые треки учитываются
+    let input_builder = InputBuilder::for_preview(&self.project);
     input_builder
       .add_segment_inputs(&mut cmd, start_time, end_time)
       .await?;
 
     // Добавляем фильтры для сегмента
-    let filter_builder = FilterBuilder::new(&self.project);
+    let filter_builder = self.filter_builder(&self.project, true);
     filter_builder
       .add_segment_filters(&mut cmd, start_time, end_time)
       .await?;
@@ -148,6 +398,115 @@ impl FFmpegBuilder {
     Ok(cmd)
   }
 
+  /// Построить команду кодирования сегмента экспорта.
+  ///
+  /// В отличие от пререндера учитывает только видимые треки и кодирует
+  /// сегмент итоговыми настройками вывода, чтобы сегменты можно было
+  /// склеить без перекодирования.
+  pub async fn build_export_segment_command(
+    &self,
+    start_time: f64,
+    end_time: f64,
+    output_path: &Path,
+  ) -> Result<Command> {
+    let mut cmd = self.encoding_command();
+
+    let input_builder =
+      InputBuilder::new(&self.project).with_hardware_decoding(self.settings.hardware_decoding);
+    input_builder
+      .add_segment_inputs(&mut cmd, start_time, end_time)
+      .await?;
+
+    let filter_builder = self.filter_builder(&self.project, false);
+    filter_builder
+      .add_segment_filters(&mut cmd, start_time, end_time)
+      .await?;
+
+    let output_builder = OutputBuilder::new(&self.project, &self.settings);
+    output_builder
+      .add_output_settings(&mut cmd, output_path)
+      .await?;
+
+    self.add_global_options(&mut cmd);
+
+    Ok(cmd)
+  }
+
+  /// Построить команду склейки сегментов через concat demuxer без перекодирования
+  pub fn build_concat_command(&self, list_path: &Path, output_path: &Path) -> Command {
+    let mut cmd = Command::new(&self.settings.ffmpeg_path);
+
+    cmd.args(["-f", "concat", "-safe", "0", "-i"]);
+    cmd.arg(list_path);
+    cmd.args(["-map", "0", "-c", "copy"]);
+    cmd.args(["-y", "-hide_banner", "-loglevel", "error"]);
+    cmd.arg(output_path);
+
+    cmd
+  }
+
+  /// Построить команду вырезания фрагмента исходного файла.
+  ///
+  /// При `stream_copy` потоки копируются без перекодирования, и начало
+  /// фрагмента совпадает с ближайшим предшествующим ключевым кадром.
+  pub fn build_media_trim_command(
+    &self,
+    input: &Path,
+    start: f64,
+    duration: f64,
+    output_path: &Path,
+    stream_copy: bool,
+  ) -> Command {
+    let mut cmd = Command::new(&self.settings.ffmpeg_path);
+
+    cmd.args(["-ss", &format!("{start:.3}"), "-i"]);
+    cmd.arg(input);
+    cmd.args(["-t", &format!("{duration:.3}")]);
+    cmd.args(["-map", "0:v?", "-map", "0:a?", "-map_metadata", "0"]);
+    if stream_copy {
+      cmd.args(["-c", "copy", "-avoid_negative_ts", "make_zero"]);
+    } else {
+      // Кодеки по умолчанию для контейнера исходного файла
+      cmd.args(["-crf", "18"]);
+    }
+    cmd.args(self.settings.process_limits.thread_args());
+    cmd.args(["-y", "-hide_banner", "-loglevel", "error"]);
+    cmd.arg(output_path);
+
+    cmd
+  }
+
+  /// Файл субтитров и индекс его входа, если субтитры выводятся дорожкой
+  fn soft_subtitles_input(&self, input_builder: &InputBuilder) -> Result<Option<(PathBuf, usize)>> {
+    if !self.project.settings.export.subtitle_mode.muxes() || self.project.subtitles.is_empty() {
+      return Ok(None);
+    }
+
+    let path = self.settings.soft_subtitles_path.clone().ok_or_else(|| {
+      VideoCompilerError::validation("Файл субтитров для отдельной дорожки не подготовлен")
+    })?;
+    let input_index =
+      input_builder.primary_input_count() + input_builder.external_audio_clips().len();
+
+    Ok(Some((path, input_index)))
+  }
+
+  /// Добавить дорожку субтитров к текущему выходу
+  fn add_soft_subtitle_stream(&self, cmd: &mut Command, input_index: usize) -> Result<()> {
+    let export = &self.project.settings.export;
+    let codec = soft_subtitle_codec(&self.project.settings.output.format)?;
+    cmd.args(["-map", &format!("{input_index}:s")]);
+    cmd.args(["-c:s", codec]);
+    cmd.args([
+      "-metadata:s:s:0",
+      &format!(
+        "language={}",
+        export.subtitle_language.as_deref().unwrap_or("und")
+      ),
+    ]);
+    Ok(())
+  }
+
   /// Добавить глобальные опции
   fn add_global_options(&self, cmd: &mut Command) {
     // Перезапись выходного файла
@@ -189,6 +548,25 @@ pub fn quality_to_crf(quality: u32) -> u32 {
   51 - (quality * 33 / 100).min(33)
 }
 
+/// Конвертировать качество (0-100) в qscale изображения (2-31, 2 - лучшее)
+pub fn quality_to_qscale(quality: u8) -> u32 {
+  2 + (100 - quality.min(100) as u32) * 29 / 100
+}
+
+/// Конвертировать качество (0-100) в VBR качество libmp3lame (0-9, 0 - лучшее)
+pub fn quality_to_mp3_vbr(quality: u32) -> u32 {
+  9 - (quality * 9 / 100).min(9)
+}
+
+/// Добавить вход изображения водяного знака
+fn add_watermark_input(cmd: &mut Command, watermark: &WatermarkConfig) {
+  if watermark.is_animated() {
+    // GIF зацикливается на всю длительность видео
+    cmd.args(["-ignore_loop", "0"]);
+  }
+  cmd.arg("-i").arg(&watermark.image_path);
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -221,7 +599,10 @@ mod tests {
       ffmpeg_path: "/usr/local/bin/ffmpeg".to_string(),
       use_hardware_acceleration: true,
       hardware_acceleration_type: Some("nvenc".to_string()),
+      hardware_decoding: false,
       global_options: vec!["-threads".to_string(), "4".to_string()],
+      soft_subtitles_path: None,
+      process_limits: Default::default(),
     };
 
     let builder = FFmpegBuilder::with_settings(project, settings.clone());
@@ -271,6 +652,45 @@ mod tests {
     );
   }
 
+  #[tokio::test]
+  async fn test_render_command_thread_limits() {
+    use crate::video_compiler::core::render_priority::RenderPriority;
+
+    let output_path = PathBuf::from("/tmp/test_output.mp4");
+    let args_of = |cmd: Command| -> Vec<String> {
+      cmd
+        .as_std()
+        .get_args()
+        .map(|s| s.to_string_lossy().to_string())
+        .collect()
+    };
+
+    let builder = FFmpegBuilder::new(create_project_with_clips())
+      .with_process_limits(RenderPriority::Background.limits_for(None, 8));
+    let args = args_of(builder.build_render_command(&output_path).await.unwrap());
+    let threads = args.iter().position(|arg| arg == "-threads").unwrap();
+    assert_eq!(args[threads + 1], "4");
+    // Ограничение относится к выходу и стоит до пути результата
+    let output = args
+      .iter()
+      .position(|arg| arg == "/tmp/test_output.mp4")
+      .unwrap();
+    assert!(threads < output);
+
+    let prerender = args_of(
+      builder
+        .build_prerender_segment_command(0.0, 1.0, &output_path)
+        .await
+        .unwrap(),
+    );
+    assert!(prerender.windows(2).any(|pair| pair == ["-threads", "4"]));
+
+    // Обычный приоритет оставляет выбор потоков FFmpeg
+    let builder = FFmpegBuilder::new(create_project_with_clips());
+    let args = args_of(builder.build_render_command(&output_path).await.unwrap());
+    assert!(args.windows(2).any(|pair| pair == ["-threads", "0"]));
+  }
+
   #[tokio::test]
   async fn test_build_preview_command() {
     let project = create_minimal_project();
@@ -361,6 +781,21 @@ mod tests {
     assert!(quality_to_crf(0) <= 51);
   }
 
+  #[test]
+  fn test_quality_to_mp3_vbr_conversion() {
+    assert_eq!(quality_to_mp3_vbr(100), 0); // Лучшее качество
+    assert_eq!(quality_to_mp3_vbr(50), 5);
+    assert_eq!(quality_to_mp3_vbr(0), 9); // Худшее качество
+    assert_eq!(quality_to_mp3_vbr(150), 0);
+  }
+
+  #[test]
+  fn test_quality_to_qscale_conversion() {
+    assert_eq!(quality_to_qscale(100), 2);
+    assert_eq!(quality_to_qscale(0), 31);
+    assert_eq!(quality_to_qscale(200), 2);
+  }
+
   #[test]
   fn test_global_options_application() {
     let project = create_minimal_project();
@@ -468,4 +903,50 @@ mod tests {
       "/opt/ffmpeg/bin/ffmpeg"
     );
   }
+
+  #[tokio::test]
+  async fn test_render_command_reports_gl_substitutions() {
+    use crate::video_compiler::schema::effects::{Transition, TransitionDuration};
+
+    let mut project = create_minimal_project();
+    project.transitions.push(Transition {
+      id: "page".to_string(),
+      transition_type: "page-turn".to_string(),
+      name: "Page Turn".to_string(),
+      duration: TransitionDuration {
+        value: 1.0,
+        min: None,
+        max: None,
+      },
+      category: None,
+      tags: Vec::new(),
+      complexity: None,
+      enabled: true,
+      parameters: std::collections::HashMap::new(),
+      ffmpeg_command: None,
+      easing: None,
+      direction: None,
+      from_clip_id: None,
+      to_clip_id: None,
+    });
+    let output_path = PathBuf::from("/tmp/output.mp4");
+
+    let builder = FFmpegBuilder::new(project)
+      .with_filter_capabilities(FilterCapabilities::from_filters(["xfade"]));
+    let render = builder
+      .build_render_command_with_warnings(&output_path)
+      .await
+      .unwrap();
+    assert_eq!(render.warnings.len(), 1);
+    assert_eq!(render.warnings[0].transition_id, "page");
+    assert_eq!(render.warnings[0].fallback, "revealleft");
+
+    let builder =
+      builder.with_filter_capabilities(FilterCapabilities::from_filters(["xfade", "gl"]));
+    let render = builder
+      .build_render_command_with_warnings(&output_path)
+      .await
+      .unwrap();
+    assert!(render.warnings.is_empty());
+  }
 }
@@ -0,0 +1,261 @@
+//! FFmpeg Builder - Возможности установленного FFmpeg
+//!
+//! Часть переходов каталога описана через фильтр `gl`, который почти никогда
+//! не входит в пользовательские сборки FFmpeg. Список фильтров берется из
+//! общего кэша возможностей FFmpeg, а переходы на `gl` при его отсутствии
+//! заменяются ближайшим вариантом `xfade`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::video_compiler::error::Result;
+use crate::video_compiler::schema::{effects::Transition, ProjectSchema};
+use crate::video_compiler::services::ffmpeg_capabilities::FfmpegCapabilitiesCache;
+
+/// Имя фильтра OpenGL переходов
+pub const GL_FILTER: &str = "gl";
+
+/// Переходы, требующие фильтра `gl`, и их замена на CPU.
+///
+/// Кортеж: тип перехода, имя перехода `gl`, ближайший переход `xfade`:
+/// - `cube-3d` - сдвиг кадра, как при повороте грани куба;
+/// - `page-turn` - открытие следующего кадра из-под уходящего;
+/// - `kaleidoscope` - раскрытие круга из центра;
+/// - `shatter` - распад на блоки через пикселизацию;
+/// - `burn` - засветка через белый кадр;
+/// - `swirl` - радиальная развертка вокруг центра.
+const GL_TRANSITIONS: &[(&str, &str, &str)] = &[
+  ("cube-3d", "cube", "slideleft"),
+  ("page-turn", "pagecurl", "revealleft"),
+  ("kaleidoscope", "kaleidoscope", "circleopen"),
+  ("shatter", "shatter", "pixelize"),
+  ("burn", "burn", "fadewhite"),
+  ("swirl", "swirl", "radial"),
+];
+
+/// Набор фильтров, доступных в сборке FFmpeg
+#[derive(Debug, Clone, Default)]
+pub struct FilterCapabilities {
+  /// `None`, если список фильтров не получен
+  filters: Option<Arc<HashSet<String>>>,
+}
+
+impl FilterCapabilities {
+  /// Возможности с заданным списком фильтров
+  pub fn from_filters<I, S>(filters: I) -> Self
+  where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+  {
+    Self {
+      filters: Some(Arc::new(filters.into_iter().map(Into::into).collect())),
+    }
+  }
+
+  /// Разобрать вывод `ffmpeg -filters`
+  pub fn from_filters_output(output: &str) -> Self {
+    Self::from_filters(parse_filters_output(output))
+  }
+
+  /// Получить список фильтров FFmpeg из общего кэша возможностей.
+  ///
+  /// Ошибки не кэшируются, чтобы следующий вызов мог повторить попытку.
+  pub async fn detect(ffmpeg_path: &str) -> Result<Self> {
+    let capabilities = FfmpegCapabilitiesCache::shared(ffmpeg_path).get().await?;
+    Ok(Self::from_filters(capabilities.filters.iter().cloned()))
+  }
+
+  /// Получить список фильтров, при ошибке считать его неизвестным
+  pub async fn detect_or_unknown(ffmpeg_path: &str) -> Self {
+    Self::detect(ffmpeg_path).await.unwrap_or_else(|e| {
+      log::warn!("Не удалось получить список фильтров FFmpeg: {e}");
+      Self::default()
+    })
+  }
+
+  /// Получен ли список фильтров
+  pub fn is_known(&self) -> bool {
+    self.filters.is_some()
+  }
+
+  /// Доступен ли фильтр. Пока список не получен, фильтр считается
+  /// отсутствующим: рендер с заменой надежнее, чем падение FFmpeg.
+  pub fn has_filter(&self, name: &str) -> bool {
+    self
+      .filters
+      .as_ref()
+      .is_some_and(|filters| filters.contains(name))
+  }
+}
+
+/// Разобрать вывод `ffmpeg -filters` в набор имен фильтров.
+///
+/// Строки фильтров имеют вид ` TSC xfade  VV->V  Описание`: флаги, имя и
+/// направления входов/выходов. Заголовок и легенда пропускаются.
+pub fn parse_filters_output(output: &str) -> HashSet<String> {
+  output
+    .lines()
+    .filter_map(|line| {
+      let mut parts = line.split_whitespace();
+      let flags = parts.next()?;
+      let name = parts.next()?;
+      let io = parts.next()?;
+      let is_flags = flags.len() == 3 && flags.chars().all(|c| "TSC.".contains(c));
+      (is_flags && io.contains("->")).then(|| name.to_string())
+    })
+    .collect()
+}
+
+/// Переход `gl` и его замена `xfade` для типа перехода
+pub fn gl_transition(transition_type: &str) -> Option<(&'static str, &'static str)> {
+  GL_TRANSITIONS
+    .iter()
+    .find(|(kind, _, _)| *kind == transition_type)
+    .map(|(_, gl, fallback)| (*gl, *fallback))
+}
+
+/// Замена перехода `gl` на `xfade`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransitionSubstitution {
+  /// ID перехода в проекте
+  pub transition_id: String,
+  /// Тип перехода
+  pub transition_type: String,
+  /// Запрошенный переход фильтра `gl`
+  pub requested: String,
+  /// Использованный переход `xfade`
+  pub fallback: String,
+}
+
+impl TransitionSubstitution {
+  /// Текст предупреждения для пользователя
+  pub fn message(&self) -> String {
+    format!(
+      "Переход '{}' ({}) требует фильтра gl, которого нет в FFmpeg; \
+       используется xfade={}",
+      self.transition_id, self.transition_type, self.fallback
+    )
+  }
+}
+
+/// Замена для перехода, если он требует недоступного фильтра `gl`
+pub fn transition_substitution(
+  transition: &Transition,
+  capabilities: &FilterCapabilities,
+) -> Option<TransitionSubstitution> {
+  let (gl, fallback) = gl_transition(&transition.transition_type)?;
+  if capabilities.has_filter(GL_FILTER) {
+    return None;
+  }
+  Some(TransitionSubstitution {
+    transition_id: transition.id.clone(),
+    transition_type: transition.transition_type.clone(),
+    requested: gl.to_string(),
+    fallback: fallback.to_string(),
+  })
+}
+
+/// Замены для всех включенных переходов проекта
+pub fn transition_substitutions(
+  project: &ProjectSchema,
+  capabilities: &FilterCapabilities,
+) -> Vec<TransitionSubstitution> {
+  project
+    .transitions
+    .iter()
+    .filter(|transition| transition.enabled)
+    .filter_map(|transition| transition_substitution(transition, capabilities))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::video_compiler::schema::effects::TransitionDuration;
+  use std::collections::HashMap;
+
+  const FILTERS_OUTPUT: &str = "Filters:
+  T.. = Timeline support
+  .S. = Slice threading
+  ..C = Command support
+  A = Audio input/output
+  V = Video input/output
+  N = Dynamic number and/or type of input/output
+  | = Source or sink filter
+ ... abench            A->A       Benchmark part of a filtergraph.
+ TSC xfade             VV->V      Cross fade one video with another video.
+ ... gl                VV->V      OpenGL shader transition.
+ ..C scale             V->V       Scale the input video size and/or convert the image format.
+";
+
+  fn transition(kind: &str) -> Transition {
+    Transition {
+      id: format!("{kind}-1"),
+      transition_type: kind.to_string(),
+      name: kind.to_string(),
+      duration: TransitionDuration {
+        value: 1.0,
+        min: None,
+        max: None,
+      },
+      category: None,
+      tags: Vec::new(),
+      complexity: None,
+      enabled: true,
+      parameters: HashMap::new(),
+      ffmpeg_command: None,
+      easing: None,
+      direction: None,
+      from_clip_id: None,
+      to_clip_id: None,
+    }
+  }
+
+  #[test]
+  fn test_parse_filters_output_skips_legend() {
+    let filters = parse_filters_output(FILTERS_OUTPUT);
+    assert_eq!(filters.len(), 4);
+    assert!(filters.contains("xfade"));
+    assert!(filters.contains("gl"));
+    assert!(filters.contains("abench"));
+    assert!(!filters.contains("="));
+  }
+
+  #[test]
+  fn test_unknown_capabilities_have_no_filters() {
+    let capabilities = FilterCapabilities::default();
+    assert!(!capabilities.is_known());
+    assert!(!capabilities.has_filter("xfade"));
+  }
+
+  #[test]
+  fn test_substitution_only_without_gl() {
+    let cube = transition("cube-3d");
+
+    let without_gl = FilterCapabilities::from_filters(["xfade"]);
+    let substitution = transition_substitution(&cube, &without_gl).unwrap();
+    assert_eq!(substitution.requested, "cube");
+    assert_eq!(substitution.fallback, "slideleft");
+    assert!(substitution.message().contains("cube-3d-1"));
+
+    let with_gl = FilterCapabilities::from_filters_output(FILTERS_OUTPUT);
+    assert!(transition_substitution(&cube, &with_gl).is_none());
+
+    // Переходы на xfade не заменяются
+    assert!(transition_substitution(&transition("wipe_left"), &without_gl).is_none());
+  }
+
+  #[test]
+  fn test_project_substitutions_skip_disabled() {
+    let mut project = ProjectSchema::new("Test".to_string());
+    project.transitions.push(transition("page-turn"));
+    let mut disabled = transition("swirl");
+    disabled.enabled = false;
+    project.transitions.push(disabled);
+
+    let substitutions = transition_substitutions(&project, &FilterCapabilities::default());
+    assert_eq!(substitutions.len(), 1);
+    assert_eq!(substitutions[0].fallback, "revealleft");
+  }
+}
@@ -0,0 +1,278 @@
+//! Command Preview - описание команды FFmpeg без запуска процесса
+//!
+//! Используется для отладки рендеринга: команда строится теми же методами
+//! FFmpegBuilder, что и при настоящем рендере, и разбирается на программу,
+//! аргументы, входы и граф фильтров.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tokio::process::Command;
+
+use crate::video_compiler::error::Result;
+use crate::video_compiler::schema::PreviewFormat;
+
+use super::builder::FFmpegBuilder;
+
+/// Вход команды FFmpeg
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommandInput {
+  /// Индекс входа в командной строке (`0:v`, `1:a`, ...)
+  pub index: usize,
+  /// Путь к файлу или описание источника lavfi
+  pub source: String,
+  /// Формат, указанный через `-f` перед входом
+  pub format: Option<String>,
+}
+
+/// Команда FFmpeg, построенная без запуска процесса
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandPreview {
+  /// Путь к исполняемому файлу FFmpeg
+  pub program: String,
+  /// Аргументы в порядке передачи процессу
+  pub args: Vec<String>,
+  /// Граф `-filter_complex`, по одной цепочке фильтров на строку
+  pub filter_graph: Option<String>,
+  /// Входные источники с индексами
+  pub inputs: Vec<CommandInput>,
+  /// Командная строка для копирования в терминал
+  pub command_line: String,
+  /// Замены переходов и другие предупреждения построения
+  pub warnings: Vec<String>,
+}
+
+impl CommandPreview {
+  /// Разобрать построенную команду
+  pub fn from_command(command: &Command, warnings: Vec<String>) -> Self {
+    let std_command = command.as_std();
+    let args: Vec<String> = std_command
+      .get_args()
+      .map(|arg| arg.to_string_lossy().to_string())
+      .collect();
+
+    let filter_graph = args
+      .iter()
+      .position(|arg| arg == "-filter_complex")
+      .and_then(|index| args.get(index + 1))
+      .map(|graph| pretty_filter_graph(graph));
+
+    let program = std_command.get_program().to_string_lossy().to_string();
+    let command_line = std::iter::once(&program)
+      .chain(&args)
+      .map(|arg| shell_quote(arg))
+      .collect::<Vec<_>>()
+      .join(" ");
+
+    Self {
+      inputs: command_inputs(&args),
+      filter_graph,
+      command_line,
+      program,
+      args,
+      warnings,
+    }
+  }
+}
+
+impl FFmpegBuilder {
+  /// Команда рендеринга проекта без запуска FFmpeg
+  pub async fn preview_render_command(&self, output_path: &Path) -> Result<CommandPreview> {
+    let render = self.build_render_command_with_warnings(output_path).await?;
+    let warnings = render.warnings.iter().map(|w| w.message()).collect();
+    Ok(CommandPreview::from_command(&render.command, warnings))
+  }
+
+  /// Команда рендеринга кадра timeline без запуска FFmpeg
+  pub async fn preview_frame_command(
+    &self,
+    timestamp: f64,
+    output_path: &Path,
+    resolution: (u32, u32),
+    format: &PreviewFormat,
+    quality: u8,
+  ) -> Result<CommandPreview> {
+    let command = self
+      .build_frame_command(timestamp, output_path, resolution, format, quality)
+      .await?;
+    let warnings = self
+      .transition_substitutions()
+      .iter()
+      .map(|w| w.message())
+      .collect();
+    Ok(CommandPreview::from_command(&command, warnings))
+  }
+}
+
+/// Входы команды: значения `-i` по порядку вместе с форматом `-f` перед ними
+fn command_inputs(args: &[String]) -> Vec<CommandInput> {
+  let mut inputs = Vec::new();
+  let mut format = None;
+  let mut iter = args.iter();
+  while let Some(arg) = iter.next() {
+    match arg.as_str() {
+      "-f" => format = iter.next().cloned(),
+      "-i" => {
+        if let Some(source) = iter.next() {
+          inputs.push(CommandInput {
+            index: inputs.len(),
+            source: source.clone(),
+            format: format.take(),
+          });
+        }
+      }
+      _ => {}
+    }
+  }
+  inputs
+}
+
+/// Разбить граф фильтров на цепочки по `;` вне кавычек и экранирования
+pub fn pretty_filter_graph(graph: &str) -> String {
+  let mut chains = Vec::new();
+  let mut current = String::new();
+  let mut quoted = false;
+  let mut escaped = false;
+
+  for ch in graph.chars() {
+    if escaped {
+      escaped = false;
+    } else if ch == '\\' {
+      escaped = true;
+    } else if ch == '\'' {
+      quoted = !quoted;
+    } else if ch == ';' && !quoted {
+      chains.push(std::mem::take(&mut current));
+      continue;
+    }
+    current.push(ch);
+  }
+  chains.push(current);
+
+  chains
+    .iter()
+    .map(|chain| chain.trim())
+    .filter(|chain| !chain.is_empty())
+    .collect::<Vec<_>>()
+    .join(";\n")
+}
+
+/// Экранировать аргумент для POSIX shell
+fn shell_quote(arg: &str) -> String {
+  let safe = !arg.is_empty()
+    && arg
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || "-_./:=,+@%".contains(c));
+  if safe {
+    arg.to_string()
+  } else {
+    format!("'{}'", arg.replace('\'', r"'\''"))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::video_compiler::tests::fixtures::*;
+  use std::path::PathBuf;
+
+  fn args_of(command: &Command) -> Vec<String> {
+    command
+      .as_std()
+      .get_args()
+      .map(|arg| arg.to_string_lossy().to_string())
+      .collect()
+  }
+
+  #[tokio::test]
+  async fn test_render_preview_matches_builder_args() {
+    let builder = FFmpegBuilder::new(create_project_with_clips());
+    let output_path = PathBuf::from("/tmp/preview_output.mp4");
+
+    let command = builder.build_render_command(&output_path).await.unwrap();
+    let preview = builder.preview_render_command(&output_path).await.unwrap();
+
+    assert_eq!(preview.args, args_of(&command));
+    assert_eq!(
+      preview.program,
+      command.as_std().get_program().to_string_lossy()
+    );
+    let input_count = preview.args.iter().filter(|arg| *arg == "-i").count();
+    assert_eq!(preview.inputs.len(), input_count);
+    assert!(preview
+      .inputs
+      .iter()
+      .enumerate()
+      .all(|(index, input)| input.index == index));
+  }
+
+  #[tokio::test]
+  async fn test_frame_preview_matches_builder_args() {
+    let builder = FFmpegBuilder::new(create_project_with_clips());
+    let output_path = PathBuf::from("/tmp/preview_frame.jpg");
+
+    let command = builder
+      .build_frame_command(1.0, &output_path, (640, 360), &PreviewFormat::Jpeg, 80)
+      .await
+      .unwrap();
+    let preview = builder
+      .preview_frame_command(1.0, &output_path, (640, 360), &PreviewFormat::Jpeg, 80)
+      .await
+      .unwrap();
+
+    assert_eq!(preview.args, args_of(&command));
+  }
+
+  #[test]
+  fn test_pretty_filter_graph_splits_chains() {
+    let graph = "[0:v]scale=1920:1080[v0];[v0]drawtext=text='a;b'[v1];[1:a]volume=0.5\\;x[a0]";
+    assert_eq!(
+      pretty_filter_graph(graph),
+      "[0:v]scale=1920:1080[v0];\n[v0]drawtext=text='a;b'[v1];\n[1:a]volume=0.5\\;x[a0]"
+    );
+  }
+
+  #[test]
+  fn test_command_inputs_track_formats() {
+    let args: Vec<String> = [
+      "-i",
+      "a.mp4",
+      "-f",
+      "lavfi",
+      "-i",
+      "color=c=black",
+      "-f",
+      "mp4",
+      "out.mp4",
+    ]
+    .iter()
+    .map(|arg| arg.to_string())
+    .collect();
+
+    assert_eq!(
+      command_inputs(&args),
+      vec![
+        CommandInput {
+          index: 0,
+          source: "a.mp4".to_string(),
+          format: None,
+        },
+        CommandInput {
+          index: 1,
+          source: "color=c=black".to_string(),
+          format: Some("lavfi".to_string()),
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn test_command_line_quotes_arguments() {
+    let mut command = Command::new("ffmpeg");
+    command.args(["-i", "my clip.mp4", "-vf", "scale=640:360"]);
+    let preview = CommandPreview::from_command(&command, vec![]);
+    assert_eq!(
+      preview.command_line,
+      "ffmpeg -i 'my clip.mp4' -vf scale=640:360"
+    );
+  }
+}
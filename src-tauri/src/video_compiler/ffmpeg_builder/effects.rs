@@ -1,26 +1,171 @@
 //! FFmpeg Builder - Модуль обработки эффектов и переходов
 
+use std::borrow::Cow;
 use std::collections::HashMap;
 
-use crate::video_compiler::error::Result;
+use crate::video_compiler::error::{Result, VideoCompilerError};
+
+use super::capabilities::{gl_transition, FilterCapabilities, GL_FILTER};
 use crate::video_compiler::schema::{
-  effects::{Effect, EffectParameter, EffectType, Filter, FilterType, Transition},
+  chroma_key::{normalize_color, ChromaKeyBackground, ChromaKeyMode, ChromaKeyParams},
+  effects::{
+    keyframe_value_at, Effect, EffectParameter, EffectType, Filter, FilterType, ParamKeyframe,
+    Transition,
+  },
   project::ProjectSchema,
-  timeline::Clip,
+  timeline::{Clip, ClipSource},
 };
 
+/// Количество постоянных участков на интервал между ключевыми кадрами
+/// для фильтров без вычисления выражений по времени
+pub const KEYFRAME_PIECEWISE_STEPS: usize = 8;
+
+/// Параметры эффекта, ключевые кадры которых переводятся в FFmpeg.
+///
+/// Параметры eq анимируются выражениями от `t`, размытие - кусочно
+/// постоянными участками с `enable`.
+pub fn animatable_parameters(effect: &Effect) -> &'static [&'static str] {
+  match effect.effect_type {
+    EffectType::ColorCorrection if !is_complex_color_correction(&effect.parameters) => {
+      &["brightness", "contrast", "saturation", "gamma"]
+    }
+    EffectType::Blur => &["radius", "sigma"],
+    EffectType::Brightness | EffectType::Contrast | EffectType::Saturation => &["value"],
+    _ => &[],
+  }
+}
+
+/// Параметры с ключевыми кадрами, которые заменяются значением первого кадра
+pub fn flattened_keyframe_parameters(effect: &Effect) -> Vec<&str> {
+  let animatable = animatable_parameters(effect);
+  effect
+    .keyframed_parameters()
+    .into_iter()
+    .filter(|name| !animatable.contains(name))
+    .collect()
+}
+
+/// Предупреждения об эффектах, часть ключевых кадров которых не анимируется
+pub fn keyframe_warnings(project: &ProjectSchema) -> Vec<String> {
+  project
+    .effects
+    .iter()
+    .filter(|effect| effect.enabled)
+    .filter_map(|effect| {
+      let flattened = flattened_keyframe_parameters(effect);
+      (!flattened.is_empty()).then(|| {
+        format!(
+          "Эффект '{}' не поддерживает анимацию параметров {}: использовано значение первого ключевого кадра",
+          effect.id,
+          flattened.join(", ")
+        )
+      })
+    })
+    .collect()
+}
+
+/// Выражение FFmpeg от времени `t` для значения по ключевым кадрам
+pub fn keyframe_expression(keyframes: &[ParamKeyframe]) -> String {
+  let Some(first) = keyframes.first() else {
+    return String::new();
+  };
+
+  let mut expression = expr_number(keyframes[keyframes.len() - 1].value);
+  for pair in keyframes.windows(2).rev() {
+    let (from, to) = (&pair[0], &pair[1]);
+    let progress = format!(
+      "(t-{})/{}",
+      expr_number(from.time),
+      expr_number(to.time - from.time)
+    );
+    expression = format!(
+      "if(lt(t,{}),{}+({})*({}),{expression})",
+      expr_number(to.time),
+      expr_number(from.value),
+      expr_number(to.value - from.value),
+      from.easing.expression(&format!("({progress})"))
+    );
+  }
+
+  if keyframes.len() == 1 {
+    return expression;
+  }
+  format!(
+    "if(lt(t,{}),{},{expression})",
+    expr_number(first.time),
+    expr_number(first.value)
+  )
+}
+
+/// Число для выражения FFmpeg без лишних нулей
+fn expr_number(value: f64) -> String {
+  let formatted = format!("{value:.4}");
+  let trimmed = formatted.trim_end_matches('0').trim_end_matches('.');
+  match trimmed {
+    "-0" | "" => "0".to_string(),
+    _ => trimmed.to_string(),
+  }
+}
+
+/// Путь к файлу как значение параметра фильтра внутри графа.
+///
+/// Спецсимволы параметра экранируются, а кавычки защищают `,;[]` графа.
+fn quote_filter_path(path: &str) -> String {
+  let escaped = path.replace('\\', "\\\\").replace(':', "\\:");
+  format!("'{}'", escaped.replace('\'', r"'\\\''"))
+}
+
+/// Эффект, в котором неанимируемые параметры заменены первым ключевым кадром
+fn flatten_keyframes(effect: &Effect) -> Cow<'_, Effect> {
+  let flattened = flattened_keyframe_parameters(effect);
+  if flattened.is_empty() {
+    return Cow::Borrowed(effect);
+  }
+
+  let mut flat = effect.clone();
+  for name in flattened {
+    if let Some(first) = flat.keyframes.remove(name).and_then(|k| k.first().cloned()) {
+      flat
+        .parameters
+        .insert(name.to_string(), EffectParameter::Float(first.value as f32));
+    }
+  }
+  Cow::Owned(flat)
+}
+
+/// Нужна ли цветокоррекция по каналам вместо eq
+fn is_complex_color_correction(parameters: &HashMap<String, EffectParameter>) -> bool {
+  parameters.keys().any(|name| {
+    name.contains("highlights_") || name.contains("midtones_") || name.contains("shadows_")
+  })
+}
+
 /// Построитель эффектов
 pub struct EffectBuilder<'a> {
   project: &'a ProjectSchema,
+  /// Фильтры, доступные в FFmpeg
+  capabilities: FilterCapabilities,
 }
 
 impl<'a> EffectBuilder<'a> {
   /// Создать новый построитель эффектов
   pub fn new(project: &'a ProjectSchema) -> Self {
-    Self { project }
+    Self {
+      project,
+      capabilities: FilterCapabilities::default(),
+    }
   }
 
-  /// Построить эффекты для клипа
+  /// Указать фильтры, доступные в FFmpeg
+  pub fn with_capabilities(mut self, capabilities: FilterCapabilities) -> Self {
+    self.capabilities = capabilities;
+    self
+  }
+
+  /// Построить эффекты для клипа.
+  ///
+  /// Порядок применения: фильтры клипа → эффекты клипа → эффекты трека,
+  /// которому принадлежит клип.
   pub async fn build_clip_effects(&self, clip: &Clip, input_index: usize) -> Result<String> {
     let mut filters = Vec::new();
 
@@ -37,16 +182,40 @@ impl<'a> EffectBuilder<'a> {
     // Применяем эффекты клипа
     for effect_id in &clip.effects {
       if let Some(effect) = self.find_effect(effect_id) {
-        let effect_str = self.build_effect(effect, input_index).await?;
+        let effect = flatten_keyframes(effect);
+        let effect_str = self.build_effect(&effect, input_index).await?;
         if !effect_str.is_empty() {
           filters.push(effect_str);
         }
       }
     }
 
+    // Применяем эффекты трека поверх эффектов клипа
+    for effect in self.track_effects(clip) {
+      let effect = flatten_keyframes(effect);
+      let effect_str = self.build_effect(&effect, input_index).await?;
+      if !effect_str.is_empty() {
+        filters.push(effect_str);
+      }
+    }
+
     Ok(filters.join(";"))
   }
 
+  /// Включенные эффекты трека клипа, кроме уже назначенных самому клипу
+  fn track_effects(&self, clip: &Clip) -> Vec<&Effect> {
+    let Some((track_idx, _)) = self.project.find_clip_position(&clip.id) else {
+      return Vec::new();
+    };
+    self.project.tracks[track_idx]
+      .effects
+      .iter()
+      .filter(|effect_id| !clip.effects.contains(effect_id))
+      .filter_map(|effect_id| self.find_effect(effect_id))
+      .filter(|effect| effect.enabled)
+      .collect()
+  }
+
   /// Построить аудио эффекты для клипа
   pub async fn build_audio_effects(&self, clip: &Clip, input_index: usize) -> Result<String> {
     let mut filters = Vec::new();
@@ -55,7 +224,8 @@ impl<'a> EffectBuilder<'a> {
     for effect_id in &clip.effects {
       if let Some(effect) = self.find_effect(effect_id) {
         if self.is_audio_effect(effect) {
-          let effect_str = self.build_audio_effect(effect, input_index)?;
+          let effect = flatten_keyframes(effect);
+          let effect_str = self.build_audio_effect(&effect, input_index)?;
           if !effect_str.is_empty() {
             filters.push(effect_str);
           }
@@ -82,6 +252,27 @@ impl<'a> EffectBuilder<'a> {
     let clip1_duration = clip1.end_time - clip1.start_time;
     let transition_start = clip1_duration - duration;
 
+    // Переходы на OpenGL без фильтра gl заменяются ближайшим xfade
+    if let Some((gl, fallback)) = gl_transition(&transition.transition_type) {
+      let (filter, name) = if self.capabilities.has_filter(GL_FILTER) {
+        ("gl", gl)
+      } else {
+        log::warn!(
+          "Фильтр gl недоступен, переход '{}' заменен на xfade={fallback}",
+          transition.id
+        );
+        ("xfade", fallback)
+      };
+      return Ok(format!(
+        "[v{}][v{}]{filter}=transition={name}:duration={}:offset={}[blend{}]",
+        input_index,
+        input_index + 1,
+        duration,
+        transition_start,
+        input_index
+      ));
+    }
+
     match transition.transition_type.as_str() {
       "fade" => Ok(format!(
         "[v{}][v{}]blend=all_expr='A*(1-T/{})+B*(T/{})'[blend{}]",
@@ -138,6 +329,9 @@ impl<'a> EffectBuilder<'a> {
   async fn build_effect(&self, effect: &Effect, input_index: usize) -> Result<String> {
     match &effect.effect_type {
       EffectType::ColorCorrection => self.build_color_correction(effect, input_index),
+      EffectType::Brightness => self.build_eq_adjustment(effect, "brightness", 0.0, input_index),
+      EffectType::Contrast => self.build_eq_adjustment(effect, "contrast", 1.0, input_index),
+      EffectType::Saturation => self.build_eq_adjustment(effect, "saturation", 1.0, input_index),
       EffectType::Blur => self.build_blur_effect(effect, input_index),
       EffectType::Sharpen => self.build_sharpen_effect(effect, input_index),
       EffectType::ChromaKey => self.build_chroma_key(effect, input_index),
@@ -251,21 +445,59 @@ impl<'a> EffectBuilder<'a> {
 
   /// Построить цветокоррекцию
   fn build_color_correction(&self, effect: &Effect, input_index: usize) -> Result<String> {
-    let brightness = self.get_param_value(&effect.parameters, "brightness", 0.0);
-    let contrast = self.get_param_value(&effect.parameters, "contrast", 1.0);
-    let saturation = self.get_param_value(&effect.parameters, "saturation", 1.0);
-    let gamma = self.get_param_value(&effect.parameters, "gamma", 1.0);
-
     // Проверяем, нужна ли сложная цветокоррекция
     let needs_complex = self.needs_complex_color_correction(&effect.parameters);
 
     if needs_complex {
-      self.build_complex_color_correction(&effect.parameters, input_index)
-    } else {
-      Ok(format!(
-        "[v{input_index}]eq=brightness={brightness}:contrast={contrast}:saturation={saturation}:gamma={gamma}[v{input_index}]"
-      ))
+      return self.build_complex_color_correction(&effect.parameters, input_index);
     }
+
+    // Параметры с ключевыми кадрами вычисляются eq для каждого кадра
+    let mut animated = false;
+    let mut value = |name: &str, default: f64| match effect.keyframes.get(name) {
+      Some(keyframes) if !keyframes.is_empty() => {
+        animated = true;
+        format!("'{}'", keyframe_expression(keyframes))
+      }
+      _ => self
+        .get_param_value(&effect.parameters, name, default)
+        .to_string(),
+    };
+    let brightness = value("brightness", 0.0);
+    let contrast = value("contrast", 1.0);
+    let saturation = value("saturation", 1.0);
+    let gamma = value("gamma", 1.0);
+    let eval = if animated { ":eval=frame" } else { "" };
+
+    Ok(format!(
+      "[v{input_index}]eq=brightness={brightness}:contrast={contrast}:saturation={saturation}:gamma={gamma}{eval}[v{input_index}]"
+    ))
+  }
+
+  /// Построить регулировку одного параметра eq по значению `value`
+  fn build_eq_adjustment(
+    &self,
+    effect: &Effect,
+    option: &str,
+    default: f64,
+    input_index: usize,
+  ) -> Result<String> {
+    let (value, eval) = match effect.keyframes.get("value") {
+      Some(keyframes) if !keyframes.is_empty() => (
+        format!("'{}'", keyframe_expression(keyframes)),
+        ":eval=frame",
+      ),
+      _ => (
+        self
+          .get_param_value(&effect.parameters, "value", default)
+          .to_string(),
+        "",
+      ),
+    };
+
+    Ok(format!(
+      "[v{input_index}]eq={option}={value}{eval}[v{input_index}]"
+    ))
   }
 
   /// Построить эффект размытия
@@ -273,8 +505,75 @@ impl<'a> EffectBuilder<'a> {
     let radius = self.get_param_value(&effect.parameters, "radius", 5.0);
     let sigma = self.get_param_value(&effect.parameters, "sigma", 1.0);
 
+    let radius_keyframes = effect.keyframes.get("radius").filter(|k| !k.is_empty());
+    let sigma_keyframes = effect.keyframes.get("sigma").filter(|k| !k.is_empty());
+    if radius_keyframes.is_none() && sigma_keyframes.is_none() {
+      return Ok(format!(
+        "[v{input_index}]gblur=radius={radius}:sigma={sigma}[v{input_index}]"
+      ));
+    }
+
+    // gblur не вычисляет выражения по времени, поэтому анимация собирается
+    // из постоянных участков, каждый из которых включен только в своем интервале
+    let mut times: Vec<f64> = radius_keyframes
+      .into_iter()
+      .chain(sigma_keyframes)
+      .flatten()
+      .map(|keyframe| keyframe.time)
+      .collect();
+    times.sort_by(|a, b| a.total_cmp(b));
+    times.dedup();
+
+    let mut segments: Vec<(Option<f64>, Option<f64>)> = Vec::new();
+    if times[0] > 0.0 {
+      segments.push((None, Some(times[0])));
+    }
+    for pair in times.windows(2) {
+      let step = (pair[1] - pair[0]) / KEYFRAME_PIECEWISE_STEPS as f64;
+      for i in 0..KEYFRAME_PIECEWISE_STEPS {
+        let start = pair[0] + step * i as f64;
+        let end = if i + 1 == KEYFRAME_PIECEWISE_STEPS {
+          pair[1]
+        } else {
+          start + step
+        };
+        segments.push((Some(start), Some(end)));
+      }
+    }
+    segments.push((times.last().copied(), None));
+
+    let sample = |keyframes: Option<&Vec<ParamKeyframe>>, time: f64, default: f64| {
+      keyframes
+        .and_then(|keyframes| keyframe_value_at(keyframes, time))
+        .unwrap_or(default)
+    };
+    let blurs: Vec<String> = segments
+      .into_iter()
+      .map(|(start, end)| {
+        let time = match (start, end) {
+          (Some(start), Some(end)) => (start + end) / 2.0,
+          (Some(time), None) | (None, Some(time)) => time,
+          (None, None) => 0.0,
+        };
+        let enable = match (start, end) {
+          (None, Some(end)) => format!("lt(t,{})", expr_number(end)),
+          (Some(start), Some(end)) => {
+            format!("gte(t,{})*lt(t,{})", expr_number(start), expr_number(end))
+          }
+          (Some(start), None) => format!("gte(t,{})", expr_number(start)),
+          (None, None) => "1".to_string(),
+        };
+        format!(
+          "gblur=radius={}:sigma={}:enable='{enable}'",
+          expr_number(sample(radius_keyframes, time, radius)),
+          expr_number(sample(sigma_keyframes, time, sigma))
+        )
+      })
+      .collect();
+
     Ok(format!(
-      "[v{input_index}]gblur=radius={radius}:sigma={sigma}[v{input_index}]"
+      "[v{input_index}]{}[v{input_index}]",
+      blurs.join(",")
     ))
   }
 
@@ -288,14 +587,71 @@ impl<'a> EffectBuilder<'a> {
     ))
   }
 
-  /// Построить хромакей
+  /// Построить хромакей.
+  ///
+  /// Ключ выделяется `chromakey` (YUV) или `colorkey` (RGB), отсветы цвета
+  /// ключа убираются `despill`. Если задан фон, вырезанный клип накладывается
+  /// на фон, приведенный к размеру клипа; выход остается под меткой `[v{index}]`.
   fn build_chroma_key(&self, effect: &Effect, input_index: usize) -> Result<String> {
-    let color = self.get_param_string(&effect.parameters, "color", "0x00ff00");
-    let similarity = self.get_param_value(&effect.parameters, "similarity", 0.3);
-    let blend = self.get_param_value(&effect.parameters, "blend", 0.0);
+    let params = ChromaKeyParams::from_effect(effect);
+    let color = normalize_color(&params.key_color);
+    let mut key = match params.key_mode {
+      ChromaKeyMode::Chroma => format!(
+        "format=yuva420p,chromakey={color}:{}:{}",
+        params.similarity, params.blend
+      ),
+      ChromaKeyMode::Color => format!(
+        "format=rgba,colorkey={color}:{}:{}",
+        params.similarity, params.blend
+      ),
+    };
+    if params.spill_suppression > 0.0 {
+      key.push_str(&format!(
+        ",despill=type={}:expand={}",
+        params.spill_type(),
+        params.spill_suppression
+      ));
+    }
+
+    let label = format!("v{input_index}");
+    let Some(background) = &params.background else {
+      return Ok(format!("[{label}]{key}[{label}]"));
+    };
+
+    let fps = self.project.timeline.fps;
+    let source = match background {
+      ChromaKeyBackground::Color(background_color) => {
+        format!("color=c={}:r={fps}", normalize_color(background_color))
+      }
+      // Изображение повторяется с частотой проекта
+      ChromaKeyBackground::Image(path) => format!(
+        "movie={}:loop=0,setpts=N/({fps}*TB)",
+        quote_filter_path(&path.to_string_lossy())
+      ),
+      // Короткий фоновый клип удерживает последний кадр
+      ChromaKeyBackground::Clip(clip_id) => {
+        let clip = self
+          .project
+          .tracks
+          .iter()
+          .flat_map(|track| &track.clips)
+          .find(|clip| &clip.id == clip_id);
+        let Some((clip, ClipSource::File(path))) = clip.map(|clip| (clip, &clip.source)) else {
+          return Err(VideoCompilerError::validation(format!(
+            "Фоновый клип '{clip_id}' хромакея '{}' не найден",
+            effect.id
+          )));
+        };
+        format!(
+          "movie={}:seek_point={},setpts=PTS-STARTPTS,tpad=stop=-1:stop_mode=clone",
+          quote_filter_path(path),
+          clip.source_start
+        )
+      }
+    };
 
     Ok(format!(
-      "[v{input_index}]chromakey={color}:{similarity}:{blend}[v{input_index}]"
+      "[{label}]{key}[{label}key];{source}[{label}keybg];[{label}keybg][{label}key]scale2ref[{label}keybgs][{label}keyfg];[{label}keybgs][{label}keyfg]overlay=shortest=1,format=yuv420p[{label}]"
     ))
   }
 
@@ -384,9 +740,7 @@ impl<'a> EffectBuilder<'a> {
 
   /// Проверить, нужна ли сложная цветокоррекция
   fn needs_complex_color_correction(&self, parameters: &HashMap<String, EffectParameter>) -> bool {
-    parameters.keys().any(|name| {
-      name.contains("highlights_") || name.contains("midtones_") || name.contains("shadows_")
-    })
+    is_complex_color_correction(parameters)
   }
 
   /// Обработать шаблон эффекта
@@ -573,6 +927,8 @@ mod tests {
       ffmpeg_command: None,
       easing: None,
       direction: None,
+      from_clip_id: None,
+      to_clip_id: None,
     };
 
     let result = builder
@@ -581,6 +937,57 @@ mod tests {
     assert!(result.is_ok());
   }
 
+  #[tokio::test]
+  async fn test_gl_transition_uses_xfade_fallback_without_gl() {
+    use crate::video_compiler::schema::effects::{Transition, TransitionDuration};
+    use crate::video_compiler::schema::timeline::Clip;
+    use std::path::PathBuf;
+
+    let project = create_minimal_project();
+    let clip1 = Clip::new(PathBuf::from("/test/video1.mp4"), 0.0, 5.0);
+    let clip2 = Clip::new(PathBuf::from("/test/video2.mp4"), 5.0, 10.0);
+    let transition = Transition {
+      id: "cube".to_string(),
+      name: "3D Cube".to_string(),
+      transition_type: "cube-3d".to_string(),
+      duration: TransitionDuration {
+        value: 1.0,
+        min: None,
+        max: None,
+      },
+      category: None,
+      tags: Vec::new(),
+      complexity: None,
+      enabled: true,
+      parameters: HashMap::new(),
+      ffmpeg_command: None,
+      easing: None,
+      direction: None,
+      from_clip_id: None,
+      to_clip_id: None,
+    };
+
+    let without_gl = EffectBuilder::new(&project)
+      .with_capabilities(FilterCapabilities::from_filters(["xfade"]))
+      .build_transition_filter(&clip1, &clip2, &transition, 0)
+      .await
+      .unwrap();
+    assert_eq!(
+      without_gl,
+      "[v0][v1]xfade=transition=slideleft:duration=1:offset=4[blend0]"
+    );
+
+    let with_gl = EffectBuilder::new(&project)
+      .with_capabilities(FilterCapabilities::from_filters(["xfade", "gl"]))
+      .build_transition_filter(&clip1, &clip2, &transition, 0)
+      .await
+      .unwrap();
+    assert_eq!(
+      with_gl,
+      "[v0][v1]gl=transition=cube:duration=1:offset=4[blend0]"
+    );
+  }
+
   #[test]
   fn test_find_effect() {
     let project = create_complex_project(); // Проект с эффектами
@@ -609,8 +1016,8 @@ mod tests {
     let builder = EffectBuilder::new(&project);
 
     let effect = create_test_effect(EffectType::Brightness);
-    let result = builder.build_effect(&effect, 0).await;
-    assert!(result.is_ok());
+    let result = builder.build_effect(&effect, 0).await.unwrap();
+    assert_eq!(result, "[v0]eq=brightness=1.2[v0]");
   }
 
   #[test]
@@ -747,6 +1154,111 @@ mod tests {
     assert!(filter.contains("0x00ff00"));
   }
 
+  #[test]
+  fn test_chroma_key_with_despill_without_background() {
+    let project = create_minimal_project();
+    let builder = EffectBuilder::new(&project);
+    let effect = ChromaKeyParams {
+      key_color: "#0000FF".to_string(),
+      spill_suppression: 0.5,
+      ..Default::default()
+    }
+    .to_effect();
+
+    assert_eq!(
+      builder.build_chroma_key(&effect, 2).unwrap(),
+      "[v2]format=yuva420p,chromakey=0x0000ff:0.3:0,despill=type=blue:expand=0.5[v2]"
+    );
+  }
+
+  #[test]
+  fn test_chroma_key_over_solid_color() {
+    let project = create_minimal_project();
+    let builder = EffectBuilder::new(&project);
+    let effect = ChromaKeyParams {
+      similarity: 0.25,
+      blend: 0.05,
+      background: Some(ChromaKeyBackground::Color("#102030".to_string())),
+      ..Default::default()
+    }
+    .to_effect();
+
+    let fps = project.timeline.fps;
+    assert_eq!(
+      builder.build_chroma_key(&effect, 1).unwrap(),
+      format!(
+        "[v1]format=yuva420p,chromakey=0x00ff00:0.25:0.05[v1key];\
+         color=c=0x102030:r={fps}[v1keybg];\
+         [v1keybg][v1key]scale2ref[v1keybgs][v1keyfg];\
+         [v1keybgs][v1keyfg]overlay=shortest=1,format=yuv420p[v1]"
+      )
+    );
+  }
+
+  #[test]
+  fn test_chroma_key_over_image() {
+    use std::path::PathBuf;
+
+    let project = create_minimal_project();
+    let builder = EffectBuilder::new(&project);
+    let effect = ChromaKeyParams {
+      key_mode: ChromaKeyMode::Color,
+      background: Some(ChromaKeyBackground::Image(PathBuf::from(
+        "/media/studio: set/bg.png",
+      ))),
+      ..Default::default()
+    }
+    .to_effect();
+
+    let fps = project.timeline.fps;
+    let filter = builder.build_chroma_key(&effect, 0).unwrap();
+    assert_eq!(
+      filter,
+      format!(
+        "[v0]format=rgba,colorkey=0x00ff00:0.3:0[v0key];\
+         movie='/media/studio\\: set/bg.png':loop=0,setpts=N/({fps}*TB)[v0keybg];\
+         [v0keybg][v0key]scale2ref[v0keybgs][v0keyfg];\
+         [v0keybgs][v0keyfg]overlay=shortest=1,format=yuv420p[v0]"
+      )
+    );
+  }
+
+  #[test]
+  fn test_chroma_key_over_clip_requires_existing_clip() {
+    let project = create_project_with_clips();
+    let builder = EffectBuilder::new(&project);
+    let background = &project.tracks[0].clips[0];
+
+    let effect = ChromaKeyParams {
+      background: Some(ChromaKeyBackground::Clip(background.id.clone())),
+      ..Default::default()
+    }
+    .to_effect();
+    let filter = builder.build_chroma_key(&effect, 3).unwrap();
+    assert!(filter.contains(&format!(
+      "movie='{}':seek_point={}",
+      match &background.source {
+        ClipSource::File(path) => path.replace(':', "\\:"),
+        _ => unreachable!(),
+      },
+      background.source_start
+    )));
+    assert!(filter.ends_with("overlay=shortest=1,format=yuv420p[v3]"));
+
+    let missing = ChromaKeyParams {
+      background: Some(ChromaKeyBackground::Clip("missing".to_string())),
+      ..Default::default()
+    }
+    .to_effect();
+    assert!(builder.build_chroma_key(&missing, 3).is_err());
+  }
+
+  #[test]
+  fn test_quote_filter_path() {
+    assert_eq!(quote_filter_path("/tmp/bg.png"), "'/tmp/bg.png'");
+    assert_eq!(quote_filter_path(r"C:\bg's.png"), r"'C\:\\bg'\\\''s.png'");
+  }
+
   #[test]
   fn test_build_custom_effect() {
     let project = create_minimal_project();
@@ -1038,6 +1550,8 @@ mod tests {
         ffmpeg_command: None,
         easing: None,
         direction: None,
+        from_clip_id: None,
+        to_clip_id: None,
       };
 
       let result = builder
@@ -1082,4 +1596,129 @@ mod tests {
     let result = builder.convert_parameter_to_string(&path_param);
     assert_eq!(result, "/test/file.txt");
   }
+
+  fn brightness_ramp() -> Vec<ParamKeyframe> {
+    use crate::video_compiler::schema::effects::KeyframeEasing;
+    vec![
+      ParamKeyframe::new(0.0, 0.0, KeyframeEasing::Linear),
+      ParamKeyframe::new(2.0, 0.5, KeyframeEasing::Linear),
+    ]
+  }
+
+  #[test]
+  fn test_keyframe_expression_brightness_ramp() {
+    assert_eq!(
+      keyframe_expression(&brightness_ramp()),
+      "if(lt(t,0),0,if(lt(t,2),0+(0.5)*(((t-0)/2)),0.5))"
+    );
+  }
+
+  #[test]
+  fn test_keyframe_expression_easing_and_single_keyframe() {
+    use crate::video_compiler::schema::effects::KeyframeEasing;
+
+    let eased = vec![
+      ParamKeyframe::new(1.0, 1.0, KeyframeEasing::EaseIn),
+      ParamKeyframe::new(3.0, 0.5, KeyframeEasing::Linear),
+    ];
+    assert_eq!(
+      keyframe_expression(&eased),
+      "if(lt(t,1),1,if(lt(t,3),1+(-0.5)*(pow(((t-1)/2),2)),0.5))"
+    );
+    assert_eq!(keyframe_expression(&eased[..1]), "1");
+  }
+
+  #[test]
+  fn test_build_color_correction_with_keyframes() {
+    let project = create_minimal_project();
+    let builder = EffectBuilder::new(&project);
+
+    let mut effect = Effect::new(EffectType::ColorCorrection, "Focus Intro".to_string());
+    effect
+      .parameters
+      .insert("brightness".to_string(), EffectParameter::Float(0.0));
+    effect
+      .keyframes
+      .insert("brightness".to_string(), brightness_ramp());
+
+    let filter = builder.build_color_correction(&effect, 1).unwrap();
+    assert_eq!(
+      filter,
+      "[v1]eq=brightness='if(lt(t,0),0,if(lt(t,2),0+(0.5)*(((t-0)/2)),0.5))':contrast=1:saturation=1:gamma=1:eval=frame[v1]"
+    );
+  }
+
+  #[test]
+  fn test_build_blur_effect_piecewise_keyframes() {
+    use crate::video_compiler::schema::effects::KeyframeEasing;
+
+    let project = create_minimal_project();
+    let builder = EffectBuilder::new(&project);
+
+    let mut effect = Effect::new(EffectType::Blur, "Focus Pull".to_string());
+    effect.keyframes.insert(
+      "sigma".to_string(),
+      vec![
+        ParamKeyframe::new(1.0, 10.0, KeyframeEasing::Linear),
+        ParamKeyframe::new(3.0, 0.0, KeyframeEasing::Linear),
+      ],
+    );
+
+    let filter = builder.build_blur_effect(&effect, 0).unwrap();
+    let blurs: Vec<&str> = filter
+      .trim_start_matches("[v0]")
+      .trim_end_matches("[v0]")
+      .split(",gblur")
+      .collect();
+
+    // Удержание до первого кадра, 8 участков между кадрами и удержание после
+    assert_eq!(blurs.len(), KEYFRAME_PIECEWISE_STEPS + 2);
+    assert_eq!(blurs[0], "gblur=radius=5:sigma=10:enable='lt(t,1)'");
+    assert_eq!(
+      blurs[1],
+      "=radius=5:sigma=9.375:enable='gte(t,1)*lt(t,1.25)'"
+    );
+    assert_eq!(
+      blurs[8],
+      "=radius=5:sigma=0.625:enable='gte(t,2.75)*lt(t,3)'"
+    );
+    assert_eq!(blurs[9], "=radius=5:sigma=0:enable='gte(t,3)'");
+  }
+
+  #[test]
+  fn test_unsupported_keyframes_flattened_with_warning() {
+    use crate::video_compiler::schema::effects::KeyframeEasing;
+
+    let mut project = create_minimal_project();
+    let mut effect = Effect::new(EffectType::Sharpen, "Sharpen".to_string());
+    effect.id = "sharpen-1".to_string();
+    effect.keyframes.insert(
+      "amount".to_string(),
+      vec![
+        ParamKeyframe::new(0.0, 2.5, KeyframeEasing::Linear),
+        ParamKeyframe::new(1.0, 0.5, KeyframeEasing::Linear),
+      ],
+    );
+    project.effects.push(effect.clone());
+
+    assert_eq!(flattened_keyframe_parameters(&effect), vec!["amount"]);
+    let warnings = keyframe_warnings(&project);
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("sharpen-1"));
+    assert!(warnings[0].contains("amount"));
+
+    let builder = EffectBuilder::new(&project);
+    let flat = flatten_keyframes(&effect);
+    assert!(flat.keyframes.is_empty());
+    let filter = builder.build_sharpen_effect(&flat, 0).unwrap();
+    assert!(filter.contains(":2.5["));
+
+    // Поддерживаемые параметры не попадают в предупреждения
+    let mut color = Effect::new(EffectType::ColorCorrection, "Color".to_string());
+    color
+      .keyframes
+      .insert("brightness".to_string(), brightness_ramp());
+    assert!(flattened_keyframe_parameters(&color).is_empty());
+    assert!(matches!(flatten_keyframes(&color), Cow::Borrowed(_)));
+  }
 }
@@ -2,32 +2,129 @@
 
 use tokio::process::Command;
 
-use crate::video_compiler::error::Result;
-use crate::video_compiler::schema::{Clip, ProjectSchema, Track, TrackType, Transition};
+use crate::video_compiler::error::{Result, VideoCompilerError};
+use crate::video_compiler::schema::{
+  Clip, ExternalAudio, HdrMode, ProjectSchema, RenditionSpec, TimecodeSource, Track, TrackType,
+  Transition,
+};
 
+use super::capabilities::FilterCapabilities;
 use super::effects::EffectBuilder;
+use super::inputs::InputBuilder;
+use super::reframe::clip_geometry_filters;
+use super::stems::AudioStem;
 use super::subtitles::SubtitleBuilder;
 use super::templates::TemplateBuilder;
 
+/// Тонмаппинг HDR (PQ/HLG, BT.2020) в SDR BT.709 через zscale
+const TONEMAP_TO_SDR_FILTER: &str = "zscale=t=linear:npl=100,format=gbrpf32le,zscale=p=bt709,\
+tonemap=tonemap=hable:desat=0,zscale=t=bt709:m=bt709:r=tv,format=yuv420p";
+
+/// Метки выходных потоков графа фильтров для одного варианта экспорта
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenditionLabels {
+  /// Метка видео потока, например `[rv0out]`
+  pub video: Option<String>,
+  /// Метка аудио потока, например `[ra0]`
+  pub audio: Option<String>,
+}
+
+/// Метка потока стема аудио трека с индексом `track_idx`
+fn stem_label(track_idx: usize) -> String {
+  format!("[outa_track{track_idx}]")
+}
+
+/// Четный размер кадра для масштабирования (yuv420p требует четных сторон)
+fn even_dimension(value: f32) -> u32 {
+  ((value / 2.0).round() as u32 * 2).max(2)
+}
+
 /// Построитель фильтров
 pub struct FilterBuilder<'a> {
   project: &'a ProjectSchema,
+  /// Учитывать скрытые треки (превью и пререндер сегментов)
+  include_hidden: bool,
+  /// Индекс входа изображения водяного знака
+  watermark_input: Option<usize>,
+  /// Выводить обработанные аудио треки отдельными потоками для стемов
+  stems: bool,
   effect_builder: EffectBuilder<'a>,
   subtitle_builder: SubtitleBuilder<'a>,
   template_builder: TemplateBuilder<'a>,
 }
 
 impl<'a> FilterBuilder<'a> {
-  /// Создать новый построитель фильтров
+  /// Создать новый построитель фильтров для финального рендера
   pub fn new(project: &'a ProjectSchema) -> Self {
     Self {
       project,
+      include_hidden: false,
+      watermark_input: None,
+      stems: false,
       effect_builder: EffectBuilder::new(project),
       subtitle_builder: SubtitleBuilder::new(project),
       template_builder: TemplateBuilder::new(project),
     }
   }
 
+  /// Создать построитель фильтров для превью: скрытые треки учитываются
+  pub fn for_preview(project: &'a ProjectSchema) -> Self {
+    Self {
+      include_hidden: true,
+      ..Self::new(project)
+    }
+  }
+
+  /// Указать фильтры, доступные в FFmpeg, для выбора переходов
+  pub fn with_capabilities(mut self, capabilities: FilterCapabilities) -> Self {
+    self.effect_builder = self.effect_builder.with_capabilities(capabilities);
+    self
+  }
+
+  /// Наложить водяной знак из настроек экспорта; изображение подано
+  /// входом `input_index`
+  pub fn with_watermark(mut self, input_index: usize) -> Self {
+    self.watermark_input = Some(input_index);
+    self
+  }
+
+  /// Дополнительно выводить каждый аудио трек в отдельный поток
+  /// (см. [`FilterBuilder::audio_stems`])
+  pub fn with_stems(mut self) -> Self {
+    self.stems = true;
+    self
+  }
+
+  /// Аудио стемы графа фильтров: по одному на каждый аудио трек с клипами.
+  ///
+  /// Поток стема берется после клиповых фильтров, громкости, фейдов и
+  /// ducking трека, но до смешивания с другими треками.
+  pub fn audio_stems(&self) -> Vec<AudioStem> {
+    if !self.stems {
+      return Vec::new();
+    }
+    self
+      .get_audio_tracks()
+      .into_iter()
+      .enumerate()
+      .filter(|(_, track)| !track.clips.is_empty())
+      .map(|(track_idx, track)| AudioStem {
+        track_id: track.id.clone(),
+        track_name: track.name.clone(),
+        label: stem_label(track_idx),
+      })
+      .collect()
+  }
+
+  /// Построитель входов с тем же набором треков
+  fn input_builder(&self) -> InputBuilder<'a> {
+    if self.include_hidden {
+      InputBuilder::for_preview(self.project)
+    } else {
+      InputBuilder::new(self.project)
+    }
+  }
+
   /// Добавить комплексные фильтры
   pub async fn add_filter_complex(&self, cmd: &mut Command) -> Result<()> {
     let filter_complex = self.build_filter_complex().await?;
@@ -38,7 +135,7 @@ impl<'a> FilterBuilder<'a> {
       // Маппинг выходов
       let has_video = self.has_video_tracks();
       let has_audio = self.has_audio_tracks();
-      let has_subtitles = !self.project.subtitles.is_empty();
+      let has_subtitles = self.burns_subtitles();
 
       if has_video {
         if has_subtitles {
@@ -56,6 +153,69 @@ impl<'a> FilterBuilder<'a> {
     Ok(())
   }
 
+  /// Добавить комплексные фильтры для многовариантного экспорта.
+  ///
+  /// Граф фильтров строится один раз, после чего итоговые потоки
+  /// размножаются через split/asplit и масштабируются под каждый вариант.
+  /// Возвращает метки потоков в порядке `renditions`; маппинг выполняет вызывающий.
+  pub async fn add_rendition_filter_complex(
+    &self,
+    cmd: &mut Command,
+    renditions: &[RenditionSpec],
+  ) -> Result<Vec<RenditionLabels>> {
+    let filter_complex = self.build_filter_complex().await?;
+    if filter_complex.is_empty() {
+      return Err(VideoCompilerError::validation(
+        "Для многовариантного экспорта нужен хотя бы один трек",
+      ));
+    }
+
+    let count = renditions.len();
+    let mut filters = vec![filter_complex];
+
+    let has_video = self.has_video_tracks();
+    if has_video {
+      let source = if self.burns_subtitles() {
+        "[outv_with_subs]"
+      } else {
+        "[outv]"
+      };
+      let outputs: String = (0..count).map(|i| format!("[rv{i}]")).collect();
+      filters.push(format!("{source}split={count}{outputs}"));
+
+      for (i, rendition) in renditions.iter().enumerate() {
+        filters.push(format!(
+          "[rv{i}]scale={}:{}[rv{i}out]",
+          rendition.resolution.width, rendition.resolution.height
+        ));
+      }
+    }
+
+    let has_audio = self.has_audio_tracks();
+    if has_audio {
+      let outputs: String = (0..count).map(|i| format!("[ra{i}]")).collect();
+      filters.push(format!("[outa]asplit={count}{outputs}"));
+    }
+
+    cmd.args(["-filter_complex", &filters.join(";")]);
+
+    Ok(
+      (0..count)
+        .map(|i| RenditionLabels {
+          video: has_video.then(|| format!("[rv{i}out]")),
+          audio: has_audio.then(|| format!("[ra{i}]")),
+        })
+        .collect(),
+    )
+  }
+
+  /// Нужно ли впечатывать субтитры в видео
+  fn burns_subtitles(&self) -> bool {
+    !self.project.subtitles.is_empty()
+      && self.project.settings.export.subtitle_mode.burns()
+      && self.has_video_tracks()
+  }
+
   /// Добавить фильтры для сегмента
   pub async fn add_segment_filters(
     &self,
@@ -92,20 +252,20 @@ impl<'a> FilterBuilder<'a> {
     if self.has_video_tracks() {
       let video_filter = self.build_video_filter_chain(&mut input_index).await?;
       if !video_filter.is_empty() {
-        filters.push(video_filter);
+        filters.push(self.apply_timecode_burn_in(self.apply_watermark(video_filter)));
       }
     }
 
-    // Обрабатываем аудио треки
-    if self.has_audio_tracks() {
+    // Обрабатываем аудио треки (включая внешний звук клипов видео треков)
+    if self.has_audio_tracks() || self.has_external_audio() {
       let audio_filter = self.build_audio_filter_chain(&mut input_index).await?;
       if !audio_filter.is_empty() {
         filters.push(audio_filter);
       }
     }
 
-    // Обрабатываем субтитры
-    if !self.project.subtitles.is_empty() {
+    // Обрабатываем субтитры (в режиме Soft они идут отдельной дорожкой)
+    if self.burns_subtitles() {
       let subtitle_filter = self.subtitle_builder.build_subtitle_filter().await?;
       if !subtitle_filter.is_empty() {
         filters.push(subtitle_filter);
@@ -150,10 +310,6 @@ impl<'a> FilterBuilder<'a> {
 
     // Обрабатываем каждый видео трек
     for (track_idx, track) in video_tracks.iter().enumerate() {
-      if !track.enabled {
-        continue;
-      }
-
       let mut track_filters = Vec::new();
 
       // Обрабатываем клипы трека
@@ -208,16 +364,19 @@ impl<'a> FilterBuilder<'a> {
   async fn build_audio_filter_chain(&self, input_index: &mut usize) -> Result<String> {
     let mut filters = Vec::new();
     let audio_tracks = self.get_audio_tracks();
+    let input_builder = self.input_builder();
 
-    for (track_idx, track) in audio_tracks.iter().enumerate() {
-      if !track.enabled {
-        continue;
-      }
+    let mut built_tracks = Vec::new();
+    let mut built_indices = Vec::new();
 
+    for (track_idx, track) in audio_tracks.iter().enumerate() {
       let mut track_filters = Vec::new();
 
       for clip in &track.clips {
-        let clip_filter = self.build_audio_clip_filter(clip, *input_index).await?;
+        let external_index = input_builder.get_external_audio_input_index(&clip.id);
+        let clip_filter = self
+          .build_audio_clip_filter(clip, *input_index, external_index)
+          .await?;
         track_filters.push(clip_filter);
         *input_index += 1;
       }
@@ -231,27 +390,123 @@ impl<'a> FilterBuilder<'a> {
           track_idx
         );
         filters.push(track_filter);
+        built_tracks.push((*track, format!("[atrack{track_idx}]")));
+        built_indices.push(track_idx);
       }
     }
 
+    let mut track_labels = Self::apply_ducking(&built_tracks, &mut filters);
+
+    // Стемы: копия каждого трека уходит в отдельный выход, минуя amix
+    if self.stems {
+      for (label, track_idx) in track_labels.iter_mut().zip(built_indices) {
+        let mix_label = format!("[astem{track_idx}mix]");
+        filters.push(format!(
+          "{label}asplit=2{mix_label}{}",
+          stem_label(track_idx)
+        ));
+        *label = mix_label;
+      }
+    }
+
+    // Внешний звук клипов видео треков
+    if let Some(external_track) = self.build_external_audio_track() {
+      filters.push(external_track);
+      track_labels.push("[atrack_ext]".to_string());
+    }
+
     // Смешиваем аудио треки
-    if filters.len() > 1 {
+    if track_labels.len() > 1 {
       let mix_filter = format!(
         "{}amix=inputs={}[outa]",
-        (0..filters.len())
-          .map(|i| format!("[atrack{i}]"))
-          .collect::<Vec<_>>()
-          .join(""),
-        filters.len()
+        track_labels.join(""),
+        track_labels.len()
       );
       filters.push(mix_filter);
-    } else if filters.len() == 1 {
-      filters.push("[atrack0][outa]".to_string());
+    } else if let Some(label) = track_labels.first() {
+      filters.push(format!("{label}[outa]"));
     }
 
     Ok(filters.join(";"))
   }
 
+  /// Применить ducking треков.
+  ///
+  /// Выход sidechain трека размножается через asplit: одна копия идет в amix,
+  /// остальные подаются вторым входом sidechaincompress приглушаемых треков.
+  /// Треки обрабатываются от источников к зависимым, поэтому sidechain может
+  /// сам быть приглушенным треком. Возвращает метки треков для amix.
+  fn apply_ducking(tracks: &[(&Track, String)], filters: &mut Vec<String>) -> Vec<String> {
+    let count = tracks.len();
+    let position = |id: &str| tracks.iter().position(|(track, _)| track.id == id);
+
+    // Индекс sidechain трека; ссылки на отсутствующие в рендере треки игнорируются
+    let mut sidechain: Vec<Option<usize>> = tracks
+      .iter()
+      .enumerate()
+      .map(|(i, (track, _))| {
+        let ducking = track.ducking.as_ref()?;
+        position(&ducking.sidechain_track_id).filter(|&j| j != i)
+      })
+      .collect();
+
+    // Глубина в цепочке sidechain; циклы (отклоняются валидацией) разрываются
+    let mut depth = vec![0usize; count];
+    for i in 0..count {
+      let mut current = sidechain[i];
+      while let Some(j) = current {
+        depth[i] += 1;
+        if depth[i] > count {
+          sidechain[i] = None;
+          depth[i] = 0;
+          break;
+        }
+        current = sidechain[j];
+      }
+    }
+
+    let mut consumers = vec![0usize; count];
+    for j in sidechain.iter().flatten() {
+      consumers[*j] += 1;
+    }
+
+    let mut order: Vec<usize> = (0..count).collect();
+    order.sort_by_key(|&i| depth[i]);
+
+    let mut labels: Vec<String> = tracks.iter().map(|(_, label)| label.clone()).collect();
+    let mut sidechain_outputs: Vec<Vec<String>> = vec![Vec::new(); count];
+
+    for i in order {
+      let mut label = labels[i].clone();
+
+      if let (Some(j), Some(ducking)) = (sidechain[i], tracks[i].0.ducking.as_ref()) {
+        if let Some(sidechain_label) = sidechain_outputs[j].pop() {
+          filters.push(format!(
+            "{label}{sidechain_label}sidechaincompress={}[aduck{i}]",
+            ducking.to_ffmpeg_params()
+          ));
+          label = format!("[aduck{i}]");
+        }
+      }
+
+      if consumers[i] > 0 {
+        let mix_label = format!("[asc{i}mix]");
+        let outputs: Vec<String> = (0..consumers[i]).map(|k| format!("[asc{i}_{k}]")).collect();
+        filters.push(format!(
+          "{label}asplit={}{mix_label}{}",
+          consumers[i] + 1,
+          outputs.concat()
+        ));
+        sidechain_outputs[i] = outputs;
+        label = mix_label;
+      }
+
+      labels[i] = label;
+    }
+
+    labels
+  }
+
   /// Построить видео фильтры для сегмента
   async fn build_segment_video_filter_chain(
     &self,
@@ -265,10 +520,6 @@ impl<'a> FilterBuilder<'a> {
     let video_tracks = self.get_video_tracks();
 
     for (track_idx, track) in video_tracks.iter().enumerate() {
-      if !track.enabled {
-        continue;
-      }
-
       let mut track_filters = Vec::new();
 
       for clip in &track.clips {
@@ -305,16 +556,15 @@ impl<'a> FilterBuilder<'a> {
     let audio_tracks = self.get_audio_tracks();
 
     for track in audio_tracks {
-      if !track.enabled {
-        continue;
-      }
-
       for clip in &track.clips {
         let clip_duration = clip.end_time - clip.start_time;
         let clip_end = clip.start_time + clip_duration;
 
+        // Сегменты пререндера не добавляют внешние входы — используем встроенный звук
         if clip.start_time < end_time && clip_end > start_time {
-          let clip_filter = self.build_audio_clip_filter(clip, *input_index).await?;
+          let clip_filter = self
+            .build_audio_clip_filter(clip, *input_index, None)
+            .await?;
           filters.push(clip_filter);
           *input_index += 1;
         }
@@ -333,15 +583,59 @@ impl<'a> FilterBuilder<'a> {
   ) -> Result<String> {
     let mut filters = Vec::new();
 
-    // Базовые настройки клипа
-    let base_filter = format!(
-      "[{}:v]scale={}:{},setpts=PTS-STARTPTS[v{}]",
-      input_index,
-      self.project.settings.resolution.width,
-      self.project.settings.resolution.height,
-      input_index
-    );
-    filters.push(base_filter);
+    // Вертикальное видео выпрямляется до любых других фильтров
+    let rotation = clip
+      .rotation_filter()
+      .map(|filter| format!("{filter},"))
+      .unwrap_or_default();
+
+    // Тонмаппинг выполняется до масштабирования и эффектов
+    let tonemap = if self.project.settings.export.hdr_mode == HdrMode::ToneMapToSdr {
+      format!("{TONEMAP_TO_SDR_FILTER},")
+    } else {
+      String::new()
+    };
+
+    // Кадрирование, отражение и поворот клипа выполняются до масштабирования
+    let geometry: String = clip
+      .transform
+      .as_ref()
+      .map(clip_geometry_filters)
+      .unwrap_or_default()
+      .into_iter()
+      .map(|filter| format!("{filter},"))
+      .collect();
+
+    let source_timecode = self.source_timecode_filter(clip);
+    let width = self.project.settings.resolution.width;
+    let height = self.project.settings.resolution.height;
+    match clip
+      .transform
+      .as_ref()
+      .filter(|transform| !transform.is_full_frame())
+    {
+      // Уменьшенный или смещенный клип накладывается на черный кадр проекта,
+      // выход остается под той же меткой [v{index}]
+      Some(transform) => {
+        filters.push(format!(
+          "[{input_index}:v]{rotation}{tonemap}{geometry}scale={}:{},setpts=PTS-STARTPTS{source_timecode}[v{input_index}fg]",
+          even_dimension(width as f32 * transform.scale_x),
+          even_dimension(height as f32 * transform.scale_y)
+        ));
+        filters.push(format!(
+          "color=c=black:s={width}x{height}:r={}[v{input_index}bg]",
+          self.project.timeline.fps
+        ));
+        filters.push(format!(
+          "[v{input_index}bg][v{input_index}fg]overlay=x={}:y={}:shortest=1[v{input_index}]",
+          (transform.position_x * width as f32).round() as i32,
+          (transform.position_y * height as f32).round() as i32
+        ));
+      }
+      None => filters.push(format!(
+        "[{input_index}:v]{rotation}{tonemap}{geometry}scale={width}:{height},setpts=PTS-STARTPTS{source_timecode}[v{input_index}]"
+      )),
+    }
 
     // Применяем эффекты
     let effects_filter = self
@@ -352,6 +646,15 @@ impl<'a> FilterBuilder<'a> {
       filters.push(effects_filter);
     }
 
+    // Нарастание и затухание клипа
+    let fades = clip.video_fade_filters();
+    if !fades.is_empty() {
+      filters.push(format!(
+        "[v{input_index}]{}[v{input_index}]",
+        fades.join(",")
+      ));
+    }
+
     // Применяем шаблоны
     if let Some(template_id) = &clip.template_id {
       let template_filter = self
@@ -366,15 +669,59 @@ impl<'a> FilterBuilder<'a> {
     Ok(filters.join(";"))
   }
 
-  /// Построить фильтр для аудио клипа
-  async fn build_audio_clip_filter(&self, clip: &Clip, input_index: usize) -> Result<String> {
+  /// Построить цепочку фильтров одного клипа для экспорта кадра.
+  ///
+  /// Клип подан входом 0, результат выходит под меткой `[v0]` в исходном
+  /// разрешении: масштабирование под кадр проекта и наложение не выполняются.
+  pub async fn build_still_clip_filter(&self, clip: &Clip) -> Result<String> {
+    let mut filters = Vec::new();
+
+    let mut base: Vec<String> = clip
+      .rotation_filter()
+      .map(str::to_string)
+      .into_iter()
+      .collect();
+    if self.project.settings.export.hdr_mode == HdrMode::ToneMapToSdr {
+      base.push(TONEMAP_TO_SDR_FILTER.to_string());
+    }
+    if let Some(transform) = &clip.transform {
+      base.extend(clip_geometry_filters(transform));
+    }
+    base.push("setpts=PTS-STARTPTS".to_string());
+    filters.push(format!("[0:v]{}[v0]", base.join(",")));
+
+    let effects_filter = self.effect_builder.build_clip_effects(clip, 0).await?;
+    if !effects_filter.is_empty() {
+      filters.push(effects_filter);
+    }
+
+    Ok(filters.join(";"))
+  }
+
+  /// Построить фильтр для аудио клипа.
+  ///
+  /// Если у клипа задан внешний аудиофайл и передан индекс его входа, звук
+  /// берется из внешнего файла со сдвигом на offset_secs вместо встроенного.
+  async fn build_audio_clip_filter(
+    &self,
+    clip: &Clip,
+    input_index: usize,
+    external_input_index: Option<usize>,
+  ) -> Result<String> {
     let mut filters = Vec::new();
 
     // Базовая обработка аудио
-    let base_filter = format!(
-      "[{}:a]asetpts=PTS-STARTPTS,volume={}[a{}]",
-      input_index, 1.0, input_index
-    );
+    let base_filter = match (clip.active_external_audio(), external_input_index) {
+      (Some(external_audio), Some(external_index)) => {
+        Self::build_external_audio_filter(clip, external_audio, external_index, input_index)
+      }
+      _ => format!(
+        "{}asetpts=PTS-STARTPTS,volume={}[a{}]",
+        clip.audio_input_label(input_index),
+        clip.volume,
+        input_index
+      ),
+    };
     filters.push(base_filter);
 
     // Применяем аудио эффекты
@@ -386,9 +733,96 @@ impl<'a> FilterBuilder<'a> {
       filters.push(audio_effects);
     }
 
+    // Нарастание и затухание звука клипа
+    let fades = clip.audio_fade_filters();
+    if !fades.is_empty() {
+      filters.push(format!(
+        "[a{input_index}]{}[a{input_index}]",
+        fades.join(",")
+      ));
+    }
+
     Ok(filters.join(";"))
   }
 
+  /// Построить фильтр звука из внешнего файла с учетом смещения синхронизации
+  fn build_external_audio_filter(
+    clip: &Clip,
+    external_audio: &ExternalAudio,
+    external_index: usize,
+    output_index: usize,
+  ) -> String {
+    let (start, end, delay) = external_audio.source_range(clip.source_start, clip.source_end);
+
+    let mut chain =
+      format!("[{external_index}:a]atrim=start={start:.3}:end={end:.3},asetpts=PTS-STARTPTS");
+    if delay > 0.0 {
+      let delay_ms = (delay * 1000.0).round() as u64;
+      chain.push_str(&format!(",adelay={delay_ms}|{delay_ms}"));
+    }
+    if (clip.speed - 1.0).abs() > f64::EPSILON {
+      chain.push_str(&format!(",atempo={}", clip.speed));
+    }
+    chain.push_str(&format!(
+      ",volume={}[a{}]",
+      external_audio.gain * clip.volume,
+      output_index
+    ));
+    chain
+  }
+
+  /// Построить дорожку из внешнего звука клипов видео треков.
+  ///
+  /// Каждый фрагмент смещается на позицию клипа на timeline и смешивается
+  /// в отдельную дорожку `[atrack_ext]`.
+  fn build_external_audio_track(&self) -> Option<String> {
+    let input_builder = self.input_builder();
+    let mut parts = Vec::new();
+    let mut labels = Vec::new();
+
+    for track in self.get_video_tracks() {
+      for clip in &track.clips {
+        let (Some(external_audio), Some(external_index)) = (
+          clip.active_external_audio(),
+          input_builder.get_external_audio_input_index(&clip.id),
+        ) else {
+          continue;
+        };
+
+        let label = format!("ext{external_index}");
+        let (start, end, delay) = external_audio.source_range(clip.source_start, clip.source_end);
+        let timeline_delay_ms = ((clip.start_time + delay) * 1000.0).round() as u64;
+        parts.push(format!(
+          "[{}:a]atrim=start={:.3}:end={:.3},asetpts=PTS-STARTPTS,volume={},adelay={}|{}[{}]",
+          external_index,
+          start,
+          end,
+          external_audio.gain * clip.volume,
+          timeline_delay_ms,
+          timeline_delay_ms,
+          label
+        ));
+        labels.push(label);
+      }
+    }
+
+    if labels.is_empty() {
+      return None;
+    }
+
+    let inputs: String = labels.iter().map(|label| format!("[{label}]")).collect();
+    if labels.len() == 1 {
+      parts.push(format!("{inputs}anull[atrack_ext]"));
+    } else {
+      parts.push(format!(
+        "{inputs}amix=inputs={}:normalize=0[atrack_ext]",
+        labels.len()
+      ));
+    }
+
+    Some(parts.join(";"))
+  }
+
   /// Построить фильтр перехода
   async fn build_transition_filter(
     &self,
@@ -404,6 +838,78 @@ impl<'a> FilterBuilder<'a> {
       .await
   }
 
+  /// Наложить водяной знак на итоговое видео цепочки `[outv]`.
+  ///
+  /// Водяной знак накладывается до субтитров, чтобы не перекрывать их.
+  fn apply_watermark(&self, video_chain: String) -> String {
+    let (Some(input_index), Some(watermark)) = (
+      self.watermark_input,
+      self.project.settings.export.watermark.as_ref(),
+    ) else {
+      return video_chain;
+    };
+    let Some(base) = video_chain.strip_suffix("[outv]") else {
+      return video_chain;
+    };
+
+    let width = even_dimension(self.project.settings.resolution.width as f32 * watermark.scale);
+    let mut overlay = format!("overlay={}", watermark.overlay_position());
+    if let Some(enable) = watermark.enable_expression() {
+      overlay.push_str(&format!(":enable='{enable}'"));
+    }
+    // Зацикленный GIF бесконечен, длительность задает основное видео
+    if watermark.is_animated() {
+      overlay.push_str(":shortest=1");
+    }
+
+    format!(
+      "{base}[outv_base];[{input_index}:v]scale={width}:-2,format=rgba,colorchannelmixer=aa={}[wm];[outv_base][wm]{overlay}[outv]",
+      watermark.opacity
+    )
+  }
+
+  /// Впечатать timecode timeline в итоговое видео цепочки `[outv]`.
+  ///
+  /// Поток приводится к частоте проекта, чтобы timecode увеличивался
+  /// ровно на один кадр выходного файла.
+  fn apply_timecode_burn_in(&self, video_chain: String) -> String {
+    let Some(burn_in) = self
+      .project
+      .settings
+      .export
+      .timecode_burn_in
+      .as_ref()
+      .filter(|burn_in| burn_in.source == TimecodeSource::Record)
+    else {
+      return video_chain;
+    };
+    let Some(base) = video_chain.strip_suffix("[outv]") else {
+      return video_chain;
+    };
+
+    let start = self.project.timecode_at(0.0);
+    format!(
+      "{base}[outv_tc];[outv_tc]fps={},{}[outv]",
+      start.rate.ffmpeg_rate(),
+      burn_in.drawtext_filter(&start)
+    )
+  }
+
+  /// Фильтр исходного timecode клипа (`,drawtext=...`) или пустая строка
+  fn source_timecode_filter(&self, clip: &Clip) -> String {
+    let burn_in = self
+      .project
+      .settings
+      .export
+      .timecode_burn_in
+      .as_ref()
+      .filter(|burn_in| burn_in.source == TimecodeSource::Source);
+    match (burn_in, clip.source_timecode_at(clip.start_time)) {
+      (Some(burn_in), Some(start)) => format!(",{}", burn_in.drawtext_filter(&start)),
+      _ => String::new(),
+    }
+  }
+
   /// Построить фильтр наложения треков
   fn build_overlay_filter(&self, track_count: usize) -> String {
     let mut overlay = String::new();
@@ -426,27 +932,40 @@ impl<'a> FilterBuilder<'a> {
   /// Проверить наличие видео треков
   pub fn has_video_tracks(&self) -> bool {
     self
-      .project
-      .tracks
-      .iter()
-      .any(|t| t.track_type == TrackType::Video && t.enabled)
+      .included_tracks()
+      .any(|t| t.track_type == TrackType::Video)
   }
 
   /// Проверить наличие аудио треков
   pub fn has_audio_tracks(&self) -> bool {
+    self
+      .included_tracks()
+      .any(|t| t.track_type == TrackType::Audio)
+  }
+
+  /// Проверить наличие клипов видео треков с внешним звуком
+  pub fn has_external_audio(&self) -> bool {
+    self
+      .get_video_tracks()
+      .iter()
+      .flat_map(|t| t.clips.iter())
+      .any(|c| c.active_external_audio().is_some())
+  }
+
+  /// Треки, участвующие в построении (включенные, а для рендера - и не скрытые)
+  fn included_tracks(&self) -> impl Iterator<Item = &'a Track> + '_ {
+    let input_builder = self.input_builder();
     self
       .project
       .tracks
       .iter()
-      .any(|t| t.track_type == TrackType::Audio && t.enabled)
+      .filter(move |t| input_builder.includes_track(t))
   }
 
   /// Получить видео треки
   fn get_video_tracks(&self) -> Vec<&Track> {
     self
-      .project
-      .tracks
-      .iter()
+      .included_tracks()
       .filter(|t| t.track_type == TrackType::Video)
       .collect()
   }
@@ -454,9 +973,7 @@ impl<'a> FilterBuilder<'a> {
   /// Получить аудио треки
   fn get_audio_tracks(&self) -> Vec<&Track> {
     self
-      .project
-      .tracks
-      .iter()
+      .included_tracks()
       .filter(|t| t.track_type == TrackType::Audio)
       .collect()
   }
@@ -465,6 +982,7 @@ impl<'a> FilterBuilder<'a> {
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::video_compiler::schema::{DuckingConfig, EffectType, Fade, FadeCurve};
   use crate::video_compiler::tests::fixtures::*;
   use tokio::process::Command;
 
@@ -677,6 +1195,127 @@ mod tests {
     assert!(filter.contains("setpts="));
   }
 
+  #[tokio::test]
+  async fn test_clip_filter_starts_with_tonemap_for_sdr_export() {
+    let mut project = create_project_with_clips();
+    let clip = project.tracks[0].clips[0].clone();
+
+    let plain = FilterBuilder::new(&project)
+      .build_clip_filter(&clip, 0, 0)
+      .await
+      .unwrap();
+    assert!(!plain.contains("tonemap"));
+
+    project.settings.export.hdr_mode = HdrMode::ToneMapToSdr;
+    let filter = FilterBuilder::new(&project)
+      .build_clip_filter(&clip, 0, 0)
+      .await
+      .unwrap();
+    assert!(filter.starts_with("[0:v]zscale=t=linear"));
+    assert!(filter.find("tonemap=hable").unwrap() < filter.find("scale=1920").unwrap());
+  }
+
+  #[tokio::test]
+  async fn test_clip_filter_rotates_portrait_video() {
+    let project = create_project_with_clips();
+    let mut clip = project.tracks[0].clips[0].clone();
+    clip.source_rotation = 90;
+
+    let filter = FilterBuilder::new(&project)
+      .build_clip_filter(&clip, 0, 0)
+      .await
+      .unwrap();
+    assert!(filter.starts_with("[0:v]transpose=clock,scale="));
+  }
+
+  #[tokio::test]
+  async fn test_clip_filter_crops_and_rotates() {
+    use crate::video_compiler::schema::{CropRect, TransformSettings};
+
+    let project = create_project_with_clips();
+    let mut clip = project.tracks[0].clips[0].clone();
+    clip.transform = Some(TransformSettings {
+      crop: Some(CropRect {
+        x: 10.0,
+        y: 20.0,
+        width: 50.0,
+        height: 40.0,
+      }),
+      rotation: 90.0,
+      flip_horizontal: true,
+      ..Default::default()
+    });
+
+    let filter = FilterBuilder::new(&project)
+      .build_clip_filter(&clip, 0, 0)
+      .await
+      .unwrap();
+    assert!(filter.starts_with(
+      "[0:v]crop=iw*0.5:ih*0.4:iw*0.1:ih*0.2,hflip,transpose=clock,scale=1920:1080,setpts=PTS-STARTPTS[v0]"
+    ));
+
+    // Произвольный угол поворачивается с черной заливкой
+    clip.transform = Some(TransformSettings {
+      rotation: -315.0,
+      ..Default::default()
+    });
+    let filter = FilterBuilder::new(&project)
+      .build_clip_filter(&clip, 0, 0)
+      .await
+      .unwrap();
+    assert!(filter.starts_with("[0:v]rotate=0.785398:ow=iw:oh=ih:c=black,scale=1920:1080"));
+  }
+
+  #[tokio::test]
+  async fn test_clip_filter_scales_and_positions_transform() {
+    use crate::video_compiler::schema::{CropRect, TransformSettings};
+
+    let project = create_project_with_clips();
+    let mut clip = project.tracks[0].clips[0].clone();
+    clip.source_rotation = 90;
+    clip.transform = Some(TransformSettings {
+      crop: Some(CropRect {
+        x: 0.0,
+        y: 0.0,
+        width: 50.0,
+        height: 100.0,
+      }),
+      scale_x: 0.5,
+      scale_y: 0.5,
+      position_x: 0.25,
+      position_y: 0.1,
+      rotation: 180.0,
+      ..Default::default()
+    });
+
+    let filter = FilterBuilder::new(&project)
+      .build_clip_filter(&clip, 2, 0)
+      .await
+      .unwrap();
+    let parts: Vec<&str> = filter.split(';').collect();
+    // Поворот исходника выполняется до кадрирования, кадрирование - до масштабирования
+    assert_eq!(
+      parts[0],
+      "[2:v]transpose=clock,crop=iw*0.5:ih*1:iw*0:ih*0,hflip,vflip,scale=960:540,setpts=PTS-STARTPTS[v2fg]"
+    );
+    assert!(parts[1].starts_with("color=c=black:s=1920x1080:"));
+    assert!(parts[1].ends_with("[v2bg]"));
+    assert_eq!(parts[2], "[v2bg][v2fg]overlay=x=480:y=108:shortest=1[v2]");
+  }
+
+  #[tokio::test]
+  async fn test_audio_clip_filter_selects_stream() {
+    let project = create_project_with_clips();
+    let mut clip = project.tracks[0].clips[0].clone();
+    clip.audio_stream_index = Some(1);
+
+    let filter = FilterBuilder::new(&project)
+      .build_audio_clip_filter(&clip, 2, None)
+      .await
+      .unwrap();
+    assert!(filter.starts_with("[2:a:1]asetpts=PTS-STARTPTS"));
+  }
+
   #[tokio::test]
   async fn test_build_audio_clip_filter() {
     let project = create_project_with_clips();
@@ -685,7 +1324,9 @@ mod tests {
     let clip = &project.tracks[0].clips[0];
     let input_index = 0;
 
-    let result = builder.build_audio_clip_filter(clip, input_index).await;
+    let result = builder
+      .build_audio_clip_filter(clip, input_index, None)
+      .await;
     assert!(result.is_ok());
 
     let filter = result.unwrap();
@@ -696,6 +1337,136 @@ mod tests {
     assert!(filter.contains("volume="));
   }
 
+  #[tokio::test]
+  async fn test_audio_clip_fades_for_each_curve() {
+    let project = create_project_with_clips();
+    let mut clip = project.tracks[0].clips[0].clone();
+    clip.start_time = 2.0;
+    clip.end_time = 12.0;
+
+    for (curve, name) in [
+      (FadeCurve::Linear, "tri"),
+      (FadeCurve::Exponential, "exp"),
+      (FadeCurve::Logarithmic, "log"),
+      (FadeCurve::SCurve, "esin"),
+    ] {
+      clip.fade_in = Some(Fade::new(1.5, curve));
+      clip.fade_out = Some(Fade::new(2.0, curve));
+
+      let filter = FilterBuilder::new(&project)
+        .build_audio_clip_filter(&clip, 3, None)
+        .await
+        .unwrap();
+      assert!(
+        filter.ends_with(&format!(
+          ";[a3]afade=t=in:st=0:d=1.500:curve={name},afade=t=out:st=8.000:d=2.000:curve={name}[a3]"
+        )),
+        "{filter}"
+      );
+    }
+
+    clip.fade_in = None;
+    let filter = FilterBuilder::new(&project)
+      .build_audio_clip_filter(&clip, 3, None)
+      .await
+      .unwrap();
+    assert!(!filter.contains("afade=t=in"));
+    assert!(filter.contains("afade=t=out:st=8.000:d=2.000:curve=esin"));
+  }
+
+  #[tokio::test]
+  async fn test_video_clip_fades() {
+    let project = create_project_with_clips();
+    let mut clip = project.tracks[0].clips[0].clone();
+    clip.start_time = 0.0;
+    clip.end_time = 6.0;
+    clip.fade_in = Some(Fade::new(0.5, FadeCurve::SCurve));
+    clip.fade_out = Some(Fade::new(1.0, FadeCurve::Exponential));
+
+    let filter = FilterBuilder::new(&project)
+      .build_clip_filter(&clip, 1, 0)
+      .await
+      .unwrap();
+    assert!(filter.contains(";[v1]fade=t=in:st=0:d=0.500,fade=t=out:st=5.000:d=1.000[v1]"));
+  }
+
+  /// Проект с двумя клипами на видео треке и эффектом яркости на треке
+  fn project_with_track_brightness() -> (ProjectSchema, String) {
+    let mut project = create_project_with_clips();
+    let brightness = create_test_effect(EffectType::Brightness);
+    let brightness_id = brightness.id.clone();
+    project.effects.push(brightness);
+    let track = &mut project.tracks[0];
+    track.clips.push(Clip::new(fixture_video_path(), 5.0, 10.0));
+    track.effects.push(brightness_id.clone());
+    (project, brightness_id)
+  }
+
+  #[tokio::test]
+  async fn test_track_effects_applied_once_per_clip_after_clip_effects() {
+    let (mut project, brightness_id) = project_with_track_brightness();
+    let contrast = create_test_effect(EffectType::Contrast);
+    let track = &mut project.tracks[0];
+    // Эффект, назначенный и клипу, и треку, не применяется дважды
+    track.clips[0].effects.push(brightness_id);
+    track.clips[1].effects.push(contrast.id.clone());
+    project.effects.push(contrast);
+
+    let mut input_index = 0;
+    let filter = FilterBuilder::new(&project)
+      .build_video_filter_chain(&mut input_index)
+      .await
+      .unwrap();
+
+    assert_eq!(filter.matches("eq=brightness=1.2").count(), 2);
+    assert_eq!(filter.matches("[v0]eq=brightness=1.2[v0]").count(), 1);
+    assert_eq!(filter.matches("[v1]eq=brightness=1.2[v1]").count(), 1);
+    let contrast_pos = filter.find("[v1]eq=contrast=1.1[v1]").unwrap();
+    let brightness_pos = filter.find("[v1]eq=brightness=1.2[v1]").unwrap();
+    assert!(contrast_pos < brightness_pos);
+  }
+
+  #[tokio::test]
+  async fn test_disabled_track_effects_skipped() {
+    let (mut project, _) = project_with_track_brightness();
+    project.effects[0].enabled = false;
+
+    let mut input_index = 0;
+    let filter = FilterBuilder::new(&project)
+      .build_video_filter_chain(&mut input_index)
+      .await
+      .unwrap();
+    assert!(!filter.contains("eq=brightness"));
+  }
+
+  #[tokio::test]
+  async fn test_clip_fades_clamped_to_clip_duration() {
+    let project = create_project_with_clips();
+    let mut clip = project.tracks[0].clips[0].clone();
+    clip.start_time = 0.0;
+    clip.end_time = 4.0;
+    clip.fade_in = Some(Fade::new(3.0, FadeCurve::Linear));
+    clip.fade_out = Some(Fade::new(5.0, FadeCurve::Linear));
+
+    // Затухание ограничено 4 с, затем оба сжаты пропорционально 3:4
+    let (fade_in, fade_out) = clip.fade_durations();
+    assert!((fade_in - 12.0 / 7.0).abs() < 1e-9);
+    assert!((fade_out - 16.0 / 7.0).abs() < 1e-9);
+    assert!(clip.fade_warning().is_some());
+
+    let filter = FilterBuilder::new(&project)
+      .build_audio_clip_filter(&clip, 0, None)
+      .await
+      .unwrap();
+    assert!(filter.contains("afade=t=in:st=0:d=1.714:curve=tri"));
+    assert!(filter.contains("afade=t=out:st=1.714:d=2.286:curve=tri"));
+
+    clip.fade_out = Some(Fade::new(1.0, FadeCurve::Linear));
+    assert!(clip.fade_warning().is_none());
+    clip.fade_in = Some(Fade::new(-1.0, FadeCurve::Linear));
+    assert!(clip.validate().is_err());
+  }
+
   #[test]
   fn test_build_overlay_filter() {
     let project = create_minimal_project();
@@ -736,6 +1507,41 @@ mod tests {
     assert!(filter.is_empty());
   }
 
+  #[tokio::test]
+  async fn test_hidden_track_excluded_from_render_only() {
+    let mut project = create_project_with_clips();
+    let mut track2 = Track::new(TrackType::Video, "Video Track 2".to_string());
+    track2.clips.push(Clip::new(
+      std::path::PathBuf::from("/tmp/video2.mp4"),
+      0.0,
+      5.0,
+    ));
+    project.tracks.push(track2);
+    project.tracks[0].hidden = true;
+
+    // Рендер: остается один трек, метки идут подряд с нуля
+    let builder = FilterBuilder::new(&project);
+    let mut input_index = 0;
+    let filter = builder
+      .build_video_filter_chain(&mut input_index)
+      .await
+      .unwrap();
+    assert_eq!(input_index, 1);
+    assert!(filter.contains("[track0]"));
+    assert!(!filter.contains("[track1]"));
+    assert!(!filter.contains("overlay"));
+
+    // Превью: скрытый трек участвует
+    let builder = FilterBuilder::for_preview(&project);
+    let mut input_index = 0;
+    let filter = builder
+      .build_video_filter_chain(&mut input_index)
+      .await
+      .unwrap();
+    assert_eq!(input_index, 2);
+    assert!(filter.contains("overlay"));
+  }
+
   #[tokio::test]
   async fn test_complex_project_filter_building() {
     let project = create_complex_project();
@@ -779,6 +1585,93 @@ mod tests {
     assert!(!filter.is_empty());
   }
 
+  #[tokio::test]
+  async fn test_filter_with_watermark_before_subtitles() {
+    use crate::video_compiler::schema::subtitles::Subtitle;
+    use crate::video_compiler::schema::{Resolution, WatermarkConfig, WatermarkPosition};
+
+    let mut project = create_project_with_clips();
+    project
+      .subtitles
+      .push(Subtitle::new("Test subtitle".to_string(), 1.0, 3.0));
+    project.settings.resolution = Resolution::new(1920, 1080);
+    project.settings.export.watermark = Some(WatermarkConfig {
+      image_path: "/tmp/logo.gif".to_string(),
+      position: WatermarkPosition::TopRight,
+      scale: 0.1,
+      opacity: 0.5,
+      margin_px: 20,
+      start_time: Some(1.0),
+      end_time: Some(4.0),
+      show_in_preview: false,
+    });
+
+    let filter = FilterBuilder::new(&project)
+      .with_watermark(1)
+      .build_filter_complex()
+      .await
+      .unwrap();
+
+    assert!(
+      filter.contains("[outv_base];[1:v]scale=192:-2,format=rgba,colorchannelmixer=aa=0.5[wm]")
+    );
+    assert!(filter
+      .contains("[outv_base][wm]overlay=x=W-w-20:y=20:enable='between(t,1,4)':shortest=1[outv]"));
+    // Субтитры накладываются поверх водяного знака
+    let watermark_pos = filter.find("[wm]overlay").unwrap();
+    let subtitles_pos = filter.find("[outv_with_subs]").unwrap();
+    assert!(watermark_pos < subtitles_pos);
+
+    // Без входа изображения водяной знак не накладывается
+    let plain = FilterBuilder::new(&project)
+      .build_filter_complex()
+      .await
+      .unwrap();
+    assert!(!plain.contains("[wm]"));
+  }
+
+  #[tokio::test]
+  async fn test_filter_with_record_timecode_burn_in() {
+    use crate::video_compiler::schema::{Timecode, TimecodeBurnIn, TimecodeRate};
+
+    let mut project = create_project_with_clips();
+    project.settings.frame_rate = 29.97;
+    project.settings.timecode.start =
+      Some(Timecode::parse("01:00:00;00", TimecodeRate::Fps29_97).unwrap());
+    project.settings.export.timecode_burn_in = Some(TimecodeBurnIn::default());
+
+    let filter = FilterBuilder::new(&project)
+      .build_filter_complex()
+      .await
+      .unwrap();
+
+    assert!(filter.contains(
+      "[outv_tc];[outv_tc]fps=30000/1001,drawtext=timecode='01\\:00\\:00;00':rate=30000/1001"
+    ));
+    assert!(filter.contains("x=(w-tw)/2:y=h-th-24[outv]"));
+  }
+
+  #[tokio::test]
+  async fn test_filter_with_source_timecode_burn_in() {
+    use crate::video_compiler::schema::{Timecode, TimecodeBurnIn, TimecodeRate, TimecodeSource};
+
+    let mut project = create_project_with_clips();
+    project.tracks[0].clips[0].source_timecode =
+      Some(Timecode::parse("10:00:00:00", TimecodeRate::Fps25).unwrap());
+    project.settings.export.timecode_burn_in = Some(TimecodeBurnIn {
+      source: TimecodeSource::Source,
+      ..TimecodeBurnIn::default()
+    });
+
+    let filter = FilterBuilder::new(&project)
+      .build_filter_complex()
+      .await
+      .unwrap();
+
+    assert!(filter.contains("setpts=PTS-STARTPTS,drawtext=timecode='10\\:00\\:00\\:00':rate=25/1:"));
+    assert!(!filter.contains("[outv_tc]"));
+  }
+
   #[tokio::test]
   async fn test_filter_with_subtitles() {
     let mut project = create_project_with_clips();
@@ -835,6 +1728,8 @@ mod tests {
       ffmpeg_command: None,
       easing: None,
       direction: None,
+      from_clip_id: None,
+      to_clip_id: None,
     };
 
     let result = builder
@@ -920,6 +1815,68 @@ mod tests {
     assert!(filter.contains("amix"));
   }
 
+  fn audio_track(id: &str, path: &str) -> Track {
+    let mut track = Track::new(TrackType::Audio, id.to_string());
+    track.id = id.to_string();
+    track
+      .clips
+      .push(Clip::new(std::path::PathBuf::from(path), 0.0, 5.0));
+    track
+  }
+
+  #[tokio::test]
+  async fn test_audio_ducking_music_under_voice() {
+    let mut project = create_minimal_project();
+    let mut music = audio_track("music", "/tmp/music.mp3");
+    music.ducking = Some(DuckingConfig::new("voice"));
+    project.tracks.push(music);
+    project.tracks.push(audio_track("voice", "/tmp/voice.wav"));
+
+    let builder = FilterBuilder::new(&project);
+    let mut input_index = 0;
+    let filter = builder
+      .build_audio_filter_chain(&mut input_index)
+      .await
+      .unwrap();
+
+    // Голос размножается: одна копия в микс, другая в sidechain музыки
+    assert!(filter.contains("[atrack1]asplit=2[asc1mix][asc1_0]"));
+    assert!(filter.contains(
+      "[atrack0][asc1_0]sidechaincompress=threshold=0.05:ratio=8:attack=20:release=250:makeup=1[aduck0]"
+    ));
+    assert!(filter.contains("[aduck0][asc1mix]amix=inputs=2[outa]"));
+
+    // asplit голоса строится до sidechaincompress, который его использует
+    assert!(filter.find("asplit").unwrap() < filter.find("sidechaincompress").unwrap());
+  }
+
+  #[tokio::test]
+  async fn test_audio_ducking_chain_and_shared_sidechain() {
+    let mut project = create_minimal_project();
+    // music приглушается под sfx, sfx и ambience приглушаются под voice
+    let mut music = audio_track("music", "/tmp/music.mp3");
+    music.ducking = Some(DuckingConfig::new("sfx"));
+    let mut sfx = audio_track("sfx", "/tmp/sfx.wav");
+    sfx.ducking = Some(DuckingConfig::new("voice"));
+    let mut ambience = audio_track("ambience", "/tmp/ambience.wav");
+    ambience.ducking = Some(DuckingConfig::new("voice"));
+    project.tracks = vec![music, sfx, ambience, audio_track("voice", "/tmp/voice.wav")];
+
+    let builder = FilterBuilder::new(&project);
+    let mut input_index = 0;
+    let filter = builder
+      .build_audio_filter_chain(&mut input_index)
+      .await
+      .unwrap();
+
+    assert!(filter.contains("[atrack3]asplit=3[asc3mix][asc3_0][asc3_1]"));
+    assert_eq!(filter.matches("sidechaincompress").count(), 3);
+    // Приглушенный sfx сам служит sidechain для music
+    assert!(filter.contains("[aduck1]asplit=2[asc1mix][asc1_0]"));
+    assert!(filter.contains("[atrack0][asc1_0]sidechaincompress"));
+    assert!(filter.contains("amix=inputs=4[outa]"));
+  }
+
   #[tokio::test]
   async fn test_clip_with_template() {
     let mut project = create_project_with_clips();
@@ -1054,4 +2011,67 @@ mod tests {
     let filter = result.unwrap();
     assert!(!filter.is_empty(), "Complex filter should not be empty");
   }
+
+  #[tokio::test]
+  async fn test_audio_clip_filter_uses_external_audio() {
+    let mut project = create_minimal_project();
+    let mut audio_track = Track::new(TrackType::Audio, "Audio Track".to_string());
+    let mut clip = Clip::new(std::path::PathBuf::from("/tmp/video.mp4"), 0.0, 5.0);
+    clip.source_start = 2.0;
+    clip.source_end = 7.0;
+    clip.external_audio = Some(ExternalAudio {
+      gain: 1.5,
+      ..ExternalAudio::new("/tmp/lav.wav", -3.0)
+    });
+    audio_track.clips.push(clip.clone());
+    project.tracks.push(audio_track);
+
+    let builder = FilterBuilder::new(&project);
+    let filter = builder
+      .build_audio_clip_filter(&clip, 0, Some(1))
+      .await
+      .unwrap();
+
+    // Диапазон 2..7 со смещением -3 начинается до записи: обрезаем до 0 и задерживаем на 1с
+    assert!(filter.contains("[1:a]atrim=start=0.000:end=4.000"));
+    assert!(filter.contains("adelay=1000|1000"));
+    assert!(filter.contains("volume=1.5"));
+    assert!(!filter.contains("[0:a]"));
+  }
+
+  #[tokio::test]
+  async fn test_external_audio_fallback_to_embedded() {
+    let mut clip = Clip::new(std::path::PathBuf::from("/tmp/video.mp4"), 0.0, 5.0);
+    let mut external_audio = ExternalAudio::new("/tmp/lav.wav", 0.5);
+    external_audio.use_embedded = true;
+    clip.external_audio = Some(external_audio);
+
+    let project = create_minimal_project();
+    let builder = FilterBuilder::new(&project);
+    let filter = builder
+      .build_audio_clip_filter(&clip, 0, Some(1))
+      .await
+      .unwrap();
+
+    assert!(filter.starts_with("[0:a]"));
+  }
+
+  #[tokio::test]
+  async fn test_filter_complex_mixes_external_audio_of_video_clips() {
+    let mut project = create_minimal_project();
+    let mut video_track = Track::new(TrackType::Video, "Video Track".to_string());
+    let mut clip = Clip::new(std::path::PathBuf::from("/tmp/video.mp4"), 1.0, 5.0);
+    clip.external_audio = Some(ExternalAudio::new("/tmp/lav.wav", 0.0));
+    video_track.clips.push(clip);
+    project.tracks.push(video_track);
+
+    let builder = FilterBuilder::new(&project);
+    assert!(builder.has_external_audio());
+
+    let filter = builder.build_filter_complex().await.unwrap();
+    assert!(filter.contains("[1:a]atrim="));
+    assert!(filter.contains("adelay=1000|1000"));
+    assert!(filter.contains("[atrack_ext]"));
+    assert!(filter.contains("[outa]"));
+  }
 }
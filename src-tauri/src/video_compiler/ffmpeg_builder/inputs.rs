@@ -4,7 +4,9 @@ use std::path::PathBuf;
 use tokio::process::Command;
 
 use crate::video_compiler::error::Result;
-use crate::video_compiler::schema::{ClipSource, ProjectSchema, TrackType};
+use crate::video_compiler::schema::{Clip, ClipSource, ProjectSchema, Track, TrackType};
+
+use super::sequences::nested_sequence_path;
 
 /// Информация о входном источнике
 #[derive(Debug, Clone)]
@@ -17,17 +19,56 @@ pub struct InputSource {
   pub duration: f64,
   /// Тип трека
   pub track_type: TrackType,
+  /// Поворот источника по часовой стрелке, выпрямляемый фильтром клипа
+  pub rotation: i32,
 }
 
 /// Построитель входных источников
 pub struct InputBuilder<'a> {
   project: &'a ProjectSchema,
+  /// Учитывать скрытые треки (превью и пререндер сегментов)
+  include_hidden: bool,
+  /// Декодировать видеовходы на GPU
+  hardware_decoding: bool,
 }
 
 impl<'a> InputBuilder<'a> {
-  /// Создать новый построитель входных источников
+  /// Создать новый построитель входных источников для финального рендера
   pub fn new(project: &'a ProjectSchema) -> Self {
-    Self { project }
+    Self {
+      project,
+      include_hidden: false,
+      hardware_decoding: false,
+    }
+  }
+
+  /// Создать построитель для превью: скрытые треки учитываются
+  pub fn for_preview(project: &'a ProjectSchema) -> Self {
+    Self {
+      project,
+      include_hidden: true,
+      hardware_decoding: false,
+    }
+  }
+
+  /// Декодировать видеовходы на GPU (`-hwaccel auto`)
+  pub fn with_hardware_decoding(mut self, enabled: bool) -> Self {
+    self.hardware_decoding = enabled;
+    self
+  }
+
+  /// Участвует ли трек в построении команды.
+  ///
+  /// Для аудиоформатов финальный рендер собирает только аудио треки:
+  /// входы видео треков (и их внешний звук) не добавляются.
+  pub fn includes_track(&self, track: &Track) -> bool {
+    if self.include_hidden {
+      track.enabled
+    } else {
+      track.is_rendered()
+        && !(self.project.settings.output.format.is_audio_only()
+          && track.track_type == TrackType::Video)
+    }
   }
 
   /// Добавить входные источники
@@ -38,6 +79,14 @@ impl<'a> InputBuilder<'a> {
       self.add_input_source(cmd, &source)?;
     }
 
+    // Внешние аудиофайлы добавляются после основных входов, чтобы не сдвигать
+    // их индексы. Обрезка выполняется фильтром atrim с учетом смещения.
+    for clip in self.external_audio_clips() {
+      if let Some(external_audio) = clip.active_external_audio() {
+        cmd.args(["-i", &external_audio.path]);
+      }
+    }
+
     Ok(())
   }
 
@@ -64,6 +113,16 @@ impl<'a> InputBuilder<'a> {
       cmd.args(["-ss", &source.start_time.to_string()]);
     }
 
+    // Поворот применяет фильтр клипа, автоповорот FFmpeg отключается
+    if source.rotation.rem_euclid(360) != 0 {
+      cmd.arg("-noautorotate");
+    }
+
+    // Параметры декодирования относятся к следующему -i
+    if source.track_type == TrackType::Video && self.should_use_hardware_decoding() {
+      cmd.args(["-hwaccel", "auto"]);
+    }
+
     // Входной файл
     cmd.args(["-i", &source.path.to_string_lossy()]);
 
@@ -71,9 +130,6 @@ impl<'a> InputBuilder<'a> {
     match source.track_type {
       TrackType::Video => {
         // Для видео можем добавить дополнительные параметры декодирования
-        if self.should_use_hardware_decoding() {
-          cmd.args(["-hwaccel", "auto"]);
-        }
       }
       TrackType::Audio => {
         // Для аудио можем настроить параметры декодирования
@@ -96,17 +152,18 @@ impl<'a> InputBuilder<'a> {
     let mut sources = Vec::new();
 
     for track in &self.project.tracks {
-      if !track.enabled {
+      if !self.includes_track(track) {
         continue;
       }
 
       for clip in &track.clips {
-        if let ClipSource::File(path) = &clip.source {
+        if let Some(path) = Self::clip_input_path(clip) {
           sources.push(InputSource {
-            path: PathBuf::from(path),
+            path,
             start_time: clip.source_start,
             duration: clip.get_source_duration(),
             track_type: track.track_type.clone(),
+            rotation: clip.source_rotation,
           });
         }
       }
@@ -124,7 +181,7 @@ impl<'a> InputBuilder<'a> {
     let mut sources = Vec::new();
 
     for track in &self.project.tracks {
-      if !track.enabled {
+      if !self.includes_track(track) {
         continue;
       }
 
@@ -142,12 +199,13 @@ impl<'a> InputBuilder<'a> {
           let segment_end_in_clip = (end_time - clip.start_time).min(clip_duration);
           let duration = segment_end_in_clip - segment_start_in_clip;
 
-          if let ClipSource::File(path) = &clip.source {
+          if let Some(path) = Self::clip_input_path(clip) {
             sources.push(InputSource {
-              path: PathBuf::from(path),
+              path,
               start_time: source_start,
               duration,
               track_type: track.track_type.clone(),
+              rotation: clip.source_rotation,
             });
           }
         }
@@ -162,7 +220,7 @@ impl<'a> InputBuilder<'a> {
     let mut input_index = 0;
 
     for track in &self.project.tracks {
-      if !track.enabled {
+      if !self.includes_track(track) {
         continue;
       }
 
@@ -177,10 +235,52 @@ impl<'a> InputBuilder<'a> {
     None
   }
 
+  /// Количество основных входов (файловые и составные клипы включенных треков)
+  pub fn primary_input_count(&self) -> usize {
+    self
+      .project
+      .tracks
+      .iter()
+      .filter(|track| self.includes_track(track))
+      .flat_map(|track| track.clips.iter())
+      .filter(|clip| Self::clip_input_path(clip).is_some())
+      .count()
+  }
+
+  /// Путь входного файла клипа. Составные клипы читают промежуточный файл
+  /// своей последовательности.
+  fn clip_input_path(clip: &Clip) -> Option<PathBuf> {
+    match &clip.source {
+      ClipSource::File(path) => Some(PathBuf::from(path)),
+      ClipSource::Sequence(sequence_id) => Some(nested_sequence_path(sequence_id)),
+      _ => None,
+    }
+  }
+
+  /// Клипы включенных треков, звук которых берется из внешнего файла
+  pub fn external_audio_clips(&self) -> Vec<&'a Clip> {
+    self
+      .project
+      .tracks
+      .iter()
+      .filter(|track| self.includes_track(track))
+      .flat_map(|track| track.clips.iter())
+      .filter(|clip| clip.active_external_audio().is_some())
+      .collect()
+  }
+
+  /// Получить индекс входа внешнего аудиофайла клипа
+  pub fn get_external_audio_input_index(&self, clip_id: &str) -> Option<usize> {
+    self
+      .external_audio_clips()
+      .iter()
+      .position(|clip| clip.id == clip_id)
+      .map(|position| self.primary_input_count() + position)
+  }
+
   /// Проверить, нужно ли использовать аппаратное декодирование
   fn should_use_hardware_decoding(&self) -> bool {
-    // Здесь можно добавить логику определения необходимости HW декодирования
-    false
+    self.hardware_decoding
   }
 }
 
@@ -202,6 +302,7 @@ mod tests {
       start_time: 10.0,
       duration: 5.0,
       track_type: TrackType::Video,
+      rotation: 0,
     };
 
     assert_eq!(source.path, PathBuf::from("/test/video.mp4"));
@@ -265,6 +366,24 @@ mod tests {
     );
   }
 
+  #[tokio::test]
+  async fn test_collect_input_sources_hidden_tracks() {
+    let mut project = create_project_with_clips();
+    project.tracks[0].hidden = true;
+
+    let sources = InputBuilder::new(&project)
+      .collect_input_sources()
+      .await
+      .unwrap();
+    assert!(sources.is_empty(), "Hidden tracks are not rendered");
+
+    let sources = InputBuilder::for_preview(&project)
+      .collect_input_sources()
+      .await
+      .unwrap();
+    assert_eq!(sources.len(), 1, "Hidden tracks stay in previews");
+  }
+
   #[tokio::test]
   async fn test_add_input_sources_empty() {
     let project = create_minimal_project();
@@ -313,6 +432,7 @@ mod tests {
       start_time: 0.0,
       duration: 10.0,
       track_type: TrackType::Video,
+      rotation: 0,
     };
 
     let result = builder.add_input_source(&mut cmd, &source);
@@ -341,6 +461,7 @@ mod tests {
       start_time: 5.0,
       duration: 10.0,
       track_type: TrackType::Video,
+      rotation: 0,
     };
 
     let result = builder.add_input_source(&mut cmd, &source);
@@ -372,6 +493,7 @@ mod tests {
       start_time: 0.0,
       duration: 5.0,
       track_type: TrackType::Audio,
+      rotation: 0,
     };
 
     let result = builder.add_input_source(&mut cmd, &source);
@@ -387,6 +509,31 @@ mod tests {
     assert!(args.contains(&"/test/audio.mp3".to_string()));
   }
 
+  #[test]
+  fn test_add_input_source_rotated_disables_autorotate() {
+    let project = create_minimal_project();
+    let builder = InputBuilder::new(&project);
+    let mut cmd = Command::new("ffmpeg");
+
+    let source = InputSource {
+      path: PathBuf::from("/test/portrait.mov"),
+      start_time: 0.0,
+      duration: 5.0,
+      track_type: TrackType::Video,
+      rotation: 90,
+    };
+    builder.add_input_source(&mut cmd, &source).unwrap();
+
+    let args: Vec<String> = cmd
+      .as_std()
+      .get_args()
+      .map(|s| s.to_string_lossy().to_string())
+      .collect();
+    let noautorotate = args.iter().position(|arg| arg == "-noautorotate").unwrap();
+    let input = args.iter().position(|arg| arg == "-i").unwrap();
+    assert!(noautorotate < input);
+  }
+
   #[tokio::test]
   async fn test_collect_segment_sources() {
     let project = create_project_with_clips();
@@ -476,6 +623,9 @@ mod tests {
 
     // По умолчанию должно возвращать false
     assert!(!builder.should_use_hardware_decoding());
+    assert!(builder
+      .with_hardware_decoding(true)
+      .should_use_hardware_decoding());
   }
 
   #[tokio::test]
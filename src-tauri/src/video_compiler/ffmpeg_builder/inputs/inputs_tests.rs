@@ -21,6 +21,7 @@ fn create_complex_project() -> ProjectSchema {
     resolution: (1920, 1080),
     sample_rate: 48000,
     aspect_ratio: crate::video_compiler::schema::AspectRatio::default(),
+    markers: Vec::new(),
   };
 
   // Видео трек с несколькими клипами
@@ -42,6 +43,18 @@ fn create_complex_project() -> ProjectSchema {
     crop: None,
     transform: None,
     audio_track_index: None,
+    audio_stream_index: None,
+    source_rotation: 0,
+    color_label: None,
+    notes: None,
+    tags: Vec::new(),
+    fade_in: None,
+    fade_out: None,
+    link_group_id: None,
+    source_timecode: None,
+    volume: 1.0,
+    external_audio: None,
+    locked: false,
     properties: ClipProperties::default(),
   });
 
@@ -62,6 +75,18 @@ fn create_complex_project() -> ProjectSchema {
     crop: None,
     transform: None,
     audio_track_index: None,
+    audio_stream_index: None,
+    source_rotation: 0,
+    color_label: None,
+    notes: None,
+    tags: Vec::new(),
+    fade_in: None,
+    fade_out: None,
+    link_group_id: None,
+    source_timecode: None,
+    volume: 1.0,
+    external_audio: None,
+    locked: false,
     properties: ClipProperties::default(),
   });
 
@@ -86,6 +111,18 @@ fn create_complex_project() -> ProjectSchema {
     crop: None,
     transform: None,
     audio_track_index: None,
+    audio_stream_index: None,
+    source_rotation: 0,
+    color_label: None,
+    notes: None,
+    tags: Vec::new(),
+    fade_in: None,
+    fade_out: None,
+    link_group_id: None,
+    source_timecode: None,
+    volume: 1.0,
+    external_audio: None,
+    locked: false,
     properties: ClipProperties::default(),
   });
 
@@ -110,6 +147,18 @@ fn create_complex_project() -> ProjectSchema {
     crop: None,
     transform: None,
     audio_track_index: None,
+    audio_stream_index: None,
+    source_rotation: 0,
+    color_label: None,
+    notes: None,
+    tags: Vec::new(),
+    fade_in: None,
+    fade_out: None,
+    link_group_id: None,
+    source_timecode: None,
+    volume: 1.0,
+    external_audio: None,
+    locked: false,
     properties: ClipProperties::default(),
   });
 
@@ -150,6 +199,18 @@ fn create_project_with_generated_clips() -> ProjectSchema {
     crop: None,
     transform: None,
     audio_track_index: None,
+    audio_stream_index: None,
+    source_rotation: 0,
+    color_label: None,
+    notes: None,
+    tags: Vec::new(),
+    fade_in: None,
+    fade_out: None,
+    link_group_id: None,
+    source_timecode: None,
+    volume: 1.0,
+    external_audio: None,
+    locked: false,
     properties: ClipProperties::default(),
   });
 
@@ -431,6 +492,7 @@ mod track_type_tests {
       start_time: 0.0,
       duration: 10.0,
       track_type: TrackType::Video,
+      rotation: 0,
     };
 
     let result = builder.add_input_source(&mut cmd, &source);
@@ -461,6 +523,7 @@ mod track_type_tests {
       start_time: 2.5,
       duration: 7.5,
       track_type: TrackType::Audio,
+      rotation: 0,
     };
 
     let result = builder.add_input_source(&mut cmd, &source);
@@ -492,6 +555,7 @@ mod track_type_tests {
       start_time: 0.0,
       duration: 60.0,
       track_type: TrackType::Subtitle,
+      rotation: 0,
     };
 
     let result = builder.add_input_source(&mut cmd, &source);
@@ -541,6 +605,7 @@ mod edge_cases_tests {
       start_time: 5.0,
       duration: 0.0, // Нулевая длительность
       track_type: TrackType::Video,
+      rotation: 0,
     };
 
     let result = builder.add_input_source(&mut cmd, &source);
@@ -570,6 +635,7 @@ mod edge_cases_tests {
       start_time: -5.0, // Отрицательное время
       duration: 10.0,
       track_type: TrackType::Video,
+      rotation: 0,
     };
 
     let result = builder.add_input_source(&mut cmd, &source);
@@ -606,6 +672,7 @@ mod edge_cases_tests {
       start_time: 0.0,
       duration: 5.0,
       track_type: TrackType::Video,
+      rotation: 0,
     };
 
     let result = builder.add_input_source(&mut cmd, &source);
@@ -636,6 +703,7 @@ mod edge_cases_tests {
       start_time: 0.0,
       duration: 5.0,
       track_type: TrackType::Video,
+      rotation: 0,
     };
 
     let result = builder.add_input_source(&mut cmd, &source);
@@ -721,6 +789,18 @@ mod clip_indexing_tests {
       crop: None,
       transform: None,
       audio_track_index: None,
+      audio_stream_index: None,
+      source_rotation: 0,
+      color_label: None,
+      notes: None,
+      tags: Vec::new(),
+      fade_in: None,
+      fade_out: None,
+      link_group_id: None,
+      source_timecode: None,
+      volume: 1.0,
+      external_audio: None,
+      locked: false,
       properties: ClipProperties::default(),
     };
 
@@ -765,6 +845,7 @@ mod hardware_decoding_tests {
       start_time: 0.0,
       duration: 10.0,
       track_type: TrackType::Video,
+      rotation: 0,
     };
 
     let result = builder.add_input_source(&mut cmd, &source);
@@ -792,6 +873,7 @@ mod input_source_validation_tests {
       start_time: 1.5,
       duration: 3.7,
       track_type: TrackType::Video,
+      rotation: 0,
     };
 
     let debug_string = format!("{source:?}");
@@ -808,6 +890,7 @@ mod input_source_validation_tests {
       start_time: 2.0,
       duration: 8.0,
       track_type: TrackType::Audio,
+      rotation: 0,
     };
 
     let cloned = original.clone();
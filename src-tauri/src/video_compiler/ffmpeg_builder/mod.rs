@@ -2,25 +2,37 @@
 //!
 //! Этот модуль разделен на несколько подмодулей для лучшей организации:
 //! - `builder` - Основная логика построителя
+//! - `capabilities` - Возможности установленного FFmpeg и замена переходов `gl`
+//! - `command_preview` - Описание команды без запуска FFmpeg (dry-run)
 //! - `filters` - Построение фильтров (видео, аудио, эффекты)
 //! - `inputs` - Обработка входных источников
 //! - `outputs` - Конфигурация выходных параметров
 //! - `effects` - Обработка эффектов и переходов
+//! - `reframe` - Анимированное кадрирование (рефрейминг) клипов
 //! - `subtitles` - Обработка субтитров
 //! - `templates` - Обработка шаблонов
+//! - `sequences` - Рендеринг вложенных последовательностей
+//! - `stems` - Экспорт аудио треков отдельными файлами
 //! - `advanced` - Расширенные операции FFmpeg
 
 pub mod advanced;
 pub mod builder;
+pub mod capabilities;
+pub mod command_preview;
 pub mod effects;
 pub mod filters;
 pub mod inputs;
 pub mod outputs;
+pub mod reframe;
+pub mod sequences;
+pub mod stems;
 pub mod subtitles;
 pub mod templates;
 
 // Re-export main types
-pub use builder::FFmpegBuilder;
+pub use builder::{FFmpegBuilder, RenderCommand};
+pub use capabilities::{FilterCapabilities, TransitionSubstitution};
+pub use command_preview::CommandPreview;
 
 #[cfg(test)]
 mod tests;
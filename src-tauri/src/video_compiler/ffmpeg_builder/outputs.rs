@@ -1,45 +1,97 @@
 //! FFmpeg Builder - Модуль конфигурации выходных параметров
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tokio::process::Command;
 
 use crate::video_compiler::error::Result;
-use crate::video_compiler::schema::{OutputFormat, ProjectSchema};
+use crate::video_compiler::schema::{HdrMode, OutputFormat, ProjectSchema, RenditionSpec};
 
-use super::builder::{quality_to_crf, FFmpegBuilderSettings};
+use super::builder::{quality_to_crf, quality_to_mp3_vbr, FFmpegBuilderSettings};
+
+/// Длительность сегмента HLS в секундах
+const HLS_SEGMENT_DURATION: u32 = 6;
 
 /// Построитель выходных параметров
 pub struct OutputBuilder<'a> {
   project: &'a ProjectSchema,
   settings: &'a FFmpegBuilderSettings,
+  /// Вариант многовариантного экспорта, для которого строится выход
+  rendition: Option<&'a RenditionSpec>,
 }
 
 impl<'a> OutputBuilder<'a> {
   /// Создать новый построитель выходных параметров
   pub fn new(project: &'a ProjectSchema, settings: &'a FFmpegBuilderSettings) -> Self {
-    Self { project, settings }
+    Self {
+      project,
+      settings,
+      rendition: None,
+    }
+  }
+
+  /// Строить выход для варианта: разрешение и битрейт берутся из него
+  pub fn with_rendition(mut self, rendition: &'a RenditionSpec) -> Self {
+    self.rendition = Some(rendition);
+    self
+  }
+
+  /// Выход кодируется в HDR
+  fn is_hdr_output(&self) -> bool {
+    self.project.settings.export.hdr_mode.is_hdr_output()
+  }
+
+  /// Выход пишется как HLS плейлист варианта
+  fn is_hls(&self) -> bool {
+    self.rendition.is_some() && self.project.settings.export.generate_hls
+  }
+
+  /// Разрешение выхода
+  fn output_resolution(&self) -> (u32, u32) {
+    match self.rendition {
+      Some(rendition) => (rendition.resolution.width, rendition.resolution.height),
+      None => (
+        self.project.settings.resolution.width,
+        self.project.settings.resolution.height,
+      ),
+    }
   }
 
   /// Добавить настройки вывода
   pub async fn add_output_settings(&self, cmd: &mut Command, output_path: &Path) -> Result<()> {
-    // Добавляем аппаратное ускорение если включено
-    if self.settings.use_hardware_acceleration {
-      self.add_hardware_acceleration(cmd).await?;
+    let audio_only = self.project.settings.output.format.is_audio_only();
+
+    if audio_only {
+      // Аудиоформаты: видео не кодируется, разрешение и частота кадров не применяются
+      cmd.arg("-vn");
+      self.add_container_settings(cmd);
     } else {
-      self.add_cpu_encoding(cmd)?;
-    }
+      let intermediate = self.project.settings.output.format.is_intermediate();
+
+      // Добавляем аппаратное ускорение если включено.
+      // ProRes и DNxHR кодируются только на CPU.
+      if self.settings.use_hardware_acceleration && !intermediate {
+        self.add_hardware_acceleration(cmd).await?;
+      } else {
+        self.add_cpu_encoding(cmd)?;
+      }
 
-    // Добавляем настройки формата
-    self.add_format_settings(cmd)?;
+      // Добавляем настройки формата
+      self.add_format_settings(cmd)?;
 
-    // Добавляем настройки битрейта
-    self.add_bitrate_settings(cmd)?;
+      // Добавляем настройки битрейта: промежуточные кодеки задают его профилем
+      if !intermediate {
+        self.add_bitrate_settings(cmd)?;
+      }
+    }
 
     // Добавляем настройки аудио
     self.add_audio_settings(cmd)?;
 
     // Добавляем расширенные настройки кодирования
-    self.add_advanced_encoding_settings(cmd)?;
+    if !audio_only {
+      self.add_advanced_encoding_settings(cmd)?;
+      self.add_color_settings(cmd);
+    }
 
     // Длительность вывода
     if self.project.settings.output.duration > 0.0 {
@@ -49,12 +101,27 @@ impl<'a> OutputBuilder<'a> {
     // Метаданные
     self.add_metadata(cmd)?;
 
+    // Сегментация HLS
+    if self.is_hls() {
+      self.add_hls_settings(cmd, output_path);
+    }
+
     // Выходной файл
     cmd.arg(output_path);
 
     Ok(())
   }
 
+  /// Добавить настройки сегментации HLS
+  fn add_hls_settings(&self, cmd: &mut Command, output_path: &Path) {
+    let segment_pattern = format!("{}_%03d.ts", output_path.with_extension("").display());
+
+    cmd.args(["-hls_time", &HLS_SEGMENT_DURATION.to_string()]);
+    cmd.args(["-hls_playlist_type", "vod"]);
+    cmd.args(["-hls_flags", "independent_segments"]);
+    cmd.args(["-hls_segment_filename", &segment_pattern]);
+  }
+
   /// Добавить настройки для пререндера
   pub async fn add_prerender_settings(&self, cmd: &mut Command, output_path: &Path) -> Result<()> {
     // Для пререндера используем промежуточный кодек с высоким качеством
@@ -71,6 +138,7 @@ impl<'a> OutputBuilder<'a> {
       ),
     ]);
     cmd.args(["-r", &self.project.settings.frame_rate.to_string()]);
+    cmd.args(self.settings.process_limits.thread_args());
 
     // Выходной файл
     cmd.arg(output_path);
@@ -152,6 +220,12 @@ impl<'a> OutputBuilder<'a> {
   /// Добавить CPU кодирование
   fn add_cpu_encoding(&self, cmd: &mut Command) -> Result<()> {
     match self.project.settings.output.format {
+      // HDR в MP4/MOV кодируется в HEVC Main 10, H.264 High не несет 10 бит
+      OutputFormat::Mp4 | OutputFormat::Mov if self.is_hdr_output() => {
+        cmd.args(["-c:v", "libx265"]);
+        cmd.args(["-preset", &self.get_preset()]);
+        cmd.args(["-tag:v", "hvc1"]);
+      }
       OutputFormat::Mp4 => {
         cmd.args(["-c:v", "libx264"]);
         cmd.args(["-preset", &self.get_preset()]);
@@ -177,6 +251,18 @@ impl<'a> OutputBuilder<'a> {
         cmd.args(["-c:v", "gif"]);
         cmd.args(["-filter:v", "fps=10,scale=320:-1:flags=lanczos"]);
       }
+      OutputFormat::Mp3 | OutputFormat::Wav | OutputFormat::Flac => {
+        // Аудиоформаты не кодируют видео
+        cmd.arg("-vn");
+      }
+      OutputFormat::MovProRes(profile) => {
+        cmd.args(["-c:v", "prores_ks"]);
+        cmd.args(["-profile:v", profile.ffmpeg_profile()]);
+      }
+      OutputFormat::MxfDnxhr(profile) => {
+        cmd.args(["-c:v", "dnxhd"]);
+        cmd.args(["-profile:v", profile.ffmpeg_profile()]);
+      }
       OutputFormat::Custom(ref format) => {
         // Для пользовательского формата используем libx264 по умолчанию
         cmd.args(["-c:v", "libx264"]);
@@ -189,6 +275,28 @@ impl<'a> OutputBuilder<'a> {
 
   /// Добавить настройки формата
   fn add_format_settings(&self, cmd: &mut Command) -> Result<()> {
+    if self.is_hls() {
+      cmd.args(["-f", "hls"]);
+    } else {
+      self.add_container_settings(cmd);
+    }
+
+    // Разрешение
+    let (width, height) = self.output_resolution();
+    cmd.args(["-s", &format!("{width}x{height}")]);
+
+    // Частота кадров
+    cmd.args(["-r", &self.project.settings.frame_rate.to_string()]);
+
+    // Соотношение сторон
+    let aspect_ratio = self.project.settings.aspect_ratio.to_ffmpeg_string();
+    cmd.args(["-aspect", &aspect_ratio]);
+
+    Ok(())
+  }
+
+  /// Добавить контейнер выходного файла
+  fn add_container_settings(&self, cmd: &mut Command) {
     match self.project.settings.output.format {
       OutputFormat::Mp4 => {
         cmd.args(["-f", "mp4"]);
@@ -197,9 +305,12 @@ impl<'a> OutputBuilder<'a> {
       OutputFormat::WebM => {
         cmd.args(["-f", "webm"]);
       }
-      OutputFormat::Mov => {
+      OutputFormat::Mov | OutputFormat::MovProRes(_) => {
         cmd.args(["-f", "mov"]);
       }
+      OutputFormat::MxfDnxhr(_) => {
+        cmd.args(["-f", "mxf"]);
+      }
       OutputFormat::Avi => {
         cmd.args(["-f", "avi"]);
       }
@@ -209,36 +320,32 @@ impl<'a> OutputBuilder<'a> {
       OutputFormat::Gif => {
         cmd.args(["-f", "gif"]);
       }
+      OutputFormat::Mp3 => {
+        cmd.args(["-f", "mp3"]);
+      }
+      OutputFormat::Wav => {
+        cmd.args(["-f", "wav"]);
+      }
+      OutputFormat::Flac => {
+        cmd.args(["-f", "flac"]);
+      }
       OutputFormat::Custom(ref format) => {
         cmd.args(["-f", format]);
       }
     }
-
-    // Разрешение
-    cmd.args([
-      "-s",
-      &format!(
-        "{}x{}",
-        self.project.settings.resolution.width, self.project.settings.resolution.height
-      ),
-    ]);
-
-    // Частота кадров
-    cmd.args(["-r", &self.project.settings.frame_rate.to_string()]);
-
-    // Соотношение сторон
-    let aspect_ratio = self.project.settings.aspect_ratio.to_ffmpeg_string();
-    cmd.args(["-aspect", &aspect_ratio]);
-
-    Ok(())
   }
 
   /// Добавить настройки битрейта
   fn add_bitrate_settings(&self, cmd: &mut Command) -> Result<()> {
     let quality = self.project.settings.output.quality;
 
-    // Видео битрейт
-    if let Some(video_bitrate) = self.project.settings.output.video_bitrate {
+    // Видео битрейт (у варианта экспорта он задан всегда)
+    let video_bitrate = self
+      .rendition
+      .map(|rendition| rendition.video_bitrate)
+      .or(self.project.settings.output.video_bitrate);
+
+    if let Some(video_bitrate) = video_bitrate {
       // Использовать заданный битрейт
       cmd.args(["-b:v", &format!("{video_bitrate}k")]);
 
@@ -270,6 +377,12 @@ impl<'a> OutputBuilder<'a> {
           let default_bitrate = self.calculate_default_bitrate();
           cmd.args(["-b:v", &format!("{default_bitrate}k")]);
         }
+        OutputFormat::Mp3 | OutputFormat::Wav | OutputFormat::Flac => {
+          // Видео битрейт для аудиоформатов не применяется
+        }
+        OutputFormat::MovProRes(_) | OutputFormat::MxfDnxhr(_) => {
+          // Битрейт промежуточных кодеков определяется профилем
+        }
         OutputFormat::Avi | OutputFormat::Custom(_) => {
           // Для других форматов используем битрейт по умолчанию
           let default_bitrate = self.calculate_default_bitrate();
@@ -303,15 +416,41 @@ impl<'a> OutputBuilder<'a> {
         cmd.args(["-an"]);
         return Ok(());
       }
+      OutputFormat::Mp3 => {
+        // VBR: качество задается через -q:a вместо битрейта
+        cmd.args(["-c:a", "libmp3lame"]);
+        let vbr_quality = quality_to_mp3_vbr(self.project.settings.output.quality);
+        cmd.args(["-q:a", &vbr_quality.to_string()]);
+      }
+      // Промежуточные кодеки передаются в монтаж с несжатым аудио (MXF требует PCM)
+      OutputFormat::Wav | OutputFormat::MovProRes(_) | OutputFormat::MxfDnxhr(_) => {
+        let codec = match self.audio_bit_depth() {
+          24 => "pcm_s24le",
+          _ => "pcm_s16le",
+        };
+        cmd.args(["-c:a", codec]);
+      }
+      OutputFormat::Flac => {
+        cmd.args(["-c:a", "flac"]);
+        // FLAC кодирует 24 бита в 32-битных сэмплах
+        let sample_format = match self.audio_bit_depth() {
+          24 => "s32",
+          _ => "s16",
+        };
+        cmd.args(["-sample_fmt", sample_format]);
+      }
       OutputFormat::Custom(_) => {
         // Для пользовательского формата используем AAC по умолчанию
         cmd.args(["-c:a", "aac"]);
       }
     }
 
-    // Аудио битрейт
-    let audio_bitrate = self.project.settings.output.audio_bitrate.unwrap_or(192);
-    cmd.args(["-b:a", &format!("{audio_bitrate}k")]);
+    // Аудио битрейт (MP3 использует VBR, WAV, FLAC и PCM промежуточных кодеков - без потерь)
+    let format = &self.project.settings.output.format;
+    if !format.is_audio_only() && !format.is_intermediate() {
+      let audio_bitrate = self.project.settings.output.audio_bitrate.unwrap_or(192);
+      cmd.args(["-b:a", &format!("{audio_bitrate}k")]);
+    }
 
     // Частота дискретизации
     cmd.args(["-ar", "48000"]);
@@ -324,15 +463,24 @@ impl<'a> OutputBuilder<'a> {
 
   /// Добавить расширенные настройки кодирования
   fn add_advanced_encoding_settings(&self, cmd: &mut Command) -> Result<()> {
-    // Пиксельный формат
-    cmd.args(["-pix_fmt", "yuv420p"]);
+    let hdr = self.is_hdr_output();
+
+    // Пиксельный формат: промежуточные кодеки задают его профилем, HDR требует 10 бит
+    let pixel_format = match self.project.settings.output.format {
+      OutputFormat::MovProRes(profile) => profile.pixel_format(),
+      OutputFormat::MxfDnxhr(profile) => profile.pixel_format(),
+      _ if hdr => "yuv420p10le",
+      _ => "yuv420p",
+    };
+    cmd.args(["-pix_fmt", pixel_format]);
 
     // Настройки для H.264
     // Проверяем используемый кодек по формату (упрощенная логика)
-    let using_h264 = matches!(
-      self.project.settings.output.format,
-      OutputFormat::Mp4 | OutputFormat::Mov | OutputFormat::Avi
-    );
+    let using_h264 = !hdr
+      && matches!(
+        self.project.settings.output.format,
+        OutputFormat::Mp4 | OutputFormat::Mov | OutputFormat::Avi
+      );
 
     if using_h264 {
       // Профиль
@@ -358,23 +506,86 @@ impl<'a> OutputBuilder<'a> {
 
     // Настройки для H.265
     // Проверяем используемый кодек по формату (упрощенная логика)
-    let using_h265 = matches!(self.project.settings.output.format, OutputFormat::Mkv);
+    let using_h265 = match self.project.settings.output.format {
+      OutputFormat::Mkv => true,
+      OutputFormat::Mp4 | OutputFormat::Mov => hdr,
+      _ => false,
+    };
 
     if using_h265 {
-      cmd.args(["-profile:v", "main"]);
+      cmd.args(["-profile:v", if hdr { "main10" } else { "main" }]);
       cmd.args(["-level", "4.1"]);
-      cmd.args([
-        "-x265-params",
-        "keyint=48:min-keyint=24:bframes=3:b-adapt=2",
-      ]);
+      cmd.args(["-x265-params", &self.x265_params()]);
+    }
+
+    // VP9 Profile 2 для 10-битного HDR в WebM
+    if hdr && matches!(self.project.settings.output.format, OutputFormat::WebM) {
+      cmd.args(["-profile:v", "2"]);
     }
 
-    // Многопоточность
-    cmd.args(["-threads", "0"]); // Автоматический выбор
+    // Многопоточность: по приоритету рендеринга или автоматический выбор
+    let threads = self.settings.process_limits.threads.unwrap_or(0);
+    cmd.args(["-threads", &threads.to_string()]);
 
     Ok(())
   }
 
+  /// Параметры x265, для HDR дополнительно сигнализация цвета и метаданные HDR10
+  fn x265_params(&self) -> String {
+    let mut params = vec!["keyint=48:min-keyint=24:bframes=3:b-adapt=2".to_string()];
+
+    let export = &self.project.settings.export;
+    if export.hdr_mode.is_hdr_output() {
+      let tags = export.color_tags();
+      params.push("repeat-headers=1".to_string());
+      if let Some(primaries) = tags.primaries {
+        params.push(format!("colorprim={primaries}"));
+      }
+      if let Some(transfer) = tags.transfer {
+        params.push(format!("transfer={transfer}"));
+      }
+      if let Some(matrix) = tags.matrix {
+        params.push(format!("colormatrix={matrix}"));
+      }
+
+      if export.hdr_mode == HdrMode::Hdr10 {
+        params.push("hdr-opt=1".to_string());
+        if let Some(metadata) = &export.hdr_metadata {
+          if let Some(master_display) = &metadata.master_display {
+            params.push(format!("master-display={master_display}"));
+          }
+          if metadata.max_cll.is_some() || metadata.max_fall.is_some() {
+            params.push(format!(
+              "max-cll={},{}",
+              metadata.max_cll.unwrap_or(0),
+              metadata.max_fall.unwrap_or(0)
+            ));
+          }
+        }
+      }
+    }
+
+    params.join(":")
+  }
+
+  /// Добавить цветовые теги выходного потока
+  fn add_color_settings(&self, cmd: &mut Command) {
+    let tags = self.project.settings.export.color_tags();
+
+    if let Some(primaries) = tags.primaries {
+      cmd.args(["-color_primaries", &primaries]);
+    }
+    if let Some(transfer) = tags.transfer {
+      cmd.args(["-color_trc", &transfer]);
+    }
+    if let Some(matrix) = tags.matrix {
+      cmd.args(["-colorspace", &matrix]);
+    }
+    if self.project.settings.export.hdr_mode != HdrMode::None {
+      cmd.args(["-color_range", "tv"]);
+    }
+  }
+
   /// Добавить метаданные
   fn add_metadata(&self, cmd: &mut Command) -> Result<()> {
     // Название проекта
@@ -401,9 +612,26 @@ impl<'a> OutputBuilder<'a> {
     // Кодировщик
     cmd.args(["-metadata", "encoder=Timeline Studio"]);
 
+    // Дорожка timecode поддерживается только контейнерами MOV и MXF
+    if self.project.settings.export.write_timecode_track
+      && self
+        .project
+        .settings
+        .output
+        .format
+        .supports_timecode_track()
+    {
+      cmd.args(["-timecode", &self.project.timecode_at(0.0).to_string()]);
+    }
+
     Ok(())
   }
 
+  /// Разрядность аудио для WAV/FLAC и PCM промежуточных кодеков
+  fn audio_bit_depth(&self) -> u8 {
+    self.project.settings.export.audio_bit_depth.unwrap_or(16)
+  }
+
   /// Получить пресет для кодирования
   fn get_preset(&self) -> String {
     match self.project.settings.output.quality {
@@ -429,6 +657,62 @@ impl<'a> OutputBuilder<'a> {
   }
 }
 
+/// Предупреждения об экспорте в промежуточный кодек (ProRes, DNxHR).
+///
+/// Такие файлы в разы больше H.264, а битрейт, CRF и аппаратное ускорение
+/// для них не применяются.
+pub fn intermediate_codec_warnings(project: &ProjectSchema) -> Vec<String> {
+  let format = &project.settings.output.format;
+  let codec = match format {
+    OutputFormat::MovProRes(profile) => format!("ProRes {profile:?}"),
+    OutputFormat::MxfDnxhr(profile) => format!("DNxHR {profile:?}"),
+    _ => return Vec::new(),
+  };
+
+  let mut warnings = vec![format!(
+    "{codec}: промежуточный кодек без сжатия для доставки, размер файла будет очень большим"
+  )];
+  let export = &project.settings.export;
+  if project.settings.output.video_bitrate.is_some() || export.crf.is_some() {
+    warnings.push(format!(
+      "{codec}: битрейт и CRF игнорируются, качество определяется профилем"
+    ));
+  }
+  if export.hardware_acceleration {
+    warnings.push(format!(
+      "{codec}: аппаратное ускорение не поддерживается, используется CPU"
+    ));
+  }
+  warnings
+}
+
+/// Путь мастер-плейлиста HLS рядом с основным выходным файлом
+pub fn hls_master_playlist_path(output_path: &Path) -> PathBuf {
+  output_path.with_extension("m3u8")
+}
+
+/// Построить мастер-плейлист HLS со ссылками на плейлисты вариантов
+pub fn build_hls_master_playlist(project: &ProjectSchema, output_path: &Path) -> String {
+  let audio_bitrate = project.settings.output.audio_bitrate.unwrap_or(192);
+  let mut playlist = String::from("#EXTM3U\n#EXT-X-VERSION:3\n#EXT-X-INDEPENDENT-SEGMENTS\n");
+
+  for rendition in &project.settings.export.renditions {
+    let variant_path = rendition.output_path(output_path, true);
+    let variant_name = variant_path
+      .file_name()
+      .map(|name| name.to_string_lossy().into_owned())
+      .unwrap_or_default();
+    let bandwidth = (rendition.video_bitrate + audio_bitrate) * 1000;
+
+    playlist.push_str(&format!(
+      "#EXT-X-STREAM-INF:BANDWIDTH={bandwidth},RESOLUTION={}x{}\n{variant_name}\n",
+      rendition.resolution.width, rendition.resolution.height
+    ));
+  }
+
+  playlist
+}
+
 #[cfg(test)]
 mod outputs_tests;
 
@@ -753,6 +1037,41 @@ mod tests {
     assert!(encoder_meta);
   }
 
+  #[test]
+  fn test_add_metadata_timecode_track() {
+    use crate::video_compiler::schema::{Timecode, TimecodeRate};
+
+    let mut project = create_minimal_project();
+    project.settings.frame_rate = 25.0;
+    project.settings.timecode.start =
+      Some(Timecode::parse("10:00:00:00", TimecodeRate::Fps25).unwrap());
+    project.settings.export.write_timecode_track = true;
+    let settings = FFmpegBuilderSettings::default();
+
+    let timecode_args = |project: &ProjectSchema| {
+      let mut cmd = Command::new("ffmpeg");
+      OutputBuilder::new(project, &settings)
+        .add_metadata(&mut cmd)
+        .unwrap();
+      let args: Vec<String> = cmd
+        .as_std()
+        .get_args()
+        .map(|s| s.to_string_lossy().to_string())
+        .collect();
+      args
+        .iter()
+        .position(|arg| arg == "-timecode")
+        .map(|index| args[index + 1].clone())
+    };
+
+    // MP4 не хранит дорожку timecode
+    project.settings.output.format = OutputFormat::Mp4;
+    assert_eq!(timecode_args(&project), None);
+
+    project.settings.output.format = OutputFormat::Mov;
+    assert_eq!(timecode_args(&project).as_deref(), Some("10:00:00:00"));
+  }
+
   #[test]
   fn test_get_preset() {
     let project = create_minimal_project();
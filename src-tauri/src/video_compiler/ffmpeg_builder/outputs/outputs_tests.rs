@@ -507,6 +507,104 @@ mod advanced_encoding_tests {
   }
 }
 
+#[cfg(test)]
+mod color_tests {
+  use super::*;
+  use crate::video_compiler::schema::{HdrMetadata, HdrMode};
+
+  fn output_args(project: &crate::video_compiler::schema::ProjectSchema) -> Vec<String> {
+    let settings = create_ffmpeg_settings(false, None);
+    let builder = OutputBuilder::new(project, &settings);
+    let mut cmd = Command::new("ffmpeg");
+    builder.add_cpu_encoding(&mut cmd).unwrap();
+    builder.add_advanced_encoding_settings(&mut cmd).unwrap();
+    builder.add_color_settings(&mut cmd);
+
+    cmd
+      .as_std()
+      .get_args()
+      .map(|s| s.to_string_lossy().to_string())
+      .collect()
+  }
+
+  fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args
+      .iter()
+      .position(|arg| arg == flag)
+      .map(|i| args[i + 1].as_str())
+  }
+
+  #[test]
+  fn test_sdr_export_has_no_color_flags_by_default() {
+    let project = create_custom_project(OutputFormat::Mp4, 85);
+    let args = output_args(&project);
+
+    assert!(!args.contains(&"-color_primaries".to_string()));
+    assert_eq!(flag_value(&args, "-c:v"), Some("libx264"));
+  }
+
+  #[test]
+  fn test_explicit_color_tags() {
+    let mut project = create_custom_project(OutputFormat::Mp4, 85);
+    project.settings.export.color_primaries = Some("bt709".to_string());
+    project.settings.export.transfer_characteristics = Some("bt709".to_string());
+    project.settings.export.matrix_coefficients = Some("bt709".to_string());
+    let args = output_args(&project);
+
+    assert_eq!(flag_value(&args, "-color_primaries"), Some("bt709"));
+    assert_eq!(flag_value(&args, "-color_trc"), Some("bt709"));
+    assert_eq!(flag_value(&args, "-colorspace"), Some("bt709"));
+  }
+
+  #[test]
+  fn test_hdr10_mp4_uses_hevc_main10_with_metadata() {
+    let mut project = create_custom_project(OutputFormat::Mp4, 85);
+    project.settings.export.hdr_mode = HdrMode::Hdr10;
+    project.settings.export.hdr_metadata = Some(HdrMetadata {
+      master_display: Some(
+        "G(13250,34500)B(7500,3000)R(34000,16000)WP(15635,16450)L(10000000,1)".to_string(),
+      ),
+      max_cll: Some(1000),
+      max_fall: Some(400),
+    });
+    let args = output_args(&project);
+
+    assert_eq!(flag_value(&args, "-c:v"), Some("libx265"));
+    assert_eq!(flag_value(&args, "-pix_fmt"), Some("yuv420p10le"));
+    assert_eq!(flag_value(&args, "-profile:v"), Some("main10"));
+    assert_eq!(flag_value(&args, "-color_primaries"), Some("bt2020"));
+    assert_eq!(flag_value(&args, "-color_trc"), Some("smpte2084"));
+    assert_eq!(flag_value(&args, "-colorspace"), Some("bt2020nc"));
+
+    let x265_params = flag_value(&args, "-x265-params").unwrap();
+    assert!(x265_params.contains("hdr-opt=1"));
+    assert!(x265_params.contains("master-display=G(13250,34500)"));
+    assert!(x265_params.contains("max-cll=1000,400"));
+  }
+
+  #[test]
+  fn test_hlg_webm_uses_vp9_profile2() {
+    let mut project = create_custom_project(OutputFormat::WebM, 85);
+    project.settings.export.hdr_mode = HdrMode::Hlg;
+    let args = output_args(&project);
+
+    assert_eq!(flag_value(&args, "-c:v"), Some("libvpx-vp9"));
+    assert_eq!(flag_value(&args, "-profile:v"), Some("2"));
+    assert_eq!(flag_value(&args, "-color_trc"), Some("arib-std-b67"));
+  }
+
+  #[test]
+  fn test_tonemap_to_sdr_tags_bt709() {
+    let mut project = create_custom_project(OutputFormat::Mp4, 85);
+    project.settings.export.hdr_mode = HdrMode::ToneMapToSdr;
+    let args = output_args(&project);
+
+    assert_eq!(flag_value(&args, "-pix_fmt"), Some("yuv420p"));
+    assert_eq!(flag_value(&args, "-c:v"), Some("libx264"));
+    assert_eq!(flag_value(&args, "-color_primaries"), Some("bt709"));
+  }
+}
+
 #[cfg(test)]
 mod metadata_tests {
   use super::*;
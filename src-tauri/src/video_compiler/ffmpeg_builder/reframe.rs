@@ -0,0 +1,100 @@
+//! FFmpeg Builder - Анимированное кадрирование (рефрейминг) клипов
+
+use crate::video_compiler::schema::{
+  crop_axis_keyframes, effects::ParamKeyframe, CropKeyframe, TransformSettings,
+};
+
+use super::effects::keyframe_expression;
+
+/// Фильтр crop, окно которого движется по ключевым кадрам пути.
+///
+/// Размер окна у фильтра crop вычисляется один раз при инициализации,
+/// поэтому анимируется только положение: `x` и `y` - кусочно линейные
+/// выражения от `t` (время от начала клипа в исходном файле).
+pub fn crop_path_filter(path: &[CropKeyframe]) -> Option<String> {
+  let first = path.first()?;
+  let fraction = |keyframes: Vec<ParamKeyframe>| -> Vec<ParamKeyframe> {
+    keyframes
+      .into_iter()
+      .map(|keyframe| ParamKeyframe {
+        value: keyframe.value / 100.0,
+        ..keyframe
+      })
+      .collect()
+  };
+  let x = keyframe_expression(&fraction(crop_axis_keyframes(path, |rect| rect.x)));
+  let y = keyframe_expression(&fraction(crop_axis_keyframes(path, |rect| rect.y)));
+
+  Some(format!(
+    "crop=w=iw*{}:h=ih*{}:x='iw*({x})':y='ih*({y})'",
+    first.rect.width / 100.0,
+    first.rect.height / 100.0
+  ))
+}
+
+/// Геометрические фильтры клипа с учетом анимированного кадрирования
+pub fn clip_geometry_filters(transform: &TransformSettings) -> Vec<String> {
+  crop_path_filter(&transform.crop_path)
+    .into_iter()
+    .chain(transform.geometry_filters())
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::video_compiler::schema::CropRect;
+
+  fn keyframe(time: f64, x: f32) -> CropKeyframe {
+    CropKeyframe {
+      time,
+      rect: CropRect {
+        x,
+        y: 0.0,
+        width: 31.25,
+        height: 100.0,
+      },
+    }
+  }
+
+  #[test]
+  fn test_crop_path_filter_pans_window() {
+    let filter = crop_path_filter(&[keyframe(0.0, 10.0), keyframe(2.0, 50.0)]).unwrap();
+    assert_eq!(
+      filter,
+      "crop=w=iw*0.3125:h=ih*1:\
+x='iw*(if(lt(t,0),0.1,if(lt(t,2),0.1+(0.4)*(((t-0)/2)),0.5)))':\
+y='ih*(if(lt(t,0),0,if(lt(t,2),0+(0)*(((t-0)/2)),0)))'"
+    );
+    assert!(crop_path_filter(&[]).is_none());
+  }
+
+  #[test]
+  fn test_clip_geometry_filters_replace_static_crop() {
+    let transform = TransformSettings {
+      crop: Some(CropRect {
+        x: 0.0,
+        y: 0.0,
+        width: 50.0,
+        height: 50.0,
+      }),
+      crop_path: vec![keyframe(0.0, 20.0)],
+      flip_horizontal: true,
+      ..Default::default()
+    };
+
+    let filters = clip_geometry_filters(&transform);
+    assert_eq!(filters.len(), 2);
+    assert!(filters[0].starts_with("crop=w=iw*0.3125:h=ih*1:x='iw*(0.2)'"));
+    assert_eq!(filters[1], "hflip");
+
+    let static_crop = TransformSettings {
+      crop_path: Vec::new(),
+      ..transform
+    };
+    assert_eq!(
+      clip_geometry_filters(&static_crop)[0],
+      "crop=iw*0.5:ih*0.5:iw*0:ih*0"
+    );
+  }
+}
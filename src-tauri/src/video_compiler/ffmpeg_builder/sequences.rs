@@ -0,0 +1,209 @@
+//! FFmpeg Builder - Рендеринг вложенных последовательностей
+//!
+//! По умолчанию составные клипы разворачиваются в клипы основного timeline.
+//! С флагом `render_nested_as_intermediate` каждая последовательность сначала
+//! рендерится в промежуточный ProRes файл, который используется как вход.
+
+use std::path::PathBuf;
+use tokio::process::Command;
+
+use crate::video_compiler::error::{Result, VideoCompilerError};
+
+use super::builder::FFmpegBuilder;
+
+/// Путь к промежуточному файлу вложенной последовательности
+pub fn nested_sequence_path(sequence_id: &str) -> PathBuf {
+  std::env::temp_dir()
+    .join("timeline-studio-sequences")
+    .join(format!("{sequence_id}.mov"))
+}
+
+/// Команда рендера промежуточного файла последовательности
+#[derive(Debug)]
+pub struct NestedSequenceCommand {
+  /// ID последовательности
+  pub sequence_id: String,
+  /// Путь к промежуточному файлу
+  pub output_path: PathBuf,
+  /// Команда FFmpeg
+  pub command: Command,
+}
+
+impl FFmpegBuilder {
+  /// Рендерятся ли вложенные последовательности в промежуточные файлы
+  pub fn renders_nested_as_intermediate(&self) -> bool {
+    self.project().settings.export.render_nested_as_intermediate
+      && self.project().has_sequence_clips()
+  }
+
+  /// Построитель для проекта с развернутыми последовательностями, если они
+  /// не рендерятся в промежуточные файлы
+  pub(super) fn flattened_sequences(&self) -> Result<Option<FFmpegBuilder>> {
+    if !self.project().has_sequence_clips()
+      || self.project().settings.export.render_nested_as_intermediate
+    {
+      return Ok(None);
+    }
+    self.flatten_all().map(Some)
+  }
+
+  /// Построитель для проекта с развернутыми последовательностями
+  pub(super) fn flatten_all(&self) -> Result<FFmpegBuilder> {
+    let project = self
+      .project()
+      .flatten_all_sequences()
+      .map_err(VideoCompilerError::validation)?;
+    Ok(
+      FFmpegBuilder::with_settings(project, self.settings().clone())
+        .with_filter_capabilities(self.filter_capabilities().clone()),
+    )
+  }
+
+  /// Команды рендера промежуточных файлов последовательностей.
+  ///
+  /// Команды упорядочены так, что вложенные последовательности рендерятся
+  /// раньше содержащих их. Пустой список, если промежуточные файлы не нужны.
+  pub async fn build_nested_sequence_commands(&self) -> Result<Vec<NestedSequenceCommand>> {
+    if !self.renders_nested_as_intermediate() {
+      return Ok(Vec::new());
+    }
+
+    let mut commands = Vec::new();
+    for sequence_id in self.project().sequence_render_order() {
+      let project = self
+        .project()
+        .sequence_project(&sequence_id)
+        .ok_or_else(|| {
+          VideoCompilerError::validation(format!("Последовательность '{sequence_id}' не найдена"))
+        })?;
+      let duration = project.timeline.duration;
+      let output_path = nested_sequence_path(&sequence_id);
+
+      let builder = FFmpegBuilder::with_settings(project, self.settings().clone())
+        .with_filter_capabilities(self.filter_capabilities().clone());
+      let command = builder
+        .build_prerender_segment_command(0.0, duration, &output_path)
+        .await?;
+
+      commands.push(NestedSequenceCommand {
+        sequence_id,
+        output_path,
+        command,
+      });
+    }
+
+    Ok(commands)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::video_compiler::schema::{Clip, ProjectSchema, Track, TrackType};
+  use std::path::Path;
+
+  fn clip(id: &str, path: &str, start: f64, end: f64) -> Clip {
+    let mut clip = Clip::new(PathBuf::from(path), start, end - start);
+    clip.id = id.to_string();
+    clip
+  }
+
+  /// Проект с двумя уровнями вложенности: outer(inner(a), b) и клип intro
+  fn two_level_project() -> (ProjectSchema, String, String) {
+    let mut project = ProjectSchema::new("Nested".to_string());
+    let mut video = Track::new(TrackType::Video, "Video".to_string());
+    video.clips = vec![
+      clip("intro", "/media/intro.mp4", 0.0, 2.0),
+      clip("a", "/media/a.mp4", 2.0, 5.0),
+      clip("b", "/media/b.mp4", 5.0, 8.0),
+    ];
+    project.tracks.push(video);
+
+    let inner = project
+      .create_sequence_from_clips(&["a".to_string()], "Inner".to_string())
+      .unwrap();
+    let inner_clip = project.tracks[0].clips[1].id.clone();
+    let outer = project
+      .create_sequence_from_clips(&[inner_clip, "b".to_string()], "Outer".to_string())
+      .unwrap();
+
+    (project, inner, outer)
+  }
+
+  fn args(cmd: &Command) -> Vec<String> {
+    cmd
+      .as_std()
+      .get_args()
+      .map(|arg| arg.to_string_lossy().to_string())
+      .collect()
+  }
+
+  fn inputs(args: &[String]) -> Vec<&str> {
+    args
+      .windows(2)
+      .filter(|pair| pair[0] == "-i")
+      .map(|pair| pair[1].as_str())
+      .collect()
+  }
+
+  #[tokio::test]
+  async fn test_two_level_nesting_flattened_render() {
+    let (project, _, _) = two_level_project();
+    assert!(project.validate().is_ok());
+
+    let builder = FFmpegBuilder::new(project);
+    assert!(builder
+      .build_nested_sequence_commands()
+      .await
+      .unwrap()
+      .is_empty());
+
+    let cmd = builder
+      .build_render_command(Path::new("/tmp/out.mp4"))
+      .await
+      .unwrap();
+    let args = args(&cmd);
+    assert_eq!(
+      inputs(&args),
+      vec!["/media/intro.mp4", "/media/a.mp4", "/media/b.mp4"]
+    );
+    assert!(!args
+      .iter()
+      .any(|arg| arg.contains("timeline-studio-sequences")));
+  }
+
+  #[tokio::test]
+  async fn test_two_level_nesting_intermediate_render() {
+    let (mut project, inner, outer) = two_level_project();
+    project.settings.export.render_nested_as_intermediate = true;
+
+    let builder = FFmpegBuilder::new(project);
+    let nested = builder.build_nested_sequence_commands().await.unwrap();
+    let order: Vec<&str> = nested.iter().map(|c| c.sequence_id.as_str()).collect();
+    assert_eq!(order, vec![inner.as_str(), outer.as_str()]);
+
+    // Внутренняя последовательность рендерится из исходного файла
+    let inner_args = args(&nested[0].command);
+    assert_eq!(inputs(&inner_args), vec!["/media/a.mp4"]);
+    assert!(inner_args.contains(&"prores_ks".to_string()));
+    let inner_path = nested_sequence_path(&inner).to_string_lossy().to_string();
+    assert!(inner_args.contains(&inner_path));
+
+    // Внешняя использует промежуточный файл внутренней
+    let outer_args = args(&nested[1].command);
+    assert_eq!(
+      inputs(&outer_args),
+      vec![inner_path.as_str(), "/media/b.mp4"]
+    );
+
+    // Основной рендер использует промежуточный файл внешней последовательности
+    let outer_path = nested_sequence_path(&outer).to_string_lossy().to_string();
+    let cmd = builder
+      .build_render_command(Path::new("/tmp/out.mp4"))
+      .await
+      .unwrap();
+    let args = args(&cmd);
+    assert_eq!(inputs(&args), vec!["/media/intro.mp4", outer_path.as_str()]);
+    assert!(args.iter().any(|arg| arg.contains("[1:v]")));
+  }
+}
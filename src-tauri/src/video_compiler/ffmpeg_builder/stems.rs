@@ -0,0 +1,239 @@
+//! FFmpeg Builder - Экспорт аудио стемов
+//!
+//! С флагом `export_stems` каждый аудио трек дополнительно выводится в свой
+//! файл `<output>_<трек>.wav` той же командой FFmpeg, что и основной файл:
+//! прогресс и отмена рендера распространяются на стемы автоматически.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+use crate::video_compiler::core::output_template::sanitize_file_stem;
+use crate::video_compiler::error::Result;
+use crate::video_compiler::schema::StemFormat;
+
+use super::builder::FFmpegBuilder;
+use super::filters::FilterBuilder;
+
+/// Поток аудио трека в графе фильтров
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioStem {
+  /// ID трека
+  pub track_id: String,
+  /// Название трека
+  pub track_name: String,
+  /// Метка потока, например `[outa_track0]`
+  pub label: String,
+}
+
+/// Выходной файл стема
+#[derive(Debug, Clone, PartialEq)]
+pub struct StemOutput {
+  /// ID трека
+  pub track_id: String,
+  /// Метка потока в графе фильтров
+  pub label: String,
+  /// Путь к файлу стема
+  pub output_path: PathBuf,
+}
+
+/// Пути файлов стемов рядом с основным файлом: `<output>_<трек>.<ext>`.
+///
+/// Название трека очищается для файловой системы; при совпадении имен
+/// добавляется номер (`_Audio_2`).
+pub fn plan_stem_outputs(
+  stems: Vec<AudioStem>,
+  base: &Path,
+  format: StemFormat,
+) -> Vec<StemOutput> {
+  let base_stem = base
+    .file_stem()
+    .map(|stem| stem.to_string_lossy().into_owned())
+    .unwrap_or_default();
+  let mut used = HashSet::new();
+
+  stems
+    .into_iter()
+    .map(|stem| {
+      let name = sanitize_file_stem(&stem.track_name);
+      let mut unique = name.clone();
+      let mut counter = 2;
+      while !used.insert(unique.to_lowercase()) {
+        unique = format!("{name}_{counter}");
+        counter += 1;
+      }
+
+      StemOutput {
+        track_id: stem.track_id,
+        label: stem.label,
+        output_path: base.with_file_name(format!("{base_stem}_{unique}.{}", format.extension())),
+      }
+    })
+    .collect()
+}
+
+impl FFmpegBuilder {
+  /// Файлы стемов, которые создаст команда рендера в `output_path`.
+  /// Пусто, если экспорт стемов выключен.
+  pub fn stem_outputs(&self, output_path: &Path) -> Result<Vec<StemOutput>> {
+    if let Some(flattened) = self.flattened_sequences()? {
+      return flattened.stem_outputs(output_path);
+    }
+    let export = &self.project().settings.export;
+    if !export.export_stems {
+      return Ok(Vec::new());
+    }
+
+    let stems = FilterBuilder::new(self.project())
+      .with_stems()
+      .audio_stems();
+    Ok(plan_stem_outputs(stems, output_path, export.stems_format))
+  }
+
+  /// Добавить выходы стемов после основного выхода
+  pub(super) fn add_stem_outputs(
+    &self,
+    cmd: &mut Command,
+    filter_builder: &FilterBuilder<'_>,
+    output_path: &Path,
+  ) {
+    let export = &self.project().settings.export;
+    let bit_depth = export.audio_bit_depth.unwrap_or(16);
+
+    for stem in plan_stem_outputs(
+      filter_builder.audio_stems(),
+      output_path,
+      export.stems_format,
+    ) {
+      cmd.args(["-map", &stem.label, "-vn", "-sn"]);
+      match (export.stems_format, bit_depth) {
+        (StemFormat::Wav, 24) => cmd.args(["-c:a", "pcm_s24le"]),
+        (StemFormat::Wav, _) => cmd.args(["-c:a", "pcm_s16le"]),
+        // FLAC кодирует 24 бита в 32-битных сэмплах
+        (StemFormat::Flac, 24) => cmd.args(["-c:a", "flac", "-sample_fmt", "s32"]),
+        (StemFormat::Flac, _) => cmd.args(["-c:a", "flac", "-sample_fmt", "s16"]),
+      };
+      cmd.args(["-ar", "48000"]);
+      cmd.arg(&stem.output_path);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::video_compiler::schema::{Clip, ProjectSchema, Track, TrackType};
+
+  fn args(cmd: &Command) -> Vec<String> {
+    cmd
+      .as_std()
+      .get_args()
+      .map(|arg| arg.to_string_lossy().to_string())
+      .collect()
+  }
+
+  fn two_audio_track_project() -> ProjectSchema {
+    let mut project = ProjectSchema::new("Stems".to_string());
+    let mut video = Track::new(TrackType::Video, "Video".to_string());
+    video
+      .clips
+      .push(Clip::new(PathBuf::from("/media/camera.mp4"), 0.0, 5.0));
+    project.tracks.push(video);
+
+    for (name, path) in [
+      ("Dialogue", "/media/voice.wav"),
+      ("Music: Score/Main", "/media/score.wav"),
+    ] {
+      let mut track = Track::new(TrackType::Audio, name.to_string());
+      track.clips.push(Clip::new(PathBuf::from(path), 0.0, 5.0));
+      project.tracks.push(track);
+    }
+    project.settings.export.export_stems = true;
+    project
+  }
+
+  #[tokio::test]
+  async fn test_render_command_maps_stem_per_audio_track() {
+    let project = two_audio_track_project();
+    let builder = FFmpegBuilder::new(project);
+    let output = Path::new("/exports/film.mp4");
+
+    let cmd = builder.build_render_command(output).await.unwrap();
+    let args = args(&cmd);
+    let filter = &args[args.iter().position(|a| a == "-filter_complex").unwrap() + 1];
+
+    assert!(filter.contains("[atrack0]asplit=2[astem0mix][outa_track0]"));
+    assert!(filter.contains("[atrack1]asplit=2[astem1mix][outa_track1]"));
+    assert!(filter.contains("[astem0mix][astem1mix]amix=inputs=2[outa]"));
+
+    // Основной выход идет первым, затем стемы со своими метками
+    let main = args.iter().position(|a| a == "/exports/film.mp4").unwrap();
+    let dialogue = args
+      .iter()
+      .position(|a| a == "/exports/film_Dialogue.wav")
+      .unwrap();
+    let music = args
+      .iter()
+      .position(|a| a == "/exports/film_Music_ Score_Main.wav")
+      .unwrap();
+    assert!(main < dialogue && dialogue < music);
+    assert_eq!(args[dialogue - 8..dialogue - 6], ["-map", "[outa_track0]"]);
+    assert_eq!(args[music - 8..music - 6], ["-map", "[outa_track1]"]);
+    assert!(args.contains(&"pcm_s16le".to_string()));
+
+    let outputs = builder.stem_outputs(output).unwrap();
+    let paths: Vec<_> = outputs
+      .iter()
+      .map(|stem| stem.output_path.clone())
+      .collect();
+    assert_eq!(
+      paths,
+      vec![
+        PathBuf::from("/exports/film_Dialogue.wav"),
+        PathBuf::from("/exports/film_Music_ Score_Main.wav"),
+      ]
+    );
+  }
+
+  #[tokio::test]
+  async fn test_stems_disabled_by_default() {
+    let mut project = two_audio_track_project();
+    project.settings.export.export_stems = false;
+    let builder = FFmpegBuilder::new(project);
+    let output = Path::new("/exports/film.mp4");
+
+    let args = args(&builder.build_render_command(output).await.unwrap());
+    assert!(!args.iter().any(|arg| arg.contains("outa_track")));
+    assert!(builder.stem_outputs(output).unwrap().is_empty());
+  }
+
+  #[test]
+  fn test_plan_stem_outputs_deduplicates_names() {
+    let stem = |id: &str, name: &str| AudioStem {
+      track_id: id.to_string(),
+      track_name: name.to_string(),
+      label: format!("[outa_{id}]"),
+    };
+    let outputs = plan_stem_outputs(
+      vec![stem("a", "SFX"), stem("b", "sfx"), stem("c", "")],
+      Path::new("/out/mix.mov"),
+      StemFormat::Flac,
+    );
+
+    let names: Vec<_> = outputs
+      .iter()
+      .map(|stem| {
+        stem
+          .output_path
+          .file_name()
+          .unwrap()
+          .to_string_lossy()
+          .to_string()
+      })
+      .collect();
+    assert_eq!(
+      names,
+      vec!["mix_SFX.flac", "mix_sfx_2.flac", "mix_untitled.flac"]
+    );
+  }
+}
@@ -1,11 +1,25 @@
 //! FFmpeg Builder - Модуль обработки субтитров
 
-use crate::video_compiler::error::Result;
+use crate::video_compiler::error::{Result, VideoCompilerError};
 use crate::video_compiler::schema::{
-  ProjectSchema, Subtitle, SubtitleAlignX, SubtitleAlignY, SubtitleAnimation,
+  OutputFormat, ProjectSchema, Subtitle, SubtitleAlignX, SubtitleAlignY, SubtitleAnimation,
   SubtitleAnimationType, SubtitleDirection, SubtitleFontWeight, SubtitlePosition,
 };
 
+/// Кодек дорожки субтитров для контейнера (режимы Soft/Both)
+pub fn soft_subtitle_codec(format: &OutputFormat) -> Result<&'static str> {
+  match format {
+    OutputFormat::Mp4 | OutputFormat::Mov | OutputFormat::MovProRes(_) => Ok("mov_text"),
+    OutputFormat::Mkv => Ok("srt"),
+    OutputFormat::WebM => Err(VideoCompilerError::validation(
+      "WebM поддерживает только WebVTT-субтитры с ограничениями плееров, используйте режим Burn",
+    )),
+    other => Err(VideoCompilerError::validation(format!(
+      "Контейнер {other:?} не поддерживает дорожку субтитров, используйте режим Burn"
+    ))),
+  }
+}
+
 /// Построитель субтитров
 pub struct SubtitleBuilder<'a> {
   project: &'a ProjectSchema,
@@ -1,10 +1,19 @@
 //! FFmpeg Builder - Модуль обработки шаблонов
 
+use std::collections::HashSet;
+
 use crate::video_compiler::error::{Result, VideoCompilerError};
 use crate::video_compiler::schema::{
-  AnimationType, ProjectSchema, StyleElementType, StyleTemplate, StyleTemplateElement, Template,
+  AnimationType, ProjectSchema, ShapeType, StyleElementType, StyleTemplate, StyleTemplateElement,
+  Template,
 };
 
+/// Ключ `properties.custom` с порядком наложения элемента
+pub const Z_INDEX_KEY: &str = "z_index";
+
+/// Ключ `properties.custom` с градиентной заливкой формы
+pub const GRADIENT_KEY: &str = "gradient";
+
 /// Построитель шаблонов
 pub struct TemplateBuilder<'a> {
   project: &'a ProjectSchema,
@@ -117,7 +126,11 @@ impl<'a> TemplateBuilder<'a> {
     Ok(filters.join(""))
   }
 
-  /// Построить фильтр для стильного шаблона
+  /// Построить фильтр для стильного шаблона.
+  ///
+  /// Элементы накладываются в порядке [`element_z_index`], при равном индексе
+  /// сохраняется порядок в шаблоне. Элементы, которые не удается отрисовать,
+  /// пропускаются; предупреждения о них дает [`style_template_warnings`].
   async fn build_style_template_filter(
     &self,
     template: &StyleTemplate,
@@ -134,229 +147,118 @@ impl<'a> TemplateBuilder<'a> {
     );
     filters.push(base_filter);
 
-    // Обрабатываем элементы шаблона
-    let mut overlay_chain = String::new();
-    let mut current_layer = "[style_base]".to_string();
-    let mut layer_names = Vec::new();
-
-    for (idx, element) in template.elements.iter().enumerate() {
-      match &element.element_type {
-        StyleElementType::Text => {
-          // Создаем текстовый элемент
-          let text_filter = self.build_text_element_filter(element, idx)?;
-          filters.push(text_filter);
-
-          // Применяем анимацию
-          if !element.animations.is_empty() {
-            let animated_filter = self.apply_element_animation(
-              &format!("[text{idx}]"),
-              element,
-              idx,
-              template.duration,
-            )?;
-            filters.push(animated_filter);
-          }
-
-          // Накладываем на текущий слой
-          let output = if idx == template.elements.len() - 1 {
-            "[style_output]"
-          } else {
-            &format!("[layer{idx}]")
-          };
-
-          overlay_chain.push_str(&format!(
-            ";{}[animated{}]overlay={}:{}{}",
-            current_layer, idx, element.position.x, element.position.y, output
-          ));
-
-          if idx < template.elements.len() - 1 {
-            let layer_name = format!("[layer{idx}]");
-            layer_names.push(layer_name.clone());
-            current_layer = layer_name;
-          }
-        }
-        StyleElementType::Image => {
-          // Загружаем изображение
-          let image_filter = format!(
-            "movie={}:loop=0,scale={}:{}[img{}]",
-            element.content, element.size.width, element.size.height, idx
-          );
-          filters.push(image_filter);
-
-          // Применяем анимацию
-          if !element.animations.is_empty() {
-            let animated_filter = self.apply_element_animation(
-              &format!("[img{idx}]"),
-              element,
-              idx,
-              template.duration,
-            )?;
-            filters.push(animated_filter);
-          }
-
-          // Накладываем на текущий слой
-          let output = if idx == template.elements.len() - 1 {
-            "[style_output]"
-          } else {
-            &format!("[layer{idx}]")
-          };
-
-          overlay_chain.push_str(&format!(
-            ";{}[animated{}]overlay={}:{}{}",
-            current_layer, idx, element.position.x, element.position.y, output
-          ));
-
-          if idx < template.elements.len() - 1 {
-            let layer_name = format!("[layer{idx}]");
-            layer_names.push(layer_name.clone());
-            current_layer = layer_name;
-          }
-        }
-        StyleElementType::Shape => {
-          // Создаем форму
-          let shape_filter = self.build_shape_element_filter(element, idx)?;
-          filters.push(shape_filter);
-
-          // Применяем анимацию
-          if !element.animations.is_empty() {
-            let animated_filter = self.apply_element_animation(
-              &format!("[shape{idx}]"),
-              element,
-              idx,
-              template.duration,
-            )?;
-            filters.push(animated_filter);
-          }
-
-          // Накладываем на текущий слой
-          let output = if idx == template.elements.len() - 1 {
-            "[style_output]"
-          } else {
-            &format!("[layer{idx}]")
-          };
-
-          overlay_chain.push_str(&format!(
-            ";{}[animated{}]overlay={}:{}{}",
-            current_layer, idx, element.position.x, element.position.y, output
-          ));
-
-          if idx < template.elements.len() - 1 {
-            let layer_name = format!("[layer{idx}]");
-            layer_names.push(layer_name.clone());
-            current_layer = layer_name;
-          }
-        }
-        StyleElementType::Video => {
-          // Используем входное видео
-          let video_filter = format!(
-            "[{}:v]scale={}:{},crop={}:{}[vid{}]",
-            input_index,
-            element.size.width,
-            element.size.height,
-            element.size.width,
-            element.size.height,
-            idx
-          );
-          filters.push(video_filter);
-
-          // Применяем анимацию
-          if !element.animations.is_empty() {
-            let animated_filter = self.apply_element_animation(
-              &format!("[vid{idx}]"),
-              element,
-              idx,
-              template.duration,
-            )?;
-            filters.push(animated_filter);
-          }
-
-          // Накладываем на текущий слой
-          let output = if idx == template.elements.len() - 1 {
-            "[style_output]"
-          } else {
-            &format!("[layer{idx}]")
-          };
-
-          overlay_chain.push_str(&format!(
-            ";{}[animated{}]overlay={}:{}{}",
-            current_layer, idx, element.position.x, element.position.y, output
-          ));
+    let elements: Vec<(usize, &StyleTemplateElement)> = ordered_elements(template)
+      .into_iter()
+      .filter(|(_, element)| is_renderable(element))
+      .collect();
 
-          if idx < template.elements.len() - 1 {
-            let layer_name = format!("[layer{idx}]");
-            layer_names.push(layer_name.clone());
-            current_layer = layer_name;
-          }
-        }
-        StyleElementType::Line => {
-          // Создаем линию как прямоугольник
-          let line_filter = format!(
-            "color=c={}:s={}x{}[line{}]",
-            element
-              .style
-              .as_ref()
-              .and_then(|s| s.color.as_deref())
-              .unwrap_or("#FFFFFF")
-              .trim_start_matches('#'),
-            element.size.width,
-            element.size.height,
-            idx
-          );
-          filters.push(line_filter);
-        }
-        StyleElementType::Icon => {
-          // Создаем иконку как изображение или текст
-          let icon_filter = format!(
-            "drawtext=text='{}':fontsize={}:fontcolor={}:x=0:y=0[icon{}]",
-            element.content,
-            element
-              .style
-              .as_ref()
-              .and_then(|s| s.font_size)
-              .unwrap_or(24),
-            element
-              .style
-              .as_ref()
-              .and_then(|s| s.color.as_deref())
-              .unwrap_or("#FFFFFF")
-              .trim_start_matches('#'),
-            idx
-          );
-          filters.push(icon_filter);
-        }
-        StyleElementType::Particles => {
-          // Создаем эффект частиц как анимированные точки
-          let particles_filter = format!(
-            "color=c={}:s={}x{}[particles{}]",
-            element
-              .style
-              .as_ref()
-              .and_then(|s| s.background_color.as_deref())
-              .unwrap_or("#000000")
-              .trim_start_matches('#'),
-            element.size.width,
-            element.size.height,
-            idx
-          );
-          filters.push(particles_filter);
+    let mut current_layer = "[style_base]".to_string();
+    for (position, (idx, element)) in elements.iter().enumerate() {
+      let idx = *idx;
+      let (source_filter, source_label) = self.build_element_source(element, idx, input_index)?;
+      filters.push(source_filter);
+
+      // Прозрачность, поворот, масштаб и размытие из свойств элемента
+      let transformed_label = match build_element_transform(element, &source_label, idx) {
+        Some(transform) => {
+          filters.push(transform);
+          format!("[xf{idx}]")
         }
-      }
-    }
+        None => source_label,
+      };
 
-    if !overlay_chain.is_empty() {
-      filters.push(overlay_chain);
+      // Без анимаций фильтр просто переименовывает выход в [animatedN]
+      filters.push(self.apply_element_animation(
+        &transformed_label,
+        element,
+        idx,
+        template.duration,
+      )?);
+
+      // Накладываем на текущий слой
+      let output = if position == elements.len() - 1 {
+        "[style_output]".to_string()
+      } else {
+        format!("[layer{idx}]")
+      };
+      filters.push(format!(
+        "{}[animated{}]overlay={}:{}{}{}",
+        current_layer,
+        idx,
+        element.position.x,
+        element.position.y,
+        overlay_enable(element),
+        output
+      ));
+      current_layer = output;
     }
 
     // Применяем переходы шаблона
     if !template.transitions.is_empty() {
       let transition_filter = self.apply_template_transitions(template)?;
-      filters.push(transition_filter);
+      if !transition_filter.is_empty() {
+        filters.push(transition_filter);
+      }
     }
 
     Ok(filters.join(";"))
   }
 
-  /// Построить фильтр для текстового элемента
+  /// Источник элемента: фильтр и метка его выхода
+  fn build_element_source(
+    &self,
+    element: &StyleTemplateElement,
+    index: usize,
+    input_index: usize,
+  ) -> Result<(String, String)> {
+    match &element.element_type {
+      StyleElementType::Text => Ok((
+        self.build_text_element_filter(element, index)?,
+        format!("[text{index}]"),
+      )),
+      StyleElementType::Image => Ok((
+        format!(
+          "movie={}:loop=0,scale={}:{}[img{}]",
+          element.content, element.size.width, element.size.height, index
+        ),
+        format!("[img{index}]"),
+      )),
+      StyleElementType::Shape => Ok((
+        self.build_shape_element_filter(element, index)?,
+        format!("[shape{index}]"),
+      )),
+      StyleElementType::Video => Ok((
+        format!(
+          "[{}:v]scale={}:{},crop={}:{}[vid{}]",
+          input_index,
+          element.size.width,
+          element.size.height,
+          element.size.width,
+          element.size.height,
+          index
+        ),
+        format!("[vid{index}]"),
+      )),
+      StyleElementType::Line => Ok((
+        self.build_line_element_filter(element, index),
+        format!("[line{index}]"),
+      )),
+      StyleElementType::Icon => Ok((
+        self.build_icon_element_filter(element, index),
+        format!("[icon{index}]"),
+      )),
+      StyleElementType::Particles => Err(VideoCompilerError::InvalidParameter(format!(
+        "Элемент '{}': частицы не поддерживаются при рендеринге",
+        element.name
+      ))),
+    }
+  }
+
+  /// Построить фильтр для текстового элемента.
+  ///
+  /// Текст рисуется на прозрачном холсте размера элемента, чтобы к нему
+  /// применялись поворот, масштаб и прозрачность; цвет фона стиля дает
+  /// подложку под текстом.
   fn build_text_element_filter(
     &self,
     element: &StyleTemplateElement,
@@ -367,9 +269,21 @@ impl<'a> TemplateBuilder<'a> {
       .as_ref()
       .ok_or_else(|| VideoCompilerError::InvalidParameter("Missing element style".to_string()))?;
 
+    let text = element
+      .properties
+      .text
+      .as_deref()
+      .unwrap_or(&element.content);
+    let text_box = style
+      .background_color
+      .as_deref()
+      .map(|color| format!(":box=1:boxcolor={}", color.trim_start_matches('#')))
+      .unwrap_or_default();
+
     Ok(format!(
-      "drawtext=text='{}':fontfile='{}':fontsize={}:fontcolor={}:x=0:y=0[text{}]",
-      element.content.replace("'", "\\'"),
+      "{},drawtext=text='{}':fontfile='{}':fontsize={}:fontcolor={}{}:x=0:y=0[text{}]",
+      transparent_canvas(element),
+      text.replace("'", "\\'"),
       self.get_system_font(style.font_family.as_deref().unwrap_or("Arial")),
       style.font_size.unwrap_or(24),
       style
@@ -377,16 +291,48 @@ impl<'a> TemplateBuilder<'a> {
         .as_deref()
         .unwrap_or("#FFFFFF")
         .trim_start_matches('#'),
+      text_box,
       index
     ))
   }
 
-  /// Построить фильтр для элемента формы
+  /// Построить фильтр для элемента формы.
+  ///
+  /// Градиент задается в `properties.custom["gradient"]` полями `from`, `to`
+  /// и `direction` (`horizontal` или `vertical`); круг и эллипс вырезаются
+  /// маской по альфа-каналу.
   fn build_shape_element_filter(
     &self,
     element: &StyleTemplateElement,
     index: usize,
   ) -> Result<String> {
+    let (width, height) = (element.size.width, element.size.height);
+
+    if let Some(gradient) = element.properties.custom.get(GRADIENT_KEY) {
+      let color = |key: &str, default: &str| {
+        gradient
+          .get(key)
+          .and_then(|v| v.as_str())
+          .unwrap_or(default)
+          .trim_start_matches('#')
+          .to_string()
+      };
+      let (x1, y1) = match gradient.get("direction").and_then(|v| v.as_str()) {
+        Some("vertical") => (0.0, height),
+        _ => (width, 0.0),
+      };
+      return Ok(format!(
+        "gradients=s={}x{}:c0=0x{}:c1=0x{}:x0=0:y0=0:x1={}:y1={}:nb_colors=2[shape{}]",
+        width,
+        height,
+        color("from", "#000000"),
+        color("to", "#FFFFFF"),
+        x1,
+        y1,
+        index
+      ));
+    }
+
     let style = element
       .style
       .as_ref()
@@ -398,12 +344,64 @@ impl<'a> TemplateBuilder<'a> {
       .unwrap_or("#000000")
       .trim_start_matches('#');
 
+    let mask = match element.properties.shape_type {
+      Some(ShapeType::Circle) | Some(ShapeType::Ellipse) => {
+        ",format=rgba,geq=r='r(X,Y)':g='g(X,Y)':b='b(X,Y)':\
+         a='if(lte(pow(X-W/2,2)/pow(W/2,2)+pow(Y-H/2,2)/pow(H/2,2),1),255,0)'"
+      }
+      _ => "",
+    };
+
     Ok(format!(
-      "color=c={}:s={}x{}[shape{}]",
-      color, element.size.width, element.size.height, index
+      "color=c={}:s={}x{}{}[shape{}]",
+      color, width, height, mask, index
     ))
   }
 
+  /// Построить фильтр для линии (разделителя)
+  fn build_line_element_filter(&self, element: &StyleTemplateElement, index: usize) -> String {
+    let color = element
+      .style
+      .as_ref()
+      .and_then(|s| s.color.as_deref())
+      .or(element.properties.stroke_color.as_deref())
+      .or(element.properties.fill_color.as_deref())
+      .unwrap_or("#FFFFFF")
+      .trim_start_matches('#');
+    // Высота линии без размера берется из толщины обводки
+    let thickness = if element.size.height >= 1.0 {
+      element.size.height
+    } else {
+      element.properties.stroke_width.unwrap_or(1.0).max(1.0)
+    };
+
+    format!(
+      "color=c={}:s={}x{}[line{}]",
+      color, element.size.width, thickness, index
+    )
+  }
+
+  /// Построить фильтр для иконки (символ шрифта на прозрачном холсте)
+  fn build_icon_element_filter(&self, element: &StyleTemplateElement, index: usize) -> String {
+    format!(
+      "{},drawtext=text='{}':fontsize={}:fontcolor={}:x=0:y=0[icon{}]",
+      transparent_canvas(element),
+      element.content.replace("'", "\\'"),
+      element
+        .style
+        .as_ref()
+        .and_then(|s| s.font_size)
+        .unwrap_or(24),
+      element
+        .style
+        .as_ref()
+        .and_then(|s| s.color.as_deref())
+        .unwrap_or("#FFFFFF")
+        .trim_start_matches('#'),
+      index
+    )
+  }
+
   /// Применить анимацию к элементу
   fn apply_element_animation(
     &self,
@@ -651,6 +649,144 @@ impl<'a> TemplateBuilder<'a> {
   }
 }
 
+/// Порядок наложения элемента: `properties.custom["z_index"]`, по умолчанию 0
+pub fn element_z_index(element: &StyleTemplateElement) -> i64 {
+  element
+    .properties
+    .custom
+    .get(Z_INDEX_KEY)
+    .and_then(|v| v.as_i64())
+    .unwrap_or(0)
+}
+
+/// Элементы шаблона с исходными индексами в порядке наложения снизу вверх
+fn ordered_elements(template: &StyleTemplate) -> Vec<(usize, &StyleTemplateElement)> {
+  let mut elements: Vec<_> = template.elements.iter().enumerate().collect();
+  // Сортировка устойчивая: при равном z_index сохраняется порядок в шаблоне
+  elements.sort_by_key(|(_, element)| element_z_index(element));
+  elements
+}
+
+/// Может ли элемент быть отрисован в графе фильтров
+fn is_renderable(element: &StyleTemplateElement) -> bool {
+  element.element_type != StyleElementType::Particles
+}
+
+/// Холст с прозрачным фоном размера элемента
+fn transparent_canvas(element: &StyleTemplateElement) -> String {
+  format!(
+    "color=c=black@0.0:s={}x{},format=rgba",
+    element.size.width, element.size.height
+  )
+}
+
+/// Прозрачность, поворот, масштаб и размытие элемента.
+///
+/// Возвращает `None`, если свойства не меняют изображение.
+fn build_element_transform(
+  element: &StyleTemplateElement,
+  input: &str,
+  index: usize,
+) -> Option<String> {
+  let properties = &element.properties;
+  let mut steps = Vec::new();
+
+  if let Some(scale) = properties.scale.filter(|s| *s > 0.0 && *s != 1.0) {
+    steps.push(format!("scale=iw*{scale}:ih*{scale}"));
+  }
+  if let Some(rotation) = properties.rotation.filter(|r| *r % 360.0 != 0.0) {
+    let angle = format!("{rotation}*PI/180");
+    steps.push(format!(
+      "rotate={angle}:c=none:ow=rotw({angle}):oh=roth({angle})"
+    ));
+  }
+  if let Some(blur) = properties.blur.filter(|b| *b > 0.0) {
+    steps.push(format!("boxblur={blur}"));
+  }
+  if let Some(opacity) = properties.opacity.filter(|o| *o < 1.0) {
+    steps.push(format!("colorchannelmixer=aa={}", opacity.max(0.0)));
+  }
+
+  if steps.is_empty() {
+    return None;
+  }
+  Some(format!("{input}format=rgba,{}[xf{index}]", steps.join(",")))
+}
+
+/// Ограничение показа элемента его временным окном
+fn overlay_enable(element: &StyleTemplateElement) -> String {
+  let timing = &element.timing;
+  if timing.out_time > timing.in_time {
+    format!(
+      ":enable='between(t,{},{})'",
+      timing.in_time, timing.out_time
+    )
+  } else {
+    String::new()
+  }
+}
+
+/// Свойства элемента, которые не удается воспроизвести при рендеринге
+fn element_limitations(element: &StyleTemplateElement) -> Vec<&'static str> {
+  let mut limitations = Vec::new();
+  let properties = &element.properties;
+
+  if element.element_type == StyleElementType::Particles {
+    limitations.push("частицы не поддерживаются, элемент пропущен");
+    return limitations;
+  }
+  if element.element_type == StyleElementType::Shape
+    && !properties.custom.contains_key(GRADIENT_KEY)
+  {
+    if let Some(ShapeType::Triangle | ShapeType::Star | ShapeType::Polygon | ShapeType::Arrow) =
+      properties.shape_type
+    {
+      limitations.push("форма рисуется прямоугольником");
+    }
+    if properties.border_radius.is_some_and(|r| r > 0.0) {
+      limitations.push("скругление углов не применяется");
+    }
+  }
+  if properties.shadow.is_some() {
+    limitations.push("тень не применяется");
+  }
+  if properties.stroke_width.is_some_and(|w| w > 0.0)
+    && element.element_type != StyleElementType::Line
+  {
+    limitations.push("обводка не применяется");
+  }
+  limitations
+}
+
+/// Предупреждения о неподдерживаемых элементах стильных шаблонов клипов проекта
+pub fn style_template_warnings(project: &ProjectSchema) -> Vec<String> {
+  let used: HashSet<&str> = project
+    .tracks
+    .iter()
+    .filter(|track| track.enabled)
+    .flat_map(|track| &track.clips)
+    .filter_map(|clip| clip.template_id.as_deref())
+    .collect();
+
+  project
+    .style_templates
+    .iter()
+    .filter(|template| used.contains(template.id.as_str()))
+    .flat_map(|template| {
+      template.elements.iter().flat_map(move |element| {
+        element_limitations(element)
+          .into_iter()
+          .map(move |limitation| {
+            format!(
+              "Шаблон '{}', элемент '{}': {}",
+              template.name, element.name, limitation
+            )
+          })
+      })
+    })
+    .collect()
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -1236,6 +1372,157 @@ mod tests {
     assert!(filter.contains("overlay"));
   }
 
+  fn lower_third_element(
+    id: &str,
+    element_type: StyleElementType,
+    properties: crate::video_compiler::schema::templates::StyleElementProperties,
+    style: Option<crate::video_compiler::schema::templates::ElementStyle>,
+  ) -> StyleTemplateElement {
+    use crate::video_compiler::schema::common::{Position2D, Size2D};
+    use crate::video_compiler::schema::templates::ElementTiming;
+
+    StyleTemplateElement {
+      id: id.to_string(),
+      element_type,
+      name: id.to_string(),
+      position: Position2D { x: 100.0, y: 900.0 },
+      size: Size2D {
+        width: 600.0,
+        height: 80.0,
+      },
+      timing: ElementTiming {
+        in_time: 0.5,
+        out_time: 4.5,
+        duration: 4.0,
+      },
+      properties,
+      animations: vec![],
+      content: id.to_string(),
+      style,
+    }
+  }
+
+  fn lower_third_template() -> StyleTemplate {
+    use crate::video_compiler::schema::templates::{
+      ElementStyle, StyleElementProperties, StyleTemplateCategory, StyleTemplateStyle,
+    };
+
+    let mut template = StyleTemplate::new(
+      "Lower Third".to_string(),
+      StyleTemplateCategory::LowerThird,
+      StyleTemplateStyle::Modern,
+      5.0,
+    );
+    template.id = "lower_third".to_string();
+
+    let mut divider = StyleElementProperties {
+      stroke_color: Some("#FFCC00".to_string()),
+      stroke_width: Some(4.0),
+      ..Default::default()
+    };
+    // Разделитель задан первым, но должен лечь поверх остальных элементов
+    divider
+      .custom
+      .insert(Z_INDEX_KEY.to_string(), serde_json::json!(10));
+
+    let mut gradient = StyleElementProperties::default();
+    gradient.custom.insert(
+      GRADIENT_KEY.to_string(),
+      serde_json::json!({"from": "#102030", "to": "#405060", "direction": "vertical"}),
+    );
+
+    template.elements = vec![
+      lower_third_element("Divider", StyleElementType::Line, divider, None),
+      lower_third_element("Backdrop", StyleElementType::Shape, gradient, None),
+      lower_third_element(
+        "Name",
+        StyleElementType::Text,
+        StyleElementProperties {
+          opacity: Some(0.5),
+          rotation: Some(15.0),
+          scale: Some(1.5),
+          ..Default::default()
+        },
+        Some(ElementStyle {
+          font_family: Some("Arial".to_string()),
+          font_size: Some(36),
+          color: Some("#FFFFFF".to_string()),
+          background_color: Some("#000000".to_string()),
+        }),
+      ),
+      lower_third_element(
+        "Badge",
+        StyleElementType::Icon,
+        StyleElementProperties::default(),
+        None,
+      ),
+      lower_third_element(
+        "Sparkles",
+        StyleElementType::Particles,
+        StyleElementProperties::default(),
+        None,
+      ),
+    ];
+    template
+  }
+
+  #[tokio::test]
+  async fn test_style_template_renders_every_element_in_z_order() {
+    let project = create_minimal_project();
+    let builder = TemplateBuilder::new(&project);
+
+    let filter = builder
+      .build_style_template_filter(&lower_third_template(), 0, 0)
+      .await
+      .unwrap();
+
+    // Каждый поддерживаемый элемент присутствует в графе
+    assert!(filter.contains("color=c=FFCC00:s=600x80[line0]"));
+    assert!(filter.contains("gradients=s=600x80:c0=0x102030:c1=0x405060"));
+    assert!(filter.contains("y1=80"));
+    assert!(filter.contains("drawtext=text='Name'"));
+    assert!(filter.contains(":box=1:boxcolor=000000"));
+    assert!(filter.contains("drawtext=text='Badge'"));
+    for idx in 0..4 {
+      assert!(filter.contains(&format!("[animated{idx}]overlay=")));
+    }
+    // Частицы не отрисовываются
+    assert!(!filter.contains("[animated4]"));
+
+    // Свойства текста больше не игнорируются
+    assert!(filter.contains("[text2]format=rgba,scale=iw*1.5:ih*1.5,rotate=15*PI/180"));
+    assert!(filter.contains("colorchannelmixer=aa=0.5[xf2]"));
+    assert!(filter.contains(":enable='between(t,0.5,4.5)'"));
+
+    // Разделитель с наибольшим z_index накладывается последним
+    let last_overlay = filter.rsplit(';').next().unwrap();
+    assert!(last_overlay.contains("[animated0]overlay="));
+    assert!(last_overlay.ends_with("[style_output]"));
+    let backdrop = filter.find("[animated1]overlay=").unwrap();
+    let name = filter.find("[animated2]overlay=").unwrap();
+    assert!(backdrop < name);
+  }
+
+  #[test]
+  fn test_style_template_warnings_for_used_templates() {
+    use crate::video_compiler::schema::{Clip, Track, TrackType};
+
+    let mut project = create_minimal_project();
+    project.style_templates.push(lower_third_template());
+    assert!(style_template_warnings(&project).is_empty());
+
+    let mut track = Track::new(TrackType::Video, "Titles".to_string());
+    let mut clip = Clip::new(std::path::PathBuf::from("/tmp/title.mp4"), 0.0, 5.0);
+    clip.template_id = Some("lower_third".to_string());
+    track.clips.push(clip);
+    project.tracks.push(track);
+
+    let warnings = style_template_warnings(&project);
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("Sparkles"));
+    assert!(warnings[0].contains("частицы"));
+  }
+
   #[tokio::test]
   async fn test_style_template_with_all_element_types() {
     let project = create_minimal_project();
@@ -70,4 +70,498 @@ mod builder_tests {
 
     assert!(result.is_ok(), "Preview command generation should succeed");
   }
+
+  fn project_with_subtitles(
+    mode: crate::video_compiler::schema::SubtitleMode,
+    format: crate::video_compiler::schema::OutputFormat,
+  ) -> crate::video_compiler::schema::ProjectSchema {
+    let mut project = create_project_with_clips();
+    project
+      .subtitles
+      .push(crate::video_compiler::schema::Subtitle::new(
+        "Hello".to_string(),
+        0.5,
+        2.0,
+      ));
+    project.settings.export.subtitle_mode = mode;
+    project.settings.output.format = format;
+    project
+  }
+
+  async fn render_args(
+    project: crate::video_compiler::schema::ProjectSchema,
+  ) -> crate::video_compiler::error::Result<Vec<String>> {
+    let builder = FFmpegBuilder::new(project)
+      .with_soft_subtitles(std::path::PathBuf::from("/tmp/pipeline/subtitles.srt"));
+    let cmd = builder
+      .build_render_command(std::path::Path::new("/tmp/output"))
+      .await?;
+    Ok(
+      cmd
+        .as_std()
+        .get_args()
+        .map(|arg| arg.to_string_lossy().to_string())
+        .collect(),
+    )
+  }
+
+  fn arg_after<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args
+      .iter()
+      .position(|arg| arg == flag)
+      .and_then(|idx| args.get(idx + 1))
+      .map(String::as_str)
+  }
+
+  #[tokio::test]
+  async fn test_subtitle_mode_burn() {
+    use crate::video_compiler::schema::{OutputFormat, SubtitleMode};
+
+    let args = render_args(project_with_subtitles(
+      SubtitleMode::Burn,
+      OutputFormat::Mp4,
+    ))
+    .await
+    .unwrap();
+
+    assert!(args.iter().any(|arg| arg.contains("drawtext")));
+    assert!(!args.contains(&"-c:s".to_string()));
+    assert!(!args.contains(&"/tmp/pipeline/subtitles.srt".to_string()));
+  }
+
+  #[tokio::test]
+  async fn test_subtitle_mode_soft_mp4() {
+    use crate::video_compiler::schema::{OutputFormat, SubtitleMode};
+
+    let mut project = project_with_subtitles(SubtitleMode::Soft, OutputFormat::Mp4);
+    project.settings.export.subtitle_language = Some("rus".to_string());
+    let args = render_args(project).await.unwrap();
+
+    assert!(!args.iter().any(|arg| arg.contains("drawtext")));
+    assert!(args.contains(&"[outv]".to_string()));
+    assert_eq!(arg_after(&args, "-c:s"), Some("mov_text"));
+    assert_eq!(arg_after(&args, "-metadata:s:s:0"), Some("language=rus"));
+
+    // Субтитры - последний вход, маппинг указывает на него
+    let input_count = args.iter().filter(|arg| *arg == "-i").count();
+    assert_eq!(
+      args
+        .iter()
+        .rposition(|arg| arg == "-i")
+        .map(|idx| &args[idx + 1]),
+      Some(&"/tmp/pipeline/subtitles.srt".to_string())
+    );
+    assert!(args.contains(&format!("{}:s", input_count - 1)));
+  }
+
+  #[tokio::test]
+  async fn test_subtitle_mode_both_mkv() {
+    use crate::video_compiler::schema::{OutputFormat, SubtitleMode};
+
+    let args = render_args(project_with_subtitles(
+      SubtitleMode::Both,
+      OutputFormat::Mkv,
+    ))
+    .await
+    .unwrap();
+
+    assert!(args.iter().any(|arg| arg.contains("drawtext")));
+    assert!(args.contains(&"[outv_with_subs]".to_string()));
+    assert_eq!(arg_after(&args, "-c:s"), Some("srt"));
+    assert_eq!(arg_after(&args, "-metadata:s:s:0"), Some("language=und"));
+  }
+
+  #[tokio::test]
+  async fn test_subtitle_mode_soft_webm_rejected() {
+    use crate::video_compiler::error::VideoCompilerError;
+    use crate::video_compiler::schema::{OutputFormat, SubtitleMode};
+
+    let result = render_args(project_with_subtitles(
+      SubtitleMode::Soft,
+      OutputFormat::WebM,
+    ))
+    .await;
+
+    match result {
+      Err(VideoCompilerError::ValidationError(message)) => assert!(message.contains("WebM")),
+      other => panic!("WebM soft subtitles must be rejected, got {other:?}"),
+    }
+  }
+
+  #[tokio::test]
+  async fn test_subtitle_mode_soft_requires_prepared_file() {
+    use crate::video_compiler::schema::{OutputFormat, SubtitleMode};
+
+    let builder = FFmpegBuilder::new(project_with_subtitles(
+      SubtitleMode::Soft,
+      OutputFormat::Mp4,
+    ));
+    let result = builder
+      .build_render_command(std::path::Path::new("/tmp/output.mp4"))
+      .await;
+
+    assert!(result.is_err());
+  }
+
+  fn project_with_renditions(generate_hls: bool) -> crate::video_compiler::schema::ProjectSchema {
+    use crate::video_compiler::schema::{RenditionSpec, Resolution};
+
+    let mut project = create_project_with_clips();
+    project.settings.export.renditions = vec![
+      RenditionSpec {
+        resolution: Resolution::full_hd(),
+        video_bitrate: 6000,
+        suffix: "_1080p".to_string(),
+      },
+      RenditionSpec {
+        resolution: Resolution::hd(),
+        video_bitrate: 3000,
+        suffix: "_720p".to_string(),
+      },
+    ];
+    project.settings.export.generate_hls = generate_hls;
+    project
+  }
+
+  #[tokio::test]
+  async fn test_renditions_single_command() {
+    let args = render_args(project_with_renditions(false)).await.unwrap();
+
+    // Граф фильтров один, финальный поток размножается под каждый вариант
+    assert_eq!(
+      args.iter().filter(|arg| *arg == "-filter_complex").count(),
+      1
+    );
+    let filter_complex = arg_after(&args, "-filter_complex").unwrap();
+    assert!(filter_complex.contains("[outv]split=2[rv0][rv1]"));
+    assert!(filter_complex.contains("[rv1]scale=1280:720[rv1out]"));
+
+    let first_output = args
+      .iter()
+      .position(|arg| arg == "/tmp/output_1080p")
+      .unwrap();
+    let second_output = args
+      .iter()
+      .position(|arg| arg == "/tmp/output_720p")
+      .unwrap();
+    assert!(first_output < second_output);
+
+    // Битрейт и разрешение задаются для каждого выхода отдельно
+    let second_args = &args[first_output..second_output];
+    assert!(second_args.contains(&"[rv1out]".to_string()));
+    assert_eq!(arg_after(second_args, "-b:v"), Some("3000k"));
+    assert_eq!(arg_after(second_args, "-s"), Some("1280x720"));
+  }
+
+  #[tokio::test]
+  async fn test_renditions_hls_outputs() {
+    let args = render_args(project_with_renditions(true)).await.unwrap();
+
+    assert_eq!(args.iter().filter(|arg| *arg == "hls").count(), 2);
+    assert!(args.contains(&"/tmp/output_1080p.m3u8".to_string()));
+    assert!(args.contains(&"/tmp/output_720p_%03d.ts".to_string()));
+  }
+
+  #[test]
+  fn test_hls_master_playlist() {
+    use super::super::outputs::{build_hls_master_playlist, hls_master_playlist_path};
+
+    let output_path = std::path::Path::new("/tmp/show.mp4");
+    let playlist = build_hls_master_playlist(&project_with_renditions(true), output_path);
+
+    assert_eq!(
+      hls_master_playlist_path(output_path),
+      std::path::PathBuf::from("/tmp/show.m3u8")
+    );
+    assert!(playlist.starts_with("#EXTM3U"));
+    assert!(playlist.contains("BANDWIDTH=6192000,RESOLUTION=1920x1080\nshow_1080p.m3u8"));
+    assert!(playlist.contains("RESOLUTION=1280x720\nshow_720p.m3u8"));
+  }
+
+  fn audio_only_project(
+    format: crate::video_compiler::schema::OutputFormat,
+  ) -> crate::video_compiler::schema::ProjectSchema {
+    use crate::video_compiler::schema::{Clip, Track, TrackType};
+
+    let mut project = create_project_with_clips();
+    let mut audio_track = Track::new(TrackType::Audio, "Voice".to_string());
+    audio_track.clips.push(Clip::new(
+      std::path::PathBuf::from("/tmp/voice.wav"),
+      0.0,
+      5.0,
+    ));
+    project.tracks.push(audio_track);
+    project.settings.output.format = format;
+    project
+  }
+
+  async fn assert_audio_only_command(
+    project: crate::video_compiler::schema::ProjectSchema,
+  ) -> Vec<String> {
+    let args = render_args(project).await.unwrap();
+
+    assert!(!args.contains(&"-c:v".to_string()));
+    assert!(!args.contains(&"-s".to_string()));
+    assert!(!args.contains(&"-r".to_string()));
+    assert!(!args.contains(&"[outv]".to_string()));
+    assert!(args.contains(&"[outa]".to_string()));
+    assert!(args.contains(&"-vn".to_string()));
+
+    // Входы видео треков не добавляются
+    assert_eq!(args.iter().filter(|arg| *arg == "-i").count(), 1);
+    args
+  }
+
+  #[tokio::test]
+  async fn test_audio_only_mp3() {
+    use crate::video_compiler::schema::OutputFormat;
+
+    let mut project = audio_only_project(OutputFormat::Mp3);
+    project.settings.output.quality = 100;
+    let args = assert_audio_only_command(project).await;
+
+    assert_eq!(arg_after(&args, "-c:a"), Some("libmp3lame"));
+    assert_eq!(arg_after(&args, "-q:a"), Some("0"));
+    assert_eq!(arg_after(&args, "-f"), Some("mp3"));
+    assert!(!args.contains(&"-b:a".to_string()));
+  }
+
+  #[tokio::test]
+  async fn test_audio_only_wav_bit_depth() {
+    use crate::video_compiler::schema::OutputFormat;
+
+    let args = assert_audio_only_command(audio_only_project(OutputFormat::Wav)).await;
+    assert_eq!(arg_after(&args, "-c:a"), Some("pcm_s16le"));
+
+    let mut project = audio_only_project(OutputFormat::Wav);
+    project.settings.export.audio_bit_depth = Some(24);
+    let args = assert_audio_only_command(project).await;
+    assert_eq!(arg_after(&args, "-c:a"), Some("pcm_s24le"));
+  }
+
+  #[tokio::test]
+  async fn test_audio_only_flac_ignores_video_settings() {
+    use crate::video_compiler::schema::{OutputFormat, Resolution};
+
+    let mut project = audio_only_project(OutputFormat::Flac);
+    project.settings.resolution = Resolution::uhd_4k();
+    project.settings.frame_rate = 0.0;
+    let args = assert_audio_only_command(project).await;
+
+    assert_eq!(arg_after(&args, "-c:a"), Some("flac"));
+    assert_eq!(arg_after(&args, "-sample_fmt"), Some("s16"));
+  }
+
+  /// Аргументы рендера в промежуточный кодек с включенным аппаратным ускорением,
+  /// битрейтом и CRF, которые должны игнорироваться
+  async fn intermediate_args(format: crate::video_compiler::schema::OutputFormat) -> Vec<String> {
+    let mut project = create_project_with_clips();
+    project.settings.output.format = format;
+    project.settings.output.video_bitrate = Some(8000);
+    project.settings.export.crf = Some(18);
+    let settings = FFmpegBuilderSettings {
+      use_hardware_acceleration: true,
+      hardware_acceleration_type: Some("nvenc".to_string()),
+      ..Default::default()
+    };
+
+    let cmd = FFmpegBuilder::with_settings(project, settings)
+      .build_render_command(std::path::Path::new("/tmp/output"))
+      .await
+      .unwrap();
+    let args: Vec<String> = cmd
+      .as_std()
+      .get_args()
+      .map(|arg| arg.to_string_lossy().to_string())
+      .collect();
+
+    for ignored in ["-b:v", "-crf", "-maxrate", "-bufsize", "-b:a"] {
+      assert!(
+        !args.contains(&ignored.to_string()),
+        "{ignored} in {args:?}"
+      );
+    }
+    assert!(!args.iter().any(|arg| arg.contains("nvenc")));
+    args
+  }
+
+  #[tokio::test]
+  async fn test_prores_profiles() {
+    use crate::video_compiler::schema::{OutputFormat, ProResProfile};
+
+    let cases = [
+      (ProResProfile::Proxy, "0", "yuv422p10le"),
+      (ProResProfile::Lt, "1", "yuv422p10le"),
+      (ProResProfile::Standard, "2", "yuv422p10le"),
+      (ProResProfile::Hq, "3", "yuv422p10le"),
+      (ProResProfile::ProRes4444, "4", "yuva444p10le"),
+    ];
+    for (profile, ffmpeg_profile, pix_fmt) in cases {
+      let args = intermediate_args(OutputFormat::MovProRes(profile)).await;
+
+      assert_eq!(arg_after(&args, "-c:v"), Some("prores_ks"));
+      assert_eq!(arg_after(&args, "-profile:v"), Some(ffmpeg_profile));
+      assert_eq!(arg_after(&args, "-pix_fmt"), Some(pix_fmt));
+      assert_eq!(arg_after(&args, "-f"), Some("mov"));
+      assert_eq!(arg_after(&args, "-c:a"), Some("pcm_s16le"));
+    }
+  }
+
+  #[tokio::test]
+  async fn test_dnxhr_profiles_force_pcm_in_mxf() {
+    use crate::video_compiler::schema::{DnxhrProfile, OutputFormat};
+
+    let cases = [
+      (DnxhrProfile::Lb, "dnxhr_lb", "yuv422p"),
+      (DnxhrProfile::Sq, "dnxhr_sq", "yuv422p"),
+      (DnxhrProfile::Hq, "dnxhr_hq", "yuv422p"),
+      (DnxhrProfile::Hqx, "dnxhr_hqx", "yuv422p10le"),
+    ];
+    for (profile, ffmpeg_profile, pix_fmt) in cases {
+      let args = intermediate_args(OutputFormat::MxfDnxhr(profile)).await;
+
+      assert_eq!(arg_after(&args, "-c:v"), Some("dnxhd"));
+      assert_eq!(arg_after(&args, "-profile:v"), Some(ffmpeg_profile));
+      assert_eq!(arg_after(&args, "-pix_fmt"), Some(pix_fmt));
+      assert_eq!(arg_after(&args, "-f"), Some("mxf"));
+      assert_eq!(arg_after(&args, "-c:a"), Some("pcm_s16le"));
+    }
+  }
+
+  #[test]
+  fn test_intermediate_codec_warnings() {
+    use super::super::outputs::intermediate_codec_warnings;
+    use crate::video_compiler::schema::{OutputFormat, ProResProfile};
+
+    let mut project = create_minimal_project();
+    project.settings.export.hardware_acceleration = false;
+    assert!(intermediate_codec_warnings(&project).is_empty());
+
+    project.settings.output.format = OutputFormat::MovProRes(ProResProfile::Hq);
+    assert_eq!(intermediate_codec_warnings(&project).len(), 1);
+
+    project.settings.export.crf = Some(18);
+    project.settings.export.hardware_acceleration = true;
+    let warnings = intermediate_codec_warnings(&project);
+    assert_eq!(warnings.len(), 3);
+    assert!(warnings[0].contains("ProRes Hq"));
+  }
+
+  async fn frame_args(
+    project: crate::video_compiler::schema::ProjectSchema,
+    timestamp: f64,
+  ) -> Vec<String> {
+    use crate::video_compiler::schema::PreviewFormat;
+
+    let cmd = FFmpegBuilder::new(project)
+      .build_frame_command(
+        timestamp,
+        std::path::Path::new("/tmp/frame.png"),
+        (640, 360),
+        &PreviewFormat::Png,
+        100,
+      )
+      .await
+      .unwrap();
+    cmd
+      .as_std()
+      .get_args()
+      .map(|arg| arg.to_string_lossy().to_string())
+      .collect()
+  }
+
+  #[tokio::test]
+  async fn test_frame_command_uses_only_intersecting_clips() {
+    use crate::video_compiler::schema::{Clip, OutputFormat, SubtitleMode, Track, TrackType};
+
+    let mut project = project_with_subtitles(SubtitleMode::Soft, OutputFormat::Mp4);
+    let mut overlay = Track::new(TrackType::Video, "Overlay".to_string());
+    overlay.hidden = true;
+    overlay.clips.push(Clip::new(
+      std::path::PathBuf::from("/tmp/overlay.mp4"),
+      1.0,
+      2.0,
+    ));
+    overlay.clips.push(Clip::new(
+      std::path::PathBuf::from("/tmp/late.mp4"),
+      4.0,
+      1.0,
+    ));
+    project.tracks.push(overlay);
+
+    let args = frame_args(project, 1.5).await;
+
+    // Основной клип и скрытый оверлей видны в момент 1.5, поздний клип - нет
+    assert_eq!(args.iter().filter(|arg| *arg == "-i").count(), 2);
+    assert!(args.contains(&"/tmp/overlay.mp4".to_string()));
+    assert!(!args.contains(&"/tmp/late.mp4".to_string()));
+    assert_eq!(arg_after(&args, "-ss"), Some("1.5"));
+
+    let filter_complex = arg_after(&args, "-filter_complex").unwrap();
+    assert!(filter_complex.contains("scale=640:360"));
+    assert!(args.contains(&"[outv_with_subs]".to_string()));
+    assert!(!args.contains(&"[outa]".to_string()));
+
+    assert_eq!(arg_after(&args, "-frames:v"), Some("1"));
+    assert_eq!(arg_after(&args, "-c:v"), Some("png"));
+    assert_eq!(arg_after(&args, "-q:v"), Some("2"));
+    assert_eq!(args.last().map(String::as_str), Some("/tmp/frame.png"));
+  }
+
+  #[tokio::test]
+  async fn test_frame_command_without_visible_clips() {
+    let args = frame_args(create_project_with_clips(), 7.0).await;
+
+    assert_eq!(arg_after(&args, "-f"), Some("lavfi"));
+    assert_eq!(arg_after(&args, "-i"), Some("color=c=black:s=640x360"));
+    assert!(!args.contains(&"-filter_complex".to_string()));
+  }
+
+  #[tokio::test]
+  async fn test_concat_command_copies_streams() {
+    let builder = FFmpegBuilder::new(create_minimal_project());
+    let cmd = builder.build_concat_command(
+      std::path::Path::new("/tmp/segments.txt"),
+      std::path::Path::new("/tmp/output.mp4"),
+    );
+    let args: Vec<String> = cmd
+      .as_std()
+      .get_args()
+      .map(|arg| arg.to_string_lossy().to_string())
+      .collect();
+
+    assert_eq!(arg_after(&args, "-f"), Some("concat"));
+    assert_eq!(arg_after(&args, "-i"), Some("/tmp/segments.txt"));
+    assert_eq!(arg_after(&args, "-c"), Some("copy"));
+    assert_eq!(args.last().map(String::as_str), Some("/tmp/output.mp4"));
+  }
+
+  #[tokio::test]
+  async fn test_media_trim_command() {
+    let builder = FFmpegBuilder::new(create_minimal_project());
+    let args_for = |stream_copy| -> Vec<String> {
+      builder
+        .build_media_trim_command(
+          std::path::Path::new("/media/source.mov"),
+          12.5,
+          30.0,
+          std::path::Path::new("/tmp/source.mov"),
+          stream_copy,
+        )
+        .as_std()
+        .get_args()
+        .map(|arg| arg.to_string_lossy().to_string())
+        .collect()
+    };
+
+    let copy = args_for(true);
+    assert_eq!(arg_after(&copy, "-ss"), Some("12.500"));
+    assert_eq!(arg_after(&copy, "-t"), Some("30.000"));
+    assert_eq!(arg_after(&copy, "-c"), Some("copy"));
+    assert_eq!(copy.last().map(String::as_str), Some("/tmp/source.mov"));
+
+    let reencode = args_for(false);
+    assert!(!reencode.contains(&"copy".to_string()));
+    assert_eq!(arg_after(&reencode, "-crf"), Some("18"));
+  }
 }
@@ -247,6 +247,8 @@ impl FFmpegExecutor {
           )),
           status: crate::video_compiler::progress::RenderStatus::Processing,
           message: Some(format!("Обработка: кадр {frame} @ {fps:.1} fps")),
+          renditions: Vec::new(),
+          stages: Vec::new(),
         });
       }
     }
@@ -261,12 +263,14 @@ pub async fn check_ffmpeg_available(ffmpeg_path: &str) -> Result<String> {
   cmd.arg("-version");
 
   let output = cmd.output().await.map_err(|e| {
-    VideoCompilerError::DependencyMissing(format!("FFmpeg не найден по пути '{ffmpeg_path}': {e}"))
+    VideoCompilerError::DependencyMissing(
+      format!("FFmpeg не найден по пути '{ffmpeg_path}': {e}").into(),
+    )
   })?;
 
   if !output.status.success() {
     return Err(VideoCompilerError::DependencyMissing(
-      "FFmpeg найден, но не может быть запущен".to_string(),
+      "FFmpeg найден, но не может быть запущен".into(),
     ));
   }
 
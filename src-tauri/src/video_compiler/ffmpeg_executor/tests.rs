@@ -192,6 +192,8 @@ async fn test_parse_progress_line() {
     estimated_remaining: Some(Duration::from_secs(41)),
     status: RenderStatus::Processing,
     message: Some("Processing frame 1234".to_string()),
+    renditions: Vec::new(),
+    stages: Vec::new(),
   };
 
   // Отправляем тестовое обновление
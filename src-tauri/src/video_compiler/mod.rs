@@ -15,6 +15,7 @@ pub mod ffmpeg_executor;
 pub mod registry;
 pub mod schema;
 pub mod services;
+pub mod test_media;
 
 #[cfg(test)]
 pub mod tests;
@@ -25,13 +26,16 @@ pub use core::error::{Result, VideoCompilerError};
 pub use core::progress::RenderProgress;
 
 // Re-export core modules that are used by other parts of the application
-pub use core::{cache, error, frame_extraction, gpu, pipeline, preview, progress, renderer};
+pub use core::{
+  cache, error, ffmpeg_manager, frame_extraction, gpu, pipeline, preview, progress, renderer,
+};
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+use crate::language_tauri::i18n::LocalizedMessage;
 use crate::video_compiler::cache::RenderCache;
 use crate::video_compiler::services::ServiceContainer;
 
@@ -50,6 +54,12 @@ pub struct CompilerSettings {
   pub hardware_acceleration: bool,
   /// Качество превью (от 1 до 100)
   pub preview_quality: u8,
+  /// Тайм-ауты и повторы операций сервисов
+  #[serde(default)]
+  pub service_policies: services::monitoring::ServicePolicySettings,
+  /// Адреса сборок FFmpeg для скачивания встроенного бинарника
+  #[serde(default = "ffmpeg_manager::default_download_sources")]
+  pub ffmpeg_download_sources: Vec<ffmpeg_manager::FfmpegDownloadSource>,
 }
 
 impl Default for CompilerSettings {
@@ -57,10 +67,12 @@ impl Default for CompilerSettings {
     Self {
       max_concurrent_jobs: 2,
       cache_size_mb: 512,
-      temp_directory: std::env::temp_dir().join("timeline-studio"),
+      temp_directory: core::temp_storage::default_temp_root(),
       ffmpeg_path: None,
       hardware_acceleration: true,
       preview_quality: 75,
+      service_policies: services::monitoring::ServicePolicySettings::default(),
+      ffmpeg_download_sources: ffmpeg_manager::default_download_sources(),
     }
   }
 }
@@ -71,6 +83,11 @@ impl Default for CompilerSettings {
 pub enum VideoCompilerEvent {
   /// Рендеринг начат
   RenderStarted { job_id: String },
+  /// Рендеринг запланирован на отложенный запуск
+  RenderScheduled {
+    job_id: String,
+    schedule: services::render_schedule::RenderSchedule,
+  },
   /// Прогресс рендеринга обновлен
   RenderProgress {
     job_id: String,
@@ -80,14 +97,57 @@ pub enum VideoCompilerEvent {
   RenderCompleted { job_id: String, output_path: String },
   /// Рендеринг завершился с ошибкой
   RenderFailed { job_id: String, error: String },
-  /// Превью сгенерировано
-  PreviewGenerated { timestamp: f64, image_data: Vec<u8> },
+  /// Предупреждение о задаче рендеринга, не прерывающее ее
+  RenderWarning { job_id: String, message: String },
+  /// Превью сгенерировано и сохранено в кэш (байты кадра не передаются)
+  PreviewGenerated {
+    file_id: String,
+    timestamp: f64,
+    cache_path: String,
+  },
   /// Кэш обновлен
   CacheUpdated { cache_size_mb: f64 },
+  /// Прогресс поиска перемещенных медиафайлов
+  RelinkScanProgress {
+    progress: services::media_relink::RelinkScanProgress,
+  },
+  /// Прогресс упаковки или распаковки архива проекта
+  ProjectPackageProgress {
+    package_id: String,
+    progress: services::project_package::PackageProgress,
+  },
+  /// Прогресс пакетного экспорта
+  BatchExportProgress {
+    progress: services::batch_export::BatchExportProgress,
+  },
+  /// Пакетный экспорт завершен
+  BatchExportCompleted {
+    summary: services::batch_export::BatchExportSummary,
+  },
+  /// Прогресс этапа Quick Edit
+  QuickEditProgress {
+    progress: services::quick_edit::QuickEditProgress,
+  },
+  /// Quick Edit завершен
+  QuickEditCompleted {
+    result: Box<services::quick_edit::QuickEditResult>,
+  },
 }
 
 /// Проверка зависимостей Video Compiler и возврат пути к FFmpeg
 pub async fn check_dependencies() -> Result<String> {
+  // Ранее скачанная сборка имеет приоритет над системной
+  if let Some(bundled) = ffmpeg_manager::FfmpegManager::new().installed_binary() {
+    if let Some(version) = ffmpeg_manager::probe_version(&bundled).await {
+      log::info!(
+        "Используется встроенный FFmpeg: {} ({version})",
+        bundled.display()
+      );
+      return Ok(bundled.to_string_lossy().to_string());
+    }
+    log::warn!("Встроенный FFmpeg не запускается: {}", bundled.display());
+  }
+
   // Список возможных путей к FFmpeg в разных системах
   let ffmpeg_paths = vec![
     "ffmpeg",                                     // По умолчанию в PATH
@@ -145,26 +205,46 @@ pub async fn check_dependencies() -> Result<String> {
     }
   }
 
-  Err(VideoCompilerError::DependencyMissing(
-    "FFmpeg не найден в системе. Установите FFmpeg для работы Video Compiler.\n\
-     Инструкции по установке:\n\
-     - macOS: brew install ffmpeg\n\
-     - Ubuntu/Debian: sudo apt install ffmpeg\n\
-     - Windows: скачайте с https://ffmpeg.org/download.html"
-      .to_string(),
-  ))
+  Err(VideoCompilerError::DependencyMissing(LocalizedMessage::new("ffmpeg.not_found", &[])))
+}
+
+/// Параметры инициализации Video Compiler
+#[derive(Debug, Clone, Default)]
+pub struct InitOptions {
+  /// Явный путь к FFmpeg вместо поиска в системе
+  pub ffmpeg_path: Option<std::path::PathBuf>,
+  /// Не определять GPU при запуске (безопасный режим)
+  pub skip_gpu_detection: bool,
 }
 
 /// Инициализация Video Compiler модуля
 pub async fn initialize() -> Result<VideoCompilerState> {
+  initialize_with(InitOptions::default()).await
+}
+
+/// Инициализация Video Compiler модуля с заданными параметрами
+pub async fn initialize_with(options: InitOptions) -> Result<VideoCompilerState> {
   log::info!("Инициализация Video Compiler модуля");
 
   // Проверяем зависимости и получаем путь к FFmpeg
-  let ffmpeg_path = check_dependencies().await?;
+  let ffmpeg_path = match &options.ffmpeg_path {
+    Some(path) => {
+      if ffmpeg_manager::probe_version(path).await.is_none() {
+        return Err(VideoCompilerError::DependencyMissing(LocalizedMessage::new(
+          "ffmpeg.not_runnable",
+          &[("path", path.display().to_string())],
+        )));
+      }
+      path.to_string_lossy().to_string()
+    }
+    None => check_dependencies().await?,
+  };
   log::info!("FFmpeg найден по пути: {ffmpeg_path}");
 
-  // Создаем временную директорию если не существует
-  let temp_dir = std::env::temp_dir().join("timeline-studio");
+  // Временная директория из сохраненных настроек, если она доступна
+  let configured_temp_dir = core::temp_storage::default_settings_path()
+    .and_then(|path| core::temp_storage::load_temp_directory(&path));
+  let temp_dir = core::temp_storage::resolve_temp_root(configured_temp_dir);
   if !temp_dir.exists() {
     tokio::fs::create_dir_all(&temp_dir)
       .await
@@ -172,13 +252,22 @@ pub async fn initialize() -> Result<VideoCompilerState> {
   }
 
   // Создаем контейнер сервисов с правильным путем к FFmpeg
-  let services = match ServiceContainer::new(
+  let mut builder = ServiceContainer::builder(
     ffmpeg_path.clone(),
     temp_dir.clone(),
     2, // max_concurrent_jobs
-  )
-  .await
-  {
+  );
+  // Запланированные рендеры переживают перезапуск приложения
+  if let Some(store) = services::render_schedule::ScheduledRenderStore::default_location() {
+    builder = builder.with_render_schedule_store(store);
+  }
+  if options.skip_gpu_detection {
+    log::info!("Определение GPU при запуске пропущено");
+    builder = builder.with_gpu(Arc::new(
+      services::GpuServiceImpl::new(ffmpeg_path.clone()).without_startup_detection(),
+    ));
+  }
+  let services = match builder.build().await {
     Ok(container) => container,
     Err(e) => {
       log::error!("Ошибка создания контейнера сервисов: {e:?}");
@@ -193,17 +282,44 @@ pub async fn initialize() -> Result<VideoCompilerState> {
 
   let services = Arc::new(services);
 
+  let settings = CompilerSettings {
+    temp_directory: temp_dir.clone(),
+    ..CompilerSettings::default()
+  };
+  services::monitoring::set_service_policies(settings.service_policies.clone());
+  core::temp_storage::set_temp_root(temp_dir.clone());
+
+  // Директории задач, оставшиеся после аварийного завершения
+  let cleanup_root = temp_dir.clone();
+  tokio::spawn(async move {
+    if let Err(e) = core::temp_storage::cleanup_orphaned_job_dirs(
+      &cleanup_root,
+      core::temp_storage::ORPHANED_JOB_MAX_AGE,
+    )
+    .await
+    {
+      log::warn!("Не удалось очистить брошенные директории задач: {e}");
+    }
+  });
+
+  // Превью сохраняются на диск, чтобы переживать перезапуск приложения
+  let preview_cache_dir = crate::app_dirs::AppDirectories::get_or_create()
+    .map(|dirs| dirs.get_preview_cache_dir())
+    .unwrap_or_else(|_| temp_dir.join("preview-cache"));
+  let cache_manager = RenderCache::with_disk_cache(
+    cache::CacheSettings::default(),
+    preview_cache_dir,
+    settings.cache_size_mb,
+  );
+
   // Создаем состояние
   let state = VideoCompilerState {
     services,
     active_jobs: Arc::new(RwLock::new(HashMap::new())),
     active_pipelines: Arc::new(RwLock::new(HashMap::new())),
-    cache_manager: Arc::new(RwLock::new(RenderCache::new())),
+    cache_manager: Arc::new(RwLock::new(cache_manager)),
     ffmpeg_path: Arc::new(RwLock::new(ffmpeg_path.clone())),
-    settings: Arc::new(RwLock::new(CompilerSettings {
-      temp_directory: temp_dir,
-      ..CompilerSettings::default()
-    })),
+    settings: Arc::new(RwLock::new(settings)),
   };
 
   log::info!("Video Compiler модуль успешно инициализирован с FFmpeg: {ffmpeg_path}");
@@ -17,6 +17,7 @@ impl CommandRegistry for VideoCompilerCommandRegistry {
       check_hardware_acceleration_support,
       detect_gpus,
       get_gpu_capabilities,
+      get_gpu_memory_info,
       get_recommended_gpu,
       set_preferred_gpu,
       set_hardware_acceleration,
@@ -27,6 +28,7 @@ impl CommandRegistry for VideoCompilerCommandRegistry {
       // Cache commands
       cache_media_metadata,
       clean_old_cache,
+      clean_stale_artifacts,
       cleanup_cache,
       clear_all_cache,
       clear_cache,
@@ -59,6 +61,13 @@ impl CommandRegistry for VideoCompilerCommandRegistry {
       get_prerendered_segments,
       delete_prerendered_segment,
       optimize_prerender_cache,
+      // Playback assist commands
+      set_playback_project,
+      update_playback_position,
+      get_playback_ready_ranges,
+      // Snap index commands
+      build_snap_index,
+      query_snap,
       // Rendering commands
       compile_video,
       cancel_render,
@@ -68,6 +77,10 @@ impl CommandRegistry for VideoCompilerCommandRegistry {
       build_segment_render_command,
       get_active_render_jobs,
       get_render_job,
+      list_scheduled_renders,
+      update_render_schedule,
+      get_render_report,
+      export_render_report_markdown,
       pause_render,
       resume_render,
       export_with_preset,
@@ -83,13 +96,53 @@ impl CommandRegistry for VideoCompilerCommandRegistry {
       get_clip_info,
       get_project_media_files,
       merge_projects,
+      migrate_project_schema,
       split_project,
       touch_project_schema,
       track_operations,
       validate_subtitle,
+      // Project storage commands
+      save_project_file,
+      load_project_file,
+      // Command preview commands
+      get_render_command_preview,
+      get_prerender_command_preview,
+      get_timeline_frame_command_preview,
+      // Project template commands
+      save_project_as_template,
+      list_project_templates,
+      create_project_from_template,
+      rename_project_template,
+      delete_project_template,
+      import_project_template,
+      // Effect preset commands
+      save_effect_preset,
+      list_effect_presets,
+      rename_effect_preset,
+      delete_effect_preset,
+      apply_effect_preset,
+      export_effect_preset,
+      import_effect_preset,
+      // Media relink commands
+      find_missing_media,
+      suggest_relink_candidates,
+      apply_media_relink,
+      // Project assets commands
+      check_project_assets,
+      substitute_project_asset,
+      // Project package commands
+      package_project,
+      unpack_project,
+      cancel_project_package,
+      // Render spec commands
+      export_render_job,
+      verify_render_spec,
+      run_render_spec,
       // Preview commands
       batch_generate_previews_service,
       generate_frame_preview,
+      render_timeline_frame,
+      chroma_key_preview,
       generate_video_thumbnails,
       generate_project_preview,
       generate_effect_preview,
@@ -109,6 +162,9 @@ impl CommandRegistry for VideoCompilerCommandRegistry {
       check_ffmpeg_available,
       check_ffmpeg_capabilities,
       check_ffmpeg_installation,
+      download_bundled_ffmpeg,
+      cancel_ffmpeg_download,
+      get_ffmpeg_status,
       get_ffmpeg_version,
       get_supported_formats,
       get_supported_video_codecs,
@@ -121,6 +177,19 @@ impl CommandRegistry for VideoCompilerCommandRegistry {
       get_media_file_info,
       // Misc commands - TODO: These will be moved to specialized modules
       add_clip_to_track,
+      update_clip,
+      move_clip,
+      create_sequence_from_clips,
+      flatten_sequence,
+      detect_timeline_gaps,
+      ripple_delete_gaps,
+      format_timeline_position,
+      set_clip_metadata,
+      find_clips,
+      update_clips,
+      apply_effect_to_clips,
+      copy_clip_attributes,
+      paste_clip_attributes,
       add_subtitles_to_project,
       concat_videos,
       create_clip,
@@ -183,6 +252,9 @@ impl CommandRegistry for VideoCompilerCommandRegistry {
       create_directory,
       create_timeline_project,
       create_workflow_preview,
+      // Quick edit commands
+      cancel_quick_edit_workflow,
+      run_quick_edit_workflow,
       // Platform optimization commands
       ffmpeg_analyze_platform_compliance,
       ffmpeg_batch_optimize_platforms,
@@ -242,6 +314,7 @@ impl CommandRegistry for VideoCompilerCommandRegistry {
       extract_timeline_frames,
       extract_video_frame,
       extract_video_frames_batch,
+      export_frame,
       generate_preview,
       generate_preview_batch,
       generate_preview_with_settings,
@@ -251,6 +324,8 @@ impl CommandRegistry for VideoCompilerCommandRegistry {
       extract_frames_for_clip_command,
       extract_frames_for_subtitles_command,
       get_frame_extraction_cache_info_command,
+      // Audio sync commands
+      probe_audio_offset,
       // Multimodal commands
       cleanup_extracted_frames,
       convert_image_to_base64,
@@ -266,6 +341,14 @@ impl CommandRegistry for VideoCompilerCommandRegistry {
       whisper_transcribe_local,
       whisper_transcribe_openai,
       whisper_translate_openai,
+      detect_audio_language,
+      whisper_transcribe_local_multilingual,
+      whisper_transcribe_openai_multilingual,
+      whisper_transcript_to_subtitles,
+      // Reframe commands
+      analyze_reframe,
+      apply_reframe,
+      reframe_clip_for_vertical,
       // Video analysis commands
       ffmpeg_analyze_audio,
       ffmpeg_analyze_motion,
@@ -295,6 +378,8 @@ impl CommandRegistry for VideoCompilerCommandRegistry {
       get_specific_service_metrics,
       get_services_health,
       restart_service,
+      get_service_policies,
+      update_service_policies,
       set_preview_ffmpeg_path,
       touch_project,
       // Service container commands
@@ -309,6 +394,7 @@ impl CommandRegistry for VideoCompilerCommandRegistry {
       set_parallel_jobs_advanced,
       set_memory_limit_advanced,
       set_temp_directory_advanced,
+      set_temp_directory,
       set_log_level_advanced,
       reset_compiler_settings_advanced,
       get_recommended_settings_advanced,
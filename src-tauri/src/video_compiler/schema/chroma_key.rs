@@ -0,0 +1,428 @@
+//! Chroma Key - Параметры хромакея и подстановка фона
+//!
+//! Параметры хранятся в `Effect::parameters` эффекта `EffectType::ChromaKey`:
+//! `key_color`, `similarity`, `blend`, `spill_suppression`, `key_mode` и один
+//! из источников фона - `background_color`, `background_image` или
+//! `background_clip_id`. Прежний параметр `color` читается как `key_color`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use super::effects::{Effect, EffectParameter, EffectType};
+use super::project::ProjectSchema;
+use super::timeline::ClipSource;
+
+/// Цвет ключа по умолчанию (зеленый фон)
+pub const DEFAULT_KEY_COLOR: &str = "0x00ff00";
+
+/// Способ выделения ключа
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChromaKeyMode {
+  /// По цветности в YUV (`chromakey`), устойчив к неравномерному освещению
+  #[default]
+  Chroma,
+  /// По цвету в RGB (`colorkey`)
+  Color,
+}
+
+/// Фон, подставляемый под клип с хромакеем
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum ChromaKeyBackground {
+  /// Сплошной цвет
+  Color(String),
+  /// Изображение
+  Image(PathBuf),
+  /// Исходный файл другого клипа проекта (ID клипа)
+  Clip(String),
+}
+
+/// Параметры хромакея
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct ChromaKeyParams {
+  /// Цвет ключа (`0xRRGGBB`, `#RRGGBB` или имя цвета FFmpeg)
+  pub key_color: String,
+  /// Близость к цвету ключа, при которой пиксель становится прозрачным (0.01 - 1.0)
+  pub similarity: f32,
+  /// Плавность края (0.0 - 1.0)
+  pub blend: f32,
+  /// Подавление отсветов цвета ключа на объекте (0.0 - 1.0)
+  pub spill_suppression: f32,
+  /// Способ выделения ключа
+  pub key_mode: ChromaKeyMode,
+  /// Фон под вырезанным объектом; без фона остается прозрачность
+  pub background: Option<ChromaKeyBackground>,
+}
+
+impl Default for ChromaKeyParams {
+  fn default() -> Self {
+    Self {
+      key_color: DEFAULT_KEY_COLOR.to_string(),
+      similarity: 0.3,
+      blend: 0.0,
+      spill_suppression: 0.0,
+      key_mode: ChromaKeyMode::default(),
+      background: None,
+    }
+  }
+}
+
+impl ChromaKeyParams {
+  /// Прочитать параметры эффекта; отсутствующие получают значения по умолчанию
+  pub fn from_effect(effect: &Effect) -> Self {
+    let parameters = &effect.parameters;
+    let defaults = Self::default();
+    let number = |name: &str, default: f32| match parameters.get(name) {
+      Some(EffectParameter::Float(value)) => *value,
+      Some(EffectParameter::Int(value)) => *value as f32,
+      _ => default,
+    };
+
+    let key_color = color_parameter(parameters, "key_color")
+      .or_else(|| color_parameter(parameters, "color"))
+      .unwrap_or(defaults.key_color);
+    let key_mode = match string_parameter(parameters, "key_mode").as_deref() {
+      Some("color") => ChromaKeyMode::Color,
+      _ => ChromaKeyMode::Chroma,
+    };
+    let background = if let Some(clip_id) = string_parameter(parameters, "background_clip_id") {
+      Some(ChromaKeyBackground::Clip(clip_id))
+    } else if let Some(path) = path_parameter(parameters, "background_image") {
+      Some(ChromaKeyBackground::Image(path))
+    } else {
+      color_parameter(parameters, "background_color").map(ChromaKeyBackground::Color)
+    };
+
+    Self {
+      key_color,
+      similarity: number("similarity", defaults.similarity),
+      blend: number("blend", defaults.blend),
+      spill_suppression: number("spill_suppression", defaults.spill_suppression),
+      key_mode,
+      background,
+    }
+  }
+
+  /// Параметры эффекта, из которых `from_effect` восстановит эти настройки
+  pub fn to_parameters(&self) -> HashMap<String, EffectParameter> {
+    let mut parameters = HashMap::from([
+      (
+        "key_color".to_string(),
+        EffectParameter::String(self.key_color.clone()),
+      ),
+      (
+        "similarity".to_string(),
+        EffectParameter::Float(self.similarity),
+      ),
+      ("blend".to_string(), EffectParameter::Float(self.blend)),
+      (
+        "spill_suppression".to_string(),
+        EffectParameter::Float(self.spill_suppression),
+      ),
+    ]);
+    if self.key_mode == ChromaKeyMode::Color {
+      parameters.insert(
+        "key_mode".to_string(),
+        EffectParameter::String("color".to_string()),
+      );
+    }
+    match &self.background {
+      Some(ChromaKeyBackground::Color(color)) => {
+        parameters.insert(
+          "background_color".to_string(),
+          EffectParameter::String(color.clone()),
+        );
+      }
+      Some(ChromaKeyBackground::Image(path)) => {
+        parameters.insert(
+          "background_image".to_string(),
+          EffectParameter::FilePath(path.clone()),
+        );
+      }
+      Some(ChromaKeyBackground::Clip(clip_id)) => {
+        parameters.insert(
+          "background_clip_id".to_string(),
+          EffectParameter::String(clip_id.clone()),
+        );
+      }
+      None => {}
+    }
+    parameters
+  }
+
+  /// Эффект хромакея с этими параметрами
+  pub fn to_effect(&self) -> Effect {
+    let mut effect = Effect::new(EffectType::ChromaKey, "Chroma Key".to_string());
+    effect.parameters = self.to_parameters();
+    effect
+  }
+
+  /// Тип фильтра `despill`: синий для синего фона, иначе зеленый
+  pub fn spill_type(&self) -> &'static str {
+    match parse_rgb(&self.key_color) {
+      Some((_, green, blue)) if blue > green => "blue",
+      None if self.key_color.eq_ignore_ascii_case("blue") => "blue",
+      _ => "green",
+    }
+  }
+
+  /// Проверить диапазоны параметров и наличие фонового изображения
+  pub fn validate(&self) -> Result<(), String> {
+    if self.key_color.trim().is_empty() {
+      return Err("Цвет ключа хромакея не задан".to_string());
+    }
+    let ranges = [
+      ("similarity", self.similarity, 0.01),
+      ("blend", self.blend, 0.0),
+      ("spill_suppression", self.spill_suppression, 0.0),
+    ];
+    for (name, value, min) in ranges {
+      if !(min..=1.0).contains(&value) {
+        return Err(format!(
+          "Параметр хромакея '{name}' должен быть от {min} до 1.0, указано {value}"
+        ));
+      }
+    }
+    match &self.background {
+      Some(ChromaKeyBackground::Image(path)) if !path.is_file() => Err(format!(
+        "Фоновое изображение хромакея не найдено: {}",
+        path.display()
+      )),
+      Some(ChromaKeyBackground::Color(color)) if color.trim().is_empty() => {
+        Err("Цвет фона хромакея не задан".to_string())
+      }
+      _ => Ok(()),
+    }
+  }
+}
+
+impl ProjectSchema {
+  /// Проверить хромакеи проекта: параметры, фоновые изображения и клипы.
+  ///
+  /// Фоновый клип должен существовать и быть файлом, а цепочка фоновых
+  /// клипов не должна возвращаться к клипу, на котором стоит хромакей.
+  pub(crate) fn validate_chroma_keys(&self) -> Result<(), String> {
+    let mut backgrounds: HashMap<&str, Vec<String>> = HashMap::new();
+
+    for effect in self
+      .effects
+      .iter()
+      .filter(|effect| effect.enabled && effect.effect_type == EffectType::ChromaKey)
+    {
+      let params = ChromaKeyParams::from_effect(effect);
+      params
+        .validate()
+        .map_err(|e| format!("Эффект '{}': {e}", effect.id))?;
+
+      let Some(ChromaKeyBackground::Clip(background_id)) = &params.background else {
+        continue;
+      };
+      let background = self
+        .tracks
+        .iter()
+        .flat_map(|track| &track.clips)
+        .find(|clip| &clip.id == background_id)
+        .ok_or_else(|| {
+          format!(
+            "Фоновый клип '{background_id}' хромакея '{}' не найден",
+            effect.id
+          )
+        })?;
+      if !matches!(background.source, ClipSource::File(_)) {
+        return Err(format!(
+          "Фоновый клип '{background_id}' хромакея '{}' должен быть файлом",
+          effect.id
+        ));
+      }
+
+      for clip in self.tracks.iter().flat_map(|track| &track.clips) {
+        if clip.effects.contains(&effect.id) {
+          backgrounds
+            .entry(clip.id.as_str())
+            .or_default()
+            .push(background_id.clone());
+        }
+      }
+    }
+
+    for clip_id in backgrounds.keys() {
+      let mut visited = HashSet::new();
+      let mut pending: Vec<&str> = backgrounds[clip_id].iter().map(String::as_str).collect();
+      while let Some(next) = pending.pop() {
+        if next == *clip_id {
+          return Err(format!(
+            "Фон хромакея клипа '{clip_id}' циклически ссылается на сам клип"
+          ));
+        }
+        if visited.insert(next) {
+          if let Some(further) = backgrounds.get(next) {
+            pending.extend(further.iter().map(String::as_str));
+          }
+        }
+      }
+    }
+
+    Ok(())
+  }
+}
+
+/// Цвет в формате FFmpeg `0xRRGGBB`, если строку удалось разобрать как hex
+pub fn normalize_color(color: &str) -> String {
+  match parse_rgb(color) {
+    Some((red, green, blue)) => format!("0x{red:02x}{green:02x}{blue:02x}"),
+    None => color.trim().to_string(),
+  }
+}
+
+/// Компоненты цвета `0xRRGGBB` / `#RRGGBB`
+fn parse_rgb(color: &str) -> Option<(u8, u8, u8)> {
+  let color = color.trim();
+  let hex = color
+    .strip_prefix("0x")
+    .or_else(|| color.strip_prefix('#'))?;
+  if hex.len() != 6 || !hex.is_ascii() {
+    return None;
+  }
+  let component = |range: std::ops::Range<usize>| u8::from_str_radix(&hex[range], 16).ok();
+  Some((component(0..2)?, component(2..4)?, component(4..6)?))
+}
+
+fn string_parameter(parameters: &HashMap<String, EffectParameter>, name: &str) -> Option<String> {
+  match parameters.get(name)? {
+    EffectParameter::String(value) if !value.is_empty() => Some(value.clone()),
+    _ => None,
+  }
+}
+
+/// Цвет из строки или из RGBA значения `EffectParameter::Color`
+fn color_parameter(parameters: &HashMap<String, EffectParameter>, name: &str) -> Option<String> {
+  match parameters.get(name)? {
+    EffectParameter::Color(rgba) => Some(format!("0x{:06x}", rgba >> 8)),
+    _ => string_parameter(parameters, name),
+  }
+}
+
+fn path_parameter(parameters: &HashMap<String, EffectParameter>, name: &str) -> Option<PathBuf> {
+  match parameters.get(name)? {
+    EffectParameter::FilePath(path) => Some(path.clone()),
+    EffectParameter::String(path) if !path.is_empty() => Some(PathBuf::from(path)),
+    _ => None,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::video_compiler::schema::timeline::{Clip, Track, TrackType};
+  use tempfile::NamedTempFile;
+
+  fn project_with_clips(ids: &[&str]) -> ProjectSchema {
+    let mut project = ProjectSchema::new("chroma".to_string());
+    let mut track = Track::new(TrackType::Video, "Video".to_string());
+    for (index, id) in ids.iter().enumerate() {
+      let mut clip = Clip::new(
+        PathBuf::from(format!("/tmp/{id}.mp4")),
+        index as f64 * 5.0,
+        5.0,
+      );
+      clip.id = id.to_string();
+      track.clips.push(clip);
+    }
+    project.tracks.push(track);
+    project
+  }
+
+  fn key_clip(project: &mut ProjectSchema, clip_id: &str, params: ChromaKeyParams) {
+    let effect = params.to_effect();
+    let (track_idx, clip_idx) = project.find_clip_position(clip_id).unwrap();
+    project.tracks[track_idx].clips[clip_idx]
+      .effects
+      .push(effect.id.clone());
+    project.effects.push(effect);
+  }
+
+  fn clip_background(clip_id: &str) -> ChromaKeyParams {
+    ChromaKeyParams {
+      background: Some(ChromaKeyBackground::Clip(clip_id.to_string())),
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn test_params_round_trip_and_legacy_color() {
+    let params = ChromaKeyParams {
+      key_color: "0x0000ff".to_string(),
+      similarity: 0.2,
+      blend: 0.1,
+      spill_suppression: 0.5,
+      key_mode: ChromaKeyMode::Color,
+      background: Some(ChromaKeyBackground::Color("black".to_string())),
+    };
+    assert_eq!(ChromaKeyParams::from_effect(&params.to_effect()), params);
+    assert_eq!(params.spill_type(), "blue");
+
+    let mut legacy = Effect::new(EffectType::ChromaKey, "Legacy".to_string());
+    legacy.parameters.insert(
+      "color".to_string(),
+      EffectParameter::String("#00FF00".to_string()),
+    );
+    let params = ChromaKeyParams::from_effect(&legacy);
+    assert_eq!(params.key_color, "#00FF00");
+    assert_eq!(params.spill_type(), "green");
+    assert_eq!(normalize_color(&params.key_color), "0x00ff00");
+  }
+
+  #[test]
+  fn test_validate_ranges_and_background_image() {
+    let params = ChromaKeyParams {
+      similarity: 1.5,
+      ..Default::default()
+    };
+    assert!(params.validate().unwrap_err().contains("similarity"));
+
+    let missing = ChromaKeyParams {
+      background: Some(ChromaKeyBackground::Image(PathBuf::from(
+        "/nonexistent/background.png",
+      ))),
+      ..Default::default()
+    };
+    assert!(missing.validate().is_err());
+
+    let image = NamedTempFile::new().unwrap();
+    let existing = ChromaKeyParams {
+      background: Some(ChromaKeyBackground::Image(image.path().to_path_buf())),
+      ..Default::default()
+    };
+    assert!(existing.validate().is_ok());
+  }
+
+  #[test]
+  fn test_validate_background_clip_references() {
+    let mut project = project_with_clips(&["fg", "bg"]);
+    key_clip(&mut project, "fg", clip_background("bg"));
+    assert!(project.validate_chroma_keys().is_ok());
+
+    let mut missing = project_with_clips(&["fg"]);
+    key_clip(&mut missing, "fg", clip_background("bg"));
+    assert!(missing
+      .validate_chroma_keys()
+      .unwrap_err()
+      .contains("не найден"));
+
+    let mut self_reference = project_with_clips(&["fg"]);
+    key_clip(&mut self_reference, "fg", clip_background("fg"));
+    assert!(self_reference
+      .validate_chroma_keys()
+      .unwrap_err()
+      .contains("циклически"));
+
+    let mut cycle = project_with_clips(&["a", "b", "c"]);
+    key_clip(&mut cycle, "a", clip_background("b"));
+    key_clip(&mut cycle, "b", clip_background("c"));
+    key_clip(&mut cycle, "c", clip_background("a"));
+    assert!(cycle.validate_chroma_keys().is_err());
+  }
+}
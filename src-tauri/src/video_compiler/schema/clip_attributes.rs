@@ -0,0 +1,575 @@
+//! Clip Attributes - Копирование и вставка атрибутов клипа
+//!
+//! Эффекты, фильтры, цветокоррекция, кроп, трансформация, прозрачность,
+//! скорость, громкость и фейды одного клипа переносятся на другие клипы.
+//! Эффекты и фильтры копируются с новыми ID, поэтому клипы после вставки
+//! редактируются независимо. Атрибуты изображения не применяются к аудио
+//! клипам, громкость - к клипам субтитров.
+
+use serde::{Deserialize, Serialize};
+
+use super::effects::{Effect, Filter};
+use super::project::ProjectSchema;
+use super::timeline::{Clip, ColorCorrection, CropSettings, Fade, TrackType, TransformSettings};
+
+/// Атрибут клипа, который можно скопировать
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ClipAttribute {
+  Effects,
+  Filters,
+  ColorCorrection,
+  Crop,
+  Transform,
+  Opacity,
+  Speed,
+  Volume,
+  Fades,
+}
+
+impl ClipAttribute {
+  /// Все атрибуты в порядке применения
+  pub const ALL: [ClipAttribute; 9] = [
+    ClipAttribute::Effects,
+    ClipAttribute::Filters,
+    ClipAttribute::ColorCorrection,
+    ClipAttribute::Crop,
+    ClipAttribute::Transform,
+    ClipAttribute::Opacity,
+    ClipAttribute::Speed,
+    ClipAttribute::Volume,
+    ClipAttribute::Fades,
+  ];
+
+  /// Применим ли атрибут к клипу трека данного типа
+  pub fn applies_to(self, track_type: &TrackType) -> bool {
+    match self {
+      ClipAttribute::Filters
+      | ClipAttribute::ColorCorrection
+      | ClipAttribute::Crop
+      | ClipAttribute::Transform
+      | ClipAttribute::Opacity => *track_type != TrackType::Audio,
+      ClipAttribute::Volume => *track_type != TrackType::Subtitle,
+      ClipAttribute::Effects | ClipAttribute::Speed | ClipAttribute::Fades => true,
+    }
+  }
+}
+
+/// Набор копируемых атрибутов (флажки интерфейса)
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct ClipAttributeMask {
+  pub effects: bool,
+  pub filters: bool,
+  pub color_correction: bool,
+  pub crop: bool,
+  pub transform: bool,
+  pub opacity: bool,
+  pub speed: bool,
+  pub volume: bool,
+  pub fades: bool,
+}
+
+impl ClipAttributeMask {
+  /// Все атрибуты
+  pub fn all() -> Self {
+    Self {
+      effects: true,
+      filters: true,
+      color_correction: true,
+      crop: true,
+      transform: true,
+      opacity: true,
+      speed: true,
+      volume: true,
+      fades: true,
+    }
+  }
+
+  /// Выбран ли атрибут
+  pub fn contains(&self, attribute: ClipAttribute) -> bool {
+    match attribute {
+      ClipAttribute::Effects => self.effects,
+      ClipAttribute::Filters => self.filters,
+      ClipAttribute::ColorCorrection => self.color_correction,
+      ClipAttribute::Crop => self.crop,
+      ClipAttribute::Transform => self.transform,
+      ClipAttribute::Opacity => self.opacity,
+      ClipAttribute::Speed => self.speed,
+      ClipAttribute::Volume => self.volume,
+      ClipAttribute::Fades => self.fades,
+    }
+  }
+
+  /// Выбранные атрибуты в порядке применения
+  pub fn attributes(&self) -> Vec<ClipAttribute> {
+    ClipAttribute::ALL
+      .into_iter()
+      .filter(|attribute| self.contains(*attribute))
+      .collect()
+  }
+}
+
+/// Скопированные атрибуты клипа. Заполнены только поля из маски.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ClipAttributes {
+  /// ID клипа-источника
+  pub source_clip_id: String,
+  /// Скопированные атрибуты
+  pub mask: ClipAttributeMask,
+  /// Эффекты клипа целиком, чтобы вставка не зависела от проекта-источника
+  #[serde(default)]
+  pub effects: Vec<Effect>,
+  /// Фильтры клипа целиком
+  #[serde(default)]
+  pub filters: Vec<Filter>,
+  #[serde(default)]
+  pub color_correction: Option<ColorCorrection>,
+  #[serde(default)]
+  pub crop: Option<CropSettings>,
+  #[serde(default)]
+  pub transform: Option<TransformSettings>,
+  #[serde(default)]
+  pub opacity: Option<f32>,
+  #[serde(default)]
+  pub speed: Option<f64>,
+  #[serde(default)]
+  pub volume: Option<f32>,
+  #[serde(default)]
+  pub fade_in: Option<Fade>,
+  #[serde(default)]
+  pub fade_out: Option<Fade>,
+}
+
+/// Способ вставки атрибутов
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PasteMode {
+  /// Атрибуты целевого клипа заменяются скопированными, в том числе пустыми
+  #[default]
+  Replace,
+  /// Эффекты и фильтры добавляются к имеющимся, пустые атрибуты не
+  /// сбрасывают значения целевого клипа
+  Merge,
+}
+
+/// Причина, по которой клип пропущен целиком
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PasteSkipReason {
+  /// Заблокирован клип или его трек
+  Locked,
+}
+
+/// Итог вставки для одного клипа
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PasteTargetSummary {
+  pub clip_id: String,
+  /// Примененные атрибуты
+  pub applied: Vec<ClipAttribute>,
+  /// Атрибуты, неприменимые к типу трека клипа
+  pub not_applicable: Vec<ClipAttribute>,
+  /// Причина пропуска клипа
+  pub skipped: Option<PasteSkipReason>,
+}
+
+impl ProjectSchema {
+  /// Скопировать атрибуты клипа по маске
+  pub fn copy_clip_attributes(
+    &self,
+    clip_id: &str,
+    mask: ClipAttributeMask,
+  ) -> Result<ClipAttributes, String> {
+    let (track_idx, clip_idx) = self
+      .find_clip_position(clip_id)
+      .ok_or_else(|| format!("Клип не найден: {clip_id}"))?;
+    let clip = &self.tracks[track_idx].clips[clip_idx];
+
+    let mut attributes = ClipAttributes {
+      source_clip_id: clip.id.clone(),
+      mask,
+      effects: Vec::new(),
+      filters: Vec::new(),
+      color_correction: None,
+      crop: None,
+      transform: None,
+      opacity: None,
+      speed: None,
+      volume: None,
+      fade_in: None,
+      fade_out: None,
+    };
+    for attribute in mask.attributes() {
+      match attribute {
+        ClipAttribute::Effects => {
+          attributes.effects = clip
+            .effects
+            .iter()
+            .filter_map(|id| self.effects.iter().find(|effect| &effect.id == id))
+            .cloned()
+            .collect();
+        }
+        ClipAttribute::Filters => {
+          attributes.filters = clip
+            .filters
+            .iter()
+            .filter_map(|id| self.filters.iter().find(|filter| &filter.id == id))
+            .cloned()
+            .collect();
+        }
+        ClipAttribute::ColorCorrection => {
+          attributes.color_correction = clip.color_correction.clone()
+        }
+        ClipAttribute::Crop => attributes.crop = clip.crop.clone(),
+        ClipAttribute::Transform => attributes.transform = clip.transform.clone(),
+        ClipAttribute::Opacity => attributes.opacity = Some(clip.opacity),
+        ClipAttribute::Speed => attributes.speed = Some(clip.speed),
+        ClipAttribute::Volume => attributes.volume = Some(clip.volume),
+        ClipAttribute::Fades => {
+          attributes.fade_in = clip.fade_in.clone();
+          attributes.fade_out = clip.fade_out.clone();
+        }
+      }
+    }
+
+    Ok(attributes)
+  }
+
+  /// Вставить атрибуты в клипы.
+  ///
+  /// Заблокированные клипы и клипы заблокированных треков пропускаются и
+  /// отмечаются в итоге. Если результат не проходит валидацию, проект не
+  /// изменяется.
+  pub fn paste_clip_attributes(
+    &mut self,
+    clip_ids: &[String],
+    attributes: &ClipAttributes,
+    mode: PasteMode,
+  ) -> Result<Vec<PasteTargetSummary>, String> {
+    let mut project = self.clone();
+    let mut summaries = Vec::with_capacity(clip_ids.len());
+
+    for clip_id in clip_ids {
+      let (track_idx, clip_idx) = project
+        .find_clip_position(clip_id)
+        .ok_or_else(|| format!("Клип не найден: {clip_id}"))?;
+      let track = &project.tracks[track_idx];
+      let mut summary = PasteTargetSummary {
+        clip_id: clip_id.clone(),
+        applied: Vec::new(),
+        not_applicable: Vec::new(),
+        skipped: None,
+      };
+      if track.locked || track.clips[clip_idx].locked {
+        summary.skipped = Some(PasteSkipReason::Locked);
+        summaries.push(summary);
+        continue;
+      }
+
+      let track_type = track.track_type.clone();
+      for attribute in attributes.mask.attributes() {
+        if !attribute.applies_to(&track_type) {
+          summary.not_applicable.push(attribute);
+          continue;
+        }
+        match attribute {
+          ClipAttribute::Effects => {
+            // Аудио клипу передаются только звуковые эффекты
+            let effects: Vec<Effect> = attributes
+              .effects
+              .iter()
+              .filter(|effect| track_type != TrackType::Audio || effect.effect_type.is_audio())
+              .map(|effect| Effect {
+                id: uuid::Uuid::new_v4().to_string(),
+                ..effect.clone()
+              })
+              .collect();
+            let ids = effects.iter().map(|effect| effect.id.clone()).collect();
+            project.effects.extend(effects);
+            let clip = &mut project.tracks[track_idx].clips[clip_idx];
+            paste_ids(&mut clip.effects, ids, mode);
+          }
+          ClipAttribute::Filters => {
+            let filters: Vec<Filter> = attributes
+              .filters
+              .iter()
+              .map(|filter| Filter {
+                id: uuid::Uuid::new_v4().to_string(),
+                ..filter.clone()
+              })
+              .collect();
+            let ids = filters.iter().map(|filter| filter.id.clone()).collect();
+            project.filters.extend(filters);
+            let clip = &mut project.tracks[track_idx].clips[clip_idx];
+            paste_ids(&mut clip.filters, ids, mode);
+          }
+          _ => apply_clip_attribute(
+            &mut project.tracks[track_idx].clips[clip_idx],
+            attribute,
+            attributes,
+            mode,
+          ),
+        }
+        summary.applied.push(attribute);
+      }
+
+      project.tracks[track_idx].clips[clip_idx]
+        .validate()
+        .map_err(|e| format!("Клип {clip_id}: {e}"))?;
+      summaries.push(summary);
+    }
+
+    // Изменение скорости меняет длительность клипа и может дать пересечения
+    project.validate().map_err(|e| e.to_string())?;
+    project.touch();
+    *self = project;
+    Ok(summaries)
+  }
+}
+
+/// Вставить ID эффектов или фильтров в список клипа
+fn paste_ids(target: &mut Vec<String>, ids: Vec<String>, mode: PasteMode) {
+  match mode {
+    PasteMode::Replace => *target = ids,
+    PasteMode::Merge => target.extend(ids),
+  }
+}
+
+/// Заменить значение атрибута. В режиме слияния пустое значение не сбрасывает
+/// значение целевого клипа.
+fn paste_option<T: Clone>(target: &mut Option<T>, value: &Option<T>, mode: PasteMode) {
+  if mode == PasteMode::Replace || value.is_some() {
+    *target = value.clone();
+  }
+}
+
+/// Применить к клипу атрибут, не требующий изменения проекта
+fn apply_clip_attribute(
+  clip: &mut Clip,
+  attribute: ClipAttribute,
+  attributes: &ClipAttributes,
+  mode: PasteMode,
+) {
+  match attribute {
+    ClipAttribute::ColorCorrection => paste_option(
+      &mut clip.color_correction,
+      &attributes.color_correction,
+      mode,
+    ),
+    ClipAttribute::Crop => paste_option(&mut clip.crop, &attributes.crop, mode),
+    ClipAttribute::Transform => paste_option(&mut clip.transform, &attributes.transform, mode),
+    ClipAttribute::Opacity => {
+      if let Some(opacity) = attributes.opacity {
+        clip.opacity = opacity;
+      }
+    }
+    ClipAttribute::Speed => {
+      // Исходный фрагмент сохраняется, длительность на timeline пересчитывается
+      if let Some(speed) = attributes.speed.filter(|speed| *speed > 0.0) {
+        clip.speed = speed;
+        clip.end_time = clip.start_time + clip.get_source_duration();
+      }
+    }
+    ClipAttribute::Volume => {
+      if let Some(volume) = attributes.volume {
+        clip.volume = volume;
+      }
+    }
+    ClipAttribute::Fades => {
+      paste_option(&mut clip.fade_in, &attributes.fade_in, mode);
+      paste_option(&mut clip.fade_out, &attributes.fade_out, mode);
+    }
+    ClipAttribute::Effects | ClipAttribute::Filters => {}
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::video_compiler::schema::effects::EffectType;
+  use crate::video_compiler::schema::timeline::{FadeCurve, Track};
+  use crate::video_compiler::schema::FilterType;
+  use std::path::PathBuf;
+
+  fn clip(id: &str, start: f64, end: f64) -> Clip {
+    let mut clip = Clip::new(
+      PathBuf::from(format!("/media/{id}.mp4")),
+      start,
+      end - start,
+    );
+    clip.id = id.to_string();
+    clip
+  }
+
+  fn track(id: &str, track_type: TrackType, clips: Vec<Clip>) -> Track {
+    let mut track = Track::new(track_type, id.to_string());
+    track.id = id.to_string();
+    track.clips = clips;
+    track
+  }
+
+  /// Клип-источник с эффектами изображения и звука, фильтром, трансформацией
+  /// и фейдами; цели - обычный, заблокированный и аудио клип
+  fn project_with_source() -> ProjectSchema {
+    let mut project = ProjectSchema::new("Attributes".to_string());
+    let blur = Effect::new(EffectType::Blur, "Blur".to_string());
+    let reverb = Effect::new(EffectType::AudioReverb, "Reverb".to_string());
+    let filter = Filter::new(FilterType::Brightness, "Bright".to_string());
+
+    let mut source = clip("source", 0.0, 4.0);
+    source.effects = vec![blur.id.clone(), reverb.id.clone()];
+    source.filters = vec![filter.id.clone()];
+    source.opacity = 0.5;
+    source.volume = 0.7;
+    source.transform = Some(TransformSettings::default());
+    source.fade_in = Some(Fade::new(0.5, FadeCurve::default()));
+
+    let mut locked = clip("locked", 10.0, 14.0);
+    locked.locked = true;
+
+    project.effects = vec![blur, reverb];
+    project.filters = vec![filter];
+    project.tracks = vec![
+      track(
+        "video",
+        TrackType::Video,
+        vec![source, clip("target", 5.0, 9.0), locked],
+      ),
+      track("audio", TrackType::Audio, vec![clip("voice", 0.0, 8.0)]),
+    ];
+    project
+  }
+
+  fn find<'a>(project: &'a ProjectSchema, clip_id: &str) -> &'a Clip {
+    let (track_idx, clip_idx) = project.find_clip_position(clip_id).unwrap();
+    &project.tracks[track_idx].clips[clip_idx]
+  }
+
+  #[test]
+  fn test_copy_fills_only_masked_attributes() {
+    let project = project_with_source();
+    let mask = ClipAttributeMask {
+      effects: true,
+      volume: true,
+      ..Default::default()
+    };
+
+    let attributes = project.copy_clip_attributes("source", mask).unwrap();
+    assert_eq!(attributes.effects.len(), 2);
+    assert!(attributes.filters.is_empty());
+    assert!(attributes.transform.is_none());
+    assert_eq!(attributes.volume, Some(0.7));
+    assert_eq!(attributes.opacity, None);
+    assert!(project.copy_clip_attributes("missing", mask).is_err());
+  }
+
+  #[test]
+  fn test_paste_onto_mixed_targets() {
+    let mut project = project_with_source();
+    let attributes = project
+      .copy_clip_attributes("source", ClipAttributeMask::all())
+      .unwrap();
+    let targets = ["target", "locked", "voice"].map(String::from);
+
+    let summaries = project
+      .paste_clip_attributes(&targets, &attributes, PasteMode::Replace)
+      .unwrap();
+
+    // Видео клип получает все атрибуты с новыми ID эффектов и фильтров
+    assert_eq!(summaries[0].applied, ClipAttribute::ALL);
+    let target = find(&project, "target");
+    assert_eq!(target.effects.len(), 2);
+    assert_eq!(target.filters.len(), 1);
+    assert!(!target.effects.contains(&attributes.effects[0].id));
+    assert!(target
+      .effects
+      .iter()
+      .all(|id| project.effects.iter().any(|effect| &effect.id == id)));
+    assert_eq!(target.opacity, 0.5);
+    assert_eq!(target.volume, 0.7);
+    assert!(target.transform.is_some());
+    assert!(target.fade_in.is_some());
+
+    // Заблокированный клип не меняется
+    assert_eq!(summaries[1].skipped, Some(PasteSkipReason::Locked));
+    assert!(summaries[1].applied.is_empty());
+    assert!(find(&project, "locked").effects.is_empty());
+
+    // Аудио клип получает только звуковые атрибуты
+    let voice_summary = &summaries[2];
+    assert_eq!(
+      voice_summary.not_applicable,
+      [
+        ClipAttribute::Filters,
+        ClipAttribute::ColorCorrection,
+        ClipAttribute::Crop,
+        ClipAttribute::Transform,
+        ClipAttribute::Opacity,
+      ]
+    );
+    let voice = find(&project, "voice");
+    assert_eq!(voice.effects.len(), 1);
+    let effect = project
+      .effects
+      .iter()
+      .find(|effect| effect.id == voice.effects[0])
+      .unwrap();
+    assert_eq!(effect.effect_type, EffectType::AudioReverb);
+    assert!(voice.filters.is_empty());
+    assert!(voice.transform.is_none());
+    assert_eq!(voice.opacity, 1.0);
+    assert_eq!(voice.volume, 0.7);
+
+    // 2 исходных эффекта + 2 для видео клипа + 1 для аудио
+    assert_eq!(project.effects.len(), 5);
+  }
+
+  #[test]
+  fn test_merge_keeps_existing_attributes() {
+    let mut project = project_with_source();
+    let existing = Effect::new(EffectType::Sepia, "Sepia".to_string());
+    let (track_idx, clip_idx) = project.find_clip_position("target").unwrap();
+    {
+      let target = &mut project.tracks[track_idx].clips[clip_idx];
+      target.effects = vec![existing.id.clone()];
+      target.fade_out = Some(Fade::new(1.0, FadeCurve::default()));
+    }
+    project.effects.push(existing.clone());
+
+    let mask = ClipAttributeMask {
+      effects: true,
+      fades: true,
+      ..Default::default()
+    };
+    let attributes = project.copy_clip_attributes("source", mask).unwrap();
+    project
+      .paste_clip_attributes(&["target".to_string()], &attributes, PasteMode::Merge)
+      .unwrap();
+
+    let target = find(&project, "target");
+    assert_eq!(target.effects.len(), 3);
+    assert_eq!(target.effects[0], existing.id);
+    assert!(target.fade_in.is_some());
+    assert!(target.fade_out.is_some());
+  }
+
+  #[test]
+  fn test_paste_rejects_overlapping_speed_change() {
+    let mut project = project_with_source();
+    let (track_idx, clip_idx) = project.find_clip_position("source").unwrap();
+    project.tracks[track_idx].clips[clip_idx].speed = 0.5;
+    let mask = ClipAttributeMask {
+      speed: true,
+      ..Default::default()
+    };
+    let attributes = project.copy_clip_attributes("source", mask).unwrap();
+    let before = find(&project, "target").end_time;
+
+    // Замедленный клип target (5-13) пересекается с клипом locked (10-14)
+    let result =
+      project.paste_clip_attributes(&["target".to_string()], &attributes, PasteMode::Replace);
+
+    assert!(result.is_err());
+    assert_eq!(find(&project, "target").end_time, before);
+  }
+}
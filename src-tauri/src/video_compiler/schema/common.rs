@@ -34,6 +34,18 @@ impl AspectRatio {
   pub fn to_ffmpeg_string(&self) -> String {
     format!("{self}")
   }
+
+  /// Отношение ширины к высоте
+  pub fn ratio(&self) -> f32 {
+    match self {
+      AspectRatio::Ratio16x9 => 16.0 / 9.0,
+      AspectRatio::Ratio4x3 => 4.0 / 3.0,
+      AspectRatio::Ratio21x9 => 21.0 / 9.0,
+      AspectRatio::Ratio1x1 => 1.0,
+      AspectRatio::Ratio9x16 => 9.0 / 16.0,
+      AspectRatio::Custom(ratio) => *ratio,
+    }
+  }
 }
 
 /// Разрешение видео
@@ -114,6 +126,313 @@ pub enum AlignY {
   Bottom,
 }
 
+/// Стандартная частота кадров timecode
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimecodeRate {
+  /// 23.976 (24000/1001)
+  Fps23_976,
+  Fps24,
+  Fps25,
+  /// 29.97 (30000/1001)
+  Fps29_97,
+  Fps30,
+  Fps50,
+  /// 59.94 (60000/1001)
+  Fps59_94,
+  Fps60,
+}
+
+impl TimecodeRate {
+  /// Все поддерживаемые частоты
+  pub const ALL: [TimecodeRate; 8] = [
+    TimecodeRate::Fps23_976,
+    TimecodeRate::Fps24,
+    TimecodeRate::Fps25,
+    TimecodeRate::Fps29_97,
+    TimecodeRate::Fps30,
+    TimecodeRate::Fps50,
+    TimecodeRate::Fps59_94,
+    TimecodeRate::Fps60,
+  ];
+
+  /// Стандартная частота, ближайшая к `fps` (допуск 0.01 кадра в секунду)
+  pub fn from_fps(fps: f64) -> Option<Self> {
+    Self::ALL
+      .into_iter()
+      .find(|rate| (rate.fps() - fps).abs() < 0.01)
+  }
+
+  /// Стандартная частота, ближайшая к `fps`
+  pub fn nearest(fps: f64) -> Self {
+    Self::ALL
+      .into_iter()
+      .min_by(|a, b| (a.fps() - fps).abs().total_cmp(&(b.fps() - fps).abs()))
+      .unwrap_or(TimecodeRate::Fps30)
+  }
+
+  /// Частота кадров дробью (числитель, знаменатель)
+  pub fn fraction(self) -> (i64, i64) {
+    match self {
+      TimecodeRate::Fps23_976 => (24000, 1001),
+      TimecodeRate::Fps24 => (24, 1),
+      TimecodeRate::Fps25 => (25, 1),
+      TimecodeRate::Fps29_97 => (30000, 1001),
+      TimecodeRate::Fps30 => (30, 1),
+      TimecodeRate::Fps50 => (50, 1),
+      TimecodeRate::Fps59_94 => (60000, 1001),
+      TimecodeRate::Fps60 => (60, 1),
+    }
+  }
+
+  /// Частота кадров в кадрах в секунду
+  pub fn fps(self) -> f64 {
+    let (numerator, denominator) = self.fraction();
+    numerator as f64 / denominator as f64
+  }
+
+  /// Частота для FFmpeg (`30000/1001`)
+  pub fn ffmpeg_rate(self) -> String {
+    let (numerator, denominator) = self.fraction();
+    format!("{numerator}/{denominator}")
+  }
+
+  /// Номинальное число кадров в секунде timecode
+  pub fn nominal(self) -> i64 {
+    match self {
+      TimecodeRate::Fps23_976 | TimecodeRate::Fps24 => 24,
+      TimecodeRate::Fps25 => 25,
+      TimecodeRate::Fps29_97 | TimecodeRate::Fps30 => 30,
+      TimecodeRate::Fps50 => 50,
+      TimecodeRate::Fps59_94 | TimecodeRate::Fps60 => 60,
+    }
+  }
+
+  /// Частота допускает drop-frame счет (29.97 и 59.94)
+  pub fn supports_drop_frame(self) -> bool {
+    matches!(self, TimecodeRate::Fps29_97 | TimecodeRate::Fps59_94)
+  }
+
+  /// Номера кадров, пропускаемые в начале каждой минуты, кроме кратных 10
+  fn dropped_per_minute(self) -> i64 {
+    if self.supports_drop_frame() {
+      self.nominal() / 15
+    } else {
+      0
+    }
+  }
+}
+
+/// SMPTE timecode: номер кадра от 00:00:00:00 при заданной частоте.
+///
+/// В drop-frame режиме (29.97 и 59.94) номера кадров 00 и 01 (00-03 для
+/// 59.94) пропускаются в начале каждой минуты, кроме кратных 10, чтобы
+/// timecode совпадал с реальным временем. Сами кадры не пропускаются:
+/// `frame` всегда равен количеству кадров.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Timecode {
+  /// Номер кадра в пределах суток
+  pub frame: i64,
+  /// Частота кадров
+  pub rate: TimecodeRate,
+  /// Drop-frame счет
+  pub drop_frame: bool,
+}
+
+impl Timecode {
+  /// Timecode кадра `frame`. Значения за пределами суток переносятся
+  /// через полночь, drop-frame игнорируется для частот без его поддержки.
+  pub fn from_frames(frame: i64, rate: TimecodeRate, drop_frame: bool) -> Self {
+    let drop_frame = drop_frame && rate.supports_drop_frame();
+    Self {
+      frame: frame.rem_euclid(Self::frames_per_day(rate, drop_frame)),
+      rate,
+      drop_frame,
+    }
+  }
+
+  /// Количество кадров в сутках timecode
+  pub fn frames_per_day(rate: TimecodeRate, drop_frame: bool) -> i64 {
+    let dropped = if drop_frame {
+      rate.dropped_per_minute() * (24 * 60 - 24 * 6)
+    } else {
+      0
+    };
+    rate.nominal() * 86_400 - dropped
+  }
+
+  /// Timecode из часов, минут, секунд и кадров
+  pub fn from_components(
+    hours: u32,
+    minutes: u32,
+    seconds: u32,
+    frames: u32,
+    rate: TimecodeRate,
+    drop_frame: bool,
+  ) -> Result<Self, String> {
+    if drop_frame && !rate.supports_drop_frame() {
+      return Err(format!(
+        "Drop-frame timecode не поддерживается для {} fps",
+        rate.fps()
+      ));
+    }
+    if hours >= 24 || minutes >= 60 || seconds >= 60 || frames as i64 >= rate.nominal() {
+      return Err(format!(
+        "Недопустимый timecode {hours:02}:{minutes:02}:{seconds:02}:{frames:02}"
+      ));
+    }
+
+    let dropped = if drop_frame {
+      rate.dropped_per_minute()
+    } else {
+      0
+    };
+    if seconds == 0 && minutes % 10 != 0 && (frames as i64) < dropped {
+      return Err(format!(
+        "Кадр {hours:02}:{minutes:02}:00;{frames:02} пропускается в drop-frame timecode"
+      ));
+    }
+
+    let total_minutes = hours as i64 * 60 + minutes as i64;
+    let frame = (total_minutes * 60 + seconds as i64) * rate.nominal() + frames as i64
+      - dropped * (total_minutes - total_minutes / 10);
+    Ok(Self {
+      frame,
+      rate,
+      drop_frame,
+    })
+  }
+
+  /// Разобрать timecode вида `HH:MM:SS:FF`.
+  ///
+  /// Разделитель `;` или `.` перед кадрами означает drop-frame.
+  pub fn parse(text: &str, rate: TimecodeRate) -> Result<Self, String> {
+    let text = text.trim();
+    let drop_frame = text.contains([';', '.']);
+    let parts: Vec<&str> = text.split([':', ';', '.']).collect();
+    let [hours, minutes, seconds, frames] = parts.as_slice() else {
+      return Err(format!("Неверный формат timecode: '{text}'"));
+    };
+
+    let number = |part: &str| -> Result<u32, String> {
+      if part.is_empty() || part.len() > 2 || !part.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(format!("Неверный формат timecode: '{text}'"));
+      }
+      part
+        .parse()
+        .map_err(|_| format!("Неверный формат timecode: '{text}'"))
+    };
+    Self::from_components(
+      number(hours)?,
+      number(minutes)?,
+      number(seconds)?,
+      number(frames)?,
+      rate,
+      drop_frame,
+    )
+  }
+
+  /// Timecode ближайшего к `seconds` кадра
+  pub fn from_seconds(seconds: f64, rate: TimecodeRate, drop_frame: bool) -> Self {
+    let (numerator, denominator) = rate.fraction();
+    let frame = (seconds * numerator as f64 / denominator as f64).round() as i64;
+    Self::from_frames(frame, rate, drop_frame)
+  }
+
+  /// Время от 00:00:00:00 в секундах
+  pub fn to_seconds(&self) -> f64 {
+    let (numerator, denominator) = self.rate.fraction();
+    self.frame as f64 * denominator as f64 / numerator as f64
+  }
+
+  /// Часы, минуты, секунды и кадры
+  pub fn components(&self) -> (u32, u32, u32, u32) {
+    let nominal = self.rate.nominal();
+    let mut frame = self.frame;
+
+    if self.drop_frame {
+      let dropped = self.rate.dropped_per_minute();
+      let per_minute = nominal * 60 - dropped;
+      let per_ten_minutes = nominal * 600 - dropped * 9;
+      let tens = frame / per_ten_minutes;
+      let remainder = frame % per_ten_minutes;
+      frame += dropped * 9 * tens;
+      if remainder > dropped {
+        frame += dropped * ((remainder - dropped) / per_minute);
+      }
+    }
+
+    let frames = frame % nominal;
+    let total_seconds = frame / nominal;
+    (
+      (total_seconds / 3600) as u32,
+      (total_seconds / 60 % 60) as u32,
+      (total_seconds % 60) as u32,
+      frames as u32,
+    )
+  }
+
+  /// Timecode той же позиции при другой частоте или режиме счета
+  pub fn convert(&self, rate: TimecodeRate, drop_frame: bool) -> Self {
+    if rate == self.rate {
+      Self::from_frames(self.frame, rate, drop_frame)
+    } else {
+      Self::from_seconds(self.to_seconds(), rate, drop_frame)
+    }
+  }
+
+  /// Количество кадров от `earlier` до этого timecode (с переходом через полночь)
+  pub fn frames_since(&self, earlier: &Timecode) -> i64 {
+    let earlier = earlier.convert(self.rate, self.drop_frame);
+    (self.frame - earlier.frame).rem_euclid(Self::frames_per_day(self.rate, self.drop_frame))
+  }
+}
+
+impl fmt::Display for Timecode {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let (hours, minutes, seconds, frames) = self.components();
+    let separator = if self.drop_frame { ';' } else { ':' };
+    write!(
+      f,
+      "{hours:02}:{minutes:02}:{seconds:02}{separator}{frames:02}"
+    )
+  }
+}
+
+impl std::ops::Add<i64> for Timecode {
+  type Output = Timecode;
+
+  fn add(self, frames: i64) -> Timecode {
+    Timecode::from_frames(self.frame + frames, self.rate, self.drop_frame)
+  }
+}
+
+impl std::ops::Sub<i64> for Timecode {
+  type Output = Timecode;
+
+  fn sub(self, frames: i64) -> Timecode {
+    Timecode::from_frames(self.frame - frames, self.rate, self.drop_frame)
+  }
+}
+
+/// Настройки timecode проекта
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct TimecodeSettings {
+  /// Drop-frame счет для 29.97 и 59.94 fps
+  pub drop_frame: bool,
+  /// Timecode начала timeline (`None` - 00:00:00:00)
+  pub start: Option<Timecode>,
+}
+
+impl Default for TimecodeSettings {
+  fn default() -> Self {
+    Self {
+      drop_frame: true,
+      start: None,
+    }
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -142,6 +461,13 @@ mod tests {
     assert_eq!(AspectRatio::Custom(2.0).to_ffmpeg_string(), "2.00:1");
   }
 
+  #[test]
+  fn test_aspect_ratio_value() {
+    assert!((AspectRatio::Ratio16x9.ratio() - 1.7778).abs() < 1e-4);
+    assert_eq!(AspectRatio::Ratio9x16.ratio(), 0.5625);
+    assert_eq!(AspectRatio::Custom(2.35).ratio(), 2.35);
+  }
+
   #[test]
   fn test_aspect_ratio_equality() {
     assert_eq!(AspectRatio::Ratio16x9, AspectRatio::Ratio16x9);
@@ -352,4 +678,172 @@ mod tests {
     let align_y = AlignY::Center;
     let _align_y_clone = align_y.clone();
   }
+
+  fn df(text: &str) -> Timecode {
+    Timecode::parse(text, TimecodeRate::Fps29_97).unwrap()
+  }
+
+  #[test]
+  fn test_timecode_rate_from_fps() {
+    assert_eq!(TimecodeRate::from_fps(29.97), Some(TimecodeRate::Fps29_97));
+    assert_eq!(
+      TimecodeRate::from_fps(30000.0 / 1001.0),
+      Some(TimecodeRate::Fps29_97)
+    );
+    assert_eq!(
+      TimecodeRate::from_fps(23.976),
+      Some(TimecodeRate::Fps23_976)
+    );
+    assert_eq!(TimecodeRate::from_fps(25.0), Some(TimecodeRate::Fps25));
+    assert_eq!(TimecodeRate::from_fps(59.94), Some(TimecodeRate::Fps59_94));
+    assert_eq!(TimecodeRate::from_fps(12.0), None);
+    assert_eq!(TimecodeRate::nearest(12.0), TimecodeRate::Fps23_976);
+    assert_eq!(TimecodeRate::nearest(29.0), TimecodeRate::Fps29_97);
+    assert_eq!(TimecodeRate::Fps23_976.ffmpeg_rate(), "24000/1001");
+  }
+
+  #[test]
+  fn test_timecode_ndf_parse_and_format() {
+    let tc = Timecode::parse("01:02:03:04", TimecodeRate::Fps25).unwrap();
+    assert!(!tc.drop_frame);
+    assert_eq!(tc.frame, (3600 + 2 * 60 + 3) * 25 + 4);
+    assert_eq!(tc.to_string(), "01:02:03:04");
+    assert_eq!(tc.to_seconds(), 3723.16);
+
+    // 23.976 считает 24 номера кадров в секунду, но идет медленнее реального времени
+    let tc = Timecode::parse("01:00:00:00", TimecodeRate::Fps23_976).unwrap();
+    assert_eq!(tc.frame, 86_400);
+    assert!((tc.to_seconds() - 3603.6).abs() < 1e-9);
+  }
+
+  #[test]
+  fn test_timecode_rejects_invalid_input() {
+    for invalid in [
+      "",
+      "01:02:03",
+      "1:2:3:4:5",
+      "aa:00:00:00",
+      "24:00:00:00",
+      "00:60:00:00",
+      "00:00:00:30",
+      "00:00:00:-1",
+    ] {
+      assert!(
+        Timecode::parse(invalid, TimecodeRate::Fps29_97).is_err(),
+        "{invalid}"
+      );
+    }
+    // Drop-frame только для 29.97 и 59.94
+    assert!(Timecode::parse("00:00:00;00", TimecodeRate::Fps25).is_err());
+    assert!(Timecode::parse("00:00:00;00", TimecodeRate::Fps23_976).is_err());
+    // Пропущенные номера кадров не существуют
+    assert!(Timecode::parse("00:01:00;00", TimecodeRate::Fps29_97).is_err());
+    assert!(Timecode::parse("00:01:00;01", TimecodeRate::Fps29_97).is_err());
+    assert!(Timecode::parse("00:10:00;00", TimecodeRate::Fps29_97).is_ok());
+    assert!(Timecode::parse("00:01:00;03", TimecodeRate::Fps59_94).is_err());
+    assert!(Timecode::parse("00:01:00;04", TimecodeRate::Fps59_94).is_ok());
+  }
+
+  #[test]
+  fn test_timecode_df_minute_boundaries() {
+    assert_eq!(df("00:00:59;29").frame, 1799);
+    assert_eq!(df("00:01:00;02").frame, 1800);
+    assert_eq!(df("00:09:59;29").frame, 17_981);
+    assert_eq!(df("00:10:00;00").frame, 17_982);
+    assert_eq!(df("00:11:00;02").frame, 17_982 + 1800);
+    assert_eq!(df("01:00:00;00").frame, 107_892);
+    assert_eq!(df("23:59:59;29").frame, 2_589_407);
+
+    let tc = Timecode::from_frames(1799, TimecodeRate::Fps29_97, true);
+    assert_eq!((tc + 1).to_string(), "00:01:00;02");
+    assert_eq!((df("00:01:00;02") - 1).to_string(), "00:00:59;29");
+    assert_eq!((df("00:09:59;29") + 1).to_string(), "00:10:00;00");
+  }
+
+  #[test]
+  fn test_timecode_df_roundtrip_every_frame_of_first_hour() {
+    for frame in 0..107_892 {
+      let tc = Timecode::from_frames(frame, TimecodeRate::Fps29_97, true);
+      let text = tc.to_string();
+      let parsed = df(&text);
+      assert_eq!(parsed.frame, frame, "{text}");
+
+      let (_, minutes, seconds, frames) = tc.components();
+      assert!(
+        !(seconds == 0 && minutes % 10 != 0 && frames < 2),
+        "пропускаемый номер {text}"
+      );
+    }
+  }
+
+  #[test]
+  fn test_timecode_df_roundtrip_across_day() {
+    let per_day = Timecode::frames_per_day(TimecodeRate::Fps29_97, true);
+    assert_eq!(per_day, 2_589_408);
+    for frame in (0..per_day).step_by(997) {
+      let tc = Timecode::from_frames(frame, TimecodeRate::Fps29_97, true);
+      assert_eq!(df(&tc.to_string()).frame, frame);
+    }
+
+    let per_day = Timecode::frames_per_day(TimecodeRate::Fps59_94, true);
+    assert_eq!(per_day, 5_178_816);
+    for frame in (0..per_day).step_by(1999) {
+      let tc = Timecode::from_frames(frame, TimecodeRate::Fps59_94, true);
+      let parsed = Timecode::parse(&tc.to_string(), TimecodeRate::Fps59_94).unwrap();
+      assert_eq!(parsed.frame, frame);
+    }
+  }
+
+  #[test]
+  fn test_timecode_df_tracks_wall_clock() {
+    // Через час drop-frame timecode совпадает с реальным временем
+    let hour = Timecode::from_seconds(3600.0, TimecodeRate::Fps29_97, true);
+    assert_eq!(hour.to_string(), "01:00:00;00");
+    // Non-drop timecode за тот же час отстает на 3.6 секунды
+    let hour = Timecode::from_seconds(3600.0, TimecodeRate::Fps29_97, false);
+    assert_eq!(hour.to_string(), "00:59:56:12");
+
+    let tc = df("01:02:03;04");
+    let seconds = tc.to_seconds();
+    assert_eq!(
+      Timecode::from_seconds(seconds, TimecodeRate::Fps29_97, true),
+      tc
+    );
+  }
+
+  #[test]
+  fn test_timecode_arithmetic_wraps_midnight() {
+    let tc = df("23:59:59;29");
+    assert_eq!((tc + 1).to_string(), "00:00:00;00");
+    assert_eq!((df("00:00:00;00") - 1).to_string(), "23:59:59;29");
+    assert_eq!(df("00:00:00;05").frames_since(&tc), 6);
+    assert_eq!(df("00:01:00;02").frames_since(&df("00:00:59;29")), 1);
+  }
+
+  #[test]
+  fn test_timecode_convert() {
+    let tc = df("00:10:00;00");
+    let ndf = tc.convert(TimecodeRate::Fps29_97, false);
+    assert_eq!(ndf.frame, tc.frame);
+    assert_eq!(ndf.to_string(), "00:09:59:12");
+
+    let pal = Timecode::parse("00:00:10:00", TimecodeRate::Fps25)
+      .unwrap()
+      .convert(TimecodeRate::Fps50, false);
+    assert_eq!(pal.to_string(), "00:00:10:00");
+
+    // Частота без drop-frame не принимает режим drop-frame
+    let tc = Timecode::from_frames(100, TimecodeRate::Fps25, true);
+    assert!(!tc.drop_frame);
+    assert_eq!(tc.to_string(), "00:00:04:00");
+  }
+
+  #[test]
+  fn test_timecode_serialization_roundtrip() {
+    let tc = df("01:02:03;04");
+    let json = serde_json::to_string(&tc).unwrap();
+    let restored: Timecode = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored, tc);
+    assert!(TimecodeSettings::default().drop_frame);
+  }
 }
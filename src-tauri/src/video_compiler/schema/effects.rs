@@ -39,6 +39,9 @@ pub struct Effect {
   pub preview_path: Option<String>,
   /// Пресеты эффекта
   pub presets: Option<HashMap<String, EffectPreset>>,
+  /// Ключевые кадры числовых параметров (время относительно начала клипа)
+  #[serde(default)]
+  pub keyframes: HashMap<String, Vec<ParamKeyframe>>,
 }
 
 /// Пресет эффекта
@@ -73,8 +76,71 @@ impl Effect {
       css_filter: None,
       preview_path: None,
       presets: None,
+      keyframes: HashMap::new(),
     }
   }
+
+  /// Имена параметров с ключевыми кадрами в алфавитном порядке
+  pub fn keyframed_parameters(&self) -> Vec<&str> {
+    let mut names: Vec<&str> = self
+      .keyframes
+      .iter()
+      .filter(|(_, keyframes)| !keyframes.is_empty())
+      .map(|(name, _)| name.as_str())
+      .collect();
+    names.sort_unstable();
+    names
+  }
+
+  /// Проверить ключевые кадры: только числовые параметры, времена
+  /// возрастают и лежат в пределах клипа длительностью `clip_duration`
+  pub fn validate_keyframes(&self, clip_duration: Option<f64>) -> Result<(), String> {
+    for name in self.keyframed_parameters() {
+      if let Some(parameter) = self.parameters.get(name) {
+        if !matches!(
+          parameter,
+          EffectParameter::Float(_) | EffectParameter::Int(_)
+        ) {
+          return Err(format!(
+            "Ключевые кадры эффекта '{}' заданы для нечислового параметра '{name}'",
+            self.id
+          ));
+        }
+      }
+
+      let mut previous: Option<f64> = None;
+      for keyframe in &self.keyframes[name] {
+        if !keyframe.time.is_finite() || keyframe.time < 0.0 {
+          return Err(format!(
+            "Время ключевого кадра параметра '{name}' эффекта '{}' должно быть неотрицательным",
+            self.id
+          ));
+        }
+        if !keyframe.value.is_finite() {
+          return Err(format!(
+            "Значение ключевого кадра параметра '{name}' эффекта '{}' должно быть числом",
+            self.id
+          ));
+        }
+        if previous.is_some_and(|previous| keyframe.time <= previous) {
+          return Err(format!(
+            "Ключевые кадры параметра '{name}' эффекта '{}' должны идти по возрастанию времени",
+            self.id
+          ));
+        }
+        if let Some(duration) = clip_duration {
+          if keyframe.time > duration {
+            return Err(format!(
+              "Ключевой кадр параметра '{name}' эффекта '{}' ({:.3}с) выходит за длительность клипа ({duration:.3}с)",
+              self.id, keyframe.time
+            ));
+          }
+        }
+        previous = Some(keyframe.time);
+      }
+    }
+    Ok(())
+  }
 }
 
 /// Тип эффекта
@@ -196,6 +262,38 @@ pub enum EffectType {
   AudioFade,
 }
 
+impl EffectType {
+  /// Обрабатывает ли эффект звук, а не изображение
+  pub fn is_audio(&self) -> bool {
+    matches!(
+      self,
+      EffectType::AudioFadeIn
+        | EffectType::AudioFadeOut
+        | EffectType::AudioCrossfade
+        | EffectType::AudioEqualizer
+        | EffectType::AudioCompressor
+        | EffectType::AudioReverb
+        | EffectType::AudioDelay
+        | EffectType::AudioChorus
+        | EffectType::AudioDistortion
+        | EffectType::AudioNormalize
+        | EffectType::AudioDenoise
+        | EffectType::AudioPitch
+        | EffectType::AudioTempo
+        | EffectType::AudioDucking
+        | EffectType::AudioGate
+        | EffectType::AudioLimiter
+        | EffectType::AudioExpander
+        | EffectType::AudioPan
+        | EffectType::AudioStereoWidth
+        | EffectType::AudioHighpass
+        | EffectType::AudioLowpass
+        | EffectType::AudioBandpass
+        | EffectType::AudioFade
+    )
+  }
+}
+
 /// Категория эффекта
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum EffectCategory {
@@ -270,6 +368,87 @@ pub enum EffectParameter {
   FilePath(PathBuf),
 }
 
+/// Ключевой кадр числового параметра эффекта
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ParamKeyframe {
+  /// Время относительно начала клипа (секунды)
+  pub time: f64,
+  /// Значение параметра
+  pub value: f64,
+  /// Сглаживание перехода к следующему ключевому кадру
+  #[serde(default)]
+  pub easing: KeyframeEasing,
+}
+
+impl ParamKeyframe {
+  /// Создать ключевой кадр
+  pub fn new(time: f64, value: f64, easing: KeyframeEasing) -> Self {
+    Self {
+      time,
+      value,
+      easing,
+    }
+  }
+}
+
+/// Сглаживание между ключевыми кадрами
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeyframeEasing {
+  /// Линейная интерполяция
+  #[default]
+  Linear,
+  /// Плавный вход
+  EaseIn,
+  /// Плавный выход
+  EaseOut,
+  /// Плавный вход и выход
+  EaseInOut,
+  /// Удержание значения до следующего кадра
+  Hold,
+}
+
+impl KeyframeEasing {
+  /// Применить сглаживание к доле перехода `p` (0..1)
+  pub fn apply(&self, p: f64) -> f64 {
+    match self {
+      KeyframeEasing::Linear => p,
+      KeyframeEasing::EaseIn => p * p,
+      KeyframeEasing::EaseOut => p * (2.0 - p),
+      KeyframeEasing::EaseInOut => p * p * (3.0 - 2.0 * p),
+      KeyframeEasing::Hold => 0.0,
+    }
+  }
+
+  /// Выражение FFmpeg для сглаживания доли перехода, заданной выражением `p`
+  pub fn expression(&self, p: &str) -> String {
+    match self {
+      KeyframeEasing::Linear => p.to_string(),
+      KeyframeEasing::EaseIn => format!("pow({p},2)"),
+      KeyframeEasing::EaseOut => format!("{p}*(2-{p})"),
+      KeyframeEasing::EaseInOut => format!("pow({p},2)*(3-2*{p})"),
+      KeyframeEasing::Hold => "0".to_string(),
+    }
+  }
+}
+
+/// Значение параметра в момент `time` по ключевым кадрам.
+///
+/// До первого и после последнего кадра значение удерживается.
+pub fn keyframe_value_at(keyframes: &[ParamKeyframe], time: f64) -> Option<f64> {
+  let first = keyframes.first()?;
+  if time <= first.time {
+    return Some(first.value);
+  }
+  for pair in keyframes.windows(2) {
+    let (from, to) = (&pair[0], &pair[1]);
+    if time < to.time {
+      let p = (time - from.time) / (to.time - from.time);
+      return Some(from.value + (to.value - from.value) * from.easing.apply(p));
+    }
+  }
+  keyframes.last().map(|last| last.value)
+}
+
 /// Фильтр для визуальных эффектов
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Filter {
@@ -372,6 +551,12 @@ pub struct Transition {
   pub easing: Option<String>,
   /// Направление перехода
   pub direction: Option<String>,
+  /// ID клипа, с которого начинается переход
+  #[serde(default)]
+  pub from_clip_id: Option<String>,
+  /// ID клипа, на который выполняется переход
+  #[serde(default)]
+  pub to_clip_id: Option<String>,
 }
 
 /// Длительность перехода
@@ -730,6 +915,8 @@ mod tests {
       ffmpeg_command: Some("-filter_complex xfade=transition=fade:duration=1".to_string()),
       easing: Some("ease-in-out".to_string()),
       direction: Some("left-to-right".to_string()),
+      from_clip_id: None,
+      to_clip_id: None,
     };
 
     assert!(!transition.id.is_empty());
@@ -770,6 +957,8 @@ mod tests {
       ffmpeg_command: None,
       easing: None,
       direction: Some("top-to-bottom".to_string()),
+      from_clip_id: None,
+      to_clip_id: None,
     };
 
     // Добавляем параметры
@@ -964,6 +1153,8 @@ mod tests {
     ];
 
     assert_eq!(audio_effects.len(), 22);
+    assert!(audio_effects.iter().all(EffectType::is_audio));
+    assert!(!EffectType::Blur.is_audio());
 
     // Создаем аудио эффект
     let mut reverb = Effect::new(EffectType::AudioReverb, "Cathedral Reverb".to_string());
@@ -979,4 +1170,70 @@ mod tests {
 
     assert_eq!(reverb.parameters.len(), 3);
   }
+
+  #[test]
+  fn test_keyframe_value_at_with_easing() {
+    let keyframes = vec![
+      ParamKeyframe::new(1.0, 0.0, KeyframeEasing::EaseInOut),
+      ParamKeyframe::new(3.0, 1.0, KeyframeEasing::Hold),
+      ParamKeyframe::new(4.0, 2.0, KeyframeEasing::Linear),
+    ];
+
+    assert_eq!(keyframe_value_at(&keyframes, 0.0), Some(0.0));
+    assert_eq!(keyframe_value_at(&keyframes, 2.0), Some(0.5));
+    assert_eq!(keyframe_value_at(&keyframes, 1.5), Some(0.15625));
+    assert_eq!(keyframe_value_at(&keyframes, 3.5), Some(1.0));
+    assert_eq!(keyframe_value_at(&keyframes, 10.0), Some(2.0));
+    assert_eq!(keyframe_value_at(&[], 1.0), None);
+  }
+
+  #[test]
+  fn test_validate_keyframes() {
+    let mut effect = Effect::new(EffectType::ColorCorrection, "Ramp".to_string());
+    effect
+      .parameters
+      .insert("brightness".to_string(), EffectParameter::Float(0.0));
+    effect.keyframes.insert(
+      "brightness".to_string(),
+      vec![
+        ParamKeyframe::new(0.0, 0.0, KeyframeEasing::Linear),
+        ParamKeyframe::new(2.0, 0.5, KeyframeEasing::Linear),
+      ],
+    );
+    assert!(effect.validate_keyframes(Some(5.0)).is_ok());
+    assert!(effect.validate_keyframes(None).is_ok());
+
+    // Ключевой кадр за пределами клипа
+    assert!(effect.validate_keyframes(Some(1.5)).is_err());
+
+    // Времена не по возрастанию
+    effect
+      .keyframes
+      .get_mut("brightness")
+      .unwrap()
+      .push(ParamKeyframe::new(1.0, 0.2, KeyframeEasing::Linear));
+    assert!(effect.validate_keyframes(None).is_err());
+    effect.keyframes.get_mut("brightness").unwrap().pop();
+
+    // Нечисловой параметр
+    effect.parameters.insert(
+      "mode".to_string(),
+      EffectParameter::String("soft".to_string()),
+    );
+    effect.keyframes.insert(
+      "mode".to_string(),
+      vec![ParamKeyframe::new(0.0, 1.0, KeyframeEasing::Linear)],
+    );
+    assert!(effect.validate_keyframes(None).is_err());
+  }
+
+  #[test]
+  fn test_effect_keyframes_default_on_deserialize() {
+    let mut json = serde_json::to_value(Effect::new(EffectType::Blur, "Blur".to_string())).unwrap();
+    json.as_object_mut().unwrap().remove("keyframes");
+
+    let effect: Effect = serde_json::from_value(json).unwrap();
+    assert!(effect.keyframes.is_empty());
+    assert!(effect.keyframed_parameters().is_empty());
+  }
 }
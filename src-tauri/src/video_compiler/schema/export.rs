@@ -2,8 +2,16 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
-use super::common::Resolution;
+use super::common::{Resolution, Timecode, TimecodeSettings};
+
+pub mod compatibility;
+
+pub use compatibility::{
+  validate_export_settings, ExportAutoFix, ExportIssue, ExportIssueKind, ExportIssueSeverity,
+  ExportSettingsValidation,
+};
 
 /// Настройки проекта
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -22,6 +30,9 @@ pub struct ProjectSettings {
   pub frame_rate: f64,
   /// Соотношение сторон (для обратной совместимости)
   pub aspect_ratio: super::common::AspectRatio,
+  /// Режим счета и начальный timecode timeline
+  #[serde(default)]
+  pub timecode: TimecodeSettings,
 }
 
 /// Настройки вывода (для обратной совместимости)
@@ -61,6 +72,7 @@ impl Default for ProjectSettings {
       resolution: Resolution::full_hd(),
       frame_rate: 30.0,
       aspect_ratio: crate::video_compiler::schema::AspectRatio::default(),
+      timecode: TimecodeSettings::default(),
     }
   }
 }
@@ -112,6 +124,394 @@ pub struct ExportSettings {
   pub audio_target: Option<f32>,
   /// Пиковый уровень аудио в dBTP
   pub audio_peak: Option<f32>,
+  /// Способ вывода субтитров
+  #[serde(default)]
+  pub subtitle_mode: SubtitleMode,
+  /// Язык дорожки субтитров (ISO 639-2) для режимов Soft/Both
+  #[serde(default)]
+  pub subtitle_language: Option<String>,
+  /// Варианты вывода для многовариантного экспорта (пусто - один файл)
+  #[serde(default)]
+  pub renditions: Vec<RenditionSpec>,
+  /// Выводить варианты как HLS и создавать мастер-плейлист
+  #[serde(default)]
+  pub generate_hls: bool,
+  /// Разрядность аудио для WAV/FLAC и промежуточных кодеков (16 или 24), по умолчанию 16
+  #[serde(default)]
+  pub audio_bit_depth: Option<u8>,
+  /// Цветовые первичные координаты (`-color_primaries`), например "bt709" или "bt2020"
+  #[serde(default)]
+  pub color_primaries: Option<String>,
+  /// Передаточная характеристика (`-color_trc`), например "smpte2084"
+  #[serde(default)]
+  pub transfer_characteristics: Option<String>,
+  /// Матрица коэффициентов (`-colorspace`), например "bt2020nc"
+  #[serde(default)]
+  pub matrix_coefficients: Option<String>,
+  /// Режим HDR вывода
+  #[serde(default)]
+  pub hdr_mode: HdrMode,
+  /// Метаданные мастеринга для HDR10 (опционально)
+  #[serde(default)]
+  pub hdr_metadata: Option<HdrMetadata>,
+  /// Рендерить вложенные последовательности в промежуточные файлы вместо
+  /// разворачивания их клипов на основной timeline
+  #[serde(default)]
+  pub render_nested_as_intermediate: bool,
+  /// Шаблон имени выходного файла, например "{project}_{date}_{resolution}"
+  #[serde(default)]
+  pub filename_template: Option<String>,
+  /// Имя последнего примененного пресета экспорта (токен `{preset}`)
+  #[serde(default)]
+  pub export_preset: Option<String>,
+  /// Переиспользовать сегменты предыдущих экспортов. `None` - по режиму
+  /// контроля битрейта, см. [`ExportSettings::segment_cache_enabled`]
+  #[serde(default)]
+  pub segment_cache: Option<bool>,
+  /// Водяной знак поверх всего видео (логотип в углу кадра)
+  #[serde(default)]
+  pub watermark: Option<WatermarkConfig>,
+  /// Впечатать бегущий timecode в видео
+  #[serde(default)]
+  pub timecode_burn_in: Option<TimecodeBurnIn>,
+  /// Записать дорожку timecode в контейнер (только MOV и MXF)
+  #[serde(default)]
+  pub write_timecode_track: bool,
+  /// Дополнительно записать каждый аудио трек отдельным файлом (стемы)
+  #[serde(default)]
+  pub export_stems: bool,
+  /// Формат файлов аудио стемов
+  #[serde(default)]
+  pub stems_format: StemFormat,
+  /// Реакция рендера на отсутствующие шрифты, LUT и другие внешние ресурсы
+  #[serde(default)]
+  pub missing_assets: MissingAssetPolicy,
+  /// Отчет о рендере рядом с выходным файлом
+  #[serde(default)]
+  pub render_report: RenderReportSettings,
+}
+
+impl ExportSettings {
+  /// Применить именованный пресет экспорта ("youtube", "instagram", "twitter").
+  /// Возвращает false для неизвестного пресета, настройки при этом не меняются.
+  pub fn apply_preset(&mut self, preset: &str) -> bool {
+    let (video_bitrate, audio_bitrate, quality) = match preset {
+      "youtube" => (8000, 192, 90),
+      "instagram" => (5000, 128, 85),
+      "twitter" => (6000, 128, 85),
+      _ => return false,
+    };
+    self.format = OutputFormat::Mp4;
+    self.video_bitrate = video_bitrate;
+    self.audio_bitrate = audio_bitrate;
+    self.quality = quality;
+    self.export_preset = Some(preset.to_string());
+    true
+  }
+
+  /// Включен ли кэш сегментов рендера.
+  ///
+  /// По умолчанию включен для CRF и VBR и выключен для строгого CBR: на
+  /// стыках склеенных сегментов буфер VBV не соблюдается.
+  pub fn segment_cache_enabled(&self) -> bool {
+    self.segment_cache.unwrap_or_else(|| {
+      !self
+        .rate_control_mode
+        .as_deref()
+        .is_some_and(|mode| mode.eq_ignore_ascii_case("cbr"))
+    })
+  }
+
+  /// Итоговые цветовые теги: явно заданные поля важнее значений режима HDR
+  pub fn color_tags(&self) -> ColorTags {
+    let defaults = self.hdr_mode.default_color_tags();
+    ColorTags {
+      primaries: self.color_primaries.clone().or(defaults.primaries),
+      transfer: self.transfer_characteristics.clone().or(defaults.transfer),
+      matrix: self.matrix_coefficients.clone().or(defaults.matrix),
+    }
+  }
+}
+
+/// Цветовые теги выходного видео
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ColorTags {
+  pub primaries: Option<String>,
+  pub transfer: Option<String>,
+  pub matrix: Option<String>,
+}
+
+impl ColorTags {
+  fn new(primaries: &str, transfer: &str, matrix: &str) -> Self {
+    Self {
+      primaries: Some(primaries.to_string()),
+      transfer: Some(transfer.to_string()),
+      matrix: Some(matrix.to_string()),
+    }
+  }
+}
+
+/// Режим HDR вывода
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HdrMode {
+  /// Без преобразования, цветовые теги только из явных полей
+  #[default]
+  None,
+  /// HDR10 (PQ, BT.2020)
+  Hdr10,
+  /// HLG (BT.2020)
+  Hlg,
+  /// Тонмаппинг HDR исходников в SDR (BT.709)
+  ToneMapToSdr,
+}
+
+impl HdrMode {
+  /// Выход кодируется в HDR: нужен 10-битный кодек и контейнер с поддержкой HDR
+  pub fn is_hdr_output(self) -> bool {
+    matches!(self, Self::Hdr10 | Self::Hlg)
+  }
+
+  /// Цветовые теги, соответствующие режиму
+  pub fn default_color_tags(self) -> ColorTags {
+    match self {
+      Self::None => ColorTags::default(),
+      Self::Hdr10 => ColorTags::new("bt2020", "smpte2084", "bt2020nc"),
+      Self::Hlg => ColorTags::new("bt2020", "arib-std-b67", "bt2020nc"),
+      Self::ToneMapToSdr => ColorTags::new("bt709", "bt709", "bt709"),
+    }
+  }
+}
+
+/// Статические метаданные HDR10 (SMPTE ST 2086 и content light level)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HdrMetadata {
+  /// Mastering display в формате x265, например
+  /// "G(13250,34500)B(7500,3000)R(34000,16000)WP(15635,16450)L(10000000,1)"
+  pub master_display: Option<String>,
+  /// Максимальная яркость контента (MaxCLL), нит
+  pub max_cll: Option<u32>,
+  /// Максимальная средняя яркость кадра (MaxFALL), нит
+  pub max_fall: Option<u32>,
+}
+
+/// Форматы изображений для водяного знака
+pub const WATERMARK_IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "webp", "bmp", "gif"];
+
+/// Положение водяного знака в кадре
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum WatermarkPosition {
+  TopLeft,
+  TopRight,
+  BottomLeft,
+  #[default]
+  BottomRight,
+  /// Положение в процентах свободного места по горизонтали и вертикали
+  /// (0 - левый/верхний край, 100 - правый/нижний)
+  Custom {
+    x: f32,
+    y: f32,
+  },
+}
+
+/// Водяной знак экспорта
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WatermarkConfig {
+  /// Путь к изображению (PNG, JPEG, WebP, BMP или GIF)
+  pub image_path: String,
+  /// Положение в кадре
+  #[serde(default)]
+  pub position: WatermarkPosition,
+  /// Ширина относительно ширины кадра (0-1]
+  pub scale: f32,
+  /// Непрозрачность (0-1]
+  pub opacity: f32,
+  /// Отступ от края кадра для угловых положений, px
+  #[serde(default)]
+  pub margin_px: u32,
+  /// Начало показа на timeline, с (`None` - с начала)
+  #[serde(default)]
+  pub start_time: Option<f64>,
+  /// Конец показа на timeline, с (`None` - до конца)
+  #[serde(default)]
+  pub end_time: Option<f64>,
+  /// Показывать в превью кадров и пререндере для проверки положения
+  #[serde(default)]
+  pub show_in_preview: bool,
+}
+
+impl WatermarkConfig {
+  /// Проверка параметров и наличия изображения
+  pub fn validate(&self) -> Result<(), String> {
+    let path = Path::new(&self.image_path);
+    let extension = path
+      .extension()
+      .map(|ext| ext.to_string_lossy().to_lowercase())
+      .unwrap_or_default();
+    if !WATERMARK_IMAGE_EXTENSIONS.contains(&extension.as_str()) {
+      return Err(format!(
+        "Неподдерживаемый формат водяного знака: '{}'",
+        self.image_path
+      ));
+    }
+    if !path.is_file() {
+      return Err(format!(
+        "Изображение водяного знака не найдено: '{}'",
+        self.image_path
+      ));
+    }
+
+    if !(self.scale > 0.0 && self.scale <= 1.0) {
+      return Err("Масштаб водяного знака должен быть в диапазоне (0, 1]".to_string());
+    }
+    if !(self.opacity > 0.0 && self.opacity <= 1.0) {
+      return Err("Непрозрачность водяного знака должна быть в диапазоне (0, 1]".to_string());
+    }
+    if let WatermarkPosition::Custom { x, y } = self.position {
+      if !(0.0..=100.0).contains(&x) || !(0.0..=100.0).contains(&y) {
+        return Err("Положение водяного знака должно быть в диапазоне 0-100%".to_string());
+      }
+    }
+
+    if let (Some(start), Some(end)) = (self.start_time, self.end_time) {
+      if end <= start {
+        return Err("Конец показа водяного знака должен быть позже начала".to_string());
+      }
+    }
+    if self.start_time.is_some_and(|t| t < 0.0) || self.end_time.is_some_and(|t| t < 0.0) {
+      return Err("Время показа водяного знака не может быть отрицательным".to_string());
+    }
+
+    Ok(())
+  }
+
+  /// Анимированный водяной знак (GIF), который нужно зацикливать
+  pub fn is_animated(&self) -> bool {
+    Path::new(&self.image_path)
+      .extension()
+      .is_some_and(|ext| ext.eq_ignore_ascii_case("gif"))
+  }
+
+  /// Координаты `x`/`y` для фильтра overlay
+  pub fn overlay_position(&self) -> String {
+    let m = self.margin_px;
+    match self.position {
+      WatermarkPosition::TopLeft => format!("x={m}:y={m}"),
+      WatermarkPosition::TopRight => format!("x=W-w-{m}:y={m}"),
+      WatermarkPosition::BottomLeft => format!("x={m}:y=H-h-{m}"),
+      WatermarkPosition::BottomRight => format!("x=W-w-{m}:y=H-h-{m}"),
+      WatermarkPosition::Custom { x, y } => {
+        format!("x=(W-w)*{}:y=(H-h)*{}", x / 100.0, y / 100.0)
+      }
+    }
+  }
+
+  /// Выражение `enable` для показа в заданном интервале
+  pub fn enable_expression(&self) -> Option<String> {
+    match (self.start_time, self.end_time) {
+      (Some(start), Some(end)) => Some(format!("between(t,{start},{end})")),
+      (Some(start), None) => Some(format!("gte(t,{start})")),
+      (None, Some(end)) => Some(format!("lte(t,{end})")),
+      (None, None) => None,
+    }
+  }
+
+  /// Виден ли водяной знак в момент `time` timeline
+  pub fn is_visible_at(&self, time: f64) -> bool {
+    self.start_time.is_none_or(|start| time >= start) && self.end_time.is_none_or(|end| time <= end)
+  }
+
+  /// Копия с интервалом показа относительно отрезка, начинающегося в
+  /// `offset`. Возвращает `None`, если водяной знак в отрезке не виден.
+  pub fn shifted(&self, offset: f64, duration: f64) -> Option<Self> {
+    if self.end_time.is_some_and(|end| end <= offset)
+      || self
+        .start_time
+        .is_some_and(|start| start >= offset + duration)
+    {
+      return None;
+    }
+    Some(Self {
+      start_time: self
+        .start_time
+        .map(|start| start - offset)
+        .filter(|start| *start > 0.0),
+      end_time: self
+        .end_time
+        .map(|end| end - offset)
+        .filter(|end| *end < duration),
+      ..self.clone()
+    })
+  }
+}
+
+/// Положение впечатанного timecode в кадре
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimecodePosition {
+  TopLeft,
+  TopCenter,
+  TopRight,
+  BottomLeft,
+  #[default]
+  BottomCenter,
+  BottomRight,
+}
+
+/// Какой timecode впечатывается в видео
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimecodeSource {
+  /// Timecode timeline проекта (record)
+  #[default]
+  Record,
+  /// Исходный timecode клипов (source); клипы без него не подписываются
+  Source,
+}
+
+/// Впечатывание timecode через drawtext
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct TimecodeBurnIn {
+  /// Источник timecode
+  pub source: TimecodeSource,
+  /// Положение в кадре
+  pub position: TimecodePosition,
+  /// Размер шрифта, px
+  pub font_size: u32,
+  /// Отступ от края кадра, px
+  pub margin_px: u32,
+}
+
+impl Default for TimecodeBurnIn {
+  fn default() -> Self {
+    Self {
+      source: TimecodeSource::Record,
+      position: TimecodePosition::BottomCenter,
+      font_size: 36,
+      margin_px: 24,
+    }
+  }
+}
+
+impl TimecodeBurnIn {
+  /// Фильтр drawtext с timecode, который начинается с `start` и
+  /// увеличивается на каждом кадре
+  pub fn drawtext_filter(&self, start: &Timecode) -> String {
+    let m = self.margin_px;
+    let (x, y) = match self.position {
+      TimecodePosition::TopLeft => (m.to_string(), m.to_string()),
+      TimecodePosition::TopCenter => ("(w-tw)/2".to_string(), m.to_string()),
+      TimecodePosition::TopRight => (format!("w-tw-{m}"), m.to_string()),
+      TimecodePosition::BottomLeft => (m.to_string(), format!("h-th-{m}")),
+      TimecodePosition::BottomCenter => ("(w-tw)/2".to_string(), format!("h-th-{m}")),
+      TimecodePosition::BottomRight => (format!("w-tw-{m}"), format!("h-th-{m}")),
+    };
+    format!(
+      "drawtext=timecode='{}':rate={}:fontsize={}:fontcolor=white:box=1:boxcolor=black@0.5:boxborderw=8:x={x}:y={y}",
+      start.to_string().replace(':', "\\:"),
+      start.rate.ffmpeg_rate(),
+      self.font_size
+    )
+  }
 }
 
 impl Default for ExportSettings {
@@ -142,6 +542,135 @@ impl Default for ExportSettings {
       normalize_audio: Some(false),
       audio_target: Some(-23.0),
       audio_peak: Some(-1.0),
+      subtitle_mode: SubtitleMode::Burn,
+      subtitle_language: None,
+      renditions: Vec::new(),
+      generate_hls: false,
+      audio_bit_depth: None,
+      color_primaries: None,
+      transfer_characteristics: None,
+      matrix_coefficients: None,
+      hdr_mode: HdrMode::None,
+      hdr_metadata: None,
+      render_nested_as_intermediate: false,
+      filename_template: None,
+      export_preset: None,
+      segment_cache: None,
+      watermark: None,
+      timecode_burn_in: None,
+      write_timecode_track: false,
+      export_stems: false,
+      stems_format: StemFormat::Wav,
+      missing_assets: MissingAssetPolicy::Warn,
+      render_report: RenderReportSettings::default(),
+    }
+  }
+}
+
+/// Вариант выходного файла при многовариантном экспорте
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RenditionSpec {
+  /// Разрешение варианта
+  pub resolution: Resolution,
+  /// Битрейт видео (kbps)
+  pub video_bitrate: u32,
+  /// Суффикс имени файла, например "_720p"
+  pub suffix: String,
+}
+
+impl RenditionSpec {
+  /// Путь к файлу варианта: суффикс добавляется к имени основного файла.
+  /// Для HLS вместо исходного расширения используется `.m3u8`.
+  pub fn output_path(&self, base: &Path, hls: bool) -> PathBuf {
+    let stem = base
+      .file_stem()
+      .map(|stem| stem.to_string_lossy().into_owned())
+      .unwrap_or_default();
+    let extension = if hls {
+      Some("m3u8".to_string())
+    } else {
+      base
+        .extension()
+        .map(|ext| ext.to_string_lossy().into_owned())
+    };
+
+    let file_name = match extension {
+      Some(ext) => format!("{stem}{}.{ext}", self.suffix),
+      None => format!("{stem}{}", self.suffix),
+    };
+    base.with_file_name(file_name)
+  }
+}
+
+/// Способ вывода субтитров при экспорте
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SubtitleMode {
+  /// Впечатать в видео через drawtext
+  #[default]
+  Burn,
+  /// Отдельной дорожкой субтитров в контейнере
+  Soft,
+  /// Впечатать и добавить дорожку
+  Both,
+}
+
+impl SubtitleMode {
+  /// Субтитры впечатываются в кадр
+  pub fn burns(self) -> bool {
+    matches!(self, Self::Burn | Self::Both)
+  }
+
+  /// Субтитры добавляются отдельной дорожкой
+  pub fn muxes(self) -> bool {
+    matches!(self, Self::Soft | Self::Both)
+  }
+}
+
+/// Формат файлов аудио стемов
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StemFormat {
+  /// Несжатый PCM
+  #[default]
+  Wav,
+  /// Сжатие без потерь
+  Flac,
+}
+
+impl StemFormat {
+  /// Расширение файла стема
+  pub fn extension(self) -> &'static str {
+    match self {
+      Self::Wav => "wav",
+      Self::Flac => "flac",
+    }
+  }
+}
+
+/// Реакция рендера на отсутствующие внешние ресурсы проекта
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MissingAssetPolicy {
+  /// Рендерить с предупреждениями (ресурсы заменяются на ходу)
+  #[default]
+  Warn,
+  /// Остановить рендер до запуска FFmpeg
+  Fail,
+}
+
+/// Настройки отчета о рендере (`<output>.report.json`)
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(default)]
+pub struct RenderReportSettings {
+  /// Сохранять отчет после экспорта
+  pub enabled: bool,
+  /// Измерять громкость результата отдельным проходом `loudnorm`
+  pub measure_loudness: bool,
+}
+
+impl Default for RenderReportSettings {
+  fn default() -> Self {
+    Self {
+      enabled: true,
+      measure_loudness: true,
     }
   }
 }
@@ -155,9 +684,125 @@ pub enum OutputFormat {
   Mkv,
   WebM,
   Gif,
+  /// Только аудио: микс аудио треков в MP3
+  Mp3,
+  /// Только аудио: микс аудио треков в несжатый WAV
+  Wav,
+  /// Только аудио: микс аудио треков в FLAC
+  Flac,
+  /// Промежуточный кодек Apple ProRes в MOV для передачи в DaVinci/Premiere
+  MovProRes(ProResProfile),
+  /// Промежуточный кодек Avid DNxHR в MXF, аудио всегда PCM
+  MxfDnxhr(DnxhrProfile),
   Custom(String),
 }
 
+impl OutputFormat {
+  /// Формат содержит только аудио: видео треки не участвуют в рендере
+  pub fn is_audio_only(&self) -> bool {
+    matches!(self, Self::Mp3 | Self::Wav | Self::Flac)
+  }
+
+  /// Контейнер поддерживает дорожку timecode
+  pub fn supports_timecode_track(&self) -> bool {
+    matches!(self, Self::Mov | Self::MovProRes(_) | Self::MxfDnxhr(_))
+  }
+
+  /// Промежуточный кодек для монтажа: без битрейта/CRF и аппаратного ускорения
+  pub fn is_intermediate(&self) -> bool {
+    matches!(self, Self::MovProRes(_) | Self::MxfDnxhr(_))
+  }
+
+  /// Расширение выходного файла без точки
+  pub fn extension(&self) -> &str {
+    match self {
+      Self::Mp4 => "mp4",
+      Self::Avi => "avi",
+      Self::Mov => "mov",
+      Self::Mkv => "mkv",
+      Self::WebM => "webm",
+      Self::Gif => "gif",
+      Self::Mp3 => "mp3",
+      Self::Wav => "wav",
+      Self::Flac => "flac",
+      Self::MovProRes(_) => "mov",
+      Self::MxfDnxhr(_) => "mxf",
+      Self::Custom(format) => format,
+    }
+  }
+}
+
+/// Профиль Apple ProRes (`prores_ks`)
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProResProfile {
+  Proxy,
+  Lt,
+  /// ProRes 422
+  #[serde(rename = "422")]
+  Standard,
+  /// ProRes 422 HQ
+  Hq,
+  /// ProRes 4444 с альфа-каналом
+  #[serde(rename = "4444")]
+  ProRes4444,
+}
+
+impl ProResProfile {
+  /// Значение `-profile:v` для `prores_ks`
+  pub fn ffmpeg_profile(self) -> &'static str {
+    match self {
+      Self::Proxy => "0",
+      Self::Lt => "1",
+      Self::Standard => "2",
+      Self::Hq => "3",
+      Self::ProRes4444 => "4",
+    }
+  }
+
+  /// Пиксельный формат: 10 бит 4:2:2, для 4444 - 4:4:4 с альфой
+  pub fn pixel_format(self) -> &'static str {
+    match self {
+      Self::ProRes4444 => "yuva444p10le",
+      _ => "yuv422p10le",
+    }
+  }
+}
+
+/// Профиль Avid DNxHR (`dnxhd`)
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DnxhrProfile {
+  /// Low Bandwidth
+  Lb,
+  /// Standard Quality
+  Sq,
+  /// High Quality
+  Hq,
+  /// High Quality 10 бит
+  Hqx,
+}
+
+impl DnxhrProfile {
+  /// Значение `-profile:v` для `dnxhd`
+  pub fn ffmpeg_profile(self) -> &'static str {
+    match self {
+      Self::Lb => "dnxhr_lb",
+      Self::Sq => "dnxhr_sq",
+      Self::Hq => "dnxhr_hq",
+      Self::Hqx => "dnxhr_hqx",
+    }
+  }
+
+  /// Пиксельный формат: HQX 10 бит, остальные профили 8 бит, все 4:2:2
+  pub fn pixel_format(self) -> &'static str {
+    match self {
+      Self::Hqx => "yuv422p10le",
+      _ => "yuv422p",
+    }
+  }
+}
+
 /// Настройки превью
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PreviewSettings {
@@ -190,6 +835,26 @@ pub enum PreviewFormat {
   WebP,
 }
 
+impl PreviewFormat {
+  /// Кодек FFmpeg для изображения
+  pub fn ffmpeg_codec(&self) -> &'static str {
+    match self {
+      Self::Jpeg => "mjpeg",
+      Self::Png => "png",
+      Self::WebP => "libwebp",
+    }
+  }
+
+  /// Допустимые расширения файла
+  pub fn extensions(&self) -> &'static [&'static str] {
+    match self {
+      Self::Jpeg => &["jpg", "jpeg"],
+      Self::Png => &["png"],
+      Self::WebP => &["webp"],
+    }
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -269,6 +934,11 @@ mod tests {
       OutputFormat::Mkv,
       OutputFormat::WebM,
       OutputFormat::Gif,
+      OutputFormat::Mp3,
+      OutputFormat::Wav,
+      OutputFormat::Flac,
+      OutputFormat::MovProRes(ProResProfile::Hq),
+      OutputFormat::MxfDnxhr(DnxhrProfile::Hqx),
       OutputFormat::Custom("custom_format".to_string()),
     ];
 
@@ -280,9 +950,68 @@ mod tests {
         OutputFormat::Mkv => {}  // Valid format variant,
         OutputFormat::WebM => {} // Valid format variant,
         OutputFormat::Gif => {}  // Valid format variant,
+        OutputFormat::Mp3 | OutputFormat::Wav | OutputFormat::Flac => {} // Audio-only variants
+        OutputFormat::MovProRes(_) | OutputFormat::MxfDnxhr(_) => {} // Intermediate codecs
         OutputFormat::Custom(name) => assert_eq!(name, "custom_format"),
       }
     }
+
+    assert!(OutputFormat::Flac.is_audio_only());
+    assert!(!OutputFormat::Mp4.is_audio_only());
+    assert!(OutputFormat::MxfDnxhr(DnxhrProfile::Lb).is_intermediate());
+    assert!(!OutputFormat::Mov.is_intermediate());
+    assert_eq!(OutputFormat::MxfDnxhr(DnxhrProfile::Lb).extension(), "mxf");
+  }
+
+  #[test]
+  fn test_intermediate_format_serialization() {
+    let format: OutputFormat = serde_json::from_str(r#"{"MovProRes":"4444"}"#).unwrap();
+    assert!(matches!(
+      format,
+      OutputFormat::MovProRes(ProResProfile::ProRes4444)
+    ));
+    assert_eq!(
+      serde_json::to_string(&OutputFormat::MxfDnxhr(DnxhrProfile::Sq)).unwrap(),
+      r#"{"MxfDnxhr":"sq"}"#
+    );
+  }
+
+  #[test]
+  #[allow(clippy::field_reassign_with_default)]
+  fn test_color_tags_resolution() {
+    let mut settings = ExportSettings::default();
+    assert_eq!(settings.color_tags(), ColorTags::default());
+
+    settings.hdr_mode = HdrMode::Hlg;
+    let tags = settings.color_tags();
+    assert_eq!(tags.primaries.as_deref(), Some("bt2020"));
+    assert_eq!(tags.transfer.as_deref(), Some("arib-std-b67"));
+
+    // Явно заданное поле переопределяет значение режима
+    settings.matrix_coefficients = Some("bt2020c".to_string());
+    assert_eq!(settings.color_tags().matrix.as_deref(), Some("bt2020c"));
+
+    assert!(HdrMode::Hdr10.is_hdr_output());
+    assert!(!HdrMode::ToneMapToSdr.is_hdr_output());
+  }
+
+  #[test]
+  fn test_color_settings_default_when_missing() {
+    let mut json = serde_json::to_value(ExportSettings::default()).unwrap();
+    let object = json.as_object_mut().unwrap();
+    for field in [
+      "color_primaries",
+      "transfer_characteristics",
+      "matrix_coefficients",
+      "hdr_mode",
+      "hdr_metadata",
+    ] {
+      object.remove(field);
+    }
+
+    let settings: ExportSettings = serde_json::from_value(json).unwrap();
+    assert_eq!(settings.hdr_mode, HdrMode::None);
+    assert!(settings.color_primaries.is_none());
   }
 
   #[test]
@@ -505,6 +1234,36 @@ mod tests {
     assert_eq!(settings.duration, 3600.5);
   }
 
+  #[test]
+  fn test_apply_named_preset() {
+    let mut settings = ExportSettings {
+      format: OutputFormat::Mov,
+      ..ExportSettings::default()
+    };
+    assert!(settings.apply_preset("youtube"));
+    assert!(matches!(settings.format, OutputFormat::Mp4));
+    assert_eq!(settings.video_bitrate, 8000);
+    assert_eq!(settings.quality, 90);
+
+    assert!(!settings.apply_preset("vimeo"));
+    assert_eq!(settings.video_bitrate, 8000);
+  }
+
+  #[test]
+  fn test_segment_cache_default_depends_on_rate_control() {
+    let mut settings = ExportSettings::default();
+    assert!(settings.segment_cache_enabled());
+
+    settings.rate_control_mode = Some("crf".to_string());
+    assert!(settings.segment_cache_enabled());
+
+    settings.rate_control_mode = Some("cbr".to_string());
+    assert!(!settings.segment_cache_enabled());
+
+    settings.segment_cache = Some(true);
+    assert!(settings.segment_cache_enabled());
+  }
+
   #[test]
   fn test_export_settings_presets() {
     let mut settings = ExportSettings::default();
@@ -575,4 +1334,122 @@ mod tests {
       assert_eq!(settings.audio_peak, Some(peak));
     }
   }
+
+  fn test_watermark(position: WatermarkPosition) -> WatermarkConfig {
+    WatermarkConfig {
+      image_path: "/tmp/logo.png".to_string(),
+      position,
+      scale: 0.1,
+      opacity: 0.8,
+      margin_px: 16,
+      start_time: None,
+      end_time: None,
+      show_in_preview: false,
+    }
+  }
+
+  #[test]
+  fn test_watermark_overlay_position_corners() {
+    let cases = [
+      (WatermarkPosition::TopLeft, "x=16:y=16"),
+      (WatermarkPosition::TopRight, "x=W-w-16:y=16"),
+      (WatermarkPosition::BottomLeft, "x=16:y=H-h-16"),
+      (WatermarkPosition::BottomRight, "x=W-w-16:y=H-h-16"),
+      (
+        WatermarkPosition::Custom { x: 50.0, y: 25.0 },
+        "x=(W-w)*0.5:y=(H-h)*0.25",
+      ),
+    ];
+
+    for (position, expected) in cases {
+      assert_eq!(test_watermark(position).overlay_position(), expected);
+    }
+  }
+
+  #[test]
+  fn test_watermark_enable_expression() {
+    let mut watermark = test_watermark(WatermarkPosition::default());
+    assert_eq!(watermark.enable_expression(), None);
+
+    watermark.start_time = Some(2.0);
+    assert_eq!(watermark.enable_expression().unwrap(), "gte(t,2)");
+
+    watermark.end_time = Some(7.5);
+    assert_eq!(watermark.enable_expression().unwrap(), "between(t,2,7.5)");
+    assert!(!watermark.is_visible_at(1.0));
+    assert!(watermark.is_visible_at(5.0));
+
+    watermark.start_time = None;
+    assert_eq!(watermark.enable_expression().unwrap(), "lte(t,7.5)");
+  }
+
+  #[test]
+  fn test_watermark_shifted_to_segment() {
+    let mut watermark = test_watermark(WatermarkPosition::TopLeft);
+    watermark.start_time = Some(12.0);
+    watermark.end_time = Some(30.0);
+
+    // Отрезок 10-20 с: показ с 2 с до конца отрезка
+    let shifted = watermark.shifted(10.0, 10.0).unwrap();
+    assert_eq!(shifted.start_time, Some(2.0));
+    assert_eq!(shifted.end_time, None);
+
+    assert!(watermark.shifted(0.0, 10.0).is_none());
+    assert!(watermark.shifted(30.0, 5.0).is_none());
+  }
+
+  #[test]
+  fn test_watermark_validation() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let image = temp_dir.path().join("logo.png");
+    std::fs::write(&image, b"png").unwrap();
+
+    let mut watermark = test_watermark(WatermarkPosition::BottomRight);
+    watermark.image_path = image.to_string_lossy().to_string();
+    assert!(watermark.validate().is_ok());
+    assert!(!watermark.is_animated());
+
+    let mut missing = watermark.clone();
+    missing.image_path = temp_dir
+      .path()
+      .join("missing.png")
+      .to_string_lossy()
+      .to_string();
+    assert!(missing.validate().unwrap_err().contains("не найдено"));
+
+    let mut unsupported = watermark.clone();
+    unsupported.image_path = "/tmp/logo.svg".to_string();
+    assert!(unsupported.validate().unwrap_err().contains("формат"));
+
+    let mut transparent = watermark.clone();
+    transparent.opacity = 0.0;
+    assert!(transparent.validate().is_err());
+
+    let mut reversed = watermark.clone();
+    reversed.start_time = Some(5.0);
+    reversed.end_time = Some(1.0);
+    assert!(reversed.validate().is_err());
+
+    let mut outside = watermark;
+    outside.position = WatermarkPosition::Custom { x: 120.0, y: 0.0 };
+    assert!(outside.validate().is_err());
+  }
+
+  #[test]
+  fn test_watermark_deserialization() {
+    let json = r#"{
+      "image_path": "/tmp/logo.gif",
+      "position": {"custom": {"x": 10.0, "y": 90.0}},
+      "scale": 0.2,
+      "opacity": 1.0
+    }"#;
+    let watermark: WatermarkConfig = serde_json::from_str(json).unwrap();
+    assert_eq!(
+      watermark.position,
+      WatermarkPosition::Custom { x: 10.0, y: 90.0 }
+    );
+    assert_eq!(watermark.margin_px, 0);
+    assert!(!watermark.show_in_preview);
+    assert!(watermark.is_animated());
+  }
 }
@@ -0,0 +1,1121 @@
+//! Матрица совместимости настроек экспорта
+//!
+//! Проверяет сочетания контейнера, видео- и аудиокодека, разрешения, частоты
+//! кадров и режима битрейта до запуска FFmpeg, который отвергает их только в
+//! середине рендера. Каждое нарушение описывается [`ExportIssue`] с
+//! предложением исправления; безопасные замены (аппаратный кодировщик на
+//! программный, CRF на CQ, нечетное разрешение на четное) применяются
+//! автоматически по флагу `apply_auto_fixes`.
+
+use serde::{Deserialize, Serialize};
+
+use super::{OutputFormat, ProjectSettings};
+use crate::video_compiler::services::ffmpeg_capabilities::FfmpegCapabilities;
+
+/// Аргументы FFmpeg, задающие видеокодек
+const VIDEO_CODEC_ARGS: &[&str] = &["-c:v", "-vcodec", "-codec:v"];
+
+/// Аргументы FFmpeg, задающие аудиокодек
+const AUDIO_CODEC_ARGS: &[&str] = &["-c:a", "-acodec", "-codec:a"];
+
+/// Допустимые семейства видеокодеков по контейнеру
+const CONTAINER_VIDEO_CODECS: &[(&str, &[&str])] = &[
+  ("mp4", &["h264", "hevc", "av1", "mpeg4", "vp9"]),
+  ("mov", &["h264", "hevc", "prores", "dnxhd", "mpeg4"]),
+  ("webm", &["vp8", "vp9", "av1"]),
+  ("avi", &["h264", "mpeg4"]),
+  (
+    "matroska",
+    &[
+      "h264", "hevc", "vp8", "vp9", "av1", "prores", "dnxhd", "mpeg4",
+    ],
+  ),
+  ("mxf", &["dnxhd"]),
+  ("gif", &["gif"]),
+];
+
+/// Допустимые, но ненадежные сочетания контейнера и видеокодека
+const CONTAINER_VIDEO_WARNINGS: &[(&str, &str, &str)] = &[(
+  "mp4",
+  "vp9",
+  "VP9 в MP4 поддерживается не всеми сборками FFmpeg и плеерами",
+)];
+
+/// Допустимые семейства аудиокодеков по контейнеру
+const CONTAINER_AUDIO_CODECS: &[(&str, &[&str])] = &[
+  ("mp4", &["aac", "mp3", "ac3", "alac", "opus", "flac"]),
+  ("mov", &["aac", "mp3", "ac3", "alac", "pcm"]),
+  ("webm", &["opus", "vorbis"]),
+  ("avi", &["mp3", "aac", "ac3", "pcm"]),
+  (
+    "matroska",
+    &["aac", "mp3", "ac3", "alac", "opus", "vorbis", "flac", "pcm"],
+  ),
+  ("mxf", &["pcm"]),
+  ("mp3", &["mp3"]),
+  ("wav", &["pcm"]),
+  ("flac", &["flac"]),
+];
+
+/// Максимальная частота кадров аппаратных кодировщиков
+const ENCODER_FPS_LIMITS: &[(&str, f64)] = &[
+  ("v4l2m2m", 60.0),
+  ("vaapi", 60.0),
+  ("qsv", 120.0),
+  ("amf", 120.0),
+  ("videotoolbox", 120.0),
+  ("nvenc", 240.0),
+];
+
+/// Режим постоянного качества, заменяющий CRF у аппаратных кодировщиков
+const HARDWARE_QUALITY_MODES: &[(&str, &str)] = &[
+  ("nvenc", "cq"),
+  ("qsv", "global_quality"),
+  ("amf", "cqp"),
+  ("vaapi", "cqp"),
+  ("videotoolbox", "constant_quality"),
+  ("v4l2m2m", "vbr"),
+];
+
+/// GIF хранит задержку кадра в сотых долях секунды
+const GIF_MAX_FPS: f64 = 50.0;
+
+/// Серьезность нарушения
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportIssueSeverity {
+  /// Экспорт завершится ошибкой FFmpeg
+  Error,
+  /// Экспорт возможен, но результат может не воспроизводиться
+  Warning,
+  /// Нарушение исправляется автоматически заменой настройки
+  AutoFixable,
+}
+
+/// Вид нарушения
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportIssueKind {
+  /// Нулевое разрешение или некорректная частота кадров
+  InvalidVideoParameters,
+  /// Видеокодек не поддерживается контейнером
+  ContainerVideoCodec,
+  /// Аудиокодек не поддерживается контейнером
+  ContainerAudioCodec,
+  /// Размер кадра не кратен шагу субдискретизации формата пикселей
+  OddDimensions,
+  /// Частота кадров выше предела кодировщика
+  FrameRateLimit,
+  /// Режим битрейта недоступен для кодировщика
+  RateControl,
+  /// Кодировщик отсутствует в сборке FFmpeg
+  EncoderUnavailable,
+  /// Мюксер контейнера отсутствует в сборке FFmpeg
+  MuxerUnavailable,
+  /// Формат пикселей отсутствует в сборке FFmpeg
+  PixelFormatUnavailable,
+}
+
+/// Автоматическое исправление настроек
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExportAutoFix {
+  /// Заменить видеокодировщик
+  VideoEncoder { encoder: String },
+  /// Убрать явный аудиокодек из `ffmpeg_args`, используется кодек контейнера
+  RemoveAudioCodecOverride,
+  /// Изменить разрешение экспорта
+  Resolution { width: u32, height: u32 },
+  /// Изменить разрешение варианта многовариантного экспорта
+  RenditionResolution {
+    index: usize,
+    width: u32,
+    height: u32,
+  },
+  /// Заменить режим контроля битрейта
+  RateControl { mode: String },
+}
+
+impl ExportAutoFix {
+  /// Применить исправление к настройкам
+  pub fn apply(&self, settings: &mut ProjectSettings) {
+    match self {
+      Self::VideoEncoder { encoder } => set_video_encoder(settings, encoder),
+      Self::RemoveAudioCodecOverride => {
+        let args = &mut settings.export.ffmpeg_args;
+        while let Some(position) = arg_position(args, AUDIO_CODEC_ARGS) {
+          args.drain(position..(position + 2).min(args.len()));
+        }
+      }
+      Self::Resolution { width, height } => {
+        settings.resolution.width = *width;
+        settings.resolution.height = *height;
+      }
+      Self::RenditionResolution {
+        index,
+        width,
+        height,
+      } => {
+        if let Some(rendition) = settings.export.renditions.get_mut(*index) {
+          rendition.resolution.width = *width;
+          rendition.resolution.height = *height;
+        }
+      }
+      Self::RateControl { mode } => {
+        settings.export.rate_control_mode = Some(mode.clone());
+      }
+    }
+  }
+}
+
+/// Нарушение совместимости настроек экспорта
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportIssue {
+  pub kind: ExportIssueKind,
+  pub severity: ExportIssueSeverity,
+  /// Описание нарушения
+  pub message: String,
+  /// Что изменить в диалоге экспорта
+  pub suggested_fix: String,
+  /// Исправление для нарушений с серьезностью `AutoFixable`
+  pub auto_fix: Option<ExportAutoFix>,
+}
+
+/// Результат проверки настроек экспорта
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportSettingsValidation {
+  pub issues: Vec<ExportIssue>,
+  /// Настройки с примененными автоисправлениями (если они запрошены)
+  pub fixed_settings: Option<ProjectSettings>,
+}
+
+impl ExportSettingsValidation {
+  /// Есть ли нарушения, которые не исправляются автоматически
+  pub fn has_errors(&self) -> bool {
+    self
+      .issues
+      .iter()
+      .any(|issue| issue.severity == ExportIssueSeverity::Error)
+  }
+
+  /// Нарушения заданной серьезности
+  pub fn issues_with(&self, severity: ExportIssueSeverity) -> impl Iterator<Item = &ExportIssue> {
+    self
+      .issues
+      .iter()
+      .filter(move |issue| issue.severity == severity)
+  }
+}
+
+/// Проверить настройки экспорта по матрице совместимости.
+///
+/// `capabilities` - возможности сборки FFmpeg; без них проверяется только
+/// матрица. Проверки выполняются по очереди над копией настроек, к которой
+/// сразу применяются автоисправления, поэтому последующие проверки видят уже
+/// исправленный кодировщик.
+pub fn validate_export_settings(
+  settings: &ProjectSettings,
+  capabilities: Option<&FfmpegCapabilities>,
+  apply_auto_fixes: bool,
+) -> ExportSettingsValidation {
+  let mut check = CompatibilityCheck {
+    settings: settings.clone(),
+    issues: Vec::new(),
+  };
+
+  if !check.settings.output.format.is_audio_only() {
+    check.video_parameters();
+    check.container_video_codec();
+    if let Some(capabilities) = capabilities {
+      check.encoder_available(capabilities);
+    }
+    check.encoder_frame_rate();
+    check.rate_control();
+    check.dimensions();
+  }
+  check.container_audio_codec();
+  if let Some(capabilities) = capabilities {
+    check.muxer_and_pixel_format(capabilities);
+  }
+
+  ExportSettingsValidation {
+    issues: check.issues,
+    fixed_settings: apply_auto_fixes.then_some(check.settings),
+  }
+}
+
+/// Проверка над рабочей копией настроек
+struct CompatibilityCheck {
+  settings: ProjectSettings,
+  issues: Vec<ExportIssue>,
+}
+
+impl CompatibilityCheck {
+  fn push(
+    &mut self,
+    kind: ExportIssueKind,
+    message: String,
+    suggested_fix: String,
+    auto_fix: Option<ExportAutoFix>,
+  ) {
+    let severity = if auto_fix.is_some() {
+      ExportIssueSeverity::AutoFixable
+    } else {
+      ExportIssueSeverity::Error
+    };
+    if let Some(fix) = &auto_fix {
+      fix.apply(&mut self.settings);
+    }
+    self.issues.push(ExportIssue {
+      kind,
+      severity,
+      message,
+      suggested_fix,
+      auto_fix,
+    });
+  }
+
+  fn warn(&mut self, kind: ExportIssueKind, message: String, suggested_fix: String) {
+    self.issues.push(ExportIssue {
+      kind,
+      severity: ExportIssueSeverity::Warning,
+      message,
+      suggested_fix,
+      auto_fix: None,
+    });
+  }
+
+  fn video_parameters(&mut self) {
+    let (width, height) = (
+      self.settings.resolution.width,
+      self.settings.resolution.height,
+    );
+    if width == 0 || height == 0 {
+      self.push(
+        ExportIssueKind::InvalidVideoParameters,
+        format!("Разрешение {width}x{height} недопустимо"),
+        "Укажите разрешение больше 0x0".to_string(),
+        None,
+      );
+    }
+    let fps = self.settings.frame_rate;
+    if !fps.is_finite() || fps <= 0.0 {
+      self.push(
+        ExportIssueKind::InvalidVideoParameters,
+        format!("Частота кадров {fps} недопустима"),
+        "Укажите положительную частоту кадров".to_string(),
+        None,
+      );
+    }
+  }
+
+  fn container_video_codec(&mut self) {
+    let (Some(container), Some(encoder)) = (
+      container_name(&self.settings.output.format),
+      video_encoder(&self.settings),
+    ) else {
+      return;
+    };
+    let Some(allowed) = lookup(CONTAINER_VIDEO_CODECS, container) else {
+      return;
+    };
+    let family = video_codec_family(&encoder);
+
+    if allowed.contains(&family) {
+      if let Some((_, _, message)) = CONTAINER_VIDEO_WARNINGS
+        .iter()
+        .find(|(c, f, _)| *c == container && *f == family)
+      {
+        self.warn(
+          ExportIssueKind::ContainerVideoCodec,
+          message.to_string(),
+          "Выберите WebM или MKV для VP9".to_string(),
+        );
+      }
+      return;
+    }
+
+    // HEVC заменяется на H.264 того же кодировщика, остальное - на кодек формата
+    let replacement = match hardware_backend(&encoder) {
+      Some(backend) if family == "hevc" && allowed.contains(&"h264") => {
+        Some(format!("h264_{backend}"))
+      }
+      _ => default_video_encoder(&self.settings).map(str::to_string),
+    };
+    let suggested_fix = match &replacement {
+      Some(replacement) => format!("Использовать кодировщик {replacement}"),
+      None => format!("Выберите контейнер, поддерживающий {family}"),
+    };
+    self.push(
+      ExportIssueKind::ContainerVideoCodec,
+      format!("Контейнер {container} не поддерживает видеокодек {family} ({encoder})"),
+      suggested_fix,
+      replacement.map(|encoder| ExportAutoFix::VideoEncoder { encoder }),
+    );
+  }
+
+  fn encoder_available(&mut self, capabilities: &FfmpegCapabilities) {
+    let Some(encoder) = video_encoder(&self.settings) else {
+      return;
+    };
+    if capabilities.encoders.is_empty() || capabilities.has_encoder(&encoder) {
+      return;
+    }
+
+    let fallback = hardware_backend(&encoder)
+      .and_then(|_| software_encoder(video_codec_family(&encoder)))
+      .filter(|software| capabilities.has_encoder(software));
+    let suggested_fix = match fallback {
+      Some(software) => format!("Использовать программный кодировщик {software}"),
+      None => "Установите сборку FFmpeg с этим кодировщиком или выберите другой формат".to_string(),
+    };
+    self.push(
+      ExportIssueKind::EncoderUnavailable,
+      format!("Кодировщик {encoder} отсутствует в сборке FFmpeg"),
+      suggested_fix,
+      fallback.map(|encoder| ExportAutoFix::VideoEncoder {
+        encoder: encoder.to_string(),
+      }),
+    );
+  }
+
+  fn encoder_frame_rate(&mut self) {
+    let Some(encoder) = video_encoder(&self.settings) else {
+      return;
+    };
+    let fps = self.settings.frame_rate;
+    if !fps.is_finite() {
+      return;
+    }
+
+    if video_codec_family(&encoder) == "gif" && fps > GIF_MAX_FPS {
+      self.warn(
+        ExportIssueKind::FrameRateLimit,
+        format!("GIF не передает частоту выше {GIF_MAX_FPS} кадров/с, задано {fps}"),
+        format!("Понизьте частоту кадров до {GIF_MAX_FPS}"),
+      );
+      return;
+    }
+
+    let Some((backend, limit)) = hardware_backend(&encoder).and_then(|backend| {
+      ENCODER_FPS_LIMITS
+        .iter()
+        .find(|(name, _)| *name == backend)
+        .map(|(_, limit)| (backend, *limit))
+    }) else {
+      return;
+    };
+    if fps <= limit {
+      return;
+    }
+
+    let fallback = software_encoder(video_codec_family(&encoder));
+    let suggested_fix = match fallback {
+      Some(software) => format!("Использовать программный кодировщик {software}"),
+      None => format!("Понизьте частоту кадров до {limit}"),
+    };
+    self.push(
+      ExportIssueKind::FrameRateLimit,
+      format!(
+        "Кодировщик {encoder} ({backend}) поддерживает не более {limit} кадров/с, задано {fps}"
+      ),
+      suggested_fix,
+      fallback.map(|encoder| ExportAutoFix::VideoEncoder {
+        encoder: encoder.to_string(),
+      }),
+    );
+  }
+
+  fn rate_control(&mut self) {
+    let export = &self.settings.export;
+    let uses_crf = match export.rate_control_mode.as_deref() {
+      Some(mode) => mode.eq_ignore_ascii_case("crf"),
+      None => export.crf.is_some(),
+    };
+    if !uses_crf || self.settings.output.format.is_intermediate() {
+      return;
+    }
+    let Some(encoder) = video_encoder(&self.settings) else {
+      return;
+    };
+    let Some(mode) =
+      hardware_backend(&encoder).and_then(|backend| lookup(HARDWARE_QUALITY_MODES, backend))
+    else {
+      return;
+    };
+
+    self.push(
+      ExportIssueKind::RateControl,
+      format!("Кодировщик {encoder} не поддерживает CRF"),
+      format!("Использовать режим {mode} с тем же уровнем качества"),
+      Some(ExportAutoFix::RateControl {
+        mode: mode.to_string(),
+      }),
+    );
+  }
+
+  fn dimensions(&mut self) {
+    let Some(pixel_format) = pixel_format(&self.settings) else {
+      return;
+    };
+    let (align_x, align_y) = chroma_alignment(&pixel_format);
+    if align_x == 1 && align_y == 1 {
+      return;
+    }
+    let aligned = |width: u32, height: u32| {
+      (
+        (width - width % align_x).max(align_x),
+        (height - height % align_y).max(align_y),
+      )
+    };
+
+    let resolution = &self.settings.resolution;
+    let (width, height) = (resolution.width, resolution.height);
+    if width > 0 && height > 0 && (width % align_x != 0 || height % align_y != 0) {
+      let (fixed_width, fixed_height) = aligned(width, height);
+      self.push(
+        ExportIssueKind::OddDimensions,
+        format!("Формат пикселей {pixel_format} требует размер кадра кратный {align_x}x{align_y}, задано {width}x{height}"),
+        format!("Использовать разрешение {fixed_width}x{fixed_height}"),
+        Some(ExportAutoFix::Resolution {
+          width: fixed_width,
+          height: fixed_height,
+        }),
+      );
+    }
+
+    let renditions: Vec<_> = self
+      .settings
+      .export
+      .renditions
+      .iter()
+      .map(|rendition| {
+        (
+          rendition.suffix.clone(),
+          rendition.resolution.width,
+          rendition.resolution.height,
+        )
+      })
+      .collect();
+    for (index, (suffix, width, height)) in renditions.into_iter().enumerate() {
+      if width == 0 || height == 0 || (width % align_x == 0 && height % align_y == 0) {
+        continue;
+      }
+      let (fixed_width, fixed_height) = aligned(width, height);
+      self.push(
+        ExportIssueKind::OddDimensions,
+        format!("Вариант '{suffix}': формат пикселей {pixel_format} требует размер кадра кратный {align_x}x{align_y}, задано {width}x{height}"),
+        format!("Использовать разрешение {fixed_width}x{fixed_height}"),
+        Some(ExportAutoFix::RenditionResolution {
+          index,
+          width: fixed_width,
+          height: fixed_height,
+        }),
+      );
+    }
+  }
+
+  fn container_audio_codec(&mut self) {
+    let Some(container) = container_name(&self.settings.output.format) else {
+      return;
+    };
+    let Some(allowed) = lookup(CONTAINER_AUDIO_CODECS, container) else {
+      return;
+    };
+    // Кодеки по умолчанию для каждого формата совместимы с контейнером
+    let Some(encoder) =
+      arg_value(&self.settings.export.ffmpeg_args, AUDIO_CODEC_ARGS).map(str::to_string)
+    else {
+      return;
+    };
+    let family = audio_codec_family(&encoder);
+    if allowed.contains(&family) {
+      return;
+    }
+
+    self.push(
+      ExportIssueKind::ContainerAudioCodec,
+      format!("Контейнер {container} не поддерживает аудиокодек {family} ({encoder})"),
+      format!("Использовать аудиокодек контейнера ({})", allowed[0]),
+      Some(ExportAutoFix::RemoveAudioCodecOverride),
+    );
+  }
+
+  fn muxer_and_pixel_format(&mut self, capabilities: &FfmpegCapabilities) {
+    let muxer = match &self.settings.output.format {
+      OutputFormat::Custom(name) => Some(name.clone()).filter(|name| !name.is_empty()),
+      format => container_name(format).map(str::to_string),
+    };
+    if let Some(container) = muxer {
+      if !capabilities.muxers.is_empty() && !capabilities.has_muxer(&container) {
+        self.push(
+          ExportIssueKind::MuxerUnavailable,
+          format!("Мюксер {container} отсутствует в сборке FFmpeg"),
+          "Выберите другой формат вывода".to_string(),
+          None,
+        );
+      }
+    }
+
+    if self.settings.output.format.is_audio_only() {
+      return;
+    }
+    if let Some(pixel_format) = pixel_format(&self.settings) {
+      if !capabilities.pixel_formats.is_empty() && !capabilities.has_pixel_format(&pixel_format) {
+        self.push(
+          ExportIssueKind::PixelFormatUnavailable,
+          format!("Формат пикселей {pixel_format} отсутствует в сборке FFmpeg"),
+          "Выберите другой формат пикселей или режим HDR".to_string(),
+          None,
+        );
+      }
+    }
+  }
+}
+
+fn lookup<T: Copy>(table: &[(&str, T)], key: &str) -> Option<T> {
+  table
+    .iter()
+    .find(|(name, _)| *name == key)
+    .map(|(_, value)| *value)
+}
+
+/// Позиция последнего аргумента из `names`, за которым следует значение
+fn arg_position(args: &[String], names: &[&str]) -> Option<usize> {
+  args
+    .iter()
+    .enumerate()
+    .rev()
+    .skip(1)
+    .find(|(_, arg)| names.contains(&arg.as_str()))
+    .map(|(position, _)| position)
+}
+
+fn arg_value<'a>(args: &'a [String], names: &[&str]) -> Option<&'a str> {
+  arg_position(args, names).map(|position| args[position + 1].as_str())
+}
+
+/// Имя мюксера FFmpeg для формата вывода (пользовательские форматы матрица
+/// не описывает)
+fn container_name(format: &OutputFormat) -> Option<&'static str> {
+  Some(match format {
+    OutputFormat::Mp4 => "mp4",
+    OutputFormat::Mov | OutputFormat::MovProRes(_) => "mov",
+    OutputFormat::WebM => "webm",
+    OutputFormat::Avi => "avi",
+    OutputFormat::Mkv => "matroska",
+    OutputFormat::MxfDnxhr(_) => "mxf",
+    OutputFormat::Gif => "gif",
+    OutputFormat::Mp3 => "mp3",
+    OutputFormat::Wav => "wav",
+    OutputFormat::Flac => "flac",
+    OutputFormat::Custom(_) => return None,
+  })
+}
+
+/// Программный кодировщик формата (тот же выбор, что и у рендера без GPU)
+fn default_video_encoder(settings: &ProjectSettings) -> Option<&'static str> {
+  let hdr = settings.export.hdr_mode.is_hdr_output();
+  Some(match settings.output.format {
+    OutputFormat::Mp4 | OutputFormat::Mov if hdr => "libx265",
+    OutputFormat::Mp4 | OutputFormat::Mov | OutputFormat::Avi | OutputFormat::Custom(_) => {
+      "libx264"
+    }
+    OutputFormat::WebM => "libvpx-vp9",
+    OutputFormat::Mkv => "libx265",
+    OutputFormat::Gif => "gif",
+    OutputFormat::MovProRes(_) => "prores_ks",
+    OutputFormat::MxfDnxhr(_) => "dnxhd",
+    OutputFormat::Mp3 | OutputFormat::Wav | OutputFormat::Flac => return None,
+  })
+}
+
+/// Видеокодировщик экспорта: явный `-c:v`, затем предпочитаемый GPU
+/// кодировщик, затем кодировщик формата
+fn video_encoder(settings: &ProjectSettings) -> Option<String> {
+  let format = &settings.output.format;
+  if format.is_audio_only() {
+    return None;
+  }
+  let export = &settings.export;
+  if let Some(encoder) = arg_value(&export.ffmpeg_args, VIDEO_CODEC_ARGS) {
+    return Some(encoder.to_string());
+  }
+  let accelerated = export.hardware_acceleration
+    && !format.is_intermediate()
+    && !matches!(format, OutputFormat::Gif | OutputFormat::WebM);
+  match &export.preferred_gpu_encoder {
+    Some(encoder) if accelerated => Some(encoder.clone()),
+    _ => default_video_encoder(settings).map(str::to_string),
+  }
+}
+
+fn set_video_encoder(settings: &mut ProjectSettings, encoder: &str) {
+  if let Some(position) = arg_position(&settings.export.ffmpeg_args, VIDEO_CODEC_ARGS) {
+    settings.export.ffmpeg_args[position + 1] = encoder.to_string();
+  } else if hardware_backend(encoder).is_some() {
+    settings.export.hardware_acceleration = true;
+    settings.export.preferred_gpu_encoder = Some(encoder.to_string());
+  } else {
+    settings.export.hardware_acceleration = false;
+    settings.export.preferred_gpu_encoder = None;
+    if default_video_encoder(settings) != Some(encoder) {
+      settings
+        .export
+        .ffmpeg_args
+        .extend(["-c:v".to_string(), encoder.to_string()]);
+    }
+  }
+}
+
+/// Формат пикселей экспорта
+fn pixel_format(settings: &ProjectSettings) -> Option<String> {
+  if let Some(pixel_format) = arg_value(&settings.export.ffmpeg_args, &["-pix_fmt"]) {
+    return Some(pixel_format.to_string());
+  }
+  match settings.output.format {
+    OutputFormat::MovProRes(profile) => Some(profile.pixel_format().to_string()),
+    OutputFormat::MxfDnxhr(profile) => Some(profile.pixel_format().to_string()),
+    OutputFormat::Gif | OutputFormat::Mp3 | OutputFormat::Wav | OutputFormat::Flac => None,
+    _ if settings.export.hdr_mode.is_hdr_output() => Some("yuv420p10le".to_string()),
+    _ => Some("yuv420p".to_string()),
+  }
+}
+
+/// Шаг размеров кадра (по ширине и высоте) для формата пикселей
+fn chroma_alignment(pixel_format: &str) -> (u32, u32) {
+  if pixel_format.contains("420") || matches!(pixel_format, "nv12" | "nv21" | "p010le" | "p016le") {
+    (2, 2)
+  } else if pixel_format.contains("422") || pixel_format == "nv16" {
+    (2, 1)
+  } else if pixel_format.contains("411") {
+    (4, 1)
+  } else {
+    (1, 1)
+  }
+}
+
+/// Аппаратный интерфейс кодировщика (`h264_nvenc` -> `nvenc`)
+fn hardware_backend(encoder: &str) -> Option<&str> {
+  encoder
+    .rsplit_once('_')
+    .map(|(_, backend)| backend)
+    .filter(|backend| lookup(ENCODER_FPS_LIMITS, backend).is_some())
+}
+
+/// Семейство видеокодека по имени кодировщика FFmpeg
+fn video_codec_family(encoder: &str) -> &str {
+  match encoder {
+    "libx264" | "libopenh264" => "h264",
+    "libx265" => "hevc",
+    "libvpx" => "vp8",
+    "libvpx-vp9" => "vp9",
+    "libaom-av1" | "libsvtav1" | "librav1e" => "av1",
+    "mpeg4" | "libxvid" => "mpeg4",
+    "dnxhd" => "dnxhd",
+    "gif" => "gif",
+    encoder if encoder.starts_with("prores") => "prores",
+    encoder => encoder.split('_').next().unwrap_or(encoder),
+  }
+}
+
+/// Семейство аудиокодека по имени кодировщика FFmpeg
+fn audio_codec_family(encoder: &str) -> &str {
+  match encoder {
+    "aac" | "libfdk_aac" => "aac",
+    "mp3" | "libmp3lame" | "libshine" => "mp3",
+    "opus" | "libopus" => "opus",
+    "vorbis" | "libvorbis" => "vorbis",
+    "ac3" | "eac3" => "ac3",
+    encoder if encoder.starts_with("pcm_") => "pcm",
+    encoder => encoder,
+  }
+}
+
+/// Программный кодировщик семейства
+fn software_encoder(family: &str) -> Option<&'static str> {
+  Some(match family {
+    "h264" => "libx264",
+    "hevc" => "libx265",
+    "vp8" => "libvpx",
+    "vp9" => "libvpx-vp9",
+    "av1" => "libaom-av1",
+    "mpeg4" => "mpeg4",
+    _ => return None,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::video_compiler::schema::{HdrMode, ProResProfile, RenditionSpec, Resolution};
+  use std::collections::BTreeSet;
+
+  use ExportIssueKind as Kind;
+  use ExportIssueSeverity as Severity;
+
+  /// Строка матрицы: формат, видеокодировщик (GPU или `-c:v`), аудиокодек,
+  /// разрешение, частота кадров, режим битрейта и ожидаемые нарушения
+  struct Case {
+    name: &'static str,
+    format: OutputFormat,
+    gpu_encoder: Option<&'static str>,
+    video_arg: Option<&'static str>,
+    audio_arg: Option<&'static str>,
+    resolution: (u32, u32),
+    fps: f64,
+    rate_control: Option<&'static str>,
+    expected: &'static [(Kind, Severity)],
+  }
+
+  impl Case {
+    const fn new(name: &'static str, format: OutputFormat) -> Self {
+      Self {
+        name,
+        format,
+        gpu_encoder: None,
+        video_arg: None,
+        audio_arg: None,
+        resolution: (1920, 1080),
+        fps: 30.0,
+        rate_control: None,
+        expected: &[],
+      }
+    }
+
+    fn settings(&self) -> ProjectSettings {
+      let mut settings = ProjectSettings::default();
+      settings.output.format = self.format.clone();
+      settings.export.hardware_acceleration = self.gpu_encoder.is_some();
+      settings.export.preferred_gpu_encoder = self.gpu_encoder.map(str::to_string);
+      if let Some(encoder) = self.video_arg {
+        settings
+          .export
+          .ffmpeg_args
+          .extend(["-c:v".to_string(), encoder.to_string()]);
+      }
+      if let Some(encoder) = self.audio_arg {
+        settings
+          .export
+          .ffmpeg_args
+          .extend(["-c:a".to_string(), encoder.to_string()]);
+      }
+      settings.resolution = Resolution::new(self.resolution.0, self.resolution.1);
+      settings.frame_rate = self.fps;
+      settings.export.rate_control_mode = self.rate_control.map(str::to_string);
+      settings
+    }
+  }
+
+  fn matrix() -> Vec<Case> {
+    use OutputFormat::*;
+    vec![
+      Case::new("mp4 defaults", Mp4),
+      Case::new("mov defaults", Mov),
+      Case::new("webm defaults", WebM),
+      Case::new("mkv defaults", Mkv),
+      Case::new("avi defaults", Avi),
+      Case::new("wav defaults", Wav),
+      Case {
+        video_arg: Some("libvpx-vp9"),
+        expected: &[(Kind::ContainerVideoCodec, Severity::Warning)],
+        ..Case::new("vp9 in mp4", Mp4)
+      },
+      Case {
+        video_arg: Some("libx265"),
+        expected: &[(Kind::ContainerVideoCodec, Severity::AutoFixable)],
+        ..Case::new("h265 in avi", Avi)
+      },
+      Case {
+        gpu_encoder: Some("hevc_nvenc"),
+        expected: &[(Kind::ContainerVideoCodec, Severity::AutoFixable)],
+        ..Case::new("hevc_nvenc in avi", Avi)
+      },
+      Case {
+        video_arg: Some("libx264"),
+        expected: &[(Kind::ContainerVideoCodec, Severity::AutoFixable)],
+        ..Case::new("h264 in webm", WebM)
+      },
+      Case {
+        video_arg: Some("prores_ks"),
+        expected: &[(Kind::ContainerVideoCodec, Severity::AutoFixable)],
+        ..Case::new("prores in mp4", Mp4)
+      },
+      Case {
+        video_arg: Some("prores_ks"),
+        ..Case::new("prores in mkv", Mkv)
+      },
+      Case {
+        video_arg: Some("libaom-av1"),
+        ..Case::new("av1 in webm", WebM)
+      },
+      Case {
+        resolution: (1919, 1080),
+        expected: &[(Kind::OddDimensions, Severity::AutoFixable)],
+        ..Case::new("odd width yuv420p", Mp4)
+      },
+      Case {
+        resolution: (1920, 1081),
+        expected: &[(Kind::OddDimensions, Severity::AutoFixable)],
+        ..Case::new("odd height yuv420p", Mov)
+      },
+      Case {
+        resolution: (1920, 1081),
+        ..Case::new("odd height prores 422", MovProRes(ProResProfile::Hq))
+      },
+      Case {
+        resolution: (1919, 1081),
+        ..Case::new("odd size prores 4444", MovProRes(ProResProfile::ProRes4444))
+      },
+      Case {
+        gpu_encoder: Some("h264_vaapi"),
+        fps: 120.0,
+        expected: &[(Kind::FrameRateLimit, Severity::AutoFixable)],
+        ..Case::new("120fps vaapi", Mp4)
+      },
+      Case {
+        gpu_encoder: Some("h264_nvenc"),
+        fps: 120.0,
+        ..Case::new("120fps nvenc", Mp4)
+      },
+      Case {
+        gpu_encoder: Some("h264_videotoolbox"),
+        fps: 144.0,
+        expected: &[(Kind::FrameRateLimit, Severity::AutoFixable)],
+        ..Case::new("144fps videotoolbox", Mov)
+      },
+      Case {
+        video_arg: Some("libx264"),
+        fps: 240.0,
+        ..Case::new("240fps software", Mp4)
+      },
+      Case {
+        fps: 60.0,
+        expected: &[(Kind::FrameRateLimit, Severity::Warning)],
+        ..Case::new("60fps gif", Gif)
+      },
+      Case {
+        gpu_encoder: Some("h264_nvenc"),
+        rate_control: Some("crf"),
+        expected: &[(Kind::RateControl, Severity::AutoFixable)],
+        ..Case::new("crf on nvenc", Mp4)
+      },
+      Case {
+        gpu_encoder: Some("h264_qsv"),
+        rate_control: Some("crf"),
+        expected: &[(Kind::RateControl, Severity::AutoFixable)],
+        ..Case::new("crf on qsv", Mp4)
+      },
+      Case {
+        rate_control: Some("crf"),
+        ..Case::new("crf on libx264", Mp4)
+      },
+      Case {
+        audio_arg: Some("aac"),
+        expected: &[(Kind::ContainerAudioCodec, Severity::AutoFixable)],
+        ..Case::new("aac in webm", WebM)
+      },
+      Case {
+        audio_arg: Some("pcm_s24le"),
+        expected: &[(Kind::ContainerAudioCodec, Severity::AutoFixable)],
+        ..Case::new("pcm in mp4", Mp4)
+      },
+      Case {
+        audio_arg: Some("pcm_s24le"),
+        ..Case::new("pcm in mov", Mov)
+      },
+      Case {
+        audio_arg: Some("libopus"),
+        ..Case::new("opus in mkv", Mkv)
+      },
+      Case {
+        audio_arg: Some("aac"),
+        expected: &[(Kind::ContainerAudioCodec, Severity::AutoFixable)],
+        ..Case::new("aac in flac", Flac)
+      },
+      Case {
+        resolution: (0, 1080),
+        expected: &[(Kind::InvalidVideoParameters, Severity::Error)],
+        ..Case::new("zero width", Mp4)
+      },
+      Case {
+        fps: 0.0,
+        expected: &[(Kind::InvalidVideoParameters, Severity::Error)],
+        ..Case::new("zero fps", Mp4)
+      },
+      Case {
+        gpu_encoder: Some("hevc_vaapi"),
+        fps: 120.0,
+        rate_control: Some("crf"),
+        resolution: (1279, 721),
+        expected: &[
+          (Kind::ContainerVideoCodec, Severity::AutoFixable),
+          (Kind::FrameRateLimit, Severity::AutoFixable),
+          (Kind::OddDimensions, Severity::AutoFixable),
+        ],
+        ..Case::new("everything wrong in avi", Avi)
+      },
+    ]
+  }
+
+  #[test]
+  fn test_compatibility_matrix() {
+    let cases = matrix();
+    assert!(cases.len() >= 20);
+
+    for case in cases {
+      let validation = validate_export_settings(&case.settings(), None, false);
+      let found: Vec<_> = validation
+        .issues
+        .iter()
+        .map(|issue| (issue.kind, issue.severity))
+        .collect();
+      assert_eq!(found, case.expected, "case '{}'", case.name);
+      assert!(validation.fixed_settings.is_none());
+      for issue in &validation.issues {
+        assert!(!issue.suggested_fix.is_empty(), "case '{}'", case.name);
+        assert_eq!(
+          issue.auto_fix.is_some(),
+          issue.severity == Severity::AutoFixable,
+          "case '{}'",
+          case.name
+        );
+      }
+    }
+  }
+
+  #[test]
+  fn test_auto_fixes_produce_valid_settings() {
+    for case in matrix() {
+      let validation = validate_export_settings(&case.settings(), None, true);
+      let fixed = validation.fixed_settings.expect("fixed settings");
+      let revalidated = validate_export_settings(&fixed, None, false);
+      assert!(
+        revalidated
+          .issues
+          .iter()
+          .all(|issue| issue.severity != Severity::AutoFixable),
+        "case '{}': {:?}",
+        case.name,
+        revalidated.issues
+      );
+    }
+  }
+
+  #[test]
+  fn test_auto_fix_substitutions() {
+    let case = matrix()
+      .into_iter()
+      .find(|case| case.name == "everything wrong in avi")
+      .unwrap();
+    let fixed = validate_export_settings(&case.settings(), None, true)
+      .fixed_settings
+      .unwrap();
+
+    // hevc_vaapi -> h264_vaapi (AVI) -> libx264 (лимит 60 кадров/с у VAAPI)
+    assert!(!fixed.export.hardware_acceleration);
+    assert_eq!(fixed.export.preferred_gpu_encoder, None);
+    assert_eq!(video_encoder(&fixed).as_deref(), Some("libx264"));
+    assert_eq!(
+      (fixed.resolution.width, fixed.resolution.height),
+      (1278, 720)
+    );
+    // CRF проверяется уже для программного кодировщика
+    assert_eq!(fixed.export.rate_control_mode.as_deref(), Some("crf"));
+
+    let mut settings = ProjectSettings::default();
+    settings.export.preferred_gpu_encoder = Some("h264_nvenc".to_string());
+    settings.export.crf = Some(23);
+    settings.export.rate_control_mode = None;
+    let fixed = validate_export_settings(&settings, None, true)
+      .fixed_settings
+      .unwrap();
+    assert_eq!(fixed.export.rate_control_mode.as_deref(), Some("cq"));
+    assert_eq!(fixed.export.crf, Some(23));
+
+    let mut settings = ProjectSettings::default();
+    settings.output.format = OutputFormat::WebM;
+    settings.export.ffmpeg_args = ["-c:a", "aac", "-b:a", "128k"].map(str::to_string).to_vec();
+    let fixed = validate_export_settings(&settings, None, true)
+      .fixed_settings
+      .unwrap();
+    assert_eq!(fixed.export.ffmpeg_args, vec!["-b:a", "128k"]);
+  }
+
+  #[test]
+  fn test_rendition_dimensions() {
+    let mut settings = ProjectSettings::default();
+    settings.export.renditions = vec![RenditionSpec {
+      resolution: Resolution::new(853, 480),
+      video_bitrate: 1500,
+      suffix: "_480p".to_string(),
+    }];
+    let validation = validate_export_settings(&settings, None, true);
+    assert_eq!(validation.issues.len(), 1);
+    assert_eq!(
+      validation.issues[0].auto_fix,
+      Some(ExportAutoFix::RenditionResolution {
+        index: 0,
+        width: 852,
+        height: 480
+      })
+    );
+  }
+
+  #[test]
+  fn test_hdr_requires_even_size_and_10bit_pixel_format() {
+    let mut settings = ProjectSettings::default();
+    settings.export.hdr_mode = HdrMode::Hdr10;
+    settings.resolution = Resolution::new(3840, 2161);
+
+    let capabilities = FfmpegCapabilities {
+      encoders: ["libx265", "libx264"].map(str::to_string).into(),
+      muxers: BTreeSet::from(["mp4".to_string()]),
+      pixel_formats: BTreeSet::from(["yuv420p".to_string()]),
+      ..Default::default()
+    };
+    let validation = validate_export_settings(&settings, Some(&capabilities), false);
+    let kinds: Vec<_> = validation.issues.iter().map(|issue| issue.kind).collect();
+    assert_eq!(
+      kinds,
+      vec![Kind::OddDimensions, Kind::PixelFormatUnavailable]
+    );
+    assert!(validation.has_errors());
+  }
+
+  #[test]
+  fn test_capabilities_fallback_and_missing_muxer() {
+    let mut settings = ProjectSettings::default();
+    settings.export.preferred_gpu_encoder = Some("h264_amf".to_string());
+    settings.output.format = OutputFormat::Mkv;
+    settings.export.ffmpeg_args = vec!["-c:v".to_string(), "h264_amf".to_string()];
+
+    let capabilities = FfmpegCapabilities {
+      encoders: ["libx264", "libx265"].map(str::to_string).into(),
+      muxers: BTreeSet::from(["mp4".to_string()]),
+      ..Default::default()
+    };
+    let validation = validate_export_settings(&settings, Some(&capabilities), true);
+    let found: Vec<_> = validation
+      .issues
+      .iter()
+      .map(|issue| (issue.kind, issue.severity))
+      .collect();
+    assert_eq!(
+      found,
+      vec![
+        (Kind::EncoderUnavailable, Severity::AutoFixable),
+        (Kind::MuxerUnavailable, Severity::Error),
+      ]
+    );
+    let fixed = validation.fixed_settings.unwrap();
+    assert_eq!(fixed.export.ffmpeg_args, vec!["-c:v", "libx264"]);
+    assert_eq!(
+      validation
+        .issues_with(Severity::Error)
+        .map(|issue| issue.kind)
+        .collect::<Vec<_>>(),
+      vec![Kind::MuxerUnavailable]
+    );
+  }
+}
@@ -0,0 +1,557 @@
+//! Gaps - Пустые промежутки timeline и их закрытие (ripple delete)
+//!
+//! Промежуток - интервал трека между 0 и концом последнего клипа, который не
+//! покрыт ни одним клипом. При закрытии промежутков последующие клипы
+//! сдвигаются влево; клипы одной группы связи ([`Clip::link_group_id`])
+//! сдвигаются на одинаковое время, даже если лежат на разных треках.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use super::project::ProjectSchema;
+use super::timeline::{Clip, Track};
+
+/// Промежутки короче этого значения считаются погрешностью округления
+pub const GAP_EPSILON: f64 = 1e-6;
+
+/// Пустой промежуток трека
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TimelineGap {
+  /// ID трека
+  pub track_id: String,
+  /// Начало промежутка
+  pub start: f64,
+  /// Конец промежутка
+  pub end: f64,
+  /// Длительность промежутка
+  pub duration: f64,
+}
+
+/// Параметры закрытия промежутков
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct RippleDeleteOptions {
+  /// ID треков для обработки (`None` - все включенные треки)
+  #[serde(default)]
+  pub tracks: Option<Vec<String>>,
+  /// Промежутки не длиннее этого значения (секунды) сохраняются как паузы
+  #[serde(default)]
+  pub min_gap_to_keep: f64,
+}
+
+/// Сдвиг клипа при закрытии промежутков
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ClipShift {
+  pub clip_id: String,
+  pub track_id: String,
+  /// Сдвиг влево в секундах
+  pub shift: f64,
+}
+
+/// Трек, пропущенный при закрытии промежутков
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SkippedTrack {
+  pub track_id: String,
+  pub reason: String,
+}
+
+/// Итог закрытия промежутков
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct RippleDeleteSummary {
+  /// Промежутки, выбранные для закрытия
+  pub closed_gaps: Vec<TimelineGap>,
+  /// Сдвинутые клипы
+  pub moved_clips: Vec<ClipShift>,
+  /// ID переходов, клипы которых сдвинулись
+  pub updated_transitions: Vec<String>,
+  /// Пропущенные треки (заблокированные)
+  pub skipped_tracks: Vec<SkippedTrack>,
+}
+
+impl Track {
+  /// Промежутки трека между 0 и концом последнего клипа
+  pub fn gaps(&self) -> Vec<TimelineGap> {
+    let mut intervals: Vec<(f64, f64)> = self
+      .clips
+      .iter()
+      .map(|clip| (clip.start_time, clip.end_time))
+      .collect();
+    intervals.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut gaps = Vec::new();
+    let mut covered_until = 0.0;
+    for (start, end) in intervals {
+      if start - covered_until > GAP_EPSILON {
+        gaps.push(TimelineGap {
+          track_id: self.id.clone(),
+          start: covered_until,
+          end: start,
+          duration: start - covered_until,
+        });
+      }
+      covered_until = f64::max(covered_until, end);
+    }
+    gaps
+  }
+}
+
+impl ProjectSchema {
+  /// Промежутки включенных треков (или одного трека `track_id`)
+  pub fn detect_gaps(&self, track_id: Option<&str>) -> Result<Vec<TimelineGap>, String> {
+    if let Some(id) = track_id {
+      if !self.tracks.iter().any(|track| track.id == id) {
+        return Err(format!("Трек '{id}' не найден"));
+      }
+    }
+
+    Ok(
+      self
+        .tracks
+        .iter()
+        .filter(|track| track.enabled && track_id.is_none_or(|id| track.id == id))
+        .flat_map(Track::gaps)
+        .collect(),
+    )
+  }
+
+  /// Закрыть промежутки треков, сдвинув последующие клипы влево.
+  ///
+  /// Заблокированные треки и клипы не сдвигаются; треки попадают в
+  /// `skipped_tracks`. Клипы группы связи сдвигаются на наименьший из сдвигов
+  /// ее участников, поэтому участник на заблокированном треке удерживает всю
+  /// группу. Сдвиги согласуются с соседними клипами, так что новых
+  /// пересечений не возникает.
+  pub fn ripple_delete_gaps(
+    &mut self,
+    options: &RippleDeleteOptions,
+  ) -> Result<RippleDeleteSummary, String> {
+    if let Some(ids) = &options.tracks {
+      if let Some(missing) = ids
+        .iter()
+        .find(|id| !self.tracks.iter().any(|track| &track.id == *id))
+      {
+        return Err(format!("Трек '{missing}' не найден"));
+      }
+    }
+
+    let mut summary = RippleDeleteSummary::default();
+    let selected: HashSet<&str> = self
+      .tracks
+      .iter()
+      .filter(|track| match &options.tracks {
+        Some(ids) => ids.contains(&track.id),
+        None => track.enabled,
+      })
+      .map(|track| track.id.as_str())
+      .collect();
+
+    // Верхняя граница сдвига каждого клипа по его треку
+    let mut shifts: Vec<Vec<f64>> = Vec::with_capacity(self.tracks.len());
+    for track in &self.tracks {
+      let is_selected = selected.contains(track.id.as_str());
+      if is_selected && track.locked {
+        summary.skipped_tracks.push(SkippedTrack {
+          track_id: track.id.clone(),
+          reason: "Трек заблокирован".to_string(),
+        });
+      }
+
+      let closed: Vec<TimelineGap> = if is_selected && !track.locked {
+        track
+          .gaps()
+          .into_iter()
+          .filter(|gap| gap.duration - options.min_gap_to_keep > GAP_EPSILON)
+          .collect()
+      } else {
+        Vec::new()
+      };
+
+      shifts.push(
+        track
+          .clips
+          .iter()
+          .map(|clip| {
+            if track.locked || clip.locked {
+              0.0
+            } else if is_selected {
+              closed
+                .iter()
+                .filter(|gap| gap.end <= clip.start_time + GAP_EPSILON)
+                .map(|gap| gap.duration)
+                .sum()
+            } else if clip.link_group_id.is_some() {
+              // Невыбранный трек следует за своей группой
+              clip.start_time
+            } else {
+              0.0
+            }
+          })
+          .collect(),
+      );
+      summary.closed_gaps.extend(closed);
+    }
+
+    self.settle_shifts(&mut shifts);
+
+    let moved: HashSet<String> = self.apply_shifts(&shifts, &mut summary);
+    self.clamp_moved_transitions(&moved, &mut summary);
+    self.validate_no_overlaps()?;
+
+    if !summary.moved_clips.is_empty() {
+      self.timeline.duration = self.get_duration();
+      self.touch();
+    }
+    Ok(summary)
+  }
+
+  /// Согласовать сдвиги: одинаковые внутри группы связи и без наложения
+  /// клипа на предыдущий клип трека. Сдвиги только уменьшаются, поэтому
+  /// итерации сходятся.
+  fn settle_shifts(&self, shifts: &mut [Vec<f64>]) {
+    let mut groups: HashMap<&str, Vec<(usize, usize)>> = HashMap::new();
+    for (track_idx, track) in self.tracks.iter().enumerate() {
+      for (clip_idx, clip) in track.clips.iter().enumerate() {
+        if let Some(group) = &clip.link_group_id {
+          groups
+            .entry(group.as_str())
+            .or_default()
+            .push((track_idx, clip_idx));
+        }
+      }
+    }
+
+    let orders: Vec<Vec<usize>> = self
+      .tracks
+      .iter()
+      .map(|track| {
+        let mut order: Vec<usize> = (0..track.clips.len()).collect();
+        order.sort_by(|&a, &b| {
+          track.clips[a]
+            .start_time
+            .total_cmp(&track.clips[b].start_time)
+        });
+        order
+      })
+      .collect();
+
+    loop {
+      let mut changed = false;
+
+      for members in groups.values() {
+        let group_shift = members
+          .iter()
+          .map(|&(t, c)| shifts[t][c])
+          .fold(f64::INFINITY, f64::min);
+        for &(t, c) in members {
+          if shifts[t][c] > group_shift {
+            shifts[t][c] = group_shift;
+            changed = true;
+          }
+        }
+      }
+
+      for (track_idx, order) in orders.iter().enumerate() {
+        let clips = &self.tracks[track_idx].clips;
+        for pair in order.windows(2) {
+          let (prev, next) = (pair[0], pair[1]);
+          let space = (clips[next].start_time - clips[prev].end_time).max(0.0);
+          let limit = shifts[track_idx][prev] + space;
+          if shifts[track_idx][next] > limit + GAP_EPSILON {
+            shifts[track_idx][next] = limit;
+            changed = true;
+          }
+        }
+      }
+
+      if !changed {
+        break;
+      }
+    }
+  }
+
+  /// Сдвинуть клипы; возвращает ID сдвинутых клипов
+  fn apply_shifts(
+    &mut self,
+    shifts: &[Vec<f64>],
+    summary: &mut RippleDeleteSummary,
+  ) -> HashSet<String> {
+    let mut moved = HashSet::new();
+    for (track, track_shifts) in self.tracks.iter_mut().zip(shifts) {
+      for (clip, &shift) in track.clips.iter_mut().zip(track_shifts) {
+        if shift <= GAP_EPSILON {
+          continue;
+        }
+        clip.start_time = (clip.start_time - shift).max(0.0);
+        clip.end_time -= shift;
+        moved.insert(clip.id.clone());
+        summary.moved_clips.push(ClipShift {
+          clip_id: clip.id.clone(),
+          track_id: track.id.clone(),
+          shift,
+        });
+      }
+      track
+        .clips
+        .sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+    }
+    moved
+  }
+
+  /// Ограничить длительность переходов сдвинутых клипов длительностью клипов
+  fn clamp_moved_transitions(
+    &mut self,
+    moved: &HashSet<String>,
+    summary: &mut RippleDeleteSummary,
+  ) {
+    let durations: HashMap<&str, f64> = self
+      .tracks
+      .iter()
+      .flat_map(|track| &track.clips)
+      .map(|clip| (clip.id.as_str(), clip.get_timeline_duration()))
+      .collect();
+
+    for transition in &mut self.transitions {
+      let clip_ids: Vec<&str> = [&transition.from_clip_id, &transition.to_clip_id]
+        .into_iter()
+        .flatten()
+        .map(String::as_str)
+        .collect();
+      if !clip_ids.iter().any(|id| moved.contains(*id)) {
+        continue;
+      }
+
+      let max_duration = clip_ids
+        .iter()
+        .filter_map(|id| durations.get(id).copied())
+        .fold(f64::INFINITY, f64::min);
+      if transition.duration.value > max_duration {
+        transition.duration.value = max_duration;
+      }
+      summary.updated_transitions.push(transition.id.clone());
+    }
+  }
+
+  /// Проверка, что клипы треков не пересекаются
+  fn validate_no_overlaps(&self) -> Result<(), String> {
+    for track in &self.tracks {
+      let mut clips: Vec<&Clip> = track.clips.iter().collect();
+      clips.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+      for pair in clips.windows(2) {
+        if pair[0].end_time - pair[1].start_time > GAP_EPSILON {
+          return Err(format!(
+            "Клипы пересекаются по времени в треке '{}': {} и {}",
+            track.name, pair[0].id, pair[1].id
+          ));
+        }
+      }
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::video_compiler::schema::effects::{Transition, TransitionDuration};
+  use crate::video_compiler::schema::timeline::TrackType;
+  use std::path::PathBuf;
+
+  fn clip(id: &str, start: f64, end: f64) -> Clip {
+    let mut clip = Clip::new(
+      PathBuf::from(format!("/media/{id}.mp4")),
+      start,
+      end - start,
+    );
+    clip.id = id.to_string();
+    clip
+  }
+
+  fn linked(id: &str, start: f64, end: f64, group: &str) -> Clip {
+    let mut clip = clip(id, start, end);
+    clip.link_group_id = Some(group.to_string());
+    clip
+  }
+
+  fn track(id: &str, track_type: TrackType, clips: Vec<Clip>) -> Track {
+    let mut track = Track::new(track_type, id.to_string());
+    track.id = id.to_string();
+    track.clips = clips;
+    track
+  }
+
+  fn times(project: &ProjectSchema, track_idx: usize) -> Vec<(f64, f64)> {
+    project.tracks[track_idx]
+      .clips
+      .iter()
+      .map(|clip| (clip.start_time, clip.end_time))
+      .collect()
+  }
+
+  /// Видео и связанное аудио с промежутком в начале и между клипами,
+  /// плюс музыкальный трек без промежутков
+  fn multi_track_project() -> ProjectSchema {
+    let mut project = ProjectSchema::new("Gaps".to_string());
+    project.tracks = vec![
+      track(
+        "video",
+        TrackType::Video,
+        vec![linked("v1", 2.0, 5.0, "a"), linked("v2", 8.0, 10.0, "b")],
+      ),
+      track(
+        "dialog",
+        TrackType::Audio,
+        vec![linked("d1", 2.0, 5.0, "a"), linked("d2", 8.5, 10.0, "b")],
+      ),
+      track("music", TrackType::Audio, vec![clip("m1", 0.0, 12.0)]),
+    ];
+    project
+  }
+
+  #[test]
+  fn test_detect_gaps_includes_leading_gap() {
+    let project = multi_track_project();
+    let gaps = project.detect_gaps(Some("video")).unwrap();
+
+    assert_eq!(gaps.len(), 2);
+    assert_eq!((gaps[0].start, gaps[0].end), (0.0, 2.0));
+    assert_eq!(
+      (gaps[1].start, gaps[1].end, gaps[1].duration),
+      (5.0, 8.0, 3.0)
+    );
+    assert!(project.detect_gaps(Some("music")).unwrap().is_empty());
+    assert!(project.detect_gaps(Some("missing")).is_err());
+  }
+
+  #[test]
+  fn test_detect_gaps_across_tracks_and_overlapping_clips() {
+    let mut project = multi_track_project();
+    // Пересекающиеся клипы одного трека не дают ложного промежутка
+    project.tracks[2].clips = vec![clip("m1", 0.0, 6.0), clip("m2", 4.0, 7.0)];
+    project.tracks[1].enabled = false;
+
+    let gaps = project.detect_gaps(None).unwrap();
+    assert_eq!(gaps.len(), 2);
+    assert!(gaps.iter().all(|gap| gap.track_id == "video"));
+  }
+
+  #[test]
+  fn test_ripple_delete_keeps_linked_clips_in_sync() {
+    let mut project = multi_track_project();
+    let summary = project
+      .ripple_delete_gaps(&RippleDeleteOptions::default())
+      .unwrap();
+
+    // Группа "b" сдвигается на наименьший сдвиг участников (видео 5.0, диалог 5.5)
+    assert_eq!(times(&project, 0), vec![(0.0, 3.0), (3.0, 5.0)]);
+    assert_eq!(times(&project, 1), vec![(0.0, 3.0), (3.5, 5.0)]);
+    assert_eq!(times(&project, 2), vec![(0.0, 12.0)]);
+    assert_eq!(summary.moved_clips.len(), 4);
+    assert_eq!(summary.closed_gaps.len(), 4);
+    assert!(summary.skipped_tracks.is_empty());
+    assert!(project.validate().is_ok());
+  }
+
+  #[test]
+  fn test_ripple_delete_respects_min_gap_and_selected_tracks() {
+    let mut project = multi_track_project();
+    project.tracks[0].clips.push(clip("v3", 10.5, 11.0));
+
+    let options = RippleDeleteOptions {
+      tracks: Some(vec!["video".to_string()]),
+      min_gap_to_keep: 1.0,
+    };
+    let summary = project.ripple_delete_gaps(&options).unwrap();
+
+    // Полусекундная пауза перед v3 сохраняется, связанный диалог следует за видео
+    assert_eq!(times(&project, 0), vec![(0.0, 3.0), (3.0, 5.0), (5.5, 6.0)]);
+    assert_eq!(times(&project, 1), vec![(0.0, 3.0), (3.5, 5.0)]);
+    assert_eq!(summary.closed_gaps.len(), 2);
+  }
+
+  #[test]
+  fn test_ripple_delete_skips_locked_track_and_holds_its_group() {
+    let mut project = multi_track_project();
+    project.tracks[1].locked = true;
+
+    let summary = project
+      .ripple_delete_gaps(&RippleDeleteOptions::default())
+      .unwrap();
+
+    assert_eq!(
+      summary.skipped_tracks,
+      vec![SkippedTrack {
+        track_id: "dialog".to_string(),
+        reason: "Трек заблокирован".to_string(),
+      }]
+    );
+    // Все видео клипы связаны с заблокированным диалогом и остаются на месте
+    assert_eq!(times(&project, 0), vec![(2.0, 5.0), (8.0, 10.0)]);
+    assert_eq!(times(&project, 1), vec![(2.0, 5.0), (8.5, 10.0)]);
+    assert!(summary.moved_clips.is_empty());
+  }
+
+  #[test]
+  fn test_ripple_delete_does_not_pass_held_clip() {
+    let mut project = ProjectSchema::new("Held".to_string());
+    project.tracks = vec![
+      track(
+        "video",
+        TrackType::Video,
+        vec![
+          linked("v1", 4.0, 6.0, "held"),
+          clip("v2", 6.0, 8.0),
+          clip("v3", 10.0, 12.0),
+        ],
+      ),
+      track(
+        "locked",
+        TrackType::Audio,
+        vec![linked("a1", 4.0, 6.0, "held")],
+      ),
+    ];
+    project.tracks[1].locked = true;
+
+    project
+      .ripple_delete_gaps(&RippleDeleteOptions::default())
+      .unwrap();
+
+    // v2 не может сдвинуться через удерживаемый v1, закрывается только промежуток за v2
+    assert_eq!(
+      times(&project, 0),
+      vec![(4.0, 6.0), (6.0, 8.0), (8.0, 10.0)]
+    );
+    assert!(project.validate().is_ok());
+  }
+
+  #[test]
+  fn test_ripple_delete_updates_transitions_of_moved_clips() {
+    let mut project = multi_track_project();
+    project.transitions.push(Transition {
+      id: "fade".to_string(),
+      transition_type: "fade".to_string(),
+      name: "Fade".to_string(),
+      duration: TransitionDuration {
+        value: 2.5,
+        min: None,
+        max: None,
+      },
+      category: None,
+      tags: Vec::new(),
+      complexity: None,
+      enabled: true,
+      parameters: HashMap::new(),
+      ffmpeg_command: None,
+      easing: None,
+      direction: None,
+      from_clip_id: Some("v1".to_string()),
+      to_clip_id: Some("v2".to_string()),
+    });
+
+    let summary = project
+      .ripple_delete_gaps(&RippleDeleteOptions::default())
+      .unwrap();
+
+    assert_eq!(summary.updated_transitions.len(), 1);
+    // Переход не длиннее сдвинутого клипа v2
+    assert_eq!(project.transitions[0].duration.value, 2.0);
+  }
+}
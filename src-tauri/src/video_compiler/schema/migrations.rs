@@ -0,0 +1,445 @@
+//! Migrations - Обновление проектов, сохраненных прежними версиями схемы
+//!
+//! Проект читается как JSON, и упорядоченные миграции по очереди переводят
+//! его на следующую версию схемы: переименовывают поля, заполняют новые
+//! поля значениями по умолчанию и перестраивают структуры. После миграций
+//! проект десериализуется и проходит валидацию. Поля, которые текущая схема
+//! не знает и которые были бы молча потеряны при сохранении, возвращаются
+//! в предупреждениях.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use super::export::ProjectSettings;
+use super::project::{ProjectSchema, SCHEMA_VERSION};
+use super::timeline::Timeline;
+use crate::video_compiler::error::{Result, VideoCompilerError};
+
+/// Версия проектов без поля `version` (прототип до 1.0)
+pub const LEGACY_SCHEMA_VERSION: &str = "0.9.0";
+
+/// Миграция JSON проекта на следующую версию схемы
+struct SchemaMigration {
+  from: &'static str,
+  to: &'static str,
+  description: &'static str,
+  apply: fn(&mut Map<String, Value>),
+}
+
+/// Миграции в порядке версий. Последняя миграция ведет к `SCHEMA_VERSION`.
+const MIGRATIONS: &[SchemaMigration] = &[
+  SchemaMigration {
+    from: "0.9.0",
+    to: "1.0.0",
+    description: "Недостающие разделы проекта, свойства клипов и структура переходов",
+    apply: migrate_prototype_layout,
+  },
+  SchemaMigration {
+    from: "1.0.0",
+    to: "1.1.0",
+    description: "Блокировка треков, заметки и теги клипов",
+    apply: migrate_track_locks_and_clip_notes,
+  },
+];
+
+/// Примененная миграция
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppliedMigration {
+  pub from: String,
+  pub to: String,
+  pub description: String,
+}
+
+/// Проект после миграции
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigratedProject {
+  /// Проект в текущей версии схемы
+  pub project: ProjectSchema,
+  /// Версия, с которой проект был сохранен
+  pub source_version: String,
+  /// Примененные миграции по порядку
+  pub applied: Vec<AppliedMigration>,
+  /// Потерянные при преобразовании данные и допущения миграции
+  pub warnings: Vec<String>,
+}
+
+impl MigratedProject {
+  /// Проект был сохранен прежней версией схемы
+  pub fn is_migrated(&self) -> bool {
+    !self.applied.is_empty()
+  }
+}
+
+/// Прочитать JSON проекта любой поддерживаемой версии схемы
+pub fn load_project_with_migration(json: &str) -> Result<MigratedProject> {
+  let value: Value = serde_json::from_str(json).map_err(|e| {
+    VideoCompilerError::ValidationError(format!("Некорректный JSON проекта: {e}").into())
+  })?;
+  migrate_project_value(value)
+}
+
+/// Перевести JSON проекта на текущую версию схемы.
+///
+/// Проекты более новой версии не открываются: их поля нельзя сохранить
+/// без потерь.
+pub fn migrate_project_value(mut value: Value) -> Result<MigratedProject> {
+  let Some(root) = value.as_object_mut() else {
+    return Err(VideoCompilerError::ValidationError(
+      "Проект должен быть JSON объектом".into(),
+    ));
+  };
+
+  let mut warnings = Vec::new();
+  let source_version = match root.get("version") {
+    Some(Value::String(version)) if !version.is_empty() => version.clone(),
+    None | Some(Value::Null) => {
+      warnings.push(format!(
+        "Версия схемы не указана, проект считается версией {LEGACY_SCHEMA_VERSION}"
+      ));
+      LEGACY_SCHEMA_VERSION.to_string()
+    }
+    Some(other) => {
+      return Err(VideoCompilerError::ValidationError(
+        format!("Некорректная версия схемы проекта: {other}").into(),
+      ))
+    }
+  };
+
+  let source = parse_version(&source_version).ok_or_else(|| {
+    VideoCompilerError::ValidationError(
+      format!("Некорректная версия схемы проекта: '{source_version}'").into(),
+    )
+  })?;
+  if source > schema_version(SCHEMA_VERSION) {
+    return Err(VideoCompilerError::UnsupportedProjectVersion {
+      found: source_version,
+      supported: SCHEMA_VERSION.to_string(),
+    });
+  }
+
+  let mut applied = Vec::new();
+  for migration in MIGRATIONS {
+    if source < schema_version(migration.to) {
+      (migration.apply)(root);
+      applied.push(AppliedMigration {
+        from: migration.from.to_string(),
+        to: migration.to.to_string(),
+        description: migration.description.to_string(),
+      });
+    }
+  }
+  root.insert(
+    "version".to_string(),
+    Value::String(SCHEMA_VERSION.to_string()),
+  );
+
+  let project: ProjectSchema = serde_json::from_value(value.clone()).map_err(|e| {
+    VideoCompilerError::ValidationError(
+      format!("Не удалось прочитать проект версии {source_version}: {e}").into(),
+    )
+  })?;
+  collect_dropped_fields(&value, &serde_json::to_value(&project)?, "", &mut warnings);
+  project.validate().map_err(VideoCompilerError::validation)?;
+
+  if !applied.is_empty() {
+    log::info!(
+      "Проект '{}' обновлен со схемы {source_version} до {SCHEMA_VERSION}",
+      project.metadata.name
+    );
+  }
+  Ok(MigratedProject {
+    project,
+    source_version,
+    applied,
+    warnings,
+  })
+}
+
+/// Разобрать версию `major.minor.patch`; недостающие части равны нулю
+fn parse_version(version: &str) -> Option<(u32, u32, u32)> {
+  let mut parts = version.trim().split('.');
+  let major = parts.next()?.parse().ok()?;
+  let minor = parts.next().map_or(Some(0), |part| part.parse().ok())?;
+  let patch = parts.next().map_or(Some(0), |part| part.parse().ok())?;
+  if parts.next().is_some() {
+    return None;
+  }
+  Some((major, minor, patch))
+}
+
+/// Версия из таблицы миграций
+fn schema_version(version: &str) -> (u32, u32, u32) {
+  parse_version(version).expect("schema versions are valid")
+}
+
+/// Объекты массива `key`
+fn objects_mut<'a>(
+  object: &'a mut Map<String, Value>,
+  key: &str,
+) -> impl Iterator<Item = &'a mut Map<String, Value>> {
+  object
+    .get_mut(key)
+    .and_then(Value::as_array_mut)
+    .into_iter()
+    .flatten()
+    .filter_map(Value::as_object_mut)
+}
+
+/// Переименовать поле, если нового имени еще нет
+fn rename_field(object: &mut Map<String, Value>, from: &str, to: &str) {
+  if let Some(value) = object.remove(from) {
+    object.entry(to).or_insert(value);
+  }
+}
+
+/// Добавить отсутствующие поля из значения по умолчанию
+fn fill_missing(object: &mut Map<String, Value>, defaults: Value) {
+  if let Value::Object(defaults) = defaults {
+    for (key, value) in defaults {
+      object.entry(key).or_insert(value);
+    }
+  }
+}
+
+/// 0.9.0 -> 1.0.0: прототип хранил только треки и переходы, переходы
+/// ссылались на клипы полями `from_clip`/`to_clip` и имели числовую
+/// длительность
+fn migrate_prototype_layout(root: &mut Map<String, Value>) {
+  for key in [
+    "tracks",
+    "effects",
+    "transitions",
+    "filters",
+    "templates",
+    "style_templates",
+    "subtitles",
+  ] {
+    root.entry(key).or_insert_with(|| json!([]));
+  }
+
+  let timeline = root.entry("timeline").or_insert_with(|| json!({}));
+  if let Some(timeline) = timeline.as_object_mut() {
+    fill_missing(timeline, json!(Timeline::default()));
+  }
+  let settings = root.entry("settings").or_insert_with(|| json!({}));
+  if let Some(settings) = settings.as_object_mut() {
+    fill_missing(settings, json!(ProjectSettings::default()));
+  }
+
+  for track in objects_mut(root, "tracks") {
+    for clip in objects_mut(track, "clips") {
+      clip.entry("properties").or_insert_with(|| {
+        json!({
+          "notes": null,
+          "tags": [],
+          "custom_metadata": {},
+        })
+      });
+    }
+  }
+
+  for transition in objects_mut(root, "transitions") {
+    rename_field(transition, "type", "transition_type");
+    rename_field(transition, "from_clip", "from_clip_id");
+    rename_field(transition, "to_clip", "to_clip_id");
+    rename_field(transition, "params", "parameters");
+
+    if let Some(seconds) = transition.get("duration").and_then(Value::as_f64) {
+      transition.insert(
+        "duration".to_string(),
+        json!({ "value": seconds, "min": null, "max": null }),
+      );
+    }
+    let name = transition
+      .get("transition_type")
+      .cloned()
+      .unwrap_or_else(|| json!("transition"));
+    transition.entry("name").or_insert(name);
+    transition.entry("tags").or_insert_with(|| json!([]));
+    transition.entry("enabled").or_insert(json!(true));
+    transition.entry("parameters").or_insert_with(|| json!({}));
+  }
+}
+
+/// 1.0.0 -> 1.1.0: треки получили блокировку, а заметки и теги клипов
+/// переехали из `properties` на уровень клипа
+fn migrate_track_locks_and_clip_notes(root: &mut Map<String, Value>) {
+  for track in objects_mut(root, "tracks") {
+    track.entry("locked").or_insert(json!(false));
+
+    for clip in objects_mut(track, "clips") {
+      let properties = clip.get("properties").cloned().unwrap_or(Value::Null);
+      if !clip.contains_key("notes") {
+        clip.insert(
+          "notes".to_string(),
+          properties.get("notes").cloned().unwrap_or(Value::Null),
+        );
+      }
+      if !clip.contains_key("tags") {
+        clip.insert(
+          "tags".to_string(),
+          properties.get("tags").cloned().unwrap_or_else(|| json!([])),
+        );
+      }
+    }
+  }
+}
+
+/// Значение без данных, потеря которого не важна
+fn is_empty_value(value: &Value) -> bool {
+  match value {
+    Value::Null => true,
+    Value::Array(items) => items.is_empty(),
+    Value::Object(fields) => fields.is_empty(),
+    _ => false,
+  }
+}
+
+/// Найти поля исходного JSON, которых нет в сериализованной схеме
+fn collect_dropped_fields(
+  original: &Value,
+  normalized: &Value,
+  path: &str,
+  warnings: &mut Vec<String>,
+) {
+  match (original, normalized) {
+    (Value::Object(original), Value::Object(normalized)) => {
+      for (key, value) in original {
+        let child = if path.is_empty() {
+          key.clone()
+        } else {
+          format!("{path}.{key}")
+        };
+        match normalized.get(key) {
+          Some(normalized) => collect_dropped_fields(value, normalized, &child, warnings),
+          None if is_empty_value(value) => {}
+          None => warnings.push(format!(
+            "Поле '{child}' не поддерживается версией схемы {SCHEMA_VERSION} и будет потеряно при сохранении"
+          )),
+        }
+      }
+    }
+    (Value::Array(original), Value::Array(normalized)) if original.len() == normalized.len() => {
+      for (idx, (value, normalized)) in original.iter().zip(normalized).enumerate() {
+        collect_dropped_fields(value, normalized, &format!("{path}[{idx}]"), warnings);
+      }
+    }
+    _ => {}
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const PROJECT_V0_9: &str = include_str!("fixtures/project_v0_9.json");
+  const PROJECT_V1_0: &str = include_str!("fixtures/project_v1_0.json");
+
+  #[test]
+  fn test_prototype_project_is_migrated() {
+    let migrated = load_project_with_migration(PROJECT_V0_9).unwrap();
+    assert!(migrated.is_migrated());
+    assert_eq!(migrated.source_version, "0.9.0");
+    let steps: Vec<(&str, &str)> = migrated
+      .applied
+      .iter()
+      .map(|m| (m.from.as_str(), m.to.as_str()))
+      .collect();
+    assert_eq!(steps, [("0.9.0", "1.0.0"), ("1.0.0", "1.1.0")]);
+
+    let project = &migrated.project;
+    assert_eq!(project.version, SCHEMA_VERSION);
+    assert_eq!(project.timeline.fps, 25);
+    assert_eq!(project.tracks[0].clips.len(), 2);
+    assert!(!project.tracks[0].locked);
+    assert!(project.subtitles.is_empty());
+    assert_eq!(project.settings.frame_rate, 30.0);
+
+    let transition = &project.transitions[0];
+    assert_eq!(transition.transition_type, "fade");
+    assert_eq!(transition.name, "fade");
+    assert_eq!(transition.duration.value, 1.0);
+    assert_eq!(transition.from_clip_id.as_deref(), Some("c1"));
+    assert_eq!(transition.to_clip_id.as_deref(), Some("c2"));
+    assert!(transition.enabled);
+    assert_eq!(transition.parameters["intensity"], json!(0.5));
+
+    // Громкость клипа прототипа переносится без потерь
+    assert_eq!(project.tracks[0].clips[0].volume, 0.8);
+    assert_eq!(project.tracks[0].clips[1].volume, 1.0);
+    assert!(migrated.warnings.is_empty());
+  }
+
+  #[test]
+  fn test_v1_0_project_is_migrated() {
+    let migrated = load_project_with_migration(PROJECT_V1_0).unwrap();
+    assert_eq!(migrated.source_version, "1.0.0");
+    assert_eq!(migrated.applied.len(), 1);
+    assert_eq!(migrated.applied[0].to, SCHEMA_VERSION);
+
+    let clip = &migrated.project.tracks[0].clips[0];
+    assert_eq!(clip.notes.as_deref(), Some("Лучший дубль"));
+    assert_eq!(clip.tags, ["interview", "a-cam"]);
+    // Явно заданные заметки уровня клипа не перезаписываются
+    let clip = &migrated.project.tracks[0].clips[1];
+    assert_eq!(clip.notes.as_deref(), Some("Перебивка"));
+    assert!(migrated.project.tracks.iter().all(|t| !t.locked));
+
+    assert_eq!(
+      migrated.warnings,
+      [format!(
+        "Поле 'settings.export.two_pass' не поддерживается версией схемы {SCHEMA_VERSION} и будет потеряно при сохранении"
+      )]
+    );
+  }
+
+  #[test]
+  fn test_current_project_is_not_migrated() {
+    let project = ProjectSchema::new("Current".to_string());
+    let json = serde_json::to_string(&project).unwrap();
+
+    let migrated = load_project_with_migration(&json).unwrap();
+    assert!(!migrated.is_migrated());
+    assert!(migrated.warnings.is_empty());
+    assert_eq!(migrated.source_version, SCHEMA_VERSION);
+  }
+
+  #[test]
+  fn test_newer_project_is_refused() {
+    let mut value = serde_json::to_value(ProjectSchema::new("Future".to_string())).unwrap();
+    value["version"] = json!("2.0.0");
+
+    let result = migrate_project_value(value);
+    assert!(matches!(
+      result,
+      Err(VideoCompilerError::UnsupportedProjectVersion { ref found, ref supported })
+        if found == "2.0.0" && supported == SCHEMA_VERSION
+    ));
+  }
+
+  #[test]
+  fn test_missing_and_invalid_versions() {
+    let mut value: Value = serde_json::from_str(PROJECT_V0_9).unwrap();
+    value.as_object_mut().unwrap().remove("version");
+    let migrated = migrate_project_value(value).unwrap();
+    assert_eq!(migrated.source_version, LEGACY_SCHEMA_VERSION);
+    assert_eq!(migrated.applied.len(), MIGRATIONS.len());
+    assert!(migrated.warnings[0].contains(LEGACY_SCHEMA_VERSION));
+
+    let mut value: Value = serde_json::from_str(PROJECT_V1_0).unwrap();
+    value["version"] = json!("one");
+    assert!(matches!(
+      migrate_project_value(value),
+      Err(VideoCompilerError::ValidationError(_))
+    ));
+    assert!(load_project_with_migration("[]").is_err());
+
+    assert_eq!(parse_version("1.2"), Some((1, 2, 0)));
+    assert_eq!(parse_version("1.2.3.4"), None);
+    assert_eq!(
+      MIGRATIONS.last().map(|m| m.to),
+      Some(SCHEMA_VERSION),
+      "the last migration must lead to the current schema version"
+    );
+  }
+}
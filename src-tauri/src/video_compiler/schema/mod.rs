@@ -3,25 +3,40 @@
 //! Схема разделена на следующие модули:
 //! - `project` - Основная схема проекта и метаданные
 //! - `timeline` - Timeline, треки и клипы
+//! - `clip_attributes` - Копирование и вставка атрибутов клипа
+//! - `gaps` - Пустые промежутки timeline и ripple delete
+//! - `migrations` - Обновление проектов прежних версий схемы
+//! - `sequence` - Вложенные последовательности (составные клипы)
 //! - `effects` - Эффекты, фильтры и переходы
+//! - `chroma_key` - Параметры хромакея и подстановка фона
 //! - `templates` - Шаблоны и стилевые шаблоны
 //! - `subtitles` - Субтитры и их настройки
 //! - `export` - Настройки экспорта и форматы вывода
 //! - `common` - Общие типы и утилиты
 
+pub mod chroma_key;
+pub mod clip_attributes;
 pub mod common;
 pub mod effects;
 pub mod export;
+pub mod gaps;
+pub mod migrations;
 pub mod project;
+pub mod sequence;
 pub mod subtitles;
 pub mod templates;
 pub mod timeline;
 
 // Re-export всех основных типов для удобства использования
+pub use chroma_key::*;
+pub use clip_attributes::*;
 pub use common::*;
 pub use effects::*;
 pub use export::*;
+pub use gaps::*;
+pub use migrations::*;
 pub use project::*;
+pub use sequence::*;
 pub use subtitles::*;
 pub use templates::*;
 pub use timeline::*;
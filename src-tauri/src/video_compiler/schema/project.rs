@@ -2,12 +2,21 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
+use crate::language_tauri::i18n::LocalizedMessage;
+
+use super::common::{Timecode, TimecodeRate};
 use super::effects::{Effect, Filter, Transition};
-use super::export::ProjectSettings;
+use super::export::{HdrMode, OutputFormat, ProjectSettings};
+use super::sequence::Sequence;
 use super::subtitles::Subtitle;
 use super::templates::{StyleTemplate, Template};
-use super::timeline::{Timeline, Track};
+use super::timeline::{ClipSearchMatch, ClipSearchQuery, Timeline, Track, TrackType};
+
+/// Текущая версия схемы проекта. Проекты прежних версий обновляются при
+/// загрузке (см. `migrations`), сохранение всегда пишет эту версию.
+pub const SCHEMA_VERSION: &str = "1.1.0";
 
 /// Основная схема проекта Timeline Studio
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -32,6 +41,9 @@ pub struct ProjectSchema {
   pub style_templates: Vec<StyleTemplate>,
   /// Субтитры проекта
   pub subtitles: Vec<Subtitle>,
+  /// Вложенные последовательности (составные клипы) по ID
+  #[serde(default)]
+  pub sequences: HashMap<String, Sequence>,
   /// Настройки проекта и экспорта
   pub settings: ProjectSettings,
 }
@@ -40,7 +52,7 @@ impl ProjectSchema {
   /// Создать новый пустой проект
   pub fn new(name: String) -> Self {
     Self {
-      version: "1.0.0".to_string(),
+      version: SCHEMA_VERSION.to_string(),
       metadata: ProjectMetadata {
         name,
         description: None,
@@ -56,24 +68,26 @@ impl ProjectSchema {
       templates: Vec::new(),
       style_templates: Vec::new(),
       subtitles: Vec::new(),
+      sequences: HashMap::new(),
       settings: ProjectSettings::default(),
     }
   }
 
-  /// Валидация схемы проекта
-  pub fn validate(&self) -> Result<(), String> {
+  /// Валидация схемы проекта. Основные ошибки приходят с ключом каталога
+  /// сообщений, чтобы интерфейс мог показать их на любом языке.
+  pub fn validate(&self) -> Result<(), LocalizedMessage> {
     // Проверка версии
     if self.version.is_empty() {
-      return Err("Версия проекта не может быть пустой".to_string());
+      return Err(LocalizedMessage::new("validation.empty_version", &[]));
     }
 
     // Проверка timeline
     if self.timeline.fps == 0 {
-      return Err("FPS должен быть больше 0".to_string());
+      return Err(LocalizedMessage::new("validation.invalid_fps", &[]));
     }
 
     if self.timeline.resolution.0 == 0 || self.timeline.resolution.1 == 0 {
-      return Err("Разрешение должно быть больше 0x0".to_string());
+      return Err(LocalizedMessage::new("validation.invalid_resolution", &[]));
     }
 
     // Проверка треков
@@ -88,13 +102,268 @@ impl ProjectSchema {
 
       for i in 0..clips.len().saturating_sub(1) {
         if clips[i].end_time > clips[i + 1].start_time {
+          return Err(LocalizedMessage::new(
+            "validation.clips_overlap",
+            &[
+              ("track", track.name.clone()),
+              ("first", clips[i].id.clone()),
+              ("second", clips[i + 1].id.clone()),
+            ],
+          ));
+        }
+      }
+    }
+
+    // Переходы не должны ссылаться на клипы отключенных треков:
+    // такие клипы не попадают во входы FFmpeg и граф фильтров ломается
+    for transition in self.transitions.iter().filter(|t| t.enabled) {
+      let clip_ids = [&transition.from_clip_id, &transition.to_clip_id];
+      for clip_id in clip_ids.into_iter().flatten() {
+        if let Some((track_idx, _)) = self.find_clip_position(clip_id) {
+          let track = &self.tracks[track_idx];
+          if !track.enabled {
+            return Err(LocalizedMessage::new(
+              "validation.transition_disabled_track",
+              &[
+                ("transition", transition.id.clone()),
+                ("clip", clip_id.clone()),
+                ("track", track.name.clone()),
+              ],
+            ));
+          }
+        }
+      }
+    }
+
+    self.validate_sequences()?;
+    self.validate_ducking()?;
+    self.validate_renditions()?;
+    self.validate_audio_output()?;
+    self.validate_color_output()?;
+    self.validate_effect_keyframes()?;
+    self.validate_chroma_keys()?;
+    if let Some(watermark) = &self.settings.export.watermark {
+      watermark.validate()?;
+    }
+
+    Ok(())
+  }
+
+  /// Проверка ключевых кадров эффектов относительно клипов, к которым они применены
+  fn validate_effect_keyframes(&self) -> Result<(), String> {
+    for effect in self
+      .effects
+      .iter()
+      .filter(|effect| !effect.keyframes.is_empty())
+    {
+      let mut applied = false;
+      for clip in self.tracks.iter().flat_map(|track| &track.clips) {
+        if clip.effects.contains(&effect.id) {
+          applied = true;
+          effect.validate_keyframes(Some(clip.get_timeline_duration()))?;
+        }
+      }
+      if !applied {
+        effect.validate_keyframes(None)?;
+      }
+    }
+    Ok(())
+  }
+
+  /// Проверка настроек экспорта в аудиоформаты
+  fn validate_audio_output(&self) -> Result<(), String> {
+    if let Some(bit_depth) = self.settings.export.audio_bit_depth {
+      if bit_depth != 16 && bit_depth != 24 {
+        return Err(format!(
+          "Разрядность аудио должна быть 16 или 24 бит, указано {bit_depth}"
+        ));
+      }
+    }
+
+    let format = &self.settings.output.format;
+    if !format.is_audio_only() {
+      return Ok(());
+    }
+
+    // Видео треки в аудиоформат не попадают, микс собирается из аудио треков
+    let has_audio_track = self
+      .tracks
+      .iter()
+      .any(|track| track.track_type == TrackType::Audio && track.is_rendered());
+    if !has_audio_track {
+      return Err(format!(
+        "Для экспорта в {format:?} нужен хотя бы один включенный аудио трек"
+      ));
+    }
+
+    if !self.settings.export.renditions.is_empty() {
+      return Err("Варианты экспорта доступны только для видео форматов".to_string());
+    }
+
+    Ok(())
+  }
+
+  /// Проверка ссылок ducking на sidechain треки и отсутствия циклов
+  fn validate_ducking(&self) -> Result<(), String> {
+    let sidechain_of = |track: &Track| -> Option<&Track> {
+      let ducking = track.ducking.as_ref()?;
+      self
+        .tracks
+        .iter()
+        .find(|t| t.id == ducking.sidechain_track_id)
+    };
+
+    for track in &self.tracks {
+      let Some(ducking) = &track.ducking else {
+        continue;
+      };
+      if track.track_type != TrackType::Audio {
+        return Err(format!(
+          "Ducking доступен только для аудио треков, трек '{}'",
+          track.name
+        ));
+      }
+      match sidechain_of(track) {
+        None => {
           return Err(format!(
-            "Клипы пересекаются по времени в треке '{}': {} и {}",
-            track.name,
-            clips[i].id,
-            clips[i + 1].id
+            "Sidechain трек '{}' для ducking трека '{}' не найден",
+            ducking.sidechain_track_id, track.name
+          ))
+        }
+        Some(sidechain) if sidechain.track_type != TrackType::Audio => {
+          return Err(format!(
+            "Sidechain трек '{}' должен быть аудио треком",
+            sidechain.name
+          ))
+        }
+        Some(_) => {}
+      }
+
+      // Цепочка sidechain не должна возвращаться к исходному треку
+      let mut current = sidechain_of(track);
+      for _ in 0..self.tracks.len() {
+        let Some(next) = current else {
+          break;
+        };
+        if next.id == track.id {
+          return Err(format!(
+            "Циклическая цепочка ducking через трек '{}'",
+            track.name
           ));
         }
+        current = sidechain_of(next);
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Проверка цветовых настроек и совместимости HDR с кодеком и контейнером
+  fn validate_color_output(&self) -> Result<(), String> {
+    const PRIMARIES: &[&str] = &["bt709", "bt2020", "bt470bg", "smpte170m", "smpte432"];
+    const TRANSFERS: &[&str] = &[
+      "bt709",
+      "smpte2084",
+      "arib-std-b67",
+      "iec61966-2-1",
+      "smpte170m",
+      "linear",
+    ];
+    const MATRICES: &[&str] = &["bt709", "bt2020nc", "bt2020c", "bt470bg", "smpte170m"];
+
+    let export = &self.settings.export;
+    let fields = [
+      ("color_primaries", &export.color_primaries, PRIMARIES),
+      (
+        "transfer_characteristics",
+        &export.transfer_characteristics,
+        TRANSFERS,
+      ),
+      ("matrix_coefficients", &export.matrix_coefficients, MATRICES),
+    ];
+    for (name, value, allowed) in fields {
+      if let Some(value) = value {
+        if !allowed.contains(&value.as_str()) {
+          return Err(format!("Неподдерживаемое значение {name}: '{value}'"));
+        }
+      }
+    }
+
+    if export.hdr_metadata.is_some() && export.hdr_mode != HdrMode::Hdr10 {
+      return Err("Метаданные мастеринга поддерживаются только для HDR10".to_string());
+    }
+
+    if !export.hdr_mode.is_hdr_output() {
+      return Ok(());
+    }
+
+    // HDR кодируется в HEVC Main 10 (MP4/MOV/MKV) или VP9 Profile 2 (WebM)
+    let format = &self.settings.output.format;
+    if !matches!(
+      format,
+      OutputFormat::Mp4 | OutputFormat::Mov | OutputFormat::Mkv | OutputFormat::WebM
+    ) {
+      return Err(format!("Формат {format:?} не поддерживает HDR вывод"));
+    }
+    if export.encoding_profile.as_deref() == Some("baseline") {
+      return Err("Профиль baseline не поддерживает HDR вывод".to_string());
+    }
+    if let Some(encoder) = &export.preferred_gpu_encoder {
+      if encoder.starts_with("h264") {
+        return Err(format!("Кодировщик {encoder} не поддерживает HDR вывод"));
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Проверка вариантов многовариантного экспорта
+  fn validate_renditions(&self) -> Result<(), String> {
+    let export = &self.settings.export;
+    if export.generate_hls {
+      if export.renditions.is_empty() {
+        return Err("Для HLS необходимо указать хотя бы один вариант экспорта".to_string());
+      }
+      if !matches!(
+        self.settings.output.format,
+        OutputFormat::Mp4 | OutputFormat::Mov
+      ) {
+        return Err("HLS поддерживается только для форматов MP4 и MOV".to_string());
+      }
+      // Сегменты HLS (MPEG-TS) не поддерживают текстовые дорожки
+      if export.subtitle_mode.muxes() && !self.subtitles.is_empty() {
+        return Err("HLS не поддерживает субтитры отдельной дорожкой".to_string());
+      }
+    }
+
+    // Варианты только уменьшают кадр timeline, масштабирование вверх не допускается
+    let (max_width, max_height) = self.timeline.resolution;
+    let mut suffixes = std::collections::HashSet::new();
+    for rendition in &export.renditions {
+      let (width, height) = (rendition.resolution.width, rendition.resolution.height);
+      if width == 0 || height == 0 {
+        return Err(format!(
+          "Разрешение варианта '{}' должно быть больше 0x0",
+          rendition.suffix
+        ));
+      }
+      if width > max_width || height > max_height {
+        return Err(format!(
+          "Вариант '{}' ({width}x{height}) превышает разрешение timeline {max_width}x{max_height}",
+          rendition.suffix
+        ));
+      }
+      if rendition.video_bitrate == 0 {
+        return Err(format!(
+          "Битрейт варианта '{}' должен быть больше 0",
+          rendition.suffix
+        ));
+      }
+      if rendition.suffix.is_empty() || !suffixes.insert(rendition.suffix.as_str()) {
+        return Err(format!(
+          "Суффикс варианта '{}' должен быть непустым и уникальным",
+          rendition.suffix
+        ));
       }
     }
 
@@ -116,6 +385,62 @@ impl ProjectSchema {
     self.metadata.modified_at = Utc::now();
   }
 
+  /// Частота timecode проекта: стандартная частота, ближайшая к частоте
+  /// кадров экспорта
+  pub fn timecode_rate(&self) -> TimecodeRate {
+    TimecodeRate::nearest(self.settings.frame_rate)
+  }
+
+  /// Timecode позиции timeline `seconds` с учетом начального timecode
+  pub fn timecode_at(&self, seconds: f64) -> Timecode {
+    let rate = self.timecode_rate();
+    let settings = &self.settings.timecode;
+    let start = settings
+      .start
+      .map(|start| start.convert(rate, settings.drop_frame))
+      .unwrap_or_else(|| Timecode::from_frames(0, rate, settings.drop_frame));
+    start + Timecode::from_seconds(seconds.max(0.0), rate, settings.drop_frame).frame
+  }
+
+  /// Проект, содержащий только клипы отрезка `[start_time, end_time)` со
+  /// временами относительно его начала. Водяной знак и timecode
+  /// продолжают проект.
+  pub fn extract_range(&self, start_time: f64, end_time: f64) -> ProjectSchema {
+    let mut segment = self.clone();
+    segment.timeline.duration = end_time - start_time;
+
+    for track in &mut segment.tracks {
+      track
+        .clips
+        .retain(|clip| clip.start_time < end_time && clip.end_time > start_time);
+
+      for clip in &mut track.clips {
+        if clip.start_time < start_time {
+          clip.source_start += start_time - clip.start_time;
+          clip.start_time = 0.0;
+        } else {
+          clip.start_time -= start_time;
+        }
+
+        if clip.end_time > end_time {
+          clip.end_time = end_time - start_time;
+        } else {
+          clip.end_time -= start_time;
+        }
+      }
+    }
+
+    segment.settings.export.watermark = segment
+      .settings
+      .export
+      .watermark
+      .take()
+      .and_then(|watermark| watermark.shifted(start_time, end_time - start_time));
+    segment.settings.timecode.start = Some(self.timecode_at(start_time));
+
+    segment
+  }
+
   /// Найти клип по ID во всех треках
   pub fn find_clip_by_id(&self, clip_id: &str) -> Option<&super::timeline::Clip> {
     for track in &self.tracks {
@@ -128,6 +453,45 @@ impl ProjectSchema {
     None
   }
 
+  /// Найти индексы трека и клипа по ID клипа
+  pub fn find_clip_position(&self, clip_id: &str) -> Option<(usize, usize)> {
+    self
+      .tracks
+      .iter()
+      .enumerate()
+      .find_map(|(track_idx, track)| {
+        track
+          .clips
+          .iter()
+          .position(|clip| clip.id == clip_id)
+          .map(|clip_idx| (track_idx, clip_idx))
+      })
+  }
+
+  /// Найти клипы треков проекта по условиям поиска
+  pub fn find_clips(&self, query: &ClipSearchQuery) -> Vec<ClipSearchMatch> {
+    self
+      .tracks
+      .iter()
+      .enumerate()
+      .flat_map(|(track_index, track)| {
+        track
+          .clips
+          .iter()
+          .enumerate()
+          .filter(|(_, clip)| query.matches(track, clip))
+          .map(move |(clip_index, clip)| ClipSearchMatch {
+            clip_id: clip.id.clone(),
+            track_id: track.id.clone(),
+            track_index,
+            clip_index,
+            start_time: clip.start_time,
+            end_time: clip.end_time,
+          })
+      })
+      .collect()
+  }
+
   /// Получить путь к файлу по ID клипа
   pub fn get_clip_file_path(&self, clip_id: &str) -> Option<String> {
     if let Some(clip) = self.find_clip_by_id(clip_id) {
@@ -139,6 +503,130 @@ impl ProjectSchema {
       None
     }
   }
+
+  /// Снимок проекта, достаточный для построения одного кадра в момент `timestamp`.
+  ///
+  /// Остаются включенные видео треки с клипами, пересекающими момент, включенные
+  /// в этот момент субтитры и используемые клипами эффекты, фильтры и шаблоны.
+  /// Время сдвигается так, что `timestamp` становится началом снимка: одинаковые
+  /// снимки дают одинаковый кадр.
+  pub fn frame_snapshot(&self, timestamp: f64) -> ProjectSchema {
+    let tracks: Vec<Track> = self
+      .tracks
+      .iter()
+      .filter(|track| track.enabled && track.track_type == TrackType::Video)
+      .filter_map(|track| {
+        let clips: Vec<_> = track
+          .clips
+          .iter()
+          .filter(|clip| clip.contains_time(timestamp))
+          .map(|clip| {
+            let mut clip = clip.clone();
+            clip.source_start += (timestamp - clip.start_time) * clip.speed;
+            clip.end_time -= timestamp;
+            clip.start_time = 0.0;
+            // Звук в кадр не попадает
+            clip.external_audio = None;
+            clip
+          })
+          .collect();
+
+        (!clips.is_empty()).then(|| Track {
+          clips,
+          ..track.clone()
+        })
+      })
+      .collect();
+
+    let effect_ids: HashSet<&str> = tracks
+      .iter()
+      .flat_map(|track| {
+        track
+          .effects
+          .iter()
+          .chain(track.clips.iter().flat_map(|clip| &clip.effects))
+      })
+      .map(String::as_str)
+      .collect();
+    let filter_ids: HashSet<&str> = tracks
+      .iter()
+      .flat_map(|track| {
+        track
+          .filters
+          .iter()
+          .chain(track.clips.iter().flat_map(|clip| &clip.filters))
+      })
+      .map(String::as_str)
+      .collect();
+    let template_ids: HashSet<&str> = tracks
+      .iter()
+      .flat_map(|track| &track.clips)
+      .filter_map(|clip| clip.template_id.as_deref())
+      .collect();
+
+    let subtitles = self
+      .subtitles
+      .iter()
+      .filter(|subtitle| {
+        subtitle.enabled && subtitle.start_time <= timestamp && timestamp < subtitle.end_time
+      })
+      .map(|subtitle| {
+        let mut subtitle = subtitle.clone();
+        subtitle.start_time -= timestamp;
+        subtitle.end_time -= timestamp;
+        subtitle
+      })
+      .collect();
+
+    ProjectSchema {
+      version: self.version.clone(),
+      metadata: self.metadata.clone(),
+      timeline: Timeline {
+        duration: tracks
+          .iter()
+          .flat_map(|track| &track.clips)
+          .map(|clip| clip.end_time)
+          .fold(0.0, f64::max),
+        ..self.timeline.clone()
+      },
+      effects: filter_by_id(&self.effects, &effect_ids, |effect| &effect.id),
+      transitions: Vec::new(),
+      filters: filter_by_id(&self.filters, &filter_ids, |filter| &filter.id),
+      templates: filter_by_id(&self.templates, &template_ids, |template| &template.id),
+      style_templates: filter_by_id(&self.style_templates, &template_ids, |template| {
+        &template.id
+      }),
+      subtitles,
+      sequences: self.sequences.clone(),
+      settings: self.settings.clone(),
+      tracks,
+    }
+  }
+
+  /// Хеш содержимого, влияющего на изображение (без метаданных проекта)
+  pub fn content_hash(&self) -> String {
+    let content = serde_json::to_vec(&(
+      &self.tracks,
+      &self.effects,
+      &self.filters,
+      &self.templates,
+      &self.style_templates,
+      &self.subtitles,
+      // Порядок HashMap не стабилен, поэтому последовательности сортируются по ID
+      self.sequences.iter().collect::<BTreeMap<_, _>>(),
+    ))
+    .unwrap_or_default();
+    format!("{:016x}", xxhash_rust::xxh3::xxh3_64(&content))
+  }
+}
+
+/// Оставить элементы, ID которых входят в набор
+fn filter_by_id<T: Clone>(items: &[T], ids: &HashSet<&str>, id: impl Fn(&T) -> &String) -> Vec<T> {
+  items
+    .iter()
+    .filter(|item| ids.contains(id(item).as_str()))
+    .cloned()
+    .collect()
 }
 
 /// Метаданные проекта
@@ -160,7 +648,7 @@ pub struct ProjectMetadata {
 mod tests {
   use super::*;
   use crate::video_compiler::schema::timeline::{
-    Clip, ClipProperties, ClipSource, Track, TrackType,
+    Clip, ClipProperties, ClipSource, DuckingConfig, Track, TrackType,
   };
 
   fn create_test_project() -> ProjectSchema {
@@ -185,6 +673,18 @@ mod tests {
       crop: None,
       transform: None,
       audio_track_index: None,
+      audio_stream_index: None,
+      source_rotation: 0,
+      color_label: None,
+      notes: None,
+      tags: Vec::new(),
+      fade_in: None,
+      fade_out: None,
+      link_group_id: None,
+      source_timecode: None,
+      volume: 1.0,
+      external_audio: None,
+      locked: false,
       properties: ClipProperties::default(),
     }
   }
@@ -197,9 +697,11 @@ mod tests {
       enabled: true,
       volume: 1.0,
       locked: false,
+      hidden: false,
       clips: Vec::new(),
       effects: Vec::new(),
       filters: Vec::new(),
+      ducking: None,
     }
   }
 
@@ -207,7 +709,7 @@ mod tests {
   fn test_project_schema_new() {
     let project = ProjectSchema::new("My Project".to_string());
 
-    assert_eq!(project.version, "1.0.0");
+    assert_eq!(project.version, SCHEMA_VERSION);
     assert_eq!(project.metadata.name, "My Project");
     assert!(project.metadata.description.is_none());
     assert!(project.metadata.author.is_none());
@@ -243,9 +745,7 @@ mod tests {
 
     let result = project.validate();
     assert!(result.is_err());
-    assert!(result
-      .unwrap_err()
-      .contains("Версия проекта не может быть пустой"));
+    assert_eq!(result.unwrap_err().key(), Some("validation.empty_version"));
   }
 
   #[test]
@@ -255,7 +755,7 @@ mod tests {
 
     let result = project.validate();
     assert!(result.is_err());
-    assert!(result.unwrap_err().contains("FPS должен быть больше 0"));
+    assert_eq!(result.unwrap_err().key(), Some("validation.invalid_fps"));
   }
 
   #[test]
@@ -266,17 +766,19 @@ mod tests {
     project.timeline.resolution = (0, 1080);
     let result = project.validate();
     assert!(result.is_err());
-    assert!(result
-      .unwrap_err()
-      .contains("Разрешение должно быть больше 0x0"));
+    assert_eq!(
+      result.unwrap_err().key(),
+      Some("validation.invalid_resolution")
+    );
 
     // Test zero height
     project.timeline.resolution = (1920, 0);
     let result = project.validate();
     assert!(result.is_err());
-    assert!(result
-      .unwrap_err()
-      .contains("Разрешение должно быть больше 0x0"));
+    assert_eq!(
+      result.unwrap_err().key(),
+      Some("validation.invalid_resolution")
+    );
 
     // Test both zero
     project.timeline.resolution = (0, 0);
@@ -299,7 +801,7 @@ mod tests {
     let result = project.validate();
     assert!(result.is_err());
     let error = result.unwrap_err();
-    assert!(error.contains("Клипы пересекаются по времени"));
+    assert_eq!(error.key(), Some("validation.clips_overlap"));
     assert!(error.contains("clip1"));
     assert!(error.contains("clip2"));
   }
@@ -493,7 +995,7 @@ mod tests {
 
     let result = project.validate();
     assert!(result.is_err());
-    assert!(result.unwrap_err().contains("Клипы пересекаются"));
+    assert_eq!(result.unwrap_err().key(), Some("validation.clips_overlap"));
   }
 
   #[test]
@@ -540,6 +1042,7 @@ mod tests {
       css_filter: None,
       preview_path: None,
       presets: None,
+      keyframes: std::collections::HashMap::new(),
     });
 
     // Add transitions
@@ -561,6 +1064,8 @@ mod tests {
       ffmpeg_command: None,
       easing: None,
       direction: None,
+      from_clip_id: None,
+      to_clip_id: None,
     });
 
     // Add filters
@@ -625,4 +1130,252 @@ mod tests {
     // Verify clone is unchanged
     assert_eq!(cloned.metadata.description, Some("Original".to_string()));
   }
+
+  #[test]
+  fn test_transition_on_disabled_track_validation() {
+    use crate::video_compiler::schema::effects::TransitionDuration;
+
+    let mut project = create_test_project();
+    let mut track = create_test_track("video", TrackType::Video);
+    track.clips.push(create_test_clip("a", 0.0, 5.0));
+    track.clips.push(create_test_clip("b", 5.0, 10.0));
+    project.tracks.push(track);
+    project.transitions.push(Transition {
+      id: "fade_ab".to_string(),
+      transition_type: "fade".to_string(),
+      name: "Fade".to_string(),
+      duration: TransitionDuration {
+        value: 1.0,
+        min: None,
+        max: None,
+      },
+      category: None,
+      tags: Vec::new(),
+      complexity: None,
+      enabled: true,
+      parameters: std::collections::HashMap::new(),
+      ffmpeg_command: None,
+      easing: None,
+      direction: None,
+      from_clip_id: Some("a".to_string()),
+      to_clip_id: Some("b".to_string()),
+    });
+    assert!(project.validate().is_ok());
+
+    project.tracks[0].enabled = false;
+    let error = project.validate().unwrap_err();
+    assert!(error.contains("fade_ab"));
+
+    // Отключенный переход не проверяется
+    project.transitions[0].enabled = false;
+    assert!(project.validate().is_ok());
+  }
+
+  #[test]
+  fn test_rendition_validation() {
+    use crate::video_compiler::schema::common::Resolution;
+    use crate::video_compiler::schema::export::RenditionSpec;
+
+    let mut project = create_test_project();
+    project.timeline.resolution = (1920, 1080);
+    project.settings.export.renditions = vec![
+      RenditionSpec {
+        resolution: Resolution::full_hd(),
+        video_bitrate: 6000,
+        suffix: "_1080p".to_string(),
+      },
+      RenditionSpec {
+        resolution: Resolution::hd(),
+        video_bitrate: 3000,
+        suffix: "_720p".to_string(),
+      },
+    ];
+    assert!(project.validate().is_ok());
+
+    // Вариант больше разрешения timeline
+    project.settings.export.renditions[0].resolution = Resolution::uhd_4k();
+    let error = project.validate().unwrap_err();
+    assert!(error.contains("_1080p"));
+
+    // Повторяющийся суффикс
+    project.settings.export.renditions[0].resolution = Resolution::full_hd();
+    project.settings.export.renditions[1].suffix = "_1080p".to_string();
+    assert!(project.validate().is_err());
+
+    // HLS без вариантов
+    project.settings.export.renditions.clear();
+    project.settings.export.generate_hls = true;
+    assert!(project.validate().is_err());
+  }
+
+  #[test]
+  fn test_audio_only_format_validation() {
+    let mut project = create_test_project();
+    project.settings.output.format = OutputFormat::Wav;
+
+    // Без аудио треков экспорт в аудиоформат невозможен
+    let mut video_track = create_test_track("video", TrackType::Video);
+    video_track.clips.push(create_test_clip("v1", 0.0, 5.0));
+    project.tracks.push(video_track);
+    let error = project.validate().unwrap_err();
+    assert!(error.contains("Wav"));
+
+    // Отключенный аудио трек не считается
+    let mut audio_track = create_test_track("audio", TrackType::Audio);
+    audio_track.clips.push(create_test_clip("a1", 0.0, 5.0));
+    audio_track.enabled = false;
+    project.tracks.push(audio_track);
+    assert!(project.validate().is_err());
+
+    project.tracks[1].enabled = true;
+    assert!(project.validate().is_ok());
+
+    project.settings.export.audio_bit_depth = Some(20);
+    assert!(project.validate().is_err());
+  }
+
+  #[test]
+  fn test_validate_ducking_references() {
+    let mut project = create_test_project();
+    let mut music = create_test_track("music", TrackType::Audio);
+    music.id = "music".to_string();
+    let mut voice = create_test_track("voice", TrackType::Audio);
+    voice.id = "voice".to_string();
+    project.tracks = vec![music, voice];
+
+    project.tracks[0].ducking = Some(DuckingConfig::new("voice"));
+    assert!(project.validate().is_ok());
+
+    // Ссылка на себя
+    project.tracks[0].ducking = Some(DuckingConfig::new("music"));
+    assert!(project.validate().is_err());
+
+    // Несуществующий трек
+    project.tracks[0].ducking = Some(DuckingConfig::new("missing"));
+    let error = project.validate().unwrap_err();
+    assert!(error.contains("missing"));
+
+    // Цикл music -> voice -> music
+    project.tracks[0].ducking = Some(DuckingConfig::new("voice"));
+    project.tracks[1].ducking = Some(DuckingConfig::new("music"));
+    let error = project.validate().unwrap_err();
+    assert!(error.contains("Циклическая"));
+
+    // Параметры вне диапазона
+    project.tracks[1].ducking = None;
+    project.tracks[0].ducking.as_mut().unwrap().ratio = 50.0;
+    assert!(project.validate().is_err());
+  }
+
+  #[test]
+  fn test_validate_hdr_output_compatibility() {
+    let mut project = create_test_project();
+    project.settings.export.hdr_mode = HdrMode::Hdr10;
+    assert!(project.validate().is_ok());
+
+    project.settings.output.format = OutputFormat::Avi;
+    let error = project.validate().unwrap_err();
+    assert!(error.contains("HDR"));
+
+    project.settings.output.format = OutputFormat::Mp4;
+    project.settings.export.encoding_profile = Some("baseline".to_string());
+    assert!(project.validate().is_err());
+
+    project.settings.export.encoding_profile = None;
+    project.settings.export.preferred_gpu_encoder = Some("h264_nvenc".to_string());
+    assert!(project.validate().is_err());
+
+    // Тонмаппинг в SDR не требует HDR-совместимого контейнера
+    project.settings.export.preferred_gpu_encoder = None;
+    project.settings.export.hdr_mode = HdrMode::ToneMapToSdr;
+    project.settings.output.format = OutputFormat::Avi;
+    assert!(project.validate().is_ok());
+
+    project.settings.export.color_primaries = Some("p3".to_string());
+    assert!(project.validate().is_err());
+  }
+
+  #[test]
+  fn test_frame_snapshot_keeps_only_visible_content() {
+    let mut project = create_test_project();
+
+    let mut video_track = create_test_track("video", TrackType::Video);
+    let mut visible = create_test_clip("visible", 2.0, 6.0);
+    visible.source_start = 1.0;
+    visible.effects = vec!["blur".to_string()];
+    visible.template_id = Some("layout".to_string());
+    video_track.clips.push(create_test_clip("before", 0.0, 2.0));
+    video_track.clips.push(visible);
+    video_track.clips.push(create_test_clip("after", 6.0, 9.0));
+    project.tracks.push(video_track);
+
+    let mut audio_track = create_test_track("audio", TrackType::Audio);
+    audio_track.clips.push(create_test_clip("music", 0.0, 9.0));
+    project.tracks.push(audio_track);
+
+    let mut empty_track = create_test_track("overlay", TrackType::Video);
+    empty_track.clips.push(create_test_clip("late", 8.0, 9.0));
+    project.tracks.push(empty_track);
+
+    project
+      .subtitles
+      .push(Subtitle::new("shown".to_string(), 3.0, 5.0));
+    project
+      .subtitles
+      .push(Subtitle::new("later".to_string(), 5.0, 7.0));
+
+    let snapshot = project.frame_snapshot(4.0);
+
+    // Только видео трек с клипом, пересекающим момент
+    assert_eq!(snapshot.tracks.len(), 1);
+    let clip = &snapshot.tracks[0].clips[0];
+    assert_eq!(clip.id, "visible");
+    assert_eq!(clip.start_time, 0.0);
+    assert_eq!(clip.end_time, 2.0);
+    assert_eq!(clip.source_start, 3.0);
+    assert_eq!(snapshot.timeline.duration, 2.0);
+
+    assert_eq!(snapshot.subtitles.len(), 1);
+    assert_eq!(snapshot.subtitles[0].text, "shown");
+    assert_eq!(snapshot.subtitles[0].start_time, -1.0);
+  }
+
+  #[test]
+  fn test_frame_snapshot_content_hash() {
+    let mut project = create_test_project();
+    let mut track = create_test_track("video", TrackType::Video);
+    track.clips.push(create_test_clip("first", 0.0, 5.0));
+    track.clips.push(create_test_clip("second", 5.0, 10.0));
+    project.tracks.push(track);
+
+    let hash = project.frame_snapshot(2.0).content_hash();
+    assert_eq!(hash, project.frame_snapshot(2.0).content_hash());
+    assert_ne!(hash, project.frame_snapshot(3.0).content_hash());
+
+    // Изменения вне момента и метаданные не влияют на хеш
+    project.touch();
+    project.tracks[0].clips[1].opacity = 0.5;
+    assert_eq!(hash, project.frame_snapshot(2.0).content_hash());
+
+    project.tracks[0].clips[0].opacity = 0.5;
+    assert_ne!(hash, project.frame_snapshot(2.0).content_hash());
+  }
+
+  #[test]
+  fn test_timecode_at_project_rate_and_start() {
+    let mut project = create_test_project();
+    project.settings.frame_rate = 30000.0 / 1001.0;
+    assert_eq!(project.timecode_rate(), TimecodeRate::Fps29_97);
+    assert_eq!(project.timecode_at(60.06).to_string(), "00:01:00;02");
+
+    project.settings.timecode.drop_frame = false;
+    assert_eq!(project.timecode_at(60.06).to_string(), "00:01:00:00");
+
+    // Начальный timecode в другой частоте пересчитывается в частоту проекта
+    project.settings.frame_rate = 25.0;
+    project.settings.timecode.start =
+      Some(Timecode::parse("01:00:00:00", TimecodeRate::Fps50).unwrap());
+    assert_eq!(project.timecode_at(1.48).to_string(), "01:00:01:12");
+    assert_eq!(project.timecode_at(-3.0).to_string(), "01:00:00:00");
+  }
 }
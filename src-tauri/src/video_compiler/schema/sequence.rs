@@ -0,0 +1,560 @@
+//! Sequence - Вложенные последовательности (составные клипы)
+//!
+//! Последовательность хранит собственные треки и отображается на родительском
+//! timeline одним клипом с источником [`ClipSource::Sequence`]. Эффекты, фильтры и
+//! шаблоны общие с проектом, поэтому последовательность содержит только треки.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use super::project::ProjectSchema;
+use super::timeline::{Clip, ClipSource, Track};
+
+/// Максимальная глубина вложенности последовательностей
+pub const MAX_SEQUENCE_DEPTH: usize = 3;
+
+/// Вложенная последовательность
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Sequence {
+  /// Уникальный идентификатор последовательности
+  pub id: String,
+  /// Название последовательности
+  pub name: String,
+  /// Треки последовательности, время отсчитывается от ее начала
+  pub tracks: Vec<Track>,
+}
+
+impl Sequence {
+  /// Создать пустую последовательность
+  pub fn new(name: String) -> Self {
+    Self {
+      id: uuid::Uuid::new_v4().to_string(),
+      name,
+      tracks: Vec::new(),
+    }
+  }
+
+  /// Длительность последовательности
+  pub fn duration(&self) -> f64 {
+    self
+      .tracks
+      .iter()
+      .flat_map(|track| &track.clips)
+      .map(|clip| clip.end_time)
+      .fold(0.0, f64::max)
+  }
+
+  /// ID последовательностей, на которые ссылаются клипы этой последовательности
+  pub fn referenced_sequences(&self) -> HashSet<&str> {
+    sequence_references(&self.tracks)
+  }
+}
+
+/// ID последовательностей, на которые ссылаются клипы треков
+fn sequence_references(tracks: &[Track]) -> HashSet<&str> {
+  tracks
+    .iter()
+    .flat_map(|track| &track.clips)
+    .filter_map(Clip::sequence_id)
+    .collect()
+}
+
+impl ProjectSchema {
+  /// Есть ли на timeline проекта клипы вложенных последовательностей
+  pub fn has_sequence_clips(&self) -> bool {
+    !sequence_references(&self.tracks).is_empty()
+  }
+
+  /// Проверка ссылок на последовательности, циклов и глубины вложенности
+  pub(super) fn validate_sequences(&self) -> Result<(), String> {
+    for (key, sequence) in &self.sequences {
+      if *key != sequence.id {
+        return Err(format!(
+          "Ключ последовательности '{key}' не совпадает с ее ID '{}'",
+          sequence.id
+        ));
+      }
+      for track in &sequence.tracks {
+        track.validate()?;
+      }
+    }
+
+    let mut depths = HashMap::new();
+    for id in sequence_references(&self.tracks) {
+      let depth = self.sequence_depth(id, &mut Vec::new(), &mut depths)?;
+      if depth > MAX_SEQUENCE_DEPTH {
+        return Err(format!(
+          "Глубина вложенности последовательностей превышает {MAX_SEQUENCE_DEPTH}"
+        ));
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Глубина вложенности последовательности (1 - без вложенных последовательностей)
+  fn sequence_depth<'a>(
+    &'a self,
+    id: &'a str,
+    stack: &mut Vec<&'a str>,
+    depths: &mut HashMap<&'a str, usize>,
+  ) -> Result<usize, String> {
+    if let Some(depth) = depths.get(id) {
+      return Ok(*depth);
+    }
+    if stack.contains(&id) {
+      return Err(format!(
+        "Последовательность '{id}' рекурсивно содержит саму себя"
+      ));
+    }
+    let sequence = self
+      .sequences
+      .get(id)
+      .ok_or_else(|| format!("Последовательность '{id}' не найдена"))?;
+
+    stack.push(id);
+    let mut depth = 1;
+    for child in sequence.referenced_sequences() {
+      depth = depth.max(1 + self.sequence_depth(child, stack, depths)?);
+    }
+    stack.pop();
+
+    depths.insert(id, depth);
+    Ok(depth)
+  }
+
+  /// Сгруппировать клипы в новую последовательность.
+  ///
+  /// Клипы переносятся в треки последовательности (ID треков сохраняются), а на
+  /// первом из затронутых треков появляется составной клип. Возвращает ID
+  /// последовательности.
+  pub fn create_sequence_from_clips(
+    &mut self,
+    clip_ids: &[String],
+    name: String,
+  ) -> Result<String, String> {
+    if clip_ids.is_empty() {
+      return Err("Не выбраны клипы для последовательности".to_string());
+    }
+
+    let mut positions = Vec::with_capacity(clip_ids.len());
+    for clip_id in clip_ids {
+      let position = self
+        .find_clip_position(clip_id)
+        .ok_or_else(|| format!("Клип '{clip_id}' не найден"))?;
+      positions.push(position);
+    }
+
+    let selected: Vec<&Clip> = positions
+      .iter()
+      .map(|&(track_idx, clip_idx)| &self.tracks[track_idx].clips[clip_idx])
+      .collect();
+    let start = selected
+      .iter()
+      .map(|clip| clip.start_time)
+      .fold(f64::INFINITY, f64::min);
+    let end = selected
+      .iter()
+      .map(|clip| clip.end_time)
+      .fold(0.0, f64::max);
+
+    let mut track_indices: Vec<usize> = positions.iter().map(|&(track_idx, _)| track_idx).collect();
+    track_indices.sort_unstable();
+    track_indices.dedup();
+    let host_track = track_indices[0];
+
+    // Составной клип не должен пересекаться с оставшимися клипами трека
+    let overlaps = self.tracks[host_track]
+      .clips
+      .iter()
+      .filter(|clip| !clip_ids.contains(&clip.id))
+      .any(|clip| clip.start_time < end && clip.end_time > start);
+    if overlaps {
+      return Err(format!(
+        "Составной клип пересекается с клипами трека '{}'",
+        self.tracks[host_track].name
+      ));
+    }
+
+    let mut sequence = Sequence::new(name);
+    for &track_idx in &track_indices {
+      let track = &mut self.tracks[track_idx];
+      let (moved, kept): (Vec<Clip>, Vec<Clip>) = track
+        .clips
+        .drain(..)
+        .partition(|clip| clip_ids.contains(&clip.id));
+      track.clips = kept;
+
+      sequence.tracks.push(Track {
+        clips: moved
+          .into_iter()
+          .map(|mut clip| {
+            clip.start_time -= start;
+            clip.end_time -= start;
+            clip
+          })
+          .collect(),
+        // Sidechain трек может остаться снаружи последовательности
+        ducking: None,
+        ..track.clone()
+      });
+    }
+
+    let sequence_id = sequence.id.clone();
+    self.tracks[host_track].add_clip(Clip::from_sequence(&sequence_id, start, end - start));
+    self.sequences.insert(sequence_id.clone(), sequence);
+    self.touch();
+
+    Ok(sequence_id)
+  }
+
+  /// Развернуть составные клипы последовательности обратно на timeline.
+  ///
+  /// Разворачивается один уровень: вложенные в нее последовательности остаются
+  /// составными клипами. Клипы возвращаются в треки с теми же ID, остальные треки
+  /// последовательности добавляются после трека составного клипа. Возвращает
+  /// количество развернутых составных клипов.
+  pub fn flatten_sequence(&mut self, sequence_id: &str) -> Result<usize, String> {
+    let sequence = self
+      .sequences
+      .get(sequence_id)
+      .cloned()
+      .ok_or_else(|| format!("Последовательность '{sequence_id}' не найдена"))?;
+
+    let mut flattened = 0;
+    while let Some((host_idx, clip_idx)) = self.find_sequence_clip(sequence_id) {
+      let compound = self.tracks[host_idx].clips.remove(clip_idx);
+      let mut insert_at = host_idx + 1;
+
+      for sequence_track in &sequence.tracks {
+        let clips: Vec<Clip> = sequence_track
+          .clips
+          .iter()
+          .filter_map(|clip| map_into_parent(clip, &compound))
+          .collect();
+        if clips.is_empty() {
+          continue;
+        }
+
+        match self.tracks.iter().position(|t| t.id == sequence_track.id) {
+          Some(target) => {
+            for clip in clips {
+              self.tracks[target].add_clip(clip);
+            }
+          }
+          None => {
+            self.tracks.insert(
+              insert_at,
+              Track {
+                clips,
+                ..sequence_track.clone()
+              },
+            );
+            insert_at += 1;
+          }
+        }
+      }
+
+      flattened += 1;
+    }
+
+    // Последовательность удаляется, если на нее больше никто не ссылается
+    let still_referenced = self
+      .sequences
+      .values()
+      .any(|s| s.referenced_sequences().contains(sequence_id));
+    if !still_referenced {
+      self.sequences.remove(sequence_id);
+    }
+    self.touch();
+
+    Ok(flattened)
+  }
+
+  /// Копия проекта, в которой все вложенные последовательности развернуты
+  pub fn flatten_all_sequences(&self) -> Result<ProjectSchema, String> {
+    let mut project = self.clone();
+    for _ in 0..=MAX_SEQUENCE_DEPTH {
+      let referenced: Vec<String> = sequence_references(&project.tracks)
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+      if referenced.is_empty() {
+        return Ok(project);
+      }
+      for sequence_id in referenced {
+        project.flatten_sequence(&sequence_id)?;
+      }
+    }
+
+    Err(format!(
+      "Глубина вложенности последовательностей превышает {MAX_SEQUENCE_DEPTH}"
+    ))
+  }
+
+  /// Проект для отдельного рендера последовательности с настройками родителя
+  pub fn sequence_project(&self, sequence_id: &str) -> Option<ProjectSchema> {
+    let sequence = self.sequences.get(sequence_id)?;
+    let mut project = self.clone();
+    project.tracks = sequence.tracks.clone();
+    project.transitions.clear();
+    project.subtitles.clear();
+    project.settings.export.renditions.clear();
+    project.settings.export.generate_hls = false;
+    project.settings.output.duration = sequence.duration();
+    project.timeline.duration = sequence.duration();
+    Some(project)
+  }
+
+  /// ID последовательностей в порядке рендера промежуточных файлов:
+  /// вложенные последовательности идут раньше содержащих их
+  pub fn sequence_render_order(&self) -> Vec<String> {
+    fn visit(project: &ProjectSchema, id: &str, order: &mut Vec<String>, depth: usize) {
+      if depth > MAX_SEQUENCE_DEPTH || order.iter().any(|known| known == id) {
+        return;
+      }
+      if let Some(sequence) = project.sequences.get(id) {
+        let mut children: Vec<&str> = sequence.referenced_sequences().into_iter().collect();
+        children.sort_unstable();
+        for child in children {
+          visit(project, child, order, depth + 1);
+        }
+        order.push(id.to_string());
+      }
+    }
+
+    let mut roots: Vec<&str> = sequence_references(&self.tracks).into_iter().collect();
+    roots.sort_unstable();
+    let mut order = Vec::new();
+    for id in roots {
+      visit(self, id, &mut order, 1);
+    }
+    order
+  }
+
+  /// Позиция первого составного клипа последовательности на timeline
+  fn find_sequence_clip(&self, sequence_id: &str) -> Option<(usize, usize)> {
+    self
+      .tracks
+      .iter()
+      .enumerate()
+      .find_map(|(track_idx, track)| {
+        track
+          .clips
+          .iter()
+          .position(|clip| clip.sequence_id() == Some(sequence_id))
+          .map(|clip_idx| (track_idx, clip_idx))
+      })
+  }
+}
+
+/// Перенести клип последовательности на родительский timeline с учетом
+/// видимого окна составного клипа. Клипы вне окна отбрасываются.
+fn map_into_parent(clip: &Clip, compound: &Clip) -> Option<Clip> {
+  let window_start = compound.source_start;
+  let window_end = compound.source_start + compound.get_timeline_duration();
+
+  let visible_start = clip.start_time.max(window_start);
+  let visible_end = clip.end_time.min(window_end);
+  if visible_end <= visible_start {
+    return None;
+  }
+
+  let mut mapped = clip.clone();
+  mapped.id = uuid::Uuid::new_v4().to_string();
+  mapped.source_start += (visible_start - clip.start_time) * clip.speed;
+  mapped.source_end = mapped.source_start + (visible_end - visible_start) * clip.speed;
+  mapped.start_time = compound.start_time + (visible_start - window_start);
+  mapped.end_time = compound.start_time + (visible_end - window_start);
+  Some(mapped)
+}
+
+impl Clip {
+  /// Составной клип вложенной последовательности
+  pub fn from_sequence(sequence_id: &str, start_time: f64, duration: f64) -> Self {
+    Self {
+      source: ClipSource::Sequence(sequence_id.to_string()),
+      ..Self::new(PathBuf::new(), start_time, duration)
+    }
+  }
+
+  /// ID последовательности, если клип составной
+  pub fn sequence_id(&self) -> Option<&str> {
+    match &self.source {
+      ClipSource::Sequence(id) => Some(id),
+      _ => None,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::video_compiler::schema::timeline::TrackType;
+
+  fn clip(id: &str, start: f64, end: f64) -> Clip {
+    let mut clip = Clip::new(
+      PathBuf::from(format!("/media/{id}.mp4")),
+      start,
+      end - start,
+    );
+    clip.id = id.to_string();
+    clip
+  }
+
+  fn project_with_clips() -> ProjectSchema {
+    let mut project = ProjectSchema::new("Nested".to_string());
+    let mut video = Track::new(TrackType::Video, "Video".to_string());
+    video.id = "video".to_string();
+    video.clips = vec![
+      clip("intro", 0.0, 2.0),
+      clip("a", 2.0, 5.0),
+      clip("b", 5.0, 8.0),
+    ];
+    let mut audio = Track::new(TrackType::Audio, "Audio".to_string());
+    audio.id = "audio".to_string();
+    audio.clips = vec![clip("music", 2.0, 8.0)];
+    project.tracks = vec![video, audio];
+    project
+  }
+
+  #[test]
+  fn test_create_sequence_from_clips() {
+    let mut project = project_with_clips();
+    let ids = ["a", "b", "music"].map(String::from);
+    let sequence_id = project
+      .create_sequence_from_clips(&ids, "Scene".to_string())
+      .unwrap();
+
+    let sequence = &project.sequences[&sequence_id];
+    assert_eq!(sequence.tracks.len(), 2);
+    assert_eq!(sequence.duration(), 6.0);
+    assert_eq!(sequence.tracks[0].clips[0].start_time, 0.0);
+
+    // Составной клип занимает место исходных на первом треке
+    let video = &project.tracks[0];
+    assert_eq!(video.clips.len(), 2);
+    assert_eq!(video.clips[1].sequence_id(), Some(sequence_id.as_str()));
+    assert_eq!(video.clips[1].start_time, 2.0);
+    assert_eq!(video.clips[1].end_time, 8.0);
+    assert!(project.tracks[1].clips.is_empty());
+    assert!(project.validate().is_ok());
+  }
+
+  #[test]
+  fn test_flatten_sequence_restores_clips() {
+    let mut project = project_with_clips();
+    let ids = ["a", "b", "music"].map(String::from);
+    let sequence_id = project
+      .create_sequence_from_clips(&ids, "Scene".to_string())
+      .unwrap();
+
+    assert_eq!(project.flatten_sequence(&sequence_id).unwrap(), 1);
+    assert!(project.sequences.is_empty());
+    assert!(!project.has_sequence_clips());
+
+    let video = &project.tracks[0];
+    let times: Vec<(f64, f64)> = video
+      .clips
+      .iter()
+      .map(|c| (c.start_time, c.end_time))
+      .collect();
+    assert_eq!(times, vec![(0.0, 2.0), (2.0, 5.0), (5.0, 8.0)]);
+    assert_eq!(project.tracks[1].clips[0].start_time, 2.0);
+  }
+
+  #[test]
+  fn test_flatten_trimmed_compound_clip() {
+    let mut project = project_with_clips();
+    let ids = ["a", "b"].map(String::from);
+    let sequence_id = project
+      .create_sequence_from_clips(&ids, "Scene".to_string())
+      .unwrap();
+
+    // Составной клип обрезан: видна только середина последовательности (1..4)
+    let compound = &mut project.tracks[0].clips[1];
+    compound.source_start = 1.0;
+    compound.source_end = 4.0;
+    compound.end_time = compound.start_time + 3.0;
+
+    project.flatten_sequence(&sequence_id).unwrap();
+    let video = &project.tracks[0];
+    assert_eq!(video.clips.len(), 3);
+    assert_eq!(
+      (video.clips[1].start_time, video.clips[1].end_time),
+      (2.0, 4.0)
+    );
+    assert_eq!(video.clips[1].source_start, 1.0);
+    assert_eq!(
+      (video.clips[2].start_time, video.clips[2].end_time),
+      (4.0, 5.0)
+    );
+    assert_eq!(video.clips[2].source_start, 0.0);
+  }
+
+  #[test]
+  fn test_validate_rejects_cycles_and_depth() {
+    let mut project = ProjectSchema::new("Nested".to_string());
+    let mut track = Track::new(TrackType::Video, "Video".to_string());
+    track.clips.push(Clip::from_sequence("s1", 0.0, 1.0));
+    project.tracks.push(track);
+
+    // Цепочка s1 -> s2 -> s3 -> s4 длиннее допустимой
+    for (id, child) in [
+      ("s1", Some("s2")),
+      ("s2", Some("s3")),
+      ("s3", Some("s4")),
+      ("s4", None),
+    ] {
+      let mut sequence = Sequence::new(id.to_string());
+      sequence.id = id.to_string();
+      let mut track = Track::new(TrackType::Video, "Inner".to_string());
+      track.clips.push(match child {
+        Some(child) => Clip::from_sequence(child, 0.0, 1.0),
+        None => clip(&format!("{id}-media"), 0.0, 1.0),
+      });
+      sequence.tracks.push(track);
+      project.sequences.insert(id.to_string(), sequence);
+    }
+    let error = project.validate().unwrap_err();
+    assert!(error.contains("Глубина"));
+
+    // Три уровня допустимы
+    project.sequences.get_mut("s3").unwrap().tracks[0].clips[0] = clip("s3-media", 0.0, 1.0);
+    assert!(project.validate().is_ok());
+    assert_eq!(project.sequence_render_order(), vec!["s3", "s2", "s1"]);
+
+    // Цикл s3 -> s1
+    project.sequences.get_mut("s3").unwrap().tracks[0].clips[0] =
+      Clip::from_sequence("s1", 0.0, 1.0);
+    let error = project.validate().unwrap_err();
+    assert!(error.contains("рекурсивно"));
+
+    // Ссылка на отсутствующую последовательность
+    project.tracks[0].clips[0] = Clip::from_sequence("missing", 0.0, 1.0);
+    assert!(project.validate().is_err());
+  }
+
+  #[test]
+  fn test_flatten_all_sequences_two_levels() {
+    let mut project = project_with_clips();
+    let inner = project
+      .create_sequence_from_clips(&["a".to_string()], "Inner".to_string())
+      .unwrap();
+    let compound_id = project.tracks[0].clips[1].id.clone();
+    project
+      .create_sequence_from_clips(&[compound_id, "b".to_string()], "Outer".to_string())
+      .unwrap();
+    assert_eq!(project.sequence_render_order()[0], inner);
+
+    let flat = project.flatten_all_sequences().unwrap();
+    assert!(!flat.has_sequence_clips());
+    let times: Vec<(f64, f64)> = flat.tracks[0]
+      .clips
+      .iter()
+      .map(|c| (c.start_time, c.end_time))
+      .collect();
+    assert_eq!(times, vec![(0.0, 2.0), (2.0, 5.0), (5.0, 8.0)]);
+  }
+}
@@ -22,6 +22,9 @@ pub struct Subtitle {
   pub enabled: bool,
   /// Анимации субтитра
   pub animations: Vec<SubtitleAnimation>,
+  /// Код языка субтитра (для многоязычных транскрипций)
+  #[serde(default)]
+  pub language: Option<String>,
 
   // Поля для обратной совместимости
   /// Семейство шрифта
@@ -55,6 +58,7 @@ impl Subtitle {
       style: SubtitleStyle::default(),
       enabled: true,
       animations: Vec::new(),
+      language: None,
       // Поля для обратной совместимости
       font_family: "Arial".to_string(),
       font_size: 24.0,
@@ -88,6 +92,17 @@ impl Subtitle {
   pub fn get_duration(&self) -> f64 {
     self.end_time - self.start_time
   }
+
+  /// Применить стиль, синхронизируя поля обратной совместимости
+  pub fn apply_style(&mut self, style: SubtitleStyle) {
+    self.font_family = style.font_family.clone();
+    self.font_size = style.font_size;
+    self.color = style.color.clone();
+    self.font_weight = style.font_weight.clone();
+    self.shadow = style.shadow_color.is_some();
+    self.outline = style.stroke_color.is_some() && style.stroke_width > 0.0;
+    self.style = style;
+  }
 }
 
 /// Позиция субтитра на экране
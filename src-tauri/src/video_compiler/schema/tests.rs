@@ -95,6 +95,7 @@ mod schema_tests {
         resolution: (1920, 1080),
         sample_rate: 48000,
         aspect_ratio: AspectRatio::Ratio16x9,
+        markers: Vec::new(),
       },
       tracks: vec![],
       effects: vec![],
@@ -102,6 +103,7 @@ mod schema_tests {
       filters: vec![],
       templates: vec![],
       style_templates: vec![],
+      sequences: Default::default(),
       subtitles: vec![],
       settings: Default::default(),
     }
@@ -3,7 +3,8 @@
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
-use super::common::AspectRatio;
+use super::common::{AspectRatio, Timecode};
+use super::effects::{keyframe_value_at, KeyframeEasing, ParamKeyframe};
 
 /// Источник клипа
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -16,6 +17,8 @@ pub enum ClipSource {
   Stream(String),
   /// Устройство (камера, микрофон)
   Device(String),
+  /// Вложенная последовательность проекта (ID последовательности)
+  Sequence(String),
 }
 
 /// Настройки timeline
@@ -31,6 +34,9 @@ pub struct Timeline {
   pub sample_rate: u32,
   /// Соотношение сторон
   pub aspect_ratio: AspectRatio,
+  /// Маркеры и главы timeline
+  #[serde(default)]
+  pub markers: Vec<TimelineMarker>,
 }
 
 impl Default for Timeline {
@@ -41,10 +47,49 @@ impl Default for Timeline {
       resolution: (1920, 1080),
       sample_rate: 48000,
       aspect_ratio: AspectRatio::Ratio16x9,
+      markers: Vec::new(),
     }
   }
 }
 
+/// Тип маркера timeline
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MarkerType {
+  /// Обычная метка
+  #[default]
+  Standard,
+  /// Начало главы
+  Chapter,
+  /// Начало раздела
+  Section,
+  /// Заметка
+  Note,
+  /// Точка экспорта
+  Export,
+}
+
+/// Маркер на timeline
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TimelineMarker {
+  /// Уникальный идентификатор маркера
+  pub id: String,
+  /// Название маркера
+  #[serde(default)]
+  pub name: String,
+  /// Позиция на timeline в секундах
+  pub time: f64,
+  /// Тип маркера
+  #[serde(default, rename = "type")]
+  pub marker_type: MarkerType,
+  /// Цвет маркера
+  #[serde(default)]
+  pub color: Option<String>,
+  /// Описание
+  #[serde(default)]
+  pub description: Option<String>,
+}
+
 /// Дорожка timeline (видео, аудио, субтитры)
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Track {
@@ -60,12 +105,22 @@ pub struct Track {
   pub volume: f32,
   /// Заблокирован ли трек для редактирования
   pub locked: bool,
+  /// Скрыт ли трек: не попадает в финальный рендер, но остается в превью
+  #[serde(default)]
+  pub hidden: bool,
   /// Список клипов в треке
   pub clips: Vec<Clip>,
-  /// ID эффектов, применяемых ко всему треку
+  /// ID эффектов, применяемых ко всему треку.
+  ///
+  /// Для видео применяются к каждому клипу трека в объявленном порядке после
+  /// фильтров и эффектов самого клипа. Выключенные эффекты и эффекты, уже
+  /// назначенные клипу, пропускаются.
   pub effects: Vec<String>,
   /// ID фильтров, применяемых ко всему треку
   pub filters: Vec<String>,
+  /// Приглушение трека, пока звучит sidechain трек (например, музыка под голосом)
+  #[serde(default)]
+  pub ducking: Option<DuckingConfig>,
 }
 
 impl Track {
@@ -78,9 +133,11 @@ impl Track {
       enabled: true,
       volume: 1.0,
       locked: false,
+      hidden: false,
       clips: Vec::new(),
       effects: Vec::new(),
       filters: Vec::new(),
+      ducking: None,
     }
   }
 
@@ -99,6 +156,16 @@ impl Track {
       clip.validate()?;
     }
 
+    if let Some(ducking) = &self.ducking {
+      if ducking.sidechain_track_id == self.id {
+        return Err(format!(
+          "Трек '{}' не может использовать себя как sidechain",
+          self.name
+        ));
+      }
+      ducking.validate()?;
+    }
+
     Ok(())
   }
 
@@ -114,6 +181,73 @@ impl Track {
   pub fn remove_clip(&mut self, clip_id: &str) {
     self.clips.retain(|c| c.id != clip_id);
   }
+
+  /// Попадает ли трек в финальный рендер (скрытые треки остаются только в превью)
+  pub fn is_rendered(&self) -> bool {
+    self.enabled && !self.hidden
+  }
+}
+
+/// Настройки ducking трека через sidechaincompress
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DuckingConfig {
+  /// ID трека, звук которого управляет приглушением (например, голос)
+  pub sidechain_track_id: String,
+  /// Порог срабатывания (линейный, 0.001-1.0)
+  pub threshold: f32,
+  /// Степень сжатия (1-20)
+  pub ratio: f32,
+  /// Атака в миллисекундах
+  pub attack: f32,
+  /// Восстановление в миллисекундах
+  pub release: f32,
+  /// Компенсация усиления (1-64)
+  pub makeup: f32,
+}
+
+impl DuckingConfig {
+  /// Настройки по умолчанию для указанного sidechain трека
+  pub fn new(sidechain_track_id: impl Into<String>) -> Self {
+    Self {
+      sidechain_track_id: sidechain_track_id.into(),
+      threshold: 0.05,
+      ratio: 8.0,
+      attack: 20.0,
+      release: 250.0,
+      makeup: 1.0,
+    }
+  }
+
+  /// Проверка диапазонов параметров sidechaincompress
+  pub fn validate(&self) -> Result<(), String> {
+    if self.sidechain_track_id.is_empty() {
+      return Err("Не указан sidechain трек для ducking".to_string());
+    }
+    if !(0.000_976_563..=1.0).contains(&self.threshold) {
+      return Err("Порог ducking должен быть в диапазоне 0.001-1.0".to_string());
+    }
+    if !(1.0..=20.0).contains(&self.ratio) {
+      return Err("Степень сжатия ducking должна быть в диапазоне 1-20".to_string());
+    }
+    if !(0.01..=2000.0).contains(&self.attack) {
+      return Err("Атака ducking должна быть в диапазоне 0.01-2000 мс".to_string());
+    }
+    if !(0.01..=9000.0).contains(&self.release) {
+      return Err("Восстановление ducking должно быть в диапазоне 0.01-9000 мс".to_string());
+    }
+    if !(1.0..=64.0).contains(&self.makeup) {
+      return Err("Компенсация усиления ducking должна быть в диапазоне 1-64".to_string());
+    }
+    Ok(())
+  }
+
+  /// Параметры фильтра sidechaincompress
+  pub fn to_ffmpeg_params(&self) -> String {
+    format!(
+      "threshold={}:ratio={}:attack={}:release={}:makeup={}",
+      self.threshold, self.ratio, self.attack, self.release, self.makeup
+    )
+  }
 }
 
 /// Тип трека
@@ -146,7 +280,7 @@ pub struct Clip {
   pub speed: f64,
   /// Прозрачность (0.0 - 1.0)
   pub opacity: f32,
-  /// ID эффектов, применяемых к клипу
+  /// ID эффектов, применяемых к клипу после его фильтров и до эффектов трека
   pub effects: Vec<String>,
   /// ID фильтров, применяемых к клипу
   pub filters: Vec<String>,
@@ -162,6 +296,43 @@ pub struct Clip {
   pub transform: Option<TransformSettings>,
   /// Альтернативный аудио трек
   pub audio_track_index: Option<u32>,
+  /// Индекс аудио потока исходного файла (`0:a:<idx>`)
+  #[serde(default)]
+  pub audio_stream_index: Option<u32>,
+  /// Поворот исходного видео по часовой стрелке из метаданных (0, 90, 180, 270)
+  #[serde(default)]
+  pub source_rotation: i32,
+  /// Внешний аудиофайл, заменяющий встроенный звук клипа
+  #[serde(default)]
+  pub external_audio: Option<ExternalAudio>,
+  /// Заблокирован ли клип для редактирования
+  #[serde(default)]
+  pub locked: bool,
+  /// Цветовая метка клипа
+  #[serde(default)]
+  pub color_label: Option<ClipColorLabel>,
+  /// Заметки пользователя
+  #[serde(default)]
+  pub notes: Option<String>,
+  /// Теги для поиска и пакетных операций
+  #[serde(default)]
+  pub tags: Vec<String>,
+  /// Нарастание в начале клипа (звук и изображение)
+  #[serde(default)]
+  pub fade_in: Option<Fade>,
+  /// Затухание в конце клипа (звук и изображение)
+  #[serde(default)]
+  pub fade_out: Option<Fade>,
+  /// Группа связанных клипов (например, видео и его аудио), которые
+  /// перемещаются по timeline вместе
+  #[serde(default)]
+  pub link_group_id: Option<String>,
+  /// Timecode первого кадра исходного файла (из метаданных источника)
+  #[serde(default)]
+  pub source_timecode: Option<Timecode>,
+  /// Громкость звука клипа (1.0 = без изменений)
+  #[serde(default = "default_clip_volume")]
+  pub volume: f32,
   /// Дополнительные свойства клипа
   pub properties: ClipProperties,
 }
@@ -186,6 +357,18 @@ impl Clip {
       crop: None,
       transform: None,
       audio_track_index: None,
+      audio_stream_index: None,
+      source_rotation: 0,
+      external_audio: None,
+      locked: false,
+      color_label: None,
+      notes: None,
+      tags: Vec::new(),
+      fade_in: None,
+      fade_out: None,
+      link_group_id: None,
+      source_timecode: None,
+      volume: 1.0,
       properties: ClipProperties::default(),
     }
   }
@@ -198,6 +381,12 @@ impl Clip {
       }
     }
 
+    if let ClipSource::Sequence(id) = &self.source {
+      if id.is_empty() {
+        return Err("ID последовательности не может быть пустым".to_string());
+      }
+    }
+
     if self.start_time < 0.0 {
       return Err("Время начала клипа не может быть отрицательным".to_string());
     }
@@ -214,9 +403,65 @@ impl Clip {
       return Err("Прозрачность должна быть в диапазоне 0.0-1.0".to_string());
     }
 
+    if self.volume < 0.0 || self.volume > 2.0 {
+      return Err("Громкость клипа должна быть в диапазоне 0.0-2.0".to_string());
+    }
+
+    if let Some(external_audio) = &self.external_audio {
+      external_audio.validate()?;
+    }
+
+    if let Some(transform) = &self.transform {
+      transform.validate()?;
+    }
+
+    for fade in self.fade_in.iter().chain(&self.fade_out) {
+      fade.validate()?;
+    }
+
     Ok(())
   }
 
+  /// Внешний аудиофайл, если он используется вместо встроенного звука
+  pub fn active_external_audio(&self) -> Option<&ExternalAudio> {
+    self
+      .external_audio
+      .as_ref()
+      .filter(|audio| !audio.use_embedded)
+  }
+
+  /// Выбранный аудио поток исходного файла
+  pub fn selected_audio_stream(&self) -> Option<u32> {
+    self.audio_stream_index.or(self.audio_track_index)
+  }
+
+  /// Метка аудио входа в filter_complex с учетом выбранного потока
+  pub fn audio_input_label(&self, input_index: usize) -> String {
+    match self.selected_audio_stream() {
+      Some(stream) => format!("[{input_index}:a:{stream}]"),
+      None => format!("[{input_index}:a]"),
+    }
+  }
+
+  /// Исходный timecode кадра клипа в момент `time` timeline
+  pub fn source_timecode_at(&self, time: f64) -> Option<Timecode> {
+    let source_time = self.source_start + (time - self.start_time) * self.speed;
+    self
+      .source_timecode
+      .map(|start| start + Timecode::from_seconds(source_time, start.rate, start.drop_frame).frame)
+  }
+
+  /// Фильтр, выпрямляющий повернутое видео. При ручном повороте вход
+  /// открывается с `-noautorotate`, чтобы FFmpeg не повернул кадр дважды.
+  pub fn rotation_filter(&self) -> Option<&'static str> {
+    match self.source_rotation.rem_euclid(360) {
+      90 => Some("transpose=clock"),
+      180 => Some("hflip,vflip"),
+      270 => Some("transpose=cclock"),
+      _ => None,
+    }
+  }
+
   /// Получить длительность клипа на timeline
   pub fn get_timeline_duration(&self) -> f64 {
     self.end_time - self.start_time
@@ -231,6 +476,322 @@ impl Clip {
   pub fn contains_time(&self, time: f64) -> bool {
     time >= self.start_time && time < self.end_time
   }
+
+  /// Длительности нарастания и затухания, ограниченные длительностью клипа.
+  ///
+  /// Если вместе они длиннее клипа, обе уменьшаются пропорционально,
+  /// чтобы затухание не начиналось раньше конца нарастания.
+  pub fn fade_durations(&self) -> (f64, f64) {
+    let duration = self.get_timeline_duration().max(0.0);
+    let requested = |fade: &Option<Fade>| {
+      fade
+        .as_ref()
+        .map_or(0.0, |fade| fade.duration.clamp(0.0, duration))
+    };
+    let (fade_in, fade_out) = (requested(&self.fade_in), requested(&self.fade_out));
+    let total = fade_in + fade_out;
+    if total > duration && total > 0.0 {
+      let scale = duration / total;
+      (fade_in * scale, fade_out * scale)
+    } else {
+      (fade_in, fade_out)
+    }
+  }
+
+  /// Предупреждение, если нарастание и затухание пришлось укоротить
+  pub fn fade_warning(&self) -> Option<String> {
+    let requested = self.fade_in.as_ref().map_or(0.0, |fade| fade.duration)
+      + self.fade_out.as_ref().map_or(0.0, |fade| fade.duration);
+    let (fade_in, fade_out) = self.fade_durations();
+    (requested - (fade_in + fade_out) > 1e-6).then(|| {
+      format!(
+        "Нарастание и затухание клипа {} ({requested:.2} с) длиннее клипа и сокращены до {:.2} с",
+        self.id,
+        fade_in + fade_out
+      )
+    })
+  }
+
+  /// Фильтры afade нарастания и затухания звука клипа
+  pub fn audio_fade_filters(&self) -> Vec<String> {
+    let (fade_in, fade_out) = self.fade_durations();
+    let mut filters = Vec::new();
+    if let Some(fade) = self.fade_in.as_ref().filter(|_| fade_in > 0.0) {
+      filters.push(format!(
+        "afade=t=in:st=0:d={fade_in:.3}:curve={}",
+        fade.curve.afade_curve()
+      ));
+    }
+    if let Some(fade) = self.fade_out.as_ref().filter(|_| fade_out > 0.0) {
+      filters.push(format!(
+        "afade=t=out:st={:.3}:d={fade_out:.3}:curve={}",
+        self.get_timeline_duration() - fade_out,
+        fade.curve.afade_curve()
+      ));
+    }
+    filters
+  }
+
+  /// Фильтры fade нарастания из черного и затухания в черный изображения клипа.
+  ///
+  /// Фильтр fade поддерживает только линейную кривую, поэтому форма кривой
+  /// влияет лишь на звук.
+  pub fn video_fade_filters(&self) -> Vec<String> {
+    let (fade_in, fade_out) = self.fade_durations();
+    let mut filters = Vec::new();
+    if fade_in > 0.0 {
+      filters.push(format!("fade=t=in:st=0:d={fade_in:.3}"));
+    }
+    if fade_out > 0.0 {
+      filters.push(format!(
+        "fade=t=out:st={:.3}:d={fade_out:.3}",
+        self.get_timeline_duration() - fade_out
+      ));
+    }
+    filters
+  }
+
+  /// Имя исходного файла клипа
+  pub fn file_name(&self) -> Option<String> {
+    match &self.source {
+      ClipSource::File(path) => std::path::Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string()),
+      _ => None,
+    }
+  }
+
+  /// Есть ли у клипа тег (без учета регистра)
+  pub fn has_tag(&self, tag: &str) -> bool {
+    let tag = tag.to_lowercase();
+    self.tags.iter().any(|own| own.to_lowercase() == tag)
+  }
+
+  /// Задать цветовую метку, заметки и теги
+  pub fn set_metadata(&mut self, metadata: ClipMetadata) {
+    self.color_label = metadata.color_label;
+    self.notes = metadata.notes.filter(|notes| !notes.trim().is_empty());
+    self.tags.clear();
+    for tag in metadata.tags {
+      let tag = tag.trim();
+      if !tag.is_empty() && !self.has_tag(tag) {
+        self.tags.push(tag.to_string());
+      }
+    }
+  }
+}
+
+/// Форма кривой нарастания или затухания
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FadeCurve {
+  #[default]
+  Linear,
+  Exponential,
+  Logarithmic,
+  SCurve,
+}
+
+impl FadeCurve {
+  /// Значение параметра `curve` фильтра afade
+  pub fn afade_curve(&self) -> &'static str {
+    match self {
+      FadeCurve::Linear => "tri",
+      FadeCurve::Exponential => "exp",
+      FadeCurve::Logarithmic => "log",
+      FadeCurve::SCurve => "esin",
+    }
+  }
+}
+
+/// Нарастание или затухание клипа
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Fade {
+  /// Длительность в секундах
+  pub duration: f64,
+  /// Форма кривой
+  #[serde(default)]
+  pub curve: FadeCurve,
+}
+
+impl Fade {
+  /// Создать нарастание или затухание
+  pub fn new(duration: f64, curve: FadeCurve) -> Self {
+    Self { duration, curve }
+  }
+
+  /// Валидация параметров
+  pub fn validate(&self) -> Result<(), String> {
+    if !self.duration.is_finite() || self.duration < 0.0 {
+      return Err("Длительность нарастания/затухания должна быть неотрицательной".to_string());
+    }
+    Ok(())
+  }
+}
+
+/// Цветовая метка клипа из фиксированной палитры
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ClipColorLabel {
+  Red,
+  Orange,
+  Yellow,
+  Green,
+  Cyan,
+  Blue,
+  Purple,
+  Pink,
+  Gray,
+}
+
+/// Организационные метаданные клипа
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ClipMetadata {
+  #[serde(default)]
+  pub color_label: Option<ClipColorLabel>,
+  #[serde(default)]
+  pub notes: Option<String>,
+  #[serde(default)]
+  pub tags: Vec<String>,
+}
+
+/// Условия поиска клипов. Все заданные условия должны выполняться,
+/// сравнение строк без учета регистра.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ClipSearchQuery {
+  /// Клип должен иметь все перечисленные теги
+  #[serde(default)]
+  pub tags: Vec<String>,
+  /// Цветовая метка
+  #[serde(default)]
+  pub color: Option<ClipColorLabel>,
+  /// Подстрока имени исходного файла или заметок
+  #[serde(default)]
+  pub text: Option<String>,
+  /// Тип трека клипа
+  #[serde(default)]
+  pub track_type: Option<TrackType>,
+  /// Интервал timeline (начало, конец), с которым пересекается клип
+  #[serde(default)]
+  pub time_range: Option<(f64, f64)>,
+}
+
+impl ClipSearchQuery {
+  /// Подходит ли клип трека под условия
+  pub fn matches(&self, track: &Track, clip: &Clip) -> bool {
+    if self
+      .track_type
+      .as_ref()
+      .is_some_and(|track_type| *track_type != track.track_type)
+    {
+      return false;
+    }
+    if self
+      .color
+      .is_some_and(|color| clip.color_label != Some(color))
+    {
+      return false;
+    }
+    if !self.tags.iter().all(|tag| clip.has_tag(tag)) {
+      return false;
+    }
+    if let Some((start, end)) = self.time_range {
+      if clip.end_time <= start || clip.start_time >= end {
+        return false;
+      }
+    }
+    match self
+      .text
+      .as_deref()
+      .map(str::trim)
+      .filter(|text| !text.is_empty())
+    {
+      Some(text) => {
+        let text = text.to_lowercase();
+        clip
+          .file_name()
+          .into_iter()
+          .chain(clip.notes.clone())
+          .any(|value| value.to_lowercase().contains(&text))
+      }
+      None => true,
+    }
+  }
+}
+
+/// Найденный клип и его положение на timeline
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ClipSearchMatch {
+  pub clip_id: String,
+  pub track_id: String,
+  pub track_index: usize,
+  pub clip_index: usize,
+  pub start_time: f64,
+  pub end_time: f64,
+}
+
+/// Внешний аудиофайл клипа (например, запись петличного микрофона)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ExternalAudio {
+  /// Путь к аудиофайлу
+  pub path: String,
+  /// Смещение синхронизации в секундах: момент `t` исходного видео
+  /// соответствует моменту `t + offset_secs` во внешнем файле
+  #[serde(default)]
+  pub offset_secs: f64,
+  /// Усиление (1.0 = без изменений)
+  #[serde(default = "default_external_audio_gain")]
+  pub gain: f32,
+  /// Использовать встроенный звук видео вместо внешнего файла (fallback)
+  #[serde(default)]
+  pub use_embedded: bool,
+}
+
+fn default_clip_volume() -> f32 {
+  1.0
+}
+
+fn default_external_audio_gain() -> f32 {
+  1.0
+}
+
+impl ExternalAudio {
+  /// Создать внешний аудиофайл со смещением
+  pub fn new(path: impl Into<String>, offset_secs: f64) -> Self {
+    Self {
+      path: path.into(),
+      offset_secs,
+      gain: 1.0,
+      use_embedded: false,
+    }
+  }
+
+  /// Валидация параметров
+  pub fn validate(&self) -> Result<(), String> {
+    if self.path.is_empty() {
+      return Err("Путь к внешнему аудиофайлу не может быть пустым".to_string());
+    }
+
+    if !self.offset_secs.is_finite() {
+      return Err("Смещение внешнего аудио должно быть конечным числом".to_string());
+    }
+
+    if self.gain < 0.0 || self.gain > 4.0 {
+      return Err("Усиление внешнего аудио должно быть в диапазоне 0.0-4.0".to_string());
+    }
+
+    Ok(())
+  }
+
+  /// Диапазон внешнего файла, соответствующий диапазону исходника клипа.
+  ///
+  /// Возвращает (начало, конец, задержка): если начало уходит в отрицательную
+  /// область, оно обрезается до нуля, а недостающая часть компенсируется задержкой.
+  pub fn source_range(&self, source_start: f64, source_end: f64) -> (f64, f64, f64) {
+    let start = source_start + self.offset_secs;
+    let end = source_end + self.offset_secs;
+    let delay = (-start).max(0.0);
+    (start.max(0.0), end.max(0.0), delay)
+  }
 }
 
 /// Настройки цветокоррекции
@@ -272,8 +833,83 @@ pub struct CropSettings {
   pub bottom: u32,
 }
 
+/// Прямоугольник кадрирования в процентах от исходного кадра
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct CropRect {
+  pub x: f32,
+  pub y: f32,
+  pub width: f32,
+  pub height: f32,
+}
+
+impl CropRect {
+  /// Валидация прямоугольника кадрирования
+  pub fn validate(&self) -> Result<(), String> {
+    let in_range = |value: f32| (0.0..=100.0).contains(&value);
+    if ![self.x, self.y, self.width, self.height]
+      .into_iter()
+      .all(in_range)
+    {
+      return Err("Кадрирование должно быть в диапазоне 0-100%".to_string());
+    }
+    if self.width <= 0.0 || self.height <= 0.0 {
+      return Err("Размер кадрирования должен быть больше 0".to_string());
+    }
+    // Допуск на округление процентов при вычислении окна
+    let limit = 100.0 + 1e-3;
+    if self.x + self.width > limit || self.y + self.height > limit {
+      return Err("Кадрирование выходит за границы кадра".to_string());
+    }
+    Ok(())
+  }
+}
+
+/// Ключевой кадр пути кадрирования
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct CropKeyframe {
+  /// Время относительно начала клипа в исходном файле (секунды)
+  pub time: f64,
+  /// Окно кадрирования в этот момент
+  pub rect: CropRect,
+}
+
+/// Линейные ключевые кадры одной координаты пути кадрирования (в процентах)
+pub fn crop_axis_keyframes(
+  path: &[CropKeyframe],
+  axis: impl Fn(&CropRect) -> f32,
+) -> Vec<ParamKeyframe> {
+  path
+    .iter()
+    .map(|keyframe| {
+      ParamKeyframe::new(
+        keyframe.time,
+        axis(&keyframe.rect) as f64,
+        KeyframeEasing::Linear,
+      )
+    })
+    .collect()
+}
+
+/// Окно кадрирования пути в момент `time` с линейной интерполяцией позиции.
+///
+/// Размер окна берется из первого ключевого кадра.
+pub fn crop_rect_at(path: &[CropKeyframe], time: f64) -> Option<CropRect> {
+  let first = path.first()?;
+  let x = keyframe_value_at(&crop_axis_keyframes(path, |rect| rect.x), time)?;
+  let y = keyframe_value_at(&crop_axis_keyframes(path, |rect| rect.y), time)?;
+  Some(CropRect {
+    x: x as f32,
+    y: y as f32,
+    ..first.rect
+  })
+}
+
 /// Настройки трансформации
+///
+/// Масштаб и позиция задаются долями кадра проекта, поворот - в градусах
+/// по часовой стрелке.
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
 pub struct TransformSettings {
   pub scale_x: f32,
   pub scale_y: f32,
@@ -282,6 +918,16 @@ pub struct TransformSettings {
   pub rotation: f32,
   pub anchor_x: f32,
   pub anchor_y: f32,
+  /// Кадрирование исходного кадра
+  pub crop: Option<CropRect>,
+  /// Анимированное кадрирование (рефрейминг), заменяет статичное `crop`.
+  ///
+  /// Размер окна постоянен, меняется только его положение.
+  pub crop_path: Vec<CropKeyframe>,
+  /// Отражение по горизонтали
+  pub flip_horizontal: bool,
+  /// Отражение по вертикали
+  pub flip_vertical: bool,
 }
 
 impl Default for TransformSettings {
@@ -294,7 +940,100 @@ impl Default for TransformSettings {
       rotation: 0.0,
       anchor_x: 0.5,
       anchor_y: 0.5,
+      crop: None,
+      crop_path: Vec::new(),
+      flip_horizontal: false,
+      flip_vertical: false,
+    }
+  }
+}
+
+impl TransformSettings {
+  /// Валидация трансформации
+  pub fn validate(&self) -> Result<(), String> {
+    if let Some(crop) = &self.crop {
+      crop.validate()?;
+    }
+
+    for keyframe in &self.crop_path {
+      keyframe.rect.validate()?;
+      if !keyframe.time.is_finite() || keyframe.time < 0.0 {
+        return Err("Время ключевого кадра кадрирования должно быть неотрицательным".to_string());
+      }
+    }
+    if self
+      .crop_path
+      .windows(2)
+      .any(|pair| pair[1].time <= pair[0].time)
+    {
+      return Err("Ключевые кадры кадрирования должны идти по возрастанию времени".to_string());
+    }
+    if let Some(first) = self.crop_path.first() {
+      if self.crop_path.iter().any(|keyframe| {
+        keyframe.rect.width != first.rect.width || keyframe.rect.height != first.rect.height
+      }) {
+        return Err(
+          "Размер окна кадрирования должен быть одинаковым во всех ключевых кадрах".to_string(),
+        );
+      }
+    }
+
+    if !(self.scale_x > 0.0 && self.scale_y > 0.0) {
+      return Err("Масштаб должен быть больше 0".to_string());
+    }
+
+    if !self.rotation.is_finite() || !self.position_x.is_finite() || !self.position_y.is_finite() {
+      return Err("Поворот и позиция должны быть конечными числами".to_string());
     }
+
+    Ok(())
+  }
+
+  /// Фильтры кадрирования, отражения и поворота в порядке применения.
+  ///
+  /// Повороты на 90° кратные углы выполняются через transpose без потери
+  /// качества, остальные углы - через rotate с черной заливкой углов.
+  /// Статичное кадрирование пропускается при заданном `crop_path`: анимированное
+  /// окно строит FFmpegBuilder.
+  pub fn geometry_filters(&self) -> Vec<String> {
+    let mut filters = Vec::new();
+
+    if let Some(crop) = self.crop.as_ref().filter(|_| self.crop_path.is_empty()) {
+      filters.push(format!(
+        "crop=iw*{}:ih*{}:iw*{}:ih*{}",
+        crop.width / 100.0,
+        crop.height / 100.0,
+        crop.x / 100.0,
+        crop.y / 100.0
+      ));
+    }
+    if self.flip_horizontal {
+      filters.push("hflip".to_string());
+    }
+    if self.flip_vertical {
+      filters.push("vflip".to_string());
+    }
+
+    let rotation = self.rotation.rem_euclid(360.0);
+    if rotation == 90.0 {
+      filters.push("transpose=clock".to_string());
+    } else if rotation == 180.0 {
+      filters.push("hflip,vflip".to_string());
+    } else if rotation == 270.0 {
+      filters.push("transpose=cclock".to_string());
+    } else if rotation != 0.0 {
+      filters.push(format!(
+        "rotate={:.6}:ow=iw:oh=ih:c=black",
+        rotation.to_radians()
+      ));
+    }
+
+    filters
+  }
+
+  /// Клип занимает весь кадр проекта без смещения
+  pub fn is_full_frame(&self) -> bool {
+    self.scale_x == 1.0 && self.scale_y == 1.0 && self.position_x == 0.0 && self.position_y == 0.0
   }
 }
 
@@ -353,6 +1092,7 @@ mod tests {
       resolution: (3840, 2160),
       sample_rate: 96000,
       aspect_ratio: AspectRatio::Ratio16x9,
+      markers: Vec::new(),
     };
 
     assert_eq!(timeline.duration, 120.5);
@@ -369,6 +1109,7 @@ mod tests {
       resolution: (1280, 720),
       sample_rate: 44100,
       aspect_ratio: AspectRatio::Ratio16x9,
+      markers: Vec::new(),
     };
 
     let json = serde_json::to_string(&timeline).unwrap();
@@ -471,6 +1212,8 @@ mod tests {
     assert!(clip.crop.is_none());
     assert!(clip.transform.is_none());
     assert!(clip.audio_track_index.is_none());
+    assert_eq!(clip.audio_input_label(0), "[0:a]");
+    assert!(clip.rotation_filter().is_none());
   }
 
   #[test]
@@ -585,11 +1328,130 @@ mod tests {
 
     // Добавляем audio track
     clip.audio_track_index = Some(2);
+    assert_eq!(clip.audio_input_label(3), "[3:a:2]");
+    clip.audio_stream_index = Some(1);
+    assert_eq!(clip.audio_input_label(3), "[3:a:1]");
 
     // Валидация должна пройти
     assert!(clip.validate().is_ok());
   }
 
+  #[test]
+  fn test_clip_transform_validation() {
+    let mut clip = Clip::new(PathBuf::from("video.mp4"), 0.0, 5.0);
+    let crop = |x, y, width, height| TransformSettings {
+      crop: Some(CropRect {
+        x,
+        y,
+        width,
+        height,
+      }),
+      ..Default::default()
+    };
+
+    clip.transform = Some(crop(10.0, 10.0, 80.0, 80.0));
+    assert!(clip.validate().is_ok());
+
+    for invalid in [
+      crop(-5.0, 0.0, 50.0, 50.0),
+      crop(0.0, 0.0, 120.0, 50.0),
+      crop(60.0, 0.0, 50.0, 50.0),
+      crop(0.0, 0.0, 0.0, 50.0),
+      crop(0.0, 0.0, 50.0, 0.0),
+    ] {
+      clip.transform = Some(invalid);
+      assert!(clip.validate().is_err());
+    }
+
+    clip.transform = Some(TransformSettings {
+      scale_x: 0.0,
+      ..Default::default()
+    });
+    assert!(clip.validate().is_err());
+  }
+
+  #[test]
+  fn test_transform_crop_path_validation() {
+    let keyframe = |time, x| CropKeyframe {
+      time,
+      rect: CropRect {
+        x,
+        y: 0.0,
+        width: 31.64,
+        height: 100.0,
+      },
+    };
+    let mut transform = TransformSettings {
+      crop: Some(CropRect {
+        x: 0.0,
+        y: 0.0,
+        width: 50.0,
+        height: 50.0,
+      }),
+      crop_path: vec![keyframe(0.0, 10.0), keyframe(2.0, 50.0)],
+      ..Default::default()
+    };
+    assert!(transform.validate().is_ok());
+    // Статичное кадрирование заменяется путем
+    assert!(transform.geometry_filters().is_empty());
+
+    let rect = crop_rect_at(&transform.crop_path, 1.0).unwrap();
+    assert_eq!(rect.x, 30.0);
+    assert_eq!(rect.width, 31.64);
+    assert_eq!(crop_rect_at(&transform.crop_path, 5.0).unwrap().x, 50.0);
+    assert!(crop_rect_at(&[], 0.0).is_none());
+
+    transform.crop_path = vec![keyframe(1.0, 10.0), keyframe(1.0, 20.0)];
+    assert!(transform.validate().is_err());
+
+    transform.crop_path = vec![keyframe(0.0, 80.0)];
+    assert!(transform.validate().is_err());
+
+    let mut resized = keyframe(1.0, 10.0);
+    resized.rect.width = 40.0;
+    transform.crop_path = vec![keyframe(0.0, 10.0), resized];
+    assert!(transform.validate().is_err());
+  }
+
+  #[test]
+  fn test_transform_deserializes_partial_json() {
+    let transform: TransformSettings =
+      serde_json::from_str(r#"{"rotation": 90.0, "flip_vertical": true}"#).unwrap();
+    assert_eq!(transform.scale_x, 1.0);
+    assert!(transform.crop.is_none());
+    assert!(transform.crop_path.is_empty());
+    assert_eq!(
+      transform.geometry_filters(),
+      vec!["vflip", "transpose=clock"]
+    );
+  }
+
+  #[test]
+  fn test_clip_source_timecode_at() {
+    use super::super::common::TimecodeRate;
+
+    let mut clip = Clip::new(PathBuf::from("camera_a.mov"), 10.0, 5.0);
+    clip.source_start = 2.0;
+    assert!(clip.source_timecode_at(10.0).is_none());
+
+    clip.source_timecode = Some(Timecode::parse("01:02:03;04", TimecodeRate::Fps29_97).unwrap());
+    let timecode = clip.source_timecode_at(11.0).unwrap();
+    assert!(timecode.drop_frame);
+    // 3 секунды исходника при 29.97 - это 90 кадров
+    assert_eq!(timecode.to_string(), "01:02:06;04");
+  }
+
+  #[test]
+  fn test_clip_rotation_filter() {
+    let mut clip = Clip::new(PathBuf::from("portrait.mov"), 0.0, 5.0);
+    clip.source_rotation = 90;
+    assert_eq!(clip.rotation_filter(), Some("transpose=clock"));
+    clip.source_rotation = -90;
+    assert_eq!(clip.rotation_filter(), Some("transpose=cclock"));
+    clip.source_rotation = 180;
+    assert_eq!(clip.rotation_filter(), Some("hflip,vflip"));
+  }
+
   #[test]
   fn test_color_correction_default() {
     let cc = ColorCorrection::default();
@@ -727,4 +1589,36 @@ mod tests {
     assert!(fast_clip.validate().is_ok());
     assert_eq!(fast_clip.get_source_duration(), 0.1);
   }
+
+  #[test]
+  fn test_external_audio_source_range() {
+    // Внешняя запись начата на 2 секунды позже видео
+    let audio = ExternalAudio::new("/audio/lav.wav", -2.0);
+    assert_eq!(audio.source_range(5.0, 10.0), (3.0, 8.0, 0.0));
+
+    // Начало клипа приходится на время до начала записи
+    assert_eq!(audio.source_range(1.0, 4.0), (0.0, 2.0, 1.0));
+  }
+
+  #[test]
+  fn test_external_audio_validation() {
+    let mut clip = Clip::new(PathBuf::from("video.mp4"), 0.0, 10.0);
+    clip.external_audio = Some(ExternalAudio::new("/audio/lav.wav", 0.5));
+    assert!(clip.validate().is_ok());
+    assert!(clip.active_external_audio().is_some());
+
+    clip.external_audio.as_mut().unwrap().use_embedded = true;
+    assert!(clip.active_external_audio().is_none());
+
+    clip.external_audio = Some(ExternalAudio::new("", 0.0));
+    assert!(clip.validate().is_err());
+  }
+
+  #[test]
+  fn test_external_audio_deserialization_defaults() {
+    let audio: ExternalAudio = serde_json::from_str(r#"{"path":"/audio/lav.wav"}"#).unwrap();
+    assert_eq!(audio.offset_secs, 0.0);
+    assert_eq!(audio.gain, 1.0);
+    assert!(!audio.use_embedded);
+  }
 }
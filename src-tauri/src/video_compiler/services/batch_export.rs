@@ -0,0 +1,788 @@
+//! Batch Export - пакетный экспорт нескольких проектов или отрезков проекта
+//!
+//! Каждый элемент пакета становится отдельной задачей RenderService. Пакет
+//! запускает элементы по мере освобождения слотов рендеринга, поэтому общий
+//! лимит одновременных задач соблюдается.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+
+use crate::video_compiler::core::render_priority::ProcessLimits;
+use crate::video_compiler::error::{Result, VideoCompilerError};
+use crate::video_compiler::schema::{ExportSettings, ProjectSchema};
+use crate::video_compiler::services::render_service::{RenderJobStatus, RenderService};
+
+/// Интервал опроса задач рендеринга пакета
+pub const BATCH_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Отрезок timeline проекта для экспорта
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ExportRange {
+  pub start_time: f64,
+  pub end_time: f64,
+}
+
+/// Элемент пакетного экспорта
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportRequest {
+  pub project: ProjectSchema,
+  /// Отрезок проекта; без него экспортируется весь проект
+  #[serde(default)]
+  pub range: Option<ExportRange>,
+  /// Настройки экспорта вместо настроек проекта
+  #[serde(default)]
+  pub settings_override: Option<ExportSettings>,
+  pub output_path: PathBuf,
+}
+
+impl ExportRequest {
+  /// Длительность результата, с
+  pub fn duration(&self) -> f64 {
+    match self.range {
+      Some(range) => (range.end_time - range.start_time).max(0.0),
+      None => self.project.get_duration(),
+    }
+  }
+
+  /// Проект задачи рендеринга: отрезок с переопределенными настройками
+  pub fn build_project(&self) -> Result<ProjectSchema> {
+    let mut project = match self.range {
+      Some(range) => {
+        let duration = self.project.get_duration();
+        if range.start_time < 0.0 || range.end_time <= range.start_time {
+          return Err(VideoCompilerError::InvalidParameter(format!(
+            "Некорректный отрезок экспорта {:.3}-{:.3}",
+            range.start_time, range.end_time
+          )));
+        }
+        if range.start_time >= duration {
+          return Err(VideoCompilerError::InvalidParameter(format!(
+            "Отрезок экспорта начинается после конца проекта ({duration:.3} с)"
+          )));
+        }
+        self
+          .project
+          .extract_range(range.start_time, range.end_time.min(duration))
+      }
+      None => self.project.clone(),
+    };
+
+    if let Some(settings) = &self.settings_override {
+      project.settings.export = settings.clone();
+    }
+    project.validate().map_err(VideoCompilerError::validation)?;
+    Ok(project)
+  }
+}
+
+/// Проверить путь результата до запуска рендеринга
+pub(crate) fn validate_output_path(path: &Path) -> Result<()> {
+  if path.file_name().is_none() || path.is_dir() {
+    return Err(VideoCompilerError::InvalidParameter(format!(
+      "Путь результата не является файлом: {}",
+      path.display()
+    )));
+  }
+  match path
+    .parent()
+    .filter(|parent| !parent.as_os_str().is_empty())
+  {
+    Some(parent) if !parent.is_dir() => Err(VideoCompilerError::ValidationError(
+      format!("Выходная директория не существует: {}", parent.display()).into(),
+    )),
+    _ => Ok(()),
+  }
+}
+
+/// Статус элемента пакета
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchItemStatus {
+  Pending,
+  Rendering,
+  Completed,
+  Failed,
+  Cancelled,
+}
+
+impl BatchItemStatus {
+  /// Элемент больше не изменится
+  pub fn is_finished(self) -> bool {
+    matches!(self, Self::Completed | Self::Failed | Self::Cancelled)
+  }
+}
+
+/// Состояние элемента пакета
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchExportItem {
+  pub index: usize,
+  /// ID задачи рендеринга после запуска
+  pub job_id: Option<String>,
+  pub output_path: PathBuf,
+  /// Длительность результата, с (вес элемента в общем прогрессе)
+  pub duration: f64,
+  pub status: BatchItemStatus,
+  /// Прогресс элемента (0-100)
+  pub percent: f32,
+  pub error: Option<String>,
+  pub output_bytes: Option<u64>,
+}
+
+impl BatchExportItem {
+  fn finish(&mut self, status: BatchItemStatus, error: Option<String>) {
+    self.status = status;
+    self.error = error;
+    if status == BatchItemStatus::Completed {
+      self.percent = 100.0;
+      self.output_bytes = std::fs::metadata(&self.output_path)
+        .ok()
+        .map(|metadata| metadata.len());
+    }
+  }
+}
+
+/// Прогресс пакета: по элементам и общий
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchExportProgress {
+  pub batch_id: String,
+  pub items: Vec<BatchExportItem>,
+  /// Завершено элементов (успешно, с ошибкой или отменено)
+  pub finished: usize,
+  pub total: usize,
+  /// Общий прогресс (0-100), взвешенный по длительности элементов
+  pub overall_percent: f64,
+}
+
+impl BatchExportProgress {
+  fn new(batch_id: &str, items: &[BatchExportItem]) -> Self {
+    let weight = |item: &BatchExportItem| item.duration.max(0.0);
+    let total_weight: f64 = items.iter().map(weight).sum();
+    let done = |item: &BatchExportItem| {
+      if item.status.is_finished() {
+        100.0
+      } else {
+        f64::from(item.percent.clamp(0.0, 100.0))
+      }
+    };
+
+    let overall_percent = if items.is_empty() {
+      100.0
+    } else if total_weight > 0.0 {
+      items
+        .iter()
+        .map(|item| done(item) * weight(item))
+        .sum::<f64>()
+        / total_weight
+    } else {
+      items.iter().map(done).sum::<f64>() / items.len() as f64
+    };
+
+    Self {
+      batch_id: batch_id.to_string(),
+      items: items.to_vec(),
+      finished: items
+        .iter()
+        .filter(|item| item.status.is_finished())
+        .count(),
+      total: items.len(),
+      overall_percent,
+    }
+  }
+}
+
+/// Ошибка элемента пакета
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchExportFailure {
+  pub index: usize,
+  pub output_path: PathBuf,
+  pub error: String,
+}
+
+/// Итог пакетного экспорта
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchExportSummary {
+  pub batch_id: String,
+  pub succeeded: usize,
+  pub failures: Vec<BatchExportFailure>,
+  pub cancelled: usize,
+  pub total_time_ms: u64,
+  pub total_output_bytes: u64,
+  pub items: Vec<BatchExportItem>,
+}
+
+/// Получатель прогресса пакетного экспорта
+pub type BatchExportProgressSink = Arc<dyn Fn(BatchExportProgress) + Send + Sync>;
+
+/// Управление выполняющимся пакетом
+#[derive(Debug, Default)]
+pub struct BatchExportControl {
+  cancel: CancellationToken,
+  force: AtomicBool,
+}
+
+impl BatchExportControl {
+  /// Отменить ожидающие элементы; с `force` отменяются и выполняющиеся
+  pub fn cancel(&self, force: bool) {
+    if force {
+      self.force.store(true, Ordering::SeqCst);
+    }
+    self.cancel.cancel();
+  }
+
+  pub fn is_cancelled(&self) -> bool {
+    self.cancel.is_cancelled()
+  }
+
+  fn is_forced(&self) -> bool {
+    self.force.load(Ordering::SeqCst)
+  }
+}
+
+/// Пакетный экспорт через RenderService
+pub struct BatchExport {
+  batch_id: String,
+  render: Arc<dyn RenderService>,
+  requests: Vec<ExportRequest>,
+  control: Arc<BatchExportControl>,
+  poll_interval: Duration,
+}
+
+impl BatchExport {
+  pub fn new(
+    batch_id: String,
+    render: Arc<dyn RenderService>,
+    requests: Vec<ExportRequest>,
+  ) -> Self {
+    Self {
+      batch_id,
+      render,
+      requests,
+      control: Arc::new(BatchExportControl::default()),
+      poll_interval: BATCH_POLL_INTERVAL,
+    }
+  }
+
+  pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+    self.poll_interval = poll_interval;
+    self
+  }
+
+  /// Управление пакетом для отмены из другой задачи
+  pub fn control(&self) -> Arc<BatchExportControl> {
+    self.control.clone()
+  }
+
+  /// Выполнить пакет до завершения всех элементов.
+  ///
+  /// Ошибка элемента не останавливает остальные: она попадает в итог.
+  pub async fn run(self, sink: Option<BatchExportProgressSink>) -> BatchExportSummary {
+    let started = Instant::now();
+    let (mut items, mut projects) = self.prepare();
+    let mut force_sent = false;
+
+    loop {
+      self.poll_running(&mut items).await;
+
+      if self.control.is_cancelled() {
+        for item in items
+          .iter_mut()
+          .filter(|item| item.status == BatchItemStatus::Pending)
+        {
+          item.finish(BatchItemStatus::Cancelled, None);
+        }
+        if self.control.is_forced() && !force_sent {
+          force_sent = true;
+          self.cancel_running(&items).await;
+        }
+      } else {
+        self.launch_pending(&mut items, &mut projects).await;
+      }
+
+      if let Some(sink) = &sink {
+        sink(BatchExportProgress::new(&self.batch_id, &items));
+      }
+      if items.iter().all(|item| item.status.is_finished()) {
+        break;
+      }
+      tokio::time::sleep(self.poll_interval).await;
+    }
+
+    let summary = BatchExportSummary {
+      batch_id: self.batch_id.clone(),
+      succeeded: count_status(&items, BatchItemStatus::Completed),
+      failures: items
+        .iter()
+        .filter(|item| item.status == BatchItemStatus::Failed)
+        .map(|item| BatchExportFailure {
+          index: item.index,
+          output_path: item.output_path.clone(),
+          error: item.error.clone().unwrap_or_default(),
+        })
+        .collect(),
+      cancelled: count_status(&items, BatchItemStatus::Cancelled),
+      total_time_ms: started.elapsed().as_millis() as u64,
+      total_output_bytes: items.iter().filter_map(|item| item.output_bytes).sum(),
+      items,
+    };
+    log::info!(
+      "Пакетный экспорт {} завершен: {} успешно, {} с ошибкой, {} отменено",
+      summary.batch_id,
+      summary.succeeded,
+      summary.failures.len(),
+      summary.cancelled
+    );
+    summary
+  }
+
+  /// Построить проекты элементов; элементы с ошибкой сразу завершаются
+  fn prepare(&self) -> (Vec<BatchExportItem>, Vec<Option<ProjectSchema>>) {
+    let mut outputs = HashSet::new();
+    let mut items = Vec::with_capacity(self.requests.len());
+    let mut projects = Vec::with_capacity(self.requests.len());
+
+    for (index, request) in self.requests.iter().enumerate() {
+      let mut item = BatchExportItem {
+        index,
+        job_id: None,
+        output_path: request.output_path.clone(),
+        duration: request.duration(),
+        status: BatchItemStatus::Pending,
+        percent: 0.0,
+        error: None,
+        output_bytes: None,
+      };
+
+      let project = validate_output_path(&request.output_path)
+        .and_then(|_| {
+          if outputs.insert(request.output_path.clone()) {
+            Ok(())
+          } else {
+            Err(VideoCompilerError::InvalidParameter(format!(
+              "Путь результата повторяется в пакете: {}",
+              request.output_path.display()
+            )))
+          }
+        })
+        .and_then(|_| request.build_project());
+      match project {
+        Ok(project) => projects.push(Some(project)),
+        Err(e) => {
+          item.finish(BatchItemStatus::Failed, Some(e.to_string()));
+          projects.push(None);
+        }
+      }
+      items.push(item);
+    }
+
+    (items, projects)
+  }
+
+  /// Запустить ожидающие элементы, пока есть свободные слоты
+  async fn launch_pending(
+    &self,
+    items: &mut [BatchExportItem],
+    projects: &mut [Option<ProjectSchema>],
+  ) {
+    for item in items
+      .iter_mut()
+      .filter(|item| item.status == BatchItemStatus::Pending)
+    {
+      if !self.render.has_available_slots().await.unwrap_or(false) {
+        return;
+      }
+      let Some(project) = projects[item.index].clone() else {
+        continue;
+      };
+
+      match self
+        .render
+        .start_render_with_limits(project, item.output_path.clone(), ProcessLimits::default())
+        .await
+      {
+        Ok(job_id) => {
+          log::info!(
+            "Пакетный экспорт {}: элемент {} запущен как задача {job_id}",
+            self.batch_id,
+            item.index
+          );
+          projects[item.index] = None;
+          item.job_id = Some(job_id);
+          item.status = BatchItemStatus::Rendering;
+        }
+        // Слот заняла задача вне пакета: элемент подождет следующего опроса
+        Err(VideoCompilerError::TooManyActiveJobs(_)) => return,
+        Err(e) => {
+          projects[item.index] = None;
+          item.finish(BatchItemStatus::Failed, Some(e.to_string()));
+        }
+      }
+    }
+  }
+
+  /// Обновить состояние выполняющихся элементов по задачам рендеринга
+  async fn poll_running(&self, items: &mut [BatchExportItem]) {
+    for item in items
+      .iter_mut()
+      .filter(|item| item.status == BatchItemStatus::Rendering)
+    {
+      let Some(job_id) = item.job_id.clone() else {
+        continue;
+      };
+      let info = match self.render.get_job_info(&job_id).await {
+        Ok(info) => info,
+        Err(e) => {
+          log::warn!("Не удалось получить состояние задачи {job_id}: {e}");
+          continue;
+        }
+      };
+
+      let Some(info) = info else {
+        // Задача удалена из истории: результат определяем по файлу
+        if item.output_path.exists() {
+          item.finish(BatchItemStatus::Completed, None);
+        } else {
+          item.finish(
+            BatchItemStatus::Failed,
+            Some(format!("Задача рендеринга {job_id} не найдена")),
+          );
+        }
+        continue;
+      };
+
+      match info.status {
+        RenderJobStatus::Completed => item.finish(BatchItemStatus::Completed, None),
+        RenderJobStatus::Failed => item.finish(
+          BatchItemStatus::Failed,
+          Some(
+            info
+              .error
+              .unwrap_or_else(|| "Рендеринг завершился с ошибкой".to_string()),
+          ),
+        ),
+        RenderJobStatus::Cancelled => item.finish(BatchItemStatus::Cancelled, None),
+        _ => {
+          if let Some(progress) = info.progress {
+            item.percent = progress.percentage;
+          }
+        }
+      }
+    }
+  }
+
+  /// Принудительно отменить выполняющиеся элементы
+  async fn cancel_running(&self, items: &[BatchExportItem]) {
+    for job_id in items
+      .iter()
+      .filter(|item| item.status == BatchItemStatus::Rendering)
+      .filter_map(|item| item.job_id.as_deref())
+    {
+      if let Err(e) = self.render.cancel_render(job_id).await {
+        log::warn!("Не удалось отменить задачу {job_id}: {e}");
+      }
+    }
+  }
+}
+
+fn count_status(items: &[BatchExportItem], status: BatchItemStatus) -> usize {
+  items.iter().filter(|item| item.status == status).count()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::video_compiler::progress::RenderProgress;
+  use crate::video_compiler::schema::{Clip, Track, TrackType};
+  use crate::video_compiler::services::render_service::RenderJobInfo;
+  use crate::video_compiler::services::Service;
+  use async_trait::async_trait;
+  use std::sync::atomic::AtomicUsize;
+  use std::sync::Mutex;
+
+  /// Задача мок-рендера: завершается после нескольких опросов
+  struct MockJob {
+    id: String,
+    output_path: PathBuf,
+    polls_left: u32,
+    status: RenderJobStatus,
+  }
+
+  /// Мок RenderService с ограниченным числом слотов
+  struct MockBatchRender {
+    max_slots: usize,
+    jobs: Mutex<Vec<MockJob>>,
+    started: Mutex<Vec<ProjectSchema>>,
+    peak: AtomicUsize,
+  }
+
+  impl MockBatchRender {
+    fn new(max_slots: usize) -> Arc<Self> {
+      Arc::new(Self {
+        max_slots,
+        jobs: Mutex::new(Vec::new()),
+        started: Mutex::new(Vec::new()),
+        peak: AtomicUsize::new(0),
+      })
+    }
+
+    fn running(jobs: &[MockJob]) -> usize {
+      jobs
+        .iter()
+        .filter(|job| job.status == RenderJobStatus::Rendering)
+        .count()
+    }
+  }
+
+  #[async_trait]
+  impl Service for MockBatchRender {
+    async fn initialize(&self) -> Result<()> {
+      Ok(())
+    }
+
+    async fn health_check(&self) -> Result<()> {
+      Ok(())
+    }
+
+    async fn shutdown(&self) -> Result<()> {
+      Ok(())
+    }
+  }
+
+  #[async_trait]
+  impl RenderService for MockBatchRender {
+    async fn start_render(&self, project: ProjectSchema, output_path: PathBuf) -> Result<String> {
+      let mut jobs = self.jobs.lock().unwrap();
+      if Self::running(&jobs) >= self.max_slots {
+        return Err(VideoCompilerError::TooManyActiveJobs("busy".into()));
+      }
+      let id = format!("job-{}", jobs.len() + 1);
+      jobs.push(MockJob {
+        id: id.clone(),
+        output_path,
+        polls_left: 2,
+        status: RenderJobStatus::Rendering,
+      });
+      self.peak.fetch_max(Self::running(&jobs), Ordering::SeqCst);
+      self.started.lock().unwrap().push(project);
+      Ok(id)
+    }
+
+    async fn get_job_info(&self, job_id: &str) -> Result<Option<RenderJobInfo>> {
+      let mut jobs = self.jobs.lock().unwrap();
+      let Some(job) = jobs.iter_mut().find(|job| job.id == job_id) else {
+        return Ok(None);
+      };
+      if job.status == RenderJobStatus::Rendering {
+        job.polls_left -= 1;
+        if job.polls_left == 0 {
+          std::fs::write(&job.output_path, vec![0u8; 1000]).unwrap();
+          job.status = RenderJobStatus::Completed;
+        }
+      }
+      Ok(Some(RenderJobInfo {
+        project_name: String::new(),
+        output_path: Some(job.output_path.clone()),
+        status: job.status.clone(),
+        progress: Some(RenderProgress {
+          percentage: 50.0,
+          ..RenderProgress::default()
+        }),
+        created_at: chrono::Utc::now(),
+        error: None,
+        process_limits: ProcessLimits::default(),
+        downgrades: Vec::new(),
+        report: None,
+      }))
+    }
+
+    async fn get_progress(&self, _job_id: &str) -> Result<Option<RenderProgress>> {
+      Ok(None)
+    }
+
+    async fn cancel_render(&self, job_id: &str) -> Result<bool> {
+      let mut jobs = self.jobs.lock().unwrap();
+      let job = jobs.iter_mut().find(|job| job.id == job_id).unwrap();
+      job.status = RenderJobStatus::Cancelled;
+      Ok(true)
+    }
+
+    async fn pause_render(&self, _job_id: &str) -> Result<bool> {
+      Ok(false)
+    }
+
+    async fn resume_render(&self, _job_id: &str) -> Result<bool> {
+      Ok(false)
+    }
+
+    async fn get_active_jobs(&self) -> Result<Vec<String>> {
+      Ok(
+        self
+          .jobs
+          .lock()
+          .unwrap()
+          .iter()
+          .map(|job| job.id.clone())
+          .collect(),
+      )
+    }
+
+    async fn has_available_slots(&self) -> Result<bool> {
+      Ok(Self::running(&self.jobs.lock().unwrap()) < self.max_slots)
+    }
+  }
+
+  fn long_edit() -> ProjectSchema {
+    let mut project = ProjectSchema::new("Long edit".to_string());
+    let mut track = Track::new(TrackType::Video, "Video".to_string());
+    for (start, end) in [(0.0, 3.0), (3.0, 6.0), (6.0, 9.0)] {
+      track
+        .clips
+        .push(Clip::new(PathBuf::from("/media/a.mp4"), start, end - start));
+    }
+    project.tracks.push(track);
+    project
+  }
+
+  fn three_item_batch(dir: &Path) -> Vec<ExportRequest> {
+    vec![
+      ExportRequest {
+        project: long_edit(),
+        range: None,
+        settings_override: None,
+        output_path: dir.join("full.mp4"),
+      },
+      ExportRequest {
+        project: long_edit(),
+        range: Some(ExportRange {
+          start_time: 2.0,
+          end_time: 5.0,
+        }),
+        settings_override: None,
+        output_path: dir.join("highlight.mp4"),
+      },
+      ExportRequest {
+        project: long_edit(),
+        range: None,
+        settings_override: None,
+        output_path: dir.join("missing").join("broken.mp4"),
+      },
+    ]
+  }
+
+  #[tokio::test]
+  async fn test_batch_export_with_invalid_output_path() {
+    let dir = tempfile::tempdir().unwrap();
+    let render = MockBatchRender::new(1);
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let sink_reports = reports.clone();
+    let sink: BatchExportProgressSink =
+      Arc::new(move |progress| sink_reports.lock().unwrap().push(progress));
+
+    let summary = BatchExport::new(
+      "batch".to_string(),
+      render.clone(),
+      three_item_batch(dir.path()),
+    )
+    .with_poll_interval(Duration::from_millis(1))
+    .run(Some(sink))
+    .await;
+
+    assert_eq!(summary.succeeded, 2);
+    assert_eq!(summary.failures.len(), 1);
+    assert_eq!(summary.failures[0].index, 2);
+    assert!(summary.failures[0].error.contains("не существует"));
+    assert_eq!(summary.total_output_bytes, 2000);
+    assert!(summary.items[..2].iter().all(|item| item.job_id.is_some()));
+    assert!(summary.items[2].job_id.is_none());
+
+    // Элементы запускались по одному, в пределах общего лимита
+    assert_eq!(render.peak.load(Ordering::SeqCst), 1);
+
+    // Отрезок экспортируется как проект со сдвинутыми клипами
+    let started = render.started.lock().unwrap();
+    let clips = &started[1].tracks[0].clips;
+    assert_eq!(clips.len(), 2);
+    assert_eq!((clips[0].start_time, clips[0].end_time), (0.0, 1.0));
+    assert_eq!(started[1].timeline.duration, 3.0);
+
+    // Общий прогресс взвешен по длительности: отрезок весит треть полного проекта
+    let reports = reports.lock().unwrap();
+    let first = &reports[0];
+    assert_eq!((first.finished, first.total), (1, 3));
+    assert!((first.overall_percent - (9.0 * 100.0) / 21.0).abs() < 1e-6);
+    let last = reports.last().unwrap();
+    assert_eq!(last.finished, 3);
+    assert_eq!(last.overall_percent, 100.0);
+  }
+
+  #[tokio::test]
+  async fn test_cancel_batch_keeps_in_flight_items() {
+    for force in [false, true] {
+      let dir = tempfile::tempdir().unwrap();
+      let render = MockBatchRender::new(1);
+      let batch = BatchExport::new(
+        "batch".to_string(),
+        render.clone(),
+        three_item_batch(dir.path()),
+      )
+      .with_poll_interval(Duration::from_millis(1));
+
+      // Отмена сразу после запуска первого элемента
+      let control = batch.control();
+      let sink: BatchExportProgressSink = Arc::new(move |progress| {
+        if progress.items[0].status == BatchItemStatus::Rendering {
+          control.cancel(force);
+        }
+      });
+      let summary = batch.run(Some(sink)).await;
+
+      assert_eq!(summary.items[1].status, BatchItemStatus::Cancelled);
+      assert_eq!(render.started.lock().unwrap().len(), 1);
+      if force {
+        assert_eq!(summary.succeeded, 0);
+        assert_eq!(summary.cancelled, 2);
+        assert_eq!(summary.items[0].status, BatchItemStatus::Cancelled);
+      } else {
+        assert_eq!(summary.succeeded, 1);
+        assert_eq!(summary.items[0].status, BatchItemStatus::Completed);
+      }
+    }
+  }
+
+  #[test]
+  fn test_build_project_range_and_override() {
+    let mut settings = ExportSettings::default();
+    settings.quality = 42;
+    let request = ExportRequest {
+      project: long_edit(),
+      range: Some(ExportRange {
+        start_time: 7.0,
+        end_time: 20.0,
+      }),
+      settings_override: Some(settings),
+      output_path: PathBuf::from("/tmp/tail.mp4"),
+    };
+
+    let project = request.build_project().unwrap();
+    assert_eq!(project.settings.export.quality, 42);
+    assert_eq!(project.tracks[0].clips.len(), 1);
+    assert_eq!(project.tracks[0].clips[0].source_start, 1.0);
+
+    let request = ExportRequest {
+      range: Some(ExportRange {
+        start_time: 12.0,
+        end_time: 15.0,
+      }),
+      ..request
+    };
+    assert!(matches!(
+      request.build_project(),
+      Err(VideoCompilerError::InvalidParameter(_))
+    ));
+  }
+}
@@ -2,10 +2,11 @@
 
 use crate::video_compiler::{
   core::{
-    cache::RenderCache,
+    cache::{CacheSettings, RenderCache},
     error::{Result, VideoCompilerError},
   },
   services::Service,
+  CompilerSettings,
 };
 use async_trait::async_trait;
 use std::{
@@ -14,6 +15,9 @@ use std::{
 };
 use tokio::sync::RwLock;
 
+/// Поддиректория дискового кэша превью в директории кэша
+pub const PREVIEW_CACHE_DIR: &str = "preview";
+
 /// Статистика кэша
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct CacheStats {
@@ -28,6 +32,11 @@ pub struct CacheStats {
   pub hit_rate: f64,
   pub memory_pressure: f64,
   pub eviction_count: u64,
+  // Разделение попаданий превью по слоям кэша
+  #[serde(default)]
+  pub memory_hits: u64,
+  #[serde(default)]
+  pub disk_hits: u64,
 }
 
 /// Расширенные метрики производительности кэша
@@ -163,7 +172,11 @@ pub struct CacheServiceImpl {
 
 impl CacheServiceImpl {
   pub fn new(cache_dir: PathBuf) -> Self {
-    let render_cache = Arc::new(RwLock::new(RenderCache::new()));
+    let render_cache = Arc::new(RwLock::new(RenderCache::with_disk_cache(
+      CacheSettings::default(),
+      cache_dir.join(PREVIEW_CACHE_DIR),
+      CompilerSettings::default().cache_size_mb,
+    )));
 
     Self {
       cache_dir,
@@ -335,6 +348,8 @@ impl CacheService for CacheServiceImpl {
 
   async fn clear_preview_cache(&self) -> Result<()> {
     log::info!("Очистка кэша превью");
+    self.render_cache.write().await.clear_previews().await;
+
     let preview_dir = self.cache_dir.join("preview");
     if preview_dir.exists() {
       tokio::fs::remove_dir_all(&preview_dir)
@@ -412,18 +427,28 @@ impl CacheService for CacheServiceImpl {
       }
     }
 
+    let render_stats = self.render_cache.read().await.get_stats().clone();
+    let cache_hits = render_stats.preview_hits + render_stats.render_hits;
+    let cache_misses = render_stats.preview_misses + render_stats.render_misses;
+    let total_requests = cache_hits + cache_misses;
+
     Ok(CacheStats {
       total_size_mb: total_size,
       preview_cache_size_mb: preview_size,
       render_cache_size_mb: render_size,
       temp_files_size_mb: temp_size,
       total_files,
-      // Пока добавляем заглушки для новых полей
-      cache_hits: 0,
-      cache_misses: 0,
-      hit_rate: 0.0,
+      cache_hits,
+      cache_misses,
+      hit_rate: if total_requests == 0 {
+        0.0
+      } else {
+        cache_hits as f64 / total_requests as f64
+      },
       memory_pressure: 0.0,
-      eviction_count: 0,
+      eviction_count: render_stats.evictions,
+      memory_hits: render_stats.preview_memory_hits,
+      disk_hits: render_stats.preview_disk_hits,
     })
   }
 
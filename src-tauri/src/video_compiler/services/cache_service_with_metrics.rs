@@ -1,13 +1,16 @@
 //! Расширенная версия CacheService с интегрированными метриками
 //!
-//! Этот файл демонстрирует как интегрировать мониторинг в сервисы
+//! Помимо метрик сервиса обертка записывает в [`CACHE_METRICS`] попадания, промахи,
+//! вытеснения и объем по доменам кэша (preview/metadata/render). Запись выполняется
+//! после возврата из внутреннего сервиса, когда его блокировки уже отпущены.
 
+use crate::core::telemetry::cache_metrics::{CacheAccess, CacheMetrics, CACHE_METRICS};
 use crate::video_compiler::{
   error::Result,
   services::{CacheService, Service, ServiceMetrics},
 };
 use async_trait::async_trait;
-use std::{path::PathBuf, sync::Arc};
+use std::{path::PathBuf, sync::Arc, time::Instant};
 
 use super::cache_service::{CacheAlert, CacheAlertThresholds, CachePerformanceMetrics, CacheStats};
 
@@ -15,12 +18,56 @@ use super::cache_service::{CacheAlert, CacheAlertThresholds, CachePerformanceMet
 pub struct CacheServiceWithMetrics {
   inner: Box<dyn CacheService>,
   metrics: Arc<ServiceMetrics>,
+  cache_metrics: Arc<CacheMetrics>,
 }
 
 impl CacheServiceWithMetrics {
-  #[allow(dead_code)]
   pub fn new(inner: Box<dyn CacheService>, metrics: Arc<ServiceMetrics>) -> Self {
-    Self { inner, metrics }
+    Self {
+      inner,
+      metrics,
+      cache_metrics: CACHE_METRICS.clone(),
+    }
+  }
+
+  /// Писать метрики доменов в другой сборщик вместо глобального
+  pub fn with_cache_metrics(mut self, cache_metrics: Arc<CacheMetrics>) -> Self {
+    self.cache_metrics = cache_metrics;
+    self
+  }
+
+  /// Начало замера; `None`, если сбор метрик кэша выключен
+  fn access_started(&self) -> Option<Instant> {
+    self.cache_metrics.is_enabled().then(Instant::now)
+  }
+
+  fn record_access(
+    &self,
+    key: &str,
+    operation: &'static str,
+    access: CacheAccess,
+    started: Option<Instant>,
+  ) {
+    if let Some(started) = started {
+      self
+        .cache_metrics
+        .record(key, operation, access, started.elapsed());
+    }
+  }
+
+  /// Учесть как вытесненные известные элементы, которых больше нет на диске
+  async fn record_evictions(&self) {
+    if !self.cache_metrics.is_enabled() {
+      return;
+    }
+
+    let mut evicted = Vec::new();
+    for key in self.cache_metrics.tracked_keys() {
+      if let Ok(false) = self.inner.exists_in_cache(&key).await {
+        evicted.push(key);
+      }
+    }
+    self.cache_metrics.record_evictions(&evicted);
   }
 }
 
@@ -76,6 +123,7 @@ impl CacheService for CacheServiceWithMetrics {
     match self.inner.clear_all().await {
       Ok(result) => {
         tracker.complete().await;
+        self.record_evictions().await;
         log::info!("[CacheService] Весь кэш успешно очищен");
         Ok(result)
       }
@@ -92,6 +140,7 @@ impl CacheService for CacheServiceWithMetrics {
     match self.inner.clear_render_cache().await {
       Ok(result) => {
         tracker.complete().await;
+        self.record_evictions().await;
         log::info!("[CacheService] Кэш рендеринга успешно очищен");
         Ok(result)
       }
@@ -108,6 +157,7 @@ impl CacheService for CacheServiceWithMetrics {
     match self.inner.clear_preview_cache().await {
       Ok(result) => {
         tracker.complete().await;
+        self.record_evictions().await;
         log::info!("[CacheService] Кэш превью успешно очищен");
         Ok(result)
       }
@@ -124,6 +174,7 @@ impl CacheService for CacheServiceWithMetrics {
     match self.inner.clear_project_cache(project_id).await {
       Ok(result) => {
         tracker.complete().await;
+        self.record_evictions().await;
         log::info!("[CacheService] Кэш проекта {project_id} успешно очищен");
         Ok(result)
       }
@@ -176,6 +227,7 @@ impl CacheService for CacheServiceWithMetrics {
     match self.inner.optimize_cache(max_age_days).await {
       Ok(removed_count) => {
         tracker.complete().await;
+        self.record_evictions().await;
         log::info!(
           "[CacheService] Оптимизация кэша завершена: удалено {removed_count} файлов старше {max_age_days} дней"
         );
@@ -205,9 +257,18 @@ impl CacheService for CacheServiceWithMetrics {
 
   async fn save_to_cache(&self, key: &str, data: &[u8]) -> Result<PathBuf> {
     let tracker = self.metrics.start_operation("save_to_cache");
+    let started = self.access_started();
     match self.inner.save_to_cache(key, data).await {
       Ok(path) => {
         tracker.complete().await;
+        self.record_access(
+          key,
+          "save_to_cache",
+          CacheAccess::Store {
+            size_bytes: data.len() as u64,
+          },
+          started,
+        );
         log::trace!(
           "[CacheService] Сохранено в кэш: {} ({} байт)",
           key,
@@ -225,9 +286,18 @@ impl CacheService for CacheServiceWithMetrics {
 
   async fn get_from_cache(&self, key: &str) -> Result<Option<Vec<u8>>> {
     let tracker = self.metrics.start_operation("get_from_cache");
+    let started = self.access_started();
     match self.inner.get_from_cache(key).await {
       Ok(Some(data)) => {
         tracker.complete().await;
+        self.record_access(
+          key,
+          "get_from_cache",
+          CacheAccess::Hit {
+            size_bytes: data.len() as u64,
+          },
+          started,
+        );
         log::trace!(
           "[CacheService] Получено из кэша: {} ({} байт)",
           key,
@@ -237,6 +307,7 @@ impl CacheService for CacheServiceWithMetrics {
       }
       Ok(None) => {
         tracker.complete().await;
+        self.record_access(key, "get_from_cache", CacheAccess::Miss, started);
         log::trace!("[CacheService] Не найдено в кэше: {key}");
         Ok(None)
       }
@@ -250,9 +321,11 @@ impl CacheService for CacheServiceWithMetrics {
 
   async fn exists_in_cache(&self, key: &str) -> Result<bool> {
     let tracker = self.metrics.start_operation("exists_in_cache");
+    let started = self.access_started();
     match self.inner.exists_in_cache(key).await {
       Ok(exists) => {
         tracker.complete().await;
+        self.record_access(key, "exists_in_cache", CacheAccess::Probe, started);
         Ok(exists)
       }
       Err(e) => {
@@ -720,4 +793,112 @@ mod tests {
     assert!(summary.total_operations >= 4); // init + save + 2*exists
     assert_eq!(summary.total_errors, 0);
   }
+
+  fn service_with_domain_metrics(
+    temp_dir: &TempDir,
+    enabled: bool,
+  ) -> (CacheServiceWithMetrics, Arc<CacheMetrics>) {
+    let inner = Box::new(CacheServiceImpl::new(temp_dir.path().to_path_buf()));
+    let metrics = Arc::new(ServiceMetrics::new("test-domains".to_string()));
+    let cache_metrics = Arc::new(CacheMetrics::default());
+    cache_metrics.set_enabled(enabled);
+    let service =
+      CacheServiceWithMetrics::new(inner, metrics).with_cache_metrics(cache_metrics.clone());
+    (service, cache_metrics)
+  }
+
+  #[tokio::test]
+  async fn test_domain_metrics_scripted_access_pattern() {
+    use crate::core::telemetry::cache_metrics::CacheDomain;
+
+    let temp_dir = TempDir::new().unwrap();
+    let (service, cache_metrics) = service_with_domain_metrics(&temp_dir, true);
+    service.initialize().await.unwrap();
+
+    service
+      .save_to_cache("preview/clip-1.jpg", &[0u8; 100])
+      .await
+      .unwrap();
+    service
+      .save_to_cache("metadata/clip-1.json", &[0u8; 20])
+      .await
+      .unwrap();
+    service
+      .save_to_cache("render/segment-1.mp4", &[0u8; 1000])
+      .await
+      .unwrap();
+
+    // preview: 3 попадания и 1 промах, metadata: 1 попадание и 1 промах
+    for _ in 0..3 {
+      service.get_from_cache("preview/clip-1.jpg").await.unwrap();
+    }
+    service.get_from_cache("preview/clip-2.jpg").await.unwrap();
+    service
+      .get_from_cache("metadata/clip-1.json")
+      .await
+      .unwrap();
+    service
+      .get_from_cache("metadata/clip-2.json")
+      .await
+      .unwrap();
+    service
+      .exists_in_cache("render/segment-1.mp4")
+      .await
+      .unwrap();
+
+    let report = cache_metrics.report(1);
+    let domain = |domain: CacheDomain| {
+      report
+        .domains
+        .iter()
+        .find(|d| d.domain == domain)
+        .unwrap()
+        .clone()
+    };
+    assert_eq!(domain(CacheDomain::Preview).hit_ratio, 0.75);
+    assert_eq!(domain(CacheDomain::Metadata).hit_ratio, 0.5);
+    assert_eq!(domain(CacheDomain::Render).hits, 0);
+    assert_eq!(domain(CacheDomain::Render).bytes_stored, 1000);
+    assert_eq!(report.largest_entries.len(), 1);
+    assert_eq!(report.largest_entries[0].key, "render/segment-1.mp4");
+
+    // Очистка удаляет все три элемента с диска
+    service.clear_all().await.unwrap();
+
+    let report = cache_metrics.report(10);
+    for d in &report.domains {
+      assert_eq!(d.evictions, 1, "domain {:?}", d.domain);
+      assert_eq!(d.bytes_stored, 0);
+      assert_eq!(d.entries, 0);
+    }
+    assert!(report.largest_entries.is_empty());
+
+    // Повторная очистка не считает вытеснения дважды
+    service.clear_all().await.unwrap();
+    let evictions: u64 = cache_metrics
+      .report(10)
+      .domains
+      .iter()
+      .map(|d| d.evictions)
+      .sum();
+    assert_eq!(evictions, 3);
+  }
+
+  #[tokio::test]
+  async fn test_domain_metrics_disabled_records_nothing() {
+    let temp_dir = TempDir::new().unwrap();
+    let (service, cache_metrics) = service_with_domain_metrics(&temp_dir, false);
+
+    service
+      .save_to_cache("preview/clip.jpg", b"data")
+      .await
+      .unwrap();
+    service.get_from_cache("preview/clip.jpg").await.unwrap();
+    service.clear_all().await.unwrap();
+
+    let report = cache_metrics.report(10);
+    assert!(!report.enabled);
+    assert!(report.domains.is_empty());
+    assert!(cache_metrics.tracked_keys().is_empty());
+  }
 }
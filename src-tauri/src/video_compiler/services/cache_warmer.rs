@@ -0,0 +1,563 @@
+//! Прогрев кэша для недавно открытых проектов
+//!
+//! [`CacheWarmer`] обходит исходники клипов проекта и заранее заполняет
+//! метаданные, первую миниатюру каждого видеоклипа и waveform аудиоклипов.
+//! Работа идет с низким приоритетом: не более двух операций одновременно,
+//! перед каждой операцией прогрев ждет завершения интерактивных запросов,
+//! а записи в кэш не вытесняют уже существующие элементы.
+
+use crate::core::tasks::{TaskKind, TASKS};
+use crate::video_compiler::{
+  cache::RenderCache,
+  core::priority::{interactive_in_flight, run_in_background, yield_to_interactive},
+  error::{Result, VideoCompilerError},
+  schema::{
+    timeline::{ClipSource, TrackType},
+    ProjectSchema,
+  },
+  services::{
+    ffmpeg_service::media_metadata_from_info, preview_service::WAVEFORM_PREVIEW_COLOR,
+    FfmpegService, PreviewService, ServiceContainer,
+  },
+};
+use chrono::{DateTime, Utc};
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::{
+  collections::HashSet,
+  path::{Path, PathBuf},
+  sync::Arc,
+  time::Duration,
+};
+use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
+
+/// Размер прогреваемой миниатюры: совпадает с размером кадра timeline по умолчанию
+pub const WARM_THUMBNAIL_SIZE: (u32, u32) = (1280, 720);
+
+/// Максимальное число одновременных операций прогрева
+pub const MAX_WARM_CONCURRENCY: usize = 2;
+
+/// Сколько последних проектов запоминается
+pub const MAX_RECENT_PROJECTS: usize = 10;
+
+/// Задержка автоматического прогрева после запуска приложения
+pub const AUTO_WARM_DELAY: Duration = Duration::from_secs(30);
+
+/// Интервал повторной проверки простоя системы
+const IDLE_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Сколько раз проверять простой, прежде чем отказаться от автопрогрева
+const IDLE_MAX_ATTEMPTS: usize = 30;
+
+/// Файл списка недавних проектов
+const RECENT_PROJECTS_FILE: &str = "recent_projects.json";
+
+/// Элемент плана прогрева
+#[derive(Debug, Clone, PartialEq)]
+pub enum WarmupItem {
+  Metadata(PathBuf),
+  Thumbnail { path: PathBuf, timestamp: f64 },
+  Waveform(PathBuf),
+}
+
+/// Итоги прогрева
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WarmupReport {
+  pub metadata: usize,
+  pub thumbnails: usize,
+  pub waveforms: usize,
+  /// Элементы, уже находившиеся в кэше
+  pub already_cached: usize,
+  pub failed: usize,
+  pub cancelled: bool,
+}
+
+/// Составить план прогрева: сначала дешевые метаданные, затем миниатюры и waveform
+pub fn plan_warmup(project: &ProjectSchema) -> Vec<WarmupItem> {
+  let mut metadata = Vec::new();
+  let mut thumbnails = Vec::new();
+  let mut waveforms = Vec::new();
+  let mut seen_files = HashSet::new();
+  let mut seen_waveforms = HashSet::new();
+
+  for track in project.tracks.iter().filter(|track| track.enabled) {
+    for clip in &track.clips {
+      let ClipSource::File(path) = &clip.source else {
+        continue;
+      };
+      let path = PathBuf::from(path);
+
+      if seen_files.insert(path.clone()) {
+        metadata.push(WarmupItem::Metadata(path.clone()));
+      }
+
+      match track.track_type {
+        TrackType::Video => {
+          let item = WarmupItem::Thumbnail {
+            path,
+            timestamp: clip.source_start.max(0.0),
+          };
+          if !thumbnails.contains(&item) {
+            thumbnails.push(item);
+          }
+        }
+        TrackType::Audio => {
+          if seen_waveforms.insert(path.clone()) {
+            waveforms.push(WarmupItem::Waveform(path));
+          }
+        }
+        TrackType::Subtitle => {}
+      }
+    }
+  }
+
+  metadata.extend(thumbnails);
+  metadata.extend(waveforms);
+  metadata
+}
+
+/// Результат одного шага прогрева
+enum WarmupOutcome {
+  Warmed(WarmupItem),
+  AlreadyCached,
+  Failed,
+}
+
+/// Низкоприоритетный прогрев кэшей превью и метаданных
+pub struct CacheWarmer {
+  preview: Arc<dyn PreviewService>,
+  ffmpeg: Arc<dyn FfmpegService>,
+  cache: Arc<RwLock<RenderCache>>,
+  concurrency: usize,
+}
+
+impl CacheWarmer {
+  pub fn new(
+    preview: Arc<dyn PreviewService>,
+    ffmpeg: Arc<dyn FfmpegService>,
+    cache: Arc<RwLock<RenderCache>>,
+  ) -> Self {
+    Self {
+      preview,
+      ffmpeg,
+      cache,
+      concurrency: 1,
+    }
+  }
+
+  /// Прогрев сервисами контейнера
+  pub fn from_services(services: &ServiceContainer, cache: Arc<RwLock<RenderCache>>) -> Self {
+    Self::new(services.preview.clone(), services.ffmpeg.clone(), cache)
+  }
+
+  /// Число одновременных операций (1-2)
+  pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+    self.concurrency = concurrency.clamp(1, MAX_WARM_CONCURRENCY);
+    self
+  }
+
+  /// Прогреть кэши для проекта.
+  ///
+  /// `on_progress` получает (выполнено, всего). Отмена `cancel` прекращает
+  /// прогрев перед следующим элементом.
+  pub async fn warm_project(
+    &self,
+    project: &ProjectSchema,
+    cancel: &CancellationToken,
+    on_progress: impl Fn(usize, usize),
+  ) -> WarmupReport {
+    let items = plan_warmup(project);
+    let total = items.len();
+    let mut report = WarmupReport::default();
+
+    run_in_background(async {
+      let mut outcomes = stream::iter(items)
+        .map(|item| async move {
+          if !yield_to_interactive(cancel).await {
+            return None;
+          }
+          Some(self.warm_item(item).await)
+        })
+        .buffer_unordered(self.concurrency);
+
+      let mut completed = 0;
+      while let Some(outcome) = outcomes.next().await {
+        match outcome {
+          Some(WarmupOutcome::Warmed(WarmupItem::Metadata(_))) => report.metadata += 1,
+          Some(WarmupOutcome::Warmed(WarmupItem::Thumbnail { .. })) => report.thumbnails += 1,
+          Some(WarmupOutcome::Warmed(WarmupItem::Waveform(_))) => report.waveforms += 1,
+          Some(WarmupOutcome::AlreadyCached) => report.already_cached += 1,
+          Some(WarmupOutcome::Failed) => report.failed += 1,
+          None => report.cancelled = true,
+        }
+        completed += 1;
+        on_progress(completed, total);
+      }
+    })
+    .await;
+
+    report.cancelled |= cancel.is_cancelled();
+    report
+  }
+
+  /// Прогреть один элемент. Ошибка не прерывает прогрев проекта.
+  async fn warm_item(&self, item: WarmupItem) -> WarmupOutcome {
+    let result = match &item {
+      WarmupItem::Metadata(path) => self.warm_metadata(path).await,
+      WarmupItem::Thumbnail { path, timestamp } => self
+        .preview
+        .generate_frame_preview(path, *timestamp, Some(WARM_THUMBNAIL_SIZE))
+        .await
+        .map(|_| true),
+      WarmupItem::Waveform(path) => {
+        let (width, height) = WARM_THUMBNAIL_SIZE;
+        self
+          .preview
+          .generate_waveform(path, width, height, WAVEFORM_PREVIEW_COLOR)
+          .await
+          .map(|_| true)
+      }
+    };
+
+    match result {
+      Ok(true) => WarmupOutcome::Warmed(item),
+      Ok(false) => WarmupOutcome::AlreadyCached,
+      Err(e) => {
+        log::debug!("Прогрев {item:?} не удался: {e}");
+        WarmupOutcome::Failed
+      }
+    }
+  }
+
+  /// Заполнить метаданные файла. `false`, если они уже в кэше.
+  async fn warm_metadata(&self, path: &Path) -> Result<bool> {
+    let key = path.to_string_lossy().to_string();
+    if self.cache.read().await.has_metadata(&key) {
+      return Ok(false);
+    }
+
+    let info = self.ffmpeg.get_file_info(path).await?;
+    self
+      .cache
+      .write()
+      .await
+      .store_metadata(key.clone(), media_metadata_from_info(&key, Some(&info)))
+      .await?;
+    Ok(true)
+  }
+}
+
+/// Запись списка недавних проектов
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecentProject {
+  /// Путь к файлу проекта
+  pub path: String,
+  pub opened_at: DateTime<Utc>,
+}
+
+/// Сохраняемый список последних открытых проектов
+pub struct RecentProjects {
+  file: PathBuf,
+  limit: usize,
+}
+
+impl RecentProjects {
+  pub fn new(file: PathBuf, limit: usize) -> Self {
+    Self { file, limit }
+  }
+
+  /// Список в директории приложения
+  pub fn default_location() -> Option<Self> {
+    let dirs = crate::app_dirs::AppDirectories::get_or_create().ok()?;
+    Some(Self::new(
+      dirs.base_dir.join(RECENT_PROJECTS_FILE),
+      MAX_RECENT_PROJECTS,
+    ))
+  }
+
+  /// Загрузить список, новые проекты первыми
+  pub async fn load(&self) -> Vec<RecentProject> {
+    match tokio::fs::read_to_string(&self.file).await {
+      Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
+      Err(_) => Vec::new(),
+    }
+  }
+
+  /// Отметить проект как открытый сейчас
+  pub async fn record(&self, path: &str) -> Result<Vec<RecentProject>> {
+    let mut projects = self.load().await;
+    projects.retain(|project| project.path != path);
+    projects.insert(
+      0,
+      RecentProject {
+        path: path.to_string(),
+        opened_at: Utc::now(),
+      },
+    );
+    projects.truncate(self.limit);
+
+    if let Some(parent) = self.file.parent() {
+      tokio::fs::create_dir_all(parent)
+        .await
+        .map_err(|e| VideoCompilerError::IoError(e.to_string()))?;
+    }
+    let content = serde_json::to_string_pretty(&projects)
+      .map_err(|e| VideoCompilerError::SerializationError(e.to_string()))?;
+    tokio::fs::write(&self.file, content)
+      .await
+      .map_err(|e| VideoCompilerError::IoError(e.to_string()))?;
+
+    Ok(projects)
+  }
+
+  /// Последний открытый проект
+  pub async fn most_recent(&self) -> Option<RecentProject> {
+    self.load().await.into_iter().next()
+  }
+}
+
+/// Простаивает ли приложение: нет фоновых задач, рендеров и интерактивных запросов
+async fn system_is_idle(services: &ServiceContainer) -> bool {
+  let no_renders = services
+    .render
+    .running_jobs()
+    .await
+    .map(|jobs| jobs.is_empty())
+    .unwrap_or(false);
+
+  no_renders && TASKS.list().is_empty() && interactive_in_flight() == 0
+}
+
+/// Прогреть кэш последнего открытого проекта, когда приложение простаивает.
+///
+/// Запускается вскоре после старта; прогрев регистрируется как отменяемая
+/// фоновая задача.
+pub async fn auto_warm_recent_project(
+  services: Arc<ServiceContainer>,
+  cache: Arc<RwLock<RenderCache>>,
+  recent: RecentProjects,
+  delay: Duration,
+) {
+  tokio::time::sleep(delay).await;
+
+  let Some(recent_project) = recent.most_recent().await else {
+    return;
+  };
+
+  let mut idle = false;
+  for _ in 0..IDLE_MAX_ATTEMPTS {
+    if system_is_idle(&services).await {
+      idle = true;
+      break;
+    }
+    tokio::time::sleep(IDLE_POLL_INTERVAL).await;
+  }
+  if !idle {
+    log::debug!("Автопрогрев кэша пропущен: приложение не простаивает");
+    return;
+  }
+
+  let project = match services
+    .project
+    .load_project(Path::new(&recent_project.path))
+    .await
+    .and_then(|result| result.into_project())
+  {
+    Ok(project) => project,
+    Err(e) => {
+      log::debug!(
+        "Автопрогрев кэша: не удалось загрузить {}: {e}",
+        recent_project.path
+      );
+      return;
+    }
+  };
+
+  let token = CancellationToken::new();
+  let cancel = token.clone();
+  let task = TASKS.register_cancellable(
+    TaskKind::CacheWarmup,
+    format!("Прогрев кэша: {}", project.metadata.name),
+    move || cancel.cancel(),
+  );
+
+  let report = CacheWarmer::from_services(&services, cache)
+    .warm_project(&project, &token, |completed, total| {
+      task.set_progress(completed as f32 / total as f32 * 100.0)
+    })
+    .await;
+  task.finish();
+
+  log::info!(
+    "Автопрогрев кэша проекта {} завершен: {report:?}",
+    project.metadata.name
+  );
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::video_compiler::schema::{Clip, Track};
+  use crate::video_compiler::services::FfmpegServiceImpl;
+  use crate::video_compiler::tests::mocks::MockPreviewService;
+
+  fn project_with_media() -> ProjectSchema {
+    let mut project = ProjectSchema::new("Warmup".to_string());
+
+    let mut video = Track::new(TrackType::Video, "Video".to_string());
+    let mut first = Clip::new(PathBuf::from("/media/a.mp4"), 0.0, 5.0);
+    first.source_start = 2.0;
+    video.clips.push(first);
+    video
+      .clips
+      .push(Clip::new(PathBuf::from("/media/a.mp4"), 5.0, 5.0));
+    project.tracks.push(video);
+
+    let mut audio = Track::new(TrackType::Audio, "Audio".to_string());
+    audio
+      .clips
+      .push(Clip::new(PathBuf::from("/media/music.mp3"), 0.0, 10.0));
+    audio
+      .clips
+      .push(Clip::new(PathBuf::from("/media/music.mp3"), 10.0, 5.0));
+    project.tracks.push(audio);
+
+    project
+  }
+
+  #[test]
+  fn test_plan_warmup_dedupes_sources() {
+    let plan = plan_warmup(&project_with_media());
+
+    assert_eq!(
+      plan,
+      vec![
+        WarmupItem::Metadata(PathBuf::from("/media/a.mp4")),
+        WarmupItem::Metadata(PathBuf::from("/media/music.mp3")),
+        WarmupItem::Thumbnail {
+          path: PathBuf::from("/media/a.mp4"),
+          timestamp: 2.0,
+        },
+        WarmupItem::Thumbnail {
+          path: PathBuf::from("/media/a.mp4"),
+          timestamp: 0.0,
+        },
+        WarmupItem::Waveform(PathBuf::from("/media/music.mp3")),
+      ]
+    );
+  }
+
+  fn warmer(preview: Arc<MockPreviewService>, cache: Arc<RwLock<RenderCache>>) -> CacheWarmer {
+    CacheWarmer::new(
+      preview,
+      Arc::new(FfmpegServiceImpl::new("nonexistent-ffmpeg".to_string())),
+      cache,
+    )
+    .with_concurrency(2)
+  }
+
+  async fn cache_with_metadata(paths: &[&str]) -> Arc<RwLock<RenderCache>> {
+    let cache = Arc::new(RwLock::new(RenderCache::new()));
+    for path in paths {
+      cache
+        .write()
+        .await
+        .store_metadata(path.to_string(), media_metadata_from_info(path, None))
+        .await
+        .unwrap();
+    }
+    cache
+  }
+
+  #[tokio::test]
+  async fn test_warm_project_fills_previews_and_skips_cached_metadata() {
+    let preview = Arc::new(MockPreviewService::default());
+    let cache = cache_with_metadata(&["/media/a.mp4", "/media/music.mp3"]).await;
+    let progress = std::sync::Mutex::new(Vec::new());
+
+    let report = warmer(preview.clone(), cache)
+      .warm_project(
+        &project_with_media(),
+        &CancellationToken::new(),
+        |completed, total| progress.lock().unwrap().push((completed, total)),
+      )
+      .await;
+
+    assert_eq!(report.thumbnails, 2);
+    assert_eq!(report.waveforms, 1);
+    assert_eq!(report.already_cached, 2);
+    assert_eq!(report.failed, 0);
+    assert!(!report.cancelled);
+    assert_eq!(progress.lock().unwrap().last(), Some(&(5, 5)));
+
+    let mut timestamps = preview.requested_timestamps.lock().unwrap().clone();
+    timestamps.sort_by(f64::total_cmp);
+    assert_eq!(timestamps, vec![0.0, 2.0]);
+  }
+
+  #[tokio::test]
+  async fn test_warm_project_stops_when_cancelled() {
+    let preview = Arc::new(MockPreviewService::default());
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+
+    let report = warmer(preview.clone(), Arc::new(RwLock::new(RenderCache::new())))
+      .warm_project(&project_with_media(), &cancel, |_, _| {})
+      .await;
+
+    assert!(report.cancelled);
+    assert_eq!(report.thumbnails + report.waveforms + report.metadata, 0);
+    assert!(preview.requested_timestamps.lock().unwrap().is_empty());
+  }
+
+  #[tokio::test]
+  async fn test_background_store_does_not_evict_interactive_entries() {
+    let cache = Arc::new(RwLock::new(RenderCache::new()));
+    cache.write().await.set_cache_limits(10, 1, 10);
+    cache
+      .write()
+      .await
+      .store_metadata(
+        "/media/interactive.mp4".to_string(),
+        media_metadata_from_info("/media/interactive.mp4", None),
+      )
+      .await
+      .unwrap();
+
+    run_in_background(async {
+      cache
+        .write()
+        .await
+        .store_metadata(
+          "/media/warm.mp4".to_string(),
+          media_metadata_from_info("/media/warm.mp4", None),
+        )
+        .await
+        .unwrap();
+    })
+    .await;
+
+    let cache = cache.read().await;
+    assert!(cache.has_metadata("/media/interactive.mp4"));
+    assert!(!cache.has_metadata("/media/warm.mp4"));
+  }
+
+  #[tokio::test]
+  async fn test_recent_projects_are_ordered_and_limited() {
+    let dir = tempfile::tempdir().unwrap();
+    let recent = RecentProjects::new(dir.path().join(RECENT_PROJECTS_FILE), 2);
+
+    recent.record("/projects/a.json").await.unwrap();
+    recent.record("/projects/b.json").await.unwrap();
+    recent.record("/projects/a.json").await.unwrap();
+    recent.record("/projects/c.json").await.unwrap();
+
+    let paths: Vec<String> = recent.load().await.into_iter().map(|p| p.path).collect();
+    assert_eq!(paths, vec!["/projects/c.json", "/projects/a.json"]);
+    assert_eq!(
+      recent.most_recent().await.map(|p| p.path),
+      Some("/projects/c.json".to_string())
+    );
+  }
+}
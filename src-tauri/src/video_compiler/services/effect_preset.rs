@@ -0,0 +1,471 @@
+//! Effect Presets - библиотека пользовательских пресетов эффектов и фильтров
+//!
+//! Пресет хранит набор эффектов и фильтров ("look"), который можно применить
+//! к клипам любого проекта. Библиотека хранится одним JSON-файлом в
+//! директории приложения пользователя, отдельные пресеты можно
+//! экспортировать в файл и импортировать на другой машине.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::video_compiler::error::{Result, VideoCompilerError};
+use crate::video_compiler::schema::{Effect, Filter};
+use crate::video_compiler::services::project_template::retain_readable;
+
+/// Файл библиотеки пресетов в директории приложения
+pub const EFFECT_PRESETS_FILE: &str = "effect_presets.json";
+
+/// Версия формата файла библиотеки
+pub const EFFECT_PRESETS_VERSION: u32 = 1;
+
+/// Пресет эффектов и фильтров
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EffectPreset {
+  /// Уникальный идентификатор пресета
+  pub id: String,
+  /// Название пресета
+  pub name: String,
+  /// Категория ("Color", "Film", ...)
+  pub category: String,
+  /// Время создания
+  pub created_at: DateTime<Utc>,
+  /// Время последнего изменения
+  pub modified_at: DateTime<Utc>,
+  /// Эффекты пресета
+  #[serde(default)]
+  pub effects: Vec<Effect>,
+  /// Фильтры пресета
+  #[serde(default)]
+  pub filters: Vec<Filter>,
+}
+
+/// Результат импорта пресета
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EffectPresetImport {
+  pub preset: EffectPreset,
+  /// Пропущенные при импорте эффекты и фильтры
+  pub warnings: Vec<String>,
+}
+
+/// Файл библиотеки пресетов
+#[derive(Debug, Serialize)]
+struct EffectPresetLibrary<'a> {
+  version: u32,
+  presets: &'a [EffectPreset],
+}
+
+impl EffectPreset {
+  /// Создать пресет. Название и категория обрезаются по краям.
+  pub fn new(
+    name: String,
+    category: String,
+    effects: Vec<Effect>,
+    filters: Vec<Filter>,
+  ) -> Result<Self> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+      return Err(VideoCompilerError::InvalidParameter(
+        "Название пресета не может быть пустым".to_string(),
+      ));
+    }
+    if effects.is_empty() && filters.is_empty() {
+      return Err(VideoCompilerError::InvalidParameter(format!(
+        "Пресет '{name}' не содержит эффектов и фильтров"
+      )));
+    }
+
+    let now = Utc::now();
+    Ok(Self {
+      id: Uuid::new_v4().to_string(),
+      name,
+      category: category.trim().to_string(),
+      created_at: now,
+      modified_at: now,
+      effects,
+      filters,
+    })
+  }
+
+  /// Копии эффектов и фильтров пресета с новыми ID.
+  ///
+  /// Каждое применение пресета создает независимые копии, чтобы изменение
+  /// параметров на одном клипе не затрагивало остальные.
+  pub fn instantiate(&self) -> (Vec<Effect>, Vec<Filter>) {
+    let effects = self
+      .effects
+      .iter()
+      .cloned()
+      .map(|mut effect| {
+        effect.id = Uuid::new_v4().to_string();
+        effect
+      })
+      .collect();
+    let filters = self
+      .filters
+      .iter()
+      .cloned()
+      .map(|mut filter| {
+        filter.id = Uuid::new_v4().to_string();
+        filter
+      })
+      .collect();
+    (effects, filters)
+  }
+}
+
+/// Разобрать пресет, пропуская эффекты и фильтры, которые не удается прочитать.
+///
+/// Пресет из более новой версии приложения может содержать неизвестные типы
+/// эффектов - они пропускаются с предупреждением, остальное загружается.
+pub fn parse_preset(mut value: Value) -> Result<(EffectPreset, Vec<String>)> {
+  let mut warnings = Vec::new();
+  if let Some(preset) = value.as_object_mut() {
+    retain_readable::<Effect>(preset.get_mut("effects"), "эффект", &mut warnings);
+    retain_readable::<Filter>(preset.get_mut("filters"), "фильтр", &mut warnings);
+  }
+
+  let preset: EffectPreset = serde_json::from_value(value)
+    .map_err(|e| VideoCompilerError::ValidationError(format!("Некорректный пресет: {e}").into()))?;
+  Ok((preset, warnings))
+}
+
+/// Библиотека пресетов пользователя
+#[derive(Debug, Clone)]
+pub struct EffectPresetStore {
+  file: PathBuf,
+}
+
+impl EffectPresetStore {
+  pub fn new(file: PathBuf) -> Self {
+    Self { file }
+  }
+
+  /// Библиотека в директории приложения
+  pub fn default_location() -> Result<Self> {
+    let dirs = crate::app_dirs::AppDirectories::get_or_create()
+      .map_err(|e| VideoCompilerError::IoError(e.to_string()))?;
+    Ok(Self::new(dirs.base_dir.join(EFFECT_PRESETS_FILE)))
+  }
+
+  /// Загрузить все пресеты.
+  ///
+  /// Отсутствующий файл - пустая библиотека. Поврежденный файл возвращает
+  /// ошибку, чтобы следующее сохранение не перезаписало пресеты пользователя.
+  pub async fn load(&self) -> Result<(Vec<EffectPreset>, Vec<String>)> {
+    let content = match tokio::fs::read_to_string(&self.file).await {
+      Ok(content) => content,
+      Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok((Vec::new(), Vec::new())),
+      Err(e) => return Err(VideoCompilerError::IoError(e.to_string())),
+    };
+    let mut library: Value = serde_json::from_str(&content).map_err(|e| {
+      VideoCompilerError::ValidationError(format!("Некорректный файл пресетов: {e}").into())
+    })?;
+
+    let mut presets = Vec::new();
+    let mut warnings = Vec::new();
+    let items = library
+      .get_mut("presets")
+      .and_then(Value::as_array_mut)
+      .map(std::mem::take)
+      .unwrap_or_default();
+    for item in items {
+      match parse_preset(item) {
+        Ok((preset, preset_warnings)) => {
+          warnings.extend(
+            preset_warnings
+              .into_iter()
+              .map(|warning| format!("{}: {warning}", preset.name)),
+          );
+          presets.push(preset);
+        }
+        Err(e) => warnings.push(format!("Пропущен пресет: {e}")),
+      }
+    }
+    Ok((presets, warnings))
+  }
+
+  /// Сохранить все пресеты
+  async fn save(&self, presets: &[EffectPreset]) -> Result<()> {
+    if let Some(parent) = self.file.parent() {
+      tokio::fs::create_dir_all(parent)
+        .await
+        .map_err(|e| VideoCompilerError::IoError(e.to_string()))?;
+    }
+    let library = EffectPresetLibrary {
+      version: EFFECT_PRESETS_VERSION,
+      presets,
+    };
+    let content = serde_json::to_string_pretty(&library)
+      .map_err(|e| VideoCompilerError::SerializationError(e.to_string()))?;
+    tokio::fs::write(&self.file, content)
+      .await
+      .map_err(|e| VideoCompilerError::IoError(e.to_string()))
+  }
+
+  /// Пресеты категории (или все), отсортированные по названию
+  pub async fn list(&self, category: Option<&str>) -> Result<Vec<EffectPreset>> {
+    let (mut presets, _) = self.load().await?;
+    if let Some(category) = category {
+      presets.retain(|preset| preset.category.eq_ignore_ascii_case(category.trim()));
+    }
+    presets.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(presets)
+  }
+
+  /// Найти пресет по ID
+  pub async fn get(&self, preset_id: &str) -> Result<EffectPreset> {
+    let (presets, _) = self.load().await?;
+    presets
+      .into_iter()
+      .find(|preset| preset.id == preset_id)
+      .ok_or_else(|| preset_not_found(preset_id))
+  }
+
+  /// Добавить пресет в библиотеку
+  pub async fn add(&self, preset: EffectPreset) -> Result<EffectPreset> {
+    let (mut presets, _) = self.load().await?;
+    presets.push(preset.clone());
+    self.save(&presets).await?;
+    Ok(preset)
+  }
+
+  /// Переименовать пресет
+  pub async fn rename(&self, preset_id: &str, name: String) -> Result<EffectPreset> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+      return Err(VideoCompilerError::InvalidParameter(
+        "Название пресета не может быть пустым".to_string(),
+      ));
+    }
+
+    let (mut presets, _) = self.load().await?;
+    let preset = presets
+      .iter_mut()
+      .find(|preset| preset.id == preset_id)
+      .ok_or_else(|| preset_not_found(preset_id))?;
+    preset.name = name;
+    preset.modified_at = Utc::now();
+    let renamed = preset.clone();
+
+    self.save(&presets).await?;
+    Ok(renamed)
+  }
+
+  /// Удалить пресет
+  pub async fn delete(&self, preset_id: &str) -> Result<()> {
+    let (mut presets, _) = self.load().await?;
+    let count = presets.len();
+    presets.retain(|preset| preset.id != preset_id);
+    if presets.len() == count {
+      return Err(preset_not_found(preset_id));
+    }
+    self.save(&presets).await
+  }
+
+  /// Экспортировать пресет в отдельный файл
+  pub async fn export(&self, preset_id: &str, path: &Path) -> Result<()> {
+    let preset = self.get(preset_id).await?;
+    let content = serde_json::to_string_pretty(&preset)
+      .map_err(|e| VideoCompilerError::SerializationError(e.to_string()))?;
+    tokio::fs::write(path, content)
+      .await
+      .map_err(|e| VideoCompilerError::IoError(e.to_string()))
+  }
+
+  /// Импортировать пресет из файла.
+  ///
+  /// Пресет без читаемых эффектов и фильтров не импортируется. ID,
+  /// совпадающий с существующим, заменяется новым.
+  pub async fn import(&self, path: &Path) -> Result<EffectPresetImport> {
+    let content = tokio::fs::read_to_string(path)
+      .await
+      .map_err(|e| VideoCompilerError::IoError(e.to_string()))?;
+    let value: Value = serde_json::from_str(&content).map_err(|e| {
+      VideoCompilerError::ValidationError(format!("Некорректный JSON пресета: {e}").into())
+    })?;
+    let (mut preset, warnings) = parse_preset(value)?;
+    if preset.effects.is_empty() && preset.filters.is_empty() {
+      return Err(VideoCompilerError::ValidationError(
+        format!(
+          "Пресет '{}' не содержит поддерживаемых эффектов и фильтров",
+          preset.name
+        )
+        .into(),
+      ));
+    }
+
+    let (mut presets, _) = self.load().await?;
+    if presets.iter().any(|existing| existing.id == preset.id) {
+      preset.id = Uuid::new_v4().to_string();
+    }
+    presets.push(preset.clone());
+    self.save(&presets).await?;
+
+    Ok(EffectPresetImport { preset, warnings })
+  }
+}
+
+fn preset_not_found(preset_id: &str) -> VideoCompilerError {
+  VideoCompilerError::InvalidParameter(format!("Effect preset not found: {preset_id}"))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::video_compiler::schema::{EffectType, FilterType};
+  use tempfile::TempDir;
+
+  fn look_preset() -> EffectPreset {
+    EffectPreset::new(
+      "Teal & Orange".to_string(),
+      "Color".to_string(),
+      vec![
+        Effect::new(EffectType::Contrast, "Contrast".to_string()),
+        Effect::new(EffectType::Vignette, "Vignette".to_string()),
+      ],
+      vec![Filter::new(FilterType::Grain, "Grain".to_string())],
+    )
+    .unwrap()
+  }
+
+  #[test]
+  fn test_preset_validation() {
+    let empty = EffectPreset::new("Look".to_string(), String::new(), vec![], vec![]);
+    assert!(matches!(
+      empty,
+      Err(VideoCompilerError::InvalidParameter(_))
+    ));
+    let unnamed = EffectPreset::new(
+      "  ".to_string(),
+      String::new(),
+      vec![Effect::new(EffectType::Blur, "Blur".to_string())],
+      vec![],
+    );
+    assert!(matches!(
+      unnamed,
+      Err(VideoCompilerError::InvalidParameter(_))
+    ));
+  }
+
+  #[test]
+  fn test_instantiate_assigns_fresh_ids() {
+    let preset = look_preset();
+    let (first_effects, first_filters) = preset.instantiate();
+    let (second_effects, _) = preset.instantiate();
+
+    assert_eq!(first_effects.len(), 2);
+    assert_eq!(first_filters.len(), 1);
+    assert_eq!(first_effects[0].effect_type, EffectType::Contrast);
+    assert_ne!(first_effects[0].id, preset.effects[0].id);
+    assert_ne!(first_effects[0].id, second_effects[0].id);
+    assert_ne!(first_filters[0].id, preset.filters[0].id);
+  }
+
+  #[test]
+  fn test_parse_preset_skips_unknown_effects() {
+    let mut value = serde_json::to_value(look_preset()).unwrap();
+    value["effects"][1]["effect_type"] = Value::String("HologramGlow".to_string());
+    value["filters"]
+      .as_array_mut()
+      .unwrap()
+      .push(serde_json::json!({ "id": "broken", "filter_type": 42 }));
+
+    let (preset, warnings) = parse_preset(value).unwrap();
+    assert_eq!(preset.effects.len(), 1);
+    assert_eq!(preset.filters.len(), 1);
+    assert_eq!(warnings.len(), 2);
+    assert!(warnings.iter().any(|w| w.contains("'broken'")));
+  }
+
+  #[tokio::test]
+  async fn test_store_crud_and_categories() {
+    let temp_dir = TempDir::new().unwrap();
+    let store = EffectPresetStore::new(temp_dir.path().join("nested").join(EFFECT_PRESETS_FILE));
+    assert!(store.list(None).await.unwrap().is_empty());
+
+    let look = store.add(look_preset()).await.unwrap();
+    let film = EffectPreset::new(
+      "Film".to_string(),
+      "Film".to_string(),
+      vec![Effect::new(EffectType::FilmGrain, "Grain".to_string())],
+      vec![],
+    )
+    .unwrap();
+    store.add(film).await.unwrap();
+
+    assert_eq!(store.list(None).await.unwrap().len(), 2);
+    let color = store.list(Some("color")).await.unwrap();
+    assert_eq!(color.len(), 1);
+    assert_eq!(color[0].id, look.id);
+
+    let renamed = store
+      .rename(&look.id, " Warm Look ".to_string())
+      .await
+      .unwrap();
+    assert_eq!(renamed.name, "Warm Look");
+    assert_eq!(store.get(&look.id).await.unwrap().name, "Warm Look");
+
+    store.delete(&look.id).await.unwrap();
+    assert!(store.get(&look.id).await.is_err());
+    assert!(store.delete(&look.id).await.is_err());
+    assert_eq!(store.list(None).await.unwrap().len(), 1);
+  }
+
+  #[tokio::test]
+  async fn test_export_import_roundtrip() {
+    let temp_dir = TempDir::new().unwrap();
+    let store = EffectPresetStore::new(temp_dir.path().join(EFFECT_PRESETS_FILE));
+    let preset = store.add(look_preset()).await.unwrap();
+
+    let export_path = temp_dir.path().join("look.json");
+    store.export(&preset.id, &export_path).await.unwrap();
+
+    // Импорт в библиотеку другого пользователя сохраняет ID
+    let other = EffectPresetStore::new(temp_dir.path().join("other").join(EFFECT_PRESETS_FILE));
+    let imported = other.import(&export_path).await.unwrap();
+    assert!(imported.warnings.is_empty());
+    assert_eq!(imported.preset.id, preset.id);
+    assert_eq!(imported.preset.name, preset.name);
+    assert_eq!(imported.preset.effects.len(), 2);
+    assert_eq!(imported.preset.filters[0].filter_type, FilterType::Grain);
+
+    // Повторный импорт в ту же библиотеку получает новый ID
+    let duplicate = store.import(&export_path).await.unwrap();
+    assert_ne!(duplicate.preset.id, preset.id);
+    assert_eq!(store.list(None).await.unwrap().len(), 2);
+  }
+
+  #[tokio::test]
+  async fn test_import_with_unknown_effects_only_fails() {
+    let temp_dir = TempDir::new().unwrap();
+    let store = EffectPresetStore::new(temp_dir.path().join(EFFECT_PRESETS_FILE));
+
+    let mut value = serde_json::to_value(look_preset()).unwrap();
+    value["effects"] = serde_json::json!([{ "id": "x", "effect_type": "FutureEffect" }]);
+    value["filters"] = serde_json::json!([]);
+    let path = temp_dir.path().join("future.json");
+    std::fs::write(&path, value.to_string()).unwrap();
+
+    let result = store.import(&path).await;
+    assert!(matches!(
+      result,
+      Err(VideoCompilerError::ValidationError(_))
+    ));
+    assert!(store.list(None).await.unwrap().is_empty());
+  }
+
+  #[tokio::test]
+  async fn test_corrupted_library_is_not_overwritten() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = temp_dir.path().join(EFFECT_PRESETS_FILE);
+    std::fs::write(&file, "{ not json").unwrap();
+    let store = EffectPresetStore::new(file.clone());
+
+    assert!(store.add(look_preset()).await.is_err());
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), "{ not json");
+  }
+}
@@ -0,0 +1,487 @@
+//! Возможности сборки FFmpeg: кодировщики, декодеры, фильтры, мюксеры,
+//! форматы пикселей и аппаратные ускорители
+//!
+//! Списки читаются из FFmpeg один раз при первом обращении и сохраняются на
+//! диск вместе с отпечатком бинарника (путь, размер, время изменения), поэтому
+//! следующие запуски приложения не запускают FFmpeg повторно. Замена
+//! бинарника меняет отпечаток, и списки читаются заново.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::UNIX_EPOCH;
+
+use crate::video_compiler::core::ffmpeg_manager::parse_component_list;
+use crate::video_compiler::error::{Result, VideoCompilerError};
+use crate::video_compiler::ffmpeg_builder::capabilities::parse_filters_output;
+
+/// Поддиректория кэша с сохраненными возможностями
+const CAPABILITIES_DIR: &str = "ffmpeg-capabilities";
+
+/// Попытки запуска FFmpeg: сразу после записи бинарника запуск может
+/// завершиться ошибкой "Text file busy"
+const PROBE_ATTEMPTS: usize = 3;
+
+/// Общие кэши по пути к FFmpeg
+static SHARED_CACHES: Lazy<Mutex<HashMap<String, Arc<FfmpegCapabilitiesCache>>>> =
+  Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Возможности сборки FFmpeg
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FfmpegCapabilities {
+  /// Первая строка `ffmpeg -version`
+  pub version: String,
+  pub encoders: BTreeSet<String>,
+  pub decoders: BTreeSet<String>,
+  pub filters: BTreeSet<String>,
+  pub muxers: BTreeSet<String>,
+  pub pixel_formats: BTreeSet<String>,
+  /// Аппаратные ускорители в порядке вывода FFmpeg
+  pub hwaccels: Vec<String>,
+}
+
+impl FfmpegCapabilities {
+  /// Разобрать вывод команд FFmpeg
+  pub fn from_outputs(outputs: &ProbeOutputs) -> Self {
+    Self {
+      version: outputs
+        .version
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .to_string(),
+      encoders: parse_component_list(&outputs.encoders)
+        .into_iter()
+        .collect(),
+      decoders: parse_component_list(&outputs.decoders)
+        .into_iter()
+        .collect(),
+      filters: parse_filters_output(&outputs.filters).into_iter().collect(),
+      muxers: parse_muxers_output(&outputs.muxers),
+      pixel_formats: parse_pix_fmts_output(&outputs.pix_fmts),
+      hwaccels: parse_hwaccels_output(&outputs.hwaccels),
+    }
+  }
+
+  /// Прочитать возможности из FFmpeg
+  pub async fn probe(ffmpeg_path: &str) -> Result<Self> {
+    let outputs = ProbeOutputs {
+      version: run_probe(ffmpeg_path, "-version").await?,
+      encoders: run_probe(ffmpeg_path, "-encoders").await?,
+      decoders: run_probe(ffmpeg_path, "-decoders").await?,
+      filters: run_probe(ffmpeg_path, "-filters").await?,
+      muxers: run_probe(ffmpeg_path, "-muxers").await?,
+      pix_fmts: run_probe(ffmpeg_path, "-pix_fmts").await?,
+      hwaccels: run_probe(ffmpeg_path, "-hwaccels").await?,
+    };
+    Ok(Self::from_outputs(&outputs))
+  }
+
+  pub fn has_encoder(&self, name: &str) -> bool {
+    self.encoders.contains(name)
+  }
+
+  pub fn has_decoder(&self, name: &str) -> bool {
+    self.decoders.contains(name)
+  }
+
+  pub fn has_filter(&self, name: &str) -> bool {
+    self.filters.contains(name)
+  }
+
+  pub fn has_muxer(&self, name: &str) -> bool {
+    self.muxers.contains(name)
+  }
+
+  pub fn has_pixel_format(&self, name: &str) -> bool {
+    self.pixel_formats.contains(name)
+  }
+
+  pub fn hwaccels(&self) -> &[String] {
+    &self.hwaccels
+  }
+}
+
+/// Вывод команд FFmpeg, из которых собираются возможности
+#[derive(Debug, Clone, Default)]
+pub struct ProbeOutputs {
+  pub version: String,
+  pub encoders: String,
+  pub decoders: String,
+  pub filters: String,
+  pub muxers: String,
+  pub pix_fmts: String,
+  pub hwaccels: String,
+}
+
+/// Отпечаток бинарника FFmpeg: изменение размера или времени изменения
+/// означает другую сборку
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BinaryFingerprint {
+  pub path: PathBuf,
+  pub size: u64,
+  pub modified_secs: u64,
+  pub modified_nanos: u32,
+}
+
+impl BinaryFingerprint {
+  /// Отпечаток бинарника; имя без пути ищется в PATH
+  pub fn of(ffmpeg_path: &str) -> Option<Self> {
+    let path = Path::new(ffmpeg_path);
+    let path = if path.components().count() > 1 {
+      path.to_path_buf()
+    } else {
+      which::which(ffmpeg_path).ok()?
+    };
+    let metadata = std::fs::metadata(&path).ok()?;
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some(Self {
+      path,
+      size: metadata.len(),
+      modified_secs: modified.as_secs(),
+      modified_nanos: modified.subsec_nanos(),
+    })
+  }
+}
+
+/// Запись кэша на диске
+#[derive(Debug, Serialize, Deserialize)]
+struct PersistedCapabilities {
+  fingerprint: BinaryFingerprint,
+  capabilities: FfmpegCapabilities,
+}
+
+/// Прочитанные возможности и отпечаток бинарника, для которого они получены
+struct LoadedCapabilities {
+  fingerprint: Option<BinaryFingerprint>,
+  capabilities: Arc<FfmpegCapabilities>,
+}
+
+/// Кэш возможностей FFmpeg для одного пути к бинарнику
+pub struct FfmpegCapabilitiesCache {
+  ffmpeg_path: String,
+  /// Директория сохраненных возможностей (`None` - только в памяти)
+  persist_dir: Option<PathBuf>,
+  loaded: tokio::sync::Mutex<Option<LoadedCapabilities>>,
+}
+
+impl FfmpegCapabilitiesCache {
+  pub fn new(ffmpeg_path: impl Into<String>, persist_dir: Option<PathBuf>) -> Self {
+    Self {
+      ffmpeg_path: ffmpeg_path.into(),
+      persist_dir,
+      loaded: tokio::sync::Mutex::new(None),
+    }
+  }
+
+  /// Общий кэш для пути к FFmpeg, сохраняемый в пользовательский кэш
+  pub fn shared(ffmpeg_path: &str) -> Arc<Self> {
+    SHARED_CACHES
+      .lock()
+      .unwrap()
+      .entry(ffmpeg_path.to_string())
+      .or_insert_with(|| {
+        let persist_dir = dirs::cache_dir().map(|dir| dir.join("timeline-studio"));
+        Arc::new(Self::new(ffmpeg_path, persist_dir))
+      })
+      .clone()
+  }
+
+  /// Возможности FFmpeg. Читаются при первом обращении и после замены
+  /// бинарника; ошибки не кэшируются, чтобы следующий вызов повторил попытку.
+  pub async fn get(&self) -> Result<Arc<FfmpegCapabilities>> {
+    let fingerprint = BinaryFingerprint::of(&self.ffmpeg_path);
+    let mut loaded = self.loaded.lock().await;
+    if let Some(current) = loaded.as_ref() {
+      if current.fingerprint == fingerprint {
+        return Ok(current.capabilities.clone());
+      }
+    }
+
+    let persisted = fingerprint
+      .as_ref()
+      .and_then(|fingerprint| self.read_persisted(fingerprint));
+    let capabilities = match persisted {
+      Some(capabilities) => capabilities,
+      None => {
+        log::info!("Чтение возможностей FFmpeg: {}", self.ffmpeg_path);
+        let capabilities = FfmpegCapabilities::probe(&self.ffmpeg_path).await?;
+        if let Some(fingerprint) = &fingerprint {
+          if let Err(e) = self.write_persisted(fingerprint, &capabilities) {
+            log::warn!("Не удалось сохранить возможности FFmpeg: {e}");
+          }
+        }
+        capabilities
+      }
+    };
+
+    let capabilities = Arc::new(capabilities);
+    *loaded = Some(LoadedCapabilities {
+      fingerprint,
+      capabilities: capabilities.clone(),
+    });
+    Ok(capabilities)
+  }
+
+  /// Файл сохраненных возможностей для пути к FFmpeg
+  fn persist_file(&self) -> Option<PathBuf> {
+    let digest = format!("{:x}", Sha256::digest(self.ffmpeg_path.as_bytes()));
+    self.persist_dir.as_ref().map(|dir| {
+      dir
+        .join(CAPABILITIES_DIR)
+        .join(format!("{}.json", &digest[..16]))
+    })
+  }
+
+  /// Сохраненные возможности, если они получены для того же бинарника
+  fn read_persisted(&self, fingerprint: &BinaryFingerprint) -> Option<FfmpegCapabilities> {
+    let content = std::fs::read_to_string(self.persist_file()?).ok()?;
+    let persisted: PersistedCapabilities = serde_json::from_str(&content).ok()?;
+    (persisted.fingerprint == *fingerprint).then_some(persisted.capabilities)
+  }
+
+  fn write_persisted(
+    &self,
+    fingerprint: &BinaryFingerprint,
+    capabilities: &FfmpegCapabilities,
+  ) -> Result<()> {
+    let Some(file) = self.persist_file() else {
+      return Ok(());
+    };
+    if let Some(parent) = file.parent() {
+      std::fs::create_dir_all(parent)?;
+    }
+    let persisted = PersistedCapabilities {
+      fingerprint: fingerprint.clone(),
+      capabilities: capabilities.clone(),
+    };
+    std::fs::write(file, serde_json::to_string(&persisted)?)?;
+    Ok(())
+  }
+}
+
+/// Вывод `ffmpeg <flag>`
+async fn run_probe(ffmpeg_path: &str, flag: &str) -> Result<String> {
+  let mut last_error = None;
+  for attempt in 0..PROBE_ATTEMPTS {
+    if attempt > 0 {
+      tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    match tokio::process::Command::new(ffmpeg_path)
+      .arg(flag)
+      .kill_on_drop(true)
+      .output()
+      .await
+    {
+      Ok(output) if output.status.success() => {
+        return Ok(String::from_utf8_lossy(&output.stdout).to_string());
+      }
+      Ok(output) => {
+        return Err(VideoCompilerError::FFmpegError {
+          exit_code: output.status.code(),
+          stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+          command: flag.to_string(),
+        });
+      }
+      Err(e) => last_error = Some(e),
+    }
+  }
+
+  Err(VideoCompilerError::FFmpegError {
+    exit_code: None,
+    stderr: format!(
+      "Не удалось запустить FFmpeg: {}",
+      last_error.map(|e| e.to_string()).unwrap_or_default()
+    ),
+    command: flag.to_string(),
+  })
+}
+
+/// Разобрать вывод `ffmpeg -muxers`.
+///
+/// Строки имеют вид ` E  mp4   MP4 (MPEG-4 Part 14)` или ` DE matroska ...`;
+/// несколько мюксеров одной строки перечисляются через запятую.
+pub fn parse_muxers_output(output: &str) -> BTreeSet<String> {
+  output
+    .lines()
+    .filter_map(|line| {
+      let mut parts = line.split_whitespace();
+      let flags = parts.next()?;
+      let names = parts.next()?;
+      let is_flags = flags.contains('E') && flags.chars().all(|c| "DEd".contains(c));
+      (is_flags && names != "=").then_some(names)
+    })
+    .flat_map(|names| names.split(',').map(str::to_string))
+    .collect()
+}
+
+/// Разобрать вывод `ffmpeg -pix_fmts`: строки ` IO... yuv420p  3  12  8-8-8`
+pub fn parse_pix_fmts_output(output: &str) -> BTreeSet<String> {
+  output
+    .lines()
+    .filter_map(|line| {
+      let mut parts = line.split_whitespace();
+      let flags = parts.next()?;
+      let name = parts.next()?;
+      let components = parts.next()?;
+      let is_flags = flags.len() == 5 && flags.chars().all(|c| "IOHPB.".contains(c));
+      (is_flags && components.parse::<u32>().is_ok()).then(|| name.to_string())
+    })
+    .collect()
+}
+
+/// Разобрать вывод `ffmpeg -hwaccels`: имена после заголовка
+pub fn parse_hwaccels_output(output: &str) -> Vec<String> {
+  output
+    .lines()
+    .skip_while(|line| !line.starts_with("Hardware acceleration methods"))
+    .skip(1)
+    .map(str::trim)
+    .filter(|line| !line.is_empty())
+    .map(str::to_string)
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tempfile::TempDir;
+
+  const ENCODERS: &str = "Encoders:\n \
+    V..... = Video\n \
+    ------\n \
+    V....D libx264              libx264 H.264 / AVC (codec h264)\n \
+    V....D h264_nvenc           NVIDIA NVENC H.264 encoder (codec h264)\n \
+    A....D aac                  AAC (Advanced Audio Coding)\n";
+
+  const MUXERS: &str = "File formats:\n \
+    D. = Demuxing supported\n \
+    .E = Muxing supported\n \
+    --\n \
+    E 3g2             3GP2 (3GPP2 file format)\n \
+    DE matroska,webm  Matroska / WebM\n  \
+    E mp4             MP4 (MPEG-4 Part 14)\n \
+    D  mov,mp4,m4a    QuickTime / MOV\n";
+
+  const PIX_FMTS: &str = "Pixel formats:\n\
+    I.... = Supported Input  format for conversion\n\
+    -----\n\
+    IO... yuv420p                3            12      8-8-8\n\
+    IO... yuv420p10le            3            15      10-10-10\n\
+    ..H.. cuda                   0             0      0\n";
+
+  const HWACCELS: &str = "Hardware acceleration methods:\nvdpau\ncuda\nvaapi\n\n";
+
+  const FILTERS: &str = "Filters:\n  \
+    T.. = Timeline support\n \
+    TSC xfade             VV->V      Cross fade.\n \
+    ... minterpolate      V->V       Frame rate conversion using Motion Interpolation.\n";
+
+  fn canned_outputs() -> ProbeOutputs {
+    ProbeOutputs {
+      version: "ffmpeg version 6.1 Copyright (c) 2000-2023\nbuilt with gcc\n".to_string(),
+      encoders: ENCODERS.to_string(),
+      decoders: String::new(),
+      filters: FILTERS.to_string(),
+      muxers: MUXERS.to_string(),
+      pix_fmts: PIX_FMTS.to_string(),
+      hwaccels: HWACCELS.to_string(),
+    }
+  }
+
+  #[test]
+  fn test_parse_canned_outputs() {
+    let capabilities = FfmpegCapabilities::from_outputs(&canned_outputs());
+
+    assert_eq!(
+      capabilities.version,
+      "ffmpeg version 6.1 Copyright (c) 2000-2023"
+    );
+    assert!(capabilities.has_encoder("h264_nvenc"));
+    assert!(capabilities.has_encoder("aac"));
+    assert!(!capabilities.has_encoder("="));
+    assert!(capabilities.has_filter("minterpolate"));
+    assert!(capabilities.has_filter("xfade"));
+    assert_eq!(
+      capabilities.muxers,
+      ["3g2", "matroska", "mp4", "webm"]
+        .iter()
+        .map(|name| name.to_string())
+        .collect::<BTreeSet<_>>()
+    );
+    assert!(!capabilities.has_muxer("mov"));
+    assert!(capabilities.has_pixel_format("yuv420p10le"));
+    assert!(!capabilities.has_pixel_format("Supported"));
+    assert_eq!(capabilities.pixel_formats.len(), 3);
+    assert_eq!(capabilities.hwaccels, ["vdpau", "cuda", "vaapi"]);
+  }
+
+  #[cfg(unix)]
+  fn write_script(path: &Path, body: &str) {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::write(path, format!("#!/bin/sh\n{body}\n")).unwrap();
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755)).unwrap();
+  }
+
+  #[cfg(unix)]
+  #[tokio::test]
+  async fn test_persisted_capabilities_are_reused_until_binary_changes() {
+    let temp_dir = TempDir::new().unwrap();
+    let ffmpeg = temp_dir.path().join("ffmpeg");
+    let ffmpeg_path = ffmpeg.to_string_lossy().to_string();
+    let cache_dir = temp_dir.path().join("cache");
+    write_script(
+      &ffmpeg,
+      r#"case "$1" in
+  -encoders) echo " V....D h264_nvenc  NVENC" ;;
+  -hwaccels) printf 'Hardware acceleration methods:\ncuda\n' ;;
+  *) echo "ffmpeg version 6.1" ;;
+esac"#,
+    );
+
+    let first = FfmpegCapabilitiesCache::new(&ffmpeg_path, Some(cache_dir.clone()));
+    let capabilities = first.get().await.unwrap();
+    assert!(capabilities.has_encoder("h264_nvenc"));
+    assert_eq!(capabilities.hwaccels, ["cuda"]);
+
+    // Новый запуск: бинарник с тем же отпечатком не запускается
+    let persisted = std::fs::read_to_string(first.persist_file().unwrap()).unwrap();
+    let failing = temp_dir.path().join("failing");
+    write_script(&failing, "exit 1");
+    let mut persisted: PersistedCapabilities = serde_json::from_str(&persisted).unwrap();
+    persisted.fingerprint = BinaryFingerprint::of(&failing.to_string_lossy()).unwrap();
+    let second = FfmpegCapabilitiesCache::new(
+      failing.to_string_lossy().to_string(),
+      Some(cache_dir.clone()),
+    );
+    std::fs::create_dir_all(second.persist_file().unwrap().parent().unwrap()).unwrap();
+    std::fs::write(
+      second.persist_file().unwrap(),
+      serde_json::to_string(&persisted).unwrap(),
+    )
+    .unwrap();
+    assert!(second.get().await.unwrap().has_encoder("h264_nvenc"));
+
+    // Замена бинарника меняет размер и время изменения: возможности читаются заново
+    write_script(&ffmpeg, "echo \"ffmpeg version 7.0\"");
+    let capabilities = first.get().await.unwrap();
+    assert_eq!(capabilities.version, "ffmpeg version 7.0");
+    assert!(!capabilities.has_encoder("h264_nvenc"));
+
+    let relaunched = FfmpegCapabilitiesCache::new(&ffmpeg_path, Some(cache_dir));
+    assert_eq!(
+      relaunched.get().await.unwrap().version,
+      "ffmpeg version 7.0"
+    );
+  }
+
+  #[tokio::test]
+  async fn test_probe_errors_are_not_cached() {
+    let cache = FfmpegCapabilitiesCache::new("/nonexistent/ffmpeg", None);
+    assert!(cache.get().await.is_err());
+    assert!(cache.loaded.lock().await.is_none());
+  }
+}
@@ -1,13 +1,22 @@
 //! FFmpeg сервис для работы с видео
 
 use crate::video_compiler::{
+  cache::{MediaMetadata, METADATA_SCHEMA_VERSION},
   error::{Result, VideoCompilerError},
-  services::Service,
+  ffmpeg_builder::FilterCapabilities,
+  services::{
+    ffmpeg_capabilities::{FfmpegCapabilities, FfmpegCapabilitiesCache},
+    monitoring::{resolve_policy, with_policy},
+    Service,
+  },
 };
 use async_trait::async_trait;
+use serde_json::Value;
 use std::{
-  path::Path,
+  collections::HashMap,
+  path::{Path, PathBuf},
   process::{Command, Stdio},
+  sync::Arc,
 };
 
 /// Трейт для работы с FFmpeg
@@ -34,6 +43,31 @@ pub trait FfmpegService: Service + Send + Sync {
 
   /// Запуск FFmpeg команды
   async fn run_command(&self, args: Vec<String>) -> Result<String>;
+
+  /// Запуск FFmpeg команды с прогрессом (0-100) по выводу `-progress`.
+  /// `duration` - длительность результата в секундах.
+  async fn run_command_with_progress(
+    &self,
+    args: Vec<String>,
+    duration: f64,
+    on_progress: &(dyn Fn(f64) + Send + Sync),
+  ) -> Result<()> {
+    let _ = duration;
+    self.run_command(args).await?;
+    on_progress(100.0);
+    Ok(())
+  }
+
+  /// Возможности сборки FFmpeg, читаются один раз и кэшируются на диске
+  async fn capabilities(&self) -> Result<Arc<FfmpegCapabilities>>;
+
+  /// Список фильтров FFmpeg, читается один раз и кэшируется
+  async fn filter_capabilities(&self) -> Result<FilterCapabilities>;
+
+  /// Доступен ли фильтр в сборке FFmpeg
+  async fn has_filter(&self, name: &str) -> Result<bool> {
+    Ok(self.filter_capabilities().await?.has_filter(name))
+  }
 }
 
 /// Информация о медиа файле
@@ -49,16 +83,195 @@ pub struct FileInfo {
   pub has_audio: bool,
   pub audio_codec: Option<String>,
   pub audio_bitrate: Option<u64>,
+  /// Цветовые характеристики видео потока
+  #[serde(default)]
+  pub color: ColorMetadata,
+  /// Поворот видео по часовой стрелке в градусах (0, 90, 180, 270)
+  #[serde(default)]
+  pub rotation: i32,
+  /// Все потоки файла
+  #[serde(default)]
+  pub streams: Vec<StreamInfo>,
+  /// Теги контейнера
+  #[serde(default)]
+  pub tags: HashMap<String, String>,
+}
+
+impl FileInfo {
+  /// Аудио потоки в порядке индексов `0:a:<idx>`
+  pub fn audio_streams(&self) -> impl Iterator<Item = &StreamInfo> {
+    self
+      .streams
+      .iter()
+      .filter(|stream| stream.codec_type == "audio")
+  }
+
+  /// Разрешение с учетом поворота
+  pub fn display_resolution(&self) -> (u32, u32) {
+    if self.rotation % 180 == 0 {
+      (self.width, self.height)
+    } else {
+      (self.height, self.width)
+    }
+  }
+}
+
+/// Информация о потоке медиа файла
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct StreamInfo {
+  /// Индекс потока в контейнере
+  pub index: u32,
+  /// Тип потока: video, audio, subtitle, data
+  pub codec_type: String,
+  pub codec_name: Option<String>,
+  /// Язык из тегов потока
+  pub language: Option<String>,
+  pub channel_layout: Option<String>,
+  pub channels: Option<u32>,
+  pub sample_rate: Option<u32>,
+  /// Битовая глубина отсчета
+  pub bit_depth: Option<u32>,
+  pub pixel_format: Option<String>,
+  /// Поворот по часовой стрелке в градусах
+  pub rotation: i32,
+}
+
+/// Цветовые характеристики видео потока
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ColorMetadata {
+  pub pixel_format: Option<String>,
+  pub color_range: Option<String>,
+  pub color_primaries: Option<String>,
+  pub transfer_characteristics: Option<String>,
+  pub matrix_coefficients: Option<String>,
+  /// Поток в HDR (PQ или HLG), UI предупреждает о смешивании с SDR клипами
+  pub is_hdr: bool,
 }
 
 /// Реализация FFmpeg сервиса
 pub struct FfmpegServiceImpl {
   ffmpeg_path: String,
+  capabilities: Arc<FfmpegCapabilitiesCache>,
 }
 
 impl FfmpegServiceImpl {
   pub fn new(ffmpeg_path: String) -> Self {
-    Self { ffmpeg_path }
+    Self {
+      capabilities: FfmpegCapabilitiesCache::shared(&ffmpeg_path),
+      ffmpeg_path,
+    }
+  }
+
+  /// Одна попытка пробинга файла. Процесс убивается, если попытку
+  /// прервал тайм-аут политики.
+  ///
+  /// Основной путь - JSON вывод ffprobe. Если ffprobe недоступен, разбирается
+  /// вывод `ffmpeg -i`, в котором нет списка потоков и тегов.
+  async fn probe_file_info(&self, path: &Path) -> Result<FileInfo> {
+    match self.probe_with_ffprobe(path).await {
+      Ok(info) => Ok(info),
+      Err(e) => {
+        log::debug!("ffprobe недоступен, используется вывод FFmpeg: {e}");
+        self.probe_with_ffmpeg(path).await
+      }
+    }
+  }
+
+  /// Пробинг через `ffprobe -show_streams -show_format` с JSON выводом
+  async fn probe_with_ffprobe(&self, path: &Path) -> Result<FileInfo> {
+    let ffprobe = ffprobe_path(&self.ffmpeg_path);
+    let output = tokio::process::Command::new(&ffprobe)
+      .args([
+        "-v",
+        "quiet",
+        "-print_format",
+        "json",
+        "-show_format",
+        "-show_streams",
+      ])
+      .arg(path)
+      .stdout(Stdio::piped())
+      .stderr(Stdio::piped())
+      .kill_on_drop(true)
+      .output()
+      .await
+      .map_err(|e| VideoCompilerError::FFmpegError {
+        exit_code: None,
+        stderr: format!("Ошибка запуска ffprobe: {e}"),
+        command: "ffprobe".to_string(),
+      })?;
+
+    if !output.status.success() {
+      return Err(VideoCompilerError::FFmpegError {
+        exit_code: output.status.code(),
+        stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        command: "ffprobe".to_string(),
+      });
+    }
+
+    let json: Value = serde_json::from_slice(&output.stdout)
+      .map_err(|e| VideoCompilerError::validation(format!("Неверный вывод ffprobe: {e}")))?;
+    parse_probe_json(&json)
+  }
+
+  /// Пробинг через разбор stderr `ffmpeg -i`
+  async fn probe_with_ffmpeg(&self, path: &Path) -> Result<FileInfo> {
+    let output = tokio::process::Command::new(&self.ffmpeg_path)
+      .args([
+        "-i",
+        path
+          .to_str()
+          .ok_or_else(|| VideoCompilerError::ValidationError("Неверный путь к файлу".into()))?,
+        "-f",
+        "null",
+        "-",
+      ])
+      .stdout(Stdio::piped())
+      .stderr(Stdio::piped())
+      .kill_on_drop(true)
+      .output()
+      .await
+      .map_err(|e| VideoCompilerError::FFmpegError {
+        exit_code: None,
+        stderr: format!("Ошибка запуска FFmpeg: {e}"),
+        command: "ffprobe".to_string(),
+      })?;
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    // Парсим информацию из вывода FFmpeg
+    let duration = parse_duration(&stderr)?;
+    let (width, height) = parse_resolution(&stderr)?;
+    let fps = parse_fps(&stderr)?;
+    let codec = parse_video_codec(&stderr)?;
+    let bitrate = parse_bitrate(&stderr)?;
+    let has_audio = stderr.contains("Audio:");
+    let audio_codec = if has_audio {
+      parse_audio_codec(&stderr)
+    } else {
+      None
+    };
+    let audio_bitrate = if has_audio {
+      parse_audio_bitrate(&stderr)
+    } else {
+      None
+    };
+
+    Ok(FileInfo {
+      duration,
+      width,
+      height,
+      fps,
+      codec,
+      bitrate,
+      has_audio,
+      audio_codec,
+      audio_bitrate,
+      color: parse_color_metadata(&stderr),
+      rotation: 0,
+      streams: Vec::new(),
+      tags: HashMap::new(),
+    })
   }
 }
 
@@ -68,7 +281,7 @@ impl Service for FfmpegServiceImpl {
     // Проверяем доступность FFmpeg
     if !self.is_available().await? {
       return Err(VideoCompilerError::DependencyMissing(
-        "FFmpeg не найден".to_string(),
+        "FFmpeg не найден".into(),
       ));
     }
 
@@ -79,7 +292,7 @@ impl Service for FfmpegServiceImpl {
   async fn health_check(&self) -> Result<()> {
     if !self.is_available().await? {
       return Err(VideoCompilerError::DependencyMissing(
-        "FFmpeg не найден".to_string(),
+        "FFmpeg не найден".into(),
       ));
     }
     Ok(())
@@ -139,56 +352,13 @@ impl FfmpegService for FfmpegServiceImpl {
   }
 
   async fn get_file_info(&self, path: &Path) -> Result<FileInfo> {
-    let output = Command::new(&self.ffmpeg_path)
-      .args([
-        "-i",
-        path.to_str().ok_or_else(|| {
-          VideoCompilerError::ValidationError("Неверный путь к файлу".to_string())
-        })?,
-        "-f",
-        "null",
-        "-",
-      ])
-      .stdout(Stdio::piped())
-      .stderr(Stdio::piped())
-      .output()
-      .map_err(|e| VideoCompilerError::FFmpegError {
-        exit_code: None,
-        stderr: format!("Ошибка запуска FFmpeg: {e}"),
-        command: "ffprobe".to_string(),
-      })?;
-
-    let stderr = String::from_utf8_lossy(&output.stderr);
-
-    // Парсим информацию из вывода FFmpeg
-    let duration = parse_duration(&stderr)?;
-    let (width, height) = parse_resolution(&stderr)?;
-    let fps = parse_fps(&stderr)?;
-    let codec = parse_video_codec(&stderr)?;
-    let bitrate = parse_bitrate(&stderr)?;
-    let has_audio = stderr.contains("Audio:");
-    let audio_codec = if has_audio {
-      parse_audio_codec(&stderr)
-    } else {
-      None
-    };
-    let audio_bitrate = if has_audio {
-      parse_audio_bitrate(&stderr)
-    } else {
-      None
-    };
-
-    Ok(FileInfo {
-      duration,
-      width,
-      height,
-      fps,
-      codec,
-      bitrate,
-      has_audio,
-      audio_codec,
-      audio_bitrate,
-    })
+    with_policy(
+      "ffmpeg-service",
+      "get_file_info",
+      resolve_policy("ffmpeg-service", "get_file_info"),
+      move || self.probe_file_info(path),
+    )
+    .await
   }
 
   async fn get_supported_formats(&self) -> Result<Vec<String>> {
@@ -288,6 +458,274 @@ impl FfmpegService for FfmpegServiceImpl {
 
     Ok(String::from_utf8_lossy(&output.stdout).to_string())
   }
+
+  async fn run_command_with_progress(
+    &self,
+    args: Vec<String>,
+    duration: f64,
+    on_progress: &(dyn Fn(f64) + Send + Sync),
+  ) -> Result<()> {
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+
+    let command_line = args.join(" ");
+    let mut child = tokio::process::Command::new(&self.ffmpeg_path)
+      .args(["-progress", "pipe:1", "-nostats"])
+      .args(&args)
+      .stdout(Stdio::piped())
+      .stderr(Stdio::piped())
+      .kill_on_drop(true)
+      .spawn()
+      .map_err(|e| VideoCompilerError::FFmpegError {
+        exit_code: None,
+        stderr: format!("Ошибка выполнения команды: {e}"),
+        command: command_line.clone(),
+      })?;
+
+    // stderr читается параллельно, чтобы FFmpeg не заблокировался на полном буфере
+    let mut stderr = child.stderr.take();
+    let stderr_task = tokio::spawn(async move {
+      let mut text = String::new();
+      if let Some(stderr) = stderr.as_mut() {
+        let _ = stderr.read_to_string(&mut text).await;
+      }
+      text
+    });
+
+    if let Some(stdout) = child.stdout.take() {
+      let mut lines = BufReader::new(stdout).lines();
+      while let Ok(Some(line)) = lines.next_line().await {
+        if let Some(seconds) = parse_progress_seconds(&line) {
+          if duration > 0.0 {
+            on_progress((seconds / duration * 100.0).clamp(0.0, 99.9));
+          }
+        }
+      }
+    }
+
+    let status = child
+      .wait()
+      .await
+      .map_err(|e| VideoCompilerError::IoError(e.to_string()))?;
+    let stderr = stderr_task.await.unwrap_or_default();
+    if !status.success() {
+      return Err(VideoCompilerError::FFmpegError {
+        exit_code: status.code(),
+        stderr,
+        command: command_line,
+      });
+    }
+
+    on_progress(100.0);
+    Ok(())
+  }
+
+  async fn capabilities(&self) -> Result<Arc<FfmpegCapabilities>> {
+    self.capabilities.get().await
+  }
+
+  async fn filter_capabilities(&self) -> Result<FilterCapabilities> {
+    Ok(FilterCapabilities::from_filters(
+      self.capabilities().await?.filters.iter().cloned(),
+    ))
+  }
+}
+
+/// Запись кэша метаданных по результату пробинга файла
+pub fn media_metadata_from_info(file_path: &str, info: Option<&FileInfo>) -> MediaMetadata {
+  let file_meta = std::fs::metadata(file_path).ok();
+  MediaMetadata {
+    file_path: file_path.to_string(),
+    file_size: file_meta.as_ref().map(|meta| meta.len()).unwrap_or(0),
+    modified_time: file_meta
+      .and_then(|meta| meta.modified().ok())
+      .unwrap_or_else(std::time::SystemTime::now),
+    duration: info.map(|info| info.duration).unwrap_or(0.0),
+    resolution: info
+      .filter(|info| info.width > 0)
+      .map(|info| (info.width, info.height)),
+    fps: info.map(|info| info.fps as f32),
+    bitrate: info.map(|info| (info.bitrate * 1000) as u32),
+    video_codec: info
+      .map(|info| info.codec.clone())
+      .filter(|codec| !codec.is_empty()),
+    audio_codec: info.and_then(|info| info.audio_codec.clone()),
+    rotation: info.map(|info| info.rotation).unwrap_or(0),
+    schema_version: METADATA_SCHEMA_VERSION,
+    cached_at: std::time::SystemTime::now(),
+  }
+}
+
+/// Путь к ffprobe, лежащему рядом с FFmpeg
+pub fn ffprobe_path(ffmpeg_path: &str) -> PathBuf {
+  let path = Path::new(ffmpeg_path);
+  match path.file_name().and_then(|name| name.to_str()) {
+    Some(name) if name.contains("ffmpeg") => path.with_file_name(name.replace("ffmpeg", "ffprobe")),
+    _ => path.to_path_buf(),
+  }
+}
+
+/// Разобрать JSON вывод `ffprobe -show_streams -show_format`
+pub fn parse_probe_json(json: &Value) -> Result<FileInfo> {
+  let streams: Vec<&Value> = json["streams"]
+    .as_array()
+    .map(|streams| streams.iter().collect())
+    .unwrap_or_default();
+  let format = &json["format"];
+  if streams.is_empty() && format.is_null() {
+    return Err(VideoCompilerError::validation(
+      "ffprobe не вернул потоков и формата",
+    ));
+  }
+
+  let video = streams
+    .iter()
+    .find(|stream| stream["codec_type"] == "video");
+  let audio = streams
+    .iter()
+    .find(|stream| stream["codec_type"] == "audio");
+
+  let duration = number_field(format, "duration")
+    .or_else(|| video.and_then(|stream| number_field(stream, "duration")))
+    .unwrap_or(0.0);
+  let fps = video
+    .and_then(|stream| stream["avg_frame_rate"].as_str().and_then(parse_rational))
+    .or_else(|| video.and_then(|stream| stream["r_frame_rate"].as_str().and_then(parse_rational)))
+    .unwrap_or(30.0);
+
+  let mut color = ColorMetadata::default();
+  if let Some(video) = video {
+    color.pixel_format = string_field(video, "pix_fmt");
+    color.color_range = string_field(video, "color_range");
+    color.color_primaries = string_field(video, "color_primaries");
+    color.transfer_characteristics = string_field(video, "color_transfer");
+    color.matrix_coefficients = string_field(video, "color_space");
+    color.is_hdr = matches!(
+      color.transfer_characteristics.as_deref(),
+      Some("smpte2084" | "arib-std-b67")
+    );
+  }
+
+  Ok(FileInfo {
+    duration,
+    width: video
+      .and_then(|stream| stream["width"].as_u64())
+      .unwrap_or(0) as u32,
+    height: video
+      .and_then(|stream| stream["height"].as_u64())
+      .unwrap_or(0) as u32,
+    fps,
+    codec: video
+      .and_then(|stream| string_field(stream, "codec_name"))
+      .unwrap_or_default(),
+    // Битрейты в kb/s, как в выводе FFmpeg
+    bitrate: number_field(format, "bit_rate").unwrap_or(0.0) as u64 / 1000,
+    has_audio: audio.is_some(),
+    audio_codec: audio.and_then(|stream| string_field(stream, "codec_name")),
+    audio_bitrate: audio
+      .and_then(|stream| number_field(stream, "bit_rate"))
+      .map(|bit_rate| bit_rate as u64 / 1000),
+    color,
+    rotation: video.map(|stream| stream_rotation(stream)).unwrap_or(0),
+    streams: streams
+      .iter()
+      .map(|stream| parse_stream_info(stream))
+      .collect(),
+    tags: string_map(&format["tags"]),
+  })
+}
+
+/// Разобрать описание одного потока ffprobe
+pub fn parse_stream_info(stream: &Value) -> StreamInfo {
+  let bit_depth = number_field(stream, "bits_per_raw_sample")
+    .or_else(|| number_field(stream, "bits_per_sample"))
+    .filter(|bits| *bits > 0.0)
+    .map(|bits| bits as u32);
+
+  StreamInfo {
+    index: stream["index"].as_u64().unwrap_or(0) as u32,
+    codec_type: string_field(stream, "codec_type").unwrap_or_else(|| "unknown".to_string()),
+    codec_name: string_field(stream, "codec_name"),
+    language: string_field(&stream["tags"], "language"),
+    channel_layout: string_field(stream, "channel_layout"),
+    channels: stream["channels"].as_u64().map(|channels| channels as u32),
+    sample_rate: number_field(stream, "sample_rate").map(|rate| rate as u32),
+    bit_depth,
+    pixel_format: string_field(stream, "pix_fmt"),
+    rotation: stream_rotation(stream),
+  }
+}
+
+/// Поворот потока по часовой стрелке, кратный 90 градусам.
+///
+/// Display Matrix хранит поворот против часовой стрелки (-90 у вертикального
+/// видео с телефона), старый тег `rotate` - по часовой стрелке.
+pub fn stream_rotation(stream: &Value) -> i32 {
+  let display_matrix = stream["side_data_list"]
+    .as_array()
+    .into_iter()
+    .flatten()
+    .find_map(|side_data| side_data["rotation"].as_f64())
+    .map(|rotation| -rotation);
+  let tag = || number_field(&stream["tags"], "rotate");
+
+  display_matrix
+    .or_else(tag)
+    .map(normalize_rotation)
+    .unwrap_or(0)
+}
+
+/// Привести угол к 0, 90, 180 или 270
+fn normalize_rotation(degrees: f64) -> i32 {
+  ((degrees / 90.0).round() as i32 * 90).rem_euclid(360)
+}
+
+/// Числовое поле, которое ffprobe может вернуть строкой
+fn number_field(value: &Value, key: &str) -> Option<f64> {
+  match &value[key] {
+    Value::Number(number) => number.as_f64(),
+    Value::String(text) => text.parse().ok(),
+    _ => None,
+  }
+}
+
+fn string_field(value: &Value, key: &str) -> Option<String> {
+  value[key]
+    .as_str()
+    .filter(|text| !text.is_empty() && *text != "unknown")
+    .map(String::from)
+}
+
+fn string_map(value: &Value) -> HashMap<String, String> {
+  value
+    .as_object()
+    .map(|tags| {
+      tags
+        .iter()
+        .filter_map(|(key, value)| value.as_str().map(|text| (key.clone(), text.to_string())))
+        .collect()
+    })
+    .unwrap_or_default()
+}
+
+/// Частота кадров вида `30000/1001`
+pub(crate) fn parse_rational(text: &str) -> Option<f64> {
+  let (num, den) = text.split_once('/')?;
+  let (num, den): (f64, f64) = (num.parse().ok()?, den.parse().ok()?);
+  (num > 0.0 && den > 0.0).then_some(num / den)
+}
+
+/// Обработанное время в секундах из строки вывода `-progress`.
+///
+/// `out_time_ms` у FFmpeg, несмотря на имя, тоже в микросекундах.
+pub(crate) fn parse_progress_seconds(line: &str) -> Option<f64> {
+  let micros = line
+    .strip_prefix("out_time_us=")
+    .or_else(|| line.strip_prefix("out_time_ms="))?;
+  micros
+    .trim()
+    .parse::<i64>()
+    .ok()
+    .map(|micros| micros.max(0) as f64 / 1_000_000.0)
 }
 
 // Вспомогательные функции для парсинга вывода FFmpeg
@@ -303,7 +741,7 @@ fn parse_duration(stderr: &str) -> Result<f64> {
     }
   }
   Err(VideoCompilerError::ValidationError(
-    "Не удалось найти длительность".to_string(),
+    "Не удалось найти длительность".into(),
   ))
 }
 
@@ -327,7 +765,7 @@ fn parse_resolution(stderr: &str) -> Result<(u32, u32)> {
     }
   }
   Err(VideoCompilerError::ValidationError(
-    "Не удалось найти разрешение".to_string(),
+    "Не удалось найти разрешение".into(),
   ))
 }
 
@@ -402,23 +840,91 @@ fn parse_audio_bitrate(stderr: &str) -> Option<u64> {
   None
 }
 
+/// Цветовые характеристики из описания видео потока, например
+/// `yuv420p10le(tv, bt2020nc/bt2020/arib-std-b67)` или `yuv420p(tv, bt709)`
+fn parse_color_metadata(stderr: &str) -> ColorMetadata {
+  let mut color = ColorMetadata::default();
+  let Some(stream) = stderr.lines().find_map(|line| line.split("Video: ").nth(1)) else {
+    return color;
+  };
+
+  // Второй элемент верхнего уровня (запятые внутри скобок не разделяют) - пиксельный формат
+  let Some(pixel_part) = split_top_level(stream).into_iter().nth(1) else {
+    return color;
+  };
+  let (format, details) = match pixel_part.split_once('(') {
+    Some((format, rest)) => (format, rest.trim_end_matches(')')),
+    None => (pixel_part, ""),
+  };
+  color.pixel_format = Some(format.trim().to_string());
+
+  for item in details.split(',').map(str::trim) {
+    match item {
+      "tv" | "pc" => color.color_range = Some(item.to_string()),
+      "progressive"
+      | "top first"
+      | "bottom first"
+      | "top coded first (swapped)"
+      | "bottom coded first (swapped)"
+      | "" => {}
+      _ => {
+        // FFmpeg печатает colorspace/primaries/trc или одно значение, если они совпадают
+        let parts: Vec<&str> = item.split('/').collect();
+        let (matrix, primaries, transfer) = match parts.as_slice() {
+          [matrix, primaries, transfer] => (*matrix, *primaries, *transfer),
+          _ => (item, item, item),
+        };
+        color.matrix_coefficients = Some(matrix.to_string());
+        color.color_primaries = Some(primaries.to_string());
+        color.transfer_characteristics = Some(transfer.to_string());
+      }
+    }
+  }
+
+  color.is_hdr = matches!(
+    color.transfer_characteristics.as_deref(),
+    Some("smpte2084" | "arib-std-b67")
+  );
+  color
+}
+
+/// Разбить описание потока по запятым вне скобок
+fn split_top_level(stream: &str) -> Vec<&str> {
+  let mut parts = Vec::new();
+  let mut depth = 0usize;
+  let mut start = 0;
+  for (i, ch) in stream.char_indices() {
+    match ch {
+      '(' | '[' => depth += 1,
+      ')' | ']' => depth = depth.saturating_sub(1),
+      ',' if depth == 0 => {
+        parts.push(stream[start..i].trim());
+        start = i + 1;
+      }
+      _ => {}
+    }
+  }
+  parts.push(stream[start..].trim());
+  parts
+}
+
 fn parse_time_to_seconds(time_str: &str) -> Result<f64> {
   let parts: Vec<&str> = time_str.split(':').collect();
   if parts.len() != 3 {
     return Err(VideoCompilerError::ValidationError(
-      "Неверный формат времени".to_string(),
+      "Неверный формат времени".into(),
     ));
   }
 
   let hours: f64 = parts[0]
     .parse()
-    .map_err(|_| VideoCompilerError::ValidationError("Неверные часы".to_string()))?;
+    .map_err(|_| VideoCompilerError::ValidationError("Неверные часы".into()))?;
   let minutes: f64 = parts[1]
     .parse()
-    .map_err(|_| VideoCompilerError::ValidationError("Неверные минуты".to_string()))?;
+    .map_err(|_| VideoCompilerError::ValidationError("Неверные минуты".into()))?;
   let seconds: f64 = parts[2]
     .parse()
-    .map_err(|_| VideoCompilerError::ValidationError("Неверные секунды".to_string()))?;
+    .map_err(|_| VideoCompilerError::ValidationError("Неверные секунды".into()))?;
 
   Ok(hours * 3600.0 + minutes * 60.0 + seconds)
 }
@@ -516,6 +1022,18 @@ mod tests {
 
   // Тесты для парсинга функций
 
+  #[test]
+  fn test_parse_progress_seconds() {
+    assert_eq!(parse_progress_seconds("out_time_us=2500000"), Some(2.5));
+    assert_eq!(parse_progress_seconds("out_time_ms=1000000"), Some(1.0));
+    assert_eq!(
+      parse_progress_seconds("out_time_us=-9223372036854775807"),
+      Some(0.0)
+    );
+    assert_eq!(parse_progress_seconds("out_time=00:00:01.000000"), None);
+    assert_eq!(parse_progress_seconds("progress=continue"), None);
+  }
+
   #[test]
   fn test_parse_time_to_seconds() {
     assert_eq!(parse_time_to_seconds("00:01:30.00").unwrap(), 90.0);
@@ -684,6 +1202,37 @@ Stream #0:1(und): Audio: aac, 48000 Hz, stereo, fltp, 192 kb/s
     assert!(codecs.is_empty()); // echo не возвращает кодеки
   }
 
+  #[test]
+  fn test_parse_color_metadata_hdr() {
+    let line = "    Stream #0:0: Video: hevc (Main 10) (hvc1 / 0x31637668), \
+yuv420p10le(tv, bt2020nc/bt2020/arib-std-b67), 3840x2160, 45000 kb/s, 29.97 fps";
+
+    let color = parse_color_metadata(line);
+    assert_eq!(color.pixel_format.as_deref(), Some("yuv420p10le"));
+    assert_eq!(color.color_range.as_deref(), Some("tv"));
+    assert_eq!(color.matrix_coefficients.as_deref(), Some("bt2020nc"));
+    assert_eq!(color.color_primaries.as_deref(), Some("bt2020"));
+    assert_eq!(
+      color.transfer_characteristics.as_deref(),
+      Some("arib-std-b67")
+    );
+    assert!(color.is_hdr);
+  }
+
+  #[test]
+  fn test_parse_color_metadata_sdr() {
+    let line = "Stream #0:0: Video: h264 (High), yuv420p(tv, bt709, progressive), 1920x1080";
+    let color = parse_color_metadata(line);
+    assert_eq!(color.color_primaries.as_deref(), Some("bt709"));
+    assert_eq!(color.transfer_characteristics.as_deref(), Some("bt709"));
+    assert!(!color.is_hdr);
+
+    // Без цветовой информации известен только пиксельный формат
+    let color = parse_color_metadata("Stream #0:0: Video: mpeg4, yuv420p, 640x480");
+    assert_eq!(color.pixel_format.as_deref(), Some("yuv420p"));
+    assert!(color.color_primaries.is_none());
+  }
+
   // Интеграционный тест для проверки полного парсинга
   #[test]
   fn test_full_ffmpeg_output_parsing() {
@@ -719,4 +1268,93 @@ Input #0, mov,mp4,m4a,3gp,3g2,mj2, from 'sample.mp4':
     );
     assert_eq!(parse_audio_bitrate(ffmpeg_output).unwrap(), 192);
   }
+
+  #[test]
+  fn test_parse_probe_json_streams_and_rotation() {
+    let json = serde_json::json!({
+      "streams": [
+        {
+          "index": 0,
+          "codec_name": "hevc",
+          "codec_type": "video",
+          "width": 1920,
+          "height": 1080,
+          "pix_fmt": "yuv420p10le",
+          "bits_per_raw_sample": "10",
+          "color_transfer": "arib-std-b67",
+          "avg_frame_rate": "30000/1001",
+          "side_data_list": [{ "side_data_type": "Display Matrix", "rotation": -90 }]
+        },
+        {
+          "index": 1,
+          "codec_name": "aac",
+          "codec_type": "audio",
+          "sample_rate": "48000",
+          "channels": 2,
+          "channel_layout": "stereo",
+          "bit_rate": "192000",
+          "tags": { "language": "eng" }
+        },
+        {
+          "index": 2,
+          "codec_name": "pcm_s24le",
+          "codec_type": "audio",
+          "sample_rate": "48000",
+          "channels": 6,
+          "channel_layout": "5.1",
+          "bits_per_sample": 24,
+          "tags": { "language": "rus" }
+        }
+      ],
+      "format": {
+        "duration": "12.500000",
+        "bit_rate": "25000000",
+        "tags": { "com.apple.quicktime.make": "Apple" }
+      }
+    });
+
+    let info = parse_probe_json(&json).unwrap();
+    assert_eq!(info.duration, 12.5);
+    assert!((info.fps - 29.97).abs() < 0.01);
+    assert_eq!(info.bitrate, 25000);
+    assert_eq!(info.audio_bitrate, Some(192));
+    assert_eq!(info.rotation, 90);
+    assert_eq!(info.display_resolution(), (1080, 1920));
+    assert_eq!(info.color.pixel_format.as_deref(), Some("yuv420p10le"));
+    assert!(info.color.is_hdr);
+    assert_eq!(info.tags["com.apple.quicktime.make"], "Apple");
+
+    assert_eq!(info.streams.len(), 3);
+    assert_eq!(info.streams[0].bit_depth, Some(10));
+    let audio: Vec<&StreamInfo> = info.audio_streams().collect();
+    assert_eq!(audio.len(), 2);
+    assert_eq!(audio[1].language.as_deref(), Some("rus"));
+    assert_eq!(audio[1].channel_layout.as_deref(), Some("5.1"));
+    assert_eq!(audio[1].sample_rate, Some(48000));
+    assert_eq!(audio[1].bit_depth, Some(24));
+  }
+
+  #[test]
+  fn test_stream_rotation_sources() {
+    // Старый тег rotate задан по часовой стрелке
+    let tagged = serde_json::json!({ "tags": { "rotate": "270" } });
+    assert_eq!(stream_rotation(&tagged), 270);
+
+    let flipped = serde_json::json!({ "side_data_list": [{ "rotation": 180 }] });
+    assert_eq!(stream_rotation(&flipped), 180);
+
+    let counter_clockwise = serde_json::json!({ "side_data_list": [{ "rotation": 90 }] });
+    assert_eq!(stream_rotation(&counter_clockwise), 270);
+
+    assert_eq!(stream_rotation(&serde_json::json!({})), 0);
+  }
+
+  #[test]
+  fn test_ffprobe_path() {
+    assert_eq!(
+      ffprobe_path("/opt/ffmpeg/bin/ffmpeg"),
+      PathBuf::from("/opt/ffmpeg/bin/ffprobe")
+    );
+    assert_eq!(ffprobe_path("ffmpeg.exe"), PathBuf::from("ffprobe.exe"));
+  }
 }
@@ -4,8 +4,12 @@ use crate::video_compiler::{
   core::{
     error::{Result, VideoCompilerError},
     gpu::{GpuDetector, GpuEncoder, GpuInfo},
+    gpu_memory::{self, GpuMemoryInfo},
+  },
+  services::{
+    monitoring::{resolve_policy, with_policy},
+    Service,
   },
-  services::Service,
 };
 use async_trait::async_trait;
 use std::sync::Arc;
@@ -50,6 +54,9 @@ pub trait GpuService: Service + Send + Sync {
 
   /// Обновить информацию о GPU
   async fn refresh_gpu_info(&self) -> Result<()>;
+
+  /// Получить занятость видеопамяти (`None`, если ее не удалось определить)
+  async fn get_gpu_memory_info(&self) -> Result<Option<GpuMemoryInfo>>;
 }
 
 /// Реализация сервиса GPU
@@ -57,6 +64,7 @@ pub struct GpuServiceImpl {
   ffmpeg_path: String,
   gpu_info_cache: Arc<RwLock<Option<Vec<GpuInfo>>>>,
   capabilities_cache: Arc<RwLock<Option<GpuCapabilities>>>,
+  detect_on_startup: bool,
 }
 
 impl GpuServiceImpl {
@@ -65,9 +73,17 @@ impl GpuServiceImpl {
       ffmpeg_path,
       gpu_info_cache: Arc::new(RwLock::new(None)),
       capabilities_cache: Arc::new(RwLock::new(None)),
+      detect_on_startup: true,
     }
   }
 
+  /// Не определять GPU при инициализации сервиса; обнаружение выполняется
+  /// только по явному запросу
+  pub fn without_startup_detection(mut self) -> Self {
+    self.detect_on_startup = false;
+    self
+  }
+
   /// Провести тестовое кодирование для бенчмарка
   async fn run_encoding_benchmark(&self, encoder: GpuEncoder) -> Result<(f64, f64)> {
     // Создаем временный тестовый файл
@@ -146,6 +162,10 @@ impl Service for GpuServiceImpl {
   async fn initialize(&self) -> Result<()> {
     log::info!("Инициализация сервиса GPU");
 
+    if !self.detect_on_startup {
+      return Ok(());
+    }
+
     // Сразу пытаемся обнаружить GPU
     self.refresh_gpu_info().await?;
 
@@ -194,7 +214,13 @@ impl GpuService for GpuServiceImpl {
 
     // Если кэш пуст, обнаруживаем GPU
     let detector = GpuDetector::new(self.ffmpeg_path.clone());
-    let gpu_info = detector.detect_gpus().await?;
+    let gpu_info = with_policy(
+      "gpu-service",
+      "detect_gpus",
+      resolve_policy("gpu-service", "detect_gpus"),
+      || detector.detect_gpus(),
+    )
+    .await?;
 
     // Сохраняем в кэш
     {
@@ -350,6 +376,11 @@ impl GpuService for GpuServiceImpl {
 
     Ok(())
   }
+
+  async fn get_gpu_memory_info(&self) -> Result<Option<GpuMemoryInfo>> {
+    // Занятость памяти меняется во время рендеринга, поэтому не кэшируется
+    Ok(gpu_memory::query_gpu_memory().await)
+  }
 }
 
 #[cfg(test)]
@@ -620,6 +651,15 @@ mod tests {
       }
       Ok(())
     }
+
+    async fn get_gpu_memory_info(&self) -> Result<Option<GpuMemoryInfo>> {
+      Ok(self.gpu_info.first().and_then(|gpu| {
+        Some(GpuMemoryInfo {
+          total_bytes: gpu.memory_total?,
+          used_bytes: gpu.memory_used.unwrap_or(0),
+        })
+      }))
+    }
   }
 
   // Базовые тесты
@@ -630,6 +670,15 @@ mod tests {
     assert_eq!(service.ffmpeg_path, "ffmpeg");
   }
 
+  #[tokio::test]
+  async fn test_initialize_without_startup_detection() {
+    let service =
+      GpuServiceImpl::new("/nonexistent/ffmpeg".to_string()).without_startup_detection();
+    // В безопасном режиме FFmpeg не запускается при инициализации
+    assert!(service.initialize().await.is_ok());
+    assert!(service.gpu_info_cache.read().await.is_none());
+  }
+
   #[tokio::test]
   async fn test_encoder_detection() {
     let service = GpuServiceImpl::new("ffmpeg".to_string());
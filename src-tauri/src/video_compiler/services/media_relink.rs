@@ -0,0 +1,623 @@
+//! Перепривязка медиафайлов проекта, перемещенных или переименованных на диске
+
+use crate::media::fingerprint::{compute_fingerprint_blocking, MediaFingerprint};
+use crate::video_compiler::{
+  core::error::{Result, VideoCompilerError},
+  schema::{ClipSource, ProjectSchema, Track},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Максимальная глубина обхода директорий поиска
+pub const RELINK_SEARCH_MAX_DEPTH: usize = 5;
+
+/// Максимальное количество кандидатов на один отсутствующий файл
+pub const MAX_RELINK_CANDIDATES: usize = 5;
+
+/// Получатель событий прогресса поиска
+pub type RelinkProgressSink = Arc<dyn Fn(RelinkScanProgress) + Send + Sync>;
+
+/// Отсутствующий медиафайл и клипы, которые на него ссылаются
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MissingMediaFile {
+  /// Исходный путь из проекта
+  pub path: String,
+  /// Имя файла (без директории)
+  pub file_name: String,
+  /// ID клипов, использующих файл
+  pub clip_ids: Vec<String>,
+}
+
+/// Отсутствующие файлы одной исходной директории
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MissingMediaGroup {
+  /// Исходная директория
+  pub directory: String,
+  /// Файлы (отсортированы по пути)
+  pub files: Vec<MissingMediaFile>,
+}
+
+/// Найденный кандидат на замену отсутствующего файла
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelinkCandidate {
+  /// Путь к найденному файлу
+  pub path: String,
+  /// Совпадает имя файла (без учета регистра)
+  pub name_match: bool,
+  /// Совпадает размер (`None`, если исходный размер неизвестен)
+  pub size_match: Option<bool>,
+  /// Совпадает отпечаток содержимого (`None`, если файл не был в реестре)
+  pub fingerprint_match: Option<bool>,
+  /// Оценка от 0 до 1, кандидаты отсортированы по убыванию
+  pub score: f32,
+}
+
+/// Кандидаты для одного отсутствующего файла
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelinkSuggestion {
+  /// Исходный путь из проекта
+  pub missing_path: String,
+  pub candidates: Vec<RelinkCandidate>,
+}
+
+/// Прогресс поиска кандидатов
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelinkScanProgress {
+  /// Просмотрено директорий
+  pub scanned_dirs: usize,
+  /// Просмотрено файлов
+  pub scanned_files: usize,
+  /// Найдено кандидатов
+  pub candidates_found: usize,
+  /// Текущая директория
+  pub current_dir: String,
+}
+
+/// Результат перепривязки одного файла
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelinkedMedia {
+  pub old_path: String,
+  pub new_path: String,
+  /// Количество обновленных ссылок (источники клипов и внешнее аудио)
+  pub references_updated: usize,
+}
+
+/// Все треки проекта, включая треки вложенных последовательностей
+pub(crate) fn all_tracks(project: &ProjectSchema) -> impl Iterator<Item = &Track> {
+  project.tracks.iter().chain(
+    project
+      .sequences
+      .values()
+      .flat_map(|sequence| sequence.tracks.iter()),
+  )
+}
+
+/// Все треки проекта для изменения
+pub(crate) fn all_tracks_mut(project: &mut ProjectSchema) -> impl Iterator<Item = &mut Track> {
+  project.tracks.iter_mut().chain(
+    project
+      .sequences
+      .values_mut()
+      .flat_map(|sequence| sequence.tracks.iter_mut()),
+  )
+}
+
+/// Найти отсутствующие медиафайлы проекта, сгруппированные по директории
+pub fn find_missing_media(project: &ProjectSchema) -> Vec<MissingMediaGroup> {
+  let mut missing: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+  for track in all_tracks(project) {
+    for clip in &track.clips {
+      let paths = match &clip.source {
+        ClipSource::File(path) => Some(path),
+        _ => None,
+      }
+      .into_iter()
+      .chain(clip.external_audio.iter().map(|audio| &audio.path));
+
+      for path in paths {
+        if !Path::new(path).exists() {
+          let clip_ids = missing.entry(path.clone()).or_default();
+          if !clip_ids.contains(&clip.id) {
+            clip_ids.push(clip.id.clone());
+          }
+        }
+      }
+    }
+  }
+
+  let mut groups: BTreeMap<String, Vec<MissingMediaFile>> = BTreeMap::new();
+  for (path, clip_ids) in missing {
+    let file_path = Path::new(&path);
+    let directory = file_path
+      .parent()
+      .map(|parent| parent.to_string_lossy().to_string())
+      .unwrap_or_default();
+    let file_name = file_path
+      .file_name()
+      .map(|name| name.to_string_lossy().to_string())
+      .unwrap_or_else(|| path.clone());
+
+    groups.entry(directory).or_default().push(MissingMediaFile {
+      path,
+      file_name,
+      clip_ids,
+    });
+  }
+
+  groups
+    .into_iter()
+    .map(|(directory, files)| MissingMediaGroup { directory, files })
+    .collect()
+}
+
+/// Оценка кандидата: совпадение отпечатка надежнее имени и размера
+fn candidate_score(
+  name_match: bool,
+  size_match: Option<bool>,
+  fingerprint_match: Option<bool>,
+) -> f32 {
+  match fingerprint_match {
+    Some(true) => 1.0,
+    Some(false) => 0.1,
+    None => {
+      let name: f32 = if name_match { 0.5 } else { 0.0 };
+      let size = match size_match {
+        Some(true) => 0.3,
+        Some(false) => -0.3,
+        None => 0.0,
+      };
+      (name + size).max(0.05)
+    }
+  }
+}
+
+/// Отсутствующий файл и то, что о нем известно из реестра
+struct MissingTarget<'a> {
+  path: &'a str,
+  file_name: String,
+  fingerprint: Option<&'a MediaFingerprint>,
+  candidates: Vec<RelinkCandidate>,
+}
+
+impl MissingTarget<'_> {
+  /// Проверить найденный файл. Файл с другим именем подходит только при
+  /// совпадении отпечатка (переименованный файл).
+  fn evaluate(&self, path: &Path, file_name: &str, size: u64) -> Option<RelinkCandidate> {
+    let name_match = file_name.to_lowercase() == self.file_name;
+    let known_size = self.fingerprint.map(|fingerprint| fingerprint.size);
+    let size_match = known_size.map(|known| known == size);
+
+    if !name_match && size_match != Some(true) {
+      return None;
+    }
+
+    let fingerprint_match = match (self.fingerprint, size_match) {
+      (Some(_), Some(false)) => Some(false),
+      (Some(known), _) => compute_fingerprint_blocking(path, known.mode)
+        .ok()
+        .map(|fingerprint| &fingerprint == known),
+      (None, _) => None,
+    };
+
+    if !name_match && fingerprint_match != Some(true) {
+      return None;
+    }
+
+    Some(RelinkCandidate {
+      path: path.to_string_lossy().to_string(),
+      name_match,
+      size_match,
+      fingerprint_match,
+      score: candidate_score(name_match, size_match, fingerprint_match),
+    })
+  }
+}
+
+/// Найти кандидатов для отсутствующих файлов проекта.
+///
+/// Директории обходятся не глубже `max_depth`, символические ссылки на
+/// директории не раскрываются. `known_media` - отпечатки из реестра медиа
+/// по исходному пути.
+pub fn suggest_relink_candidates(
+  project: &ProjectSchema,
+  search_dirs: &[PathBuf],
+  known_media: &HashMap<String, MediaFingerprint>,
+  max_depth: usize,
+  progress: Option<&RelinkProgressSink>,
+) -> Vec<RelinkSuggestion> {
+  let missing = find_missing_media(project);
+  let mut targets: Vec<MissingTarget> = missing
+    .iter()
+    .flat_map(|group| group.files.iter())
+    .map(|file| MissingTarget {
+      path: &file.path,
+      file_name: file.file_name.to_lowercase(),
+      fingerprint: known_media.get(&file.path),
+      candidates: Vec::new(),
+    })
+    .collect();
+
+  if targets.is_empty() {
+    return Vec::new();
+  }
+
+  let mut state = RelinkScanProgress {
+    scanned_dirs: 0,
+    scanned_files: 0,
+    candidates_found: 0,
+    current_dir: String::new(),
+  };
+  let mut visited = HashSet::new();
+  let mut seen_files = HashSet::new();
+  let mut stack: Vec<(PathBuf, usize)> = search_dirs
+    .iter()
+    .rev()
+    .map(|dir| (dir.clone(), 0))
+    .collect();
+
+  while let Some((dir, depth)) = stack.pop() {
+    let canonical = dir.canonicalize().unwrap_or_else(|_| dir.clone());
+    if !visited.insert(canonical) {
+      continue;
+    }
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+      log::debug!("Не удалось прочитать директорию {}", dir.display());
+      continue;
+    };
+
+    state.scanned_dirs += 1;
+    state.current_dir = dir.to_string_lossy().to_string();
+
+    let mut subdirs = Vec::new();
+    for entry in entries.flatten() {
+      let Ok(file_type) = entry.file_type() else {
+        continue;
+      };
+      let path = entry.path();
+      if file_type.is_dir() {
+        if depth < max_depth {
+          subdirs.push(path);
+        }
+        continue;
+      }
+      if !file_type.is_file() || !seen_files.insert(path.clone()) {
+        continue;
+      }
+
+      state.scanned_files += 1;
+      let Ok(metadata) = entry.metadata() else {
+        continue;
+      };
+      let file_name = entry.file_name().to_string_lossy().to_string();
+      for target in &mut targets {
+        if let Some(candidate) = target.evaluate(&path, &file_name, metadata.len()) {
+          target.candidates.push(candidate);
+          state.candidates_found += 1;
+        }
+      }
+    }
+
+    subdirs.sort();
+    stack.extend(subdirs.into_iter().rev().map(|subdir| (subdir, depth + 1)));
+
+    if let Some(progress) = progress {
+      progress(state.clone());
+    }
+  }
+
+  targets
+    .into_iter()
+    .map(|mut target| {
+      target.candidates.sort_by(|a, b| {
+        b.score
+          .total_cmp(&a.score)
+          .then_with(|| a.path.cmp(&b.path))
+      });
+      target.candidates.truncate(MAX_RELINK_CANDIDATES);
+      RelinkSuggestion {
+        missing_path: target.path.to_string(),
+        candidates: target.candidates,
+      }
+    })
+    .collect()
+}
+
+/// Переписать пути медиафайлов проекта по сопоставлению старый -> новый путь.
+///
+/// Сопоставление может быть частичным: остальные ссылки не меняются. Все
+/// новые пути проверяются до изменения проекта.
+pub fn apply_relink(
+  project: &mut ProjectSchema,
+  mapping: &HashMap<String, String>,
+) -> Result<Vec<RelinkedMedia>> {
+  if let Some(new_path) = mapping.values().find(|path| !Path::new(path).is_file()) {
+    return Err(VideoCompilerError::MediaFileError {
+      path: new_path.clone(),
+      reason: "Файл для перепривязки не найден".to_string(),
+    });
+  }
+
+  let mut updated: HashMap<&str, usize> = HashMap::new();
+  for track in all_tracks_mut(project) {
+    for clip in &mut track.clips {
+      let source = match &mut clip.source {
+        ClipSource::File(path) => Some(path),
+        _ => None,
+      };
+      let paths = source
+        .into_iter()
+        .chain(clip.external_audio.iter_mut().map(|audio| &mut audio.path));
+
+      for path in paths {
+        if let Some((old_path, new_path)) = mapping.get_key_value(path.as_str()) {
+          *path = new_path.clone();
+          *updated.entry(old_path.as_str()).or_default() += 1;
+        }
+      }
+    }
+  }
+
+  let mut relinked: Vec<RelinkedMedia> = updated
+    .into_iter()
+    .map(|(old_path, references_updated)| RelinkedMedia {
+      old_path: old_path.to_string(),
+      new_path: mapping[old_path].clone(),
+      references_updated,
+    })
+    .collect();
+  relinked.sort_by(|a, b| a.old_path.cmp(&b.old_path));
+
+  if !relinked.is_empty() {
+    project.touch();
+  }
+  Ok(relinked)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::media::fingerprint::FingerprintMode;
+  use crate::video_compiler::ffmpeg_builder::FFmpegBuilder;
+  use crate::video_compiler::schema::{Clip, TrackType};
+  use std::fs;
+  use std::sync::Mutex;
+
+  fn project_with_media(paths: &[&Path]) -> ProjectSchema {
+    let mut project = ProjectSchema::new("Relink".to_string());
+    let mut track = Track::new(TrackType::Video, "Video".to_string());
+    for (i, path) in paths.iter().enumerate() {
+      track
+        .clips
+        .push(Clip::new(path.to_path_buf(), i as f64 * 5.0, 5.0));
+    }
+    project.tracks.push(track);
+    project
+  }
+
+  fn clip_paths(project: &ProjectSchema) -> Vec<String> {
+    project.tracks[0]
+      .clips
+      .iter()
+      .filter_map(|clip| match &clip.source {
+        ClipSource::File(path) => Some(path.clone()),
+        _ => None,
+      })
+      .collect()
+  }
+
+  #[test]
+  fn test_find_missing_media_groups_by_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    let present = dir.path().join("present.mp4");
+    fs::write(&present, b"data").unwrap();
+    let old_a = dir.path().join("old_a").join("a.mp4");
+    let old_b = dir.path().join("old_b").join("b.mp4");
+
+    let project = project_with_media(&[&present, &old_a, &old_b, &old_a]);
+    let groups = find_missing_media(&project);
+
+    assert_eq!(groups.len(), 2);
+    assert_eq!(
+      groups[0].directory,
+      dir.path().join("old_a").to_string_lossy()
+    );
+    assert_eq!(groups[0].files[0].file_name, "a.mp4");
+    assert_eq!(groups[0].files[0].clip_ids.len(), 2);
+    assert_eq!(groups[1].files[0].path, old_b.to_string_lossy());
+  }
+
+  #[test]
+  fn test_moved_directory_is_relinked() {
+    let dir = tempfile::tempdir().unwrap();
+    let old_dir = dir.path().join("Footage");
+    let new_dir = dir.path().join("External").join("Archive").join("Footage");
+    fs::create_dir_all(&old_dir).unwrap();
+    let mut old_paths = Vec::new();
+    let mut fingerprints = HashMap::new();
+    for name in ["intro.mp4", "main.mp4"] {
+      let path = old_dir.join(name);
+      fs::write(&path, format!("content of {name}")).unwrap();
+      fingerprints.insert(
+        path.to_string_lossy().to_string(),
+        compute_fingerprint_blocking(&path, FingerprintMode::Fast).unwrap(),
+      );
+      old_paths.push(path);
+    }
+    let mut project = project_with_media(&[&old_paths[0], &old_paths[1]]);
+
+    // Перемещаем директорию; рядом лежит файл с тем же именем, но другим содержимым
+    fs::create_dir_all(new_dir.parent().unwrap()).unwrap();
+    fs::rename(&old_dir, &new_dir).unwrap();
+    fs::write(dir.path().join("External").join("main.mp4"), b"other").unwrap();
+    assert_eq!(find_missing_media(&project)[0].files.len(), 2);
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink: RelinkProgressSink = {
+      let events = events.clone();
+      Arc::new(move |progress: RelinkScanProgress| events.lock().unwrap().push(progress))
+    };
+    let suggestions = suggest_relink_candidates(
+      &project,
+      &[dir.path().join("External")],
+      &fingerprints,
+      RELINK_SEARCH_MAX_DEPTH,
+      Some(&sink),
+    );
+
+    assert_eq!(suggestions.len(), 2);
+    let main = &suggestions[1];
+    assert_eq!(main.candidates.len(), 2);
+    assert_eq!(
+      main.candidates[0].path,
+      new_dir.join("main.mp4").to_string_lossy()
+    );
+    assert_eq!(main.candidates[0].fingerprint_match, Some(true));
+    assert_eq!(main.candidates[1].fingerprint_match, Some(false));
+    assert!(main.candidates[0].score > main.candidates[1].score);
+    assert_eq!(events.lock().unwrap().last().unwrap().scanned_dirs, 3);
+
+    // Частичное сопоставление: перепривязываем только intro
+    let mapping: HashMap<String, String> = [(
+      old_paths[0].to_string_lossy().to_string(),
+      suggestions[0].candidates[0].path.clone(),
+    )]
+    .into_iter()
+    .collect();
+    let relinked = apply_relink(&mut project, &mapping).unwrap();
+    assert_eq!(relinked.len(), 1);
+    assert_eq!(relinked[0].references_updated, 1);
+    assert_eq!(find_missing_media(&project)[0].files.len(), 1);
+
+    let mapping: HashMap<String, String> = [(
+      old_paths[1].to_string_lossy().to_string(),
+      main.candidates[0].path.clone(),
+    )]
+    .into_iter()
+    .collect();
+    apply_relink(&mut project, &mapping).unwrap();
+    assert!(find_missing_media(&project).is_empty());
+
+    let new_paths: Vec<String> = ["intro.mp4", "main.mp4"]
+      .iter()
+      .map(|name| new_dir.join(name).to_string_lossy().to_string())
+      .collect();
+    assert_eq!(clip_paths(&project), new_paths);
+  }
+
+  #[tokio::test]
+  async fn test_relinked_project_renders_new_paths() {
+    let dir = tempfile::tempdir().unwrap();
+    let old_path = dir.path().join("old").join("clip.mp4");
+    let new_path = dir.path().join("new").join("clip.mp4");
+    fs::create_dir_all(new_path.parent().unwrap()).unwrap();
+    fs::write(&new_path, b"video").unwrap();
+
+    let mut project = project_with_media(&[&old_path]);
+    let suggestions = suggest_relink_candidates(
+      &project,
+      &[dir.path().to_path_buf()],
+      &HashMap::new(),
+      RELINK_SEARCH_MAX_DEPTH,
+      None,
+    );
+    let candidate = &suggestions[0].candidates[0];
+    assert!(candidate.name_match);
+    assert_eq!(candidate.fingerprint_match, None);
+
+    let mapping = HashMap::from([(
+      old_path.to_string_lossy().to_string(),
+      candidate.path.clone(),
+    )]);
+    apply_relink(&mut project, &mapping).unwrap();
+
+    let args: Vec<String> = FFmpegBuilder::new(project)
+      .build_render_command(&dir.path().join("out.mp4"))
+      .await
+      .unwrap()
+      .as_std()
+      .get_args()
+      .map(|arg| arg.to_string_lossy().to_string())
+      .collect();
+    assert!(args.contains(&new_path.to_string_lossy().to_string()));
+    assert!(!args.contains(&old_path.to_string_lossy().to_string()));
+  }
+
+  #[test]
+  fn test_renamed_file_found_by_fingerprint_only() {
+    let dir = tempfile::tempdir().unwrap();
+    let old_path = dir.path().join("DSC_0001.mov");
+    fs::write(&old_path, b"the same footage").unwrap();
+    let fingerprint = compute_fingerprint_blocking(&old_path, FingerprintMode::Fast).unwrap();
+    let project = project_with_media(&[&old_path]);
+
+    let search = dir.path().join("renamed");
+    fs::create_dir_all(&search).unwrap();
+    fs::rename(&old_path, search.join("wedding_ceremony.mov")).unwrap();
+    // Тот же размер, но другое содержимое
+    fs::write(search.join("unrelated.mov"), b"other footage!!!").unwrap();
+
+    let suggestions = suggest_relink_candidates(
+      &project,
+      &[search.clone()],
+      &HashMap::from([(old_path.to_string_lossy().to_string(), fingerprint)]),
+      RELINK_SEARCH_MAX_DEPTH,
+      None,
+    );
+
+    let candidates = &suggestions[0].candidates;
+    assert_eq!(candidates.len(), 1);
+    assert!(!candidates[0].name_match);
+    assert_eq!(candidates[0].fingerprint_match, Some(true));
+    assert!(candidates[0].path.ends_with("wedding_ceremony.mov"));
+  }
+
+  #[test]
+  fn test_search_depth_is_bounded() {
+    let dir = tempfile::tempdir().unwrap();
+    let deep = dir.path().join("a").join("b").join("c");
+    fs::create_dir_all(&deep).unwrap();
+    fs::write(deep.join("clip.mp4"), b"video").unwrap();
+    let project = project_with_media(&[&dir.path().join("missing").join("clip.mp4")]);
+
+    let shallow = suggest_relink_candidates(
+      &project,
+      &[dir.path().to_path_buf()],
+      &HashMap::new(),
+      2,
+      None,
+    );
+    assert!(shallow[0].candidates.is_empty());
+
+    let full = suggest_relink_candidates(
+      &project,
+      &[dir.path().to_path_buf()],
+      &HashMap::new(),
+      3,
+      None,
+    );
+    assert_eq!(full[0].candidates.len(), 1);
+  }
+
+  #[test]
+  fn test_apply_relink_rejects_missing_target() {
+    let dir = tempfile::tempdir().unwrap();
+    let old_path = dir.path().join("clip.mp4");
+    let mut project = project_with_media(&[&old_path]);
+
+    let mapping = HashMap::from([(
+      old_path.to_string_lossy().to_string(),
+      dir.path().join("nowhere.mp4").to_string_lossy().to_string(),
+    )]);
+    assert!(matches!(
+      apply_relink(&mut project, &mapping),
+      Err(VideoCompilerError::MediaFileError { .. })
+    ));
+    assert_eq!(
+      clip_paths(&project),
+      vec![old_path.to_string_lossy().to_string()]
+    );
+  }
+}
@@ -3,27 +3,50 @@
 //! Этот модуль содержит бизнес-логику, отделенную от Tauri команд.
 //! Каждый сервис представляет собой независимый компонент с четко определенными обязанностями.
 
+pub mod batch_export;
 pub mod cache_service;
 pub mod cache_service_with_metrics;
+pub mod cache_warmer;
+pub mod effect_preset;
+pub mod ffmpeg_capabilities;
 pub mod ffmpeg_service;
 pub mod gpu_service;
+pub mod media_relink;
 pub mod monitoring;
+pub mod playback_assist;
 pub mod preview_service;
+pub mod project_assets;
+pub mod project_package;
 pub mod project_service;
+pub mod project_storage;
+pub mod project_template;
+pub mod quick_edit;
+pub mod render_report;
+pub mod render_schedule;
 pub mod render_service;
+pub mod render_spec;
+pub mod schema_journal;
+pub mod snap_index;
+pub mod stale_artifacts;
 
 // Re-export основных типов и трейтов
 pub use cache_service::{CacheService, CacheServiceImpl};
-pub use ffmpeg_service::{FfmpegService, FfmpegServiceImpl, FileInfo};
+pub use ffmpeg_service::{ColorMetadata, FfmpegService, FfmpegServiceImpl, FileInfo, StreamInfo};
 pub use gpu_service::{GpuService, GpuServiceImpl};
 pub use monitoring::{ServiceMetrics, METRICS};
 pub use preview_service::{PreviewService, PreviewServiceImpl};
 pub use project_service::{ProjectService, ProjectServiceImpl};
 pub use render_service::{RenderService, RenderServiceImpl};
 
-use crate::video_compiler::Result;
+use crate::video_compiler::{error::VideoCompilerError, Result};
 use async_trait::async_trait;
-use std::sync::Arc;
+use std::{
+  path::PathBuf,
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+  },
+};
 
 /// Основной трейт для всех сервисов
 #[async_trait]
@@ -48,6 +71,8 @@ pub struct ServiceContainer {
   pub ffmpeg: Arc<dyn FfmpegService>,
   /// Метрики для каждого сервиса
   pub metrics: ServiceMetricsContainer,
+  /// Были ли сервисы инициализированы через initialize_all
+  initialized: AtomicBool,
 }
 
 /// Контейнер метрик для всех сервисов
@@ -60,6 +85,151 @@ pub struct ServiceMetricsContainer {
   pub ffmpeg: Arc<ServiceMetrics>,
 }
 
+impl ServiceMetricsContainer {
+  /// Зарегистрировать метрики всех сервисов в глобальном реестре
+  pub async fn register() -> Self {
+    Self {
+      render: METRICS.register_service(RENDER_SERVICE.to_string()).await,
+      cache: METRICS.register_service(CACHE_SERVICE.to_string()).await,
+      gpu: METRICS.register_service(GPU_SERVICE.to_string()).await,
+      preview: METRICS.register_service(PREVIEW_SERVICE.to_string()).await,
+      project: METRICS.register_service(PROJECT_SERVICE.to_string()).await,
+      ffmpeg: METRICS.register_service(FFMPEG_SERVICE.to_string()).await,
+    }
+  }
+
+  /// Метрики без регистрации в глобальном реестре (для синхронного создания)
+  pub fn unregistered() -> Self {
+    let metrics = |name: &str| Arc::new(ServiceMetrics::new(name.to_string()));
+    Self {
+      render: metrics(RENDER_SERVICE),
+      cache: metrics(CACHE_SERVICE),
+      gpu: metrics(GPU_SERVICE),
+      preview: metrics(PREVIEW_SERVICE),
+      project: metrics(PROJECT_SERVICE),
+      ffmpeg: metrics(FFMPEG_SERVICE),
+    }
+  }
+}
+
+const RENDER_SERVICE: &str = "render-service";
+const CACHE_SERVICE: &str = "cache-service";
+const GPU_SERVICE: &str = "gpu-service";
+const PREVIEW_SERVICE: &str = "preview-service";
+const PROJECT_SERVICE: &str = "project-service";
+const FFMPEG_SERVICE: &str = "ffmpeg-service";
+
+/// Builder контейнера сервисов
+///
+/// Незаданные сервисы создаются из реальных реализаций.
+pub struct ServiceContainerBuilder {
+  ffmpeg_path: String,
+  cache_dir: PathBuf,
+  max_concurrent_jobs: usize,
+  render: Option<Arc<dyn RenderService>>,
+  cache: Option<Arc<dyn CacheService>>,
+  gpu: Option<Arc<dyn GpuService>>,
+  preview: Option<Arc<dyn PreviewService>>,
+  project: Option<Arc<dyn ProjectService>>,
+  ffmpeg: Option<Arc<dyn FfmpegService>>,
+  render_schedule_store: Option<render_schedule::ScheduledRenderStore>,
+}
+
+impl ServiceContainerBuilder {
+  /// Использовать заданный FfmpegService
+  pub fn with_ffmpeg(mut self, ffmpeg: Arc<dyn FfmpegService>) -> Self {
+    self.ffmpeg = Some(ffmpeg);
+    self
+  }
+
+  /// Использовать заданный CacheService
+  pub fn with_cache(mut self, cache: Arc<dyn CacheService>) -> Self {
+    self.cache = Some(cache);
+    self
+  }
+
+  /// Использовать заданный GpuService
+  pub fn with_gpu(mut self, gpu: Arc<dyn GpuService>) -> Self {
+    self.gpu = Some(gpu);
+    self
+  }
+
+  /// Использовать заданный PreviewService
+  pub fn with_preview(mut self, preview: Arc<dyn PreviewService>) -> Self {
+    self.preview = Some(preview);
+    self
+  }
+
+  /// Использовать заданный ProjectService
+  pub fn with_project(mut self, project: Arc<dyn ProjectService>) -> Self {
+    self.project = Some(project);
+    self
+  }
+
+  /// Использовать заданный RenderService
+  pub fn with_render(mut self, render: Arc<dyn RenderService>) -> Self {
+    self.render = Some(render);
+    self
+  }
+
+  /// Сохранять запланированные рендеры встроенного RenderService в заданном хранилище
+  pub fn with_render_schedule_store(
+    mut self,
+    store: render_schedule::ScheduledRenderStore,
+  ) -> Self {
+    self.render_schedule_store = Some(store);
+    self
+  }
+
+  /// Собрать контейнер и зарегистрировать метрики сервисов
+  pub async fn build(self) -> Result<ServiceContainer> {
+    let metrics = ServiceMetricsContainer::register().await;
+    Ok(self.build_with_metrics(metrics))
+  }
+
+  /// Собрать контейнер с готовыми метриками
+  pub fn build_with_metrics(self, metrics: ServiceMetricsContainer) -> ServiceContainer {
+    // Зависимые сервисы получают уже выбранные ffmpeg и cache
+    let ffmpeg = self
+      .ffmpeg
+      .unwrap_or_else(|| Arc::new(FfmpegServiceImpl::new(self.ffmpeg_path.clone())));
+    let cache = self.cache.unwrap_or_else(|| {
+      Arc::new(cache_service_with_metrics::CacheServiceWithMetrics::new(
+        Box::new(CacheServiceImpl::new(self.cache_dir)),
+        metrics.cache.clone(),
+      ))
+    });
+    let gpu = self
+      .gpu
+      .unwrap_or_else(|| Arc::new(GpuServiceImpl::new(self.ffmpeg_path)));
+    let preview = self
+      .preview
+      .unwrap_or_else(|| Arc::new(PreviewServiceImpl::new(ffmpeg.clone())));
+    let project = self
+      .project
+      .unwrap_or_else(|| Arc::new(ProjectServiceImpl::new()));
+    let render_schedule_store = self.render_schedule_store;
+    let render = self.render.unwrap_or_else(|| {
+      let render = RenderServiceImpl::new(ffmpeg.clone(), self.max_concurrent_jobs, cache.clone());
+      Arc::new(match render_schedule_store {
+        Some(store) => render.with_schedule_store(store),
+        None => render,
+      })
+    });
+
+    ServiceContainer {
+      render,
+      cache,
+      gpu,
+      preview,
+      project,
+      ffmpeg,
+      metrics,
+      initialized: AtomicBool::new(false),
+    }
+  }
+}
+
 impl ServiceContainer {
   /// Создание нового контейнера сервисов
   pub async fn new(
@@ -67,41 +237,110 @@ impl ServiceContainer {
     cache_dir: std::path::PathBuf,
     max_concurrent_jobs: usize,
   ) -> Result<Self> {
-    // Создаем сервисы
-    let ffmpeg = Arc::new(FfmpegServiceImpl::new(ffmpeg_path.clone()));
-    let cache = Arc::new(CacheServiceImpl::new(cache_dir));
-    let gpu = Arc::new(GpuServiceImpl::new(ffmpeg_path));
-    let preview = Arc::new(PreviewServiceImpl::new(ffmpeg.clone()));
-    let project = Arc::new(ProjectServiceImpl::new());
-    let render = Arc::new(RenderServiceImpl::new(
-      ffmpeg.clone(),
+    Self::builder(ffmpeg_path, cache_dir, max_concurrent_jobs)
+      .build()
+      .await
+  }
+
+  /// Builder контейнера с возможностью подменить отдельные сервисы
+  pub fn builder(
+    ffmpeg_path: String,
+    cache_dir: PathBuf,
+    max_concurrent_jobs: usize,
+  ) -> ServiceContainerBuilder {
+    ServiceContainerBuilder {
+      ffmpeg_path,
+      cache_dir,
       max_concurrent_jobs,
-      cache.clone(),
-    ));
-
-    // Регистрируем метрики для каждого сервиса
-    let metrics = ServiceMetricsContainer {
-      render: METRICS.register_service("render-service".to_string()).await,
-      cache: METRICS.register_service("cache-service".to_string()).await,
-      gpu: METRICS.register_service("gpu-service".to_string()).await,
-      preview: METRICS
-        .register_service("preview-service".to_string())
-        .await,
-      project: METRICS
-        .register_service("project-service".to_string())
-        .await,
-      ffmpeg: METRICS.register_service("ffmpeg-service".to_string()).await,
-    };
-
-    Ok(Self {
-      render,
-      cache,
-      gpu,
-      preview,
-      project,
-      ffmpeg,
-      metrics,
-    })
+      render: None,
+      cache: None,
+      gpu: None,
+      preview: None,
+      project: None,
+      ffmpeg: None,
+      render_schedule_store: None,
+    }
+  }
+
+  /// Заменить RenderService до initialize_all
+  pub async fn replace_render(&mut self, render: Arc<dyn RenderService>) -> Result<()> {
+    self.ensure_replaceable(RENDER_SERVICE)?;
+    self.render = render;
+    self.metrics.render = METRICS.register_service(RENDER_SERVICE.to_string()).await;
+    Ok(())
+  }
+
+  /// Заменить CacheService до initialize_all
+  pub async fn replace_cache(&mut self, cache: Arc<dyn CacheService>) -> Result<()> {
+    self.ensure_replaceable(CACHE_SERVICE)?;
+    self.cache = cache;
+    self.metrics.cache = METRICS.register_service(CACHE_SERVICE.to_string()).await;
+    Ok(())
+  }
+
+  /// Заменить GpuService до initialize_all
+  pub async fn replace_gpu(&mut self, gpu: Arc<dyn GpuService>) -> Result<()> {
+    self.ensure_replaceable(GPU_SERVICE)?;
+    self.gpu = gpu;
+    self.metrics.gpu = METRICS.register_service(GPU_SERVICE.to_string()).await;
+    Ok(())
+  }
+
+  /// Заменить PreviewService до initialize_all
+  pub async fn replace_preview(&mut self, preview: Arc<dyn PreviewService>) -> Result<()> {
+    self.ensure_replaceable(PREVIEW_SERVICE)?;
+    self.preview = preview;
+    self.metrics.preview = METRICS.register_service(PREVIEW_SERVICE.to_string()).await;
+    Ok(())
+  }
+
+  /// Заменить ProjectService до initialize_all
+  pub async fn replace_project(&mut self, project: Arc<dyn ProjectService>) -> Result<()> {
+    self.ensure_replaceable(PROJECT_SERVICE)?;
+    self.project = project;
+    self.metrics.project = METRICS.register_service(PROJECT_SERVICE.to_string()).await;
+    Ok(())
+  }
+
+  /// Заменить FfmpegService до initialize_all
+  pub async fn replace_ffmpeg(&mut self, ffmpeg: Arc<dyn FfmpegService>) -> Result<()> {
+    self.ensure_replaceable(FFMPEG_SERVICE)?;
+    self.ffmpeg = ffmpeg;
+    self.metrics.ffmpeg = METRICS.register_service(FFMPEG_SERVICE.to_string()).await;
+    Ok(())
+  }
+
+  /// Инициализированные сервисы заменять нельзя
+  fn ensure_replaceable(&self, service: &str) -> Result<()> {
+    if self.initialized.load(Ordering::SeqCst) {
+      return Err(VideoCompilerError::InternalError(format!(
+        "Cannot replace {service} after initialize_all"
+      )));
+    }
+    Ok(())
+  }
+
+  /// Зарегистрировать сервисы в общем DI контейнере для плагинов и телеметрии
+  pub async fn register_into(&self, container: &crate::core::di::ServiceContainer) -> Result<()> {
+    container
+      .register_instance(RENDER_SERVICE, self.render.clone())
+      .await?;
+    container
+      .register_instance(CACHE_SERVICE, self.cache.clone())
+      .await?;
+    container
+      .register_instance(GPU_SERVICE, self.gpu.clone())
+      .await?;
+    container
+      .register_instance(PREVIEW_SERVICE, self.preview.clone())
+      .await?;
+    container
+      .register_instance(PROJECT_SERVICE, self.project.clone())
+      .await?;
+    container
+      .register_instance(FFMPEG_SERVICE, self.ffmpeg.clone())
+      .await?;
+    Ok(())
   }
 
   /// Инициализация всех сервисов
@@ -113,6 +352,7 @@ impl ServiceContainer {
     self.preview.initialize().await?;
     self.project.initialize().await?;
     self.render.initialize().await?;
+    self.initialized.store(true, Ordering::SeqCst);
 
     log::info!("Все сервисы успешно инициализированы");
     Ok(())
@@ -196,4 +436,77 @@ mod tests {
 
     assert!(container.is_ok());
   }
+
+  #[tokio::test]
+  async fn test_builder_installs_mocks_and_defaults() {
+    use crate::video_compiler::tests::mocks::MockRenderService;
+
+    let temp_dir = TempDir::new().unwrap();
+    let render = Arc::new(MockRenderService::default());
+    let container =
+      ServiceContainer::builder("ffmpeg".to_string(), temp_dir.path().to_path_buf(), 2)
+        .with_render(render.clone())
+        .build()
+        .await
+        .unwrap();
+
+    container.health_check_all().await.unwrap();
+    let project = crate::video_compiler::schema::ProjectSchema::new("Mock".to_string());
+    let job_id = container
+      .render
+      .start_render(project, PathBuf::from("/tmp/out.mp4"))
+      .await
+      .unwrap();
+    assert_eq!(job_id, "mock-job-1");
+    assert_eq!(render.started.lock().unwrap().len(), 1);
+  }
+
+  #[tokio::test]
+  async fn test_replace_service_only_before_initialize() {
+    use crate::video_compiler::tests::mocks::MockRenderService;
+
+    let temp_dir = TempDir::new().unwrap();
+    let mut container =
+      ServiceContainer::new("ffmpeg".to_string(), temp_dir.path().to_path_buf(), 2)
+        .await
+        .unwrap();
+
+    container
+      .replace_render(Arc::new(MockRenderService::default()))
+      .await
+      .unwrap();
+    assert_eq!(container.render.get_active_jobs().await.unwrap().len(), 0);
+
+    container.initialize_all().await.unwrap();
+    let result = container
+      .replace_render(Arc::new(MockRenderService::default()))
+      .await;
+    assert!(matches!(result, Err(VideoCompilerError::InternalError(_))));
+  }
+
+  #[tokio::test]
+  async fn test_register_into_core_container() {
+    use crate::video_compiler::tests::mocks::MockRenderService;
+
+    let temp_dir = TempDir::new().unwrap();
+    let container =
+      ServiceContainer::builder("ffmpeg".to_string(), temp_dir.path().to_path_buf(), 2)
+        .with_render(Arc::new(MockRenderService::default()))
+        .build()
+        .await
+        .unwrap();
+    let di = crate::core::di::ServiceContainer::new();
+    container.register_into(&di).await.unwrap();
+
+    let render = di.resolve_instance::<dyn RenderService>().await.unwrap();
+    render
+      .start_render(
+        crate::video_compiler::schema::ProjectSchema::new("Mock".to_string()),
+        PathBuf::from("/tmp/out.mp4"),
+      )
+      .await
+      .unwrap();
+    assert_eq!(container.render.get_active_jobs().await.unwrap().len(), 1);
+    assert!(di.resolve_instance::<dyn FfmpegService>().await.is_ok());
+  }
 }
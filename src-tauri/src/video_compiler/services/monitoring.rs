@@ -2,7 +2,9 @@
 //!
 //! Предоставляет метрики и инструменты для отслеживания производительности сервисов
 
+use crate::video_compiler::error::{Result, VideoCompilerError};
 use std::collections::HashMap;
+use std::future::Future;
 use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -19,6 +21,12 @@ pub struct OperationMetrics {
   pub errors: u64,
   pub last_error: Option<String>,
   pub last_operation_time: Option<std::time::SystemTime>,
+  /// Попытки, прерванные по тайм-ауту
+  #[serde(default)]
+  pub timeouts: u64,
+  /// Повторные попытки после ошибки
+  #[serde(default)]
+  pub retries: u64,
 }
 
 impl Default for OperationMetrics {
@@ -32,6 +40,8 @@ impl Default for OperationMetrics {
       errors: 0,
       last_error: None,
       last_operation_time: None,
+      timeouts: 0,
+      retries: 0,
     }
   }
 }
@@ -43,6 +53,8 @@ pub struct ServiceMetrics {
   pub operations: Arc<RwLock<HashMap<String, OperationMetrics>>>,
   pub total_operations: AtomicU64,
   pub total_errors: AtomicU64,
+  pub total_timeouts: AtomicU64,
+  pub total_retries: AtomicU64,
   pub active_operations: AtomicUsize,
   pub start_time: Instant,
 }
@@ -54,6 +66,8 @@ impl ServiceMetrics {
       operations: Arc::new(RwLock::new(HashMap::new())),
       total_operations: AtomicU64::new(0),
       total_errors: AtomicU64::new(0),
+      total_timeouts: AtomicU64::new(0),
+      total_retries: AtomicU64::new(0),
       active_operations: AtomicUsize::new(0),
       start_time: Instant::now(),
     }
@@ -100,6 +114,26 @@ impl ServiceMetrics {
     }
   }
 
+  /// Записать тайм-аут попытки операции
+  pub async fn record_timeout(&self, operation_name: &str) {
+    self.total_timeouts.fetch_add(1, Ordering::Relaxed);
+    let mut operations = self.operations.write().await;
+    operations
+      .entry(operation_name.to_string())
+      .or_default()
+      .timeouts += 1;
+  }
+
+  /// Записать повторную попытку операции
+  pub async fn record_retry(&self, operation_name: &str) {
+    self.total_retries.fetch_add(1, Ordering::Relaxed);
+    let mut operations = self.operations.write().await;
+    operations
+      .entry(operation_name.to_string())
+      .or_default()
+      .retries += 1;
+  }
+
   /// Получить сводку метрик
   pub async fn get_summary(&self) -> MetricsSummary {
     let operations = self.operations.read().await;
@@ -110,6 +144,8 @@ impl ServiceMetrics {
       uptime_seconds: uptime.as_secs(),
       total_operations: self.total_operations.load(Ordering::Relaxed),
       total_errors: self.total_errors.load(Ordering::Relaxed),
+      total_timeouts: self.total_timeouts.load(Ordering::Relaxed),
+      total_retries: self.total_retries.load(Ordering::Relaxed),
       active_operations: self.active_operations.load(Ordering::Relaxed),
       operations_per_second: self.total_operations.load(Ordering::Relaxed) as f64
         / uptime.as_secs_f64().max(1.0),
@@ -128,6 +164,8 @@ impl ServiceMetrics {
     self.operations.write().await.clear();
     self.total_operations.store(0, Ordering::Relaxed);
     self.total_errors.store(0, Ordering::Relaxed);
+    self.total_timeouts.store(0, Ordering::Relaxed);
+    self.total_retries.store(0, Ordering::Relaxed);
     self.active_operations.store(0, Ordering::Relaxed);
   }
 }
@@ -206,6 +244,8 @@ pub struct MetricsSummary {
   pub uptime_seconds: u64,
   pub total_operations: u64,
   pub total_errors: u64,
+  pub total_timeouts: u64,
+  pub total_retries: u64,
   pub active_operations: usize,
   pub operations_per_second: f64,
   pub error_rate: f64,
@@ -283,6 +323,24 @@ impl MetricsRegistry {
         service_name, summary.total_errors
       ));
 
+      output.push_str(&format!(
+        "# HELP {service_name}_timeouts_total Total number of timed out attempts\n"
+      ));
+      output.push_str(&format!("# TYPE {service_name}_timeouts_total counter\n"));
+      output.push_str(&format!(
+        "{}_timeouts_total {}\n",
+        service_name, summary.total_timeouts
+      ));
+
+      output.push_str(&format!(
+        "# HELP {service_name}_retries_total Total number of retried attempts\n"
+      ));
+      output.push_str(&format!("# TYPE {service_name}_retries_total counter\n"));
+      output.push_str(&format!(
+        "{}_retries_total {}\n",
+        service_name, summary.total_retries
+      ));
+
       output.push_str(&format!(
         "# HELP {service_name}_active_operations Current active operations\n"
       ));
@@ -327,6 +385,200 @@ impl MetricsRegistry {
 use once_cell::sync::Lazy;
 pub static METRICS: Lazy<MetricsRegistry> = Lazy::new(MetricsRegistry::new);
 
+/// Политика выполнения операции сервиса: тайм-аут на попытку и повторы
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Policy {
+  /// Тайм-аут одной попытки (`None` - без ограничения)
+  pub timeout: Option<Duration>,
+  /// Количество повторов после ошибки, допускающей повтор
+  pub retries: u32,
+  /// Задержка перед первым повтором, удваивается с каждой попыткой
+  pub backoff: Duration,
+}
+
+impl Policy {
+  /// Операция без тайм-аута и повторов
+  pub const fn unbounded() -> Self {
+    Self {
+      timeout: None,
+      retries: 0,
+      backoff: Duration::ZERO,
+    }
+  }
+
+  /// Тайм-аут без повторов (для неидемпотентных операций)
+  pub const fn timeout_only(timeout: Duration) -> Self {
+    Self {
+      timeout: Some(timeout),
+      retries: 0,
+      backoff: Duration::ZERO,
+    }
+  }
+
+  /// Тайм-аут с повторами (для идемпотентных операций: пробинг, обнаружение, чтение кэша)
+  pub const fn idempotent(timeout: Duration, retries: u32, backoff: Duration) -> Self {
+    Self {
+      timeout: Some(timeout),
+      retries,
+      backoff,
+    }
+  }
+
+  /// Задержка перед повтором с номером `attempt` (начиная с 1)
+  fn backoff_for(&self, attempt: u32) -> Duration {
+    self
+      .backoff
+      .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+  }
+}
+
+impl Default for Policy {
+  fn default() -> Self {
+    Self::timeout_only(Duration::from_secs(60))
+  }
+}
+
+/// Политики сервисов: значение по умолчанию и переопределения по операциям.
+///
+/// Ключ переопределения - `"<сервис>.<операция>"`, например
+/// `"ffmpeg-service.get_file_info"`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ServicePolicySettings {
+  pub default: Policy,
+  #[serde(default)]
+  pub overrides: HashMap<String, Policy>,
+}
+
+impl ServicePolicySettings {
+  /// Политика для операции сервиса
+  pub fn policy_for(&self, service_name: &str, operation_name: &str) -> Policy {
+    self
+      .overrides
+      .get(&format!("{service_name}.{operation_name}"))
+      .copied()
+      .unwrap_or(self.default)
+  }
+}
+
+impl Default for ServicePolicySettings {
+  fn default() -> Self {
+    let overrides = [
+      (
+        "ffmpeg-service.get_file_info",
+        Policy::idempotent(Duration::from_secs(30), 2, Duration::from_millis(250)),
+      ),
+      (
+        "gpu-service.detect_gpus",
+        Policy::idempotent(Duration::from_secs(20), 1, Duration::from_millis(500)),
+      ),
+      (
+        "preview-service.generate_frame_preview",
+        Policy::idempotent(Duration::from_secs(15), 1, Duration::from_millis(200)),
+      ),
+      // Финальное кодирование может идти часами, ограничивать его тайм-аутом нельзя
+      ("render-service.render", Policy::unbounded()),
+    ]
+    .into_iter()
+    .map(|(key, policy)| (key.to_string(), policy))
+    .collect();
+
+    Self {
+      default: Policy::default(),
+      overrides,
+    }
+  }
+}
+
+/// Текущие политики сервисов (синхронизируются с `CompilerSettings::service_policies`)
+static POLICIES: Lazy<std::sync::RwLock<ServicePolicySettings>> =
+  Lazy::new(|| std::sync::RwLock::new(ServicePolicySettings::default()));
+
+/// Установить политики сервисов
+pub fn set_service_policies(settings: ServicePolicySettings) {
+  match POLICIES.write() {
+    Ok(mut policies) => *policies = settings,
+    Err(poisoned) => *poisoned.into_inner() = settings,
+  }
+}
+
+/// Получить текущие политики сервисов
+pub fn service_policies() -> ServicePolicySettings {
+  match POLICIES.read() {
+    Ok(policies) => policies.clone(),
+    Err(poisoned) => poisoned.into_inner().clone(),
+  }
+}
+
+/// Политика для операции сервиса из текущих настроек
+pub fn resolve_policy(service_name: &str, operation_name: &str) -> Policy {
+  service_policies().policy_for(service_name, operation_name)
+}
+
+/// Выполнить операцию сервиса с тайм-аутом и повторами.
+///
+/// Каждая попытка учитывается в метриках сервиса из `METRICS` (если сервис
+/// зарегистрирован), тайм-ауты и повторы считаются отдельно. Повторяются только
+/// ошибки с `is_retryable()`, поэтому политику с `retries > 0` стоит задавать
+/// лишь идемпотентным операциям.
+pub async fn with_policy<T, F, Fut>(
+  service_name: &str,
+  operation_name: &str,
+  policy: Policy,
+  mut operation: F,
+) -> Result<T>
+where
+  F: FnMut() -> Fut,
+  Fut: Future<Output = Result<T>>,
+{
+  let metrics = METRICS.get_service_metrics(service_name).await;
+  let mut attempt = 0;
+
+  loop {
+    let started = Instant::now();
+    let result = match policy.timeout {
+      Some(timeout) => match tokio::time::timeout(timeout, operation()).await {
+        Ok(result) => result,
+        Err(_) => {
+          if let Some(metrics) = &metrics {
+            metrics.record_timeout(operation_name).await;
+          }
+          Err(VideoCompilerError::timeout(format!(
+            "{service_name}.{operation_name} не завершилась за {timeout:?}"
+          )))
+        }
+      },
+      None => operation().await,
+    };
+
+    if let Some(metrics) = &metrics {
+      metrics
+        .record_operation(
+          operation_name,
+          started.elapsed(),
+          result.is_ok(),
+          result.as_ref().err().map(|e| e.to_string()),
+        )
+        .await;
+    }
+
+    match result {
+      Err(e) if e.is_retryable() && attempt < policy.retries => {
+        attempt += 1;
+        let delay = policy.backoff_for(attempt);
+        log::warn!(
+          "[{service_name}] Операция '{operation_name}' завершилась ошибкой: {e}, повтор {attempt}/{} через {delay:?}",
+          policy.retries
+        );
+        if let Some(metrics) = &metrics {
+          metrics.record_retry(operation_name).await;
+        }
+        tokio::time::sleep(delay).await;
+      }
+      result => return result,
+    }
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -780,4 +1032,98 @@ mod tests {
     let summary_with_errors = metrics.get_summary().await;
     assert!((summary_with_errors.error_rate - (2.0 / 7.0)).abs() < f64::EPSILON);
   }
+
+  #[test]
+  fn test_policy_overrides() {
+    let settings = ServicePolicySettings::default();
+
+    let probe = settings.policy_for("ffmpeg-service", "get_file_info");
+    assert!(probe.retries > 0);
+    assert!(probe.timeout.is_some());
+
+    // Кодирование не ограничено тайм-аутом
+    assert_eq!(
+      settings.policy_for("render-service", "render"),
+      Policy::unbounded()
+    );
+
+    // Неизвестная операция получает политику по умолчанию
+    assert_eq!(
+      settings.policy_for("cache-service", "get"),
+      settings.default
+    );
+  }
+
+  #[test]
+  fn test_policy_backoff_is_exponential() {
+    let policy = Policy::idempotent(Duration::from_secs(1), 3, Duration::from_millis(100));
+    assert_eq!(policy.backoff_for(1), Duration::from_millis(100));
+    assert_eq!(policy.backoff_for(2), Duration::from_millis(200));
+    assert_eq!(policy.backoff_for(3), Duration::from_millis(400));
+  }
+
+  #[tokio::test]
+  async fn test_with_policy_timeout_and_retries() {
+    let metrics = METRICS
+      .register_service("policy-timeout-test".to_string())
+      .await;
+    let policy = Policy::idempotent(Duration::from_millis(20), 2, Duration::from_millis(1));
+    let attempts = AtomicUsize::new(0);
+
+    let result: Result<()> = with_policy("policy-timeout-test", "probe", policy, || async {
+      attempts.fetch_add(1, Ordering::SeqCst);
+      tokio::time::sleep(Duration::from_secs(5)).await;
+      Ok(())
+    })
+    .await;
+
+    match result {
+      Err(VideoCompilerError::TimeoutError(message)) => assert!(message.contains("probe")),
+      other => panic!("Ожидался тайм-аут, получено {other:?}"),
+    }
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+    let summary = metrics.get_summary().await;
+    assert_eq!(summary.total_timeouts, 3);
+    assert_eq!(summary.total_retries, 2);
+    assert_eq!(summary.total_errors, 3);
+    assert_eq!(summary.operation_metrics["probe"].timeouts, 3);
+    assert_eq!(summary.operation_metrics["probe"].retries, 2);
+  }
+
+  #[tokio::test]
+  async fn test_with_policy_retries_until_success() {
+    let attempts = AtomicUsize::new(0);
+    let policy = Policy::idempotent(Duration::from_secs(1), 3, Duration::from_millis(1));
+
+    let result = with_policy("policy-unregistered-test", "detect", policy, || async {
+      if attempts.fetch_add(1, Ordering::SeqCst) < 2 {
+        Err(VideoCompilerError::IoError("busy".to_string()))
+      } else {
+        Ok(42)
+      }
+    })
+    .await;
+
+    assert_eq!(result.unwrap(), 42);
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+  }
+
+  #[tokio::test]
+  async fn test_with_policy_does_not_retry_permanent_errors() {
+    let attempts = AtomicUsize::new(0);
+    let policy = Policy::idempotent(Duration::from_secs(1), 3, Duration::from_millis(1));
+
+    let result: Result<()> = with_policy("policy-permanent-test", "probe", policy, || async {
+      attempts.fetch_add(1, Ordering::SeqCst);
+      Err(VideoCompilerError::validation("bad input"))
+    })
+    .await;
+
+    assert!(matches!(
+      result,
+      Err(VideoCompilerError::ValidationError(_))
+    ));
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+  }
 }
@@ -0,0 +1,740 @@
+//! Предрендер во время воспроизведения
+//!
+//! Frontend сообщает положение playhead и состояние воспроизведения, а
+//! [`PlaybackAssist`] держит предрендеренным небольшое окно впереди playhead
+//! отрезками по 2 секунды в пониженном качестве. Отрезки позади playhead
+//! удаляются, работа приостанавливается на паузе и во время полного рендера.
+//! Отрезки регистрируются в кэше рендеринга и подчиняются его лимитам:
+//! отрезок, вытесненный из кэша, перестает считаться готовым.
+
+use crate::video_compiler::{
+  cache::{RenderCache, RenderCacheData},
+  core::priority::{run_in_background, yield_to_interactive},
+  error::{Result, VideoCompilerError},
+  schema::ProjectSchema,
+};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::{Mutex, RwLock};
+use tokio_util::sync::CancellationToken;
+
+/// Окно предрендера впереди playhead по умолчанию (секунды)
+pub const DEFAULT_LOOKAHEAD_SECS: f64 = 10.0;
+
+/// Длительность отрезка предрендера (секунды)
+pub const PLAYBACK_CHUNK_SECS: f64 = 2.0;
+
+/// Качество экспорта отрезков воспроизведения
+pub const PLAYBACK_PRERENDER_QUALITY: u8 = 50;
+
+/// Префикс ключей отрезков в кэше рендеринга
+pub const PLAYBACK_CACHE_PREFIX: &str = "prerender_playback_";
+
+/// Интервал проверки завершения полного рендера
+const FULL_RENDER_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Параметры окна предрендера
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlaybackAssistConfig {
+  /// Сколько секунд впереди playhead держать готовыми
+  pub lookahead_secs: f64,
+  /// Длительность отрезка (секунды)
+  pub chunk_secs: f64,
+}
+
+impl Default for PlaybackAssistConfig {
+  fn default() -> Self {
+    Self {
+      lookahead_secs: DEFAULT_LOOKAHEAD_SECS,
+      chunk_secs: PLAYBACK_CHUNK_SECS,
+    }
+  }
+}
+
+/// Отрезок timeline `[start, end)` в секундах
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PlaybackRange {
+  pub start: f64,
+  pub end: f64,
+}
+
+/// Готовый отрезок воспроизведения
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaybackChunk {
+  /// Номер отрезка от начала timeline
+  pub index: usize,
+  pub range: PlaybackRange,
+  pub path: PathBuf,
+  pub size_bytes: u64,
+}
+
+/// Окно отрезков вокруг playhead одного проекта
+#[derive(Debug, Clone)]
+pub struct PlaybackWindow {
+  config: PlaybackAssistConfig,
+  duration: f64,
+  playhead: f64,
+  playing: bool,
+  ready: BTreeMap<usize, PlaybackChunk>,
+  in_flight: Option<usize>,
+  /// Отрезки, рендер которых завершился ошибкой, повторно не запрашиваются
+  failed: BTreeSet<usize>,
+}
+
+impl PlaybackWindow {
+  pub fn new(config: PlaybackAssistConfig, duration: f64) -> Self {
+    Self {
+      config,
+      duration,
+      playhead: 0.0,
+      playing: false,
+      ready: BTreeMap::new(),
+      in_flight: None,
+      failed: BTreeSet::new(),
+    }
+  }
+
+  /// Идет ли воспроизведение
+  pub fn is_playing(&self) -> bool {
+    self.playing
+  }
+
+  /// Параметры окна
+  pub fn config(&self) -> PlaybackAssistConfig {
+    self.config
+  }
+
+  /// Границы отрезка по номеру
+  pub fn chunk_range(&self, index: usize) -> PlaybackRange {
+    let start = index as f64 * self.config.chunk_secs;
+    PlaybackRange {
+      start,
+      end: (start + self.config.chunk_secs).min(self.duration),
+    }
+  }
+
+  /// Номер отрезка под playhead: все отрезки до него позади
+  fn current_chunk(&self) -> usize {
+    (self.playhead / self.config.chunk_secs).floor() as usize
+  }
+
+  /// Номера отрезков от текущего до конца окна впереди playhead
+  pub fn wanted_chunks(&self) -> Range<usize> {
+    if self.playhead >= self.duration {
+      return 0..0;
+    }
+    let first = self.current_chunk();
+    let window_end = (self.playhead + self.config.lookahead_secs).min(self.duration);
+    let last = (window_end / self.config.chunk_secs).ceil() as usize;
+    first..last.max(first + 1)
+  }
+
+  /// Обновить положение playhead. Возвращает отрезки позади playhead,
+  /// которые больше не нужны.
+  pub fn update(&mut self, time: f64, playing: bool) -> Vec<PlaybackChunk> {
+    self.playhead = time.clamp(0.0, self.duration);
+    self.playing = playing;
+
+    let mut behind = self.ready.split_off(&self.current_chunk());
+    std::mem::swap(&mut behind, &mut self.ready);
+    behind.into_values().collect()
+  }
+
+  /// Следующий отрезок для предрендера: первый неготовый в окне.
+  /// На паузе и пока предыдущий отрезок рендерится - `None`.
+  pub fn next_request(&self) -> Option<usize> {
+    if !self.playing || self.in_flight.is_some() {
+      return None;
+    }
+    self
+      .wanted_chunks()
+      .find(|index| !self.ready.contains_key(index) && !self.failed.contains(index))
+  }
+
+  /// Отметить начало рендера отрезка
+  pub fn start(&mut self, index: usize) {
+    self.in_flight = Some(index);
+  }
+
+  /// Принять отрендеренный отрезок. `false`, если playhead успел уйти
+  /// дальше и отрезок не нужен.
+  pub fn complete(&mut self, index: usize, chunk: PlaybackChunk) -> bool {
+    self.in_flight = None;
+    if index < self.current_chunk() {
+      return false;
+    }
+    self.ready.insert(index, chunk);
+    true
+  }
+
+  /// Рендер отрезка не удался или был прерван
+  pub fn abort(&mut self, index: usize, failed: bool) {
+    self.in_flight = None;
+    if failed {
+      self.failed.insert(index);
+    }
+  }
+
+  /// Убрать готовый отрезок (например, вытесненный из кэша)
+  pub fn remove(&mut self, index: usize) -> Option<PlaybackChunk> {
+    self.ready.remove(&index)
+  }
+
+  /// Номера готовых отрезков
+  pub fn ready_chunks(&self) -> Vec<usize> {
+    self.ready.keys().copied().collect()
+  }
+
+  /// Начать заново для измененного проекта или параметров окна; положение
+  /// playhead сохраняется. Возвращает все прежние отрезки.
+  pub fn reset(&mut self, config: PlaybackAssistConfig, duration: f64) -> Vec<PlaybackChunk> {
+    self.config = config;
+    self.duration = duration;
+    self.playhead = self.playhead.min(duration);
+    self.in_flight = None;
+    self.failed.clear();
+    std::mem::take(&mut self.ready).into_values().collect()
+  }
+
+  /// Готовые участки timeline, соседние отрезки объединены
+  pub fn ready_ranges(&self) -> Vec<PlaybackRange> {
+    let mut ranges: Vec<PlaybackRange> = Vec::new();
+    for chunk in self.ready.values() {
+      match ranges.last_mut() {
+        Some(last) if (last.end - chunk.range.start).abs() < 1e-9 => last.end = chunk.range.end,
+        _ => ranges.push(chunk.range),
+      }
+    }
+    ranges
+  }
+}
+
+/// Проект отрезка воспроизведения: пониженное разрешение и быстрое кодирование
+pub fn playback_chunk_project(project: &ProjectSchema) -> ProjectSchema {
+  let mut project = project.clone();
+  let half = |value: u32| (value / 2).max(2) & !1;
+
+  let (width, height) = project.timeline.resolution;
+  project.timeline.resolution = (half(width), half(height));
+  let resolution = &mut project.settings.resolution;
+  resolution.width = half(resolution.width);
+  resolution.height = half(resolution.height);
+
+  let export = &mut project.settings.export;
+  export.quality = export.quality.min(PLAYBACK_PRERENDER_QUALITY);
+  export.preset = Some("ultrafast".to_string());
+  export.optimize_for_speed = Some(true);
+  export.export_stems = false;
+  export.renditions.clear();
+  export.generate_hls = false;
+  project
+}
+
+/// Рендер отрезков воспроизведения
+#[async_trait]
+pub trait PlaybackChunkRenderer: Send + Sync {
+  /// Отрендерить отрезок проекта и вернуть путь к файлу
+  async fn render_chunk(&self, project: &ProjectSchema, range: PlaybackRange) -> Result<PathBuf>;
+
+  /// Идет ли полный рендер; на это время предрендер приостанавливается
+  async fn full_render_running(&self) -> bool;
+}
+
+/// Состояние воспроизведения одного проекта
+struct PlaybackSession {
+  project: Arc<ProjectSchema>,
+  window: PlaybackWindow,
+  worker_running: bool,
+  /// Отменяется при смене проекта, чтобы не принимать устаревшие отрезки
+  cancel: CancellationToken,
+}
+
+/// Предрендер окна впереди playhead для плавного воспроизведения
+pub struct PlaybackAssist {
+  renderer: Arc<dyn PlaybackChunkRenderer>,
+  cache: Arc<RwLock<RenderCache>>,
+  sessions: Mutex<HashMap<String, PlaybackSession>>,
+}
+
+impl PlaybackAssist {
+  pub fn new(renderer: Arc<dyn PlaybackChunkRenderer>, cache: Arc<RwLock<RenderCache>>) -> Self {
+    Self {
+      renderer,
+      cache,
+      sessions: Mutex::new(HashMap::new()),
+    }
+  }
+
+  fn cache_key(project_id: &str, index: usize) -> String {
+    format!("{PLAYBACK_CACHE_PREFIX}{project_id}_{index}")
+  }
+
+  /// Передать проект для предрендера. `config` - параметры окна (`None` -
+  /// прежние или по умолчанию). Если проект или параметры изменились, готовые
+  /// отрезки удаляются, а текущий рендер отрезка отбрасывается.
+  pub async fn set_project(
+    &self,
+    project_id: &str,
+    project: ProjectSchema,
+    config: Option<PlaybackAssistConfig>,
+  ) {
+    let mut sessions = self.sessions.lock().await;
+    let discarded = match sessions.get_mut(project_id) {
+      Some(session) => {
+        let config = config.unwrap_or(session.window.config());
+        if config == session.window.config()
+          && serde_json::to_value(&*session.project).ok() == serde_json::to_value(&project).ok()
+        {
+          return;
+        }
+        session.cancel.cancel();
+        session.cancel = CancellationToken::new();
+        let discarded = session.window.reset(config, project.get_duration());
+        session.project = Arc::new(project);
+        discarded
+      }
+      None => {
+        sessions.insert(
+          project_id.to_string(),
+          PlaybackSession {
+            window: PlaybackWindow::new(config.unwrap_or_default(), project.get_duration()),
+            project: Arc::new(project),
+            worker_running: false,
+            cancel: CancellationToken::new(),
+          },
+        );
+        Vec::new()
+      }
+    };
+    drop(sessions);
+    self.discard(project_id, discarded).await;
+  }
+
+  /// Обновить положение playhead и запустить предрендер при воспроизведении
+  pub async fn update_position(
+    self: &Arc<Self>,
+    project_id: &str,
+    time: f64,
+    playing: bool,
+  ) -> Result<()> {
+    let mut sessions = self.sessions.lock().await;
+    let session = sessions.get_mut(project_id).ok_or_else(|| {
+      VideoCompilerError::InvalidParameter(format!(
+        "Проект {project_id} не передан для воспроизведения"
+      ))
+    })?;
+
+    let behind = session.window.update(time, playing);
+    let spawn_worker = playing && !session.worker_running;
+    session.worker_running |= spawn_worker;
+    drop(sessions);
+
+    self.discard(project_id, behind).await;
+    if spawn_worker {
+      tokio::spawn(self.clone().run_worker(project_id.to_string()));
+    }
+    Ok(())
+  }
+
+  /// Готовые участки timeline проекта
+  pub async fn ready_ranges(&self, project_id: &str) -> Vec<PlaybackRange> {
+    self.prune_evicted(project_id).await;
+    self
+      .sessions
+      .lock()
+      .await
+      .get(project_id)
+      .map(|session| session.window.ready_ranges())
+      .unwrap_or_default()
+  }
+
+  /// Убрать отрезки, вытесненные из кэша рендеринга по его лимитам
+  async fn prune_evicted(&self, project_id: &str) {
+    let mut sessions = self.sessions.lock().await;
+    let Some(session) = sessions.get_mut(project_id) else {
+      return;
+    };
+    let cache = self.cache.read().await;
+    let evicted: Vec<PlaybackChunk> = session
+      .window
+      .ready_chunks()
+      .into_iter()
+      .filter(|index| !cache.has_render_data(&Self::cache_key(project_id, *index)))
+      .filter_map(|index| session.window.remove(index))
+      .collect();
+    drop(cache);
+    drop(sessions);
+    for chunk in evicted {
+      remove_chunk_file(&chunk).await;
+    }
+  }
+
+  /// Удалить файлы и записи кэша ненужных отрезков
+  async fn discard(&self, project_id: &str, chunks: Vec<PlaybackChunk>) {
+    if chunks.is_empty() {
+      return;
+    }
+    let mut cache = self.cache.write().await;
+    for chunk in &chunks {
+      cache.remove_render_data(&Self::cache_key(project_id, chunk.index));
+    }
+    drop(cache);
+    for chunk in &chunks {
+      remove_chunk_file(chunk).await;
+    }
+  }
+
+  /// Рендер отрезков по одному, пока идет воспроизведение и окно не заполнено
+  async fn run_worker(self: Arc<Self>, project_id: String) {
+    loop {
+      if self.renderer.full_render_running().await {
+        let mut sessions = self.sessions.lock().await;
+        match sessions.get_mut(&project_id) {
+          Some(session) if session.window.is_playing() => {}
+          Some(session) => {
+            session.worker_running = false;
+            return;
+          }
+          None => return,
+        }
+        drop(sessions);
+        tokio::time::sleep(FULL_RENDER_POLL_INTERVAL).await;
+        continue;
+      }
+
+      self.prune_evicted(&project_id).await;
+      let (index, range, project, cancel) = {
+        let mut sessions = self.sessions.lock().await;
+        let Some(session) = sessions.get_mut(&project_id) else {
+          return;
+        };
+        let Some(index) = session.window.next_request() else {
+          session.worker_running = false;
+          return;
+        };
+        session.window.start(index);
+        (
+          index,
+          session.window.chunk_range(index),
+          session.project.clone(),
+          session.cancel.clone(),
+        )
+      };
+
+      let rendered = if yield_to_interactive(&cancel).await {
+        Some(run_in_background(self.renderer.render_chunk(&project, range)).await)
+      } else {
+        None
+      };
+
+      let mut sessions = self.sessions.lock().await;
+      let session = sessions
+        .get_mut(&project_id)
+        .filter(|_| !cancel.is_cancelled());
+      match (session, rendered) {
+        (Some(session), Some(Ok(path))) => {
+          let size_bytes = tokio::fs::metadata(&path)
+            .await
+            .map(|meta| meta.len())
+            .unwrap_or(0);
+          let chunk = PlaybackChunk {
+            index,
+            range,
+            path,
+            size_bytes,
+          };
+          if session.window.complete(index, chunk.clone()) {
+            let key = Self::cache_key(&project_id, index);
+            let data = RenderCacheData {
+              cache_key: key.clone(),
+              output_path: chunk.path.clone(),
+              render_hash: key.clone(),
+              created_at: SystemTime::now(),
+              file_size: size_bytes,
+            };
+            if let Err(e) = self.cache.write().await.store_render_data(key, data).await {
+              log::warn!("Отрезок воспроизведения не добавлен в кэш: {e}");
+            }
+          } else {
+            remove_chunk_file(&chunk).await;
+          }
+        }
+        (Some(session), Some(Err(e))) => {
+          log::warn!(
+            "Предрендер отрезка {:.1}-{:.1} с не удался: {e}",
+            range.start,
+            range.end
+          );
+          session.window.abort(index, true);
+        }
+        (Some(session), None) => session.window.abort(index, false),
+        (None, Some(Ok(path))) => {
+          let _ = tokio::fs::remove_file(path).await;
+        }
+        (None, _) => {}
+      }
+    }
+  }
+}
+
+async fn remove_chunk_file(chunk: &PlaybackChunk) {
+  if let Err(e) = tokio::fs::remove_file(&chunk.path).await {
+    if e.kind() != std::io::ErrorKind::NotFound {
+      log::warn!(
+        "Не удалось удалить отрезок воспроизведения {}: {e}",
+        chunk.path.display()
+      );
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::video_compiler::schema::{Clip, Track, TrackType};
+  use std::sync::atomic::{AtomicBool, Ordering};
+
+  fn chunk(window: &PlaybackWindow, index: usize) -> PlaybackChunk {
+    PlaybackChunk {
+      index,
+      range: window.chunk_range(index),
+      path: PathBuf::from(format!("/tmp/chunk_{index}.mp4")),
+      size_bytes: 100,
+    }
+  }
+
+  /// Рендерить запрошенные отрезки, пока окно не заполнится
+  fn fill(window: &mut PlaybackWindow) -> Vec<(f64, f64)> {
+    let mut requested = Vec::new();
+    while let Some(index) = window.next_request() {
+      window.start(index);
+      let range = window.chunk_range(index);
+      requested.push((range.start, range.end));
+      assert!(window.complete(index, chunk(window, index)));
+    }
+    requested
+  }
+
+  #[test]
+  fn test_moving_playhead_requests_and_evicts_chunks() {
+    let mut window = PlaybackWindow::new(PlaybackAssistConfig::default(), 30.0);
+
+    assert!(window.update(0.0, true).is_empty());
+    assert_eq!(
+      fill(&mut window),
+      vec![(0.0, 2.0), (2.0, 4.0), (4.0, 6.0), (6.0, 8.0), (8.0, 10.0)]
+    );
+    assert_eq!(
+      window.ready_ranges(),
+      vec![PlaybackRange {
+        start: 0.0,
+        end: 10.0
+      }]
+    );
+
+    // Playhead в середине третьего отрезка: два первых позади
+    let evicted: Vec<_> = window
+      .update(5.0, true)
+      .iter()
+      .map(|chunk| (chunk.range.start, chunk.range.end))
+      .collect();
+    assert_eq!(evicted, vec![(0.0, 2.0), (2.0, 4.0)]);
+    assert_eq!(
+      fill(&mut window),
+      vec![(10.0, 12.0), (12.0, 14.0), (14.0, 16.0)]
+    );
+    assert_eq!(
+      window.ready_ranges(),
+      vec![PlaybackRange {
+        start: 4.0,
+        end: 16.0
+      }]
+    );
+
+    // Переход назад: отрезки впереди остаются, недостающие запрашиваются
+    assert!(window.update(1.0, true).is_empty());
+    assert_eq!(fill(&mut window), vec![(0.0, 2.0), (2.0, 4.0)]);
+  }
+
+  #[test]
+  fn test_window_clips_to_project_end() {
+    let mut window = PlaybackWindow::new(PlaybackAssistConfig::default(), 7.0);
+    window.update(3.5, true);
+    assert_eq!(fill(&mut window), vec![(2.0, 4.0), (4.0, 6.0), (6.0, 7.0)]);
+
+    // В конце проекта остается только последний отрезок
+    window.update(7.0, true);
+    assert_eq!(window.next_request(), None);
+    assert_eq!(
+      window.ready_ranges(),
+      vec![PlaybackRange {
+        start: 6.0,
+        end: 7.0
+      }]
+    );
+  }
+
+  #[test]
+  fn test_paused_window_requests_nothing() {
+    let mut window = PlaybackWindow::new(PlaybackAssistConfig::default(), 30.0);
+    window.update(4.0, false);
+    assert_eq!(window.next_request(), None);
+
+    window.update(4.0, true);
+    let index = window.next_request().unwrap();
+    window.start(index);
+    // Пока отрезок рендерится, следующий не запрашивается
+    assert_eq!(window.next_request(), None);
+
+    // Playhead ушел дальше отрезка за время рендера
+    window.update(9.0, true);
+    assert!(!window.complete(index, chunk(&window, index)));
+    assert_eq!(window.next_request(), Some(4));
+  }
+
+  #[test]
+  fn test_failed_chunk_is_not_retried() {
+    let mut window = PlaybackWindow::new(PlaybackAssistConfig::default(), 4.0);
+    window.update(0.0, true);
+    window.start(0);
+    window.abort(0, true);
+    assert_eq!(window.next_request(), Some(1));
+  }
+
+  /// Рендерер, записывающий пустые файлы и запрошенные отрезки
+  struct FakeRenderer {
+    dir: PathBuf,
+    requested: std::sync::Mutex<Vec<(f64, f64)>>,
+    full_render: AtomicBool,
+  }
+
+  #[async_trait]
+  impl PlaybackChunkRenderer for FakeRenderer {
+    async fn render_chunk(
+      &self,
+      _project: &ProjectSchema,
+      range: PlaybackRange,
+    ) -> Result<PathBuf> {
+      self
+        .requested
+        .lock()
+        .unwrap()
+        .push((range.start, range.end));
+      let path = self
+        .dir
+        .join(format!("chunk_{}_{}.mp4", range.start, range.end));
+      tokio::fs::write(&path, b"chunk").await.unwrap();
+      Ok(path)
+    }
+
+    async fn full_render_running(&self) -> bool {
+      self.full_render.load(Ordering::SeqCst)
+    }
+  }
+
+  fn project(duration: f64) -> ProjectSchema {
+    let mut project = ProjectSchema::new("Playback".to_string());
+    let mut track = Track::new(TrackType::Video, "Video".to_string());
+    track
+      .clips
+      .push(Clip::new(PathBuf::from("/media/a.mp4"), 0.0, duration));
+    project.tracks.push(track);
+    project
+  }
+
+  async fn wait_for_ranges(assist: &PlaybackAssist, expected: Vec<PlaybackRange>) {
+    for _ in 0..200 {
+      if assist.ready_ranges("p1").await == expected {
+        return;
+      }
+      tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!(
+      "ожидались {expected:?}, готовы {:?}",
+      assist.ready_ranges("p1").await
+    );
+  }
+
+  #[tokio::test]
+  async fn test_assist_prerenders_ahead_and_respects_cache() {
+    let dir = tempfile::tempdir().unwrap();
+    let renderer = Arc::new(FakeRenderer {
+      dir: dir.path().to_path_buf(),
+      requested: std::sync::Mutex::new(Vec::new()),
+      full_render: AtomicBool::new(false),
+    });
+    let cache = Arc::new(RwLock::new(RenderCache::new()));
+    let config = PlaybackAssistConfig {
+      lookahead_secs: 4.0,
+      chunk_secs: 2.0,
+    };
+    let assist = Arc::new(PlaybackAssist::new(renderer.clone(), cache.clone()));
+
+    assert!(assist.update_position("p1", 0.0, true).await.is_err());
+    assist.set_project("p1", project(20.0), Some(config)).await;
+    assist.update_position("p1", 0.0, true).await.unwrap();
+    wait_for_ranges(
+      &assist,
+      vec![PlaybackRange {
+        start: 0.0,
+        end: 4.0,
+      }],
+    )
+    .await;
+    assert!(cache
+      .read()
+      .await
+      .has_render_data("prerender_playback_p1_0"));
+
+    // Отрезок позади playhead удаляется вместе с файлом и записью кэша
+    let first = dir.path().join("chunk_0_2.mp4");
+    assert!(first.exists());
+    assist.update_position("p1", 2.5, true).await.unwrap();
+    wait_for_ranges(
+      &assist,
+      vec![PlaybackRange {
+        start: 2.0,
+        end: 8.0,
+      }],
+    )
+    .await;
+    assert!(!first.exists());
+    assert!(!cache
+      .read()
+      .await
+      .has_render_data("prerender_playback_p1_0"));
+
+    // Во время полного рендера новые отрезки не запрашиваются
+    renderer.full_render.store(true, Ordering::SeqCst);
+    assist.update_position("p1", 6.5, true).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(
+      *renderer.requested.lock().unwrap(),
+      vec![(0.0, 2.0), (2.0, 4.0), (4.0, 6.0), (6.0, 8.0)]
+    );
+
+    // Очистка кэша рендеринга снимает готовность отрезков
+    cache.write().await.clear_all().await;
+    assert!(assist.ready_ranges("p1").await.is_empty());
+    assist.update_position("p1", 6.5, false).await.unwrap();
+  }
+
+  #[test]
+  fn test_playback_chunk_project_reduces_quality() {
+    let mut source = project(4.0);
+    source.timeline.resolution = (1920, 1080);
+    source.settings.export.export_stems = true;
+
+    let reduced = playback_chunk_project(&source);
+    assert_eq!(reduced.timeline.resolution, (960, 540));
+    assert!(reduced.settings.export.quality <= PLAYBACK_PRERENDER_QUALITY);
+    assert_eq!(reduced.settings.export.preset.as_deref(), Some("ultrafast"));
+    assert!(!reduced.settings.export.export_stems);
+  }
+}
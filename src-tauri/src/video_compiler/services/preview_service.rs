@@ -1,12 +1,19 @@
 //! Сервис генерации превью
 
 use crate::video_compiler::{
+  core::{priority, temp_storage},
   error::{Result, VideoCompilerError},
   ffmpeg_builder::FFmpegBuilder,
   ffmpeg_executor::FFmpegExecutor,
   preview::PreviewGenerator,
-  schema::{Clip, ProjectSchema},
-  services::{FfmpegService, Service},
+  schema::{
+    timeline::{ClipSource, TrackType},
+    Clip, ProjectSchema,
+  },
+  services::{
+    monitoring::{resolve_policy, with_policy},
+    FfmpegService, Service,
+  },
 };
 use async_trait::async_trait;
 use std::{
@@ -16,6 +23,9 @@ use std::{
 };
 use tokio::sync::RwLock;
 
+/// Цвет waveform в превью проектов без видео
+pub(crate) const WAVEFORM_PREVIEW_COLOR: &str = "#00ff00";
+
 /// Тип превью
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum PreviewType {
@@ -118,16 +128,48 @@ pub trait PreviewService: Service + Send + Sync {
 /// Реализация сервиса превью
 pub struct PreviewServiceImpl {
   preview_cache: Arc<RwLock<HashMap<String, PreviewResult>>>,
-  temp_dir: PathBuf,
 }
 
 impl PreviewServiceImpl {
   pub fn new(_ffmpeg_service: Arc<dyn FfmpegService>) -> Self {
-    let temp_dir = std::env::temp_dir().join("timeline-studio-preview");
-
     Self {
       preview_cache: Arc::new(RwLock::new(HashMap::new())),
-      temp_dir,
+    }
+  }
+
+  /// Временная директория превью. Берется из текущего корня временных
+  /// файлов, чтобы смена директории в настройках применялась сразу.
+  fn temp_dir(&self) -> PathBuf {
+    temp_storage::temp_root().join(temp_storage::PREVIEW_DIR)
+  }
+
+  /// Аудиофайл для превью проекта без видео: клип, активный в момент `timestamp`,
+  /// иначе первый аудио клип. `None`, если в проекте есть видео или нет аудио.
+  fn audio_only_preview_source(project: &ProjectSchema, timestamp: f64) -> Option<String> {
+    let has_video = !project.settings.output.format.is_audio_only()
+      && project.tracks.iter().any(|track| {
+        track.enabled && track.track_type == TrackType::Video && !track.clips.is_empty()
+      });
+    if has_video {
+      return None;
+    }
+
+    let audio_clips: Vec<&Clip> = project
+      .tracks
+      .iter()
+      .filter(|track| track.enabled && track.track_type == TrackType::Audio)
+      .flat_map(|track| track.clips.iter())
+      .filter(|clip| matches!(clip.source, ClipSource::File(_)))
+      .collect();
+
+    let clip = audio_clips
+      .iter()
+      .find(|clip| clip.start_time <= timestamp && timestamp <= clip.end_time)
+      .or_else(|| audio_clips.first())?;
+
+    match &clip.source {
+      ClipSource::File(path) => Some(path.clone()),
+      _ => None,
     }
   }
 
@@ -159,7 +201,9 @@ impl PreviewServiceImpl {
     thumbnail_size: (u32, u32),
   ) -> Result<Vec<u8>> {
     // Создаем временную директорию для композиции
-    let temp_dir_path = std::env::temp_dir().join(format!("storyboard_{}", uuid::Uuid::new_v4()));
+    let temp_dir_path = self
+      .temp_dir()
+      .join(format!("storyboard_{}", uuid::Uuid::new_v4()));
     std::fs::create_dir_all(&temp_dir_path).map_err(|e| {
       VideoCompilerError::IoError(format!("Не удалось создать временную директорию: {e}"))
     })?;
@@ -259,7 +303,7 @@ impl Service for PreviewServiceImpl {
     log::info!("Инициализация сервиса превью");
 
     // Создаем временную директорию
-    tokio::fs::create_dir_all(&self.temp_dir)
+    tokio::fs::create_dir_all(self.temp_dir())
       .await
       .map_err(|e| VideoCompilerError::IoError(e.to_string()))?;
 
@@ -279,8 +323,9 @@ impl Service for PreviewServiceImpl {
     self.preview_cache.write().await.clear();
 
     // Удаляем временные файлы
-    if self.temp_dir.exists() {
-      let _ = tokio::fs::remove_dir_all(&self.temp_dir).await;
+    let temp_dir = self.temp_dir();
+    if temp_dir.exists() {
+      let _ = tokio::fs::remove_dir_all(&temp_dir).await;
     }
 
     Ok(())
@@ -295,6 +340,8 @@ impl PreviewService for PreviewServiceImpl {
     timestamp: f64,
     resolution: Option<(u32, u32)>,
   ) -> Result<Vec<u8>> {
+    let _interactive = priority::begin_interactive();
+
     // Проверяем корректность timestamp
     if timestamp < 0.0 {
       return Err(VideoCompilerError::InvalidParameter(format!(
@@ -331,28 +378,27 @@ impl PreviewService for PreviewServiceImpl {
     let cache = Arc::new(RwLock::new(crate::video_compiler::cache::RenderCache::new()));
     let generator = PreviewGenerator::new(cache);
 
-    let preview_data = generator
-      .generate_preview(
-        std::path::Path::new(video_path),
-        timestamp,
-        resolution,
-        Some(85),
-      )
-      .await
-      .map_err(|e| {
-        log::error!("Ошибка генерации превью для {video_path:?}: {e}");
-        match e {
-          VideoCompilerError::FFmpegError { .. } => VideoCompilerError::PreviewError {
-            timestamp,
-            reason: format!("FFmpeg не смог сгенерировать кадр: {e}"),
-          },
-          VideoCompilerError::TimeoutError { .. } => VideoCompilerError::PreviewError {
-            timestamp,
-            reason: "Превышено время ожидания генерации превью".to_string(),
-          },
-          _ => e,
-        }
-      })?;
+    let preview_data = with_policy(
+      "preview-service",
+      "generate_frame_preview",
+      resolve_policy("preview-service", "generate_frame_preview"),
+      || generator.generate_preview(video_path, timestamp, resolution, Some(85)),
+    )
+    .await
+    .map_err(|e| {
+      log::error!("Ошибка генерации превью для {video_path:?}: {e}");
+      match e {
+        VideoCompilerError::FFmpegError { .. } => VideoCompilerError::PreviewError {
+          timestamp,
+          reason: format!("FFmpeg не смог сгенерировать кадр: {e}"),
+        },
+        VideoCompilerError::TimeoutError { .. } => VideoCompilerError::PreviewError {
+          timestamp,
+          reason: "Превышено время ожидания генерации превью".to_string(),
+        },
+        _ => e,
+      }
+    })?;
 
     // Проверяем размер полученных данных
     if preview_data.is_empty() {
@@ -387,7 +433,7 @@ impl PreviewService for PreviewServiceImpl {
   ) -> Result<Vec<Vec<u8>>> {
     // Создаем временную директорию для миниатюр
     let temp_dir = self
-      .temp_dir
+      .temp_dir()
       .join(format!("thumbs_{}", uuid::Uuid::new_v4()));
     tokio::fs::create_dir_all(&temp_dir)
       .await
@@ -494,6 +540,8 @@ impl PreviewService for PreviewServiceImpl {
     height: u32,
     color: &str,
   ) -> Result<Vec<u8>> {
+    let _interactive = priority::begin_interactive();
+
     // Проверяем кэш
     let cache_key = format!(
       "waveform:{}:{}x{}:{}",
@@ -509,7 +557,7 @@ impl PreviewService for PreviewServiceImpl {
 
     // Генерируем waveform с помощью FFmpeg
     let output_path = self
-      .temp_dir
+      .temp_dir()
       .join(format!("waveform_{}.png", uuid::Uuid::new_v4()));
 
     // Используем FFmpegBuilder для создания команды
@@ -606,6 +654,11 @@ impl PreviewService for PreviewServiceImpl {
   async fn cache_preview(&self, key: &str, result: &PreviewResult) -> Result<()> {
     let mut cache = self.preview_cache.write().await;
 
+    // Прогрев не вытесняет превью, созданные интерактивными запросами
+    if priority::is_background() && cache.len() > 1000 && !cache.contains_key(key) {
+      return Ok(());
+    }
+
     // Ограничиваем размер кэша
     if cache.len() > 1000 {
       // Удаляем старые записи (простая стратегия FIFO)
@@ -641,6 +694,37 @@ impl PreviewService for PreviewServiceImpl {
       quality: 85,
     });
 
+    // У проекта без видео вместо кадра отдаем изображение waveform
+    if let Some(audio_path) = Self::audio_only_preview_source(project, timestamp) {
+      if !Path::new(&audio_path).exists() {
+        return Err(VideoCompilerError::MediaFileError {
+          path: audio_path,
+          reason: "Файл не найден".to_string(),
+        });
+      }
+
+      let (width, height) = (opts.width.unwrap_or(1280), opts.height.unwrap_or(720));
+      let waveform = self
+        .generate_waveform(
+          Path::new(&audio_path),
+          width,
+          height,
+          WAVEFORM_PREVIEW_COLOR,
+        )
+        .await?;
+
+      if let Some(parent) = Path::new(output_path).parent() {
+        tokio::fs::create_dir_all(parent).await.map_err(|e| {
+          VideoCompilerError::IoError(format!("Не удалось создать директорию: {e}"))
+        })?;
+      }
+
+      tokio::fs::write(output_path, waveform)
+        .await
+        .map_err(|e| VideoCompilerError::IoError(format!("Не удалось сохранить превью: {e}")))?;
+      return Ok(());
+    }
+
     // Находим первый клип в таймлайне, который активен в указанное время
     let mut source_file = None;
     let mut clip_offset = 0.0;
@@ -22,10 +22,12 @@ fn create_test_project_with_clips() -> ProjectSchema {
     track_type: crate::video_compiler::schema::timeline::TrackType::Video,
     enabled: true,
     locked: false,
+    hidden: false,
     clips: vec![],
     effects: vec![],
     volume: 1.0,
     filters: vec![],
+    ducking: None,
   };
 
   // Добавляем клипы
@@ -46,6 +48,18 @@ fn create_test_project_with_clips() -> ProjectSchema {
     crop: None,
     transform: None,
     audio_track_index: None,
+    audio_stream_index: None,
+    source_rotation: 0,
+    color_label: None,
+    notes: None,
+    tags: Vec::new(),
+    fade_in: None,
+    fade_out: None,
+    link_group_id: None,
+    source_timecode: None,
+    volume: 1.0,
+    external_audio: None,
+    locked: false,
     properties: Default::default(),
   });
 
@@ -66,6 +80,18 @@ fn create_test_project_with_clips() -> ProjectSchema {
     crop: None,
     transform: None,
     audio_track_index: None,
+    audio_stream_index: None,
+    source_rotation: 0,
+    color_label: None,
+    notes: None,
+    tags: Vec::new(),
+    fade_in: None,
+    fade_out: None,
+    link_group_id: None,
+    source_timecode: None,
+    volume: 1.0,
+    external_audio: None,
+    locked: false,
     properties: Default::default(),
   });
 
@@ -91,7 +117,7 @@ mod initialization_tests {
     let service = PreviewServiceImpl::new(ffmpeg_service);
 
     service.initialize().await.unwrap();
-    assert!(service.temp_dir.exists());
+    assert!(service.temp_dir().exists());
   }
 
   #[tokio::test]
@@ -277,6 +303,58 @@ mod frame_generation_tests {
     assert!(result.is_ok());
     assert!(output_path.exists());
   }
+
+  #[tokio::test]
+  async fn test_generate_frame_audio_only_project_uses_waveform() {
+    use crate::video_compiler::schema::timeline::TrackType;
+
+    let ffmpeg_service = create_mock_ffmpeg_service();
+    let service = PreviewServiceImpl::new(ffmpeg_service);
+    service.initialize().await.unwrap();
+
+    let temp_dir = TempDir::new().unwrap();
+    let audio_path = temp_dir.path().join("podcast.wav");
+    tokio::fs::write(&audio_path, b"mock audio data")
+      .await
+      .unwrap();
+
+    let mut project = ProjectSchema::new("podcast".to_string());
+    let mut track = Track::new(TrackType::Audio, "Voice".to_string());
+    track.clips.push(Clip::new(audio_path.clone(), 0.0, 10.0));
+    project.tracks.push(track);
+
+    // Waveform берется из кэша, FFmpeg не запускается
+    let cache_key = format!(
+      "waveform:{}:{}x{}:{}",
+      audio_path.to_string_lossy(),
+      640,
+      360,
+      "#00ff00"
+    );
+    let waveform = PreviewResult {
+      preview_type: PreviewType::Waveform,
+      data: vec![0x89, 0x50, 0x4E, 0x47],
+      format: "png".to_string(),
+      resolution: (640, 360),
+      timestamp: None,
+    };
+    service.cache_preview(&cache_key, &waveform).await.unwrap();
+
+    let output_path = temp_dir.path().join("preview.png");
+    let options = crate::video_compiler::core::preview::PreviewOptions {
+      width: Some(640),
+      height: Some(360),
+      format: "png".to_string(),
+      quality: 85,
+    };
+    service
+      .generate_frame(&project, 3.0, output_path.to_str().unwrap(), Some(options))
+      .await
+      .unwrap();
+
+    let data = tokio::fs::read(&output_path).await.unwrap();
+    assert_eq!(data, waveform.data);
+  }
 }
 
 #[cfg(test)]
@@ -0,0 +1,498 @@
+//! Внешние ресурсы проекта (шрифты, изображения шаблонов, файлы эффектов,
+//! водяной знак, внешнее аудио), которых нет на текущей машине
+
+use crate::video_compiler::schema::{EffectParameter, ProjectSchema};
+use crate::video_compiler::services::media_relink::{all_tracks, all_tracks_mut};
+use crate::video_compiler::services::project_template::find_missing_fonts;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+/// ID ссылки на водяной знак в настройках экспорта
+pub const WATERMARK_REFERENCE: &str = "export.watermark";
+
+/// Вид внешнего ресурса
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AssetKind {
+  /// Семейство шрифта субтитров или стильного шаблона
+  Font,
+  /// Изображение элемента стильного шаблона
+  StyleTemplateImage,
+  /// Файл в параметре эффекта (например, LUT)
+  EffectFile,
+  /// Изображение водяного знака
+  Watermark,
+  /// Внешний аудиофайл клипа
+  ExternalAudio,
+}
+
+impl AssetKind {
+  /// Описание вида ресурса для сообщений
+  pub fn label(self) -> &'static str {
+    match self {
+      Self::Font => "шрифт",
+      Self::StyleTemplateImage => "изображение шаблона",
+      Self::EffectFile => "файл эффекта",
+      Self::Watermark => "водяной знак",
+      Self::ExternalAudio => "внешнее аудио",
+    }
+  }
+}
+
+/// Ссылка на ресурс: семейство шрифта или путь к файлу
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetRef {
+  pub kind: AssetKind,
+  pub value: String,
+}
+
+/// Ресурс, который не удалось найти на текущей машине
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MissingAsset {
+  #[serde(flatten)]
+  pub asset: AssetRef,
+  /// ID элементов проекта, ссылающихся на ресурс (субтитры, элементы
+  /// шаблонов, эффекты, клипы или [`WATERMARK_REFERENCE`]), отсортированы
+  pub referenced_by: Vec<String>,
+}
+
+impl MissingAsset {
+  /// Предупреждение для пользователя
+  pub fn message(&self) -> String {
+    format!(
+      "Не найден {}: '{}' (используется: {})",
+      self.asset.kind.label(),
+      self.asset.value,
+      self.referenced_by.join(", ")
+    )
+  }
+}
+
+/// Отчет о ресурсах проекта
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProjectAssetReport {
+  /// Отсутствующие ресурсы, упорядочены по виду и значению
+  pub missing: Vec<MissingAsset>,
+}
+
+impl ProjectAssetReport {
+  /// Все ресурсы найдены
+  pub fn is_empty(&self) -> bool {
+    self.missing.is_empty()
+  }
+
+  /// Предупреждения по каждому отсутствующему ресурсу
+  pub fn warnings(&self) -> Vec<String> {
+    self.missing.iter().map(MissingAsset::message).collect()
+  }
+}
+
+/// Ссылки проекта на внешние ресурсы: (вид, значение) -> ID элементов
+fn collect_references(project: &ProjectSchema) -> BTreeMap<(AssetKind, String), BTreeSet<String>> {
+  let mut references: BTreeMap<(AssetKind, String), BTreeSet<String>> = BTreeMap::new();
+  let mut add = |kind: AssetKind, value: &str, id: &str| {
+    if !value.is_empty() {
+      references
+        .entry((kind, value.to_string()))
+        .or_default()
+        .insert(id.to_string());
+    }
+  };
+
+  for subtitle in &project.subtitles {
+    add(AssetKind::Font, &subtitle.style.font_family, &subtitle.id);
+    add(AssetKind::Font, &subtitle.font_family, &subtitle.id);
+  }
+  for template in &project.style_templates {
+    for element in &template.elements {
+      let properties = &element.properties;
+      if let Some(family) = &properties.font_family {
+        add(AssetKind::Font, family, &element.id);
+      }
+      if let Some(family) = element.style.as_ref().and_then(|s| s.font_family.as_ref()) {
+        add(AssetKind::Font, family, &element.id);
+      }
+      if let Some(image) = &properties.image_path {
+        add(AssetKind::StyleTemplateImage, image, &element.id);
+      }
+    }
+  }
+  for effect in &project.effects {
+    for parameter in effect.parameters.values() {
+      if let EffectParameter::FilePath(path) = parameter {
+        add(AssetKind::EffectFile, &path.to_string_lossy(), &effect.id);
+      }
+    }
+  }
+  if let Some(watermark) = &project.settings.export.watermark {
+    add(
+      AssetKind::Watermark,
+      &watermark.image_path,
+      WATERMARK_REFERENCE,
+    );
+  }
+  for track in all_tracks(project) {
+    for clip in &track.clips {
+      if let Some(audio) = clip.active_external_audio() {
+        add(AssetKind::ExternalAudio, &audio.path, &clip.id);
+      }
+    }
+  }
+
+  references
+}
+
+/// Найти внешние ресурсы проекта, которых нет на текущей машине.
+///
+/// Шрифты ищутся в `font_dirs` (см. `system_font_dirs`), остальные ресурсы
+/// проверяются по наличию файла.
+pub fn check_project_assets(project: &ProjectSchema, font_dirs: &[PathBuf]) -> ProjectAssetReport {
+  let references = collect_references(project);
+
+  let families: Vec<String> = references
+    .keys()
+    .filter(|(kind, _)| *kind == AssetKind::Font)
+    .map(|(_, family)| family.clone())
+    .collect();
+  let missing_fonts = find_missing_fonts(&families, font_dirs);
+
+  let missing = references
+    .into_iter()
+    .filter(|((kind, value), _)| match kind {
+      AssetKind::Font => missing_fonts.contains(value),
+      _ => !Path::new(value).exists(),
+    })
+    .map(|((kind, value), ids)| MissingAsset {
+      asset: AssetRef { kind, value },
+      referenced_by: ids.into_iter().collect(),
+    })
+    .collect();
+
+  ProjectAssetReport { missing }
+}
+
+/// Заменить все ссылки на ресурс. Для шрифтов `replacement` - имя семейства,
+/// для остальных ресурсов - путь к файлу. Возвращает количество замен.
+pub fn substitute_project_asset(
+  project: &mut ProjectSchema,
+  asset: &AssetRef,
+  replacement: &str,
+) -> usize {
+  let mut replaced = 0;
+  let mut replace = |value: &mut String| {
+    if *value == asset.value {
+      *value = replacement.to_string();
+      replaced += 1;
+    }
+  };
+
+  match asset.kind {
+    AssetKind::Font => {
+      for subtitle in &mut project.subtitles {
+        replace(&mut subtitle.style.font_family);
+        replace(&mut subtitle.font_family);
+      }
+      for element in project
+        .style_templates
+        .iter_mut()
+        .flat_map(|template| &mut template.elements)
+      {
+        if let Some(family) = &mut element.properties.font_family {
+          replace(family);
+        }
+        if let Some(family) = element.style.as_mut().and_then(|s| s.font_family.as_mut()) {
+          replace(family);
+        }
+      }
+    }
+    AssetKind::StyleTemplateImage => {
+      for element in project
+        .style_templates
+        .iter_mut()
+        .flat_map(|template| &mut template.elements)
+      {
+        if let Some(image) = &mut element.properties.image_path {
+          replace(image);
+        }
+      }
+    }
+    AssetKind::EffectFile => {
+      for effect in &mut project.effects {
+        for parameter in effect.parameters.values_mut() {
+          if let EffectParameter::FilePath(path) = parameter {
+            if path.to_string_lossy() == asset.value {
+              *path = PathBuf::from(replacement);
+              replaced += 1;
+            }
+          }
+        }
+      }
+    }
+    AssetKind::Watermark => {
+      if let Some(watermark) = &mut project.settings.export.watermark {
+        replace(&mut watermark.image_path);
+      }
+    }
+    AssetKind::ExternalAudio => {
+      for track in all_tracks_mut(project) {
+        for clip in &mut track.clips {
+          if let Some(audio) = &mut clip.external_audio {
+            replace(&mut audio.path);
+          }
+        }
+      }
+    }
+  }
+
+  replaced
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::video_compiler::schema::{
+    Clip, Effect, EffectType, ElementStyle, ElementTiming, ExternalAudio, Position2D, Size2D,
+    StyleElementProperties, StyleElementType, StyleTemplate, StyleTemplateCategory,
+    StyleTemplateElement, StyleTemplateStyle, Subtitle, Track, TrackType, WatermarkConfig,
+    WatermarkPosition,
+  };
+  use std::fs;
+
+  fn subtitle(id: &str, family: &str) -> Subtitle {
+    let mut subtitle = Subtitle::new("text".to_string(), 0.0, 1.0);
+    subtitle.id = id.to_string();
+    subtitle.font_family = family.to_string();
+    subtitle.style.font_family = family.to_string();
+    subtitle
+  }
+
+  fn element(
+    id: &str,
+    element_type: StyleElementType,
+    properties: StyleElementProperties,
+    style_family: Option<&str>,
+  ) -> StyleTemplateElement {
+    StyleTemplateElement {
+      id: id.to_string(),
+      element_type,
+      name: id.to_string(),
+      position: Position2D { x: 0.0, y: 0.0 },
+      size: Size2D {
+        width: 100.0,
+        height: 100.0,
+      },
+      timing: ElementTiming {
+        in_time: 0.0,
+        out_time: 2.0,
+        duration: 2.0,
+      },
+      properties,
+      animations: vec![],
+      content: String::new(),
+      style: style_family.map(|family| ElementStyle {
+        font_family: Some(family.to_string()),
+        font_size: None,
+        color: None,
+        background_color: None,
+      }),
+    }
+  }
+
+  fn lut_effect(id: &str, path: &Path) -> Effect {
+    let mut effect = Effect::new(EffectType::Vintage, id.to_string());
+    effect.id = id.to_string();
+    effect.parameters.insert(
+      "lut_file".to_string(),
+      EffectParameter::FilePath(path.to_path_buf()),
+    );
+    effect
+  }
+
+  /// Проект, открытый на машине без части ресурсов автора. В `dir` есть
+  /// шрифт Inter, `present.cube` и `present.png`.
+  fn shared_project(dir: &Path) -> ProjectSchema {
+    let fonts = dir.join("fonts");
+    fs::create_dir_all(&fonts).unwrap();
+    fs::write(fonts.join("Inter-Regular.ttf"), b"font").unwrap();
+    fs::write(dir.join("present.cube"), b"lut").unwrap();
+    fs::write(dir.join("present.png"), b"png").unwrap();
+    let path = |name: &str| dir.join(name).to_string_lossy().to_string();
+
+    let mut project = ProjectSchema::new("Shared".to_string());
+    project.subtitles = vec![
+      subtitle("sub-1", "Brand Sans"),
+      subtitle("sub-2", "Inter"),
+      subtitle("sub-3", "Brand Sans"),
+    ];
+
+    let mut template = StyleTemplate::new(
+      "Intro".to_string(),
+      StyleTemplateCategory::Intro,
+      StyleTemplateStyle::Corporate,
+      2.0,
+    );
+    template.elements = vec![
+      element(
+        "title",
+        StyleElementType::Text,
+        StyleElementProperties {
+          font_family: Some("Display Serif".to_string()),
+          ..Default::default()
+        },
+        Some("Brand Sans"),
+      ),
+      element(
+        "logo",
+        StyleElementType::Image,
+        StyleElementProperties {
+          image_path: Some(path("missing_logo.png")),
+          ..Default::default()
+        },
+        None,
+      ),
+      element(
+        "badge",
+        StyleElementType::Image,
+        StyleElementProperties {
+          image_path: Some(path("present.png")),
+          ..Default::default()
+        },
+        None,
+      ),
+    ];
+    project.style_templates.push(template);
+
+    project.effects = vec![
+      lut_effect("grade-a", &dir.join("film.cube")),
+      lut_effect("grade-b", &dir.join("film.cube")),
+      lut_effect("grade-ok", &dir.join("present.cube")),
+    ];
+
+    project.settings.export.watermark = Some(WatermarkConfig {
+      image_path: path("watermark.png"),
+      position: WatermarkPosition::default(),
+      scale: 0.1,
+      opacity: 0.8,
+      margin_px: 16,
+      start_time: None,
+      end_time: None,
+      show_in_preview: false,
+    });
+
+    let mut track = Track::new(TrackType::Video, "Video".to_string());
+    let mut with_lav = Clip::new(dir.join("camera.mp4"), 0.0, 5.0);
+    with_lav.id = "clip-lav".to_string();
+    with_lav.external_audio = Some(ExternalAudio::new(path("lav.wav"), 0.0));
+    let mut fallback = Clip::new(dir.join("camera.mp4"), 5.0, 5.0);
+    fallback.id = "clip-fallback".to_string();
+    fallback.external_audio = Some(ExternalAudio {
+      use_embedded: true,
+      ..ExternalAudio::new(path("boom.wav"), 0.0)
+    });
+    track.clips = vec![with_lav, fallback];
+    project.tracks.push(track);
+
+    project
+  }
+
+  fn missing(kind: AssetKind, value: String, ids: &[&str]) -> MissingAsset {
+    MissingAsset {
+      asset: AssetRef { kind, value },
+      referenced_by: ids.iter().map(|id| id.to_string()).collect(),
+    }
+  }
+
+  #[test]
+  fn test_check_project_assets_reports_broken_references() {
+    let dir = tempfile::tempdir().unwrap();
+    let project = shared_project(dir.path());
+    let path = |name: &str| dir.path().join(name).to_string_lossy().to_string();
+
+    let report = check_project_assets(&project, &[dir.path().join("fonts")]);
+
+    assert_eq!(
+      report.missing,
+      vec![
+        missing(
+          AssetKind::Font,
+          "Brand Sans".to_string(),
+          &["sub-1", "sub-3", "title"]
+        ),
+        missing(AssetKind::Font, "Display Serif".to_string(), &["title"]),
+        missing(
+          AssetKind::StyleTemplateImage,
+          path("missing_logo.png"),
+          &["logo"]
+        ),
+        missing(
+          AssetKind::EffectFile,
+          path("film.cube"),
+          &["grade-a", "grade-b"]
+        ),
+        missing(
+          AssetKind::Watermark,
+          path("watermark.png"),
+          &[WATERMARK_REFERENCE]
+        ),
+        missing(AssetKind::ExternalAudio, path("lav.wav"), &["clip-lav"]),
+      ]
+    );
+    assert_eq!(
+      report.warnings()[0],
+      "Не найден шрифт: 'Brand Sans' (используется: sub-1, sub-3, title)"
+    );
+  }
+
+  #[test]
+  fn test_substitute_project_asset_rewrites_all_references() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut project = shared_project(dir.path());
+    let fonts = [dir.path().join("fonts")];
+
+    let font = AssetRef {
+      kind: AssetKind::Font,
+      value: "Brand Sans".to_string(),
+    };
+    // Две реплики по два поля и стиль элемента шаблона
+    assert_eq!(substitute_project_asset(&mut project, &font, "Inter"), 5);
+
+    let lut = AssetRef {
+      kind: AssetKind::EffectFile,
+      value: dir.path().join("film.cube").to_string_lossy().to_string(),
+    };
+    let replacement = dir.path().join("present.cube");
+    assert_eq!(
+      substitute_project_asset(&mut project, &lut, &replacement.to_string_lossy()),
+      2
+    );
+
+    let report = check_project_assets(&project, &fonts);
+    let kinds: Vec<(AssetKind, &str)> = report
+      .missing
+      .iter()
+      .map(|m| (m.asset.kind, m.asset.value.as_str()))
+      .collect();
+    assert!(!kinds.contains(&(AssetKind::Font, "Brand Sans")));
+    assert!(!kinds.iter().any(|(kind, _)| *kind == AssetKind::EffectFile));
+    assert_eq!(report.missing.len(), 4);
+    assert!(matches!(
+      &project.effects[1].parameters["lut_file"],
+      EffectParameter::FilePath(path) if path == &replacement
+    ));
+  }
+
+  #[test]
+  fn test_substitute_unknown_asset_changes_nothing() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut project = shared_project(dir.path());
+    let asset = AssetRef {
+      kind: AssetKind::Watermark,
+      value: "/elsewhere/logo.png".to_string(),
+    };
+    assert_eq!(
+      substitute_project_asset(&mut project, &asset, "/new/logo.png"),
+      0
+    );
+  }
+}
@@ -0,0 +1,1069 @@
+//! Упаковка проекта в самодостаточный архив для совместной работы
+//!
+//! Архив содержит `manifest.json` (пути внутри архива, размеры и SHA-256
+//! файлов), `project.json` - копию схемы с путями относительно архива, и сами
+//! файлы в `media/`, `proxies/` и `caches/`. Файлы передаются в zip потоково,
+//! без загрузки целиком в память. При распаковке пути схемы снова становятся
+//! абсолютными, а несовпадения хешей возвращаются по каждому файлу.
+
+use crate::video_compiler::{
+  core::error::{Result, VideoCompilerError},
+  core::segment_cache,
+  schema::{ClipSource, ProjectSchema, WATERMARK_IMAGE_EXTENSIONS},
+  services::media_relink::{all_tracks, all_tracks_mut},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+
+/// Имя манифеста в архиве
+pub const PACKAGE_MANIFEST_FILE: &str = "manifest.json";
+
+/// Имя схемы проекта в архиве
+pub const PACKAGE_PROJECT_FILE: &str = "project.json";
+
+/// Версия формата архива
+pub const PACKAGE_FORMAT_VERSION: u32 = 1;
+
+/// Запас по краям используемого фрагмента при обрезке медиа (секунды)
+pub const DEFAULT_TRIM_HANDLE_SECS: f64 = 2.0;
+
+/// Размер буфера потокового копирования
+const COPY_BUFFER_SIZE: usize = 1024 * 1024;
+
+/// Событие прогресса отправляется не чаще, чем раз в столько байт
+const PROGRESS_BYTES_STEP: u64 = 16 * 1024 * 1024;
+
+/// Получатель событий прогресса упаковки и распаковки
+pub type PackageProgressSink = Arc<dyn Fn(PackageProgress) + Send + Sync>;
+
+/// Способ обрезки неиспользуемых частей медиа
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrimMode {
+  /// Перекодирование: фрагмент начинается точно с запрошенного момента
+  #[default]
+  Reencode,
+  /// Копирование потоков: начало сдвигается к предшествующему ключевому кадру
+  StreamCopy,
+}
+
+/// Опции упаковки проекта
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageOptions {
+  /// Добавить прокси-файлы медиа
+  #[serde(default)]
+  pub include_proxies: bool,
+  /// Добавить кэш сегментов рендера проекта
+  #[serde(default)]
+  pub include_caches: bool,
+  /// Оставить от видео и аудио только используемые фрагменты с запасом
+  #[serde(default)]
+  pub trim_unused_media: bool,
+  #[serde(default)]
+  pub trim_mode: TrimMode,
+  /// Запас по краям фрагмента (секунды)
+  #[serde(default = "default_handle_secs")]
+  pub handle_secs: f64,
+}
+
+fn default_handle_secs() -> f64 {
+  DEFAULT_TRIM_HANDLE_SECS
+}
+
+impl Default for PackageOptions {
+  fn default() -> Self {
+    Self {
+      include_proxies: false,
+      include_caches: false,
+      trim_unused_media: false,
+      trim_mode: TrimMode::default(),
+      handle_secs: DEFAULT_TRIM_HANDLE_SECS,
+    }
+  }
+}
+
+/// Окружение упаковки: FFmpeg и директории приложения
+#[derive(Debug, Clone)]
+pub struct PackageContext {
+  /// Путь к FFmpeg для обрезки медиа
+  pub ffmpeg_path: String,
+  /// Корень временных файлов (обрезанные медиа, кэш сегментов)
+  pub temp_root: PathBuf,
+  /// Директория прокси-файлов
+  pub proxy_dir: Option<PathBuf>,
+}
+
+/// Тип файла в архиве
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PackageEntryKind {
+  /// Файл, на который ссылается схема
+  Media,
+  /// Прокси медиафайла
+  Proxy,
+  /// Сегмент кэша рендера
+  Cache,
+}
+
+/// Фрагмент исходного файла, оставленный при обрезке (секунды)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TrimRange {
+  pub start: f64,
+  pub end: f64,
+}
+
+/// Файл в архиве
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PackageEntry {
+  /// Путь внутри архива
+  pub archive_path: String,
+  pub kind: PackageEntryKind,
+  /// Путь у автора проекта (для прокси - путь исходного медиафайла)
+  pub original_path: String,
+  /// Размер в байтах
+  pub size: u64,
+  /// SHA-256 содержимого
+  pub sha256: String,
+  /// Оставленный фрагмент, если медиа обрезано
+  pub trim: Option<TrimRange>,
+}
+
+/// Манифест архива
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PackageManifest {
+  pub format_version: u32,
+  pub project_name: String,
+  pub created_at: DateTime<Utc>,
+  pub entries: Vec<PackageEntry>,
+}
+
+/// Этап упаковки или распаковки
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PackageStage {
+  /// Обрезка медиа
+  Trimming,
+  /// Запись файлов в архив
+  Archiving,
+  /// Извлечение и проверка файлов
+  Extracting,
+}
+
+/// Прогресс упаковки или распаковки
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageProgress {
+  pub stage: PackageStage,
+  /// Текущий файл (путь внутри архива)
+  pub current_file: Option<String>,
+  pub files_done: usize,
+  pub files_total: usize,
+  pub bytes_done: u64,
+  pub bytes_total: u64,
+}
+
+/// Результат упаковки
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageSummary {
+  /// Путь к архиву
+  pub output_path: String,
+  pub manifest: PackageManifest,
+  /// Файлы, на которые ссылается схема, но которых нет на диске
+  pub missing: Vec<String>,
+}
+
+/// Файл архива, не прошедший проверку при распаковке
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PackageFileIssue {
+  /// Путь внутри архива
+  pub archive_path: String,
+  /// SHA-256 из манифеста
+  pub expected_sha256: String,
+  /// Фактический SHA-256 (`None`, если файла нет в архиве)
+  pub actual_sha256: Option<String>,
+}
+
+/// Результат распаковки
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnpackedProject {
+  /// Схема с абсолютными путями, готовая к открытию
+  pub project: ProjectSchema,
+  /// Путь к сохраненной схеме в директории распаковки
+  pub project_path: String,
+  pub manifest: PackageManifest,
+  /// Файлы с несовпадающим хешем или отсутствующие в архиве
+  pub issues: Vec<PackageFileIssue>,
+}
+
+/// Файл, запланированный к упаковке
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedPackageFile {
+  /// Файл на диске
+  pub source_path: PathBuf,
+  pub archive_path: String,
+  pub kind: PackageEntryKind,
+  pub original_path: String,
+  /// Запрошенный фрагмент для обрезки
+  pub trim: Option<TrimRange>,
+}
+
+/// План упаковки
+#[derive(Debug, Clone, Default)]
+pub struct PackagePlan {
+  pub files: Vec<PlannedPackageFile>,
+  /// Отсутствующие на диске файлы схемы
+  pub missing: Vec<String>,
+}
+
+/// Обойти все пути к внешним файлам схемы: исходники клипов, внешнее аудио,
+/// водяной знак и изображения стильных шаблонов
+pub fn for_each_media_path_mut(project: &mut ProjectSchema, mut visit: impl FnMut(&mut String)) {
+  for track in all_tracks_mut(project) {
+    for clip in &mut track.clips {
+      if let ClipSource::File(path) = &mut clip.source {
+        visit(path);
+      }
+      if let Some(audio) = &mut clip.external_audio {
+        visit(&mut audio.path);
+      }
+    }
+  }
+
+  if let Some(watermark) = &mut project.settings.export.watermark {
+    visit(&mut watermark.image_path);
+  }
+
+  for template in &mut project.style_templates {
+    for element in &mut template.elements {
+      if let Some(path) = &mut element.properties.image_path {
+        visit(path);
+      }
+    }
+  }
+}
+
+/// Пути ко всем внешним файлам схемы (без повторов, отсортированы)
+pub fn media_paths(project: &ProjectSchema) -> BTreeSet<String> {
+  let mut paths = BTreeSet::new();
+  for_each_media_path_mut(&mut project.clone(), |path| {
+    if !path.is_empty() {
+      paths.insert(path.clone());
+    }
+  });
+  paths
+}
+
+/// Используемые фрагменты файлов, которые можно обрезать.
+///
+/// Обрезаются только видео и аудио, на которые ссылаются исключительно
+/// исходники клипов: внешнее аудио, водяной знак и изображения шаблонов
+/// копируются целиком.
+fn trimmable_ranges(project: &ProjectSchema, handle_secs: f64) -> HashMap<String, TrimRange> {
+  let mut ranges: HashMap<String, TrimRange> = HashMap::new();
+  let mut whole_files = HashSet::new();
+
+  for track in all_tracks(project) {
+    for clip in &track.clips {
+      if let ClipSource::File(path) = &clip.source {
+        let range = ranges.entry(path.clone()).or_insert(TrimRange {
+          start: clip.source_start,
+          end: clip.source_end,
+        });
+        range.start = range.start.min(clip.source_start);
+        range.end = range.end.max(clip.source_end);
+      }
+      if let Some(audio) = &clip.external_audio {
+        whole_files.insert(audio.path.clone());
+      }
+    }
+  }
+  if let Some(watermark) = &project.settings.export.watermark {
+    whole_files.insert(watermark.image_path.clone());
+  }
+  for template in &project.style_templates {
+    for element in &template.elements {
+      whole_files.extend(element.properties.image_path.clone());
+    }
+  }
+
+  ranges.retain(|path, range| {
+    !whole_files.contains(path) && !is_still_image(Path::new(path)) && range.end > range.start
+  });
+  for range in ranges.values_mut() {
+    range.start = (range.start - handle_secs).max(0.0);
+    range.end += handle_secs;
+  }
+  ranges
+}
+
+/// Файл - неподвижное изображение
+fn is_still_image(path: &Path) -> bool {
+  path
+    .extension()
+    .and_then(|extension| extension.to_str())
+    .is_some_and(|extension| {
+      WATERMARK_IMAGE_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str())
+    })
+}
+
+/// Имя файла для архива: только допустимые символы
+fn archive_file_name(path: &Path) -> String {
+  let name = path
+    .file_name()
+    .map(|name| name.to_string_lossy().to_string())
+    .unwrap_or_else(|| "file".to_string());
+  name
+    .chars()
+    .map(|c| {
+      if c.is_alphanumeric() || matches!(c, '.' | '-' | '_') {
+        c
+      } else {
+        '_'
+      }
+    })
+    .collect()
+}
+
+/// Составить список файлов архива
+pub fn plan_package(
+  project: &ProjectSchema,
+  options: &PackageOptions,
+  context: &PackageContext,
+) -> PackagePlan {
+  let ranges = if options.trim_unused_media {
+    trimmable_ranges(project, options.handle_secs.max(0.0))
+  } else {
+    HashMap::new()
+  };
+
+  let mut plan = PackagePlan::default();
+  let mut present = Vec::new();
+  for path in media_paths(project) {
+    if Path::new(&path).is_file() {
+      present.push(path);
+    } else {
+      plan.missing.push(path);
+    }
+  }
+
+  for (index, path) in present.iter().enumerate() {
+    let source_path = PathBuf::from(path);
+    plan.files.push(PlannedPackageFile {
+      archive_path: format!("media/{:03}_{}", index + 1, archive_file_name(&source_path)),
+      source_path,
+      kind: PackageEntryKind::Media,
+      original_path: path.clone(),
+      trim: ranges.get(path).copied(),
+    });
+  }
+
+  if options.include_proxies {
+    if let Some(proxy_dir) = &context.proxy_dir {
+      plan.files.extend(find_proxies(proxy_dir, &present));
+    }
+  }
+
+  if options.include_caches {
+    let segment_dir = segment_cache::segment_cache_dir(&context.temp_root);
+    plan
+      .files
+      .extend(find_cached_segments(&segment_dir, project));
+  }
+
+  plan
+}
+
+/// Прокси медиафайлов: файлы директории прокси с именем `<имя исходника>`
+/// или `<имя исходника>_<суффикс>` (без расширения)
+fn find_proxies(proxy_dir: &Path, media: &[String]) -> Vec<PlannedPackageFile> {
+  let Ok(entries) = std::fs::read_dir(proxy_dir) else {
+    return Vec::new();
+  };
+  let mut proxy_files: Vec<PathBuf> = entries
+    .flatten()
+    .map(|entry| entry.path())
+    .filter(|path| path.is_file())
+    .collect();
+  proxy_files.sort();
+
+  let mut proxies = Vec::new();
+  for original in media {
+    let Some(stem) = Path::new(original).file_stem().map(|s| s.to_string_lossy()) else {
+      continue;
+    };
+    for proxy in &proxy_files {
+      let Some(proxy_stem) = proxy.file_stem().map(|s| s.to_string_lossy()) else {
+        continue;
+      };
+      let matches = proxy_stem == stem
+        || proxy_stem
+          .strip_prefix(stem.as_ref())
+          .is_some_and(|rest| rest.starts_with('_'));
+      if matches {
+        proxies.push(PlannedPackageFile {
+          source_path: proxy.clone(),
+          archive_path: format!("proxies/{}", archive_file_name(proxy)),
+          kind: PackageEntryKind::Proxy,
+          original_path: original.clone(),
+          trim: None,
+        });
+      }
+    }
+  }
+  let mut seen = HashSet::new();
+  proxies.retain(|proxy| seen.insert(proxy.archive_path.clone()));
+  proxies
+}
+
+/// Готовые сегменты кэша рендера для текущего состояния проекта
+fn find_cached_segments(segment_dir: &Path, project: &ProjectSchema) -> Vec<PlannedPackageFile> {
+  let hashes: HashSet<String> = segment_cache::plan_segments(project)
+    .into_iter()
+    .map(|segment| segment.content_hash)
+    .collect();
+  let Ok(entries) = std::fs::read_dir(segment_dir) else {
+    return Vec::new();
+  };
+
+  let mut segments: Vec<PlannedPackageFile> = entries
+    .flatten()
+    .map(|entry| entry.path())
+    .filter(|path| {
+      path.is_file()
+        && path
+          .file_stem()
+          .is_some_and(|stem| hashes.contains(stem.to_string_lossy().as_ref()))
+    })
+    .map(|path| PlannedPackageFile {
+      archive_path: format!("caches/segments/{}", archive_file_name(&path)),
+      original_path: path.to_string_lossy().to_string(),
+      source_path: path,
+      kind: PackageEntryKind::Cache,
+      trim: None,
+    })
+    .collect();
+  segments.sort_by(|a, b| a.archive_path.cmp(&b.archive_path));
+  segments
+}
+
+/// Копия схемы с путями внутри архива.
+///
+/// `trim_starts` - фактическое начало оставленного фрагмента для обрезанных
+/// файлов: время клипов в исходнике сдвигается на него, а смещение внешнего
+/// аудио компенсирует сдвиг.
+pub fn package_schema(
+  project: &ProjectSchema,
+  archive_paths: &HashMap<String, String>,
+  trim_starts: &HashMap<String, f64>,
+) -> ProjectSchema {
+  let mut packaged = project.clone();
+
+  for track in all_tracks_mut(&mut packaged) {
+    for clip in &mut track.clips {
+      let ClipSource::File(path) = &clip.source else {
+        continue;
+      };
+      if let Some(shift) = trim_starts.get(path).copied() {
+        clip.source_start -= shift;
+        clip.source_end -= shift;
+        if let Some(audio) = &mut clip.external_audio {
+          audio.offset_secs += shift;
+        }
+      }
+    }
+  }
+
+  for_each_media_path_mut(&mut packaged, |path| {
+    if let Some(archive_path) = archive_paths.get(path.as_str()) {
+      *path = archive_path.clone();
+    }
+  });
+  packaged
+}
+
+/// Потоковое копирование с подсчетом SHA-256 и проверкой отмены
+fn copy_hashed(
+  reader: &mut impl Read,
+  writer: &mut impl Write,
+  cancel: &CancellationToken,
+  mut on_bytes: impl FnMut(u64),
+) -> Result<(u64, String)> {
+  let mut hasher = Sha256::new();
+  let mut buffer = vec![0u8; COPY_BUFFER_SIZE];
+  let mut total = 0u64;
+  loop {
+    if cancel.is_cancelled() {
+      return Err(VideoCompilerError::CancelledError(
+        "Операция с архивом проекта отменена".to_string(),
+      ));
+    }
+    let read = reader.read(&mut buffer)?;
+    if read == 0 {
+      break;
+    }
+    hasher.update(&buffer[..read]);
+    writer.write_all(&buffer[..read])?;
+    total += read as u64;
+    on_bytes(read as u64);
+  }
+  Ok((total, hex_digest(hasher)))
+}
+
+fn hex_digest(hasher: Sha256) -> String {
+  hasher
+    .finalize()
+    .iter()
+    .map(|byte| format!("{byte:02x}"))
+    .collect()
+}
+
+fn zip_error(e: zip::result::ZipError) -> VideoCompilerError {
+  VideoCompilerError::IoError(format!("Ошибка zip-архива: {e}"))
+}
+
+/// Счетчик прогресса с ограничением частоты событий
+struct ProgressTracker<'a> {
+  sink: Option<&'a PackageProgressSink>,
+  progress: PackageProgress,
+  last_reported: u64,
+}
+
+impl<'a> ProgressTracker<'a> {
+  fn new(
+    sink: Option<&'a PackageProgressSink>,
+    stage: PackageStage,
+    files_total: usize,
+    bytes_total: u64,
+  ) -> Self {
+    Self {
+      sink,
+      progress: PackageProgress {
+        stage,
+        current_file: None,
+        files_done: 0,
+        files_total,
+        bytes_done: 0,
+        bytes_total,
+      },
+      last_reported: 0,
+    }
+  }
+
+  fn start_file(&mut self, archive_path: &str) {
+    self.progress.current_file = Some(archive_path.to_string());
+    self.report();
+  }
+
+  fn add_bytes(&mut self, bytes: u64) {
+    self.progress.bytes_done += bytes;
+    if self.progress.bytes_done - self.last_reported >= PROGRESS_BYTES_STEP {
+      self.report();
+    }
+  }
+
+  fn finish_file(&mut self) {
+    self.progress.files_done += 1;
+    self.report();
+  }
+
+  fn report(&mut self) {
+    self.last_reported = self.progress.bytes_done;
+    if let Some(sink) = self.sink {
+      sink(self.progress.clone());
+    }
+  }
+}
+
+/// Записать архив: файлы плана, схему и манифест.
+///
+/// Файл плана с `trim` уже должен быть обрезан: `source_path` указывает на
+/// обрезанную копию, а `trim` хранит фактический фрагмент. При ошибке или
+/// отмене недописанный архив удаляется.
+pub fn write_package_archive(
+  files: &[PlannedPackageFile],
+  packaged_project: &ProjectSchema,
+  output_path: &Path,
+  progress: Option<&PackageProgressSink>,
+  cancel: &CancellationToken,
+) -> Result<PackageManifest> {
+  if let Some(parent) = output_path.parent() {
+    std::fs::create_dir_all(parent)?;
+  }
+
+  let result = write_archive_contents(files, packaged_project, output_path, progress, cancel);
+  if result.is_err() {
+    let _ = std::fs::remove_file(output_path);
+  }
+  result
+}
+
+fn write_archive_contents(
+  files: &[PlannedPackageFile],
+  packaged_project: &ProjectSchema,
+  output_path: &Path,
+  progress: Option<&PackageProgressSink>,
+  cancel: &CancellationToken,
+) -> Result<PackageManifest> {
+  let sizes = files
+    .iter()
+    .map(|file| Ok(std::fs::metadata(&file.source_path)?.len()))
+    .collect::<Result<Vec<u64>>>()?;
+  let mut tracker = ProgressTracker::new(
+    progress,
+    PackageStage::Archiving,
+    files.len(),
+    sizes.iter().sum(),
+  );
+
+  let mut archive = zip::ZipWriter::new(std::fs::File::create(output_path)?);
+  // Медиа уже сжаты: храним без повторного сжатия
+  let stored =
+    zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+  let deflated =
+    zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+  let mut entries = Vec::with_capacity(files.len());
+  for (file, size) in files.iter().zip(sizes) {
+    tracker.start_file(&file.archive_path);
+    archive
+      .start_file(
+        file.archive_path.as_str(),
+        stored.large_file(size >= u32::MAX as u64),
+      )
+      .map_err(zip_error)?;
+
+    let mut source = std::fs::File::open(&file.source_path)?;
+    let (size, sha256) = copy_hashed(&mut source, &mut archive, cancel, |bytes| {
+      tracker.add_bytes(bytes)
+    })?;
+    entries.push(PackageEntry {
+      archive_path: file.archive_path.clone(),
+      kind: file.kind,
+      original_path: file.original_path.clone(),
+      size,
+      sha256,
+      trim: file.trim,
+    });
+    tracker.finish_file();
+  }
+
+  let manifest = PackageManifest {
+    format_version: PACKAGE_FORMAT_VERSION,
+    project_name: packaged_project.metadata.name.clone(),
+    created_at: Utc::now(),
+    entries,
+  };
+
+  archive
+    .start_file(PACKAGE_PROJECT_FILE, deflated)
+    .map_err(zip_error)?;
+  serde_json::to_writer_pretty(&mut archive, packaged_project)?;
+  archive
+    .start_file(PACKAGE_MANIFEST_FILE, deflated)
+    .map_err(zip_error)?;
+  serde_json::to_writer_pretty(&mut archive, &manifest)?;
+  archive.finish().map_err(zip_error)?;
+
+  Ok(manifest)
+}
+
+/// Прочитать JSON-файл архива
+fn read_archive_json<T: serde::de::DeserializeOwned>(
+  archive: &mut zip::ZipArchive<std::fs::File>,
+  name: &str,
+) -> Result<T> {
+  let file = archive
+    .by_name(name)
+    .map_err(|e| VideoCompilerError::validation(format!("В архиве проекта нет {name}: {e}")))?;
+  Ok(serde_json::from_reader(std::io::BufReader::new(file))?)
+}
+
+/// Безопасный относительный путь внутри директории распаковки
+fn enclosed_archive_path(archive_path: &str) -> Result<PathBuf> {
+  let path = Path::new(archive_path);
+  let safe = !archive_path.is_empty()
+    && path
+      .components()
+      .all(|component| matches!(component, std::path::Component::Normal(_)));
+  if safe {
+    Ok(path.to_path_buf())
+  } else {
+    Err(VideoCompilerError::SecurityError(format!(
+      "Недопустимый путь в архиве проекта: {archive_path}"
+    )))
+  }
+}
+
+/// Распаковать архив проекта в `destination`.
+///
+/// Извлекаются файлы манифеста; для каждого проверяется SHA-256, и
+/// несовпадения возвращаются по файлам, не прерывая распаковку. Схема
+/// сохраняется в `destination/project.json` с абсолютными путями.
+pub fn unpack_package_archive(
+  zip_path: &Path,
+  destination: &Path,
+  progress: Option<&PackageProgressSink>,
+  cancel: &CancellationToken,
+) -> Result<UnpackedProject> {
+  let mut archive = zip::ZipArchive::new(std::fs::File::open(zip_path)?).map_err(zip_error)?;
+  let manifest: PackageManifest = read_archive_json(&mut archive, PACKAGE_MANIFEST_FILE)?;
+  if manifest.format_version > PACKAGE_FORMAT_VERSION {
+    return Err(VideoCompilerError::UnsupportedFormat {
+      format: format!("project package v{}", manifest.format_version),
+      file_path: zip_path.to_string_lossy().to_string(),
+    });
+  }
+  let mut project: ProjectSchema = read_archive_json(&mut archive, PACKAGE_PROJECT_FILE)?;
+
+  std::fs::create_dir_all(destination)?;
+  let mut tracker = ProgressTracker::new(
+    progress,
+    PackageStage::Extracting,
+    manifest.entries.len(),
+    manifest.entries.iter().map(|entry| entry.size).sum(),
+  );
+
+  let mut issues = Vec::new();
+  let mut extracted = BTreeMap::new();
+  for entry in &manifest.entries {
+    let relative = enclosed_archive_path(&entry.archive_path)?;
+    tracker.start_file(&entry.archive_path);
+
+    let mut file = match archive.by_name(&entry.archive_path) {
+      Ok(file) => file,
+      Err(_) => {
+        issues.push(PackageFileIssue {
+          archive_path: entry.archive_path.clone(),
+          expected_sha256: entry.sha256.clone(),
+          actual_sha256: None,
+        });
+        tracker.finish_file();
+        continue;
+      }
+    };
+
+    let target = destination.join(&relative);
+    if let Some(parent) = target.parent() {
+      std::fs::create_dir_all(parent)?;
+    }
+    let mut output = std::io::BufWriter::new(std::fs::File::create(&target)?);
+    let (_, sha256) = copy_hashed(&mut file, &mut output, cancel, |bytes| {
+      tracker.add_bytes(bytes)
+    })?;
+    output.flush()?;
+
+    if sha256 != entry.sha256 {
+      issues.push(PackageFileIssue {
+        archive_path: entry.archive_path.clone(),
+        expected_sha256: entry.sha256.clone(),
+        actual_sha256: Some(sha256),
+      });
+    }
+    extracted.insert(entry.archive_path.clone(), target);
+    tracker.finish_file();
+  }
+
+  for_each_media_path_mut(&mut project, |path| {
+    if let Some(target) = extracted.get(path.as_str()) {
+      *path = target.to_string_lossy().to_string();
+    }
+  });
+
+  let project_path = destination.join(PACKAGE_PROJECT_FILE);
+  std::fs::write(&project_path, serde_json::to_string_pretty(&project)?)?;
+
+  Ok(UnpackedProject {
+    project,
+    project_path: project_path.to_string_lossy().to_string(),
+    manifest,
+    issues,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::video_compiler::schema::{
+    Clip, ExternalAudio, Track, TrackType, WatermarkConfig, WatermarkPosition,
+  };
+  use std::fs;
+  use tempfile::TempDir;
+
+  fn context(dir: &Path) -> PackageContext {
+    PackageContext {
+      ffmpeg_path: "ffmpeg".to_string(),
+      temp_root: dir.join("temp"),
+      proxy_dir: Some(dir.join("proxies")),
+    }
+  }
+
+  /// Проект с двумя клипами одного видео, внешним аудио и водяным знаком
+  fn project_with_media(dir: &Path) -> ProjectSchema {
+    let media = dir.join("Footage");
+    fs::create_dir_all(&media).unwrap();
+    for (name, content) in [
+      ("interview.mp4", "video data"),
+      ("lav mic.wav", "audio data"),
+      ("logo.png", "png data"),
+    ] {
+      fs::write(media.join(name), content).unwrap();
+    }
+
+    let mut project = ProjectSchema::new("Package Test".to_string());
+    let mut track = Track::new(TrackType::Video, "Video".to_string());
+    let mut first = Clip::new(media.join("interview.mp4"), 0.0, 5.0);
+    first.source_start = 10.0;
+    first.source_end = 15.0;
+    first.external_audio = Some(ExternalAudio::new(
+      media.join("lav mic.wav").to_string_lossy(),
+      1.5,
+    ));
+    let mut second = Clip::new(media.join("interview.mp4"), 5.0, 5.0);
+    second.source_start = 40.0;
+    second.source_end = 45.0;
+    track.clips.extend([first, second]);
+    track
+      .clips
+      .push(Clip::new(dir.join("missing.mp4"), 10.0, 2.0));
+    project.tracks.push(track);
+
+    project.settings.export.watermark = Some(WatermarkConfig {
+      image_path: media.join("logo.png").to_string_lossy().to_string(),
+      position: WatermarkPosition::default(),
+      scale: 0.1,
+      opacity: 1.0,
+      margin_px: 16,
+      start_time: None,
+      end_time: None,
+      show_in_preview: false,
+    });
+    project
+  }
+
+  #[test]
+  fn test_plan_collects_referenced_media() {
+    let dir = TempDir::new().unwrap();
+    let project = project_with_media(dir.path());
+
+    let plan = plan_package(&project, &PackageOptions::default(), &context(dir.path()));
+    let archive_paths: Vec<&str> = plan
+      .files
+      .iter()
+      .map(|file| file.archive_path.as_str())
+      .collect();
+    assert_eq!(
+      archive_paths,
+      vec![
+        "media/001_interview.mp4",
+        "media/002_lav_mic.wav",
+        "media/003_logo.png"
+      ]
+    );
+    assert!(plan.files.iter().all(|file| file.trim.is_none()));
+    assert_eq!(plan.missing.len(), 1);
+    assert!(plan.missing[0].ends_with("missing.mp4"));
+  }
+
+  #[test]
+  fn test_plan_trims_only_clip_sources() {
+    let dir = TempDir::new().unwrap();
+    let project = project_with_media(dir.path());
+    fs::create_dir_all(dir.path().join("proxies")).unwrap();
+    fs::write(dir.path().join("proxies").join("interview_proxy.mp4"), "p").unwrap();
+    fs::write(dir.path().join("proxies").join("interviewer.mp4"), "x").unwrap();
+
+    let options = PackageOptions {
+      include_proxies: true,
+      trim_unused_media: true,
+      handle_secs: 2.0,
+      ..Default::default()
+    };
+    let plan = plan_package(&project, &options, &context(dir.path()));
+
+    let video = &plan.files[0];
+    assert_eq!(
+      video.trim,
+      Some(TrimRange {
+        start: 8.0,
+        end: 47.0
+      })
+    );
+    // Внешнее аудио и изображения копируются целиком
+    assert!(plan.files[1..].iter().all(|file| file.trim.is_none()));
+
+    let proxies: Vec<&PlannedPackageFile> = plan
+      .files
+      .iter()
+      .filter(|file| file.kind == PackageEntryKind::Proxy)
+      .collect();
+    assert_eq!(proxies.len(), 1);
+    assert_eq!(proxies[0].archive_path, "proxies/interview_proxy.mp4");
+    assert_eq!(proxies[0].original_path, video.original_path);
+  }
+
+  #[test]
+  fn test_package_schema_shifts_trimmed_clips() {
+    let dir = TempDir::new().unwrap();
+    let project = project_with_media(dir.path());
+    let plan = plan_package(&project, &PackageOptions::default(), &context(dir.path()));
+    let archive_paths: HashMap<String, String> = plan
+      .files
+      .iter()
+      .map(|file| (file.original_path.clone(), file.archive_path.clone()))
+      .collect();
+    let trim_starts = HashMap::from([(plan.files[0].original_path.clone(), 8.0)]);
+
+    let packaged = package_schema(&project, &archive_paths, &trim_starts);
+    let clips = &packaged.tracks[0].clips;
+    assert!(
+      matches!(&clips[0].source, ClipSource::File(path) if path == "media/001_interview.mp4")
+    );
+    assert_eq!((clips[0].source_start, clips[0].source_end), (2.0, 7.0));
+    assert_eq!((clips[1].source_start, clips[1].source_end), (32.0, 37.0));
+    let audio = clips[0].external_audio.as_ref().unwrap();
+    assert_eq!(audio.path, "media/002_lav_mic.wav");
+    assert_eq!(audio.offset_secs, 9.5);
+    assert_eq!(
+      packaged.settings.export.watermark.unwrap().image_path,
+      "media/003_logo.png"
+    );
+    // Отсутствующий файл остается со старым путем
+    assert!(matches!(&clips[2].source, ClipSource::File(path) if path.ends_with("missing.mp4")));
+  }
+
+  /// Упаковать проект без обрезки
+  fn write_test_package(dir: &Path, project: &ProjectSchema) -> (PathBuf, PackageManifest) {
+    let plan = plan_package(project, &PackageOptions::default(), &context(dir));
+    let archive_paths = plan
+      .files
+      .iter()
+      .map(|file| (file.original_path.clone(), file.archive_path.clone()))
+      .collect();
+    let packaged = package_schema(project, &archive_paths, &HashMap::new());
+    let zip_path = dir.join("out").join("project.zip");
+    let manifest = write_package_archive(
+      &plan.files,
+      &packaged,
+      &zip_path,
+      None,
+      &CancellationToken::new(),
+    )
+    .unwrap();
+    (zip_path, manifest)
+  }
+
+  #[test]
+  fn test_package_roundtrip() {
+    let dir = TempDir::new().unwrap();
+    let project = project_with_media(dir.path());
+    let (zip_path, manifest) = write_test_package(dir.path(), &project);
+    assert_eq!(manifest.entries.len(), 3);
+    assert_eq!(manifest.entries[0].size, "video data".len() as u64);
+
+    let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let sink_events = events.clone();
+    let sink: PackageProgressSink = Arc::new(move |progress: PackageProgress| {
+      sink_events.lock().unwrap().push(progress);
+    });
+    let destination = dir.path().join("Unpacked");
+    let unpacked = unpack_package_archive(
+      &zip_path,
+      &destination,
+      Some(&sink),
+      &CancellationToken::new(),
+    )
+    .unwrap();
+
+    assert!(unpacked.issues.is_empty());
+    let paths = media_paths(&unpacked.project);
+    assert!(paths
+      .iter()
+      .filter(|path| !path.ends_with("missing.mp4"))
+      .all(|path| Path::new(path).starts_with(&destination) && Path::new(path).is_file()));
+    let clip_path = destination.join("media").join("001_interview.mp4");
+    assert_eq!(fs::read_to_string(clip_path).unwrap(), "video data");
+    assert!(destination.join(PACKAGE_PROJECT_FILE).is_file());
+
+    let last = events.lock().unwrap().last().cloned().unwrap();
+    assert_eq!(last.stage, PackageStage::Extracting);
+    assert_eq!(last.files_done, 3);
+    assert_eq!(last.bytes_done, last.bytes_total);
+  }
+
+  #[test]
+  fn test_unpack_reports_hash_mismatch_per_file() {
+    let dir = TempDir::new().unwrap();
+    let project = project_with_media(dir.path());
+    let (zip_path, mut manifest) = write_test_package(dir.path(), &project);
+
+    // Подменяем манифест: хеш одного файла не совпадает, другого нет в архиве
+    manifest.entries[1].sha256 = "0".repeat(64);
+    manifest.entries.push(PackageEntry {
+      archive_path: "media/004_absent.mp4".to_string(),
+      kind: PackageEntryKind::Media,
+      original_path: "/absent.mp4".to_string(),
+      size: 1,
+      sha256: "1".repeat(64),
+      trim: None,
+    });
+    let tampered = dir.path().join("tampered.zip");
+    {
+      let mut source = zip::ZipArchive::new(fs::File::open(&zip_path).unwrap()).unwrap();
+      let mut writer = zip::ZipWriter::new(fs::File::create(&tampered).unwrap());
+      for i in 0..source.len() {
+        let file = source.by_index(i).unwrap();
+        if file.name() != PACKAGE_MANIFEST_FILE {
+          writer.raw_copy_file(file).unwrap();
+        }
+      }
+      writer
+        .start_file(
+          PACKAGE_MANIFEST_FILE,
+          zip::write::SimpleFileOptions::default(),
+        )
+        .unwrap();
+      serde_json::to_writer(&mut writer, &manifest).unwrap();
+      writer.finish().unwrap();
+    }
+
+    let unpacked = unpack_package_archive(
+      &tampered,
+      &dir.path().join("Unpacked"),
+      None,
+      &CancellationToken::new(),
+    )
+    .unwrap();
+    assert_eq!(unpacked.issues.len(), 2);
+    assert_eq!(unpacked.issues[0].archive_path, "media/002_lav_mic.wav");
+    assert!(unpacked.issues[0].actual_sha256.is_some());
+    assert_eq!(unpacked.issues[1].archive_path, "media/004_absent.mp4");
+    assert_eq!(unpacked.issues[1].actual_sha256, None);
+  }
+
+  #[test]
+  fn test_cancelled_packaging_removes_archive() {
+    let dir = TempDir::new().unwrap();
+    let project = project_with_media(dir.path());
+    let plan = plan_package(&project, &PackageOptions::default(), &context(dir.path()));
+    let zip_path = dir.path().join("cancelled.zip");
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+
+    let result = write_package_archive(&plan.files, &project, &zip_path, None, &cancel);
+    assert!(matches!(result, Err(VideoCompilerError::CancelledError(_))));
+    assert!(!zip_path.exists());
+  }
+
+  #[test]
+  fn test_enclosed_archive_path() {
+    assert!(enclosed_archive_path("media/001_a.mp4").is_ok());
+    assert!(enclosed_archive_path("../evil.mp4").is_err());
+    assert!(enclosed_archive_path("/etc/passwd").is_err());
+    assert!(enclosed_archive_path("").is_err());
+  }
+}
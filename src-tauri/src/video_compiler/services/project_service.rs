@@ -1,12 +1,36 @@
 //! Сервис управления проектами
 
+use crate::media::fingerprint::MediaFingerprint;
 use crate::video_compiler::{
   core::error::{Result, VideoCompilerError},
-  schema::{ClipSource, ProjectMetadata, ProjectSchema, Timeline},
-  services::Service,
+  core::temp_storage,
+  ffmpeg_builder::{FFmpegBuilder, FFmpegBuilderSettings},
+  schema::{ClipSource, ProjectMetadata, ProjectSchema, Timeline, SCHEMA_VERSION},
+  services::{
+    ffmpeg_service::ffprobe_path,
+    media_relink::{
+      self, MissingMediaGroup, RelinkProgressSink, RelinkSuggestion, RelinkedMedia,
+      RELINK_SEARCH_MAX_DEPTH,
+    },
+    project_package::{
+      self, PackageContext, PackageEntryKind, PackageOptions, PackageProgress, PackageProgressSink,
+      PackageStage, PackageSummary, PlannedPackageFile, TrimMode, TrimRange, UnpackedProject,
+    },
+    project_storage::{self, ProjectLoadResult},
+    project_template::{
+      parse_template, system_font_dirs, template_path, ProjectTemplate, ProjectTemplateInfo,
+      TemplateInstance, TemplateMediaBinding, TEMPLATE_FILE_EXTENSION,
+    },
+    schema_journal::{SchemaChange, SchemaJournal, SchemaJournalConfig, SchemaJournalState},
+    Service,
+  },
 };
 use async_trait::async_trait;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
 /// Результат анализа проекта
@@ -44,10 +68,12 @@ pub trait ProjectService: Service + Send + Sync {
   /// Создать новый проект
   async fn create_project(&self, name: String) -> Result<ProjectSchema>;
 
-  /// Загрузить проект из файла
-  async fn load_project(&self, path: &Path) -> Result<ProjectSchema>;
+  /// Загрузить проект из файла с проверкой контрольной суммы.
+  ///
+  /// Если файл поврежден, возвращается самая свежая целая резервная копия.
+  async fn load_project(&self, path: &Path) -> Result<ProjectLoadResult>;
 
-  /// Сохранить проект в файл
+  /// Сохранить проект в файл: валидация, резервные копии и атомарная запись
   async fn save_project(&self, project: &ProjectSchema, path: &Path) -> Result<()>;
 
   /// Валидировать схему проекта
@@ -86,10 +112,125 @@ pub trait ProjectService: Service + Send + Sync {
     project: &mut ProjectSchema,
     search_paths: Vec<PathBuf>,
   ) -> Result<usize>;
+
+  /// Найти отсутствующие медиафайлы, сгруппированные по исходной директории
+  async fn find_missing_media(&self, project: &ProjectSchema) -> Result<Vec<MissingMediaGroup>>;
+
+  /// Подобрать ранжированных кандидатов для отсутствующих медиафайлов.
+  ///
+  /// `known_media` - отпечатки содержимого из реестра медиа по исходному пути.
+  async fn suggest_relink_candidates(
+    &self,
+    project: &ProjectSchema,
+    search_dirs: Vec<PathBuf>,
+    known_media: HashMap<String, MediaFingerprint>,
+    progress: Option<RelinkProgressSink>,
+  ) -> Result<Vec<RelinkSuggestion>>;
+
+  /// Переписать пути медиафайлов по сопоставлению старый -> новый путь
+  async fn apply_relink(
+    &self,
+    project: &mut ProjectSchema,
+    mapping: &HashMap<String, String>,
+  ) -> Result<Vec<RelinkedMedia>>;
+
+  /// Сохранить проект как шаблон
+  async fn save_project_as_template(
+    &self,
+    project: &ProjectSchema,
+    name: String,
+    include_media: bool,
+  ) -> Result<ProjectTemplateInfo>;
+
+  /// Список сохраненных шаблонов
+  async fn list_project_templates(&self) -> Result<Vec<ProjectTemplateInfo>>;
+
+  /// Загрузить шаблон вместе с предупреждениями загрузки
+  async fn load_project_template(
+    &self,
+    template_id: &str,
+  ) -> Result<(ProjectTemplate, Vec<String>)>;
+
+  /// Создать проект из шаблона с подстановкой медиафайлов
+  async fn create_project_from_template(
+    &self,
+    template_id: &str,
+    name: String,
+    media_bindings: Vec<TemplateMediaBinding>,
+  ) -> Result<TemplateInstance>;
+
+  /// Переименовать шаблон
+  async fn rename_project_template(
+    &self,
+    template_id: &str,
+    name: String,
+  ) -> Result<ProjectTemplateInfo>;
+
+  /// Удалить шаблон
+  async fn delete_project_template(&self, template_id: &str) -> Result<()>;
+
+  /// Импортировать файл шаблона (например, созданный на другой машине)
+  async fn import_project_template(&self, path: &Path) -> Result<ProjectTemplateInfo>;
+
+  /// Записать изменение схемы, выполненное backend командой
+  ///
+  /// Возвращает `None`, если схема не изменилась.
+  async fn record_schema_change(
+    &self,
+    project_id: &str,
+    label: &str,
+    before: &ProjectSchema,
+    after: &ProjectSchema,
+  ) -> Result<Option<SchemaChange>>;
+
+  /// Отменить последнее изменение схемы проекта
+  async fn undo_schema_change(&self, project_id: &str) -> Result<Option<ProjectSchema>>;
+
+  /// Повторить отмененное изменение схемы проекта
+  async fn redo_schema_change(&self, project_id: &str) -> Result<Option<ProjectSchema>>;
+
+  /// Доступные операции undo/redo проекта
+  async fn schema_journal_state(&self, project_id: &str) -> Result<SchemaJournalState>;
+
+  /// Закрыть проект: освободить его журнал изменений
+  async fn close_project(&self, project_id: &str) -> Result<()>;
+
+  /// Упаковать проект со всеми используемыми файлами в zip-архив.
+  ///
+  /// `package_id` идентифицирует операцию для отмены через
+  /// [`ProjectService::cancel_package_operation`].
+  async fn package_project(
+    &self,
+    package_id: &str,
+    project: &ProjectSchema,
+    output_zip: &Path,
+    options: PackageOptions,
+    context: PackageContext,
+    progress: Option<PackageProgressSink>,
+  ) -> Result<PackageSummary>;
+
+  /// Распаковать архив проекта и вернуть схему с абсолютными путями
+  async fn unpack_project(
+    &self,
+    package_id: &str,
+    zip_path: &Path,
+    destination: &Path,
+    progress: Option<PackageProgressSink>,
+  ) -> Result<UnpackedProject>;
+
+  /// Отменить упаковку или распаковку (`false`, если операция не найдена)
+  async fn cancel_package_operation(&self, package_id: &str) -> Result<bool>;
 }
 
 /// Реализация сервиса проектов
-pub struct ProjectServiceImpl;
+pub struct ProjectServiceImpl {
+  /// Директория шаблонов (по умолчанию - Projects/Templates в директориях приложения)
+  templates_dir: Option<PathBuf>,
+  /// Журнал изменений схем для undo/redo
+  journal: SchemaJournal,
+  /// Токены отмены активных операций с архивами проектов
+  package_operations: Arc<RwLock<HashMap<String, CancellationToken>>>,
+}
 
 impl Default for ProjectServiceImpl {
   fn default() -> Self {
@@ -99,7 +240,72 @@ impl Default for ProjectServiceImpl {
 
 impl ProjectServiceImpl {
   pub fn new() -> Self {
-    Self
+    Self {
+      templates_dir: None,
+      journal: SchemaJournal::default(),
+      package_operations: Arc::new(RwLock::new(HashMap::new())),
+    }
+  }
+
+  /// Создать сервис с явной директорией шаблонов
+  pub fn with_templates_dir(templates_dir: PathBuf) -> Self {
+    Self {
+      templates_dir: Some(templates_dir),
+      journal: SchemaJournal::default(),
+      package_operations: Arc::new(RwLock::new(HashMap::new())),
+    }
+  }
+
+  /// Задать ограничения журнала изменений схем
+  pub fn with_journal_config(mut self, config: SchemaJournalConfig) -> Self {
+    self.journal = SchemaJournal::new(config);
+    self
+  }
+
+  /// Директория шаблонов (создается при первом обращении)
+  async fn templates_dir(&self) -> Result<PathBuf> {
+    let dir = match &self.templates_dir {
+      Some(dir) => dir.clone(),
+      None => crate::app_dirs::AppDirectories::get_or_create()
+        .map_err(|e| VideoCompilerError::IoError(e.to_string()))?
+        .get_project_templates_dir(),
+    };
+    tokio::fs::create_dir_all(&dir)
+      .await
+      .map_err(|e| VideoCompilerError::IoError(e.to_string()))?;
+    Ok(dir)
+  }
+
+  /// Записать шаблон в директорию шаблонов
+  async fn write_template(&self, template: &ProjectTemplate) -> Result<()> {
+    let path = template_path(&self.templates_dir().await?, &template.id)?;
+    let content = serde_json::to_string_pretty(template)
+      .map_err(|e| VideoCompilerError::SerializationError(e.to_string()))?;
+
+    tokio::fs::write(path, content)
+      .await
+      .map_err(|e| VideoCompilerError::IoError(e.to_string()))
+  }
+
+  /// Прочитать файл шаблона
+  async fn read_template(&self, path: &Path) -> Result<(ProjectTemplate, Vec<String>)> {
+    let content = tokio::fs::read_to_string(path)
+      .await
+      .map_err(|e| VideoCompilerError::IoError(e.to_string()))?;
+    parse_template(&content, &system_font_dirs())
+  }
+
+  /// Зарегистрировать операцию с архивом проекта
+  async fn begin_package_operation(&self, package_id: &str) -> Result<CancellationToken> {
+    let mut operations = self.package_operations.write().await;
+    if operations.contains_key(package_id) {
+      return Err(VideoCompilerError::validation(format!(
+        "Операция с архивом {package_id} уже выполняется"
+      )));
+    }
+    let token = CancellationToken::new();
+    operations.insert(package_id.to_string(), token.clone());
+    Ok(token)
   }
 
   /// Проверить наличие медиафайлов
@@ -166,7 +372,7 @@ impl ProjectService for ProjectServiceImpl {
     let now = chrono::Utc::now();
 
     Ok(ProjectSchema {
-      version: "1.0.0".to_string(),
+      version: SCHEMA_VERSION.to_string(),
       metadata: ProjectMetadata {
         name,
         description: None,
@@ -181,34 +387,28 @@ impl ProjectService for ProjectServiceImpl {
       filters: vec![],
       templates: vec![],
       style_templates: vec![],
+      sequences: Default::default(),
       subtitles: vec![],
       settings: Default::default(),
     })
   }
 
-  async fn load_project(&self, path: &Path) -> Result<ProjectSchema> {
-    let content = tokio::fs::read_to_string(path)
+  async fn load_project(&self, path: &Path) -> Result<ProjectLoadResult> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || project_storage::load_project_file(&path))
       .await
-      .map_err(|e| VideoCompilerError::IoError(e.to_string()))?;
-
-    serde_json::from_str(&content).map_err(|e| VideoCompilerError::ValidationError(e.to_string()))
+      .map_err(|e| VideoCompilerError::InternalError(format!("Project loading failed: {e}")))?
   }
 
   async fn save_project(&self, project: &ProjectSchema, path: &Path) -> Result<()> {
-    let content = serde_json::to_string_pretty(project)
-      .map_err(|e| VideoCompilerError::SerializationError(e.to_string()))?;
-
-    tokio::fs::write(path, content)
+    let (project, path) = (project.clone(), path.to_path_buf());
+    tokio::task::spawn_blocking(move || project_storage::save_project_file(&project, &path))
       .await
-      .map_err(|e| VideoCompilerError::IoError(e.to_string()))?;
-
-    Ok(())
+      .map_err(|e| VideoCompilerError::InternalError(format!("Project saving failed: {e}")))?
   }
 
   async fn validate_project(&self, project: &ProjectSchema) -> Result<()> {
-    project
-      .validate()
-      .map_err(VideoCompilerError::ValidationError)?;
+    project.validate().map_err(VideoCompilerError::validation)?;
 
     // Дополнительные проверки
     let missing_files = self.check_media_files(project).await;
@@ -406,7 +606,7 @@ impl ProjectService for ProjectServiceImpl {
 
   async fn import_project(&self, path: &Path) -> Result<ProjectSchema> {
     // Пока поддерживаем только JSON
-    self.load_project(path).await
+    self.load_project(path).await?.into_project()
   }
 
   async fn backup_project(&self, project: &ProjectSchema, backup_path: &Path) -> Result<()> {
@@ -464,6 +664,405 @@ impl ProjectService for ProjectServiceImpl {
 
     Ok(restored_count)
   }
+
+  async fn find_missing_media(&self, project: &ProjectSchema) -> Result<Vec<MissingMediaGroup>> {
+    Ok(media_relink::find_missing_media(project))
+  }
+
+  async fn suggest_relink_candidates(
+    &self,
+    project: &ProjectSchema,
+    search_dirs: Vec<PathBuf>,
+    known_media: HashMap<String, MediaFingerprint>,
+    progress: Option<RelinkProgressSink>,
+  ) -> Result<Vec<RelinkSuggestion>> {
+    if let Some(dir) = search_dirs.iter().find(|dir| !dir.is_dir()) {
+      return Err(VideoCompilerError::InvalidPath(
+        dir.to_string_lossy().to_string(),
+      ));
+    }
+
+    // Обход директорий и хеширование файлов - блокирующие операции
+    let project = project.clone();
+    tokio::task::spawn_blocking(move || {
+      media_relink::suggest_relink_candidates(
+        &project,
+        &search_dirs,
+        &known_media,
+        RELINK_SEARCH_MAX_DEPTH,
+        progress.as_ref(),
+      )
+    })
+    .await
+    .map_err(|e| VideoCompilerError::InternalError(format!("Relink scan failed: {e}")))
+  }
+
+  async fn apply_relink(
+    &self,
+    project: &mut ProjectSchema,
+    mapping: &HashMap<String, String>,
+  ) -> Result<Vec<RelinkedMedia>> {
+    media_relink::apply_relink(project, mapping)
+  }
+
+  async fn save_project_as_template(
+    &self,
+    project: &ProjectSchema,
+    name: String,
+    include_media: bool,
+  ) -> Result<ProjectTemplateInfo> {
+    if name.trim().is_empty() {
+      return Err(VideoCompilerError::InvalidParameter(
+        "Название шаблона не может быть пустым".to_string(),
+      ));
+    }
+
+    let template = ProjectTemplate::from_project(project, name, include_media);
+    self.write_template(&template).await?;
+    Ok(template.info(Vec::new()))
+  }
+
+  async fn list_project_templates(&self) -> Result<Vec<ProjectTemplateInfo>> {
+    let dir = self.templates_dir().await?;
+    let mut entries = tokio::fs::read_dir(&dir)
+      .await
+      .map_err(|e| VideoCompilerError::IoError(e.to_string()))?;
+
+    let mut templates = Vec::new();
+    while let Some(entry) = entries
+      .next_entry()
+      .await
+      .map_err(|e| VideoCompilerError::IoError(e.to_string()))?
+    {
+      let path = entry.path();
+      if path.extension().and_then(|e| e.to_str()) != Some(TEMPLATE_FILE_EXTENSION) {
+        continue;
+      }
+      // Битый файл не должен скрывать остальные шаблоны
+      match self.read_template(&path).await {
+        Ok((template, warnings)) => templates.push(template.info(warnings)),
+        Err(e) => log::warn!("Не удалось прочитать шаблон {}: {}", path.display(), e),
+      }
+    }
+
+    templates.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(templates)
+  }
+
+  async fn load_project_template(
+    &self,
+    template_id: &str,
+  ) -> Result<(ProjectTemplate, Vec<String>)> {
+    let path = template_path(&self.templates_dir().await?, template_id)?;
+    if !path.exists() {
+      return Err(VideoCompilerError::TemplateNotFound(
+        template_id.to_string(),
+      ));
+    }
+    self.read_template(&path).await
+  }
+
+  async fn create_project_from_template(
+    &self,
+    template_id: &str,
+    name: String,
+    media_bindings: Vec<TemplateMediaBinding>,
+  ) -> Result<TemplateInstance> {
+    let (template, mut warnings) = self.load_project_template(template_id).await?;
+    let mut instance = template.instantiate(name, &media_bindings);
+    warnings.append(&mut instance.warnings);
+    instance.warnings = warnings;
+    Ok(instance)
+  }
+
+  async fn rename_project_template(
+    &self,
+    template_id: &str,
+    name: String,
+  ) -> Result<ProjectTemplateInfo> {
+    if name.trim().is_empty() {
+      return Err(VideoCompilerError::InvalidParameter(
+        "Название шаблона не может быть пустым".to_string(),
+      ));
+    }
+
+    let (mut template, warnings) = self.load_project_template(template_id).await?;
+    template.name = name.clone();
+    template.project.metadata.name = name;
+    template.modified_at = chrono::Utc::now();
+    self.write_template(&template).await?;
+    Ok(template.info(warnings))
+  }
+
+  async fn delete_project_template(&self, template_id: &str) -> Result<()> {
+    let path = template_path(&self.templates_dir().await?, template_id)?;
+    if !path.exists() {
+      return Err(VideoCompilerError::TemplateNotFound(
+        template_id.to_string(),
+      ));
+    }
+    tokio::fs::remove_file(path)
+      .await
+      .map_err(|e| VideoCompilerError::IoError(e.to_string()))
+  }
+
+  async fn import_project_template(&self, path: &Path) -> Result<ProjectTemplateInfo> {
+    let (mut template, warnings) = self.read_template(path).await?;
+
+    // ID из чужого файла может быть некорректным или совпасть с существующим
+    let dir = self.templates_dir().await?;
+    let id_taken = template_path(&dir, &template.id)
+      .map(|p| p.exists())
+      .unwrap_or(true);
+    if id_taken {
+      template.id = Uuid::new_v4().to_string();
+    }
+
+    self.write_template(&template).await?;
+    Ok(template.info(warnings))
+  }
+
+  async fn record_schema_change(
+    &self,
+    project_id: &str,
+    label: &str,
+    before: &ProjectSchema,
+    after: &ProjectSchema,
+  ) -> Result<Option<SchemaChange>> {
+    self.journal.record(project_id, label, before, after).await
+  }
+
+  async fn undo_schema_change(&self, project_id: &str) -> Result<Option<ProjectSchema>> {
+    self.journal.undo(project_id).await
+  }
+
+  async fn redo_schema_change(&self, project_id: &str) -> Result<Option<ProjectSchema>> {
+    self.journal.redo(project_id).await
+  }
+
+  async fn schema_journal_state(&self, project_id: &str) -> Result<SchemaJournalState> {
+    Ok(self.journal.state(project_id).await)
+  }
+
+  async fn close_project(&self, project_id: &str) -> Result<()> {
+    self.journal.clear(project_id).await;
+    Ok(())
+  }
+
+  async fn package_project(
+    &self,
+    package_id: &str,
+    project: &ProjectSchema,
+    output_zip: &Path,
+    options: PackageOptions,
+    context: PackageContext,
+    progress: Option<PackageProgressSink>,
+  ) -> Result<PackageSummary> {
+    let cancel = self.begin_package_operation(package_id).await?;
+    let staging = temp_storage::job_dir(&context.temp_root, &format!("package-{}", Uuid::new_v4()));
+
+    let result = write_package(
+      project, output_zip, options, context, &staging, progress, &cancel,
+    )
+    .await;
+
+    let _ = tokio::fs::remove_dir_all(&staging).await;
+    self.package_operations.write().await.remove(package_id);
+    result
+  }
+
+  async fn unpack_project(
+    &self,
+    package_id: &str,
+    zip_path: &Path,
+    destination: &Path,
+    progress: Option<PackageProgressSink>,
+  ) -> Result<UnpackedProject> {
+    let cancel = self.begin_package_operation(package_id).await?;
+    let (zip_path, destination) = (zip_path.to_path_buf(), destination.to_path_buf());
+
+    let result = tokio::task::spawn_blocking(move || {
+      project_package::unpack_package_archive(&zip_path, &destination, progress.as_ref(), &cancel)
+    })
+    .await
+    .map_err(|e| VideoCompilerError::InternalError(format!("Project unpacking failed: {e}")));
+
+    self.package_operations.write().await.remove(package_id);
+    result?
+  }
+
+  async fn cancel_package_operation(&self, package_id: &str) -> Result<bool> {
+    match self.package_operations.read().await.get(package_id) {
+      Some(token) => {
+        token.cancel();
+        Ok(true)
+      }
+      None => Ok(false),
+    }
+  }
+}
+
+/// Собрать архив проекта: обрезанные копии медиа готовятся в `staging`
+async fn write_package(
+  project: &ProjectSchema,
+  output_zip: &Path,
+  options: PackageOptions,
+  context: PackageContext,
+  staging: &Path,
+  progress: Option<PackageProgressSink>,
+  cancel: &CancellationToken,
+) -> Result<PackageSummary> {
+  let plan = {
+    let (project, options, context) = (project.clone(), options.clone(), context.clone());
+    tokio::task::spawn_blocking(move || project_package::plan_package(&project, &options, &context))
+      .await
+      .map_err(|e| VideoCompilerError::InternalError(format!("Package planning failed: {e}")))?
+  };
+
+  let mut files = plan.files;
+  let trim_starts = trim_package_media(
+    project,
+    &mut files,
+    options.trim_mode,
+    &context,
+    staging,
+    progress.as_ref(),
+    cancel,
+  )
+  .await?;
+
+  let archive_paths: HashMap<String, String> = files
+    .iter()
+    .filter(|file| file.kind == PackageEntryKind::Media)
+    .map(|file| (file.original_path.clone(), file.archive_path.clone()))
+    .collect();
+  let packaged = project_package::package_schema(project, &archive_paths, &trim_starts);
+
+  let output = output_zip.to_path_buf();
+  let cancel = cancel.clone();
+  let manifest = tokio::task::spawn_blocking(move || {
+    project_package::write_package_archive(&files, &packaged, &output, progress.as_ref(), &cancel)
+  })
+  .await
+  .map_err(|e| VideoCompilerError::InternalError(format!("Project packaging failed: {e}")))??;
+
+  Ok(PackageSummary {
+    output_path: output_zip.to_string_lossy().to_string(),
+    manifest,
+    missing: plan.missing,
+  })
+}
+
+/// Обрезать медиа плана до используемых фрагментов.
+///
+/// Обрезанные копии пишутся в `staging`, файлы плана переключаются на них.
+/// Возвращает фактическое начало фрагмента для каждого исходного пути.
+async fn trim_package_media(
+  project: &ProjectSchema,
+  files: &mut [PlannedPackageFile],
+  mode: TrimMode,
+  context: &PackageContext,
+  staging: &Path,
+  progress: Option<&PackageProgressSink>,
+  cancel: &CancellationToken,
+) -> Result<HashMap<String, f64>> {
+  let files_total = files.iter().filter(|file| file.trim.is_some()).count();
+  let mut trim_starts = HashMap::new();
+  if files_total == 0 {
+    return Ok(trim_starts);
+  }
+
+  tokio::fs::create_dir_all(staging).await?;
+  let builder = FFmpegBuilder::with_settings(
+    project.clone(),
+    FFmpegBuilderSettings {
+      ffmpeg_path: context.ffmpeg_path.clone(),
+      ..Default::default()
+    },
+  );
+
+  let mut files_done = 0;
+  for file in files.iter_mut() {
+    let Some(range) = file.trim else {
+      continue;
+    };
+    if let Some(sink) = progress {
+      sink(PackageProgress {
+        stage: PackageStage::Trimming,
+        current_file: Some(file.archive_path.clone()),
+        files_done,
+        files_total,
+        bytes_done: 0,
+        bytes_total: 0,
+      });
+    }
+
+    let stream_copy = mode == TrimMode::StreamCopy;
+    let start = if stream_copy {
+      probe_keyframe_start(&context.ffmpeg_path, &file.source_path, range.start)
+        .await
+        .unwrap_or(range.start)
+    } else {
+      range.start
+    };
+    let output = staging.join(
+      Path::new(&file.archive_path)
+        .file_name()
+        .unwrap_or_default(),
+    );
+
+    let mut cmd = builder.build_media_trim_command(
+      &file.source_path,
+      start,
+      range.end - start,
+      &output,
+      stream_copy,
+    );
+    cmd.kill_on_drop(true);
+    let command = format!("{:?}", cmd.as_std());
+    let result = tokio::select! {
+      result = cmd.output() => result?,
+      _ = cancel.cancelled() => {
+        return Err(VideoCompilerError::CancelledError(
+          "Упаковка проекта отменена".to_string(),
+        ));
+      }
+    };
+    if !result.status.success() {
+      return Err(VideoCompilerError::ffmpeg(
+        result.status.code(),
+        String::from_utf8_lossy(&result.stderr),
+        command,
+      ));
+    }
+
+    file.source_path = output;
+    file.trim = Some(TrimRange {
+      start,
+      end: range.end,
+    });
+    trim_starts.insert(file.original_path.clone(), start);
+    files_done += 1;
+  }
+
+  Ok(trim_starts)
+}
+
+/// Ключевой кадр, с которого начнется копирование потоков при обрезке с
+/// момента `start` (`None` для файлов без видео)
+async fn probe_keyframe_start(ffmpeg_path: &str, input: &Path, start: f64) -> Option<f64> {
+  let output = tokio::process::Command::new(ffprobe_path(ffmpeg_path))
+    .args(["-v", "error", "-select_streams", "v:0"])
+    .args(["-read_intervals", &format!("{start:.3}%+#1")])
+    .args(["-show_entries", "packet=pts_time", "-of", "csv=p=0"])
+    .arg(input)
+    .output()
+    .await
+    .ok()?;
+  String::from_utf8_lossy(&output.stdout)
+    .lines()
+    .find_map(|line| line.trim().trim_end_matches(',').parse::<f64>().ok())
+    .map(|keyframe| keyframe.min(start))
 }
 
 #[cfg(test)]
@@ -483,6 +1082,43 @@ mod tests {
     assert_eq!(project.timeline.fps, 30);
   }
 
+  #[tokio::test]
+  async fn test_save_writes_current_schema_version() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("project.json");
+    let service = ProjectServiceImpl::new();
+
+    let mut project = service.create_project("Old".to_string()).await.unwrap();
+    project.version = "1.0.0".to_string();
+    service.save_project(&project, &path).await.unwrap();
+
+    let raw: serde_json::Value =
+      serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(raw["version"], SCHEMA_VERSION);
+
+    let loaded = service
+      .load_project(&path)
+      .await
+      .unwrap()
+      .into_project()
+      .unwrap();
+    assert_eq!(loaded.version, SCHEMA_VERSION);
+    assert_eq!(loaded.metadata.name, "Old");
+  }
+
+  #[tokio::test]
+  async fn test_load_refuses_newer_schema() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("project.json");
+    std::fs::write(&path, r#"{"version": "99.0.0"}"#).unwrap();
+
+    let result = ProjectServiceImpl::new().load_project(&path).await;
+    assert!(matches!(
+      result,
+      Err(VideoCompilerError::UnsupportedProjectVersion { .. })
+    ));
+  }
+
   #[tokio::test]
   async fn test_project_analysis() {
     let service = ProjectServiceImpl::new();
@@ -496,4 +1132,136 @@ mod tests {
     assert_eq!(analysis.total_tracks, 0);
     assert!(analysis.missing_files.is_empty());
   }
+
+  #[tokio::test]
+  async fn test_project_template_lifecycle() {
+    let dir = tempfile::tempdir().unwrap();
+    let service = ProjectServiceImpl::with_templates_dir(dir.path().to_path_buf());
+
+    let mut project = service.create_project("Show".to_string()).await.unwrap();
+    let mut track = crate::video_compiler::schema::Track::new(
+      crate::video_compiler::schema::TrackType::Video,
+      "Video".to_string(),
+    );
+    track.clips.push(crate::video_compiler::schema::Clip::new(
+      PathBuf::from("/media/episode.mp4"),
+      0.0,
+      60.0,
+    ));
+    project.tracks.push(track);
+
+    let info = service
+      .save_project_as_template(&project, "Weekly".to_string(), false)
+      .await
+      .unwrap();
+    assert_eq!(info.roles, vec!["main"]);
+
+    let renamed = service
+      .rename_project_template(&info.id, "Weekly Show".to_string())
+      .await
+      .unwrap();
+    assert_eq!(renamed.name, "Weekly Show");
+
+    let listed = service.list_project_templates().await.unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].name, "Weekly Show");
+
+    let instance = service
+      .create_project_from_template(
+        &info.id,
+        "Episode 2".to_string(),
+        vec![TemplateMediaBinding {
+          role: "main".to_string(),
+          path: "/media/episode2.mp4".to_string(),
+          duration: Some(30.0),
+        }],
+      )
+      .await
+      .unwrap();
+    assert!(instance.warnings.is_empty());
+    assert_eq!(instance.project.get_duration(), 30.0);
+
+    service.delete_project_template(&info.id).await.unwrap();
+    assert!(service.list_project_templates().await.unwrap().is_empty());
+    assert!(matches!(
+      service.delete_project_template(&info.id).await,
+      Err(VideoCompilerError::TemplateNotFound(_))
+    ));
+  }
+
+  #[tokio::test]
+  async fn test_import_project_template_assigns_new_id_on_conflict() {
+    let dir = tempfile::tempdir().unwrap();
+    let service = ProjectServiceImpl::with_templates_dir(dir.path().join("templates"));
+    let project = service.create_project("Show".to_string()).await.unwrap();
+
+    let info = service
+      .save_project_as_template(&project, "Weekly".to_string(), false)
+      .await
+      .unwrap();
+    let exported = dir
+      .path()
+      .join("templates")
+      .join(format!("{}.json", info.id));
+    let copy = dir.path().join("shared.json");
+    std::fs::copy(exported, &copy).unwrap();
+
+    let imported = service.import_project_template(&copy).await.unwrap();
+    assert_ne!(imported.id, info.id);
+    assert_eq!(service.list_project_templates().await.unwrap().len(), 2);
+  }
+
+  #[tokio::test]
+  async fn test_schema_journal_undo_redo_sequence() {
+    let as_json = |project: &ProjectSchema| serde_json::to_value(project).unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let subtitle_path = dir.path().join("captions.srt");
+    std::fs::write(&subtitle_path, "1\n00:00:00,000 --> 00:00:02,000\nHello\n").unwrap();
+
+    let service = ProjectServiceImpl::new();
+    let original = service.create_project("Journal".to_string()).await.unwrap();
+
+    // Пресет экспорта
+    let mut with_preset = original.clone();
+    assert!(with_preset.settings.export.apply_preset("youtube"));
+    let change = service
+      .record_schema_change("p1", "Apply export preset", &original, &with_preset)
+      .await
+      .unwrap()
+      .unwrap();
+    let mut restored = as_json(&with_preset);
+    change.inverse.apply(&mut restored).unwrap();
+    assert_eq!(restored, as_json(&original));
+
+    // Импорт субтитров
+    let with_subtitles = crate::subtitles::import_subtitles_into_project(
+      with_preset.clone(),
+      &subtitle_path.to_string_lossy(),
+      None,
+    )
+    .unwrap()
+    .project;
+    service
+      .record_schema_change("p1", "Import subtitles", &with_preset, &with_subtitles)
+      .await
+      .unwrap()
+      .unwrap();
+
+    let undone = service.undo_schema_change("p1").await.unwrap().unwrap();
+    assert_eq!(as_json(&undone), as_json(&with_preset));
+
+    let undone = service.undo_schema_change("p1").await.unwrap().unwrap();
+    assert_eq!(as_json(&undone), as_json(&original));
+    assert!(service.undo_schema_change("p1").await.unwrap().is_none());
+
+    let redone = service.redo_schema_change("p1").await.unwrap().unwrap();
+    assert_eq!(as_json(&redone), as_json(&with_preset));
+
+    let state = service.schema_journal_state("p1").await.unwrap();
+    assert_eq!(state.undo_labels, vec!["Apply export preset"]);
+    assert_eq!(state.redo_labels, vec!["Import subtitles"]);
+
+    service.close_project("p1").await.unwrap();
+    assert!(service.redo_schema_change("p1").await.unwrap().is_none());
+  }
 }
@@ -0,0 +1,426 @@
+//! Хранение файлов проекта - атомарная запись, контрольная сумма и
+//! ротация резервных копий
+//!
+//! Файл проекта остается обычным JSON. Первым полем записывается
+//! `checksum` - SHA-256 остального содержимого файла, поэтому оборванная
+//! или испорченная запись обнаруживается при загрузке. Новое содержимое
+//! пишется во временный файл рядом с проектом и заменяет его переименованием;
+//! предыдущие версии сохраняются как `<файл>.bak`, `<файл>.bak.1`, ...
+
+use crate::video_compiler::{
+  core::error::{Result, VideoCompilerError},
+  schema::{migrate_project_value, MigratedProject, ProjectSchema, SCHEMA_VERSION},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::ffi::OsString;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Количество хранимых резервных копий
+pub const PROJECT_BACKUP_COUNT: usize = 3;
+
+/// Поле контрольной суммы в файле проекта
+const CHECKSUM_FIELD: &str = "checksum";
+
+/// Начало заголовка с контрольной суммой
+const CHECKSUM_HEADER_PREFIX: &str = "{\n  \"checksum\": \"sha256:";
+
+/// Результат загрузки проекта
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ProjectLoadResult {
+  /// Файл проекта прочитан без ошибок
+  Loaded { project: Box<ProjectSchema> },
+  /// Файл проекта поврежден, загружена самая свежая целая резервная копия
+  LoadedFromBackup {
+    project: Box<ProjectSchema>,
+    /// Путь к использованной резервной копии
+    which: PathBuf,
+    /// Описание повреждения основного файла
+    details: String,
+  },
+  /// Файл проекта и все резервные копии повреждены
+  Corrupt { details: String },
+}
+
+impl ProjectLoadResult {
+  /// Проект, если его удалось прочитать из файла или резервной копии
+  pub fn into_project(self) -> Result<ProjectSchema> {
+    match self {
+      Self::Loaded { project } | Self::LoadedFromBackup { project, .. } => Ok(*project),
+      Self::Corrupt { details } => Err(VideoCompilerError::validation(format!(
+        "Файл проекта поврежден: {details}"
+      ))),
+    }
+  }
+}
+
+/// Путь к резервной копии с номером `index` (0 - самая свежая)
+pub fn backup_path(path: &Path, index: usize) -> PathBuf {
+  let mut name = path.as_os_str().to_os_string();
+  name.push(".bak");
+  if index > 0 {
+    name.push(format!(".{index}"));
+  }
+  PathBuf::from(name)
+}
+
+/// Резервные копии проекта, от самой свежей к самой старой
+pub fn existing_backups(path: &Path) -> Vec<PathBuf> {
+  (0..PROJECT_BACKUP_COUNT)
+    .map(|index| backup_path(path, index))
+    .filter(|backup| backup.is_file())
+    .collect()
+}
+
+/// Сериализовать проект в текущей версии схемы с заголовком контрольной суммы
+pub fn encode_project(project: &ProjectSchema) -> Result<String> {
+  let mut value = serde_json::to_value(project)
+    .map_err(|e| VideoCompilerError::SerializationError(e.to_string()))?;
+  value["version"] = Value::String(SCHEMA_VERSION.to_string());
+  if let Some(root) = value.as_object_mut() {
+    root.remove(CHECKSUM_FIELD);
+  }
+  let body = serde_json::to_string_pretty(&value)
+    .map_err(|e| VideoCompilerError::SerializationError(e.to_string()))?;
+
+  // Тело pretty JSON объекта всегда начинается с "{\n"
+  let rest = &body[2..];
+  Ok(format!(
+    "{CHECKSUM_HEADER_PREFIX}{}\",\n{rest}",
+    sha256_hex(&format!("{{\n{rest}"))
+  ))
+}
+
+/// Проверить контрольную сумму и прочитать проект любой поддерживаемой версии.
+///
+/// Файлы без заголовка (сохраненные до появления контрольной суммы или
+/// другими программами) читаются без проверки.
+pub fn decode_project(content: &str) -> Result<MigratedProject> {
+  if let Some(header) = content.strip_prefix(CHECKSUM_HEADER_PREFIX) {
+    let (expected, rest) = header
+      .split_once("\",\n")
+      .ok_or_else(|| VideoCompilerError::validation("Некорректный заголовок контрольной суммы"))?;
+    let actual = sha256_hex(&format!("{{\n{rest}"));
+    if actual != expected {
+      return Err(VideoCompilerError::validation(format!(
+        "Контрольная сумма не совпадает: ожидалась {expected}, получена {actual}"
+      )));
+    }
+  }
+
+  let mut value: Value = serde_json::from_str(content)
+    .map_err(|e| VideoCompilerError::validation(format!("Некорректный JSON проекта: {e}")))?;
+  if let Some(root) = value.as_object_mut() {
+    root.remove(CHECKSUM_FIELD);
+  }
+  migrate_project_value(value)
+}
+
+/// Сохранить проект: валидация, ротация резервных копий и атомарная замена файла
+pub fn save_project_file(project: &ProjectSchema, path: &Path) -> Result<()> {
+  project.validate().map_err(VideoCompilerError::validation)?;
+  let content = encode_project(project)?;
+
+  if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+    std::fs::create_dir_all(parent)?;
+  }
+
+  let temp_path = temp_path_for(path);
+  if let Err(e) = write_synced(&temp_path, content.as_bytes()) {
+    let _ = std::fs::remove_file(&temp_path);
+    return Err(e.into());
+  }
+
+  if path.is_file() {
+    if let Err(e) = rotate_backups(path) {
+      let _ = std::fs::remove_file(&temp_path);
+      return Err(e);
+    }
+  }
+
+  replace_with_temp(&temp_path, path)
+}
+
+/// Загрузить проект; при повреждении основного файла - из самой свежей
+/// целой резервной копии
+pub fn load_project_file(path: &Path) -> Result<ProjectLoadResult> {
+  let details = match read_project(path) {
+    Ok(migrated) => {
+      log_migration(path, &migrated);
+      return Ok(ProjectLoadResult::Loaded {
+        project: Box::new(migrated.project),
+      });
+    }
+    // Отсутствующий файл и проект новой версии не считаются повреждением
+    Err(e @ VideoCompilerError::IoError(_))
+    | Err(e @ VideoCompilerError::UnsupportedProjectVersion { .. }) => return Err(e),
+    Err(e) => e.to_string(),
+  };
+  log::warn!("Файл проекта {} поврежден: {details}", path.display());
+
+  for backup in existing_backups(path) {
+    match read_project(&backup) {
+      Ok(migrated) => {
+        log_migration(&backup, &migrated);
+        return Ok(ProjectLoadResult::LoadedFromBackup {
+          project: Box::new(migrated.project),
+          which: backup,
+          details,
+        });
+      }
+      Err(e) => log::warn!("Резервная копия {} повреждена: {e}", backup.display()),
+    }
+  }
+
+  Ok(ProjectLoadResult::Corrupt { details })
+}
+
+fn read_project(path: &Path) -> Result<MigratedProject> {
+  let content = std::fs::read_to_string(path)
+    .map_err(|e| VideoCompilerError::IoError(format!("{}: {e}", path.display())))?;
+  decode_project(&content)
+}
+
+fn log_migration(path: &Path, migrated: &MigratedProject) {
+  for migration in &migrated.applied {
+    log::info!(
+      "Миграция схемы {} -> {}: {}",
+      migration.from,
+      migration.to,
+      migration.description
+    );
+  }
+  for warning in &migrated.warnings {
+    log::warn!("{}: {}", path.display(), warning);
+  }
+}
+
+/// Сдвинуть резервные копии на одну позицию и скопировать текущий файл в `.bak`.
+///
+/// Текущий файл копируется, а не переименовывается: до замены новым
+/// содержимым проект остается на месте.
+fn rotate_backups(path: &Path) -> Result<()> {
+  let oldest = backup_path(path, PROJECT_BACKUP_COUNT - 1);
+  if oldest.exists() {
+    std::fs::remove_file(&oldest)?;
+  }
+  for index in (0..PROJECT_BACKUP_COUNT - 1).rev() {
+    let from = backup_path(path, index);
+    if from.exists() {
+      std::fs::rename(&from, backup_path(path, index + 1))?;
+    }
+  }
+  let newest = backup_path(path, 0);
+  std::fs::copy(path, &newest)?;
+  File::open(&newest)?.sync_all()?;
+  Ok(())
+}
+
+/// Заменить файл проекта временным файлом.
+///
+/// На сетевых дисках переименование поверх существующего файла может
+/// завершиться ошибкой - тогда содержимое копируется с fsync.
+fn replace_with_temp(temp_path: &Path, path: &Path) -> Result<()> {
+  match std::fs::rename(temp_path, path) {
+    Ok(()) => {
+      sync_parent_dir(path);
+      Ok(())
+    }
+    Err(e) => {
+      log::warn!(
+        "Не удалось переименовать {} в {}: {e}, используется копирование",
+        temp_path.display(),
+        path.display()
+      );
+      let result = copy_replace(temp_path, path);
+      let _ = std::fs::remove_file(temp_path);
+      result
+    }
+  }
+}
+
+/// Перезаписать файл содержимым `source` с синхронизацией на диск
+fn copy_replace(source: &Path, path: &Path) -> Result<()> {
+  let content = std::fs::read(source)?;
+  write_synced(path, &content)?;
+  Ok(())
+}
+
+fn write_synced(path: &Path, content: &[u8]) -> std::io::Result<()> {
+  let mut file = OpenOptions::new()
+    .write(true)
+    .create(true)
+    .truncate(true)
+    .open(path)?;
+  file.write_all(content)?;
+  file.sync_all()
+}
+
+/// Временный файл в той же директории, что и проект
+fn temp_path_for(path: &Path) -> PathBuf {
+  let mut name = OsString::from(".");
+  name.push(path.file_name().unwrap_or_default());
+  name.push(format!(".{}.tmp", Uuid::new_v4().simple()));
+  path.with_file_name(name)
+}
+
+/// Синхронизировать директорию, чтобы переименование пережило сбой питания
+fn sync_parent_dir(path: &Path) {
+  #[cfg(unix)]
+  if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+    if let Ok(dir) = File::open(parent) {
+      let _ = dir.sync_all();
+    }
+  }
+  #[cfg(not(unix))]
+  let _ = path;
+}
+
+fn sha256_hex(content: &str) -> String {
+  Sha256::digest(content.as_bytes())
+    .iter()
+    .map(|byte| format!("{byte:02x}"))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn project(name: &str) -> ProjectSchema {
+    ProjectSchema::new(name.to_string())
+  }
+
+  #[test]
+  fn test_round_trip_with_checksum() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("project.json");
+
+    save_project_file(&project("Alpha"), &path).unwrap();
+
+    let content = std::fs::read_to_string(&path).unwrap();
+    assert!(content.starts_with(CHECKSUM_HEADER_PREFIX));
+    let raw: Value = serde_json::from_str(&content).unwrap();
+    assert_eq!(raw["version"], SCHEMA_VERSION);
+
+    match load_project_file(&path).unwrap() {
+      ProjectLoadResult::Loaded { project } => assert_eq!(project.metadata.name, "Alpha"),
+      other => panic!("unexpected result: {other:?}"),
+    }
+  }
+
+  #[test]
+  fn test_checksum_mismatch_is_detected() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("project.json");
+    save_project_file(&project("Alpha"), &path).unwrap();
+
+    // JSON остается корректным, но содержимое изменено вне приложения
+    let tampered = std::fs::read_to_string(&path)
+      .unwrap()
+      .replace("\"Alpha\"", "\"Omega\"");
+    std::fs::write(&path, tampered).unwrap();
+
+    let error = decode_project(&std::fs::read_to_string(&path).unwrap()).unwrap_err();
+    assert!(error.to_string().contains("Контрольная сумма"));
+    assert!(matches!(
+      load_project_file(&path).unwrap(),
+      ProjectLoadResult::Corrupt { .. }
+    ));
+  }
+
+  #[test]
+  fn test_interrupted_write_falls_back_to_backup() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("project.json");
+    save_project_file(&project("First"), &path).unwrap();
+    save_project_file(&project("Second"), &path).unwrap();
+
+    // Запись, оборванная на середине: временный файл усечен
+    let temp = temp_path_for(&path);
+    let encoded = encode_project(&project("Third")).unwrap();
+    std::fs::write(&temp, &encoded[..encoded.len() / 2]).unwrap();
+
+    // Временный файл не затрагивает сохраненный проект
+    match load_project_file(&path).unwrap() {
+      ProjectLoadResult::Loaded { project } => assert_eq!(project.metadata.name, "Second"),
+      other => panic!("unexpected result: {other:?}"),
+    }
+
+    // Усеченный файл на месте проекта (копирование на сетевом диске)
+    std::fs::rename(&temp, &path).unwrap();
+    match load_project_file(&path).unwrap() {
+      ProjectLoadResult::LoadedFromBackup { project, which, .. } => {
+        assert_eq!(project.metadata.name, "First");
+        assert_eq!(which, backup_path(&path, 0));
+      }
+      other => panic!("unexpected result: {other:?}"),
+    }
+  }
+
+  #[test]
+  fn test_backup_rotation_keeps_newest_first() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("project.json");
+    for name in ["v1", "v2", "v3", "v4", "v5"] {
+      save_project_file(&project(name), &path).unwrap();
+    }
+
+    let names: Vec<String> = existing_backups(&path)
+      .iter()
+      .map(|backup| {
+        let content = std::fs::read_to_string(backup).unwrap();
+        decode_project(&content).unwrap().project.metadata.name
+      })
+      .collect();
+    assert_eq!(names, vec!["v4", "v3", "v2"]);
+    assert!(!backup_path(&path, PROJECT_BACKUP_COUNT).exists());
+
+    // Временные файлы не остаются в директории проекта
+    let leftovers = std::fs::read_dir(dir.path())
+      .unwrap()
+      .filter_map(|entry| entry.ok())
+      .filter(|entry| entry.file_name().to_string_lossy().ends_with(".tmp"))
+      .count();
+    assert_eq!(leftovers, 0);
+  }
+
+  #[test]
+  fn test_save_rejects_invalid_project() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("project.json");
+    let mut invalid = project("Broken");
+    invalid.timeline.fps = 0;
+
+    assert!(save_project_file(&invalid, &path).is_err());
+    assert!(!path.exists());
+  }
+
+  #[test]
+  fn test_copy_replace_overwrites_target() {
+    let dir = tempfile::tempdir().unwrap();
+    let (source, target) = (dir.path().join("new"), dir.path().join("old"));
+    std::fs::write(&source, "new content").unwrap();
+    std::fs::write(&target, "old content that is longer").unwrap();
+
+    copy_replace(&source, &target).unwrap();
+    assert_eq!(std::fs::read_to_string(&target).unwrap(), "new content");
+  }
+
+  #[test]
+  fn test_unchecked_legacy_file_loads() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("project.json");
+    std::fs::write(&path, serde_json::to_string(&project("Legacy")).unwrap()).unwrap();
+
+    assert!(matches!(
+      load_project_file(&path).unwrap(),
+      ProjectLoadResult::Loaded { .. }
+    ));
+  }
+}
@@ -0,0 +1,613 @@
+//! Шаблоны проектов - переиспользуемые заготовки с раскладкой треков,
+//! настройками экспорта и слотами медиа (интро, основная часть, аутро)
+
+use crate::video_compiler::{
+  core::error::{Result, VideoCompilerError},
+  schema::{
+    ClipSource, Effect, Filter, ProjectSchema, StyleTemplate, Subtitle, Template, Transition,
+  },
+};
+use chrono::{DateTime, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Расширение файлов шаблонов
+pub const TEMPLATE_FILE_EXTENSION: &str = "json";
+
+/// Слот медиа в шаблоне: все клипы, использовавшие один исходный файл
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateMediaSlot {
+  /// Роль слота ("intro", "main", "outro", "main_2", ...)
+  pub role: String,
+  /// Имя исходного файла (без директории)
+  pub file_name: String,
+  /// Абсолютный путь к исходному файлу, если шаблон сохранен с медиа
+  pub original_path: Option<String>,
+  /// Использованная длительность исходника (максимальный source_end клипов слота)
+  pub duration: f64,
+  /// ID клипов, привязанных к слоту
+  pub clip_ids: Vec<String>,
+}
+
+/// Шаблон проекта
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectTemplate {
+  /// Уникальный идентификатор шаблона
+  pub id: String,
+  /// Название шаблона
+  pub name: String,
+  /// Время создания
+  pub created_at: DateTime<Utc>,
+  /// Время последнего изменения
+  pub modified_at: DateTime<Utc>,
+  /// Сохранены ли абсолютные пути к медиа
+  pub includes_media: bool,
+  /// Слоты медиа
+  pub slots: Vec<TemplateMediaSlot>,
+  /// Заготовка проекта
+  pub project: ProjectSchema,
+}
+
+/// Краткая информация о шаблоне для списка
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectTemplateInfo {
+  pub id: String,
+  pub name: String,
+  pub created_at: DateTime<Utc>,
+  pub modified_at: DateTime<Utc>,
+  pub includes_media: bool,
+  /// Роли слотов в порядке появления на timeline
+  pub roles: Vec<String>,
+  pub track_count: usize,
+  /// Предупреждения, полученные при загрузке файла шаблона
+  pub warnings: Vec<String>,
+}
+
+/// Привязка медиафайла к слоту шаблона
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateMediaBinding {
+  /// Роль слота
+  pub role: String,
+  /// Путь к новому медиафайлу
+  pub path: String,
+  /// Длительность нового файла (если известна)
+  pub duration: Option<f64>,
+}
+
+/// Результат создания проекта из шаблона
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateInstance {
+  pub project: ProjectSchema,
+  pub warnings: Vec<String>,
+}
+
+impl ProjectTemplate {
+  /// Создать шаблон из проекта.
+  ///
+  /// Каждый исходный файл становится слотом. Роли назначаются по порядку
+  /// первого появления на timeline: при трех и более файлах первый - "intro",
+  /// последний - "outro", остальные - "main", "main_2", ... Без `include_media`
+  /// в клипах остается только имя файла.
+  pub fn from_project(project: &ProjectSchema, name: String, include_media: bool) -> Self {
+    let mut project = project.clone();
+    let now = Utc::now();
+    project.metadata.name = name.clone();
+
+    // Собираем исходные файлы в порядке первого появления
+    let mut first_use: Vec<(String, f64)> = Vec::new();
+    let mut usage: HashMap<String, (f64, Vec<String>)> = HashMap::new();
+    for track in &project.tracks {
+      for clip in &track.clips {
+        if let ClipSource::File(path) = &clip.source {
+          let entry = usage.entry(path.clone()).or_insert_with(|| {
+            first_use.push((path.clone(), clip.start_time));
+            (0.0, Vec::new())
+          });
+          entry.0 = entry.0.max(clip.source_end);
+          entry.1.push(clip.id.clone());
+          if let Some(first) = first_use.iter_mut().find(|(p, _)| p == path) {
+            first.1 = first.1.min(clip.start_time);
+          }
+        }
+      }
+    }
+    first_use.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+    let roles = assign_roles(first_use.len());
+    let mut slots = Vec::with_capacity(first_use.len());
+    let mut replacements = HashMap::new();
+    for ((path, _), role) in first_use.into_iter().zip(roles) {
+      let (duration, clip_ids) = usage.remove(&path).unwrap_or_default();
+      let file_name = Path::new(&path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.clone());
+
+      if !include_media {
+        replacements.insert(path.clone(), file_name.clone());
+      }
+      slots.push(TemplateMediaSlot {
+        role,
+        file_name,
+        original_path: include_media.then_some(path),
+        duration,
+        clip_ids,
+      });
+    }
+
+    if !include_media {
+      for track in &mut project.tracks {
+        for clip in &mut track.clips {
+          if let ClipSource::File(path) = &mut clip.source {
+            if let Some(file_name) = replacements.get(path.as_str()) {
+              *path = file_name.clone();
+            }
+          }
+        }
+      }
+    }
+
+    Self {
+      id: Uuid::new_v4().to_string(),
+      name,
+      created_at: now,
+      modified_at: now,
+      includes_media: include_media,
+      slots,
+      project,
+    }
+  }
+
+  /// Краткая информация о шаблоне
+  pub fn info(&self, warnings: Vec<String>) -> ProjectTemplateInfo {
+    ProjectTemplateInfo {
+      id: self.id.clone(),
+      name: self.name.clone(),
+      created_at: self.created_at,
+      modified_at: self.modified_at,
+      includes_media: self.includes_media,
+      roles: self.slots.iter().map(|s| s.role.clone()).collect(),
+      track_count: self.project.tracks.len(),
+      warnings,
+    }
+  }
+
+  /// Создать новый проект, подставив медиафайлы в слоты.
+  ///
+  /// Клипы слота с известной длительностью нового файла масштабируются
+  /// пропорционально, последующие клипы того же трека сдвигаются на разницу.
+  /// Слоты без привязки остаются как есть и попадают в предупреждения.
+  pub fn instantiate(&self, name: String, bindings: &[TemplateMediaBinding]) -> TemplateInstance {
+    let mut project = self.project.clone();
+    let mut warnings = Vec::new();
+    let now = Utc::now();
+    project.metadata.name = name;
+    project.metadata.created_at = now;
+    project.metadata.modified_at = now;
+
+    // clip_id -> (новый путь, коэффициент масштабирования)
+    let mut clip_bindings: HashMap<&str, (&str, Option<f64>)> = HashMap::new();
+    for binding in bindings {
+      let Some(slot) = self.slots.iter().find(|s| s.role == binding.role) else {
+        warnings.push(format!("В шаблоне нет слота '{}'", binding.role));
+        continue;
+      };
+      let scale = binding
+        .duration
+        .filter(|d| *d > 0.0 && slot.duration > 0.0)
+        .map(|d| d / slot.duration);
+      for clip_id in &slot.clip_ids {
+        clip_bindings.insert(clip_id.as_str(), (binding.path.as_str(), scale));
+      }
+    }
+
+    for slot in &self.slots {
+      if !bindings.iter().any(|b| b.role == slot.role) {
+        warnings.push(format!(
+          "Слот '{}' не привязан к медиафайлу ({})",
+          slot.role, slot.file_name
+        ));
+      }
+    }
+
+    for track in &mut project.tracks {
+      track
+        .clips
+        .sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+
+      let mut shift = 0.0;
+      for clip in &mut track.clips {
+        let length = clip.end_time - clip.start_time;
+        clip.start_time += shift;
+        clip.end_time = clip.start_time + length;
+
+        if let Some((path, scale)) = clip_bindings.get(clip.id.as_str()) {
+          clip.source = ClipSource::File(path.to_string());
+          if let Some(scale) = scale {
+            let new_length = length * scale;
+            clip.source_start *= scale;
+            clip.source_end *= scale;
+            clip.end_time = clip.start_time + new_length;
+            shift += new_length - length;
+          }
+        }
+      }
+    }
+
+    project.timeline.duration = project.get_duration();
+    TemplateInstance { project, warnings }
+  }
+}
+
+/// Назначить роли слотам по порядку появления
+fn assign_roles(count: usize) -> Vec<String> {
+  let main_role = |index: usize| {
+    if index == 0 {
+      "main".to_string()
+    } else {
+      format!("main_{}", index + 1)
+    }
+  };
+
+  if count < 3 {
+    return (0..count).map(main_role).collect();
+  }
+
+  let mut roles = Vec::with_capacity(count);
+  roles.push("intro".to_string());
+  roles.extend((0..count - 2).map(main_role));
+  roles.push("outro".to_string());
+  roles
+}
+
+/// Разобрать файл шаблона, пропуская элементы, которые не удается прочитать.
+///
+/// Шаблоны с другой машины могут ссылаться на неизвестные типы эффектов,
+/// отсутствующие шрифты и т.п. - такие шаблоны загружаются, а проблемы
+/// возвращаются в виде предупреждений.
+pub fn parse_template(
+  content: &str,
+  font_dirs: &[PathBuf],
+) -> Result<(ProjectTemplate, Vec<String>)> {
+  let mut value: Value = serde_json::from_str(content).map_err(|e| {
+    VideoCompilerError::ValidationError(format!("Некорректный JSON шаблона: {e}").into())
+  })?;
+  let mut warnings = Vec::new();
+
+  if let Some(project) = value.get_mut("project").and_then(Value::as_object_mut) {
+    retain_readable::<Effect>(project.get_mut("effects"), "эффект", &mut warnings);
+    retain_readable::<Filter>(project.get_mut("filters"), "фильтр", &mut warnings);
+    retain_readable::<Transition>(project.get_mut("transitions"), "переход", &mut warnings);
+    retain_readable::<Template>(
+      project.get_mut("templates"),
+      "шаблон раскладки",
+      &mut warnings,
+    );
+    retain_readable::<StyleTemplate>(
+      project.get_mut("style_templates"),
+      "стильный шаблон",
+      &mut warnings,
+    );
+    retain_readable::<Subtitle>(project.get_mut("subtitles"), "субтитр", &mut warnings);
+  }
+
+  let mut template: ProjectTemplate = serde_json::from_value(value)
+    .map_err(|e| VideoCompilerError::ValidationError(format!("Некорректный шаблон: {e}").into()))?;
+
+  drop_dangling_references(&mut template.project, &mut warnings);
+
+  for family in find_missing_fonts(&used_font_families(&template.project), font_dirs) {
+    warnings.push(format!("Шрифт '{family}' не найден в системе"));
+  }
+
+  Ok((template, warnings))
+}
+
+/// Оставить в JSON-массиве только элементы, которые читаются как `T`
+pub(crate) fn retain_readable<T: DeserializeOwned>(
+  array: Option<&mut Value>,
+  kind: &str,
+  warnings: &mut Vec<String>,
+) {
+  let Some(items) = array.and_then(Value::as_array_mut) else {
+    return;
+  };
+  items.retain(|item| match serde_json::from_value::<T>(item.clone()) {
+    Ok(_) => true,
+    Err(e) => {
+      let id = item.get("id").and_then(Value::as_str).unwrap_or("?");
+      warnings.push(format!("Пропущен {kind} '{id}': {e}"));
+      false
+    }
+  });
+}
+
+/// Удалить ссылки клипов и треков на отсутствующие эффекты, фильтры и раскладки
+fn drop_dangling_references(project: &mut ProjectSchema, warnings: &mut Vec<String>) {
+  let effect_ids: HashSet<String> = project.effects.iter().map(|e| e.id.clone()).collect();
+  let filter_ids: HashSet<String> = project.filters.iter().map(|f| f.id.clone()).collect();
+  let template_ids: HashSet<String> = project.templates.iter().map(|t| t.id.clone()).collect();
+
+  let mut retain = |ids: &mut Vec<String>, known: &HashSet<String>, kind: &str| {
+    ids.retain(|id| {
+      let found = known.contains(id);
+      if !found {
+        warnings.push(format!("Ссылка на отсутствующий {kind} '{id}' удалена"));
+      }
+      found
+    });
+  };
+
+  for track in &mut project.tracks {
+    retain(&mut track.effects, &effect_ids, "эффект");
+    retain(&mut track.filters, &filter_ids, "фильтр");
+    for clip in &mut track.clips {
+      retain(&mut clip.effects, &effect_ids, "эффект");
+      retain(&mut clip.filters, &filter_ids, "фильтр");
+    }
+  }
+
+  for track in &mut project.tracks {
+    for clip in &mut track.clips {
+      if let Some(template_id) = &clip.template_id {
+        if !template_ids.contains(template_id) {
+          warnings.push(format!(
+            "Ссылка на отсутствующий шаблон раскладки '{template_id}' удалена"
+          ));
+          clip.template_id = None;
+          clip.template_position = None;
+        }
+      }
+    }
+  }
+}
+
+/// Семейства шрифтов, используемые в субтитрах и стильных шаблонах
+fn used_font_families(project: &ProjectSchema) -> Vec<String> {
+  let mut families: Vec<String> = Vec::new();
+  let mut push = |family: &str| {
+    if !family.is_empty() && !families.iter().any(|f| f == family) {
+      families.push(family.to_string());
+    }
+  };
+
+  for subtitle in &project.subtitles {
+    push(&subtitle.style.font_family);
+    push(&subtitle.font_family);
+  }
+  for template in &project.style_templates {
+    for element in &template.elements {
+      if let Some(family) = &element.properties.font_family {
+        push(family);
+      }
+      if let Some(family) = element.style.as_ref().and_then(|s| s.font_family.as_ref()) {
+        push(family);
+      }
+    }
+  }
+
+  families
+}
+
+/// Стандартные директории шрифтов текущей платформы
+pub fn system_font_dirs() -> Vec<PathBuf> {
+  let mut dirs = Vec::new();
+
+  #[cfg(target_os = "macos")]
+  {
+    dirs.push(PathBuf::from("/System/Library/Fonts"));
+    dirs.push(PathBuf::from("/Library/Fonts"));
+  }
+
+  #[cfg(target_os = "windows")]
+  {
+    if let Ok(windir) = std::env::var("WINDIR") {
+      dirs.push(PathBuf::from(windir).join("Fonts"));
+    }
+  }
+
+  #[cfg(target_os = "linux")]
+  {
+    dirs.push(PathBuf::from("/usr/share/fonts"));
+    dirs.push(PathBuf::from("/usr/local/share/fonts"));
+  }
+
+  if let Some(font_dir) = dirs::font_dir() {
+    dirs.push(font_dir);
+  }
+
+  dirs
+}
+
+/// Найти семейства, для которых нет файла шрифта в указанных директориях.
+///
+/// Сопоставление приблизительное: имя файла без расширения, приведенное к
+/// нижнему регистру без пробелов и дефисов, должно начинаться с имени семейства.
+/// Если в директориях нет ни одного шрифта, проверка не выполняется.
+pub fn find_missing_fonts(families: &[String], font_dirs: &[PathBuf]) -> Vec<String> {
+  fn normalize(name: &str) -> String {
+    name
+      .chars()
+      .filter(|c| c.is_alphanumeric())
+      .flat_map(char::to_lowercase)
+      .collect()
+  }
+
+  fn collect(dir: &Path, depth: usize, stems: &mut Vec<String>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+      return;
+    };
+    for entry in entries.flatten() {
+      let path = entry.path();
+      if path.is_dir() {
+        if depth > 0 {
+          collect(&path, depth - 1, stems);
+        }
+      } else if matches!(
+        path
+          .extension()
+          .and_then(|e| e.to_str())
+          .map(str::to_lowercase)
+          .as_deref(),
+        Some("ttf" | "otf" | "ttc" | "woff" | "woff2")
+      ) {
+        if let Some(stem) = path.file_stem() {
+          stems.push(normalize(&stem.to_string_lossy()));
+        }
+      }
+    }
+  }
+
+  let mut stems = Vec::new();
+  for dir in font_dirs {
+    collect(dir, 4, &mut stems);
+  }
+  if stems.is_empty() {
+    return Vec::new();
+  }
+
+  families
+    .iter()
+    .filter(|family| {
+      let family = normalize(family);
+      !stems.iter().any(|stem| stem.starts_with(&family))
+    })
+    .cloned()
+    .collect()
+}
+
+/// Путь к файлу шаблона в директории шаблонов
+pub fn template_path(dir: &Path, template_id: &str) -> Result<PathBuf> {
+  // ID используется как имя файла, поэтому не допускаем разделителей путей
+  if template_id.is_empty()
+    || template_id
+      .chars()
+      .any(|c| !(c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+  {
+    return Err(VideoCompilerError::InvalidParameter(format!(
+      "Некорректный ID шаблона: {template_id}"
+    )));
+  }
+  Ok(dir.join(format!("{template_id}.{TEMPLATE_FILE_EXTENSION}")))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::video_compiler::schema::{Clip, Track, TrackType};
+
+  fn clip(id: &str, path: &str, start: f64, end: f64) -> Clip {
+    let mut clip = Clip::new(PathBuf::from(path), start, end - start);
+    clip.id = id.to_string();
+    clip
+  }
+
+  fn show_project() -> ProjectSchema {
+    let mut project = ProjectSchema::new("Weekly Show".to_string());
+    let mut video = Track::new(TrackType::Video, "Video".to_string());
+    video
+      .clips
+      .push(clip("c1", "/media/ep1/intro.mp4", 0.0, 5.0));
+    video
+      .clips
+      .push(clip("c2", "/media/ep1/episode.mp4", 5.0, 65.0));
+    video
+      .clips
+      .push(clip("c3", "/media/ep1/outro.mp4", 65.0, 70.0));
+    project.tracks.push(video);
+    project
+  }
+
+  #[test]
+  fn test_save_strips_absolute_paths() {
+    let template = ProjectTemplate::from_project(&show_project(), "Show".to_string(), false);
+
+    let roles: Vec<_> = template.slots.iter().map(|s| s.role.as_str()).collect();
+    assert_eq!(roles, vec!["intro", "main", "outro"]);
+    assert!(template.slots.iter().all(|s| s.original_path.is_none()));
+    for clip in &template.project.tracks[0].clips {
+      match &clip.source {
+        ClipSource::File(path) => assert!(!path.starts_with('/'), "{path}"),
+        other => panic!("unexpected source {other:?}"),
+      }
+    }
+
+    let with_media = ProjectTemplate::from_project(&show_project(), "Show".to_string(), true);
+    assert_eq!(
+      with_media.slots[1].original_path.as_deref(),
+      Some("/media/ep1/episode.mp4")
+    );
+  }
+
+  #[test]
+  fn test_assign_roles() {
+    assert_eq!(assign_roles(1), vec!["main"]);
+    assert_eq!(assign_roles(2), vec!["main", "main_2"]);
+    assert_eq!(assign_roles(4), vec!["intro", "main", "main_2", "outro"]);
+  }
+
+  #[test]
+  fn test_instantiate_rescales_and_shifts() {
+    let template = ProjectTemplate::from_project(&show_project(), "Show".to_string(), false);
+    let bindings = vec![TemplateMediaBinding {
+      role: "main".to_string(),
+      path: "/media/ep2/episode.mp4".to_string(),
+      duration: Some(90.0),
+    }];
+
+    let instance = template.instantiate("Episode 2".to_string(), &bindings);
+    let clips = &instance.project.tracks[0].clips;
+    assert_eq!(instance.project.metadata.name, "Episode 2");
+    assert!(matches!(&clips[1].source, ClipSource::File(p) if p == "/media/ep2/episode.mp4"));
+    assert!((clips[1].end_time - 95.0).abs() < 1e-9);
+    assert!((clips[2].start_time - 95.0).abs() < 1e-9);
+    assert!((instance.project.timeline.duration - 100.0).abs() < 1e-9);
+
+    // intro и outro не привязаны
+    assert_eq!(instance.warnings.len(), 2);
+  }
+
+  #[test]
+  fn test_parse_template_tolerates_unknown_effects() {
+    let mut template = ProjectTemplate::from_project(&show_project(), "Show".to_string(), false);
+    template.project.tracks[0].clips[0]
+      .effects
+      .push("missing-effect".to_string());
+
+    let mut value = serde_json::to_value(&template).unwrap();
+    value["project"]["effects"] = serde_json::json!([{ "id": "fx-1", "effect_type": "HoloBlur" }]);
+    let content = serde_json::to_string(&value).unwrap();
+
+    let (loaded, warnings) = parse_template(&content, &[]).unwrap();
+    assert!(loaded.project.effects.is_empty());
+    assert!(loaded.project.tracks[0].clips[0].effects.is_empty());
+    assert_eq!(warnings.len(), 2);
+    assert!(warnings[0].contains("fx-1"));
+  }
+
+  #[test]
+  fn test_find_missing_fonts() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("truetype")).unwrap();
+    std::fs::write(dir.path().join("truetype/OpenSans-Regular.ttf"), b"").unwrap();
+
+    let families = vec!["Open Sans".to_string(), "Brand Display".to_string()];
+    let missing = find_missing_fonts(&families, &[dir.path().to_path_buf()]);
+    assert_eq!(missing, vec!["Brand Display"]);
+
+    // Без шрифтов проверку не выполняем
+    let empty = tempfile::tempdir().unwrap();
+    assert!(find_missing_fonts(&families, &[empty.path().to_path_buf()]).is_empty());
+  }
+
+  #[test]
+  fn test_template_path_rejects_traversal() {
+    let dir = Path::new("/templates");
+    assert!(template_path(dir, "abc-123").is_ok());
+    assert!(template_path(dir, "../secret").is_err());
+    assert!(template_path(dir, "").is_err());
+  }
+}
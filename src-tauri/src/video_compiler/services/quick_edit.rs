@@ -0,0 +1,1014 @@
+//! Quick Edit - автоматический монтаж папки одной задачей
+//!
+//! Этапы: сканирование папки, анализ файлов (качество сегментов и ключевые
+//! моменты), детекция битов музыки, план монтажа, схема проекта и рендер.
+//! План и проект сохраняются до рендера, поэтому сгенерированный проект можно
+//! открыть и доработать вручную. Тяжелые этапы выполняет [`QuickEditBackend`],
+//! рендер - [`RenderService`].
+
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+
+use crate::montage_planner::services::{Beat, BeatGrid};
+use crate::montage_planner::types::{DetectedMoment, MontageConfig, MontagePlan, MontageStyle};
+use crate::video_compiler::core::render_priority::ProcessLimits;
+use crate::video_compiler::error::{Result, VideoCompilerError};
+use crate::video_compiler::schema::{
+  Clip, ExportSettings, Fade, FadeCurve, ProjectSchema, Track, TrackType,
+};
+use crate::video_compiler::services::batch_export::validate_output_path;
+use crate::video_compiler::services::project_storage::save_project_file;
+use crate::video_compiler::services::render_service::{RenderJobStatus, RenderService};
+
+/// Интервал опроса задачи рендеринга
+pub const QUICK_EDIT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Минимальное качество моментов для плана
+const QUICK_EDIT_QUALITY_THRESHOLD: f32 = 50.0;
+
+/// Вес разнообразия моментов в плане
+const QUICK_EDIT_DIVERSITY_WEIGHT: f32 = 0.5;
+
+/// Этап Quick Edit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuickEditStage {
+  Scanning,
+  Analyzing,
+  Planning,
+  Rendering,
+}
+
+impl QuickEditStage {
+  /// Общий прогресс (0-100) в начале и в конце этапа
+  pub fn percent_range(self) -> (f64, f64) {
+    match self {
+      Self::Scanning => (0.0, 10.0),
+      Self::Analyzing => (10.0, 40.0),
+      Self::Planning => (40.0, 50.0),
+      Self::Rendering => (50.0, 100.0),
+    }
+  }
+
+  fn overall_percent(self, stage_percent: f64) -> f64 {
+    let (start, end) = self.percent_range();
+    start + (end - start) * stage_percent.clamp(0.0, 100.0) / 100.0
+  }
+}
+
+/// Итоговый статус Quick Edit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuickEditStatus {
+  /// Видео отрендерено
+  Completed,
+  /// План и проект готовы, рендер не запрашивался
+  Planned,
+  Failed,
+  Cancelled,
+}
+
+/// Прогресс Quick Edit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuickEditProgress {
+  pub workflow_id: String,
+  pub stage: QuickEditStage,
+  /// Прогресс текущего этапа (0-100)
+  pub stage_percent: f64,
+  /// Общий прогресс (0-100)
+  pub overall_percent: f64,
+  pub message: String,
+}
+
+/// Параметры Quick Edit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuickEditRequest {
+  pub input_folder: PathBuf,
+  /// Музыка для звуковой дорожки и привязки склеек к битам
+  #[serde(default)]
+  pub music_path: Option<PathBuf>,
+  /// Желаемая длительность результата, с
+  pub target_duration: f64,
+  pub style: MontageStyle,
+  pub output_path: PathBuf,
+  /// Пресет экспорта ("youtube", "instagram", "twitter")
+  #[serde(default)]
+  pub export_preset: Option<String>,
+  /// Рендерить сразу; без рендера возвращаются только план и проект
+  #[serde(default = "default_render")]
+  pub render: bool,
+  /// Папка для плана и проекта; по умолчанию папка результата
+  #[serde(default)]
+  pub artifacts_dir: Option<PathBuf>,
+}
+
+fn default_render() -> bool {
+  true
+}
+
+impl QuickEditRequest {
+  /// Проверить параметры до запуска этапов
+  pub fn validate(&self) -> Result<()> {
+    if !self.input_folder.is_dir() {
+      return Err(VideoCompilerError::InvalidPath(format!(
+        "Папка с материалами не найдена: {}",
+        self.input_folder.display()
+      )));
+    }
+    if !self.target_duration.is_finite() || self.target_duration <= 0.0 {
+      return Err(VideoCompilerError::InvalidParameter(format!(
+        "Некорректная длительность результата: {}",
+        self.target_duration
+      )));
+    }
+    if let Some(music) = &self.music_path {
+      if !music.is_file() {
+        return Err(VideoCompilerError::MediaFileError {
+          path: music.to_string_lossy().to_string(),
+          reason: "Музыкальный файл не найден".to_string(),
+        });
+      }
+    }
+    if let Some(preset) = &self.export_preset {
+      if !ExportSettings::default().apply_preset(preset) {
+        return Err(VideoCompilerError::InvalidParameter(format!(
+          "Неизвестный пресет экспорта: {preset}"
+        )));
+      }
+    }
+    validate_output_path(&self.output_path)
+  }
+
+  /// Путь промежуточного файла рядом с результатом: `<имя>.<suffix>`
+  fn artifact_path(&self, suffix: &str) -> PathBuf {
+    let dir = self.artifacts_dir.clone().unwrap_or_else(|| {
+      self
+        .output_path
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default()
+    });
+    let stem = self
+      .output_path
+      .file_stem()
+      .map(|stem| stem.to_string_lossy().to_string())
+      .unwrap_or_else(|| "quick_edit".to_string());
+    dir.join(format!("{stem}.{suffix}"))
+  }
+}
+
+/// Предупреждение, не прервавшее Quick Edit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuickEditWarning {
+  pub stage: QuickEditStage,
+  pub path: Option<String>,
+  pub message: String,
+}
+
+/// Сохраненные промежуточные результаты
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuickEditArtifacts {
+  /// План монтажа (JSON)
+  pub plan_path: PathBuf,
+  /// Сгенерированный проект для ручной доработки
+  pub project_path: PathBuf,
+}
+
+/// Итог Quick Edit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuickEditResult {
+  pub workflow_id: String,
+  pub status: QuickEditStatus,
+  /// Последний начатый этап
+  pub stage: QuickEditStage,
+  pub error: Option<String>,
+  /// Файлы, попавшие в анализ успешно
+  pub source_files: Vec<String>,
+  /// Файлы, пропущенные из-за ошибки анализа
+  pub skipped_files: usize,
+  pub plan: Option<MontagePlan>,
+  pub project: Option<ProjectSchema>,
+  pub artifacts: Option<QuickEditArtifacts>,
+  pub render_job_id: Option<String>,
+  /// Путь к видео после успешного рендера
+  pub output_path: Option<PathBuf>,
+  pub warnings: Vec<QuickEditWarning>,
+  pub total_time_ms: u64,
+}
+
+/// Получатель прогресса Quick Edit
+pub type QuickEditProgressSink = Arc<dyn Fn(QuickEditProgress) + Send + Sync>;
+
+/// Тяжелые этапы Quick Edit
+#[async_trait]
+pub trait QuickEditBackend: Send + Sync {
+  /// Найти и обработать видеофайлы папки
+  async fn scan(&self, folder: &Path, cancel: &CancellationToken) -> Result<Vec<PathBuf>>;
+
+  /// Ключевые моменты файла с учетом качества его сегментов
+  async fn analyze(&self, file: &Path) -> Result<Vec<DetectedMoment>>;
+
+  /// Биты музыкального трека
+  async fn detect_beats(&self, music: &Path) -> Result<Vec<Beat>>;
+
+  /// План монтажа по моментам всех файлов
+  async fn plan(
+    &self,
+    moments: &[DetectedMoment],
+    config: &MontageConfig,
+    source_files: &[String],
+    beat_grid: Option<&BeatGrid>,
+  ) -> Result<MontagePlan>;
+}
+
+/// Quick Edit: сканирование, план монтажа и рендер одной задачей
+pub struct QuickEdit {
+  workflow_id: String,
+  backend: Arc<dyn QuickEditBackend>,
+  render: Arc<dyn RenderService>,
+  request: QuickEditRequest,
+  cancel: CancellationToken,
+  poll_interval: Duration,
+}
+
+impl QuickEdit {
+  pub fn new(
+    workflow_id: String,
+    backend: Arc<dyn QuickEditBackend>,
+    render: Arc<dyn RenderService>,
+    request: QuickEditRequest,
+  ) -> Self {
+    Self {
+      workflow_id,
+      backend,
+      render,
+      request,
+      cancel: CancellationToken::new(),
+      poll_interval: QUICK_EDIT_POLL_INTERVAL,
+    }
+  }
+
+  pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+    self.poll_interval = poll_interval;
+    self
+  }
+
+  /// Токен отмены; отмена прерывает текущий этап
+  pub fn cancel_token(&self) -> CancellationToken {
+    self.cancel.clone()
+  }
+
+  /// Выполнить все этапы.
+  ///
+  /// Ошибка возвращается только для некорректных параметров; ошибка этапа
+  /// или отмена попадают в итог вместе с уже полученными результатами.
+  pub async fn run(self, sink: Option<QuickEditProgressSink>) -> Result<QuickEditResult> {
+    self.request.validate()?;
+
+    let started = Instant::now();
+    let mut result = QuickEditResult {
+      workflow_id: self.workflow_id.clone(),
+      status: QuickEditStatus::Failed,
+      stage: QuickEditStage::Scanning,
+      error: None,
+      source_files: Vec::new(),
+      skipped_files: 0,
+      plan: None,
+      project: None,
+      artifacts: None,
+      render_job_id: None,
+      output_path: None,
+      warnings: Vec::new(),
+      total_time_ms: 0,
+    };
+
+    match self.execute(&mut result, sink.as_ref()).await {
+      Ok(status) => result.status = status,
+      Err(VideoCompilerError::CancelledError(_)) => result.status = QuickEditStatus::Cancelled,
+      Err(e) => {
+        log::warn!(
+          "Quick edit {} остановлен на этапе {:?}: {e}",
+          self.workflow_id,
+          result.stage
+        );
+        result.error = Some(e.to_string());
+      }
+    }
+    result.total_time_ms = started.elapsed().as_millis() as u64;
+
+    log::info!(
+      "Quick edit {} завершен со статусом {:?}: {} файлов, {} пропущено",
+      self.workflow_id,
+      result.status,
+      result.source_files.len(),
+      result.skipped_files
+    );
+    Ok(result)
+  }
+
+  async fn execute(
+    &self,
+    result: &mut QuickEditResult,
+    sink: Option<&QuickEditProgressSink>,
+  ) -> Result<QuickEditStatus> {
+    // Сканирование
+    result.stage = QuickEditStage::Scanning;
+    self.report(sink, result.stage, 0.0, "Сканирование папки");
+    let files = self
+      .cancellable(self.backend.scan(&self.request.input_folder, &self.cancel))
+      .await?;
+    if files.is_empty() {
+      return Err(VideoCompilerError::InvalidParameter(format!(
+        "В папке нет видеофайлов: {}",
+        self.request.input_folder.display()
+      )));
+    }
+    self.report(
+      sink,
+      result.stage,
+      100.0,
+      format!("Найдено видеофайлов: {}", files.len()),
+    );
+
+    // Анализ: ошибка отдельного файла только пропускает его
+    result.stage = QuickEditStage::Analyzing;
+    let steps = files.len() + usize::from(self.request.music_path.is_some());
+    let mut moments = Vec::new();
+    for (index, file) in files.iter().enumerate() {
+      let path = file.to_string_lossy().to_string();
+      match self.cancellable(self.backend.analyze(file)).await {
+        Ok(file_moments) if !file_moments.is_empty() => {
+          moments.extend(file_moments.into_iter().map(|mut moment| {
+            moment.source_file = Some(path.clone());
+            moment
+          }));
+          result.source_files.push(path.clone());
+        }
+        Ok(_) => {
+          result.skipped_files += 1;
+          result.warnings.push(QuickEditWarning {
+            stage: result.stage,
+            path: Some(path.clone()),
+            message: "Ключевые моменты не найдены".to_string(),
+          });
+        }
+        Err(e @ VideoCompilerError::CancelledError(_)) => return Err(e),
+        Err(e) => {
+          log::warn!("Quick edit {}: файл {path} пропущен: {e}", self.workflow_id);
+          result.skipped_files += 1;
+          result.warnings.push(QuickEditWarning {
+            stage: result.stage,
+            path: Some(path.clone()),
+            message: e.to_string(),
+          });
+        }
+      }
+      self.report(
+        sink,
+        result.stage,
+        (index + 1) as f64 * 100.0 / steps as f64,
+        format!("Проанализирован {path}"),
+      );
+    }
+
+    let mut beat_grid = None;
+    if let Some(music) = &self.request.music_path {
+      match self.cancellable(self.backend.detect_beats(music)).await {
+        Ok(beats) if !beats.is_empty() => beat_grid = Some(BeatGrid::new(beats)),
+        Ok(_) => result.warnings.push(QuickEditWarning {
+          stage: result.stage,
+          path: Some(music.to_string_lossy().to_string()),
+          message: "Биты не найдены, склейки не привязаны к музыке".to_string(),
+        }),
+        Err(e @ VideoCompilerError::CancelledError(_)) => return Err(e),
+        Err(e) => result.warnings.push(QuickEditWarning {
+          stage: result.stage,
+          path: Some(music.to_string_lossy().to_string()),
+          message: format!("Детекция битов не удалась: {e}"),
+        }),
+      }
+      self.report(sink, result.stage, 100.0, "Биты музыки обработаны");
+    }
+
+    if moments.is_empty() {
+      return Err(VideoCompilerError::InvalidParameter(
+        "Ни в одном файле не найдено моментов для монтажа".to_string(),
+      ));
+    }
+
+    // План и проект
+    result.stage = QuickEditStage::Planning;
+    self.report(sink, result.stage, 0.0, "Построение плана монтажа");
+    let config = MontageConfig {
+      style: self.request.style.clone(),
+      target_duration: self.request.target_duration,
+      quality_threshold: QUICK_EDIT_QUALITY_THRESHOLD,
+      diversity_weight: QUICK_EDIT_DIVERSITY_WEIGHT,
+      rhythm_sync: beat_grid.is_some(),
+      max_cuts_per_minute: None,
+    };
+    let plan = self
+      .cancellable(
+        self
+          .backend
+          .plan(&moments, &config, &result.source_files, beat_grid.as_ref()),
+      )
+      .await?;
+    let project = build_project(&plan, &self.request)?;
+    let artifacts = self.persist(&plan, &project)?;
+    result.plan = Some(plan);
+    result.project = Some(project.clone());
+    result.artifacts = Some(artifacts);
+    self.report(sink, result.stage, 100.0, "План и проект сохранены");
+
+    if !self.request.render {
+      return Ok(QuickEditStatus::Planned);
+    }
+
+    // Рендер
+    result.stage = QuickEditStage::Rendering;
+    self.report(sink, result.stage, 0.0, "Запуск рендеринга");
+    let job_id = self
+      .render
+      .start_render_with_limits(
+        project,
+        self.request.output_path.clone(),
+        ProcessLimits::default(),
+      )
+      .await?;
+    result.render_job_id = Some(job_id.clone());
+    self.wait_for_render(&job_id, sink).await?;
+    result.output_path = Some(self.request.output_path.clone());
+    self.report(sink, result.stage, 100.0, "Видео готово");
+    Ok(QuickEditStatus::Completed)
+  }
+
+  /// Ждать задачу рендеринга; при отмене Quick Edit задача тоже отменяется
+  async fn wait_for_render(
+    &self,
+    job_id: &str,
+    sink: Option<&QuickEditProgressSink>,
+  ) -> Result<()> {
+    loop {
+      if self.cancel.is_cancelled() {
+        if let Err(e) = self.render.cancel_render(job_id).await {
+          log::warn!("Не удалось отменить задачу {job_id}: {e}");
+        }
+        return Err(cancelled_error());
+      }
+
+      let Some(info) = self.render.get_job_info(job_id).await? else {
+        // Задача удалена из истории: результат определяем по файлу
+        if self.request.output_path.exists() {
+          return Ok(());
+        }
+        return Err(VideoCompilerError::RenderError {
+          job_id: job_id.to_string(),
+          stage: "quick_edit".to_string(),
+          message: "Задача рендеринга не найдена".to_string(),
+        });
+      };
+
+      match info.status {
+        RenderJobStatus::Completed => return Ok(()),
+        RenderJobStatus::Failed => {
+          return Err(VideoCompilerError::RenderError {
+            job_id: job_id.to_string(),
+            stage: "quick_edit".to_string(),
+            message: info
+              .error
+              .unwrap_or_else(|| "Рендеринг завершился с ошибкой".to_string()),
+          })
+        }
+        RenderJobStatus::Cancelled => return Err(cancelled_error()),
+        _ => {
+          if let Some(progress) = info.progress {
+            self.report(
+              sink,
+              QuickEditStage::Rendering,
+              f64::from(progress.percentage),
+              "Рендеринг",
+            );
+          }
+        }
+      }
+
+      tokio::select! {
+        _ = self.cancel.cancelled() => {}
+        _ = tokio::time::sleep(self.poll_interval) => {}
+      }
+    }
+  }
+
+  /// Сохранить план и проект до рендера
+  fn persist(&self, plan: &MontagePlan, project: &ProjectSchema) -> Result<QuickEditArtifacts> {
+    let plan_path = self.request.artifact_path("plan.json");
+    let project_path = self.request.artifact_path("project.json");
+    if let Some(dir) = plan_path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+      std::fs::create_dir_all(dir).map_err(|e| VideoCompilerError::IoError(e.to_string()))?;
+    }
+
+    let plan_json = serde_json::to_string_pretty(plan)
+      .map_err(|e| VideoCompilerError::SerializationError(e.to_string()))?;
+    std::fs::write(&plan_path, plan_json)
+      .map_err(|e| VideoCompilerError::IoError(e.to_string()))?;
+    save_project_file(project, &project_path)?;
+
+    Ok(QuickEditArtifacts {
+      plan_path,
+      project_path,
+    })
+  }
+
+  /// Выполнить этап, прерывая его при отмене
+  async fn cancellable<T>(&self, stage: impl Future<Output = Result<T>>) -> Result<T> {
+    tokio::select! {
+      biased;
+      _ = self.cancel.cancelled() => Err(cancelled_error()),
+      value = stage => value,
+    }
+  }
+
+  fn report(
+    &self,
+    sink: Option<&QuickEditProgressSink>,
+    stage: QuickEditStage,
+    stage_percent: f64,
+    message: impl Into<String>,
+  ) {
+    if let Some(sink) = sink {
+      sink(QuickEditProgress {
+        workflow_id: self.workflow_id.clone(),
+        stage,
+        stage_percent,
+        overall_percent: stage.overall_percent(stage_percent),
+        message: message.into(),
+      });
+    }
+  }
+}
+
+fn cancelled_error() -> VideoCompilerError {
+  VideoCompilerError::CancelledError("Quick edit отменен".to_string())
+}
+
+/// Проект из плана: клипы подряд на видео треке, музыка на аудио треке
+pub fn build_project(plan: &MontagePlan, request: &QuickEditRequest) -> Result<ProjectSchema> {
+  let name = request
+    .output_path
+    .file_stem()
+    .map(|stem| stem.to_string_lossy().to_string())
+    .unwrap_or_else(|| plan.name.clone());
+  let mut project = ProjectSchema::new(name);
+
+  let mut clips: Vec<_> = plan.clips.iter().collect();
+  clips.sort_by_key(|clip| clip.order);
+
+  let mut video = Track::new(TrackType::Video, "Quick Edit".to_string());
+  let mut position = 0.0;
+  for planned in clips {
+    let mut clip = Clip::new(
+      PathBuf::from(&planned.source_file),
+      position,
+      planned.duration,
+    );
+    clip.source_start = planned.start_time;
+    clip.source_end = planned.end_time;
+    clip.fade_in = planned
+      .adjustments
+      .fade_in
+      .map(|duration| Fade::new(duration, FadeCurve::Linear));
+    clip.fade_out = planned
+      .adjustments
+      .fade_out
+      .map(|duration| Fade::new(duration, FadeCurve::Linear));
+    position += planned.duration;
+    video.clips.push(clip);
+  }
+  project.tracks.push(video);
+
+  if let Some(music) = &request.music_path {
+    let mut audio = Track::new(TrackType::Audio, "Music".to_string());
+    audio.clips.push(Clip::new(music.clone(), 0.0, position));
+    project.tracks.push(audio);
+  }
+
+  project.timeline.duration = position;
+  if let Some(preset) = &request.export_preset {
+    project.settings.export.apply_preset(preset);
+  }
+  project.validate().map_err(VideoCompilerError::validation)?;
+  Ok(project)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::montage_planner::types::{ClipAdjustments, MomentCategory, MomentScores, MontageClip};
+  use crate::video_compiler::progress::RenderProgress;
+  use crate::video_compiler::services::render_service::RenderJobInfo;
+  use crate::video_compiler::services::Service;
+  use std::sync::Mutex;
+
+  fn moment(timestamp: f64, duration: f64) -> DetectedMoment {
+    DetectedMoment {
+      timestamp,
+      duration,
+      category: MomentCategory::Highlight,
+      scores: MomentScores {
+        visual: 80.0,
+        technical: 80.0,
+        emotional: 60.0,
+        narrative: 60.0,
+        action: 70.0,
+        composition: 75.0,
+      },
+      total_score: 75.0,
+      description: format!("moment at {timestamp}"),
+      tags: Vec::new(),
+      source_file: None,
+    }
+  }
+
+  /// Мок тяжелых этапов: синтетические файлы и моменты, простой план
+  struct MockBackend {
+    files: Vec<PathBuf>,
+    calls: Mutex<Vec<String>>,
+  }
+
+  impl MockBackend {
+    fn new(folder: &Path, names: &[&str]) -> Arc<Self> {
+      Arc::new(Self {
+        files: names.iter().map(|name| folder.join(name)).collect(),
+        calls: Mutex::new(Vec::new()),
+      })
+    }
+
+    fn calls(&self) -> Vec<String> {
+      self.calls.lock().unwrap().clone()
+    }
+
+    fn call(&self, call: String) {
+      self.calls.lock().unwrap().push(call);
+    }
+  }
+
+  #[async_trait]
+  impl QuickEditBackend for MockBackend {
+    async fn scan(&self, _folder: &Path, _cancel: &CancellationToken) -> Result<Vec<PathBuf>> {
+      self.call("scan".to_string());
+      Ok(self.files.clone())
+    }
+
+    async fn analyze(&self, file: &Path) -> Result<Vec<DetectedMoment>> {
+      let name = file.file_name().unwrap().to_string_lossy().to_string();
+      self.call(format!("analyze:{name}"));
+      match name.as_str() {
+        "broken.mp4" => Err(VideoCompilerError::MediaFileError {
+          path: file.to_string_lossy().to_string(),
+          reason: "moov atom not found".to_string(),
+        }),
+        "slow.mp4" => std::future::pending().await,
+        _ => Ok(vec![moment(1.0, 2.0), moment(5.0, 3.0)]),
+      }
+    }
+
+    async fn detect_beats(&self, _music: &Path) -> Result<Vec<Beat>> {
+      self.call("beats".to_string());
+      Ok(
+        (1..=20)
+          .map(|i| Beat {
+            time: f64::from(i) * 0.5,
+            strength: 0.8,
+            is_downbeat_guess: i % 4 == 1,
+          })
+          .collect(),
+      )
+    }
+
+    async fn plan(
+      &self,
+      moments: &[DetectedMoment],
+      config: &MontageConfig,
+      _source_files: &[String],
+      beat_grid: Option<&BeatGrid>,
+    ) -> Result<MontagePlan> {
+      self.call(format!("plan:beats={}", beat_grid.is_some()));
+      let mut clips = Vec::new();
+      let mut total = 0.0;
+      for (order, moment) in moments.iter().enumerate() {
+        if total >= config.target_duration {
+          break;
+        }
+        total += moment.duration;
+        clips.push(MontageClip {
+          id: format!("clip_{order}"),
+          source_file: moment.source_file.clone().unwrap(),
+          start_time: moment.timestamp,
+          end_time: moment.timestamp + moment.duration,
+          duration: moment.duration,
+          moment: moment.clone(),
+          adjustments: ClipAdjustments {
+            speed_multiplier: None,
+            color_correction: None,
+            stabilization: false,
+            crop: None,
+            fade_in: Some(0.2),
+            fade_out: None,
+          },
+          order: order as u32,
+        });
+      }
+      Ok(MontagePlan {
+        id: "plan".to_string(),
+        name: "Mock plan".to_string(),
+        style: config.style.clone(),
+        total_duration: total,
+        clips,
+        transitions: Vec::new(),
+        quality_score: 80.0,
+        engagement_score: 70.0,
+        created_at: chrono::Utc::now().to_rfc3339(),
+      })
+    }
+  }
+
+  /// Мок рендера: задача завершается через два опроса и пишет файл
+  #[derive(Default)]
+  struct MockRender {
+    started: Mutex<Vec<ProjectSchema>>,
+    output: Mutex<Option<PathBuf>>,
+    polls: Mutex<u32>,
+    cancelled: Mutex<bool>,
+  }
+
+  #[async_trait]
+  impl Service for MockRender {
+    async fn initialize(&self) -> Result<()> {
+      Ok(())
+    }
+
+    async fn health_check(&self) -> Result<()> {
+      Ok(())
+    }
+
+    async fn shutdown(&self) -> Result<()> {
+      Ok(())
+    }
+  }
+
+  #[async_trait]
+  impl RenderService for MockRender {
+    async fn start_render(&self, project: ProjectSchema, output_path: PathBuf) -> Result<String> {
+      self.started.lock().unwrap().push(project);
+      *self.output.lock().unwrap() = Some(output_path);
+      Ok("render-1".to_string())
+    }
+
+    async fn get_job_info(&self, _job_id: &str) -> Result<Option<RenderJobInfo>> {
+      let mut polls = self.polls.lock().unwrap();
+      *polls += 1;
+      let status = if *polls >= 2 {
+        let output = self.output.lock().unwrap().clone().unwrap();
+        std::fs::write(output, b"video").unwrap();
+        RenderJobStatus::Completed
+      } else {
+        RenderJobStatus::Rendering
+      };
+      Ok(Some(RenderJobInfo {
+        project_name: String::new(),
+        output_path: self.output.lock().unwrap().clone(),
+        status,
+        progress: Some(RenderProgress {
+          percentage: 50.0,
+          ..RenderProgress::default()
+        }),
+        created_at: chrono::Utc::now(),
+        error: None,
+        process_limits: ProcessLimits::default(),
+        downgrades: Vec::new(),
+        report: None,
+      }))
+    }
+
+    async fn get_progress(&self, _job_id: &str) -> Result<Option<RenderProgress>> {
+      Ok(None)
+    }
+
+    async fn cancel_render(&self, _job_id: &str) -> Result<bool> {
+      *self.cancelled.lock().unwrap() = true;
+      Ok(true)
+    }
+
+    async fn pause_render(&self, _job_id: &str) -> Result<bool> {
+      Ok(false)
+    }
+
+    async fn resume_render(&self, _job_id: &str) -> Result<bool> {
+      Ok(false)
+    }
+
+    async fn get_active_jobs(&self) -> Result<Vec<String>> {
+      Ok(Vec::new())
+    }
+
+    async fn has_available_slots(&self) -> Result<bool> {
+      Ok(true)
+    }
+  }
+
+  fn request(dir: &Path, music: Option<PathBuf>) -> QuickEditRequest {
+    QuickEditRequest {
+      input_folder: dir.to_path_buf(),
+      music_path: music,
+      target_duration: 6.0,
+      style: MontageStyle::MusicVideo,
+      output_path: dir.join("out").join("trip.mp4"),
+      export_preset: Some("instagram".to_string()),
+      render: true,
+      artifacts_dir: None,
+    }
+  }
+
+  fn collecting_sink() -> (QuickEditProgressSink, Arc<Mutex<Vec<QuickEditProgress>>>) {
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let sink_reports = reports.clone();
+    let sink: QuickEditProgressSink =
+      Arc::new(move |progress| sink_reports.lock().unwrap().push(progress));
+    (sink, reports)
+  }
+
+  #[tokio::test]
+  async fn test_quick_edit_runs_stages_in_order() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("out")).unwrap();
+    let music = dir.path().join("song.mp3");
+    std::fs::write(&music, b"music").unwrap();
+    let backend = MockBackend::new(dir.path(), &["beach.mp4", "broken.mp4", "city.mp4"]);
+    let render = Arc::new(MockRender::default());
+    let (sink, reports) = collecting_sink();
+
+    let result = QuickEdit::new(
+      "qe-1".to_string(),
+      backend.clone(),
+      render.clone(),
+      request(dir.path(), Some(music.clone())),
+    )
+    .with_poll_interval(Duration::from_millis(1))
+    .run(Some(sink))
+    .await
+    .unwrap();
+
+    assert_eq!(result.status, QuickEditStatus::Completed, "{result:?}");
+    assert_eq!(
+      backend.calls(),
+      vec![
+        "scan",
+        "analyze:beach.mp4",
+        "analyze:broken.mp4",
+        "analyze:city.mp4",
+        "beats",
+        "plan:beats=true",
+      ]
+    );
+
+    // Поврежденный файл пропущен с предупреждением
+    assert_eq!(result.source_files.len(), 2);
+    assert_eq!(result.skipped_files, 1);
+    assert_eq!(result.warnings.len(), 1);
+    assert_eq!(result.warnings[0].stage, QuickEditStage::Analyzing);
+    assert!(result.warnings[0].message.contains("moov atom"));
+
+    // Прогресс идет по этапам и не убывает; границы этапов 10/40/50/100
+    let reports = reports.lock().unwrap();
+    assert!(reports
+      .windows(2)
+      .all(|pair| pair[1].overall_percent >= pair[0].overall_percent));
+    let stage_end = |stage: QuickEditStage| {
+      reports
+        .iter()
+        .filter(|progress| progress.stage == stage)
+        .map(|progress| progress.overall_percent)
+        .fold(0.0, f64::max)
+    };
+    assert_eq!(stage_end(QuickEditStage::Scanning), 10.0);
+    assert_eq!(stage_end(QuickEditStage::Analyzing), 40.0);
+    assert_eq!(stage_end(QuickEditStage::Planning), 50.0);
+    assert_eq!(stage_end(QuickEditStage::Rendering), 100.0);
+
+    // Рендер получил проект из плана с музыкой и пресетом
+    let started = render.started.lock().unwrap();
+    assert_eq!(started.len(), 1);
+    let project = &started[0];
+    assert_eq!(
+      project.settings.export.export_preset.as_deref(),
+      Some("instagram")
+    );
+    let video = &project.tracks[0];
+    assert_eq!(video.clips.len(), 3);
+    assert_eq!(video.clips[1].start_time, 2.0);
+    assert_eq!(video.clips[1].source_start, 5.0);
+    assert_eq!(video.clips[1].source_end, 8.0);
+    assert_eq!(project.tracks[1].track_type, TrackType::Audio);
+    assert_eq!(project.timeline.duration, 7.0);
+    assert!(result.output_path.unwrap().exists());
+
+    // План и проект сохранены рядом с результатом
+    let artifacts = result.artifacts.unwrap();
+    assert_eq!(
+      artifacts.plan_path,
+      dir.path().join("out").join("trip.plan.json")
+    );
+    let plan: MontagePlan =
+      serde_json::from_str(&std::fs::read_to_string(&artifacts.plan_path).unwrap()).unwrap();
+    assert_eq!(plan.clips.len(), 3);
+    let loaded =
+      crate::video_compiler::services::project_storage::load_project_file(&artifacts.project_path)
+        .unwrap()
+        .into_project()
+        .unwrap();
+    assert_eq!(loaded.tracks[0].clips.len(), 3);
+  }
+
+  #[tokio::test]
+  async fn test_quick_edit_without_render_returns_project() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("out")).unwrap();
+    let backend = MockBackend::new(dir.path(), &["beach.mp4"]);
+    let render = Arc::new(MockRender::default());
+    let mut request = request(dir.path(), None);
+    request.render = false;
+
+    let result = QuickEdit::new("qe-2".to_string(), backend.clone(), render.clone(), request)
+      .run(None)
+      .await
+      .unwrap();
+
+    assert_eq!(result.status, QuickEditStatus::Planned);
+    assert_eq!(result.stage, QuickEditStage::Planning);
+    assert!(backend.calls().contains(&"plan:beats=false".to_string()));
+    assert!(render.started.lock().unwrap().is_empty());
+    assert!(result.project.is_some());
+    assert!(result.artifacts.unwrap().project_path.exists());
+  }
+
+  #[tokio::test]
+  async fn test_quick_edit_cancel_aborts_active_stage() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("out")).unwrap();
+    let backend = MockBackend::new(dir.path(), &["beach.mp4", "slow.mp4", "city.mp4"]);
+    let render = Arc::new(MockRender::default());
+
+    let quick_edit = QuickEdit::new(
+      "qe-3".to_string(),
+      backend.clone(),
+      render.clone(),
+      request(dir.path(), None),
+    );
+    let cancel = quick_edit.cancel_token();
+    let run = tokio::spawn(quick_edit.run(None));
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    cancel.cancel();
+
+    let result = tokio::time::timeout(Duration::from_secs(5), run)
+      .await
+      .unwrap()
+      .unwrap()
+      .unwrap();
+    assert_eq!(result.status, QuickEditStatus::Cancelled);
+    assert_eq!(result.stage, QuickEditStage::Analyzing);
+    assert!(!backend.calls().contains(&"analyze:city.mp4".to_string()));
+    assert!(result.plan.is_none());
+    assert!(render.started.lock().unwrap().is_empty());
+  }
+
+  #[tokio::test]
+  async fn test_quick_edit_rejects_unknown_preset() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("out")).unwrap();
+    let backend = MockBackend::new(dir.path(), &["beach.mp4"]);
+    let mut request = request(dir.path(), None);
+    request.export_preset = Some("vhs".to_string());
+
+    let result = QuickEdit::new(
+      "qe-4".to_string(),
+      backend.clone(),
+      Arc::new(MockRender::default()),
+      request,
+    )
+    .run(None)
+    .await;
+
+    assert!(matches!(
+      result,
+      Err(VideoCompilerError::InvalidParameter(_))
+    ));
+    assert!(backend.calls().is_empty());
+  }
+}
@@ -0,0 +1,608 @@
+//! Render Report - отчет о завершенном экспорте
+//!
+//! После каждого экспорта рядом с выходным файлом сохраняется
+//! `<output>.report.json`: исходные файлы с кодеками и длительностями,
+//! фактически примененные настройки экспорта, время этапов конвейера,
+//! проверка результата (длительность, битрейт, разрешение, громкость) и
+//! предупреждения рендера. Отчет можно выгрузить в Markdown для передачи
+//! заказчику.
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::video_compiler::error::{Result, VideoCompilerError};
+use crate::video_compiler::schema::{ExportSettings, ProjectSchema};
+use crate::video_compiler::services::ffmpeg_service::{FfmpegService, FfmpegServiceImpl, FileInfo};
+use crate::video_compiler::services::project_package::media_paths;
+
+/// Суффикс файла отчета рядом с выходным файлом
+pub const REPORT_SUFFIX: &str = ".report.json";
+
+/// Исходный файл проекта
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReportInput {
+  pub path: String,
+  pub video_codec: Option<String>,
+  pub audio_codec: Option<String>,
+  /// Длительность в секундах
+  pub duration: Option<f64>,
+  /// Причина, по которой файл не удалось проверить
+  pub error: Option<String>,
+}
+
+/// Время выполнения этапа конвейера
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StageTiming {
+  pub name: String,
+  pub seconds: f64,
+}
+
+/// Характеристики выходного файла
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReportOutput {
+  pub size_bytes: u64,
+  /// Длительность в секундах
+  pub duration: f64,
+  /// Общий битрейт в кбит/с
+  pub bitrate_kbps: u64,
+  pub width: u32,
+  pub height: u32,
+  pub video_codec: String,
+  pub audio_codec: Option<String>,
+}
+
+/// Результат измерения громкости фильтром `loudnorm`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct LoudnessMeasurement {
+  /// Интегральная громкость, LUFS
+  pub integrated_lufs: f64,
+  /// Истинный пик, dBTP
+  pub true_peak_dbtp: f64,
+  /// Диапазон громкости, LU
+  pub loudness_range_lu: f64,
+}
+
+/// Отчет о рендере
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderReport {
+  pub job_id: String,
+  pub project_name: String,
+  pub output_path: PathBuf,
+  pub created_at: chrono::DateTime<chrono::Utc>,
+  pub inputs: Vec<ReportInput>,
+  /// Настройки экспорта с учетом понижений режима (GPU -> CPU)
+  pub settings: ExportSettings,
+  /// Этапы конвейера в порядке выполнения
+  pub stages: Vec<StageTiming>,
+  pub total_duration_secs: f64,
+  /// `None`, если выходной файл не удалось проверить
+  pub output: Option<ReportOutput>,
+  /// `None`, если проверка громкости отключена или не удалась
+  pub loudness: Option<LoudnessMeasurement>,
+  /// Предупреждения рендера, включая замены фильтров и понижения режима
+  pub warnings: Vec<String>,
+}
+
+/// Источник сведений о файлах для отчета
+#[async_trait]
+pub trait ReportProbe: Send + Sync {
+  /// Кодеки, длительность и разрешение файла
+  async fn file_info(&self, path: &Path) -> Result<FileInfo>;
+
+  /// Измерить громкость файла
+  async fn measure_loudness(&self, path: &Path) -> Result<LoudnessMeasurement>;
+}
+
+/// Проверка файлов через FFmpeg
+pub struct FfmpegReportProbe {
+  ffmpeg_path: String,
+}
+
+impl FfmpegReportProbe {
+  pub fn new(ffmpeg_path: String) -> Self {
+    Self { ffmpeg_path }
+  }
+}
+
+#[async_trait]
+impl ReportProbe for FfmpegReportProbe {
+  async fn file_info(&self, path: &Path) -> Result<FileInfo> {
+    FfmpegServiceImpl::new(self.ffmpeg_path.clone())
+      .get_file_info(path)
+      .await
+  }
+
+  async fn measure_loudness(&self, path: &Path) -> Result<LoudnessMeasurement> {
+    let output = tokio::process::Command::new(&self.ffmpeg_path)
+      .args(["-hide_banner", "-nostats", "-i"])
+      .arg(path)
+      .args([
+        "-vn",
+        "-af",
+        "loudnorm=print_format=json",
+        "-f",
+        "null",
+        "-",
+      ])
+      .stdout(Stdio::null())
+      .stderr(Stdio::piped())
+      .kill_on_drop(true)
+      .output()
+      .await
+      .map_err(|e| VideoCompilerError::FFmpegError {
+        exit_code: None,
+        stderr: format!("Ошибка запуска FFmpeg: {e}"),
+        command: "loudnorm".to_string(),
+      })?;
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+      return Err(VideoCompilerError::FFmpegError {
+        exit_code: output.status.code(),
+        stderr: stderr.to_string(),
+        command: "loudnorm".to_string(),
+      });
+    }
+    parse_loudnorm_output(&stderr)
+  }
+}
+
+/// Разобрать JSON блок, который `loudnorm=print_format=json` печатает в
+/// конце stderr. Значения в нем записаны строками.
+pub fn parse_loudnorm_output(stderr: &str) -> Result<LoudnessMeasurement> {
+  let start = stderr.rfind('{');
+  let end = stderr.rfind('}');
+  let json = match (start, end) {
+    (Some(start), Some(end)) if start < end => &stderr[start..=end],
+    _ => {
+      return Err(VideoCompilerError::validation(
+        "В выводе loudnorm нет результатов измерения",
+      ))
+    }
+  };
+  let value: serde_json::Value = serde_json::from_str(json)
+    .map_err(|e| VideoCompilerError::validation(format!("Неверный вывод loudnorm: {e}")))?;
+
+  let field = |name: &str| -> Result<f64> {
+    let raw = &value[name];
+    raw
+      .as_str()
+      .and_then(|s| s.trim().parse::<f64>().ok())
+      .or_else(|| raw.as_f64())
+      .ok_or_else(|| VideoCompilerError::validation(format!("В выводе loudnorm нет поля {name}")))
+  };
+
+  Ok(LoudnessMeasurement {
+    integrated_lufs: field("input_i")?,
+    true_peak_dbtp: field("input_tp")?,
+    loudness_range_lu: field("input_lra")?,
+  })
+}
+
+/// Путь к файлу отчета для выходного файла: `<output>.report.json`
+pub fn report_path(output_path: &Path) -> PathBuf {
+  let mut path = output_path.as_os_str().to_owned();
+  path.push(REPORT_SUFFIX);
+  PathBuf::from(path)
+}
+
+/// Собрать отчет о завершенном рендере.
+///
+/// `project` - фактически отрендеренный проект (после понижений режима и
+/// замен фильтров), `stages` - этапы в порядке выполнения. Ошибки проверки
+/// отдельных файлов попадают в отчет, а не прерывают его сборку.
+pub async fn build_render_report(
+  probe: &dyn ReportProbe,
+  job_id: &str,
+  project: &ProjectSchema,
+  output_path: &Path,
+  stages: Vec<StageTiming>,
+  warnings: Vec<String>,
+) -> RenderReport {
+  let mut warnings = warnings;
+
+  let mut inputs = Vec::new();
+  for path in media_paths(project) {
+    let input = match probe.file_info(Path::new(&path)).await {
+      Ok(info) => ReportInput {
+        path,
+        video_codec: (!info.codec.is_empty()).then_some(info.codec),
+        audio_codec: info.audio_codec,
+        duration: Some(info.duration),
+        error: None,
+      },
+      Err(e) => ReportInput {
+        path,
+        video_codec: None,
+        audio_codec: None,
+        duration: None,
+        error: Some(e.to_string()),
+      },
+    };
+    inputs.push(input);
+  }
+
+  let output = match probe.file_info(output_path).await {
+    Ok(info) => Some(ReportOutput {
+      size_bytes: std::fs::metadata(output_path).map(|m| m.len()).unwrap_or(0),
+      duration: info.duration,
+      bitrate_kbps: info.bitrate,
+      width: info.width,
+      height: info.height,
+      video_codec: info.codec,
+      audio_codec: info.audio_codec,
+    }),
+    Err(e) => {
+      warnings.push(format!("Не удалось проверить выходной файл: {e}"));
+      None
+    }
+  };
+
+  let has_audio = output
+    .as_ref()
+    .is_some_and(|output| output.audio_codec.is_some());
+  let loudness = if project.settings.export.render_report.measure_loudness && has_audio {
+    match probe.measure_loudness(output_path).await {
+      Ok(loudness) => Some(loudness),
+      Err(e) => {
+        warnings.push(format!("Не удалось измерить громкость: {e}"));
+        None
+      }
+    }
+  } else {
+    None
+  };
+
+  let total_duration_secs = stages.iter().map(|stage| stage.seconds).sum();
+
+  RenderReport {
+    job_id: job_id.to_string(),
+    project_name: project.metadata.name.clone(),
+    output_path: output_path.to_path_buf(),
+    created_at: chrono::Utc::now(),
+    inputs,
+    settings: project.settings.export.clone(),
+    stages,
+    total_duration_secs,
+    output,
+    loudness,
+    warnings,
+  }
+}
+
+impl RenderReport {
+  /// Сохранить отчет рядом с выходным файлом
+  pub async fn save(&self) -> Result<PathBuf> {
+    let path = report_path(&self.output_path);
+    let json = serde_json::to_string_pretty(self)
+      .map_err(|e| VideoCompilerError::IoError(format!("Не удалось сериализовать отчет: {e}")))?;
+    tokio::fs::write(&path, json)
+      .await
+      .map_err(|e| VideoCompilerError::IoError(format!("Не удалось сохранить отчет: {e}")))?;
+    Ok(path)
+  }
+
+  /// Загрузить отчет для выходного файла
+  pub async fn load(output_path: &Path) -> Result<Self> {
+    let path = report_path(output_path);
+    let json = tokio::fs::read_to_string(&path)
+      .await
+      .map_err(|e| VideoCompilerError::IoError(format!("Не удалось прочитать {path:?}: {e}")))?;
+    serde_json::from_str(&json)
+      .map_err(|e| VideoCompilerError::validation(format!("Неверный отчет {path:?}: {e}")))
+  }
+
+  /// Отчет в читаемом виде (Markdown)
+  pub fn to_markdown(&self) -> String {
+    let mut md = String::new();
+    md.push_str(&format!("# Отчет о рендере: {}\n\n", self.project_name));
+    md.push_str(&format!("- Задача: `{}`\n", self.job_id));
+    md.push_str(&format!("- Файл: `{}`\n", self.output_path.display()));
+    md.push_str(&format!("- Дата: {}\n\n", self.created_at.to_rfc3339()));
+
+    md.push_str("## Результат\n\n");
+    match &self.output {
+      Some(output) => {
+        md.push_str(&format!(
+          "- Разрешение: {}x{}\n",
+          output.width, output.height
+        ));
+        md.push_str(&format!("- Длительность: {:.2} с\n", output.duration));
+        md.push_str(&format!("- Битрейт: {} кбит/с\n", output.bitrate_kbps));
+        md.push_str(&format!(
+          "- Кодеки: {} / {}\n",
+          output.video_codec,
+          output.audio_codec.as_deref().unwrap_or("нет аудио")
+        ));
+        md.push_str(&format!("- Размер: {} байт\n", output.size_bytes));
+      }
+      None => md.push_str("Выходной файл не проверен\n"),
+    }
+    if let Some(loudness) = &self.loudness {
+      md.push_str(&format!(
+        "- Громкость: {:.1} LUFS, пик {:.1} dBTP, диапазон {:.1} LU\n",
+        loudness.integrated_lufs, loudness.true_peak_dbtp, loudness.loudness_range_lu
+      ));
+    }
+
+    md.push_str("\n## Настройки экспорта\n\n");
+    md.push_str(&format!(
+      "- Формат: {:?}, качество {}\n",
+      self.settings.format, self.settings.quality
+    ));
+    md.push_str(&format!(
+      "- Битрейт видео/аудио: {} / {} кбит/с\n",
+      self.settings.video_bitrate, self.settings.audio_bitrate
+    ));
+    md.push_str(&format!(
+      "- Аппаратное ускорение: {}\n",
+      if self.settings.hardware_acceleration {
+        "да"
+      } else {
+        "нет"
+      }
+    ));
+
+    md.push_str("\n## Исходные файлы\n\n");
+    md.push_str("| Файл | Видео | Аудио | Длительность |\n");
+    md.push_str("|---|---|---|---|\n");
+    for input in &self.inputs {
+      let duration = match (&input.error, input.duration) {
+        (Some(error), _) => format!("ошибка: {error}"),
+        (None, Some(duration)) => format!("{duration:.2} с"),
+        (None, None) => "-".to_string(),
+      };
+      md.push_str(&format!(
+        "| {} | {} | {} | {} |\n",
+        input.path,
+        input.video_codec.as_deref().unwrap_or("-"),
+        input.audio_codec.as_deref().unwrap_or("-"),
+        duration
+      ));
+    }
+
+    md.push_str("\n## Этапы\n\n");
+    for stage in &self.stages {
+      md.push_str(&format!("- {}: {:.2} с\n", stage.name, stage.seconds));
+    }
+    md.push_str(&format!("- Всего: {:.2} с\n", self.total_duration_secs));
+
+    if !self.warnings.is_empty() {
+      md.push_str("\n## Предупреждения\n\n");
+      for warning in &self.warnings {
+        md.push_str(&format!("- {warning}\n"));
+      }
+    }
+    md
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::video_compiler::schema::{Clip, Track, TrackType};
+  use crate::video_compiler::services::ffmpeg_service::ColorMetadata;
+  use std::collections::HashMap;
+  use std::sync::atomic::{AtomicUsize, Ordering};
+
+  struct FakeProbe {
+    loudness_calls: AtomicUsize,
+  }
+
+  impl FakeProbe {
+    fn new() -> Self {
+      Self {
+        loudness_calls: AtomicUsize::new(0),
+      }
+    }
+  }
+
+  #[async_trait]
+  impl ReportProbe for FakeProbe {
+    async fn file_info(&self, path: &Path) -> Result<FileInfo> {
+      if path.to_string_lossy().contains("missing") {
+        return Err(VideoCompilerError::MediaFileError {
+          path: path.to_string_lossy().to_string(),
+          reason: "not found".to_string(),
+        });
+      }
+      Ok(FileInfo {
+        duration: 12.5,
+        width: 1920,
+        height: 1080,
+        fps: 30.0,
+        codec: "h264".to_string(),
+        bitrate: 8000,
+        has_audio: true,
+        audio_codec: Some("aac".to_string()),
+        audio_bitrate: Some(192),
+        color: ColorMetadata::default(),
+        rotation: 0,
+        streams: Vec::new(),
+        tags: HashMap::new(),
+      })
+    }
+
+    async fn measure_loudness(&self, _path: &Path) -> Result<LoudnessMeasurement> {
+      self.loudness_calls.fetch_add(1, Ordering::SeqCst);
+      Ok(LoudnessMeasurement {
+        integrated_lufs: -16.2,
+        true_peak_dbtp: -1.4,
+        loudness_range_lu: 6.8,
+      })
+    }
+  }
+
+  fn project() -> ProjectSchema {
+    let mut project = ProjectSchema::new("Report".to_string());
+    let mut track = Track::new(TrackType::Video, "Video".to_string());
+    track
+      .clips
+      .push(Clip::new(PathBuf::from("/media/a.mp4"), 0.0, 5.0));
+    track
+      .clips
+      .push(Clip::new(PathBuf::from("/media/missing.mov"), 5.0, 5.0));
+    project.tracks.push(track);
+    project
+  }
+
+  fn stages() -> Vec<StageTiming> {
+    vec![
+      StageTiming {
+        name: "Validation".to_string(),
+        seconds: 0.5,
+      },
+      StageTiming {
+        name: "Encoding".to_string(),
+        seconds: 10.0,
+      },
+    ]
+  }
+
+  #[tokio::test]
+  async fn test_build_report_structure() {
+    let probe = FakeProbe::new();
+    let report = build_render_report(
+      &probe,
+      "job-1",
+      &project(),
+      Path::new("/out/video.mp4"),
+      stages(),
+      vec!["Фильтр заменен".to_string()],
+    )
+    .await;
+
+    assert_eq!(report.job_id, "job-1");
+    assert_eq!(report.project_name, "Report");
+    assert_eq!(report.inputs.len(), 2);
+    let ok = report
+      .inputs
+      .iter()
+      .find(|input| input.path == "/media/a.mp4")
+      .unwrap();
+    assert_eq!(ok.video_codec.as_deref(), Some("h264"));
+    assert_eq!(ok.duration, Some(12.5));
+    let missing = report
+      .inputs
+      .iter()
+      .find(|input| input.path.contains("missing"))
+      .unwrap();
+    assert!(missing.error.is_some());
+
+    let stage_names: Vec<_> = report.stages.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(stage_names, ["Validation", "Encoding"]);
+    assert_eq!(report.total_duration_secs, 10.5);
+
+    let output = report.output.unwrap();
+    assert_eq!((output.width, output.height), (1920, 1080));
+    assert_eq!(output.bitrate_kbps, 8000);
+    assert_eq!(report.loudness.unwrap().integrated_lufs, -16.2);
+    assert_eq!(report.warnings, ["Фильтр заменен"]);
+
+    let json = serde_json::to_value(&report).unwrap();
+    for key in [
+      "inputs", "settings", "stages", "output", "loudness", "warnings",
+    ] {
+      assert!(json.get(key).is_some(), "в отчете нет поля {key}");
+    }
+  }
+
+  #[tokio::test]
+  async fn test_loudness_pass_can_be_skipped() {
+    let probe = FakeProbe::new();
+    let mut project = project();
+    project.settings.export.render_report.measure_loudness = false;
+
+    let report = build_render_report(
+      &probe,
+      "job-2",
+      &project,
+      Path::new("/out/video.mp4"),
+      stages(),
+      Vec::new(),
+    )
+    .await;
+
+    assert!(report.loudness.is_none());
+    assert_eq!(probe.loudness_calls.load(Ordering::SeqCst), 0);
+  }
+
+  #[tokio::test]
+  async fn test_unprobed_output_is_reported_as_warning() {
+    let probe = FakeProbe::new();
+    let report = build_render_report(
+      &probe,
+      "job-3",
+      &project(),
+      Path::new("/out/missing.mp4"),
+      stages(),
+      Vec::new(),
+    )
+    .await;
+
+    assert!(report.output.is_none());
+    assert!(report.loudness.is_none());
+    assert_eq!(report.warnings.len(), 1);
+  }
+
+  #[test]
+  fn test_parse_loudnorm_output() {
+    let stderr = r#"[Parsed_loudnorm_0 @ 0x55d]
+{
+	"input_i" : "-23.54",
+	"input_tp" : "-7.96",
+	"input_lra" : "0.00",
+	"input_thresh" : "-34.17",
+	"output_i" : "-23.07",
+	"normalization_type" : "dynamic",
+	"target_offset" : "0.07"
+}
+"#;
+    let loudness = parse_loudnorm_output(stderr).unwrap();
+    assert_eq!(loudness.integrated_lufs, -23.54);
+    assert_eq!(loudness.true_peak_dbtp, -7.96);
+    assert_eq!(loudness.loudness_range_lu, 0.0);
+
+    assert!(parse_loudnorm_output("no json here").is_err());
+  }
+
+  #[test]
+  fn test_report_path_appends_suffix() {
+    assert_eq!(
+      report_path(Path::new("/out/video.mp4")),
+      PathBuf::from("/out/video.mp4.report.json")
+    );
+  }
+
+  #[tokio::test]
+  async fn test_report_roundtrip_and_markdown() {
+    let dir = tempfile::tempdir().unwrap();
+    let output_path = dir.path().join("video.mp4");
+    let probe = FakeProbe::new();
+    let report = build_render_report(
+      &probe,
+      "job-4",
+      &project(),
+      &output_path,
+      stages(),
+      vec!["GPU недоступен".to_string()],
+    )
+    .await;
+
+    let saved = report.save().await.unwrap();
+    assert_eq!(saved, dir.path().join("video.mp4.report.json"));
+    let loaded = RenderReport::load(&output_path).await.unwrap();
+    assert_eq!(loaded.job_id, "job-4");
+    assert_eq!(loaded.inputs, report.inputs);
+
+    let md = loaded.to_markdown();
+    assert!(md.starts_with("# Отчет о рендере: Report"));
+    assert!(md.contains("1920x1080"));
+    assert!(md.contains("-16.2 LUFS"));
+    assert!(md.contains("| /media/a.mp4 | h264 | aac | 12.50 с |"));
+    assert!(md.contains("- GPU недоступен"));
+  }
+}
@@ -0,0 +1,309 @@
+//! Render Schedule - Отложенный запуск рендеринга
+//!
+//! Рендеринг можно запланировать на заданное время или на момент, когда
+//! приложение простаивает заданное число минут. Запланированные задачи
+//! хранятся на диске вместе со снимком проекта, поэтому перезапуск
+//! приложения до времени старта их не теряет.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::video_compiler::core::render_priority::ProcessLimits;
+use crate::video_compiler::error::{Result, VideoCompilerError};
+use crate::video_compiler::schema::ProjectSchema;
+use crate::video_compiler::services::RenderService;
+
+/// Файл запланированных рендеров в директории приложения
+pub const SCHEDULED_RENDERS_FILE: &str = "scheduled_renders.json";
+
+/// Интервал проверки запланированных рендеров
+pub const SCHEDULER_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Длительность простоя по умолчанию для запуска в режиме простоя
+pub const DEFAULT_IDLE_MINUTES: u32 = 10;
+
+/// Условие запуска запланированного рендеринга
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum RenderSchedule {
+  /// Запуск в заданный момент
+  At { start_at: DateTime<Utc> },
+  /// Запуск после простоя приложения в течение `idle_minutes` минут
+  WhenIdle { idle_minutes: u32 },
+}
+
+impl RenderSchedule {
+  /// Условие из параметров команды: RFC3339-время или флаг запуска при простое.
+  ///
+  /// Без параметров возвращает `None` (немедленный запуск).
+  pub fn from_request(
+    start_at: Option<String>,
+    start_when_idle: Option<bool>,
+    idle_minutes: Option<u32>,
+  ) -> Result<Option<Self>> {
+    let when_idle = start_when_idle.unwrap_or(false);
+    match start_at {
+      Some(_) if when_idle => Err(VideoCompilerError::validation(
+        "Нельзя одновременно задать start_at и start_when_idle",
+      )),
+      Some(start_at) => {
+        let start_at = DateTime::parse_from_rfc3339(&start_at)
+          .map_err(|e| {
+            VideoCompilerError::validation(format!("Некорректное время start_at '{start_at}': {e}"))
+          })?
+          .with_timezone(&Utc);
+        Ok(Some(Self::At { start_at }))
+      }
+      None if when_idle => Ok(Some(Self::WhenIdle {
+        idle_minutes: idle_minutes.unwrap_or(DEFAULT_IDLE_MINUTES),
+      })),
+      None => Ok(None),
+    }
+  }
+
+  /// Наступило ли время запуска
+  pub fn is_due(&self, now: DateTime<Utc>, idle_for: Duration) -> bool {
+    match self {
+      Self::At { start_at } => *start_at <= now,
+      Self::WhenIdle { idle_minutes } => {
+        idle_for >= Duration::from_secs(u64::from(*idle_minutes) * 60)
+      }
+    }
+  }
+
+  /// Время запуска уже прошло (для запуска по времени)
+  pub fn is_past(&self, now: DateTime<Utc>) -> bool {
+    matches!(self, Self::At { start_at } if *start_at <= now)
+  }
+}
+
+/// Запланированный рендеринг со снимком проекта и настроек
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledRender {
+  pub id: String,
+  pub project: ProjectSchema,
+  pub output_path: PathBuf,
+  #[serde(default)]
+  pub limits: ProcessLimits,
+  pub schedule: RenderSchedule,
+  pub created_at: DateTime<Utc>,
+}
+
+/// Сведения о запланированном рендеринге для UI (без снимка проекта)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledRenderInfo {
+  pub id: String,
+  pub project_name: String,
+  pub output_path: String,
+  pub process_limits: ProcessLimits,
+  pub schedule: RenderSchedule,
+  pub created_at: DateTime<Utc>,
+}
+
+impl From<&ScheduledRender> for ScheduledRenderInfo {
+  fn from(render: &ScheduledRender) -> Self {
+    Self {
+      id: render.id.clone(),
+      project_name: render.project.metadata.name.clone(),
+      output_path: render.output_path.to_string_lossy().to_string(),
+      process_limits: render.limits,
+      schedule: render.schedule.clone(),
+      created_at: render.created_at,
+    }
+  }
+}
+
+/// Сохраняемый список запланированных рендеров
+#[derive(Debug, Clone)]
+pub struct ScheduledRenderStore {
+  file: PathBuf,
+}
+
+impl ScheduledRenderStore {
+  pub fn new(file: PathBuf) -> Self {
+    Self { file }
+  }
+
+  /// Список в директории приложения
+  pub fn default_location() -> Option<Self> {
+    let dirs = crate::app_dirs::AppDirectories::get_or_create().ok()?;
+    Some(Self::new(dirs.base_dir.join(SCHEDULED_RENDERS_FILE)))
+  }
+
+  /// Загрузить запланированные рендеры
+  pub async fn load(&self) -> Vec<ScheduledRender> {
+    match tokio::fs::read_to_string(&self.file).await {
+      Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
+      Err(_) => Vec::new(),
+    }
+  }
+
+  /// Сохранить запланированные рендеры
+  pub async fn save(&self, renders: &[ScheduledRender]) -> Result<()> {
+    if let Some(parent) = self.file.parent() {
+      tokio::fs::create_dir_all(parent)
+        .await
+        .map_err(|e| VideoCompilerError::IoError(e.to_string()))?;
+    }
+    let content = serde_json::to_string_pretty(renders)
+      .map_err(|e| VideoCompilerError::SerializationError(e.to_string()))?;
+    tokio::fs::write(&self.file, content)
+      .await
+      .map_err(|e| VideoCompilerError::IoError(e.to_string()))
+  }
+}
+
+/// Отслеживание длительности простоя приложения
+#[derive(Debug, Default)]
+pub struct IdleTracker {
+  idle_since: Option<Instant>,
+}
+
+impl IdleTracker {
+  /// Учесть текущую занятость и вернуть длительность простоя.
+  ///
+  /// Занятость или более поздняя интерактивная активность сбрасывают отсчет.
+  pub fn observe(&mut self, busy: bool, now: Instant, last_activity: Option<Instant>) -> Duration {
+    if busy {
+      self.idle_since = None;
+      return Duration::ZERO;
+    }
+    let mut since = *self.idle_since.get_or_insert(now);
+    if let Some(last_activity) = last_activity {
+      if last_activity > since {
+        since = last_activity;
+        self.idle_since = Some(last_activity);
+      }
+    }
+    now.saturating_duration_since(since)
+  }
+}
+
+/// Фоновый планировщик: периодически запускает рендеры, время которых наступило.
+///
+/// `on_started` вызывается для каждой запущенной задачи.
+pub async fn run_scheduler<F>(render: Arc<dyn RenderService>, interval: Duration, on_started: F)
+where
+  F: Fn(&str) + Send + Sync,
+{
+  loop {
+    match render.start_due_scheduled_renders().await {
+      Ok(started) => {
+        for job_id in &started {
+          log::info!("Запланированный рендеринг {job_id} запущен");
+          on_started(job_id);
+        }
+      }
+      Err(e) => log::warn!("Ошибка запуска запланированных рендеров: {e}"),
+    }
+    tokio::time::sleep(interval).await;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tempfile::TempDir;
+
+  #[test]
+  fn test_schedule_from_request() {
+    assert_eq!(
+      RenderSchedule::from_request(None, None, None).unwrap(),
+      None
+    );
+
+    let schedule =
+      RenderSchedule::from_request(Some("2030-01-02T02:00:00+03:00".into()), None, None)
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+      schedule,
+      RenderSchedule::At {
+        start_at: "2030-01-01T23:00:00Z".parse().unwrap()
+      }
+    );
+
+    assert_eq!(
+      RenderSchedule::from_request(None, Some(true), None).unwrap(),
+      Some(RenderSchedule::WhenIdle {
+        idle_minutes: DEFAULT_IDLE_MINUTES
+      })
+    );
+    assert!(RenderSchedule::from_request(Some("tonight".into()), None, None).is_err());
+    assert!(
+      RenderSchedule::from_request(Some("2030-01-01T00:00:00Z".into()), Some(true), None).is_err()
+    );
+  }
+
+  #[test]
+  fn test_schedule_is_due() {
+    let now = Utc::now();
+    let past = RenderSchedule::At {
+      start_at: now - chrono::Duration::minutes(1),
+    };
+    let future = RenderSchedule::At {
+      start_at: now + chrono::Duration::hours(1),
+    };
+    assert!(past.is_due(now, Duration::ZERO));
+    assert!(past.is_past(now));
+    assert!(!future.is_due(now, Duration::from_secs(3600)));
+
+    let idle = RenderSchedule::WhenIdle { idle_minutes: 5 };
+    assert!(!idle.is_due(now, Duration::from_secs(299)));
+    assert!(idle.is_due(now, Duration::from_secs(300)));
+    assert!(!idle.is_past(now));
+  }
+
+  #[test]
+  fn test_idle_tracker_resets_on_activity() {
+    let mut tracker = IdleTracker::default();
+    let start = Instant::now();
+
+    assert_eq!(tracker.observe(false, start, None), Duration::ZERO);
+    let later = start + Duration::from_secs(120);
+    assert_eq!(
+      tracker.observe(false, later, None),
+      Duration::from_secs(120)
+    );
+
+    // Интерактивный запрос сдвигает начало простоя
+    let activity = start + Duration::from_secs(100);
+    assert_eq!(
+      tracker.observe(false, later, Some(activity)),
+      Duration::from_secs(20)
+    );
+
+    // Занятость сбрасывает отсчет полностью
+    assert_eq!(tracker.observe(true, later, None), Duration::ZERO);
+    let after_busy = later + Duration::from_secs(10);
+    assert_eq!(tracker.observe(false, after_busy, None), Duration::ZERO);
+  }
+
+  #[tokio::test]
+  async fn test_store_roundtrip() {
+    let temp_dir = TempDir::new().unwrap();
+    let store =
+      ScheduledRenderStore::new(temp_dir.path().join("nested").join(SCHEDULED_RENDERS_FILE));
+    assert!(store.load().await.is_empty());
+
+    let render = ScheduledRender {
+      id: "job-1".to_string(),
+      project: ProjectSchema::new("Night Render".to_string()),
+      output_path: PathBuf::from("/tmp/night.mp4"),
+      limits: ProcessLimits::default(),
+      schedule: RenderSchedule::WhenIdle { idle_minutes: 15 },
+      created_at: Utc::now(),
+    };
+    store.save(std::slice::from_ref(&render)).await.unwrap();
+
+    let loaded = store.load().await;
+    assert_eq!(loaded.len(), 1);
+    assert_eq!(loaded[0].id, "job-1");
+    assert_eq!(loaded[0].schedule, render.schedule);
+    assert_eq!(loaded[0].project.metadata.name, "Night Render");
+  }
+}
@@ -1,15 +1,32 @@
 //! Сервис рендеринга видео
 
+use crate::core::logging;
+use crate::core::tasks::{TaskKind, TaskManager, TaskStatus, TASKS};
+use crate::language_tauri::i18n::LocalizedMessage;
 use crate::video_compiler::{
+  core::{
+    gpu_memory::RenderDowngrade,
+    priority::{interactive_in_flight, last_interactive_activity},
+    render_priority::ProcessLimits,
+  },
   error::{Result, VideoCompilerError},
-  progress::RenderProgress,
+  progress::{ProgressUpdate, RenderProgress},
   renderer::VideoRenderer,
   schema::ProjectSchema,
-  services::{CacheService, FfmpegService, Service},
+  services::{
+    monitoring::resolve_policy,
+    project_package::media_paths,
+    render_report::RenderReport,
+    render_schedule::{
+      IdleTracker, RenderSchedule, ScheduledRender, ScheduledRenderInfo, ScheduledRenderStore,
+    },
+    CacheService, FfmpegService, Service,
+  },
 };
 use async_trait::async_trait;
-use std::{collections::HashMap, path::PathBuf, sync::Arc};
-use tokio::sync::RwLock;
+use std::{collections::HashMap, path::PathBuf, sync::Arc, time::Instant};
+use tokio::sync::{Mutex, RwLock};
+use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
 /// Трейт для сервиса рендеринга
@@ -19,6 +36,21 @@ pub trait RenderService: Service + Send + Sync {
   /// Запуск рендеринга проекта
   async fn start_render(&self, project: ProjectSchema, output_path: PathBuf) -> Result<String>;
 
+  /// Запуск рендеринга с приоритетом и ограничением потоков FFmpeg
+  async fn start_render_with_limits(
+    &self,
+    project: ProjectSchema,
+    output_path: PathBuf,
+    _limits: ProcessLimits,
+  ) -> Result<String> {
+    self.start_render(project, output_path).await
+  }
+
+  /// Сведения о задаче рендеринга
+  async fn get_job_info(&self, _job_id: &str) -> Result<Option<RenderJobInfo>> {
+    Ok(None)
+  }
+
   /// Получение прогресса рендеринга
   async fn get_progress(&self, job_id: &str) -> Result<Option<RenderProgress>>;
 
@@ -36,11 +68,56 @@ pub trait RenderService: Service + Send + Sync {
 
   /// Проверка доступности слотов для рендеринга
   async fn has_available_slots(&self) -> Result<bool>;
+
+  /// Задачи, которые еще выполняются (без завершенных и проваленных)
+  async fn running_jobs(&self) -> Result<Vec<String>> {
+    self.get_active_jobs().await
+  }
+
+  /// Жестко прервать задачу с сохранением причины в истории задач
+  async fn abort_render(&self, job_id: &str, _reason: &str) -> Result<bool> {
+    self.cancel_render(job_id).await
+  }
+
+  /// Файлы, которые используют выполняющиеся и запланированные задачи:
+  /// результаты и медиафайлы проектов
+  async fn active_job_paths(&self) -> Result<Vec<PathBuf>> {
+    Ok(Vec::new())
+  }
+
+  /// Запланировать рендеринг; время в прошлом запускает его сразу
+  async fn schedule_render(
+    &self,
+    project: ProjectSchema,
+    output_path: PathBuf,
+    limits: ProcessLimits,
+    _schedule: RenderSchedule,
+  ) -> Result<String> {
+    self
+      .start_render_with_limits(project, output_path, limits)
+      .await
+  }
+
+  /// Запланированные, но еще не запущенные рендеры
+  async fn list_scheduled_renders(&self) -> Result<Vec<ScheduledRenderInfo>> {
+    Ok(Vec::new())
+  }
+
+  /// Изменить условие запуска запланированного рендеринга
+  async fn update_render_schedule(&self, _job_id: &str, _schedule: RenderSchedule) -> Result<bool> {
+    Ok(false)
+  }
+
+  /// Запустить запланированные рендеры, время которых наступило
+  async fn start_due_scheduled_renders(&self) -> Result<Vec<String>> {
+    Ok(Vec::new())
+  }
 }
 
 /// Статус задачи рендеринга
 #[derive(Debug, Clone, PartialEq)]
 pub enum RenderJobStatus {
+  Scheduled,
   Initializing,
   Rendering,
   Paused,
@@ -59,6 +136,48 @@ pub struct RenderJob {
   pub created_at: chrono::DateTime<chrono::Utc>,
   pub error: Option<String>,
   pub renderer: Option<VideoRenderer>,
+  /// Путь к результату
+  pub output_path: Option<PathBuf>,
+  /// Итоговые приоритет и ограничение потоков FFmpeg
+  pub process_limits: ProcessLimits,
+  /// Понижения режима кодирования из-за нехватки видеопамяти
+  pub downgrades: Vec<RenderDowngrade>,
+  /// Отчет о завершенном рендере
+  pub report: Option<RenderReport>,
+}
+
+/// Сведения о задаче рендеринга для команд
+#[derive(Debug, Clone)]
+pub struct RenderJobInfo {
+  pub project_name: String,
+  pub output_path: Option<PathBuf>,
+  pub status: RenderJobStatus,
+  pub progress: Option<RenderProgress>,
+  pub created_at: chrono::DateTime<chrono::Utc>,
+  pub error: Option<String>,
+  pub process_limits: ProcessLimits,
+  pub downgrades: Vec<RenderDowngrade>,
+  pub report: Option<RenderReport>,
+}
+
+impl From<&RenderJob> for RenderJobInfo {
+  fn from(job: &RenderJob) -> Self {
+    Self {
+      project_name: job
+        .project_schema
+        .as_ref()
+        .map(|project| project.metadata.name.clone())
+        .unwrap_or_default(),
+      output_path: job.output_path.clone(),
+      status: job.status.clone(),
+      progress: job.progress.clone(),
+      created_at: job.created_at,
+      error: job.error.clone(),
+      process_limits: job.process_limits,
+      downgrades: job.downgrades.clone(),
+      report: job.report.clone(),
+    }
+  }
 }
 
 // Ручная реализация Clone для RenderJob, так как VideoRenderer не поддерживает Clone
@@ -72,6 +191,10 @@ impl Clone for RenderJob {
       created_at: self.created_at,
       error: self.error.clone(),
       renderer: None, // Не клонируем renderer
+      output_path: self.output_path.clone(),
+      process_limits: self.process_limits,
+      downgrades: self.downgrades.clone(),
+      report: self.report.clone(),
     }
   }
 }
@@ -79,9 +202,19 @@ impl Clone for RenderJob {
 /// Реализация сервиса рендеринга
 pub struct RenderServiceImpl {
   active_jobs: Arc<RwLock<HashMap<String, RenderJob>>>,
+  /// Токены отмены фоновых задач рендеринга
+  cancellations: Arc<RwLock<HashMap<String, CancellationToken>>>,
   max_concurrent_jobs: usize,
   #[allow(dead_code)]
   cache_service: Arc<dyn CacheService>,
+  /// Реестр фоновых задач, в котором регистрируются рендеры
+  tasks: Arc<TaskManager>,
+  /// Запланированные, но еще не запущенные рендеры
+  scheduled: Arc<RwLock<Vec<ScheduledRender>>>,
+  /// Хранилище запланированных рендеров на диске
+  schedule_store: Option<ScheduledRenderStore>,
+  /// Длительность простоя для запуска в режиме простоя
+  idle: Mutex<IdleTracker>,
 }
 
 impl RenderServiceImpl {
@@ -92,8 +225,57 @@ impl RenderServiceImpl {
   ) -> Self {
     Self {
       active_jobs: Arc::new(RwLock::new(HashMap::new())),
+      cancellations: Arc::new(RwLock::new(HashMap::new())),
       max_concurrent_jobs,
       cache_service,
+      tasks: TASKS.clone(),
+      scheduled: Arc::new(RwLock::new(Vec::new())),
+      schedule_store: None,
+      idle: Mutex::new(IdleTracker::default()),
+    }
+  }
+
+  /// Сохранять запланированные рендеры в заданном хранилище
+  pub fn with_schedule_store(mut self, store: ScheduledRenderStore) -> Self {
+    self.schedule_store = Some(store);
+    self
+  }
+
+  /// Записать текущий список запланированных рендеров на диск
+  async fn persist_schedule(&self) {
+    let Some(store) = &self.schedule_store else {
+      return;
+    };
+    let scheduled = self.scheduled.read().await.clone();
+    if let Err(e) = store.save(&scheduled).await {
+      log::warn!("Не удалось сохранить запланированные рендеры: {e}");
+    }
+  }
+
+  /// Занято ли приложение рендерами, фоновыми задачами или запросами UI
+  async fn is_busy(&self) -> bool {
+    let rendering = self
+      .running_jobs()
+      .await
+      .map(|jobs| !jobs.is_empty())
+      .unwrap_or(true);
+    rendering || !self.tasks.list().is_empty() || interactive_in_flight() > 0
+  }
+
+  /// Регистрировать рендеры в заданном реестре фоновых задач
+  pub fn with_task_manager(mut self, tasks: Arc<TaskManager>) -> Self {
+    self.tasks = tasks;
+    self
+  }
+
+  /// Остановить фоновую задачу рендеринга
+  async fn cancel_background_task(&self, job_id: &str) -> bool {
+    match self.cancellations.write().await.remove(job_id) {
+      Some(token) => {
+        token.cancel();
+        true
+      }
+      None => false,
     }
   }
 }
@@ -102,6 +284,15 @@ impl RenderServiceImpl {
 impl Service for RenderServiceImpl {
   async fn initialize(&self) -> Result<()> {
     log::info!("Инициализация сервиса рендеринга");
+
+    // Запланированные до перезапуска рендеры восстанавливаются с диска
+    if let Some(store) = &self.schedule_store {
+      let restored = store.load().await;
+      if !restored.is_empty() {
+        log::info!("Восстановлено запланированных рендеров: {}", restored.len());
+      }
+      *self.scheduled.write().await = restored;
+    }
     Ok(())
   }
 
@@ -188,27 +379,17 @@ impl RenderServiceImpl {
     let jobs = self.active_jobs.read().await;
     Ok(jobs.values().cloned().collect())
   }
-}
-
-#[async_trait]
-impl RenderService for RenderServiceImpl {
-  async fn start_render(&self, project: ProjectSchema, output_path: PathBuf) -> Result<String> {
-    // Проверяем доступность слотов
-    if !self.has_available_slots().await? {
-      return Err(VideoCompilerError::TooManyActiveJobs(format!(
-        "Максимальное количество одновременных задач: {}",
-        self.max_concurrent_jobs
-      )));
-    }
-
-    // Валидируем проект
-    project.validate()?;
-
-    // Создаем ID задачи
-    let job_id = Uuid::new_v4().to_string();
 
+  /// Запустить рендеринг проверенного проекта под заданным ID задачи
+  async fn launch_render(
+    &self,
+    job_id: String,
+    project: ProjectSchema,
+    output_path: PathBuf,
+    limits: ProcessLimits,
+  ) -> Result<String> {
     // Создаем прогресс канал
-    let (progress_sender, _progress_receiver) = tokio::sync::mpsc::unbounded_channel();
+    let (progress_sender, mut progress_receiver) = tokio::sync::mpsc::unbounded_channel();
 
     // Создаем временные настройки для рендерера
     let settings = Arc::new(RwLock::new(
@@ -221,7 +402,9 @@ impl RenderService for RenderServiceImpl {
     let cache = Arc::new(RwLock::new(crate::video_compiler::cache::RenderCache::new()));
 
     // Создаем рендерер
-    let renderer = VideoRenderer::new(project.clone(), settings, cache, progress_sender).await?;
+    let renderer = VideoRenderer::new(project.clone(), settings, cache, progress_sender)
+      .await?
+      .with_process_limits(limits);
 
     // Создаем задачу
     let job = RenderJob {
@@ -232,6 +415,10 @@ impl RenderService for RenderServiceImpl {
       created_at: chrono::Utc::now(),
       error: None,
       renderer: Some(renderer),
+      output_path: Some(output_path.clone()),
+      process_limits: limits,
+      downgrades: Vec::new(),
+      report: None,
     };
 
     // Добавляем в активные задачи
@@ -241,11 +428,70 @@ impl RenderService for RenderServiceImpl {
     }
 
     // Запускаем рендеринг в фоне
+    let cancel_token = CancellationToken::new();
+    self
+      .cancellations
+      .write()
+      .await
+      .insert(job_id.clone(), cancel_token.clone());
+
+    // Отмена из панели фоновых задач идет через тот же токен
+    let task_cancel = cancel_token.clone();
+    let task = Arc::new(self.tasks.register_cancellable(
+      TaskKind::Render,
+      format!("Рендеринг {}", project.metadata.name),
+      move || task_cancel.cancel(),
+    ));
+
+    // Прогресс рендерера пишем в задачу рендеринга и в реестр фоновых задач
+    let progress_jobs = self.active_jobs.clone();
+    let progress_task = task.clone();
+    let progress_job_id = job_id.clone();
+    tokio::spawn(async move {
+      while let Some(update) = progress_receiver.recv().await {
+        match update {
+          ProgressUpdate::ProgressChanged { progress, .. } => {
+            progress_task.set_progress(progress.percentage);
+            progress_task.set_status(TaskStatus::Running);
+            if let Some(job) = progress_jobs.write().await.get_mut(&progress_job_id) {
+              job.status = RenderJobStatus::Rendering;
+              job.progress = Some(progress);
+            }
+          }
+          // Понижение режима видно в задаче рендеринга и в панели фоновых задач
+          ProgressUpdate::RenderDowngraded {
+            downgrade, message, ..
+          } => {
+            progress_task.set_message(message);
+            if let Some(job) = progress_jobs.write().await.get_mut(&progress_job_id) {
+              if !job.downgrades.contains(&downgrade) {
+                job.downgrades.push(downgrade);
+              }
+            }
+          }
+          // Отчет хранится в истории задач под идентификатором сервиса
+          ProgressUpdate::ReportReady { report, .. } => {
+            let mut report = *report;
+            report.job_id = progress_job_id.clone();
+            if let Err(e) = report.save().await {
+              log::warn!("Не удалось обновить отчет о рендере: {e}");
+            }
+            if let Some(job) = progress_jobs.write().await.get_mut(&progress_job_id) {
+              job.report = Some(report);
+            }
+          }
+          _ => {}
+        }
+      }
+    });
+
     let jobs = self.active_jobs.clone();
+    let cancellations = self.cancellations.clone();
     let job_id_clone = job_id.clone();
     let output_path_clone = output_path.clone();
 
-    tokio::spawn(async move {
+    // Записи логов рендеринга помечаются job_id для фильтрации по рендеру
+    tokio::spawn(logging::with_job_id(job_id.clone(), async move {
       // Получаем рендерер из задачи
       let renderer = {
         let mut jobs_lock = jobs.write().await;
@@ -257,7 +503,41 @@ impl RenderService for RenderServiceImpl {
       };
 
       if let Some(mut renderer) = renderer {
-        match renderer.render(&output_path_clone).await {
+        // Кодирование не повторяется и по умолчанию не ограничено тайм-аутом
+        // (политика "render-service.render"), поэтому with_policy здесь не используется
+        let render = renderer.render(&output_path_clone);
+        let render = async {
+          match resolve_policy("render-service", "render").timeout {
+            Some(timeout) => tokio::time::timeout(timeout, render)
+              .await
+              .unwrap_or_else(|_| {
+                Err(VideoCompilerError::timeout(format!(
+                  "render-service.render не завершилась за {timeout:?}"
+                )))
+              }),
+            None => render.await,
+          }
+        };
+
+        // Отмена прерывает future рендеринга вместе с процессом FFmpeg
+        let result = tokio::select! {
+          result = render => Some(result),
+          _ = cancel_token.cancelled() => None,
+        };
+        let result = match result {
+          Some(result) => result,
+          None => {
+            if let Err(e) = renderer.cancel().await {
+              log::warn!("Ошибка отмены рендерера {job_id_clone}: {e:?}");
+            }
+            Err(VideoCompilerError::CancelledError(format!(
+              "Рендеринг {job_id_clone} отменен"
+            )))
+          }
+        };
+        cancellations.write().await.remove(&job_id_clone);
+
+        match result {
           Ok(_) => {
             log::info!("Рендеринг {job_id_clone} успешно завершен");
             // Обновляем статус
@@ -266,6 +546,13 @@ impl RenderService for RenderServiceImpl {
               job.status = RenderJobStatus::Completed;
             }
           }
+          Err(VideoCompilerError::CancelledError(_)) => {
+            log::info!("Рендеринг {job_id_clone} отменен");
+            let mut jobs_lock = jobs.write().await;
+            if let Some(job) = jobs_lock.get_mut(&job_id_clone) {
+              job.status = RenderJobStatus::Cancelled;
+            }
+          }
           Err(e) => {
             log::error!("Ошибка рендеринга {job_id_clone}: {e:?}");
             // Обновляем статус
@@ -277,17 +564,89 @@ impl RenderService for RenderServiceImpl {
           }
         }
       }
-    });
+      // Снимаем рендер с учета фоновых задач
+      drop(task);
+    }));
 
     Ok(job_id)
   }
+}
+
+#[async_trait]
+impl RenderService for RenderServiceImpl {
+  async fn start_render(&self, project: ProjectSchema, output_path: PathBuf) -> Result<String> {
+    self
+      .start_render_with_limits(project, output_path, ProcessLimits::default())
+      .await
+  }
+
+  async fn start_render_with_limits(
+    &self,
+    project: ProjectSchema,
+    output_path: PathBuf,
+    limits: ProcessLimits,
+  ) -> Result<String> {
+    // Проверяем доступность слотов
+    if !self.has_available_slots().await? {
+      return Err(VideoCompilerError::TooManyActiveJobs(
+        LocalizedMessage::new(
+          "render_queue.limit_reached",
+          &[("limit", self.max_concurrent_jobs.to_string())],
+        ),
+      ));
+    }
+
+    // Валидируем проект
+    project.validate()?;
+
+    self
+      .launch_render(Uuid::new_v4().to_string(), project, output_path, limits)
+      .await
+  }
 
   async fn get_progress(&self, job_id: &str) -> Result<Option<RenderProgress>> {
     let jobs = self.active_jobs.read().await;
     Ok(jobs.get(job_id).and_then(|job| job.progress.clone()))
   }
 
+  async fn get_job_info(&self, job_id: &str) -> Result<Option<RenderJobInfo>> {
+    if let Some(info) = self.active_jobs.read().await.get(job_id) {
+      return Ok(Some(RenderJobInfo::from(info)));
+    }
+    let scheduled = self.scheduled.read().await;
+    Ok(
+      scheduled
+        .iter()
+        .find(|render| render.id == job_id)
+        .map(|render| RenderJobInfo {
+          project_name: render.project.metadata.name.clone(),
+          output_path: Some(render.output_path.clone()),
+          status: RenderJobStatus::Scheduled,
+          progress: None,
+          created_at: render.created_at,
+          error: None,
+          process_limits: render.limits,
+          downgrades: Vec::new(),
+          report: None,
+        }),
+    )
+  }
+
   async fn cancel_render(&self, job_id: &str) -> Result<bool> {
+    // Запланированная задача просто снимается с расписания
+    let unscheduled = {
+      let mut scheduled = self.scheduled.write().await;
+      let before = scheduled.len();
+      scheduled.retain(|render| render.id != job_id);
+      scheduled.len() != before
+    };
+    if unscheduled {
+      self.persist_schedule().await;
+      return Ok(true);
+    }
+
+    self.cancel_background_task(job_id).await;
+
     let mut jobs = self.active_jobs.write().await;
     if let Some(mut job) = jobs.remove(job_id) {
       if let Some(renderer) = job.renderer.as_mut() {
@@ -330,13 +689,201 @@ impl RenderService for RenderServiceImpl {
   }
 
   async fn get_active_jobs(&self) -> Result<Vec<String>> {
-    let jobs = self.active_jobs.read().await;
-    Ok(jobs.keys().cloned().collect())
+    let mut job_ids: Vec<String> = self.active_jobs.read().await.keys().cloned().collect();
+    job_ids.extend(
+      self
+        .scheduled
+        .read()
+        .await
+        .iter()
+        .map(|render| render.id.clone()),
+    );
+    Ok(job_ids)
   }
 
   async fn has_available_slots(&self) -> Result<bool> {
+    // Завершенные задачи остаются в истории, но слот не занимают
+    Ok(self.running_jobs().await?.len() < self.max_concurrent_jobs)
+  }
+
+  async fn running_jobs(&self) -> Result<Vec<String>> {
     let jobs = self.active_jobs.read().await;
-    Ok(jobs.len() < self.max_concurrent_jobs)
+    Ok(
+      jobs
+        .values()
+        .filter(|job| {
+          matches!(
+            job.status,
+            RenderJobStatus::Initializing | RenderJobStatus::Rendering | RenderJobStatus::Paused
+          )
+        })
+        .map(|job| job.id.clone())
+        .collect(),
+    )
+  }
+
+  async fn active_job_paths(&self) -> Result<Vec<PathBuf>> {
+    let project_media =
+      |project: &ProjectSchema| media_paths(project).into_iter().map(PathBuf::from);
+
+    let mut paths = Vec::new();
+    for job in self.active_jobs.read().await.values().filter(|job| {
+      matches!(
+        job.status,
+        RenderJobStatus::Initializing | RenderJobStatus::Rendering | RenderJobStatus::Paused
+      )
+    }) {
+      paths.extend(job.output_path.clone());
+      paths.extend(job.project_schema.iter().flat_map(project_media));
+    }
+    for render in self.scheduled.read().await.iter() {
+      paths.push(render.output_path.clone());
+      paths.extend(project_media(&render.project));
+    }
+    Ok(paths)
+  }
+
+  async fn abort_render(&self, job_id: &str, reason: &str) -> Result<bool> {
+    let cancelled = self.cancel_background_task(job_id).await;
+
+    let mut jobs = self.active_jobs.write().await;
+    let Some(job) = jobs.get_mut(job_id) else {
+      return Ok(cancelled);
+    };
+    if let Some(renderer) = job.renderer.as_mut() {
+      renderer.cancel().await?;
+    }
+    // Задача остается в списке, чтобы история фиксировала причину сбоя
+    job.status = RenderJobStatus::Failed;
+    job.error = Some(reason.to_string());
+    Ok(true)
+  }
+
+  async fn schedule_render(
+    &self,
+    project: ProjectSchema,
+    output_path: PathBuf,
+    limits: ProcessLimits,
+    schedule: RenderSchedule,
+  ) -> Result<String> {
+    if schedule.is_past(chrono::Utc::now()) {
+      return self
+        .start_render_with_limits(project, output_path, limits)
+        .await;
+    }
+
+    project.validate()?;
+
+    let render = ScheduledRender {
+      id: Uuid::new_v4().to_string(),
+      project,
+      output_path,
+      limits,
+      schedule,
+      created_at: chrono::Utc::now(),
+    };
+    let job_id = render.id.clone();
+    self.scheduled.write().await.push(render);
+    self.persist_schedule().await;
+
+    log::info!("Рендеринг {job_id} запланирован");
+    Ok(job_id)
+  }
+
+  async fn list_scheduled_renders(&self) -> Result<Vec<ScheduledRenderInfo>> {
+    let scheduled = self.scheduled.read().await;
+    Ok(scheduled.iter().map(ScheduledRenderInfo::from).collect())
+  }
+
+  async fn update_render_schedule(&self, job_id: &str, schedule: RenderSchedule) -> Result<bool> {
+    let start_now = schedule.is_past(chrono::Utc::now());
+    {
+      let mut scheduled = self.scheduled.write().await;
+      let Some(render) = scheduled.iter_mut().find(|render| render.id == job_id) else {
+        return Ok(false);
+      };
+      render.schedule = schedule;
+    }
+    self.persist_schedule().await;
+
+    // Перенос на прошедшее время запускает задачу без ожидания планировщика
+    if start_now {
+      self.start_due_scheduled_renders().await?;
+    }
+    Ok(true)
+  }
+
+  async fn start_due_scheduled_renders(&self) -> Result<Vec<String>> {
+    let busy = self.is_busy().await;
+    let idle_for =
+      self
+        .idle
+        .lock()
+        .await
+        .observe(busy, Instant::now(), last_interactive_activity());
+    let now = chrono::Utc::now();
+
+    let mut started = Vec::new();
+    let mut changed = false;
+    loop {
+      if !self.has_available_slots().await? {
+        break;
+      }
+      let render = {
+        let mut scheduled = self.scheduled.write().await;
+        match scheduled
+          .iter()
+          .position(|render| render.schedule.is_due(now, idle_for))
+        {
+          Some(index) => scheduled.remove(index),
+          None => break,
+        }
+      };
+      changed = true;
+
+      let job_id = render.id.clone();
+      let result = match render.project.validate() {
+        Ok(()) => {
+          self
+            .launch_render(
+              job_id.clone(),
+              render.project.clone(),
+              render.output_path.clone(),
+              render.limits,
+            )
+            .await
+        }
+        Err(e) => Err(VideoCompilerError::validation(e)),
+      };
+      match result {
+        Ok(job_id) => started.push(job_id),
+        Err(e) => {
+          // Неудачный запуск остается в истории задач с причиной
+          log::error!("Не удалось запустить запланированный рендеринг {job_id}: {e}");
+          self.active_jobs.write().await.insert(
+            job_id.clone(),
+            RenderJob {
+              id: job_id,
+              project_schema: Some(render.project),
+              status: RenderJobStatus::Failed,
+              progress: None,
+              created_at: render.created_at,
+              error: Some(e.to_string()),
+              renderer: None,
+              output_path: Some(render.output_path),
+              process_limits: render.limits,
+              downgrades: Vec::new(),
+              report: None,
+            },
+          );
+        }
+      }
+    }
+
+    if changed {
+      self.persist_schedule().await;
+    }
+    Ok(started)
   }
 }
 
@@ -414,6 +961,10 @@ mod tests {
           created_at: chrono::Utc::now(),
           error: None,
           renderer: None,
+          output_path: None,
+          process_limits: Default::default(),
+          downgrades: Vec::new(),
+          report: None,
         },
       );
     }
@@ -427,6 +978,58 @@ mod tests {
     assert_eq!(job.status, RenderJobStatus::Initializing);
   }
 
+  #[tokio::test]
+  async fn test_abort_render_keeps_failed_job_in_history() {
+    let ffmpeg_service = Arc::new(FfmpegServiceImpl::new("ffmpeg".to_string()));
+    let cache_service = Arc::new(CacheServiceImpl::new(std::env::temp_dir()));
+    let service = RenderServiceImpl::new(ffmpeg_service, 3, cache_service);
+
+    {
+      let mut active_jobs = service.active_jobs.write().await;
+      for (id, status) in [
+        ("running", RenderJobStatus::Rendering),
+        ("done", RenderJobStatus::Completed),
+      ] {
+        active_jobs.insert(
+          id.to_string(),
+          RenderJob {
+            id: id.to_string(),
+            project_schema: None,
+            status,
+            progress: None,
+            created_at: chrono::Utc::now(),
+            error: None,
+            renderer: None,
+            output_path: None,
+            process_limits: Default::default(),
+            downgrades: Vec::new(),
+            report: None,
+          },
+        );
+      }
+    }
+    let token = CancellationToken::new();
+    service
+      .cancellations
+      .write()
+      .await
+      .insert("running".to_string(), token.clone());
+
+    assert_eq!(service.running_jobs().await.unwrap(), vec!["running"]);
+
+    assert!(service
+      .abort_render("running", "Application shutdown")
+      .await
+      .unwrap());
+    assert!(token.is_cancelled());
+    assert!(service.running_jobs().await.unwrap().is_empty());
+
+    let job = service.get_job_status("running").await.unwrap().unwrap();
+    assert_eq!(job.status, RenderJobStatus::Failed);
+    assert_eq!(job.error.as_deref(), Some("Application shutdown"));
+    assert!(!service.abort_render("missing", "reason").await.unwrap());
+  }
+
   #[tokio::test]
   async fn test_job_cleanup() {
     use uuid::Uuid;
@@ -450,6 +1053,10 @@ mod tests {
           created_at: chrono::Utc::now(),
           error: None,
           renderer: None,
+          output_path: None,
+          process_limits: Default::default(),
+          downgrades: Vec::new(),
+          report: None,
         },
       );
     }
@@ -487,6 +1094,10 @@ mod tests {
             created_at: chrono::Utc::now(),
             error: None,
             renderer: None,
+            output_path: None,
+            process_limits: Default::default(),
+            downgrades: Vec::new(),
+            report: None,
           },
         );
       }
@@ -495,6 +1106,18 @@ mod tests {
     // Теперь слотов нет
     assert!(!service.has_available_slots().await.unwrap());
 
+    // Завершенная задача освобождает слот, оставаясь в истории
+    service
+      .update_job_status("job_0", RenderJobStatus::Completed)
+      .await
+      .unwrap();
+    assert!(service.has_available_slots().await.unwrap());
+    service
+      .update_job_status("job_0", RenderJobStatus::Rendering)
+      .await
+      .unwrap();
+    assert!(!service.has_available_slots().await.unwrap());
+
     // Освобождаем один слот
     {
       let mut active_jobs = service.active_jobs.write().await;
@@ -529,6 +1152,10 @@ mod tests {
           created_at: chrono::Utc::now(),
           error: None,
           renderer: None,
+          output_path: None,
+          process_limits: Default::default(),
+          downgrades: Vec::new(),
+          report: None,
         },
       );
     }
@@ -582,6 +1209,10 @@ mod tests {
           created_at: chrono::Utc::now(),
           error: None,
           renderer: None,
+          output_path: None,
+          process_limits: Default::default(),
+          downgrades: Vec::new(),
+          report: None,
         },
       );
     }
@@ -597,6 +1228,8 @@ mod tests {
       estimated_remaining: Some(Duration::from_secs(45)),
       status: RenderStatus::Processing,
       message: Some("Processing frame 100".to_string()),
+      renditions: Vec::new(),
+      stages: Vec::new(),
     };
 
     service
@@ -4,7 +4,8 @@
 
 use super::*;
 use crate::video_compiler::schema::{
-  Clip, ClipSource, ExportSettings, OutputFormat, ProjectSchema, Timeline, Track, TrackType,
+  Clip, ClipSource, ExportSettings, HdrMode, MissingAssetPolicy, OutputFormat, ProjectSchema,
+  RenderReportSettings, StemFormat, SubtitleMode, Timeline, Track, TrackType,
 };
 use crate::video_compiler::services::{CacheServiceImpl, FfmpegServiceImpl};
 use std::sync::Arc;
@@ -21,6 +22,7 @@ fn create_test_project_with_content(name: &str) -> ProjectSchema {
     resolution: (1920, 1080),
     sample_rate: 48000,
     aspect_ratio: crate::video_compiler::schema::AspectRatio::default(),
+    markers: Vec::new(),
   };
 
   // Настройка экспорта
@@ -46,6 +48,27 @@ fn create_test_project_with_content(name: &str) -> ProjectSchema {
     normalize_audio: Some(false),
     audio_target: None,
     audio_peak: None,
+    subtitle_mode: SubtitleMode::Burn,
+    subtitle_language: None,
+    renditions: Vec::new(),
+    generate_hls: false,
+    audio_bit_depth: None,
+    color_primaries: None,
+    transfer_characteristics: None,
+    matrix_coefficients: None,
+    hdr_mode: HdrMode::None,
+    hdr_metadata: None,
+    render_nested_as_intermediate: false,
+    filename_template: None,
+    export_preset: None,
+    segment_cache: None,
+    watermark: None,
+    timecode_burn_in: None,
+    write_timecode_track: false,
+    export_stems: false,
+    stems_format: StemFormat::Wav,
+    missing_assets: MissingAssetPolicy::Warn,
+    render_report: RenderReportSettings::default(),
   };
 
   // Добавляем тестовые треки и клипы
@@ -67,6 +90,18 @@ fn create_test_project_with_content(name: &str) -> ProjectSchema {
     crop: None,
     transform: None,
     audio_track_index: None,
+    audio_stream_index: None,
+    source_rotation: 0,
+    color_label: None,
+    notes: None,
+    tags: Vec::new(),
+    fade_in: None,
+    fade_out: None,
+    link_group_id: None,
+    source_timecode: None,
+    volume: 1.0,
+    external_audio: None,
+    locked: false,
     properties: crate::video_compiler::schema::ClipProperties::default(),
   });
 
@@ -88,6 +123,18 @@ fn create_test_project_with_content(name: &str) -> ProjectSchema {
     crop: None,
     transform: None,
     audio_track_index: None,
+    audio_stream_index: None,
+    source_rotation: 0,
+    color_label: None,
+    notes: None,
+    tags: Vec::new(),
+    fade_in: None,
+    fade_out: None,
+    link_group_id: None,
+    source_timecode: None,
+    volume: 1.0,
+    external_audio: None,
+    locked: false,
     properties: crate::video_compiler::schema::ClipProperties::default(),
   });
 
@@ -133,6 +180,42 @@ mod integration_tests {
     }
   }
 
+  #[tokio::test]
+  async fn test_start_render_registers_cancellable_background_task() {
+    let temp_dir = TempDir::new().unwrap();
+    let ffmpeg_service = Arc::new(FfmpegServiceImpl::new("ffmpeg".to_string()));
+    let cache_service = Arc::new(CacheServiceImpl::new(temp_dir.path().to_path_buf()));
+    let tasks = Arc::new(TaskManager::new());
+    let service =
+      RenderServiceImpl::new(ffmpeg_service, 2, cache_service).with_task_manager(tasks.clone());
+
+    let project = create_test_project_with_content("Background Task");
+    let output_path = temp_dir.path().join("output.mp4");
+
+    let Ok(job_id) = service.start_render(project, output_path).await else {
+      println!("Renderer unavailable in test environment");
+      return;
+    };
+
+    let Some(task) = tasks.list().into_iter().next() else {
+      // Рендер успел завершиться и снять задачу с учета
+      return;
+    };
+    assert_eq!(task.kind, TaskKind::Render);
+    assert!(task.cancellable);
+
+    // Отмена из панели задач останавливает фоновый рендеринг
+    assert!(tasks.cancel(&task.id).unwrap());
+    tokio::time::timeout(std::time::Duration::from_secs(5), async {
+      while tasks.get(&task.id).is_some() {
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+      }
+    })
+    .await
+    .expect("render task should deregister after cancellation");
+    assert!(!service.cancellations.read().await.contains_key(&job_id));
+  }
+
   #[tokio::test]
   async fn test_start_render_exceeds_concurrent_limit() {
     let temp_dir = TempDir::new().unwrap();
@@ -153,6 +236,10 @@ mod integration_tests {
           created_at: chrono::Utc::now(),
           error: None,
           renderer: None,
+          output_path: None,
+          process_limits: Default::default(),
+          downgrades: Vec::new(),
+          report: None,
         },
       );
     }
@@ -272,6 +359,10 @@ mod concurrent_operations_tests {
               created_at: chrono::Utc::now(),
               error: None,
               renderer: None,
+              output_path: None,
+              process_limits: Default::default(),
+              downgrades: Vec::new(),
+              report: None,
             },
           );
         }
@@ -331,6 +422,10 @@ mod concurrent_operations_tests {
                 created_at: chrono::Utc::now(),
                 error: None,
                 renderer: None,
+                output_path: None,
+                process_limits: Default::default(),
+                downgrades: Vec::new(),
+                report: None,
               },
             );
           }
@@ -453,6 +548,10 @@ mod edge_cases_tests {
           created_at: chrono::Utc::now(),
           error: None,
           renderer: None,
+          output_path: None,
+          process_limits: Default::default(),
+          downgrades: Vec::new(),
+          report: None,
         },
       );
     }
@@ -491,6 +590,10 @@ mod service_lifecycle_tests {
             created_at: chrono::Utc::now(),
             error: None,
             renderer: None,
+            output_path: None,
+            process_limits: Default::default(),
+            downgrades: Vec::new(),
+            report: None,
           },
         );
       }
@@ -532,6 +635,10 @@ mod service_lifecycle_tests {
           created_at: chrono::Utc::now(),
           error: None,
           renderer: None,
+          output_path: None,
+          process_limits: Default::default(),
+          downgrades: Vec::new(),
+          report: None,
         },
       );
     }
@@ -568,6 +675,10 @@ mod render_job_tests {
       created_at: chrono::Utc::now(),
       error: Some("test error".to_string()),
       renderer: None,
+      output_path: None,
+      process_limits: Default::default(),
+      downgrades: Vec::new(),
+      report: None,
     };
 
     // Clone должен работать корректно
@@ -591,3 +702,123 @@ mod render_job_tests {
     assert_ne!(RenderJobStatus::Completed, RenderJobStatus::Failed);
   }
 }
+
+#[cfg(test)]
+mod scheduled_render_tests {
+  use super::*;
+  use crate::video_compiler::services::render_schedule::SCHEDULED_RENDERS_FILE;
+
+  fn create_service(temp_dir: &TempDir) -> RenderServiceImpl {
+    let ffmpeg_service = Arc::new(FfmpegServiceImpl::new("ffmpeg".to_string()));
+    let cache_service = Arc::new(CacheServiceImpl::new(temp_dir.path().to_path_buf()));
+    RenderServiceImpl::new(ffmpeg_service, 2, cache_service)
+      .with_task_manager(Arc::new(TaskManager::new()))
+      .with_schedule_store(ScheduledRenderStore::new(
+        temp_dir.path().join(SCHEDULED_RENDERS_FILE),
+      ))
+  }
+
+  fn tonight() -> RenderSchedule {
+    RenderSchedule::At {
+      start_at: chrono::Utc::now() + chrono::Duration::hours(6),
+    }
+  }
+
+  #[tokio::test]
+  async fn test_scheduled_render_is_visible_and_survives_restart() {
+    let temp_dir = TempDir::new().unwrap();
+    let service = create_service(&temp_dir);
+
+    let job_id = service
+      .schedule_render(
+        create_test_project_with_content("Night Render"),
+        temp_dir.path().join("night.mp4"),
+        ProcessLimits::default(),
+        tonight(),
+      )
+      .await
+      .unwrap();
+
+    // Задача видна в списке задач со статусом Scheduled, но не занимает слот
+    let job_ids = RenderService::get_active_jobs(&service).await.unwrap();
+    assert_eq!(job_ids, vec![job_id.clone()]);
+    let info = service.get_job_info(&job_id).await.unwrap().unwrap();
+    assert_eq!(info.status, RenderJobStatus::Scheduled);
+    assert_eq!(info.project_name, "Night Render");
+    assert!(service.running_jobs().await.unwrap().is_empty());
+    assert!(service
+      .start_due_scheduled_renders()
+      .await
+      .unwrap()
+      .is_empty());
+
+    // Новый экземпляр сервиса восстанавливает расписание с диска
+    let restarted = create_service(&temp_dir);
+    restarted.initialize().await.unwrap();
+    let scheduled = restarted.list_scheduled_renders().await.unwrap();
+    assert_eq!(scheduled.len(), 1);
+    assert_eq!(scheduled[0].id, job_id);
+    assert_eq!(scheduled[0].project_name, "Night Render");
+  }
+
+  #[tokio::test]
+  async fn test_cancel_scheduled_render() {
+    let temp_dir = TempDir::new().unwrap();
+    let service = create_service(&temp_dir);
+
+    let job_id = service
+      .schedule_render(
+        create_test_project_with_content("Cancelled"),
+        temp_dir.path().join("cancelled.mp4"),
+        ProcessLimits::default(),
+        tonight(),
+      )
+      .await
+      .unwrap();
+
+    assert!(service.cancel_render(&job_id).await.unwrap());
+    assert!(service.list_scheduled_renders().await.unwrap().is_empty());
+    assert!(service.get_job_info(&job_id).await.unwrap().is_none());
+
+    let restarted = create_service(&temp_dir);
+    restarted.initialize().await.unwrap();
+    assert!(restarted.list_scheduled_renders().await.unwrap().is_empty());
+  }
+
+  #[tokio::test]
+  async fn test_update_render_schedule() {
+    let temp_dir = TempDir::new().unwrap();
+    let service = create_service(&temp_dir);
+
+    let job_id = service
+      .schedule_render(
+        create_test_project_with_content("Rescheduled"),
+        temp_dir.path().join("rescheduled.mp4"),
+        ProcessLimits::default(),
+        tonight(),
+      )
+      .await
+      .unwrap();
+
+    let idle = RenderSchedule::WhenIdle { idle_minutes: 30 };
+    assert!(service
+      .update_render_schedule(&job_id, idle.clone())
+      .await
+      .unwrap());
+    assert!(!service
+      .update_render_schedule("missing", idle.clone())
+      .await
+      .unwrap());
+
+    let scheduled = service.list_scheduled_renders().await.unwrap();
+    assert_eq!(scheduled[0].schedule, idle);
+
+    // Простой только начался, поэтому задача продолжает ждать
+    assert!(service
+      .start_due_scheduled_renders()
+      .await
+      .unwrap()
+      .is_empty());
+    assert_eq!(service.list_scheduled_renders().await.unwrap().len(), 1);
+  }
+}
@@ -0,0 +1,518 @@
+//! Render Spec - переносимое описание задачи рендеринга
+//!
+//! Спецификация содержит все, что нужно для повторения рендеринга на другой
+//! машине: схему проекта с уже примененными настройками экспорта, требования к
+//! FFmpeg и список медиафайлов с SHA-256. Пути к медиа в схеме хранятся
+//! относительно общей директории и на целевой машине разрешаются от указанного
+//! `media_root`. Сам рендеринг выполняется обычным конвейером.
+
+use crate::video_compiler::{
+  core::error::{Result, VideoCompilerError},
+  core::ffmpeg_manager::{self, FfmpegStatus, REQUIRED_ENCODERS, REQUIRED_FILTERS},
+  schema::{ExportSettings, ProjectSchema},
+  services::project_package::{for_each_media_path_mut, media_paths},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+/// Версия формата спецификации
+pub const RENDER_SPEC_FORMAT_VERSION: u32 = 1;
+
+/// Требования к FFmpeg на машине рендеринга
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FfmpegRequirements {
+  /// Версия FFmpeg, с которой экспортирована задача (для справки)
+  pub exported_with: Option<String>,
+  /// Обязательные кодировщики
+  pub encoders: Vec<String>,
+  /// Обязательные фильтры
+  pub filters: Vec<String>,
+}
+
+/// Медиафайл задачи
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderSpecMedia {
+  /// Путь относительно `media_root` с разделителями `/`
+  pub path: String,
+  /// Путь на машине, где задача экспортирована
+  pub original_path: String,
+  pub size: u64,
+  pub sha256: String,
+}
+
+/// Переносимая спецификация рендеринга
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderSpec {
+  pub format_version: u32,
+  pub created_at: DateTime<Utc>,
+  /// Схема проекта с относительными путями к медиа
+  pub project: ProjectSchema,
+  pub ffmpeg: FfmpegRequirements,
+  pub media: Vec<RenderSpecMedia>,
+}
+
+/// Краткие сведения об экспортированной спецификации
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderSpecSummary {
+  pub spec_path: String,
+  pub media_count: usize,
+  pub total_bytes: u64,
+  /// Общая директория медиа на исходной машине
+  pub source_media_root: Option<String>,
+}
+
+/// Медиафайл, содержимое которого отличается от экспортированного
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderSpecMismatch {
+  pub path: String,
+  pub expected_sha256: String,
+  pub actual_sha256: String,
+}
+
+/// Результат проверки медиафайлов спецификации
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RenderSpecVerification {
+  /// Отсутствующие файлы (относительные пути)
+  pub missing: Vec<String>,
+  /// Файлы с несовпадающим хешем
+  pub mismatched: Vec<RenderSpecMismatch>,
+}
+
+impl RenderSpecVerification {
+  /// Все медиафайлы на месте и совпадают
+  pub fn is_ok(&self) -> bool {
+    self.missing.is_empty() && self.mismatched.is_empty()
+  }
+}
+
+/// Общая директория всех медиафайлов
+fn common_media_root(paths: &[PathBuf]) -> Result<Option<PathBuf>> {
+  let mut root: Option<PathBuf> = None;
+  for path in paths {
+    let parent = path.parent().unwrap_or(Path::new("")).to_path_buf();
+    root = Some(match root {
+      None => parent,
+      Some(current) => current
+        .ancestors()
+        .find(|ancestor| parent.starts_with(ancestor))
+        .map(Path::to_path_buf)
+        .unwrap_or_default(),
+    });
+  }
+
+  match root {
+    Some(root) if root.as_os_str().is_empty() && paths.iter().any(|path| path.is_absolute()) => {
+      Err(VideoCompilerError::validation(
+        "Медиафайлы проекта не имеют общей директории (разные диски)",
+      ))
+    }
+    root => Ok(root),
+  }
+}
+
+/// Путь относительно `root` с разделителями `/`
+fn portable_relative_path(path: &Path, root: &Path) -> String {
+  path
+    .strip_prefix(root)
+    .unwrap_or(path)
+    .components()
+    .filter_map(|component| match component {
+      Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+      _ => None,
+    })
+    .collect::<Vec<_>>()
+    .join("/")
+}
+
+/// Разрешить относительный путь спецификации от `media_root`.
+///
+/// Пути с `..` или абсолютные отклоняются, чтобы спецификация не ссылалась
+/// на файлы вне `media_root`.
+fn resolve_media_path(media_root: &Path, relative: &str) -> Result<PathBuf> {
+  let mut resolved = media_root.to_path_buf();
+  for part in relative.split('/').filter(|part| !part.is_empty()) {
+    if matches!(part, "." | "..") || Path::new(part).is_absolute() || part.contains('\\') {
+      return Err(VideoCompilerError::validation(format!(
+        "Недопустимый путь медиафайла в спецификации: {relative}"
+      )));
+    }
+    resolved.push(part);
+  }
+  Ok(resolved)
+}
+
+/// Собрать спецификацию рендеринга из проекта и настроек экспорта
+pub async fn build_render_spec(
+  project: &ProjectSchema,
+  export: ExportSettings,
+  ffmpeg_path: &str,
+) -> Result<(RenderSpec, Option<PathBuf>)> {
+  let mut project = project.clone();
+  project.settings.export = export;
+  project.validate().map_err(VideoCompilerError::validation)?;
+
+  let originals: Vec<PathBuf> = media_paths(&project)
+    .into_iter()
+    .map(PathBuf::from)
+    .collect();
+  let root = common_media_root(&originals)?;
+  let root_path = root.clone().unwrap_or_default();
+
+  let mut media = Vec::with_capacity(originals.len());
+  let mut relative = HashMap::new();
+  for original in &originals {
+    let metadata =
+      tokio::fs::metadata(original)
+        .await
+        .map_err(|e| VideoCompilerError::MediaFileError {
+          path: original.to_string_lossy().to_string(),
+          reason: e.to_string(),
+        })?;
+    let path = portable_relative_path(original, &root_path);
+    let original_path = original.to_string_lossy().to_string();
+    relative.insert(original_path.clone(), path.clone());
+    media.push(RenderSpecMedia {
+      path,
+      original_path,
+      size: metadata.len(),
+      sha256: ffmpeg_manager::sha256_file(original).await?,
+    });
+  }
+
+  for_each_media_path_mut(&mut project, |path| {
+    if let Some(relative) = relative.get(path.as_str()) {
+      *path = relative.clone();
+    }
+  });
+
+  let spec = RenderSpec {
+    format_version: RENDER_SPEC_FORMAT_VERSION,
+    created_at: Utc::now(),
+    project,
+    ffmpeg: FfmpegRequirements {
+      exported_with: ffmpeg_manager::probe_version(Path::new(ffmpeg_path)).await,
+      encoders: REQUIRED_ENCODERS
+        .iter()
+        .map(|name| name.to_string())
+        .collect(),
+      filters: REQUIRED_FILTERS
+        .iter()
+        .map(|name| name.to_string())
+        .collect(),
+    },
+    media,
+  };
+  Ok((spec, root))
+}
+
+/// Сохранить спецификацию рендеринга в файл
+pub async fn export_render_spec(
+  project: &ProjectSchema,
+  export: ExportSettings,
+  ffmpeg_path: &str,
+  spec_path: &Path,
+) -> Result<RenderSpecSummary> {
+  let (spec, root) = build_render_spec(project, export, ffmpeg_path).await?;
+
+  if let Some(parent) = spec_path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+    tokio::fs::create_dir_all(parent).await?;
+  }
+  let content = serde_json::to_string_pretty(&spec)
+    .map_err(|e| VideoCompilerError::SerializationError(e.to_string()))?;
+  tokio::fs::write(spec_path, content).await?;
+
+  Ok(RenderSpecSummary {
+    spec_path: spec_path.to_string_lossy().to_string(),
+    media_count: spec.media.len(),
+    total_bytes: spec.media.iter().map(|media| media.size).sum(),
+    source_media_root: root.map(|root| root.to_string_lossy().to_string()),
+  })
+}
+
+/// Разобрать спецификацию, проверив версию формата до разбора остальных полей
+pub fn parse_render_spec(content: &str) -> Result<RenderSpec> {
+  let value: serde_json::Value = serde_json::from_str(content).map_err(|e| {
+    VideoCompilerError::SerializationError(format!("Некорректный файл спецификации: {e}"))
+  })?;
+  let version = value
+    .get("format_version")
+    .and_then(serde_json::Value::as_u64)
+    .ok_or_else(|| {
+      VideoCompilerError::validation("В спецификации рендеринга нет поля format_version")
+    })?;
+  if version != u64::from(RENDER_SPEC_FORMAT_VERSION) {
+    return Err(VideoCompilerError::validation(format!(
+      "Неподдерживаемая версия спецификации рендеринга: {version} \
+       (поддерживается {RENDER_SPEC_FORMAT_VERSION})"
+    )));
+  }
+  serde_json::from_value(value).map_err(|e| {
+    VideoCompilerError::SerializationError(format!("Некорректная спецификация рендеринга: {e}"))
+  })
+}
+
+/// Загрузить спецификацию рендеринга из файла
+pub async fn load_render_spec(spec_path: &Path) -> Result<RenderSpec> {
+  let content = tokio::fs::read_to_string(spec_path).await?;
+  parse_render_spec(&content)
+}
+
+/// Проверить наличие и хеши медиафайлов спецификации в `media_root`
+pub async fn verify_render_media(
+  spec: &RenderSpec,
+  media_root: &Path,
+) -> Result<RenderSpecVerification> {
+  let mut verification = RenderSpecVerification::default();
+  for media in &spec.media {
+    let path = resolve_media_path(media_root, &media.path)?;
+    if !path.is_file() {
+      verification.missing.push(media.path.clone());
+      continue;
+    }
+    let actual = ffmpeg_manager::sha256_file(&path).await?;
+    if !actual.eq_ignore_ascii_case(&media.sha256) {
+      verification.mismatched.push(RenderSpecMismatch {
+        path: media.path.clone(),
+        expected_sha256: media.sha256.clone(),
+        actual_sha256: actual,
+      });
+    }
+  }
+  Ok(verification)
+}
+
+/// Кодировщики и фильтры из требований, которых нет в FFmpeg целевой машины
+pub fn missing_ffmpeg_components(
+  requirements: &FfmpegRequirements,
+  status: &FfmpegStatus,
+) -> Vec<String> {
+  let missing = |required: &[String], available: &std::collections::BTreeMap<String, bool>| {
+    required
+      .iter()
+      .filter(|name| available.get(name.as_str()) == Some(&false))
+      .cloned()
+      .collect::<Vec<_>>()
+  };
+  let mut components = missing(&requirements.encoders, &status.encoders);
+  components.extend(missing(&requirements.filters, &status.filters));
+  components
+}
+
+/// Подготовить проект спецификации к рендерингу на этой машине.
+///
+/// Проверяет FFmpeg и медиафайлы и возвращает схему с абсолютными путями от
+/// `media_root`, готовую для обычного конвейера рендеринга.
+pub async fn prepare_render_spec(
+  spec: RenderSpec,
+  media_root: &Path,
+  ffmpeg: &FfmpegStatus,
+) -> Result<ProjectSchema> {
+  if !ffmpeg.available {
+    return Err(VideoCompilerError::DependencyMissing(
+      format!("FFmpeg не запускается: {}", ffmpeg.path).into(),
+    ));
+  }
+  let missing_components = missing_ffmpeg_components(&spec.ffmpeg, ffmpeg);
+  if !missing_components.is_empty() {
+    return Err(VideoCompilerError::DependencyMissing(
+      format!(
+        "FFmpeg не поддерживает компоненты, нужные задаче: {}",
+        missing_components.join(", ")
+      )
+      .into(),
+    ));
+  }
+
+  let verification = verify_render_media(&spec, media_root).await?;
+  if !verification.is_ok() {
+    let mismatched: Vec<&str> = verification
+      .mismatched
+      .iter()
+      .map(|media| media.path.as_str())
+      .collect();
+    return Err(VideoCompilerError::validation(format!(
+      "Медиафайлы задачи не совпадают с экспортированными. Отсутствуют: [{}]; изменены: [{}]",
+      verification.missing.join(", "),
+      mismatched.join(", ")
+    )));
+  }
+
+  let mut resolved = HashMap::new();
+  for media in &spec.media {
+    let path = resolve_media_path(media_root, &media.path)?;
+    resolved.insert(media.path.clone(), path.to_string_lossy().to_string());
+  }
+
+  let mut project = spec.project;
+  for_each_media_path_mut(&mut project, |path| {
+    if let Some(absolute) = resolved.get(path.as_str()) {
+      *path = absolute.clone();
+    }
+  });
+  Ok(project)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::video_compiler::schema::{Clip, ClipSource, Track, TrackType};
+  use std::collections::BTreeMap;
+  use std::fs;
+  use tempfile::TempDir;
+
+  fn project_with_media(media: &Path) -> ProjectSchema {
+    fs::create_dir_all(media.join("audio")).unwrap();
+    fs::write(media.join("intro.mp4"), "video data").unwrap();
+    fs::write(media.join("audio").join("music.wav"), "audio data").unwrap();
+
+    let mut project = ProjectSchema::new("Spec Test".to_string());
+    let mut video = Track::new(TrackType::Video, "Video".to_string());
+    video
+      .clips
+      .push(Clip::new(media.join("intro.mp4"), 0.0, 5.0));
+    let mut audio = Track::new(TrackType::Audio, "Audio".to_string());
+    audio
+      .clips
+      .push(Clip::new(media.join("audio").join("music.wav"), 0.0, 5.0));
+    project.tracks.extend([video, audio]);
+    project
+  }
+
+  fn ready_ffmpeg() -> FfmpegStatus {
+    FfmpegStatus {
+      path: "ffmpeg".to_string(),
+      source: ffmpeg_manager::FfmpegSource::System,
+      available: true,
+      version: Some("ffmpeg version 6.1".to_string()),
+      encoders: REQUIRED_ENCODERS
+        .iter()
+        .map(|name| (name.to_string(), true))
+        .collect(),
+      filters: REQUIRED_FILTERS
+        .iter()
+        .map(|name| (name.to_string(), true))
+        .collect(),
+    }
+  }
+
+  fn clip_paths(project: &ProjectSchema) -> Vec<String> {
+    project
+      .tracks
+      .iter()
+      .flat_map(|track| &track.clips)
+      .filter_map(|clip| match &clip.source {
+        ClipSource::File(path) => Some(path.clone()),
+        _ => None,
+      })
+      .collect()
+  }
+
+  #[tokio::test]
+  async fn test_render_spec_roundtrip_on_other_media_root() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("workstation").join("Footage");
+    let project = project_with_media(&source);
+    let mut export = project.settings.export.clone();
+    export.crf = Some(18);
+
+    let spec_path = temp_dir.path().join("job").join("render.json");
+    let summary = export_render_spec(&project, export, "ffmpeg", &spec_path)
+      .await
+      .unwrap();
+    assert_eq!(summary.media_count, 2);
+    assert_eq!(summary.total_bytes, 20);
+    assert_eq!(
+      summary.source_media_root.as_deref(),
+      Some(source.to_string_lossy().as_ref())
+    );
+
+    let spec = load_render_spec(&spec_path).await.unwrap();
+    assert_eq!(spec.project.settings.export.crf, Some(18));
+    assert_eq!(
+      clip_paths(&spec.project),
+      vec!["intro.mp4".to_string(), "audio/music.wav".to_string()]
+    );
+
+    // Медиа скопированы на машину рендеринга в другую директорию
+    let farm = temp_dir.path().join("farm");
+    project_with_media(&farm);
+    let project = prepare_render_spec(spec, &farm, &ready_ffmpeg())
+      .await
+      .unwrap();
+    assert_eq!(
+      clip_paths(&project),
+      vec![
+        farm.join("intro.mp4").to_string_lossy().to_string(),
+        farm
+          .join("audio")
+          .join("music.wav")
+          .to_string_lossy()
+          .to_string(),
+      ]
+    );
+  }
+
+  #[tokio::test]
+  async fn test_verify_reports_missing_and_mismatched_media() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("source");
+    let project = project_with_media(&source);
+    let (spec, _) = build_render_spec(&project, project.settings.export.clone(), "ffmpeg")
+      .await
+      .unwrap();
+
+    let farm = temp_dir.path().join("farm");
+    fs::create_dir_all(&farm).unwrap();
+    fs::write(farm.join("intro.mp4"), "re-encoded video").unwrap();
+
+    let verification = verify_render_media(&spec, &farm).await.unwrap();
+    assert!(!verification.is_ok());
+    assert_eq!(verification.missing, vec!["audio/music.wav".to_string()]);
+    assert_eq!(verification.mismatched.len(), 1);
+    assert_eq!(verification.mismatched[0].path, "intro.mp4");
+
+    let error = prepare_render_spec(spec, &farm, &ready_ffmpeg())
+      .await
+      .unwrap_err();
+    assert!(error.to_string().contains("audio/music.wav"));
+  }
+
+  #[test]
+  fn test_unsupported_spec_version_is_rejected() {
+    let error = parse_render_spec(r#"{"format_version": 99, "project": {}}"#).unwrap_err();
+    assert!(error.to_string().contains("99"));
+
+    let error = parse_render_spec(r#"{"project": {}}"#).unwrap_err();
+    assert!(error.to_string().contains("format_version"));
+  }
+
+  #[test]
+  fn test_spec_paths_cannot_escape_media_root() {
+    let root = Path::new("/farm/media");
+    assert_eq!(
+      resolve_media_path(root, "audio/music.wav").unwrap(),
+      root.join("audio").join("music.wav")
+    );
+    assert!(resolve_media_path(root, "../secrets.txt").is_err());
+    assert!(resolve_media_path(root, "audio/../../secrets.txt").is_err());
+  }
+
+  #[test]
+  fn test_missing_ffmpeg_components() {
+    let mut status = ready_ffmpeg();
+    let requirements = FfmpegRequirements {
+      exported_with: None,
+      encoders: vec!["libx264".to_string(), "libx265".to_string()],
+      filters: vec!["xfade".to_string()],
+    };
+    assert!(missing_ffmpeg_components(&requirements, &status).is_empty());
+
+    status.filters = BTreeMap::from([("xfade".to_string(), false)]);
+    assert_eq!(
+      missing_ffmpeg_components(&requirements, &status),
+      vec!["xfade".to_string()]
+    );
+  }
+}
@@ -0,0 +1,461 @@
+//! Журнал изменений схемы проекта для undo/redo
+//!
+//! Backend команды, изменяющие `ProjectSchema`, записывают изменение в журнал
+//! проекта. Изменение хранится как пара структурных патчей (прямой и обратный)
+//! поверх JSON представления схемы, а не как полные снимки. Журнал ограничен по
+//! числу записей и суммарному размеру патчей.
+
+use crate::video_compiler::{
+  error::{Result, VideoCompilerError},
+  schema::ProjectSchema,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+/// Ограничения журнала одного проекта
+#[derive(Debug, Clone)]
+pub struct SchemaJournalConfig {
+  /// Максимальное число записей undo
+  pub max_entries: usize,
+  /// Максимальный суммарный размер патчей undo и redo в байтах
+  pub max_total_bytes: usize,
+}
+
+impl Default for SchemaJournalConfig {
+  fn default() -> Self {
+    Self {
+      max_entries: 100,
+      max_total_bytes: 16 * 1024 * 1024,
+    }
+  }
+}
+
+/// Операция патча: установить значение по JSON pointer или удалить ключ
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PatchOp {
+  /// JSON pointer (RFC 6901), пустая строка - корень
+  pub path: String,
+  /// Новое значение; `None` удаляет ключ объекта
+  pub value: Option<Value>,
+}
+
+/// Структурный патч JSON представления схемы
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SchemaPatch {
+  pub ops: Vec<PatchOp>,
+}
+
+impl SchemaPatch {
+  /// Патч, превращающий `from` в `to`
+  ///
+  /// Объекты сравниваются по ключам, массивы одинаковой длины - поэлементно,
+  /// остальные различия заменяют значение целиком.
+  pub fn diff(from: &Value, to: &Value) -> Self {
+    let mut ops = Vec::new();
+    diff_values(String::new(), from, to, &mut ops);
+    Self { ops }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.ops.is_empty()
+  }
+
+  /// Применить патч к значению
+  pub fn apply(&self, target: &mut Value) -> Result<()> {
+    for op in &self.ops {
+      apply_op(target, op)?;
+    }
+    Ok(())
+  }
+
+  /// Приблизительный размер патча в байтах
+  fn size_bytes(&self) -> usize {
+    serde_json::to_vec(self).map_or(0, |bytes| bytes.len())
+  }
+}
+
+fn escape_pointer_token(token: &str) -> String {
+  token.replace('~', "~0").replace('/', "~1")
+}
+
+fn unescape_pointer_token(token: &str) -> String {
+  token.replace("~1", "/").replace("~0", "~")
+}
+
+fn diff_values(path: String, from: &Value, to: &Value, ops: &mut Vec<PatchOp>) {
+  match (from, to) {
+    (Value::Object(from_map), Value::Object(to_map)) => {
+      for (key, from_value) in from_map {
+        let child = format!("{path}/{}", escape_pointer_token(key));
+        match to_map.get(key) {
+          Some(to_value) => diff_values(child, from_value, to_value, ops),
+          None => ops.push(PatchOp {
+            path: child,
+            value: None,
+          }),
+        }
+      }
+      for (key, to_value) in to_map {
+        if !from_map.contains_key(key) {
+          ops.push(PatchOp {
+            path: format!("{path}/{}", escape_pointer_token(key)),
+            value: Some(to_value.clone()),
+          });
+        }
+      }
+    }
+    (Value::Array(from_items), Value::Array(to_items)) if from_items.len() == to_items.len() => {
+      for (index, (from_item, to_item)) in from_items.iter().zip(to_items).enumerate() {
+        diff_values(format!("{path}/{index}"), from_item, to_item, ops);
+      }
+    }
+    _ if from != to => ops.push(PatchOp {
+      path,
+      value: Some(to.clone()),
+    }),
+    _ => {}
+  }
+}
+
+fn apply_op(target: &mut Value, op: &PatchOp) -> Result<()> {
+  if op.path.is_empty() {
+    *target = op.value.clone().unwrap_or(Value::Null);
+    return Ok(());
+  }
+
+  let invalid_path = || VideoCompilerError::validation(format!("Invalid patch path: {}", op.path));
+  let (parent_path, last) = op.path.rsplit_once('/').ok_or_else(invalid_path)?;
+  let parent = if parent_path.is_empty() {
+    &mut *target
+  } else {
+    target.pointer_mut(parent_path).ok_or_else(invalid_path)?
+  };
+  let key = unescape_pointer_token(last);
+
+  match (parent, &op.value) {
+    (Value::Object(map), Some(value)) => {
+      map.insert(key, value.clone());
+    }
+    (Value::Object(map), None) => {
+      map.remove(&key);
+    }
+    (Value::Array(items), Some(value)) => {
+      let index: usize = key.parse().map_err(|_| invalid_path())?;
+      let item = items.get_mut(index).ok_or_else(invalid_path)?;
+      *item = value.clone();
+    }
+    _ => return Err(invalid_path()),
+  }
+  Ok(())
+}
+
+/// Изменение, записанное в журнал (возвращается вместе с новой схемой)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaChange {
+  pub id: String,
+  /// Название операции для UI ("Apply export preset", "Import subtitles", ...)
+  pub label: String,
+  /// Патч, возвращающий схему к состоянию до изменения
+  pub inverse: SchemaPatch,
+}
+
+/// Состояние журнала проекта для UI
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SchemaJournalState {
+  /// Названия операций, доступных для undo (последняя - в конце)
+  pub undo_labels: Vec<String>,
+  /// Названия операций, доступных для redo (следующая - в конце)
+  pub redo_labels: Vec<String>,
+}
+
+struct JournalEntry {
+  id: String,
+  label: String,
+  forward: SchemaPatch,
+  inverse: SchemaPatch,
+  bytes: usize,
+}
+
+#[derive(Default)]
+struct ProjectJournal {
+  /// Схема после последнего примененного изменения
+  current: Value,
+  undo: VecDeque<JournalEntry>,
+  redo: Vec<JournalEntry>,
+  bytes: usize,
+}
+
+impl ProjectJournal {
+  fn clear_redo(&mut self) {
+    for entry in self.redo.drain(..) {
+      self.bytes -= entry.bytes;
+    }
+  }
+
+  /// Удалить старейшие записи, выходящие за ограничения
+  fn enforce_limits(&mut self, config: &SchemaJournalConfig) {
+    while self.undo.len() > config.max_entries
+      || (self.bytes > config.max_total_bytes && !self.undo.is_empty())
+    {
+      if let Some(entry) = self.undo.pop_front() {
+        self.bytes -= entry.bytes;
+      }
+    }
+  }
+}
+
+/// Журнал изменений схем по ID проекта
+pub struct SchemaJournal {
+  config: SchemaJournalConfig,
+  projects: RwLock<HashMap<String, ProjectJournal>>,
+}
+
+impl Default for SchemaJournal {
+  fn default() -> Self {
+    Self::new(SchemaJournalConfig::default())
+  }
+}
+
+impl SchemaJournal {
+  pub fn new(config: SchemaJournalConfig) -> Self {
+    Self {
+      config,
+      projects: RwLock::new(HashMap::new()),
+    }
+  }
+
+  /// Записать изменение схемы проекта
+  ///
+  /// Возвращает `None`, если схема не изменилась. Новое изменение очищает redo.
+  pub async fn record(
+    &self,
+    project_id: &str,
+    label: &str,
+    before: &ProjectSchema,
+    after: &ProjectSchema,
+  ) -> Result<Option<SchemaChange>> {
+    let before = serde_json::to_value(before)?;
+    let after = serde_json::to_value(after)?;
+
+    let forward = SchemaPatch::diff(&before, &after);
+    if forward.is_empty() {
+      return Ok(None);
+    }
+    let inverse = SchemaPatch::diff(&after, &before);
+    let entry = JournalEntry {
+      id: Uuid::new_v4().to_string(),
+      label: label.to_string(),
+      bytes: forward.size_bytes() + inverse.size_bytes(),
+      forward,
+      inverse,
+    };
+    let change = SchemaChange {
+      id: entry.id.clone(),
+      label: entry.label.clone(),
+      inverse: entry.inverse.clone(),
+    };
+
+    let mut projects = self.projects.write().await;
+    let journal = projects.entry(project_id.to_string()).or_default();
+    journal.clear_redo();
+    journal.current = after;
+    journal.bytes += entry.bytes;
+    journal.undo.push_back(entry);
+    journal.enforce_limits(&self.config);
+
+    Ok(Some(change))
+  }
+
+  /// Отменить последнее изменение, возвращает схему для применения
+  pub async fn undo(&self, project_id: &str) -> Result<Option<ProjectSchema>> {
+    let mut projects = self.projects.write().await;
+    let Some(journal) = projects.get_mut(project_id) else {
+      return Ok(None);
+    };
+    let Some(entry) = journal.undo.pop_back() else {
+      return Ok(None);
+    };
+
+    match Self::step(&mut journal.current, &entry.inverse) {
+      Ok(schema) => {
+        journal.redo.push(entry);
+        Ok(Some(schema))
+      }
+      Err(e) => {
+        journal.undo.push_back(entry);
+        Err(e)
+      }
+    }
+  }
+
+  /// Повторить отмененное изменение, возвращает схему для применения
+  pub async fn redo(&self, project_id: &str) -> Result<Option<ProjectSchema>> {
+    let mut projects = self.projects.write().await;
+    let Some(journal) = projects.get_mut(project_id) else {
+      return Ok(None);
+    };
+    let Some(entry) = journal.redo.pop() else {
+      return Ok(None);
+    };
+
+    match Self::step(&mut journal.current, &entry.forward) {
+      Ok(schema) => {
+        journal.undo.push_back(entry);
+        Ok(Some(schema))
+      }
+      Err(e) => {
+        journal.redo.push(entry);
+        Err(e)
+      }
+    }
+  }
+
+  /// Применить патч к текущей схеме журнала; при ошибке схема не меняется
+  fn step(current: &mut Value, patch: &SchemaPatch) -> Result<ProjectSchema> {
+    let mut next = current.clone();
+    patch.apply(&mut next)?;
+    let schema: ProjectSchema = serde_json::from_value(next.clone())?;
+    *current = next;
+    Ok(schema)
+  }
+
+  /// Доступные операции undo/redo проекта
+  pub async fn state(&self, project_id: &str) -> SchemaJournalState {
+    let projects = self.projects.read().await;
+    projects
+      .get(project_id)
+      .map(|journal| SchemaJournalState {
+        undo_labels: journal.undo.iter().map(|e| e.label.clone()).collect(),
+        redo_labels: journal.redo.iter().map(|e| e.label.clone()).collect(),
+      })
+      .unwrap_or_default()
+  }
+
+  /// Суммарный размер патчей журнала проекта
+  pub async fn total_bytes(&self, project_id: &str) -> usize {
+    let projects = self.projects.read().await;
+    projects.get(project_id).map_or(0, |journal| journal.bytes)
+  }
+
+  /// Удалить журнал проекта (при закрытии проекта)
+  pub async fn clear(&self, project_id: &str) {
+    self.projects.write().await.remove(project_id);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  #[test]
+  fn test_patch_roundtrip() {
+    let from = json!({"a": 1, "b": {"c": [1, 2, 3], "d/e": "x"}, "removed": true});
+    let to = json!({"a": 2, "b": {"c": [1, 5, 3], "d/e": "y"}, "list": [1]});
+
+    let forward = SchemaPatch::diff(&from, &to);
+    let inverse = SchemaPatch::diff(&to, &from);
+
+    let mut value = from.clone();
+    forward.apply(&mut value).unwrap();
+    assert_eq!(value, to);
+    inverse.apply(&mut value).unwrap();
+    assert_eq!(value, from);
+  }
+
+  #[test]
+  fn test_patch_replaces_resized_arrays() {
+    let from = json!({"items": [1, 2]});
+    let to = json!({"items": [1, 2, 3]});
+
+    let patch = SchemaPatch::diff(&from, &to);
+    assert_eq!(patch.ops.len(), 1);
+    assert_eq!(patch.ops[0].path, "/items");
+  }
+
+  #[test]
+  fn test_apply_rejects_invalid_path() {
+    let patch = SchemaPatch {
+      ops: vec![PatchOp {
+        path: "/missing/key".to_string(),
+        value: Some(json!(1)),
+      }],
+    };
+    assert!(patch.apply(&mut json!({})).is_err());
+  }
+
+  #[tokio::test]
+  async fn test_record_ignores_unchanged_schema() {
+    let journal = SchemaJournal::default();
+    let project = ProjectSchema::new("Test".to_string());
+
+    let change = journal
+      .record("p1", "Noop", &project, &project)
+      .await
+      .unwrap();
+    assert!(change.is_none());
+    assert!(journal.undo("p1").await.unwrap().is_none());
+  }
+
+  #[tokio::test]
+  async fn test_journal_caps_entries_and_bytes() {
+    let journal = SchemaJournal::new(SchemaJournalConfig {
+      max_entries: 2,
+      max_total_bytes: usize::MAX,
+    });
+    let mut project = ProjectSchema::new("Test".to_string());
+    for quality in [10, 20, 30] {
+      let before = project.clone();
+      project.settings.export.quality = quality;
+      journal
+        .record("p1", &format!("quality {quality}"), &before, &project)
+        .await
+        .unwrap();
+    }
+    assert_eq!(
+      journal.state("p1").await.undo_labels,
+      vec!["quality 20", "quality 30"]
+    );
+
+    let tiny = SchemaJournal::new(SchemaJournalConfig {
+      max_entries: 100,
+      max_total_bytes: 1,
+    });
+    let before = project.clone();
+    project.settings.export.quality = 40;
+    tiny
+      .record("p1", "quality", &before, &project)
+      .await
+      .unwrap();
+    assert!(tiny.state("p1").await.undo_labels.is_empty());
+    assert_eq!(tiny.total_bytes("p1").await, 0);
+  }
+
+  #[tokio::test]
+  async fn test_new_change_clears_redo_and_close_clears_journal() {
+    let journal = SchemaJournal::default();
+    let original = ProjectSchema::new("Test".to_string());
+    let mut edited = original.clone();
+    edited.settings.export.quality = 50;
+
+    journal
+      .record("p1", "first", &original, &edited)
+      .await
+      .unwrap();
+    journal.undo("p1").await.unwrap();
+    assert_eq!(journal.state("p1").await.redo_labels, vec!["first"]);
+
+    journal
+      .record("p1", "second", &original, &edited)
+      .await
+      .unwrap();
+    assert!(journal.state("p1").await.redo_labels.is_empty());
+
+    journal.clear("p1").await;
+    assert!(journal.undo("p1").await.unwrap().is_none());
+    assert_eq!(journal.total_bytes("p1").await, 0);
+  }
+}
@@ -0,0 +1,518 @@
+//! Индекс точек привязки timeline
+//!
+//! Для больших проектов поиск точек привязки при перетаскивании клипа
+//! выполняется на стороне Rust: [`SnapIndex`] хранит отсортированный список
+//! уникальных времен (границы клипов, маркеры, главы, биты музыки), а запрос
+//! ближайшей точки выполняется бинарным поиском. Индексы кэшируются в
+//! [`SnapIndexCache`] по ревизии проекта и перестраиваются только после ее
+//! изменения.
+
+use crate::video_compiler::schema::{ClipSource, MarkerType, ProjectSchema};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Времена ближе этого порога (секунды) считаются одной точкой привязки
+pub const SNAP_TIME_EPSILON: f64 = 1e-6;
+
+/// Сколько индексов (ревизий) держать в кэше
+pub const SNAP_INDEX_CACHE_CAPACITY: usize = 8;
+
+/// Источник точки привязки (в порядке приоритета)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapPointKind {
+  /// Маркер главы
+  Chapter,
+  /// Остальные маркеры
+  Marker,
+  /// Начало клипа
+  ClipStart,
+  /// Конец клипа
+  ClipEnd,
+  /// Бит музыки
+  Beat,
+  /// Текущее положение playhead (не хранится в индексе)
+  Playhead,
+}
+
+/// Точка привязки с типами всех совпавших в ней источников
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapPoint {
+  /// Позиция на timeline в секундах
+  pub time: f64,
+  /// Источники точки, отсортированы по приоритету
+  pub kinds: Vec<SnapPointKind>,
+}
+
+/// Какие точки включать в индекс
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SnapIndexOptions {
+  /// Начала и концы клипов
+  pub include_clip_edges: bool,
+  /// Маркеры timeline (кроме глав)
+  pub include_markers: bool,
+  /// Маркеры глав
+  pub include_chapter_points: bool,
+  /// Путь к аудиофайлу, биты которого переносятся на клипы из этого файла
+  pub include_beats: Option<String>,
+}
+
+impl Default for SnapIndexOptions {
+  fn default() -> Self {
+    Self {
+      include_clip_edges: true,
+      include_markers: true,
+      include_chapter_points: true,
+      include_beats: None,
+    }
+  }
+}
+
+/// Запрос ближайшей точки привязки
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapQuery {
+  /// Время, для которого ищется привязка (секунды)
+  pub time: f64,
+  /// Максимальное расстояние до точки (секунды)
+  pub tolerance: f64,
+  /// Учитывать только точки этих типов (`None` - все)
+  #[serde(default)]
+  pub kinds: Option<Vec<SnapPointKind>>,
+  /// Текущее положение playhead
+  #[serde(default)]
+  pub playhead: Option<f64>,
+}
+
+impl SnapQuery {
+  fn accepts(&self, kind: SnapPointKind) -> bool {
+    self
+      .kinds
+      .as_ref()
+      .is_none_or(|kinds| kinds.contains(&kind))
+  }
+}
+
+/// Найденная точка привязки
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SnapMatch {
+  /// Позиция точки на timeline
+  pub time: f64,
+  /// Тип точки с наивысшим приоритетом среди подходящих под фильтр
+  pub kind: SnapPointKind,
+  /// Расстояние от запрошенного времени
+  pub distance: f64,
+}
+
+/// Отсортированный индекс точек привязки одной ревизии проекта
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapIndex {
+  /// Ревизия, для которой построен индекс
+  pub index_id: String,
+  /// Точки по возрастанию времени, без дубликатов
+  pub points: Vec<SnapPoint>,
+}
+
+impl SnapIndex {
+  /// Построить индекс
+  ///
+  /// `beats` - времена битов внутри файла `options.include_beats`; каждый бит
+  /// переносится на timeline через клипы этого файла с учетом обрезки и
+  /// скорости.
+  pub fn build(project: &ProjectSchema, options: &SnapIndexOptions, beats: &[f64]) -> Self {
+    let mut raw = Vec::new();
+
+    if options.include_clip_edges {
+      for clip in project.tracks.iter().flat_map(|track| &track.clips) {
+        raw.push((clip.start_time, SnapPointKind::ClipStart));
+        raw.push((clip.end_time, SnapPointKind::ClipEnd));
+      }
+    }
+
+    for marker in &project.timeline.markers {
+      if marker.marker_type == MarkerType::Chapter {
+        if options.include_chapter_points {
+          raw.push((marker.time, SnapPointKind::Chapter));
+        }
+      } else if options.include_markers {
+        raw.push((marker.time, SnapPointKind::Marker));
+      }
+    }
+
+    if let Some(beats_file) = &options.include_beats {
+      raw.extend(
+        beat_times_on_timeline(project, Path::new(beats_file), beats)
+          .map(|time| (time, SnapPointKind::Beat)),
+      );
+    }
+
+    raw.retain(|(time, _)| time.is_finite());
+    raw.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut points: Vec<SnapPoint> = Vec::new();
+    for (time, kind) in raw {
+      match points.last_mut() {
+        Some(last) if time - last.time < SNAP_TIME_EPSILON => {
+          if !last.kinds.contains(&kind) {
+            last.kinds.push(kind);
+            last.kinds.sort();
+          }
+        }
+        _ => points.push(SnapPoint {
+          time,
+          kinds: vec![kind],
+        }),
+      }
+    }
+
+    Self {
+      index_id: snap_revision(project, options, beats),
+      points,
+    }
+  }
+
+  /// Ближайшая точка в пределах допуска
+  ///
+  /// При равном расстоянии выбирается более ранняя точка. Playhead из запроса
+  /// участвует наравне с точками индекса.
+  pub fn query(&self, query: &SnapQuery) -> Option<SnapMatch> {
+    let tolerance = query.tolerance.max(0.0);
+    let matching = |point: &SnapPoint| {
+      point
+        .kinds
+        .iter()
+        .copied()
+        .find(|kind| query.accepts(*kind))
+        .map(|kind| SnapMatch {
+          time: point.time,
+          kind,
+          distance: (point.time - query.time).abs(),
+        })
+    };
+
+    let split = self.points.partition_point(|point| point.time < query.time);
+    let before = self.points[..split]
+      .iter()
+      .rev()
+      .take_while(|point| query.time - point.time <= tolerance)
+      .find_map(matching);
+    let after = self.points[split..]
+      .iter()
+      .take_while(|point| point.time - query.time <= tolerance)
+      .find_map(matching);
+    let playhead = query
+      .playhead
+      .filter(|_| query.accepts(SnapPointKind::Playhead))
+      .map(|time| SnapMatch {
+        time,
+        kind: SnapPointKind::Playhead,
+        distance: (time - query.time).abs(),
+      })
+      .filter(|candidate| candidate.distance <= tolerance);
+
+    [before, after, playhead]
+      .into_iter()
+      .flatten()
+      .min_by(|a, b| {
+        a.distance
+          .total_cmp(&b.distance)
+          .then(a.time.total_cmp(&b.time))
+      })
+  }
+}
+
+/// Ревизия индекса: меняется при изменении клипов, маркеров, опций или битов
+pub fn snap_revision(project: &ProjectSchema, options: &SnapIndexOptions, beats: &[f64]) -> String {
+  let content = serde_json::to_vec(&(&project.tracks, &project.timeline.markers, options, beats))
+    .unwrap_or_default();
+  format!("{:016x}", xxhash_rust::xxh3::xxh3_64(&content))
+}
+
+/// Перенести биты файла на timeline через клипы, использующие этот файл
+fn beat_times_on_timeline<'a>(
+  project: &'a ProjectSchema,
+  beats_file: &'a Path,
+  beats: &'a [f64],
+) -> impl Iterator<Item = f64> + 'a {
+  project
+    .tracks
+    .iter()
+    .flat_map(|track| &track.clips)
+    .filter(
+      move |clip| matches!(&clip.source, ClipSource::File(path) if Path::new(path) == beats_file),
+    )
+    .flat_map(move |clip| {
+      let speed = if clip.speed > 0.0 { clip.speed } else { 1.0 };
+      beats
+        .iter()
+        .filter(move |beat| **beat >= clip.source_start && **beat <= clip.source_end)
+        .map(move |beat| clip.start_time + (beat - clip.source_start) / speed)
+        .filter(move |time| *time <= clip.end_time + SNAP_TIME_EPSILON)
+    })
+}
+
+#[derive(Debug, Default)]
+struct SnapIndexEntries {
+  indexes: HashMap<String, Arc<SnapIndex>>,
+  /// Ревизии в порядке последнего использования (последняя - самая свежая)
+  order: VecDeque<String>,
+}
+
+impl SnapIndexEntries {
+  fn touch(&mut self, index_id: &str) {
+    if let Some(position) = self.order.iter().position(|id| id == index_id) {
+      let id = self.order.remove(position).unwrap_or_default();
+      self.order.push_back(id);
+    }
+  }
+}
+
+/// Кэш индексов привязки по ревизии проекта
+#[derive(Debug, Default)]
+pub struct SnapIndexCache {
+  entries: Mutex<SnapIndexEntries>,
+  builds: AtomicU64,
+  hits: AtomicU64,
+}
+
+impl SnapIndexCache {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Индекс текущей ревизии проекта; строится только если ревизия новая
+  pub fn get_or_build(
+    &self,
+    project: &ProjectSchema,
+    options: &SnapIndexOptions,
+    beats: &[f64],
+  ) -> Arc<SnapIndex> {
+    let index_id = snap_revision(project, options, beats);
+    if let Some(index) = self.get(&index_id) {
+      return index;
+    }
+
+    let index = Arc::new(SnapIndex::build(project, options, beats));
+    self.builds.fetch_add(1, Ordering::Relaxed);
+
+    let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+    if entries
+      .indexes
+      .insert(index_id.clone(), index.clone())
+      .is_none()
+    {
+      entries.order.push_back(index_id);
+    }
+    while entries.order.len() > SNAP_INDEX_CACHE_CAPACITY {
+      if let Some(evicted) = entries.order.pop_front() {
+        entries.indexes.remove(&evicted);
+      }
+    }
+    index
+  }
+
+  /// Индекс по ревизии, если он еще в кэше
+  pub fn get(&self, index_id: &str) -> Option<Arc<SnapIndex>> {
+    let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+    let index = entries.indexes.get(index_id).cloned()?;
+    entries.touch(index_id);
+    self.hits.fetch_add(1, Ordering::Relaxed);
+    Some(index)
+  }
+
+  /// Сколько раз индекс строился заново
+  pub fn builds(&self) -> u64 {
+    self.builds.load(Ordering::Relaxed)
+  }
+
+  /// Сколько обращений обслужено из кэша
+  pub fn hits(&self) -> u64 {
+    self.hits.load(Ordering::Relaxed)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::video_compiler::schema::{Clip, TimelineMarker, Track, TrackType};
+  use std::path::PathBuf;
+
+  const MUSIC: &str = "/media/music.mp3";
+
+  /// Проект из 1000 клипов на двух треках со смещенными границами
+  fn large_project() -> ProjectSchema {
+    let mut project = ProjectSchema::new("snap".to_string());
+    let mut video = Track::new(TrackType::Video, "Video".to_string());
+    let mut audio = Track::new(TrackType::Audio, "Audio".to_string());
+    for i in 0..500 {
+      let start = i as f64 * 2.0;
+      video.clips.push(Clip::new(
+        PathBuf::from(format!("/media/clip_{i}.mp4")),
+        start,
+        1.5,
+      ));
+      let mut music = Clip::new(PathBuf::from(MUSIC), start + 0.25, 2.0);
+      music.source_start = 10.0;
+      music.source_end = 12.0;
+      audio.clips.push(music);
+    }
+    project.tracks = vec![video, audio];
+    project.timeline.markers = vec![
+      marker("m1", 100.1, MarkerType::Standard),
+      marker("c1", 300.7, MarkerType::Chapter),
+      marker("dup", 2.0, MarkerType::Note),
+    ];
+    project
+  }
+
+  fn marker(id: &str, time: f64, marker_type: MarkerType) -> TimelineMarker {
+    TimelineMarker {
+      id: id.to_string(),
+      name: id.to_string(),
+      time,
+      marker_type,
+      color: None,
+      description: None,
+    }
+  }
+
+  fn query(time: f64, tolerance: f64) -> SnapQuery {
+    SnapQuery {
+      time,
+      tolerance,
+      kinds: None,
+      playhead: None,
+    }
+  }
+
+  /// Эталон: полный перебор всех точек
+  fn brute_force(index: &SnapIndex, query: &SnapQuery) -> Option<f64> {
+    index
+      .points
+      .iter()
+      .filter(|point| point.kinds.iter().any(|kind| query.accepts(*kind)))
+      .map(|point| point.time)
+      .filter(|time| (time - query.time).abs() <= query.tolerance)
+      .min_by(|a, b| {
+        (a - query.time)
+          .abs()
+          .total_cmp(&(b - query.time).abs())
+          .then(a.total_cmp(b))
+      })
+  }
+
+  #[test]
+  fn test_index_is_sorted_and_deduplicated() {
+    let project = large_project();
+    let index = SnapIndex::build(&project, &SnapIndexOptions::default(), &[]);
+
+    assert!(index
+      .points
+      .windows(2)
+      .all(|pair| pair[1].time - pair[0].time >= SNAP_TIME_EPSILON));
+    // Маркер в 2.0 совпадает с началом второго видеоклипа
+    let shared = index.points.iter().find(|p| p.time == 2.0).unwrap();
+    assert_eq!(
+      shared.kinds,
+      vec![SnapPointKind::Marker, SnapPointKind::ClipStart]
+    );
+    // 1000 клипов x 2 границы, часть совпадает, плюс маркеры
+    assert!(index.points.len() > 1000);
+    assert!(index
+      .points
+      .iter()
+      .any(|p| p.kinds == vec![SnapPointKind::Chapter]));
+  }
+
+  #[test]
+  fn test_query_matches_brute_force() {
+    let project = large_project();
+    let index = SnapIndex::build(&project, &SnapIndexOptions::default(), &[]);
+
+    for step in 0..4000 {
+      let q = query(step as f64 * 0.2537, 0.3);
+      let expected = brute_force(&index, &q);
+      assert_eq!(index.query(&q).map(|m| m.time), expected, "time {}", q.time);
+    }
+  }
+
+  #[test]
+  fn test_query_filter_and_playhead() {
+    let project = large_project();
+    let index = SnapIndex::build(&project, &SnapIndexOptions::default(), &[]);
+
+    let mut q = query(300.5, 0.5);
+    q.kinds = Some(vec![SnapPointKind::Chapter]);
+    let found = index.query(&q).unwrap();
+    assert_eq!(found.kind, SnapPointKind::Chapter);
+    assert_eq!(found.time, 300.7);
+
+    q.kinds = None;
+    q.playhead = Some(300.45);
+    let found = index.query(&q).unwrap();
+    assert_eq!(found.kind, SnapPointKind::Playhead);
+
+    // Вне допуска ничего не находится
+    assert!(index.query(&query(100.6, 0.1)).is_none());
+  }
+
+  #[test]
+  fn test_beats_mapped_through_music_clips() {
+    let project = large_project();
+    let options = SnapIndexOptions {
+      include_clip_edges: false,
+      include_markers: false,
+      include_chapter_points: false,
+      include_beats: Some(MUSIC.to_string()),
+    };
+    // Бит 11.0 в файле - через секунду после source_start каждого клипа,
+    // бит 5.0 лежит вне обрезки
+    let index = SnapIndex::build(&project, &options, &[5.0, 11.0]);
+
+    assert_eq!(index.points.len(), 500);
+    assert_eq!(index.points[0].time, 1.25);
+    assert!(index
+      .points
+      .iter()
+      .all(|p| p.kinds == vec![SnapPointKind::Beat]));
+  }
+
+  #[test]
+  fn test_cache_reuses_index_until_project_changes() {
+    let mut project = large_project();
+    let options = SnapIndexOptions::default();
+    let cache = SnapIndexCache::new();
+
+    let first = cache.get_or_build(&project, &options, &[]);
+    for _ in 0..10 {
+      let again = cache.get_or_build(&project, &options, &[]);
+      assert!(Arc::ptr_eq(&first, &again));
+      assert!(cache.get(&first.index_id).is_some());
+    }
+    assert_eq!(cache.builds(), 1);
+    assert_eq!(cache.hits(), 20);
+
+    project.tracks[0].clips[0].end_time = 1.75;
+    let rebuilt = cache.get_or_build(&project, &options, &[]);
+    assert_ne!(rebuilt.index_id, first.index_id);
+    assert_eq!(cache.builds(), 2);
+    assert!(rebuilt.points.iter().any(|p| p.time == 1.75));
+  }
+
+  #[test]
+  fn test_cache_evicts_oldest_revisions() {
+    let mut project = large_project();
+    let options = SnapIndexOptions::default();
+    let cache = SnapIndexCache::new();
+
+    let first = cache.get_or_build(&project, &options, &[]);
+    for i in 0..SNAP_INDEX_CACHE_CAPACITY {
+      project.timeline.markers[0].time = 50.0 + i as f64;
+      cache.get_or_build(&project, &options, &[]);
+    }
+    assert!(cache.get(&first.index_id).is_none());
+  }
+}
@@ -0,0 +1,208 @@
+//! Stale Artifacts - разросшийся кэш и брошенные временные файлы
+//!
+//! Общая логика проверок состояния кэша и временной директории и команды
+//! очистки, которую интерфейс предлагает, когда проверка сообщает о
+//! деградации.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::video_compiler::core::cache::DiskPreviewCache;
+use crate::video_compiler::core::temp_storage::{
+  cleanup_orphaned_job_dirs, find_orphaned_job_dirs, STALE_JOB_MAX_AGE,
+};
+use crate::video_compiler::error::Result;
+use crate::video_compiler::services::cache_service::{CacheService, PREVIEW_CACHE_DIR};
+
+/// Во сколько раз размер кэша может превысить лимит до деградации
+pub const CACHE_LIMIT_TOLERANCE: f64 = 1.2;
+
+/// Количество брошенных директорий задач, после которого временная
+/// директория считается деградировавшей
+pub const ORPHANED_TEMP_DIRS_THRESHOLD: usize = 20;
+
+/// Возраст файлов кэша, удаляемых при очистке разросшегося кэша
+pub const STALE_CACHE_MAX_AGE_DAYS: u32 = 7;
+
+/// Имя команды очистки для данных проверок состояния
+pub const REMEDIATION_COMMAND: &str = "clean_stale_artifacts";
+
+/// Текущее использование кэша
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheUsage {
+  /// Размер директории кэша в байтах
+  pub size_bytes: u64,
+  /// Настроенный лимит в байтах
+  pub limit_bytes: u64,
+  /// Описание проблемы с индексом дискового кэша превью
+  pub index_error: Option<String>,
+}
+
+impl CacheUsage {
+  /// Размер превышает лимит больше чем на допуск
+  pub fn exceeds_tolerance(&self) -> bool {
+    self.size_bytes as f64 > self.limit_bytes as f64 * CACHE_LIMIT_TOLERANCE
+  }
+
+  /// Процент использования лимита
+  pub fn usage_percent(&self) -> f64 {
+    if self.limit_bytes == 0 {
+      return 0.0;
+    }
+    self.size_bytes as f64 / self.limit_bytes as f64 * 100.0
+  }
+}
+
+/// Результат очистки устаревших файлов
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StaleArtifactsReport {
+  /// Удалено брошенных директорий задач
+  pub removed_temp_dirs: usize,
+  /// Освобождено во временной директории, байт
+  pub freed_temp_bytes: u64,
+  /// Удалено устаревших файлов кэша
+  pub removed_cache_files: usize,
+  /// Размер кэша до очистки, байт
+  pub cache_bytes_before: u64,
+  /// Размер кэша после очистки, байт
+  pub cache_bytes_after: u64,
+  /// Индекс кэша превью восстановлен
+  pub index_repaired: bool,
+  /// Кэш превью очищен полностью, потому что удаления старых файлов не хватило
+  pub preview_cache_cleared: bool,
+}
+
+/// Размер кэша и состояние индекса дискового кэша превью
+pub async fn inspect_cache(cache: &dyn CacheService, limit_bytes: u64) -> Result<CacheUsage> {
+  let cache_path = cache.get_cache_path().await?;
+  let index_error = DiskPreviewCache::check_index_file(&cache_path.join(PREVIEW_CACHE_DIR))
+    .await
+    .err();
+
+  Ok(CacheUsage {
+    size_bytes: cache_size_bytes(cache, &cache_path).await?,
+    limit_bytes,
+    index_error,
+  })
+}
+
+/// Удалить брошенные директории задач и привести кэш к лимиту.
+///
+/// Поврежденный индекс превью восстанавливается сканированием директории.
+/// Если после удаления старых файлов кэш все еще больше лимита, кэш превью
+/// очищается полностью.
+pub async fn clean_stale_artifacts(
+  cache: &dyn CacheService,
+  limit_bytes: u64,
+  temp_root: &Path,
+) -> Result<StaleArtifactsReport> {
+  let mut report = StaleArtifactsReport::default();
+
+  let orphaned = find_orphaned_job_dirs(temp_root, STALE_JOB_MAX_AGE).await?;
+  report.freed_temp_bytes = orphaned.total_bytes;
+  report.removed_temp_dirs = cleanup_orphaned_job_dirs(temp_root, STALE_JOB_MAX_AGE).await?;
+
+  let usage = inspect_cache(cache, limit_bytes).await?;
+  report.cache_bytes_before = usage.size_bytes;
+
+  if usage.index_error.is_some() {
+    let cache_path = cache.get_cache_path().await?;
+    DiskPreviewCache::new(cache_path.join(PREVIEW_CACHE_DIR))
+      .repair_index()
+      .await?;
+    report.index_repaired = true;
+  }
+
+  let cache_path = cache.get_cache_path().await?;
+  if usage.size_bytes > limit_bytes {
+    report.removed_cache_files = cache.optimize_cache(STALE_CACHE_MAX_AGE_DAYS).await?;
+    if cache_size_bytes(cache, &cache_path).await? > limit_bytes {
+      cache.clear_preview_cache().await?;
+      report.preview_cache_cleared = true;
+    }
+  }
+  report.cache_bytes_after = cache_size_bytes(cache, &cache_path).await?;
+
+  log::info!(
+    "Очистка устаревших файлов: {} директорий задач, {} файлов кэша, кэш {} -> {} байт",
+    report.removed_temp_dirs,
+    report.removed_cache_files,
+    report.cache_bytes_before,
+    report.cache_bytes_after
+  );
+  Ok(report)
+}
+
+/// Размер директории кэша в байтах (отсутствующая директория пуста)
+async fn cache_size_bytes(cache: &dyn CacheService, cache_path: &Path) -> Result<u64> {
+  if !cache_path.exists() {
+    return Ok(0);
+  }
+  let size_mb = cache.get_cache_size().await?;
+  Ok((size_mb * 1024.0 * 1024.0).round() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::video_compiler::core::temp_storage::job_dir;
+  use crate::video_compiler::services::CacheServiceImpl;
+  use std::time::{Duration, SystemTime};
+
+  fn make_stale(path: &Path) {
+    let old = SystemTime::now() - Duration::from_secs(2 * 24 * 60 * 60);
+    filetime::set_file_mtime(path, filetime::FileTime::from_system_time(old)).unwrap();
+  }
+
+  #[tokio::test]
+  async fn test_inspect_cache_reports_size_and_index() {
+    let cache_dir = tempfile::tempdir().unwrap();
+    let cache = CacheServiceImpl::new(cache_dir.path().to_path_buf());
+    let preview_dir = cache_dir.path().join(PREVIEW_CACHE_DIR);
+    std::fs::create_dir_all(&preview_dir).unwrap();
+    std::fs::write(preview_dir.join("a.preview"), vec![0u8; 3000]).unwrap();
+
+    let usage = inspect_cache(&cache, 2000).await.unwrap();
+    assert!(usage.exceeds_tolerance());
+    assert!(usage.index_error.is_none());
+    assert!((usage.usage_percent() - 150.0).abs() < 1.0);
+
+    std::fs::write(preview_dir.join("index.json"), b"{ broken").unwrap();
+    let usage = inspect_cache(&cache, 1_000_000).await.unwrap();
+    assert!(!usage.exceeds_tolerance());
+    assert!(usage.index_error.is_some());
+  }
+
+  #[tokio::test]
+  async fn test_clean_stale_artifacts() {
+    let temp_root = tempfile::tempdir().unwrap();
+    let stale = job_dir(temp_root.path(), "stale");
+    let active = job_dir(temp_root.path(), "active");
+    std::fs::create_dir_all(&stale).unwrap();
+    std::fs::create_dir_all(&active).unwrap();
+    std::fs::write(stale.join("part.mp4"), vec![0u8; 500]).unwrap();
+    make_stale(&stale);
+
+    let cache_dir = tempfile::tempdir().unwrap();
+    let cache = CacheServiceImpl::new(cache_dir.path().to_path_buf());
+    let preview_dir = cache_dir.path().join(PREVIEW_CACHE_DIR);
+    std::fs::create_dir_all(&preview_dir).unwrap();
+    std::fs::write(preview_dir.join("a.preview"), vec![0u8; 4000]).unwrap();
+    std::fs::write(preview_dir.join("index.json"), b"{ broken").unwrap();
+
+    let report = clean_stale_artifacts(&cache, 1000, temp_root.path())
+      .await
+      .unwrap();
+    assert_eq!(report.removed_temp_dirs, 1);
+    assert_eq!(report.freed_temp_bytes, 500);
+    assert!(!stale.exists());
+    assert!(active.exists());
+
+    // Свежие превью не устарели, поэтому кэш превью очищается полностью
+    assert!(report.index_repaired);
+    assert!(report.preview_cache_cleared);
+    assert!(report.cache_bytes_before >= 4000);
+    assert!(report.cache_bytes_after < 1000);
+  }
+}
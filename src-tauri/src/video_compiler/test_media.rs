@@ -0,0 +1,403 @@
+//! Генератор синтетических медиафайлов для тестов и воспроизведения багов
+//!
+//! Файлы создаются через источники lavfi FFmpeg (`testsrc`, `smptebars`, `sine`, ...),
+//! поэтому не требуют реальных медиа в репозитории. Запись разрешена только во
+//! временную директорию системы и директорию кэшей приложения.
+
+use crate::video_compiler::error::{Result, VideoCompilerError};
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+use tokio::process::Command;
+
+/// Максимальная длительность генерируемого файла в секундах
+const MAX_DURATION: f64 = 600.0;
+
+/// Максимальное разрешение генерируемого видео
+const MAX_RESOLUTION: (u32, u32) = (7680, 4320);
+
+/// Включены ли dev-команды генерации медиа в этой сборке
+pub fn dev_tools_enabled() -> bool {
+  cfg!(any(debug_assertions, feature = "dev-tools"))
+}
+
+/// Тестовый узор видео (источник lavfi)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TestPattern {
+  /// Цветные полосы со счетчиком кадров
+  #[default]
+  TestSrc,
+  /// Анимированный узор с таймкодом
+  TestSrc2,
+  /// Настроечная таблица SMPTE
+  SmpteBars,
+  /// Однотонный черный кадр
+  Black,
+  /// Фрактал Мандельброта (высокая детализация для проверки кодеков)
+  Mandelbrot,
+}
+
+impl TestPattern {
+  fn lavfi_source(self, resolution: (u32, u32), fps: u32, duration: f64) -> String {
+    let (width, height) = resolution;
+    match self {
+      TestPattern::TestSrc => {
+        format!("testsrc=size={width}x{height}:rate={fps}:duration={duration}")
+      }
+      TestPattern::TestSrc2 => {
+        format!("testsrc2=size={width}x{height}:rate={fps}:duration={duration}")
+      }
+      TestPattern::SmpteBars => {
+        format!("smptebars=size={width}x{height}:rate={fps}:duration={duration}")
+      }
+      TestPattern::Black => {
+        format!("color=c=black:size={width}x{height}:rate={fps}:duration={duration}")
+      }
+      TestPattern::Mandelbrot => {
+        // У mandelbrot нет параметра duration, длительность ограничивается через -t
+        format!("mandelbrot=size={width}x{height}:rate={fps}")
+      }
+    }
+  }
+}
+
+/// Параметры тестового видео
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestVideoSpec {
+  pub duration: f64,
+  pub resolution: (u32, u32),
+  pub fps: u32,
+  #[serde(default)]
+  pub pattern: TestPattern,
+  /// Добавить синусоидальную дорожку 440 Гц
+  #[serde(default)]
+  pub with_audio: bool,
+}
+
+impl Default for TestVideoSpec {
+  fn default() -> Self {
+    Self {
+      duration: 2.0,
+      resolution: (320, 240),
+      fps: 25,
+      pattern: TestPattern::TestSrc,
+      with_audio: false,
+    }
+  }
+}
+
+impl TestVideoSpec {
+  fn validate(&self) -> Result<()> {
+    validate_duration(self.duration)?;
+    validate_resolution(self.resolution)?;
+    if !(1..=240).contains(&self.fps) {
+      return Err(VideoCompilerError::InvalidParameter(format!(
+        "fps должен быть в диапазоне 1..=240, получено {}",
+        self.fps
+      )));
+    }
+    Ok(())
+  }
+}
+
+fn validate_duration(duration: f64) -> Result<()> {
+  if !(duration > 0.0 && duration <= MAX_DURATION) {
+    return Err(VideoCompilerError::InvalidParameter(format!(
+      "Длительность должна быть в диапазоне (0, {MAX_DURATION}] секунд, получено {duration}"
+    )));
+  }
+  Ok(())
+}
+
+fn validate_resolution((width, height): (u32, u32)) -> Result<()> {
+  // Большинство кодеков требуют четных размеров кадра
+  if width == 0
+    || height == 0
+    || width > MAX_RESOLUTION.0
+    || height > MAX_RESOLUTION.1
+    || width % 2 != 0
+    || height % 2 != 0
+  {
+    return Err(VideoCompilerError::InvalidParameter(format!(
+      "Недопустимое разрешение {width}x{height}"
+    )));
+  }
+  Ok(())
+}
+
+/// Аргументы FFmpeg для генерации тестового видео
+pub fn test_video_args(output: &Path, spec: &TestVideoSpec) -> Vec<String> {
+  let mut args = vec![
+    "-y".to_string(),
+    "-f".to_string(),
+    "lavfi".to_string(),
+    "-i".to_string(),
+    spec
+      .pattern
+      .lavfi_source(spec.resolution, spec.fps, spec.duration),
+  ];
+  if spec.with_audio {
+    args.extend([
+      "-f".to_string(),
+      "lavfi".to_string(),
+      "-i".to_string(),
+      format!(
+        "sine=frequency=440:sample_rate=48000:duration={}",
+        spec.duration
+      ),
+    ]);
+  }
+  args.extend([
+    "-t".to_string(),
+    spec.duration.to_string(),
+    "-pix_fmt".to_string(),
+    "yuv420p".to_string(),
+    output.to_string_lossy().to_string(),
+  ]);
+  args
+}
+
+/// Аргументы FFmpeg для генерации тонового аудио
+pub fn test_audio_args(output: &Path, duration: f64, tone_hz: u32) -> Vec<String> {
+  vec![
+    "-y".to_string(),
+    "-f".to_string(),
+    "lavfi".to_string(),
+    "-i".to_string(),
+    format!("sine=frequency={tone_hz}:sample_rate=48000:duration={duration}"),
+    output.to_string_lossy().to_string(),
+  ]
+}
+
+/// Аргументы FFmpeg для генерации тестового изображения
+pub fn test_image_args(output: &Path, resolution: (u32, u32)) -> Vec<String> {
+  let (width, height) = resolution;
+  vec![
+    "-y".to_string(),
+    "-f".to_string(),
+    "lavfi".to_string(),
+    "-i".to_string(),
+    format!("testsrc=size={width}x{height}:rate=1"),
+    "-frames:v".to_string(),
+    "1".to_string(),
+    output.to_string_lossy().to_string(),
+  ]
+}
+
+/// Директории, в которые разрешена запись тестовых медиа
+pub fn allowed_output_roots() -> Vec<PathBuf> {
+  let mut roots = vec![std::env::temp_dir()];
+  if let Ok(dirs) = crate::app_dirs::AppDirectories::get_or_create() {
+    roots.push(dirs.caches_dir);
+  }
+  roots
+}
+
+/// Канонический путь, разрешающий символические ссылки существующей части пути
+fn resolve_path(path: &Path) -> Result<PathBuf> {
+  let mut existing = path;
+  let mut rest = Vec::new();
+  while !existing.exists() {
+    let (Some(parent), Some(name)) = (existing.parent(), existing.file_name()) else {
+      break;
+    };
+    rest.push(name.to_os_string());
+    existing = parent;
+  }
+
+  let mut resolved = existing
+    .canonicalize()
+    .map_err(|e| VideoCompilerError::InvalidPath(format!("{}: {e}", path.display())))?;
+  for name in rest.into_iter().rev() {
+    resolved.push(name);
+  }
+  Ok(resolved)
+}
+
+/// Проверить, что путь вывода находится внутри одной из разрешенных директорий
+pub fn ensure_output_allowed(path: &Path, allowed_roots: &[PathBuf]) -> Result<PathBuf> {
+  if !path.is_absolute() || path.components().any(|c| c == Component::ParentDir) {
+    return Err(VideoCompilerError::SecurityError(format!(
+      "Путь должен быть абсолютным и без '..': {}",
+      path.display()
+    )));
+  }
+
+  let resolved = resolve_path(path)?;
+  let allowed = allowed_roots.iter().any(|root| {
+    root
+      .canonicalize()
+      .map(|root| resolved.starts_with(root))
+      .unwrap_or(false)
+  });
+  if !allowed {
+    return Err(VideoCompilerError::SecurityError(format!(
+      "Запись тестовых медиа разрешена только во временные директории и кэш приложения: {}",
+      path.display()
+    )));
+  }
+  Ok(resolved)
+}
+
+/// Доступен ли FFmpeg по указанному пути
+pub async fn ffmpeg_available(ffmpeg_path: &str) -> bool {
+  Command::new(ffmpeg_path)
+    .arg("-version")
+    .output()
+    .await
+    .map(|output| output.status.success())
+    .unwrap_or(false)
+}
+
+async fn run_ffmpeg(ffmpeg_path: &str, output: &Path, args: Vec<String>) -> Result<PathBuf> {
+  if let Some(parent) = output.parent() {
+    tokio::fs::create_dir_all(parent)
+      .await
+      .map_err(|e| VideoCompilerError::IoError(e.to_string()))?;
+  }
+
+  let command = format!("{ffmpeg_path} {}", args.join(" "));
+  let result = Command::new(ffmpeg_path)
+    .args(&args)
+    .output()
+    .await
+    .map_err(|e| VideoCompilerError::FFmpegError {
+      exit_code: None,
+      stderr: format!("Ошибка запуска FFmpeg: {e}"),
+      command: command.clone(),
+    })?;
+
+  if !result.status.success() {
+    return Err(VideoCompilerError::FFmpegError {
+      exit_code: result.status.code(),
+      stderr: String::from_utf8_lossy(&result.stderr).to_string(),
+      command,
+    });
+  }
+  Ok(output.to_path_buf())
+}
+
+/// Сгенерировать тестовое видео
+pub async fn generate_test_video(
+  ffmpeg_path: &str,
+  output: &Path,
+  spec: &TestVideoSpec,
+) -> Result<PathBuf> {
+  spec.validate()?;
+  run_ffmpeg(ffmpeg_path, output, test_video_args(output, spec)).await
+}
+
+/// Сгенерировать тоновый аудиофайл
+pub async fn generate_test_audio(
+  ffmpeg_path: &str,
+  output: &Path,
+  duration: f64,
+  tone_hz: u32,
+) -> Result<PathBuf> {
+  validate_duration(duration)?;
+  if !(20..=20_000).contains(&tone_hz) {
+    return Err(VideoCompilerError::InvalidParameter(format!(
+      "Частота тона должна быть в диапазоне 20..=20000 Гц, получено {tone_hz}"
+    )));
+  }
+  run_ffmpeg(
+    ffmpeg_path,
+    output,
+    test_audio_args(output, duration, tone_hz),
+  )
+  .await
+}
+
+/// Сгенерировать тестовое изображение
+pub async fn generate_test_image(
+  ffmpeg_path: &str,
+  output: &Path,
+  resolution: (u32, u32),
+) -> Result<PathBuf> {
+  validate_resolution(resolution)?;
+  run_ffmpeg(ffmpeg_path, output, test_image_args(output, resolution)).await
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tempfile::TempDir;
+
+  #[test]
+  fn test_video_args_include_audio_and_duration() {
+    let spec = TestVideoSpec {
+      duration: 3.0,
+      resolution: (640, 360),
+      fps: 30,
+      pattern: TestPattern::SmpteBars,
+      with_audio: true,
+    };
+    let args = test_video_args(Path::new("/tmp/out.mp4"), &spec);
+
+    assert!(args.contains(&"smptebars=size=640x360:rate=30:duration=3".to_string()));
+    assert!(args.iter().any(|a| a.starts_with("sine=frequency=440")));
+    assert_eq!(args.last().unwrap(), "/tmp/out.mp4");
+  }
+
+  #[test]
+  fn test_spec_validation() {
+    let odd = TestVideoSpec {
+      resolution: (641, 360),
+      ..Default::default()
+    };
+    assert!(odd.validate().is_err());
+
+    let long = TestVideoSpec {
+      duration: MAX_DURATION + 1.0,
+      ..Default::default()
+    };
+    assert!(long.validate().is_err());
+    assert!(TestVideoSpec::default().validate().is_ok());
+  }
+
+  #[test]
+  fn test_output_path_restrictions() {
+    let root = TempDir::new().unwrap();
+    let roots = vec![root.path().to_path_buf()];
+
+    let inside = root.path().join("nested/clip.mp4");
+    assert!(ensure_output_allowed(&inside, &roots).is_ok());
+
+    let escaped = root.path().join("../clip.mp4");
+    assert!(ensure_output_allowed(&escaped, &roots).is_err());
+    assert!(ensure_output_allowed(Path::new("relative.mp4"), &roots).is_err());
+
+    let outside = TempDir::new().unwrap();
+    assert!(ensure_output_allowed(&outside.path().join("clip.mp4"), &roots).is_err());
+  }
+
+  #[tokio::test]
+  async fn test_generate_media_end_to_end() {
+    if !ffmpeg_available("ffmpeg").await {
+      println!("Skipping test_generate_media_end_to_end: FFmpeg not available");
+      return;
+    }
+    let dir = TempDir::new().unwrap();
+
+    let video = generate_test_video(
+      "ffmpeg",
+      &dir.path().join("video.mp4"),
+      &TestVideoSpec {
+        with_audio: true,
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+    let audio = generate_test_audio("ffmpeg", &dir.path().join("tone.wav"), 1.0, 1000)
+      .await
+      .unwrap();
+    let image = generate_test_image("ffmpeg", &dir.path().join("frame.png"), (64, 64))
+      .await
+      .unwrap();
+
+    for path in [video, audio, image] {
+      assert!(std::fs::metadata(&path).unwrap().len() > 0);
+    }
+  }
+}
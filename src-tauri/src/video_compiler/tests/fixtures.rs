@@ -8,9 +8,55 @@ use crate::video_compiler::schema::{
   project::{ProjectMetadata, ProjectSchema},
   timeline::{Clip, Timeline, Track, TrackType},
 };
+use crate::video_compiler::test_media::{
+  ffmpeg_available, generate_test_video, TestPattern, TestVideoSpec,
+};
 use chrono::Utc;
 use std::collections::HashMap;
 use std::path::PathBuf;
+use tokio::sync::OnceCell;
+
+/// Длительность видео фикстур в секундах
+pub const FIXTURE_VIDEO_DURATION: f64 = 6.0;
+
+static GENERATED_VIDEO: OnceCell<Option<PathBuf>> = OnceCell::const_new();
+
+/// Путь видео фикстур (файл создается [`generated_test_video`])
+pub fn fixture_video_path() -> PathBuf {
+  std::env::temp_dir()
+    .join("timeline-studio-test-media")
+    .join(format!("fixture-video-{}.mp4", std::process::id()))
+}
+
+/// Сгенерировать видео фикстур через FFmpeg lavfi (один раз на процесс)
+///
+/// Возвращает `None`, если FFmpeg недоступен; тест в этом случае должен
+/// пропустить проверки, требующие реального файла.
+pub async fn generated_test_video() -> Option<PathBuf> {
+  GENERATED_VIDEO
+    .get_or_init(|| async {
+      if !ffmpeg_available("ffmpeg").await {
+        println!("FFmpeg not available: skipping checks that need generated test media");
+        return None;
+      }
+      let spec = TestVideoSpec {
+        duration: FIXTURE_VIDEO_DURATION,
+        resolution: (320, 240),
+        fps: 25,
+        pattern: TestPattern::TestSrc2,
+        with_audio: true,
+      };
+      match generate_test_video("ffmpeg", &fixture_video_path(), &spec).await {
+        Ok(path) => Some(path),
+        Err(e) => {
+          println!("Failed to generate test media, skipping: {e}");
+          None
+        }
+      }
+    })
+    .await
+    .clone()
+}
 
 /// Создает минимальный валидный проект для тестирования
 pub fn create_minimal_project() -> ProjectSchema {
@@ -29,6 +75,7 @@ pub fn create_minimal_project() -> ProjectSchema {
       resolution: (1920, 1080),
       sample_rate: 48000,
       aspect_ratio: AspectRatio::Ratio16x9,
+      markers: Vec::new(),
     },
     tracks: vec![],
     effects: vec![],
@@ -36,6 +83,7 @@ pub fn create_minimal_project() -> ProjectSchema {
     filters: vec![],
     templates: vec![],
     style_templates: vec![],
+    sequences: Default::default(),
     subtitles: vec![],
     settings: Default::default(),
   }
@@ -46,7 +94,7 @@ pub fn create_project_with_clips() -> ProjectSchema {
   let mut project = create_minimal_project();
 
   // Создаем тестовый клип с правильными полями
-  let test_clip = Clip::new(PathBuf::from("/tmp/test-video.mp4"), 0.0, 5.0);
+  let test_clip = Clip::new(fixture_video_path(), 0.0, 5.0);
 
   // Добавляем видео трек с клипом
   let mut video_track = Track::new(TrackType::Video, "Video Track 1".to_string());
@@ -106,5 +154,6 @@ pub fn create_test_effect(effect_type: EffectType) -> Effect {
     css_filter: None,
     preview_path: None,
     presets: None,
+    keyframes: HashMap::new(),
   }
 }
@@ -9,9 +9,16 @@ use crate::video_compiler::{
     progress::{RenderProgress, RenderStatus},
   },
   error::Result,
+  schema::ProjectSchema,
+  services::{
+    preview_service::{PreviewRequest, PreviewResult as ServicePreviewResult},
+    PreviewService, RenderService, Service, ServiceContainer, ServiceContainerBuilder,
+  },
   CompilerSettings,
 };
+use async_trait::async_trait;
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::RwLock;
@@ -101,11 +108,6 @@ impl MockGpuDetector {
 /// Создание мок состояния для тестов
 #[allow(dead_code)]
 pub async fn create_mock_state() -> crate::video_compiler::commands::state::VideoCompilerState {
-  let settings = Arc::new(RwLock::new(CompilerSettings::default()));
-  let cache_manager = Arc::new(RwLock::new(RenderCache::new()));
-  let active_jobs = Arc::new(RwLock::new(HashMap::new()));
-  let ffmpeg_path = Arc::new(RwLock::new("ffmpeg".to_string()));
-
   // Создаем контейнер сервисов для теста
   let services = crate::video_compiler::services::ServiceContainer::new(
     "ffmpeg".to_string(),
@@ -115,13 +117,198 @@ pub async fn create_mock_state() -> crate::video_compiler::commands::state::Vide
   .await
   .unwrap();
 
+  create_mock_state_with_services(services)
+}
+
+/// Builder контейнера сервисов для тестов с реальными реализациями по умолчанию
+#[allow(dead_code)]
+pub fn mock_services_builder() -> ServiceContainerBuilder {
+  ServiceContainer::builder(
+    "ffmpeg".to_string(),
+    std::env::temp_dir().join("test-timeline-studio"),
+    2,
+  )
+}
+
+/// Создание мок состояния с заданным контейнером сервисов
+#[allow(dead_code)]
+pub fn create_mock_state_with_services(
+  services: ServiceContainer,
+) -> crate::video_compiler::commands::state::VideoCompilerState {
   crate::video_compiler::commands::state::VideoCompilerState {
     services: Arc::new(services),
-    active_jobs,
+    active_jobs: Arc::new(RwLock::new(HashMap::new())),
     active_pipelines: Arc::new(RwLock::new(HashMap::new())),
-    cache_manager,
-    ffmpeg_path,
-    settings,
+    cache_manager: Arc::new(RwLock::new(RenderCache::new())),
+    ffmpeg_path: Arc::new(RwLock::new("ffmpeg".to_string())),
+    settings: Arc::new(RwLock::new(CompilerSettings::default())),
+  }
+}
+
+/// Мок RenderService, запоминающий запущенные задачи
+#[derive(Default)]
+#[allow(dead_code)]
+pub struct MockRenderService {
+  pub started: std::sync::Mutex<Vec<(String, PathBuf)>>,
+  pub cancelled: std::sync::Mutex<Vec<String>>,
+}
+
+#[async_trait]
+impl Service for MockRenderService {
+  async fn initialize(&self) -> Result<()> {
+    Ok(())
+  }
+
+  async fn health_check(&self) -> Result<()> {
+    Ok(())
+  }
+
+  async fn shutdown(&self) -> Result<()> {
+    Ok(())
+  }
+}
+
+#[async_trait]
+impl RenderService for MockRenderService {
+  async fn start_render(&self, project: ProjectSchema, output_path: PathBuf) -> Result<String> {
+    let mut started = self.started.lock().unwrap();
+    started.push((project.metadata.name, output_path));
+    Ok(format!("mock-job-{}", started.len()))
+  }
+
+  async fn get_progress(&self, _job_id: &str) -> Result<Option<RenderProgress>> {
+    Ok(Some(create_mock_render_progress()))
+  }
+
+  async fn cancel_render(&self, job_id: &str) -> Result<bool> {
+    self.cancelled.lock().unwrap().push(job_id.to_string());
+    Ok(true)
+  }
+
+  async fn pause_render(&self, _job_id: &str) -> Result<bool> {
+    Ok(true)
+  }
+
+  async fn resume_render(&self, _job_id: &str) -> Result<bool> {
+    Ok(true)
+  }
+
+  async fn get_active_jobs(&self) -> Result<Vec<String>> {
+    let started = self.started.lock().unwrap();
+    Ok(
+      (1..=started.len())
+        .map(|i| format!("mock-job-{i}"))
+        .collect(),
+    )
+  }
+
+  async fn has_available_slots(&self) -> Result<bool> {
+    Ok(true)
+  }
+}
+
+/// Мок PreviewService, возвращающий фиксированные байты без FFmpeg
+#[derive(Default)]
+#[allow(dead_code)]
+pub struct MockPreviewService {
+  pub requested_timestamps: std::sync::Mutex<Vec<f64>>,
+}
+
+#[allow(dead_code)]
+impl MockPreviewService {
+  pub const FRAME: &'static [u8] = &[0xFF, 0xD8, 0xFF, 0xD9];
+}
+
+#[async_trait]
+impl Service for MockPreviewService {
+  async fn initialize(&self) -> Result<()> {
+    Ok(())
+  }
+
+  async fn health_check(&self) -> Result<()> {
+    Ok(())
+  }
+
+  async fn shutdown(&self) -> Result<()> {
+    Ok(())
+  }
+}
+
+#[async_trait]
+impl PreviewService for MockPreviewService {
+  async fn generate_frame_preview(
+    &self,
+    _video_path: &Path,
+    timestamp: f64,
+    _resolution: Option<(u32, u32)>,
+  ) -> Result<Vec<u8>> {
+    self.requested_timestamps.lock().unwrap().push(timestamp);
+    Ok(Self::FRAME.to_vec())
+  }
+
+  async fn generate_video_thumbnails(
+    &self,
+    _video_path: &Path,
+    count: usize,
+    _resolution: Option<(u32, u32)>,
+  ) -> Result<Vec<Vec<u8>>> {
+    Ok(vec![Self::FRAME.to_vec(); count])
+  }
+
+  async fn generate_storyboard(
+    &self,
+    _project: &ProjectSchema,
+    _columns: u32,
+    _rows: u32,
+    _thumbnail_size: (u32, u32),
+  ) -> Result<Vec<u8>> {
+    Ok(Self::FRAME.to_vec())
+  }
+
+  async fn generate_waveform(
+    &self,
+    _audio_path: &Path,
+    _width: u32,
+    _height: u32,
+    _color: &str,
+  ) -> Result<Vec<u8>> {
+    Ok(Self::FRAME.to_vec())
+  }
+
+  async fn batch_generate_previews(
+    &self,
+    _requests: Vec<PreviewRequest>,
+  ) -> Result<Vec<ServicePreviewResult>> {
+    Ok(Vec::new())
+  }
+
+  async fn get_cached_preview(&self, _key: &str) -> Result<Option<ServicePreviewResult>> {
+    Ok(None)
+  }
+
+  async fn cache_preview(&self, _key: &str, _result: &ServicePreviewResult) -> Result<()> {
+    Ok(())
+  }
+
+  async fn generate_frame(
+    &self,
+    _project: &ProjectSchema,
+    timestamp: f64,
+    _output_path: &str,
+    _options: Option<crate::video_compiler::core::preview::PreviewOptions>,
+  ) -> Result<()> {
+    self.requested_timestamps.lock().unwrap().push(timestamp);
+    Ok(())
+  }
+
+  async fn generate_preview_batch_for_file(
+    &self,
+    _video_path: &Path,
+    timestamps: Vec<f64>,
+    _resolution: Option<(u32, u32)>,
+    _quality: Option<u8>,
+  ) -> Result<Vec<Vec<u8>>> {
+    Ok(vec![Self::FRAME.to_vec(); timestamps.len()])
   }
 }
 
@@ -137,6 +324,8 @@ pub fn create_mock_render_progress() -> RenderProgress {
     estimated_remaining: Some(Duration::from_secs(30)),
     status: RenderStatus::Processing,
     message: Some("Rendering video...".to_string()),
+    renditions: Vec::new(),
+    stages: Vec::new(),
   }
 }
 
@@ -335,8 +335,8 @@ export function TauriMockProvider({ children }: { children: React.ReactNode }) {
               // Для тестов возвращаем null, если не переопределено
               return { path: null }
             case "scan_media_folder":
-              // Возвращаем пустой массив файлов
-              return []
+              // Возвращаем пустой результат сканирования
+              return { new_files: [], known_files: [] }
             case "process_media_files":
               // Возвращаем успешный результат
               return { success: true, processed: 0 }
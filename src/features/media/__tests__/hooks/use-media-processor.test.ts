@@ -1,7 +1,7 @@
 import { act, renderHook, waitFor } from "@testing-library/react"
 import { afterEach, beforeEach, describe, expect, it, vi } from "vitest"
 
-import { type DiscoveredFile, useMediaProcessor } from "@/features/media/hooks/use-media-processor"
+import { type DiscoveredFile, type MediaScanResult, useMediaProcessor } from "@/features/media/hooks/use-media-processor"
 import type { MediaFile } from "@/features/media/types/media"
 
 // Mock Tauri API
@@ -282,7 +282,7 @@ describe("useMediaProcessor", () => {
   describe("scanFolder", () => {
     it("should scan folder successfully", async () => {
       const mockFiles: MediaFile[] = [mockMediaFile]
-      mockInvoke.mockResolvedValue(mockFiles)
+      mockInvoke.mockResolvedValue({ new_files: mockFiles, known_files: [] })
 
       const { result } = renderHook(() => useMediaProcessor())
 
@@ -303,8 +303,8 @@ describe("useMediaProcessor", () => {
     })
 
     it("should set isProcessing state correctly", async () => {
-      let resolveScan: (value: MediaFile[]) => void
-      const scanPromise = new Promise<MediaFile[]>((resolve) => {
+      let resolveScan: (value: MediaScanResult) => void
+      const scanPromise = new Promise<MediaScanResult>((resolve) => {
         resolveScan = resolve
       })
       mockInvoke.mockReturnValue(scanPromise)
@@ -323,7 +323,7 @@ describe("useMediaProcessor", () => {
 
       // Resolve the promise
       await act(async () => {
-        resolveScan!([mockMediaFile])
+        resolveScan!({ new_files: [mockMediaFile], known_files: [] })
       })
 
       // Check isProcessing is back to false
@@ -351,7 +351,7 @@ describe("useMediaProcessor", () => {
   describe("scanFolderWithThumbnails", () => {
     it("should scan folder with thumbnails successfully", async () => {
       const mockFiles: MediaFile[] = [mockMediaFile]
-      mockInvoke.mockResolvedValue(mockFiles)
+      mockInvoke.mockResolvedValue({ new_files: mockFiles, known_files: [] })
 
       const { result } = renderHook(() => useMediaProcessor())
 
@@ -369,7 +369,7 @@ describe("useMediaProcessor", () => {
     })
 
     it("should use default thumbnail dimensions", async () => {
-      mockInvoke.mockResolvedValue([])
+      mockInvoke.mockResolvedValue({ new_files: [], known_files: [] })
 
       const { result } = renderHook(() => useMediaProcessor())
 
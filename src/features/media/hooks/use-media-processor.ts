@@ -44,6 +44,24 @@ interface ProcessingErrorData {
   error: string
 }
 
+// Файл, содержимое которого уже известно реестру (обработка пропущена)
+export interface KnownMediaFile {
+  path: string
+  known_path: string
+  file_id?: string
+  fingerprint: {
+    mode: "fast" | "full"
+    size: number
+    hash: string
+  }
+}
+
+// Результат сканирования папки
+export interface MediaScanResult {
+  new_files: MediaFile[]
+  known_files: KnownMediaFile[]
+}
+
 interface ScanProgressData {
   current: number
   total: number
@@ -152,10 +170,10 @@ export function useMediaProcessor(options: UseMediaProcessorOptions = {}) {
     setProgress({ current: 0, total: 0 })
 
     try {
-      const files = await invoke<MediaFile[]>("scan_media_folder", {
+      const result = await invoke<MediaScanResult>("scan_media_folder", {
         folderPath,
       })
-      return files
+      return result.new_files
     } catch (error) {
       console.error("Failed to scan folder:", error)
       throw error
@@ -171,12 +189,12 @@ export function useMediaProcessor(options: UseMediaProcessorOptions = {}) {
       setProgress({ current: 0, total: 0 })
 
       try {
-        const files = await invoke<MediaFile[]>("scan_media_folder_with_thumbnails", {
+        const result = await invoke<MediaScanResult>("scan_media_folder_with_thumbnails", {
           folderPath,
           width,
           height,
         })
-        return files
+        return result.new_files
       } catch (error) {
         console.error("Failed to scan folder with thumbnails:", error)
         throw error
@@ -57,6 +57,6 @@ export interface VideoCompilerEvents {
   render_progress: { job_id: string; progress: number }
   render_completed: { job_id: string; output_path: string }
   render_failed: { job_id: string; error: string }
-  preview_generated: { timestamp: number; image_data: Uint8Array }
+  preview_generated: { file_id: string; timestamp: number; cache_path: string }
   cache_updated: { cache_size_mb: number }
 }